@@ -59,7 +59,9 @@ const RING_SRCS: &[(&[&str], &str)] = &[
     (&[], "crypto/fipsmodule/ec/ecp_nistz.c"),
     (&[], "crypto/fipsmodule/ec/ecp_nistz256.c"),
     (&[], "crypto/fipsmodule/ec/gfp_p256.c"),
+    (&[], "crypto/fipsmodule/ec/gfp_k256.c"),
     (&[], "crypto/fipsmodule/ec/gfp_p384.c"),
+    (&[], "crypto/fipsmodule/ec/gfp_p521.c"),
     (&[], "crypto/limbs/limbs.c"),
     (&[], "crypto/mem.c"),
     (&[], "crypto/fipsmodule/modes/gcm.c"),
@@ -114,6 +116,34 @@ const RING_SRCS: &[(&[&str], &str)] = &[
     (&[AARCH64], SHA512_ARMV8),
 ];
 
+// Sources that only the algorithms of one Cargo feature use, which are left
+// out of the build when that feature is disabled. The Montgomery
+// multiplication assembly language code isn't listed under `rsa` because the
+// elliptic curve code uses it too.
+#[cfg_attr(rustfmt, rustfmt_skip)]
+const FEATURE_SRCS: &[(&str, &[&str])] = &[
+    ("aead-aes-gcm-siv", &["crypto/cipher_extra/asm/aes128gcmsiv-x86_64.pl"]),
+    ("aead-chacha", &["crypto/chacha/asm/chacha-x86.pl",
+                      "crypto/chacha/asm/chacha-x86_64.pl",
+                      "crypto/chacha/asm/chacha-armv4.pl",
+                      "crypto/chacha/asm/chacha-armv8.pl",
+                      "crypto/poly1305/asm/poly1305-x86.pl",
+                      "crypto/poly1305/asm/poly1305-x86_64.pl",
+                      "crypto/poly1305/asm/poly1305-armv4.pl",
+                      "crypto/poly1305/asm/poly1305-armv8.pl"]),
+    ("rsa", &["crypto/fipsmodule/bn/montgomery.c",
+              "crypto/fipsmodule/bn/montgomery_inv.c"]),
+];
+
+// Sources that the `intrinsics` feature replaces with Rust code, which are
+// left out of the build when it is enabled.
+const INTRINSICS_REPLACED_SRCS: &[&str] = &[
+    "crypto/cipher_extra/asm/aes128gcmsiv-x86_64.pl",
+    "crypto/fipsmodule/aes/asm/aesni-x86_64.pl",
+    "crypto/fipsmodule/modes/asm/aesni-gcm-x86_64.pl",
+    "crypto/fipsmodule/modes/asm/ghash-x86_64.pl",
+];
+
 const SHA256_X86_64: &str = "crypto/fipsmodule/sha/asm/sha256-x86_64.pl";
 const SHA512_X86_64: &str = "crypto/fipsmodule/sha/asm/sha512-x86_64.pl";
 
@@ -126,9 +156,13 @@ const RING_TEST_SRCS: &[&str] = &[("crypto/constant_time_test.c")];
 const RING_INCLUDES: &[&str] =
     &["crypto/fipsmodule/bn/internal.h",
       "crypto/fipsmodule/ec/ecp_nistz256_table.inl",
+      "crypto/fipsmodule/ec/ecp_k256.inl",
       "crypto/fipsmodule/ec/ecp_nistz384.inl",
+      "crypto/fipsmodule/ec/ecp_nistz521.inl",
       "crypto/fipsmodule/ec/ecp_nistz.h",
+      "crypto/fipsmodule/ec/ecp_k256.h",
       "crypto/fipsmodule/ec/ecp_nistz384.h",
+      "crypto/fipsmodule/ec/ecp_nistz521.h",
       "crypto/fipsmodule/ec/ecp_nistz256.h",
       "crypto/block.h",
       "crypto/internal.h",
@@ -389,7 +423,10 @@ fn build_c_code(target: &Target, pregenerated: PathBuf, out_dir: &Path) {
     };
 
     let perlasm_src_dsts =
-        perlasm_src_dsts(asm_dir, target.arch(), Some(target.os()), perlasm_format);
+        perlasm_src_dsts(asm_dir, target.arch(), Some(target.os()), perlasm_format)
+            .into_iter()
+            .filter(|(src, _)| is_enabled_by_features(src))
+            .collect::<Vec<_>>();
 
     if !use_pregenerated {
         perlasm(
@@ -416,7 +453,7 @@ fn build_c_code(target: &Target, pregenerated: PathBuf, out_dir: &Path) {
 
     let core_srcs = sources_for_arch(target.arch())
         .into_iter()
-        .filter(|p| !is_perlasm(&p))
+        .filter(|p| !is_perlasm(&p) && is_enabled_by_features(p))
         .collect::<Vec<_>>();
 
     let test_srcs = RING_TEST_SRCS.iter().map(PathBuf::from).collect::<Vec<_>>();
@@ -666,6 +703,24 @@ fn sources_for_arch(arch: &str) -> Vec<PathBuf> {
         .collect::<Vec<_>>()
 }
 
+// Whether `src` is needed by the Cargo features that are enabled. Cargo sets
+// `CARGO_FEATURE_<name>` for each enabled feature of the package.
+fn is_enabled_by_features(src: &Path) -> bool {
+    let is_feature_enabled = |feature: &str| {
+        let var = format!("CARGO_FEATURE_{}", feature.to_uppercase().replace('-', "_"));
+        std::env::var_os(var).is_some()
+    };
+    if is_feature_enabled("intrinsics")
+        && INTRINSICS_REPLACED_SRCS.iter().any(|s| Path::new(s) == src)
+    {
+        return false;
+    }
+    FEATURE_SRCS
+        .iter()
+        .filter(|&&(_, srcs)| srcs.iter().any(|s| Path::new(s) == src))
+        .all(|&(feature, _)| is_feature_enabled(feature))
+}
+
 fn perlasm_src_dsts(
     out_dir: &Path,
     arch: &str,
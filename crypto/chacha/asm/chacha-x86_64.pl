@@ -65,7 +65,9 @@ $0 =~ m/(.*[\/\\])[^\/\\]+$/; $dir=$1;
 ( $xlate="${dir}../../perlasm/x86_64-xlate.pl" and -f $xlate) or
 die "can't locate x86_64-xlate.pl";
 
-$avx = 2;
+# The AVX-512 code paths are left out of the "nasm" flavour because it is
+# assembled with yasm, which doesn't support AVX-512.
+$avx = ($flavour =~ /nasm/) ? 2 : 3;
 
 open OUT,"| \"$^X\" $xlate $flavour $output";
 *STDOUT=*OUT;
@@ -2010,7 +2012,7 @@ $code.=<<___;
 	dec		$len
 	jnz		.Loop_tail_avx512
 
-	vmovdqa32	$a_,0x00(%rsp)
+	vmovdqu32	$a_,0x00(%rsp)
 
 .Ldone_avx512:
 	vzeroall
@@ -0,0 +1,34 @@
+/* Copyright 2019 Brian Smith.
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+ * SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+ * OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+ * CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE. */
+
+#ifndef OPENSSL_HEADER_EC_ECP_K256_H
+#define OPENSSL_HEADER_EC_ECP_K256_H
+
+#include "../../limbs/limbs.h"
+
+#define K256_LIMBS (256u / LIMB_BITS)
+
+typedef struct {
+  Limb X[K256_LIMBS];
+  Limb Y[K256_LIMBS];
+  Limb Z[K256_LIMBS];
+} K256_POINT;
+
+typedef struct {
+  Limb X[K256_LIMBS];
+  Limb Y[K256_LIMBS];
+} K256_POINT_AFFINE;
+
+
+#endif // OPENSSL_HEADER_EC_ECP_K256_H
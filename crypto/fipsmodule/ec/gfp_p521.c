@@ -0,0 +1,246 @@
+/* Copyright 2019 Brian Smith.
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+ * SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+ * OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+ * CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE. */
+
+#include "../../limbs/limbs.h"
+
+#include <string.h>
+
+#include "ecp_nistz521.h"
+#include "../bn/internal.h"
+#include "../../internal.h"
+
+#include "../../limbs/limbs.inl"
+
+ /* XXX: Here we assume that the conversion from |Carry| to |Limb| is
+  * constant-time, but we haven't verified that assumption. TODO: Fix it so
+  * we don't need to make that assumption. */
+
+
+typedef Limb Elem[P521_LIMBS];
+typedef Limb ScalarMont[P521_LIMBS];
+typedef Limb Scalar[P521_LIMBS];
+
+
+/* Field elements are kept in the plain (non-Montgomery) representation, i.e.
+ * R = 1, since reduction modulo the Mersenne prime q is cheap. The top limb is
+ * a single 0x1ff on both 32-bit and 64-bit targets because
+ * 521 % LIMB_BITS == 9 in both cases. */
+
+static const BN_ULONG Q[P521_LIMBS] = {
+  TOBN(0xffffffff, 0xffffffff),
+  TOBN(0xffffffff, 0xffffffff),
+  TOBN(0xffffffff, 0xffffffff),
+  TOBN(0xffffffff, 0xffffffff),
+  TOBN(0xffffffff, 0xffffffff),
+  TOBN(0xffffffff, 0xffffffff),
+  TOBN(0xffffffff, 0xffffffff),
+  TOBN(0xffffffff, 0xffffffff),
+  0x1ff,
+};
+
+static const BN_ULONG N[P521_LIMBS] = {
+  TOBN(0xbb6fb71e, 0x91386409),
+  TOBN(0x3bb5c9b8, 0x899c47ae),
+  TOBN(0x7fcc0148, 0xf709a5d0),
+  TOBN(0x51868783, 0xbf2f966b),
+  TOBN(0xffffffff, 0xfffffffa),
+  TOBN(0xffffffff, 0xffffffff),
+  TOBN(0xffffffff, 0xffffffff),
+  TOBN(0xffffffff, 0xffffffff),
+  0x1ff,
+};
+
+
+static const BN_ULONG ONE[P521_LIMBS] = {
+  TOBN(0, 1), TOBN(0, 0), TOBN(0, 0), TOBN(0, 0), TOBN(0, 0), TOBN(0, 0),
+  TOBN(0, 0), TOBN(0, 0), 0,
+};
+
+
+/* XXX: MSVC for x86 warns when it fails to inline these functions it should
+ * probably inline. */
+#if defined(_MSC_VER)  && defined(OPENSSL_X86)
+#define INLINE_IF_POSSIBLE __forceinline
+#else
+#define INLINE_IF_POSSIBLE inline
+#endif
+
+
+static INLINE_IF_POSSIBLE Limb is_equal(const Elem a, const Elem b) {
+  return LIMBS_equal(a, b, P521_LIMBS);
+}
+
+static INLINE_IF_POSSIBLE void copy_conditional(Elem r, const Elem a,
+                                                const Limb condition) {
+  for (size_t i = 0; i < P521_LIMBS; ++i) {
+    r[i] = constant_time_select_w(condition, a[i], r[i]);
+  }
+}
+
+
+static void elem_add(Elem r, const Elem a, const Elem b) {
+  LIMBS_add_mod(r, a, b, Q, P521_LIMBS);
+}
+
+static void elem_sub(Elem r, const Elem a, const Elem b) {
+  LIMBS_sub_mod(r, a, b, Q, P521_LIMBS);
+}
+
+static void elem_div_by_2(Elem r, const Elem a) {
+  /* See the comment in |elem_div_by_2| in gfp_p384.c; the same reasoning
+   * applies to any odd |q|. */
+
+  Limb is_odd = constant_time_is_nonzero_w(a[0] & 1);
+
+  /* r = a >> 1. */
+  Limb carry = a[P521_LIMBS - 1] & 1;
+  r[P521_LIMBS - 1] = a[P521_LIMBS - 1] >> 1;
+  for (size_t i = 1; i < P521_LIMBS; ++i) {
+    Limb new_carry = a[P521_LIMBS - i - 1];
+    r[P521_LIMBS - i - 1] =
+        (a[P521_LIMBS - i - 1] >> 1) | (carry << (LIMB_BITS - 1));
+    carry = new_carry;
+  }
+
+  /* (q + 1) >> 1 == 2**520. */
+  static const Elem Q_PLUS_1_SHR_1 = {
+    TOBN(0, 0), TOBN(0, 0), TOBN(0, 0), TOBN(0, 0), TOBN(0, 0), TOBN(0, 0),
+    TOBN(0, 0), TOBN(0, 0), 0x100,
+  };
+
+  Elem adjusted;
+  BN_ULONG carry2 = limbs_add(adjusted, r, Q_PLUS_1_SHR_1, P521_LIMBS);
+#if defined(NDEBUG)
+  (void)carry2;
+#endif
+  assert(carry2 == 0);
+
+  copy_conditional(r, adjusted, is_odd);
+}
+
+/* |r = a * b (mod q)|. Since R = 1 this is a plain modular multiplication,
+ * but it keeps the |_mont| name so that the shared point arithmetic in
+ * ecp_nistz521.inl can be written the same way as for the other curves. */
+static void elem_mul_mont(Elem r, const Elem a, const Elem b) {
+  Limb t[2 * P521_LIMBS];
+  memset(t, 0, sizeof(t));
+  for (size_t i = 0; i < P521_LIMBS; ++i) {
+    t[P521_LIMBS + i] = GFp_bn_mul_add_words(&t[i], a, P521_LIMBS, b[i]);
+  }
+
+  /* Since q == 2**521 - 1, t == hi * 2**521 + lo == hi + lo (mod q). Bit 521
+   * is bit 9 of limb |P521_LIMBS - 1| on both 32-bit and 64-bit targets. */
+  Elem hi;
+  for (size_t i = 0; i < P521_LIMBS; ++i) {
+    hi[i] = (t[P521_LIMBS - 1 + i] >> 9) | (t[P521_LIMBS + i] << (LIMB_BITS - 9));
+  }
+  t[P521_LIMBS - 1] &= 0x1ff;
+
+  /* hi + lo < 2**522, so the sum doesn't overflow |P521_LIMBS| limbs. */
+  Carry carry = limbs_add(r, hi, t, P521_LIMBS);
+#if defined(NDEBUG)
+  (void)carry;
+#endif
+  assert(carry == 0);
+
+  /* Fold bit 521 back in. Afterwards r <= q. */
+  Limb top = r[P521_LIMBS - 1] >> 9;
+  r[P521_LIMBS - 1] &= 0x1ff;
+  carry = limb_add(&r[0], r[0], top);
+  for (size_t i = 1; i < P521_LIMBS; ++i) {
+    carry = limb_adc(&r[i], r[i], 0, carry);
+  }
+
+  LIMBS_reduce_once(r, Q, P521_LIMBS);
+}
+
+static inline void elem_mul_by_2(Elem r, const Elem a) {
+  LIMBS_shl_mod(r, a, Q, P521_LIMBS);
+}
+
+static INLINE_IF_POSSIBLE void elem_mul_by_3(Elem r, const Elem a) {
+  /* XXX: inefficient. TODO: Replace with an integrated shift + add. */
+  Elem doubled;
+  elem_add(doubled, a, a);
+  elem_add(r, doubled, a);
+}
+
+static inline void elem_sqr_mont(Elem r, const Elem a) {
+  /* XXX: Inefficient. TODO: Add a dedicated squaring routine. */
+  elem_mul_mont(r, a, a);
+}
+
+void GFp_p521_elem_add(Elem r, const Elem a, const Elem b) {
+  elem_add(r, a, b);
+}
+
+void GFp_p521_elem_sub(Elem r, const Elem a, const Elem b) {
+  elem_sub(r, a, b);
+}
+
+void GFp_p521_elem_div_by_2(Elem r, const Elem a) {
+  elem_div_by_2(r, a);
+}
+
+void GFp_p521_elem_mul_mont(Elem r, const Elem a, const Elem b) {
+  elem_mul_mont(r, a, b);
+}
+
+void GFp_p521_elem_neg(Elem r, const Elem a) {
+  Limb is_zero = LIMBS_are_zero(a, P521_LIMBS);
+  Carry borrow = limbs_sub(r, Q, a, P521_LIMBS);
+#if defined(NDEBUG)
+  (void)borrow;
+#endif
+  assert(borrow == 0);
+  for (size_t i = 0; i < P521_LIMBS; ++i) {
+    r[i] = constant_time_select_w(is_zero, 0, r[i]);
+  }
+}
+
+
+void GFp_p521_scalar_mul_mont(ScalarMont r, const ScalarMont a,
+                              const ScalarMont b) {
+  static const BN_ULONG N_N0[] = {
+    BN_MONT_CTX_N0(0x1d2f5ccd, 0x79a995c7)
+  };
+  /* XXX: Inefficient. TODO: Add dedicated multiplication routine. */
+  GFp_bn_mul_mont(r, a, b, N, N_N0, P521_LIMBS);
+}
+
+
+/* TODO(perf): Optimize this. */
+
+static void gfp_p521_point_select_w5(P521_POINT *out,
+                                     const P521_POINT table[16], size_t index) {
+  Elem x; memset(x, 0, sizeof(x));
+  Elem y; memset(y, 0, sizeof(y));
+  Elem z; memset(z, 0, sizeof(z));
+
+  for (size_t i = 0; i < 16; ++i) {
+    Limb mask = constant_time_eq_w(index, i + 1);
+    for (size_t j = 0; j < P521_LIMBS; ++j) {
+      x[j] |= table[i].X[j] & mask;
+      y[j] |= table[i].Y[j] & mask;
+      z[j] |= table[i].Z[j] & mask;
+    }
+  }
+
+  limbs_copy(out->X, x, P521_LIMBS);
+  limbs_copy(out->Y, y, P521_LIMBS);
+  limbs_copy(out->Z, z, P521_LIMBS);
+}
+
+
+#include "ecp_nistz521.inl"
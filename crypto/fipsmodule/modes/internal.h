@@ -66,9 +66,10 @@ typedef struct {
     // Relative position of Xi, H and pre-computed Htable is used in some
     // assembler modules, i.e. don't change the order!
     alignas(16) uint8_t Xi[16];
+    // Not used by the assembly language code.
     struct {
         uint64_t u[2];
-    } H_unused;
+    } H;
     GCM128_KEY key;
 } GCM128_CONTEXT;
 
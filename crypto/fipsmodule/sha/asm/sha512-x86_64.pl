@@ -127,8 +127,11 @@ die "can't locate x86_64-xlate.pl";
 # output, so this isn't useful anyway.
 $avx = 2;
 
-$shaext=0;
-$avx=1		if (!$shaext && $avx);
+# The SHA extensions implementation is exported separately as
+# GFp_sha256_block_data_order_shaext and selected by the caller, so enabling
+# it must not also enable the AVX2 code path.
+$shaext=1;
+$avx=1		if ($avx);
 
 open OUT,"| \"$^X\" \"$xlate\" $flavour \"$output\"";
 *STDOUT=*OUT;
@@ -266,10 +269,6 @@ $code.=<<___ if ($SZ==4 || $avx);
 	mov	0(%r11),%r9d
 	mov	4(%r11),%r10d
 	mov	8(%r11),%r11d
-___
-$code.=<<___ if ($SZ==4 && $shaext);
-	test	\$`1<<29`,%r11d		# check for SHA
-	jnz	_shaext_shortcut
 ___
     # XOP codepath removed.
 ___
@@ -551,10 +550,10 @@ my ($Wi,$ABEF,$CDGH,$TMP,$BSWAP,$ABEF_SAVE,$CDGH_SAVE)=map("%xmm$_",(0..2,7..10)
 my @MSG=map("%xmm$_",(3..6));
 
 $code.=<<___;
+.globl	GFp_sha256_block_data_order_shaext
 .type	GFp_sha256_block_data_order_shaext,\@function,3
 .align	64
 GFp_sha256_block_data_order_shaext:
-_shaext_shortcut:
 ___
 $code.=<<___ if ($win64);
 	lea	`-8-5*16`(%rsp),%rsp
@@ -56,9 +56,40 @@
 
 #include <GFp/mem.h>
 
+#if defined(OPENSSL_X86_64)
+#include <emmintrin.h>
+#elif defined(OPENSSL_AARCH64)
+#include <arm_neon.h>
+#endif
+
 int GFp_memcmp(const uint8_t *a, const uint8_t *b, size_t len) {
   uint8_t x = 0;
-  for (size_t i = 0; i < len; i++) {
+  size_t i = 0;
+
+  /* Accumulate the differences 16 bytes at a time using the vector unit,
+   * which is always available on these targets. Like the byte-wise loop
+   * below, this touches every byte and never branches on the data. */
+#if defined(OPENSSL_X86_64)
+  __m128i acc = _mm_setzero_si128();
+  for (; len - i >= 16; i += 16) {
+    __m128i va = _mm_loadu_si128((const __m128i *)(a + i));
+    __m128i vb = _mm_loadu_si128((const __m128i *)(b + i));
+    acc = _mm_or_si128(acc, _mm_xor_si128(va, vb));
+  }
+  uint8_t acc_bytes[16];
+  _mm_storeu_si128((__m128i *)acc_bytes, acc);
+  for (size_t j = 0; j < sizeof(acc_bytes); j++) {
+    x |= acc_bytes[j];
+  }
+#elif defined(OPENSSL_AARCH64)
+  uint8x16_t acc = vdupq_n_u8(0);
+  for (; len - i >= 16; i += 16) {
+    acc = vorrq_u8(acc, veorq_u8(vld1q_u8(a + i), vld1q_u8(b + i)));
+  }
+  x |= vmaxvq_u8(acc);
+#endif
+
+  for (; i < len; i++) {
     x |= a[i] ^ b[i];
   }
 
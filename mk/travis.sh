@@ -138,4 +138,7 @@ fi
 # code builds.
 cargo build -vv -j2 ${mode-} ${FEATURES_X-} --target=$TARGET_X
 
+# Verify that the `#![no_std]` build, without `std` or the heap, works.
+cargo build -vv -j2 ${mode-} --no-default-features --target=$TARGET_X
+
 echo end of mk/travis.sh
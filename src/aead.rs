@@ -23,19 +23,52 @@
 
 use self::block::{Block, BLOCK_LEN};
 use crate::{
-    constant_time, cpu, error, hkdf,
+    constant_time, cpu, error, hkdf, policy,
     polyfill::{self, convert::*},
 };
-use std::fmt;
+use core::{convert::TryFrom, mem::MaybeUninit, ops::RangeFrom};
+use core::fmt;
+
+#[cfg(feature = "audit")]
+use crate::audit;
+#[cfg(feature = "aead-aes-gcm-siv")]
+use crate::zeroize::Zeroize;
+#[cfg(feature = "aead-aes-gcm-siv")]
+use core::convert::TryInto;
 
 pub use self::{
+    aegis::{AEGIS_128L, AEGIS_256},
     aes_gcm::{AES_128_GCM, AES_256_GCM},
-    aes_gcm_siv::{AES_128_GCM_SIV, AES_256_GCM_SIV},
-    chacha20_poly1305::CHACHA20_POLY1305,
+    aes_siv::{AES_128_SIV_CMAC, AES_256_SIV_CMAC},
+    ascon::ASCON_128,
+    batch::open_batch,
+    bounded_use::BoundedUseKey,
     nonce::{Nonce, NONCE_LEN},
+    nonce_sequence::{
+        CounterNonceSequence, Endianness, LimitedNonceSequence, NonceSequence,
+        RandomNonceSequence, COUNTER_NONCE_PREFIX_LEN,
+    },
+};
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+pub use self::{
+    aes_gcm_siv::{AES_128_GCM_SIV, AES_256_GCM_SIV},
+    multipart::{OpeningContext, SealingContext, SealingEncryptor},
+};
+
+#[cfg(feature = "aead-chacha")]
+pub use self::{
+    chacha20_poly1305::CHACHA20_POLY1305, xchacha20_poly1305::XCHACHA20_POLY1305,
 };
 
+#[cfg(feature = "use_heap")]
+pub use self::key_ring::{KeyRing, KEY_ID_LEN};
+
 /// A key for authenticating and decrypting (“opening”) AEAD-protected data.
+///
+/// With the `use_heap` feature, cloning a key is cheap because the clones
+/// share the expanded key.
+#[cfg_attr(feature = "use_heap", derive(Clone))]
 pub struct OpeningKey {
     key: Key,
 }
@@ -44,11 +77,16 @@ derive_debug_via_field!(OpeningKey, key);
 
 impl OpeningKey {
     /// Create a new `OpeningKey` by extracting the key's value from `okm`.
+    ///
+    /// Fails if `algorithm` isn't allowed by the current `policy`.
     #[inline]
-    pub fn derive(algorithm: &'static Algorithm, okm: hkdf::Okm) -> Self {
-        Self {
-            key: Key::derive(algorithm, okm),
-        }
+    pub fn derive(
+        algorithm: &'static Algorithm,
+        okm: hkdf::Okm,
+    ) -> Result<Self, error::Unspecified> {
+        Ok(Self {
+            key: Key::derive(algorithm, okm)?,
+        })
     }
 
     /// Create a new opening key.
@@ -69,6 +107,34 @@ impl OpeningKey {
     pub fn algorithm(&self) -> &'static Algorithm {
         self.key.algorithm()
     }
+
+    /// Serializes the key, including its expanded form, so that it can be
+    /// restored with `deserialize()` without expanding it again.
+    ///
+    /// Only `AES_128_GCM_SIV` and `AES_256_GCM_SIV` keys can be serialized.
+    /// The serialized key is as secret as the key itself.
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    #[inline]
+    pub fn serialize(&self) -> Result<SerializedKey, error::Unspecified> {
+        self.key.serialize()
+    }
+
+    /// Restores an opening key serialized by `OpeningKey::serialize()` or
+    /// `SealingKey::serialize()`.
+    ///
+    /// Fails if `serialized` isn't a serialized `algorithm` key, or if it was
+    /// serialized on a CPU with different features or by a different build of
+    /// *ring*; the key must then be constructed from its bytes again.
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    #[inline]
+    pub fn deserialize(
+        algorithm: &'static Algorithm,
+        serialized: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        Ok(Self {
+            key: Key::deserialize(algorithm, serialized)?,
+        })
+    }
 }
 
 /// Authenticates and decrypts (“opens”) data in place.
@@ -123,38 +189,154 @@ pub fn open_in_place<'a, A: AsRef<[u8]>>(
     ciphertext_and_tag_modified_in_place: &'a mut [u8],
 ) -> Result<&'a mut [u8], error::Unspecified> {
     open_in_place_(
-        key,
+        &key.key,
+        &key.key.inner,
         nonce,
         Aad::from(aad.as_ref()),
         in_prefix_len,
         ciphertext_and_tag_modified_in_place,
     )
+    .map_err(error::Unspecified::from)
 }
 
-fn open_in_place_<'a>(
+/// Authenticates and decrypts (“opens”) data in place, moving the plaintext
+/// to the start of `in_out`.
+///
+/// The ciphertext and tag are `in_out[ciphertext_and_tag]`; anything before
+/// them, such as a record header, is overwritten. When `open_within()`
+/// returns `Ok(plaintext)`, the decrypted output is `plaintext`, which is
+/// `&mut in_out[..plaintext.len()]`. When it returns `Err(..)`, `in_out` may
+/// have been overwritten in an unspecified way.
+///
+/// This is equivalent to `open_in_place()` with an `in_prefix_len` of
+/// `ciphertext_and_tag.start`; see `open_in_place()` for how it is used to
+/// reassemble messages from packets in place.
+#[inline]
+pub fn open_within<'a, A: AsRef<[u8]>>(
     key: &OpeningKey,
     nonce: Nonce,
-    aad: Aad<&[u8]>,
+    aad: Aad<A>,
+    in_out: &'a mut [u8],
+    ciphertext_and_tag: RangeFrom<usize>,
+) -> Result<&'a mut [u8], error::Unspecified> {
+    open_in_place(key, nonce, aad, ciphertext_and_tag.start, in_out)
+}
+
+/// Like `open_in_place()`, but reports why the input was rejected.
+///
+/// When the tag doesn't match, the error is always the same; it only
+/// distinguishes inputs that are malformed, e.g. too short to contain a tag
+/// or too long for the algorithm, or used with the wrong type of nonce for
+/// the key. See `error::InputRejected`.
+pub fn open_in_place_detailed<'a, A: AsRef<[u8]>>(
+    key: &OpeningKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
+    in_prefix_len: usize,
+    ciphertext_and_tag_modified_in_place: &'a mut [u8],
+) -> Result<&'a mut [u8], error::InputRejected> {
+    open_in_place_(
+        &key.key,
+        &key.key.inner,
+        nonce,
+        Aad::from(aad.as_ref()),
+        in_prefix_len,
+        ciphertext_and_tag_modified_in_place,
+    )
+}
+
+/// Like `open_in_place()`, except that long AES-GCM-SIV messages are
+/// decrypted and authenticated on several threads, one per CPU core.
+///
+/// The result is the same as the one `open_in_place()` would return.
+/// Messages that are too short to benefit, and messages for other
+/// algorithms, are opened on the calling thread. If a thread can't be
+/// spawned then its part of the message is processed on the calling thread.
+///
+/// Only available with the `aead_parallel` feature.
+#[cfg(feature = "aead_parallel")]
+pub fn open_in_place_parallel<'a, A: AsRef<[u8]>>(
+    key: &OpeningKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
     in_prefix_len: usize,
     ciphertext_and_tag_modified_in_place: &'a mut [u8],
 ) -> Result<&'a mut [u8], error::Unspecified> {
+    let open: OpenFn = match key.key.algorithm.id {
+        AlgorithmID::AES_128_GCM_SIV | AlgorithmID::AES_256_GCM_SIV => aes_gcm_siv::open_parallel,
+        _ => key.key.algorithm.open,
+    };
+    open_in_place_with_(
+        &key.key,
+        &key.key.inner,
+        nonce,
+        Aad::from(aad.as_ref()),
+        in_prefix_len,
+        ciphertext_and_tag_modified_in_place,
+        open,
+    )
+    .map_err(error::Unspecified::from)
+}
+
+// `inner` is `key`'s own `KeyInner`, except for XChaCha20-Poly1305, where it
+// is the ChaCha20-Poly1305 subkey derived from the extended nonce, and for
+// AEGIS and Ascon, where it also holds the nonce.
+fn open_in_place_<'a>(
+    key: &Key,
+    inner: &KeyInner,
+    nonce: Nonce,
+    aad: Aad<&[u8]>,
+    in_prefix_len: usize,
+    ciphertext_and_tag_modified_in_place: &'a mut [u8],
+) -> Result<&'a mut [u8], error::InputRejected> {
+    open_in_place_with_(
+        key,
+        inner,
+        nonce,
+        aad,
+        in_prefix_len,
+        ciphertext_and_tag_modified_in_place,
+        key.algorithm.open,
+    )
+}
+
+// Like `open_in_place_`, except the message is decrypted and authenticated
+// with `open` instead of `key.algorithm.open`.
+fn open_in_place_with_<'a>(
+    key: &Key,
+    inner: &KeyInner,
+    nonce: Nonce,
+    aad: Aad<&[u8]>,
+    in_prefix_len: usize,
+    ciphertext_and_tag_modified_in_place: &'a mut [u8],
+    open: OpenFn,
+) -> Result<&'a mut [u8], error::InputRejected> {
     let ciphertext_and_tag_len = ciphertext_and_tag_modified_in_place
         .len()
         .checked_sub(in_prefix_len)
-        .ok_or(error::Unspecified)?;
+        .ok_or_else(error::InputRejected::too_short)?;
     let ciphertext_len = ciphertext_and_tag_len
         .checked_sub(TAG_LEN)
-        .ok_or(error::Unspecified)?;
-    check_per_nonce_max_bytes(key.key.algorithm, ciphertext_len)?;
-    match key.key.algorithm.id {
-        AlgorithmID::AES_128_GCM_SIV | AlgorithmID::AES_256_GCM_SIV => {
-            let Tag(calculated_tag) = (key.key.algorithm.open)(
-                &key.key.inner,
+        .ok_or_else(error::InputRejected::too_short)?;
+    check_nonce_len(inner)?;
+    key.algorithm
+        .check_input_lens(aad.0.len(), ciphertext_len)?;
+    #[cfg(feature = "audit")]
+    audit::record(
+        key.algorithm,
+        audit::Operation::Open,
+        &key.fingerprint,
+        ciphertext_len,
+    );
+    match key.algorithm.id {
+        ref id if id.is_siv() => {
+            let Tag(calculated_tag) = open(
+                inner,
                 nonce,
                 aad,
                 in_prefix_len,
                 ciphertext_and_tag_modified_in_place,
-                key.key.cpu_features,
+                key.cpu_features,
             );
             let received_tag = &ciphertext_and_tag_modified_in_place
                 [in_prefix_len + ciphertext_len..ciphertext_and_tag_modified_in_place.len()];
@@ -163,7 +345,7 @@ fn open_in_place_<'a>(
                 .is_err()
             {
                 zero_out_plain_text(&mut ciphertext_and_tag_modified_in_place[..ciphertext_len]);
-                return Err(error::Unspecified);
+                return Err(error::InputRejected::authentication_failed());
             }
             // `ciphertext_len` is also the plaintext length.
             return Ok(&mut ciphertext_and_tag_modified_in_place[..(ciphertext_len)]);
@@ -171,20 +353,20 @@ fn open_in_place_<'a>(
         _ => {
             let (in_out, received_tag) =
                 ciphertext_and_tag_modified_in_place.split_at_mut(in_prefix_len + ciphertext_len);
-            let Tag(calculated_tag) = (key.key.algorithm.open)(
-                &key.key.inner,
+            let Tag(calculated_tag) = open(
+                inner,
                 nonce,
                 aad,
                 in_prefix_len,
                 in_out,
-                key.key.cpu_features,
+                key.cpu_features,
             );
 
             if constant_time::verify_slices_are_equal(calculated_tag.as_ref(), received_tag)
                 .is_err()
             {
                 zero_out_plain_text(&mut in_out[..ciphertext_len]);
-                return Err(error::Unspecified);
+                return Err(error::InputRejected::authentication_failed());
             }
             // `ciphertext_len` is also the plaintext length.
             return Ok(&mut in_out[..ciphertext_len]);
@@ -192,6 +374,157 @@ fn open_in_place_<'a>(
     }
 }
 
+/// Authenticates and decrypts (“opens”) `ciphertext_and_tag` into `out`.
+///
+/// `out` may be uninitialized, e.g. a freshly allocated or pooled buffer. It
+/// must be at least `ciphertext_and_tag.len()` bytes long because the tag is
+/// copied along with the ciphertext. When `open_into()` returns
+/// `Ok(plaintext)`, `plaintext` is the initialized prefix of `out`. When it
+/// returns `Err(..)`, the first `ciphertext_and_tag.len()` bytes of `out` may
+/// have been overwritten in an unspecified way.
+pub fn open_into<'o, A: AsRef<[u8]>>(
+    key: &OpeningKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
+    ciphertext_and_tag: &[u8],
+    out: &'o mut [MaybeUninit<u8>],
+) -> Result<&'o mut [u8], error::Unspecified> {
+    let in_out = copy_into_uninit(ciphertext_and_tag, out, 0)?;
+    open_in_place_(
+        &key.key,
+        &key.key.inner,
+        nonce,
+        Aad::from(aad.as_ref()),
+        0,
+        in_out,
+    )
+    .map_err(error::Unspecified::from)
+}
+
+/// Authenticates and decrypts (“opens”) `ciphertext_and_tag` into
+/// `plaintext_out`, leaving `ciphertext_and_tag` unmodified.
+///
+/// Unlike `open_into()`, the ciphertext isn't copied into the output first
+/// when the implementation can decrypt from one buffer into another, and
+/// `plaintext_out` only needs to be as long as the plaintext, i.e.
+/// `ciphertext_and_tag.len() - key.algorithm().tag_len()` bytes. When
+/// `open_to()` returns `Ok(plaintext)`, `plaintext` is that prefix of
+/// `plaintext_out`. When it returns `Err(..)`, that prefix has been zeroed.
+pub fn open_to<'o, A: AsRef<[u8]>>(
+    key: &OpeningKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
+    ciphertext_and_tag: &[u8],
+    plaintext_out: &'o mut [u8],
+) -> Result<&'o mut [u8], error::Unspecified> {
+    let ciphertext_len = ciphertext_and_tag
+        .len()
+        .checked_sub(TAG_LEN)
+        .ok_or(error::Unspecified)?;
+    let (ciphertext, received_tag) = ciphertext_and_tag.split_at(ciphertext_len);
+    let received_tag: &[u8; TAG_LEN] = received_tag.try_into_()?;
+    let plaintext_out = plaintext_out
+        .get_mut(..ciphertext_len)
+        .ok_or(error::Unspecified)?;
+    check_nonce_len(&key.key.inner)?;
+    key.key
+        .algorithm
+        .check_input_lens(aad.as_ref().len(), ciphertext_len)?;
+    #[cfg(feature = "audit")]
+    audit::record(
+        key.key.algorithm,
+        audit::Operation::Open,
+        &key.key.fingerprint,
+        ciphertext_len,
+    );
+    let aad = Aad::from(aad.as_ref());
+    let Tag(calculated_tag) = match key.key.algorithm.id {
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        AlgorithmID::AES_128_GCM_SIV | AlgorithmID::AES_256_GCM_SIV => aes_gcm_siv::open_to(
+            &key.key,
+            nonce,
+            aad,
+            ciphertext,
+            received_tag,
+            plaintext_out,
+        ),
+        AlgorithmID::AES_128_SIV_CMAC | AlgorithmID::AES_256_SIV_CMAC => {
+            plaintext_out.copy_from_slice(ciphertext);
+            aes_siv::open_detached(&key.key.inner, nonce, aad, received_tag, plaintext_out)
+        }
+        _ => {
+            plaintext_out.copy_from_slice(ciphertext);
+            (key.key.algorithm.open)(
+                &key.key.inner,
+                nonce,
+                aad,
+                0,
+                plaintext_out,
+                key.key.cpu_features,
+            )
+        }
+    };
+    if constant_time::verify_slices_are_equal(calculated_tag.as_ref(), received_tag).is_err() {
+        zero_out_plain_text(plaintext_out);
+        return Err(error::Unspecified);
+    }
+    Ok(plaintext_out)
+}
+
+/// Authenticates and decrypts (“opens”) data in place, with the tag stored
+/// separately from the ciphertext.
+///
+/// The input ciphertext is `in_out` and `received_tag` is its tag, which
+/// must be `key.algorithm().tag_len()` bytes long. When
+/// `open_in_place_detached()` returns `Ok(plaintext)`, the decrypted output
+/// is `plaintext`, which is `in_out`. When it returns `Err(..)`, `in_out` may
+/// have been overwritten in an unspecified way.
+pub fn open_in_place_detached<'a, A: AsRef<[u8]>>(
+    key: &OpeningKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
+    received_tag: &[u8],
+    in_out: &'a mut [u8],
+) -> Result<&'a mut [u8], error::Unspecified> {
+    let received_tag: &[u8; TAG_LEN] = received_tag.try_into_()?;
+    check_nonce_len(&key.key.inner)?;
+    key.key
+        .algorithm
+        .check_input_lens(aad.as_ref().len(), in_out.len())?;
+    #[cfg(feature = "audit")]
+    audit::record(
+        key.key.algorithm,
+        audit::Operation::Open,
+        &key.key.fingerprint,
+        in_out.len(),
+    );
+    let aad = Aad::from(aad.as_ref());
+    let Tag(calculated_tag) = match key.key.algorithm.id {
+        // The AES-GCM-SIV and AES-SIV `open` functions read the tag from
+        // the end of their input.
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        AlgorithmID::AES_128_GCM_SIV | AlgorithmID::AES_256_GCM_SIV => {
+            multipart::open_detached(&key.key, nonce, aad, received_tag, in_out)
+        }
+        AlgorithmID::AES_128_SIV_CMAC | AlgorithmID::AES_256_SIV_CMAC => {
+            aes_siv::open_detached(&key.key.inner, nonce, aad, received_tag, in_out)
+        }
+        _ => (key.key.algorithm.open)(
+            &key.key.inner,
+            nonce,
+            aad,
+            0,
+            in_out,
+            key.key.cpu_features,
+        ),
+    };
+    if constant_time::verify_slices_are_equal(calculated_tag.as_ref(), received_tag).is_err() {
+        zero_out_plain_text(in_out);
+        return Err(error::Unspecified);
+    }
+    Ok(in_out)
+}
+
 fn zero_out_plain_text(cipher_text: &mut [u8]) {
     // Zero out the plaintext so that it isn't accidentally leaked or used
     // after verification fails. It would be safest if we could check the
@@ -203,6 +536,10 @@ fn zero_out_plain_text(cipher_text: &mut [u8]) {
 }
 
 /// A key for encrypting and signing (“sealing”) data.
+///
+/// With the `use_heap` feature, cloning a key is cheap because the clones
+/// share the expanded key.
+#[cfg_attr(feature = "use_heap", derive(Clone))]
 pub struct SealingKey {
     key: Key,
 }
@@ -211,11 +548,16 @@ derive_debug_via_field!(SealingKey, key);
 
 impl SealingKey {
     /// Create a new `OpeningKey` by extracting the key's value from `okm`.
+    ///
+    /// Fails if `algorithm` isn't allowed by the current `policy`.
     #[inline]
-    pub fn derive(algorithm: &'static Algorithm, okm: hkdf::Okm) -> Self {
-        Self {
-            key: Key::derive(algorithm, okm),
-        }
+    pub fn derive(
+        algorithm: &'static Algorithm,
+        okm: hkdf::Okm,
+    ) -> Result<Self, error::Unspecified> {
+        Ok(Self {
+            key: Key::derive(algorithm, okm)?,
+        })
     }
 
     /// Constructs a new sealing key from `key_bytes`.
@@ -234,6 +576,34 @@ impl SealingKey {
     pub fn algorithm(&self) -> &'static Algorithm {
         self.key.algorithm()
     }
+
+    /// Serializes the key, including its expanded form, so that it can be
+    /// restored with `deserialize()` without expanding it again.
+    ///
+    /// Only `AES_128_GCM_SIV` and `AES_256_GCM_SIV` keys can be serialized.
+    /// The serialized key is as secret as the key itself.
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    #[inline]
+    pub fn serialize(&self) -> Result<SerializedKey, error::Unspecified> {
+        self.key.serialize()
+    }
+
+    /// Restores a sealing key serialized by `SealingKey::serialize()` or
+    /// `OpeningKey::serialize()`.
+    ///
+    /// Fails if `serialized` isn't a serialized `algorithm` key, or if it was
+    /// serialized on a CPU with different features or by a different build of
+    /// *ring*; the key must then be constructed from its bytes again.
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    #[inline]
+    pub fn deserialize(
+        algorithm: &'static Algorithm,
+        serialized: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        Ok(Self {
+            key: Key::deserialize(algorithm, serialized)?,
+        })
+    }
 }
 
 /// Encrypts and signs (“seals”) data in place.
@@ -261,39 +631,328 @@ pub fn seal_in_place<A: AsRef<[u8]>>(
     out_suffix_capacity: usize,
 ) -> Result<usize, error::Unspecified> {
     seal_in_place_(
-        key,
+        &key.key,
+        &key.key.inner,
         nonce,
         Aad::from(aad.as_ref()),
         in_out,
         out_suffix_capacity,
     )
+    .map_err(error::Unspecified::from)
 }
 
-fn seal_in_place_(
+/// Like `seal_in_place()`, but reports why the input was rejected. See
+/// `error::InputRejected`.
+pub fn seal_in_place_detailed<A: AsRef<[u8]>>(
     key: &SealingKey,
     nonce: Nonce,
-    aad: Aad<&[u8]>,
+    Aad(aad): Aad<A>,
+    in_out: &mut [u8],
+    out_suffix_capacity: usize,
+) -> Result<usize, error::InputRejected> {
+    seal_in_place_(
+        &key.key,
+        &key.key.inner,
+        nonce,
+        Aad::from(aad.as_ref()),
+        in_out,
+        out_suffix_capacity,
+    )
+}
+
+/// Like `seal_in_place()`, except that long AES-GCM-SIV messages are
+/// encrypted and authenticated on several threads, one per CPU core.
+///
+/// The output is the same as the one `seal_in_place()` would produce.
+/// Messages that are too short to benefit, and messages for other
+/// algorithms, are sealed on the calling thread. If a thread can't be
+/// spawned then its part of the message is processed on the calling thread.
+///
+/// Only available with the `aead_parallel` feature.
+#[cfg(feature = "aead_parallel")]
+pub fn seal_in_place_parallel<A: AsRef<[u8]>>(
+    key: &SealingKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
     in_out: &mut [u8],
     out_suffix_capacity: usize,
 ) -> Result<usize, error::Unspecified> {
-    if out_suffix_capacity < key.key.algorithm.tag_len() {
-        return Err(error::Unspecified);
+    let seal: SealFn = match key.key.algorithm.id {
+        AlgorithmID::AES_128_GCM_SIV | AlgorithmID::AES_256_GCM_SIV => aes_gcm_siv::seal_parallel,
+        _ => key.key.algorithm.seal,
+    };
+    seal_in_place_with_(
+        &key.key,
+        &key.key.inner,
+        nonce,
+        Aad::from(aad.as_ref()),
+        in_out,
+        out_suffix_capacity,
+        seal,
+    )
+    .map_err(error::Unspecified::from)
+}
+
+// `inner` is as for `open_in_place_`.
+fn seal_in_place_(
+    key: &Key,
+    inner: &KeyInner,
+    nonce: Nonce,
+    aad: Aad<&[u8]>,
+    in_out: &mut [u8],
+    out_suffix_capacity: usize,
+) -> Result<usize, error::InputRejected> {
+    seal_in_place_with_(
+        key,
+        inner,
+        nonce,
+        aad,
+        in_out,
+        out_suffix_capacity,
+        key.algorithm.seal,
+    )
+}
+
+// Like `seal_in_place_`, except the message is encrypted and authenticated
+// with `seal` instead of `key.algorithm.seal`.
+fn seal_in_place_with_(
+    key: &Key,
+    inner: &KeyInner,
+    nonce: Nonce,
+    aad: Aad<&[u8]>,
+    in_out: &mut [u8],
+    out_suffix_capacity: usize,
+    seal: SealFn,
+) -> Result<usize, error::InputRejected> {
+    if out_suffix_capacity < key.algorithm.tag_len() {
+        return Err(error::InputRejected::invalid_suffix_capacity());
     }
     let in_out_len = in_out
         .len()
         .checked_sub(out_suffix_capacity)
-        .ok_or(error::Unspecified)?;
-    check_per_nonce_max_bytes(key.key.algorithm, in_out_len)?;
+        .ok_or_else(error::InputRejected::too_short)?;
     let (in_out, tag_out) = in_out.split_at_mut(in_out_len);
 
-    let tag_out: &mut [u8; TAG_LEN] = tag_out.try_into_()?;
-    let Tag(tag) =
-        (key.key.algorithm.seal)(&key.key.inner, nonce, aad, in_out, key.key.cpu_features);
+    let tag_out: &mut [u8; TAG_LEN] = tag_out
+        .try_into_()
+        .map_err(|_| error::InputRejected::invalid_suffix_capacity())?;
+    let Tag(tag) = seal_in_place_detached_with_(key, inner, nonce, aad, in_out, seal)?;
     tag_out.copy_from_slice(tag.as_ref());
 
     Ok(in_out_len + TAG_LEN)
 }
 
+/// Encrypts and signs (“seals”) data in place, returning the tag separately.
+///
+/// `nonce` must be unique for every use of the key to seal data.
+///
+/// The input is `in_out`. When `seal_in_place_detached()` returns `Ok(tag)`,
+/// the encrypted output is `in_out`, and `tag` is its tag.
+///
+/// `aad` is the additional authenticated data, if any.
+pub fn seal_in_place_detached<A: AsRef<[u8]>>(
+    key: &SealingKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
+    in_out: &mut [u8],
+) -> Result<Tag, error::Unspecified> {
+    seal_in_place_detached_(
+        &key.key,
+        &key.key.inner,
+        nonce,
+        Aad::from(aad.as_ref()),
+        in_out,
+    )
+    .map_err(error::Unspecified::from)
+}
+
+// `inner` is as for `open_in_place_`.
+fn seal_in_place_detached_(
+    key: &Key,
+    inner: &KeyInner,
+    nonce: Nonce,
+    aad: Aad<&[u8]>,
+    in_out: &mut [u8],
+) -> Result<Tag, error::InputRejected> {
+    seal_in_place_detached_with_(key, inner, nonce, aad, in_out, key.algorithm.seal)
+}
+
+fn seal_in_place_detached_with_(
+    key: &Key,
+    inner: &KeyInner,
+    nonce: Nonce,
+    aad: Aad<&[u8]>,
+    in_out: &mut [u8],
+    seal: SealFn,
+) -> Result<Tag, error::InputRejected> {
+    check_nonce_len(inner)?;
+    key.algorithm.check_input_lens(aad.0.len(), in_out.len())?;
+    #[cfg(feature = "audit")]
+    audit::record(
+        key.algorithm,
+        audit::Operation::Seal,
+        &key.fingerprint,
+        in_out.len(),
+    );
+    Ok(seal(inner, nonce, aad, in_out, key.cpu_features))
+}
+
+/// Encrypts and signs (“seals”) `plaintext` into `out`.
+///
+/// `nonce` must be unique for every use of the key to seal data.
+///
+/// `out` may be uninitialized, e.g. a freshly allocated or pooled buffer. It
+/// must be at least `plaintext.len() + key.algorithm().tag_len()` bytes long.
+/// When `seal_into()` returns `Ok(ciphertext_and_tag)`,
+/// `ciphertext_and_tag` is the initialized prefix of `out`.
+///
+/// `aad` is the additional authenticated data, if any.
+pub fn seal_into<'o, A: AsRef<[u8]>>(
+    key: &SealingKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
+    plaintext: &[u8],
+    out: &'o mut [MaybeUninit<u8>],
+) -> Result<&'o mut [u8], error::Unspecified> {
+    let tag_len = key.key.algorithm.tag_len();
+    let in_out = copy_into_uninit(plaintext, out, tag_len)?;
+    let out_len = seal_in_place_(
+        &key.key,
+        &key.key.inner,
+        nonce,
+        Aad::from(aad.as_ref()),
+        in_out,
+        tag_len,
+    )?;
+    Ok(&mut in_out[..out_len])
+}
+
+/// Encrypts and signs (“seals”) `plaintext` into `out`, leaving `plaintext`
+/// unmodified.
+///
+/// `nonce` must be unique for every use of the key to seal data.
+///
+/// Unlike `seal_into()`, the plaintext isn't copied into the output first
+/// when the implementation can encrypt from one buffer into another. `out`
+/// must be at least `plaintext.len() + key.algorithm().tag_len()` bytes
+/// long. When `seal_to()` returns `Ok(ciphertext_and_tag)`,
+/// `ciphertext_and_tag` is that prefix of `out`.
+///
+/// `aad` is the additional authenticated data, if any.
+pub fn seal_to<'o, A: AsRef<[u8]>>(
+    key: &SealingKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
+    plaintext: &[u8],
+    out: &'o mut [u8],
+) -> Result<&'o mut [u8], error::Unspecified> {
+    let out_len = plaintext
+        .len()
+        .checked_add(TAG_LEN)
+        .ok_or(error::Unspecified)?;
+    let out = out.get_mut(..out_len).ok_or(error::Unspecified)?;
+    let (ciphertext, tag_out) = out.split_at_mut(plaintext.len());
+    check_nonce_len(&key.key.inner)?;
+    key.key
+        .algorithm
+        .check_input_lens(aad.as_ref().len(), plaintext.len())?;
+    #[cfg(feature = "audit")]
+    audit::record(
+        key.key.algorithm,
+        audit::Operation::Seal,
+        &key.key.fingerprint,
+        plaintext.len(),
+    );
+    let aad = Aad::from(aad.as_ref());
+    let Tag(tag) = match key.key.algorithm.id {
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        AlgorithmID::AES_128_GCM_SIV | AlgorithmID::AES_256_GCM_SIV => aes_gcm_siv::seal_to(
+            &key.key.inner,
+            nonce,
+            aad,
+            plaintext,
+            ciphertext,
+            key.key.cpu_features,
+        ),
+        _ => {
+            ciphertext.copy_from_slice(plaintext);
+            (key.key.algorithm.seal)(&key.key.inner, nonce, aad, ciphertext, key.key.cpu_features)
+        }
+    };
+    tag_out.copy_from_slice(tag.as_ref());
+    Ok(out)
+}
+
+/// Encrypts and signs (“seals”) the concatenation of `plaintext_segments`
+/// into `out`.
+///
+/// This is like `seal_into()` except the plaintext is given as a list of
+/// segments, e.g. a record's header, payload, and padding, which are gathered
+/// directly into `out` instead of first being copied into a contiguous
+/// buffer. `out` must be at least the total length of the segments plus
+/// `key.algorithm().tag_len()` bytes long.
+///
+/// `nonce` must be unique for every use of the key to seal data.
+///
+/// `aad` is the additional authenticated data, if any.
+pub fn seal_into_vectored<'o, A: AsRef<[u8]>>(
+    key: &SealingKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
+    plaintext_segments: &[&[u8]],
+    out: &'o mut [MaybeUninit<u8>],
+) -> Result<&'o mut [u8], error::Unspecified> {
+    let tag_len = key.key.algorithm.tag_len();
+    let in_out = copy_segments_into_uninit(plaintext_segments, out, tag_len)?;
+    let out_len = seal_in_place_(
+        &key.key,
+        &key.key.inner,
+        nonce,
+        Aad::from(aad.as_ref()),
+        in_out,
+        tag_len,
+    )?;
+    Ok(&mut in_out[..out_len])
+}
+
+// Copies `src` to the start of `out` and zeros the `suffix_len` bytes after
+// it, returning that initialized part of `out`. The rest of `out` is left
+// untouched, so large outputs aren't filled twice.
+fn copy_into_uninit<'o>(
+    src: &[u8],
+    out: &'o mut [MaybeUninit<u8>],
+    suffix_len: usize,
+) -> Result<&'o mut [u8], error::Unspecified> {
+    copy_segments_into_uninit(&[src], out, suffix_len)
+}
+
+// Like `copy_into_uninit`, for the concatenation of `srcs`.
+fn copy_segments_into_uninit<'o>(
+    srcs: &[&[u8]],
+    out: &'o mut [MaybeUninit<u8>],
+    suffix_len: usize,
+) -> Result<&'o mut [u8], error::Unspecified> {
+    let src_len = srcs.iter().try_fold(0usize, |len, src| len.checked_add(src.len()));
+    let src_len = src_len.ok_or(error::Unspecified)?;
+    let len = src_len.checked_add(suffix_len).ok_or(error::Unspecified)?;
+    if out.len() < len {
+        return Err(error::Unspecified);
+    }
+    let out = out.as_mut_ptr() as *mut u8;
+    // `out` has room for `len` bytes, all of which are initialized
+    // before the slice is formed, and the sources can't overlap `out`
+    // because `out` is borrowed mutably.
+    unsafe {
+        let mut dst = out;
+        for src in srcs {
+            core::ptr::copy_nonoverlapping(src.as_ptr(), dst, src.len());
+            dst = dst.add(src.len());
+        }
+        core::ptr::write_bytes(dst, 0, suffix_len);
+        Ok(core::slice::from_raw_parts_mut(out, len))
+    }
+}
+
 /// The additionally authenticated data (AAD) for an opening or sealing
 /// operation. This data is authenticated but is **not** encrypted.
 #[repr(transparent)]
@@ -316,10 +975,32 @@ impl Aad<[u8; 0]> {
 
 /// `OpeningKey` and `SealingKey` are type-safety wrappers around `Key`, which
 /// does all the actual work via the C AEAD interface.
+#[cfg_attr(feature = "use_heap", derive(Clone))]
 struct Key {
-    inner: KeyInner,
+    inner: SharedKeyInner,
     algorithm: &'static Algorithm,
     cpu_features: cpu::Features,
+    #[cfg(feature = "audit")]
+    fingerprint: audit::Fingerprint,
+}
+
+// The expanded key (e.g. the AES key schedule and the GHASH table) is large,
+// so when the heap is available it is shared between clones of a key, which
+// may be used concurrently from multiple threads. With `memory_locking` it is
+// kept in locked memory.
+#[cfg(all(feature = "use_heap", not(feature = "memory_locking")))]
+type SharedKeyInner = std::sync::Arc<KeyInner>;
+
+#[cfg(feature = "memory_locking")]
+type SharedKeyInner = std::sync::Arc<crate::secret::Box<KeyInner>>;
+
+#[cfg(not(feature = "use_heap"))]
+type SharedKeyInner = KeyInner;
+
+fn share_key_inner(inner: KeyInner) -> SharedKeyInner {
+    #[cfg(feature = "memory_locking")]
+    let inner = crate::secret::Box::new(inner);
+    SharedKeyInner::from(inner)
 }
 
 derive_debug_via_field!(Key, algorithm);
@@ -327,24 +1008,36 @@ derive_debug_via_field!(Key, algorithm);
 #[allow(variant_size_differences)]
 enum KeyInner {
     AesGcm(aes_gcm::Key),
+    #[cfg(feature = "aead-aes-gcm-siv")]
     AesGcmSiv(aes_gcm_siv::Key),
+    AesSiv(aes_siv::Key),
+    Aegis(aegis::Key),
+    AegisMessage(aegis::MessageKey),
+    Ascon(ascon::Key),
+    AsconMessage(ascon::MessageKey),
+    #[cfg(feature = "aead-chacha")]
     ChaCha20Poly1305(chacha20_poly1305::Key),
+    #[cfg(feature = "aead-chacha")]
+    XChaCha20Poly1305(xchacha20_poly1305::Key),
 }
 
 impl Key {
-    fn derive(algorithm: &'static Algorithm, okm: hkdf::Okm) -> Self {
+    fn derive(algorithm: &'static Algorithm, okm: hkdf::Okm) -> Result<Self, error::Unspecified> {
         let mut key_bytes = [0; MAX_KEY_LEN];
         let key_bytes = &mut key_bytes[..algorithm.key_len];
-        okm.fill(key_bytes).unwrap();
-        Self::new(algorithm, key_bytes).unwrap()
+        okm.fill(key_bytes)?;
+        Self::new(algorithm, key_bytes)
     }
 
     fn new(algorithm: &'static Algorithm, key_bytes: &[u8]) -> Result<Self, error::Unspecified> {
+        policy::check(algorithm.fips_approved())?;
         let cpu_features = cpu::features();
         Ok(Self {
-            inner: (algorithm.init)(key_bytes, cpu_features)?,
+            inner: share_key_inner((algorithm.init)(key_bytes, cpu_features)?),
             algorithm,
             cpu_features,
+            #[cfg(feature = "audit")]
+            fingerprint: audit::Fingerprint::new(key_bytes),
         })
     }
 
@@ -353,37 +1046,160 @@ impl Key {
     fn algorithm(&self) -> &'static Algorithm {
         self.algorithm
     }
+
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    fn serialize(&self) -> Result<SerializedKey, error::Unspecified> {
+        let inner: &KeyInner = &self.inner;
+        let (algorithm, key) = match (&self.algorithm.id, inner) {
+            (AlgorithmID::AES_128_GCM_SIV, KeyInner::AesGcmSiv(key)) => (1, key),
+            (AlgorithmID::AES_256_GCM_SIV, KeyInner::AesGcmSiv(key)) => (2, key),
+            _ => {
+                return Err(error::Unspecified);
+            }
+        };
+
+        let mut serialized = SerializedKey([0u8; SERIALIZED_KEY_LEN]);
+        let (header, rest) = serialized.0.split_at_mut(SERIALIZED_KEY_HEADER_LEN);
+        header[0] = SERIALIZED_KEY_VERSION;
+        header[1] = algorithm;
+        #[cfg(feature = "audit")]
+        {
+            header[2] = SERIALIZED_KEY_HAS_FINGERPRINT;
+            header[4..].copy_from_slice(self.fingerprint.as_ref());
+        }
+        key.serialize(self.cpu_features, rest.try_into().unwrap());
+        Ok(serialized)
+    }
+
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    fn deserialize(
+        algorithm: &'static Algorithm,
+        serialized: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        policy::check(algorithm.fips_approved())?;
+        if serialized.len() != SERIALIZED_KEY_LEN {
+            return Err(error::Unspecified);
+        }
+        let (header, rest) = serialized.split_at(SERIALIZED_KEY_HEADER_LEN);
+        let variant = match (&algorithm.id, header[1]) {
+            (AlgorithmID::AES_128_GCM_SIV, 1) => aes::Variant::AES_128,
+            (AlgorithmID::AES_256_GCM_SIV, 2) => aes::Variant::AES_256,
+            _ => {
+                return Err(error::Unspecified);
+            }
+        };
+        if header[0] != SERIALIZED_KEY_VERSION
+            || header[2] & !SERIALIZED_KEY_HAS_FINGERPRINT != 0
+            || header[3] != 0
+        {
+            return Err(error::Unspecified);
+        }
+        #[cfg(feature = "audit")]
+        let fingerprint = {
+            if header[2] != SERIALIZED_KEY_HAS_FINGERPRINT {
+                return Err(error::Unspecified);
+            }
+            let mut fingerprint = [0u8; audit::FINGERPRINT_LEN];
+            fingerprint.copy_from_slice(&header[4..]);
+            audit::Fingerprint::from_bytes(fingerprint)
+        };
+
+        let cpu_features = cpu::features();
+        let key = gcm_siv::Key::deserialize(rest.try_into().unwrap(), variant, cpu_features)?;
+        Ok(Self {
+            inner: share_key_inner(KeyInner::AesGcmSiv(key)),
+            algorithm,
+            cpu_features,
+            #[cfg(feature = "audit")]
+            fingerprint,
+        })
+    }
+}
+
+/// A serialized AES-GCM-SIV key, from `SealingKey::serialize()` or
+/// `OpeningKey::serialize()`.
+///
+/// The encoding starts with a version number. It is zeroed when dropped.
+#[cfg(feature = "aead-aes-gcm-siv")]
+pub struct SerializedKey([u8; SERIALIZED_KEY_LEN]);
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+impl AsRef<[u8]> for SerializedKey {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.0[..]
+    }
 }
 
+#[cfg(feature = "aead-aes-gcm-siv")]
+impl Drop for SerializedKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+impl fmt::Debug for SerializedKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SerializedKey").finish()
+    }
+}
+
+/// The length of a `SerializedKey`.
+#[cfg(feature = "aead-aes-gcm-siv")]
+pub const SERIALIZED_KEY_LEN: usize = SERIALIZED_KEY_HEADER_LEN + gcm_siv::SERIALIZED_LEN;
+
+// The header is the version, the algorithm, flags, a zero byte, and the
+// audit fingerprint if the flags say there is one.
+#[cfg(feature = "aead-aes-gcm-siv")]
+const SERIALIZED_KEY_HEADER_LEN: usize = 4 + 16;
+#[cfg(feature = "aead-aes-gcm-siv")]
+const SERIALIZED_KEY_VERSION: u8 = 1;
+#[cfg(feature = "aead-aes-gcm-siv")]
+const SERIALIZED_KEY_HAS_FINGERPRINT: u8 = 1;
+
 impl fmt::Debug for KeyInner {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             KeyInner::AesGcm(_) => write!(f, "AesGcm"),
+            #[cfg(feature = "aead-aes-gcm-siv")]
             KeyInner::AesGcmSiv(_) => write!(f, "AesGcmSiv"),
+            KeyInner::AesSiv(_) => write!(f, "AesSiv"),
+            KeyInner::Aegis(_) => write!(f, "Aegis"),
+            KeyInner::AegisMessage(_) => write!(f, "AegisMessage"),
+            KeyInner::Ascon(_) => write!(f, "Ascon"),
+            KeyInner::AsconMessage(_) => write!(f, "AsconMessage"),
+            #[cfg(feature = "aead-chacha")]
             KeyInner::ChaCha20Poly1305(_) => write!(f, "ChaCha20Poly1305"),
+            #[cfg(feature = "aead-chacha")]
+            KeyInner::XChaCha20Poly1305(_) => write!(f, "XChaCha20Poly1305"),
         }
     }
 }
 
+type SealFn = fn(
+    key: &KeyInner,
+    nonce: Nonce,
+    aad: Aad<&[u8]>,
+    in_out: &mut [u8],
+    cpu_features: cpu::Features,
+) -> Tag;
+
+type OpenFn = fn(
+    key: &KeyInner,
+    nonce: Nonce,
+    aad: Aad<&[u8]>,
+    in_prefix_len: usize,
+    in_out: &mut [u8],
+    cpu_features: cpu::Features,
+) -> Tag;
+
 /// An AEAD Algorithm.
 pub struct Algorithm {
     init: fn(key: &[u8], cpu_features: cpu::Features) -> Result<KeyInner, error::Unspecified>,
 
-    seal: fn(
-        key: &KeyInner,
-        nonce: Nonce,
-        aad: Aad<&[u8]>,
-        in_out: &mut [u8],
-        cpu_features: cpu::Features,
-    ) -> Tag,
-    open: fn(
-        key: &KeyInner,
-        nonce: Nonce,
-        aad: Aad<&[u8]>,
-        in_prefix_len: usize,
-        in_out: &mut [u8],
-        cpu_features: cpu::Features,
-    ) -> Tag,
+    seal: SealFn,
+    open: OpenFn,
 
     key_len: usize,
     id: AlgorithmID,
@@ -416,21 +1232,189 @@ impl Algorithm {
     }
 
     /// The length of the nonces.
+    ///
+    /// This is `xchacha20_poly1305::NONCE_LEN` for `XCHACHA20_POLY1305`,
+    /// `aegis::AEGIS_128L_NONCE_LEN` for `AEGIS_128L`,
+    /// `aegis::AEGIS_256_NONCE_LEN` for `AEGIS_256`, `ascon::NONCE_LEN` for
+    /// `ASCON_128`, and `NONCE_LEN` for the other algorithms.
     #[inline(always)]
     pub fn nonce_len(&self) -> usize {
-        NONCE_LEN
+        match self.id {
+            AlgorithmID::AEGIS_128L => aegis::AEGIS_128L_NONCE_LEN,
+            AlgorithmID::AEGIS_256 => aegis::AEGIS_256_NONCE_LEN,
+            AlgorithmID::ASCON_128 => ascon::NONCE_LEN,
+            #[cfg(feature = "aead-chacha")]
+            AlgorithmID::XCHACHA20_POLY1305 => xchacha20_poly1305::NONCE_LEN,
+            _ => NONCE_LEN,
+        }
+    }
+
+    /// The maximum length of the plaintext, or of the ciphertext without the
+    /// tag, that may be sealed or opened with one nonce.
+    #[inline]
+    pub fn max_input_len(&self) -> u64 {
+        self.max_input_len
+    }
+
+    /// The maximum length of the additional authenticated data.
+    ///
+    /// This is 2**61 - 1 for `AES_128_GCM` and `AES_256_GCM` (see
+    /// [NIST SP 800-38D]) and for `AEGIS_128L` and `AEGIS_256`, 2**36 for `AES_128_GCM_SIV` and `AES_256_GCM_SIV`
+    /// (see [RFC 8452 Section 6]), and `u64::max_value()`, i.e. unlimited,
+    /// for the other algorithms.
+    ///
+    /// [NIST SP 800-38D]: https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38d.pdf
+    /// [RFC 8452 Section 6]: https://tools.ietf.org/html/rfc8452#section-6
+    pub fn max_aad_len(&self) -> u64 {
+        match self.id {
+            AlgorithmID::AES_128_GCM | AlgorithmID::AES_256_GCM => (1 << 61) - 1,
+            #[cfg(feature = "aead-aes-gcm-siv")]
+            AlgorithmID::AES_128_GCM_SIV | AlgorithmID::AES_256_GCM_SIV => 1 << 36,
+            AlgorithmID::AEGIS_128L | AlgorithmID::AEGIS_256 => aegis::MAX_INPUT_LEN,
+            AlgorithmID::AES_128_SIV_CMAC | AlgorithmID::AES_256_SIV_CMAC | AlgorithmID::ASCON_128 => {
+                u64::max_value()
+            }
+            #[cfg(feature = "aead-chacha")]
+            AlgorithmID::CHACHA20_POLY1305 | AlgorithmID::XCHACHA20_POLY1305 => u64::max_value(),
+        }
+    }
+
+    /// Checks that `aad_len` bytes of additional authenticated data and
+    /// `in_out_len` bytes of plaintext, or of ciphertext without the tag, may
+    /// be sealed or opened with one nonce.
+    ///
+    /// Sealing and opening fail with `error::Unspecified` in exactly the
+    /// cases where this fails; this says which input is too long.
+    pub fn check_input_lens(&self, aad_len: usize, in_out_len: usize) -> Result<(), TooLong> {
+        if polyfill::u64_from_usize(aad_len) > self.max_aad_len() {
+            return Err(TooLong {
+                what: TooLongInput::Aad,
+                max: self.max_aad_len(),
+            });
+        }
+        if polyfill::u64_from_usize(in_out_len) > self.max_input_len {
+            return Err(TooLong {
+                what: TooLongInput::InOut,
+                max: self.max_input_len,
+            });
+        }
+        Ok(())
+    }
+
+    // Whether the algorithm is allowed by `policy::Policy::FipsApproved`.
+    pub(crate) fn fips_approved(&self) -> bool {
+        match self.id {
+            AlgorithmID::AES_128_GCM | AlgorithmID::AES_256_GCM => true,
+            _ => false,
+        }
+    }
+
+    /// All the AEAD algorithms in this module.
+    pub fn all() -> &'static [&'static Self] {
+        ALL_ALGORITHMS
+    }
+
+    /// The AEAD algorithm of the TLS 1.3 cipher suite `id`, as listed in
+    /// [RFC 8446 Appendix B.4], e.g. `AES_128_GCM` for 0x1301
+    /// (`TLS_AES_128_GCM_SHA256`).
+    ///
+    /// The cipher suite's hash algorithm is given by
+    /// `digest::Algorithm::from_tls_id()`. Returns `None` for cipher suites
+    /// that aren't supported.
+    ///
+    /// [RFC 8446 Appendix B.4]: https://tools.ietf.org/html/rfc8446#appendix-B.4
+    pub fn from_tls_id(id: u16) -> Option<&'static Self> {
+        match id {
+            0x1301 => Some(&AES_128_GCM),
+            0x1302 => Some(&AES_256_GCM),
+            #[cfg(feature = "aead-chacha")]
+            0x1303 => Some(&CHACHA20_POLY1305),
+            _ => None,
+        }
+    }
+
+    /// The AEAD algorithm named `name`, e.g. `AES_128_GCM` for
+    /// `"AES_128_GCM"`. The names are those of the statics in this module,
+    /// which are also what the `Debug` implementation prints.
+    pub fn from_name(name: &str) -> Option<&'static Self> {
+        Self::all()
+            .iter()
+            .find(|algorithm| algorithm.id.name() == name)
+            .cloned()
     }
 }
 
 derive_debug_via_id!(Algorithm);
 
+static ALL_ALGORITHMS: &[&Algorithm] = &[
+    &AES_128_GCM,
+    &AES_256_GCM,
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    &AES_128_GCM_SIV,
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    &AES_256_GCM_SIV,
+    &AES_128_SIV_CMAC,
+    &AES_256_SIV_CMAC,
+    &AEGIS_128L,
+    &AEGIS_256,
+    &ASCON_128,
+    #[cfg(feature = "aead-chacha")]
+    &CHACHA20_POLY1305,
+    #[cfg(feature = "aead-chacha")]
+    &XCHACHA20_POLY1305,
+];
+
 #[derive(Debug, Eq, PartialEq)]
 enum AlgorithmID {
     AES_128_GCM,
     AES_256_GCM,
+    #[cfg(feature = "aead-aes-gcm-siv")]
     AES_128_GCM_SIV,
+    #[cfg(feature = "aead-aes-gcm-siv")]
     AES_256_GCM_SIV,
+    AES_128_SIV_CMAC,
+    AES_256_SIV_CMAC,
+    AEGIS_128L,
+    AEGIS_256,
+    ASCON_128,
+    #[cfg(feature = "aead-chacha")]
     CHACHA20_POLY1305,
+    #[cfg(feature = "aead-chacha")]
+    XCHACHA20_POLY1305,
+}
+
+impl AlgorithmID {
+    // Must match the `Debug` output.
+    fn name(&self) -> &'static str {
+        match self {
+            AlgorithmID::AES_128_GCM => "AES_128_GCM",
+            AlgorithmID::AES_256_GCM => "AES_256_GCM",
+            #[cfg(feature = "aead-aes-gcm-siv")]
+            AlgorithmID::AES_128_GCM_SIV => "AES_128_GCM_SIV",
+            #[cfg(feature = "aead-aes-gcm-siv")]
+            AlgorithmID::AES_256_GCM_SIV => "AES_256_GCM_SIV",
+            AlgorithmID::AES_128_SIV_CMAC => "AES_128_SIV_CMAC",
+            AlgorithmID::AES_256_SIV_CMAC => "AES_256_SIV_CMAC",
+            AlgorithmID::AEGIS_128L => "AEGIS_128L",
+            AlgorithmID::AEGIS_256 => "AEGIS_256",
+            AlgorithmID::ASCON_128 => "ASCON_128",
+            #[cfg(feature = "aead-chacha")]
+            AlgorithmID::CHACHA20_POLY1305 => "CHACHA20_POLY1305",
+            #[cfg(feature = "aead-chacha")]
+            AlgorithmID::XCHACHA20_POLY1305 => "XCHACHA20_POLY1305",
+        }
+    }
+
+    // Whether the algorithm is a SIV construction, whose `open` reads the
+    // tag from the end of its input.
+    fn is_siv(&self) -> bool {
+        match self {
+            #[cfg(feature = "aead-aes-gcm-siv")]
+            AlgorithmID::AES_128_GCM_SIV | AlgorithmID::AES_256_GCM_SIV => true,
+            AlgorithmID::AES_128_SIV_CMAC | AlgorithmID::AES_256_SIV_CMAC => true,
+            _ => false,
+        }
+    }
 }
 
 impl PartialEq for Algorithm {
@@ -441,12 +1425,104 @@ impl PartialEq for Algorithm {
 
 impl Eq for Algorithm {}
 
+/// An error from `Algorithm::check_input_lens()`: an input is longer than
+/// the algorithm allows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TooLong {
+    what: TooLongInput,
+    max: u64,
+}
+
+impl TooLong {
+    /// Which input is too long.
+    #[inline]
+    pub fn what(&self) -> TooLongInput {
+        self.what
+    }
+
+    /// The maximum length of that input.
+    #[inline]
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+}
+
+impl From<TooLong> for error::Unspecified {
+    fn from(_: TooLong) -> Self {
+        error::Unspecified
+    }
+}
+
+impl From<TooLong> for error::InputRejected {
+    fn from(too_long: TooLong) -> Self {
+        match too_long.what {
+            TooLongInput::Aad => error::InputRejected::aad_too_long(),
+            TooLongInput::InOut => error::InputRejected::too_long(),
+        }
+    }
+}
+
+impl fmt::Display for TooLong {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let what = match self.what {
+            TooLongInput::Aad => "Aad",
+            TooLongInput::InOut => "InOut",
+        };
+        write!(
+            f,
+            "ring::aead::TooLong {{ what: {}, max: {} }}",
+            what, self.max
+        )
+    }
+}
+
+#[cfg(feature = "use_heap")]
+impl std::error::Error for TooLong {}
+
+/// An input that may be too long; see `TooLong`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TooLongInput {
+    /// The additional authenticated data.
+    Aad,
+
+    /// The plaintext when sealing, or the ciphertext without the tag when
+    /// opening.
+    InOut,
+}
+
 /// An authentication tag.
 #[must_use]
 #[repr(C)]
-struct Tag(Block);
+pub struct Tag(Block);
+
+impl AsRef<[u8]> for Tag {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl From<[u8; MAX_TAG_LEN]> for Tag {
+    #[inline]
+    fn from(value: [u8; MAX_TAG_LEN]) -> Self {
+        Tag(Block::from(&value))
+    }
+}
+
+impl TryFrom<&[u8]> for Tag {
+    type Error = error::Unspecified;
 
-const MAX_KEY_LEN: usize = 32;
+    /// Fails if `value` isn't `MAX_TAG_LEN` bytes long.
+    #[inline]
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let value: &[u8; MAX_TAG_LEN] = value.try_into_()?;
+        Ok(Tag(Block::from(value)))
+    }
+}
+
+derive_debug_self_as_ref_hex_bytes!(Tag);
+
+pub(crate) const MAX_KEY_LEN: usize = 64;
 
 // All the AEADs we support use 128-bit tags.
 const TAG_LEN: usize = BLOCK_LEN;
@@ -454,10 +1530,23 @@ const TAG_LEN: usize = BLOCK_LEN;
 /// The maximum length of a tag for the algorithms in this module.
 pub const MAX_TAG_LEN: usize = TAG_LEN;
 
-fn check_per_nonce_max_bytes(alg: &Algorithm, in_out_len: usize) -> Result<(), error::Unspecified> {
-    if polyfill::u64_from_usize(in_out_len) > alg.max_input_len {
-        return Err(error::Unspecified);
+// XChaCha20-Poly1305 keys can only be used with the extended nonces of
+// `xchacha20_poly1305`, which pass the derived subkey as `inner` instead.
+// Likewise AEGIS and Ascon keys can only be used with the nonces of `aegis`
+// and `ascon`.
+fn check_nonce_len(inner: &KeyInner) -> Result<(), error::InputRejected> {
+    match inner {
+        #[cfg(feature = "aead-chacha")]
+        KeyInner::XChaCha20Poly1305(_) => Err(error::InputRejected::wrong_nonce_type()),
+        KeyInner::Aegis(_) | KeyInner::Ascon(_) => Err(error::InputRejected::wrong_nonce_type()),
+        _ => Ok(()),
     }
+}
+
+// For the algorithms whose additional authenticated data is unlimited or
+// has already been checked.
+fn check_per_nonce_max_bytes(alg: &Algorithm, in_out_len: usize) -> Result<(), error::Unspecified> {
+    alg.check_input_lens(0, in_out_len)?;
     Ok(())
 }
 
@@ -467,16 +1556,57 @@ enum Direction {
     Sealing,
 }
 
-mod aes;
+pub mod aegis;
+pub(crate) mod aes;
 mod aes_gcm;
+
+#[cfg(feature = "aead-aes-gcm-siv")]
 mod aes_gcm_siv;
+
+pub mod aes_kw;
+pub mod aes_siv;
+pub mod ascon;
+
+#[cfg(feature = "aead-aes-gcm-siv")]
 mod gcm_siv;
-mod block;
-mod chacha;
+
+mod batch;
+pub(crate) mod block;
+mod bounded_use;
+
+#[cfg(feature = "aead-chacha")]
+pub(crate) mod chacha;
+
+#[cfg(feature = "aead-chacha")]
 mod chacha20_poly1305;
+
+#[cfg(feature = "aead-chacha")]
 pub mod chacha20_poly1305_openssh;
-mod gcm;
+
+pub mod committing;
+pub(crate) mod gcm;
+
+#[cfg(feature = "testing-internals")]
+pub mod internals;
+
+pub(crate) mod inverse_cipher;
+
+#[cfg(feature = "use_heap")]
+mod key_ring;
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+mod multipart;
+
 mod nonce;
-mod poly1305;
+mod nonce_sequence;
+pub mod per_message;
+
+#[cfg(feature = "aead-chacha")]
+pub(crate) mod poly1305;
+
 pub mod quic;
 mod shift;
+pub mod stream;
+
+#[cfg(feature = "aead-chacha")]
+pub mod xchacha20_poly1305;
@@ -0,0 +1,874 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! AEGIS-128L and AEGIS-256 sealing and opening.
+//!
+//! Keys for `AEGIS_128L` and `AEGIS_256` are constructed like those for the
+//! other algorithms, as `aead::SealingKey` and `aead::OpeningKey`, but they
+//! must be used with the functions of this module, which take the longer
+//! `Nonce` defined here; the functions in `aead` reject them.
+//!
+//! AEGIS is built from the AES round function. On x86-64 and AArch64 CPUs
+//! with AES instructions it is much faster than AES-GCM. Otherwise, this uses
+//! a portable implementation that computes the S-box with arithmetic in
+//! GF(2**8) instead of table lookups, so it is constant-time but much slower
+//! than ChaCha20-Poly1305, which should be preferred on such CPUs.
+
+use super::{
+    inverse_cipher::{s_box, xtime},
+    Aad, Algorithm, AlgorithmID, Block, KeyInner, OpeningKey, SealingKey, Tag, BLOCK_LEN,
+};
+use crate::{
+    cpu, error,
+    polyfill::{self, convert::*},
+};
+use core::ops::RangeFrom;
+
+/// AEGIS-128L as described in [draft-irtf-cfrg-aegis-aead].
+///
+/// The keys are 128 bits long and the nonces are 128 bits long. Messages are
+/// sealed and opened with `seal_in_place()` and `open_in_place()` of this
+/// module. Nonces are long enough that generating each one randomly is safe
+/// for up to 2**48 messages sealed with the same key.
+///
+/// [draft-irtf-cfrg-aegis-aead]:
+///     https://tools.ietf.org/html/draft-irtf-cfrg-aegis-aead-04
+pub static AEGIS_128L: Algorithm = Algorithm {
+    key_len: AEGIS_128L_KEY_LEN,
+    init: aegis_128l_init,
+    seal: aegis_seal,
+    open: aegis_open,
+    id: AlgorithmID::AEGIS_128L,
+    max_input_len: MAX_INPUT_LEN,
+};
+
+/// AEGIS-256 as described in [draft-irtf-cfrg-aegis-aead].
+///
+/// The keys are 256 bits long and the nonces are 256 bits long. Messages are
+/// sealed and opened with `seal_in_place()` and `open_in_place()` of this
+/// module. Nonces are long enough that generating each one randomly is safe,
+/// even for very many messages sealed with the same key.
+///
+/// [draft-irtf-cfrg-aegis-aead]:
+///     https://tools.ietf.org/html/draft-irtf-cfrg-aegis-aead-04
+pub static AEGIS_256: Algorithm = Algorithm {
+    key_len: AEGIS_256_KEY_LEN,
+    init: aegis_256_init,
+    seal: aegis_seal,
+    open: aegis_open,
+    id: AlgorithmID::AEGIS_256,
+    max_input_len: MAX_INPUT_LEN,
+};
+
+/// The length of an AEGIS-128L nonce.
+pub const AEGIS_128L_NONCE_LEN: usize = 128 / 8;
+
+/// The length of an AEGIS-256 nonce.
+pub const AEGIS_256_NONCE_LEN: usize = 256 / 8;
+
+const AEGIS_128L_KEY_LEN: usize = 128 / 8;
+const AEGIS_256_KEY_LEN: usize = 256 / 8;
+
+// The maximum length of the plaintext and of the additional authenticated
+// data, 2**61 - 1 bytes, so that their lengths in bits fit in 64 bits.
+pub(super) const MAX_INPUT_LEN: u64 = (1 << 61) - 1;
+
+/// A nonce for a single AEGIS-128L or AEGIS-256 opening or sealing operation.
+///
+/// The user must ensure, for a particular key, that each nonce is unique.
+///
+/// `Nonce` intentionally doesn't implement `Clone` to ensure that each one is
+/// consumed at most once.
+pub struct Nonce {
+    bytes: [u8; AEGIS_256_NONCE_LEN],
+    len: usize,
+}
+
+impl Nonce {
+    /// Constructs a `Nonce` with the given value, assuming that the value is
+    /// unique for the lifetime of the key it is being used with.
+    ///
+    /// Fails unless `value` is `AEGIS_128L_NONCE_LEN` or
+    /// `AEGIS_256_NONCE_LEN` bytes long. Sealing and opening fail unless it
+    /// is as long as the nonces of the key's algorithm.
+    pub fn try_assume_unique_for_key(value: &[u8]) -> Result<Self, error::Unspecified> {
+        if value.len() != AEGIS_128L_NONCE_LEN && value.len() != AEGIS_256_NONCE_LEN {
+            return Err(error::Unspecified);
+        }
+        let mut bytes = [0u8; AEGIS_256_NONCE_LEN];
+        bytes[..value.len()].copy_from_slice(value);
+        Ok(Self {
+            bytes,
+            len: value.len(),
+        })
+    }
+}
+
+impl AsRef<[u8]> for Nonce {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Encrypts and signs (“seals”) data in place with an AEGIS key.
+///
+/// This is like `aead::seal_in_place()` except for the type of `nonce`.
+/// Fails unless `key`'s algorithm is `AEGIS_128L` or `AEGIS_256` and `nonce`
+/// is as long as its nonces.
+pub fn seal_in_place<A: AsRef<[u8]>>(
+    key: &SealingKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
+    in_out: &mut [u8],
+    out_suffix_capacity: usize,
+) -> Result<usize, error::Unspecified> {
+    let message_key = MessageKey::new(&key.key.inner, nonce)?;
+    super::seal_in_place_(
+        &key.key,
+        &KeyInner::AegisMessage(message_key),
+        unused_nonce(),
+        Aad::from(aad.as_ref()),
+        in_out,
+        out_suffix_capacity,
+    )
+    .map_err(error::Unspecified::from)
+}
+
+/// Authenticates and decrypts (“opens”) data in place with an AEGIS key.
+///
+/// This is like `aead::open_in_place()` except for the type of `nonce`.
+/// Fails unless `key`'s algorithm is `AEGIS_128L` or `AEGIS_256` and `nonce`
+/// is as long as its nonces.
+pub fn open_in_place<'a, A: AsRef<[u8]>>(
+    key: &OpeningKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
+    in_prefix_len: usize,
+    ciphertext_and_tag_modified_in_place: &'a mut [u8],
+) -> Result<&'a mut [u8], error::Unspecified> {
+    let message_key = MessageKey::new(&key.key.inner, nonce)?;
+    super::open_in_place_(
+        &key.key,
+        &KeyInner::AegisMessage(message_key),
+        unused_nonce(),
+        Aad::from(aad.as_ref()),
+        in_prefix_len,
+        ciphertext_and_tag_modified_in_place,
+    )
+    .map_err(error::Unspecified::from)
+}
+
+/// Authenticates and decrypts (“opens”) data in place with an AEGIS key,
+/// moving the plaintext to the start of `in_out`.
+///
+/// This is like `aead::open_within()` except for the type of `nonce`.
+/// Fails unless `key`'s algorithm is `AEGIS_128L` or `AEGIS_256` and `nonce`
+/// is as long as its nonces.
+#[inline]
+pub fn open_within<'a, A: AsRef<[u8]>>(
+    key: &OpeningKey,
+    nonce: Nonce,
+    aad: Aad<A>,
+    in_out: &'a mut [u8],
+    ciphertext_and_tag: RangeFrom<usize>,
+) -> Result<&'a mut [u8], error::Unspecified> {
+    open_in_place(key, nonce, aad, ciphertext_and_tag.start, in_out)
+}
+
+#[derive(Clone, Copy)]
+enum Variant {
+    Aegis128L,
+    Aegis256,
+}
+
+pub(super) struct Key {
+    variant: Variant,
+    bytes: [u8; AEGIS_256_KEY_LEN],
+}
+
+// A key together with the nonce of one message. The `aead` functions get
+// this as their `KeyInner`, and a `super::Nonce` that isn't used.
+pub(super) struct MessageKey {
+    variant: Variant,
+    key: [u8; AEGIS_256_KEY_LEN],
+    nonce: [u8; AEGIS_256_NONCE_LEN],
+}
+
+impl MessageKey {
+    fn new(key: &KeyInner, nonce: Nonce) -> Result<Self, error::Unspecified> {
+        let key = match key {
+            KeyInner::Aegis(key) => key,
+            _ => return Err(error::Unspecified),
+        };
+        let nonce_len = match key.variant {
+            Variant::Aegis128L => AEGIS_128L_NONCE_LEN,
+            Variant::Aegis256 => AEGIS_256_NONCE_LEN,
+        };
+        if nonce.len != nonce_len {
+            return Err(error::Unspecified);
+        }
+        Ok(Self {
+            variant: key.variant,
+            key: key.bytes,
+            nonce: nonce.bytes,
+        })
+    }
+}
+
+fn unused_nonce() -> super::Nonce {
+    super::Nonce::assume_unique_for_key([0; super::NONCE_LEN])
+}
+
+fn aegis_128l_init(key: &[u8], _todo: cpu::Features) -> Result<KeyInner, error::Unspecified> {
+    init(key, Variant::Aegis128L)
+}
+
+fn aegis_256_init(key: &[u8], _todo: cpu::Features) -> Result<KeyInner, error::Unspecified> {
+    init(key, Variant::Aegis256)
+}
+
+fn init(key: &[u8], variant: Variant) -> Result<KeyInner, error::Unspecified> {
+    let key_len = match variant {
+        Variant::Aegis128L => AEGIS_128L_KEY_LEN,
+        Variant::Aegis256 => AEGIS_256_KEY_LEN,
+    };
+    if key.len() != key_len {
+        return Err(error::Unspecified);
+    }
+    let mut bytes = [0u8; AEGIS_256_KEY_LEN];
+    bytes[..key_len].copy_from_slice(key);
+    Ok(KeyInner::Aegis(Key { variant, bytes }))
+}
+
+fn aegis_seal(
+    key: &KeyInner,
+    _nonce: super::Nonce,
+    Aad(aad): Aad<&[u8]>,
+    in_out: &mut [u8],
+    _cpu_features: cpu::Features,
+) -> Tag {
+    let key = message_key(key);
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if cpu::intel::AES.available(_cpu_features) {
+            return Tag(unsafe { aesni::seal(key, aad, in_out) });
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        if cpu::arm::AES.available(_cpu_features) {
+            return Tag(unsafe { neon::seal(key, aad, in_out) });
+        }
+    }
+
+    Tag(unsafe { seal::<Portable>(key, aad, in_out) })
+}
+
+fn aegis_open(
+    key: &KeyInner,
+    _nonce: super::Nonce,
+    Aad(aad): Aad<&[u8]>,
+    in_prefix_len: usize,
+    in_out: &mut [u8],
+    _cpu_features: cpu::Features,
+) -> Tag {
+    let key = message_key(key);
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if cpu::intel::AES.available(_cpu_features) {
+            return Tag(unsafe { aesni::open(key, aad, in_prefix_len, in_out) });
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        if cpu::arm::AES.available(_cpu_features) {
+            return Tag(unsafe { neon::open(key, aad, in_prefix_len, in_out) });
+        }
+    }
+
+    Tag(unsafe { open::<Portable>(key, aad, in_prefix_len, in_out) })
+}
+
+// `aead` rejects `KeyInner::Aegis` keys in `check_nonce_len()`, so only the
+// functions of this module get here.
+fn message_key(key: &KeyInner) -> &MessageKey {
+    match key {
+        KeyInner::AegisMessage(key) => key,
+        _ => unreachable!(),
+    }
+}
+
+// The constants of the initialization, the Fibonacci sequence modulo 256.
+const C0: [u8; BLOCK_LEN] = [
+    0x00, 0x01, 0x01, 0x02, 0x03, 0x05, 0x08, 0x0d, 0x15, 0x22, 0x37, 0x59, 0x90, 0xe9, 0x79, 0x62,
+];
+const C1: [u8; BLOCK_LEN] = [
+    0xdb, 0x3d, 0x18, 0x55, 0x6d, 0xc2, 0x2f, 0xf1, 0x20, 0x11, 0x31, 0x42, 0x73, 0xb5, 0x28, 0xdd,
+];
+
+// AEGIS-128L absorbs and encrypts 32 bytes at a time, AEGIS-256 16 bytes.
+const MAX_RATE: usize = 2 * BLOCK_LEN;
+
+// A 128-bit value of the state, held in a register where possible.
+//
+// The methods are unsafe because implementations may use instructions that
+// the CPU must be checked for; the caller is responsible for that.
+trait Lane: Copy {
+    unsafe fn load(bytes: &[u8; BLOCK_LEN]) -> Self;
+    unsafe fn store(self, bytes: &mut [u8; BLOCK_LEN]);
+    unsafe fn xor(self, other: Self) -> Self;
+    unsafe fn and(self, other: Self) -> Self;
+
+    // `MixColumns(ShiftRows(SubBytes(self))) ^ round_key`, the AES
+    // encryption round function.
+    unsafe fn aes_round(self, round_key: Self) -> Self;
+}
+
+// The AEGIS-128L or AEGIS-256 state.
+trait State: Sized {
+    // How many bytes are absorbed or encrypted at once.
+    const RATE: usize;
+
+    unsafe fn new(key: &MessageKey) -> Self;
+
+    // Each of these takes `RATE` bytes.
+    unsafe fn absorb(&mut self, input: &[u8]);
+    unsafe fn encrypt(&mut self, in_out: &mut [u8]);
+
+    // Only the first `len` bytes of `in_out` are ciphertext; the plaintext
+    // after them is replaced with zeros before it is absorbed.
+    unsafe fn decrypt(&mut self, in_out: &mut [u8], len: usize);
+
+    unsafe fn finalize(self, aad_len: usize, in_out_len: usize) -> Block;
+}
+
+#[inline(always)]
+unsafe fn load<L: Lane>(bytes: &[u8]) -> L {
+    L::load(bytes[..BLOCK_LEN].try_into_().unwrap())
+}
+
+#[inline(always)]
+unsafe fn store<L: Lane>(lane: L, bytes: &mut [u8]) {
+    lane.store((&mut bytes[..BLOCK_LEN]).try_into_().unwrap())
+}
+
+// `LE64(aad_len * 8) || LE64(in_out_len * 8)`, XORed into the state before
+// the tag is computed.
+fn lengths_block(aad_len: usize, in_out_len: usize) -> [u8; BLOCK_LEN] {
+    let mut block = [0u8; BLOCK_LEN];
+    let (aad_bits, in_out_bits) = block.split_at_mut(8);
+    aad_bits.copy_from_slice(&(polyfill::u64_from_usize(aad_len) * 8).to_le_bytes());
+    in_out_bits.copy_from_slice(&(polyfill::u64_from_usize(in_out_len) * 8).to_le_bytes());
+    block
+}
+
+#[inline(always)]
+unsafe fn seal<L: Lane>(key: &MessageKey, aad: &[u8], in_out: &mut [u8]) -> Block {
+    match key.variant {
+        Variant::Aegis128L => seal_::<Aegis128L<L>>(key, aad, in_out),
+        Variant::Aegis256 => seal_::<Aegis256<L>>(key, aad, in_out),
+    }
+}
+
+#[inline(always)]
+unsafe fn open<L: Lane>(
+    key: &MessageKey,
+    aad: &[u8],
+    in_prefix_len: usize,
+    in_out: &mut [u8],
+) -> Block {
+    match key.variant {
+        Variant::Aegis128L => open_::<Aegis128L<L>>(key, aad, in_prefix_len, in_out),
+        Variant::Aegis256 => open_::<Aegis256<L>>(key, aad, in_prefix_len, in_out),
+    }
+}
+
+#[inline(always)]
+unsafe fn seal_<S: State>(key: &MessageKey, aad: &[u8], in_out: &mut [u8]) -> Block {
+    let mut state = S::new(key);
+    absorb_aad(&mut state, aad);
+    for chunk in in_out.chunks_mut(S::RATE) {
+        if chunk.len() == S::RATE {
+            state.encrypt(chunk);
+        } else {
+            // The last, partial block is padded with zeros.
+            let mut block = [0u8; MAX_RATE];
+            block[..chunk.len()].copy_from_slice(chunk);
+            state.encrypt(&mut block[..S::RATE]);
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+    }
+    state.finalize(aad.len(), in_out.len())
+}
+
+// Decrypts `in_out[in_prefix_len..]` into `in_out[..(in_out.len() -
+// in_prefix_len)]`. Each block is copied out before its plaintext is written,
+// which can only overwrite ciphertext that was already decrypted.
+#[inline(always)]
+unsafe fn open_<S: State>(
+    key: &MessageKey,
+    aad: &[u8],
+    in_prefix_len: usize,
+    in_out: &mut [u8],
+) -> Block {
+    let mut state = S::new(key);
+    absorb_aad(&mut state, aad);
+    let ciphertext_len = in_out.len() - in_prefix_len;
+    let mut block = [0u8; MAX_RATE];
+    let mut start = 0;
+    while start < ciphertext_len {
+        let len = core::cmp::min(S::RATE, ciphertext_len - start);
+        block[..len].copy_from_slice(&in_out[(in_prefix_len + start)..][..len]);
+        state.decrypt(&mut block[..S::RATE], len);
+        in_out[start..][..len].copy_from_slice(&block[..len]);
+        start += len;
+    }
+    state.finalize(aad.len(), ciphertext_len)
+}
+
+#[inline(always)]
+unsafe fn absorb_aad<S: State>(state: &mut S, aad: &[u8]) {
+    for chunk in aad.chunks(S::RATE) {
+        if chunk.len() == S::RATE {
+            state.absorb(chunk);
+        } else {
+            let mut block = [0u8; MAX_RATE];
+            block[..chunk.len()].copy_from_slice(chunk);
+            state.absorb(&block[..S::RATE]);
+        }
+    }
+}
+
+struct Aegis128L<L>([L; 8]);
+
+impl<L: Lane> Aegis128L<L> {
+    #[inline(always)]
+    unsafe fn update(&mut self, m0: L, m1: L) {
+        let s = &mut self.0;
+        let s7 = s[7];
+        s[7] = s[6].aes_round(s[7]);
+        s[6] = s[5].aes_round(s[6]);
+        s[5] = s[4].aes_round(s[5]);
+        s[4] = s[3].aes_round(s[4].xor(m1));
+        s[3] = s[2].aes_round(s[3]);
+        s[2] = s[1].aes_round(s[2]);
+        s[1] = s[0].aes_round(s[1]);
+        s[0] = s7.aes_round(s[0].xor(m0));
+    }
+
+    #[inline(always)]
+    unsafe fn keystream(&self) -> (L, L) {
+        let s = &self.0;
+        (
+            s[6].xor(s[1]).xor(s[2].and(s[3])),
+            s[2].xor(s[5]).xor(s[6].and(s[7])),
+        )
+    }
+}
+
+impl<L: Lane> State for Aegis128L<L> {
+    const RATE: usize = 2 * BLOCK_LEN;
+
+    #[inline(always)]
+    unsafe fn new(key: &MessageKey) -> Self {
+        let k: L = load(&key.key);
+        let n: L = load(&key.nonce);
+        let c0 = L::load(&C0);
+        let c1 = L::load(&C1);
+        let k_n = k.xor(n);
+        let mut state = Self([k_n, c1, c0, c1, k_n, k.xor(c0), k.xor(c1), k.xor(c0)]);
+        for _ in 0..10 {
+            state.update(n, k);
+        }
+        state
+    }
+
+    #[inline(always)]
+    unsafe fn absorb(&mut self, input: &[u8]) {
+        self.update(load(input), load(&input[BLOCK_LEN..]));
+    }
+
+    #[inline(always)]
+    unsafe fn encrypt(&mut self, in_out: &mut [u8]) {
+        let (z0, z1) = self.keystream();
+        let x0: L = load(in_out);
+        let x1: L = load(&in_out[BLOCK_LEN..]);
+        store(x0.xor(z0), in_out);
+        store(x1.xor(z1), &mut in_out[BLOCK_LEN..]);
+        self.update(x0, x1);
+    }
+
+    #[inline(always)]
+    unsafe fn decrypt(&mut self, in_out: &mut [u8], len: usize) {
+        let (z0, z1) = self.keystream();
+        let mut x0 = load::<L>(in_out).xor(z0);
+        let mut x1 = load::<L>(&in_out[BLOCK_LEN..]).xor(z1);
+        store(x0, in_out);
+        store(x1, &mut in_out[BLOCK_LEN..]);
+        if len < Self::RATE {
+            for b in in_out[len..].iter_mut() {
+                *b = 0;
+            }
+            x0 = load(in_out);
+            x1 = load(&in_out[BLOCK_LEN..]);
+        }
+        self.update(x0, x1);
+    }
+
+    #[inline(always)]
+    unsafe fn finalize(mut self, aad_len: usize, in_out_len: usize) -> Block {
+        let t = self.0[2].xor(L::load(&lengths_block(aad_len, in_out_len)));
+        for _ in 0..7 {
+            self.update(t, t);
+        }
+        let s = &self.0;
+        let tag = s[0]
+            .xor(s[1])
+            .xor(s[2])
+            .xor(s[3])
+            .xor(s[4])
+            .xor(s[5])
+            .xor(s[6]);
+        let mut bytes = [0u8; BLOCK_LEN];
+        tag.store(&mut bytes);
+        Block::from(&bytes)
+    }
+}
+
+struct Aegis256<L>([L; 6]);
+
+impl<L: Lane> Aegis256<L> {
+    #[inline(always)]
+    unsafe fn update(&mut self, m: L) {
+        let s = &mut self.0;
+        let s5 = s[5];
+        s[5] = s[4].aes_round(s[5]);
+        s[4] = s[3].aes_round(s[4]);
+        s[3] = s[2].aes_round(s[3]);
+        s[2] = s[1].aes_round(s[2]);
+        s[1] = s[0].aes_round(s[1]);
+        s[0] = s5.aes_round(s[0].xor(m));
+    }
+
+    #[inline(always)]
+    unsafe fn keystream(&self) -> L {
+        let s = &self.0;
+        s[1].xor(s[4]).xor(s[5]).xor(s[2].and(s[3]))
+    }
+}
+
+impl<L: Lane> State for Aegis256<L> {
+    const RATE: usize = BLOCK_LEN;
+
+    #[inline(always)]
+    unsafe fn new(key: &MessageKey) -> Self {
+        let k0: L = load(&key.key);
+        let k1: L = load(&key.key[BLOCK_LEN..]);
+        let n0: L = load(&key.nonce);
+        let n1: L = load(&key.nonce[BLOCK_LEN..]);
+        let c0 = L::load(&C0);
+        let c1 = L::load(&C1);
+        let k0_n0 = k0.xor(n0);
+        let k1_n1 = k1.xor(n1);
+        let mut state = Self([k0_n0, k1_n1, c1, c0, k0.xor(c0), k1.xor(c1)]);
+        for _ in 0..4 {
+            state.update(k0);
+            state.update(k1);
+            state.update(k0_n0);
+            state.update(k1_n1);
+        }
+        state
+    }
+
+    #[inline(always)]
+    unsafe fn absorb(&mut self, input: &[u8]) {
+        self.update(load(input));
+    }
+
+    #[inline(always)]
+    unsafe fn encrypt(&mut self, in_out: &mut [u8]) {
+        let z = self.keystream();
+        let x: L = load(in_out);
+        store(x.xor(z), in_out);
+        self.update(x);
+    }
+
+    #[inline(always)]
+    unsafe fn decrypt(&mut self, in_out: &mut [u8], len: usize) {
+        let z = self.keystream();
+        let mut x = load::<L>(in_out).xor(z);
+        store(x, in_out);
+        if len < Self::RATE {
+            for b in in_out[len..].iter_mut() {
+                *b = 0;
+            }
+            x = load(in_out);
+        }
+        self.update(x);
+    }
+
+    #[inline(always)]
+    unsafe fn finalize(mut self, aad_len: usize, in_out_len: usize) -> Block {
+        let t = self.0[3].xor(L::load(&lengths_block(aad_len, in_out_len)));
+        for _ in 0..7 {
+            self.update(t);
+        }
+        let s = &self.0;
+        let tag = s[0].xor(s[1]).xor(s[2]).xor(s[3]).xor(s[4]).xor(s[5]);
+        let mut bytes = [0u8; BLOCK_LEN];
+        tag.store(&mut bytes);
+        Block::from(&bytes)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod aesni {
+    use super::{Block, Lane, MessageKey, BLOCK_LEN};
+    use core::arch::x86_64::*;
+
+    #[derive(Clone, Copy)]
+    struct AesNi(__m128i);
+
+    impl Lane for AesNi {
+        #[inline(always)]
+        unsafe fn load(bytes: &[u8; BLOCK_LEN]) -> Self {
+            AesNi(_mm_loadu_si128(bytes.as_ptr() as *const __m128i))
+        }
+
+        #[inline(always)]
+        unsafe fn store(self, bytes: &mut [u8; BLOCK_LEN]) {
+            _mm_storeu_si128(bytes.as_mut_ptr() as *mut __m128i, self.0)
+        }
+
+        #[inline(always)]
+        unsafe fn xor(self, other: Self) -> Self {
+            AesNi(_mm_xor_si128(self.0, other.0))
+        }
+
+        #[inline(always)]
+        unsafe fn and(self, other: Self) -> Self {
+            AesNi(_mm_and_si128(self.0, other.0))
+        }
+
+        // `aesenc` is exactly the AES round function.
+        #[inline(always)]
+        unsafe fn aes_round(self, round_key: Self) -> Self {
+            AesNi(_mm_aesenc_si128(self.0, round_key.0))
+        }
+    }
+
+    #[target_feature(enable = "aes,sse2")]
+    pub(super) unsafe fn seal(key: &MessageKey, aad: &[u8], in_out: &mut [u8]) -> Block {
+        super::seal::<AesNi>(key, aad, in_out)
+    }
+
+    #[target_feature(enable = "aes,sse2")]
+    pub(super) unsafe fn open(
+        key: &MessageKey,
+        aad: &[u8],
+        in_prefix_len: usize,
+        in_out: &mut [u8],
+    ) -> Block {
+        super::open::<AesNi>(key, aad, in_prefix_len, in_out)
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use super::{Block, Lane, MessageKey, BLOCK_LEN};
+    use core::arch::aarch64::*;
+
+    #[derive(Clone, Copy)]
+    struct Neon(uint8x16_t);
+
+    impl Lane for Neon {
+        #[inline(always)]
+        unsafe fn load(bytes: &[u8; BLOCK_LEN]) -> Self {
+            Neon(vld1q_u8(bytes.as_ptr()))
+        }
+
+        #[inline(always)]
+        unsafe fn store(self, bytes: &mut [u8; BLOCK_LEN]) {
+            vst1q_u8(bytes.as_mut_ptr(), self.0)
+        }
+
+        #[inline(always)]
+        unsafe fn xor(self, other: Self) -> Self {
+            Neon(veorq_u8(self.0, other.0))
+        }
+
+        #[inline(always)]
+        unsafe fn and(self, other: Self) -> Self {
+            Neon(vandq_u8(self.0, other.0))
+        }
+
+        // `aese` adds the round key first and then does `ShiftRows` and
+        // `SubBytes`, so it is given a zero key and the round key is added
+        // after `aesmc`.
+        #[inline(always)]
+        unsafe fn aes_round(self, round_key: Self) -> Self {
+            Neon(veorq_u8(
+                vaesmcq_u8(vaeseq_u8(self.0, vdupq_n_u8(0))),
+                round_key.0,
+            ))
+        }
+    }
+
+    #[target_feature(enable = "neon,aes")]
+    pub(super) unsafe fn seal(key: &MessageKey, aad: &[u8], in_out: &mut [u8]) -> Block {
+        super::seal::<Neon>(key, aad, in_out)
+    }
+
+    #[target_feature(enable = "neon,aes")]
+    pub(super) unsafe fn open(
+        key: &MessageKey,
+        aad: &[u8],
+        in_prefix_len: usize,
+        in_out: &mut [u8],
+    ) -> Block {
+        super::open::<Neon>(key, aad, in_prefix_len, in_out)
+    }
+}
+
+// The state is stored column by column, as in `inverse_cipher`.
+#[derive(Clone, Copy)]
+struct Portable([u8; BLOCK_LEN]);
+
+impl Lane for Portable {
+    #[inline(always)]
+    unsafe fn load(bytes: &[u8; BLOCK_LEN]) -> Self {
+        Portable(*bytes)
+    }
+
+    #[inline(always)]
+    unsafe fn store(self, bytes: &mut [u8; BLOCK_LEN]) {
+        *bytes = self.0;
+    }
+
+    #[inline(always)]
+    unsafe fn xor(mut self, other: Self) -> Self {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a ^= b;
+        }
+        self
+    }
+
+    #[inline(always)]
+    unsafe fn and(mut self, other: Self) -> Self {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a &= b;
+        }
+        self
+    }
+
+    unsafe fn aes_round(self, round_key: Self) -> Self {
+        // `SubBytes` and `ShiftRows`, which rotates row `r` left by `r`
+        // columns.
+        let mut s = [0u8; BLOCK_LEN];
+        for c in 0..4 {
+            for r in 0..4 {
+                s[4 * c + r] = s_box(self.0[4 * ((c + r) % 4) + r]);
+            }
+        }
+        // `MixColumns` and `AddRoundKey`.
+        let mut out = round_key.0;
+        for (column, out) in s.chunks(4).zip(out.chunks_mut(4)) {
+            for (r, out) in out.iter_mut().enumerate() {
+                let a1 = column[(r + 1) % 4];
+                *out ^=
+                    xtime(column[r]) ^ xtime(a1) ^ a1 ^ column[(r + 2) % 4] ^ column[(r + 3) % 4];
+            }
+        }
+        Portable(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The AESRound test vector of draft-irtf-cfrg-aegis-aead.
+    #[test]
+    fn test_aes_round() {
+        const IN: [u8; BLOCK_LEN] = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f,
+        ];
+        const ROUND_KEY: [u8; BLOCK_LEN] = [
+            0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d,
+            0x1e, 0x1f,
+        ];
+        const OUT: [u8; BLOCK_LEN] = [
+            0x7a, 0x7b, 0x4e, 0x56, 0x38, 0x78, 0x25, 0x46, 0xa8, 0xc0, 0x47, 0x7a, 0x3b, 0x81,
+            0x3f, 0x43,
+        ];
+        let mut out = [0u8; BLOCK_LEN];
+        unsafe {
+            Portable::load(&IN)
+                .aes_round(Portable::load(&ROUND_KEY))
+                .store(&mut out);
+        }
+        assert_eq!(out, OUT);
+    }
+
+    // Checks the portable implementation against the one the CPU uses, which
+    // `tests/aead_tests.rs` checks against the test vectors.
+    #[test]
+    fn test_portable() {
+        for &variant in &[Variant::Aegis128L, Variant::Aegis256] {
+            let message_key = || {
+                let mut key = MessageKey {
+                    variant,
+                    key: [0u8; AEGIS_256_KEY_LEN],
+                    nonce: [0u8; AEGIS_256_NONCE_LEN],
+                };
+                for (i, b) in key.key.iter_mut().chain(key.nonce.iter_mut()).enumerate() {
+                    *b = i as u8;
+                }
+                key
+            };
+            let aad = [0x5a; 45];
+            for len in 0..100 {
+                let aad = &aad[..(len % aad.len())];
+                let mut in_out = [0u8; 100];
+                for (i, b) in in_out.iter_mut().enumerate() {
+                    *b = i as u8;
+                }
+                let in_out = &mut in_out[..len];
+
+                let mut expected = [0u8; 100];
+                let expected = &mut expected[..len];
+                expected.copy_from_slice(in_out);
+                let Tag(expected_tag) = aegis_seal(
+                    &KeyInner::AegisMessage(message_key()),
+                    unused_nonce(),
+                    Aad::from(aad),
+                    expected,
+                    cpu::features(),
+                );
+
+                let tag = unsafe { seal::<Portable>(&message_key(), aad, in_out) };
+                assert_eq!(in_out, expected);
+                assert_eq!(tag.as_ref(), expected_tag.as_ref());
+
+                let tag = unsafe { open::<Portable>(&message_key(), aad, 0, in_out) };
+                assert_eq!(tag.as_ref(), expected_tag.as_ref());
+            }
+        }
+    }
+}
@@ -16,7 +16,7 @@ use super::{
     nonce::{self, Iv},
     shift, Block, Direction, BLOCK_LEN,
 };
-use crate::{bits::BitLength, c, cpu, endian::*, error, polyfill};
+use crate::{bits::BitLength, c, cpu, endian::*, error, polyfill, zeroize::Zeroize};
 
 pub(crate) struct Key {
     inner: AES_KEY,
@@ -44,6 +44,12 @@ impl Key {
         };
 
         match detect_implementation(cpu_features) {
+            #[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
+            Implementation::HWAES => unsafe {
+                aesni::set_encrypt_key(bytes, variant, &mut key);
+            },
+
+            #[cfg(not(all(feature = "intrinsics", target_arch = "x86_64")))]
             Implementation::HWAES => {
                 extern "C" {
                     fn GFp_aes_hw_set_encrypt_key(
@@ -109,6 +115,12 @@ impl Key {
         let aliasing_mut: *mut Block = &mut a;
 
         match detect_implementation(self.cpu_features) {
+            #[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
+            Implementation::HWAES => {
+                a = unsafe { aesni::encrypt_block(&self.inner, a) };
+            }
+
+            #[cfg(not(all(feature = "intrinsics", target_arch = "x86_64")))]
             Implementation::HWAES => {
                 extern "C" {
                     fn GFp_aes_hw_encrypt(a: *const Block, r: *mut Block, key: &AES_KEY);
@@ -170,6 +182,15 @@ impl Key {
         assert_eq!(blocks, polyfill::usize_from_u32(blocks_u32));
 
         match detect_implementation(self.cpu_features) {
+            #[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
+            Implementation::HWAES => {
+                unsafe {
+                    aesni::ctr32_encrypt_blocks(input, output, blocks, &self.inner, ctr);
+                }
+                ctr.increment_by_less_safe(blocks_u32);
+            }
+
+            #[cfg(not(all(feature = "intrinsics", target_arch = "x86_64")))]
             Implementation::HWAES => {
                 extern "C" {
                     fn GFp_aes_hw_ctr32_encrypt_blocks(
@@ -211,6 +232,23 @@ impl Key {
         }
     }
 
+    /// Encrypts or decrypts `in_out` in place in CTR mode, starting at `ctr`.
+    ///
+    /// The caller must ensure that the 32-bit counter doesn't wrap.
+    pub(crate) fn ctr32_encrypt_in_place(&self, mut ctr: Counter, in_out: &mut [u8]) {
+        let whole_len = in_out.len() - (in_out.len() % BLOCK_LEN);
+        // The assembly implementations don't support empty input.
+        if whole_len > 0 {
+            self.ctr32_encrypt_blocks(&mut in_out[..whole_len], Direction::Sealing, &mut ctr);
+        }
+
+        shift::shift_partial((0, &mut in_out[whole_len..]), |remainder| {
+            let mut input = Block::zero();
+            input.partial_copy_from(remainder);
+            self.encrypt_iv_xor_block(ctr.into(), input)
+        });
+    }
+
     pub fn new_mask(&self, sample: Block) -> [u8; 5] {
         let block = self.encrypt_block(sample);
 
@@ -220,7 +258,7 @@ impl Key {
         out
     }
 
-    #[cfg(target_arch = "x86_64")]
+    #[cfg(all(target_arch = "x86_64", not(feature = "intrinsics")))]
     #[must_use]
     pub fn is_aes_hw(&self) -> bool {
         match detect_implementation(self.cpu_features) {
@@ -229,13 +267,67 @@ impl Key {
         }
     }
 
-    #[cfg(target_arch = "x86_64")]
+    #[cfg(all(target_arch = "x86_64", not(feature = "intrinsics")))]
     #[must_use]
     pub(super) fn inner_less_safe(&self) -> &AES_KEY {
         &self.inner
     }
+
+    /// Identifies the layout of the key schedule, which differs between
+    /// implementations.
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    pub(super) fn schedule_layout(cpu_features: cpu::Features) -> u8 {
+        let intrinsics = if cfg!(all(feature = "intrinsics", target_arch = "x86_64")) {
+            0x08
+        } else {
+            0
+        };
+        detect_implementation(cpu_features) as u8 | intrinsics
+    }
+
+    /// Writes the key schedule to `out` and returns the number of rounds as
+    /// stored by the implementation.
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    pub(super) fn write_schedule(&self, out: &mut [u8; SCHEDULE_LEN]) -> u32 {
+        for (out, word) in out.chunks_mut(4).zip(self.inner.rd_key.iter()) {
+            out.copy_from_slice(&word.to_ne_bytes());
+        }
+        self.inner.rounds
+    }
+
+    /// Reconstructs a key from a key schedule written by `write_schedule()`
+    /// with the same `schedule_layout()`.
+    ///
+    /// Fails if `rounds` isn't what the implementation stores for `variant`.
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    pub(super) fn from_schedule(
+        rounds: u32,
+        schedule: &[u8; SCHEDULE_LEN],
+        variant: Variant,
+        cpu_features: cpu::Features,
+    ) -> Result<Self, error::Unspecified> {
+        let zero_key = [0u8; 32];
+        let zero_key = match variant {
+            Variant::AES_128 => &zero_key[..16],
+            Variant::AES_256 => &zero_key[..],
+        };
+        let mut key = Self::new(zero_key, variant, cpu_features)?;
+        if key.inner.rounds != rounds {
+            return Err(error::Unspecified);
+        }
+        for (word, bytes) in key.inner.rd_key.iter_mut().zip(schedule.chunks(4)) {
+            let mut word_bytes = [0u8; 4];
+            word_bytes.copy_from_slice(bytes);
+            *word = u32::from_ne_bytes(word_bytes);
+        }
+        Ok(key)
+    }
 }
 
+/// The length of a key schedule written by `Key::write_schedule()`.
+#[cfg(feature = "aead-aes-gcm-siv")]
+pub(super) const SCHEDULE_LEN: usize = 4 * 4 * (MAX_ROUNDS + 1);
+
 // Keep this in sync with AES_KEY in aes.h.
 #[repr(C)]
 pub(super) struct AES_KEY {
@@ -243,6 +335,24 @@ pub(super) struct AES_KEY {
     pub rounds: c::uint,
 }
 
+impl Drop for AES_KEY {
+    fn drop(&mut self) {
+        self.rd_key.zeroize();
+    }
+}
+
+#[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
+impl AES_KEY {
+    fn rd_key_bytes(&self) -> &[u8] {
+        unsafe {
+            core::slice::from_raw_parts(
+                self.rd_key.as_ptr() as *const u8,
+                self.rd_key.len() * core::mem::size_of::<u32>(),
+            )
+        }
+    }
+}
+
 // Keep this in sync with `AES_MAXNR` in aes.h.
 const MAX_ROUNDS: usize = 14;
 
@@ -290,6 +400,178 @@ fn detect_implementation(cpu_features: cpu::Features) -> Implementation {
     Implementation::Fallback
 }
 
+// An implementation of the `HWAES` functions using intrinsics instead of the
+// perlasm-generated assembly language code. The expanded key is stored in
+// `AES_KEY` the same way `GFp_aes_hw_set_encrypt_key` stores it, except that
+// `rounds` is the actual number of rounds.
+#[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
+mod aesni {
+    use super::{Counter, Variant, AES_KEY, BLOCK_LEN};
+    use crate::aead::Block;
+    use core::arch::x86_64::*;
+
+    #[target_feature(enable = "aes,sse2")]
+    pub(super) unsafe fn set_encrypt_key(bytes: &[u8], variant: Variant, key: &mut AES_KEY) {
+        let rd_key = key.rd_key.as_mut_ptr() as *mut __m128i;
+        let rounds = match variant {
+            Variant::AES_128 => {
+                let mut rk = _mm_loadu_si128(bytes.as_ptr() as *const __m128i);
+                _mm_storeu_si128(rd_key, rk);
+                for i in 1..=10 {
+                    let assist = match i {
+                        1 => _mm_aeskeygenassist_si128(rk, 0x01),
+                        2 => _mm_aeskeygenassist_si128(rk, 0x02),
+                        3 => _mm_aeskeygenassist_si128(rk, 0x04),
+                        4 => _mm_aeskeygenassist_si128(rk, 0x08),
+                        5 => _mm_aeskeygenassist_si128(rk, 0x10),
+                        6 => _mm_aeskeygenassist_si128(rk, 0x20),
+                        7 => _mm_aeskeygenassist_si128(rk, 0x40),
+                        8 => _mm_aeskeygenassist_si128(rk, 0x80),
+                        9 => _mm_aeskeygenassist_si128(rk, 0x1b),
+                        _ => _mm_aeskeygenassist_si128(rk, 0x36),
+                    };
+                    rk = expand_step(rk, _mm_shuffle_epi32(assist, 0xff));
+                    _mm_storeu_si128(rd_key.add(i), rk);
+                }
+                10
+            }
+
+            Variant::AES_256 => {
+                let mut even = _mm_loadu_si128(bytes.as_ptr() as *const __m128i);
+                let mut odd = _mm_loadu_si128(bytes[BLOCK_LEN..].as_ptr() as *const __m128i);
+                _mm_storeu_si128(rd_key, even);
+                _mm_storeu_si128(rd_key.add(1), odd);
+                for i in 1..=7 {
+                    let assist = match i {
+                        1 => _mm_aeskeygenassist_si128(odd, 0x01),
+                        2 => _mm_aeskeygenassist_si128(odd, 0x02),
+                        3 => _mm_aeskeygenassist_si128(odd, 0x04),
+                        4 => _mm_aeskeygenassist_si128(odd, 0x08),
+                        5 => _mm_aeskeygenassist_si128(odd, 0x10),
+                        6 => _mm_aeskeygenassist_si128(odd, 0x20),
+                        _ => _mm_aeskeygenassist_si128(odd, 0x40),
+                    };
+                    even = expand_step(even, _mm_shuffle_epi32(assist, 0xff));
+                    _mm_storeu_si128(rd_key.add(2 * i), even);
+                    if i < 7 {
+                        let assist = _mm_aeskeygenassist_si128(even, 0x00);
+                        odd = expand_step(odd, _mm_shuffle_epi32(assist, 0xaa));
+                        _mm_storeu_si128(rd_key.add(2 * i + 1), odd);
+                    }
+                }
+                14
+            }
+        };
+        key.rounds = rounds;
+    }
+
+    // Computes the next round key from the previous one, `prev`, and the
+    // broadcast output of `aeskeygenassist`.
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn expand_step(prev: __m128i, assist: __m128i) -> __m128i {
+        let mut rk = prev;
+        rk = _mm_xor_si128(rk, _mm_slli_si128(rk, 4));
+        rk = _mm_xor_si128(rk, _mm_slli_si128(rk, 4));
+        rk = _mm_xor_si128(rk, _mm_slli_si128(rk, 4));
+        _mm_xor_si128(rk, assist)
+    }
+
+    #[target_feature(enable = "aes,sse2")]
+    pub(super) unsafe fn encrypt_block(key: &AES_KEY, a: Block) -> Block {
+        let mut block = [encrypt_prepare(key, load(a.as_ref()))];
+        encrypt_rounds(key, &mut block);
+        let mut r = Block::zero();
+        store(r.as_mut(), block[0]);
+        r
+    }
+
+    // Like `GFp_aes_hw_ctr32_encrypt_blocks`, this doesn't update `ivec`, and
+    // `output` may be `input` or precede it. The blocks are encrypted four at
+    // a time so that the latency of each `aesenc` is hidden by the others.
+    #[target_feature(enable = "aes,sse2")]
+    pub(super) unsafe fn ctr32_encrypt_blocks(
+        input: *const u8,
+        output: *mut u8,
+        blocks: usize,
+        key: &AES_KEY,
+        ivec: &Counter,
+    ) {
+        const LANES: usize = 4;
+
+        let ivec: *const Counter = ivec;
+        let mut iv = *(ivec as *const [u8; BLOCK_LEN]);
+        let mut ctr = u32::from_be_bytes([iv[12], iv[13], iv[14], iv[15]]);
+
+        let mut i = 0;
+        while i < blocks {
+            let lanes = core::cmp::min(LANES, blocks - i);
+            let mut state = [_mm_setzero_si128(); LANES];
+            for s in state[..lanes].iter_mut() {
+                iv[12..].copy_from_slice(&ctr.to_be_bytes());
+                ctr = ctr.wrapping_add(1);
+                *s = encrypt_prepare(key, load(&iv));
+            }
+            encrypt_rounds(key, &mut state[..lanes]);
+
+            // Every input block of this group is read before any output
+            // block is written, since they may overlap.
+            let input = input.add(i * BLOCK_LEN) as *const __m128i;
+            for (j, s) in state[..lanes].iter_mut().enumerate() {
+                *s = _mm_xor_si128(*s, _mm_loadu_si128(input.add(j)));
+            }
+            let output = output.add(i * BLOCK_LEN) as *mut __m128i;
+            for (j, s) in state[..lanes].iter().enumerate() {
+                _mm_storeu_si128(output.add(j), *s);
+            }
+
+            i += lanes;
+        }
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn encrypt_prepare(key: &AES_KEY, block: __m128i) -> __m128i {
+        _mm_xor_si128(block, round_key(key, 0))
+    }
+
+    #[inline]
+    #[target_feature(enable = "aes,sse2")]
+    unsafe fn encrypt_rounds(key: &AES_KEY, blocks: &mut [__m128i]) {
+        let rounds = key.rounds as usize;
+        for round in 1..rounds {
+            let rk = round_key(key, round);
+            for b in blocks.iter_mut() {
+                *b = _mm_aesenc_si128(*b, rk);
+            }
+        }
+        let rk = round_key(key, rounds);
+        for b in blocks.iter_mut() {
+            *b = _mm_aesenclast_si128(*b, rk);
+        }
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn round_key(key: &AES_KEY, round: usize) -> __m128i {
+        load(&key.rd_key_bytes()[(round * BLOCK_LEN)..][..BLOCK_LEN])
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn load(bytes: &[u8]) -> __m128i {
+        debug_assert_eq!(bytes.len(), BLOCK_LEN);
+        _mm_loadu_si128(bytes.as_ptr() as *const __m128i)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn store(bytes: &mut [u8], value: __m128i) {
+        debug_assert_eq!(bytes.len(), BLOCK_LEN);
+        _mm_storeu_si128(bytes.as_mut_ptr() as *mut __m128i, value)
+    }
+}
+
 #[must_use]
 #[repr(transparent)]
 pub struct ZeroMeansSuccess(c::int);
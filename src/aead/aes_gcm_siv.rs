@@ -14,24 +14,40 @@
 
 use super::{
     aes::{
-        self, Variant,
+        Variant,
         Variant::{AES_128, AES_256},
     },
+    gcm::{self, PolyValContext},
     gcm_siv::{
-        self, Auth_Key, Encryption_Key, GcmSivAsmContext, GcmSivContext,
+        self, Auth_Key, Encryption_Key, GcmSivAsmContext, GcmSivContext, Htable,
         Implementation::{AVX_AESNI, FALLBACK},
         Out_Tag, AES_ASM_KEY,
     },
-    Aad, Block, Nonce, Tag, BLOCK_LEN,
+    Aad, Block, Nonce, Tag, BLOCK_LEN, NONCE_LEN,
 };
-use crate::{aead, aead::TAG_LEN, cpu, error};
-use std::convert::TryInto;
-use std::mem::MaybeUninit;
+use crate::{aead, aead::TAG_LEN, c, cpu, error, zeroize::Zeroize};
+use core::convert::TryInto;
+
+#[cfg(feature = "aead_parallel")]
+use std::{sync::Mutex, vec::Vec};
+
+#[cfg(target_arch = "x86_64")]
+use super::gcm_siv::{vaes, Implementation::VAES_VPCLMULQDQ};
+
+#[cfg(target_arch = "aarch64")]
+use super::gcm_siv::{neon, Implementation::NEON_PMULL};
 
 /// AES-GCM-SIV as described in https://tools.ietf.org/html/draft-irtf-cfrg-gcmsiv-03.
 ///
 /// There are two implementations in this file(asm and non-asm), the ASM version is for x86_64
 /// architecture wchich supports AES acceleration and AVX instruction sets.
+/// On AArch64 CPUs with the AES and PMULL instructions, a NEON intrinsics
+/// version is used instead of the non-asm one.
+/// On x86_64 CPUs that also have the VAES and VPCLMULQDQ extensions, longer
+/// messages are encrypted and authenticated with intrinsics-based kernels
+/// that operate on two blocks per 256-bit register.
+/// With the `intrinsics` feature, the functions of the ASM version are
+/// implemented with intrinsics instead of assembly language.
 ///
 /// The keys are 128/256 bits long and the nonces are 96 bits long.
 ///
@@ -78,14 +94,6 @@ fn init(
     )?))
 }
 
-fn get_encryption_key_size(variant: Variant) -> usize {
-    let enc_key_size = match variant {
-        AES_128 => 16,
-        AES_256 => 32,
-    };
-    enc_key_size
-}
-
 fn seal_fallback(
     key: &aead::KeyInner,
     nonce: Nonce,
@@ -98,141 +106,137 @@ fn seal_fallback(
         key_type => panic!("Unexpected key type {:?}", key_type),
     };
 
-    let gcm_siv_ctx = GcmSivContext::new();
-    let mut auth_key = [0u8; TAG_LEN];
-    let mut enc_key = [0u8; 32];
-    gcm_siv_ctx.kdf(
-        &mut auth_key,
-        &mut enc_key,
-        key.variant.clone(),
-        &nonce,
-        &key,
-    );
-
-    let (first, second) = auth_key.split_at(TAG_LEN / 2);
-    let auth_key = Block::from_u64_native(
-        u64::from_ne_bytes(first.try_into().unwrap()),
-        u64::from_ne_bytes(second.try_into().unwrap()),
-    );
-    let enc_key = aes::Key::new(
-        &enc_key[0..get_encryption_key_size(key.variant.clone())],
-        key.variant.clone(),
-        cpu::features(),
-    ).unwrap();
+    let gcm_siv_ctx = GcmSivContext::new(key, &nonce, cpu_features);
 
-    let tag = gcm_siv_ctx.gcm_siv_polyval(in_out, aad, &nonce, &auth_key, cpu_features);
-    let tag = enc_key.encrypt_block(tag);
+    let tag = gcm_siv_ctx.gcm_siv_polyval(in_out, aad);
+    let tag = gcm_siv_ctx.encrypt_block(tag);
 
-    gcm_siv_ctx.gcm_siv_crypt(in_out, 0, &tag, &enc_key);
+    gcm_siv_ctx.gcm_siv_crypt(in_out, 0, &tag);
 
     return Tag(tag);
 }
 
-fn seal_aes_avxni(key: &aead::KeyInner, nonce: Nonce, aad: &[u8], in_out: &mut [u8]) -> Tag {
+/// The minimum length, in bytes, of a message that is sealed with the
+/// eight-way `enc_msg_x8` kernels instead of the four-way `enc_msg_x4`
+/// kernels, which have less setup.
+///
+/// The crossover depends on the microarchitecture. On CPUs with AVX2 it was
+/// measured with the `internal_benches` benchmarks below, which should be
+/// rerun when the kernels change; the original threshold is kept for older
+/// CPUs.
+fn seal_wide_kernels_min_len(_cpu_features: cpu::Features) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if cpu::intel::AVX2.available(_cpu_features) {
+            return 1024;
+        }
+    }
+    128
+}
+
+/// The minimum length, in bytes, of a message that is opened with the
+/// six-way `dec` kernels. Shorter messages are decrypted with the
+/// `enc_msg_x4` kernels and authenticated a block at a time, which avoids
+/// computing the table of powers of the authentication key that `dec` needs.
+/// `dec` only uses more than the first power for six or more blocks.
+const OPEN_WIDE_KERNELS_MIN_LEN: usize = 6 * BLOCK_LEN;
+
+fn seal_aes_avxni(
+    key: &aead::KeyInner,
+    nonce: Nonce,
+    aad: &[u8],
+    in_out: &mut [u8],
+    wide_kernels_min_len: usize,
+) -> Tag {
     let asm_key = match key {
         aead::KeyInner::AesGcmSiv(key) => key,
         key_type => panic!("Unexpected key type {:?}", key_type),
     };
 
-    let aes_asm_key = asm_key.aes_asm_key.as_ref().expect("Missing AES ASM KEY");
+    let (auth_key, enc_key) = derive_keys(asm_key, &nonce);
 
-    let (mut auth_key, mut enc_key) =  (MaybeUninit::<Auth_Key>::uninit(), MaybeUninit::<Encryption_Key>::uninit());
-    let gcm_siv_asm_ctx = GcmSivAsmContext::new();
-    gcm_siv_asm_ctx.kdf(&nonce, &asm_key, &mut auth_key, &mut enc_key);
-
-    let auth_key = unsafe { auth_key.assume_init() };
-    let enc_key = unsafe { enc_key.assume_init() };
-
-    let mut out_tag = gcm_siv_asm_ctx.gcm_siv_asm_polyval(nonce.as_ref(), aad, in_out, &auth_key);
+    let mut out_tag =
+        GcmSivAsmContext::new().gcm_siv_asm_polyval(nonce.as_ref(), aad, in_out, &auth_key);
     let whole_in_out_len = in_out.len() - (in_out.len() % BLOCK_LEN);
+    let use_wide_kernels = in_out.len() >= wide_kernels_min_len;
+
+    // The key schedule for this message's encryption key. This must not
+    // overwrite the key's own key schedule, which is shared by every use of
+    // the key.
+    let expanded_key =
+        AES_ASM_KEY::expand_from_and_encrypt(&enc_key, asm_key.variant, &mut out_tag);
 
     match asm_key.variant {
         AES_128 => {
             extern "C" {
-                fn aes128gcmsiv_aes_ks_enc_x1(
-                    input: *const Out_Tag,
-                    output: *mut Out_Tag,
-                    expanded_key: *const AES_ASM_KEY,
-                    enc_key: *const Encryption_Key,
-                );
                 fn aes128gcmsiv_enc_msg_x4(
                     input: *const u8,
                     output: *mut u8,
                     tag: *const Out_Tag,
                     expanded_key: *const AES_ASM_KEY,
-                    input_len: libc::c_uint,
+                    input_len: c::uint,
                 );
                 fn aes128gcmsiv_enc_msg_x8(
                     input: *const u8,
                     output: *mut u8,
                     tag: *const Out_Tag,
                     expanded_key: *const AES_ASM_KEY,
-                    input_len: libc::c_uint,
+                    input_len: c::uint,
                 );
             }
             unsafe {
-                aes128gcmsiv_aes_ks_enc_x1(&out_tag, &mut out_tag, aes_asm_key, &enc_key);
-
-                if in_out.len() < 128 {
-                    aes128gcmsiv_enc_msg_x4(
+                if use_wide_kernels {
+                    aes128gcmsiv_enc_msg_x8(
                         in_out.as_ptr(),
                         in_out.as_mut_ptr(),
                         &out_tag,
-                        aes_asm_key,
-                        whole_in_out_len as libc::c_uint,
+                        &expanded_key,
+                        whole_in_out_len as c::uint,
                     );
                 } else {
-                    aes128gcmsiv_enc_msg_x8(
+                    aes128gcmsiv_enc_msg_x4(
                         in_out.as_ptr(),
                         in_out.as_mut_ptr(),
                         &out_tag,
-                        aes_asm_key,
-                        whole_in_out_len as libc::c_uint,
+                        &expanded_key,
+                        whole_in_out_len as c::uint,
                     );
                 }
             }
         }
         AES_256 => {
             extern "C" {
-                fn aes256gcmsiv_aes_ks_enc_x1(
-                    input: *const Out_Tag,
-                    output: *mut Out_Tag,
-                    expanded_key: *const AES_ASM_KEY,
-                    enc_key: *const Encryption_Key,
-                );
                 fn aes256gcmsiv_enc_msg_x4(
                     input: *const u8,
                     output: *mut u8,
                     tag: *const Out_Tag,
                     expanded_key: *const AES_ASM_KEY,
-                    input_len: libc::c_uint,
+                    input_len: c::uint,
                 );
                 fn aes256gcmsiv_enc_msg_x8(
                     input: *const u8,
                     output: *mut u8,
                     tag: *const Out_Tag,
                     expanded_key: *const AES_ASM_KEY,
-                    input_len: libc::c_uint,
+                    input_len: c::uint,
                 );
             }
             unsafe {
-                aes256gcmsiv_aes_ks_enc_x1(&out_tag, &mut out_tag, aes_asm_key, &enc_key);
-
-                if in_out.len() < 128 {
-                    aes256gcmsiv_enc_msg_x4(
+                if use_wide_kernels {
+                    aes256gcmsiv_enc_msg_x8(
                         in_out.as_ptr(),
                         in_out.as_mut_ptr(),
                         &out_tag,
-                        aes_asm_key,
-                        whole_in_out_len as libc::c_uint,
+                        &expanded_key,
+                        whole_in_out_len as c::uint,
                     );
                 } else {
-                    aes256gcmsiv_enc_msg_x8(
+                    aes256gcmsiv_enc_msg_x4(
                         in_out.as_ptr(),
                         in_out.as_mut_ptr(),
                         &out_tag,
-                        aes_asm_key,
-                        whole_in_out_len as libc::c_uint,
+                        &expanded_key,
+                        whole_in_out_len as c::uint,
                     );
                 }
             }
@@ -242,7 +246,7 @@ fn seal_aes_avxni(key: &aead::KeyInner, nonce: Nonce, aad: &[u8], in_out: &mut [
         crypt_last_block(
             &out_tag.tag,
             in_out,
-            &aes_asm_key,
+            &expanded_key,
             &asm_key.variant,
             in_out.len(),
             0,
@@ -265,9 +269,53 @@ fn aes_gcm_siv_seal(
             return seal_fallback(key, nonce, aad, in_out, cpu_features);
         }
         AVX_AESNI => {
-            return seal_aes_avxni(key, nonce, aad, in_out);
+            return seal_avx_aesni_any_len(key, nonce, aad, in_out, cpu_features);
+        }
+        #[cfg(target_arch = "x86_64")]
+        VAES_VPCLMULQDQ => {
+            if in_out.len() < VAES_MIN_LEN {
+                return seal_avx_aesni_any_len(key, nonce, aad, in_out, cpu_features);
+            }
+            return seal_vaes(key, nonce, aad, in_out);
+        }
+        #[cfg(target_arch = "aarch64")]
+        NEON_PMULL => {
+            return seal_neon(key, nonce, aad, in_out);
+        }
+    }
+}
+
+fn seal_avx_aesni_any_len(
+    key: &aead::KeyInner,
+    nonce: Nonce,
+    aad: &[u8],
+    in_out: &mut [u8],
+    cpu_features: cpu::Features,
+) -> Tag {
+    if !fits_avx_aesni(aad, in_out.len()) {
+        return seal_fallback(key, nonce, aad, in_out, cpu_features);
+    }
+    if let aead::KeyInner::AesGcmSiv(key) = key {
+        if aad.len() <= SHORT_MAX_LEN && in_out.len() <= SHORT_MAX_LEN {
+            return seal_short_avx_aesni(key, nonce, aad, in_out);
         }
     }
+    seal_aes_avxni(
+        key,
+        nonce,
+        aad,
+        in_out,
+        seal_wide_kernels_min_len(cpu_features),
+    )
+}
+
+// Whether the AVX/AES-NI assembly language kernels, which take lengths as
+// `c::uint`, can process `aad` and `in_out_len` bytes of input. Longer
+// inputs, which AES-GCM-SIV allows up to 2**36 bytes of, are processed by
+// the portable implementation instead.
+fn fits_avx_aesni(aad: &[u8], in_out_len: usize) -> bool {
+    const MAX_LEN: usize = u32::max_value() as usize;
+    aad.len() <= MAX_LEN && in_out_len <= MAX_LEN
 }
 
 const CALCULATED_TAG_LEN: usize = 16 * 8;
@@ -279,9 +327,7 @@ pub struct CalculatedTag {
 
 impl Drop for CalculatedTag {
     fn drop(&mut self) {
-        for byte in self.tag.iter_mut() {
-            *byte = 0;
-        }
+        self.tag.zeroize();
     }
 }
 
@@ -290,11 +336,26 @@ pub struct HTable {
     htable: [u8; 16 * 6],
 }
 
+impl HTable {
+    // The first six powers of the POLYVAL key `auth_key`, for the `dec`
+    // kernels.
+    fn new(auth_key: &Auth_Key) -> Self {
+        extern "C" {
+            fn aesgcmsiv_htable6_init(htable: *mut HTable, auth_key: *const Auth_Key);
+        }
+        let mut htable = HTable {
+            htable: [0u8; 16 * 6],
+        };
+        unsafe {
+            aesgcmsiv_htable6_init(&mut htable, auth_key);
+        }
+        htable
+    }
+}
+
 impl Drop for HTable {
     fn drop(&mut self) {
-        for byte in self.htable.iter_mut() {
-            *byte = 0;
-        }
+        self.htable.zeroize();
     }
 }
 
@@ -303,7 +364,7 @@ pub struct Counter {
     counter: [u8; BLOCK_LEN],
 }
 
-fn crypt_last_block(
+pub(super) fn crypt_last_block(
     tag: &[u8],
     in_out: &mut [u8],
     expanded_key: &AES_ASM_KEY,
@@ -318,7 +379,23 @@ fn crypt_last_block(
     let last_val = u32::from_le_bytes(counter[0..4].try_into().unwrap()).wrapping_add((in_out_len / BLOCK_LEN) as u32);
     counter[0..4].copy_from_slice(&last_val.to_le_bytes());
 
-    let mut counter = Counter { counter };
+    let key_stream = encrypt_block(&counter, expanded_key, variant);
+
+    let last_bytes_offset = (in_out_len - (in_out_len % BLOCK_LEN)) + in_prefix_len;
+    let last_bytes_len = in_out_len % BLOCK_LEN;
+
+    for i in last_bytes_offset..(last_bytes_offset + last_bytes_len) {
+        // Since in_prefix_len is ignored we have to do in_out[i-in_prefix_len] to store bytes at the right offsets
+        in_out[i - in_prefix_len] = in_out[i] ^ key_stream[i - last_bytes_offset];
+    }
+}
+
+fn encrypt_block(
+    block: &[u8; BLOCK_LEN],
+    expanded_key: &AES_ASM_KEY,
+    variant: &Variant,
+) -> [u8; BLOCK_LEN] {
+    let mut block = Counter { counter: *block };
 
     match variant {
         AES_128 => {
@@ -330,7 +407,7 @@ fn crypt_last_block(
                 );
             }
             unsafe {
-                aes128gcmsiv_ecb_enc_block(&counter, &mut counter, expanded_key);
+                aes128gcmsiv_ecb_enc_block(&block, &mut block, expanded_key);
             }
         }
         AES_256 => {
@@ -342,17 +419,109 @@ fn crypt_last_block(
                 );
             }
             unsafe {
-                aes256gcmsiv_ecb_enc_block(&counter, &mut counter, expanded_key);
+                aes256gcmsiv_ecb_enc_block(&block, &mut block, expanded_key);
             }
         }
     }
 
-    let last_bytes_offset = (in_out_len - (in_out_len % BLOCK_LEN)) + in_prefix_len;
-    let last_bytes_len = in_out_len % BLOCK_LEN;
+    block.counter
+}
 
-    for i in last_bytes_offset..(last_bytes_offset + last_bytes_len) {
-        // Since in_prefix_len is ignored we have to do in_out[i-in_prefix_len] to store bytes at the right offsets
-        in_out[i - in_prefix_len] = in_out[i] ^ counter.counter[i - last_bytes_offset];
+// Messages and additional data of at most this many bytes are sealed and
+// opened by `seal_short_avx_aesni` and `open_short_avx_aesni`.
+const SHORT_MAX_LEN: usize = BLOCK_LEN;
+
+// Seals a short message by going straight to single-block operations: the
+// whole POLYVAL input is hashed in one pass and the message is encrypted
+// with a single block of key stream.
+fn seal_short_avx_aesni(key: &gcm_siv::Key, nonce: Nonce, aad: &[u8], in_out: &mut [u8]) -> Tag {
+    debug_assert!(aad.len() <= SHORT_MAX_LEN && in_out.len() <= SHORT_MAX_LEN);
+
+    let (auth_key, enc_key) = derive_keys(key, &nonce);
+    let mut out_tag = polyval_short(&auth_key, &nonce, aad, in_out);
+
+    // Expand the encryption key while encrypting the tag.
+    let expanded_key = AES_ASM_KEY::expand_from_and_encrypt(&enc_key, key.variant, &mut out_tag);
+
+    crypt_short(&out_tag.tag, in_out, 0, &expanded_key, &key.variant);
+
+    Tag(Block::from(&out_tag.tag))
+}
+
+// The counterpart of `seal_short_avx_aesni`.
+fn open_short_avx_aesni(
+    key: &gcm_siv::Key,
+    nonce: Nonce,
+    aad: &[u8],
+    in_prefix_len: usize,
+    in_out: &mut [u8],
+) -> Tag {
+    let in_out_len = in_out.len() - TAG_LEN - in_prefix_len;
+    debug_assert!(aad.len() <= SHORT_MAX_LEN && in_out_len <= SHORT_MAX_LEN);
+
+    let (auth_key, enc_key) = derive_keys(key, &nonce);
+    let expanded_key = AES_ASM_KEY::expand_from(&enc_key, key.variant);
+
+    let mut tag = [0u8; TAG_LEN];
+    tag.copy_from_slice(&in_out[(in_prefix_len + in_out_len)..]);
+    crypt_short(
+        &tag,
+        &mut in_out[..(in_prefix_len + in_out_len)],
+        in_prefix_len,
+        &expanded_key,
+        &key.variant,
+    );
+
+    let out_tag = polyval_short(&auth_key, &nonce, aad, &in_out[..in_out_len]);
+    Tag(Block::from(&encrypt_block(&out_tag.tag, &expanded_key, &key.variant)))
+}
+
+fn derive_keys(key: &gcm_siv::Key, nonce: &Nonce) -> (Auth_Key, Encryption_Key) {
+    GcmSivAsmContext::new().kdf(nonce, key)
+}
+
+// Computes the tag's POLYVAL input for a short message, in one pass over
+// the padded additional data, the padded message, and the length block.
+fn polyval_short(auth_key: &Auth_Key, nonce: &Nonce, aad: &[u8], msg: &[u8]) -> Out_Tag {
+    let mut blocks = [0u8; 3 * BLOCK_LEN];
+    let mut num_blocks = 0;
+    for input in &[aad, msg] {
+        if !input.is_empty() {
+            blocks[(num_blocks * BLOCK_LEN)..][..input.len()].copy_from_slice(input);
+            num_blocks += 1;
+        }
+    }
+    let length_block = &mut blocks[(num_blocks * BLOCK_LEN)..][..BLOCK_LEN];
+    length_block[..8].copy_from_slice(&(aad.len() as u64 * 8).to_le_bytes());
+    length_block[8..].copy_from_slice(&(msg.len() as u64 * 8).to_le_bytes());
+    num_blocks += 1;
+
+    let mut out_tag = Out_Tag { tag: [0u8; TAG_LEN] };
+    gcm_siv::polyval_asm(&mut out_tag, auth_key, None, &blocks[..(num_blocks * BLOCK_LEN)]);
+
+    let nonce = nonce.as_ref();
+    for i in 0..nonce.len() {
+        out_tag.tag[i] ^= nonce[i];
+    }
+    out_tag.tag[15] &= 0x7f;
+
+    out_tag
+}
+
+// Encrypts or decrypts a message of at most one block, which is
+// `in_out[in_prefix_len..]`, into `in_out`.
+fn crypt_short(
+    tag: &[u8; TAG_LEN],
+    in_out: &mut [u8],
+    in_prefix_len: usize,
+    expanded_key: &AES_ASM_KEY,
+    variant: &Variant,
+) {
+    let mut counter = *tag;
+    counter[15] |= 0x80;
+    let key_stream = encrypt_block(&counter, expanded_key, variant);
+    for i in 0..(in_out.len() - in_prefix_len) {
+        in_out[i] = in_out[in_prefix_len + i] ^ key_stream[i];
     }
 }
 
@@ -383,44 +552,85 @@ fn open_fallback(
         u64::from_ne_bytes(tag_second_block),
     );
 
-    let gcm_siv_ctx = GcmSivContext::new();
-    let mut auth_key = [0u8; TAG_LEN];
-    let mut enc_key = [0u8; BLOCK_LEN * 2];
-    gcm_siv_ctx.kdf(
-        &mut auth_key,
-        &mut enc_key,
-        key.variant.clone(),
-        &nonce,
-        &key,
-    );
-    let (first, second) = auth_key.split_at(TAG_LEN / 2);
-    let auth_key = Block::from_u64_native(
-        u64::from_ne_bytes(first.try_into().unwrap()),
-        u64::from_ne_bytes(second.try_into().unwrap()),
-    );
+    let gcm_siv_ctx = GcmSivContext::new(key, &nonce, cpu_features);
 
-    let enc_key = aes::Key::new(
-        &enc_key[0..get_encryption_key_size(key.variant.clone())],
-        key.variant.clone(),
-        cpu::features(),
-    ).unwrap();
+    gcm_siv_ctx.gcm_siv_crypt(&mut in_out[0..in_out_len - TAG_LEN], in_prefix_len, &tag);
 
-    gcm_siv_ctx.gcm_siv_crypt(
-        &mut in_out[0..in_out_len - TAG_LEN],
-        in_prefix_len,
-        &tag,
-        &enc_key,
-    );
+    let tag = gcm_siv_ctx.gcm_siv_polyval(&in_out[0..in_out_len - TAG_LEN - in_prefix_len], aad);
 
-    let tag = gcm_siv_ctx.gcm_siv_polyval(
-        &mut in_out[0..in_out_len - TAG_LEN - in_prefix_len],
-        aad,
-        &nonce,
-        &auth_key,
-        cpu_features,
-    );
+    return Tag(gcm_siv_ctx.encrypt_block(tag));
+}
+
+/// Whether `open_batch` should be used to open records that have distinct
+/// nonces. It interleaves the `PolyValContext` updates of `open_fallback`;
+/// the other implementations have their own POLYVAL kernels.
+pub(super) fn batches_polyval(cpu_features: cpu::Features) -> bool {
+    match gcm_siv::detect_implementation(cpu_features) {
+        FALLBACK => true,
+        _ => false,
+    }
+}
+
+/// Opens up to `gcm::BATCH_LEN` records like `open_fallback` and returns
+/// their calculated tags. The POLYVAL updates for the records' whole
+/// plaintext blocks are done in lockstep.
+pub(super) fn open_batch<A: AsRef<[u8]>>(
+    key: &aead::KeyInner,
+    records: &mut [(Nonce, Aad<A>, &mut [u8])],
+    cpu_features: cpu::Features,
+) -> [Block; gcm::BATCH_LEN] {
+    let key = match key {
+        aead::KeyInner::AesGcmSiv(key) => key,
+        _ => unreachable!(),
+    };
+    let len = records.len();
+    assert!(len <= gcm::BATCH_LEN);
 
-    return Tag(enc_key.encrypt_block(tag));
+    let new_ctx = |i: usize| {
+        records
+            .get(i)
+            .map(|(nonce, _, _)| GcmSivContext::new(key, nonce, cpu_features))
+    };
+    let gcm_siv_ctxs = [new_ctx(0), new_ctx(1), new_ctx(2), new_ctx(3)];
+    let unkeyed = || PolyValContext::new(&Block::zero(), cpu_features);
+    let mut polyval_ctxs = [unkeyed(), unkeyed(), unkeyed(), unkeyed()];
+    let polyval_ctxs = &mut polyval_ctxs[..len];
+
+    let mut whole_plaintexts: [&[u8]; gcm::BATCH_LEN] = [&[]; gcm::BATCH_LEN];
+    for (((gcm_siv_ctx, polyval_ctx), whole_plaintext), (_, Aad(aad), in_out)) in gcm_siv_ctxs
+        .iter()
+        .flatten()
+        .zip(polyval_ctxs.iter_mut())
+        .zip(whole_plaintexts.iter_mut())
+        .zip(records.iter_mut())
+    {
+        let (ciphertext, received_tag) = in_out.split_at_mut(in_out.len() - TAG_LEN);
+        let received_tag: &[u8; TAG_LEN] = (&received_tag[..]).try_into().unwrap();
+        gcm_siv_ctx.gcm_siv_crypt(ciphertext, 0, &Block::from(received_tag));
+
+        *polyval_ctx = gcm_siv_ctx.new_polyval();
+        GcmSivContext::update_blocks(aad.as_ref(), polyval_ctx);
+        let whole_len = ciphertext.len() - (ciphertext.len() % BLOCK_LEN);
+        *whole_plaintext = &ciphertext[..whole_len];
+    }
+    PolyValContext::update_blocks_batch(polyval_ctxs, &whole_plaintexts[..len]);
+
+    let mut tags = [Block::zero(); gcm::BATCH_LEN];
+    for (((gcm_siv_ctx, polyval_ctx), tag), (_, Aad(aad), in_out)) in gcm_siv_ctxs
+        .iter()
+        .flatten()
+        .zip(polyval_ctxs.iter_mut())
+        .zip(tags.iter_mut())
+        .zip(records.iter())
+    {
+        let plaintext = &in_out[..(in_out.len() - TAG_LEN)];
+        let whole_len = plaintext.len() - (plaintext.len() % BLOCK_LEN);
+        GcmSivContext::update_blocks(&plaintext[whole_len..], polyval_ctx);
+        let calculated_tag =
+            gcm_siv_ctx.finish_polyval(polyval_ctx, aad.as_ref().len(), plaintext.len());
+        *tag = gcm_siv_ctx.encrypt_block(calculated_tag);
+    }
+    tags
 }
 
 fn open_avx_aesni(
@@ -429,45 +639,15 @@ fn open_avx_aesni(
     aad: &[u8],
     in_prefix_len: usize,
     in_out: &mut [u8],
+    wide_kernels_min_len: usize,
 ) -> Tag {
     let asm_key = match key {
         aead::KeyInner::AesGcmSiv(key) => key,
         key_type => panic!("Expected AesGcmSiv key Found: {:?}", key_type),
     };
 
-    let (mut auth_key, mut enc_key) = (MaybeUninit::<Auth_Key>::uninit(), MaybeUninit::<Encryption_Key>::uninit());
-    let gcm_siv_asm_ctx = GcmSivAsmContext::new();
-    gcm_siv_asm_ctx.kdf(&nonce, &asm_key, &mut auth_key, &mut enc_key);
-    let auth_key = unsafe { auth_key.assume_init() };
-    let enc_key = unsafe { enc_key.assume_init() };
-
-    let mut expanded_key: AES_ASM_KEY;
-    expanded_key = { unsafe { MaybeUninit::uninit().assume_init() } };
-
-    match &asm_key.variant {
-        AES_128 => {
-            extern "C" {
-                fn aes128gcmsiv_aes_ks(
-                    enc_key: *const Encryption_Key,
-                    expanded_key: *mut AES_ASM_KEY,
-                );
-            }
-            unsafe {
-                aes128gcmsiv_aes_ks(&enc_key, &mut expanded_key);
-            }
-        }
-        AES_256 => {
-            extern "C" {
-                fn aes256gcmsiv_aes_ks(
-                    enc_key: *const Encryption_Key,
-                    expanded_key: *mut AES_ASM_KEY,
-                );
-            }
-            unsafe {
-                aes256gcmsiv_aes_ks(&enc_key, &mut expanded_key);
-            }
-        }
-    }
+    let (auth_key, enc_key) = derive_keys(asm_key, &nonce);
+    let expanded_key = AES_ASM_KEY::expand_from(&enc_key, asm_key.variant);
 
     // calculated_tag is 16*8 bytes, rather than 16 bytes, because
     // aes[128|256]gcmsiv_dec uses the extra as scratch space.
@@ -480,7 +660,7 @@ fn open_avx_aesni(
             calculated_tag: *mut CalculatedTag,
             record_auth_key: *const Auth_Key,
             ad: *const u8,
-            ad_blocks: libc::c_uint,
+            ad_blocks: c::uint,
         );
     }
     unsafe {
@@ -488,7 +668,7 @@ fn open_avx_aesni(
             &mut calculated_tag,
             &auth_key,
             aad.as_ptr(),
-            (aad.len() / BLOCK_LEN) as libc::c_uint,
+            (aad.len() / BLOCK_LEN) as c::uint,
         );
     }
 
@@ -502,7 +682,7 @@ fn open_avx_aesni(
                 calculated_tag: *mut CalculatedTag,
                 auth_key: *const Auth_Key,
                 scratch: *const u8,
-                scratch_blocks: libc::c_uint,
+                scratch_blocks: c::uint,
             );
         }
         unsafe {
@@ -510,59 +690,122 @@ fn open_avx_aesni(
         }
     }
 
-    let mut htable = MaybeUninit::<HTable>::uninit();
-    extern "C" {
-        fn aesgcmsiv_htable6_init(htable: *mut HTable, auth_key: *const Auth_Key);
-    }
-    unsafe {
-        aesgcmsiv_htable6_init(htable.as_mut_ptr(), &auth_key);
-    }
-
-    let htable = unsafe { htable.assume_init() };
-
     let in_out_len = in_out.len() - TAG_LEN - in_prefix_len;
-    match &asm_key.variant {
-        AES_128 => {
-            extern "C" {
-                fn aes128gcmsiv_dec(
-                    input: *const u8,
-                    output: *mut u8,
-                    calculated_tag: *mut CalculatedTag,
-                    htable: *const HTable,
-                    expanded_key: *const AES_ASM_KEY,
-                    plaintext_len: libc::c_uint,
-                );
+    let whole_in_out_len = in_out_len - (in_out_len % BLOCK_LEN);
+    if in_out_len >= wide_kernels_min_len {
+        let htable = HTable::new(&auth_key);
+
+        match &asm_key.variant {
+            AES_128 => {
+                extern "C" {
+                    fn aes128gcmsiv_dec(
+                        input: *const u8,
+                        output: *mut u8,
+                        calculated_tag: *mut CalculatedTag,
+                        htable: *const HTable,
+                        expanded_key: *const AES_ASM_KEY,
+                        plaintext_len: c::uint,
+                    );
+                }
+                unsafe {
+                    aes128gcmsiv_dec(
+                        in_out[in_prefix_len..].as_ptr(),
+                        in_out.as_mut_ptr(),
+                        &mut calculated_tag,
+                        &htable,
+                        &expanded_key,
+                        in_out_len as c::uint,
+                    );
+                }
             }
-            unsafe {
-                aes128gcmsiv_dec(
-                    in_out[in_prefix_len..].as_ptr(),
-                    in_out.as_mut_ptr(),
-                    &mut calculated_tag,
-                    &htable,
-                    &expanded_key,
-                    in_out_len as libc::c_uint,
-                );
+            AES_256 => {
+                extern "C" {
+                    fn aes256gcmsiv_dec(
+                        input: *const u8,
+                        output: *mut u8,
+                        calculated_tag: *mut CalculatedTag,
+                        htable: *const HTable,
+                        expanded_key: *const AES_ASM_KEY,
+                        plaintext_len: c::uint,
+                    );
+                }
+                unsafe {
+                    aes256gcmsiv_dec(
+                        in_out[in_prefix_len..].as_ptr(),
+                        in_out.as_mut_ptr(),
+                        &mut calculated_tag,
+                        &htable,
+                        &expanded_key,
+                        in_out_len as c::uint,
+                    );
+                }
             }
         }
-        AES_256 => {
+    } else {
+        // Decrypt with the same kernels used for sealing short messages and
+        // then authenticate the plaintext a block at a time, which avoids
+        // computing `htable`.
+        let mut tag = Out_Tag { tag: [0u8; TAG_LEN] };
+        tag.tag
+            .copy_from_slice(&in_out[(in_prefix_len + in_out_len)..][..TAG_LEN]);
+        match &asm_key.variant {
+            AES_128 => {
+                extern "C" {
+                    fn aes128gcmsiv_enc_msg_x4(
+                        input: *const u8,
+                        output: *mut u8,
+                        tag: *const Out_Tag,
+                        expanded_key: *const AES_ASM_KEY,
+                        input_len: c::uint,
+                    );
+                }
+                unsafe {
+                    aes128gcmsiv_enc_msg_x4(
+                        in_out[in_prefix_len..].as_ptr(),
+                        in_out.as_mut_ptr(),
+                        &tag,
+                        &expanded_key,
+                        whole_in_out_len as c::uint,
+                    );
+                }
+            }
+            AES_256 => {
+                extern "C" {
+                    fn aes256gcmsiv_enc_msg_x4(
+                        input: *const u8,
+                        output: *mut u8,
+                        tag: *const Out_Tag,
+                        expanded_key: *const AES_ASM_KEY,
+                        input_len: c::uint,
+                    );
+                }
+                unsafe {
+                    aes256gcmsiv_enc_msg_x4(
+                        in_out[in_prefix_len..].as_ptr(),
+                        in_out.as_mut_ptr(),
+                        &tag,
+                        &expanded_key,
+                        whole_in_out_len as c::uint,
+                    );
+                }
+            }
+        }
+
+        if whole_in_out_len > 0 {
             extern "C" {
-                fn aes256gcmsiv_dec(
-                    input: *const u8,
-                    output: *mut u8,
+                fn aesgcmsiv_polyval_horner(
                     calculated_tag: *mut CalculatedTag,
-                    htable: *const HTable,
-                    expanded_key: *const AES_ASM_KEY,
-                    plaintext_len: libc::c_uint,
+                    auth_key: *const Auth_Key,
+                    input: *const u8,
+                    input_blocks: c::uint,
                 );
             }
             unsafe {
-                aes256gcmsiv_dec(
-                    in_out[in_prefix_len..].as_ptr(),
-                    in_out.as_mut_ptr(),
+                aesgcmsiv_polyval_horner(
                     &mut calculated_tag,
-                    &htable,
-                    &expanded_key,
-                    in_out_len as libc::c_uint,
+                    &auth_key,
+                    in_out.as_ptr(),
+                    (whole_in_out_len / BLOCK_LEN) as c::uint,
                 );
             }
         }
@@ -589,7 +832,7 @@ fn open_avx_aesni(
                 calculated_tag: *mut CalculatedTag,
                 auth_key: *const Auth_Key,
                 scratch: *const u8,
-                scratch_blocks: libc::c_uint,
+                scratch_blocks: c::uint,
             );
         }
         unsafe {
@@ -607,7 +850,7 @@ fn open_avx_aesni(
                 calculated_tag: *mut CalculatedTag,
                 record_auth_key: *const Auth_Key,
                 len_block: *const u64,
-                len_block_len: libc::c_uint,
+                len_block_len: c::uint,
             );
         }
         unsafe {
@@ -667,22 +910,851 @@ fn aes_gcm_siv_open(
             return open_fallback(key, nonce, aad, in_prefix_len, in_out, cpu_features);
         }
         AVX_AESNI => {
-            return open_avx_aesni(key, nonce, aad, in_prefix_len, in_out);
+            return open_avx_aesni_any_len(key, nonce, aad, in_prefix_len, in_out, cpu_features);
+        }
+        #[cfg(target_arch = "x86_64")]
+        VAES_VPCLMULQDQ => {
+            if in_out.len() - TAG_LEN - in_prefix_len < VAES_MIN_LEN {
+                return open_avx_aesni_any_len(
+                    key,
+                    nonce,
+                    aad,
+                    in_prefix_len,
+                    in_out,
+                    cpu_features,
+                );
+            }
+            return open_vaes(key, nonce, aad, in_prefix_len, in_out);
+        }
+        #[cfg(target_arch = "aarch64")]
+        NEON_PMULL => {
+            return open_neon(key, nonce, aad, in_prefix_len, in_out);
         }
     }
 }
 
-pub type Key = gcm_siv::Key;
+fn open_avx_aesni_any_len(
+    key: &aead::KeyInner,
+    nonce: Nonce,
+    aad: &[u8],
+    in_prefix_len: usize,
+    in_out: &mut [u8],
+    cpu_features: cpu::Features,
+) -> Tag {
+    if !fits_avx_aesni(aad, in_out.len()) {
+        return open_fallback(key, nonce, aad, in_prefix_len, in_out, cpu_features);
+    }
+    if let aead::KeyInner::AesGcmSiv(key) = key {
+        let in_out_len = in_out.len() - TAG_LEN - in_prefix_len;
+        if aad.len() <= SHORT_MAX_LEN && in_out_len <= SHORT_MAX_LEN {
+            return open_short_avx_aesni(key, nonce, aad, in_prefix_len, in_out);
+        }
+    }
+    open_avx_aesni(
+        key,
+        nonce,
+        aad,
+        in_prefix_len,
+        in_out,
+        OPEN_WIDE_KERNELS_MIN_LEN,
+    )
+}
 
-#[cfg(test)]
-mod tests {
-    use crate::aead::aes::Variant;
-    use crate::aead::aes_gcm_siv::{aes_gcm_siv_open, aes_gcm_siv_seal, init};
-    use crate::aead::{Aad, Nonce};
-    use crate::cpu;
+// Encrypts `plaintext` into `out`, which is the same length, and returns the
+// tag, for `aead::seal_to()`. The portable implementation reads and writes
+// the two buffers directly; the others seal a copy of `plaintext` in place.
+pub(super) fn seal_to(
+    key: &aead::KeyInner,
+    nonce: Nonce,
+    aad: Aad<&[u8]>,
+    plaintext: &[u8],
+    out: &mut [u8],
+    cpu_features: cpu::Features,
+) -> Tag {
+    match gcm_siv::detect_implementation(cpu_features) {
+        FALLBACK => {
+            let key = match key {
+                aead::KeyInner::AesGcmSiv(key) => key,
+                key_type => panic!("Unexpected key type {:?}", key_type),
+            };
+            let Aad(aad) = aad;
+            let gcm_siv_ctx = GcmSivContext::new(key, &nonce, cpu_features);
+
+            let tag = gcm_siv_ctx.gcm_siv_polyval(plaintext, aad);
+            let tag = gcm_siv_ctx.encrypt_block(tag);
+
+            gcm_siv_ctx.gcm_siv_crypt_to(plaintext, out, &tag);
+            Tag(tag)
+        }
+        _ => {
+            out.copy_from_slice(plaintext);
+            aes_gcm_siv_seal(key, nonce, aad, out, cpu_features)
+        }
+    }
+}
 
-    #[test]
-    fn test_data_alignments() {
+// Decrypts `ciphertext` into `out`, which is the same length, and returns
+// the calculated tag, for `aead::open_to()`. As with `seal_to`, only the
+// portable implementation reads and writes the two buffers directly; the
+// others open a copy of `ciphertext` in place, like `aead::multipart` does
+// for detached tags, since their `open` functions read the tag from the end
+// of their input.
+pub(super) fn open_to(
+    key: &aead::Key,
+    nonce: Nonce,
+    aad: Aad<&[u8]>,
+    ciphertext: &[u8],
+    received_tag: &[u8; TAG_LEN],
+    out: &mut [u8],
+) -> Tag {
+    match gcm_siv::detect_implementation(key.cpu_features) {
+        FALLBACK => {
+            let inner: &aead::KeyInner = &key.inner;
+            let inner = match inner {
+                aead::KeyInner::AesGcmSiv(inner) => inner,
+                key_type => panic!("Expected AesGcmSiv key Found: {:?}", key_type),
+            };
+            let Aad(aad) = aad;
+            let gcm_siv_ctx = GcmSivContext::new(inner, &nonce, key.cpu_features);
+
+            gcm_siv_ctx.gcm_siv_crypt_to(ciphertext, out, &Block::from(received_tag));
+
+            let tag = gcm_siv_ctx.gcm_siv_polyval(out, aad);
+            Tag(gcm_siv_ctx.encrypt_block(tag))
+        }
+        _ => {
+            out.copy_from_slice(ciphertext);
+            super::multipart::open_detached(key, nonce, aad, received_tag, out)
+        }
+    }
+}
+
+/// The minimum length, in bytes, of a message that is sealed or opened with
+/// the `VAES_VPCLMULQDQ` kernels, which process eight blocks at a time.
+/// Shorter messages are handled as with `AVX_AESNI`.
+#[cfg(target_arch = "x86_64")]
+const VAES_MIN_LEN: usize = 8 * BLOCK_LEN;
+
+#[cfg(target_arch = "x86_64")]
+fn seal_vaes(key: &aead::KeyInner, nonce: Nonce, aad: &[u8], in_out: &mut [u8]) -> Tag {
+    let key = match key {
+        aead::KeyInner::AesGcmSiv(key) => key,
+        key_type => panic!("Expected AesGcmSiv key Found: {:?}", key_type),
+    };
+
+    let (auth_key, enc_key) = derive_keys(key, &nonce);
+    let expanded_key = AES_ASM_KEY::expand_from(&enc_key, key.variant);
+    let tag = vaes_tag(&auth_key, &expanded_key, key.variant, &nonce, aad, in_out);
+    unsafe {
+        vaes::ctr32_encrypt(&expanded_key, key.variant, &tag, in_out, 0);
+    }
+    Tag(Block::from(&tag))
+}
+
+#[cfg(target_arch = "x86_64")]
+fn open_vaes(
+    key: &aead::KeyInner,
+    nonce: Nonce,
+    aad: &[u8],
+    in_prefix_len: usize,
+    in_out: &mut [u8],
+) -> Tag {
+    let key = match key {
+        aead::KeyInner::AesGcmSiv(key) => key,
+        key_type => panic!("Expected AesGcmSiv key Found: {:?}", key_type),
+    };
+
+    let in_out_len = in_out.len() - TAG_LEN - in_prefix_len;
+    let mut received_tag = [0u8; TAG_LEN];
+    received_tag.copy_from_slice(&in_out[(in_prefix_len + in_out_len)..]);
+
+    let (auth_key, enc_key) = derive_keys(key, &nonce);
+    let expanded_key = AES_ASM_KEY::expand_from(&enc_key, key.variant);
+    unsafe {
+        vaes::ctr32_encrypt(
+            &expanded_key,
+            key.variant,
+            &received_tag,
+            &mut in_out[..(in_prefix_len + in_out_len)],
+            in_prefix_len,
+        );
+    }
+    let tag = vaes_tag(
+        &auth_key,
+        &expanded_key,
+        key.variant,
+        &nonce,
+        aad,
+        &in_out[..in_out_len],
+    );
+    Tag(Block::from(&tag))
+}
+
+// Computes the tag for `msg` with the `VAES_VPCLMULQDQ` implementation.
+#[cfg(target_arch = "x86_64")]
+fn vaes_tag(
+    auth_key: &Auth_Key,
+    expanded_key: &AES_ASM_KEY,
+    variant: Variant,
+    nonce: &Nonce,
+    aad: &[u8],
+    msg: &[u8],
+) -> [u8; TAG_LEN] {
+    let mut length_block = [0u8; BLOCK_LEN];
+    length_block[..8].copy_from_slice(&(aad.len() as u64 * 8).to_le_bytes());
+    length_block[8..].copy_from_slice(&(msg.len() as u64 * 8).to_le_bytes());
+
+    let mut tag = [0u8; TAG_LEN];
+    unsafe {
+        let powers = vaes::Powers::new(auth_key);
+        vaes::polyval(&mut tag, &powers, aad);
+        vaes::polyval(&mut tag, &powers, msg);
+        vaes::polyval(&mut tag, &powers, &length_block);
+    }
+    for (t, n) in tag.iter_mut().zip(nonce.as_ref().iter()) {
+        *t ^= n;
+    }
+    tag[15] &= 0x7f;
+    encrypt_block(&tag, expanded_key, &variant)
+}
+
+#[cfg(target_arch = "aarch64")]
+fn seal_neon(key: &aead::KeyInner, nonce: Nonce, aad: &[u8], in_out: &mut [u8]) -> Tag {
+    let key = match key {
+        aead::KeyInner::AesGcmSiv(key) => key,
+        key_type => panic!("Expected AesGcmSiv key Found: {:?}", key_type),
+    };
+
+    let (auth_key, expanded_key) = derive_neon_keys(key, &nonce);
+    let tag = neon_tag(&auth_key, &expanded_key, key.variant, &nonce, aad, in_out);
+    unsafe {
+        neon::ctr32_encrypt(&expanded_key, key.variant, &tag, in_out, 0);
+    }
+    Tag(Block::from(&tag))
+}
+
+#[cfg(target_arch = "aarch64")]
+fn open_neon(
+    key: &aead::KeyInner,
+    nonce: Nonce,
+    aad: &[u8],
+    in_prefix_len: usize,
+    in_out: &mut [u8],
+) -> Tag {
+    let key = match key {
+        aead::KeyInner::AesGcmSiv(key) => key,
+        key_type => panic!("Expected AesGcmSiv key Found: {:?}", key_type),
+    };
+
+    let in_out_len = in_out.len() - TAG_LEN - in_prefix_len;
+    let mut received_tag = [0u8; TAG_LEN];
+    received_tag.copy_from_slice(&in_out[(in_prefix_len + in_out_len)..]);
+
+    let (auth_key, expanded_key) = derive_neon_keys(key, &nonce);
+    unsafe {
+        neon::ctr32_encrypt(
+            &expanded_key,
+            key.variant,
+            &received_tag,
+            &mut in_out[..(in_prefix_len + in_out_len)],
+            in_prefix_len,
+        );
+    }
+    let tag = neon_tag(
+        &auth_key,
+        &expanded_key,
+        key.variant,
+        &nonce,
+        aad,
+        &in_out[..in_out_len],
+    );
+    Tag(Block::from(&tag))
+}
+
+// Derives the per-nonce POLYVAL key and expanded AES key for the
+// `NEON_PMULL` implementation. Each block of key material contributes its
+// first half.
+#[cfg(target_arch = "aarch64")]
+fn derive_neon_keys(key: &gcm_siv::Key, nonce: &Nonce) -> ([u8; BLOCK_LEN], AES_ASM_KEY) {
+    let master_key = key.aes_asm_key.as_ref().expect("Missing AES ASM KEY");
+    let enc_key_len = match key.variant {
+        AES_128 => 16,
+        AES_256 => 32,
+    };
+
+    let mut key_material = [0u8; BLOCK_LEN + 32];
+    let mut counter = [0u8; BLOCK_LEN];
+    counter[4..].copy_from_slice(nonce.as_ref());
+    for (i, half) in key_material[..(BLOCK_LEN + enc_key_len)]
+        .chunks_mut(BLOCK_LEN / 2)
+        .enumerate()
+    {
+        counter[..4].copy_from_slice(&(i as u32).to_le_bytes());
+        let block = unsafe { neon::encrypt_block(master_key, key.variant, &counter) };
+        half.copy_from_slice(&block[..(BLOCK_LEN / 2)]);
+    }
+
+    let mut auth_key = [0u8; BLOCK_LEN];
+    auth_key.copy_from_slice(&key_material[..BLOCK_LEN]);
+    let expanded_key =
+        unsafe { neon::set_encrypt_key(&key_material[BLOCK_LEN..][..enc_key_len], key.variant) };
+    for byte in key_material.iter_mut() {
+        *byte = 0;
+    }
+    (auth_key, expanded_key)
+}
+
+// Computes the tag for `msg` with the `NEON_PMULL` implementation.
+#[cfg(target_arch = "aarch64")]
+fn neon_tag(
+    auth_key: &[u8; BLOCK_LEN],
+    expanded_key: &AES_ASM_KEY,
+    variant: Variant,
+    nonce: &Nonce,
+    aad: &[u8],
+    msg: &[u8],
+) -> [u8; TAG_LEN] {
+    let mut length_block = [0u8; BLOCK_LEN];
+    length_block[..8].copy_from_slice(&(aad.len() as u64 * 8).to_le_bytes());
+    length_block[8..].copy_from_slice(&(msg.len() as u64 * 8).to_le_bytes());
+
+    let mut tag = [0u8; TAG_LEN];
+    unsafe {
+        neon::polyval(&mut tag, auth_key, aad);
+        neon::polyval(&mut tag, auth_key, msg);
+        neon::polyval(&mut tag, auth_key, &length_block);
+    }
+    for (t, n) in tag.iter_mut().zip(nonce.as_ref().iter()) {
+        *t ^= n;
+    }
+    tag[15] &= 0x7f;
+    unsafe { neon::encrypt_block(expanded_key, variant, &tag) }
+}
+
+/// The per-nonce state of a streaming AES-GCM-SIV operation: the derived
+/// keys and the POLYVAL accumulator.
+pub(super) struct Stream {
+    nonce: [u8; NONCE_LEN],
+    inner: StreamInner,
+}
+
+#[allow(variant_size_differences)]
+enum StreamInner {
+    Fallback {
+        polyval: PolyValContext,
+        ctx: GcmSivContext,
+    },
+    AvxAesni {
+        auth_key: Auth_Key,
+        htable: Option<Htable>,
+        out_tag: Out_Tag,
+        expanded_key: AES_ASM_KEY,
+        variant: Variant,
+    },
+    #[cfg(target_arch = "aarch64")]
+    Neon {
+        auth_key: [u8; BLOCK_LEN],
+        polyval: [u8; BLOCK_LEN],
+        expanded_key: AES_ASM_KEY,
+        variant: Variant,
+    },
+}
+
+impl StreamInner {
+    fn new_avx_aesni(key: &gcm_siv::Key, nonce: &Nonce) -> Self {
+        let (auth_key, enc_key) = derive_keys(key, nonce);
+        #[cfg(not(feature = "small_stack"))]
+        let htable = Some(Htable::new(&auth_key));
+        #[cfg(feature = "small_stack")]
+        let htable = None;
+        StreamInner::AvxAesni {
+            htable,
+            out_tag: Out_Tag { tag: [0u8; TAG_LEN] },
+            expanded_key: AES_ASM_KEY::expand_from(&enc_key, key.variant),
+            variant: key.variant,
+            auth_key,
+        }
+    }
+}
+
+impl Stream {
+    pub(super) fn new(key: &aead::KeyInner, nonce: Nonce, cpu_features: cpu::Features) -> Self {
+        let key = match key {
+            aead::KeyInner::AesGcmSiv(key) => key,
+            key_type => panic!("Expected AesGcmSiv key Found: {:?}", key_type),
+        };
+        let inner = match gcm_siv::detect_implementation(cpu_features) {
+            FALLBACK => {
+                let ctx = GcmSivContext::new(key, &nonce, cpu_features);
+                StreamInner::Fallback {
+                    polyval: ctx.new_polyval(),
+                    ctx,
+                }
+            }
+            AVX_AESNI => StreamInner::new_avx_aesni(key, &nonce),
+            // Streams are processed piecemeal, so they don't benefit from the
+            // wider kernels.
+            #[cfg(target_arch = "x86_64")]
+            VAES_VPCLMULQDQ => StreamInner::new_avx_aesni(key, &nonce),
+            #[cfg(target_arch = "aarch64")]
+            NEON_PMULL => {
+                let (auth_key, expanded_key) = derive_neon_keys(key, &nonce);
+                StreamInner::Neon {
+                    auth_key,
+                    polyval: [0u8; BLOCK_LEN],
+                    expanded_key,
+                    variant: key.variant,
+                }
+            }
+        };
+        Self {
+            nonce: *nonce.as_ref(),
+            inner,
+        }
+    }
+
+    /// Updates POLYVAL with `input`. Only the last input of the additional
+    /// data and the last input of the message may end with a partial block,
+    /// which is padded with zeros.
+    pub(super) fn update(&mut self, input: &[u8]) {
+        match &mut self.inner {
+            StreamInner::Fallback { polyval, .. } => GcmSivContext::update_blocks(input, polyval),
+            StreamInner::AvxAesni {
+                auth_key,
+                htable,
+                out_tag,
+                ..
+            } => gcm_siv::polyval_asm(out_tag, auth_key, htable.as_ref(), input),
+            #[cfg(target_arch = "aarch64")]
+            StreamInner::Neon {
+                auth_key, polyval, ..
+            } => unsafe { neon::polyval(polyval, auth_key, input) },
+        }
+    }
+
+    /// Starts over with the same derived keys, for another message with the
+    /// same nonce.
+    pub(super) fn reset(&mut self) {
+        match &mut self.inner {
+            StreamInner::Fallback { polyval, .. } => polyval.reset(),
+            StreamInner::AvxAesni { out_tag, .. } => out_tag.tag = [0u8; TAG_LEN],
+            #[cfg(target_arch = "aarch64")]
+            StreamInner::Neon { polyval, .. } => *polyval = [0u8; BLOCK_LEN],
+        }
+    }
+
+    /// Finishes POLYVAL and returns the tag, given the lengths of the
+    /// additional data and the message.
+    pub(super) fn tag(&mut self, aad_len: u64, in_out_len: u64) -> [u8; TAG_LEN] {
+        self.update(&length_block(aad_len, in_out_len));
+
+        let polyval = match &mut self.inner {
+            StreamInner::Fallback { polyval, .. } => *polyval.pre_finish().as_ref(),
+            StreamInner::AvxAesni { out_tag, .. } => out_tag.tag,
+            #[cfg(target_arch = "aarch64")]
+            StreamInner::Neon { polyval, .. } => *polyval,
+        };
+        self.tag_from_polyval(polyval)
+    }
+
+    /// Returns the tag for the finished POLYVAL accumulator `polyval`.
+    fn tag_from_polyval(&self, mut polyval: [u8; BLOCK_LEN]) -> [u8; TAG_LEN] {
+        for (t, n) in polyval.iter_mut().zip(self.nonce.iter()) {
+            *t ^= n;
+        }
+        polyval[15] &= 0x7f;
+        self.encrypt_block(&polyval)
+    }
+
+    /// The POLYVAL key, H.
+    #[cfg(feature = "aead_parallel")]
+    fn auth_key(&self) -> [u8; BLOCK_LEN] {
+        match &self.inner {
+            StreamInner::Fallback { ctx, .. } => *ctx.auth_key().as_ref(),
+            StreamInner::AvxAesni { auth_key, .. } => {
+                let mut r = [0u8; BLOCK_LEN];
+                for (out, word) in r.chunks_mut(8).zip(auth_key.key.iter()) {
+                    out.copy_from_slice(&word.to_ne_bytes());
+                }
+                r
+            }
+            #[cfg(target_arch = "aarch64")]
+            StreamInner::Neon { auth_key, .. } => *auth_key,
+        }
+    }
+
+    /// Returns POLYVAL of `input` on its own, padded with zeros to a whole
+    /// number of blocks, without updating the accumulator.
+    #[cfg(feature = "aead_parallel")]
+    fn polyval(&self, input: &[u8]) -> [u8; BLOCK_LEN] {
+        match &self.inner {
+            StreamInner::Fallback { ctx, .. } => {
+                let mut polyval = ctx.new_polyval();
+                GcmSivContext::update_blocks(input, &mut polyval);
+                *polyval.pre_finish().as_ref()
+            }
+            StreamInner::AvxAesni {
+                auth_key, htable, ..
+            } => {
+                let mut out_tag = Out_Tag {
+                    tag: [0u8; TAG_LEN],
+                };
+                gcm_siv::polyval_asm(&mut out_tag, auth_key, htable.as_ref(), input);
+                out_tag.tag
+            }
+            #[cfg(target_arch = "aarch64")]
+            StreamInner::Neon { auth_key, .. } => {
+                let mut polyval = [0u8; BLOCK_LEN];
+                unsafe { neon::polyval(&mut polyval, auth_key, input) };
+                polyval
+            }
+        }
+    }
+
+    /// Encrypts or decrypts `in_out` with the key stream for `tag`, starting
+    /// with the block at `index`.
+    #[cfg(feature = "aead_parallel")]
+    fn crypt(&self, tag: &[u8; TAG_LEN], index: u32, in_out: &mut [u8]) {
+        let mut tag = *tag;
+        let ctr = u32::from_le_bytes(tag[0..4].try_into().unwrap()).wrapping_add(index);
+        tag[0..4].copy_from_slice(&ctr.to_le_bytes());
+        match &self.inner {
+            StreamInner::Fallback { ctx, .. } => ctx.gcm_siv_crypt(in_out, 0, &Block::from(&tag)),
+            StreamInner::AvxAesni {
+                expanded_key,
+                variant,
+                ..
+            } => ctr32_encrypt_avx_aesni(expanded_key, *variant, &tag, in_out),
+            #[cfg(target_arch = "aarch64")]
+            StreamInner::Neon {
+                expanded_key,
+                variant,
+                ..
+            } => unsafe { neon::ctr32_encrypt(expanded_key, *variant, &tag, in_out, 0) },
+        }
+    }
+
+    /// Returns the block of key stream at `index` for `tag`.
+    pub(super) fn key_stream_block(&self, tag: &[u8; TAG_LEN], index: u32) -> [u8; BLOCK_LEN] {
+        let mut counter = *tag;
+        counter[15] |= 0x80;
+        let ctr = u32::from_le_bytes(counter[0..4].try_into().unwrap()).wrapping_add(index);
+        counter[0..4].copy_from_slice(&ctr.to_le_bytes());
+        self.encrypt_block(&counter)
+    }
+
+    fn encrypt_block(&self, block: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        match &self.inner {
+            StreamInner::Fallback { ctx, .. } => *ctx.encrypt_block(Block::from(block)).as_ref(),
+            StreamInner::AvxAesni {
+                expanded_key,
+                variant,
+                ..
+            } => encrypt_block(block, expanded_key, variant),
+            #[cfg(target_arch = "aarch64")]
+            StreamInner::Neon {
+                expanded_key,
+                variant,
+                ..
+            } => unsafe { neon::encrypt_block(expanded_key, *variant, block) },
+        }
+    }
+}
+
+// The last block of POLYVAL's input, which holds the lengths, in bits, of
+// the additional data and of the message.
+fn length_block(aad_len: u64, in_out_len: u64) -> [u8; BLOCK_LEN] {
+    let mut length_block = [0u8; BLOCK_LEN];
+    length_block[..8].copy_from_slice(&(aad_len * 8).to_le_bytes());
+    length_block[8..].copy_from_slice(&(in_out_len * 8).to_le_bytes());
+    length_block
+}
+
+// Encrypts or decrypts `in_out` with the `enc_msg_x8` kernels, with the key
+// stream for `tag`.
+#[cfg(feature = "aead_parallel")]
+fn ctr32_encrypt_avx_aesni(
+    expanded_key: &AES_ASM_KEY,
+    variant: Variant,
+    tag: &[u8; TAG_LEN],
+    in_out: &mut [u8],
+) {
+    extern "C" {
+        fn aes128gcmsiv_enc_msg_x8(
+            input: *const u8,
+            output: *mut u8,
+            tag: *const Out_Tag,
+            expanded_key: *const AES_ASM_KEY,
+            input_len: c::uint,
+        );
+        fn aes256gcmsiv_enc_msg_x8(
+            input: *const u8,
+            output: *mut u8,
+            tag: *const Out_Tag,
+            expanded_key: *const AES_ASM_KEY,
+            input_len: c::uint,
+        );
+    }
+
+    // The kernels take the length as a `c::uint`, so longer inputs are
+    // processed in chunks.
+    const MAX_CHUNK_LEN: usize = (u32::max_value() as usize) / BLOCK_LEN * BLOCK_LEN;
+
+    let whole_len = in_out.len() - (in_out.len() % BLOCK_LEN);
+    let mut out_tag = Out_Tag { tag: *tag };
+    for chunk in in_out[..whole_len].chunks_mut(MAX_CHUNK_LEN) {
+        unsafe {
+            match variant {
+                AES_128 => aes128gcmsiv_enc_msg_x8(
+                    chunk.as_ptr(),
+                    chunk.as_mut_ptr(),
+                    &out_tag,
+                    expanded_key,
+                    chunk.len() as c::uint,
+                ),
+                AES_256 => aes256gcmsiv_enc_msg_x8(
+                    chunk.as_ptr(),
+                    chunk.as_mut_ptr(),
+                    &out_tag,
+                    expanded_key,
+                    chunk.len() as c::uint,
+                ),
+            }
+        }
+        let ctr = u32::from_le_bytes(out_tag.tag[0..4].try_into().unwrap())
+            .wrapping_add((chunk.len() / BLOCK_LEN) as u32);
+        out_tag.tag[0..4].copy_from_slice(&ctr.to_le_bytes());
+    }
+    if in_out.len() % BLOCK_LEN != 0 {
+        crypt_last_block(tag, in_out, expanded_key, &variant, in_out.len(), 0);
+    }
+}
+
+/// The minimum length, in bytes, of each thread's part of a message that is
+/// sealed or opened by `seal_parallel` and `open_parallel`. Starting a thread
+/// costs about as much as encrypting and authenticating tens of kilobytes,
+/// so shorter messages are processed on the calling thread alone.
+#[cfg(feature = "aead_parallel")]
+const PARALLEL_MIN_CHUNK_LEN: usize = 1 << 20;
+
+/// Seals `in_out` like `aes_gcm_siv_seal`, but on several threads: each
+/// thread computes POLYVAL of its part of the message, the partial results
+/// are combined into the tag, and then each thread encrypts its part of the
+/// message with the key stream starting at the part's first block.
+#[cfg(feature = "aead_parallel")]
+pub(super) fn seal_parallel(
+    key: &aead::KeyInner,
+    nonce: Nonce,
+    aad: Aad<&[u8]>,
+    in_out: &mut [u8],
+    cpu_features: cpu::Features,
+) -> Tag {
+    let chunk_len = match parallel_chunk_len(in_out.len()) {
+        Some(chunk_len) => chunk_len,
+        None => return aes_gcm_siv_seal(key, nonce, aad, in_out, cpu_features),
+    };
+    let Aad(aad) = aad;
+    let stream = Stream::new(key, nonce, cpu_features);
+
+    let polyvals = map_parallel(in_out.chunks(chunk_len).collect(), |chunk| {
+        stream.polyval(chunk)
+    });
+    let polyval = combine_polyvals(
+        &stream,
+        aad,
+        &polyvals,
+        chunk_len,
+        in_out.len(),
+        cpu_features,
+    );
+    let tag = stream.tag_from_polyval(polyval);
+
+    let _: Vec<()> = map_parallel(
+        in_out.chunks_mut(chunk_len).enumerate().collect(),
+        |(i, chunk)| stream.crypt(&tag, chunk_index(i, chunk_len), chunk),
+    );
+    Tag(Block::from(&tag))
+}
+
+/// Opens `in_out` like `aes_gcm_siv_open`, but on several threads: each
+/// thread decrypts its part of the message and computes POLYVAL of the
+/// plaintext, and the partial results are combined into the tag.
+#[cfg(feature = "aead_parallel")]
+pub(super) fn open_parallel(
+    key: &aead::KeyInner,
+    nonce: Nonce,
+    aad: Aad<&[u8]>,
+    in_prefix_len: usize,
+    in_out: &mut [u8],
+    cpu_features: cpu::Features,
+) -> Tag {
+    let in_out_len = in_out.len() - TAG_LEN - in_prefix_len;
+    let chunk_len = match parallel_chunk_len(in_out_len) {
+        Some(chunk_len) => chunk_len,
+        None => return aes_gcm_siv_open(key, nonce, aad, in_prefix_len, in_out, cpu_features),
+    };
+    let Aad(aad) = aad;
+    let stream = Stream::new(key, nonce, cpu_features);
+
+    let mut received_tag = [0u8; TAG_LEN];
+    received_tag.copy_from_slice(&in_out[(in_prefix_len + in_out_len)..]);
+
+    // Each thread decrypts its part in place, so the ciphertext is first
+    // moved to where the plaintext goes.
+    if in_prefix_len > 0 {
+        in_out.copy_within(in_prefix_len..(in_prefix_len + in_out_len), 0);
+    }
+    let polyvals = map_parallel(
+        in_out[..in_out_len]
+            .chunks_mut(chunk_len)
+            .enumerate()
+            .collect(),
+        |(i, chunk)| {
+            stream.crypt(&received_tag, chunk_index(i, chunk_len), chunk);
+            stream.polyval(chunk)
+        },
+    );
+    let polyval = combine_polyvals(&stream, aad, &polyvals, chunk_len, in_out_len, cpu_features);
+    Tag(Block::from(&stream.tag_from_polyval(polyval)))
+}
+
+// The length of each thread's part of a message of `in_out_len` bytes,
+// which is a whole number of blocks, or `None` if the message should be
+// processed on the calling thread alone.
+#[cfg(feature = "aead_parallel")]
+fn parallel_chunk_len(in_out_len: usize) -> Option<usize> {
+    let threads = std::thread::available_parallelism()
+        .map(core::num::NonZeroUsize::get)
+        .unwrap_or(1);
+    let threads = core::cmp::min(threads, in_out_len / PARALLEL_MIN_CHUNK_LEN);
+    if threads < 2 {
+        return None;
+    }
+    let chunk_blocks = (in_out_len + (threads * BLOCK_LEN) - 1) / (threads * BLOCK_LEN);
+    Some(chunk_blocks * BLOCK_LEN)
+}
+
+// The index of the first block of the `i`th part of the message. The
+// counter is 32 bits and wraps, so only the low 32 bits matter.
+#[cfg(feature = "aead_parallel")]
+fn chunk_index(i: usize, chunk_len: usize) -> u32 {
+    (i * (chunk_len / BLOCK_LEN)) as u32
+}
+
+// Combines POLYVAL of each part of the message, `polyvals`, with POLYVAL of
+// `aad` and of the length block, into POLYVAL of the whole input. POLYVAL
+// of X_1, ..., X_n is the sum of X_i • H**(n - i + 1), so the accumulator
+// for the input up to a part of n blocks is multiplied by H**n before the
+// part's POLYVAL is added to it.
+#[cfg(feature = "aead_parallel")]
+fn combine_polyvals(
+    stream: &Stream,
+    aad: &[u8],
+    polyvals: &[[u8; BLOCK_LEN]],
+    chunk_len: usize,
+    in_out_len: usize,
+    cpu_features: cpu::Features,
+) -> [u8; BLOCK_LEN] {
+    let h = stream.auth_key();
+    let chunk_power = polyval_pow(&h, (chunk_len / BLOCK_LEN) as u64, cpu_features);
+
+    let mut acc = stream.polyval(aad);
+    for (i, polyval) in polyvals.iter().enumerate() {
+        let len = core::cmp::min(chunk_len, in_out_len - (i * chunk_len));
+        let power = if len == chunk_len {
+            chunk_power
+        } else {
+            polyval_pow(&h, ((len + BLOCK_LEN - 1) / BLOCK_LEN) as u64, cpu_features)
+        };
+        acc = polyval_mul(&acc, &power, cpu_features);
+        for (a, p) in acc.iter_mut().zip(polyval.iter()) {
+            *a ^= p;
+        }
+    }
+
+    let length_block = length_block(aad.len() as u64, in_out_len as u64);
+    for (a, l) in acc.iter_mut().zip(length_block.iter()) {
+        *a ^= l;
+    }
+    polyval_mul(&acc, &h, cpu_features)
+}
+
+// a • b, the product of `a` and `b` in POLYVAL's field.
+#[cfg(feature = "aead_parallel")]
+fn polyval_mul(
+    a: &[u8; BLOCK_LEN],
+    b: &[u8; BLOCK_LEN],
+    cpu_features: cpu::Features,
+) -> [u8; BLOCK_LEN] {
+    // POLYVAL(b, a) = a • b.
+    let mut polyval = PolyValContext::new(&Block::from(b), cpu_features);
+    polyval.update_blocks(a);
+    *polyval.pre_finish().as_ref()
+}
+
+// h • h • ... • h, with `n` factors. `n` must be at least one.
+#[cfg(feature = "aead_parallel")]
+fn polyval_pow(h: &[u8; BLOCK_LEN], n: u64, cpu_features: cpu::Features) -> [u8; BLOCK_LEN] {
+    debug_assert!(n >= 1);
+    let mut r = *h;
+    for bit in (0..(63 - n.leading_zeros())).rev() {
+        r = polyval_mul(&r, &r, cpu_features);
+        if (n >> bit) & 1 == 1 {
+            r = polyval_mul(&r, h, cpu_features);
+        }
+    }
+    r
+}
+
+// Returns `f` applied to each of `inputs`, using a thread for each input
+// except the first, which the calling thread handles. If a thread can't be
+// spawned then the calling thread handles its input too. A panic on any of
+// the threads is propagated.
+#[cfg(feature = "aead_parallel")]
+fn map_parallel<T: Send, R: Send>(inputs: Vec<T>, f: impl Fn(T) -> R + Sync) -> Vec<R> {
+    // Each input is taken out of its slot by whichever thread handles it.
+    let slots: Vec<_> = inputs
+        .into_iter()
+        .map(|input| Mutex::new(Some(input)))
+        .collect();
+    let take = |i: usize| slots[i].lock().unwrap().take().unwrap();
+    let (f, take) = (&f, &take);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (1..slots.len())
+            .map(|i| {
+                std::thread::Builder::new()
+                    .spawn_scoped(scope, move || f(take(i)))
+                    .ok()
+            })
+            .collect();
+        let mut results = Vec::with_capacity(slots.len());
+        if !slots.is_empty() {
+            results.push(f(take(0)));
+        }
+        for (i, handle) in (1..).zip(handles) {
+            results.push(match handle {
+                Some(handle) => handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e)),
+                None => f(take(i)),
+            });
+        }
+        results
+    })
+}
+
+pub type Key = gcm_siv::Key;
+
+#[cfg(test)]
+mod tests {
+    use crate::aead::aes::Variant;
+    use crate::aead::aes_gcm_siv::{aes_gcm_siv_open, aes_gcm_siv_seal, init};
+    use crate::aead::{Aad, Nonce};
+    use crate::cpu;
+    use std::string::String;
+
+    #[test]
+    fn test_data_alignments() {
         // KEY: ee8e1ed9ff2540ae8f2ba9f50bc2f27c
         // NONCE: 752abad3e0afb5f434dc4310
         // IN: "Hello world"
@@ -740,4 +1812,265 @@ mod tests {
         assert_eq!(result_plain_text.as_bytes(), &in_out[0..11]);
     }
 
+
+    // The short message path must agree with the general one.
+    #[test]
+    fn test_short_messages() {
+        use crate::aead::{
+            aes_gcm_siv::{
+                open_avx_aesni, open_short_avx_aesni, seal_aes_avxni, seal_short_avx_aesni,
+            },
+            gcm_siv, KeyInner, TAG_LEN,
+        };
+
+        let cpu_features = cpu::features();
+        match gcm_siv::detect_implementation(cpu_features) {
+            gcm_siv::Implementation::AVX_AESNI => (),
+            #[cfg(target_arch = "x86_64")]
+            gcm_siv::Implementation::VAES_VPCLMULQDQ => (),
+            _ => {
+                return;
+            }
+        }
+
+        for &(variant, key_len) in &[(Variant::AES_128, 16), (Variant::AES_256, 32)] {
+            let key = init(&[3; 32][..key_len], variant, cpu_features).unwrap();
+            let short_key = match &key {
+                KeyInner::AesGcmSiv(key) => key,
+                _ => unreachable!(),
+            };
+            for aad_len in 0..=16 {
+                let aad = [1u8; 16];
+                let aad = &aad[..aad_len];
+                for len in 0..=16 {
+                    let nonce = || Nonce::assume_unique_for_key([2; 12]);
+
+                    let mut expected = [4u8; 16];
+                    let expected_tag = seal_aes_avxni(&key, nonce(), aad, &mut expected[..len], 0);
+                    let mut actual = [4u8; 16];
+                    let actual_tag =
+                        seal_short_avx_aesni(short_key, nonce(), aad, &mut actual[..len]);
+                    assert_eq!(expected[..len], actual[..len]);
+                    assert_eq!(expected_tag.0.as_ref(), actual_tag.0.as_ref());
+
+                    for &in_prefix_len in &[0, 1, 15, 16, 17] {
+                        let mut in_out = [0u8; 17 + 16 + TAG_LEN];
+                        let in_out = &mut in_out[..(in_prefix_len + len + TAG_LEN)];
+                        in_out[in_prefix_len..][..len].copy_from_slice(&actual[..len]);
+                        in_out[(in_prefix_len + len)..].copy_from_slice(actual_tag.0.as_ref());
+                        let mut expected = [0u8; 17 + 16 + TAG_LEN];
+                        let expected = &mut expected[..in_out.len()];
+                        expected.copy_from_slice(in_out);
+
+                        let expected_tag =
+                            open_avx_aesni(&key, nonce(), aad, in_prefix_len, expected, 0);
+                        let actual_tag =
+                            open_short_avx_aesni(short_key, nonce(), aad, in_prefix_len, in_out);
+                        assert_eq!(&expected[..len], &in_out[..len]);
+                        assert_eq!(&in_out[..len], &[4u8; 16][..len]);
+                        assert_eq!(expected_tag.0.as_ref(), actual_tag.0.as_ref());
+                    }
+                }
+            }
+        }
+    }
+
+    // The `VAES_VPCLMULQDQ` kernels must agree with the `AVX_AESNI` ones,
+    // including for partial groups of eight blocks and partial blocks.
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_vaes() {
+        use crate::aead::{
+            aes_gcm_siv::{open_avx_aesni, open_vaes, seal_aes_avxni, seal_vaes},
+            gcm_siv, TAG_LEN,
+        };
+
+        let cpu_features = cpu::features();
+        match gcm_siv::detect_implementation(cpu_features) {
+            gcm_siv::Implementation::VAES_VPCLMULQDQ => (),
+            _ => {
+                return;
+            }
+        }
+
+        const MAX_LEN: usize = 20 * 16 + 3;
+        for &(variant, key_len) in &[(Variant::AES_128, 16), (Variant::AES_256, 32)] {
+            let key = init(&[3; 32][..key_len], variant, cpu_features).unwrap();
+            for &aad_len in &[0, 1, 16, 127, 128, 129, 300] {
+                let aad = [1u8; 300];
+                let aad = &aad[..aad_len];
+                for len in (0..=MAX_LEN).step_by(7).chain(Some(8 * 16)) {
+                    let nonce = || Nonce::assume_unique_for_key([2; 12]);
+                    let mut plaintext = [0u8; MAX_LEN];
+                    for (i, b) in plaintext.iter_mut().enumerate() {
+                        *b = i as u8;
+                    }
+                    let plaintext = &plaintext[..len];
+
+                    let mut expected = [0u8; MAX_LEN];
+                    let expected = &mut expected[..len];
+                    expected.copy_from_slice(plaintext);
+                    let expected_tag = seal_aes_avxni(&key, nonce(), aad, expected, 0);
+                    let mut actual = [0u8; MAX_LEN];
+                    let actual = &mut actual[..len];
+                    actual.copy_from_slice(plaintext);
+                    let actual_tag = seal_vaes(&key, nonce(), aad, actual);
+                    assert_eq!(expected, actual);
+                    assert_eq!(expected_tag.0.as_ref(), actual_tag.0.as_ref());
+
+                    for &in_prefix_len in &[0, 1, 16, 17] {
+                        let mut in_out = [0u8; 17 + MAX_LEN + TAG_LEN];
+                        let in_out = &mut in_out[..(in_prefix_len + len + TAG_LEN)];
+                        in_out[in_prefix_len..][..len].copy_from_slice(actual);
+                        in_out[(in_prefix_len + len)..].copy_from_slice(actual_tag.0.as_ref());
+                        let mut expected = [0u8; 17 + MAX_LEN + TAG_LEN];
+                        let expected = &mut expected[..in_out.len()];
+                        expected.copy_from_slice(in_out);
+
+                        let expected_tag =
+                            open_avx_aesni(&key, nonce(), aad, in_prefix_len, expected, 0);
+                        let actual_tag = open_vaes(&key, nonce(), aad, in_prefix_len, in_out);
+                        assert_eq!(&expected[..len], &in_out[..len]);
+                        assert_eq!(&in_out[..len], plaintext);
+                        assert_eq!(expected_tag.0.as_ref(), actual_tag.0.as_ref());
+                    }
+                }
+            }
+        }
+    }
+
+    // `open_batch` must agree with `open_fallback` for every record, whether
+    // or not the record is valid, for groups of every size.
+    #[test]
+    fn test_open_batch() {
+        use crate::aead::{
+            aes_gcm_siv::{open_batch, open_fallback},
+            gcm, TAG_LEN,
+        };
+        use std::{vec, vec::Vec};
+
+        // (AAD length, plaintext length)
+        const LENS: &[(usize, usize)] = &[
+            (17, 47),
+            (0, 64),
+            (40, 100),
+            (16, 16),
+            (3, 1),
+            (0, 0),
+            (5, 33),
+            (0, 200),
+        ];
+
+        let cpu_features = cpu::features();
+        for &(variant, key_len) in &[(Variant::AES_128, 16), (Variant::AES_256, 32)] {
+            let key = init(&[3; 32][..key_len], variant, cpu_features).unwrap();
+            let nonce = |i: usize| Nonce::assume_unique_for_key([i as u8; 12]);
+            let aads = LENS
+                .iter()
+                .map(|&(aad_len, _)| vec![1u8; aad_len])
+                .collect::<Vec<_>>();
+            let mut sealed = Vec::new();
+            for (i, (&(_, len), aad)) in LENS.iter().zip(aads.iter()).enumerate() {
+                let mut in_out = vec![i as u8; len];
+                let tag = aes_gcm_siv_seal(&key, nonce(i), Aad(aad), &mut in_out, cpu_features);
+                in_out.extend_from_slice(tag.0.as_ref());
+                sealed.push(in_out);
+            }
+            // Corrupt one of the records.
+            let last = sealed[3].len() - 1;
+            sealed[3][last] ^= 1;
+
+            for group_len in 1..=gcm::BATCH_LEN {
+                let mut expected = sealed.clone();
+                let expected_tags = expected
+                    .iter_mut()
+                    .zip(aads.iter())
+                    .enumerate()
+                    .map(|(i, (in_out, aad))| {
+                        open_fallback(&key, nonce(i), aad, 0, in_out, cpu_features)
+                    })
+                    .collect::<Vec<_>>();
+
+                let mut actual = sealed.clone();
+                let mut records = actual
+                    .iter_mut()
+                    .zip(aads.iter())
+                    .enumerate()
+                    .map(|(i, (in_out, aad))| (nonce(i), Aad(&aad[..]), &mut in_out[..]))
+                    .collect::<Vec<_>>();
+                let mut actual_tags = Vec::new();
+                for group in records.chunks_mut(group_len) {
+                    let tags = open_batch(&key, group, cpu_features);
+                    actual_tags.extend_from_slice(&tags[..group.len()]);
+                }
+                drop(records);
+
+                for (i, ((expected, actual), (expected_tag, actual_tag))) in expected
+                    .iter()
+                    .zip(actual.iter())
+                    .zip(expected_tags.iter().zip(actual_tags.iter()))
+                    .enumerate()
+                {
+                    let plaintext_len = expected.len() - TAG_LEN;
+                    assert_eq!(&expected[..plaintext_len], &actual[..plaintext_len]);
+                    assert_eq!(expected_tag.0.as_ref(), actual_tag.as_ref());
+                    assert_eq!(actual_tag.as_ref() == &actual[plaintext_len..], i != 3);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "internal_benches", target_arch = "x86_64"))]
+mod internal_benches {
+    use super::*;
+    extern crate test;
+
+    // Each benchmark seals or opens a message of `$len` bytes using only the
+    // narrow (`usize::MAX`) or only the wide (`0`) kernels. Compare them to
+    // find the crossover points for the current CPU.
+    macro_rules! bench_kernels {
+        ( $name:ident, $len:expr, $wide_kernels_min_len:expr, $f:expr ) => {
+            #[bench]
+            fn $name(bench: &mut test::Bencher) {
+                let cpu_features = cpu::features();
+                match gcm_siv::detect_implementation(cpu_features) {
+                    gcm_siv::Implementation::AVX_AESNI => (),
+                    gcm_siv::Implementation::VAES_VPCLMULQDQ => (),
+                    _ => {
+                        return;
+                    }
+                }
+                let key = init(&[1; 16], AES_128, cpu_features).unwrap();
+                let mut in_out = [0u8; $len + TAG_LEN];
+                bench.iter(|| {
+                    let nonce = Nonce::assume_unique_for_key([0; 12]);
+                    let _ = $f(&key, nonce, &mut in_out, $wide_kernels_min_len);
+                });
+            }
+        };
+    }
+
+    fn seal(key: &aead::KeyInner, nonce: Nonce, in_out: &mut [u8], min_len: usize) -> Tag {
+        let in_len = in_out.len() - TAG_LEN;
+        seal_aes_avxni(key, nonce, &[], &mut in_out[..in_len], min_len)
+    }
+
+    fn open(key: &aead::KeyInner, nonce: Nonce, in_out: &mut [u8], min_len: usize) -> Tag {
+        open_avx_aesni(key, nonce, &[], 0, in_out, min_len)
+    }
+
+    bench_kernels!(seal_512_narrow, 512, usize::max_value(), seal);
+    bench_kernels!(seal_512_wide, 512, 0, seal);
+    bench_kernels!(seal_1024_narrow, 1024, usize::max_value(), seal);
+    bench_kernels!(seal_1024_wide, 1024, 0, seal);
+    bench_kernels!(seal_2048_narrow, 2048, usize::max_value(), seal);
+    bench_kernels!(seal_2048_wide, 2048, 0, seal);
+
+    bench_kernels!(open_64_narrow, 64, usize::max_value(), open);
+    bench_kernels!(open_64_wide, 64, 0, open);
+    bench_kernels!(open_96_narrow, 96, usize::max_value(), open);
+    bench_kernels!(open_96_wide, 96, 0, open);
+    bench_kernels!(open_128_narrow, 128, usize::max_value(), open);
+    bench_kernels!(open_128_wide, 128, 0, open);
 }
@@ -0,0 +1,365 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! AES Key Wrap.
+//!
+//! `Key::wrap()` and `Key::unwrap()` implement AES-KW as specified in
+//! [RFC 3394], for keys that are a multiple of 8 bytes long.
+//! `Key::wrap_with_padding()` and `Key::unwrap_with_padding()` implement
+//! AES-KWP as specified in [RFC 5649], for keys of any length. Both add 8
+//! bytes (16 bytes for padded inputs of up to 8 bytes) that are checked when
+//! unwrapping, so a wrapped key can't be modified without detection.
+//!
+//! Unwrapping uses a portable, constant-time implementation of the AES
+//! inverse cipher that is much slower than the AES encryption used
+//! elsewhere. It is meant for keys, not bulk data.
+//!
+//! [RFC 3394]: https://tools.ietf.org/html/rfc3394
+//! [RFC 5649]: https://tools.ietf.org/html/rfc5649
+
+use super::{aes, inverse_cipher, Block, BLOCK_LEN};
+use crate::{constant_time, cpu, error, polyfill};
+
+#[cfg(feature = "audit")]
+use crate::audit;
+
+/// A key for wrapping and unwrapping keys.
+pub struct Key {
+    aes_key: aes::Key,
+    inverse_cipher_key: inverse_cipher::Key,
+    algorithm: &'static Algorithm,
+    #[cfg(feature = "audit")]
+    fingerprint: audit::Fingerprint,
+}
+
+derive_debug_via_field!(Key, algorithm);
+
+impl Key {
+    /// Create a new key wrapping key.
+    ///
+    /// `key_bytes` must be exactly `algorithm.key_len()` bytes long.
+    pub fn new(
+        algorithm: &'static Algorithm,
+        key_bytes: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        let cpu_features = cpu::features();
+        let aes_key = aes::Key::new(key_bytes, algorithm.variant, cpu_features)?;
+        Ok(Self {
+            aes_key,
+            inverse_cipher_key: inverse_cipher::Key::new(
+                key_bytes,
+                algorithm.variant,
+                cpu_features,
+            ),
+            algorithm,
+            #[cfg(feature = "audit")]
+            fingerprint: audit::Fingerprint::new(key_bytes),
+        })
+    }
+
+    /// The key's algorithm.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+
+    /// Wraps `input` with AES-KW, writing the result to the start of `output`
+    /// and returning its length, `input.len() + 8`.
+    ///
+    /// Fails if `input` isn't a multiple of 8 bytes long, if it is shorter
+    /// than 16 bytes, or if `output` is too short.
+    pub fn wrap(&self, input: &[u8], output: &mut [u8]) -> Result<usize, error::Unspecified> {
+        if input.len() % SEMIBLOCK_LEN != 0 || input.len() < 2 * SEMIBLOCK_LEN {
+            return Err(error::Unspecified);
+        }
+        let output_len = input.len() + SEMIBLOCK_LEN;
+        let output = output.get_mut(..output_len).ok_or(error::Unspecified)?;
+        #[cfg(feature = "audit")]
+        audit::record(
+            self.algorithm,
+            audit::Operation::Seal,
+            &self.fingerprint,
+            input.len(),
+        );
+
+        let (a_out, r) = output.split_at_mut(SEMIBLOCK_LEN);
+        r.copy_from_slice(input);
+        let mut a = DEFAULT_IV;
+        w(&self.aes_key, &mut a, r);
+        a_out.copy_from_slice(&a);
+        Ok(output_len)
+    }
+
+    /// Unwraps the AES-KW-wrapped `input`, writing the result to the start of
+    /// `output` and returning its length, `input.len() - 8`.
+    ///
+    /// Fails if `input` isn't the output of `wrap()` with this key, or if
+    /// `output` is too short. On failure, `output` is zeroed.
+    pub fn unwrap(&self, input: &[u8], output: &mut [u8]) -> Result<usize, error::Unspecified> {
+        if input.len() % SEMIBLOCK_LEN != 0 || input.len() < 3 * SEMIBLOCK_LEN {
+            return Err(error::Unspecified);
+        }
+        let output_len = input.len() - SEMIBLOCK_LEN;
+        let output = output.get_mut(..output_len).ok_or(error::Unspecified)?;
+        #[cfg(feature = "audit")]
+        audit::record(
+            self.algorithm,
+            audit::Operation::Open,
+            &self.fingerprint,
+            output_len,
+        );
+
+        let mut a = semiblock(&input[..SEMIBLOCK_LEN]);
+        output.copy_from_slice(&input[SEMIBLOCK_LEN..]);
+        w_inverse(&self.inverse_cipher_key, &mut a, output);
+
+        check_or_clear(
+            output,
+            constant_time::verify_slices_are_equal(&a, &DEFAULT_IV),
+        )?;
+        Ok(output_len)
+    }
+
+    /// Wraps `input` with AES-KWP, writing the result to the start of
+    /// `output` and returning its length, which is `input.len()` rounded up
+    /// to a multiple of 8, plus 8.
+    ///
+    /// Fails if `input` is empty, if it is longer than 2**32 - 1 bytes, or if
+    /// `output` is too short.
+    pub fn wrap_with_padding(
+        &self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<usize, error::Unspecified> {
+        let mli = kwp_message_length_indicator(input.len())?;
+        let padded_len = (input.len() + SEMIBLOCK_LEN - 1) / SEMIBLOCK_LEN * SEMIBLOCK_LEN;
+        let output_len = padded_len + SEMIBLOCK_LEN;
+        let output = output.get_mut(..output_len).ok_or(error::Unspecified)?;
+        #[cfg(feature = "audit")]
+        audit::record(
+            self.algorithm,
+            audit::Operation::Seal,
+            &self.fingerprint,
+            input.len(),
+        );
+
+        let mut a = [0u8; SEMIBLOCK_LEN];
+        a[..4].copy_from_slice(&KWP_IV_PREFIX);
+        a[4..].copy_from_slice(&mli);
+
+        if padded_len == SEMIBLOCK_LEN {
+            // RFC 5649 Section 4.1, step 2.
+            let mut block = [0u8; BLOCK_LEN];
+            block[..SEMIBLOCK_LEN].copy_from_slice(&a);
+            block[SEMIBLOCK_LEN..][..input.len()].copy_from_slice(input);
+            let block = self.aes_key.encrypt_block(Block::from(&block));
+            output.copy_from_slice(block.as_ref());
+        } else {
+            let (a_out, r) = output.split_at_mut(SEMIBLOCK_LEN);
+            r[..input.len()].copy_from_slice(input);
+            polyfill::slice::fill(&mut r[input.len()..], 0);
+            w(&self.aes_key, &mut a, r);
+            a_out.copy_from_slice(&a);
+        }
+        Ok(output_len)
+    }
+
+    /// Unwraps the AES-KWP-wrapped `input`, writing the result to the start
+    /// of `output` and returning its length.
+    ///
+    /// `output` must be at least `input.len() - 8` bytes long, which is the
+    /// length of the unwrapped key with its padding. Fails if `input` isn't
+    /// the output of `wrap_with_padding()` with this key, or if `output` is
+    /// too short. On failure, `output` is zeroed.
+    pub fn unwrap_with_padding(
+        &self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<usize, error::Unspecified> {
+        if input.len() % SEMIBLOCK_LEN != 0 || input.len() < 2 * SEMIBLOCK_LEN {
+            return Err(error::Unspecified);
+        }
+        let padded_len = input.len() - SEMIBLOCK_LEN;
+        let output = output.get_mut(..padded_len).ok_or(error::Unspecified)?;
+        #[cfg(feature = "audit")]
+        audit::record(
+            self.algorithm,
+            audit::Operation::Open,
+            &self.fingerprint,
+            padded_len,
+        );
+
+        let mut a;
+        if padded_len == SEMIBLOCK_LEN {
+            // RFC 5649 Section 4.2, step 1.
+            let block = self
+                .inverse_cipher_key
+                .decrypt_block(Block::from(&semiblock_pair(input)));
+            a = semiblock(&block.as_ref()[..SEMIBLOCK_LEN]);
+            output.copy_from_slice(&block.as_ref()[SEMIBLOCK_LEN..]);
+        } else {
+            a = semiblock(&input[..SEMIBLOCK_LEN]);
+            output.copy_from_slice(&input[SEMIBLOCK_LEN..]);
+            w_inverse(&self.inverse_cipher_key, &mut a, output);
+        }
+
+        // RFC 5649 Section 3.
+        let result =
+            constant_time::verify_slices_are_equal(&a[..4], &KWP_IV_PREFIX).and_then(|()| {
+                let mli = u32::from_be_bytes([a[4], a[5], a[6], a[7]]);
+                let mli = polyfill::usize_from_u32(mli);
+                if mli + SEMIBLOCK_LEN <= padded_len || mli > padded_len {
+                    return Err(error::Unspecified);
+                }
+                let zeros = [0u8; SEMIBLOCK_LEN];
+                constant_time::verify_slices_are_equal(
+                    &output[mli..],
+                    &zeros[..(padded_len - mli)],
+                )?;
+                Ok(mli)
+            });
+        check_or_clear(output, result)
+    }
+}
+
+/// An AES Key Wrap algorithm.
+pub struct Algorithm {
+    variant: aes::Variant,
+    key_len: usize,
+    id: AlgorithmID,
+}
+
+impl Algorithm {
+    /// The length of the key.
+    #[inline(always)]
+    pub fn key_len(&self) -> usize {
+        self.key_len
+    }
+}
+
+derive_debug_via_id!(Algorithm);
+
+#[derive(Debug, Eq, PartialEq)]
+enum AlgorithmID {
+    AES_128,
+    AES_256,
+}
+
+impl PartialEq for Algorithm {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Algorithm {}
+
+/// AES Key Wrap with a 128-bit key.
+pub static AES_128: Algorithm = Algorithm {
+    variant: aes::Variant::AES_128,
+    key_len: 16,
+    id: AlgorithmID::AES_128,
+};
+
+/// AES Key Wrap with a 256-bit key.
+pub static AES_256: Algorithm = Algorithm {
+    variant: aes::Variant::AES_256,
+    key_len: 32,
+    id: AlgorithmID::AES_256,
+};
+
+/// The maximum length of an input to `Key::wrap_with_padding()`.
+pub const MAX_PADDED_INPUT_LEN: u64 = 0xffff_ffff;
+
+const SEMIBLOCK_LEN: usize = BLOCK_LEN / 2;
+
+type Semiblock = [u8; SEMIBLOCK_LEN];
+
+// RFC 3394 Section 2.2.3.1.
+const DEFAULT_IV: Semiblock = [0xa6; SEMIBLOCK_LEN];
+
+// RFC 5649 Section 3.
+const KWP_IV_PREFIX: [u8; 4] = [0xa6, 0x59, 0x59, 0xa6];
+
+fn kwp_message_length_indicator(len: usize) -> Result<[u8; 4], error::Unspecified> {
+    let len = polyfill::u64_from_usize(len);
+    if len == 0 || len > MAX_PADDED_INPUT_LEN {
+        return Err(error::Unspecified);
+    }
+    Ok((len as u32).to_be_bytes())
+}
+
+fn semiblock(bytes: &[u8]) -> Semiblock {
+    let mut r = [0u8; SEMIBLOCK_LEN];
+    r.copy_from_slice(bytes);
+    r
+}
+
+fn semiblock_pair(bytes: &[u8]) -> [u8; BLOCK_LEN] {
+    let mut r = [0u8; BLOCK_LEN];
+    r.copy_from_slice(bytes);
+    r
+}
+
+// The wrapping process W of RFC 3394 Section 2.2.1, in its index-based form,
+// applied to the integrity check register `a` and the semiblocks `r`.
+fn w(aes_key: &aes::Key, a: &mut Semiblock, r: &mut [u8]) {
+    let n = r.len() / SEMIBLOCK_LEN;
+    for j in 0..6 {
+        for (i, r_i) in r.chunks_mut(SEMIBLOCK_LEN).enumerate() {
+            let mut block = [0u8; BLOCK_LEN];
+            block[..SEMIBLOCK_LEN].copy_from_slice(a);
+            block[SEMIBLOCK_LEN..].copy_from_slice(r_i);
+            let b = aes_key.encrypt_block(Block::from(&block));
+            let (msb, lsb) = b.as_ref().split_at(SEMIBLOCK_LEN);
+            *a = xor_t(semiblock(msb), n * j + i + 1);
+            r_i.copy_from_slice(lsb);
+        }
+    }
+}
+
+// The unwrapping process W⁻¹ of RFC 3394 Section 2.2.2, in its index-based
+// form.
+fn w_inverse(key: &inverse_cipher::Key, a: &mut Semiblock, r: &mut [u8]) {
+    let n = r.len() / SEMIBLOCK_LEN;
+    for j in (0..6).rev() {
+        for (i, r_i) in r.chunks_mut(SEMIBLOCK_LEN).enumerate().rev() {
+            let mut block = [0u8; BLOCK_LEN];
+            block[..SEMIBLOCK_LEN].copy_from_slice(&xor_t(*a, n * j + i + 1));
+            block[SEMIBLOCK_LEN..].copy_from_slice(r_i);
+            let b = key.decrypt_block(Block::from(&block));
+            let (msb, lsb) = b.as_ref().split_at(SEMIBLOCK_LEN);
+            *a = semiblock(msb);
+            r_i.copy_from_slice(lsb);
+        }
+    }
+}
+
+fn xor_t(mut a: Semiblock, t: usize) -> Semiblock {
+    let t = polyfill::u64_from_usize(t).to_be_bytes();
+    for (a, t) in a.iter_mut().zip(t.iter()) {
+        *a ^= t;
+    }
+    a
+}
+
+// Unverified output must not be released.
+fn check_or_clear<T>(
+    output: &mut [u8],
+    result: Result<T, error::Unspecified>,
+) -> Result<T, error::Unspecified> {
+    if result.is_err() {
+        polyfill::slice::fill(output, 0);
+    }
+    result
+}
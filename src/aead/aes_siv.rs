@@ -0,0 +1,352 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! AES-SIV deterministic authenticated encryption.
+//!
+//! `AES_128_SIV_CMAC` and `AES_256_SIV_CMAC` keys can be used with the
+//! functions in `aead` like the keys of the other algorithms, with the
+//! additional authenticated data and then the nonce as the associated data
+//! of [RFC 5297]. Unlike most AEADs, reusing a nonce only reveals whether the
+//! same message was sealed twice with the same additional authenticated
+//! data.
+//!
+//! The `seal_in_place()` and `open_in_place()` functions of this module take
+//! the associated data as a list of components instead, and no nonce. With
+//! no nonce among the components, sealing is deterministic: the same
+//! plaintext and components always give the same output, which is useful
+//! where nonces can't be stored, e.g. for encrypting database fields that
+//! are looked up by their encrypted value.
+//!
+//! As in the rest of `aead`, the tag (the synthetic IV of RFC 5297) follows
+//! the ciphertext, whereas RFC 5297 puts it before the ciphertext.
+//!
+//! [RFC 5297]: https://tools.ietf.org/html/rfc5297
+
+use super::{
+    aes::{self, Counter},
+    shift, Aad, Algorithm, AlgorithmID, Block, Direction, KeyInner, Nonce, OpeningKey, SealingKey,
+    Tag, BLOCK_LEN, NONCE_LEN, TAG_LEN,
+};
+use crate::{
+    aes_cmac::{self, dbl, pad},
+    constant_time, cpu, error,
+    polyfill::convert::*,
+};
+
+#[cfg(feature = "audit")]
+use crate::audit;
+
+/// AES-SIV-CMAC with AES-128, which RFC 5297 calls `AEAD_AES_SIV_CMAC_256`.
+///
+/// The keys are 256 bits long: the first half is the key for S2V and the
+/// second half is the key for encryption. The nonces are 96 bits long.
+pub static AES_128_SIV_CMAC: Algorithm = Algorithm {
+    key_len: 32,
+    init: init_128,
+    seal: aes_siv_seal,
+    open: aes_siv_open,
+    id: AlgorithmID::AES_128_SIV_CMAC,
+    max_input_len: AES_SIV_MAX_INPUT_LEN,
+};
+
+/// AES-SIV-CMAC with AES-256, which RFC 5297 calls `AEAD_AES_SIV_CMAC_512`.
+///
+/// The keys are 512 bits long: the first half is the key for S2V and the
+/// second half is the key for encryption. The nonces are 96 bits long.
+pub static AES_256_SIV_CMAC: Algorithm = Algorithm {
+    key_len: 64,
+    init: init_256,
+    seal: aes_siv_seal,
+    open: aes_siv_open,
+    id: AlgorithmID::AES_256_SIV_CMAC,
+    max_input_len: AES_SIV_MAX_INPUT_LEN,
+};
+
+// The synthetic IV has bit 31 of its last word cleared, so the 32-bit
+// counter doesn't wrap for up to 2**31 blocks.
+const AES_SIV_MAX_INPUT_LEN: u64 = super::max_input_len(BLOCK_LEN, 1 << 31);
+
+/// The maximum number of associated data components for the functions of
+/// this module.
+///
+/// RFC 5297 allows at most 127 components including the plaintext.
+pub const MAX_ASSOCIATED_DATA_COMPONENTS: usize = 126;
+
+/// Encrypts and signs (“seals”) data in place with an AES-SIV key and the
+/// given associated data components.
+///
+/// This is like `aead::seal_in_place()` except that there is no nonce and
+/// the associated data is a list of components, which may include a nonce.
+/// Fails unless `key`'s algorithm is `AES_128_SIV_CMAC` or
+/// `AES_256_SIV_CMAC`, or if there are more than
+/// `MAX_ASSOCIATED_DATA_COMPONENTS` components.
+pub fn seal_in_place(
+    key: &SealingKey,
+    associated_data: &[&[u8]],
+    in_out: &mut [u8],
+    out_suffix_capacity: usize,
+) -> Result<usize, error::Unspecified> {
+    let siv_key = check_key(&key.key.inner, associated_data)?;
+    if out_suffix_capacity < TAG_LEN {
+        return Err(error::Unspecified);
+    }
+    let in_out_len = in_out
+        .len()
+        .checked_sub(out_suffix_capacity)
+        .ok_or(error::Unspecified)?;
+    let (in_out, tag_out) = in_out.split_at_mut(in_out_len);
+    let tag_out: &mut [u8; TAG_LEN] = tag_out.try_into_()?;
+    super::check_per_nonce_max_bytes(key.key.algorithm, in_out_len)?;
+    #[cfg(feature = "audit")]
+    audit::record(
+        key.key.algorithm,
+        audit::Operation::Seal,
+        &key.key.fingerprint,
+        in_out_len,
+    );
+
+    let Tag(tag) = seal(siv_key, associated_data, in_out);
+    tag_out.copy_from_slice(tag.as_ref());
+    Ok(in_out_len + TAG_LEN)
+}
+
+/// Authenticates and decrypts (“opens”) data in place with an AES-SIV key
+/// and the given associated data components.
+///
+/// This is like `aead::open_in_place()` except that there is no nonce and
+/// the associated data is a list of components, which may include a nonce.
+/// Fails unless `key`'s algorithm is `AES_128_SIV_CMAC` or
+/// `AES_256_SIV_CMAC`, or if there are more than
+/// `MAX_ASSOCIATED_DATA_COMPONENTS` components.
+pub fn open_in_place<'a>(
+    key: &OpeningKey,
+    associated_data: &[&[u8]],
+    in_prefix_len: usize,
+    ciphertext_and_tag_modified_in_place: &'a mut [u8],
+) -> Result<&'a mut [u8], error::Unspecified> {
+    let siv_key = check_key(&key.key.inner, associated_data)?;
+    let ciphertext_len = ciphertext_and_tag_modified_in_place
+        .len()
+        .checked_sub(in_prefix_len)
+        .and_then(|len| len.checked_sub(TAG_LEN))
+        .ok_or(error::Unspecified)?;
+    super::check_per_nonce_max_bytes(key.key.algorithm, ciphertext_len)?;
+    #[cfg(feature = "audit")]
+    audit::record(
+        key.key.algorithm,
+        audit::Operation::Open,
+        &key.key.fingerprint,
+        ciphertext_len,
+    );
+
+    let (in_out, received_tag) =
+        ciphertext_and_tag_modified_in_place.split_at_mut(in_prefix_len + ciphertext_len);
+    let received_tag: &[u8; TAG_LEN] = (&received_tag[..]).try_into_()?;
+    let Tag(calculated_tag) = open(
+        siv_key,
+        associated_data,
+        Direction::Opening { in_prefix_len },
+        received_tag,
+        in_out,
+    );
+    if constant_time::verify_slices_are_equal(calculated_tag.as_ref(), received_tag).is_err() {
+        super::zero_out_plain_text(&mut in_out[..ciphertext_len]);
+        return Err(error::Unspecified);
+    }
+    Ok(&mut in_out[..ciphertext_len])
+}
+
+pub(super) struct Key {
+    mac_key: aes_cmac::Key,
+    ctr_key: aes::Key,
+}
+
+fn init_128(key: &[u8], cpu_features: cpu::Features) -> Result<KeyInner, error::Unspecified> {
+    init(key, aes::Variant::AES_128, cpu_features)
+}
+
+fn init_256(key: &[u8], cpu_features: cpu::Features) -> Result<KeyInner, error::Unspecified> {
+    init(key, aes::Variant::AES_256, cpu_features)
+}
+
+fn init(
+    key: &[u8],
+    variant: aes::Variant,
+    cpu_features: cpu::Features,
+) -> Result<KeyInner, error::Unspecified> {
+    let (mac_key, ctr_key) = key.split_at(key.len() / 2);
+    let mac_algorithm = match variant {
+        aes::Variant::AES_128 => &aes_cmac::AES_128,
+        aes::Variant::AES_256 => &aes_cmac::AES_256,
+    };
+    let mac_key = aes_cmac::Key::new(mac_algorithm, mac_key)?;
+    let ctr_key = aes::Key::new(ctr_key, variant, cpu_features)?;
+    Ok(KeyInner::AesSiv(Key { mac_key, ctr_key }))
+}
+
+fn check_key<'k>(
+    key: &'k KeyInner,
+    associated_data: &[&[u8]],
+) -> Result<&'k Key, error::Unspecified> {
+    if associated_data.len() > MAX_ASSOCIATED_DATA_COMPONENTS {
+        return Err(error::Unspecified);
+    }
+    match key {
+        KeyInner::AesSiv(key) => Ok(key),
+        _ => Err(error::Unspecified),
+    }
+}
+
+fn siv_key(key: &KeyInner) -> &Key {
+    match key {
+        KeyInner::AesSiv(key) => key,
+        _ => unreachable!(),
+    }
+}
+
+fn aes_siv_seal(
+    key: &KeyInner,
+    nonce: Nonce,
+    Aad(aad): Aad<&[u8]>,
+    in_out: &mut [u8],
+    _cpu_features: cpu::Features,
+) -> Tag {
+    seal(siv_key(key), &[aad, &nonce.as_ref()[..]], in_out)
+}
+
+// Like the AES-GCM-SIV `open` functions, this reads the tag from the end of
+// `in_out`.
+fn aes_siv_open(
+    key: &KeyInner,
+    nonce: Nonce,
+    Aad(aad): Aad<&[u8]>,
+    in_prefix_len: usize,
+    in_out: &mut [u8],
+    _cpu_features: cpu::Features,
+) -> Tag {
+    let (in_out, received_tag) = in_out.split_at_mut(in_out.len() - TAG_LEN);
+    let received_tag: &[u8; TAG_LEN] = (&received_tag[..]).try_into_().unwrap();
+    open(
+        siv_key(key),
+        &[aad, &nonce.as_ref()[..]],
+        Direction::Opening { in_prefix_len },
+        received_tag,
+        in_out,
+    )
+}
+
+// Decrypts `in_out` with `received_tag` and returns the calculated tag, for
+// opening with a detached tag.
+pub(super) fn open_detached(
+    key: &KeyInner,
+    nonce: Nonce,
+    Aad(aad): Aad<&[u8]>,
+    received_tag: &[u8; TAG_LEN],
+    in_out: &mut [u8],
+) -> Tag {
+    open(
+        siv_key(key),
+        &[aad, &nonce.as_ref()[..]],
+        Direction::Opening { in_prefix_len: 0 },
+        received_tag,
+        in_out,
+    )
+}
+
+fn seal(key: &Key, associated_data: &[&[u8]], in_out: &mut [u8]) -> Tag {
+    let siv = s2v(key, associated_data, in_out);
+    ctr(&key.ctr_key, &siv, in_out, Direction::Sealing);
+    Tag(siv)
+}
+
+fn open(
+    key: &Key,
+    associated_data: &[&[u8]],
+    direction: Direction,
+    received_tag: &[u8; TAG_LEN],
+    in_out: &mut [u8],
+) -> Tag {
+    let in_prefix_len = match direction {
+        Direction::Opening { in_prefix_len } => in_prefix_len,
+        Direction::Sealing => 0,
+    };
+    let plaintext_len = in_out.len() - in_prefix_len;
+    ctr(&key.ctr_key, &Block::from(received_tag), in_out, direction);
+    Tag(s2v(key, associated_data, &in_out[..plaintext_len]))
+}
+
+// AES-CTR with the synthetic IV, from RFC 5297 Section 2.5.
+fn ctr(key: &aes::Key, siv: &Block, in_out: &mut [u8], direction: Direction) {
+    let mut iv = *siv.as_ref();
+    iv[8] &= 0x7f;
+    iv[12] &= 0x7f;
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce.copy_from_slice(&iv[..NONCE_LEN]);
+    let mut counter = [0u8; 4];
+    counter.copy_from_slice(&iv[NONCE_LEN..]);
+    let mut ctr = Counter::zero(Nonce::assume_unique_for_key(nonce));
+    ctr.increment_by_less_safe(u32::from_be_bytes(counter));
+
+    let in_prefix_len = match direction {
+        Direction::Opening { in_prefix_len } => in_prefix_len,
+        Direction::Sealing => 0,
+    };
+    let in_out_len = in_out.len() - in_prefix_len;
+    let whole_len = in_out_len - (in_out_len % BLOCK_LEN);
+    // The assembly implementations don't support empty input.
+    if whole_len > 0 {
+        key.ctr32_encrypt_blocks(
+            &mut in_out[..(in_prefix_len + whole_len)],
+            direction,
+            &mut ctr,
+        );
+    }
+
+    shift::shift_partial((in_prefix_len, &mut in_out[whole_len..]), |remainder| {
+        let mut input = Block::zero();
+        input.partial_copy_from(remainder);
+        key.encrypt_iv_xor_block(ctr.into(), input)
+    });
+}
+
+// S2V, from RFC 5297 Section 2.4, of the associated data components and the
+// plaintext.
+fn s2v(key: &Key, associated_data: &[&[u8]], plaintext: &[u8]) -> Block {
+    let mut d = cmac(key, &[0u8; BLOCK_LEN]);
+    for component in associated_data {
+        d = dbl(d);
+        d.bitxor_assign(cmac(key, component));
+    }
+
+    let mut mac = aes_cmac::Context::with_key(&key.mac_key);
+    if plaintext.len() >= BLOCK_LEN {
+        let (head, last) = plaintext.split_at(plaintext.len() - BLOCK_LEN);
+        let last: &[u8; BLOCK_LEN] = last.try_into_().unwrap();
+        let mut last = Block::from(last);
+        last.bitxor_assign(d);
+        mac.update(head);
+        mac.update(last.as_ref());
+    } else {
+        let mut t = dbl(d);
+        t.bitxor_assign(pad(plaintext));
+        mac.update(t.as_ref());
+    }
+    mac.finish()
+}
+
+fn cmac(key: &Key, input: &[u8]) -> Block {
+    let mut mac = aes_cmac::Context::with_key(&key.mac_key);
+    mac.update(input);
+    mac.finish()
+}
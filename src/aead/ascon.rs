@@ -0,0 +1,306 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Ascon-128 sealing and opening.
+//!
+//! Keys for `ASCON_128` are constructed like those for the other algorithms,
+//! as `aead::SealingKey` and `aead::OpeningKey`, but they must be used with
+//! the functions of this module, which take the 128-bit `Nonce` defined here;
+//! the functions in `aead` reject them.
+//!
+//! Ascon is meant for constrained devices. The implementation is portable
+//! Rust that only uses bitwise operations and rotations of 64-bit words, so
+//! it is constant-time without AES instructions or tables, and it is small.
+//! On CPUs with AES instructions, AES-GCM and AEGIS are much faster.
+
+use super::{Aad, Algorithm, AlgorithmID, Block, KeyInner, OpeningKey, SealingKey, Tag};
+use crate::{
+    cpu,
+    digest::ascon::{load_u64, permute, STATE_WORDS},
+    endian::*,
+    error,
+    polyfill::convert::*,
+};
+use core::ops::RangeFrom;
+
+/// Ascon-128 as specified in [Ascon v1.2], the winner of the NIST
+/// lightweight cryptography competition.
+///
+/// The keys are 128 bits long and the nonces are 128 bits long. Messages are
+/// sealed and opened with `seal_in_place()` and `open_in_place()` of this
+/// module. Nonces are long enough that generating each one randomly is safe
+/// for up to 2**48 messages sealed with the same key.
+///
+/// [Ascon v1.2]: https://ascon.iaik.tugraz.at/files/asconv12-nist.pdf
+pub static ASCON_128: Algorithm = Algorithm {
+    key_len: KEY_LEN,
+    init: ascon_128_init,
+    seal: ascon_128_seal,
+    open: ascon_128_open,
+    id: AlgorithmID::ASCON_128,
+    max_input_len: u64::max_value(),
+};
+
+/// The length of an Ascon-128 nonce.
+pub const NONCE_LEN: usize = 128 / 8;
+
+const KEY_LEN: usize = 128 / 8;
+
+// Ascon-128 absorbs and encrypts 8 bytes at a time.
+const RATE: usize = 64 / 8;
+
+// The key length, the rate, and the numbers of rounds of the initialization
+// and of the processing of each block.
+const IV: u64 = 0x80400c0600000000;
+
+/// A nonce for a single Ascon-128 opening or sealing operation.
+///
+/// The user must ensure, for a particular key, that each nonce is unique.
+///
+/// `Nonce` intentionally doesn't implement `Clone` to ensure that each one is
+/// consumed at most once.
+pub struct Nonce([u8; NONCE_LEN]);
+
+impl Nonce {
+    /// Constructs a `Nonce` with the given value, assuming that the value is
+    /// unique for the lifetime of the key it is being used with.
+    ///
+    /// Fails if `value` isn't `NONCE_LEN` bytes long.
+    #[inline]
+    pub fn try_assume_unique_for_key(value: &[u8]) -> Result<Self, error::Unspecified> {
+        let value: &[u8; NONCE_LEN] = value.try_into_()?;
+        Ok(Self::assume_unique_for_key(*value))
+    }
+
+    /// Constructs a `Nonce` with the given value, assuming that the value is
+    /// unique for the lifetime of the key it is being used with.
+    #[inline]
+    pub fn assume_unique_for_key(value: [u8; NONCE_LEN]) -> Self {
+        Self(value)
+    }
+}
+
+impl AsRef<[u8; NONCE_LEN]> for Nonce {
+    fn as_ref(&self) -> &[u8; NONCE_LEN] {
+        &self.0
+    }
+}
+
+/// Encrypts and signs (“seals”) data in place with an Ascon-128 key.
+///
+/// This is like `aead::seal_in_place()` except for the type of `nonce`.
+/// Fails unless `key`'s algorithm is `ASCON_128`.
+pub fn seal_in_place<A: AsRef<[u8]>>(
+    key: &SealingKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
+    in_out: &mut [u8],
+    out_suffix_capacity: usize,
+) -> Result<usize, error::Unspecified> {
+    let message_key = MessageKey::new(&key.key.inner, nonce)?;
+    super::seal_in_place_(
+        &key.key,
+        &KeyInner::AsconMessage(message_key),
+        unused_nonce(),
+        Aad::from(aad.as_ref()),
+        in_out,
+        out_suffix_capacity,
+    )
+    .map_err(error::Unspecified::from)
+}
+
+/// Authenticates and decrypts (“opens”) data in place with an Ascon-128 key.
+///
+/// This is like `aead::open_in_place()` except for the type of `nonce`.
+/// Fails unless `key`'s algorithm is `ASCON_128`.
+pub fn open_in_place<'a, A: AsRef<[u8]>>(
+    key: &OpeningKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
+    in_prefix_len: usize,
+    ciphertext_and_tag_modified_in_place: &'a mut [u8],
+) -> Result<&'a mut [u8], error::Unspecified> {
+    let message_key = MessageKey::new(&key.key.inner, nonce)?;
+    super::open_in_place_(
+        &key.key,
+        &KeyInner::AsconMessage(message_key),
+        unused_nonce(),
+        Aad::from(aad.as_ref()),
+        in_prefix_len,
+        ciphertext_and_tag_modified_in_place,
+    )
+    .map_err(error::Unspecified::from)
+}
+
+/// Authenticates and decrypts (“opens”) data in place with an Ascon-128 key,
+/// moving the plaintext to the start of `in_out`.
+///
+/// This is like `aead::open_within()` except for the type of `nonce`.
+/// Fails unless `key`'s algorithm is `ASCON_128`.
+#[inline]
+pub fn open_within<'a, A: AsRef<[u8]>>(
+    key: &OpeningKey,
+    nonce: Nonce,
+    aad: Aad<A>,
+    in_out: &'a mut [u8],
+    ciphertext_and_tag: RangeFrom<usize>,
+) -> Result<&'a mut [u8], error::Unspecified> {
+    open_in_place(key, nonce, aad, ciphertext_and_tag.start, in_out)
+}
+
+// The key, as two big-endian words.
+pub(super) struct Key([u64; 2]);
+
+// A key together with the nonce of one message. The `aead` functions get
+// this as their `KeyInner`, and a `super::Nonce` that isn't used.
+pub(super) struct MessageKey {
+    key: [u64; 2],
+    nonce: [u64; 2],
+}
+
+impl MessageKey {
+    fn new(key: &KeyInner, Nonce(nonce): Nonce) -> Result<Self, error::Unspecified> {
+        let Key(key) = match key {
+            KeyInner::Ascon(key) => key,
+            _ => return Err(error::Unspecified),
+        };
+        Ok(Self {
+            key: *key,
+            nonce: [load_u64(&nonce[..8]), load_u64(&nonce[8..])],
+        })
+    }
+}
+
+fn unused_nonce() -> super::Nonce {
+    super::Nonce::assume_unique_for_key([0; super::NONCE_LEN])
+}
+
+fn ascon_128_init(key: &[u8], _todo: cpu::Features) -> Result<KeyInner, error::Unspecified> {
+    let key: &[u8; KEY_LEN] = key.try_into_()?;
+    Ok(KeyInner::Ascon(Key([
+        load_u64(&key[..8]),
+        load_u64(&key[8..]),
+    ])))
+}
+
+fn ascon_128_seal(
+    key: &KeyInner,
+    _nonce: super::Nonce,
+    Aad(aad): Aad<&[u8]>,
+    in_out: &mut [u8],
+    _cpu_features: cpu::Features,
+) -> Tag {
+    let key = message_key(key);
+    let mut x = initialize(key, aad);
+
+    let mut blocks = in_out.chunks_exact_mut(RATE);
+    for block in &mut blocks {
+        x[0] ^= load_u64(block);
+        block.copy_from_slice(&x[0].to_be_bytes());
+        permute(&mut x, 6);
+    }
+    let last = blocks.into_remainder();
+    x[0] ^= load_padded(last);
+    let last_len = last.len();
+    last.copy_from_slice(&x[0].to_be_bytes()[..last_len]);
+
+    finalize(key, x)
+}
+
+// Decrypts `in_out[in_prefix_len..]` into `in_out[..(in_out.len() -
+// in_prefix_len)]`. Each block is read before its plaintext is written,
+// which can only overwrite ciphertext that was already decrypted.
+fn ascon_128_open(
+    key: &KeyInner,
+    _nonce: super::Nonce,
+    Aad(aad): Aad<&[u8]>,
+    in_prefix_len: usize,
+    in_out: &mut [u8],
+    _cpu_features: cpu::Features,
+) -> Tag {
+    let key = message_key(key);
+    let mut x = initialize(key, aad);
+
+    let ciphertext_len = in_out.len() - in_prefix_len;
+    let full_blocks_len = ciphertext_len - (ciphertext_len % RATE);
+    for start in (0..full_blocks_len).step_by(RATE) {
+        let c = load_u64(&in_out[(in_prefix_len + start)..][..RATE]);
+        in_out[start..][..RATE].copy_from_slice(&(x[0] ^ c).to_be_bytes());
+        x[0] = c;
+        permute(&mut x, 6);
+    }
+
+    // The plaintext of the last, partial, block is padded and absorbed, which
+    // replaces the first bytes of `x[0]` with the ciphertext.
+    let last_len = ciphertext_len - full_blocks_len;
+    let mut block = [0u8; RATE];
+    block[..last_len].copy_from_slice(&in_out[(in_prefix_len + full_blocks_len)..]);
+    let plaintext = (x[0] ^ load_u64(&block)).to_be_bytes();
+    let plaintext = &plaintext[..last_len];
+    in_out[full_blocks_len..][..last_len].copy_from_slice(plaintext);
+    x[0] ^= load_padded(plaintext);
+
+    finalize(key, x)
+}
+
+// `aead` rejects `KeyInner::Ascon` keys in `check_nonce_len()`, so only the
+// functions of this module get here.
+fn message_key(key: &KeyInner) -> &MessageKey {
+    match key {
+        KeyInner::AsconMessage(key) => key,
+        _ => unreachable!(),
+    }
+}
+
+// Initializes the state with the key and the nonce and absorbs `aad`.
+fn initialize(key: &MessageKey, aad: &[u8]) -> [u64; STATE_WORDS] {
+    let mut x = [IV, key.key[0], key.key[1], key.nonce[0], key.nonce[1]];
+    permute(&mut x, 12);
+    x[3] ^= key.key[0];
+    x[4] ^= key.key[1];
+
+    // Empty additional authenticated data isn't padded.
+    if !aad.is_empty() {
+        let mut blocks = aad.chunks_exact(RATE);
+        for block in &mut blocks {
+            x[0] ^= load_u64(block);
+            permute(&mut x, 6);
+        }
+        x[0] ^= load_padded(blocks.remainder());
+        permute(&mut x, 6);
+    }
+
+    // Domain separation of the additional authenticated data and the
+    // plaintext.
+    x[4] ^= 1;
+    x
+}
+
+fn finalize(key: &MessageKey, mut x: [u64; STATE_WORDS]) -> Tag {
+    x[1] ^= key.key[0];
+    x[2] ^= key.key[1];
+    permute(&mut x, 12);
+    Tag(Block::from_u64_be(
+        BigEndian::from(x[3] ^ key.key[0]),
+        BigEndian::from(x[4] ^ key.key[1]),
+    ))
+}
+
+// `bytes`, which are fewer than `RATE`, followed by a one bit and then zeros.
+fn load_padded(bytes: &[u8]) -> u64 {
+    let mut block = [0u8; RATE];
+    block[..bytes.len()].copy_from_slice(bytes);
+    block[bytes.len()] = 0x80;
+    load_u64(&block)
+}
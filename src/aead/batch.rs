@@ -0,0 +1,233 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Opening many records with the same key at once.
+
+use super::{
+    aes_gcm, check_nonce_len, gcm, zero_out_plain_text, Aad, AlgorithmID, Block, Key, Nonce,
+    OpeningKey, Tag, TAG_LEN,
+};
+use crate::{constant_time, error};
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+use super::{aes_gcm_siv, aes_gcm_siv::Stream, multipart};
+#[cfg(feature = "aead-aes-gcm-siv")]
+use crate::polyfill::convert::*;
+
+#[cfg(feature = "audit")]
+use crate::audit;
+
+/// Authenticates and decrypts (“opens”) a batch of records in place.
+///
+/// Each record is a nonce, the additional authenticated data, and the
+/// record's ciphertext followed by its tag, like the arguments of
+/// `open_in_place()` with an `in_prefix_len` of zero. When `open_batch()`
+/// returns `Ok(())`, the plaintext of each record is the first
+/// `in_out.len() - key.algorithm().tag_len()` bytes of its `in_out`.
+///
+/// Every record is opened even if an earlier one fails to open, and the
+/// result doesn't say which ones failed. If any record fails to open, the
+/// plaintexts of all of the records are overwritten with zeros and
+/// `open_batch()` fails; open the records one at a time with
+/// `open_in_place()` to find out which ones are invalid. `open_batch()` also
+/// fails, without decrypting anything, if any record is too short to contain
+/// a tag or too long for the algorithm.
+///
+/// This is faster than opening the records one at a time when there are
+/// many short records: AES-GCM authenticates several records in lockstep,
+/// and AES-GCM-SIV derives the per-nonce keys only once for consecutive
+/// records that have the same nonce and, where the implementation allows,
+/// authenticates records with distinct nonces in lockstep.
+pub fn open_batch<A: AsRef<[u8]>>(
+    key: &OpeningKey,
+    records: &mut [(Nonce, Aad<A>, &mut [u8])],
+) -> Result<(), error::Unspecified> {
+    let key = &key.key;
+    check_nonce_len(&key.inner)?;
+    for (_, Aad(aad), in_out) in records.iter() {
+        let ciphertext_len = in_out
+            .len()
+            .checked_sub(TAG_LEN)
+            .ok_or(error::Unspecified)?;
+        key.algorithm
+            .check_input_lens(aad.as_ref().len(), ciphertext_len)?;
+    }
+    #[cfg(feature = "audit")]
+    for (_, _, in_out) in records.iter() {
+        audit::record(
+            key.algorithm,
+            audit::Operation::Open,
+            &key.fingerprint,
+            in_out.len() - TAG_LEN,
+        );
+    }
+
+    let mut all_verified = true;
+    let mut verify = |calculated_tag: &Block, in_out: &[u8]| {
+        let received_tag = &in_out[(in_out.len() - TAG_LEN)..];
+        let verified =
+            constant_time::verify_slices_are_equal(calculated_tag.as_ref(), received_tag);
+        all_verified &= verified.is_ok();
+    };
+
+    match key.algorithm.id {
+        AlgorithmID::AES_128_GCM | AlgorithmID::AES_256_GCM => {
+            for group in records.chunks_mut(gcm::BATCH_LEN) {
+                let calculated_tags = aes_gcm::open_batch(&key.inner, group, key.cpu_features);
+                for ((_, _, in_out), calculated_tag) in group.iter().zip(calculated_tags.iter()) {
+                    verify(calculated_tag, in_out);
+                }
+            }
+        }
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        AlgorithmID::AES_128_GCM_SIV | AlgorithmID::AES_256_GCM_SIV => {
+            let batches_polyval = aes_gcm_siv::batches_polyval(key.cpu_features);
+            let mut records = &mut records[..];
+            while !records.is_empty() {
+                let nonce = *records[0].0.as_ref();
+                let run_len = records
+                    .iter()
+                    .take_while(|(n, _, _)| *n.as_ref() == nonce)
+                    .count();
+
+                if run_len == 1 && batches_polyval {
+                    let (group, rest) = records.split_at_mut(distinct_nonces_len(records));
+                    records = rest;
+
+                    let calculated_tags =
+                        aes_gcm_siv::open_batch(&key.inner, group, key.cpu_features);
+                    for ((_, _, in_out), calculated_tag) in group.iter().zip(calculated_tags.iter())
+                    {
+                        verify(calculated_tag, in_out);
+                    }
+                    continue;
+                }
+
+                let (run, rest) = records.split_at_mut(run_len);
+                records = rest;
+
+                if run_len == 1 {
+                    let (_, Aad(aad), in_out) = &mut run[0];
+                    let Tag(calculated_tag) = open_one(key, nonce, aad.as_ref(), in_out);
+                    verify(&calculated_tag, in_out);
+                    continue;
+                }
+
+                let nonce = Nonce::assume_unique_for_key(nonce);
+                let mut stream = Stream::new(&key.inner, nonce, key.cpu_features);
+                for (_, Aad(aad), in_out) in run.iter_mut() {
+                    let (ciphertext, received_tag) = in_out.split_at_mut(in_out.len() - TAG_LEN);
+                    let received_tag: &[u8; TAG_LEN] = (&received_tag[..]).try_into_().unwrap();
+                    let (next, Tag(calculated_tag)) = multipart::open_detached_with_stream(
+                        key.algorithm,
+                        stream,
+                        Aad::from(aad.as_ref()),
+                        received_tag,
+                        ciphertext,
+                    );
+                    stream = next;
+                    verify(&calculated_tag, in_out);
+                }
+            }
+        }
+        _ => {
+            for (nonce, Aad(aad), in_out) in records.iter_mut() {
+                let Tag(calculated_tag) = open_one(key, *nonce.as_ref(), aad.as_ref(), in_out);
+                verify(&calculated_tag, in_out);
+            }
+        }
+    }
+
+    if !all_verified {
+        for (_, _, in_out) in records.iter_mut() {
+            let ciphertext_len = in_out.len() - TAG_LEN;
+            zero_out_plain_text(&mut in_out[..ciphertext_len]);
+        }
+        return Err(error::Unspecified);
+    }
+    Ok(())
+}
+
+// Returns the number of records, up to `gcm::BATCH_LEN`, at the start of
+// `records` that each have a different nonce than the record after them.
+#[cfg(feature = "aead-aes-gcm-siv")]
+fn distinct_nonces_len<A: AsRef<[u8]>>(records: &[(Nonce, Aad<A>, &mut [u8])]) -> usize {
+    let max_len = core::cmp::min(records.len(), gcm::BATCH_LEN);
+    let mut len = 0;
+    while len < max_len {
+        if let Some((next, _, _)) = records.get(len + 1) {
+            if next.as_ref() == records[len].0.as_ref() {
+                break;
+            }
+        }
+        len += 1;
+    }
+    len
+}
+
+// Decrypts the ciphertext of `in_out`, which is followed by the tag, and
+// returns the calculated tag.
+fn open_one(key: &Key, nonce: [u8; super::NONCE_LEN], aad: &[u8], in_out: &mut [u8]) -> Tag {
+    let nonce = Nonce::assume_unique_for_key(nonce);
+    let aad = Aad::from(aad);
+    match key.algorithm.id {
+        // These read the tag from the end of their input.
+        ref id if id.is_siv() => {
+            (key.algorithm.open)(&key.inner, nonce, aad, 0, in_out, key.cpu_features)
+        }
+        _ => {
+            let ciphertext_len = in_out.len() - TAG_LEN;
+            (key.algorithm.open)(
+                &key.inner,
+                nonce,
+                aad,
+                0,
+                &mut in_out[..ciphertext_len],
+                key.cpu_features,
+            )
+        }
+    }
+}
+
+#[cfg(all(test, feature = "aead-aes-gcm-siv"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distinct_nonces_len_test() {
+        // (nonces, expected length)
+        const TESTS: &[(&[u8], usize)] = &[
+            (&[], 0),
+            (&[1], 1),
+            (&[1, 1], 0),
+            (&[1, 2, 2], 1),
+            (&[1, 2, 3], 3),
+            (&[1, 2, 1, 2], 4),
+            (&[1, 2, 3, 4, 5], 4),
+            (&[1, 2, 3, 3, 4], 2),
+        ];
+        for &(nonces, expected) in TESTS {
+            let mut in_outs = [[0u8; TAG_LEN]; 5];
+            let records = nonces
+                .iter()
+                .zip(in_outs.iter_mut())
+                .map(|(&n, in_out)| {
+                    let nonce = Nonce::assume_unique_for_key([n; crate::aead::NONCE_LEN]);
+                    (nonce, Aad::empty(), &mut in_out[..])
+                })
+                .collect::<std::vec::Vec<_>>();
+            assert_eq!(distinct_nonces_len(&records), expected);
+        }
+    }
+}
@@ -12,7 +12,10 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-use crate::{endian::*, polyfill::convert::*};
+use crate::{endian::*, zeroize::Zeroable};
+
+#[cfg(feature = "aead-chacha")]
+use crate::polyfill::convert::*;
 
 /// An array of 16 bytes that can (in the x86_64 and AAarch64 ABIs, at least)
 /// be efficiently passed by value and returned by value (i.e. in registers),
@@ -24,6 +27,8 @@ pub struct Block {
     subblocks: [u64; 2],
 }
 
+unsafe impl Zeroable for Block {}
+
 pub const BLOCK_LEN: usize = 16;
 
 impl Block {
@@ -32,6 +37,7 @@ impl Block {
         Self { subblocks: [0, 0] }
     }
 
+    #[cfg(any(test, feature = "aead-chacha"))]
     #[inline]
     pub fn from_u64_le(first: LittleEndian<u64>, second: LittleEndian<u64>) -> Self {
         Self {
@@ -102,6 +108,7 @@ impl From<&'_ [u8; BLOCK_LEN]> for Block {
     }
 }
 
+#[cfg(feature = "aead-chacha")]
 impl From_<&'_ [u8; 2 * BLOCK_LEN]> for [Block; 2] {
     #[inline]
     fn from_(bytes: &[u8; 2 * BLOCK_LEN]) -> Self {
@@ -124,6 +131,7 @@ impl AsMut<[u8; BLOCK_LEN]> for Block {
 }
 
 /// Like `AsMut`.
+#[cfg(feature = "aead-chacha")]
 impl From_<&mut [Block; 2]> for &mut [u8; 2 * BLOCK_LEN] {
     #[inline]
     fn from_(bytes: &mut [Block; 2]) -> Self {
@@ -0,0 +1,154 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Sealing keys that enforce a limit on the number of messages sealed.
+
+use super::{Aad, Algorithm, AlgorithmID, Nonce, SealingKey, Tag, NONCE_LEN};
+use crate::error;
+
+/// A sealing key that fails after it has sealed a given number of messages.
+///
+/// Every AEAD has a limit on the number of messages that may be sealed with
+/// one key before its security guarantees weaken, e.g. 2**32 for AES-GCM
+/// with random nonces per NIST SP 800-38D. `BoundedUseKey` counts every
+/// sealing operation and fails once the limit has been reached, at which
+/// point the key must be replaced.
+///
+/// A `BoundedUseKey` is constructed from the key's bytes, not from a
+/// `SealingKey`, so that it owns the only `SealingKey` for them. The limit
+/// can still be exceeded by constructing other keys from the same bytes, so
+/// the bytes shouldn't be used for anything else.
+///
+/// Each call to a sealing method uses up one message, even if sealing fails,
+/// so the count never underestimates the number of messages sealed.
+pub struct BoundedUseKey {
+    key: SealingKey,
+    remaining: u64,
+}
+
+impl BoundedUseKey {
+    /// Constructs a key that seals at most `limit` messages with the
+    /// `algorithm` key `key_bytes`.
+    ///
+    /// Fails if `SealingKey::new(algorithm, key_bytes)` would fail, or if
+    /// `algorithm` can't be used with `aead::seal_in_place()` because its
+    /// nonces aren't `NONCE_LEN` bytes long; see `recommended_limit()` for
+    /// the algorithms that can.
+    pub fn new(
+        algorithm: &'static Algorithm,
+        key_bytes: &[u8],
+        limit: u64,
+    ) -> Result<Self, error::Unspecified> {
+        if algorithm.nonce_len() != NONCE_LEN {
+            return Err(error::Unspecified);
+        }
+        Ok(Self {
+            key: SealingKey::new(algorithm, key_bytes)?,
+            remaining: limit,
+        })
+    }
+
+    /// Constructs a key that seals at most `recommended_limit(algorithm)`
+    /// messages with the `algorithm` key `key_bytes`.
+    pub fn with_recommended_limit(
+        algorithm: &'static Algorithm,
+        key_bytes: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        let limit = Self::recommended_limit(algorithm)?;
+        Self::new(algorithm, key_bytes, limit)
+    }
+
+    /// The recommended maximum number of messages to seal with one key of
+    /// `algorithm` when the nonces are chosen at random.
+    ///
+    /// This is 2**32 for `AES_128_GCM`, `AES_256_GCM`, and
+    /// `CHACHA20_POLY1305`, whose 96-bit random nonces are only unlikely to
+    /// repeat for that many messages, and 2**48 for `AES_128_GCM_SIV` and
+    /// `AES_256_GCM_SIV` (see [RFC 8452 Section 9]) and for
+    /// `AES_128_SIV_CMAC` and `AES_256_SIV_CMAC` (see
+    /// [RFC 5297 Section 7]).
+    ///
+    /// Fails for the other algorithms, `AEGIS_128L`, `AEGIS_256`,
+    /// `ASCON_128`, and `XCHACHA20_POLY1305`, which `BoundedUseKey` doesn't
+    /// support because they are sealed with their own nonce types.
+    ///
+    /// [RFC 8452 Section 9]: https://tools.ietf.org/html/rfc8452#section-9
+    /// [RFC 5297 Section 7]: https://tools.ietf.org/html/rfc5297#section-7
+    pub fn recommended_limit(algorithm: &'static Algorithm) -> Result<u64, error::Unspecified> {
+        match algorithm.id {
+            AlgorithmID::AES_128_GCM | AlgorithmID::AES_256_GCM => Ok(1 << 32),
+            #[cfg(feature = "aead-chacha")]
+            AlgorithmID::CHACHA20_POLY1305 => Ok(1 << 32),
+            #[cfg(feature = "aead-aes-gcm-siv")]
+            AlgorithmID::AES_128_GCM_SIV | AlgorithmID::AES_256_GCM_SIV => Ok(1 << 48),
+            AlgorithmID::AES_128_SIV_CMAC | AlgorithmID::AES_256_SIV_CMAC => Ok(1 << 48),
+            AlgorithmID::AEGIS_128L | AlgorithmID::AEGIS_256 | AlgorithmID::ASCON_128 => {
+                Err(error::Unspecified)
+            }
+            #[cfg(feature = "aead-chacha")]
+            AlgorithmID::XCHACHA20_POLY1305 => Err(error::Unspecified),
+        }
+    }
+
+    /// The key's AEAD algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.key.algorithm()
+    }
+
+    /// The number of messages that may still be sealed.
+    #[inline]
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Like `aead::seal_in_place()`, but fails if the limit has been
+    /// reached.
+    pub fn seal_in_place<A: AsRef<[u8]>>(
+        &mut self,
+        nonce: Nonce,
+        aad: Aad<A>,
+        in_out: &mut [u8],
+        out_suffix_capacity: usize,
+    ) -> Result<usize, error::Unspecified> {
+        self.use_once()?;
+        super::seal_in_place(&self.key, nonce, aad, in_out, out_suffix_capacity)
+    }
+
+    /// Like `aead::seal_in_place_detached()`, but fails if the limit has been
+    /// reached.
+    pub fn seal_in_place_detached<A: AsRef<[u8]>>(
+        &mut self,
+        nonce: Nonce,
+        aad: Aad<A>,
+        in_out: &mut [u8],
+    ) -> Result<Tag, error::Unspecified> {
+        self.use_once()?;
+        super::seal_in_place_detached(&self.key, nonce, aad, in_out)
+    }
+
+    fn use_once(&mut self) -> Result<(), error::Unspecified> {
+        self.remaining = self.remaining.checked_sub(1).ok_or(error::Unspecified)?;
+        Ok(())
+    }
+}
+
+impl core::fmt::Debug for BoundedUseKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("BoundedUseKey")
+            .field("key", &self.key)
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
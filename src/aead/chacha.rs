@@ -17,7 +17,7 @@ use super::{
     nonce::{self, Iv},
     Block, BLOCK_LEN,
 };
-use crate::{c, endian::*, polyfill::convert::*};
+use crate::{c, endian::*, polyfill::convert::*, zeroize::Zeroize};
 
 #[repr(C)]
 pub struct Key([Block; KEY_BLOCKS]);
@@ -28,6 +28,12 @@ impl From<&'_ [u8; KEY_LEN]> for Key {
     }
 }
 
+impl Drop for Key {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl Key {
     #[inline] // Optimize away match on `counter`.
     pub fn encrypt_in_place(&self, counter: Counter, in_out: &mut [u8]) {
@@ -129,6 +135,60 @@ impl Key {
     }
 }
 
+/// HChaCha20, as specified in [draft-irtf-cfrg-xchacha] Section 2.2.
+///
+/// This derives the subkey used by XChaCha20 from the key and the first 16
+/// bytes of the extended nonce.
+///
+/// [draft-irtf-cfrg-xchacha]:
+///     https://tools.ietf.org/html/draft-irtf-cfrg-xchacha-03
+pub fn hchacha20(key: &[u8; KEY_LEN], input: &[u8; HCHACHA20_INPUT_LEN]) -> [u8; KEY_LEN] {
+    fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+        state[a] = state[a].wrapping_add(state[b]);
+        state[d] = (state[d] ^ state[a]).rotate_left(16);
+        state[c] = state[c].wrapping_add(state[d]);
+        state[b] = (state[b] ^ state[c]).rotate_left(12);
+        state[a] = state[a].wrapping_add(state[b]);
+        state[d] = (state[d] ^ state[a]).rotate_left(8);
+        state[c] = state[c].wrapping_add(state[d]);
+        state[b] = (state[b] ^ state[c]).rotate_left(7);
+    }
+
+    fn load_le(bytes: &[u8], out: &mut [u32]) {
+        for (word, bytes) in out.iter_mut().zip(bytes.chunks(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+    }
+
+    let mut state = [0u32; 16];
+    load_le(b"expand 32-byte k", &mut state[..4]);
+    load_le(key, &mut state[4..12]);
+    load_le(input, &mut state[12..]);
+
+    for _ in 0..10 {
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    // Unlike the ChaCha20 block function, the input isn't added back in.
+    let mut out = [0u8; KEY_LEN];
+    for (out, word) in out
+        .chunks_mut(4)
+        .zip(state[..4].iter().chain(state[12..].iter()))
+    {
+        out.copy_from_slice(&word.to_le_bytes());
+    }
+    out
+}
+
+pub const HCHACHA20_INPUT_LEN: usize = 16;
+
 pub type Counter = nonce::Counter<LittleEndian<u32>>;
 
 enum CounterOrIv {
@@ -191,6 +251,22 @@ mod tests {
         });
     }
 
+    // From draft-irtf-cfrg-xchacha-03 Section 2.2.1.
+    #[test]
+    fn hchacha20_test() {
+        let mut key = [0u8; KEY_LEN];
+        for (i, b) in key.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        let input = test::from_hex("000000090000004a0000000031415927").unwrap();
+        let input: &[u8; HCHACHA20_INPUT_LEN] = input.as_slice().try_into_().unwrap();
+        assert_eq!(
+            &hchacha20(&key, input)[..],
+            &test::from_hex("82413b4227b27bfed30e42508a877d73a0f9e4d58a74a853c12ec41326d3ecdc")
+                .unwrap()[..]
+        );
+    }
+
     fn chacha20_test_case_inner(
         key: &Key,
         nonce: &[u8],
@@ -47,7 +47,8 @@ fn chacha20_poly1305_init(
     Ok(aead::KeyInner::ChaCha20Poly1305(chacha::Key::from(key)))
 }
 
-fn chacha20_poly1305_seal(
+// Also used by xchacha20_poly1305.
+pub(super) fn chacha20_poly1305_seal(
     key: &aead::KeyInner,
     nonce: Nonce,
     aad: Aad<&[u8]>,
@@ -57,7 +58,7 @@ fn chacha20_poly1305_seal(
     aead(key, nonce, aad, in_out, Direction::Sealing, cpu_features)
 }
 
-fn chacha20_poly1305_open(
+pub(super) fn chacha20_poly1305_open(
     key: &aead::KeyInner,
     nonce: Nonce,
     aad: Aad<&[u8]>,
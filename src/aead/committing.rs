@@ -0,0 +1,166 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Key-committing sealing and opening for any AEAD algorithm.
+//!
+//! AES-GCM, AES-GCM-SIV, and ChaCha20-Poly1305 are not key-committing: an
+//! attacker who chooses the keys can construct a ciphertext that opens
+//! successfully under more than one of them. This matters when a message
+//! may be opened with any of several keys, e.g. in multi-recipient formats
+//! and password-based encryption.
+//!
+//! The keys of this module derive, with HKDF-SHA256, an encryption key for
+//! the underlying algorithm and a `COMMITMENT_LEN`-byte commitment to the
+//! key. The commitment is appended to every sealed message after the tag,
+//! and opening fails unless it matches the opening key's commitment, before
+//! any decryption is done. Finding two keys with the same commitment is as
+//! hard as finding a collision in SHA-256.
+
+use super::{Aad, Algorithm, Nonce};
+use crate::{constant_time, digest, error, hkdf, policy};
+
+/// The length of the commitment that is appended to every sealed message.
+pub const COMMITMENT_LEN: usize = digest::SHA256_OUTPUT_LEN;
+
+const KEY_LABEL: &[u8] = b"ring key-committing AEAD key";
+const COMMITMENT_LABEL: &[u8] = b"ring key-committing AEAD commitment";
+
+/// A key-committing key for sealing.
+pub struct SealingKey {
+    key: super::SealingKey,
+    commitment: [u8; COMMITMENT_LEN],
+}
+
+derive_debug_via_field!(SealingKey, key);
+
+impl SealingKey {
+    /// Constructs a sealing key from `key_bytes`, which must be exactly
+    /// `algorithm.key_len()` bytes long.
+    pub fn new(
+        algorithm: &'static Algorithm,
+        key_bytes: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        let (prk, commitment) = derive(algorithm, key_bytes)?;
+        Ok(Self {
+            key: super::SealingKey::derive(algorithm, prk.expand(KEY_LABEL))?,
+            commitment,
+        })
+    }
+
+    /// The key's AEAD algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.key.algorithm()
+    }
+}
+
+/// A key-committing key for opening.
+pub struct OpeningKey {
+    key: super::OpeningKey,
+    commitment: [u8; COMMITMENT_LEN],
+}
+
+derive_debug_via_field!(OpeningKey, key);
+
+impl OpeningKey {
+    /// Constructs an opening key from `key_bytes`, which must be exactly
+    /// `algorithm.key_len()` bytes long.
+    pub fn new(
+        algorithm: &'static Algorithm,
+        key_bytes: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        let (prk, commitment) = derive(algorithm, key_bytes)?;
+        Ok(Self {
+            key: super::OpeningKey::derive(algorithm, prk.expand(KEY_LABEL))?,
+            commitment,
+        })
+    }
+
+    /// The key's AEAD algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.key.algorithm()
+    }
+}
+
+fn derive(
+    algorithm: &'static Algorithm,
+    key_bytes: &[u8],
+) -> Result<(hkdf::Prk, [u8; COMMITMENT_LEN]), error::Unspecified> {
+    if key_bytes.len() != algorithm.key_len() {
+        return Err(error::Unspecified);
+    }
+    policy::check(algorithm.fips_approved())?;
+    let prk = hkdf::Salt::new(&digest::SHA256, &[]).extract(key_bytes);
+    let mut commitment = [0u8; COMMITMENT_LEN];
+    prk.fill(COMMITMENT_LABEL, &mut commitment)?;
+    Ok((prk, commitment))
+}
+
+/// Encrypts and signs (“seals”) data in place, and appends the key's
+/// commitment.
+///
+/// This is like `aead::seal_in_place()`, except that `out_suffix_capacity`
+/// must be at least `key.algorithm().tag_len() + COMMITMENT_LEN`. The output
+/// is the ciphertext, followed by the tag, followed by the commitment.
+pub fn seal_in_place<A: AsRef<[u8]>>(
+    key: &SealingKey,
+    nonce: Nonce,
+    aad: Aad<A>,
+    in_out: &mut [u8],
+    out_suffix_capacity: usize,
+) -> Result<usize, error::Unspecified> {
+    let in_out_len = in_out
+        .len()
+        .checked_sub(COMMITMENT_LEN)
+        .ok_or(error::Unspecified)?;
+    let out_suffix_capacity = out_suffix_capacity
+        .checked_sub(COMMITMENT_LEN)
+        .ok_or(error::Unspecified)?;
+    let out_len = super::seal_in_place(
+        &key.key,
+        nonce,
+        aad,
+        &mut in_out[..in_out_len],
+        out_suffix_capacity,
+    )?;
+    in_out[out_len..(out_len + COMMITMENT_LEN)].copy_from_slice(&key.commitment);
+    Ok(out_len + COMMITMENT_LEN)
+}
+
+/// Checks the commitment, then authenticates and decrypts (“opens”) data in
+/// place.
+///
+/// This is like `aead::open_in_place()`, except that the input is the
+/// ciphertext, followed by the tag, followed by the commitment. Opening
+/// fails without decrypting anything if the commitment isn't the key's.
+pub fn open_in_place<'a, A: AsRef<[u8]>>(
+    key: &OpeningKey,
+    nonce: Nonce,
+    aad: Aad<A>,
+    in_prefix_len: usize,
+    ciphertext_and_tag_and_commitment_modified_in_place: &'a mut [u8],
+) -> Result<&'a mut [u8], error::Unspecified> {
+    let in_out = ciphertext_and_tag_and_commitment_modified_in_place;
+    let commitment_index = in_out
+        .len()
+        .checked_sub(COMMITMENT_LEN)
+        .ok_or(error::Unspecified)?;
+    if commitment_index < in_prefix_len {
+        return Err(error::Unspecified);
+    }
+    let (in_out, commitment) = in_out.split_at_mut(commitment_index);
+    constant_time::verify_slices_are_equal(commitment, &key.commitment)?;
+    super::open_in_place(&key.key, nonce, aad, in_prefix_len, in_out)
+}
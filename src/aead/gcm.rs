@@ -15,54 +15,73 @@
 use super::{Block, BLOCK_LEN};
 use crate::cpu;
 use crate::c;
+use crate::zeroize::{Zeroable, Zeroize};
 
-#[repr(transparent)]
-pub struct Key(GCM128_KEY);
+pub struct Key {
+    inner: GCM128_KEY,
+
+    // Used by `update_blocks_batch`.
+    h_be: Block,
+}
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        self.h_be.zeroize();
+    }
+}
 
 impl Key {
-    pub(super) fn new(mut h_be: Block, cpu_features: cpu::Features) -> Self {
+    pub(crate) fn new(mut h_be: Block, cpu_features: cpu::Features) -> Self {
         let h = h_be.u64s_be_to_native();
 
-        let mut key = Self(GCM128_KEY {
-            Htable: [u128 { hi: 0, lo: 0 }; GCM128_HTABLE_LEN],
-        });
+        let mut key = Self {
+            inner: GCM128_KEY {
+                Htable: [u128 { hi: 0, lo: 0 }; GCM128_HTABLE_LEN],
+            },
+            h_be,
+        };
 
         match detect_implementation(cpu_features) {
-            #[cfg(target_arch = "x86_64")]
+            #[cfg(all(target_arch = "x86_64", not(feature = "intrinsics")))]
             Implementation::CLMUL if has_avx_movbe(cpu_features) => {
                 extern "C" {
-                    fn GFp_gcm_init_avx(key: &mut Key, h: &[u64; 2]);
+                    fn GFp_gcm_init_avx(key: &mut GCM128_KEY, h: &[u64; 2]);
                 }
                 unsafe {
-                    GFp_gcm_init_avx(&mut key, &h);
+                    GFp_gcm_init_avx(&mut key.inner, &h);
                 }
             }
 
+            // `Context` multiplies by `H` directly, so no table is needed.
+            #[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
+            Implementation::CLMUL => {}
+
+            #[cfg(not(all(feature = "intrinsics", target_arch = "x86_64")))]
             Implementation::CLMUL => {
                 extern "C" {
-                    fn GFp_gcm_init_clmul(key: &mut Key, h: &[u64; 2]);
+                    fn GFp_gcm_init_clmul(key: &mut GCM128_KEY, h: &[u64; 2]);
                 }
                 unsafe {
-                    GFp_gcm_init_clmul(&mut key, &h);
+                    GFp_gcm_init_clmul(&mut key.inner, &h);
                 }
             }
 
             #[cfg(any(target_arch = "arm"))]
             Implementation::NEON => {
                 extern "C" {
-                    fn GFp_gcm_init_neon(key: &mut Key, h: &[u64; 2]);
+                    fn GFp_gcm_init_neon(key: &mut GCM128_KEY, h: &[u64; 2]);
                 }
                 unsafe {
-                    GFp_gcm_init_neon(&mut key, &h);
+                    GFp_gcm_init_neon(&mut key.inner, &h);
                 }
             }
 
             Implementation::Fallback => {
                 extern "C" {
-                    fn GFp_gcm_init_4bit(key: &mut Key, h: &[u64; 2]);
+                    fn GFp_gcm_init_4bit(key: &mut GCM128_KEY, h: &[u64; 2]);
                 }
                 unsafe {
-                    GFp_gcm_init_4bit(&mut key, &h);
+                    GFp_gcm_init_4bit(&mut key.inner, &h);
                 }
             }
         }
@@ -82,8 +101,8 @@ impl Context {
         let ctx = Context {
             inner: GCM128_CONTEXT {
                 Xi: Block::zero(),
-                H_unused: Block::zero(),
-                key: key.0.clone(),
+                H: key.h_be,
+                key: key.inner.clone(),
             },
             cpu_features,
         };
@@ -98,7 +117,7 @@ impl Context {
         let key_aliasing: *const GCM128_KEY = &self.inner.key;
 
         match detect_implementation(self.cpu_features) {
-            #[cfg(target_arch = "x86_64")]
+            #[cfg(all(target_arch = "x86_64", not(feature = "intrinsics")))]
             Implementation::CLMUL if has_avx_movbe(self.cpu_features) => {
                 extern "C" {
                     fn GFp_gcm_ghash_avx(
@@ -113,6 +132,12 @@ impl Context {
                 }
             }
 
+            #[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
+            Implementation::CLMUL => unsafe {
+                clmul::ghash(&mut self.inner.Xi, &self.inner.H, input);
+            },
+
+            #[cfg(not(all(feature = "intrinsics", target_arch = "x86_64")))]
             Implementation::CLMUL => {
                 extern "C" {
                     fn GFp_gcm_ghash_clmul(
@@ -164,6 +189,12 @@ impl Context {
         let key_aliasing: *const GCM128_KEY = &self.inner.key;
 
         match detect_implementation(self.cpu_features) {
+            #[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
+            Implementation::CLMUL => unsafe {
+                clmul::gmult(&mut self.inner.Xi, &self.inner.H);
+            },
+
+            #[cfg(not(all(feature = "intrinsics", target_arch = "x86_64")))]
             Implementation::CLMUL => {
                 extern "C" {
                     fn GFp_gcm_gmult_clmul(ctx: &mut Context, Htable: *const GCM128_KEY);
@@ -200,14 +231,19 @@ impl Context {
         self.inner.Xi = self.inner.Xi.reverse();
     }
 
-    pub(super) fn pre_finish<F>(self, f: F) -> super::Tag
+    /// The GHASH of the blocks so far.
+    pub(crate) fn value(&self) -> Block {
+        self.inner.Xi
+    }
+
+    pub(super) fn pre_finish<F>(&mut self, f: F) -> super::Tag
     where
         F: FnOnce(Block) -> super::Tag,
     {
         f(self.inner.Xi)
     }
 
-    #[cfg(target_arch = "x86_64")]
+    #[cfg(all(target_arch = "x86_64", not(feature = "intrinsics")))]
     pub(super) fn is_avx2(&self, cpu_features: cpu::Features) -> bool {
         match detect_implementation(cpu_features) {
             Implementation::CLMUL => has_avx_movbe(self.cpu_features),
@@ -216,6 +252,237 @@ impl Context {
     }
 }
 
+/// Updates each of `contexts` with the whole blocks of the corresponding
+/// element of `inputs`.
+///
+/// This is equivalent to calling `update_blocks` on each context, except
+/// that `BATCH_LEN` messages at a time are authenticated in lockstep, so that
+/// the multiplications for different messages overlap instead of each
+/// waiting for the previous one to finish. This matters most when there are
+/// many short messages, where the per-message work can't be aggregated.
+pub(super) fn update_blocks_batch(contexts: &mut [Context], inputs: &[&[u8]]) {
+    update_blocks_batch_(contexts, inputs)
+}
+
+/// The number of messages that `update_blocks_batch` authenticates in
+/// lockstep.
+pub(super) const BATCH_LEN: usize = 4;
+
+/// Returns `BATCH_LEN` new contexts for `update_blocks_batch`.
+pub(super) fn new_batch(key: &Key, cpu_features: cpu::Features) -> [Context; BATCH_LEN] {
+    [
+        Context::new(key, cpu_features),
+        Context::new(key, cpu_features),
+        Context::new(key, cpu_features),
+        Context::new(key, cpu_features),
+    ]
+}
+
+trait BatchContext {
+    /// Whether each block is byte-reversed before it is input to GHASH.
+    const REVERSE_BLOCKS: bool;
+
+    fn ghash_context(&mut self) -> &mut Context;
+    fn update_blocks(&mut self, input: &[u8]);
+}
+
+impl BatchContext for Context {
+    const REVERSE_BLOCKS: bool = false;
+
+    fn ghash_context(&mut self) -> &mut Context {
+        self
+    }
+
+    fn update_blocks(&mut self, input: &[u8]) {
+        Context::update_blocks(self, input)
+    }
+}
+
+fn update_blocks_batch_<C: BatchContext>(contexts: &mut [C], inputs: &[&[u8]]) {
+    assert_eq!(contexts.len(), inputs.len());
+
+    let mut groups = contexts.chunks_exact_mut(BATCH_LEN);
+    let mut group_inputs = inputs.chunks_exact(BATCH_LEN);
+    for (group, inputs) in (&mut groups).zip(&mut group_inputs) {
+        let lockstep_len = inputs.iter().map(|input| input.len()).min().unwrap_or(0);
+        let done = if lockstep_len > 0 {
+            update_blocks_interleaved(group, inputs, lockstep_len)
+        } else {
+            0
+        };
+        for (ctx, input) in group.iter_mut().zip(inputs) {
+            if input.len() > done {
+                ctx.update_blocks(&input[done..]);
+            }
+        }
+    }
+
+    let remainder = groups.into_remainder();
+    for (ctx, input) in remainder.iter_mut().zip(group_inputs.remainder()) {
+        if !input.is_empty() {
+            ctx.update_blocks(input);
+        }
+    }
+}
+
+// Returns the number of bytes of each input that were processed.
+#[cfg(target_arch = "x86_64")]
+fn update_blocks_interleaved<C: BatchContext>(
+    group: &mut [C],
+    inputs: &[&[u8]],
+    len: usize,
+) -> usize {
+    debug_assert_eq!(group.len(), BATCH_LEN);
+    debug_assert_eq!(len % BLOCK_LEN, 0);
+
+    let cpu_features = group[0].ghash_context().cpu_features;
+    if !cpu::intel::PCLMULQDQ.available(cpu_features) || !cpu::intel::SSSE3.available(cpu_features)
+    {
+        return 0;
+    }
+
+    let mut xis = [Block::zero(); BATCH_LEN];
+    let mut hs = [Block::zero(); BATCH_LEN];
+    for ((xi, h), ctx) in xis.iter_mut().zip(hs.iter_mut()).zip(group.iter_mut()) {
+        let ctx = ctx.ghash_context();
+        *xi = ctx.inner.Xi;
+        *h = ctx.inner.H;
+    }
+    unsafe {
+        clmul::ghash_interleaved(&mut xis, &hs, inputs, len, C::REVERSE_BLOCKS);
+    }
+    for (ctx, xi) in group.iter_mut().zip(xis.iter()) {
+        ctx.ghash_context().inner.Xi = *xi;
+    }
+
+    len
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn update_blocks_interleaved<C: BatchContext>(_: &mut [C], _: &[&[u8]], _: usize) -> usize {
+    0 // This doesn't process any of the input so it all remains.
+}
+
+#[cfg(target_arch = "x86_64")]
+mod clmul {
+    use super::{Block, BATCH_LEN, BLOCK_LEN};
+    use core::arch::x86_64::*;
+
+    // Blocks and field elements are byte-swapped when they are loaded so
+    // that the bit-reflected multiplication below can use them directly.
+
+    #[cfg(feature = "intrinsics")]
+    #[target_feature(enable = "pclmulqdq,ssse3")]
+    pub(super) unsafe fn ghash(xi: &mut Block, h: &Block, input: &[u8]) {
+        let bswap = _mm_set_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+        let h = _mm_shuffle_epi8(load(h.as_ref()), bswap);
+        let mut x = _mm_shuffle_epi8(load(xi.as_ref()), bswap);
+        for block in input.chunks_exact(BLOCK_LEN) {
+            let block = _mm_shuffle_epi8(load(block), bswap);
+            x = gfmul(_mm_xor_si128(x, block), h);
+        }
+        store(xi, _mm_shuffle_epi8(x, bswap));
+    }
+
+    #[cfg(feature = "intrinsics")]
+    #[target_feature(enable = "pclmulqdq,ssse3")]
+    pub(super) unsafe fn gmult(xi: &mut Block, h: &Block) {
+        let bswap = _mm_set_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+        let h = _mm_shuffle_epi8(load(h.as_ref()), bswap);
+        let x = _mm_shuffle_epi8(load(xi.as_ref()), bswap);
+        store(xi, _mm_shuffle_epi8(gfmul(x, h), bswap));
+    }
+
+    #[target_feature(enable = "pclmulqdq,ssse3")]
+    pub(super) unsafe fn ghash_interleaved(
+        xis: &mut [Block; BATCH_LEN],
+        hs: &[Block; BATCH_LEN],
+        inputs: &[&[u8]],
+        len: usize,
+        reverse_blocks: bool,
+    ) {
+        let bswap = _mm_set_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+        // Byte-swapping a byte-reversed block is a no-op.
+        let input_mask = if reverse_blocks {
+            _mm_set_epi8(15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0)
+        } else {
+            bswap
+        };
+
+        let mut x = [_mm_setzero_si128(); BATCH_LEN];
+        let mut h = [_mm_setzero_si128(); BATCH_LEN];
+        for i in 0..BATCH_LEN {
+            x[i] = _mm_shuffle_epi8(load(xis[i].as_ref()), bswap);
+            h[i] = _mm_shuffle_epi8(load(hs[i].as_ref()), bswap);
+        }
+
+        let mut offset = 0;
+        while offset < len {
+            for i in 0..BATCH_LEN {
+                let block = &inputs[i][offset..][..BLOCK_LEN];
+                let block = _mm_shuffle_epi8(load(block), input_mask);
+                x[i] = gfmul(_mm_xor_si128(x[i], block), h[i]);
+            }
+            offset += BLOCK_LEN;
+        }
+
+        for i in 0..BATCH_LEN {
+            store(&mut xis[i], _mm_shuffle_epi8(x[i], bswap));
+        }
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn load(bytes: &[u8]) -> __m128i {
+        debug_assert_eq!(bytes.len(), BLOCK_LEN);
+        _mm_loadu_si128(bytes.as_ptr() as *const __m128i)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn store(block: &mut Block, value: __m128i) {
+        let bytes: &mut [u8; BLOCK_LEN] = block.as_mut();
+        _mm_storeu_si128(bytes.as_mut_ptr() as *mut __m128i, value)
+    }
+
+    // Multiplication in GF(2^128) with the GHASH reduction polynomial, from
+    // Figure 5 of "Intel Carry-Less Multiplication Instruction and its Usage
+    // for Computing the GCM Mode", Revision 2.02.
+    #[inline]
+    #[target_feature(enable = "pclmulqdq")]
+    unsafe fn gfmul(a: __m128i, b: __m128i) -> __m128i {
+        // Schoolbook multiplication, giving the 256-bit product `hi:lo`.
+        let mut lo = _mm_clmulepi64_si128(a, b, 0x00);
+        let mid = _mm_xor_si128(
+            _mm_clmulepi64_si128(a, b, 0x10),
+            _mm_clmulepi64_si128(a, b, 0x01),
+        );
+        let mut hi = _mm_clmulepi64_si128(a, b, 0x11);
+        lo = _mm_xor_si128(lo, _mm_slli_si128(mid, 8));
+        hi = _mm_xor_si128(hi, _mm_srli_si128(mid, 8));
+
+        // Shift the product left by one bit because the operands are
+        // bit-reflected.
+        let lo_carry = _mm_srli_epi32(lo, 31);
+        let hi_carry = _mm_srli_epi32(hi, 31);
+        lo = _mm_or_si128(_mm_slli_epi32(lo, 1), _mm_slli_si128(lo_carry, 4));
+        hi = _mm_or_si128(_mm_slli_epi32(hi, 1), _mm_slli_si128(hi_carry, 4));
+        hi = _mm_or_si128(hi, _mm_srli_si128(lo_carry, 12));
+
+        // Reduce modulo x^128 + x^7 + x^2 + x + 1.
+        let t = _mm_xor_si128(
+            _mm_xor_si128(_mm_slli_epi32(lo, 31), _mm_slli_epi32(lo, 30)),
+            _mm_slli_epi32(lo, 25),
+        );
+        lo = _mm_xor_si128(lo, _mm_slli_si128(t, 12));
+        let u = _mm_xor_si128(
+            _mm_xor_si128(_mm_srli_epi32(lo, 1), _mm_srli_epi32(lo, 2)),
+            _mm_xor_si128(_mm_srli_epi32(lo, 7), _mm_srli_si128(t, 4)),
+        );
+        _mm_xor_si128(hi, _mm_xor_si128(lo, u))
+    }
+}
+
 // Keep in sync with `GCM128_KEY` in modes/internal.h.
 #[derive(Clone)]
 #[repr(C, align(16))]
@@ -223,6 +490,12 @@ struct GCM128_KEY {
     Htable: [u128; GCM128_HTABLE_LEN],
 }
 
+impl Drop for GCM128_KEY {
+    fn drop(&mut self) {
+        self.Htable.zeroize();
+    }
+}
+
 #[derive(Clone, Copy)]
 #[repr(C)]
 struct u128 {
@@ -230,16 +503,25 @@ struct u128 {
     lo: u64,
 }
 
+unsafe impl Zeroable for u128 {}
+
 const GCM128_HTABLE_LEN: usize = 16;
 
 // Keep in sync with `GCM128_CONTEXT` in modes/internal.h.
 #[repr(C, align(16))]
 struct GCM128_CONTEXT {
     Xi: Block,
-    H_unused: Block,
+    H: Block, // Not used by the assembly language code.
     key: GCM128_KEY,
 }
 
+impl Drop for GCM128_CONTEXT {
+    fn drop(&mut self) {
+        self.Xi.zeroize();
+        self.H.zeroize();
+    }
+}
+
 enum Implementation {
     CLMUL,
 
@@ -267,7 +549,9 @@ fn detect_implementation(cpu: cpu::Features) -> Implementation {
     Implementation::Fallback
 }
 
-#[cfg(target_arch = "x86_64")]
+// The AVX GHASH and integrated AES-GCM code only exist in assembly language
+// form, so they aren't used with the `intrinsics` feature.
+#[cfg(all(target_arch = "x86_64", not(feature = "intrinsics")))]
 fn has_avx_movbe(cpu_features: cpu::Features) -> bool {
     return cpu::intel::AVX.available(cpu_features) && cpu::intel::MOVBE.available(cpu_features);
 }
@@ -288,7 +572,7 @@ impl PolyValContext {
     // ByteReverse(X_n))).
     //
     // See https://tools.ietf.org/html/draft-irtf-cfrg-gcmsiv-02#appendix-A.
-    pub(super) fn new(auth_key: &Block, cpu_features: cpu::Features) -> PolyValContext {
+    pub(crate) fn new(auth_key: &Block, cpu_features: cpu::Features) -> PolyValContext {
         let mut auth_key = auth_key.u64s_native();
         PolyValContext::reverse_and_mulX_ghash(&mut auth_key);
 
@@ -322,14 +606,19 @@ impl PolyValContext {
     pub fn update_blocks(&mut self, input: &[u8]) {
 
         let mut in_len = input.len();
-        // Allocate 32 * 16 bytes for reversed
-        const REVERSED_SIZE: usize = 32 * 16;
+        // The input is byte-reversed into this buffer a chunk at a time. With
+        // the `small_stack` feature the chunks are a single block, which uses
+        // 496 fewer bytes of stack but is slower.
+        #[cfg(not(feature = "small_stack"))]
+        const REVERSED_SIZE: usize = 32 * BLOCK_LEN;
+        #[cfg(feature = "small_stack")]
+        const REVERSED_SIZE: usize = BLOCK_LEN;
         let mut reversed = [0u8; REVERSED_SIZE];
 
         let mut start = 0;
         while start < input.len() {
             let mut todo = in_len;
-            todo = std::cmp::min(todo, REVERSED_SIZE);
+            todo = core::cmp::min(todo, REVERSED_SIZE);
 
             let reversed = &mut reversed[0..todo];
             reversed.copy_from_slice(&input[start..todo + start]);
@@ -342,8 +631,87 @@ impl PolyValContext {
         }
     }
 
+    /// Like `update_blocks_batch`, for POLYVAL.
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    pub(super) fn update_blocks_batch(contexts: &mut [PolyValContext], inputs: &[&[u8]]) {
+        update_blocks_batch_(contexts, inputs)
+    }
+
     pub fn pre_finish(&mut self) -> Block {
         self.gcm_ctx.reverse();
         self.gcm_ctx.inner.Xi
     }
-}
\ No newline at end of file
+
+    /// Starts over with the same key.
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    pub(super) fn reset(&mut self) {
+        self.gcm_ctx.inner.Xi = Block::zero();
+    }
+}
+
+impl BatchContext for PolyValContext {
+    const REVERSE_BLOCKS: bool = true;
+
+    fn ghash_context(&mut self) -> &mut Context {
+        &mut self.gcm_ctx
+    }
+
+    fn update_blocks(&mut self, input: &[u8]) {
+        PolyValContext::update_blocks(self, input)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    // The batch functions must give the same results as updating each
+    // context separately, for any mix of message lengths.
+    #[test]
+    fn update_blocks_batch_test() {
+        const LENS_IN_BLOCKS: &[usize] = &[3, 2, 4, 3, 5, 9, 2, 0, 6, 5, 3];
+
+        let cpu_features = cpu::features();
+        let h = Block::from(&[0x66; BLOCK_LEN]);
+        let inputs = LENS_IN_BLOCKS
+            .iter()
+            .enumerate()
+            .map(|(i, blocks)| {
+                (0..(blocks * BLOCK_LEN))
+                    .map(|j| (i * 251 + j * 31) as u8)
+                    .collect::<Vec<u8>>()
+            })
+            .collect::<Vec<_>>();
+        let inputs = inputs.iter().map(|input| &input[..]).collect::<Vec<_>>();
+
+        let key = Key::new(h, cpu_features);
+        let mut batch = inputs
+            .iter()
+            .map(|_| Context::new(&key, cpu_features))
+            .collect::<Vec<_>>();
+        update_blocks_batch(&mut batch, &inputs);
+        for (batch_ctx, input) in batch.iter().zip(inputs.iter()) {
+            let mut ctx = Context::new(&key, cpu_features);
+            if !input.is_empty() {
+                ctx.update_blocks(input);
+            }
+            assert_eq!(batch_ctx.inner.Xi.as_ref(), ctx.inner.Xi.as_ref());
+        }
+
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        {
+            let mut batch = inputs
+                .iter()
+                .map(|_| PolyValContext::new(&h, cpu_features))
+                .collect::<Vec<_>>();
+            PolyValContext::update_blocks_batch(&mut batch, &inputs);
+            for (batch_ctx, input) in batch.iter_mut().zip(inputs.iter()) {
+                let mut ctx = PolyValContext::new(&h, cpu_features);
+                if !input.is_empty() {
+                    ctx.update_blocks(input);
+                }
+                assert_eq!(batch_ctx.pre_finish().as_ref(), ctx.pre_finish().as_ref());
+            }
+        }
+    }
+}
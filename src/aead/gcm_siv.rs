@@ -18,12 +18,13 @@ use crate::aead::{
     block::Block,
     block::BLOCK_LEN,
     gcm::PolyValContext,
-    Nonce, TAG_LEN,
+    Nonce, NONCE_LEN, TAG_LEN,
 };
 
-use crate::{bits::BitLength, cpu, endian::BigEndian, endian::LittleEndian, error};
-use std::convert::TryInto;
-use std::mem::MaybeUninit;
+use crate::{
+    bits::BitLength, c, cpu, endian::BigEndian, endian::LittleEndian, error, zeroize::Zeroize,
+};
+use core::convert::TryInto;
 
 #[repr(C, align(16))]
 pub struct Key {
@@ -35,11 +36,61 @@ pub struct Key {
 #[repr(C, align(16))]
 pub struct AES_ASM_KEY(pub [u8; 15 * 16]);
 
+impl AES_ASM_KEY {
+    // The assembly language functions write the whole key schedule, but it
+    // is zeroed first so that it is never uninitialized.
+    fn zero() -> Self {
+        AES_ASM_KEY([0u8; 15 * 16])
+    }
+
+    /// Expands the message-encryption key `enc_key`.
+    pub(super) fn expand_from(enc_key: &Encryption_Key, variant: Variant) -> Self {
+        extern "C" {
+            fn aes128gcmsiv_aes_ks(enc_key: *const Encryption_Key, expanded_key: *mut AES_ASM_KEY);
+            fn aes256gcmsiv_aes_ks(enc_key: *const Encryption_Key, expanded_key: *mut AES_ASM_KEY);
+        }
+        let mut expanded_key = Self::zero();
+        match variant {
+            AES_128 => unsafe { aes128gcmsiv_aes_ks(enc_key, &mut expanded_key) },
+            AES_256 => unsafe { aes256gcmsiv_aes_ks(enc_key, &mut expanded_key) },
+        }
+        expanded_key
+    }
+
+    /// Expands the message-encryption key `enc_key` and encrypts `tag` in
+    /// place with it, in one pass.
+    pub(super) fn expand_from_and_encrypt(
+        enc_key: &Encryption_Key,
+        variant: Variant,
+        tag: &mut Out_Tag,
+    ) -> Self {
+        extern "C" {
+            fn aes128gcmsiv_aes_ks_enc_x1(
+                input: *const Out_Tag,
+                output: *mut Out_Tag,
+                expanded_key: *mut AES_ASM_KEY,
+                enc_key: *const Encryption_Key,
+            );
+            fn aes256gcmsiv_aes_ks_enc_x1(
+                input: *const Out_Tag,
+                output: *mut Out_Tag,
+                expanded_key: *mut AES_ASM_KEY,
+                enc_key: *const Encryption_Key,
+            );
+        }
+        let mut expanded_key = Self::zero();
+        let tag: *mut Out_Tag = tag;
+        match variant {
+            AES_128 => unsafe { aes128gcmsiv_aes_ks_enc_x1(tag, tag, &mut expanded_key, enc_key) },
+            AES_256 => unsafe { aes256gcmsiv_aes_ks_enc_x1(tag, tag, &mut expanded_key, enc_key) },
+        }
+        expanded_key
+    }
+}
+
 impl Drop for AES_ASM_KEY {
     fn drop(&mut self) {
-        for byte in self.0.iter_mut() {
-            *byte = 0;
-        }
+        self.0.zeroize();
     }
 }
 
@@ -48,9 +99,7 @@ pub struct KeyMaterial([u64; 12]);
 
 impl Drop for KeyMaterial {
     fn drop(&mut self) {
-        for byte in self.0.iter_mut() {
-            *byte = 0;
-        }
+        self.0.zeroize();
     }
 }
 
@@ -70,38 +119,26 @@ impl Key {
         let mut key;
 
         match detect_implementation(cpu_features) {
-
             Implementation::AVX_AESNI => {
-
-                let mut aes_asm_key = MaybeUninit::<AES_ASM_KEY>::uninit();
-
-                match variant {
-                    AES_128 => {
-                        extern "C" {
-                            fn aes128gcmsiv_aes_ks(
-                                user_key: *const u8,
-                                expanded_key: *mut AES_ASM_KEY,
-                            );
-                        }
-                        unsafe {
-                            aes128gcmsiv_aes_ks(user_key.as_ptr(), aes_asm_key.as_mut_ptr());
-                        }
-                    }
-                    AES_256 => {
-                        extern "C" {
-                            fn aes256gcmsiv_aes_ks(
-                                user_key: *const u8,
-                                expanded_key: *mut AES_ASM_KEY,
-                            );
-                        }
-                        unsafe {
-                            aes256gcmsiv_aes_ks(user_key.as_ptr(), aes_asm_key.as_mut_ptr());
-                        }
-                    }
-                }
-                let aes_asm_key = unsafe { aes_asm_key.assume_init() };
                 key = Key {
-                    aes_asm_key: Some(aes_asm_key),
+                    aes_asm_key: Some(set_encrypt_key_asm(user_key, variant)),
+                    aes_key: None,
+                    variant: variant.clone(),
+                };
+            }
+            // The `VAES_VPCLMULQDQ` implementation uses the same key schedule.
+            #[cfg(target_arch = "x86_64")]
+            Implementation::VAES_VPCLMULQDQ => {
+                key = Key {
+                    aes_asm_key: Some(set_encrypt_key_asm(user_key, variant)),
+                    aes_key: None,
+                    variant,
+                };
+            }
+            #[cfg(target_arch = "aarch64")]
+            Implementation::NEON_PMULL => {
+                key = Key {
+                    aes_asm_key: Some(unsafe { neon::set_encrypt_key(user_key, variant) }),
                     aes_key: None,
                     variant: variant.clone(),
                 };
@@ -116,6 +153,120 @@ impl Key {
         }
         Ok(key)
     }
+
+    /// Like `new`, except that the portable implementation is always used.
+    #[cfg(feature = "testing-internals")]
+    pub(super) fn new_portable(
+        user_key: &[u8],
+        variant: Variant,
+        cpu_features: cpu::Features,
+    ) -> Result<Self, error::Unspecified> {
+        Ok(Key {
+            aes_asm_key: None,
+            aes_key: Some(aes::Key::new(user_key, variant, cpu_features)?),
+            variant,
+        })
+    }
+
+    /// Writes the expanded key to `out`: a byte identifying its layout, three
+    /// zero bytes, the number of rounds as stored by the implementation (as a
+    /// little-endian `u32`), and the key schedule.
+    ///
+    /// The layout depends on the implementation, which depends on the CPU.
+    pub(super) fn serialize(&self, cpu_features: cpu::Features, out: &mut [u8; SERIALIZED_LEN]) {
+        for byte in out.iter_mut() {
+            *byte = 0;
+        }
+        out[0] = schedule_layout(cpu_features);
+        let (rounds, schedule) = out[4..].split_at_mut(4);
+        let schedule: &mut [u8; aes::SCHEDULE_LEN] = schedule.try_into().unwrap();
+        match (&self.aes_asm_key, &self.aes_key) {
+            (Some(aes_asm_key), _) => schedule.copy_from_slice(&aes_asm_key.0),
+            (None, Some(aes_key)) => {
+                let r = aes_key.write_schedule(schedule);
+                rounds.copy_from_slice(&r.to_le_bytes());
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+
+    /// Reconstructs a key written by `serialize()`.
+    ///
+    /// Fails if the key was written by a different implementation.
+    pub(super) fn deserialize(
+        serialized: &[u8; SERIALIZED_LEN],
+        variant: Variant,
+        cpu_features: cpu::Features,
+    ) -> Result<Self, error::Unspecified> {
+        if serialized[0] != schedule_layout(cpu_features) || serialized[1..4] != [0, 0, 0] {
+            return Err(error::Unspecified);
+        }
+        let rounds = u32::from_le_bytes(serialized[4..8].try_into().unwrap());
+        let schedule: &[u8; aes::SCHEDULE_LEN] = serialized[8..].try_into().unwrap();
+        match detect_implementation(cpu_features) {
+            Implementation::FALLBACK => Ok(Key {
+                aes_asm_key: None,
+                aes_key: Some(aes::Key::from_schedule(
+                    rounds,
+                    schedule,
+                    variant,
+                    cpu_features,
+                )?),
+                variant,
+            }),
+            _ => {
+                if rounds != 0 {
+                    return Err(error::Unspecified);
+                }
+                let mut aes_asm_key = AES_ASM_KEY([0u8; 15 * 16]);
+                aes_asm_key.0.copy_from_slice(schedule);
+                Ok(Key {
+                    aes_asm_key: Some(aes_asm_key),
+                    aes_key: None,
+                    variant,
+                })
+            }
+        }
+    }
+}
+
+// Expands `user_key` with the key schedule of the `AVX_AESNI` and
+// `VAES_VPCLMULQDQ` implementations.
+fn set_encrypt_key_asm(user_key: &[u8], variant: Variant) -> AES_ASM_KEY {
+    let mut aes_asm_key = AES_ASM_KEY::zero();
+    match variant {
+        AES_128 => {
+            extern "C" {
+                fn aes128gcmsiv_aes_ks(user_key: *const u8, expanded_key: *mut AES_ASM_KEY);
+            }
+            unsafe {
+                aes128gcmsiv_aes_ks(user_key.as_ptr(), &mut aes_asm_key);
+            }
+        }
+        AES_256 => {
+            extern "C" {
+                fn aes256gcmsiv_aes_ks(user_key: *const u8, expanded_key: *mut AES_ASM_KEY);
+            }
+            unsafe {
+                aes256gcmsiv_aes_ks(user_key.as_ptr(), &mut aes_asm_key);
+            }
+        }
+    }
+    aes_asm_key
+}
+
+/// The length of a key written by `Key::serialize()`.
+pub(super) const SERIALIZED_LEN: usize = 8 + aes::SCHEDULE_LEN;
+
+fn schedule_layout(cpu_features: cpu::Features) -> u8 {
+    match detect_implementation(cpu_features) {
+        Implementation::AVX_AESNI => 1,
+        #[cfg(target_arch = "x86_64")]
+        Implementation::VAES_VPCLMULQDQ => 1,
+        #[cfg(target_arch = "aarch64")]
+        Implementation::NEON_PMULL => 2,
+        Implementation::FALLBACK => 0x10 | aes::Key::schedule_layout(cpu_features),
+    }
 }
 
 pub struct GcmSivAsmContext;
@@ -125,16 +276,11 @@ impl GcmSivAsmContext {
         GcmSivAsmContext
     }
 
-    pub fn kdf(
-        &self,
-        nonce: &Nonce,
-        key: &Key,
-        auth_key: &mut MaybeUninit<Auth_Key>,
-        enc_key: &mut MaybeUninit<Encryption_Key>,
-    ) {
+    /// Derives the POLYVAL key and the message-encryption key for `nonce`.
+    pub fn kdf(&self, nonce: &Nonce, key: &Key) -> (Auth_Key, Encryption_Key) {
         let aes_asm_key = key.aes_asm_key.as_ref().expect("Missing AES ASM KEY");
 
-        let mut key_material = MaybeUninit::<KeyMaterial>::uninit();
+        let mut key_material = KeyMaterial([0u64; 12]);
         let nonce = nonce.as_ref();
         let nonce = Nonce::try_assume_unique_for_key(nonce).expect("Nonce expected");
         let counter: nonce::Counter<BigEndian<u32>> = nonce::Counter::zero(nonce);
@@ -149,7 +295,7 @@ impl GcmSivAsmContext {
                     );
                 }
                 unsafe {
-                    aes128gcmsiv_kdf(&counter, key_material.as_mut_ptr(), aes_asm_key);
+                    aes128gcmsiv_kdf(&counter, &mut key_material, aes_asm_key);
                 }
             }
             AES_256 => {
@@ -161,22 +307,26 @@ impl GcmSivAsmContext {
                     );
                 }
                 unsafe {
-                    aes256gcmsiv_kdf(&counter, key_material.as_mut_ptr(), aes_asm_key);
+                    aes256gcmsiv_kdf(&counter, &mut key_material, aes_asm_key);
                 }
             }
         }
-        let key_material = unsafe { key_material.assume_init() };
         // The key material array contains auth key at index 0 and 2
-        unsafe { auth_key.as_mut_ptr().write(Auth_Key {key: [key_material.0[0], key_material.0[2]]}) };
+        let auth_key = Auth_Key {
+            key: [key_material.0[0], key_material.0[2]],
+        };
         // The key material array contains encryption key at index 4, 6, 8, 10
         // Note that in a 128 version of AES_GCM_SIV only the 4th and 6th index is used to compute
         // the encryption key where as 256 version uses all of them
-        unsafe { enc_key.as_mut_ptr().write(Encryption_Key {key: [
-            key_material.0[4],
-            key_material.0[6],
-            key_material.0[8],
-            key_material.0[10],
-        ]})};
+        let enc_key = Encryption_Key {
+            key: [
+                key_material.0[4],
+                key_material.0[6],
+                key_material.0[8],
+                key_material.0[10],
+            ],
+        };
+        (auth_key, enc_key)
     }
 
     pub fn gcm_siv_asm_polyval(
@@ -189,134 +339,20 @@ impl GcmSivAsmContext {
         let tag = [0u8; TAG_LEN];
         let mut out_tag = Out_Tag { tag };
 
-        let ad_blocks = ad.len() / BLOCK_LEN;
-        let in_blocks = input.len() / BLOCK_LEN;
-
-        let mut htable_init = false;
-        let mut htable= MaybeUninit::<Htable>::uninit();
-
-        if ad_blocks > 8 || in_blocks > 8 {
-            htable_init = true;
-            extern "C" {
-                fn aesgcmsiv_htable_init(out_htable: *mut Htable, auth_key: *const Auth_Key);
-            }
-            unsafe {
-                aesgcmsiv_htable_init(htable.as_mut_ptr(), auth_key);
-            }
-        }
-        let htable = unsafe { htable.assume_init() };
-
-        let whole_ad_len = ad.len() - (ad.len() % BLOCK_LEN);
-        let remaining_ad_len = ad.len() % BLOCK_LEN;
-        if htable_init {
-            extern "C" {
-                fn aesgcmsiv_htable_polyval(
-                    out_htable: *const Htable,
-                    input: *const u8,
-                    input_len: libc::c_uint,
-                    in_out_poly: *mut Out_Tag,
-                );
-            }
-            unsafe {
-                aesgcmsiv_htable_polyval(
-                    &htable,
-                    ad.as_ptr(),
-                    whole_ad_len as libc::c_uint,
-                    &mut out_tag,
-                );
-            }
-        } else {
-            extern "C" {
-                fn aesgcmsiv_polyval_horner(
-                    in_out_poly: *mut Out_Tag,
-                    auth_key: *const Auth_Key,
-                    ad: *const u8,
-                    ad_blocks: libc::c_uint,
-                );
-            }
-            unsafe {
-                aesgcmsiv_polyval_horner(
-                    &mut out_tag,
-                    auth_key,
-                    ad.as_ptr(),
-                    ad_blocks as libc::c_uint,
-                );
-            }
-        }
-
-        let mut scratch = [0u8; BLOCK_LEN];
-        if (ad.len() & remaining_ad_len) != 0 {
-            let left = &mut scratch[..ad.len() & remaining_ad_len];
-            left.copy_from_slice(&ad[whole_ad_len..ad.len()]);
-
-            extern "C" {
-                fn aesgcmsiv_polyval_horner(
-                    in_out_poly: *mut Out_Tag,
-                    key: *const Auth_Key,
-                    scratch: *const u8,
-                    scratch_blocks: libc::c_uint,
-                );
-            }
-            unsafe {
-                aesgcmsiv_polyval_horner(&mut out_tag, auth_key, scratch.as_ptr(), 1);
-            }
-        }
-
-        let whole_in_len = input.len() - (input.len() % BLOCK_LEN);
-        let remaining_in_len = input.len() % BLOCK_LEN;
-        if htable_init {
-            extern "C" {
-                fn aesgcmsiv_htable_polyval(
-                    out_htable: *const Htable,
-                    input: *const u8,
-                    input_len: libc::c_uint,
-                    in_out_poly: *mut Out_Tag,
-                );
-            }
-            unsafe {
-                aesgcmsiv_htable_polyval(
-                    &htable,
-                    input.as_ptr(),
-                    whole_in_len as libc::c_uint,
-                    &mut out_tag,
-                );
-            }
+        // The table only pays for itself for longer inputs. With the
+        // `small_stack` feature it is never used, saving its 128 bytes of
+        // stack at the cost of speed.
+        #[cfg(not(feature = "small_stack"))]
+        let htable = if ad.len() / BLOCK_LEN > 8 || input.len() / BLOCK_LEN > 8 {
+            Some(Htable::new(auth_key))
         } else {
-            extern "C" {
-                fn aesgcmsiv_polyval_horner(
-                    in_out_poly: *mut Out_Tag,
-                    auth_key: *const Auth_Key,
-                    input: *const u8,
-                    in_blocks: libc::c_uint,
-                );
-            }
-            unsafe {
-                aesgcmsiv_polyval_horner(
-                    &mut out_tag,
-                    auth_key,
-                    input.as_ptr(),
-                    in_blocks as libc::c_uint,
-                );
-            }
-        }
+            None
+        };
+        #[cfg(feature = "small_stack")]
+        let htable: Option<Htable> = None;
 
-        let mut scratch = [0u8; BLOCK_LEN];
-        if remaining_in_len != 0 {
-            let left = &mut scratch[..remaining_in_len];
-            left.copy_from_slice(&input[whole_in_len..input.len()]);
-
-            extern "C" {
-                fn aesgcmsiv_polyval_horner(
-                    in_out_poly: *mut Out_Tag,
-                    key: *const Auth_Key,
-                    scratch: *const u8,
-                    scratch_blocks: libc::c_uint,
-                );
-            }
-            unsafe {
-                aesgcmsiv_polyval_horner(&mut out_tag, auth_key, scratch.as_ptr(), 1);
-            }
-        }
+        polyval_asm(&mut out_tag, auth_key, htable.as_ref(), ad);
+        polyval_asm(&mut out_tag, auth_key, htable.as_ref(), input);
 
         let length_block = [ad.len() as u64 * 8, input.len() as u64 * 8];
         extern "C" {
@@ -324,7 +360,7 @@ impl GcmSivAsmContext {
                 out_tag: *mut Out_Tag,
                 auth_key: *const Auth_Key,
                 length_block: *const u64,
-                length_blocks: libc::c_uint,
+                length_blocks: c::uint,
             );
         }
         unsafe {
@@ -340,16 +376,84 @@ impl GcmSivAsmContext {
     }
 }
 
+// Updates `out_tag` with `input`, padding the last partial block, if any,
+// with zeros.
+pub(super) fn polyval_asm(out_tag: &mut Out_Tag, auth_key: &Auth_Key, htable: Option<&Htable>, input: &[u8]) {
+    extern "C" {
+        fn aesgcmsiv_htable_polyval(
+            htable: *const Htable,
+            input: *const u8,
+            input_len: c::uint,
+            in_out_poly: *mut Out_Tag,
+        );
+        fn aesgcmsiv_polyval_horner(
+            in_out_poly: *mut Out_Tag,
+            auth_key: *const Auth_Key,
+            input: *const u8,
+            in_blocks: c::uint,
+        );
+    }
+
+    // The assembly language functions take the length as a `c::uint`, so
+    // longer inputs are processed in chunks.
+    const MAX_CHUNK_LEN: usize = (u32::max_value() as usize) / BLOCK_LEN * BLOCK_LEN;
+
+    let whole_len = input.len() - (input.len() % BLOCK_LEN);
+    for chunk in input[..whole_len].chunks(MAX_CHUNK_LEN) {
+        match htable {
+            Some(htable) => unsafe {
+                aesgcmsiv_htable_polyval(
+                    htable,
+                    chunk.as_ptr(),
+                    chunk.len() as c::uint,
+                    out_tag,
+                );
+            },
+            None => unsafe {
+                aesgcmsiv_polyval_horner(
+                    out_tag,
+                    auth_key,
+                    chunk.as_ptr(),
+                    (chunk.len() / BLOCK_LEN) as c::uint,
+                );
+            },
+        }
+    }
+
+    let remainder = &input[whole_len..];
+    if !remainder.is_empty() {
+        let mut scratch = [0u8; BLOCK_LEN];
+        scratch[..remainder.len()].copy_from_slice(remainder);
+        unsafe {
+            aesgcmsiv_polyval_horner(out_tag, auth_key, scratch.as_ptr(), 1);
+        }
+    }
+}
+
 #[repr(C, align(16))]
 pub struct Htable {
     htable: [u8; 16 * 8],
 }
 
+#[cfg(not(feature = "small_stack"))]
+impl Htable {
+    pub(super) fn new(auth_key: &Auth_Key) -> Self {
+        extern "C" {
+            fn aesgcmsiv_htable_init(out_htable: *mut Htable, auth_key: *const Auth_Key);
+        }
+        let mut htable = Htable {
+            htable: [0u8; 16 * 8],
+        };
+        unsafe {
+            aesgcmsiv_htable_init(&mut htable, auth_key);
+        }
+        htable
+    }
+}
+
 impl Drop for Htable {
     fn drop(&mut self) {
-        for byte in self.htable.iter_mut() {
-            *byte = 0;
-        }
+        self.htable.zeroize();
     }
 }
 
@@ -360,9 +464,7 @@ pub struct Out_Tag {
 
 impl Drop for Out_Tag {
     fn drop(&mut self) {
-        for byte in self.tag.iter_mut() {
-            *byte = 0;
-        }
+        self.tag.zeroize();
     }
 }
 
@@ -373,9 +475,7 @@ pub struct Encryption_Key {
 
 impl Drop for Encryption_Key {
     fn drop(&mut self) {
-        for byte in self.key.iter_mut() {
-            *byte = 0;
-        }
+        self.key.zeroize();
     }
 }
 
@@ -386,22 +486,73 @@ pub struct Auth_Key {
 
 impl Drop for Auth_Key {
     fn drop(&mut self) {
-        for byte in self.key.iter_mut() {
-            *byte = 0;
-        }
+        self.key.zeroize();
     }
 }
 
-#[repr(C, align(16))]
-pub(super) struct GcmSivContext;
+/// The per-nonce state of the fallback implementation.
+///
+/// The POLYVAL key and the message-encryption key are derived once, and the
+/// message-encryption key's AES key schedule is expanded once, when the
+/// context is constructed; the same schedule is then used to encrypt the
+/// tag and for the CTR pass.
+pub(super) struct GcmSivContext {
+    auth_key: Block,
+    enc_key: aes::Key,
+    nonce: [u8; NONCE_LEN],
+    cpu_features: cpu::Features,
+}
+
+impl Drop for GcmSivContext {
+    fn drop(&mut self) {
+        self.auth_key.zeroize();
+    }
+}
 
 impl GcmSivContext {
-    pub fn new() -> Self {
-        GcmSivContext
+    pub(super) fn new(key: &Key, nonce: &Nonce, cpu_features: cpu::Features) -> Self {
+        let mut auth_key_bytes = [0u8; TAG_LEN];
+        let mut enc_key_bytes = [0u8; 32];
+        Self::derive_keys(key, nonce, &mut auth_key_bytes, &mut enc_key_bytes);
+
+        let (first, second) = auth_key_bytes.split_at(TAG_LEN / 2);
+        let auth_key = Block::from_u64_native(
+            u64::from_ne_bytes(first.try_into().unwrap()),
+            u64::from_ne_bytes(second.try_into().unwrap()),
+        );
+        let enc_key_len = match key.variant {
+            AES_128 => 16,
+            AES_256 => 32,
+        };
+        let enc_key =
+            aes::Key::new(&enc_key_bytes[..enc_key_len], key.variant, cpu_features).unwrap();
+
+        auth_key_bytes.zeroize();
+        enc_key_bytes.zeroize();
+        Self {
+            auth_key,
+            enc_key,
+            nonce: *nonce.as_ref(),
+            cpu_features,
+        }
     }
 
-    pub fn kdf(
-        &self,
+    /// Derives the POLYVAL key and the message-encryption key for `nonce`.
+    /// Only the first 16 bytes of `enc_key` are used for AES-128 keys.
+    pub(super) fn derive_keys(
+        key: &Key,
+        nonce: &Nonce,
+        auth_key: &mut [u8; TAG_LEN],
+        enc_key: &mut [u8; 32],
+    ) {
+        if key.aes_key.is_some() {
+            Self::kdf(auth_key, enc_key, key.variant, nonce, key);
+        } else {
+            Self::kdf_asm(auth_key, enc_key, nonce, key);
+        }
+    }
+
+    fn kdf(
         auth_key: &mut [u8; 16],
         enc_key: &mut [u8],
         variant: Variant,
@@ -440,6 +591,40 @@ impl GcmSivContext {
         auth_key.copy_from_slice(&key_material[0..16]);
         // the last 16-48 bytes contains the enc_key
         enc_key.copy_from_slice(&key_material[16..16 + 32]);
+        key_material.zeroize();
+    }
+
+    // The AVX/AES-NI implementations use the fallback implementation for
+    // inputs that are too long for their assembly language kernels. Their
+    // keys only have the assembly language key schedule, so the keys are
+    // derived with the assembly language KDF, which gives the same result.
+    fn kdf_asm(auth_key: &mut [u8; 16], enc_key: &mut [u8], nonce: &Nonce, key: &Key) {
+        let (asm_auth_key, asm_enc_key) = GcmSivAsmContext::new().kdf(nonce, key);
+
+        for (out, word) in auth_key.chunks_mut(8).zip(asm_auth_key.key.iter()) {
+            out.copy_from_slice(&word.to_ne_bytes());
+        }
+        for (out, word) in enc_key.chunks_mut(8).zip(asm_enc_key.key.iter()) {
+            out.copy_from_slice(&word.to_ne_bytes());
+        }
+    }
+
+    /// Returns a POLYVAL context keyed with the POLYVAL key, for callers
+    /// that process the input piecemeal.
+    /// The POLYVAL key.
+    #[cfg(feature = "aead_parallel")]
+    pub(super) fn auth_key(&self) -> &Block {
+        &self.auth_key
+    }
+
+    pub(super) fn new_polyval(&self) -> PolyValContext {
+        PolyValContext::new(&self.auth_key, self.cpu_features)
+    }
+
+    /// Encrypts `block` with the message-encryption key.
+    #[inline]
+    pub(super) fn encrypt_block(&self, block: Block) -> Block {
+        self.enc_key.encrypt_block(block)
     }
 
     pub(super) fn update_blocks(input: &[u8], polyval_ctx: &mut PolyValContext) {
@@ -453,39 +638,43 @@ impl GcmSivContext {
         }
     }
 
-    pub(super) fn gcm_siv_polyval(
-        &self,
-        input: &[u8],
-        ad: &[u8],
-        nonce: &Nonce,
-        auth_key: &Block,
-        cpu_features: cpu::Features,
-    ) -> Block {
-        let mut polyval_ctx = PolyValContext::new(auth_key, cpu_features);
+    /// Computes the tag, before it is encrypted.
+    pub(super) fn gcm_siv_polyval(&self, input: &[u8], ad: &[u8]) -> Block {
+        let mut polyval_ctx = self.new_polyval();
 
         // update ad blocks
         GcmSivContext::update_blocks(&ad, &mut polyval_ctx);
         // update input blocks
         GcmSivContext::update_blocks(&input, &mut polyval_ctx);
 
+        self.finish_polyval(&mut polyval_ctx, ad.len(), input.len())
+    }
+
+    /// Like `gcm_siv_polyval`, given `polyval_ctx` from `new_polyval()` that
+    /// has been updated with the padded `ad` and input.
+    pub(super) fn finish_polyval(
+        &self,
+        polyval_ctx: &mut PolyValContext,
+        ad_len: usize,
+        input_len: usize,
+    ) -> Block {
         // initialization vector is 8 bytes ad_len and 8 bytes input_len
         let mut len_block = [0u8; BLOCK_LEN];
         len_block[0..BLOCK_LEN / 2]
-            .copy_from_slice(&((ad.len() * (BLOCK_LEN / 2)) as u64).to_ne_bytes());
+            .copy_from_slice(&((ad_len * (BLOCK_LEN / 2)) as u64).to_ne_bytes());
         len_block[BLOCK_LEN / 2..BLOCK_LEN]
-            .copy_from_slice(&((input.len() * (BLOCK_LEN / 2)) as u64).to_ne_bytes());
+            .copy_from_slice(&((input_len * (BLOCK_LEN / 2)) as u64).to_ne_bytes());
         polyval_ctx.update_blocks(&len_block);
 
         let mut tag_block = polyval_ctx.pre_finish();
         let tag = tag_block.as_mut();
 
-        let nonce = nonce.as_ref();
-        for i in 0..nonce.len() {
-            tag[i] ^= nonce[i];
+        for i in 0..self.nonce.len() {
+            tag[i] ^= self.nonce[i];
         }
         tag[15] &= 0x7f;
 
-        let (first, second) = tag.split_at(std::mem::size_of::<u64>());
+        let (first, second) = tag.split_at(core::mem::size_of::<u64>());
 
         Block::from_u64_be(
             BigEndian::from(u64::from_be_bytes(first.try_into().unwrap())),
@@ -493,13 +682,9 @@ impl GcmSivContext {
         )
     }
 
-    pub(super) fn gcm_siv_crypt(
-        &self,
-        in_out: &mut [u8],
-        in_prefix_len: usize,
-        tag: &Block,
-        enc_key: &aes::Key,
-    ) {
+    /// Encrypts or decrypts `in_out` in CTR mode, with the initial counter
+    /// derived from `tag`.
+    pub(super) fn gcm_siv_crypt(&self, in_out: &mut [u8], in_prefix_len: usize, tag: &Block) {
         let in_out_len = in_out.len() - in_prefix_len;
 
         let mut ctr = [0u8; TAG_LEN];
@@ -508,9 +693,9 @@ impl GcmSivContext {
 
         let mut done = 0;
         for _ in (0..in_out_len).step_by(BLOCK_LEN) {
-            let todo = std::cmp::min(BLOCK_LEN, in_out_len - done);
+            let todo = core::cmp::min(BLOCK_LEN, in_out_len - done);
 
-            let key_stream = enc_key.encrypt_block(Block::from(&ctr));
+            let key_stream = self.encrypt_block(Block::from(&ctr));
             let key_stream = key_stream.as_ref();
 
             let last_val = u32::from_le_bytes(ctr[0..4].try_into().unwrap()).wrapping_add(1);
@@ -524,23 +709,1010 @@ impl GcmSivContext {
             done += todo;
         }
     }
+
+    /// Like `gcm_siv_crypt`, except `input` is encrypted or decrypted into
+    /// `output`, which is the same length.
+    pub(super) fn gcm_siv_crypt_to(&self, input: &[u8], output: &mut [u8], tag: &Block) {
+        let mut ctr = [0u8; TAG_LEN];
+        ctr.copy_from_slice(tag.as_ref());
+        ctr[15] |= 0x80;
+
+        for (input, output) in input.chunks(BLOCK_LEN).zip(output.chunks_mut(BLOCK_LEN)) {
+            let key_stream = self.encrypt_block(Block::from(&ctr));
+
+            let last_val = u32::from_le_bytes(ctr[0..4].try_into().unwrap()).wrapping_add(1);
+            ctr[0..4].copy_from_slice(&last_val.to_le_bytes());
+
+            for ((output, input), key_stream) in
+                output.iter_mut().zip(input).zip(key_stream.as_ref())
+            {
+                *output = input ^ key_stream;
+            }
+        }
+    }
 }
 
 pub enum Implementation {
     #[allow(dead_code)]
     AVX_AESNI,
+    #[cfg(target_arch = "x86_64")]
+    VAES_VPCLMULQDQ,
+    #[cfg(target_arch = "aarch64")]
+    NEON_PMULL,
     FALLBACK,
 }
 
 pub(super) fn detect_implementation(_cpu_features: cpu::Features) -> Implementation {
     #[cfg(target_arch = "x86_64")]
     {
-        if (cpu::intel::AES.available(_cpu_features)) && (cpu::intel::AVX.available(_cpu_features))
-        {
+        if cpu::intel::AES.available(_cpu_features) && cpu::intel::AVX.available(_cpu_features) {
+            if cpu::intel::AVX2.available(_cpu_features)
+                && cpu::intel::PCLMULQDQ.available(_cpu_features)
+                && cpu::intel::VAES.available(_cpu_features)
+                && cpu::intel::VPCLMULQDQ.available(_cpu_features)
+            {
+                return Implementation::VAES_VPCLMULQDQ;
+            }
             return Implementation::AVX_AESNI;
         }
     }
+    #[cfg(target_arch = "aarch64")]
+    {
+        if cpu::arm::AES.available(_cpu_features) && cpu::arm::PMULL.available(_cpu_features) {
+            return Implementation::NEON_PMULL;
+        }
+    }
     return Implementation::FALLBACK;
 }
 
 pub type Counter = nonce::Counter<LittleEndian<u32>>;
+
+// The `NEON_PMULL` implementation, for AArch64 CPUs with the AES and PMULL
+// instructions, using intrinsics. The expanded keys are stored in
+// `AES_ASM_KEY` as the round keys in order, like the `AVX_AESNI`
+// implementation's.
+#[cfg(target_arch = "aarch64")]
+pub(super) mod neon {
+    use super::{Variant, AES_ASM_KEY, BLOCK_LEN};
+    use crate::aead::aes::Variant::{AES_128, AES_256};
+    use core::arch::aarch64::*;
+
+    #[target_feature(enable = "neon,aes")]
+    pub(in crate::aead) unsafe fn set_encrypt_key(key: &[u8], variant: Variant) -> AES_ASM_KEY {
+        let (key_words, rounds) = match variant {
+            AES_128 => (4, 10),
+            AES_256 => (8, 14),
+        };
+        debug_assert_eq!(key.len(), key_words * 4);
+
+        let mut w = [0u32; 4 * 15];
+        for (word, bytes) in w.iter_mut().zip(key.chunks(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        let mut rcon = 1;
+        for i in key_words..(4 * (rounds + 1)) {
+            let mut t = w[i - 1];
+            if i % key_words == 0 {
+                t = sub_word(t).rotate_right(8) ^ rcon;
+                rcon = (rcon << 1) ^ ((rcon >> 7) * 0x11b);
+            } else if key_words > 6 && i % key_words == 4 {
+                t = sub_word(t);
+            }
+            w[i] = w[i - key_words] ^ t;
+        }
+
+        let mut r = AES_ASM_KEY([0u8; 15 * 16]);
+        for (bytes, word) in r.0.chunks_mut(4).zip(w.iter_mut()) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+            *word = 0;
+        }
+        r
+    }
+
+    // `aese` with a zero round key applies `SubBytes` after `ShiftRows`,
+    // which doesn't move any bytes when all the columns are the same.
+    #[inline]
+    #[target_feature(enable = "neon,aes")]
+    unsafe fn sub_word(word: u32) -> u32 {
+        let columns = vreinterpretq_u8_u32(vdupq_n_u32(word));
+        let substituted = vaeseq_u8(columns, vdupq_n_u8(0));
+        vgetq_lane_u32::<0>(vreinterpretq_u32_u8(substituted))
+    }
+
+    #[target_feature(enable = "neon,aes")]
+    pub(in crate::aead) unsafe fn encrypt_block(
+        key: &AES_ASM_KEY,
+        variant: Variant,
+        block: &[u8; BLOCK_LEN],
+    ) -> [u8; BLOCK_LEN] {
+        let mut state = [load(block)];
+        encrypt_blocks(key, variant, &mut state);
+        let mut r = [0u8; BLOCK_LEN];
+        store(&mut r, state[0]);
+        r
+    }
+
+    // Encrypts or decrypts `in_out[in_prefix_len..]` into `in_out` with the
+    // key stream for `tag`. The blocks are encrypted four at a time so that
+    // the latency of each `aese` is hidden by the others.
+    #[target_feature(enable = "neon,aes")]
+    pub(in crate::aead) unsafe fn ctr32_encrypt(
+        key: &AES_ASM_KEY,
+        variant: Variant,
+        tag: &[u8; BLOCK_LEN],
+        in_out: &mut [u8],
+        in_prefix_len: usize,
+    ) {
+        const LANES: usize = 4;
+
+        let mut counter = *tag;
+        counter[15] |= 0x80;
+        let mut ctr = u32::from_le_bytes([counter[0], counter[1], counter[2], counter[3]]);
+
+        let len = in_out.len() - in_prefix_len;
+        let mut done = 0;
+        while done < len {
+            let mut state = [vdupq_n_u8(0); LANES];
+            for s in state.iter_mut() {
+                counter[..4].copy_from_slice(&ctr.to_le_bytes());
+                ctr = ctr.wrapping_add(1);
+                *s = load(&counter);
+            }
+            encrypt_blocks(key, variant, &mut state);
+
+            let mut key_stream = [0u8; LANES * BLOCK_LEN];
+            for (bytes, s) in key_stream.chunks_mut(BLOCK_LEN).zip(state.iter()) {
+                store(bytes, *s);
+            }
+            let todo = core::cmp::min(key_stream.len(), len - done);
+            for (i, k) in key_stream[..todo].iter().enumerate() {
+                in_out[done + i] = in_out[in_prefix_len + done + i] ^ k;
+            }
+            done += todo;
+        }
+    }
+
+    #[inline]
+    #[target_feature(enable = "neon,aes")]
+    unsafe fn encrypt_blocks(key: &AES_ASM_KEY, variant: Variant, blocks: &mut [uint8x16_t]) {
+        let rounds = match variant {
+            AES_128 => 10,
+            AES_256 => 14,
+        };
+        for round in 0..(rounds - 1) {
+            let rk = round_key(key, round);
+            for b in blocks.iter_mut() {
+                *b = vaesmcq_u8(vaeseq_u8(*b, rk));
+            }
+        }
+        let rk = round_key(key, rounds - 1);
+        let last = round_key(key, rounds);
+        for b in blocks.iter_mut() {
+            *b = veorq_u8(vaeseq_u8(*b, rk), last);
+        }
+    }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn round_key(key: &AES_ASM_KEY, round: usize) -> uint8x16_t {
+        load(&key.0[(round * BLOCK_LEN)..][..BLOCK_LEN])
+    }
+
+    // Updates the POLYVAL accumulator `acc` with `input`, padding the last
+    // partial block, if any, with zeros.
+    #[target_feature(enable = "neon,aes")]
+    pub(in crate::aead) unsafe fn polyval(
+        acc: &mut [u8; BLOCK_LEN],
+        auth_key: &[u8; BLOCK_LEN],
+        input: &[u8],
+    ) {
+        let h = load(auth_key);
+        let mut y = load(acc);
+        let whole_len = input.len() - (input.len() % BLOCK_LEN);
+        for block in input[..whole_len].chunks(BLOCK_LEN) {
+            y = gf_mul(veorq_u8(y, load(block)), h);
+        }
+        let remainder = &input[whole_len..];
+        if !remainder.is_empty() {
+            let mut block = [0u8; BLOCK_LEN];
+            block[..remainder.len()].copy_from_slice(remainder);
+            y = gf_mul(veorq_u8(y, load(&block)), h);
+        }
+        store(acc, y);
+    }
+
+    // Returns `a * b * x^-128` in POLYVAL's field. The 256-bit product is
+    // computed with Karatsuba multiplication and then reduced with two
+    // folding steps by x^64.
+    #[inline]
+    #[target_feature(enable = "neon,aes")]
+    unsafe fn gf_mul(a: uint8x16_t, b: uint8x16_t) -> uint8x16_t {
+        let m = pmull(
+            veorq_u8(a, vextq_u8::<8>(a, a)),
+            veorq_u8(b, vextq_u8::<8>(b, b)),
+        );
+        let h = pmull2(a, b);
+        let l = pmull(a, b);
+
+        let t = veorq_u8(veorq_u8(m, vextq_u8::<8>(l, h)), veorq_u8(h, l));
+        let lo = vextq_u8::<8>(vextq_u8::<8>(l, l), t);
+        let hi = vextq_u8::<8>(t, vextq_u8::<8>(h, h));
+
+        // x^127 + x^126 + x^121 + x^63 + x^62 + x^57.
+        let poly = vreinterpretq_u8_u64(vdupq_n_u64(0xc200_0000_0000_0000));
+        let f = pmull(lo, poly);
+        let lo = veorq_u8(lo, vextq_u8::<8>(f, f));
+        let f = pmull2(lo, poly);
+        veorq_u8(hi, veorq_u8(f, lo))
+    }
+
+    // The carryless product of the low halves of `a` and `b`.
+    #[inline]
+    #[target_feature(enable = "neon,aes")]
+    unsafe fn pmull(a: uint8x16_t, b: uint8x16_t) -> uint8x16_t {
+        vreinterpretq_u8_p128(vmull_p64(
+            vgetq_lane_u64::<0>(vreinterpretq_u64_u8(a)),
+            vgetq_lane_u64::<0>(vreinterpretq_u64_u8(b)),
+        ))
+    }
+
+    // The carryless product of the high halves of `a` and `b`.
+    #[inline]
+    #[target_feature(enable = "neon,aes")]
+    unsafe fn pmull2(a: uint8x16_t, b: uint8x16_t) -> uint8x16_t {
+        vreinterpretq_u8_p128(vmull_p64(
+            vgetq_lane_u64::<1>(vreinterpretq_u64_u8(a)),
+            vgetq_lane_u64::<1>(vreinterpretq_u64_u8(b)),
+        ))
+    }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn load(bytes: &[u8]) -> uint8x16_t {
+        debug_assert_eq!(bytes.len(), BLOCK_LEN);
+        vld1q_u8(bytes.as_ptr())
+    }
+
+    #[inline]
+    #[target_feature(enable = "neon")]
+    unsafe fn store(bytes: &mut [u8], value: uint8x16_t) {
+        debug_assert_eq!(bytes.len(), BLOCK_LEN);
+        vst1q_u8(bytes.as_mut_ptr(), value)
+    }
+}
+
+// The `VAES_VPCLMULQDQ` implementation, for x86-64 CPUs with the VAES and
+// VPCLMULQDQ extensions, using intrinsics. The AES key schedule and the key
+// derivation are those of the `AVX_AESNI` implementation; only the bulk
+// encryption and POLYVAL are done here, eight blocks at a time, two per
+// 256-bit register.
+#[cfg(target_arch = "x86_64")]
+pub(super) mod vaes {
+    use super::{Auth_Key, Variant, AES_ASM_KEY, BLOCK_LEN};
+    use crate::{
+        aead::aes::Variant::{AES_128, AES_256},
+        zeroize::Zeroize,
+    };
+    use core::arch::x86_64::*;
+
+    // The number of blocks processed by each iteration of the kernels.
+    const BLOCKS: usize = 8;
+
+    /// The powers H^8, H^7, ..., H^1 of the authentication key in POLYVAL's
+    /// field, in that order, so that the first block of eight is multiplied
+    /// by the highest power.
+    pub(in crate::aead) struct Powers([u8; BLOCKS * BLOCK_LEN]);
+
+    impl Drop for Powers {
+        fn drop(&mut self) {
+            self.0.zeroize();
+        }
+    }
+
+    impl Powers {
+        #[target_feature(enable = "avx2,pclmulqdq")]
+        pub(in crate::aead) unsafe fn new(auth_key: &Auth_Key) -> Self {
+            let h = _mm_loadu_si128(auth_key.key.as_ptr() as *const __m128i);
+            let mut r = Powers([0u8; BLOCKS * BLOCK_LEN]);
+            let mut power = h;
+            for i in (0..BLOCKS).rev() {
+                store(&mut r.0[(i * BLOCK_LEN)..][..BLOCK_LEN], power);
+                if i != 0 {
+                    power = gf_mul(power, h);
+                }
+            }
+            r
+        }
+    }
+
+    // Encrypts or decrypts `in_out[in_prefix_len..]` into `in_out` with the
+    // key stream for `tag`.
+    #[target_feature(enable = "avx2,aes,vaes")]
+    pub(in crate::aead) unsafe fn ctr32_encrypt(
+        key: &AES_ASM_KEY,
+        variant: Variant,
+        tag: &[u8; BLOCK_LEN],
+        in_out: &mut [u8],
+        in_prefix_len: usize,
+    ) {
+        let rounds = match variant {
+            AES_128 => 10,
+            AES_256 => 14,
+        };
+        let mut round_keys = [_mm256_setzero_si256(); 15];
+        for (i, rk) in round_keys[..=rounds].iter_mut().enumerate() {
+            *rk = _mm256_broadcastsi128_si256(load(&key.0[(i * BLOCK_LEN)..][..BLOCK_LEN]));
+        }
+
+        let mut counter = *tag;
+        counter[15] |= 0x80;
+        // The counter is the first 32-bit word of each block, and it wraps
+        // around within that word, as `_mm256_add_epi32` does.
+        let mut counters = _mm256_add_epi32(
+            _mm256_broadcastsi128_si256(load(&counter)),
+            _mm256_set_epi32(0, 0, 0, 1, 0, 0, 0, 0),
+        );
+        let two = _mm256_set_epi32(0, 0, 0, 2, 0, 0, 0, 2);
+
+        let len = in_out.len() - in_prefix_len;
+        let mut done = 0;
+        while done < len {
+            let mut state = [_mm256_setzero_si256(); BLOCKS / 2];
+            for s in state.iter_mut() {
+                *s = _mm256_xor_si256(counters, round_keys[0]);
+                counters = _mm256_add_epi32(counters, two);
+            }
+            for rk in &round_keys[1..rounds] {
+                for s in state.iter_mut() {
+                    *s = _mm256_aesenc_epi128(*s, *rk);
+                }
+            }
+            for s in state.iter_mut() {
+                *s = _mm256_aesenclast_epi128(*s, round_keys[rounds]);
+            }
+
+            let todo = core::cmp::min(BLOCKS * BLOCK_LEN, len - done);
+            if todo == BLOCKS * BLOCK_LEN {
+                // All the input is loaded before any output is stored, and
+                // the output never overlaps the input of later iterations.
+                let input = in_out.as_ptr().add(in_prefix_len + done) as *const __m256i;
+                let output = in_out.as_mut_ptr().add(done) as *mut __m256i;
+                let mut x = [_mm256_setzero_si256(); BLOCKS / 2];
+                for (i, x) in x.iter_mut().enumerate() {
+                    *x = _mm256_xor_si256(_mm256_loadu_si256(input.add(i)), state[i]);
+                }
+                for (i, x) in x.iter().enumerate() {
+                    _mm256_storeu_si256(output.add(i), *x);
+                }
+            } else {
+                let mut key_stream = [0u8; BLOCKS * BLOCK_LEN];
+                for (i, s) in state.iter().enumerate() {
+                    let output = key_stream.as_mut_ptr().add(i * 2 * BLOCK_LEN);
+                    _mm256_storeu_si256(output as *mut __m256i, *s);
+                }
+                for (i, k) in key_stream[..todo].iter().enumerate() {
+                    in_out[done + i] = in_out[in_prefix_len + done + i] ^ k;
+                }
+                key_stream.zeroize();
+            }
+            done += todo;
+        }
+    }
+
+    // Updates the POLYVAL accumulator `acc` with `input`, padding the last
+    // partial block, if any, with zeros. Each group of eight blocks is
+    // multiplied by the powers of the authentication key and the products
+    // are reduced together.
+    #[target_feature(enable = "avx2,pclmulqdq,vpclmulqdq")]
+    pub(in crate::aead) unsafe fn polyval(
+        acc: &mut [u8; BLOCK_LEN],
+        powers: &Powers,
+        input: &[u8],
+    ) {
+        let mut y = load(acc);
+
+        let mut chunks = input.chunks_exact(BLOCKS * BLOCK_LEN);
+        for chunk in &mut chunks {
+            let chunk = chunk.as_ptr() as *const __m256i;
+            let h = powers.0.as_ptr() as *const __m256i;
+            let mut lo = _mm256_setzero_si256();
+            let mut mid = _mm256_setzero_si256();
+            let mut hi = _mm256_setzero_si256();
+            for i in 0..(BLOCKS / 2) {
+                let mut x = _mm256_loadu_si256(chunk.add(i));
+                if i == 0 {
+                    x = _mm256_xor_si256(x, _mm256_set_m128i(_mm_setzero_si128(), y));
+                }
+                let h = _mm256_loadu_si256(h.add(i));
+                lo = _mm256_xor_si256(lo, _mm256_clmulepi64_epi128(x, h, 0x00));
+                hi = _mm256_xor_si256(hi, _mm256_clmulepi64_epi128(x, h, 0x11));
+                mid = _mm256_xor_si256(mid, _mm256_clmulepi64_epi128(x, h, 0x01));
+                mid = _mm256_xor_si256(mid, _mm256_clmulepi64_epi128(x, h, 0x10));
+            }
+            y = reduce(fold(lo), fold(mid), fold(hi));
+        }
+
+        let h = load(&powers.0[((BLOCKS - 1) * BLOCK_LEN)..]);
+        let remainder = chunks.remainder();
+        let whole_len = remainder.len() - (remainder.len() % BLOCK_LEN);
+        for block in remainder[..whole_len].chunks(BLOCK_LEN) {
+            y = gf_mul(_mm_xor_si128(y, load(block)), h);
+        }
+        let remainder = &remainder[whole_len..];
+        if !remainder.is_empty() {
+            let mut block = [0u8; BLOCK_LEN];
+            block[..remainder.len()].copy_from_slice(remainder);
+            y = gf_mul(_mm_xor_si128(y, load(&block)), h);
+        }
+        store(acc, y);
+    }
+
+    // Adds the two 128-bit lanes of `v`.
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn fold(v: __m256i) -> __m128i {
+        _mm_xor_si128(_mm256_castsi256_si128(v), _mm256_extracti128_si256(v, 1))
+    }
+
+    // Returns `a * b * x^-128` in POLYVAL's field.
+    #[inline]
+    #[target_feature(enable = "pclmulqdq")]
+    pub(super) unsafe fn gf_mul(a: __m128i, b: __m128i) -> __m128i {
+        let lo = _mm_clmulepi64_si128(a, b, 0x00);
+        let hi = _mm_clmulepi64_si128(a, b, 0x11);
+        let mid = _mm_xor_si128(
+            _mm_clmulepi64_si128(a, b, 0x01),
+            _mm_clmulepi64_si128(a, b, 0x10),
+        );
+        reduce(lo, mid, hi)
+    }
+
+    // Returns `(hi * x^128 + mid * x^64 + lo) * x^-128` in POLYVAL's field,
+    // reducing with two folding steps by x^64, like `GFMUL` in
+    // aes128gcmsiv-x86_64.pl.
+    #[inline]
+    #[target_feature(enable = "pclmulqdq")]
+    pub(super) unsafe fn reduce(lo: __m128i, mid: __m128i, hi: __m128i) -> __m128i {
+        let lo = _mm_xor_si128(lo, _mm_slli_si128(mid, 8));
+        let hi = _mm_xor_si128(hi, _mm_srli_si128(mid, 8));
+
+        // x^127 + x^126 + x^121 + x^63 + x^62 + x^57, in the high half.
+        let poly = _mm_set_epi64x(0xc200_0000_0000_0000u64 as i64, 1);
+        let t = _mm_clmulepi64_si128(lo, poly, 0x10);
+        let lo = _mm_xor_si128(_mm_shuffle_epi32(lo, 0x4e), t);
+        let t = _mm_clmulepi64_si128(lo, poly, 0x10);
+        let lo = _mm_xor_si128(_mm_shuffle_epi32(lo, 0x4e), t);
+        _mm_xor_si128(hi, lo)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn load(bytes: &[u8]) -> __m128i {
+        debug_assert_eq!(bytes.len(), BLOCK_LEN);
+        _mm_loadu_si128(bytes.as_ptr() as *const __m128i)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn store(bytes: &mut [u8], value: __m128i) {
+        debug_assert_eq!(bytes.len(), BLOCK_LEN);
+        _mm_storeu_si128(bytes.as_mut_ptr() as *mut __m128i, value)
+    }
+}
+
+// The `AVX_AESNI` implementation using intrinsics instead of the
+// perlasm-generated assembly language code, for the `intrinsics` feature.
+// Each function has the name and the ABI of the aes128gcmsiv-x86_64.pl
+// function it replaces, so the existing declarations of those resolve to
+// these, and computes the same results. Unlike the assembly language code,
+// `aes128gcmsiv_dec` and `aes256gcmsiv_dec` don't use the space after the
+// tag as scratch space.
+#[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
+mod avx_aesni {
+    use super::{
+        vaes::{gf_mul, reduce},
+        BLOCK_LEN,
+    };
+    use crate::c;
+    use core::arch::x86_64::*;
+
+    #[no_mangle]
+    #[target_feature(enable = "aes,avx")]
+    unsafe extern "C" fn aes128gcmsiv_aes_ks(key: *const u8, out_expanded_key: *mut u8) {
+        expand_key(key, 10, out_expanded_key);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "aes,avx")]
+    unsafe extern "C" fn aes256gcmsiv_aes_ks(key: *const u8, out_expanded_key: *mut u8) {
+        expand_key(key, 14, out_expanded_key);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "aes,avx")]
+    unsafe extern "C" fn aes128gcmsiv_aes_ks_enc_x1(
+        input: *const u8,
+        output: *mut u8,
+        out_expanded_key: *mut u8,
+        key: *const u8,
+    ) {
+        expand_key(key, 10, out_expanded_key);
+        ecb_encrypt_block(input, output, out_expanded_key, 10);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "aes,avx")]
+    unsafe extern "C" fn aes256gcmsiv_aes_ks_enc_x1(
+        input: *const u8,
+        output: *mut u8,
+        out_expanded_key: *mut u8,
+        key: *const u8,
+    ) {
+        expand_key(key, 14, out_expanded_key);
+        ecb_encrypt_block(input, output, out_expanded_key, 14);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "aes,avx")]
+    unsafe extern "C" fn aes128gcmsiv_ecb_enc_block(
+        input: *const u8,
+        output: *mut u8,
+        expanded_key: *const u8,
+    ) {
+        ecb_encrypt_block(input, output, expanded_key, 10);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "aes,avx")]
+    unsafe extern "C" fn aes256gcmsiv_ecb_enc_block(
+        input: *const u8,
+        output: *mut u8,
+        expanded_key: *const u8,
+    ) {
+        ecb_encrypt_block(input, output, expanded_key, 14);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "aes,avx")]
+    unsafe extern "C" fn aes128gcmsiv_kdf(
+        nonce: *const u8,
+        out_key_material: *mut u8,
+        expanded_key: *const u8,
+    ) {
+        kdf(nonce, out_key_material, expanded_key, 10, 4);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "aes,avx")]
+    unsafe extern "C" fn aes256gcmsiv_kdf(
+        nonce: *const u8,
+        out_key_material: *mut u8,
+        expanded_key: *const u8,
+    ) {
+        kdf(nonce, out_key_material, expanded_key, 14, 6);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "aes,avx")]
+    unsafe extern "C" fn aes128gcmsiv_enc_msg_x4(
+        input: *const u8,
+        output: *mut u8,
+        tag: *const u8,
+        expanded_key: *const u8,
+        input_len: c::uint,
+    ) {
+        ctr32_encrypt(input, output, tag, expanded_key, 10, input_len);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "aes,avx")]
+    unsafe extern "C" fn aes128gcmsiv_enc_msg_x8(
+        input: *const u8,
+        output: *mut u8,
+        tag: *const u8,
+        expanded_key: *const u8,
+        input_len: c::uint,
+    ) {
+        ctr32_encrypt(input, output, tag, expanded_key, 10, input_len);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "aes,avx")]
+    unsafe extern "C" fn aes256gcmsiv_enc_msg_x4(
+        input: *const u8,
+        output: *mut u8,
+        tag: *const u8,
+        expanded_key: *const u8,
+        input_len: c::uint,
+    ) {
+        ctr32_encrypt(input, output, tag, expanded_key, 14, input_len);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "aes,avx")]
+    unsafe extern "C" fn aes256gcmsiv_enc_msg_x8(
+        input: *const u8,
+        output: *mut u8,
+        tag: *const u8,
+        expanded_key: *const u8,
+        input_len: c::uint,
+    ) {
+        ctr32_encrypt(input, output, tag, expanded_key, 14, input_len);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "aes,avx,pclmulqdq")]
+    unsafe extern "C" fn aes128gcmsiv_dec(
+        input: *const u8,
+        output: *mut u8,
+        in_out_calculated_tag: *mut u8,
+        htable: *const u8,
+        expanded_key: *const u8,
+        plaintext_len: c::uint,
+    ) {
+        dec(
+            input,
+            output,
+            in_out_calculated_tag,
+            htable,
+            expanded_key,
+            10,
+            plaintext_len,
+        );
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "aes,avx,pclmulqdq")]
+    unsafe extern "C" fn aes256gcmsiv_dec(
+        input: *const u8,
+        output: *mut u8,
+        in_out_calculated_tag: *mut u8,
+        htable: *const u8,
+        expanded_key: *const u8,
+        plaintext_len: c::uint,
+    ) {
+        dec(
+            input,
+            output,
+            in_out_calculated_tag,
+            htable,
+            expanded_key,
+            14,
+            plaintext_len,
+        );
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "avx,pclmulqdq")]
+    unsafe extern "C" fn aesgcmsiv_htable_init(out_htable: *mut u8, auth_key: *const u8) {
+        htable_init(out_htable, auth_key, 8);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "avx,pclmulqdq")]
+    unsafe extern "C" fn aesgcmsiv_htable6_init(out_htable: *mut u8, auth_key: *const u8) {
+        htable_init(out_htable, auth_key, 6);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "avx,pclmulqdq")]
+    unsafe extern "C" fn aesgcmsiv_htable_polyval(
+        htable: *const u8,
+        input: *const u8,
+        input_len: c::uint,
+        in_out_poly: *mut u8,
+    ) {
+        let blocks = (input_len as usize) / BLOCK_LEN;
+        let t = polyval(load(in_out_poly), htable, 8, input, blocks);
+        store(in_out_poly, t);
+    }
+
+    #[no_mangle]
+    #[target_feature(enable = "avx,pclmulqdq")]
+    unsafe extern "C" fn aesgcmsiv_polyval_horner(
+        in_out_poly: *mut u8,
+        auth_key: *const u8,
+        input: *const u8,
+        in_blocks: c::uint,
+    ) {
+        let t = polyval(load(in_out_poly), auth_key, 1, input, in_blocks as usize);
+        store(in_out_poly, t);
+    }
+
+    // Writes the `rounds + 1` round keys for `key` to `out`. As in the
+    // assembly language code, `aesenclast` applied to the rotated last word
+    // of the previous round key, broadcast to every word, computes
+    // `SubWord(RotWord(w)) ^ rcon`.
+    #[inline]
+    #[target_feature(enable = "aes,avx")]
+    unsafe fn expand_key(key: *const u8, rounds: usize, out: *mut u8) {
+        let rot_word = _mm_set1_epi32(0x0c0f_0e0d);
+        let mut rcon = _mm_set1_epi32(1);
+        match rounds {
+            10 => {
+                let mut rk = load(key);
+                store(out, rk);
+                for i in 1..=10 {
+                    if i == 9 {
+                        rcon = _mm_set1_epi32(0x1b);
+                    }
+                    let t = _mm_aesenclast_si128(_mm_shuffle_epi8(rk, rot_word), rcon);
+                    rcon = _mm_slli_epi32(rcon, 1);
+                    rk = expand_step(rk, t);
+                    store(out.add(i * BLOCK_LEN), rk);
+                }
+            }
+            _ => {
+                let mut even = load(key);
+                let mut odd = load(key.add(BLOCK_LEN));
+                store(out, even);
+                store(out.add(BLOCK_LEN), odd);
+                for i in 1..=7 {
+                    let t = _mm_aesenclast_si128(_mm_shuffle_epi8(odd, rot_word), rcon);
+                    rcon = _mm_slli_epi32(rcon, 1);
+                    even = expand_step(even, t);
+                    store(out.add(2 * i * BLOCK_LEN), even);
+                    if i < 7 {
+                        // `SubWord(w)`, without the rotation and the `rcon`.
+                        let t = _mm_aesenclast_si128(
+                            _mm_shuffle_epi32(even, 0xff),
+                            _mm_setzero_si128(),
+                        );
+                        odd = expand_step(odd, t);
+                        store(out.add((2 * i + 1) * BLOCK_LEN), odd);
+                    }
+                }
+            }
+        }
+    }
+
+    // Computes the next round key from the previous one, `prev`, and `t`,
+    // which has the same value in every word.
+    #[inline]
+    #[target_feature(enable = "avx")]
+    unsafe fn expand_step(prev: __m128i, t: __m128i) -> __m128i {
+        let mut rk = prev;
+        rk = _mm_xor_si128(rk, _mm_slli_si128(rk, 4));
+        rk = _mm_xor_si128(rk, _mm_slli_si128(rk, 4));
+        rk = _mm_xor_si128(rk, _mm_slli_si128(rk, 4));
+        _mm_xor_si128(rk, t)
+    }
+
+    #[inline]
+    #[target_feature(enable = "aes,avx")]
+    unsafe fn ecb_encrypt_block(
+        input: *const u8,
+        output: *mut u8,
+        expanded_key: *const u8,
+        rounds: usize,
+    ) {
+        let mut block = [load(input)];
+        encrypt_blocks(expanded_key, rounds, &mut block);
+        store(output, block[0]);
+    }
+
+    // Encrypts each of `blocks` in place, loading each round key once.
+    #[inline]
+    #[target_feature(enable = "aes,avx")]
+    unsafe fn encrypt_blocks(expanded_key: *const u8, rounds: usize, blocks: &mut [__m128i]) {
+        let rk = load(expanded_key);
+        for b in blocks.iter_mut() {
+            *b = _mm_xor_si128(*b, rk);
+        }
+        for i in 1..rounds {
+            let rk = load(expanded_key.add(i * BLOCK_LEN));
+            for b in blocks.iter_mut() {
+                *b = _mm_aesenc_si128(*b, rk);
+            }
+        }
+        let rk = load(expanded_key.add(rounds * BLOCK_LEN));
+        for b in blocks.iter_mut() {
+            *b = _mm_aesenclast_si128(*b, rk);
+        }
+    }
+
+    // Encrypts the blocks whose first word is 0, 1, ..., `num_blocks - 1`
+    // and whose other three words are the first three words of `nonce`.
+    #[inline]
+    #[target_feature(enable = "aes,avx")]
+    unsafe fn kdf(
+        nonce: *const u8,
+        out_key_material: *mut u8,
+        expanded_key: *const u8,
+        rounds: usize,
+        num_blocks: usize,
+    ) {
+        let mut blocks = [_mm_setzero_si128(); 6];
+        let mut block = _mm_and_si128(
+            _mm_shuffle_epi32(load(nonce), 0x90),
+            _mm_set_epi32(-1, -1, -1, 0),
+        );
+        for b in blocks[..num_blocks].iter_mut() {
+            *b = block;
+            block = _mm_add_epi32(block, _mm_set_epi32(0, 0, 0, 1));
+        }
+        encrypt_blocks(expanded_key, rounds, &mut blocks[..num_blocks]);
+        for (i, b) in blocks[..num_blocks].iter().enumerate() {
+            store(out_key_material.add(i * BLOCK_LEN), *b);
+        }
+    }
+
+    // Returns the initial counter block for the tag at `tag`: the tag with
+    // the most significant bit of its last byte set.
+    #[inline]
+    #[target_feature(enable = "avx")]
+    unsafe fn initial_counter(tag: *const u8) -> __m128i {
+        _mm_or_si128(load(tag), _mm_set_epi32(0x8000_0000u32 as i32, 0, 0, 0))
+    }
+
+    // Encrypts or decrypts the whole blocks of the `input_len` bytes at
+    // `input` into `output`, with the counter, which is the first 32-bit
+    // word of each block, starting at `counter` and wrapping around. Like
+    // the `ctr32_encrypt_blocks` functions, `output` may be `input` or
+    // precede it, so every input block of a group is loaded before any
+    // output block of the group is stored. Returns the next counter block.
+    #[inline]
+    #[target_feature(enable = "aes,avx")]
+    unsafe fn ctr32_encrypt_group(
+        input: *const u8,
+        output: *mut u8,
+        mut counter: __m128i,
+        expanded_key: *const u8,
+        rounds: usize,
+        state: &mut [__m128i],
+    ) -> __m128i {
+        for s in state.iter_mut() {
+            *s = counter;
+            counter = _mm_add_epi32(counter, _mm_set_epi32(0, 0, 0, 1));
+        }
+        encrypt_blocks(expanded_key, rounds, state);
+        for (i, s) in state.iter_mut().enumerate() {
+            *s = _mm_xor_si128(*s, load(input.add(i * BLOCK_LEN)));
+        }
+        for (i, s) in state.iter().enumerate() {
+            store(output.add(i * BLOCK_LEN), *s);
+        }
+        counter
+    }
+
+    #[inline]
+    #[target_feature(enable = "aes,avx")]
+    unsafe fn ctr32_encrypt(
+        input: *const u8,
+        output: *mut u8,
+        tag: *const u8,
+        expanded_key: *const u8,
+        rounds: usize,
+        input_len: c::uint,
+    ) {
+        // The blocks are encrypted eight at a time so that the latency of
+        // each `aesenc` is hidden by the others.
+        const LANES: usize = 8;
+
+        let blocks = (input_len as usize) / BLOCK_LEN;
+        let mut counter = initial_counter(tag);
+        let mut i = 0;
+        while i < blocks {
+            let lanes = core::cmp::min(LANES, blocks - i);
+            let mut state = [_mm_setzero_si128(); LANES];
+            counter = ctr32_encrypt_group(
+                input.add(i * BLOCK_LEN),
+                output.add(i * BLOCK_LEN),
+                counter,
+                expanded_key,
+                rounds,
+                &mut state[..lanes],
+            );
+            i += lanes;
+        }
+    }
+
+    // Decrypts the whole blocks of the `plaintext_len` bytes at `input`, which
+    // are followed by the tag, into `output`, and updates the POLYVAL
+    // accumulator at `in_out_calculated_tag` with them, using the powers of
+    // the authentication key at `htable`.
+    #[inline]
+    #[target_feature(enable = "aes,avx,pclmulqdq")]
+    unsafe fn dec(
+        input: *const u8,
+        output: *mut u8,
+        in_out_calculated_tag: *mut u8,
+        htable: *const u8,
+        expanded_key: *const u8,
+        rounds: usize,
+        plaintext_len: c::uint,
+    ) {
+        const LANES: usize = 6;
+
+        let plaintext_len = plaintext_len as usize;
+        let blocks = plaintext_len / BLOCK_LEN;
+        if blocks == 0 {
+            return;
+        }
+        let mut counter = initial_counter(input.add(plaintext_len));
+        let mut t = load(in_out_calculated_tag);
+        let mut i = 0;
+        while i < blocks {
+            let lanes = core::cmp::min(LANES, blocks - i);
+            let mut state = [_mm_setzero_si128(); LANES];
+            let output = output.add(i * BLOCK_LEN);
+            counter = ctr32_encrypt_group(
+                input.add(i * BLOCK_LEN),
+                output,
+                counter,
+                expanded_key,
+                rounds,
+                &mut state[..lanes],
+            );
+            t = polyval(t, htable, LANES, output, lanes);
+            i += lanes;
+        }
+        store(in_out_calculated_tag, t);
+    }
+
+    // Writes the authentication key H and its powers H^2, ..., H^n to
+    // `out_htable`.
+    #[inline]
+    #[target_feature(enable = "avx,pclmulqdq")]
+    unsafe fn htable_init(out_htable: *mut u8, auth_key: *const u8, n: usize) {
+        let h = load(auth_key);
+        let mut power = h;
+        store(out_htable, power);
+        for i in 1..n {
+            power = gf_mul(power, h);
+            store(out_htable.add(i * BLOCK_LEN), power);
+        }
+    }
+
+    // Returns the POLYVAL accumulator `t` updated with `blocks` blocks at
+    // `input`, where `powers` points to H, H^2, ..., H^n. Each group of `n`
+    // blocks is multiplied by the powers and the products are reduced
+    // together; the remaining blocks are multiplied by H one at a time.
+    #[inline]
+    #[target_feature(enable = "avx,pclmulqdq")]
+    unsafe fn polyval(
+        mut t: __m128i,
+        powers: *const u8,
+        n: usize,
+        input: *const u8,
+        blocks: usize,
+    ) -> __m128i {
+        let mut i = 0;
+        while blocks - i >= n {
+            let mut lo = _mm_setzero_si128();
+            let mut mid = _mm_setzero_si128();
+            let mut hi = _mm_setzero_si128();
+            for j in 0..n {
+                let mut x = load(input.add((i + j) * BLOCK_LEN));
+                if j == 0 {
+                    x = _mm_xor_si128(x, t);
+                }
+                let h = load(powers.add((n - 1 - j) * BLOCK_LEN));
+                lo = _mm_xor_si128(lo, _mm_clmulepi64_si128(x, h, 0x00));
+                hi = _mm_xor_si128(hi, _mm_clmulepi64_si128(x, h, 0x11));
+                mid = _mm_xor_si128(mid, _mm_clmulepi64_si128(x, h, 0x01));
+                mid = _mm_xor_si128(mid, _mm_clmulepi64_si128(x, h, 0x10));
+            }
+            t = reduce(lo, mid, hi);
+            i += n;
+        }
+        let h = load(powers);
+        while i < blocks {
+            t = gf_mul(_mm_xor_si128(t, load(input.add(i * BLOCK_LEN))), h);
+            i += 1;
+        }
+        t
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx")]
+    unsafe fn load(p: *const u8) -> __m128i {
+        _mm_loadu_si128(p as *const __m128i)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx")]
+    unsafe fn store(p: *mut u8, value: __m128i) {
+        _mm_storeu_si128(p as *mut __m128i, value)
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! The individual stages of AES-GCM-SIV, for property-based tests and
+//! differential fuzzing against a reference implementation.
+//!
+//! **These are not a stable API and are not for encryption.** They are only
+//! available with the `testing-internals` feature.
+//!
+//! `derive_keys()` and `polyval_tag()` always use the portable
+//! implementation, whatever the CPU supports, so their results only depend
+//! on their inputs. Names follow [RFC 8452].
+//!
+//! [RFC 8452]: https://tools.ietf.org/html/rfc8452
+
+use super::{
+    aes::Variant,
+    block::BLOCK_LEN,
+    gcm_siv::{self, GcmSivContext},
+    Nonce, NONCE_LEN,
+};
+use crate::{cpu, error, zeroize::Zeroize};
+
+/// The per-nonce keys derived from a key-generating key, as in
+/// [RFC 8452 Section 4].
+///
+/// [RFC 8452 Section 4]: https://tools.ietf.org/html/rfc8452#section-4
+pub struct DerivedKeys {
+    message_authentication_key: [u8; BLOCK_LEN],
+    message_encryption_key: [u8; 32],
+    message_encryption_key_len: usize,
+}
+
+impl DerivedKeys {
+    /// The POLYVAL key.
+    #[inline]
+    pub fn message_authentication_key(&self) -> &[u8; BLOCK_LEN] {
+        &self.message_authentication_key
+    }
+
+    /// The AES key for the tag and the CTR pass, which is as long as the
+    /// key-generating key.
+    #[inline]
+    pub fn message_encryption_key(&self) -> &[u8] {
+        &self.message_encryption_key[..self.message_encryption_key_len]
+    }
+}
+
+impl Drop for DerivedKeys {
+    fn drop(&mut self) {
+        self.message_authentication_key.zeroize();
+        self.message_encryption_key.zeroize();
+    }
+}
+
+/// Derives the per-nonce keys from `key_generating_key`, which must be 16 or
+/// 32 bytes long.
+pub fn derive_keys(
+    key_generating_key: &[u8],
+    nonce: &[u8; NONCE_LEN],
+) -> Result<DerivedKeys, error::Unspecified> {
+    let key = portable_key(key_generating_key)?;
+    let mut keys = DerivedKeys {
+        message_authentication_key: [0; BLOCK_LEN],
+        message_encryption_key: [0; 32],
+        message_encryption_key_len: key_generating_key.len(),
+    };
+    GcmSivContext::derive_keys(
+        &key,
+        &Nonce::assume_unique_for_key(*nonce),
+        &mut keys.message_authentication_key,
+        &mut keys.message_encryption_key,
+    );
+    Ok(keys)
+}
+
+/// Computes the tag of `plaintext` and `aad` before it is encrypted: POLYVAL
+/// of the additional data, the plaintext, and their lengths, XORed with the
+/// nonce and with the most significant bit of the last byte cleared.
+///
+/// The keys are derived from `key_generating_key` as by `derive_keys()`.
+pub fn polyval_tag(
+    key_generating_key: &[u8],
+    nonce: &[u8; NONCE_LEN],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<[u8; BLOCK_LEN], error::Unspecified> {
+    let key = portable_key(key_generating_key)?;
+    let nonce = Nonce::assume_unique_for_key(*nonce);
+    let ctx = GcmSivContext::new(&key, &nonce, cpu::features());
+    let tag = ctx.gcm_siv_polyval(plaintext, aad);
+    Ok(*tag.as_ref())
+}
+
+/// Encrypts or decrypts the final partial block of a message with the
+/// AES-NI assembly language code, as the AVX/AES-NI implementation does.
+///
+/// The message is `in_out[in_prefix_len..]`. Only its last
+/// `(in_out.len() - in_prefix_len) % 16` bytes are processed, and they are
+/// written `in_prefix_len` bytes earlier, as when opening in place; the
+/// counter block is derived from `tag` as in [RFC 8452 Section 4].
+///
+/// Fails if `message_encryption_key` isn't 16 or 32 bytes long, or if the
+/// CPU doesn't support the AVX/AES-NI implementation.
+///
+/// [RFC 8452 Section 4]: https://tools.ietf.org/html/rfc8452#section-4
+#[cfg(target_arch = "x86_64")]
+pub fn crypt_last_block(
+    message_encryption_key: &[u8],
+    tag: &[u8; BLOCK_LEN],
+    in_prefix_len: usize,
+    in_out: &mut [u8],
+) -> Result<(), error::Unspecified> {
+    let cpu_features = cpu::features();
+    if !cpu::intel::AES.available(cpu_features) || !cpu::intel::AVX.available(cpu_features) {
+        return Err(error::Unspecified);
+    }
+    let variant = variant(message_encryption_key)?;
+    let in_out_len = in_out
+        .len()
+        .checked_sub(in_prefix_len)
+        .ok_or(error::Unspecified)?;
+
+    let mut enc_key = gcm_siv::Encryption_Key { key: [0; 4] };
+    for (word, bytes) in enc_key.key.iter_mut().zip(message_encryption_key.chunks(8)) {
+        let mut word_bytes = [0u8; 8];
+        word_bytes.copy_from_slice(bytes);
+        *word = u64::from_ne_bytes(word_bytes);
+    }
+    let expanded_key = gcm_siv::AES_ASM_KEY::expand_from(&enc_key, variant);
+
+    super::aes_gcm_siv::crypt_last_block(
+        tag,
+        in_out,
+        &expanded_key,
+        &variant,
+        in_out_len,
+        in_prefix_len,
+    );
+    Ok(())
+}
+
+fn portable_key(key_generating_key: &[u8]) -> Result<gcm_siv::Key, error::Unspecified> {
+    let variant = variant(key_generating_key)?;
+    gcm_siv::Key::new_portable(key_generating_key, variant, cpu::features())
+}
+
+fn variant(key: &[u8]) -> Result<Variant, error::Unspecified> {
+    match key.len() {
+        16 => Ok(Variant::AES_128),
+        32 => Ok(Variant::AES_256),
+        _ => Err(error::Unspecified),
+    }
+}
@@ -0,0 +1,347 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! The AES inverse cipher of [FIPS 197] Section 5.3.
+//!
+//! Only AES key unwrapping, `cipher::aes`, and encrypted PKCS#8 need AES
+//! decryption, so the AES implementations don't provide it. On x86-64 and
+//! AArch64 CPUs with AES instructions, blocks are decrypted with the
+//! equivalent inverse cipher of FIPS 197 Section 5.3.5. Otherwise, this uses a straightforward portable
+//! implementation that computes the S-box with arithmetic in GF(2**8) instead
+//! of table lookups, so it is constant-time but slow; that's fine for
+//! unwrapping keys.
+//!
+//! [FIPS 197]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.197.pdf
+
+use super::{aes::Variant, Block, BLOCK_LEN};
+use crate::{cpu, zeroize::Zeroize};
+
+const MAX_ROUNDS: usize = 14;
+
+pub(crate) struct Key {
+    round_keys: [[u8; BLOCK_LEN]; MAX_ROUNDS + 1],
+
+    // The round keys of the equivalent inverse cipher: `round_keys` with
+    // `InvMixColumns` applied to all but the first and last.
+    #[cfg_attr(
+        not(any(target_arch = "x86_64", target_arch = "aarch64")),
+        allow(dead_code)
+    )]
+    dec_round_keys: [[u8; BLOCK_LEN]; MAX_ROUNDS + 1],
+
+    rounds: usize,
+
+    #[cfg_attr(
+        not(any(target_arch = "x86_64", target_arch = "aarch64")),
+        allow(dead_code)
+    )]
+    cpu_features: cpu::Features,
+}
+
+impl Key {
+    /// FIPS 197 Section 5.2. `bytes` must be the length of a `variant` key.
+    pub fn new(bytes: &[u8], variant: Variant, cpu_features: cpu::Features) -> Self {
+        let (nk, rounds) = match variant {
+            Variant::AES_128 => (4, 10),
+            Variant::AES_256 => (8, 14),
+        };
+        assert_eq!(bytes.len(), 4 * nk);
+
+        let mut w = [[0u8; 4]; 4 * (MAX_ROUNDS + 1)];
+        for (w, bytes) in w.iter_mut().zip(bytes.chunks(4)) {
+            w.copy_from_slice(bytes);
+        }
+        let mut rcon = 1u8;
+        for i in nk..(4 * (rounds + 1)) {
+            let mut temp = w[i - 1];
+            if i % nk == 0 {
+                temp.rotate_left(1);
+                sub_word(&mut temp);
+                temp[0] ^= rcon;
+                rcon = xtime(rcon);
+            } else if nk > 6 && i % nk == 4 {
+                sub_word(&mut temp);
+            }
+            for j in 0..4 {
+                w[i][j] = w[i - nk][j] ^ temp[j];
+            }
+        }
+
+        let mut round_keys = [[0u8; BLOCK_LEN]; MAX_ROUNDS + 1];
+        for (round_key, w) in round_keys.iter_mut().zip(w.chunks(4)) {
+            for (round_key, w) in round_key.chunks_mut(4).zip(w.iter()) {
+                round_key.copy_from_slice(w);
+            }
+        }
+
+        let mut dec_round_keys = round_keys;
+        for round_key in dec_round_keys[1..rounds].iter_mut() {
+            inv_mix_columns(round_key);
+        }
+
+        Self {
+            round_keys,
+            dec_round_keys,
+            rounds,
+            cpu_features,
+        }
+    }
+
+    pub fn decrypt_block(&self, input: Block) -> Block {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if cpu::intel::AES.available(self.cpu_features) {
+                return unsafe { aesni::decrypt_block(&self.dec_round_keys, self.rounds, input) };
+            }
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        {
+            if cpu::arm::AES.available(self.cpu_features) {
+                return unsafe { neon::decrypt_block(&self.dec_round_keys, self.rounds, input) };
+            }
+        }
+
+        self.decrypt_block_nohw(input)
+    }
+
+    fn decrypt_block_nohw(&self, input: Block) -> Block {
+        let mut state = *input.as_ref();
+        add_round_key(&mut state, &self.round_keys[self.rounds]);
+        for round in (1..self.rounds).rev() {
+            inv_shift_rows(&mut state);
+            inv_sub_bytes(&mut state);
+            add_round_key(&mut state, &self.round_keys[round]);
+            inv_mix_columns(&mut state);
+        }
+        inv_shift_rows(&mut state);
+        inv_sub_bytes(&mut state);
+        add_round_key(&mut state, &self.round_keys[0]);
+        Block::from(&state)
+    }
+}
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        for round_key in self.round_keys.iter_mut() {
+            round_key.zeroize();
+        }
+        for round_key in self.dec_round_keys.iter_mut() {
+            round_key.zeroize();
+        }
+    }
+}
+
+// `aesdec` does `InvShiftRows`, `InvSubBytes`, and `InvMixColumns` and then
+// adds the round key, so it needs the equivalent inverse cipher's keys.
+#[cfg(target_arch = "x86_64")]
+mod aesni {
+    use super::{Block, BLOCK_LEN, MAX_ROUNDS};
+    use core::arch::x86_64::*;
+
+    #[target_feature(enable = "aes,sse2")]
+    pub(super) unsafe fn decrypt_block(
+        dec_round_keys: &[[u8; BLOCK_LEN]; MAX_ROUNDS + 1],
+        rounds: usize,
+        input: Block,
+    ) -> Block {
+        let mut state = _mm_xor_si128(load(input.as_ref()), load(&dec_round_keys[rounds]));
+        for round_key in dec_round_keys[1..rounds].iter().rev() {
+            state = _mm_aesdec_si128(state, load(round_key));
+        }
+        state = _mm_aesdeclast_si128(state, load(&dec_round_keys[0]));
+
+        let mut output = [0u8; BLOCK_LEN];
+        _mm_storeu_si128(output.as_mut_ptr() as *mut __m128i, state);
+        Block::from(&output)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn load(bytes: &[u8; BLOCK_LEN]) -> __m128i {
+        _mm_loadu_si128(bytes.as_ptr() as *const __m128i)
+    }
+}
+
+// `aesd` adds the round key first and then does `InvShiftRows` and
+// `InvSubBytes`; `InvMixColumns` is the separate `aesimc`.
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use super::{Block, BLOCK_LEN, MAX_ROUNDS};
+    use core::arch::aarch64::*;
+
+    #[target_feature(enable = "neon,aes")]
+    pub(super) unsafe fn decrypt_block(
+        dec_round_keys: &[[u8; BLOCK_LEN]; MAX_ROUNDS + 1],
+        rounds: usize,
+        input: Block,
+    ) -> Block {
+        let mut state = vld1q_u8(input.as_ref().as_ptr());
+        for round_key in dec_round_keys[2..=rounds].iter().rev() {
+            state = vaesimcq_u8(vaesdq_u8(state, vld1q_u8(round_key.as_ptr())));
+        }
+        state = vaesdq_u8(state, vld1q_u8(dec_round_keys[1].as_ptr()));
+        state = veorq_u8(state, vld1q_u8(dec_round_keys[0].as_ptr()));
+
+        let mut output = [0u8; BLOCK_LEN];
+        vst1q_u8(output.as_mut_ptr(), state);
+        Block::from(&output)
+    }
+}
+
+type State = [u8; BLOCK_LEN];
+
+fn add_round_key(state: &mut State, round_key: &[u8; BLOCK_LEN]) {
+    for (s, k) in state.iter_mut().zip(round_key.iter()) {
+        *s ^= k;
+    }
+}
+
+// The state is stored column by column, so row `r` of column `c` is
+// `state[4 * c + r]`. Row `r` is rotated right by `r` columns.
+fn inv_shift_rows(state: &mut State) {
+    let old = *state;
+    for c in 0..4 {
+        for r in 1..4 {
+            state[4 * c + r] = old[4 * ((c + 4 - r) % 4) + r];
+        }
+    }
+}
+
+fn inv_sub_bytes(state: &mut State) {
+    for s in state.iter_mut() {
+        *s = inv_s_box(*s);
+    }
+}
+
+fn inv_mix_columns(state: &mut State) {
+    for column in state.chunks_mut(4) {
+        let a = [column[0], column[1], column[2], column[3]];
+        for (r, b) in column.iter_mut().enumerate() {
+            *b = gf_mul(a[r], 0x0e)
+                ^ gf_mul(a[(r + 1) % 4], 0x0b)
+                ^ gf_mul(a[(r + 2) % 4], 0x0d)
+                ^ gf_mul(a[(r + 3) % 4], 0x09);
+        }
+    }
+}
+
+fn sub_word(word: &mut [u8; 4]) {
+    for b in word.iter_mut() {
+        *b = s_box(*b);
+    }
+}
+
+// Also used by `aegis`.
+pub(super) fn s_box(x: u8) -> u8 {
+    let b = gf_inv(x);
+    b ^ b.rotate_left(1) ^ b.rotate_left(2) ^ b.rotate_left(3) ^ b.rotate_left(4) ^ 0x63
+}
+
+fn inv_s_box(x: u8) -> u8 {
+    gf_inv(x.rotate_left(1) ^ x.rotate_left(3) ^ x.rotate_left(6) ^ 0x05)
+}
+
+// Multiplication by x in GF(2**8) modulo x**8 + x**4 + x**3 + x + 1. Also
+// used by `aegis`.
+pub(super) fn xtime(a: u8) -> u8 {
+    (a << 1) ^ (0u8.wrapping_sub(a >> 7) & 0x1b)
+}
+
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut r = 0;
+    for _ in 0..8 {
+        r ^= a & 0u8.wrapping_sub(b & 1);
+        a = xtime(a);
+        b >>= 1;
+    }
+    r
+}
+
+// Returns `x**254`, which is the multiplicative inverse of `x`, or zero if
+// `x` is zero.
+fn gf_inv(x: u8) -> u8 {
+    let x2 = gf_mul(x, x);
+    let x3 = gf_mul(x2, x);
+    let x6 = gf_mul(x3, x3);
+    let x12 = gf_mul(x6, x6);
+    let x15 = gf_mul(x12, x3);
+    let x30 = gf_mul(x15, x15);
+    let x60 = gf_mul(x30, x30);
+    let x120 = gf_mul(x60, x60);
+    let x240 = gf_mul(x120, x120);
+    let x252 = gf_mul(x240, x12);
+    gf_mul(x252, x2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aead::aes, cpu};
+
+    #[test]
+    fn test_s_box() {
+        assert_eq!(s_box(0x00), 0x63);
+        assert_eq!(s_box(0x53), 0xed);
+        for x in 0..=255 {
+            assert_eq!(inv_s_box(s_box(x)), x);
+        }
+    }
+
+    // FIPS 197 Appendix C.1 and C.3.
+    #[test]
+    fn test_decrypt_block() {
+        const KEY: [u8; 32] = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b,
+            0x1c, 0x1d, 0x1e, 0x1f,
+        ];
+        const PLAINTEXT: [u8; BLOCK_LEN] = [
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ];
+        const TESTS: &[(Variant, usize, [u8; BLOCK_LEN])] = &[
+            (
+                Variant::AES_128,
+                16,
+                [
+                    0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70,
+                    0xb4, 0xc5, 0x5a,
+                ],
+            ),
+            (
+                Variant::AES_256,
+                32,
+                [
+                    0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b,
+                    0x49, 0x60, 0x89,
+                ],
+            ),
+        ];
+        for &(variant, key_len, ref ciphertext) in TESTS {
+            let key = &KEY[..key_len];
+
+            let encrypted = aes::Key::new(key, variant, cpu::features())
+                .unwrap()
+                .encrypt_block(Block::from(&PLAINTEXT));
+            assert_eq!(encrypted.as_ref(), ciphertext);
+
+            let key = Key::new(key, variant, cpu::features());
+            let decrypted = key.decrypt_block(Block::from(ciphertext));
+            assert_eq!(decrypted.as_ref(), &PLAINTEXT);
+            let decrypted = key.decrypt_block_nohw(Block::from(ciphertext));
+            assert_eq!(decrypted.as_ref(), &PLAINTEXT);
+        }
+    }
+}
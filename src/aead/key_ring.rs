@@ -0,0 +1,195 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Sealing and opening with a set of keys that is rotated over time.
+
+use super::{open_in_place_, seal_in_place_, Aad, Algorithm, Key, Nonce, MAX_KEY_LEN};
+use crate::{error, hkdf};
+use core::convert::TryInto;
+use std::vec::Vec;
+
+/// The length of the key ID that `KeyRing::seal_in_place()` writes before
+/// the ciphertext.
+pub const KEY_ID_LEN: usize = 4;
+
+/// A set of keys, each tagged with a key ID, for rotating keys without
+/// breaking the ability to open data sealed with older keys.
+///
+/// `seal_in_place()` always uses the newest key, the one most recently added,
+/// and prefixes its output with that key's ID, encoded as `KEY_ID_LEN` bytes
+/// in big-endian order. `open_in_place()` uses the prefix to choose the key.
+/// Retire a key with `remove()` once nothing sealed with it needs to be
+/// opened anymore.
+///
+/// Key IDs aren't secret; anybody who sees the sealed data sees which key
+/// sealed it.
+pub struct KeyRing {
+    algorithm: &'static Algorithm,
+
+    // Oldest first. Key IDs are unique.
+    keys: Vec<(u32, Key)>,
+}
+
+impl KeyRing {
+    /// Constructs an empty key ring for keys of `algorithm`.
+    pub fn new(algorithm: &'static Algorithm) -> Self {
+        Self {
+            algorithm,
+            keys: Vec::new(),
+        }
+    }
+
+    /// The algorithm of the keys.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+
+    /// Adds the key `key_bytes` with the ID `key_id`, making it the key used
+    /// for sealing.
+    ///
+    /// `key_bytes` must be exactly `self.algorithm().key_len()` bytes long.
+    /// Fails if the key ring already has a key with the ID `key_id`.
+    pub fn add(&mut self, key_id: u32, key_bytes: &[u8]) -> Result<(), error::Unspecified> {
+        if self.contains(key_id) {
+            return Err(error::Unspecified);
+        }
+        let key = Key::new(self.algorithm, key_bytes)?;
+        self.keys.push((key_id, key));
+        Ok(())
+    }
+
+    /// Adds a key with the ID `key_id` derived from `prk` with HKDF-Expand,
+    /// making it the key used for sealing.
+    ///
+    /// The `info` for HKDF-Expand is `key_id` encoded as `KEY_ID_LEN` bytes
+    /// in big-endian order, so every member of a fleet that shares `prk`
+    /// derives the same key for a given key ID. Fails if the key ring
+    /// already has a key with the ID `key_id`.
+    pub fn add_derived(&mut self, key_id: u32, prk: &hkdf::Prk) -> Result<(), error::Unspecified> {
+        let mut key_bytes = [0; MAX_KEY_LEN];
+        let key_bytes = &mut key_bytes[..self.algorithm.key_len];
+        prk.fill(&key_id.to_be_bytes(), key_bytes)?;
+        self.add(key_id, key_bytes)
+    }
+
+    /// Removes the key with the ID `key_id`.
+    ///
+    /// Fails if the key ring has no key with the ID `key_id`.
+    pub fn remove(&mut self, key_id: u32) -> Result<(), error::Unspecified> {
+        let index = self
+            .keys
+            .iter()
+            .position(|(id, _)| *id == key_id)
+            .ok_or(error::Unspecified)?;
+        let _ = self.keys.remove(index);
+        Ok(())
+    }
+
+    /// Whether the key ring has a key with the ID `key_id`.
+    pub fn contains(&self, key_id: u32) -> bool {
+        self.key(key_id).is_some()
+    }
+
+    /// The ID of the key used for sealing, or `None` if the key ring is
+    /// empty.
+    pub fn sealing_key_id(&self) -> Option<u32> {
+        self.keys.last().map(|(id, _)| *id)
+    }
+
+    /// Encrypts and signs (“seals”) data in place with the newest key.
+    ///
+    /// `nonce` must be unique for every use of the key to seal data.
+    ///
+    /// The first `KEY_ID_LEN` bytes of `in_out` are overwritten with the key
+    /// ID. The input is the rest of `in_out` up to the suffix of
+    /// `out_suffix_capacity` bytes, as for `seal_in_place()`. When
+    /// `KeyRing::seal_in_place()` returns `Ok(out_len)`, the key ID followed
+    /// by the encrypted and signed output is `in_out[..out_len]`.
+    ///
+    /// Fails if the key ring is empty.
+    ///
+    /// `aad` is the additional authenticated data, if any.
+    pub fn seal_in_place<A: AsRef<[u8]>>(
+        &self,
+        nonce: Nonce,
+        Aad(aad): Aad<A>,
+        in_out: &mut [u8],
+        out_suffix_capacity: usize,
+    ) -> Result<usize, error::Unspecified> {
+        let (key_id, key) = self.keys.last().ok_or(error::Unspecified)?;
+        if in_out.len() < KEY_ID_LEN {
+            return Err(error::Unspecified);
+        }
+        let (key_id_out, in_out) = in_out.split_at_mut(KEY_ID_LEN);
+        let out_len = seal_in_place_(
+            key,
+            &key.inner,
+            nonce,
+            Aad::from(aad.as_ref()),
+            in_out,
+            out_suffix_capacity,
+        )?;
+        key_id_out.copy_from_slice(&key_id.to_be_bytes());
+        Ok(KEY_ID_LEN + out_len)
+    }
+
+    /// Authenticates and decrypts (“opens”) data in place, with the key
+    /// whose ID prefixes it.
+    ///
+    /// `in_out` is the output of `KeyRing::seal_in_place()`: the key ID,
+    /// then the ciphertext and tag. When `KeyRing::open_in_place()` returns
+    /// `Ok(plaintext)`, the decrypted output is `plaintext`, which is
+    /// `&mut in_out[..plaintext.len()]`; the key ID is overwritten. When it
+    /// returns `Err(..)`, `in_out` may have been overwritten in an
+    /// unspecified way.
+    ///
+    /// Fails, without decrypting anything, if the key ring has no key with
+    /// the ID in `in_out`, e.g. because that key has been removed.
+    pub fn open_in_place<'a, A: AsRef<[u8]>>(
+        &self,
+        nonce: Nonce,
+        Aad(aad): Aad<A>,
+        in_out: &'a mut [u8],
+    ) -> Result<&'a mut [u8], error::Unspecified> {
+        let key_id = in_out.get(..KEY_ID_LEN).ok_or(error::Unspecified)?;
+        let key_id = u32::from_be_bytes(key_id.try_into().unwrap());
+        let key = self.key(key_id).ok_or(error::Unspecified)?;
+        open_in_place_(
+            key,
+            &key.inner,
+            nonce,
+            Aad::from(aad.as_ref()),
+            KEY_ID_LEN,
+            in_out,
+        )
+        .map_err(error::Unspecified::from)
+    }
+
+    fn key(&self, key_id: u32) -> Option<&Key> {
+        self.keys
+            .iter()
+            .find(|(id, _)| *id == key_id)
+            .map(|(_, key)| key)
+    }
+}
+
+impl core::fmt::Debug for KeyRing {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("KeyRing")
+            .field("algorithm", self.algorithm)
+            .field("sealing_key_id", &self.sealing_key_id())
+            .finish()
+    }
+}
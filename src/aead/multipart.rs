@@ -0,0 +1,355 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Multi-part sealing and opening for AES-GCM-SIV.
+
+use super::{
+    aes_gcm_siv::Stream, Aad, Algorithm, AlgorithmID, Block, Key, Nonce, OpeningKey, SealingKey,
+    Tag, BLOCK_LEN, TAG_LEN,
+};
+use crate::{constant_time, error, polyfill};
+
+#[cfg(feature = "audit")]
+use crate::audit;
+#[cfg(feature = "audit")]
+use core::convert::TryFrom;
+
+/// The first pass of a multi-part AES-GCM-SIV sealing operation, which
+/// authenticates the plaintext.
+///
+/// AES-GCM-SIV computes the tag from the whole plaintext and then uses the
+/// tag as the initial counter for encrypting it, so a multi-part sealing
+/// operation takes two passes over the plaintext. First, pass all of the
+/// plaintext, in chunks of any size, to `update()`. Then `finish()` returns
+/// a `SealingEncryptor`, which has the tag, for the second pass. The output
+/// is the same as that of `seal_in_place()`.
+pub struct SealingContext {
+    algorithm: &'static Algorithm,
+    authenticator: Authenticator,
+    #[cfg(feature = "audit")]
+    fingerprint: audit::Fingerprint,
+}
+
+impl SealingContext {
+    /// Starts sealing a message with `key`, `nonce`, and `aad`.
+    ///
+    /// `nonce` must be unique for every use of the key to seal data. Fails
+    /// unless `key`'s algorithm is `AES_128_GCM_SIV` or `AES_256_GCM_SIV`.
+    pub fn new<A: AsRef<[u8]>>(
+        key: &SealingKey,
+        nonce: Nonce,
+        aad: Aad<A>,
+    ) -> Result<Self, error::Unspecified> {
+        Ok(Self {
+            algorithm: key.key.algorithm,
+            authenticator: Authenticator::new(&key.key, nonce, aad)?,
+            #[cfg(feature = "audit")]
+            fingerprint: key.key.fingerprint,
+        })
+    }
+
+    /// Authenticates the next chunk of the plaintext.
+    ///
+    /// Fails if the total length of the plaintext would exceed the
+    /// algorithm's limit.
+    pub fn update(&mut self, plaintext: &[u8]) -> Result<(), error::Unspecified> {
+        self.authenticator.update(self.algorithm, plaintext)
+    }
+
+    /// Finishes authenticating the plaintext and starts encrypting it.
+    pub fn finish(self) -> SealingEncryptor {
+        let in_out_len = self.authenticator.in_out_len;
+        #[cfg(feature = "audit")]
+        audit::record(
+            self.algorithm,
+            audit::Operation::Seal,
+            &self.fingerprint,
+            usize::try_from(in_out_len).unwrap_or(usize::MAX),
+        );
+        let (stream, tag) = self.authenticator.finish();
+        SealingEncryptor {
+            stream,
+            key_stream: KeyStream::new(tag),
+            in_out_len,
+        }
+    }
+}
+
+/// The second pass of a multi-part AES-GCM-SIV sealing operation, which
+/// encrypts the plaintext.
+pub struct SealingEncryptor {
+    stream: Stream,
+    key_stream: KeyStream,
+    in_out_len: u64,
+}
+
+impl SealingEncryptor {
+    /// The tag.
+    #[inline]
+    pub fn tag(&self) -> &[u8] {
+        &self.key_stream.tag
+    }
+
+    /// Encrypts the next chunk of the plaintext in place.
+    ///
+    /// The chunks must be the same plaintext that was given to
+    /// `SealingContext::update()`, though they may be split differently.
+    /// Fails if the total length would exceed the length of that plaintext.
+    pub fn encrypt(&mut self, in_out: &mut [u8]) -> Result<(), error::Unspecified> {
+        if self.key_stream.position + polyfill::u64_from_usize(in_out.len()) > self.in_out_len {
+            return Err(error::Unspecified);
+        }
+        self.key_stream.xor(&self.stream, in_out);
+        Ok(())
+    }
+
+    /// Finishes the sealing operation and returns the tag.
+    ///
+    /// Fails unless exactly as much plaintext was encrypted as was
+    /// authenticated.
+    pub fn finish(self) -> Result<[u8; TAG_LEN], error::Unspecified> {
+        if self.key_stream.position != self.in_out_len {
+            return Err(error::Unspecified);
+        }
+        Ok(self.key_stream.tag)
+    }
+}
+
+/// A multi-part AES-GCM-SIV opening operation.
+///
+/// Opening takes a single pass: the tag is needed first, and then the
+/// ciphertext is decrypted in chunks of any size with `update()`, and the
+/// tag is verified by `finish()`.
+///
+/// **The decrypted chunks are not authenticated until `finish()` returns
+/// `Ok(())`**, so they must not be used, e.g. parsed or written anywhere
+/// other than to a temporary location, until then. If `finish()` fails,
+/// all of the decrypted output must be discarded.
+pub struct OpeningContext {
+    algorithm: &'static Algorithm,
+    authenticator: Authenticator,
+    key_stream: KeyStream,
+    #[cfg(feature = "audit")]
+    fingerprint: audit::Fingerprint,
+}
+
+impl OpeningContext {
+    /// Starts opening a message that was sealed with `key`, `nonce`, and
+    /// `aad`, and has the tag `tag`.
+    ///
+    /// Fails unless `key`'s algorithm is `AES_128_GCM_SIV` or
+    /// `AES_256_GCM_SIV`, or if `tag` isn't `key.algorithm().tag_len()` bytes
+    /// long.
+    pub fn new<A: AsRef<[u8]>>(
+        key: &OpeningKey,
+        nonce: Nonce,
+        aad: Aad<A>,
+        tag: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        if tag.len() != TAG_LEN {
+            return Err(error::Unspecified);
+        }
+        let mut received_tag = [0u8; TAG_LEN];
+        received_tag.copy_from_slice(tag);
+        Ok(Self {
+            algorithm: key.key.algorithm,
+            authenticator: Authenticator::new(&key.key, nonce, aad)?,
+            key_stream: KeyStream::new(received_tag),
+            #[cfg(feature = "audit")]
+            fingerprint: key.key.fingerprint,
+        })
+    }
+
+    /// Decrypts the next chunk of the ciphertext in place.
+    ///
+    /// Fails if the total length of the ciphertext would exceed the
+    /// algorithm's limit.
+    pub fn update(&mut self, in_out: &mut [u8]) -> Result<(), error::Unspecified> {
+        check_in_out_len(self.algorithm, self.authenticator.in_out_len, in_out.len())?;
+        self.key_stream.xor(&self.authenticator.stream, in_out);
+        self.authenticator.update(self.algorithm, in_out)
+    }
+
+    /// Verifies the tag.
+    ///
+    /// When this fails, all of the decrypted output must be discarded.
+    pub fn finish(self) -> Result<(), error::Unspecified> {
+        #[cfg(feature = "audit")]
+        audit::record(
+            self.algorithm,
+            audit::Operation::Open,
+            &self.fingerprint,
+            usize::try_from(self.authenticator.in_out_len).unwrap_or(usize::MAX),
+        );
+        let (_, calculated_tag) = self.authenticator.finish();
+        constant_time::verify_slices_are_equal(&calculated_tag, &self.key_stream.tag)
+    }
+}
+
+derive_debug_via_field!(SealingContext, algorithm);
+derive_debug_via_field!(SealingEncryptor, in_out_len);
+derive_debug_via_field!(OpeningContext, algorithm);
+
+// Decrypts `in_out` with `received_tag` and returns the calculated tag, for
+// opening AES-GCM-SIV with a detached tag. The caller checks the length.
+pub(super) fn open_detached(
+    key: &Key,
+    nonce: Nonce,
+    aad: Aad<&[u8]>,
+    received_tag: &[u8; TAG_LEN],
+    in_out: &mut [u8],
+) -> Tag {
+    let authenticator = Authenticator::new(key, nonce, aad).unwrap();
+    let (_, tag) = open_detached_(key.algorithm, authenticator, received_tag, in_out);
+    tag
+}
+
+// Like `open_detached`, but with the keys already derived in `stream` for
+// `nonce`. `stream` is returned so that it can be reused for the next
+// message with the same nonce.
+pub(super) fn open_detached_with_stream(
+    algorithm: &Algorithm,
+    mut stream: Stream,
+    aad: Aad<&[u8]>,
+    received_tag: &[u8; TAG_LEN],
+    in_out: &mut [u8],
+) -> (Stream, Tag) {
+    stream.reset();
+    let authenticator = Authenticator::with_stream(stream, aad);
+    open_detached_(algorithm, authenticator, received_tag, in_out)
+}
+
+fn open_detached_(
+    algorithm: &Algorithm,
+    mut authenticator: Authenticator,
+    received_tag: &[u8; TAG_LEN],
+    in_out: &mut [u8],
+) -> (Stream, Tag) {
+    KeyStream::new(*received_tag).xor(&authenticator.stream, in_out);
+    authenticator.update(algorithm, in_out).unwrap();
+    let (stream, calculated_tag) = authenticator.finish();
+    (stream, Tag(Block::from(&calculated_tag)))
+}
+
+// Computes POLYVAL over the additional data and the message, buffering any
+// partial block of the message between updates.
+struct Authenticator {
+    stream: Stream,
+    aad_len: u64,
+    in_out_len: u64,
+    partial: [u8; BLOCK_LEN],
+}
+
+impl Authenticator {
+    fn new<A: AsRef<[u8]>>(
+        key: &Key,
+        nonce: Nonce,
+        Aad(aad): Aad<A>,
+    ) -> Result<Self, error::Unspecified> {
+        match key.algorithm.id {
+            AlgorithmID::AES_128_GCM_SIV | AlgorithmID::AES_256_GCM_SIV => (),
+            _ => {
+                return Err(error::Unspecified);
+            }
+        }
+        let stream = Stream::new(&key.inner, nonce, key.cpu_features);
+        Ok(Self::with_stream(stream, Aad(aad)))
+    }
+
+    fn with_stream<A: AsRef<[u8]>>(mut stream: Stream, Aad(aad): Aad<A>) -> Self {
+        stream.update(aad.as_ref());
+        Self {
+            stream,
+            aad_len: polyfill::u64_from_usize(aad.as_ref().len()),
+            in_out_len: 0,
+            partial: [0u8; BLOCK_LEN],
+        }
+    }
+
+    fn update(&mut self, algorithm: &Algorithm, input: &[u8]) -> Result<(), error::Unspecified> {
+        check_in_out_len(algorithm, self.in_out_len, input.len())?;
+
+        let mut input = input;
+        let partial_len = partial_len(self.in_out_len);
+        self.in_out_len += polyfill::u64_from_usize(input.len());
+        if partial_len > 0 {
+            let todo = core::cmp::min(BLOCK_LEN - partial_len, input.len());
+            self.partial[partial_len..][..todo].copy_from_slice(&input[..todo]);
+            input = &input[todo..];
+            if partial_len + todo < BLOCK_LEN {
+                return Ok(());
+            }
+            self.stream.update(&self.partial);
+        }
+
+        let whole_len = input.len() - (input.len() % BLOCK_LEN);
+        self.stream.update(&input[..whole_len]);
+        let remainder = &input[whole_len..];
+        self.partial[..remainder.len()].copy_from_slice(remainder);
+        Ok(())
+    }
+
+    fn finish(mut self) -> (Stream, [u8; TAG_LEN]) {
+        let partial_len = partial_len(self.in_out_len);
+        self.stream.update(&self.partial[..partial_len]);
+        let tag = self.stream.tag(self.aad_len, self.in_out_len);
+        (self.stream, tag)
+    }
+}
+
+// Generates the AES-CTR key stream for a tag, a block at a time.
+struct KeyStream {
+    tag: [u8; TAG_LEN],
+    position: u64,
+    block: [u8; BLOCK_LEN],
+}
+
+impl KeyStream {
+    fn new(tag: [u8; TAG_LEN]) -> Self {
+        Self {
+            tag,
+            position: 0,
+            block: [0u8; BLOCK_LEN],
+        }
+    }
+
+    fn xor(&mut self, stream: &Stream, in_out: &mut [u8]) {
+        for byte in in_out {
+            let offset = partial_len(self.position);
+            if offset == 0 {
+                // The counter wraps at 2**32 blocks, but `check_in_out_len`
+                // keeps the message shorter than that.
+                let index = (self.position / polyfill::u64_from_usize(BLOCK_LEN)) as u32;
+                self.block = stream.key_stream_block(&self.tag, index);
+            }
+            *byte ^= self.block[offset];
+            self.position += 1;
+        }
+    }
+}
+
+fn partial_len(len: u64) -> usize {
+    (len % polyfill::u64_from_usize(BLOCK_LEN)) as usize
+}
+
+fn check_in_out_len(
+    algorithm: &Algorithm,
+    so_far: u64,
+    len: usize,
+) -> Result<(), error::Unspecified> {
+    match so_far.checked_add(polyfill::u64_from_usize(len)) {
+        Some(total) if total <= algorithm.max_input_len => Ok(()),
+        _ => Err(error::Unspecified),
+    }
+}
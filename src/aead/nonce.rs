@@ -23,7 +23,7 @@
 //! 3. For each block encrypted, increment the counter. Each time the counter
 //!    is incremented, the current value is returned.
 
-use super::Block;
+use super::{Block, COUNTER_NONCE_PREFIX_LEN};
 use crate::{endian::*, error, polyfill::convert::*};
 use core::marker::PhantomData;
 
@@ -52,6 +52,35 @@ impl Nonce {
     pub fn assume_unique_for_key(value: [u8; NONCE_LEN]) -> Self {
         Self(value)
     }
+
+    /// Constructs a `Nonce` from `prefix` followed by the big-endian encoding
+    /// of `counter`, assuming that the value is unique for the lifetime of the
+    /// key it is being used with.
+    ///
+    /// This is the nonce that a `CounterNonceSequence` with
+    /// `Endianness::Big` gives for `counter`.
+    #[inline]
+    pub fn from_u64_counter(prefix: [u8; COUNTER_NONCE_PREFIX_LEN], counter: u64) -> Self {
+        let mut value = [0u8; NONCE_LEN];
+        value[..COUNTER_NONCE_PREFIX_LEN].copy_from_slice(&prefix);
+        value[COUNTER_NONCE_PREFIX_LEN..].copy_from_slice(&counter.to_be_bytes());
+        Self(value)
+    }
+
+    /// Constructs a `Nonce` from the big-endian encoding of `value`, assuming
+    /// that the value is unique for the lifetime of the key it is being used
+    /// with.
+    ///
+    /// Fails if `value` doesn't fit in 96 bits.
+    #[inline]
+    pub fn from_u96(value: u128) -> Result<Self, error::Unspecified> {
+        let bytes = value.to_be_bytes();
+        let (high, low) = bytes.split_at(bytes.len() - NONCE_LEN);
+        if high.iter().any(|&b| b != 0) {
+            return Err(error::Unspecified);
+        }
+        Self::try_assume_unique_for_key(low)
+    }
 }
 
 impl AsRef<[u8; NONCE_LEN]> for Nonce {
@@ -148,6 +177,7 @@ impl<U32: Layout<u32>> From<Counter<U32>> for Block
 }
 
 impl Iv {
+    #[cfg(feature = "aead-chacha")]
     #[inline]
     pub fn assume_unique_for_key(a: Block) -> Self {
         Self(a)
@@ -0,0 +1,169 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Sources of nonces for sealing many messages with the same key.
+
+use super::{Nonce, NONCE_LEN};
+use crate::{error, rand};
+
+/// A sequence of unique nonces.
+///
+/// A given `NonceSequence` must never return the same `Nonce` twice from
+/// `advance()`, and should be used with a single key.
+pub trait NonceSequence {
+    /// Returns the next nonce in the sequence.
+    ///
+    /// Fails if the sequence is exhausted.
+    fn advance(&mut self) -> Result<Nonce, error::Unspecified>;
+}
+
+/// The byte order of the counter in a `CounterNonceSequence`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Endianness {
+    /// Most significant byte first, as in TLS and most other protocols.
+    Big,
+
+    /// Least significant byte first.
+    Little,
+}
+
+/// The length of the fixed prefix of the nonces of a `CounterNonceSequence`.
+pub const COUNTER_NONCE_PREFIX_LEN: usize = NONCE_LEN - 8;
+
+/// A `NonceSequence` of nonces made of a fixed prefix followed by a 64-bit
+/// counter, as recommended in [RFC 5116 Section 3.2].
+///
+/// The sequence is exhausted when the counter would wrap around. Keys shared
+/// by several senders must give each one a distinct prefix.
+///
+/// [RFC 5116 Section 3.2]: https://tools.ietf.org/html/rfc5116#section-3.2
+pub struct CounterNonceSequence {
+    prefix: [u8; COUNTER_NONCE_PREFIX_LEN],
+    next: Option<u64>,
+    endianness: Endianness,
+}
+
+impl CounterNonceSequence {
+    /// Constructs a sequence that starts at the counter value `start`.
+    pub fn new(prefix: [u8; COUNTER_NONCE_PREFIX_LEN], start: u64, endianness: Endianness) -> Self {
+        Self {
+            prefix,
+            next: Some(start),
+            endianness,
+        }
+    }
+}
+
+impl NonceSequence for CounterNonceSequence {
+    fn advance(&mut self) -> Result<Nonce, error::Unspecified> {
+        let counter = self.next.ok_or(error::Unspecified)?;
+        self.next = counter.checked_add(1);
+
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce[..COUNTER_NONCE_PREFIX_LEN].copy_from_slice(&self.prefix);
+        let counter = match self.endianness {
+            Endianness::Big => counter.to_be_bytes(),
+            Endianness::Little => counter.to_le_bytes(),
+        };
+        nonce[COUNTER_NONCE_PREFIX_LEN..].copy_from_slice(&counter);
+        Ok(Nonce::assume_unique_for_key(nonce))
+    }
+}
+
+derive_debug_via_field!(CounterNonceSequence, next);
+
+/// A `NonceSequence` of random nonces from `rand::SystemRandom`.
+///
+/// Random 96-bit nonces are only unlikely to repeat for a limited number of
+/// messages; NIST SP 800-38D allows at most 2**32 of them per key. Wrap the
+/// sequence in a `LimitedNonceSequence` to enforce such a limit.
+pub struct RandomNonceSequence {
+    rng: rand::SystemRandom,
+}
+
+impl RandomNonceSequence {
+    /// Constructs a new `RandomNonceSequence`.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            rng: rand::SystemRandom::new(),
+        }
+    }
+}
+
+impl Default for RandomNonceSequence {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NonceSequence for RandomNonceSequence {
+    fn advance(&mut self) -> Result<Nonce, error::Unspecified> {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::SecureRandom::fill(&self.rng, &mut nonce)?;
+        Ok(Nonce::assume_unique_for_key(nonce))
+    }
+}
+
+impl core::fmt::Debug for RandomNonceSequence {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("RandomNonceSequence").finish()
+    }
+}
+
+/// A `NonceSequence` that fails after its inner sequence has been advanced a
+/// given number of times.
+///
+/// This bounds the number of messages sealed with a key.
+pub struct LimitedNonceSequence<N: NonceSequence> {
+    inner: N,
+    remaining: u64,
+}
+
+impl<N: NonceSequence> LimitedNonceSequence<N> {
+    /// Constructs a sequence that returns at most `limit` nonces from
+    /// `inner`.
+    pub fn new(inner: N, limit: u64) -> Self {
+        Self {
+            inner,
+            remaining: limit,
+        }
+    }
+
+    /// The number of nonces that may still be returned.
+    #[inline]
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+}
+
+impl<N: NonceSequence> NonceSequence for LimitedNonceSequence<N> {
+    fn advance(&mut self) -> Result<Nonce, error::Unspecified> {
+        if self.remaining == 0 {
+            return Err(error::Unspecified);
+        }
+        let nonce = self.inner.advance()?;
+        self.remaining -= 1;
+        Ok(nonce)
+    }
+}
+
+impl<N: NonceSequence> core::fmt::Debug for LimitedNonceSequence<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("LimitedNonceSequence")
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
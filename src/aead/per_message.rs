@@ -0,0 +1,136 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Per-message keys and nonces derived from a master key.
+//!
+//! Every key of an AEAD algorithm may only be used for a limited number of
+//! messages; e.g. [RFC 8452 Section 9] limits an `AES_256_GCM_SIV` key to
+//! 2**50 messages of up to 2**32 bytes, and fewer if the nonces are random.
+//! Instead of using the key directly, a `MasterKey` derives a fresh key and
+//! nonce for each message from the master key and an identifier of the
+//! message, with HKDF-SHA256. The limits then apply to each derived key, of
+//! which only one message is sealed with, instead of to the master key.
+//!
+//! The message identifier is sent along with the message, like a nonce. It
+//! should be unique for each message, e.g. a counter or at least 16 random
+//! bytes, but with `AES_128_GCM_SIV` or `AES_256_GCM_SIV` a repeated
+//! identifier only reveals whether the same message was sealed twice.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "aead-aes-gcm-siv")]
+//! # fn main() -> Result<(), ring::error::Unspecified> {
+//! use ring::{aead, rand::{self, SecureRandom}};
+//!
+//! let rng = rand::SystemRandom::new();
+//! let mut master_key_bytes = [0u8; 32];
+//! rng.fill(&mut master_key_bytes)?;
+//! let master_key =
+//!     aead::per_message::MasterKey::new(&aead::AES_256_GCM_SIV, &master_key_bytes)?;
+//!
+//! let mut message_id = [0u8; 16];
+//! rng.fill(&mut message_id)?;
+//! let (key, nonce) = master_key.sealing_key(&message_id)?;
+//! let mut in_out = b"hello".to_vec();
+//! in_out.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+//! let len = aead::seal_in_place(&key, nonce, aead::Aad::empty(), &mut in_out, aead::MAX_TAG_LEN)?;
+//!
+//! // The recipient derives the same key and nonce from `message_id`.
+//! let (key, nonce) = master_key.opening_key(&message_id)?;
+//! let plaintext = aead::open_in_place(&key, nonce, aead::Aad::empty(), 0, &mut in_out[..len])?;
+//! assert_eq!(plaintext, &b"hello"[..]);
+//!
+//! # Ok(())
+//! # }
+//! #
+//! # #[cfg(not(feature = "aead-aes-gcm-siv"))]
+//! # fn main() {}
+//! ```
+//!
+//! [RFC 8452 Section 9]: https://tools.ietf.org/html/rfc8452#section-9
+
+use super::{Algorithm, Nonce, OpeningKey, SealingKey, MAX_KEY_LEN, NONCE_LEN};
+use crate::{digest, error, hkdf, policy, zeroize::Zeroize};
+
+const LABEL: &[u8] = b"ring per-message AEAD key and nonce";
+
+/// A key from which a key and a nonce are derived for each message.
+pub struct MasterKey {
+    prk: hkdf::Prk,
+    algorithm: &'static Algorithm,
+}
+
+derive_debug_via_field!(MasterKey, algorithm);
+
+impl MasterKey {
+    /// Constructs a master key for the keys of `algorithm` from
+    /// `key_bytes`, which must be exactly `algorithm.key_len()` bytes long.
+    ///
+    /// Fails for `XCHACHA20_POLY1305`, whose nonces are longer than those of
+    /// the other algorithms.
+    pub fn new(
+        algorithm: &'static Algorithm,
+        key_bytes: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        if key_bytes.len() != algorithm.key_len() || algorithm.nonce_len() != NONCE_LEN {
+            return Err(error::Unspecified);
+        }
+        policy::check(algorithm.fips_approved())?;
+        Ok(Self {
+            prk: hkdf::Salt::new(&digest::SHA256, &[]).extract(key_bytes),
+            algorithm,
+        })
+    }
+
+    /// The algorithm of the derived keys.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+
+    /// Derives the key and nonce for sealing the message identified by
+    /// `message_id`.
+    pub fn sealing_key(
+        &self,
+        message_id: &[u8],
+    ) -> Result<(SealingKey, Nonce), error::Unspecified> {
+        self.derive(message_id, SealingKey::new)
+    }
+
+    /// Derives the key and nonce for opening the message identified by
+    /// `message_id`; they are the same as those `sealing_key()` derives.
+    pub fn opening_key(
+        &self,
+        message_id: &[u8],
+    ) -> Result<(OpeningKey, Nonce), error::Unspecified> {
+        self.derive(message_id, OpeningKey::new)
+    }
+
+    fn derive<K>(
+        &self,
+        message_id: &[u8],
+        new_key: fn(&'static Algorithm, &[u8]) -> Result<K, error::Unspecified>,
+    ) -> Result<(K, Nonce), error::Unspecified> {
+        let key_len = self.algorithm.key_len();
+        let mut okm = [0u8; MAX_KEY_LEN + NONCE_LEN];
+        let okm = &mut okm[..(key_len + NONCE_LEN)];
+        self.prk.fill_with_info_parts(&[LABEL, message_id], okm)?;
+        let (key_bytes, nonce_bytes) = okm.split_at_mut(key_len);
+        let key = new_key(self.algorithm, key_bytes);
+        key_bytes[..].zeroize();
+        let nonce = Nonce::try_assume_unique_for_key(nonce_bytes)?;
+        Ok((key?, nonce))
+    }
+}
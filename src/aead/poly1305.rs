@@ -19,7 +19,7 @@ use super::{
     block::{Block, BLOCK_LEN},
     Tag,
 };
-use crate::{bssl, c, error};
+use crate::{bssl, c, error, zeroize::Zeroize};
 
 /// A Poly1305 key.
 pub struct Key([Block; KEY_BLOCKS]);
@@ -30,6 +30,12 @@ impl From<[Block; KEY_BLOCKS]> for Key {
     }
 }
 
+impl Drop for Key {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 pub const KEY_BLOCKS: usize = 2;
 
 pub struct Context {
@@ -43,6 +49,12 @@ pub struct Context {
 struct Opaque([u8; OPAQUE_LEN]);
 const OPAQUE_LEN: usize = 192;
 
+impl Drop for Opaque {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl Context {
     #[inline]
     pub fn from_key(Key(key_and_nonce): Key) -> Self {
@@ -164,9 +176,9 @@ impl Funcs {
 
 /// Implements the original, non-IETF padding semantics.
 ///
-/// This is used by chacha20_poly1305_openssh and the standalone
-/// poly1305 test vectors.
-pub(super) fn sign(key: Key, input: &[u8]) -> Tag {
+/// This is used by chacha20_poly1305_openssh, `ring::poly1305`, and the
+/// standalone poly1305 test vectors.
+pub(crate) fn sign(key: Key, input: &[u8]) -> Tag {
     let mut ctx = Context::from_key(key);
     let remainder_len = input.len() % BLOCK_LEN;
     let full_blocks_len = input.len() - remainder_len;
@@ -14,14 +14,23 @@
 
 //! QUIC Header Protection.
 //!
-//! See draft-ietf-quic-tls.
+//! See [RFC 9001 Section 5.4]. `HeaderProtectionKey::new_mask()` computes
+//! the mask for a sample of the packet's ciphertext using a single AES block
+//! encryption or a single ChaCha20 block. `encrypt_in_place()` and
+//! `decrypt_in_place()` also apply the mask to the first byte and the packet
+//! number.
+//!
+//! [RFC 9001 Section 5.4]: https://tools.ietf.org/html/rfc9001#section-5.4
 
 use crate::{
-    aead::{aes, block::Block, chacha},
+    aead::{aes, block::Block},
     cpu, error,
     polyfill::convert::*,
 };
 
+#[cfg(feature = "aead-chacha")]
+use crate::aead::chacha;
+
 /// A key for generating QUIC Header Protection masks.
 pub struct HeaderProtectionKey {
     inner: KeyInner,
@@ -31,6 +40,7 @@ pub struct HeaderProtectionKey {
 #[allow(variant_size_differences)]
 enum KeyInner {
     Aes(aes::Key),
+    #[cfg(feature = "aead-chacha")]
     ChaCha20(chacha::Key),
 }
 
@@ -59,6 +69,45 @@ impl HeaderProtectionKey {
         Ok(out)
     }
 
+    /// Applies header protection to a packet's first byte and packet
+    /// number, in place.
+    ///
+    /// `sample` is as for `new_mask()`. The length of the packet number is
+    /// taken from the unprotected `first` byte; `packet_number` may be
+    /// longer, e.g. when it is the rest of the header, in which case only
+    /// the packet number is masked.
+    pub fn encrypt_in_place(
+        &self,
+        sample: &[u8],
+        first: &mut u8,
+        packet_number: &mut [u8],
+    ) -> Result<(), error::Unspecified> {
+        let mask = self.new_mask(sample)?;
+        let pn_len = packet_number_len(*first, packet_number)?;
+        *first ^= mask[0] & first_byte_mask(*first);
+        xor_packet_number(&mask, &mut packet_number[..pn_len]);
+        Ok(())
+    }
+
+    /// Removes header protection from a packet's first byte and packet
+    /// number, in place.
+    ///
+    /// This is the inverse of `encrypt_in_place()`. The length of the
+    /// packet number is taken from the `first` byte after it is unmasked.
+    pub fn decrypt_in_place(
+        &self,
+        sample: &[u8],
+        first: &mut u8,
+        packet_number: &mut [u8],
+    ) -> Result<(), error::Unspecified> {
+        let mask = self.new_mask(sample)?;
+        let unmasked_first = *first ^ (mask[0] & first_byte_mask(*first));
+        let pn_len = packet_number_len(unmasked_first, packet_number)?;
+        *first = unmasked_first;
+        xor_packet_number(&mask, &mut packet_number[..pn_len]);
+        Ok(())
+    }
+
     /// The key's algorithm.
     #[inline(always)]
     pub fn algorithm(&self) -> &'static Algorithm {
@@ -66,6 +115,34 @@ impl HeaderProtectionKey {
     }
 }
 
+// The low four bits of a long header's first byte, and the low five bits of
+// a short header's first byte, are protected.
+fn first_byte_mask(first: u8) -> u8 {
+    const LONG_HEADER_FORM: u8 = 0x80;
+    if first & LONG_HEADER_FORM != 0 {
+        0x0f
+    } else {
+        0x1f
+    }
+}
+
+fn packet_number_len(
+    unprotected_first: u8,
+    packet_number: &[u8],
+) -> Result<usize, error::Unspecified> {
+    let pn_len = usize::from(unprotected_first & 0x03) + 1;
+    if packet_number.len() < pn_len {
+        return Err(error::Unspecified);
+    }
+    Ok(pn_len)
+}
+
+fn xor_packet_number(mask: &[u8; 5], packet_number: &mut [u8]) {
+    for (pn, m) in packet_number.iter_mut().zip(mask[1..].iter()) {
+        *pn ^= m;
+    }
+}
+
 const SAMPLE_LEN: usize = super::TAG_LEN;
 
 /// A QUIC Header Protection Algorithm.
@@ -98,6 +175,7 @@ derive_debug_via_id!(Algorithm);
 enum AlgorithmID {
     AES_128,
     AES_256,
+    #[cfg(feature = "aead-chacha")]
     CHACHA20,
 }
 
@@ -138,6 +216,7 @@ fn aes_init_256(key: &[u8], cpu_features: cpu::Features) -> Result<KeyInner, err
 fn aes_new_mask(key: &KeyInner, sample: Block) -> [u8; 5] {
     let aes_key = match key {
         KeyInner::Aes(key) => key,
+        #[cfg(feature = "aead-chacha")]
         _ => unreachable!(),
     };
 
@@ -145,6 +224,7 @@ fn aes_new_mask(key: &KeyInner, sample: Block) -> [u8; 5] {
 }
 
 /// ChaCha20.
+#[cfg(feature = "aead-chacha")]
 pub static CHACHA20: Algorithm = Algorithm {
     key_len: chacha::KEY_LEN,
     init: chacha20_init,
@@ -152,11 +232,13 @@ pub static CHACHA20: Algorithm = Algorithm {
     id: AlgorithmID::CHACHA20,
 };
 
+#[cfg(feature = "aead-chacha")]
 fn chacha20_init(key: &[u8], _todo: cpu::Features) -> Result<KeyInner, error::Unspecified> {
     let chacha20_key: &[u8; chacha::KEY_LEN] = key.try_into_()?;
     Ok(KeyInner::ChaCha20(chacha::Key::from(chacha20_key)))
 }
 
+#[cfg(feature = "aead-chacha")]
 fn chacha20_new_mask(key: &KeyInner, sample: Block) -> [u8; 5] {
     let chacha20_key = match key {
         KeyInner::ChaCha20(key) => key,
@@ -0,0 +1,507 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! The STREAM construction, for sealing a long message, such as a file, as a
+//! sequence of chunks.
+//!
+//! STREAM, from [Online Authenticated-Encryption and its Nonce-Reuse
+//! Misuse-Resistance], seals each chunk with a nonce made of a per-message
+//! prefix, the chunk's index, and a flag that is set only for the last
+//! chunk:
+//!
+//! ```ascii-art
+//! [nonce prefix (7 bytes)][index (4 bytes, big-endian)][last (1 byte)]
+//! ```
+//!
+//! so that chunks can't be reordered, dropped, duplicated, or moved between
+//! messages, and the message can't be truncated at a chunk boundary, without
+//! opening failing. Each chunk can be opened, and its plaintext used, as
+//! soon as it arrives.
+//!
+//! Any algorithm in this module with `NONCE_LEN`-byte nonces can be used;
+//! `AES_256_GCM_SIV` is a good choice.
+//!
+//! `Encryptor` and `Decryptor` seal and open one chunk at a time, in place.
+//! With the `use_heap` feature, `Writer` is a `std::io::Write` that seals
+//! what's written to it, and `Reader` is a `std::io::Read` that pulls sealed
+//! chunks from another reader and opens them.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "aead-aes-gcm-siv")]
+//! # fn main() -> Result<(), ring::error::Unspecified> {
+//! use ring::aead::{self, stream};
+//! use std::io::{Read, Write};
+//!
+//! let key_bytes = [0x42; 32];
+//! let sealing_key = aead::SealingKey::new(&aead::AES_256_GCM_SIV, &key_bytes)?;
+//! let opening_key = aead::OpeningKey::new(&aead::AES_256_GCM_SIV, &key_bytes)?;
+//!
+//! // The nonce prefix must be unique for each message sealed with the key.
+//! let nonce_prefix = [1, 2, 3, 4, 5, 6, 7];
+//! let file = vec![0xab; 100_000];
+//!
+//! let mut writer = stream::Writer::new(&sealing_key, nonce_prefix, 4096, Vec::new())?;
+//! writer.write_all(&file).unwrap();
+//! let sealed = writer.finish().unwrap();
+//!
+//! let mut reader = stream::Reader::new(&opening_key, nonce_prefix, 4096, &sealed[..])?;
+//! let mut opened = Vec::new();
+//! reader.read_to_end(&mut opened).unwrap();
+//! assert_eq!(opened, file);
+//!
+//! // Truncating the sealed message at a chunk boundary is detected.
+//! let truncated = &sealed[..(4096 + aead::MAX_TAG_LEN)];
+//! let mut reader = stream::Reader::new(&opening_key, nonce_prefix, 4096, truncated)?;
+//! assert!(reader.read_to_end(&mut Vec::new()).is_err());
+//!
+//! # Ok(())
+//! # }
+//! #
+//! # #[cfg(not(feature = "aead-aes-gcm-siv"))]
+//! # fn main() {}
+//! ```
+//!
+//! [Online Authenticated-Encryption and its Nonce-Reuse Misuse-Resistance]:
+//!     https://eprint.iacr.org/2015/189.pdf
+
+use super::{
+    open_in_place, seal_in_place, Aad, Algorithm, Nonce, OpeningKey, SealingKey, NONCE_LEN,
+};
+use crate::error;
+use core::fmt;
+
+#[cfg(feature = "use_heap")]
+use super::TAG_LEN;
+#[cfg(feature = "use_heap")]
+use std::{io, vec::Vec};
+
+/// The length of the nonce prefix.
+pub const NONCE_PREFIX_LEN: usize = NONCE_LEN - COUNTER_LEN - 1;
+
+const COUNTER_LEN: usize = 4;
+
+/// Seals a message one chunk at a time.
+pub struct Encryptor<'a> {
+    key: &'a SealingKey,
+    nonces: Nonces,
+}
+
+impl<'a> Encryptor<'a> {
+    /// Starts sealing a message with `key`.
+    ///
+    /// `nonce_prefix` must be unique for every message sealed with `key`.
+    /// Fails unless `key`'s algorithm has `NONCE_LEN`-byte nonces.
+    pub fn new(
+        key: &'a SealingKey,
+        nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    ) -> Result<Self, error::Unspecified> {
+        check_algorithm(key.algorithm())?;
+        Ok(Self {
+            key,
+            nonces: Nonces::new(nonce_prefix),
+        })
+    }
+
+    /// Seals the next chunk, which isn't the last one, in place.
+    ///
+    /// `in_out` and `out_suffix_capacity` are as for `aead::seal_in_place()`.
+    /// Fails if the message already has 2**32 chunks.
+    pub fn seal_next_in_place<A: AsRef<[u8]>>(
+        &mut self,
+        aad: Aad<A>,
+        in_out: &mut [u8],
+        out_suffix_capacity: usize,
+    ) -> Result<usize, error::Unspecified> {
+        let nonce = self.nonces.nonce(false)?;
+        let out_len = seal_in_place(self.key, nonce, aad, in_out, out_suffix_capacity)?;
+        self.nonces.advance();
+        Ok(out_len)
+    }
+
+    /// Seals the last chunk in place, finishing the message.
+    ///
+    /// `in_out` and `out_suffix_capacity` are as for `aead::seal_in_place()`.
+    pub fn seal_last_in_place<A: AsRef<[u8]>>(
+        self,
+        aad: Aad<A>,
+        in_out: &mut [u8],
+        out_suffix_capacity: usize,
+    ) -> Result<usize, error::Unspecified> {
+        let nonce = self.nonces.nonce(true)?;
+        seal_in_place(self.key, nonce, aad, in_out, out_suffix_capacity)
+    }
+}
+
+impl fmt::Debug for Encryptor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("Encryptor")
+            .field("algorithm", self.key.algorithm())
+            .finish()
+    }
+}
+
+/// Opens a message one chunk at a time.
+///
+/// The chunks must be opened in order, each with the same additional data it
+/// was sealed with, and the message is only complete once
+/// `open_last_in_place()` succeeds. After any chunk fails to open, the
+/// `Decryptor` must be discarded.
+pub struct Decryptor<'a> {
+    key: &'a OpeningKey,
+    nonces: Nonces,
+}
+
+impl<'a> Decryptor<'a> {
+    /// Starts opening a message that was sealed with `key` and
+    /// `nonce_prefix`.
+    ///
+    /// Fails unless `key`'s algorithm has `NONCE_LEN`-byte nonces.
+    pub fn new(
+        key: &'a OpeningKey,
+        nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    ) -> Result<Self, error::Unspecified> {
+        check_algorithm(key.algorithm())?;
+        Ok(Self {
+            key,
+            nonces: Nonces::new(nonce_prefix),
+        })
+    }
+
+    /// Opens the next chunk, which isn't the last one, in place.
+    ///
+    /// `in_out` is the sealed chunk, including its tag. As for
+    /// `aead::open_in_place()` with an `in_prefix_len` of 0, the plaintext is
+    /// returned as a prefix of `in_out`.
+    pub fn open_next_in_place<'i, A: AsRef<[u8]>>(
+        &mut self,
+        aad: Aad<A>,
+        in_out: &'i mut [u8],
+    ) -> Result<&'i mut [u8], error::Unspecified> {
+        let nonce = self.nonces.nonce(false)?;
+        let plaintext = open_in_place(self.key, nonce, aad, 0, in_out)?;
+        self.nonces.advance();
+        Ok(plaintext)
+    }
+
+    /// Opens the last chunk in place, finishing the message.
+    ///
+    /// `in_out` is as for `open_next_in_place()`.
+    pub fn open_last_in_place<A: AsRef<[u8]>>(
+        self,
+        aad: Aad<A>,
+        in_out: &mut [u8],
+    ) -> Result<&mut [u8], error::Unspecified> {
+        let nonce = self.nonces.nonce(true)?;
+        open_in_place(self.key, nonce, aad, 0, in_out)
+    }
+}
+
+impl fmt::Debug for Decryptor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("Decryptor")
+            .field("algorithm", self.key.algorithm())
+            .finish()
+    }
+}
+
+/// A `std::io::Write` that seals what's written to it as a STREAM message
+/// with fixed-length chunks, and writes the sealed chunks to another writer.
+///
+/// Every chunk but the last has `chunk_len` bytes of plaintext, so it is
+/// `chunk_len + key.algorithm().tag_len()` bytes long when sealed. The last
+/// chunk, which may be empty, is only sealed by `finish()`; until then the
+/// sealed message is incomplete and won't open.
+///
+/// After any operation fails, the `Writer` must be discarded.
+#[cfg(feature = "use_heap")]
+pub struct Writer<'a, W: io::Write> {
+    encryptor: Encryptor<'a>,
+    inner: W,
+    chunk_len: usize,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "use_heap")]
+impl<'a, W: io::Write> Writer<'a, W> {
+    /// Constructs a `Writer` that seals with `key` and `nonce_prefix`, in
+    /// chunks of `chunk_len` bytes of plaintext, and writes to `inner`.
+    ///
+    /// Fails as for `Encryptor::new()`, or if `chunk_len` is zero.
+    pub fn new(
+        key: &'a SealingKey,
+        nonce_prefix: [u8; NONCE_PREFIX_LEN],
+        chunk_len: usize,
+        inner: W,
+    ) -> Result<Self, error::Unspecified> {
+        if chunk_len == 0 {
+            return Err(error::Unspecified);
+        }
+        let sealed_chunk_len = chunk_len.checked_add(TAG_LEN).ok_or(error::Unspecified)?;
+        Ok(Self {
+            encryptor: Encryptor::new(key, nonce_prefix)?,
+            inner,
+            chunk_len,
+            buffer: Vec::with_capacity(sealed_chunk_len),
+        })
+    }
+
+    /// Seals and writes the last chunk, and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let plaintext_len = self.buffer.len();
+        self.buffer.resize(plaintext_len + TAG_LEN, 0);
+        let _ = self
+            .encryptor
+            .seal_last_in_place(Aad::empty(), &mut self.buffer, TAG_LEN)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        self.inner.write_all(&self.buffer)?;
+        Ok(self.inner)
+    }
+}
+
+#[cfg(feature = "use_heap")]
+impl<W: io::Write> io::Write for Writer<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        // A full chunk is only sealed once it's known not to be the last one.
+        if self.buffer.len() == self.chunk_len {
+            self.buffer.resize(self.chunk_len + TAG_LEN, 0);
+            let _ = self
+                .encryptor
+                .seal_next_in_place(Aad::empty(), &mut self.buffer, TAG_LEN)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            self.inner.write_all(&self.buffer)?;
+            self.buffer.clear();
+        }
+
+        let todo = core::cmp::min(self.chunk_len - self.buffer.len(), buf.len());
+        self.buffer.extend_from_slice(&buf[..todo]);
+        Ok(todo)
+    }
+
+    /// Flushes the inner writer. The buffered plaintext of the current chunk
+    /// isn't written until the chunk is full, or until `finish()`.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(feature = "use_heap")]
+impl<W: io::Write> fmt::Debug for Writer<'_, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("Writer")
+            .field("algorithm", self.encryptor.key.algorithm())
+            .field("chunk_len", &self.chunk_len)
+            .finish()
+    }
+}
+
+/// A `std::io::Read` that reads a STREAM message sealed by `Writer` from
+/// another reader, and opens it.
+///
+/// Each chunk's plaintext is returned only after the chunk is opened, and
+/// the end of the message is only reported after the last chunk is opened,
+/// so a message that was truncated or tampered with results in an error
+/// with the kind `std::io::ErrorKind::InvalidData`. After any error, the
+/// `Reader` must be discarded; the data already read from it is
+/// authentic, but incomplete.
+#[cfg(feature = "use_heap")]
+pub struct Reader<'a, R: io::Read> {
+    decryptor: Option<Decryptor<'a>>,
+    inner: R,
+    chunk_len: usize,
+
+    // Sealed data read from `inner`, up to one more byte than a sealed
+    // chunk, which tells whether the chunk is the last one.
+    sealed: Vec<u8>,
+
+    // The plaintext of the current chunk, and how much of it has been read.
+    plaintext: Vec<u8>,
+    plaintext_read: usize,
+
+    failed: bool,
+}
+
+#[cfg(feature = "use_heap")]
+impl<'a, R: io::Read> Reader<'a, R> {
+    /// Constructs a `Reader` that opens a message sealed with `key` and
+    /// `nonce_prefix`, in chunks of `chunk_len` bytes of plaintext, read from
+    /// `inner`.
+    ///
+    /// Fails as for `Decryptor::new()`, or if `chunk_len` is zero.
+    pub fn new(
+        key: &'a OpeningKey,
+        nonce_prefix: [u8; NONCE_PREFIX_LEN],
+        chunk_len: usize,
+        inner: R,
+    ) -> Result<Self, error::Unspecified> {
+        if chunk_len == 0 {
+            return Err(error::Unspecified);
+        }
+        let sealed_chunk_len = chunk_len.checked_add(TAG_LEN).ok_or(error::Unspecified)?;
+        Ok(Self {
+            decryptor: Some(Decryptor::new(key, nonce_prefix)?),
+            inner,
+            chunk_len,
+            sealed: Vec::with_capacity(sealed_chunk_len + 1),
+            plaintext: Vec::with_capacity(sealed_chunk_len),
+            plaintext_read: 0,
+            failed: false,
+        })
+    }
+
+    /// Returns the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    // Reads and opens the next chunk. Does nothing after the last chunk.
+    fn open_next_chunk(&mut self) -> io::Result<()> {
+        let sealed_chunk_len = self.chunk_len + TAG_LEN;
+
+        let mut at_end = false;
+        while self.sealed.len() < sealed_chunk_len + 1 {
+            let filled = self.sealed.len();
+            self.sealed.resize(sealed_chunk_len + 1, 0);
+            let result = self.inner.read(&mut self.sealed[filled..]);
+            let read_len = *result.as_ref().unwrap_or(&0);
+            self.sealed.truncate(filled + read_len);
+            match result {
+                Ok(0) => {
+                    at_end = true;
+                    break;
+                }
+                Ok(_) => (),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => {
+                    return Err(e);
+                }
+            }
+        }
+
+        let sealed_len = if at_end {
+            self.sealed.len()
+        } else {
+            sealed_chunk_len
+        };
+        self.plaintext.clear();
+        self.plaintext.extend(self.sealed.drain(..sealed_len));
+        self.plaintext_read = 0;
+
+        let result = if at_end {
+            match self.decryptor.take() {
+                Some(decryptor) => decryptor
+                    .open_last_in_place(Aad::empty(), &mut self.plaintext)
+                    .map(|plaintext| plaintext.len()),
+                None => Ok(0),
+            }
+        } else {
+            match &mut self.decryptor {
+                Some(decryptor) => decryptor
+                    .open_next_in_place(Aad::empty(), &mut self.plaintext)
+                    .map(|plaintext| plaintext.len()),
+                None => Ok(0),
+            }
+        };
+        match result {
+            Ok(plaintext_len) => {
+                self.plaintext.truncate(plaintext_len);
+                Ok(())
+            }
+            Err(e) => {
+                self.plaintext.clear();
+                self.failed = true;
+                Err(io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "use_heap")]
+impl<R: io::Read> io::Read for Reader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.failed {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                error::Unspecified,
+            ));
+        }
+        while self.plaintext_read == self.plaintext.len() {
+            if buf.is_empty() || self.decryptor.is_none() {
+                return Ok(0);
+            }
+            self.open_next_chunk()?;
+        }
+        let available = &self.plaintext[self.plaintext_read..];
+        let todo = core::cmp::min(available.len(), buf.len());
+        buf[..todo].copy_from_slice(&available[..todo]);
+        self.plaintext_read += todo;
+        Ok(todo)
+    }
+}
+
+#[cfg(feature = "use_heap")]
+impl<R: io::Read> fmt::Debug for Reader<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("Reader")
+            .field("chunk_len", &self.chunk_len)
+            .finish()
+    }
+}
+
+fn check_algorithm(algorithm: &Algorithm) -> Result<(), error::Unspecified> {
+    if algorithm.nonce_len() != NONCE_LEN {
+        return Err(error::Unspecified);
+    }
+    Ok(())
+}
+
+// The nonces of the chunks of a message.
+struct Nonces {
+    prefix: [u8; NONCE_PREFIX_LEN],
+    counter: u32,
+    exhausted: bool,
+}
+
+impl Nonces {
+    fn new(prefix: [u8; NONCE_PREFIX_LEN]) -> Self {
+        Self {
+            prefix,
+            counter: 0,
+            exhausted: false,
+        }
+    }
+
+    fn nonce(&self, last: bool) -> Result<Nonce, error::Unspecified> {
+        if self.exhausted {
+            return Err(error::Unspecified);
+        }
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce[..NONCE_PREFIX_LEN].copy_from_slice(&self.prefix);
+        nonce[NONCE_PREFIX_LEN..][..COUNTER_LEN].copy_from_slice(&self.counter.to_be_bytes());
+        nonce[NONCE_LEN - 1] = u8::from(last);
+        Ok(Nonce::assume_unique_for_key(nonce))
+    }
+
+    fn advance(&mut self) {
+        match self.counter.checked_add(1) {
+            Some(counter) => self.counter = counter,
+            None => self.exhausted = true,
+        }
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! XChaCha20-Poly1305 sealing and opening with 192-bit nonces.
+//!
+//! Keys for `XCHACHA20_POLY1305` are constructed like those for the other
+//! algorithms, as `aead::SealingKey` and `aead::OpeningKey`, but they must be
+//! used with the functions of this module, which take the longer `Nonce`
+//! defined here; the functions in `aead` reject them. Nonces are long enough
+//! that generating each one randomly is safe, even for very many messages
+//! sealed with the same key.
+
+use super::{
+    chacha, chacha20_poly1305, Aad, Algorithm, AlgorithmID, KeyInner, OpeningKey, SealingKey,
+};
+use crate::{cpu, error, polyfill::convert::*};
+use core::ops::RangeFrom;
+
+/// XChaCha20-Poly1305 as described in [draft-irtf-cfrg-xchacha].
+///
+/// The keys are 256 bits long and the nonces are 192 bits long. Messages are
+/// sealed and opened with `seal_in_place()` and `open_in_place()` of this
+/// module.
+///
+/// [draft-irtf-cfrg-xchacha]:
+///     https://tools.ietf.org/html/draft-irtf-cfrg-xchacha-03
+pub static XCHACHA20_POLY1305: Algorithm = Algorithm {
+    key_len: chacha::KEY_LEN,
+    init: xchacha20_poly1305_init,
+    // These are only given the ChaCha20-Poly1305 subkey.
+    seal: chacha20_poly1305::chacha20_poly1305_seal,
+    open: chacha20_poly1305::chacha20_poly1305_open,
+    id: AlgorithmID::XCHACHA20_POLY1305,
+    max_input_len: super::max_input_len(64, 1),
+};
+
+/// The length of an XChaCha20-Poly1305 nonce.
+pub const NONCE_LEN: usize = 192 / 8;
+
+/// A nonce for a single XChaCha20-Poly1305 opening or sealing operation.
+///
+/// The user must ensure, for a particular key, that each nonce is unique.
+///
+/// `Nonce` intentionally doesn't implement `Clone` to ensure that each one is
+/// consumed at most once.
+pub struct Nonce([u8; NONCE_LEN]);
+
+impl Nonce {
+    /// Constructs a `Nonce` with the given value, assuming that the value is
+    /// unique for the lifetime of the key it is being used with.
+    ///
+    /// Fails if `value` isn't `NONCE_LEN` bytes long.
+    #[inline]
+    pub fn try_assume_unique_for_key(value: &[u8]) -> Result<Self, error::Unspecified> {
+        let value: &[u8; NONCE_LEN] = value.try_into_()?;
+        Ok(Self::assume_unique_for_key(*value))
+    }
+
+    /// Constructs a `Nonce` with the given value, assuming that the value is
+    /// unique for the lifetime of the key it is being used with.
+    #[inline]
+    pub fn assume_unique_for_key(value: [u8; NONCE_LEN]) -> Self {
+        Self(value)
+    }
+}
+
+impl AsRef<[u8; NONCE_LEN]> for Nonce {
+    fn as_ref(&self) -> &[u8; NONCE_LEN] {
+        &self.0
+    }
+}
+
+/// Encrypts and signs (“seals”) data in place with an XChaCha20-Poly1305
+/// key.
+///
+/// This is like `aead::seal_in_place()` except for the type of `nonce`.
+/// Fails unless `key`'s algorithm is `XCHACHA20_POLY1305`.
+pub fn seal_in_place<A: AsRef<[u8]>>(
+    key: &SealingKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
+    in_out: &mut [u8],
+    out_suffix_capacity: usize,
+) -> Result<usize, error::Unspecified> {
+    let (subkey, nonce) = subkey_and_nonce(&key.key.inner, nonce)?;
+    super::seal_in_place_(
+        &key.key,
+        &KeyInner::ChaCha20Poly1305(subkey),
+        nonce,
+        Aad::from(aad.as_ref()),
+        in_out,
+        out_suffix_capacity,
+    )
+    .map_err(error::Unspecified::from)
+}
+
+/// Authenticates and decrypts (“opens”) data in place with an
+/// XChaCha20-Poly1305 key.
+///
+/// This is like `aead::open_in_place()` except for the type of `nonce`.
+/// Fails unless `key`'s algorithm is `XCHACHA20_POLY1305`.
+pub fn open_in_place<'a, A: AsRef<[u8]>>(
+    key: &OpeningKey,
+    nonce: Nonce,
+    Aad(aad): Aad<A>,
+    in_prefix_len: usize,
+    ciphertext_and_tag_modified_in_place: &'a mut [u8],
+) -> Result<&'a mut [u8], error::Unspecified> {
+    let (subkey, nonce) = subkey_and_nonce(&key.key.inner, nonce)?;
+    super::open_in_place_(
+        &key.key,
+        &KeyInner::ChaCha20Poly1305(subkey),
+        nonce,
+        Aad::from(aad.as_ref()),
+        in_prefix_len,
+        ciphertext_and_tag_modified_in_place,
+    )
+    .map_err(error::Unspecified::from)
+}
+
+/// Authenticates and decrypts (“opens”) data in place with an
+/// XChaCha20-Poly1305 key, moving the plaintext to the start of `in_out`.
+///
+/// This is like `aead::open_within()` except for the type of `nonce`.
+/// Fails unless `key`'s algorithm is `XCHACHA20_POLY1305`.
+#[inline]
+pub fn open_within<'a, A: AsRef<[u8]>>(
+    key: &OpeningKey,
+    nonce: Nonce,
+    aad: Aad<A>,
+    in_out: &'a mut [u8],
+    ciphertext_and_tag: RangeFrom<usize>,
+) -> Result<&'a mut [u8], error::Unspecified> {
+    open_in_place(key, nonce, aad, ciphertext_and_tag.start, in_out)
+}
+
+pub(super) struct Key([u8; chacha::KEY_LEN]);
+
+fn xchacha20_poly1305_init(
+    key: &[u8],
+    _todo: cpu::Features,
+) -> Result<KeyInner, error::Unspecified> {
+    let key: &[u8; chacha::KEY_LEN] = key.try_into_()?;
+    Ok(KeyInner::XChaCha20Poly1305(Key(*key)))
+}
+
+fn subkey_and_nonce(
+    key: &KeyInner,
+    Nonce(nonce): Nonce,
+) -> Result<(chacha::Key, super::Nonce), error::Unspecified> {
+    match key {
+        KeyInner::XChaCha20Poly1305(Key(key)) => Ok(derive_subkey(key, &nonce)),
+        _ => Err(error::Unspecified),
+    }
+}
+
+/// Derives the ChaCha20 key and nonce that XChaCha20 uses for `nonce`, as
+/// specified in [draft-irtf-cfrg-xchacha] Section 2.3.
+///
+/// Also used by `paseto`.
+///
+/// [draft-irtf-cfrg-xchacha]:
+///     https://tools.ietf.org/html/draft-irtf-cfrg-xchacha-03
+pub(crate) fn derive_subkey(
+    key: &[u8; chacha::KEY_LEN],
+    nonce: &[u8; NONCE_LEN],
+) -> (chacha::Key, super::Nonce) {
+    let (hchacha20_input, nonce) = nonce.split_at(chacha::HCHACHA20_INPUT_LEN);
+    let mut input = [0u8; chacha::HCHACHA20_INPUT_LEN];
+    input.copy_from_slice(hchacha20_input);
+    let subkey = chacha::Key::from(&chacha::hchacha20(key, &input));
+
+    let mut chacha_nonce = [0u8; super::NONCE_LEN];
+    chacha_nonce[4..].copy_from_slice(nonce);
+    (subkey, super::Nonce::assume_unique_for_key(chacha_nonce))
+}
@@ -0,0 +1,312 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! AES-CMAC, as specified in [RFC 4493] for AES-128 and in
+//! [NIST SP 800-38B] for AES-256.
+//!
+//! The API mirrors `ring::hmac`: construct a `Key` once, then use it for any
+//! number of `sign` and `verify` operations, or use `Context` when the input
+//! is in multiple parts.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::{aes_cmac, rand};
+//!
+//! let rng = rand::SystemRandom::new();
+//! let key = aes_cmac::Key::generate(&aes_cmac::AES_128, &rng)?;
+//!
+//! let msg = "hello, world";
+//!
+//! let tag = aes_cmac::sign(&key, msg.as_bytes());
+//!
+//! // [We give access to the message to an untrusted party, and they give it
+//! // back to us. We need to verify they didn't tamper with it.]
+//!
+//! aes_cmac::verify(&key, msg.as_bytes(), tag.as_ref())?;
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [RFC 4493]: https://tools.ietf.org/html/rfc4493
+//! [NIST SP 800-38B]:
+//!     https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-38B.pdf
+
+use crate::{
+    aead::{
+        aes,
+        block::{Block, BLOCK_LEN},
+    },
+    constant_time, cpu, error, rand,
+};
+
+#[cfg(feature = "audit")]
+use crate::audit;
+
+/// An AES-CMAC tag.
+///
+/// For a given tag `t`, use `t.as_ref()` to get the tag value as a byte slice.
+#[derive(Clone, Copy)]
+pub struct Tag(Block);
+
+impl AsRef<[u8]> for Tag {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+derive_debug_self_as_ref_hex_bytes!(Tag);
+
+/// The length of an AES-CMAC tag.
+pub const TAG_LEN: usize = BLOCK_LEN;
+
+/// A key for AES-CMAC.
+pub struct Key {
+    aes_key: aes::Key,
+    subkeys: [Block; 2],
+    algorithm: &'static Algorithm,
+    #[cfg(feature = "audit")]
+    fingerprint: audit::Fingerprint,
+}
+
+derive_debug_via_field!(Key, algorithm);
+
+impl Key {
+    /// Generate a key for `algorithm` with a random value generated from
+    /// `rng`.
+    pub fn generate(
+        algorithm: &'static Algorithm,
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<Self, error::Unspecified> {
+        let mut key_bytes = [0; MAX_KEY_LEN];
+        let key_bytes = &mut key_bytes[..algorithm.key_len];
+        rng.fill(key_bytes)?;
+        Self::new(algorithm, key_bytes)
+    }
+
+    /// Construct a key for `algorithm` with the given key value.
+    ///
+    /// `key_value` must be exactly `algorithm.key_len()` bytes long.
+    pub fn new(
+        algorithm: &'static Algorithm,
+        key_value: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        let aes_key = aes::Key::new(key_value, algorithm.variant, cpu::features())?;
+
+        // RFC 4493 Section 2.3.
+        let k1 = dbl(aes_key.encrypt_block(Block::zero()));
+        let k2 = dbl(k1);
+        Ok(Self {
+            aes_key,
+            subkeys: [k1, k2],
+            algorithm,
+            #[cfg(feature = "audit")]
+            fingerprint: audit::Fingerprint::new(key_value),
+        })
+    }
+
+    /// The key's algorithm.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+}
+
+/// An AES-CMAC algorithm.
+pub struct Algorithm {
+    variant: aes::Variant,
+    key_len: usize,
+    id: AlgorithmID,
+}
+
+impl Algorithm {
+    /// The length of the key.
+    #[inline(always)]
+    pub fn key_len(&self) -> usize {
+        self.key_len
+    }
+}
+
+derive_debug_via_id!(Algorithm);
+
+#[derive(Debug, Eq, PartialEq)]
+enum AlgorithmID {
+    AES_128_CMAC,
+    AES_256_CMAC,
+}
+
+impl PartialEq for Algorithm {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Algorithm {}
+
+/// AES-CMAC with a 128-bit key.
+pub static AES_128: Algorithm = Algorithm {
+    variant: aes::Variant::AES_128,
+    key_len: 16,
+    id: AlgorithmID::AES_128_CMAC,
+};
+
+/// AES-CMAC with a 256-bit key.
+pub static AES_256: Algorithm = Algorithm {
+    variant: aes::Variant::AES_256,
+    key_len: 32,
+    id: AlgorithmID::AES_256_CMAC,
+};
+
+const MAX_KEY_LEN: usize = 32;
+
+/// A context for multi-step (Init-Update-Finish) AES-CMAC signing.
+///
+/// Use `sign` for single-step signing.
+#[derive(Clone)]
+pub struct Context<'a> {
+    key: &'a Key,
+    state: Block,
+
+    // The last block of the input, which may be a whole block, is kept here
+    // until `sign()`.
+    pending: [u8; BLOCK_LEN],
+    pending_len: usize,
+
+    #[cfg(feature = "audit")]
+    len: usize,
+}
+
+impl core::fmt::Debug for Context<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Context")
+            .field("algorithm", self.key.algorithm)
+            .finish()
+    }
+}
+
+impl<'a> Context<'a> {
+    /// Constructs a new signing context using the given key.
+    pub fn with_key(key: &'a Key) -> Self {
+        Self {
+            key,
+            state: Block::zero(),
+            pending: [0u8; BLOCK_LEN],
+            pending_len: 0,
+            #[cfg(feature = "audit")]
+            len: 0,
+        }
+    }
+
+    /// Updates the tag calculation with all the data in `data`. `update` may
+    /// be called zero or more times until `sign` is called.
+    pub fn update(&mut self, mut data: &[u8]) {
+        #[cfg(feature = "audit")]
+        {
+            self.len = self.len.wrapping_add(data.len());
+        }
+        while !data.is_empty() {
+            if self.pending_len == BLOCK_LEN {
+                self.state.bitxor_assign(Block::from(&self.pending));
+                self.state = self.key.aes_key.encrypt_block(self.state);
+                self.pending_len = 0;
+            }
+            let todo = core::cmp::min(BLOCK_LEN - self.pending_len, data.len());
+            self.pending[self.pending_len..][..todo].copy_from_slice(&data[..todo]);
+            self.pending_len += todo;
+            data = &data[todo..];
+        }
+    }
+
+    /// Finalizes the tag calculation and returns the tag. `sign` consumes
+    /// the context so it cannot be (mis-)used after `sign` has been called.
+    ///
+    /// It is generally not safe to implement verification by comparing the
+    /// return value of `sign` to a tag. Use `verify` for verification
+    /// instead.
+    pub fn sign(self) -> Tag {
+        #[cfg(feature = "audit")]
+        self.record(audit::Operation::HmacSign);
+        Tag(self.finish())
+    }
+
+    // Like `sign`, but not audited; AES-SIV uses this for S2V.
+    pub(crate) fn finish(mut self) -> Block {
+        let last = if self.pending_len == BLOCK_LEN {
+            let mut last = Block::from(&self.pending);
+            last.bitxor_assign(self.key.subkeys[0]);
+            last
+        } else {
+            let mut last = pad(&self.pending[..self.pending_len]);
+            last.bitxor_assign(self.key.subkeys[1]);
+            last
+        };
+        self.state.bitxor_assign(last);
+        self.key.aes_key.encrypt_block(self.state)
+    }
+
+    #[cfg(feature = "audit")]
+    fn record(&self, operation: audit::Operation) {
+        audit::record(
+            self.key.algorithm,
+            operation,
+            &self.key.fingerprint,
+            self.len,
+        );
+    }
+}
+
+/// Calculates the AES-CMAC tag of `data` using the key `key` in one step.
+///
+/// Use `Context` to calculate tags where the input is in multiple parts.
+///
+/// It is generally not safe to implement verification by comparing the
+/// return value of `sign` to a tag. Use `verify` for verification instead.
+pub fn sign(key: &Key, data: &[u8]) -> Tag {
+    let mut ctx = Context::with_key(key);
+    ctx.update(data);
+    ctx.sign()
+}
+
+/// Calculates the AES-CMAC tag of `data` using the key `key`, and verifies
+/// whether the resultant value equals `tag`, in one step.
+///
+/// The verification will be done in constant time to prevent timing attacks.
+pub fn verify(key: &Key, data: &[u8], tag: &[u8]) -> Result<(), error::Unspecified> {
+    let mut ctx = Context::with_key(key);
+    ctx.update(data);
+    #[cfg(feature = "audit")]
+    ctx.record(audit::Operation::HmacVerify);
+    constant_time::verify_slices_are_equal(ctx.finish().as_ref(), tag)
+}
+
+// Pads a partial block with a one bit and then zeros.
+pub(crate) fn pad(partial: &[u8]) -> Block {
+    let mut block = Block::zero();
+    block.partial_copy_from(partial);
+    block.as_mut()[partial.len()] = 0x80;
+    block
+}
+
+// Multiplies by x in GF(2**128), with the big-endian bit order of RFC 4493.
+pub(crate) fn dbl(block: Block) -> Block {
+    let input = block.as_ref();
+    let mut output = [0u8; BLOCK_LEN];
+    for i in 0..(BLOCK_LEN - 1) {
+        output[i] = (input[i] << 1) | (input[i + 1] >> 7);
+    }
+    // Reduce without branching on the secret high bit.
+    output[BLOCK_LEN - 1] = (input[BLOCK_LEN - 1] << 1) ^ (0x87 & 0u8.wrapping_sub(input[0] >> 7));
+    Block::from(&output)
+}
@@ -16,9 +16,10 @@
 //!
 //! # Example
 //!
-//! Note that this example uses X25519, but ECDH using NIST P-256/P-384 is done
-//! exactly the same way, just substituting
-//! `agreement::ECDH_P256`/`agreement::ECDH_P384` for `agreement::X25519`.
+//! Note that this example uses X25519, but ECDH using NIST P-256/P-384 or
+//! secp256k1 is done exactly the same way, just substituting
+//! `agreement::ECDH_P256`/`agreement::ECDH_P384`/`agreement::ECDH_K256` for
+//! `agreement::X25519`.
 //!
 //! ```
 //! use ring::{agreement, rand};
@@ -62,12 +63,15 @@
 // The "NSA Guide" steps here are from from section 3.1, "Ephemeral Unified
 // Model."
 
-use crate::{cpu, ec, error, rand};
+use crate::{cpu, ec, error, pkcs8, policy, rand};
 use untrusted;
 
+pub mod x25519;
+
 pub use crate::ec::{
     curve25519::x25519::X25519,
-    suite_b::ecdh::{ECDH_P256, ECDH_P384},
+    curve448::x448::X448,
+    suite_b::ecdh::{ECDH_K256, ECDH_P256, ECDH_P384},
 };
 
 /// A key agreement algorithm.
@@ -78,10 +82,25 @@ pub struct Algorithm {
         private_key: &ec::Seed,
         peer_public_key: untrusted::Input,
     ) -> Result<(), error::Unspecified>,
+    pub(crate) pkcs8_template: &'static pkcs8::Template,
+    pub(crate) private_key_from_pkcs8: fn(
+        input: untrusted::Input,
+        cpu_features: cpu::Features,
+    ) -> Result<ec::Seed, error::KeyRejected>,
 }
 
 derive_debug_via_field!(Algorithm, curve);
 
+impl Algorithm {
+    // Whether the algorithm is allowed by `policy::Policy::FipsApproved`.
+    fn fips_approved(&self) -> bool {
+        match self.curve.id {
+            ec::CurveID::P256 | ec::CurveID::P384 => true,
+            _ => false,
+        }
+    }
+}
+
 impl Eq for Algorithm {}
 impl PartialEq for Algorithm {
     fn eq(&self, other: &Algorithm) -> bool {
@@ -103,6 +122,7 @@ impl EphemeralPrivateKey {
         alg: &'static Algorithm,
         rng: &dyn rand::SecureRandom,
     ) -> Result<Self, error::Unspecified> {
+        policy::check(alg.fips_approved())?;
         let cpu_features = cpu::features();
 
         // NSA Guide Step 1.
@@ -124,12 +144,119 @@ impl EphemeralPrivateKey {
         self.private_key.compute_public_key().map(PublicKey)
     }
 
+    /// Computes the public key from the private key, in the compressed form
+    /// of [SEC 1] Section 2.3.3.
+    ///
+    /// Fails for algorithms that have no compressed form, such as `X25519`.
+    ///
+    /// [SEC 1]: http://www.secg.org/sec1-v2.pdf
+    pub fn compute_compressed_public_key(&self) -> Result<PublicKey, error::Unspecified> {
+        // NSA Guide Step 1, as in `compute_public_key()`.
+        self.private_key
+            .compute_public_key()?
+            .compressed()
+            .map(PublicKey)
+    }
+
     #[cfg(test)]
     pub fn bytes(&self) -> &[u8] {
         self.private_key.bytes_less_safe()
     }
 }
 
+/// A private key that may be used for any number of key agreements.
+///
+/// Reusing a private key gives up the forward secrecy that an
+/// `EphemeralPrivateKey` provides: anybody who later obtains the private key
+/// can recompute the shared secret of every past key agreement done with it.
+/// Use `ReusablePrivateKey` only for protocols that require a static key,
+/// e.g. the static keys of the Noise protocols or static-static ECDH, and
+/// prefer `EphemeralPrivateKey` everywhere else.
+pub struct ReusablePrivateKey {
+    private_key: ec::Seed,
+    alg: &'static Algorithm,
+}
+
+derive_debug_via_field!(ReusablePrivateKey, alg);
+
+impl ReusablePrivateKey {
+    /// Generate a new reusable private key for the given algorithm.
+    pub fn generate(
+        alg: &'static Algorithm,
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<Self, error::Unspecified> {
+        policy::check(alg.fips_approved())?;
+        let private_key = ec::Seed::generate(alg.curve, rng, cpu::features())?;
+        Ok(Self { private_key, alg })
+    }
+
+    /// Generates a new private key and returns it serialized as a PKCS#8
+    /// document.
+    ///
+    /// For `X25519` and `X448` the PKCS#8 document will be a v2
+    /// `OneAsymmetricKey` with the public key, as described in
+    /// [RFC 5958 Section 2] and [RFC 8410]. For `ECDH_P256`, `ECDH_P384`, and
+    /// `ECDH_K256` it will be a v1 id-ecPublicKey `ECPrivateKey` with the
+    /// public key, the same format that
+    /// `signature::EcdsaKeyPair::generate_pkcs8()` uses.
+    ///
+    /// [RFC 5958 Section 2]: https://tools.ietf.org/html/rfc5958#section-2
+    /// [RFC 8410]: https://tools.ietf.org/html/rfc8410
+    pub fn generate_pkcs8(
+        alg: &'static Algorithm,
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<pkcs8::Document, error::Unspecified> {
+        policy::check(alg.fips_approved())?;
+        let private_key = ec::Seed::generate(alg.curve, rng, cpu::features())?;
+        let public_key = private_key.compute_public_key()?;
+        Ok(pkcs8::wrap_key(
+            alg.pkcs8_template,
+            private_key.bytes_less_safe(),
+            public_key.as_ref(),
+        ))
+    }
+
+    /// Constructs a reusable private key by parsing an unencrypted PKCS#8
+    /// private key in the format produced by `generate_pkcs8()`.
+    ///
+    /// For `X25519` and `X448`, both v1 and v2 documents are accepted; if the
+    /// public key is present then it is verified to be consistent with the
+    /// private key. For `ECDH_P256`, `ECDH_P384`, and `ECDH_K256`, the input
+    /// must be a v1 document whose `ECPrivateKey` contains the public key, as
+    /// required by `signature::EcdsaKeyPair::from_pkcs8()`.
+    pub fn from_pkcs8(alg: &'static Algorithm, pkcs8: &[u8]) -> Result<Self, error::KeyRejected> {
+        policy::check(alg.fips_approved())?;
+        let private_key =
+            (alg.private_key_from_pkcs8)(untrusted::Input::from(pkcs8), cpu::features())?;
+        Ok(Self { private_key, alg })
+    }
+
+    /// Computes the public key from the private key.
+    #[inline]
+    pub fn compute_public_key(&self) -> Result<PublicKey, error::Unspecified> {
+        self.private_key.compute_public_key().map(PublicKey)
+    }
+
+    /// Computes the public key from the private key, in the compressed form
+    /// of [SEC 1] Section 2.3.3.
+    ///
+    /// Fails for algorithms that have no compressed form, such as `X25519`.
+    ///
+    /// [SEC 1]: http://www.secg.org/sec1-v2.pdf
+    pub fn compute_compressed_public_key(&self) -> Result<PublicKey, error::Unspecified> {
+        self.private_key
+            .compute_public_key()?
+            .compressed()
+            .map(PublicKey)
+    }
+
+    /// The key agreement algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.alg
+    }
+}
+
 /// A public key for key agreement.
 #[derive(Clone)]
 pub struct PublicKey(ec::PublicKey);
@@ -167,6 +294,107 @@ impl<B: AsRef<[u8]>> UnparsedPublicKey<B> {
     pub fn new(algorithm: &'static Algorithm, bytes: B) -> Self {
         Self { algorithm, bytes }
     }
+
+    /// The algorithm for the public key.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+
+    /// The unvalidated public key bytes.
+    #[inline]
+    pub fn bytes(&self) -> &B {
+        &self.bytes
+    }
+}
+
+/// A private key for key agreement.
+///
+/// `EphemeralPrivateKey` and `&ReusablePrivateKey` implement `KeyAgreement`.
+/// Implement it for keys that are held elsewhere, e.g. in an HSM, a TPM, or a
+/// cloud KMS. Since `agree` consumes the key, a key that may be used for more
+/// than one key agreement should implement `KeyAgreement` for a reference to
+/// it.
+pub trait KeyAgreement: Sized {
+    /// The key agreement algorithm.
+    fn algorithm(&self) -> &'static Algorithm;
+
+    /// Computes the public key, in the format used by `UnparsedPublicKey` for
+    /// the algorithm.
+    fn compute_public_key_bytes(&self, out: &mut [u8]) -> Result<usize, error::Unspecified>;
+
+    /// Performs a key agreement with `peer_public_key` and calls `kdf` with
+    /// the raw key material, like `agree_ephemeral()`.
+    fn agree<F, R, E>(
+        self,
+        peer_public_key: &UnparsedPublicKey<&[u8]>,
+        error_value: E,
+        kdf: F,
+    ) -> Result<R, E>
+    where
+        F: FnOnce(&[u8]) -> Result<R, E>;
+}
+
+impl KeyAgreement for EphemeralPrivateKey {
+    #[inline]
+    fn algorithm(&self) -> &'static Algorithm {
+        self.alg
+    }
+
+    fn compute_public_key_bytes(&self, out: &mut [u8]) -> Result<usize, error::Unspecified> {
+        let public_key = self.compute_public_key()?;
+        let public_key = public_key.as_ref();
+        out.get_mut(..public_key.len())
+            .ok_or(error::Unspecified)?
+            .copy_from_slice(public_key);
+        Ok(public_key.len())
+    }
+
+    fn agree<F, R, E>(
+        self,
+        peer_public_key: &UnparsedPublicKey<&[u8]>,
+        error_value: E,
+        kdf: F,
+    ) -> Result<R, E>
+    where
+        F: FnOnce(&[u8]) -> Result<R, E>,
+    {
+        agree_ephemeral_(self, *peer_public_key, error_value, kdf)
+    }
+}
+
+impl KeyAgreement for &ReusablePrivateKey {
+    #[inline]
+    fn algorithm(&self) -> &'static Algorithm {
+        self.alg
+    }
+
+    fn compute_public_key_bytes(&self, out: &mut [u8]) -> Result<usize, error::Unspecified> {
+        let public_key = self.compute_public_key()?;
+        let public_key = public_key.as_ref();
+        out.get_mut(..public_key.len())
+            .ok_or(error::Unspecified)?
+            .copy_from_slice(public_key);
+        Ok(public_key.len())
+    }
+
+    fn agree<F, R, E>(
+        self,
+        peer_public_key: &UnparsedPublicKey<&[u8]>,
+        error_value: E,
+        kdf: F,
+    ) -> Result<R, E>
+    where
+        F: FnOnce(&[u8]) -> Result<R, E>,
+    {
+        agree_(
+            &self.private_key,
+            self.alg,
+            *peer_public_key,
+            error_value,
+            kdf,
+        )
+    }
 }
 
 /// Performs a key agreement with an ephemeral private key and the given public
@@ -213,6 +441,55 @@ fn agree_ephemeral_<F, R, E>(
     error_value: E,
     kdf: F,
 ) -> Result<R, E>
+where
+    F: FnOnce(&[u8]) -> Result<R, E>,
+{
+    agree_(
+        &my_private_key.private_key,
+        my_private_key.alg,
+        peer_public_key,
+        error_value,
+        kdf,
+    )
+}
+
+/// Performs a key agreement with a reusable private key and the given public
+/// key.
+///
+/// Unlike `agree_ephemeral`, this borrows `my_private_key` so that it can be
+/// used again; see the `ReusablePrivateKey` documentation for the loss of
+/// forward secrecy that this implies. Otherwise this works exactly like
+/// `agree_ephemeral`.
+#[inline]
+pub fn agree_reusable<B: AsRef<[u8]>, F, R, E>(
+    my_private_key: &ReusablePrivateKey,
+    peer_public_key: &UnparsedPublicKey<B>,
+    error_value: E,
+    kdf: F,
+) -> Result<R, E>
+where
+    F: FnOnce(&[u8]) -> Result<R, E>,
+{
+    let peer_public_key = UnparsedPublicKey {
+        algorithm: peer_public_key.algorithm,
+        bytes: peer_public_key.bytes.as_ref(),
+    };
+    agree_(
+        &my_private_key.private_key,
+        my_private_key.alg,
+        peer_public_key,
+        error_value,
+        kdf,
+    )
+}
+
+fn agree_<F, R, E>(
+    my_private_key: &ec::Seed,
+    alg: &'static Algorithm,
+    peer_public_key: UnparsedPublicKey<&[u8]>,
+    error_value: E,
+    kdf: F,
+) -> Result<R, E>
 where
     F: FnOnce(&[u8]) -> Result<R, E>,
 {
@@ -221,12 +498,10 @@ where
     // The domain parameters are hard-coded. This check verifies that the
     // peer's public key's domain parameters match the domain parameters of
     // this private key.
-    if peer_public_key.algorithm != my_private_key.alg {
+    if peer_public_key.algorithm != alg {
         return Err(error_value);
     }
 
-    let alg = &my_private_key.alg;
-
     // NSA Guide Prerequisite 2, regarding which KDFs are allowed, is delegated
     // to the caller.
 
@@ -246,7 +521,7 @@ where
     // that doesn't meet the NSA requirement to "zeroize."
     (alg.ecdh)(
         shared_key,
-        &my_private_key.private_key,
+        my_private_key,
         untrusted::Input::from(peer_public_key.bytes),
     )
     .map_err(|_| error_value)?;
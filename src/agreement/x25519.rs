@@ -0,0 +1,158 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! The X25519 function of [RFC 7748 Section 5].
+//!
+//! **This is a hazmat API.** `agreement::agree_ephemeral()` with
+//! `agreement::X25519` is the right choice for Diffie-Hellman key agreement.
+//! `scalar_mult()` is for protocols that use the X25519 function in other
+//! ways, or that must decide themselves whether a peer may force the result
+//! to a known value by sending a point of low order (see the notes on
+//! "contributory" behavior in [RFC 7748 Section 7]).
+//!
+//! [RFC 7748 Section 5]: https://tools.ietf.org/html/rfc7748#section-5
+//! [RFC 7748 Section 7]: https://tools.ietf.org/html/rfc7748#section-7
+
+use crate::{constant_time, cpu, ec::curve25519::x25519, error};
+
+/// The length of a scalar.
+pub const SCALAR_LEN: usize = 32;
+
+/// The length of an encoded point, i.e. of a u-coordinate.
+pub const POINT_LEN: usize = 32;
+
+/// Which inputs `scalar_mult()` rejects.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LowOrderCheck {
+    /// Reject nothing. Every input has a result, as RFC 7748 specifies.
+    None,
+
+    /// Reject an all-zero result, as `agreement::X25519` does; see
+    /// [RFC 7748 Section 6.1]. The check is done in constant time.
+    ///
+    /// [RFC 7748 Section 6.1]: https://tools.ietf.org/html/rfc7748#section-6.1
+    RejectAllZeroOutput,
+
+    /// Reject points of low order before doing the multiplication, so that
+    /// whether `scalar_mult()` fails doesn't depend on the scalar. These are
+    /// exactly the points for which the result would be all zeros.
+    RejectLowOrderPoints,
+}
+
+/// An error from `scalar_mult()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScalarMultError {
+    /// The point is of low order.
+    LowOrderPoint,
+
+    /// The result is all zeros.
+    AllZeroOutput,
+}
+
+impl From<ScalarMultError> for error::Unspecified {
+    fn from(_: ScalarMultError) -> Self {
+        error::Unspecified
+    }
+}
+
+impl core::fmt::Display for ScalarMultError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(match self {
+            ScalarMultError::LowOrderPoint => {
+                "ring::agreement::x25519::ScalarMultError::LowOrderPoint"
+            }
+            ScalarMultError::AllZeroOutput => {
+                "ring::agreement::x25519::ScalarMultError::AllZeroOutput"
+            }
+        })
+    }
+}
+
+#[cfg(feature = "use_heap")]
+impl std::error::Error for ScalarMultError {}
+
+/// Computes X25519(`scalar`, `point`).
+///
+/// `scalar` is clamped, and the most significant bit of `point` is ignored,
+/// as RFC 7748 specifies. Non-canonical encodings of `point` are accepted.
+pub fn scalar_mult(
+    scalar: &[u8; SCALAR_LEN],
+    point: &[u8; POINT_LEN],
+    check: LowOrderCheck,
+) -> Result<[u8; POINT_LEN], ScalarMultError> {
+    if check == LowOrderCheck::RejectLowOrderPoints && is_low_order(point) {
+        return Err(ScalarMultError::LowOrderPoint);
+    }
+
+    let mut out = [0u8; POINT_LEN];
+    x25519::scalar_mult(&mut out, scalar, point, cpu::features());
+
+    if check != LowOrderCheck::None
+        && constant_time::verify_slices_are_equal(&out, &[0u8; POINT_LEN]).is_ok()
+    {
+        return Err(ScalarMultError::AllZeroOutput);
+    }
+
+    Ok(out)
+}
+
+// Whether `point` encodes, after its most significant bit is cleared, a point
+// of low order on the curve or on its twist.
+fn is_low_order(point: &[u8; POINT_LEN]) -> bool {
+    const LOW_ORDER_POINTS: [[u8; POINT_LEN]; 7] = [
+        // 0 and 1.
+        [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ],
+        [
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ],
+        // The points of order 8.
+        [
+            0xe0, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f,
+            0xc4, 0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16,
+            0x5f, 0x49, 0xb8, 0x00,
+        ],
+        [
+            0x5f, 0x9c, 0x95, 0xbc, 0xa3, 0x50, 0x8c, 0x24, 0xb1, 0xd0, 0xb1, 0x55, 0x9c, 0x83,
+            0xef, 0x5b, 0x04, 0x44, 0x5c, 0xc4, 0x58, 0x1c, 0x8e, 0x86, 0xd8, 0x22, 0x4e, 0xdd,
+            0xd0, 0x9f, 0x11, 0x57,
+        ],
+        // p - 1.
+        [
+            0xec, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0x7f,
+        ],
+        // p and p + 1, the non-canonical encodings of 0 and 1.
+        [
+            0xed, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0x7f,
+        ],
+        [
+            0xee, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0x7f,
+        ],
+    ];
+
+    let mut point = *point;
+    point[POINT_LEN - 1] &= 0x7f;
+    LOW_ORDER_POINTS.iter().any(|low_order| *low_order == point)
+}
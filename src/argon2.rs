@@ -0,0 +1,594 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Argon2 memory-hard password hashing, as specified in [RFC 9106].
+//!
+//! Unlike PBKDF2, whose cost is purely computational, Argon2 also requires
+//! a configurable amount of memory, which makes attacks using GPUs and
+//! custom hardware much more expensive. Use `ARGON2ID` unless there is a
+//! specific reason to use `ARGON2I`.
+//!
+//! The lanes of the memory are filled one after another, so the
+//! `parallelism` parameter affects the output but not the speed of this
+//! implementation.
+//!
+//! # Example
+//!
+//! ```
+//! use ring::argon2;
+//! use std::num::NonZeroU32;
+//!
+//! // Normally these parameters would be loaded from a configuration file.
+//! let params = argon2::Params::new(
+//!     64, // KiB
+//!     NonZeroU32::new(3).unwrap(),
+//!     NonZeroU32::new(1).unwrap(),
+//! )?;
+//!
+//! // The salt should be unique per password.
+//! let salt = b"alice@example.com, db 1";
+//!
+//! let mut to_store = [0u8; 32];
+//! argon2::derive(&argon2::ARGON2ID, &params, salt, b"@74d7]404j|W}6u", &mut to_store)?;
+//!
+//! assert!(argon2::verify(&argon2::ARGON2ID, &params, salt, b"wrong password", &to_store)
+//!     .is_err());
+//! assert!(argon2::verify(&argon2::ARGON2ID, &params, salt, b"@74d7]404j|W}6u", &to_store)
+//!     .is_ok());
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [RFC 9106]: https://tools.ietf.org/html/rfc9106
+
+use crate::{constant_time, digest::blake2b, error};
+use core::num::NonZeroU32;
+use std::vec;
+
+/// An Argon2 variant.
+#[derive(Debug)]
+pub struct Algorithm {
+    id: AlgorithmID,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum AlgorithmID {
+    Argon2i = 1,
+    Argon2id = 2,
+}
+
+/// Argon2id, which uses data-independent memory access for the first half
+/// of the first pass and data-dependent memory access afterwards.
+pub static ARGON2ID: Algorithm = Algorithm {
+    id: AlgorithmID::Argon2id,
+};
+
+/// Argon2i, which uses only data-independent memory access.
+pub static ARGON2I: Algorithm = Algorithm {
+    id: AlgorithmID::Argon2i,
+};
+
+/// The cost parameters of an Argon2 computation.
+#[derive(Clone, Copy, Debug)]
+pub struct Params {
+    memory_kib: u32,
+    iterations: NonZeroU32,
+    parallelism: NonZeroU32,
+}
+
+impl Params {
+    /// Constructs Argon2 cost parameters.
+    ///
+    /// | Parameter   | RFC 9106 Section 3.1 Term
+    /// |-------------|---------------------------
+    /// | memory_kib  | m (memory size in KiB)
+    /// | iterations  | t (number of passes)
+    /// | parallelism | p (degree of parallelism)
+    ///
+    /// Fails unless `parallelism <= 2**24 - 1` and
+    /// `memory_kib >= 8 * parallelism`.
+    pub fn new(
+        memory_kib: u32,
+        iterations: NonZeroU32,
+        parallelism: NonZeroU32,
+    ) -> Result<Self, error::Unspecified> {
+        let lanes = parallelism.get();
+        if lanes > MAX_PARALLELISM || memory_kib < 2 * SYNC_POINTS * lanes {
+            return Err(error::Unspecified);
+        }
+        Ok(Self {
+            memory_kib,
+            iterations,
+            parallelism,
+        })
+    }
+
+    /// The memory size, in KiB.
+    #[inline]
+    pub fn memory_kib(&self) -> u32 {
+        self.memory_kib
+    }
+
+    /// The number of passes over the memory.
+    #[inline]
+    pub fn iterations(&self) -> NonZeroU32 {
+        self.iterations
+    }
+
+    /// The number of lanes the memory is divided into.
+    #[inline]
+    pub fn parallelism(&self) -> NonZeroU32 {
+        self.parallelism
+    }
+}
+
+/// The minimum salt length, in bytes.
+pub const MIN_SALT_LEN: usize = 8;
+
+/// The minimum output length, in bytes.
+pub const MIN_OUTPUT_LEN: usize = 4;
+
+/// Fills `out` with the tag derived using Argon2 with the given inputs.
+///
+/// Do not use `derive` as part of verifying a secret; use `verify` instead, to
+/// minimize the effectiveness of timing attacks.
+///
+/// `out` may be any length from `MIN_OUTPUT_LEN` to 2**32 - 1 bytes; the
+/// output is not a prefix of a longer output for the same inputs, since the
+/// length is an input to Argon2.
+///
+/// | Parameter   | RFC 9106 Section 3.1 Term
+/// |-------------|---------------------------
+/// | algorithm   | y (Argon2 type)
+/// | params      | m, t, p
+/// | salt        | S (nonce)
+/// | secret      | P (message)
+/// | out         | tag
+/// | out.len()   | T (tag length)
+///
+/// Fails if `salt` is shorter than `MIN_SALT_LEN`, or if any of the lengths
+/// don't fit in 32 bits.
+pub fn derive(
+    algorithm: &'static Algorithm,
+    params: &Params,
+    salt: &[u8],
+    secret: &[u8],
+    out: &mut [u8],
+) -> Result<(), error::Unspecified> {
+    derive_(algorithm, params, salt, secret, &[], &[], out)
+}
+
+/// Verifies that a previously-derived (e.g., using `derive`) Argon2 tag
+/// matches the Argon2 tag derived from the other inputs.
+///
+/// The comparison is done in constant time to prevent timing attacks. The
+/// length of the tag derived from the other inputs is
+/// `previously_derived.len()`.
+///
+/// | Parameter                  | RFC 9106 Section 3.1 Term
+/// |----------------------------|---------------------------
+/// | algorithm                  | y (Argon2 type)
+/// | params                     | m, t, p
+/// | salt                       | S (nonce)
+/// | secret                     | P (message)
+/// | previously_derived         | tag
+/// | previously_derived.len()   | T (tag length)
+pub fn verify(
+    algorithm: &'static Algorithm,
+    params: &Params,
+    salt: &[u8],
+    secret: &[u8],
+    previously_derived: &[u8],
+) -> Result<(), error::Unspecified> {
+    let mut derived = vec![0u8; previously_derived.len()];
+    derive(algorithm, params, salt, secret, &mut derived)?;
+    constant_time::verify_slices_are_equal(&derived, previously_derived)
+}
+
+const VERSION: u32 = 0x13;
+const SYNC_POINTS: u32 = 4;
+const MAX_PARALLELISM: u32 = (1 << 24) - 1;
+
+const BLOCK_WORDS: usize = 128;
+const BLOCK_LEN: usize = BLOCK_WORDS * 8;
+
+// The length of H_0.
+const PREHASH_LEN: usize = 64;
+
+#[derive(Clone, Copy)]
+struct Block([u64; BLOCK_WORDS]);
+
+impl Block {
+    const ZERO: Self = Block([0; BLOCK_WORDS]);
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let mut block = Self::ZERO;
+        for (word, bytes) in block.0.iter_mut().zip(bytes.chunks_exact(8)) {
+            let mut le = [0u8; 8];
+            le.copy_from_slice(bytes);
+            *word = u64::from_le_bytes(le);
+        }
+        block
+    }
+
+    fn to_bytes(self) -> [u8; BLOCK_LEN] {
+        let mut bytes = [0u8; BLOCK_LEN];
+        for (bytes, word) in bytes.chunks_exact_mut(8).zip(self.0.iter()) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+/// The shape of the memory for a particular set of parameters.
+struct Instance {
+    algorithm: AlgorithmID,
+    passes: u32,
+    lanes: u32,
+    lane_len: u32,
+    segment_len: u32,
+}
+
+impl Instance {
+    fn memory_blocks(&self) -> u32 {
+        self.lanes * self.lane_len
+    }
+}
+
+// `key` and `ad` are the optional secret value K and associated data X, which
+// aren't exposed, but which the test vectors of RFC 9106 use.
+fn derive_(
+    algorithm: &'static Algorithm,
+    params: &Params,
+    salt: &[u8],
+    secret: &[u8],
+    key: &[u8],
+    ad: &[u8],
+    out: &mut [u8],
+) -> Result<(), error::Unspecified> {
+    if salt.len() < MIN_SALT_LEN || out.len() < MIN_OUTPUT_LEN {
+        return Err(error::Unspecified);
+    }
+    let salt_len = len_u32(salt)?;
+    let secret_len = len_u32(secret)?;
+    let key_len = len_u32(key)?;
+    let ad_len = len_u32(ad)?;
+    let out_len = len_u32(out)?;
+
+    let lanes = params.parallelism.get();
+    let segment_len = params.memory_kib / (SYNC_POINTS * lanes);
+    let instance = Instance {
+        algorithm: algorithm.id,
+        passes: params.iterations.get(),
+        lanes,
+        lane_len: segment_len * SYNC_POINTS,
+        segment_len,
+    };
+
+    let mut h0 = [0u8; PREHASH_LEN + 8];
+    {
+        let mut ctx = blake2b::Context::new(&[], PREHASH_LEN);
+        ctx.update(&lanes.to_le_bytes());
+        ctx.update(&out_len.to_le_bytes());
+        ctx.update(&params.memory_kib.to_le_bytes());
+        ctx.update(&instance.passes.to_le_bytes());
+        ctx.update(&VERSION.to_le_bytes());
+        ctx.update(&(instance.algorithm as u32).to_le_bytes());
+        ctx.update(&secret_len.to_le_bytes());
+        ctx.update(secret);
+        ctx.update(&salt_len.to_le_bytes());
+        ctx.update(salt);
+        ctx.update(&key_len.to_le_bytes());
+        ctx.update(key);
+        ctx.update(&ad_len.to_le_bytes());
+        ctx.update(ad);
+        h0[..PREHASH_LEN].copy_from_slice(ctx.finish().as_ref());
+    }
+
+    let mut memory = vec![Block::ZERO; instance.memory_blocks() as usize];
+
+    // The first two blocks of each lane are derived from H_0.
+    let mut block_bytes = [0u8; BLOCK_LEN];
+    for lane in 0..lanes {
+        h0[PREHASH_LEN + 4..].copy_from_slice(&lane.to_le_bytes());
+        for i in 0..2u32 {
+            h0[PREHASH_LEN..][..4].copy_from_slice(&i.to_le_bytes());
+            hash_long(&mut block_bytes, &h0);
+            let index = (lane * instance.lane_len + i) as usize;
+            memory[index] = Block::from_bytes(&block_bytes);
+        }
+    }
+
+    for pass in 0..instance.passes {
+        for slice in 0..SYNC_POINTS {
+            for lane in 0..lanes {
+                fill_segment(&instance, &mut memory, pass, slice, lane);
+            }
+        }
+    }
+
+    let mut last = memory[(instance.lane_len - 1) as usize];
+    for lane in 1..lanes {
+        let index = (lane * instance.lane_len + instance.lane_len - 1) as usize;
+        for (a, b) in last.0.iter_mut().zip(memory[index].0.iter()) {
+            *a ^= b;
+        }
+    }
+    hash_long(out, &last.to_bytes());
+
+    Ok(())
+}
+
+fn len_u32(value: &[u8]) -> Result<u32, error::Unspecified> {
+    if value.len() as u64 > u64::from(u32::max_value()) {
+        return Err(error::Unspecified);
+    }
+    Ok(value.len() as u32)
+}
+
+fn fill_segment(instance: &Instance, memory: &mut [Block], pass: u32, slice: u32, lane: u32) {
+    let data_independent = match instance.algorithm {
+        AlgorithmID::Argon2i => true,
+        AlgorithmID::Argon2id => pass == 0 && slice < SYNC_POINTS / 2,
+    };
+
+    let mut address_input = Block::ZERO;
+    let mut addresses = Block::ZERO;
+    if data_independent {
+        address_input.0[0] = u64::from(pass);
+        address_input.0[1] = u64::from(lane);
+        address_input.0[2] = u64::from(slice);
+        address_input.0[3] = u64::from(instance.memory_blocks());
+        address_input.0[4] = u64::from(instance.passes);
+        address_input.0[5] = instance.algorithm as u64;
+    }
+
+    // The first two blocks of each lane were already derived from H_0.
+    let first = if pass == 0 && slice == 0 {
+        if data_independent {
+            next_addresses(&mut addresses, &mut address_input);
+        }
+        2
+    } else {
+        0
+    };
+
+    let lane_start = lane * instance.lane_len;
+    for index in first..instance.segment_len {
+        let column = slice * instance.segment_len + index;
+        let prev_column = if column == 0 {
+            instance.lane_len - 1
+        } else {
+            column - 1
+        };
+        let current = (lane_start + column) as usize;
+        let prev = (lane_start + prev_column) as usize;
+
+        let pseudo_rand = if data_independent {
+            if index as usize % BLOCK_WORDS == 0 {
+                next_addresses(&mut addresses, &mut address_input);
+            }
+            addresses.0[index as usize % BLOCK_WORDS]
+        } else {
+            memory[prev].0[0]
+        };
+
+        let ref_lane = if pass == 0 && slice == 0 {
+            lane
+        } else {
+            ((pseudo_rand >> 32) % u64::from(instance.lanes)) as u32
+        };
+        let ref_column = reference_column(
+            instance,
+            pass,
+            slice,
+            index,
+            pseudo_rand as u32,
+            ref_lane == lane,
+        );
+        let reference = (ref_lane * instance.lane_len + ref_column) as usize;
+
+        let (prev, reference) = (memory[prev], memory[reference]);
+        compress(&mut memory[current], &prev, &reference, pass != 0);
+    }
+}
+
+/// Maps `j1` to the column of the reference block within its lane, as
+/// specified in RFC 9106 Section 3.4.1.2.
+fn reference_column(
+    instance: &Instance,
+    pass: u32,
+    slice: u32,
+    index: u32,
+    j1: u32,
+    same_lane: bool,
+) -> u32 {
+    // The blocks that may be referenced: those of the last `SYNC_POINTS - 1`
+    // (finished) segments, or of all the finished segments during the first
+    // pass, plus the blocks already computed in the current segment if the
+    // reference is in the same lane. The block just before the current one is
+    // never referenced since it is always used anyway.
+    let finished = if pass == 0 {
+        slice * instance.segment_len
+    } else {
+        instance.lane_len - instance.segment_len
+    };
+    let area = if same_lane {
+        finished + index - 1
+    } else if index == 0 {
+        finished - 1
+    } else {
+        finished
+    };
+
+    let x = (u64::from(j1) * u64::from(j1)) >> 32;
+    let y = (u64::from(area) * x) >> 32;
+    let relative = u64::from(area) - 1 - y;
+
+    let start = if pass == 0 || slice == SYNC_POINTS - 1 {
+        0
+    } else {
+        (slice + 1) * instance.segment_len
+    };
+    ((u64::from(start) + relative) % u64::from(instance.lane_len)) as u32
+}
+
+fn next_addresses(addresses: &mut Block, input: &mut Block) {
+    input.0[6] += 1;
+    *addresses = Block::ZERO;
+    compress(addresses, &Block::ZERO, input, false);
+    let tmp = *addresses;
+    *addresses = Block::ZERO;
+    compress(addresses, &Block::ZERO, &tmp, false);
+}
+
+/// The compression function G, which sets `out` to G(x, y), or XORs G(x, y)
+/// into `out` when `xor` is true.
+fn compress(out: &mut Block, x: &Block, y: &Block, xor: bool) {
+    let mut r = Block::ZERO;
+    for ((r, x), y) in r.0.iter_mut().zip(x.0.iter()).zip(y.0.iter()) {
+        *r = x ^ y;
+    }
+
+    let mut z = r;
+    // Apply the permutation P to each row of eight 16-byte registers...
+    for row in z.0.chunks_exact_mut(16) {
+        let mut v = [0u64; 16];
+        v.copy_from_slice(row);
+        permute(&mut v);
+        row.copy_from_slice(&v);
+    }
+    // ...and then to each column.
+    for column in 0..8 {
+        let mut v = [0u64; 16];
+        for (i, pair) in v.chunks_exact_mut(2).enumerate() {
+            pair.copy_from_slice(&z.0[(16 * i + 2 * column)..][..2]);
+        }
+        permute(&mut v);
+        for (i, pair) in v.chunks_exact(2).enumerate() {
+            z.0[(16 * i + 2 * column)..][..2].copy_from_slice(pair);
+        }
+    }
+
+    for ((out, r), z) in out.0.iter_mut().zip(r.0.iter()).zip(z.0.iter()) {
+        if xor {
+            *out ^= r ^ z;
+        } else {
+            *out = r ^ z;
+        }
+    }
+}
+
+// The permutation P: the BLAKE2b round function, with the message words
+// replaced by multiplications.
+fn permute(v: &mut [u64; 16]) {
+    gb(v, 0, 4, 8, 12);
+    gb(v, 1, 5, 9, 13);
+    gb(v, 2, 6, 10, 14);
+    gb(v, 3, 7, 11, 15);
+    gb(v, 0, 5, 10, 15);
+    gb(v, 1, 6, 11, 12);
+    gb(v, 2, 7, 8, 13);
+    gb(v, 3, 4, 9, 14);
+}
+
+#[inline(always)]
+fn gb(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize) {
+    v[a] = fblamka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = fblamka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = fblamka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = fblamka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+#[inline(always)]
+fn fblamka(x: u64, y: u64) -> u64 {
+    let xy = u64::from(x as u32) * u64::from(y as u32);
+    x.wrapping_add(y).wrapping_add(xy.wrapping_mul(2))
+}
+
+/// The variable-length hash function H' of RFC 9106 Section 3.3, which
+/// fills `out` with H'^`out.len()`(`input`).
+fn hash_long(out: &mut [u8], input: &[u8]) {
+    let out_len = (out.len() as u32).to_le_bytes();
+    if out.len() <= blake2b::MAX_OUTPUT_LEN {
+        let mut ctx = blake2b::Context::new(&[], out.len());
+        ctx.update(&out_len);
+        ctx.update(input);
+        out.copy_from_slice(ctx.finish().as_ref());
+        return;
+    }
+
+    const HALF: usize = blake2b::MAX_OUTPUT_LEN / 2;
+
+    let mut ctx = blake2b::Context::new(&[], blake2b::MAX_OUTPUT_LEN);
+    ctx.update(&out_len);
+    ctx.update(input);
+    let mut v = ctx.finish();
+
+    // Each intermediate hash contributes its first half to the output; the
+    // last one, whose length depends on what remains, contributes all of it.
+    let mut out = out;
+    loop {
+        let (head, rest) = out.split_at_mut(HALF);
+        head.copy_from_slice(&v.as_ref()[..HALF]);
+        out = rest;
+        if out.len() <= blake2b::MAX_OUTPUT_LEN {
+            break;
+        }
+        v = blake2b::digest(&[], blake2b::MAX_OUTPUT_LEN, v.as_ref());
+    }
+    let v = blake2b::digest(&[], out.len(), v.as_ref());
+    out.copy_from_slice(v.as_ref());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+
+    // The test vectors of RFC 9106 Sections 5.2 and 5.3.
+    #[test]
+    fn test_rfc9106() {
+        let params =
+            Params::new(32, NonZeroU32::new(3).unwrap(), NonZeroU32::new(4).unwrap()).unwrap();
+        let tests: [(&'static Algorithm, &str); 2] = [
+            (
+                &ARGON2I,
+                "c814d9d1dc7f37aa13f0d77f2494bda1c8de6b016dd388d29952a4c4672b6ce8",
+            ),
+            (
+                &ARGON2ID,
+                "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659",
+            ),
+        ];
+        for &(algorithm, expected) in tests.iter() {
+            let mut out = [0u8; 32];
+            derive_(
+                algorithm,
+                &params,
+                &[0x02; 16],
+                &[0x01; 32],
+                &[0x03; 8],
+                &[0x04; 12],
+                &mut out,
+            )
+            .unwrap();
+            assert_eq!(&out[..], &test::from_hex(expected).unwrap()[..]);
+        }
+    }
+}
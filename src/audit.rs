@@ -0,0 +1,202 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Operation audit hooks.
+//!
+//! A hook registered with `set_hook` is called once for every AEAD seal or
+//! open, HMAC, AES-CMAC, or keyed BLAKE2 or BLAKE3 sign or verify, and
+//! signature sign or verify operation. The hook receives an `Event`
+//! describing the algorithm, the kind of operation, a fingerprint of the key,
+//! and the number of bytes processed. Key material is never passed to the
+//! hook.
+//!
+//! A key's fingerprint is a truncated SHA-256 digest of the key with a
+//! domain separation prefix. For key pairs, it is the fingerprint of the
+//! public key, so signing and verification with the same key pair have the
+//! same fingerprint. Fingerprints of secret keys are only useful for telling
+//! keys apart; they must not be used as key check values by other protocols.
+//!
+//! The hook is called synchronously on the thread doing the operation, once
+//! the inputs have been checked but before the result is known, so failed
+//! verifications are recorded too. It should be fast and must not panic.
+
+use crate::{digest, error};
+use core::fmt;
+
+/// The length of a key fingerprint.
+pub const FINGERPRINT_LEN: usize = 16;
+
+/// A fingerprint identifying a key without revealing it.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct Fingerprint([u8; FINGERPRINT_LEN]);
+
+impl Fingerprint {
+    pub(crate) fn new(key: &[u8]) -> Self {
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        ctx.update(b"ring audit key fingerprint\0");
+        ctx.update(key);
+        let digest = ctx.finish();
+        let mut fingerprint = [0; FINGERPRINT_LEN];
+        fingerprint.copy_from_slice(&digest.as_ref()[..FINGERPRINT_LEN]);
+        Fingerprint(fingerprint)
+    }
+
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    pub(crate) fn from_bytes(bytes: [u8; FINGERPRINT_LEN]) -> Self {
+        Fingerprint(bytes)
+    }
+}
+
+impl AsRef<[u8]> for Fingerprint {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Fingerprint(")?;
+        for b in self.0.iter() {
+            write!(f, "{:02x}", b)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// The kind of an audited operation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operation {
+    /// An AEAD seal (encrypt and authenticate).
+    Seal,
+
+    /// An AEAD open (authenticate and decrypt).
+    Open,
+
+    /// An HMAC, AES-CMAC, or keyed BLAKE2 or BLAKE3 tag calculation.
+    HmacSign,
+
+    /// An HMAC, AES-CMAC, or keyed BLAKE2 or BLAKE3 tag verification.
+    HmacVerify,
+
+    /// A public key signature calculation.
+    Sign,
+
+    /// A public key signature verification.
+    Verify,
+}
+
+/// An audited operation.
+pub struct Event<'a> {
+    algorithm: &'a dyn fmt::Debug,
+    operation: Operation,
+    key_fingerprint: &'a Fingerprint,
+    len: usize,
+}
+
+impl Event<'_> {
+    /// The algorithm, e.g. `&aead::AES_128_GCM`, `&digest::SHA256` for
+    /// HMAC-SHA256, or `&signature::ED25519`. Its `Debug` representation is
+    /// the algorithm's name.
+    #[inline]
+    pub fn algorithm(&self) -> &dyn fmt::Debug {
+        self.algorithm
+    }
+
+    /// The kind of operation.
+    #[inline]
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// The fingerprint of the key.
+    #[inline]
+    pub fn key_fingerprint(&self) -> &Fingerprint {
+        self.key_fingerprint
+    }
+
+    /// The number of bytes of input processed, excluding any AAD, tag, or
+    /// signature.
+    #[inline]
+    pub fn input_len(&self) -> usize {
+        self.len
+    }
+}
+
+impl fmt::Debug for Event<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Event")
+            .field("algorithm", self.algorithm)
+            .field("operation", &self.operation)
+            .field("key_fingerprint", self.key_fingerprint)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+/// An audit hook.
+pub type Hook = fn(&Event);
+
+static HOOK: spin::Once<Hook> = spin::Once::new();
+
+/// Registers `hook` to be called for every audited operation.
+///
+/// Only one hook can be registered, and it can't be unregistered. Fails if a
+/// hook was already registered.
+pub fn set_hook(hook: Hook) -> Result<(), error::Unspecified> {
+    let mut registered = false;
+    let _ = HOOK.call_once(|| {
+        registered = true;
+        hook
+    });
+    if registered {
+        Ok(())
+    } else {
+        Err(error::Unspecified)
+    }
+}
+
+pub(crate) fn record(
+    algorithm: &dyn fmt::Debug,
+    operation: Operation,
+    key_fingerprint: &Fingerprint,
+    len: usize,
+) {
+    if let Some(hook) = HOOK.r#try() {
+        hook(&Event {
+            algorithm,
+            operation,
+            key_fingerprint,
+            len,
+        });
+    }
+}
+
+// Like `record`, but the fingerprint of `public_key` is only calculated when
+// a hook is registered.
+pub(crate) fn record_public_key(
+    algorithm: &dyn fmt::Debug,
+    operation: Operation,
+    public_key: &[u8],
+    len: usize,
+) {
+    if let Some(hook) = HOOK.r#try() {
+        hook(&Event {
+            algorithm,
+            operation,
+            key_fingerprint: &Fingerprint::new(public_key),
+            len,
+        });
+    }
+}
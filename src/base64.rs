@@ -0,0 +1,241 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Base64, as specified in [RFC 4648].
+//!
+//! Both the standard alphabet with padding, as used by PEM-like formats, and
+//! the URL- and filename-safe alphabet without padding, as used by JOSE and
+//! PASETO, are supported.
+//!
+//! The encoding and decoding of each digit is done in constant time, without
+//! table lookups, so that they are safe to use for secret values such as
+//! private keys. The length of the input, the amount of padding, and whether
+//! the input is valid are not secret.
+//!
+//! [RFC 4648]: https://tools.ietf.org/html/rfc4648
+
+use crate::{constant_time::range_mask, error};
+use std::{string::String, vec::Vec};
+
+// The alphabets differ only in the last two digits.
+struct Variant {
+    digit_62: u8,
+    digit_63: u8,
+    padded: bool,
+}
+
+static STANDARD: Variant = Variant {
+    digit_62: b'+',
+    digit_63: b'/',
+    padded: true,
+};
+
+static URL_SAFE_NO_PAD: Variant = Variant {
+    digit_62: b'-',
+    digit_63: b'_',
+    padded: false,
+};
+
+impl Variant {
+    // Maps `value`, which must be less than 64, to its digit.
+    fn encode_digit(&self, value: u8) -> u8 {
+        (range_mask(value, 0, 25) & value.wrapping_add(b'A'))
+            | (range_mask(value, 26, 51) & value.wrapping_add(b'a' - 26))
+            | (range_mask(value, 52, 61) & value.wrapping_sub(52 - b'0'))
+            | (range_mask(value, 62, 62) & self.digit_62)
+            | (range_mask(value, 63, 63) & self.digit_63)
+    }
+
+    // Maps `digit` to its value. The returned mask is 0xff if `digit` is in
+    // the alphabet and 0 otherwise.
+    fn decode_digit(&self, digit: u8) -> (u8, u8) {
+        let upper = range_mask(digit, b'A', b'Z');
+        let lower = range_mask(digit, b'a', b'z');
+        let number = range_mask(digit, b'0', b'9');
+        let is_62 = range_mask(digit, self.digit_62, self.digit_62);
+        let is_63 = range_mask(digit, self.digit_63, self.digit_63);
+        let value = (upper & digit.wrapping_sub(b'A'))
+            | (lower & digit.wrapping_sub(b'a' - 26))
+            | (number & digit.wrapping_add(52 - b'0'))
+            | (is_62 & 62)
+            | (is_63 & 63);
+        (value, upper | lower | number | is_62 | is_63)
+    }
+}
+
+/// Encodes `input` with the standard alphabet, with padding.
+pub fn encode(input: &[u8]) -> String {
+    encode_variant(&STANDARD, input)
+}
+
+/// Decodes `input`, which must use the standard alphabet, with padding.
+pub fn decode(input: &str) -> Result<Vec<u8>, error::Unspecified> {
+    decode_variant(&STANDARD, input)
+}
+
+/// Encodes `input` with the URL-safe alphabet, without padding.
+pub fn url_encode(input: &[u8]) -> String {
+    encode_variant(&URL_SAFE_NO_PAD, input)
+}
+
+/// Decodes `input`, which must use the URL-safe alphabet, without padding.
+pub fn url_decode(input: &str) -> Result<Vec<u8>, error::Unspecified> {
+    decode_variant(&URL_SAFE_NO_PAD, input)
+}
+
+fn encode_variant(variant: &Variant, input: &[u8]) -> String {
+    let mut out = String::with_capacity((input.len() + 2) / 3 * 4);
+    for chunk in input.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..(chunk.len() + 1) {
+            let value = ((n >> (18 - (6 * i))) & 0x3f) as u8;
+            out.push(char::from(variant.encode_digit(value)));
+        }
+        if variant.padded {
+            for _ in chunk.len()..3 {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+// Only the canonical encoding is accepted; in particular, unused trailing
+// bits must be zero so that each value has exactly one encoding.
+fn decode_variant(variant: &Variant, input: &str) -> Result<Vec<u8>, error::Unspecified> {
+    let mut input = input.as_bytes();
+    if variant.padded {
+        if input.len() % 4 != 0 {
+            return Err(error::Unspecified);
+        }
+        let unpadded_len = if input.ends_with(b"==") {
+            input.len() - 2
+        } else if input.ends_with(b"=") {
+            input.len() - 1
+        } else {
+            input.len()
+        };
+        input = &input[..unpadded_len];
+    }
+    if input.len() % 4 == 1 {
+        return Err(error::Unspecified);
+    }
+
+    // Invalid digits are accumulated into `valid` instead of returning early
+    // so that the position of the first one isn't revealed.
+    let mut valid = 0xff;
+    let mut unused_bits = 0;
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    for chunk in input.chunks(4) {
+        let mut n = 0u32;
+        for (i, digit) in chunk.iter().enumerate() {
+            let (value, is_digit) = variant.decode_digit(*digit);
+            valid &= is_digit;
+            n |= u32::from(value) << (18 - (6 * i));
+        }
+        let bytes = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+        let len = chunk.len() - 1;
+        unused_bits |= bytes[len..].iter().fold(0, |acc, b| acc | *b);
+        out.extend_from_slice(&bytes[..len]);
+    }
+    if valid != 0xff || unused_bits != 0 {
+        return Err(error::Unspecified);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every digit must round-trip and match the alphabets in RFC 4648.
+    #[test]
+    fn test_digits() {
+        const ALPHABETS: &[(&Variant, &[u8; 64])] = &[
+            (
+                &STANDARD,
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
+            ),
+            (
+                &URL_SAFE_NO_PAD,
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+            ),
+        ];
+        for (variant, alphabet) in ALPHABETS {
+            for digit in 0..=255u8 {
+                let (value, valid) = variant.decode_digit(digit);
+                match alphabet.iter().position(|a| *a == digit) {
+                    Some(expected) => {
+                        assert_eq!(valid, 0xff);
+                        assert_eq!(usize::from(value), expected);
+                        assert_eq!(variant.encode_digit(value), digit);
+                    }
+                    None => assert_eq!(valid, 0),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_url_encode_decode() {
+        const TEST_CASES: &[(&[u8], &str)] = &[
+            (b"", ""),
+            (b"f", "Zg"),
+            (b"fo", "Zm8"),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg"),
+            (b"\xfb\xff", "-_8"),
+        ];
+        for (bytes, encoded) in TEST_CASES {
+            assert_eq!(&url_encode(bytes), encoded);
+            assert_eq!(&url_decode(encoded).unwrap()[..], *bytes);
+        }
+
+        // Padding, invalid lengths, non-canonical trailing bits, and the
+        // standard (non-URL) alphabet are rejected.
+        for bad in &["Zg==", "Z", "Zh", "Zm9=", "+/8", "Zm9v\n"] {
+            assert!(url_decode(bad).is_err());
+        }
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        const TEST_CASES: &[(&[u8], &str)] = &[
+            (b"", ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"fooba", "Zm9vYmE="),
+            (b"\xfb\xff", "+/8="),
+        ];
+        for (bytes, encoded) in TEST_CASES {
+            assert_eq!(&encode(bytes), encoded);
+            assert_eq!(&decode(encoded).unwrap()[..], *bytes);
+        }
+
+        // Missing or excess padding, padding in the middle, non-canonical
+        // trailing bits, and the URL-safe alphabet are rejected.
+        for bad in &[
+            "Zg", "Zg=", "Zg===", "Zm9v====", "Zg==Zg==", "Zh==", "-_8=", "Zm9v\n",
+        ] {
+            assert!(decode(bad).is_err(), "{}", bad);
+        }
+    }
+}
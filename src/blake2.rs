@@ -0,0 +1,211 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! The keyed mode of BLAKE2, as specified in [RFC 7693], used as a MAC.
+//!
+//! Keyed BLAKE2 is a MAC on its own; unlike SHA-2, BLAKE2 doesn't need to be
+//! wrapped in HMAC. The API mirrors `ring::hmac`: construct a `Key` once,
+//! then use it for any number of `sign` and `verify` operations, or use
+//! `Context` when the input is in multiple parts.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::{blake2, digest, rand};
+//!
+//! let rng = rand::SystemRandom::new();
+//! let key = blake2::Key::generate(&digest::BLAKE2B_512, &rng)?;
+//!
+//! let msg = "hello, world";
+//!
+//! let tag = blake2::sign(&key, msg.as_bytes());
+//!
+//! // [We give access to the message to an untrusted party, and they give it
+//! // back to us. We need to verify they didn't tamper with it.]
+//!
+//! blake2::verify(&key, msg.as_bytes(), tag.as_ref())?;
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [RFC 7693]: https://tools.ietf.org/html/rfc7693
+
+use crate::{constant_time, digest, error, rand};
+
+#[cfg(feature = "audit")]
+use crate::audit;
+
+/// A keyed BLAKE2 tag.
+///
+/// For a given tag `t`, use `t.as_ref()` to get the tag value as a byte slice.
+#[derive(Clone, Copy, Debug)]
+pub struct Tag(digest::Digest);
+
+impl AsRef<[u8]> for Tag {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+/// A key for keyed BLAKE2.
+#[derive(Clone)]
+pub struct Key {
+    ctx_prototype: Context,
+}
+
+impl core::fmt::Debug for Key {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Key")
+            .field("algorithm", self.digest_algorithm())
+            .finish()
+    }
+}
+
+impl Key {
+    /// Generate a key for `digest_alg`, which must be `digest::BLAKE2B_512`
+    /// or `digest::BLAKE2S_256`, with a random value generated from `rng`.
+    ///
+    /// The key will be `digest_alg.output_len` bytes long, the maximum key
+    /// length.
+    pub fn generate(
+        digest_alg: &'static digest::Algorithm,
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<Self, error::Unspecified> {
+        let mut key_bytes = [0; digest::MAX_OUTPUT_LEN];
+        let key_bytes = &mut key_bytes[..digest_alg.output_len];
+        rng.fill(key_bytes)?;
+        Self::new(digest_alg, key_bytes)
+    }
+
+    /// Construct a key for `digest_alg`, which must be `digest::BLAKE2B_512`
+    /// or `digest::BLAKE2S_256`, with the given key value.
+    ///
+    /// Fails if `digest_alg` isn't a BLAKE2 algorithm, or unless
+    /// `1 <= key_value.len() <= digest_alg.output_len`. Unlike HMAC, keyed
+    /// BLAKE2 doesn't hash overly long keys.
+    pub fn new(
+        digest_alg: &'static digest::Algorithm,
+        key_value: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        if !(digest_alg == &digest::BLAKE2B_512 || digest_alg == &digest::BLAKE2S_256) {
+            return Err(error::Unspecified);
+        }
+        if key_value.is_empty() || key_value.len() > digest_alg.output_len {
+            return Err(error::Unspecified);
+        }
+        Ok(Self {
+            ctx_prototype: Context {
+                inner: digest::Context::with_blake2_key(digest_alg, key_value),
+                #[cfg(feature = "audit")]
+                fingerprint: audit::Fingerprint::new(key_value),
+                #[cfg(feature = "audit")]
+                len: 0,
+            },
+        })
+    }
+
+    /// The digest algorithm for the key.
+    pub fn digest_algorithm(&self) -> &'static digest::Algorithm {
+        self.ctx_prototype.inner.algorithm()
+    }
+}
+
+/// A context for multi-step (Init-Update-Finish) keyed BLAKE2 signing.
+///
+/// Use `sign` for single-step signing.
+#[derive(Clone)]
+pub struct Context {
+    inner: digest::Context,
+    #[cfg(feature = "audit")]
+    fingerprint: audit::Fingerprint,
+    #[cfg(feature = "audit")]
+    len: usize,
+}
+
+impl core::fmt::Debug for Context {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Context")
+            .field("algorithm", self.inner.algorithm())
+            .finish()
+    }
+}
+
+impl Context {
+    /// Constructs a new signing context using the given key.
+    pub fn with_key(signing_key: &Key) -> Self {
+        signing_key.ctx_prototype.clone()
+    }
+
+    /// Updates the tag calculation with all the data in `data`. `update` may
+    /// be called zero or more times until `sign` is called.
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+        #[cfg(feature = "audit")]
+        {
+            self.len = self.len.wrapping_add(data.len());
+        }
+    }
+
+    /// Finalizes the tag calculation and returns the tag. `sign` consumes
+    /// the context so it cannot be (mis-)used after `sign` has been called.
+    ///
+    /// It is generally not safe to implement verification by comparing the
+    /// return value of `sign` to a tag. Use `verify` for verification
+    /// instead.
+    pub fn sign(self) -> Tag {
+        #[cfg(feature = "audit")]
+        self.record(audit::Operation::HmacSign);
+        self.sign_()
+    }
+
+    fn sign_(self) -> Tag {
+        Tag(self.inner.finish())
+    }
+
+    #[cfg(feature = "audit")]
+    fn record(&self, operation: audit::Operation) {
+        audit::record(
+            self.inner.algorithm(),
+            operation,
+            &self.fingerprint,
+            self.len,
+        );
+    }
+}
+
+/// Calculates the keyed BLAKE2 tag of `data` using the key `key` in one
+/// step.
+///
+/// Use `Context` to calculate tags where the input is in multiple parts.
+///
+/// It is generally not safe to implement verification by comparing the
+/// return value of `sign` to a tag. Use `verify` for verification instead.
+pub fn sign(key: &Key, data: &[u8]) -> Tag {
+    let mut ctx = Context::with_key(key);
+    ctx.update(data);
+    ctx.sign()
+}
+
+/// Calculates the keyed BLAKE2 tag of `data` using the key `key`, and
+/// verifies whether the resultant value equals `tag`, in one step.
+///
+/// The verification will be done in constant time to prevent timing attacks.
+pub fn verify(key: &Key, data: &[u8], tag: &[u8]) -> Result<(), error::Unspecified> {
+    let mut ctx = Context::with_key(key);
+    ctx.update(data);
+    #[cfg(feature = "audit")]
+    ctx.record(audit::Operation::HmacVerify);
+    constant_time::verify_slices_are_equal(ctx.sign_().as_ref(), tag)
+}
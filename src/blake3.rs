@@ -0,0 +1,772 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! BLAKE3, as specified in the [BLAKE3 paper].
+//!
+//! BLAKE3 has three modes: `hash()`; `keyed_hash()`, a MAC that, like keyed
+//! BLAKE2, doesn't need to be wrapped in HMAC; and `derive_key()`, a KDF for
+//! deriving keys from key material with a hardcoded, globally unique context
+//! string. Each mode can also produce any amount of output, with
+//! `Context::finish_xof()`.
+//!
+//! The input is split into 1024-byte chunks that are hashed independently
+//! and combined in a binary tree. With the `blake3_parallel` feature,
+//! `Context::update_parallel()` uses this to hash long inputs on several
+//! threads.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::blake3;
+//!
+//! let digest = blake3::hash(b"hello, world");
+//!
+//! let mut ctx = blake3::Context::new();
+//! ctx.update(b"hello, ");
+//! ctx.update(b"world");
+//! assert_eq!(ctx.finish().as_ref(), digest.as_ref());
+//! ```
+//!
+//! [BLAKE3 paper]: https://github.com/BLAKE3-team/BLAKE3-specs/blob/master/blake3.pdf
+
+use crate::{constant_time, debug, error, rand, zeroize::Zeroize};
+use core::cmp;
+
+#[cfg(feature = "audit")]
+use crate::audit;
+
+/// The length of the output of `hash()`, `keyed_hash()`, and
+/// `Context::finish()`.
+pub const OUTPUT_LEN: usize = 32;
+
+/// The length of a key for keyed BLAKE3.
+pub const KEY_LEN: usize = 32;
+
+/// A BLAKE3 output of `OUTPUT_LEN` bytes.
+///
+/// For a given digest `d`, use `d.as_ref()` to get the value as a byte slice.
+#[derive(Clone, Copy)]
+pub struct Digest([u8; OUTPUT_LEN]);
+
+impl AsRef<[u8]> for Digest {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl core::fmt::Debug for Digest {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.write_str("BLAKE3:")?;
+        debug::write_hex_bytes(fmt, self.as_ref())
+    }
+}
+
+/// A key for keyed BLAKE3.
+#[derive(Clone)]
+pub struct Key {
+    words: [u32; 8],
+    #[cfg(feature = "audit")]
+    fingerprint: audit::Fingerprint,
+}
+
+impl core::fmt::Debug for Key {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Key").finish()
+    }
+}
+
+impl Key {
+    /// Generate a key with a random value generated from `rng`.
+    pub fn generate(rng: &dyn rand::SecureRandom) -> Result<Self, error::Unspecified> {
+        let mut key_bytes = [0; KEY_LEN];
+        rng.fill(&mut key_bytes)?;
+        let key = Self::new(&key_bytes);
+        key_bytes.zeroize();
+        key
+    }
+
+    /// Construct a key with the given key value.
+    ///
+    /// Fails unless `key_value.len() == KEY_LEN`. Unlike HMAC, keyed BLAKE3
+    /// doesn't hash overly long keys; use `derive_key()` to derive a key from
+    /// a key of another length.
+    pub fn new(key_value: &[u8]) -> Result<Self, error::Unspecified> {
+        if key_value.len() != KEY_LEN {
+            return Err(error::Unspecified);
+        }
+        let mut words = [0; 8];
+        load_words(key_value, &mut words);
+        Ok(Self {
+            words,
+            #[cfg(feature = "audit")]
+            fingerprint: audit::Fingerprint::new(key_value),
+        })
+    }
+}
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        self.words.zeroize();
+    }
+}
+
+/// A context for multi-step (Init-Update-Finish) BLAKE3 hashing in any of
+/// its modes.
+///
+/// Use `hash()`, `keyed_hash()`, or `derive_key()` when the input is in one
+/// part.
+#[derive(Clone)]
+pub struct Context {
+    key: [u32; 8],
+    flags: u32,
+    chunk: ChunkState,
+
+    // The chaining values of the complete subtrees to the left of `chunk`,
+    // largest first. There is one for each bit that is set in the number of
+    // chunks before `chunk`.
+    cv_stack: [[u32; 8]; MAX_DEPTH],
+    cv_stack_len: usize,
+
+    #[cfg(feature = "audit")]
+    fingerprint: Option<audit::Fingerprint>,
+    #[cfg(feature = "audit")]
+    len: usize,
+}
+
+impl core::fmt::Debug for Context {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Context").finish()
+    }
+}
+
+impl Context {
+    /// Constructs a context for hashing, as by `hash()`.
+    pub fn new() -> Self {
+        Self::new_(&IV, 0)
+    }
+
+    /// Constructs a context for keyed hashing with `key`, as by
+    /// `keyed_hash()`.
+    pub fn with_key(key: &Key) -> Self {
+        #[cfg_attr(not(feature = "audit"), allow(unused_mut))]
+        let mut ctx = Self::new_(&key.words, KEYED_HASH);
+        #[cfg(feature = "audit")]
+        {
+            ctx.fingerprint = Some(key.fingerprint.clone());
+        }
+        ctx
+    }
+
+    /// Constructs a context for deriving a key with the context string
+    /// `context`, as by `derive_key()`. The key material is the input.
+    pub fn for_key_derivation(context: &str) -> Self {
+        let mut context_ctx = Self::new_(&IV, DERIVE_KEY_CONTEXT);
+        context_ctx.update_(context.as_bytes());
+        let mut context_key = [0; KEY_LEN];
+        context_ctx.root_output().fill(0, &mut context_key);
+        let mut context_key_words = [0; 8];
+        load_words(&context_key, &mut context_key_words);
+        context_key.zeroize();
+        let ctx = Self::new_(&context_key_words, DERIVE_KEY_MATERIAL);
+        context_key_words.zeroize();
+        ctx
+    }
+
+    fn new_(key: &[u32; 8], flags: u32) -> Self {
+        Self {
+            key: *key,
+            flags,
+            chunk: ChunkState::new(key, 0, flags),
+            cv_stack: [[0; 8]; MAX_DEPTH],
+            cv_stack_len: 0,
+            #[cfg(feature = "audit")]
+            fingerprint: None,
+            #[cfg(feature = "audit")]
+            len: 0,
+        }
+    }
+
+    /// Updates the context with all the data in `data`. `update` may be
+    /// called zero or more times until `finish` or `finish_xof` is called.
+    pub fn update(&mut self, data: &[u8]) {
+        self.count(data.len());
+        self.update_(data);
+    }
+
+    /// Like `update`, but hashes long inputs on several threads, up to the
+    /// number given by `std::thread::available_parallelism()`. The result is
+    /// the same as that of `update`.
+    ///
+    /// Each thread hashes at least 128KB of the input, so shorter inputs are
+    /// hashed on the calling thread alone. If a thread can't be spawned then
+    /// its part of the input is hashed on the calling thread.
+    ///
+    /// Only available with the `blake3_parallel` feature.
+    #[cfg(feature = "blake3_parallel")]
+    pub fn update_parallel(&mut self, mut data: &[u8]) {
+        self.count(data.len());
+        let threads = std::thread::available_parallelism()
+            .map(core::num::NonZeroUsize::get)
+            .unwrap_or(1);
+
+        // Fill the current chunk so that the rest of the input starts at a
+        // chunk boundary.
+        if self.chunk.len() > 0 {
+            let len = cmp::min(CHUNK_LEN - self.chunk.len(), data.len());
+            self.update_(&data[..len]);
+            data = &data[len..];
+        }
+
+        while !data.is_empty() {
+            if self.chunk.len() == CHUNK_LEN {
+                self.push_chunk();
+            }
+            let chunk_counter = self.chunk.chunk_counter;
+
+            // The largest subtree that starts at `chunk_counter` and is
+            // followed by more input, so that it isn't the root.
+            let max_chunks = ((data.len() - 1) / CHUNK_LEN) as u64;
+            if max_chunks == 0 {
+                break;
+            }
+            let mut subtree_chunks: u64 = 1 << (63 - max_chunks.leading_zeros());
+            if chunk_counter != 0 {
+                subtree_chunks = cmp::min(subtree_chunks, 1 << chunk_counter.trailing_zeros());
+            }
+            let subtree_len = (subtree_chunks as usize) * CHUNK_LEN;
+            if subtree_len < 2 * PARALLEL_MIN_LEN {
+                break;
+            }
+
+            let (subtree, rest) = data.split_at(subtree_len);
+            let cv = subtree_cv_parallel(&self.key, self.flags, subtree, chunk_counter, threads);
+            self.push_cv(cv, chunk_counter + subtree_chunks, subtree_chunks);
+            self.chunk = ChunkState::new(&self.key, chunk_counter + subtree_chunks, self.flags);
+            data = rest;
+        }
+
+        self.update_(data);
+    }
+
+    fn update_(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // A full chunk is only finished once it is known that it isn't
+            // the last one, which is the root if it is the only one.
+            if self.chunk.len() == CHUNK_LEN {
+                self.push_chunk();
+            }
+            let len = cmp::min(CHUNK_LEN - self.chunk.len(), data.len());
+            self.chunk.update(&data[..len]);
+            data = &data[len..];
+        }
+    }
+
+    #[inline]
+    fn count(&mut self, _len: usize) {
+        #[cfg(feature = "audit")]
+        {
+            self.len = self.len.wrapping_add(_len);
+        }
+    }
+
+    // Finishes the current chunk, which is full, and starts the next one.
+    fn push_chunk(&mut self) {
+        let cv = self.chunk.output().chaining_value();
+        let total_chunks = self.chunk.chunk_counter + 1;
+        self.push_cv(cv, total_chunks, 1);
+        self.chunk = ChunkState::new(&self.key, total_chunks, self.flags);
+    }
+
+    // Adds `cv`, the chaining value of a subtree of `subtree_chunks` chunks,
+    // a power of two, that ends before chunk `total_chunks`. It is merged
+    // with the subtrees on the stack that it completes.
+    fn push_cv(&mut self, mut cv: [u32; 8], total_chunks: u64, subtree_chunks: u64) {
+        let mut total_subtrees = total_chunks / subtree_chunks;
+        while total_subtrees & 1 == 0 {
+            self.cv_stack_len -= 1;
+            cv = parent_cv(
+                &self.cv_stack[self.cv_stack_len],
+                &cv,
+                &self.key,
+                self.flags,
+            );
+            total_subtrees >>= 1;
+        }
+        self.cv_stack[self.cv_stack_len] = cv;
+        self.cv_stack_len += 1;
+    }
+
+    /// Finalizes the calculation and returns the `OUTPUT_LEN`-byte output.
+    /// `finish` consumes the context so it cannot be (mis-)used after
+    /// `finish` has been called.
+    ///
+    /// It is generally not safe to implement verification of a keyed BLAKE3
+    /// tag by comparing the return value of `finish` to the tag. Use
+    /// `verify` for verification instead.
+    pub fn finish(self) -> Digest {
+        #[cfg(feature = "audit")]
+        self.record(audit::Operation::HmacSign);
+        self.finish_()
+    }
+
+    fn finish_(self) -> Digest {
+        let mut out = [0; OUTPUT_LEN];
+        self.root_output().fill(0, &mut out);
+        Digest(out)
+    }
+
+    /// Finalizes the calculation and returns a reader of the extendable
+    /// output, of which the output of `finish` is the first `OUTPUT_LEN`
+    /// bytes. Shorter outputs are prefixes of longer ones.
+    pub fn finish_xof(self) -> OutputReader {
+        #[cfg(feature = "audit")]
+        self.record(audit::Operation::HmacSign);
+        OutputReader {
+            output: self.root_output(),
+            position: 0,
+        }
+    }
+
+    fn root_output(&self) -> Output {
+        let mut output = self.chunk.output();
+        for cv in self.cv_stack[..self.cv_stack_len].iter().rev() {
+            output = parent_output(cv, &output.chaining_value(), &self.key, self.flags);
+        }
+        output
+    }
+
+    #[cfg(feature = "audit")]
+    fn record(&self, operation: audit::Operation) {
+        if let Some(fingerprint) = &self.fingerprint {
+            audit::record(&AlgorithmName, operation, fingerprint, self.len);
+        }
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        self.key.zeroize();
+        for cv in self.cv_stack.iter_mut() {
+            cv.zeroize();
+        }
+    }
+}
+
+/// The extendable output of BLAKE3, from `Context::finish_xof()`.
+///
+/// Any amount of output can be read, in pieces of any size, with
+/// `squeeze()`. Reading the output in pieces gives the same output as
+/// reading it all at once.
+pub struct OutputReader {
+    output: Output,
+    position: u64,
+}
+
+impl core::fmt::Debug for OutputReader {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("OutputReader").finish()
+    }
+}
+
+impl OutputReader {
+    /// Fills `out` with the next `out.len()` bytes of output.
+    pub fn squeeze(&mut self, out: &mut [u8]) {
+        self.output.fill(self.position, out);
+        self.position += out.len() as u64;
+    }
+}
+
+/// Calculates the BLAKE3 hash of `data` in one step.
+///
+/// Use `Context` to hash data that is in multiple parts.
+pub fn hash(data: &[u8]) -> Digest {
+    let mut ctx = Context::new();
+    ctx.update(data);
+    ctx.finish()
+}
+
+/// Calculates the keyed BLAKE3 tag of `data` using the key `key` in one
+/// step.
+///
+/// Use `Context::with_key` to calculate tags where the input is in multiple
+/// parts.
+///
+/// It is generally not safe to implement verification by comparing the
+/// return value of `keyed_hash` to a tag. Use `verify` for verification
+/// instead.
+pub fn keyed_hash(key: &Key, data: &[u8]) -> Digest {
+    let mut ctx = Context::with_key(key);
+    ctx.update(data);
+    ctx.finish()
+}
+
+/// Calculates the keyed BLAKE3 tag of `data` using the key `key`, and
+/// verifies whether the resultant value equals `tag`, in one step.
+///
+/// The verification will be done in constant time to prevent timing attacks.
+pub fn verify(key: &Key, data: &[u8], tag: &[u8]) -> Result<(), error::Unspecified> {
+    let mut ctx = Context::with_key(key);
+    ctx.update(data);
+    #[cfg(feature = "audit")]
+    ctx.record(audit::Operation::HmacVerify);
+    constant_time::verify_slices_are_equal(ctx.finish_().as_ref(), tag)
+}
+
+/// Fills `out` with the key derived from `key_material` with the context
+/// string `context`.
+///
+/// `context` should be hardcoded, globally unique, and application-specific,
+/// e.g. `"example.com 2019-12-25 16:18:03 session tokens v1"`. It must not
+/// depend on secrets or on other runtime values. Any length of output may
+/// be derived; shorter outputs are prefixes of longer ones.
+pub fn derive_key(context: &str, key_material: &[u8], out: &mut [u8]) {
+    let mut ctx = Context::for_key_derivation(context);
+    ctx.update_(key_material);
+    ctx.root_output().fill(0, out);
+}
+
+#[cfg(feature = "audit")]
+struct AlgorithmName;
+
+#[cfg(feature = "audit")]
+impl core::fmt::Debug for AlgorithmName {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.write_str("BLAKE3")
+    }
+}
+
+const BLOCK_LEN: usize = 64;
+const CHUNK_LEN: usize = 1024;
+
+// The input length is at most 2**64 bytes, which is 2**54 chunks.
+const MAX_DEPTH: usize = 54;
+
+/// The minimum length, in bytes, of each thread's part of the input that is
+/// hashed by `Context::update_parallel`. Starting a thread costs about as
+/// much as hashing tens of kilobytes, so shorter inputs are hashed on the
+/// calling thread alone.
+#[cfg(feature = "blake3_parallel")]
+const PARALLEL_MIN_LEN: usize = 128 * 1024;
+
+// Domain separation flags.
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+const KEYED_HASH: u32 = 1 << 4;
+const DERIVE_KEY_CONTEXT: u32 = 1 << 5;
+const DERIVE_KEY_MATERIAL: u32 = 1 << 6;
+
+// The initial hash value of SHA-256.
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+// The state of the chunk that is being hashed.
+#[derive(Clone)]
+struct ChunkState {
+    cv: [u32; 8],
+    chunk_counter: u64,
+    block: [u8; BLOCK_LEN],
+    block_len: usize,
+    blocks_compressed: usize,
+    flags: u32,
+}
+
+impl ChunkState {
+    fn new(key: &[u32; 8], chunk_counter: u64, flags: u32) -> Self {
+        Self {
+            cv: *key,
+            chunk_counter,
+            block: [0; BLOCK_LEN],
+            block_len: 0,
+            blocks_compressed: 0,
+            flags,
+        }
+    }
+
+    fn len(&self) -> usize {
+        (BLOCK_LEN * self.blocks_compressed) + self.block_len
+    }
+
+    fn start_flag(&self) -> u32 {
+        if self.blocks_compressed == 0 {
+            CHUNK_START
+        } else {
+            0
+        }
+    }
+
+    // `data` must fit in the chunk.
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // Like chunks, a full block is only compressed once it is known
+            // that it isn't the last one.
+            if self.block_len == BLOCK_LEN {
+                let mut block_words = [0; 16];
+                load_words(&self.block, &mut block_words);
+                let state = compress(
+                    &self.cv,
+                    &block_words,
+                    self.chunk_counter,
+                    BLOCK_LEN as u32,
+                    self.flags | self.start_flag(),
+                );
+                self.cv.copy_from_slice(&state[..8]);
+                self.blocks_compressed += 1;
+                self.block_len = 0;
+            }
+            let len = cmp::min(BLOCK_LEN - self.block_len, data.len());
+            self.block[self.block_len..][..len].copy_from_slice(&data[..len]);
+            self.block_len += len;
+            data = &data[len..];
+        }
+    }
+
+    fn output(&self) -> Output {
+        let mut block = [0; BLOCK_LEN];
+        block[..self.block_len].copy_from_slice(&self.block[..self.block_len]);
+        let mut block_words = [0; 16];
+        load_words(&block, &mut block_words);
+        block.zeroize();
+        Output {
+            input_cv: self.cv,
+            block_words,
+            counter: self.chunk_counter,
+            block_len: self.block_len as u32,
+            flags: self.flags | self.start_flag() | CHUNK_END,
+        }
+    }
+}
+
+impl Drop for ChunkState {
+    fn drop(&mut self) {
+        self.cv.zeroize();
+        self.block.zeroize();
+    }
+}
+
+// The inputs to the last compression of a chunk or parent node, from which
+// either its chaining value or, if it is the root, the output is computed.
+struct Output {
+    input_cv: [u32; 8],
+    block_words: [u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+}
+
+impl Output {
+    fn chaining_value(&self) -> [u32; 8] {
+        let state = compress(
+            &self.input_cv,
+            &self.block_words,
+            self.counter,
+            self.block_len,
+            self.flags,
+        );
+        let mut cv = [0; 8];
+        cv.copy_from_slice(&state[..8]);
+        cv
+    }
+
+    // Fills `out` with the root output starting at byte `position`.
+    fn fill(&self, mut position: u64, out: &mut [u8]) {
+        const OUTPUT_BLOCK_LEN: usize = 2 * OUTPUT_LEN;
+        let mut filled = 0;
+        while filled < out.len() {
+            let mut state = compress(
+                &self.input_cv,
+                &self.block_words,
+                position / (OUTPUT_BLOCK_LEN as u64),
+                self.block_len,
+                self.flags | ROOT,
+            );
+            let mut block = [0; OUTPUT_BLOCK_LEN];
+            for (word, bytes) in state.iter().zip(block.chunks_mut(4)) {
+                bytes.copy_from_slice(&word.to_le_bytes());
+            }
+            let offset = (position % (OUTPUT_BLOCK_LEN as u64)) as usize;
+            let len = cmp::min(OUTPUT_BLOCK_LEN - offset, out.len() - filled);
+            out[filled..][..len].copy_from_slice(&block[offset..][..len]);
+            state.zeroize();
+            block.zeroize();
+            filled += len;
+            position += len as u64;
+        }
+    }
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        self.input_cv.zeroize();
+        self.block_words.zeroize();
+    }
+}
+
+fn parent_output(left: &[u32; 8], right: &[u32; 8], key: &[u32; 8], flags: u32) -> Output {
+    let mut block_words = [0; 16];
+    block_words[..8].copy_from_slice(left);
+    block_words[8..].copy_from_slice(right);
+    Output {
+        input_cv: *key,
+        block_words,
+        counter: 0,
+        block_len: BLOCK_LEN as u32,
+        flags: PARENT | flags,
+    }
+}
+
+fn parent_cv(left: &[u32; 8], right: &[u32; 8], key: &[u32; 8], flags: u32) -> [u32; 8] {
+    parent_output(left, right, key, flags).chaining_value()
+}
+
+// The chaining value of the subtree of `input`, which is a power of two
+// number of whole chunks, the first of which is chunk `chunk_counter`. The
+// subtree must not be the root.
+#[cfg(feature = "blake3_parallel")]
+fn subtree_cv(key: &[u32; 8], flags: u32, input: &[u8], chunk_counter: u64) -> [u32; 8] {
+    if input.len() == CHUNK_LEN {
+        let mut chunk = ChunkState::new(key, chunk_counter, flags);
+        chunk.update(input);
+        return chunk.output().chaining_value();
+    }
+    let (left, right) = input.split_at(input.len() / 2);
+    let right_counter = chunk_counter + (left.len() / CHUNK_LEN) as u64;
+    let left_cv = subtree_cv(key, flags, left, chunk_counter);
+    let right_cv = subtree_cv(key, flags, right, right_counter);
+    parent_cv(&left_cv, &right_cv, key, flags)
+}
+
+// Like `subtree_cv`, but the left half of the subtree is hashed on a new
+// thread while the right half is hashed on the calling thread, recursively,
+// until there are `threads` threads or the halves would be shorter than
+// `PARALLEL_MIN_LEN`. A panic on any of the threads is propagated.
+#[cfg(feature = "blake3_parallel")]
+fn subtree_cv_parallel(
+    key: &[u32; 8],
+    flags: u32,
+    input: &[u8],
+    chunk_counter: u64,
+    threads: usize,
+) -> [u32; 8] {
+    if threads < 2 || input.len() < 2 * PARALLEL_MIN_LEN {
+        return subtree_cv(key, flags, input, chunk_counter);
+    }
+    let (left, right) = input.split_at(input.len() / 2);
+    let right_counter = chunk_counter + (left.len() / CHUNK_LEN) as u64;
+    let left_threads = threads / 2;
+    let (left_cv, right_cv) = std::thread::scope(|scope| {
+        let handle = std::thread::Builder::new()
+            .spawn_scoped(scope, || {
+                subtree_cv_parallel(key, flags, left, chunk_counter, left_threads)
+            })
+            .ok();
+        let right_cv =
+            subtree_cv_parallel(key, flags, right, right_counter, threads - left_threads);
+        let left_cv = match handle {
+            Some(handle) => handle
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e)),
+            None => subtree_cv(key, flags, left, chunk_counter),
+        };
+        (left_cv, right_cv)
+    });
+    parent_cv(&left_cv, &right_cv, key, flags)
+}
+
+// The compression function. The first eight words of the result are the
+// chaining value; all sixteen are used for the root output.
+fn compress(
+    cv: &[u32; 8],
+    block_words: &[u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+) -> [u32; 16] {
+    let mut state = [
+        cv[0],
+        cv[1],
+        cv[2],
+        cv[3],
+        cv[4],
+        cv[5],
+        cv[6],
+        cv[7],
+        IV[0],
+        IV[1],
+        IV[2],
+        IV[3],
+        counter as u32,
+        (counter >> 32) as u32,
+        block_len,
+        flags,
+    ];
+    let mut m = *block_words;
+    for round in 0..7 {
+        // Mix the columns.
+        g(&mut state, 0, 4, 8, 12, m[0], m[1]);
+        g(&mut state, 1, 5, 9, 13, m[2], m[3]);
+        g(&mut state, 2, 6, 10, 14, m[4], m[5]);
+        g(&mut state, 3, 7, 11, 15, m[6], m[7]);
+        // Mix the diagonals.
+        g(&mut state, 0, 5, 10, 15, m[8], m[9]);
+        g(&mut state, 1, 6, 11, 12, m[10], m[11]);
+        g(&mut state, 2, 7, 8, 13, m[12], m[13]);
+        g(&mut state, 3, 4, 9, 14, m[14], m[15]);
+
+        if round < 6 {
+            let mut permuted = [0; 16];
+            for (p, &i) in permuted.iter_mut().zip(MSG_PERMUTATION.iter()) {
+                *p = m[i];
+            }
+            m = permuted;
+        }
+    }
+    m.zeroize();
+    for (i, cv_word) in cv.iter().enumerate() {
+        state[i] ^= state[i + 8];
+        state[i + 8] ^= cv_word;
+    }
+    state
+}
+
+#[inline(always)]
+fn g(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, mx: u32, my: u32) {
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(mx);
+    state[d] = (state[d] ^ state[a]).rotate_right(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(12);
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(my);
+    state[d] = (state[d] ^ state[a]).rotate_right(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(7);
+}
+
+// Loads the little-endian words of `bytes` into `words`.
+fn load_words(bytes: &[u8], words: &mut [u32]) {
+    debug_assert_eq!(bytes.len(), 4 * words.len());
+    for (word, bytes) in words.iter_mut().zip(bytes.chunks(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! A minimal CBOR (RFC 7049) reader and writer.
+//!
+//! Only definite-length items with minimally-encoded arguments are
+//! supported, as required by CTAP2's canonical form and COSE's
+//! deterministic encoding. When reading, values other than integers and
+//! byte strings are skipped.
+
+use crate::error;
+use std::vec::Vec;
+use untrusted;
+
+#[derive(Clone, Copy)]
+pub(crate) enum Value<'a> {
+    Int(i64),
+    Bytes(&'a [u8]),
+    Other,
+}
+
+pub(crate) const MAJOR_UNSIGNED: u8 = 0;
+pub(crate) const MAJOR_NEGATIVE: u8 = 1;
+pub(crate) const MAJOR_BYTES: u8 = 2;
+pub(crate) const MAJOR_TEXT: u8 = 3;
+pub(crate) const MAJOR_ARRAY: u8 = 4;
+pub(crate) const MAJOR_MAP: u8 = 5;
+pub(crate) const MAJOR_TAG: u8 = 6;
+pub(crate) const MAJOR_SIMPLE: u8 = 7;
+
+/// The simple value `null`.
+pub(crate) const SIMPLE_NULL: u64 = 22;
+
+// Bounds the recursion when skipping nested arrays and maps.
+const MAX_DEPTH: usize = 4;
+
+pub(crate) fn read_value<'a>(
+    input: &mut untrusted::Reader<'a>,
+    depth: usize,
+) -> Result<Value<'a>, error::Unspecified> {
+    if depth > MAX_DEPTH {
+        return Err(error::Unspecified);
+    }
+    let (major, argument) = read_any_head(input)?;
+    let value = match major {
+        MAJOR_UNSIGNED | MAJOR_NEGATIVE => {
+            if argument > i64::max_value() as u64 {
+                return Err(error::Unspecified);
+            }
+            let argument = argument as i64;
+            Value::Int(if major == MAJOR_UNSIGNED {
+                argument
+            } else {
+                -1 - argument
+            })
+        }
+        MAJOR_BYTES => Value::Bytes(read_content(input, argument)?),
+        MAJOR_TEXT => {
+            let _ = read_content(input, argument)?;
+            Value::Other
+        }
+        MAJOR_ARRAY | MAJOR_MAP => {
+            let items = if major == MAJOR_MAP {
+                argument.checked_mul(2).ok_or(error::Unspecified)?
+            } else {
+                argument
+            };
+            for _ in 0..items {
+                let _ = read_value(input, depth + 1)?;
+            }
+            Value::Other
+        }
+        MAJOR_TAG => {
+            let _ = read_value(input, depth + 1)?;
+            Value::Other
+        }
+        _ => {
+            debug_assert_eq!(major, MAJOR_SIMPLE);
+            // Only the simple values `false`, `true`, `null`, and
+            // `undefined` are allowed.
+            match argument {
+                20..=23 => Value::Other,
+                _ => {
+                    return Err(error::Unspecified);
+                }
+            }
+        }
+    };
+    Ok(value)
+}
+
+pub(crate) fn read_head(
+    input: &mut untrusted::Reader,
+    expected_major: u8,
+) -> Result<u64, error::Unspecified> {
+    let (major, argument) = read_any_head(input)?;
+    if major != expected_major {
+        return Err(error::Unspecified);
+    }
+    Ok(argument)
+}
+
+pub(crate) fn read_any_head(
+    input: &mut untrusted::Reader,
+) -> Result<(u8, u64), error::Unspecified> {
+    let initial_byte = input.read_byte()?;
+    let major = initial_byte >> 5;
+    let additional_info = initial_byte & 0x1f;
+    let (len, min) = match additional_info {
+        0..=23 => {
+            return Ok((major, u64::from(additional_info)));
+        }
+        24 => (1, 24),
+        25 => (2, 0x100),
+        26 => (4, 0x1_0000),
+        27 => (8, 0x1_0000_0000),
+        // Reserved values and indefinite lengths.
+        _ => {
+            return Err(error::Unspecified);
+        }
+    };
+    let mut argument = 0u64;
+    for b in input.read_bytes(len)?.as_slice_less_safe() {
+        argument = (argument << 8) | u64::from(*b);
+    }
+    if argument < min {
+        return Err(error::Unspecified);
+    }
+    Ok((major, argument))
+}
+
+/// Reads the content of a byte string or text string whose head has
+/// already been read.
+pub(crate) fn read_content<'a>(
+    input: &mut untrusted::Reader<'a>,
+    len: u64,
+) -> Result<&'a [u8], error::Unspecified> {
+    if len > usize::max_value() as u64 {
+        return Err(error::Unspecified);
+    }
+    Ok(input.read_bytes(len as usize)?.as_slice_less_safe())
+}
+
+pub(crate) fn write_head(out: &mut Vec<u8>, major: u8, argument: u64) {
+    let major = major << 5;
+    if argument < 24 {
+        out.push(major | argument as u8);
+        return;
+    }
+    let (additional_info, len) = if argument <= 0xff {
+        (24, 1)
+    } else if argument <= 0xffff {
+        (25, 2)
+    } else if argument <= 0xffff_ffff {
+        (26, 4)
+    } else {
+        (27, 8)
+    };
+    out.push(major | additional_info);
+    out.extend_from_slice(&argument.to_be_bytes()[(8 - len)..]);
+}
+
+pub(crate) fn write_int(out: &mut Vec<u8>, value: i64) {
+    if value >= 0 {
+        write_head(out, MAJOR_UNSIGNED, value as u64);
+    } else {
+        write_head(out, MAJOR_NEGATIVE, !value as u64);
+    }
+}
+
+pub(crate) fn write_bytes(out: &mut Vec<u8>, value: &[u8]) {
+    write_head(out, MAJOR_BYTES, value.len() as u64);
+    out.extend_from_slice(value);
+}
+
+pub(crate) fn write_text(out: &mut Vec<u8>, value: &str) {
+    write_head(out, MAJOR_TEXT, value.len() as u64);
+    out.extend_from_slice(value.as_bytes());
+}
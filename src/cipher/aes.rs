@@ -0,0 +1,147 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Single-block AES encryption and decryption, as specified in [FIPS 197].
+//!
+//! See the warnings in the `cipher` module documentation.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::cipher::aes;
+//!
+//! let key = aes::Key::new(&aes::AES_128, &[0x2b; 16])?;
+//!
+//! let plaintext = [0u8; aes::BLOCK_LEN];
+//! let ciphertext = key.encrypt_block(plaintext);
+//! assert_eq!(key.decrypt_block(ciphertext), plaintext);
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [FIPS 197]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.197.pdf
+
+use crate::{
+    aead::{aes, block, inverse_cipher},
+    cpu, error,
+};
+
+/// The length of an AES block.
+pub const BLOCK_LEN: usize = block::BLOCK_LEN;
+
+/// An AES block.
+pub type Block = [u8; BLOCK_LEN];
+
+/// An AES key, for encrypting and decrypting single blocks.
+///
+/// Encryption uses the same implementation as the AES-based AEADs, including
+/// AES-NI and the ARMv8 AES instructions when the CPU has them.
+pub struct Key {
+    aes_key: aes::Key,
+    inverse_cipher_key: inverse_cipher::Key,
+    algorithm: &'static Algorithm,
+}
+
+derive_debug_via_field!(Key, algorithm);
+
+impl Key {
+    /// Constructs a key for `algorithm` with the given key value.
+    ///
+    /// `key_bytes` must be exactly `algorithm.key_len()` bytes long.
+    pub fn new(
+        algorithm: &'static Algorithm,
+        key_bytes: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        let cpu_features = cpu::features();
+        let aes_key = aes::Key::new(key_bytes, algorithm.variant, cpu_features)?;
+        Ok(Self {
+            aes_key,
+            inverse_cipher_key: inverse_cipher::Key::new(
+                key_bytes,
+                algorithm.variant,
+                cpu_features,
+            ),
+            algorithm,
+        })
+    }
+
+    /// The key's algorithm.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+
+    /// Encrypts one block with the AES cipher.
+    #[inline]
+    pub fn encrypt_block(&self, block: Block) -> Block {
+        *self
+            .aes_key
+            .encrypt_block(block::Block::from(&block))
+            .as_ref()
+    }
+
+    /// Decrypts one block with the AES inverse cipher.
+    #[inline]
+    pub fn decrypt_block(&self, block: Block) -> Block {
+        *self
+            .inverse_cipher_key
+            .decrypt_block(block::Block::from(&block))
+            .as_ref()
+    }
+}
+
+/// An AES block cipher algorithm.
+pub struct Algorithm {
+    variant: aes::Variant,
+    key_len: usize,
+    id: AlgorithmID,
+}
+
+impl Algorithm {
+    /// The length of the key.
+    #[inline(always)]
+    pub fn key_len(&self) -> usize {
+        self.key_len
+    }
+}
+
+derive_debug_via_id!(Algorithm);
+
+#[derive(Debug, Eq, PartialEq)]
+enum AlgorithmID {
+    AES_128,
+    AES_256,
+}
+
+impl PartialEq for Algorithm {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Algorithm {}
+
+/// AES with a 128-bit key.
+pub static AES_128: Algorithm = Algorithm {
+    variant: aes::Variant::AES_128,
+    key_len: 16,
+    id: AlgorithmID::AES_128,
+};
+
+/// AES with a 256-bit key.
+pub static AES_256: Algorithm = Algorithm {
+    variant: aes::Variant::AES_256,
+    key_len: 32,
+    id: AlgorithmID::AES_256,
+};
@@ -0,0 +1,499 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Verification of CMS (PKCS#7) `SignedData`.
+//!
+//! See [RFC 5652 Section 5]. Both attached (encapsulated) and detached
+//! content are supported. When a `SignerInfo` has signed attributes, the
+//! `content-type` and `message-digest` attributes are checked against the
+//! content and the signature is verified over the DER encoding of the signed
+//! attributes, as the RFC requires.
+//!
+//! Certificate path building is out of scope; the caller is responsible for
+//! finding the signer's certificate (e.g. using `SignerInfo::signer_id()`),
+//! validating it, and extracting its public key.
+//!
+//! Only DER is accepted, not BER, and lengths are limited to those supported
+//! by `io::der`; large content should be verified in detached form.
+//!
+//! [RFC 5652 Section 5]: https://tools.ietf.org/html/rfc5652#section-5
+
+use crate::{
+    constant_time, digest, error,
+    io::{
+        der::{self, Tag},
+        der_writer,
+    },
+    signature,
+};
+use std::vec::Vec;
+use untrusted;
+
+/// A parsed `SignedData`.
+#[derive(Debug)]
+pub struct SignedData<'a> {
+    content_type: &'a [u8],
+    content: Option<&'a [u8]>,
+    signers: Vec<SignerInfo<'a>>,
+}
+
+impl<'a> SignedData<'a> {
+    /// Parses a DER-encoded `ContentInfo` containing a `SignedData`.
+    ///
+    /// `SignerInfo`s that use unsupported digest or signature algorithms are
+    /// rejected.
+    pub fn from_der(input: &'a [u8]) -> Result<Self, error::Unspecified> {
+        untrusted::Input::from(input).read_all(error::Unspecified, |input| {
+            der::nested(input, Tag::Sequence, error::Unspecified, |content_info| {
+                let content_type = der::expect_tag_and_get_value(content_info, Tag::OID)?;
+                if content_type.as_slice_less_safe() != OID_SIGNED_DATA {
+                    return Err(error::Unspecified);
+                }
+                der::nested(
+                    content_info,
+                    Tag::ContextSpecificConstructed0,
+                    error::Unspecified,
+                    |explicit| der::nested(explicit, Tag::Sequence, error::Unspecified, parse),
+                )
+            })
+        })
+    }
+
+    /// The encapsulated content type, as the value of the DER-encoded OID.
+    #[inline]
+    pub fn content_type(&self) -> &'a [u8] {
+        self.content_type
+    }
+
+    /// The encapsulated content, or `None` if the content is detached.
+    #[inline]
+    pub fn content(&self) -> Option<&'a [u8]> {
+        self.content
+    }
+
+    /// The signers.
+    #[inline]
+    pub fn signers(&self) -> &[SignerInfo<'a>] {
+        &self.signers
+    }
+
+    /// Verifies `signer`'s signature using `public_key`.
+    ///
+    /// `detached_content` must be `Some` exactly when the content isn't
+    /// encapsulated in the `SignedData`.
+    pub fn verify(
+        &self,
+        signer: &SignerInfo,
+        public_key: PublicKey,
+        detached_content: Option<&[u8]>,
+    ) -> Result<(), error::Unspecified> {
+        let content = match (self.content, detached_content) {
+            (Some(content), None) | (None, Some(content)) => content,
+            _ => {
+                return Err(error::Unspecified);
+            }
+        };
+        let verification_alg = signer.verification_alg(&public_key)?;
+        let public_key = signature::UnparsedPublicKey::new(verification_alg, public_key.bytes());
+
+        match signer.signed_attrs {
+            Some(signed_attrs) => {
+                check_signed_attrs(
+                    signed_attrs,
+                    self.content_type,
+                    digest::digest(signer.digest_alg, content).as_ref(),
+                )?;
+                // The signature is over the attributes encoded with the
+                // `SET OF` tag, not the `[0] IMPLICIT` tag they were sent
+                // with.
+                let signed_attrs = der_writer::write_all(Tag::Set, &|output| {
+                    output.write_bytes(signed_attrs.as_slice_less_safe())
+                });
+                public_key.verify(&signed_attrs, signer.signature)
+            }
+            None => {
+                // Without signed attributes the content type isn't
+                // authenticated, so RFC 5652 requires it to be id-data.
+                if self.content_type != OID_DATA {
+                    return Err(error::Unspecified);
+                }
+                public_key.verify(content, signer.signature)
+            }
+        }
+    }
+}
+
+/// A parsed `SignerInfo`.
+#[derive(Debug)]
+pub struct SignerInfo<'a> {
+    signer_id: SignerIdentifier<'a>,
+    digest_alg: &'static digest::Algorithm,
+    signed_attrs: Option<untrusted::Input<'a>>,
+    signature_alg: SignatureAlgorithm,
+    signature: &'a [u8],
+}
+
+impl<'a> SignerInfo<'a> {
+    /// Identifies the signer's certificate.
+    #[inline]
+    pub fn signer_id(&self) -> &SignerIdentifier<'a> {
+        &self.signer_id
+    }
+
+    /// The digest algorithm used.
+    #[inline]
+    pub fn digest_algorithm(&self) -> &'static digest::Algorithm {
+        self.digest_alg
+    }
+
+    fn verification_alg(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<&'static dyn signature::VerificationAlgorithm, error::Unspecified> {
+        let candidates = match (self.signature_alg, public_key) {
+            (SignatureAlgorithm::Ecdsa, PublicKey::EcdsaP256(_)) => ECDSA_P256_ALGORITHMS,
+            (SignatureAlgorithm::Ecdsa, PublicKey::EcdsaP384(_)) => ECDSA_P384_ALGORITHMS,
+            (SignatureAlgorithm::RsaPkcs1, PublicKey::Rsa(_)) => RSA_PKCS1_ALGORITHMS,
+            _ => {
+                return Err(error::Unspecified);
+            }
+        };
+        candidates
+            .iter()
+            .find(|(digest_alg, _)| *digest_alg == self.digest_alg)
+            .map(|(_, verification_alg)| *verification_alg)
+            .ok_or(error::Unspecified)
+    }
+}
+
+/// Identifies the certificate of the signer of a `SignerInfo`.
+#[derive(Clone, Copy, Debug)]
+pub enum SignerIdentifier<'a> {
+    /// The issuer's distinguished name and the certificate's serial number.
+    IssuerAndSerialNumber {
+        /// The DER-encoded `Name` of the issuer, including the outer
+        /// `SEQUENCE` tag and length.
+        issuer: &'a [u8],
+
+        /// The big-endian encoding of the serial number.
+        serial_number: &'a [u8],
+    },
+
+    /// The value of the certificate's subject key identifier extension.
+    SubjectKeyIdentifier(&'a [u8]),
+}
+
+/// The public key of a signer.
+#[derive(Clone, Copy, Debug)]
+pub enum PublicKey<'a> {
+    /// An uncompressed P-256 point.
+    EcdsaP256(&'a [u8]),
+
+    /// An uncompressed P-384 point.
+    EcdsaP384(&'a [u8]),
+
+    /// An ASN.1 `RSAPublicKey`.
+    Rsa(&'a [u8]),
+}
+
+impl<'a> PublicKey<'a> {
+    fn bytes(&self) -> &'a [u8] {
+        match *self {
+            PublicKey::EcdsaP256(bytes) | PublicKey::EcdsaP384(bytes) | PublicKey::Rsa(bytes) => {
+                bytes
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum SignatureAlgorithm {
+    Ecdsa,
+    RsaPkcs1,
+}
+
+fn parse<'a>(input: &mut untrusted::Reader<'a>) -> Result<SignedData<'a>, error::Unspecified> {
+    let _version = der::small_nonnegative_integer(input)?;
+
+    // The digest algorithms of the `SignerInfo`s are what matter.
+    let _ = der::expect_tag_and_get_value(input, Tag::Set)?;
+
+    let (content_type, content) = der::nested(input, Tag::Sequence, error::Unspecified, |encap| {
+        let content_type = der::expect_tag_and_get_value(encap, Tag::OID)?;
+        let content = if encap.at_end() {
+            None
+        } else {
+            let content = der::nested(
+                encap,
+                Tag::ContextSpecificConstructed0,
+                error::Unspecified,
+                |explicit| der::expect_tag_and_get_value(explicit, Tag::OctetString),
+            )?;
+            Some(content.as_slice_less_safe())
+        };
+        Ok((content_type.as_slice_less_safe(), content))
+    })?;
+
+    // Certificates and CRLs are ignored; see the module documentation.
+    if input.peek(Tag::ContextSpecificConstructed0.into()) {
+        let _ = der::expect_tag_and_get_value(input, Tag::ContextSpecificConstructed0)?;
+    }
+    if input.peek(Tag::ContextSpecificConstructed1.into()) {
+        let _ = der::expect_tag_and_get_value(input, Tag::ContextSpecificConstructed1)?;
+    }
+
+    let signers = der::nested(input, Tag::Set, error::Unspecified, |signer_infos| {
+        let mut signers = Vec::new();
+        while !signer_infos.at_end() {
+            signers.push(der::nested(
+                signer_infos,
+                Tag::Sequence,
+                error::Unspecified,
+                parse_signer_info,
+            )?);
+        }
+        Ok(signers)
+    })?;
+
+    Ok(SignedData {
+        content_type,
+        content,
+        signers,
+    })
+}
+
+fn parse_signer_info<'a>(
+    input: &mut untrusted::Reader<'a>,
+) -> Result<SignerInfo<'a>, error::Unspecified> {
+    let version = der::small_nonnegative_integer(input)?;
+    let signer_id = match version {
+        1 => der::nested(input, Tag::Sequence, error::Unspecified, |isn| {
+            let issuer =
+                isn.read_partial(|isn| der::expect_tag_and_get_value(isn, Tag::Sequence))?;
+            let serial_number = der::expect_tag_and_get_value(isn, Tag::Integer)?;
+            Ok(SignerIdentifier::IssuerAndSerialNumber {
+                issuer: issuer.0.as_slice_less_safe(),
+                serial_number: serial_number.as_slice_less_safe(),
+            })
+        })?,
+        3 => {
+            let ski = der::expect_tag_and_get_value(input, Tag::ContextSpecific0)?;
+            SignerIdentifier::SubjectKeyIdentifier(ski.as_slice_less_safe())
+        }
+        _ => {
+            return Err(error::Unspecified);
+        }
+    };
+
+    let digest_alg = der::nested(input, Tag::Sequence, error::Unspecified, |alg_id| {
+        let oid = der::expect_tag_and_get_value(alg_id, Tag::OID)?;
+        skip_null_parameters(alg_id)?;
+        DIGEST_ALGORITHMS
+            .iter()
+            .find(|(alg_oid, _)| oid.as_slice_less_safe() == *alg_oid)
+            .map(|(_, alg)| *alg)
+            .ok_or(error::Unspecified)
+    })?;
+
+    let signed_attrs = if input.peek(Tag::ContextSpecificConstructed0.into()) {
+        Some(der::expect_tag_and_get_value(
+            input,
+            Tag::ContextSpecificConstructed0,
+        )?)
+    } else {
+        None
+    };
+
+    let signature_alg = der::nested(input, Tag::Sequence, error::Unspecified, |alg_id| {
+        let oid = der::expect_tag_and_get_value(alg_id, Tag::OID)?;
+        let (alg, digest_alg_from_oid) = SIGNATURE_ALGORITHMS
+            .iter()
+            .find(|(alg_oid, _, _)| oid.as_slice_less_safe() == *alg_oid)
+            .map(|(_, alg, digest_alg)| (*alg, *digest_alg))
+            .ok_or(error::Unspecified)?;
+        if let Some(digest_alg_from_oid) = digest_alg_from_oid {
+            if digest_alg_from_oid != digest_alg {
+                return Err(error::Unspecified);
+            }
+        }
+        match alg {
+            SignatureAlgorithm::RsaPkcs1 => skip_null_parameters(alg_id)?,
+            SignatureAlgorithm::Ecdsa => (), // Parameters must be absent.
+        }
+        Ok(alg)
+    })?;
+
+    let signature = der::expect_tag_and_get_value(input, Tag::OctetString)?;
+
+    // Unsigned attributes aren't covered by the signature so they're ignored.
+    if input.peek(Tag::ContextSpecificConstructed1.into()) {
+        let _ = der::expect_tag_and_get_value(input, Tag::ContextSpecificConstructed1)?;
+    }
+
+    Ok(SignerInfo {
+        signer_id,
+        digest_alg,
+        signed_attrs,
+        signature_alg,
+        signature: signature.as_slice_less_safe(),
+    })
+}
+
+fn skip_null_parameters(input: &mut untrusted::Reader) -> Result<(), error::Unspecified> {
+    if !input.at_end() {
+        let null = der::expect_tag_and_get_value(input, Tag::Null)?;
+        if !null.is_empty() {
+            return Err(error::Unspecified);
+        }
+    }
+    Ok(())
+}
+
+fn check_signed_attrs(
+    signed_attrs: untrusted::Input,
+    content_type: &[u8],
+    content_digest: &[u8],
+) -> Result<(), error::Unspecified> {
+    let mut found_content_type = false;
+    let mut found_message_digest = false;
+
+    signed_attrs.read_all(error::Unspecified, |attrs| {
+        while !attrs.at_end() {
+            der::nested(attrs, Tag::Sequence, error::Unspecified, |attr| {
+                let attr_type = der::expect_tag_and_get_value(attr, Tag::OID)?;
+                let values = der::expect_tag_and_get_value(attr, Tag::Set)?;
+                let (seen, tag, expected) = if attr_type.as_slice_less_safe() == OID_CONTENT_TYPE {
+                    (&mut found_content_type, Tag::OID, content_type)
+                } else if attr_type.as_slice_less_safe() == OID_MESSAGE_DIGEST {
+                    (&mut found_message_digest, Tag::OctetString, content_digest)
+                } else {
+                    return Ok(());
+                };
+                // Each attribute must occur once, with exactly one value.
+                if *seen {
+                    return Err(error::Unspecified);
+                }
+                *seen = true;
+                let value = values.read_all(error::Unspecified, |values| {
+                    der::expect_tag_and_get_value(values, tag)
+                })?;
+                constant_time::verify_slices_are_equal(value.as_slice_less_safe(), expected)
+            })?;
+        }
+        Ok(())
+    })?;
+
+    if !found_content_type || !found_message_digest {
+        return Err(error::Unspecified);
+    }
+    Ok(())
+}
+
+// id-signedData, 1.2.840.113549.1.7.2.
+const OID_SIGNED_DATA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02];
+
+// id-data, 1.2.840.113549.1.7.1.
+const OID_DATA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01];
+
+// id-contentType, 1.2.840.113549.1.9.3.
+const OID_CONTENT_TYPE: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x03];
+
+// id-messageDigest, 1.2.840.113549.1.9.4.
+const OID_MESSAGE_DIGEST: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x04];
+
+type VerificationAlgorithms = &'static [(
+    &'static digest::Algorithm,
+    &'static dyn signature::VerificationAlgorithm,
+)];
+
+static ECDSA_P256_ALGORITHMS: VerificationAlgorithms = &[
+    (&digest::SHA256, &signature::ECDSA_P256_SHA256_ASN1),
+    (&digest::SHA384, &signature::ECDSA_P256_SHA384_ASN1),
+];
+
+static ECDSA_P384_ALGORITHMS: VerificationAlgorithms = &[
+    (&digest::SHA256, &signature::ECDSA_P384_SHA256_ASN1),
+    (&digest::SHA384, &signature::ECDSA_P384_SHA384_ASN1),
+];
+
+static RSA_PKCS1_ALGORITHMS: VerificationAlgorithms = &[
+    (&digest::SHA256, &signature::RSA_PKCS1_2048_8192_SHA256),
+    (&digest::SHA384, &signature::RSA_PKCS1_2048_8192_SHA384),
+    (&digest::SHA512, &signature::RSA_PKCS1_2048_8192_SHA512),
+];
+
+static DIGEST_ALGORITHMS: &[(&[u8], &digest::Algorithm)] = &[
+    // id-sha256, 2.16.840.1.101.3.4.2.1.
+    (
+        &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01],
+        &digest::SHA256,
+    ),
+    // id-sha384, 2.16.840.1.101.3.4.2.2.
+    (
+        &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02],
+        &digest::SHA384,
+    ),
+    // id-sha512, 2.16.840.1.101.3.4.2.3.
+    (
+        &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03],
+        &digest::SHA512,
+    ),
+];
+
+// Some signers identify the signature algorithm only by the key type, in
+// which case the digest algorithm is the one in `digestAlgorithm`.
+static SIGNATURE_ALGORITHMS: &[(&[u8], SignatureAlgorithm, Option<&digest::Algorithm>)] = &[
+    // rsaEncryption, 1.2.840.113549.1.1.1.
+    (
+        &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01],
+        SignatureAlgorithm::RsaPkcs1,
+        None,
+    ),
+    // sha256WithRSAEncryption, 1.2.840.113549.1.1.11.
+    (
+        &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b],
+        SignatureAlgorithm::RsaPkcs1,
+        Some(&digest::SHA256),
+    ),
+    // sha384WithRSAEncryption, 1.2.840.113549.1.1.12.
+    (
+        &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0c],
+        SignatureAlgorithm::RsaPkcs1,
+        Some(&digest::SHA384),
+    ),
+    // sha512WithRSAEncryption, 1.2.840.113549.1.1.13.
+    (
+        &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0d],
+        SignatureAlgorithm::RsaPkcs1,
+        Some(&digest::SHA512),
+    ),
+    // id-ecPublicKey, 1.2.840.10045.2.1.
+    (
+        &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01],
+        SignatureAlgorithm::Ecdsa,
+        None,
+    ),
+    // ecdsa-with-SHA256, 1.2.840.10045.4.3.2.
+    (
+        &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02],
+        SignatureAlgorithm::Ecdsa,
+        Some(&digest::SHA256),
+    ),
+    // ecdsa-with-SHA384, 1.2.840.10045.4.3.3.
+    (
+        &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x03],
+        SignatureAlgorithm::Ecdsa,
+        Some(&digest::SHA384),
+    ),
+];
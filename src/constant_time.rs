@@ -13,8 +13,16 @@
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
 //! Constant-time operations.
+//!
+//! Unless otherwise noted, these functions take the same amount of time
+//! regardless of the contents of their inputs, but NOT regardless of the
+//! lengths of their inputs.
 
 use crate::{c, error};
+use core::cmp::Ordering;
+
+#[cfg(feature = "use_heap")]
+use std::{string::String, vec::Vec};
 
 /// Returns `Ok(())` if `a == b` and `Err(error::Unspecified)` otherwise.
 /// The comparison of `a` and `b` is done in constant time with respect to the
@@ -31,6 +39,172 @@ pub fn verify_slices_are_equal(a: &[u8], b: &[u8]) -> Result<(), error::Unspecif
     }
 }
 
+/// Compares `a` and `b` as big-endian unsigned integers.
+///
+/// Returns `Err(error::Unspecified)` if `a` and `b` have different lengths.
+/// The comparison is done in constant time with respect to the contents of
+/// `a` and `b`; only the result is revealed.
+pub fn compare(a: &[u8], b: &[u8]) -> Result<Ordering, error::Unspecified> {
+    if a.len() != b.len() {
+        return Err(error::Unspecified);
+    }
+    // `gt` and `lt` are each 0 or 1. Once either is set, later (less
+    // significant) bytes don't affect the result.
+    let mut gt = 0u32;
+    let mut lt = 0u32;
+    for (a, b) in a.iter().zip(b) {
+        let (a, b) = (u32::from(*a), u32::from(*b));
+        let undecided = 1 ^ (gt | lt);
+        gt |= undecided & (b.wrapping_sub(a) >> 31);
+        lt |= undecided & (a.wrapping_sub(b) >> 31);
+    }
+    Ok(match (gt, lt) {
+        (1, _) => Ordering::Greater,
+        (_, 1) => Ordering::Less,
+        _ => Ordering::Equal,
+    })
+}
+
+/// Copies `src` into `dst` if `condition` is true; otherwise leaves `dst`
+/// unchanged.
+///
+/// Returns `Err(error::Unspecified)` if `dst` and `src` have different
+/// lengths. Both slices are read and `dst` is written either way.
+pub fn conditional_copy(
+    condition: bool,
+    dst: &mut [u8],
+    src: &[u8],
+) -> Result<(), error::Unspecified> {
+    if dst.len() != src.len() {
+        return Err(error::Unspecified);
+    }
+    let mask = mask_from_bool(condition);
+    for (d, s) in dst.iter_mut().zip(src) {
+        *d ^= mask & (*d ^ *s);
+    }
+    Ok(())
+}
+
+/// Writes `a` to `out` if `condition` is true and `b` otherwise.
+///
+/// Returns `Err(error::Unspecified)` unless `a`, `b`, and `out` all have the
+/// same length.
+pub fn select(
+    condition: bool,
+    a: &[u8],
+    b: &[u8],
+    out: &mut [u8],
+) -> Result<(), error::Unspecified> {
+    if a.len() != b.len() || out.len() != b.len() {
+        return Err(error::Unspecified);
+    }
+    out.copy_from_slice(b);
+    conditional_copy(condition, out, a)
+}
+
+/// Encodes `input` as lowercase hexadecimal.
+#[cfg(feature = "use_heap")]
+pub fn hex_encode(input: &[u8]) -> String {
+    let mut out = String::with_capacity(input.len() * 2);
+    for b in input {
+        out.push(char::from(hex_encode_digit(b >> 4)));
+        out.push(char::from(hex_encode_digit(b & 0xf)));
+    }
+    out
+}
+
+/// Decodes hexadecimal `input`, which may use either case.
+///
+/// Returns `Err(error::Unspecified)` if `input` has an odd length or contains
+/// a non-hexadecimal character. The position of an invalid character is not
+/// revealed.
+#[cfg(feature = "use_heap")]
+pub fn hex_decode(input: &str) -> Result<Vec<u8>, error::Unspecified> {
+    let input = input.as_bytes();
+    if input.len() % 2 != 0 {
+        return Err(error::Unspecified);
+    }
+    let mut valid = 0xff;
+    let mut out = Vec::with_capacity(input.len() / 2);
+    for pair in input.chunks(2) {
+        let (hi, hi_valid) = hex_decode_digit(pair[0]);
+        let (lo, lo_valid) = hex_decode_digit(pair[1]);
+        valid &= hi_valid & lo_valid;
+        out.push((hi << 4) | lo);
+    }
+    if valid != 0xff {
+        return Err(error::Unspecified);
+    }
+    Ok(out)
+}
+
+/// Encodes `input` as base64 with the standard alphabet, with padding.
+#[cfg(feature = "use_heap")]
+pub fn base64_encode(input: &[u8]) -> String {
+    crate::base64::encode(input)
+}
+
+/// Decodes base64 `input`, which must use the standard alphabet, with
+/// padding.
+///
+/// Only the canonical encoding is accepted. Whether the input is valid, and
+/// the amount of padding, are not secret.
+#[cfg(feature = "use_heap")]
+pub fn base64_decode(input: &str) -> Result<Vec<u8>, error::Unspecified> {
+    crate::base64::decode(input)
+}
+
+/// Encodes `input` as base64 with the URL-safe alphabet, without padding.
+#[cfg(feature = "use_heap")]
+pub fn base64_url_encode(input: &[u8]) -> String {
+    crate::base64::url_encode(input)
+}
+
+/// Decodes base64 `input`, which must use the URL-safe alphabet, without
+/// padding.
+///
+/// Only the canonical encoding is accepted. Whether the input is valid is
+/// not secret.
+#[cfg(feature = "use_heap")]
+pub fn base64_url_decode(input: &str) -> Result<Vec<u8>, error::Unspecified> {
+    crate::base64::url_decode(input)
+}
+
+// Returns 0xff if `a` is in the range [lo, hi] and 0 otherwise.
+#[cfg(feature = "use_heap")]
+pub(crate) fn range_mask(a: u8, lo: u8, hi: u8) -> u8 {
+    let a = u32::from(a);
+    let below = a.wrapping_sub(u32::from(lo)) >> 31;
+    let above = u32::from(hi).wrapping_sub(a) >> 31;
+    ((below | above) as u8).wrapping_sub(1)
+}
+
+// Returns 0xff if `condition` is true and 0 otherwise. The volatile read keeps
+// the optimizer from turning uses of the mask back into branches.
+fn mask_from_bool(condition: bool) -> u8 {
+    let mask = 0u8.wrapping_sub(u8::from(condition));
+    unsafe { core::ptr::read_volatile(&mask) }
+}
+
+#[cfg(feature = "use_heap")]
+fn hex_encode_digit(value: u8) -> u8 {
+    (range_mask(value, 0, 9) & value.wrapping_add(b'0'))
+        | (range_mask(value, 10, 15) & value.wrapping_add(b'a' - 10))
+}
+
+// Returns the digit's value and a mask that is 0xff if `digit` is valid and 0
+// otherwise.
+#[cfg(feature = "use_heap")]
+fn hex_decode_digit(digit: u8) -> (u8, u8) {
+    let number = range_mask(digit, b'0', b'9');
+    let lower = range_mask(digit, b'a', b'f');
+    let upper = range_mask(digit, b'A', b'F');
+    let value = (number & digit.wrapping_sub(b'0'))
+        | (lower & digit.wrapping_sub(b'a' - 10))
+        | (upper & digit.wrapping_sub(b'A' - 10));
+    (value, number | lower | upper)
+}
+
 extern "C" {
     fn GFp_memcmp(a: *const u8, b: *const u8, len: c::size_t) -> c::int;
 }
@@ -46,4 +220,83 @@ mod tests {
         }
         Result::from(unsafe { bssl_constant_time_test_main() })
     }
+
+    // Exercise both the vectorized loop and the byte-wise loop for the
+    // remainder, with a difference in every possible position.
+    #[test]
+    fn test_verify_slices_are_equal() {
+        use super::verify_slices_are_equal;
+        use std::vec::Vec;
+
+        for &len in &[0, 1, 15, 16, 17, 31, 32, 33, 100, 4096 + 7] {
+            let a = (0..len).map(|i| i as u8).collect::<Vec<u8>>();
+            let mut b = a.clone();
+            assert!(verify_slices_are_equal(&a, &b).is_ok());
+            for i in 0..len {
+                b[i] ^= 0x80;
+                assert!(verify_slices_are_equal(&a, &b).is_err());
+                b[i] ^= 0x80;
+            }
+            if len > 0 {
+                assert!(verify_slices_are_equal(&a, &b[..(len - 1)]).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        use super::compare;
+        use core::cmp::Ordering;
+
+        assert_eq!(compare(&[], &[]), Ok(Ordering::Equal));
+        assert_eq!(compare(&[1, 2, 3], &[1, 2, 3]), Ok(Ordering::Equal));
+        assert_eq!(compare(&[1, 2, 4], &[1, 2, 3]), Ok(Ordering::Greater));
+        assert_eq!(compare(&[1, 2, 3], &[1, 2, 4]), Ok(Ordering::Less));
+        // The most significant difference decides the result.
+        assert_eq!(compare(&[2, 0, 0], &[1, 0xff, 0xff]), Ok(Ordering::Greater));
+        assert_eq!(compare(&[0, 0xff, 0], &[1, 0, 0xff]), Ok(Ordering::Less));
+        assert_eq!(compare(&[0xff, 0], &[0x7f, 0]), Ok(Ordering::Greater));
+        assert!(compare(&[1], &[0, 1]).is_err());
+    }
+
+    #[test]
+    fn test_conditional_copy_and_select() {
+        use super::{conditional_copy, select};
+
+        let a = [1u8, 2, 3];
+        let b = [4u8, 5, 6];
+
+        let mut dst = a;
+        assert!(conditional_copy(false, &mut dst, &b).is_ok());
+        assert_eq!(dst, a);
+        assert!(conditional_copy(true, &mut dst, &b).is_ok());
+        assert_eq!(dst, b);
+        assert!(conditional_copy(true, &mut dst, &b[..2]).is_err());
+
+        let mut out = [0u8; 3];
+        assert!(select(true, &a, &b, &mut out).is_ok());
+        assert_eq!(out, a);
+        assert!(select(false, &a, &b, &mut out).is_ok());
+        assert_eq!(out, b);
+        assert!(select(true, &a, &b[..2], &mut out).is_err());
+        assert!(select(true, &a, &b, &mut out[..2]).is_err());
+    }
+
+    #[cfg(feature = "use_heap")]
+    #[test]
+    fn test_hex() {
+        use super::{hex_decode, hex_encode};
+
+        let all_bytes = (0..=255).collect::<std::vec::Vec<u8>>();
+        let encoded = hex_encode(&all_bytes);
+        assert_eq!(&encoded[..8], "00010203");
+        assert_eq!(&encoded[(encoded.len() - 8)..], "fcfdfeff");
+        assert_eq!(hex_decode(&encoded).unwrap(), all_bytes);
+        assert_eq!(hex_decode(&encoded.to_uppercase()).unwrap(), all_bytes);
+        assert_eq!(hex_decode("").unwrap(), &[]);
+
+        for bad in &["0", "0g", "g0", "/0", ":0", "@0", "G0", "`0", "00 "] {
+            assert!(hex_decode(bad).is_err(), "{}", bad);
+        }
+    }
 }
@@ -0,0 +1,472 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! CBOR Object Signing and Encryption (COSE) keys and single-signer
+//! signatures.
+//!
+//! See [RFC 8152]. `PublicKey::from_cose_key` parses a `COSE_Key` (Section
+//! 13), and `sign1`, `verify_sign1`, and `verify_sign1_detached` produce and
+//! verify `COSE_Sign1` messages (Section 4.2).
+//!
+//! As with `jose`, the verifier decides which algorithm is acceptable: the
+//! `alg` in the protected header must be the algorithm of the key. Messages
+//! with a `crit` header parameter are rejected since no extensions are
+//! supported. The unprotected header is ignored.
+//!
+//! [RFC 8152]: https://tools.ietf.org/html/rfc8152
+
+use crate::{
+    cbor::{self, Value},
+    error, rand, signature,
+};
+use std::vec::Vec;
+use untrusted;
+
+/// A COSE signature algorithm.
+pub struct Algorithm {
+    identifier: i32,
+    kty: i64,
+    crv: i64,
+    elem_len: usize,
+    verification: &'static dyn signature::VerificationAlgorithm,
+    scheme: signature::SignatureScheme,
+    id: AlgorithmID,
+}
+
+derive_debug_via_id!(Algorithm);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum AlgorithmID {
+    ES256,
+    ES384,
+    #[cfg(feature = "sig-ed25519")]
+    EDDSA,
+}
+
+impl PartialEq for Algorithm {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Algorithm {}
+
+impl Algorithm {
+    /// The COSE algorithm identifier, as registered with IANA.
+    #[inline]
+    pub fn identifier(&self) -> i32 {
+        self.identifier
+    }
+}
+
+/// ECDSA using P-256 and SHA-256.
+pub static ES256: Algorithm = Algorithm {
+    identifier: -7,
+    kty: KTY_EC2,
+    crv: CRV_P256,
+    elem_len: 32,
+    verification: &signature::ECDSA_P256_SHA256_FIXED,
+    scheme: signature::SignatureScheme::ECDSA_P256_SHA256_FIXED,
+    id: AlgorithmID::ES256,
+};
+
+/// ECDSA using P-384 and SHA-384.
+pub static ES384: Algorithm = Algorithm {
+    identifier: -35,
+    kty: KTY_EC2,
+    crv: CRV_P384,
+    elem_len: 48,
+    verification: &signature::ECDSA_P384_SHA384_FIXED,
+    scheme: signature::SignatureScheme::ECDSA_P384_SHA384_FIXED,
+    id: AlgorithmID::ES384,
+};
+
+/// EdDSA. Only Ed25519 keys are supported.
+#[cfg(feature = "sig-ed25519")]
+pub static EDDSA: Algorithm = Algorithm {
+    identifier: -8,
+    kty: KTY_OKP,
+    crv: CRV_ED25519,
+    elem_len: 32,
+    verification: &signature::ED25519,
+    scheme: signature::SignatureScheme::ED25519,
+    id: AlgorithmID::EDDSA,
+};
+
+static ALL_ALGORITHMS: &[&Algorithm] = &[
+    &ES256,
+    &ES384,
+    #[cfg(feature = "sig-ed25519")]
+    &EDDSA,
+];
+
+/// A public key parsed from a `COSE_Key`.
+#[derive(Clone, Debug)]
+pub struct PublicKey {
+    algorithm: &'static Algorithm,
+    key: Vec<u8>,
+    key_id: Option<Vec<u8>>,
+}
+
+impl PublicKey {
+    /// Parses a CBOR-encoded `COSE_Key`.
+    ///
+    /// The key must have an algorithm (`alg`) parameter, and its key type
+    /// and curve must match the algorithm. EC2 keys must have both
+    /// coordinates; compressed points aren't supported. Parameters other
+    /// than `kty`, `kid`, `alg`, and the key type's public parameters are
+    /// ignored.
+    pub fn from_cose_key(cose_key: &[u8]) -> Result<Self, error::Unspecified> {
+        let params =
+            untrusted::Input::from(cose_key).read_all(error::Unspecified, read_key_params)?;
+
+        let algorithm = match params.alg {
+            Some(Value::Int(alg)) => ALL_ALGORITHMS
+                .iter()
+                .find(|algorithm| i64::from(algorithm.identifier) == alg)
+                .ok_or(error::Unspecified)?,
+            _ => {
+                return Err(error::Unspecified);
+            }
+        };
+
+        let key_id = match params.kid {
+            None => None,
+            Some(Value::Bytes(kid)) => Some(kid.to_vec()),
+            Some(_) => {
+                return Err(error::Unspecified);
+            }
+        };
+
+        let x = match (params.kty, params.minus_1, params.minus_2) {
+            (Some(Value::Int(kty)), Some(Value::Int(crv)), Some(Value::Bytes(x)))
+                if kty == algorithm.kty
+                    && crv == algorithm.crv
+                    && x.len() == algorithm.elem_len =>
+            {
+                x
+            }
+            _ => {
+                return Err(error::Unspecified);
+            }
+        };
+
+        let key = if algorithm.kty == KTY_EC2 {
+            let y = match params.minus_3 {
+                Some(Value::Bytes(y)) if y.len() == algorithm.elem_len => y,
+                _ => {
+                    return Err(error::Unspecified);
+                }
+            };
+            let mut point = Vec::with_capacity(1 + x.len() + y.len());
+            point.push(0x04);
+            point.extend_from_slice(x);
+            point.extend_from_slice(y);
+            point
+        } else {
+            x.to_vec()
+        };
+
+        Ok(Self {
+            algorithm,
+            key,
+            key_id,
+        })
+    }
+
+    /// The algorithm of the key.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+
+    /// The key identifier (`kid`), if the `COSE_Key` has one.
+    #[inline]
+    pub fn key_id(&self) -> Option<&[u8]> {
+        self.key_id.as_ref().map(|kid| &kid[..])
+    }
+}
+
+impl AsRef<[u8]> for PublicKey {
+    /// The public key, in the format used by the algorithm's
+    /// `signature::VerificationAlgorithm`.
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.key
+    }
+}
+
+/// A key for signing.
+#[derive(Clone, Copy, Debug)]
+pub enum SigningKey<'a> {
+    /// An ECDSA key pair. It must have been constructed with the `_FIXED`
+    /// signing algorithm for the curve.
+    Ecdsa(&'a signature::EcdsaKeyPair),
+
+    /// An Ed25519 key pair.
+    #[cfg(feature = "sig-ed25519")]
+    Ed25519(&'a signature::Ed25519KeyPair),
+
+    /// Any other private key, e.g. one held in an HSM.
+    Signer(&'a dyn signature::Signer),
+}
+
+/// Signs `payload`, returning an untagged `COSE_Sign1` message that
+/// contains the payload.
+///
+/// The protected header contains only the `alg` parameter and the
+/// unprotected header is empty. `external_aad` is the externally supplied
+/// data that is signed but not included in the message; it is usually
+/// empty.
+pub fn sign1(
+    algorithm: &'static Algorithm,
+    key: SigningKey,
+    payload: &[u8],
+    external_aad: &[u8],
+    rng: &dyn rand::SecureRandom,
+) -> Result<Vec<u8>, error::Unspecified> {
+    let signer: &dyn signature::Signer = match key {
+        SigningKey::Ecdsa(key_pair) => key_pair,
+        #[cfg(feature = "sig-ed25519")]
+        SigningKey::Ed25519(key_pair) => key_pair,
+        SigningKey::Signer(signer) => signer,
+    };
+    if !signer.supports(algorithm.scheme) {
+        return Err(error::Unspecified);
+    }
+
+    let mut protected = Vec::new();
+    cbor::write_head(&mut protected, cbor::MAJOR_MAP, 1);
+    cbor::write_int(&mut protected, HEADER_ALG);
+    cbor::write_int(&mut protected, i64::from(algorithm.identifier));
+
+    let tbs = sig_structure(&protected, external_aad, payload);
+    let signature = signer.sign(algorithm.scheme, rng, &tbs)?;
+
+    let mut message = Vec::with_capacity(tbs.len() + signature.len());
+    cbor::write_head(&mut message, cbor::MAJOR_ARRAY, 4);
+    cbor::write_bytes(&mut message, &protected);
+    cbor::write_head(&mut message, cbor::MAJOR_MAP, 0);
+    cbor::write_bytes(&mut message, payload);
+    cbor::write_bytes(&mut message, &signature);
+    Ok(message)
+}
+
+/// Verifies the `COSE_Sign1` message `cose_sign1` and returns its payload.
+///
+/// The message may be tagged or untagged. Verification fails if the
+/// payload is detached; use `verify_sign1_detached` for such messages.
+pub fn verify_sign1<'a>(
+    key: &PublicKey,
+    cose_sign1: &'a [u8],
+    external_aad: &[u8],
+) -> Result<&'a [u8], error::Unspecified> {
+    let message = Sign1::parse(cose_sign1)?;
+    let payload = message.payload.ok_or(error::Unspecified)?;
+    message.verify(key, payload, external_aad)?;
+    Ok(payload)
+}
+
+/// Verifies the `COSE_Sign1` message `cose_sign1`, whose payload is
+/// detached, over `payload`.
+///
+/// Verification fails if the message contains a payload.
+pub fn verify_sign1_detached(
+    key: &PublicKey,
+    cose_sign1: &[u8],
+    payload: &[u8],
+    external_aad: &[u8],
+) -> Result<(), error::Unspecified> {
+    let message = Sign1::parse(cose_sign1)?;
+    if message.payload.is_some() {
+        return Err(error::Unspecified);
+    }
+    message.verify(key, payload, external_aad)
+}
+
+struct Sign1<'a> {
+    protected: &'a [u8],
+    payload: Option<&'a [u8]>,
+    signature: &'a [u8],
+}
+
+impl<'a> Sign1<'a> {
+    fn parse(cose_sign1: &'a [u8]) -> Result<Self, error::Unspecified> {
+        untrusted::Input::from(cose_sign1).read_all(error::Unspecified, |input| {
+            let (major, argument) = cbor::read_any_head(input)?;
+            let (major, argument) = if major == cbor::MAJOR_TAG {
+                if argument != TAG_COSE_SIGN1 {
+                    return Err(error::Unspecified);
+                }
+                cbor::read_any_head(input)?
+            } else {
+                (major, argument)
+            };
+            if major != cbor::MAJOR_ARRAY || argument != 4 {
+                return Err(error::Unspecified);
+            }
+
+            let protected = read_bytes(input)?;
+
+            let unprotected_len = cbor::read_head(input, cbor::MAJOR_MAP)?;
+            for _ in 0..unprotected_len {
+                let _ = cbor::read_value(input, 1)?;
+                let _ = cbor::read_value(input, 1)?;
+            }
+
+            let payload = match cbor::read_any_head(input)? {
+                (cbor::MAJOR_BYTES, len) => Some(cbor::read_content(input, len)?),
+                (cbor::MAJOR_SIMPLE, cbor::SIMPLE_NULL) => None,
+                _ => {
+                    return Err(error::Unspecified);
+                }
+            };
+
+            let signature = read_bytes(input)?;
+
+            Ok(Self {
+                protected,
+                payload,
+                signature,
+            })
+        })
+    }
+
+    fn verify(
+        &self,
+        key: &PublicKey,
+        payload: &[u8],
+        external_aad: &[u8],
+    ) -> Result<(), error::Unspecified> {
+        let alg = parse_protected_header(self.protected)?;
+        if alg != i64::from(key.algorithm.identifier) {
+            return Err(error::Unspecified);
+        }
+        let tbs = sig_structure(self.protected, external_aad, payload);
+        signature::UnparsedPublicKey::new(key.algorithm.verification, &key.key)
+            .verify(&tbs, self.signature)
+    }
+}
+
+/// Returns the `alg` of a serialized protected header, which must be a map
+/// with no `crit` parameter.
+fn parse_protected_header(protected: &[u8]) -> Result<i64, error::Unspecified> {
+    untrusted::Input::from(protected).read_all(error::Unspecified, |input| {
+        let mut alg = None;
+        let len = cbor::read_head(input, cbor::MAJOR_MAP)?;
+        for _ in 0..len {
+            let label = cbor::read_value(input, 0)?;
+            let value = cbor::read_value(input, 0)?;
+            match (label, value) {
+                (Value::Int(HEADER_ALG), Value::Int(value)) => {
+                    // Duplicate labels are not allowed.
+                    if alg.replace(value).is_some() {
+                        return Err(error::Unspecified);
+                    }
+                }
+                (Value::Int(HEADER_ALG), _) | (Value::Int(HEADER_CRIT), _) => {
+                    return Err(error::Unspecified);
+                }
+                (Value::Int(_), _) | (Value::Other, _) => {}
+                (Value::Bytes(_), _) => {
+                    return Err(error::Unspecified);
+                }
+            }
+        }
+        alg.ok_or(error::Unspecified)
+    })
+}
+
+/// The CBOR encoding of the `Sig_structure` for a `COSE_Sign1` message
+/// (RFC 8152 Section 4.4).
+fn sig_structure(protected: &[u8], external_aad: &[u8], payload: &[u8]) -> Vec<u8> {
+    const CONTEXT: &str = "Signature1";
+    let mut tbs = Vec::with_capacity(
+        32 + CONTEXT.len() + protected.len() + external_aad.len() + payload.len(),
+    );
+    cbor::write_head(&mut tbs, cbor::MAJOR_ARRAY, 4);
+    cbor::write_text(&mut tbs, CONTEXT);
+    cbor::write_bytes(&mut tbs, protected);
+    cbor::write_bytes(&mut tbs, external_aad);
+    cbor::write_bytes(&mut tbs, payload);
+    tbs
+}
+
+fn read_bytes<'a>(input: &mut untrusted::Reader<'a>) -> Result<&'a [u8], error::Unspecified> {
+    let len = cbor::read_head(input, cbor::MAJOR_BYTES)?;
+    cbor::read_content(input, len)
+}
+
+const TAG_COSE_SIGN1: u64 = 18;
+
+// Common header parameters (RFC 8152 Section 3.1).
+const HEADER_ALG: i64 = 1;
+const HEADER_CRIT: i64 = 2;
+
+// COSE key parameters (RFC 8152 Section 13). The meaning of the negative
+// labels depends on the key type.
+const KEY_KTY: i64 = 1;
+const KEY_KID: i64 = 2;
+const KEY_ALG: i64 = 3;
+
+#[cfg(feature = "sig-ed25519")]
+pub(crate) const KTY_OKP: i64 = 1;
+pub(crate) const KTY_EC2: i64 = 2;
+#[cfg(feature = "rsa")]
+pub(crate) const KTY_RSA: i64 = 3;
+
+pub(crate) const CRV_P256: i64 = 1;
+const CRV_P384: i64 = 2;
+#[cfg(feature = "sig-ed25519")]
+pub(crate) const CRV_ED25519: i64 = 6;
+
+#[derive(Default)]
+pub(crate) struct KeyParams<'a> {
+    pub(crate) kty: Option<Value<'a>>,
+    pub(crate) kid: Option<Value<'a>>,
+    pub(crate) alg: Option<Value<'a>>,
+    pub(crate) minus_1: Option<Value<'a>>,
+    pub(crate) minus_2: Option<Value<'a>>,
+    pub(crate) minus_3: Option<Value<'a>>,
+}
+
+pub(crate) fn read_key_params<'a>(
+    input: &mut untrusted::Reader<'a>,
+) -> Result<KeyParams<'a>, error::Unspecified> {
+    let mut params = KeyParams::default();
+    let len = cbor::read_head(input, cbor::MAJOR_MAP)?;
+    for _ in 0..len {
+        let label = cbor::read_value(input, 0)?;
+        let value = cbor::read_value(input, 0)?;
+        let param = match label {
+            Value::Int(KEY_KTY) => &mut params.kty,
+            Value::Int(KEY_KID) => &mut params.kid,
+            Value::Int(KEY_ALG) => &mut params.alg,
+            Value::Int(-1) => &mut params.minus_1,
+            Value::Int(-2) => &mut params.minus_2,
+            Value::Int(-3) => &mut params.minus_3,
+            Value::Int(_) | Value::Other => {
+                continue;
+            }
+            Value::Bytes(_) => {
+                return Err(error::Unspecified);
+            }
+        };
+        // Duplicate labels are not allowed.
+        if param.replace(value).is_some() {
+            return Err(error::Unspecified);
+        }
+    }
+    Ok(params)
+}
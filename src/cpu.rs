@@ -28,34 +28,47 @@ pub(crate) fn features() -> Features {
     // assumed to be present; see `arm::Feature`.
     #[cfg(not(target_os = "ios"))]
     {
-        static INIT: spin::Once<()> = spin::Once::new();
-        let () = INIT.call_once(|| {
-            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-            {
-                extern "C" {
-                    fn GFp_cpuid_setup();
-                }
-                unsafe {
-                    GFp_cpuid_setup();
-                }
-            }
+        // After the first call this is a single load, so it is cheap enough
+        // to be done on every call; the detection itself is kept out of line.
+        if INIT.r#try().is_none() {
+            init();
+        }
+    }
 
-            #[cfg(all(
-                any(target_os = "android", target_os = "linux"),
-                any(target_arch = "aarch64", target_arch = "arm")
-            ))]
-            {
-                arm::linux_setup();
-            }
+    Features(())
+}
 
-            #[cfg(all(target_os = "fuchsia", any(target_arch = "aarch64")))]
-            {
-                arm::fuchsia_setup();
+#[cfg(not(target_os = "ios"))]
+static INIT: spin::Once<()> = spin::Once::new();
+
+#[cfg(not(target_os = "ios"))]
+#[cold]
+#[inline(never)]
+fn init() {
+    let () = INIT.call_once(|| {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            extern "C" {
+                fn GFp_cpuid_setup();
             }
-        });
-    }
+            unsafe {
+                GFp_cpuid_setup();
+            }
+        }
 
-    Features(())
+        #[cfg(all(
+            any(target_os = "android", target_os = "linux"),
+            any(target_arch = "aarch64", target_arch = "arm")
+        ))]
+        {
+            arm::linux_setup();
+        }
+
+        #[cfg(all(target_os = "fuchsia", any(target_arch = "aarch64")))]
+        {
+            arm::fuchsia_setup();
+        }
+    });
 }
 
 pub(crate) mod arm {
@@ -103,6 +116,9 @@ pub(crate) mod arm {
             const HWCAP_PMULL: c::ulong = 1 << 1 + OFFSET;
             const HWCAP_SHA2: c::ulong = 1 << 3 + OFFSET;
 
+            #[cfg(target_arch = "aarch64")]
+            const HWCAP_SHA512: c::ulong = 1 << 21;
+
             if caps & HWCAP_AES == HWCAP_AES {
                 features |= AES.mask;
             }
@@ -110,7 +126,13 @@ pub(crate) mod arm {
                 features |= PMULL.mask;
             }
             if caps & HWCAP_SHA2 == HWCAP_SHA2 {
-                features |= 1 << 4;
+                features |= SHA256.mask;
+            }
+            #[cfg(target_arch = "aarch64")]
+            {
+                if caps & HWCAP_SHA512 == HWCAP_SHA512 {
+                    features |= SHA512.mask;
+                }
             }
 
             unsafe { GFp_armcap_P = features };
@@ -148,7 +170,7 @@ pub(crate) mod arm {
                 features |= PMULL.mask;
             }
             if caps & ZX_ARM64_FEATURE_ISA_SHA2 == ZX_ARM64_FEATURE_ISA_SHA2 {
-                features |= 1 << 4;
+                features |= SHA256.mask;
             }
 
             unsafe { GFp_armcap_P = features };
@@ -211,6 +233,21 @@ pub(crate) mod arm {
         ios: true,
     };
 
+    // Keep in sync with `ARMV8_SHA256`.
+    #[cfg(any(target_arch = "aarch64", target_arch = "arm"))]
+    pub(crate) const SHA256: Feature = Feature {
+        mask: 1 << 4,
+        ios: true,
+    };
+
+    // Keep in sync with `ARMV8_SHA512`. Not all 64-bit iOS devices have the
+    // SHA-512 instructions.
+    #[cfg(target_arch = "aarch64")]
+    pub(crate) const SHA512: Feature = Feature {
+        mask: 1 << 6,
+        ios: false,
+    };
+
     #[cfg(all(
         any(target_os = "android", target_os = "linux", target_os = "fuchsia"),
         any(target_arch = "arm", target_arch = "aarch64")
@@ -264,6 +301,7 @@ pub(crate) mod intel {
     };
 
     #[cfg(target_arch = "x86_64")]
+    #[cfg_attr(feature = "intrinsics", allow(dead_code))]
     pub(crate) const MOVBE: Feature = Feature {
         word: 1,
         mask: 1 << 22,
@@ -275,11 +313,36 @@ pub(crate) mod intel {
     };
 
     #[cfg(target_arch = "x86_64")]
+    #[cfg_attr(feature = "intrinsics", allow(dead_code))]
     pub(crate) const AVX: Feature = Feature {
         word: 1,
         mask: 1 << 28,
     };
 
+    #[cfg(target_arch = "x86_64")]
+    pub(crate) const AVX2: Feature = Feature {
+        word: 2,
+        mask: 1 << 5,
+    };
+
+    #[cfg(target_arch = "x86_64")]
+    pub(crate) const SHA: Feature = Feature {
+        word: 2,
+        mask: 1 << 29,
+    };
+
+    #[cfg(all(target_arch = "x86_64", feature = "aead-aes-gcm-siv"))]
+    pub(crate) const VAES: Feature = Feature {
+        word: 3,
+        mask: 1 << 9,
+    };
+
+    #[cfg(all(target_arch = "x86_64", feature = "aead-aes-gcm-siv"))]
+    pub(crate) const VPCLMULQDQ: Feature = Feature {
+        word: 3,
+        mask: 1 << 10,
+    };
+
     #[cfg(all(target_arch = "x86_64", test))]
     mod x86_64_tests {
         use super::*;
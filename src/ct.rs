@@ -0,0 +1,295 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Certificate Transparency signed certificate timestamps (SCTs).
+//!
+//! See [RFC 6962]. Only v1 SCTs are supported. The only signature algorithms
+//! allowed by RFC 6962 are ECDSA P-256 with SHA-256 and RSA PKCS#1 v1.5 with
+//! SHA-256, and only those are supported.
+//!
+//! This module doesn't parse certificates; the caller is responsible for
+//! extracting the SCT list from the certificate, OCSP response, or TLS
+//! extension, and for constructing the `Entry` that the SCT is over.
+//!
+//! [RFC 6962]: https://tools.ietf.org/html/rfc6962
+
+use crate::{error, signature};
+use std::vec::Vec;
+use untrusted;
+
+/// A signature algorithm that may be used by a Certificate Transparency log.
+pub struct SignatureAlgorithm {
+    hash: u8,
+    signature: u8,
+    verification_alg: &'static dyn signature::VerificationAlgorithm,
+    id: AlgorithmID,
+}
+
+derive_debug_via_id!(SignatureAlgorithm);
+
+#[derive(Debug, Eq, PartialEq)]
+enum AlgorithmID {
+    ECDSA_P256_SHA256,
+    RSA_PKCS1_SHA256,
+}
+
+impl PartialEq for SignatureAlgorithm {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for SignatureAlgorithm {}
+
+/// ECDSA using the P-256 curve and SHA-256. The log's public key is an
+/// uncompressed point, as for `signature::ECDSA_P256_SHA256_ASN1`.
+pub static ECDSA_P256_SHA256: SignatureAlgorithm = SignatureAlgorithm {
+    hash: HASH_SHA256,
+    signature: SIGNATURE_ECDSA,
+    verification_alg: &signature::ECDSA_P256_SHA256_ASN1,
+    id: AlgorithmID::ECDSA_P256_SHA256,
+};
+
+/// RSA PKCS#1 1.5 signatures using SHA-256 with 2048-8192 bit keys. The log's
+/// public key is an ASN.1 `RSAPublicKey`, as for
+/// `signature::RSA_PKCS1_2048_8192_SHA256`.
+pub static RSA_PKCS1_SHA256: SignatureAlgorithm = SignatureAlgorithm {
+    hash: HASH_SHA256,
+    signature: SIGNATURE_RSA,
+    verification_alg: &signature::RSA_PKCS1_2048_8192_SHA256,
+    id: AlgorithmID::RSA_PKCS1_SHA256,
+};
+
+const HASH_SHA256: u8 = 4;
+const SIGNATURE_RSA: u8 = 1;
+const SIGNATURE_ECDSA: u8 = 3;
+
+static ALL_ALGORITHMS: &[&SignatureAlgorithm] = &[&ECDSA_P256_SHA256, &RSA_PKCS1_SHA256];
+
+/// A log trusted by the caller.
+#[derive(Clone, Copy, Debug)]
+pub struct Log<'a> {
+    /// The log ID; i.e. the SHA-256 digest of the log's DER-encoded
+    /// `SubjectPublicKeyInfo`.
+    pub id: &'a [u8; LOG_ID_LEN],
+
+    /// The log's public key, in the format required by `algorithm`.
+    pub public_key: &'a [u8],
+
+    /// The algorithm the log signs with.
+    pub algorithm: &'static SignatureAlgorithm,
+}
+
+/// The length of a log ID.
+pub const LOG_ID_LEN: usize = 32;
+
+/// The log entry that an SCT was issued for.
+#[derive(Clone, Copy, Debug)]
+pub enum Entry<'a> {
+    /// An X.509 certificate, DER-encoded. This is used for SCTs delivered in
+    /// the TLS extension or in OCSP responses.
+    X509(&'a [u8]),
+
+    /// A precertificate. This is used for SCTs embedded in certificates.
+    Precert {
+        /// The SHA-256 digest of the issuer's DER-encoded
+        /// `SubjectPublicKeyInfo`.
+        issuer_key_hash: &'a [u8; 32],
+
+        /// The DER-encoded `TBSCertificate` of the certificate, with the SCT
+        /// list extension removed.
+        tbs_certificate: &'a [u8],
+    },
+}
+
+/// A parsed v1 signed certificate timestamp.
+#[derive(Clone, Copy, Debug)]
+pub struct Sct<'a> {
+    log_id: &'a [u8],
+    timestamp: u64,
+    extensions: &'a [u8],
+    algorithm: &'static SignatureAlgorithm,
+    signature: &'a [u8],
+}
+
+impl<'a> Sct<'a> {
+    /// Parses a serialized `SignedCertificateTimestamp`.
+    ///
+    /// Fails if the SCT isn't a v1 SCT or if it uses an unsupported signature
+    /// algorithm.
+    pub fn parse(input: &'a [u8]) -> Result<Self, error::Unspecified> {
+        untrusted::Input::from(input).read_all(error::Unspecified, |input| {
+            let version = input.read_byte()?;
+            if version != VERSION_V1 {
+                return Err(error::Unspecified);
+            }
+            let log_id = input.read_bytes(LOG_ID_LEN)?;
+            let timestamp = read_u64(input)?;
+            let extensions = read_u16_prefixed(input)?;
+            let hash = input.read_byte()?;
+            let signature = input.read_byte()?;
+            let algorithm = ALL_ALGORITHMS
+                .iter()
+                .find(|alg| alg.hash == hash && alg.signature == signature)
+                .ok_or(error::Unspecified)?;
+            let signature = read_u16_prefixed(input)?;
+            Ok(Self {
+                log_id: log_id.as_slice_less_safe(),
+                timestamp,
+                extensions: extensions.as_slice_less_safe(),
+                algorithm,
+                signature: signature.as_slice_less_safe(),
+            })
+        })
+    }
+
+    /// The ID of the log that issued the SCT.
+    #[inline]
+    pub fn log_id(&self) -> &'a [u8] {
+        self.log_id
+    }
+
+    /// The time the SCT was issued, in milliseconds since the Unix epoch.
+    #[inline]
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// The algorithm the SCT was signed with.
+    #[inline]
+    pub fn algorithm(&self) -> &'static SignatureAlgorithm {
+        self.algorithm
+    }
+
+    /// Verifies that the SCT was issued for `entry` by one of `logs` no later
+    /// than `now`, which is the current time in milliseconds since the Unix
+    /// epoch. On success the log that issued the SCT is returned.
+    pub fn verify<'l>(
+        &self,
+        entry: &Entry,
+        logs: &[Log<'l>],
+        now: u64,
+    ) -> Result<Log<'l>, error::Unspecified> {
+        let log = logs
+            .iter()
+            .find(|log| &log.id[..] == self.log_id)
+            .ok_or(error::Unspecified)?;
+        if log.algorithm != self.algorithm {
+            return Err(error::Unspecified);
+        }
+        if self.timestamp > now {
+            return Err(error::Unspecified);
+        }
+
+        let signed_data = self.signed_data(entry)?;
+        signature::UnparsedPublicKey::new(log.algorithm.verification_alg, log.public_key)
+            .verify(&signed_data, self.signature)?;
+
+        Ok(*log)
+    }
+
+    // The `digitally-signed` struct of RFC 6962 Section 3.2.
+    fn signed_data(&self, entry: &Entry) -> Result<Vec<u8>, error::Unspecified> {
+        let mut out = Vec::new();
+        out.push(VERSION_V1);
+        out.push(SIGNATURE_TYPE_CERTIFICATE_TIMESTAMP);
+        out.extend_from_slice(&self.timestamp.to_be_bytes());
+        match entry {
+            Entry::X509(cert) => {
+                out.extend_from_slice(&ENTRY_TYPE_X509.to_be_bytes());
+                write_u24_prefixed(&mut out, cert)?;
+            }
+            Entry::Precert {
+                issuer_key_hash,
+                tbs_certificate,
+            } => {
+                out.extend_from_slice(&ENTRY_TYPE_PRECERT.to_be_bytes());
+                out.extend_from_slice(&issuer_key_hash[..]);
+                write_u24_prefixed(&mut out, tbs_certificate)?;
+            }
+        }
+        write_u16_prefixed(&mut out, self.extensions)?;
+        Ok(out)
+    }
+}
+
+/// Parses a `SignedCertificateTimestampList`, as found in the X.509 extension,
+/// the OCSP extension, and the TLS extension.
+///
+/// SCTs that use an unsupported version or algorithm are skipped, as
+/// required by RFC 6962 Section 3.3. A malformed list is an error.
+pub fn parse_sct_list(input: &[u8]) -> Result<Vec<Sct<'_>>, error::Unspecified> {
+    untrusted::Input::from(input).read_all(error::Unspecified, |input| {
+        let list = read_u16_prefixed(input)?;
+        list.read_all(error::Unspecified, |list| {
+            let mut scts = Vec::new();
+            loop {
+                let sct = read_u16_prefixed(list)?;
+                if sct.is_empty() {
+                    return Err(error::Unspecified);
+                }
+                if let Ok(sct) = Sct::parse(sct.as_slice_less_safe()) {
+                    scts.push(sct);
+                }
+                if list.at_end() {
+                    break;
+                }
+            }
+            Ok(scts)
+        })
+    })
+}
+
+const VERSION_V1: u8 = 0;
+const SIGNATURE_TYPE_CERTIFICATE_TIMESTAMP: u8 = 0;
+const ENTRY_TYPE_X509: u16 = 0;
+const ENTRY_TYPE_PRECERT: u16 = 1;
+
+fn read_u64(input: &mut untrusted::Reader) -> Result<u64, error::Unspecified> {
+    let mut value = 0u64;
+    for _ in 0..8 {
+        value = (value << 8) | u64::from(input.read_byte()?);
+    }
+    Ok(value)
+}
+
+fn read_u16_prefixed<'a>(
+    input: &mut untrusted::Reader<'a>,
+) -> Result<untrusted::Input<'a>, error::Unspecified> {
+    let hi = usize::from(input.read_byte()?);
+    let lo = usize::from(input.read_byte()?);
+    let len = (hi << 8) | lo;
+    Ok(input.read_bytes(len)?)
+}
+
+fn write_u16_prefixed(out: &mut Vec<u8>, value: &[u8]) -> Result<(), error::Unspecified> {
+    if value.len() > 0xffff {
+        return Err(error::Unspecified);
+    }
+    out.push((value.len() >> 8) as u8);
+    out.push(value.len() as u8);
+    out.extend_from_slice(value);
+    Ok(())
+}
+
+fn write_u24_prefixed(out: &mut Vec<u8>, value: &[u8]) -> Result<(), error::Unspecified> {
+    if value.is_empty() || value.len() > 0xff_ffff {
+        return Err(error::Unspecified);
+    }
+    out.push((value.len() >> 16) as u8);
+    out.push((value.len() >> 8) as u8);
+    out.push(value.len() as u8);
+    out.extend_from_slice(value);
+    Ok(())
+}
@@ -12,7 +12,8 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-//! SHA-2 and the legacy SHA-1 digest algorithm.
+//! SHA-2, SHA-3, BLAKE2, Ascon-Hash, and the legacy SHA-1 digest algorithm,
+//! and the SHAKE extendable-output functions.
 //!
 //! If all the data is available in a single contiguous slice then the `digest`
 //! function should be used. Otherwise, the digest can be calculated in
@@ -24,10 +25,19 @@
 // The goal for this implementation is to drive the overhead as close to zero
 // as possible.
 
-use crate::{c, cpu, debug, endian::*, polyfill};
+use crate::{c, constant_time, cpu, debug, endian::*, error, polyfill, zeroize::{Zeroable, Zeroize}};
 use core::num::Wrapping;
 
+pub(crate) mod ascon;
+#[cfg_attr(not(feature = "use_heap"), allow(dead_code))]
+pub(crate) mod blake2b;
+mod blake2s;
+pub mod multi;
 mod sha1;
+mod sha2;
+mod sha3;
+
+pub use self::sha3::{Shake128, Shake256};
 
 /// A context for multi-step (Init-Update-Finish) digest calculations.
 ///
@@ -46,6 +56,12 @@ mod sha1;
 ///
 /// assert_eq!(&one_shot.as_ref(), &multi_part.as_ref());
 /// ```
+///
+/// Cloning a context is cheap; it copies the fixed-size state and doesn't
+/// allocate. A context that has been fed a common prefix, e.g. a
+/// domain-separation label, can be cloned to compute many digests of inputs
+/// that start with that prefix without hashing it again, as the `merkle`
+/// module does.
 #[derive(Clone)]
 pub struct Context {
     state: State,
@@ -82,7 +98,16 @@ impl Context {
     /// zero or more times until `finish` is called. It must not be called
     /// after `finish` has been called.
     pub fn update(&mut self, data: &[u8]) {
-        if data.len() < self.algorithm.block_len - self.num_pending {
+        // BLAKE2 flags the final block, so a full block can only be
+        // processed once we know more data follows it.
+        let holds_last_block = match self.algorithm.padding {
+            Padding::Blake2 { .. } => true,
+            _ => false,
+        };
+
+        if data.len() < self.algorithm.block_len - self.num_pending
+            || (holds_last_block && data.len() == self.algorithm.block_len - self.num_pending)
+        {
             self.pending[self.num_pending..(self.num_pending + data.len())].copy_from_slice(data);
             self.num_pending += data.len();
             return;
@@ -103,8 +128,12 @@ impl Context {
             self.num_pending = 0;
         }
 
-        let num_blocks = remaining.len() / self.algorithm.block_len;
-        let num_to_save_for_later = remaining.len() % self.algorithm.block_len;
+        let mut num_blocks = remaining.len() / self.algorithm.block_len;
+        let mut num_to_save_for_later = remaining.len() % self.algorithm.block_len;
+        if holds_last_block && num_blocks > 0 && num_to_save_for_later == 0 {
+            num_blocks -= 1;
+            num_to_save_for_later = self.algorithm.block_len;
+        }
         if num_blocks > 0 {
             unsafe {
                 (self.algorithm.block_data_order)(&mut self.state, remaining.as_ptr(), num_blocks);
@@ -128,6 +157,31 @@ impl Context {
         // We know |num_pending < self.algorithm.block_len|, because we would
         // have processed the block otherwise.
 
+        match self.algorithm.padding {
+            Padding::MerkleDamgard => (),
+            Padding::Keccak => {
+                let block = &mut self.pending[..self.algorithm.block_len];
+                sha3::pad(block, self.num_pending);
+                unsafe {
+                    (self.algorithm.block_data_order)(&mut self.state, block.as_ptr(), 1);
+                }
+                return self.output();
+            }
+            Padding::Blake2 { finish, .. } => {
+                let block = &mut self.pending[..self.algorithm.block_len];
+                finish(&mut self.state, block, self.num_pending);
+                return self.output();
+            }
+            Padding::Ascon => {
+                let block = &mut self.pending[..self.algorithm.block_len];
+                ascon::pad(block, self.num_pending);
+                unsafe {
+                    (self.algorithm.block_data_order)(&mut self.state, block.as_ptr(), 1);
+                }
+                return self.output();
+            }
+        }
+
         let mut padding_pos = self.num_pending;
         self.pending[padding_pos] = 0x80;
         padding_pos += 1;
@@ -163,12 +217,154 @@ impl Context {
             (self.algorithm.block_data_order)(&mut self.state, self.pending.as_ptr(), 1);
         }
 
+        self.output()
+    }
+
+    fn output(&self) -> Digest {
         Digest {
             algorithm: self.algorithm,
             value: (self.algorithm.format_output)(self.state),
         }
     }
 
+    /// Constructs a context for the keyed mode of a BLAKE2 algorithm.
+    ///
+    /// Panics unless `algorithm` is a BLAKE2 algorithm and
+    /// `1 <= key.len() <= algorithm.output_len`.
+    pub(crate) fn with_blake2_key(algorithm: &'static Algorithm, key: &[u8]) -> Self {
+        let set_key_len = match algorithm.padding {
+            Padding::Blake2 { set_key_len, .. } => set_key_len,
+            _ => panic!("{:?} has no keyed mode", algorithm),
+        };
+        assert!(!key.is_empty() && key.len() <= algorithm.output_len);
+
+        // The key, padded with zeros to a full block, is the first block of
+        // the input.
+        let mut ctx = Self::new(algorithm);
+        set_key_len(&mut ctx.state, key.len());
+        ctx.pending[..key.len()].copy_from_slice(key);
+        ctx.num_pending = algorithm.block_len;
+        ctx
+    }
+
+    /// Serializes the state of the context so that the calculation can be
+    /// resumed later, e.g. after a restart, with `resume_from_state()`.
+    ///
+    /// Only the SHA-256 and SHA-512 families (`SHA256`, `SHA384`, `SHA512`,
+    /// and `SHA512_256`) are supported. The serialized state reveals
+    /// everything the context has been updated with since its last complete
+    /// block, and is as sensitive as the input.
+    pub fn serialize_state(&self) -> Result<SerializedState, error::Unspecified> {
+        let algorithm_byte = serialized_state_algorithm(self.algorithm)?;
+
+        let mut serialized = SerializedState([0; SERIALIZED_STATE_LEN]);
+        {
+            let (header, rest) = serialized.0.split_at_mut(4);
+            header[0] = SERIALIZED_STATE_VERSION;
+            header[1] = algorithm_byte;
+            header[2] = self.num_pending as u8;
+
+            let (completed_data_blocks, rest) = rest.split_at_mut(8);
+            completed_data_blocks.copy_from_slice(&self.completed_data_blocks.to_be_bytes());
+
+            let (chaining_value, rest) = rest.split_at_mut(MAX_CHAINING_LEN);
+            if self.algorithm.block_len == SHA512_BLOCK_LEN {
+                let words = unsafe { &self.state.as64 };
+                for (out, word) in chaining_value.chunks_mut(8).zip(words.iter()) {
+                    out.copy_from_slice(&word.0.to_be_bytes());
+                }
+            } else {
+                let words = unsafe { &self.state.as32 };
+                for (out, word) in chaining_value.chunks_mut(4).zip(words.iter()) {
+                    out.copy_from_slice(&word.0.to_be_bytes());
+                }
+            }
+
+            let (pending, _) = rest.split_at_mut(SHA512_BLOCK_LEN);
+            pending[..self.num_pending].copy_from_slice(&self.pending[..self.num_pending]);
+        }
+        let (body, check) = serialized.0.split_at_mut(SERIALIZED_STATE_CHECK_START);
+        check.copy_from_slice(&serialized_state_check(body));
+
+        Ok(serialized)
+    }
+
+    /// Resumes a calculation from a state serialized by `serialize_state()`.
+    ///
+    /// Fails if `serialized` isn't a serialized state of an `algorithm`
+    /// context. The serialized state ends with a check value that catches
+    /// corruption, such as truncation or flipped bits. The check value isn't
+    /// keyed, so it doesn't stop someone who can write the serialized state
+    /// from forging a valid one; store it authenticated, e.g. with
+    /// `ring::hmac` or `ring::aead`, if that matters.
+    pub fn resume_from_state(
+        algorithm: &'static Algorithm,
+        serialized: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        if serialized.len() != SERIALIZED_STATE_LEN {
+            return Err(error::Unspecified);
+        }
+        let (body, check) = serialized.split_at(SERIALIZED_STATE_CHECK_START);
+        constant_time::verify_slices_are_equal(&serialized_state_check(body), check)?;
+
+        let (header, rest) = body.split_at(4);
+        if header[0] != SERIALIZED_STATE_VERSION
+            || header[1] != serialized_state_algorithm(algorithm)?
+            || header[3] != 0
+        {
+            return Err(error::Unspecified);
+        }
+        let num_pending = usize::from(header[2]);
+        if num_pending >= algorithm.block_len {
+            return Err(error::Unspecified);
+        }
+
+        let (completed_data_blocks, rest) = rest.split_at(8);
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(completed_data_blocks);
+        let completed_data_blocks = u64::from_be_bytes(bytes);
+
+        // Reject states that `finish()` would panic on.
+        let _ = completed_data_blocks
+            .checked_mul(polyfill::u64_from_usize(algorithm.block_len))
+            .and_then(|len| len.checked_add(polyfill::u64_from_usize(num_pending)))
+            .and_then(|len| len.checked_mul(8))
+            .ok_or(error::Unspecified)?;
+
+        let mut ctx = Self::new(algorithm);
+        ctx.completed_data_blocks = completed_data_blocks;
+
+        let (chaining_value, rest) = rest.split_at(MAX_CHAINING_LEN);
+        if algorithm.block_len == SHA512_BLOCK_LEN {
+            let words = unsafe { &mut ctx.state.as64 };
+            for (word, bytes) in words.iter_mut().zip(chaining_value.chunks(8)) {
+                let mut be = [0; 8];
+                be.copy_from_slice(bytes);
+                *word = Wrapping(u64::from_be_bytes(be));
+            }
+        } else {
+            // SHA-256's chaining value only fills the first half.
+            if chaining_value[SHA256_OUTPUT_LEN..].iter().any(|&b| b != 0) {
+                return Err(error::Unspecified);
+            }
+            let words = unsafe { &mut ctx.state.as32 };
+            for (word, bytes) in words.iter_mut().zip(chaining_value.chunks(4)) {
+                let mut be = [0; 4];
+                be.copy_from_slice(bytes);
+                *word = Wrapping(u32::from_be_bytes(be));
+            }
+        }
+
+        let pending = rest;
+        if pending[num_pending..].iter().any(|&b| b != 0) {
+            return Err(error::Unspecified);
+        }
+        ctx.pending[..num_pending].copy_from_slice(&pending[..num_pending]);
+        ctx.num_pending = num_pending;
+
+        Ok(ctx)
+    }
+
     /// The algorithm that this context is using.
     #[inline(always)]
     pub fn algorithm(&self) -> &'static Algorithm {
@@ -176,6 +372,68 @@ impl Context {
     }
 }
 
+impl Drop for Context {
+    fn drop(&mut self) {
+        self.state.zeroize();
+        self.pending.zeroize();
+    }
+}
+
+/// A serialized `Context` state, from `Context::serialize_state()`.
+///
+/// The encoding starts with a version number. It is zeroed when dropped.
+pub struct SerializedState([u8; SERIALIZED_STATE_LEN]);
+
+impl AsRef<[u8]> for SerializedState {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.0[..]
+    }
+}
+
+impl Drop for SerializedState {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl core::fmt::Debug for SerializedState {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("SerializedState").finish()
+    }
+}
+
+/// The length of a `SerializedState`.
+pub const SERIALIZED_STATE_LEN: usize = SERIALIZED_STATE_CHECK_START + SERIALIZED_STATE_CHECK_LEN;
+
+// The version, the algorithm, the number of pending bytes, and a zero byte;
+// the number of completed blocks; the chaining value, padded with zeros to
+// `MAX_CHAINING_LEN`; the pending input, padded with zeros to the SHA-512
+// block length; and then the check value.
+const SERIALIZED_STATE_CHECK_START: usize = 4 + 8 + MAX_CHAINING_LEN + SHA512_BLOCK_LEN;
+const SERIALIZED_STATE_CHECK_LEN: usize = 16;
+const SERIALIZED_STATE_VERSION: u8 = 1;
+
+fn serialized_state_algorithm(algorithm: &Algorithm) -> Result<u8, error::Unspecified> {
+    match algorithm.id {
+        AlgorithmID::SHA256 => Ok(1),
+        AlgorithmID::SHA384 => Ok(2),
+        AlgorithmID::SHA512 => Ok(3),
+        AlgorithmID::SHA512_256 => Ok(4),
+        _ => Err(error::Unspecified),
+    }
+}
+
+fn serialized_state_check(body: &[u8]) -> [u8; SERIALIZED_STATE_CHECK_LEN] {
+    let mut ctx = Context::new(&SHA256);
+    ctx.update(b"ring digest state\0");
+    ctx.update(body);
+    let digest = ctx.finish();
+    let mut check = [0; SERIALIZED_STATE_CHECK_LEN];
+    check.copy_from_slice(&digest.as_ref()[..SERIALIZED_STATE_CHECK_LEN]);
+    check
+}
+
 /// Returns the digest of `data` using the given digest algorithm.
 ///
 /// # Examples:
@@ -197,6 +455,19 @@ pub fn digest(algorithm: &'static Algorithm, data: &[u8]) -> Digest {
     ctx.finish()
 }
 
+/// Returns the digest of the concatenation of `parts` using the given digest
+/// algorithm.
+///
+/// This is equivalent to `digest()` of the concatenated input, without
+/// needing to concatenate it first.
+pub fn digest_all(algorithm: &'static Algorithm, parts: &[&[u8]]) -> Digest {
+    let mut ctx = Context::new(algorithm);
+    for part in parts {
+        ctx.update(part);
+    }
+    ctx.finish()
+}
+
 /// A calculated digest value.
 ///
 /// Use `as_ref` to get the value as a `&[u8]`.
@@ -247,6 +518,8 @@ pub struct Algorithm {
     /// The length of the length in the padding.
     len_len: usize,
 
+    padding: Padding,
+
     block_data_order: unsafe extern "C" fn(state: &mut State, data: *const u8, num: c::size_t),
     format_output: fn(input: State) -> Output,
 
@@ -262,6 +535,87 @@ enum AlgorithmID {
     SHA384,
     SHA512,
     SHA512_256,
+    SHA3_256,
+    SHA3_384,
+    SHA3_512,
+    BLAKE2B_512,
+    BLAKE2S_256,
+    ASCON_HASH,
+}
+
+// How the final block is padded.
+#[derive(Clone, Copy)]
+enum Padding {
+    // The padding of [FIPS 180-4] Section 5.1, which ends with the length of
+    // the input.
+    MerkleDamgard,
+
+    // The pad10*1 padding of [FIPS 202], preceded by the SHA-3 domain
+    // separation bits.
+    Keccak,
+
+    // BLAKE2 pads the final block with zeros, and marks it as final, in
+    // `finish`.
+    Blake2 {
+        finish: fn(state: &mut State, block: &mut [u8], num_pending: usize),
+        set_key_len: fn(state: &mut State, key_len: usize),
+    },
+
+    // Ascon-Hash pads the final block with a one bit and then zeros.
+    Ascon,
+}
+
+impl AlgorithmID {
+    // Must match the `Debug` output.
+    fn name(&self) -> &'static str {
+        match self {
+            AlgorithmID::SHA1 => "SHA1",
+            AlgorithmID::SHA256 => "SHA256",
+            AlgorithmID::SHA384 => "SHA384",
+            AlgorithmID::SHA512 => "SHA512",
+            AlgorithmID::SHA512_256 => "SHA512_256",
+            AlgorithmID::SHA3_256 => "SHA3_256",
+            AlgorithmID::SHA3_384 => "SHA3_384",
+            AlgorithmID::SHA3_512 => "SHA3_512",
+            AlgorithmID::BLAKE2B_512 => "BLAKE2B_512",
+            AlgorithmID::BLAKE2S_256 => "BLAKE2S_256",
+            AlgorithmID::ASCON_HASH => "ASCON_HASH",
+        }
+    }
+}
+
+impl Algorithm {
+    /// All the digest algorithms in this module.
+    pub fn all() -> &'static [&'static Self] {
+        &ALL_ALGORITHMS
+    }
+
+    /// The hash algorithm of the TLS 1.3 cipher suite `id`, as listed in
+    /// [RFC 8446 Appendix B.4], e.g. `SHA384` for 0x1302
+    /// (`TLS_AES_256_GCM_SHA384`).
+    ///
+    /// The cipher suite's AEAD algorithm is given by
+    /// `aead::Algorithm::from_tls_id()`. Returns `None` for cipher suites
+    /// that aren't supported.
+    ///
+    /// [RFC 8446 Appendix B.4]: https://tools.ietf.org/html/rfc8446#appendix-B.4
+    pub fn from_tls_id(id: u16) -> Option<&'static Self> {
+        match id {
+            0x1301 | 0x1303 => Some(&SHA256),
+            0x1302 => Some(&SHA384),
+            _ => None,
+        }
+    }
+
+    /// The digest algorithm named `name`, e.g. `SHA256` for `"SHA256"`. The
+    /// names are those of the statics in this module, which are also what the
+    /// `Debug` implementation prints.
+    pub fn from_name(name: &str) -> Option<&'static Self> {
+        Self::all()
+            .iter()
+            .find(|algorithm| algorithm.id.name() == name)
+            .cloned()
+    }
 }
 
 impl PartialEq for Algorithm {
@@ -274,6 +628,20 @@ impl Eq for Algorithm {}
 
 derive_debug_via_id!(Algorithm);
 
+static ALL_ALGORITHMS: [&Algorithm; 11] = [
+    &SHA1,
+    &SHA256,
+    &SHA384,
+    &SHA512,
+    &SHA512_256,
+    &SHA3_256,
+    &SHA3_384,
+    &SHA3_512,
+    &BLAKE2B_512,
+    &BLAKE2S_256,
+    &ASCON_HASH,
+];
+
 /// SHA-1 as specified in [FIPS 180-4]. Deprecated.
 ///
 /// [FIPS 180-4]: http://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf
@@ -282,6 +650,7 @@ pub static SHA1: Algorithm = Algorithm {
     chaining_len: sha1::CHAINING_LEN,
     block_len: sha1::BLOCK_LEN,
     len_len: 64 / 8,
+    padding: Padding::MerkleDamgard,
     block_data_order: sha1::block_data_order,
     format_output: sha256_format_output,
     initial_state: State {
@@ -307,7 +676,8 @@ pub static SHA256: Algorithm = Algorithm {
     chaining_len: SHA256_OUTPUT_LEN,
     block_len: 512 / 8,
     len_len: 64 / 8,
-    block_data_order: GFp_sha256_block_data_order,
+    padding: Padding::MerkleDamgard,
+    block_data_order: sha2::sha256_block_data_order,
     format_output: sha256_format_output,
     initial_state: State {
         as32: [
@@ -332,7 +702,8 @@ pub static SHA384: Algorithm = Algorithm {
     chaining_len: SHA512_OUTPUT_LEN,
     block_len: SHA512_BLOCK_LEN,
     len_len: SHA512_LEN_LEN,
-    block_data_order: GFp_sha512_block_data_order,
+    padding: Padding::MerkleDamgard,
+    block_data_order: sha2::sha512_block_data_order,
     format_output: sha512_format_output,
     initial_state: State {
         as64: [
@@ -357,7 +728,8 @@ pub static SHA512: Algorithm = Algorithm {
     chaining_len: SHA512_OUTPUT_LEN,
     block_len: SHA512_BLOCK_LEN,
     len_len: SHA512_LEN_LEN,
-    block_data_order: GFp_sha512_block_data_order,
+    padding: Padding::MerkleDamgard,
+    block_data_order: sha2::sha512_block_data_order,
     format_output: sha512_format_output,
     initial_state: State {
         as64: [
@@ -386,7 +758,8 @@ pub static SHA512_256: Algorithm = Algorithm {
     chaining_len: SHA512_OUTPUT_LEN,
     block_len: SHA512_BLOCK_LEN,
     len_len: SHA512_LEN_LEN,
-    block_data_order: GFp_sha512_block_data_order,
+    padding: Padding::MerkleDamgard,
+    block_data_order: sha2::sha512_block_data_order,
     format_output: sha512_format_output,
     initial_state: State {
         as64: [
@@ -403,13 +776,134 @@ pub static SHA512_256: Algorithm = Algorithm {
     id: AlgorithmID::SHA512_256,
 };
 
+/// SHA3-256 as specified in [FIPS 202].
+///
+/// [FIPS 202]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+pub static SHA3_256: Algorithm = Algorithm {
+    output_len: SHA3_256_OUTPUT_LEN,
+    chaining_len: SHA3_256_OUTPUT_LEN,
+    block_len: sha3::SHA3_256_RATE,
+    len_len: 0,
+    padding: Padding::Keccak,
+    block_data_order: sha3::sha3_256_block_data_order,
+    format_output: sha3::format_output,
+    initial_state: State {
+        keccak: [0; sha3::STATE_LANES],
+    },
+    id: AlgorithmID::SHA3_256,
+};
+
+/// SHA3-384 as specified in [FIPS 202].
+///
+/// [FIPS 202]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+pub static SHA3_384: Algorithm = Algorithm {
+    output_len: SHA3_384_OUTPUT_LEN,
+    chaining_len: SHA3_384_OUTPUT_LEN,
+    block_len: sha3::SHA3_384_RATE,
+    len_len: 0,
+    padding: Padding::Keccak,
+    block_data_order: sha3::sha3_384_block_data_order,
+    format_output: sha3::format_output,
+    initial_state: State {
+        keccak: [0; sha3::STATE_LANES],
+    },
+    id: AlgorithmID::SHA3_384,
+};
+
+/// SHA3-512 as specified in [FIPS 202].
+///
+/// [FIPS 202]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+pub static SHA3_512: Algorithm = Algorithm {
+    output_len: SHA3_512_OUTPUT_LEN,
+    chaining_len: SHA3_512_OUTPUT_LEN,
+    block_len: sha3::SHA3_512_RATE,
+    len_len: 0,
+    padding: Padding::Keccak,
+    block_data_order: sha3::sha3_512_block_data_order,
+    format_output: sha3::format_output,
+    initial_state: State {
+        keccak: [0; sha3::STATE_LANES],
+    },
+    id: AlgorithmID::SHA3_512,
+};
+
+/// BLAKE2b-512, i.e. BLAKE2b with a 64-byte output, as specified in
+/// [RFC 7693].
+///
+/// [RFC 7693]: https://tools.ietf.org/html/rfc7693
+pub static BLAKE2B_512: Algorithm = Algorithm {
+    output_len: BLAKE2B_512_OUTPUT_LEN,
+    chaining_len: BLAKE2B_512_OUTPUT_LEN,
+    block_len: blake2b::BLOCK_LEN,
+    len_len: 0,
+    padding: Padding::Blake2 {
+        finish: blake2b::finish,
+        set_key_len: blake2b::set_key_len,
+    },
+    block_data_order: blake2b::block_data_order,
+    format_output: blake2b::format_output,
+    initial_state: State {
+        blake2b: blake2b::INITIAL_STATE_512,
+    },
+    id: AlgorithmID::BLAKE2B_512,
+};
+
+/// BLAKE2s-256, i.e. BLAKE2s with a 32-byte output, as specified in
+/// [RFC 7693].
+///
+/// [RFC 7693]: https://tools.ietf.org/html/rfc7693
+pub static BLAKE2S_256: Algorithm = Algorithm {
+    output_len: BLAKE2S_256_OUTPUT_LEN,
+    chaining_len: BLAKE2S_256_OUTPUT_LEN,
+    block_len: blake2s::BLOCK_LEN,
+    len_len: 0,
+    padding: Padding::Blake2 {
+        finish: blake2s::finish,
+        set_key_len: blake2s::set_key_len,
+    },
+    block_data_order: blake2s::block_data_order,
+    format_output: blake2s::format_output,
+    initial_state: State {
+        blake2s: blake2s::INITIAL_STATE_256,
+    },
+    id: AlgorithmID::BLAKE2S_256,
+};
+
+/// Ascon-Hash as specified in [Ascon v1.2], the hash function of the winner
+/// of the NIST lightweight cryptography competition.
+///
+/// Its blocks are only 8 bytes long, shorter than its output, so it can't be
+/// used with `hmac`, `hkdf`, or `pbkdf2`.
+///
+/// [Ascon v1.2]: https://ascon.iaik.tugraz.at/files/asconv12-nist.pdf
+pub static ASCON_HASH: Algorithm = Algorithm {
+    output_len: ASCON_HASH_OUTPUT_LEN,
+    chaining_len: ASCON_HASH_OUTPUT_LEN,
+    block_len: ascon::BLOCK_LEN,
+    len_len: 0,
+    padding: Padding::Ascon,
+    block_data_order: ascon::block_data_order,
+    format_output: ascon::format_output,
+    initial_state: State {
+        ascon: ascon::INITIAL_STATE,
+    },
+    id: AlgorithmID::ASCON_HASH,
+};
+
 #[derive(Clone, Copy)] // XXX: Why do we need to be `Copy`?
 #[repr(C)]
 union State {
     as64: [Wrapping<u64>; 512 / 8 / core::mem::size_of::<Wrapping<u64>>()],
     as32: [Wrapping<u32>; 256 / 8 / core::mem::size_of::<Wrapping<u32>>()],
+    keccak: [u64; sha3::STATE_LANES],
+    blake2b: blake2b::State,
+    blake2s: blake2s::State,
+    ascon: [u64; ascon::STATE_WORDS],
 }
 
+// Every variant is made up of plain integers.
+unsafe impl Zeroable for State {}
+
 #[derive(Clone, Copy)]
 #[repr(C)]
 union Output {
@@ -419,7 +913,7 @@ union Output {
 
 /// The maximum block length (`Algorithm::block_len`) of all the algorithms in
 /// this module.
-pub const MAX_BLOCK_LEN: usize = 1024 / 8;
+pub const MAX_BLOCK_LEN: usize = sha3::SHA3_256_RATE;
 
 /// The maximum output length (`Algorithm::output_len`) of all the algorithms
 /// in this module.
@@ -476,27 +970,46 @@ pub const SHA512_OUTPUT_LEN: usize = 512 / 8;
 /// The length of the output of SHA-512/256, in bytes.
 pub const SHA512_256_OUTPUT_LEN: usize = 256 / 8;
 
+/// The length of the output of SHA3-256, in bytes.
+pub const SHA3_256_OUTPUT_LEN: usize = 256 / 8;
+
+/// The length of the output of SHA3-384, in bytes.
+pub const SHA3_384_OUTPUT_LEN: usize = 384 / 8;
+
+/// The length of the output of SHA3-512, in bytes.
+pub const SHA3_512_OUTPUT_LEN: usize = 512 / 8;
+
+/// The length of the output of BLAKE2b-512, in bytes.
+pub const BLAKE2B_512_OUTPUT_LEN: usize = blake2b::MAX_OUTPUT_LEN;
+
+/// The length of the output of BLAKE2s-256, in bytes.
+pub const BLAKE2S_256_OUTPUT_LEN: usize = blake2s::OUTPUT_LEN;
+
+/// The length of the output of Ascon-Hash, in bytes.
+pub const ASCON_HASH_OUTPUT_LEN: usize = ascon::OUTPUT_LEN;
+
 /// The length of a block for SHA-512-based algorithms, in bytes.
 const SHA512_BLOCK_LEN: usize = 1024 / 8;
 
 /// The length of the length field for SHA-512-based algorithms, in bytes.
 const SHA512_LEN_LEN: usize = 128 / 8;
 
-extern "C" {
-    fn GFp_sha256_block_data_order(state: &mut State, data: *const u8, num: c::size_t);
-    fn GFp_sha512_block_data_order(state: &mut State, data: *const u8, num: c::size_t);
-}
-
 #[cfg(test)]
 pub mod test_util {
     use super::super::digest;
 
-    pub static ALL_ALGORITHMS: [&digest::Algorithm; 5] = [
+    // All the algorithms except `ASCON_HASH`, which `hmac` doesn't support.
+    pub static ALL_ALGORITHMS: [&digest::Algorithm; 10] = [
         &digest::SHA1,
         &digest::SHA256,
         &digest::SHA384,
         &digest::SHA512,
         &digest::SHA512_256,
+        &digest::SHA3_256,
+        &digest::SHA3_384,
+        &digest::SHA3_512,
+        &digest::BLAKE2B_512,
+        &digest::BLAKE2S_256,
     ];
 }
 
@@ -0,0 +1,124 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Ascon-Hash and the Ascon permutation, which `aead::ascon` also uses, as
+//! specified in [Ascon v1.2].
+//!
+//! The permutation only uses bitwise operations and rotations of 64-bit
+//! words, so it is constant-time without any tables.
+//!
+//! [Ascon v1.2]: https://ascon.iaik.tugraz.at/files/asconv12-nist.pdf
+
+use crate::{c, endian::*};
+
+/// The number of 64-bit words in the Ascon state.
+pub(crate) const STATE_WORDS: usize = 5;
+
+pub(super) const BLOCK_LEN: usize = 64 / 8;
+pub(super) const OUTPUT_LEN: usize = 256 / 8;
+
+/// p**12(IV || 0**256), where IV is Ascon-Hash's initialization vector.
+pub(super) const INITIAL_STATE: [u64; STATE_WORDS] = [
+    0xee9398aadb67f03d,
+    0x8bb21831c60f1002,
+    0xb48a92db98d5da62,
+    0x43189921b8f8e3e8,
+    0x348fa5c9d525e140,
+];
+
+pub(super) unsafe extern "C" fn block_data_order(
+    state: &mut super::State,
+    data: *const u8,
+    num: c::size_t,
+) {
+    let state = &mut state.ascon;
+    let data = core::slice::from_raw_parts(data, num * BLOCK_LEN);
+    for block in data.chunks_exact(BLOCK_LEN) {
+        state[0] ^= load_u64(block);
+        permute(state, 12);
+    }
+}
+
+/// Pads the final, partial, block of an Ascon-Hash digest, which is
+/// `block[..num_pending]`, with a one bit and then zeros. `block` is exactly
+/// one block long.
+pub(super) fn pad(block: &mut [u8], num_pending: usize) {
+    block[num_pending] = 0x80;
+    for b in &mut block[(num_pending + 1)..] {
+        *b = 0;
+    }
+}
+
+pub(super) fn format_output(input: super::State) -> super::Output {
+    let mut state = unsafe { input.ascon };
+    let mut output = super::Output {
+        as64: [BigEndian::ZERO; 8],
+    };
+    // The digest is squeezed out of the first word of the state, with the
+    // permutation applied between the words.
+    let words = unsafe { &mut output.as64[..(OUTPUT_LEN / 8)] };
+    for (i, out) in words.iter_mut().enumerate() {
+        if i > 0 {
+            permute(&mut state, 12);
+        }
+        *out = BigEndian::from(state[0]);
+    }
+    output
+}
+
+/// Loads the big-endian word `bytes`, which is exactly 8 bytes long.
+pub(crate) fn load_u64(bytes: &[u8]) -> u64 {
+    let mut be = [0u8; 8];
+    be.copy_from_slice(bytes);
+    u64::from_be_bytes(be)
+}
+
+const ROUND_CONSTANTS: [u64; 12] = [
+    0xf0, 0xe1, 0xd2, 0xc3, 0xb4, 0xa5, 0x96, 0x87, 0x78, 0x69, 0x5a, 0x4b,
+];
+
+/// The permutation p**`rounds`, which uses the last `rounds` of the 12 round
+/// constants.
+pub(crate) fn permute(x: &mut [u64; STATE_WORDS], rounds: usize) {
+    for &c in &ROUND_CONSTANTS[(ROUND_CONSTANTS.len() - rounds)..] {
+        // Addition of the round constant.
+        x[2] ^= c;
+
+        // The substitution layer, the 5-bit S-box applied to each bit slice.
+        x[0] ^= x[4];
+        x[4] ^= x[3];
+        x[2] ^= x[1];
+        let t0 = !x[0] & x[1];
+        let t1 = !x[1] & x[2];
+        let t2 = !x[2] & x[3];
+        let t3 = !x[3] & x[4];
+        let t4 = !x[4] & x[0];
+        x[0] ^= t1;
+        x[1] ^= t2;
+        x[2] ^= t3;
+        x[3] ^= t4;
+        x[4] ^= t0;
+        x[1] ^= x[0];
+        x[0] ^= x[4];
+        x[3] ^= x[2];
+        x[2] = !x[2];
+
+        // The linear diffusion layer.
+        x[0] ^= x[0].rotate_right(19) ^ x[0].rotate_right(28);
+        x[1] ^= x[1].rotate_right(61) ^ x[1].rotate_right(39);
+        x[2] ^= x[2].rotate_right(1) ^ x[2].rotate_right(6);
+        x[3] ^= x[3].rotate_right(10) ^ x[3].rotate_right(17);
+        x[4] ^= x[4].rotate_right(7) ^ x[4].rotate_right(41);
+    }
+}
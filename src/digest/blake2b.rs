@@ -0,0 +1,313 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! BLAKE2b, as specified in [RFC 7693], including the keyed mode and
+//! variable-length output.
+//!
+//! Like the SHA-1 implementation, this favors size and simplicity over
+//! speed; there is no assembly language implementation.
+//!
+//! [RFC 7693]: https://tools.ietf.org/html/rfc7693
+
+use crate::{c, endian::*};
+
+pub const BLOCK_LEN: usize = 128;
+pub const MAX_OUTPUT_LEN: usize = 64;
+pub const MAX_KEY_LEN: usize = 64;
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// The chaining value and byte counter of a BLAKE2b computation.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub(super) struct State {
+    h: [u64; 8],
+    t: u128,
+}
+
+impl State {
+    const fn new(key_len: usize, output_len: usize) -> Self {
+        Self {
+            h: [
+                IV[0] ^ 0x0101_0000 ^ ((key_len as u64) << 8) ^ (output_len as u64),
+                IV[1],
+                IV[2],
+                IV[3],
+                IV[4],
+                IV[5],
+                IV[6],
+                IV[7],
+            ],
+            t: 0,
+        }
+    }
+}
+
+/// The initial state of unkeyed BLAKE2b-512.
+pub(super) const INITIAL_STATE_512: State = State::new(0, MAX_OUTPUT_LEN);
+
+pub(super) unsafe extern "C" fn block_data_order(
+    state: &mut super::State,
+    data: *const u8,
+    num: c::size_t,
+) {
+    let state = &mut state.blake2b;
+    let data = core::slice::from_raw_parts(data, num * BLOCK_LEN);
+    for block in data.chunks_exact(BLOCK_LEN) {
+        state.t += BLOCK_LEN as u128;
+        compress(state, block, false);
+    }
+}
+
+/// Compresses the final block, which is `block[..num_pending]`.
+pub(super) fn finish(state: &mut super::State, block: &mut [u8], num_pending: usize) {
+    let state = unsafe { &mut state.blake2b };
+    state.t += num_pending as u128;
+    for b in &mut block[num_pending..] {
+        *b = 0;
+    }
+    compress(state, block, true);
+}
+
+/// Mixes the length of the key into the parameter block of the unkeyed
+/// initial state.
+pub(super) fn set_key_len(state: &mut super::State, key_len: usize) {
+    let state = unsafe { &mut state.blake2b };
+    state.h[0] ^= (key_len as u64) << 8;
+}
+
+pub(super) fn format_output(input: super::State) -> super::Output {
+    let input = unsafe { &input.blake2b };
+    let mut output = super::Output {
+        as64: [BigEndian::ZERO; 8],
+    };
+    for (out, h) in unsafe { output.as64.iter_mut() }.zip(input.h.iter()) {
+        *out = BigEndian::from(u64::from_be_bytes(h.to_le_bytes()));
+    }
+    output
+}
+
+/// A BLAKE2b context.
+#[derive(Clone)]
+pub struct Context {
+    state: State,
+    pending: [u8; BLOCK_LEN],
+    num_pending: usize,
+    output_len: usize,
+}
+
+impl Context {
+    /// Constructs a context for computing a BLAKE2b digest of `output_len`
+    /// bytes, keyed with `key` unless `key` is empty.
+    ///
+    /// Panics unless `1 <= output_len <= MAX_OUTPUT_LEN` and
+    /// `key.len() <= MAX_KEY_LEN`.
+    pub fn new(key: &[u8], output_len: usize) -> Self {
+        assert!(output_len >= 1 && output_len <= MAX_OUTPUT_LEN);
+        assert!(key.len() <= MAX_KEY_LEN);
+
+        let mut ctx = Self {
+            state: State::new(key.len(), output_len),
+            pending: [0; BLOCK_LEN],
+            num_pending: 0,
+            output_len,
+        };
+        if !key.is_empty() {
+            ctx.pending[..key.len()].copy_from_slice(key);
+            ctx.num_pending = BLOCK_LEN;
+        }
+        ctx
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // The final block must be processed by `finish`, so a full block
+            // is only compressed once we know more data follows it.
+            if self.num_pending == BLOCK_LEN {
+                self.state.t += BLOCK_LEN as u128;
+                compress(&mut self.state, &self.pending, false);
+                self.num_pending = 0;
+            }
+            let to_copy = core::cmp::min(BLOCK_LEN - self.num_pending, data.len());
+            self.pending[self.num_pending..][..to_copy].copy_from_slice(&data[..to_copy]);
+            self.num_pending += to_copy;
+            data = &data[to_copy..];
+        }
+    }
+
+    pub fn finish(mut self) -> Digest {
+        self.state.t += self.num_pending as u128;
+        for b in &mut self.pending[self.num_pending..] {
+            *b = 0;
+        }
+        compress(&mut self.state, &self.pending, true);
+
+        let mut value = [0u8; MAX_OUTPUT_LEN];
+        for (out, h) in value.chunks_mut(8).zip(self.state.h.iter()) {
+            out.copy_from_slice(&h.to_le_bytes());
+        }
+        Digest {
+            value,
+            len: self.output_len,
+        }
+    }
+}
+
+fn compress(state: &mut State, block: &[u8], last: bool) {
+    let mut m = [0u64; 16];
+    for (m, bytes) in m.iter_mut().zip(block.chunks(8)) {
+        let mut word = [0u8; 8];
+        word.copy_from_slice(bytes);
+        *m = u64::from_le_bytes(word);
+    }
+
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(&state.h);
+    v[8..].copy_from_slice(&IV);
+    v[12] ^= state.t as u64;
+    v[13] ^= (state.t >> 64) as u64;
+    if last {
+        v[14] = !v[14];
+    }
+
+    for round in 0..12 {
+        let s = &SIGMA[round % 10];
+        g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+        g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+        g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+        g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+        g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+        g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+        g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+    }
+
+    for i in 0..8 {
+        state.h[i] ^= v[i] ^ v[i + 8];
+    }
+}
+
+#[inline(always)]
+fn g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// A BLAKE2b digest.
+#[derive(Clone, Copy)]
+pub struct Digest {
+    value: [u8; MAX_OUTPUT_LEN],
+    len: usize,
+}
+
+impl AsRef<[u8]> for Digest {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u8] {
+        &self.value[..self.len]
+    }
+}
+
+/// Returns the `output_len`-byte BLAKE2b digest of `data`, keyed with `key`
+/// unless `key` is empty.
+pub fn digest(key: &[u8], output_len: usize, data: &[u8]) -> Digest {
+    let mut ctx = Context::new(key, output_len);
+    ctx.update(data);
+    ctx.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_blake2b() {
+        let key: Vec<u8> = (0..64).collect();
+        let data: Vec<u8> = (0..=255).collect();
+        let test_cases: &[(&[u8], usize, &[u8], &str)] = &[
+            // RFC 7693 Appendix A.
+            (
+                b"",
+                64,
+                b"abc",
+                "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+                 7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+            ),
+            (
+                &key,
+                64,
+                b"",
+                "10ebb67700b1868efb4417987acf4690ae9d972fb7a590c2f02871799aaa4786\
+                 b5e996e8f0f4eb981fc214b005f42d2ff4233499391653df7aefcbc13fc51568",
+            ),
+            (
+                &key,
+                64,
+                &data[..255],
+                "142709d62e28fcccd0af97fad0f8465b971e82201dc51070faa0372aa43e9248\
+                 4be1c1e73ba10906d5d1853db6a4106e0a7bf9800d373d6dee2d46d62ef2a461",
+            ),
+            (
+                b"",
+                20,
+                &data[..128],
+                "e6992372ab022447b34f6d6032fbab707a11adef",
+            ),
+        ];
+
+        for &(key, output_len, data, expected) in test_cases {
+            let expected = test::from_hex(expected).unwrap();
+            assert_eq!(digest(key, output_len, data).as_ref(), &expected[..]);
+
+            // Feeding the input incrementally must give the same result,
+            // regardless of how it is split across block boundaries.
+            for split in &[1, 63, 127, 128, 129] {
+                let mut ctx = Context::new(key, output_len);
+                for chunk in data.chunks(*split) {
+                    ctx.update(chunk);
+                }
+                assert_eq!(ctx.finish().as_ref(), &expected[..]);
+            }
+        }
+    }
+}
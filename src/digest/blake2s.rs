@@ -0,0 +1,151 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! BLAKE2s, as specified in [RFC 7693].
+//!
+//! Like the BLAKE2b implementation, this favors size and simplicity over
+//! speed; there is no assembly language implementation.
+//!
+//! [RFC 7693]: https://tools.ietf.org/html/rfc7693
+
+use crate::{c, endian::*};
+
+pub const BLOCK_LEN: usize = 64;
+pub const OUTPUT_LEN: usize = 32;
+
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// The chaining value and byte counter of a BLAKE2s computation.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub(super) struct State {
+    h: [u32; 8],
+    t: u64,
+}
+
+/// The initial state of unkeyed BLAKE2s-256.
+pub(super) const INITIAL_STATE_256: State = State {
+    h: [
+        IV[0] ^ 0x0101_0000 ^ (OUTPUT_LEN as u32),
+        IV[1],
+        IV[2],
+        IV[3],
+        IV[4],
+        IV[5],
+        IV[6],
+        IV[7],
+    ],
+    t: 0,
+};
+
+pub(super) unsafe extern "C" fn block_data_order(
+    state: &mut super::State,
+    data: *const u8,
+    num: c::size_t,
+) {
+    let state = &mut state.blake2s;
+    let data = core::slice::from_raw_parts(data, num * BLOCK_LEN);
+    for block in data.chunks_exact(BLOCK_LEN) {
+        state.t += BLOCK_LEN as u64;
+        compress(state, block, false);
+    }
+}
+
+/// Compresses the final block, which is `block[..num_pending]`.
+pub(super) fn finish(state: &mut super::State, block: &mut [u8], num_pending: usize) {
+    let state = unsafe { &mut state.blake2s };
+    state.t += num_pending as u64;
+    for b in &mut block[num_pending..] {
+        *b = 0;
+    }
+    compress(state, block, true);
+}
+
+/// Mixes the length of the key into the parameter block of the unkeyed
+/// initial state.
+pub(super) fn set_key_len(state: &mut super::State, key_len: usize) {
+    let state = unsafe { &mut state.blake2s };
+    state.h[0] ^= (key_len as u32) << 8;
+}
+
+pub(super) fn format_output(input: super::State) -> super::Output {
+    let input = unsafe { &input.blake2s };
+    let mut output = super::Output {
+        as32: [BigEndian::ZERO; 8],
+    };
+    for (out, h) in unsafe { output.as32.iter_mut() }.zip(input.h.iter()) {
+        *out = BigEndian::from(u32::from_be_bytes(h.to_le_bytes()));
+    }
+    output
+}
+
+fn compress(state: &mut State, block: &[u8], last: bool) {
+    let mut m = [0u32; 16];
+    for (m, bytes) in m.iter_mut().zip(block.chunks(4)) {
+        let mut word = [0u8; 4];
+        word.copy_from_slice(bytes);
+        *m = u32::from_le_bytes(word);
+    }
+
+    let mut v = [0u32; 16];
+    v[..8].copy_from_slice(&state.h);
+    v[8..].copy_from_slice(&IV);
+    v[12] ^= state.t as u32;
+    v[13] ^= (state.t >> 32) as u32;
+    if last {
+        v[14] = !v[14];
+    }
+
+    for s in SIGMA.iter() {
+        g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+        g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+        g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+        g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+        g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+        g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+        g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+    }
+
+    for i in 0..8 {
+        state.h[i] ^= v[i] ^ v[i + 8];
+    }
+}
+
+#[inline(always)]
+fn g(v: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, x: u32, y: u32) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(12);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(8);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(7);
+}
@@ -0,0 +1,337 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Hashing several independent messages at once.
+//!
+//! SHA-256 is inherently serial within a message, but independent messages
+//! can be hashed in the lanes of SIMD registers. On x86-64, `sha256_x4`
+//! hashes four messages with SSE2 and `sha256_x8` hashes eight messages
+//! with AVX2, when the CPU supports it. Elsewhere, the messages are hashed
+//! one at a time; the results are the same either way.
+//!
+//! The messages are processed in lockstep for as many whole blocks as the
+//! shortest message has, and the rest of each message is hashed on its own,
+//! so this works best when the messages have similar lengths, e.g. the
+//! fixed-size chunks of a content-addressed store.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::digest;
+//!
+//! let chunks: [&[u8]; 4] = [b"chunk 0", b"chunk 1", b"chunk 2", b"chunk 3"];
+//! let digests = digest::multi::sha256_x4(&chunks);
+//!
+//! for (chunk, multi) in chunks.iter().zip(digests.iter()) {
+//!     let single = digest::digest(&digest::SHA256, chunk);
+//!     assert_eq!(multi.as_ref(), single.as_ref());
+//! }
+//! ```
+
+use super::{Context, Digest, Output, State, MAX_BLOCK_LEN, SHA256};
+use crate::{cpu, endian::*, polyfill};
+use core::num::Wrapping;
+
+const BLOCK_LEN: usize = 512 / 8;
+
+/// Calculates the SHA-256 digests of four messages.
+pub fn sha256_x4(messages: &[&[u8]; 4]) -> [Digest; 4] {
+    let cpu_features = cpu::features();
+    let mut digests = [EMPTY_DIGEST; 4];
+    sha256_lanes(messages, &mut digests, cpu_features, x4_kernel());
+    digests
+}
+
+/// Calculates the SHA-256 digests of eight messages.
+pub fn sha256_x8(messages: &[&[u8]; 8]) -> [Digest; 8] {
+    let cpu_features = cpu::features();
+    let mut digests = [EMPTY_DIGEST; 8];
+    match x8_kernel(cpu_features) {
+        Some(kernel) => sha256_lanes(messages, &mut digests, cpu_features, Some(kernel)),
+        None => {
+            let (first, second) = messages.split_at(4);
+            let (first_digests, second_digests) = digests.split_at_mut(4);
+            sha256_lanes(first, first_digests, cpu_features, x4_kernel());
+            sha256_lanes(second, second_digests, cpu_features, x4_kernel());
+        }
+    }
+    digests
+}
+
+// Compresses `num_blocks` blocks of each of the messages into the
+// corresponding chaining values.
+type Kernel = unsafe fn(states: &mut [[u32; 8]], messages: &[&[u8]], num_blocks: usize);
+
+fn x4_kernel() -> Option<Kernel> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        // SSE2 is part of the x86-64 baseline.
+        Some(x86_64::sha256_x4_sse2)
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        None
+    }
+}
+
+fn x8_kernel(cpu_features: cpu::Features) -> Option<Kernel> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if cpu::intel::AVX2.available(cpu_features) {
+            return Some(x86_64::sha256_x8_avx2);
+        }
+    }
+
+    let _ = cpu_features;
+    None
+}
+
+fn sha256_lanes(
+    messages: &[&[u8]],
+    digests: &mut [Digest],
+    cpu_features: cpu::Features,
+    kernel: Option<Kernel>,
+) {
+    let initial_state = unsafe { &SHA256.initial_state.as32 };
+    let mut states = [[0u32; 8]; 8];
+    let states = &mut states[..messages.len()];
+    for state in states.iter_mut() {
+        for (word, initial) in state.iter_mut().zip(initial_state.iter()) {
+            *word = initial.0;
+        }
+    }
+
+    let num_common_blocks = match kernel {
+        Some(kernel) => {
+            let num_common_blocks = messages
+                .iter()
+                .map(|message| message.len() / BLOCK_LEN)
+                .min()
+                .unwrap_or(0);
+            if num_common_blocks > 0 {
+                unsafe { kernel(states, messages, num_common_blocks) };
+            }
+            num_common_blocks
+        }
+        None => 0,
+    };
+
+    // Hash the rest of each message, and add the padding, on its own.
+    for ((message, state), digest) in messages.iter().zip(states.iter()).zip(digests.iter_mut()) {
+        let mut as32 = *initial_state;
+        for (word, value) in as32.iter_mut().zip(state.iter()) {
+            *word = Wrapping(*value);
+        }
+        let mut ctx = Context {
+            state: State { as32 },
+            completed_data_blocks: polyfill::u64_from_usize(num_common_blocks),
+            pending: [0; MAX_BLOCK_LEN],
+            num_pending: 0,
+            algorithm: &SHA256,
+            cpu_features,
+        };
+        ctx.update(&message[(num_common_blocks * BLOCK_LEN)..]);
+        *digest = ctx.finish();
+    }
+}
+
+const EMPTY_DIGEST: Digest = Digest {
+    value: Output {
+        as32: [BigEndian::ZERO; 8],
+    },
+    algorithm: &SHA256,
+};
+
+#[cfg(target_arch = "x86_64")]
+mod x86_64 {
+    use super::BLOCK_LEN;
+    use core::arch::x86_64::*;
+
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+
+    // The kernels are the same except for the vector type and the names of
+    // the instructions, so they are generated from one definition.
+    macro_rules! sha256_kernel {
+        ( $name:ident, $features:expr, $lanes:expr, $vector:ident, $set1:ident, $loadu:ident,
+          $storeu:ident, $add:ident, $and:ident, $andnot:ident, $or:ident, $xor:ident,
+          $srli:ident, $slli:ident ) => {
+            #[target_feature(enable = $features)]
+            pub(super) unsafe fn $name(
+                states: &mut [[u32; 8]],
+                messages: &[&[u8]],
+                num_blocks: usize,
+            ) {
+                assert_eq!(states.len(), $lanes);
+                assert_eq!(messages.len(), $lanes);
+
+                macro_rules! rotr {
+                    ( $x:expr, $n:expr ) => {{
+                        let x = $x;
+                        $or($srli(x, $n), $slli(x, 32 - $n))
+                    }};
+                }
+
+                // Loads a vector with `$word` evaluated for each `$lane`.
+                macro_rules! gather {
+                    ( |$lane:ident| $word:expr ) => {{
+                        let mut words = [0u32; $lanes];
+                        for ($lane, word) in words.iter_mut().enumerate() {
+                            *word = $word;
+                        }
+                        $loadu(words.as_ptr() as *const $vector)
+                    }};
+                }
+
+                let mut h = [$set1(0); 8];
+                for (i, h) in h.iter_mut().enumerate() {
+                    *h = gather!(|lane| states[lane][i]);
+                }
+
+                for block in 0..num_blocks {
+                    let mut w = [$set1(0); 16];
+                    for (i, w) in w.iter_mut().enumerate() {
+                        *w = gather!(|lane| {
+                            let start = (block * BLOCK_LEN) + (4 * i);
+                            let mut word = [0u8; 4];
+                            word.copy_from_slice(&messages[lane][start..][..4]);
+                            u32::from_be_bytes(word)
+                        });
+                    }
+
+                    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+                    for t in 0..64 {
+                        if t >= 16 {
+                            let w15 = w[(t + 1) % 16];
+                            let w2 = w[(t + 14) % 16];
+                            let s0 = $xor($xor(rotr!(w15, 7), rotr!(w15, 18)), $srli(w15, 3));
+                            let s1 = $xor($xor(rotr!(w2, 17), rotr!(w2, 19)), $srli(w2, 10));
+                            w[t % 16] = $add($add(w[t % 16], s0), $add(w[(t + 9) % 16], s1));
+                        }
+
+                        let big_s1 = $xor($xor(rotr!(e, 6), rotr!(e, 11)), rotr!(e, 25));
+                        let ch = $xor($and(e, f), $andnot(e, g));
+                        let t1 = $add(
+                            $add($add(hh, big_s1), $add(ch, $set1(K[t] as i32))),
+                            w[t % 16],
+                        );
+                        let big_s0 = $xor($xor(rotr!(a, 2), rotr!(a, 13)), rotr!(a, 22));
+                        let maj = $xor($xor($and(a, b), $and(a, c)), $and(b, c));
+                        let t2 = $add(big_s0, maj);
+
+                        hh = g;
+                        g = f;
+                        f = e;
+                        e = $add(d, t1);
+                        d = c;
+                        c = b;
+                        b = a;
+                        a = $add(t1, t2);
+                    }
+
+                    for (h, v) in h.iter_mut().zip([a, b, c, d, e, f, g, hh].iter()) {
+                        *h = $add(*h, *v);
+                    }
+                }
+
+                for (i, h) in h.iter().enumerate() {
+                    let mut words = [0u32; $lanes];
+                    $storeu(words.as_mut_ptr() as *mut $vector, *h);
+                    for (state, word) in states.iter_mut().zip(words.iter()) {
+                        state[i] = *word;
+                    }
+                }
+            }
+        };
+    }
+
+    sha256_kernel!(
+        sha256_x4_sse2,
+        "sse2",
+        4,
+        __m128i,
+        _mm_set1_epi32,
+        _mm_loadu_si128,
+        _mm_storeu_si128,
+        _mm_add_epi32,
+        _mm_and_si128,
+        _mm_andnot_si128,
+        _mm_or_si128,
+        _mm_xor_si128,
+        _mm_srli_epi32,
+        _mm_slli_epi32
+    );
+
+    sha256_kernel!(
+        sha256_x8_avx2,
+        "avx2",
+        8,
+        __m256i,
+        _mm256_set1_epi32,
+        _mm256_loadu_si256,
+        _mm256_storeu_si256,
+        _mm256_add_epi32,
+        _mm256_and_si256,
+        _mm256_andnot_si256,
+        _mm256_or_si256,
+        _mm256_xor_si256,
+        _mm256_srli_epi32,
+        _mm256_slli_epi32
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest;
+
+    // Exercises the portable path, and the four-way kernel in the place of
+    // the eight-way one, regardless of the CPU.
+    #[test]
+    fn test_sha256_lanes() {
+        let data = [0x5au8; 1000];
+        let messages: [&[u8]; 8] = [
+            &data[..0],
+            &data[..1],
+            &data[..64],
+            &data[..128],
+            &data[..200],
+            &data[..255],
+            &data[..512],
+            &data[..1000],
+        ];
+        let cpu_features = cpu::features();
+        for kernel in &[None, x4_kernel()] {
+            for messages in &[&messages[..4], &messages[4..], &messages[2..6]] {
+                let mut digests = [EMPTY_DIGEST; 4];
+                sha256_lanes(messages, &mut digests, cpu_features, *kernel);
+                for (message, actual) in messages.iter().zip(digests.iter()) {
+                    let expected = digest::digest(&SHA256, message);
+                    assert_eq!(actual.as_ref(), expected.as_ref());
+                }
+            }
+        }
+    }
+}
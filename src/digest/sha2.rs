@@ -0,0 +1,243 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Selection of the SHA-2 block functions.
+//!
+//! On x86-64 CPUs with the SHA extensions, SHA-256 uses
+//! `GFp_sha256_block_data_order_shaext`. On AArch64 CPUs with the ARMv8.2
+//! SHA-512 instructions, SHA-384 and SHA-512 use the `sha512` module below.
+//! The AArch64 and ARM assembly already uses the ARMv8 SHA-256 instructions
+//! when `GFp_armcap_P` says they are available. Everything else uses the
+//! generic assembly implementations.
+
+use super::State;
+use crate::c;
+
+pub(super) unsafe extern "C" fn sha256_block_data_order(
+    state: &mut State,
+    data: *const u8,
+    num: c::size_t,
+) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if crate::cpu::intel::SHA.available(crate::cpu::features()) {
+            return GFp_sha256_block_data_order_shaext(state, data, num);
+        }
+    }
+
+    GFp_sha256_block_data_order(state, data, num)
+}
+
+pub(super) unsafe extern "C" fn sha512_block_data_order(
+    state: &mut State,
+    data: *const u8,
+    num: c::size_t,
+) {
+    #[cfg(target_arch = "aarch64")]
+    {
+        if crate::cpu::arm::SHA512.available(crate::cpu::features()) {
+            let data = core::slice::from_raw_parts(data, num * super::SHA512_BLOCK_LEN);
+            return sha512::block_data_order(&mut state.as64, data);
+        }
+    }
+
+    GFp_sha512_block_data_order(state, data, num)
+}
+
+extern "C" {
+    fn GFp_sha256_block_data_order(state: &mut State, data: *const u8, num: c::size_t);
+    fn GFp_sha512_block_data_order(state: &mut State, data: *const u8, num: c::size_t);
+}
+
+#[cfg(target_arch = "x86_64")]
+extern "C" {
+    fn GFp_sha256_block_data_order_shaext(state: &mut State, data: *const u8, num: c::size_t);
+}
+
+// SHA-512 using the ARMv8.2 `sha512h`, `sha512h2`, `sha512su0`, and
+// `sha512su1` instructions, two rounds at a time.
+#[cfg(target_arch = "aarch64")]
+mod sha512 {
+    use crate::digest::SHA512_BLOCK_LEN;
+    use core::{arch::aarch64::*, num::Wrapping};
+
+    #[target_feature(enable = "neon,sha3")]
+    pub(super) unsafe fn block_data_order(state: &mut [Wrapping<u64>; 8], data: &[u8]) {
+        // `hash[0]` holds (a, b), `hash[1]` holds (c, d), and so on.
+        let mut hash = [vdupq_n_u64(0); 4];
+        for (h, s) in hash.iter_mut().zip(state.chunks(2)) {
+            *h = vcombine_u64(vcreate_u64(s[0].0), vcreate_u64(s[1].0));
+        }
+
+        for block in data.chunks(SHA512_BLOCK_LEN) {
+            let saved = hash;
+
+            let mut w = [vdupq_n_u64(0); 8];
+            for (w, bytes) in w.iter_mut().zip(block.chunks(16)) {
+                *w = vreinterpretq_u64_u8(vrev64q_u8(vld1q_u8(bytes.as_ptr())));
+            }
+
+            for i in 0..40 {
+                if i >= 8 {
+                    let w_9_10 = vextq_u64::<1>(w[(i + 4) % 8], w[(i + 5) % 8]);
+                    w[i % 8] = vsha512su1q_u64(
+                        vsha512su0q_u64(w[i % 8], w[(i + 1) % 8]),
+                        w[(i + 7) % 8],
+                        w_9_10,
+                    );
+                }
+
+                // The roles of the four pairs rotate by one every two rounds.
+                let j = 4 - (i % 4);
+                let (ab, cd, ef, gh) = (j % 4, (j + 1) % 4, (j + 2) % 4, (j + 3) % 4);
+
+                let sum = vaddq_u64(w[i % 8], vld1q_u64(K[(2 * i)..].as_ptr()));
+                let sum = vaddq_u64(vextq_u64::<1>(sum, sum), hash[gh]);
+                let t = vsha512hq_u64(
+                    sum,
+                    vextq_u64::<1>(hash[ef], hash[gh]),
+                    vextq_u64::<1>(hash[cd], hash[ef]),
+                );
+                hash[gh] = vsha512h2q_u64(t, hash[cd], hash[ab]);
+                hash[cd] = vaddq_u64(hash[cd], t);
+            }
+
+            for (h, s) in hash.iter_mut().zip(saved.iter()) {
+                *h = vaddq_u64(*h, *s);
+            }
+        }
+
+        for (s, h) in state.chunks_mut(2).zip(hash.iter()) {
+            s[0] = Wrapping(vgetq_lane_u64::<0>(*h));
+            s[1] = Wrapping(vgetq_lane_u64::<1>(*h));
+        }
+    }
+
+    static K: [u64; 80] = [
+        0x428a2f98d728ae22,
+        0x7137449123ef65cd,
+        0xb5c0fbcfec4d3b2f,
+        0xe9b5dba58189dbbc,
+        0x3956c25bf348b538,
+        0x59f111f1b605d019,
+        0x923f82a4af194f9b,
+        0xab1c5ed5da6d8118,
+        0xd807aa98a3030242,
+        0x12835b0145706fbe,
+        0x243185be4ee4b28c,
+        0x550c7dc3d5ffb4e2,
+        0x72be5d74f27b896f,
+        0x80deb1fe3b1696b1,
+        0x9bdc06a725c71235,
+        0xc19bf174cf692694,
+        0xe49b69c19ef14ad2,
+        0xefbe4786384f25e3,
+        0x0fc19dc68b8cd5b5,
+        0x240ca1cc77ac9c65,
+        0x2de92c6f592b0275,
+        0x4a7484aa6ea6e483,
+        0x5cb0a9dcbd41fbd4,
+        0x76f988da831153b5,
+        0x983e5152ee66dfab,
+        0xa831c66d2db43210,
+        0xb00327c898fb213f,
+        0xbf597fc7beef0ee4,
+        0xc6e00bf33da88fc2,
+        0xd5a79147930aa725,
+        0x06ca6351e003826f,
+        0x142929670a0e6e70,
+        0x27b70a8546d22ffc,
+        0x2e1b21385c26c926,
+        0x4d2c6dfc5ac42aed,
+        0x53380d139d95b3df,
+        0x650a73548baf63de,
+        0x766a0abb3c77b2a8,
+        0x81c2c92e47edaee6,
+        0x92722c851482353b,
+        0xa2bfe8a14cf10364,
+        0xa81a664bbc423001,
+        0xc24b8b70d0f89791,
+        0xc76c51a30654be30,
+        0xd192e819d6ef5218,
+        0xd69906245565a910,
+        0xf40e35855771202a,
+        0x106aa07032bbd1b8,
+        0x19a4c116b8d2d0c8,
+        0x1e376c085141ab53,
+        0x2748774cdf8eeb99,
+        0x34b0bcb5e19b48a8,
+        0x391c0cb3c5c95a63,
+        0x4ed8aa4ae3418acb,
+        0x5b9cca4f7763e373,
+        0x682e6ff3d6b2b8a3,
+        0x748f82ee5defb2fc,
+        0x78a5636f43172f60,
+        0x84c87814a1f0ab72,
+        0x8cc702081a6439ec,
+        0x90befffa23631e28,
+        0xa4506cebde82bde9,
+        0xbef9a3f7b2c67915,
+        0xc67178f2e372532b,
+        0xca273eceea26619c,
+        0xd186b8c721c0c207,
+        0xeada7dd6cde0eb1e,
+        0xf57d4f7fee6ed178,
+        0x06f067aa72176fba,
+        0x0a637dc5a2c898a6,
+        0x113f9804bef90dae,
+        0x1b710b35131c471b,
+        0x28db77f523047d84,
+        0x32caab7b40c72493,
+        0x3c9ebe0a15c9bebc,
+        0x431d67c49c100d4c,
+        0x4cc5d4becb3e42b6,
+        0x597f299cfc657e2a,
+        0x5fcb6fab3ad6faec,
+        0x6c44198c4a475817,
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest::{SHA256, SHA512};
+
+    // Compares the selected block functions against the generic ones, so
+    // that the hardware implementations are checked on CPUs that have them.
+    #[test]
+    fn test_block_data_order() {
+        let mut data = [0u8; 7 * 128];
+        for (i, b) in data.iter_mut().enumerate() {
+            *b = (i * 7 + 3) as u8;
+        }
+
+        for &num in &[1, 2, 3, 7] {
+            let mut selected = SHA256.initial_state;
+            let mut generic = SHA256.initial_state;
+            unsafe {
+                sha256_block_data_order(&mut selected, data.as_ptr(), num);
+                GFp_sha256_block_data_order(&mut generic, data.as_ptr(), num);
+                assert_eq!(selected.as32, generic.as32);
+            }
+
+            let mut selected = SHA512.initial_state;
+            let mut generic = SHA512.initial_state;
+            unsafe {
+                sha512_block_data_order(&mut selected, data.as_ptr(), num);
+                GFp_sha512_block_data_order(&mut generic, data.as_ptr(), num);
+                assert_eq!(selected.as64, generic.as64);
+            }
+        }
+    }
+}
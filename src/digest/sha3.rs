@@ -0,0 +1,288 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! SHA-3 and SHAKE, as specified in [FIPS 202].
+//!
+//! Like the SHA-1 implementation, this favors size and simplicity over
+//! speed; there is no assembly language implementation.
+//!
+//! [FIPS 202]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+
+use crate::{c, endian::*};
+
+/// The number of 64-bit lanes in the Keccak-f[1600] state.
+pub(super) const STATE_LANES: usize = 25;
+
+pub(super) const SHA3_256_RATE: usize = 1088 / 8;
+pub(super) const SHA3_384_RATE: usize = 832 / 8;
+pub(super) const SHA3_512_RATE: usize = 576 / 8;
+const SHAKE128_RATE: usize = 1344 / 8;
+const SHAKE256_RATE: usize = 1088 / 8;
+
+// The domain separation bits and the first bit of the padding.
+const SHA3_PAD: u8 = 0x06;
+const SHAKE_PAD: u8 = 0x1f;
+
+pub(super) unsafe extern "C" fn sha3_256_block_data_order(
+    state: &mut super::State,
+    data: *const u8,
+    num: c::size_t,
+) {
+    block_data_order(&mut state.keccak, data, num, SHA3_256_RATE)
+}
+
+pub(super) unsafe extern "C" fn sha3_384_block_data_order(
+    state: &mut super::State,
+    data: *const u8,
+    num: c::size_t,
+) {
+    block_data_order(&mut state.keccak, data, num, SHA3_384_RATE)
+}
+
+pub(super) unsafe extern "C" fn sha3_512_block_data_order(
+    state: &mut super::State,
+    data: *const u8,
+    num: c::size_t,
+) {
+    block_data_order(&mut state.keccak, data, num, SHA3_512_RATE)
+}
+
+unsafe fn block_data_order(
+    state: &mut [u64; STATE_LANES],
+    data: *const u8,
+    num: c::size_t,
+    rate: usize,
+) {
+    let data = core::slice::from_raw_parts(data, num * rate);
+    for block in data.chunks_exact(rate) {
+        absorb_block(state, block);
+    }
+}
+
+pub(super) fn format_output(input: super::State) -> super::Output {
+    let input = unsafe { &input.keccak };
+    let mut output = super::Output {
+        as64: [BigEndian::ZERO; 8],
+    };
+    // The digest is the first bytes of the state, which is little-endian.
+    for (out, lane) in unsafe { output.as64.iter_mut() }.zip(input.iter()) {
+        *out = BigEndian::from(u64::from_be_bytes(lane.to_le_bytes()));
+    }
+    output
+}
+
+/// Pads the final, partial, block of a SHA-3 digest, which is
+/// `block[..num_pending]`. `block` is exactly one block long.
+pub(super) fn pad(block: &mut [u8], num_pending: usize) {
+    pad_(block, num_pending, SHA3_PAD)
+}
+
+fn pad_(block: &mut [u8], num_pending: usize, domain: u8) {
+    for b in &mut block[num_pending..] {
+        *b = 0;
+    }
+    block[num_pending] ^= domain;
+    let last = block.len() - 1;
+    block[last] ^= 0x80;
+}
+
+fn absorb_block(state: &mut [u64; STATE_LANES], block: &[u8]) {
+    for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+        let mut le = [0u8; 8];
+        le.copy_from_slice(bytes);
+        *lane ^= u64::from_le_bytes(le);
+    }
+    keccak_f(state);
+}
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+// The rotation offsets of ρ, in the order in which π visits the lanes,
+// starting with lane 1.
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+// Keccak-f[1600]. Lane (x, y) is `a[x + 5 * y]`.
+fn keccak_f(a: &mut [u64; STATE_LANES]) {
+    for rc in ROUND_CONSTANTS.iter() {
+        // θ
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[x + 5 * y] ^= d;
+            }
+        }
+
+        // ρ and π
+        let mut last = a[1];
+        for (&pi, &rho) in PI.iter().zip(RHO.iter()) {
+            let next = a[pi];
+            a[pi] = last.rotate_left(rho);
+            last = next;
+        }
+
+        // χ
+        for y in 0..5 {
+            let mut row = [0u64; 5];
+            row.copy_from_slice(&a[(5 * y)..][..5]);
+            for x in 0..5 {
+                a[x + 5 * y] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // ι
+        a[0] ^= rc;
+    }
+}
+
+/// The Keccak sponge of a SHAKE context.
+#[derive(Clone)]
+struct Sponge {
+    state: [u64; STATE_LANES],
+    rate: usize,
+
+    // The position within the current block, in bytes, while absorbing or
+    // squeezing.
+    position: usize,
+    squeezing: bool,
+}
+
+impl Sponge {
+    fn new(rate: usize) -> Self {
+        Self {
+            state: [0; STATE_LANES],
+            rate,
+            position: 0,
+            squeezing: false,
+        }
+    }
+
+    fn absorb(&mut self, data: &[u8]) {
+        assert!(!self.squeezing);
+        for &b in data {
+            self.state[self.position / 8] ^= u64::from(b) << (8 * (self.position % 8));
+            self.position += 1;
+            if self.position == self.rate {
+                keccak_f(&mut self.state);
+                self.position = 0;
+            }
+        }
+    }
+
+    fn squeeze(&mut self, out: &mut [u8]) {
+        if !self.squeezing {
+            let mut block = [0u8; SHAKE128_RATE];
+            let block = &mut block[..self.rate];
+            pad_(block, self.position, SHAKE_PAD);
+            let position = self.position;
+            self.absorb(&block[position..]);
+            self.squeezing = true;
+        }
+        for b in out {
+            if self.position == self.rate {
+                keccak_f(&mut self.state);
+                self.position = 0;
+            }
+            *b = (self.state[self.position / 8] >> (8 * (self.position % 8))) as u8;
+            self.position += 1;
+        }
+    }
+}
+
+macro_rules! shake {
+    ( $name:ident, $rate:expr, $doc:expr ) => {
+        #[doc = $doc]
+        ///
+        /// Input is added with `update()`; then any amount of output can be
+        /// read, in pieces of any size, with `squeeze()`. Reading the output
+        /// in pieces gives the same output as reading it all at once.
+        ///
+        /// [FIPS 202]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+        #[derive(Clone)]
+        pub struct $name {
+            sponge: Sponge,
+        }
+
+        impl $name {
+            /// Constructs a new context.
+            pub fn new() -> Self {
+                Self {
+                    sponge: Sponge::new($rate),
+                }
+            }
+
+            /// Updates the context with all the data in `data`.
+            ///
+            /// Panics if called after `squeeze()`.
+            pub fn update(&mut self, data: &[u8]) {
+                self.sponge.absorb(data)
+            }
+
+            /// Fills `out` with the next `out.len()` bytes of output.
+            pub fn squeeze(&mut self, out: &mut [u8]) {
+                self.sponge.squeeze(out)
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    };
+}
+
+shake!(
+    Shake128,
+    SHAKE128_RATE,
+    "A SHAKE128 context, for the extendable-output function of [FIPS 202]."
+);
+shake!(
+    Shake256,
+    SHAKE256_RATE,
+    "A SHAKE256 context, for the extendable-output function of [FIPS 202]."
+);
@@ -0,0 +1,401 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! DNSSEC RRSIG verification.
+//!
+//! See [RFC 4034]. The supported algorithms are RSA/SHA-256 (8, [RFC 5702]),
+//! ECDSA P-256 with SHA-256 (13) and ECDSA P-384 with SHA-384 (14)
+//! ([RFC 6605]), and Ed25519 (15, [RFC 8080]). As with the rest of *ring*,
+//! RSA keys smaller than 2048 bits are rejected.
+//!
+//! The caller is responsible for assembling the RRset in the canonical form
+//! and order of RFC 4034 Section 6, with the TTL of each RR set to the
+//! RRSIG's original TTL, and for the rest of DNSSEC validation, such as
+//! authenticating the DNSKEY and checking the RRSIG's owner name and labels.
+//!
+//! [RFC 4034]: https://tools.ietf.org/html/rfc4034
+//! [RFC 5702]: https://tools.ietf.org/html/rfc5702
+//! [RFC 6605]: https://tools.ietf.org/html/rfc6605
+//! [RFC 8080]: https://tools.ietf.org/html/rfc8080
+
+use crate::{error, signature};
+use std::vec::Vec;
+use untrusted;
+
+/// A DNSSEC signature algorithm.
+pub struct Algorithm {
+    number: u8,
+    id: AlgorithmID,
+}
+
+derive_debug_via_id!(Algorithm);
+
+#[derive(Debug, Eq, PartialEq)]
+enum AlgorithmID {
+    RSASHA256,
+    ECDSAP256SHA256,
+    ECDSAP384SHA384,
+    #[cfg(feature = "sig-ed25519")]
+    ED25519,
+}
+
+impl PartialEq for Algorithm {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Algorithm {}
+
+impl Algorithm {
+    /// The algorithm number, as registered with IANA.
+    #[inline]
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+}
+
+/// RSA/SHA-256.
+pub static RSASHA256: Algorithm = Algorithm {
+    number: 8,
+    id: AlgorithmID::RSASHA256,
+};
+
+/// ECDSA using the P-256 curve and SHA-256.
+pub static ECDSAP256SHA256: Algorithm = Algorithm {
+    number: 13,
+    id: AlgorithmID::ECDSAP256SHA256,
+};
+
+/// ECDSA using the P-384 curve and SHA-384.
+pub static ECDSAP384SHA384: Algorithm = Algorithm {
+    number: 14,
+    id: AlgorithmID::ECDSAP384SHA384,
+};
+
+/// Ed25519.
+#[cfg(feature = "sig-ed25519")]
+pub static ED25519: Algorithm = Algorithm {
+    number: 15,
+    id: AlgorithmID::ED25519,
+};
+
+static ALL_ALGORITHMS: &[&Algorithm] = &[
+    &RSASHA256,
+    &ECDSAP256SHA256,
+    &ECDSAP384SHA384,
+    #[cfg(feature = "sig-ed25519")]
+    &ED25519,
+];
+
+/// A parsed DNSKEY record.
+#[derive(Clone, Copy, Debug)]
+pub struct Dnskey<'a> {
+    flags: u16,
+    algorithm: &'static Algorithm,
+    public_key: &'a [u8],
+    key_tag: u16,
+}
+
+impl<'a> Dnskey<'a> {
+    /// Parses the RDATA of a DNSKEY record.
+    ///
+    /// Fails if the key uses an unsupported algorithm.
+    pub fn parse(rdata: &'a [u8]) -> Result<Self, error::Unspecified> {
+        let (flags, algorithm, public_key) =
+            untrusted::Input::from(rdata).read_all(error::Unspecified, |input| {
+                let flags = read_u16(input)?;
+                if input.read_byte()? != PROTOCOL {
+                    return Err(error::Unspecified);
+                }
+                let algorithm = read_algorithm(input)?;
+                let public_key = input.read_bytes_to_end().as_slice_less_safe();
+                Ok((flags, algorithm, public_key))
+            })?;
+        Ok(Self {
+            flags,
+            algorithm,
+            public_key,
+            key_tag: key_tag(rdata),
+        })
+    }
+
+    /// The flags.
+    #[inline]
+    pub fn flags(&self) -> u16 {
+        self.flags
+    }
+
+    /// The algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+
+    /// The key tag, computed as described in RFC 4034 Appendix B.
+    #[inline]
+    pub fn key_tag(&self) -> u16 {
+        self.key_tag
+    }
+
+    fn verify_signature(&self, msg: &[u8], signature: &[u8]) -> Result<(), error::Unspecified> {
+        match self.algorithm.id {
+            AlgorithmID::RSASHA256 => {
+                let (e, n) = parse_rsa_public_key(self.public_key)?;
+                signature::RsaPublicKeyComponents { n, e }.verify(
+                    &signature::RSA_PKCS1_2048_8192_SHA256,
+                    msg,
+                    signature,
+                )
+            }
+            AlgorithmID::ECDSAP256SHA256 => verify_ecdsa(
+                &signature::ECDSA_P256_SHA256_FIXED,
+                32,
+                self.public_key,
+                msg,
+                signature,
+            ),
+            AlgorithmID::ECDSAP384SHA384 => verify_ecdsa(
+                &signature::ECDSA_P384_SHA384_FIXED,
+                48,
+                self.public_key,
+                msg,
+                signature,
+            ),
+            #[cfg(feature = "sig-ed25519")]
+            AlgorithmID::ED25519 => {
+                signature::UnparsedPublicKey::new(&signature::ED25519, self.public_key)
+                    .verify(msg, signature)
+            }
+        }
+    }
+}
+
+/// A parsed RRSIG record.
+#[derive(Clone, Copy, Debug)]
+pub struct Rrsig<'a> {
+    type_covered: u16,
+    algorithm: &'static Algorithm,
+    labels: u8,
+    original_ttl: u32,
+    expiration: u32,
+    inception: u32,
+    key_tag: u16,
+    signer_name: &'a [u8],
+    signed_rdata: &'a [u8],
+    signature: &'a [u8],
+}
+
+impl<'a> Rrsig<'a> {
+    /// Parses the RDATA of an RRSIG record.
+    ///
+    /// The signer's name must be uncompressed and, as required by RFC 4034
+    /// Section 3.1.7, in lowercase. Fails if the signature uses an
+    /// unsupported algorithm.
+    pub fn parse(rdata: &'a [u8]) -> Result<Self, error::Unspecified> {
+        untrusted::Input::from(rdata).read_all(error::Unspecified, |input| {
+            let type_covered = read_u16(input)?;
+            let algorithm = read_algorithm(input)?;
+            let labels = input.read_byte()?;
+            let original_ttl = read_u32(input)?;
+            let expiration = read_u32(input)?;
+            let inception = read_u32(input)?;
+            let key_tag = read_u16(input)?;
+            let rest = input.read_bytes_to_end().as_slice_less_safe();
+            let (signer_name, signature) = rest.split_at(name_len(rest)?);
+            Ok(Self {
+                type_covered,
+                algorithm,
+                labels,
+                original_ttl,
+                expiration,
+                inception,
+                key_tag,
+                signer_name,
+                signed_rdata: &rdata[..(rdata.len() - signature.len())],
+                signature,
+            })
+        })
+    }
+
+    /// The type of the RRset that is covered.
+    #[inline]
+    pub fn type_covered(&self) -> u16 {
+        self.type_covered
+    }
+
+    /// The algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+
+    /// The number of labels in the original owner name.
+    #[inline]
+    pub fn labels(&self) -> u8 {
+        self.labels
+    }
+
+    /// The TTL of the covered RRset as it appears in the zone.
+    #[inline]
+    pub fn original_ttl(&self) -> u32 {
+        self.original_ttl
+    }
+
+    /// The key tag of the DNSKEY that made the signature.
+    #[inline]
+    pub fn key_tag(&self) -> u16 {
+        self.key_tag
+    }
+
+    /// The signer's name, in wire format.
+    #[inline]
+    pub fn signer_name(&self) -> &'a [u8] {
+        self.signer_name
+    }
+
+    /// Verifies that the RRSIG is a signature over `rrset` by `dnskey` that
+    /// is valid at `now`, which is the current time in seconds since the
+    /// Unix epoch, truncated to 32 bits.
+    ///
+    /// `rrset` is the concatenation of the RRs of the RRset in canonical form
+    /// and order. `dnskey` must be a zone key using the RRSIG's algorithm
+    /// and key tag.
+    pub fn verify(
+        &self,
+        dnskey: &Dnskey,
+        rrset: &[u8],
+        now: u32,
+    ) -> Result<(), error::Unspecified> {
+        if dnskey.flags & FLAG_ZONE_KEY == 0
+            || dnskey.algorithm != self.algorithm
+            || dnskey.key_tag != self.key_tag
+        {
+            return Err(error::Unspecified);
+        }
+
+        // The times use serial number arithmetic (RFC 1982), so they are
+        // compared using the sign of the 32-bit difference.
+        let elapsed_since_inception = now.wrapping_sub(self.inception) as i32;
+        let remaining_until_expiration = self.expiration.wrapping_sub(now) as i32;
+        if elapsed_since_inception < 0 || remaining_until_expiration < 0 {
+            return Err(error::Unspecified);
+        }
+
+        let mut signed_data = Vec::with_capacity(self.signed_rdata.len() + rrset.len());
+        signed_data.extend_from_slice(self.signed_rdata);
+        signed_data.extend_from_slice(rrset);
+        dnskey.verify_signature(&signed_data, self.signature)
+    }
+}
+
+/// Computes the key tag of the DNSKEY record with RDATA `rdata`, as described
+/// in RFC 4034 Appendix B.
+pub fn key_tag(rdata: &[u8]) -> u16 {
+    let mut accumulator = 0u32;
+    for (i, b) in rdata.iter().enumerate() {
+        accumulator += if i & 1 == 0 {
+            u32::from(*b) << 8
+        } else {
+            u32::from(*b)
+        };
+    }
+    accumulator += (accumulator >> 16) & 0xffff;
+    accumulator as u16
+}
+
+const PROTOCOL: u8 = 3;
+const FLAG_ZONE_KEY: u16 = 0x0100;
+
+// RFC 3110 Section 2: the exponent length is one byte, or, if that byte is
+// zero, the following two bytes. Returns the exponent and the modulus.
+fn parse_rsa_public_key(public_key: &[u8]) -> Result<(&[u8], &[u8]), error::Unspecified> {
+    untrusted::Input::from(public_key).read_all(error::Unspecified, |input| {
+        let exponent_len = match input.read_byte()? {
+            0 => usize::from(read_u16(input)?),
+            len => usize::from(len),
+        };
+        let exponent = input.read_bytes(exponent_len)?;
+        let modulus = input.read_bytes_to_end();
+        Ok((exponent.as_slice_less_safe(), modulus.as_slice_less_safe()))
+    })
+}
+
+// RFC 6605 Section 4: the public key is the point without the leading 0x04
+// byte, and the signature is `r` followed by `s`, as in the `_FIXED`
+// algorithms.
+fn verify_ecdsa(
+    alg: &'static signature::EcdsaVerificationAlgorithm,
+    elem_len: usize,
+    public_key: &[u8],
+    msg: &[u8],
+    signature: &[u8],
+) -> Result<(), error::Unspecified> {
+    if public_key.len() != 2 * elem_len {
+        return Err(error::Unspecified);
+    }
+    let mut uncompressed = [0u8; 1 + (2 * 48)];
+    let uncompressed = &mut uncompressed[..(1 + public_key.len())];
+    uncompressed[0] = 0x04;
+    uncompressed[1..].copy_from_slice(public_key);
+    signature::UnparsedPublicKey::new(alg, uncompressed).verify(msg, signature)
+}
+
+fn read_algorithm(input: &mut untrusted::Reader) -> Result<&'static Algorithm, error::Unspecified> {
+    let number = input.read_byte()?;
+    ALL_ALGORITHMS
+        .iter()
+        .find(|alg| alg.number == number)
+        .cloned()
+        .ok_or(error::Unspecified)
+}
+
+// Returns the length of the uncompressed, lowercase domain name in wire format
+// at the start of `input`.
+fn name_len(input: &[u8]) -> Result<usize, error::Unspecified> {
+    const MAX_NAME_LEN: usize = 255;
+    let mut len = 0;
+    loop {
+        let label_len = usize::from(*input.get(len).ok_or(error::Unspecified)?);
+        // Compression pointers and extended label types are not allowed.
+        if label_len > 63 {
+            return Err(error::Unspecified);
+        }
+        let label = input
+            .get((len + 1)..(len + 1 + label_len))
+            .ok_or(error::Unspecified)?;
+        if label.iter().any(|b| b.is_ascii_uppercase()) {
+            return Err(error::Unspecified);
+        }
+        len += 1 + label_len;
+        if len > MAX_NAME_LEN {
+            return Err(error::Unspecified);
+        }
+        if label_len == 0 {
+            return Ok(len);
+        }
+    }
+}
+
+fn read_u16(input: &mut untrusted::Reader) -> Result<u16, error::Unspecified> {
+    let hi = u16::from(input.read_byte()?);
+    let lo = u16::from(input.read_byte()?);
+    Ok((hi << 8) | lo)
+}
+
+fn read_u32(input: &mut untrusted::Reader) -> Result<u32, error::Unspecified> {
+    let hi = u32::from(read_u16(input)?);
+    let lo = u32::from(read_u16(input)?);
+    Ok((hi << 16) | lo)
+}
@@ -0,0 +1,232 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! CTR_DRBG, as specified in [NIST SP 800-90A Rev. 1], using AES-256 and
+//! without a derivation function.
+//!
+//! This is the same configuration that BoringSSL uses. Without a derivation
+//! function, entropy input must be full-entropy and exactly `ENTROPY_LEN`
+//! bytes long; personalization strings and additional input may be up to
+//! `MAX_ADDITIONAL_INPUT_LEN` bytes long.
+//!
+//! ```
+//! use ring::{drbg, rand::{self, SecureRandom}};
+//!
+//! let system_random = rand::SystemRandom::new();
+//! let drbg = drbg::CtrDrbg::new(&system_random, b"my application")?;
+//!
+//! let mut key = [0u8; 32];
+//! drbg.fill(&mut key)?;
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [NIST SP 800-90A Rev. 1]:
+//!     https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-90Ar1.pdf
+
+use crate::{
+    aead::{aes, block::Block},
+    cpu, error, rand, sealed,
+    zeroize::Zeroize,
+};
+use core::cell::RefCell;
+
+/// The length of the entropy input to `CtrDrbg::instantiate()` and
+/// `CtrDrbg::reseed()`, which is the seed length of AES-256 CTR_DRBG.
+pub const ENTROPY_LEN: usize = KEY_LEN + BLOCK_LEN;
+
+/// The maximum length of a personalization string or additional input.
+pub const MAX_ADDITIONAL_INPUT_LEN: usize = ENTROPY_LEN;
+
+/// The maximum number of bytes that a single call to `CtrDrbg::generate()`
+/// may return.
+pub const MAX_REQUEST_LEN: usize = 1 << 16;
+
+/// The number of calls to `generate()` after which the DRBG must be reseeded.
+const RESEED_INTERVAL: u64 = 1 << 48;
+
+const KEY_LEN: usize = 32;
+const BLOCK_LEN: usize = 16;
+
+/// An AES-256 CTR_DRBG.
+///
+/// A `CtrDrbg` is not thread-safe; use a separate instance for each thread.
+pub struct CtrDrbg<'a> {
+    state: RefCell<State>,
+    entropy_source: Option<&'a dyn rand::SecureRandom>,
+}
+
+impl<'a> CtrDrbg<'a> {
+    /// Instantiates a DRBG seeded from `entropy_source`, which should be a
+    /// `rand::SystemRandom` or another source of full-entropy input.
+    ///
+    /// The DRBG reseeds itself from `entropy_source` automatically whenever
+    /// the reseed interval is reached.
+    pub fn new(
+        entropy_source: &'a dyn rand::SecureRandom,
+        personalization: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        let mut entropy = [0u8; ENTROPY_LEN];
+        entropy_source.fill(&mut entropy)?;
+        let mut drbg = Self::instantiate(&entropy, personalization)?;
+        entropy.zeroize();
+        drbg.entropy_source = Some(entropy_source);
+        Ok(drbg)
+    }
+
+    /// Instantiates a DRBG with the given entropy input.
+    ///
+    /// A DRBG constructed this way must be reseeded explicitly with
+    /// `reseed()`. This is mostly useful for known-answer tests.
+    pub fn instantiate(
+        entropy: &[u8; ENTROPY_LEN],
+        personalization: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        let mut state = State {
+            key: aes::Key::new(&[0u8; KEY_LEN], aes::Variant::AES_256, cpu::features())?,
+            v: [0u8; BLOCK_LEN],
+            reseed_counter: 1,
+        };
+        state.update(&seed_material(entropy, personalization)?);
+        Ok(Self {
+            state: RefCell::new(state),
+            entropy_source: None,
+        })
+    }
+
+    /// Reseeds the DRBG with the given entropy input and additional input.
+    pub fn reseed(
+        &self,
+        entropy: &[u8; ENTROPY_LEN],
+        additional_input: &[u8],
+    ) -> Result<(), error::Unspecified> {
+        let mut state = self.state.borrow_mut();
+        state.update(&seed_material(entropy, additional_input)?);
+        state.reseed_counter = 1;
+        Ok(())
+    }
+
+    /// Fills `out` with pseudo-random bytes, mixing in `additional_input`.
+    ///
+    /// `out` may be at most `MAX_REQUEST_LEN` bytes long. Fails if the DRBG
+    /// must be reseeded and it has no entropy source to reseed itself from.
+    pub fn generate(
+        &self,
+        out: &mut [u8],
+        additional_input: &[u8],
+    ) -> Result<(), error::Unspecified> {
+        if out.len() > MAX_REQUEST_LEN {
+            return Err(error::Unspecified);
+        }
+        let mut padded_input = pad(additional_input)?;
+
+        if self.state.borrow().reseed_counter > RESEED_INTERVAL {
+            let entropy_source = self.entropy_source.ok_or(error::Unspecified)?;
+            let mut entropy = [0u8; ENTROPY_LEN];
+            entropy_source.fill(&mut entropy)?;
+            self.reseed(&entropy, &[])?;
+            entropy.zeroize();
+        }
+
+        let mut state = self.state.borrow_mut();
+        if !additional_input.is_empty() {
+            state.update(&padded_input);
+        }
+        for chunk in out.chunks_mut(BLOCK_LEN) {
+            let block = state.next_block();
+            chunk.copy_from_slice(&block.as_ref()[..chunk.len()]);
+        }
+        state.update(&padded_input);
+        state.reseed_counter += 1;
+        padded_input.zeroize();
+        Ok(())
+    }
+}
+
+impl rand::SecureRandom for CtrDrbg<'_> {
+    fn fill(&self, dest: &mut [u8]) -> Result<(), error::Unspecified> {
+        for chunk in dest.chunks_mut(MAX_REQUEST_LEN) {
+            self.generate(chunk, &[])?;
+        }
+        Ok(())
+    }
+}
+
+impl sealed::Sealed for CtrDrbg<'_> {}
+
+impl core::fmt::Debug for CtrDrbg<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("CtrDrbg").finish()
+    }
+}
+
+struct State {
+    key: aes::Key,
+    v: [u8; BLOCK_LEN],
+    reseed_counter: u64,
+}
+
+impl State {
+    // Increments V and returns its encryption.
+    fn next_block(&mut self) -> Block {
+        self.v = u128::from_be_bytes(self.v).wrapping_add(1).to_be_bytes();
+        self.key.encrypt_block(Block::from(&self.v))
+    }
+
+    // CTR_DRBG_Update.
+    fn update(&mut self, provided_data: &[u8; ENTROPY_LEN]) {
+        let mut temp = [0u8; ENTROPY_LEN];
+        for (chunk, data) in temp
+            .chunks_mut(BLOCK_LEN)
+            .zip(provided_data.chunks(BLOCK_LEN))
+        {
+            let block = self.next_block();
+            for ((t, b), d) in chunk.iter_mut().zip(block.as_ref()).zip(data) {
+                *t = b ^ d;
+            }
+        }
+        // `KEY_LEN` is a valid AES-256 key length.
+        self.key = aes::Key::new(&temp[..KEY_LEN], aes::Variant::AES_256, cpu::features()).unwrap();
+        self.v.copy_from_slice(&temp[KEY_LEN..]);
+        temp.zeroize();
+    }
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        self.v.zeroize();
+    }
+}
+
+fn seed_material(
+    entropy: &[u8; ENTROPY_LEN],
+    input: &[u8],
+) -> Result<[u8; ENTROPY_LEN], error::Unspecified> {
+    let mut seed_material = pad(input)?;
+    for (s, e) in seed_material.iter_mut().zip(entropy.iter()) {
+        *s ^= e;
+    }
+    Ok(seed_material)
+}
+
+// Pads a personalization string or additional input with zeros to the seed
+// length.
+fn pad(input: &[u8]) -> Result<[u8; ENTROPY_LEN], error::Unspecified> {
+    if input.len() > MAX_ADDITIONAL_INPUT_LEN {
+        return Err(error::Unspecified);
+    }
+    let mut padded = [0u8; ENTROPY_LEN];
+    padded[..input.len()].copy_from_slice(input);
+    Ok(padded)
+}
@@ -37,11 +37,14 @@ derive_debug_via_id!(Curve);
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum CurveID {
     Curve25519,
+    Curve448,
+    K256,
     P256,
     P384,
+    P521,
 }
 
-const ELEM_MAX_BITS: usize = 384;
+const ELEM_MAX_BITS: usize = 521;
 pub const ELEM_MAX_BYTES: usize = (ELEM_MAX_BITS + 7) / 8;
 
 pub const SCALAR_MAX_BYTES: usize = ELEM_MAX_BYTES;
@@ -52,11 +55,14 @@ const SEED_MAX_BYTES: usize = ELEM_MAX_BYTES;
 /// This is NOT the maximum length of a PKCS#8 document that can be consumed by
 /// `pkcs8::unwrap_key()`.
 ///
-/// `40` is the length of the P-384 template. It is actually one byte shorter
-/// than the P-256 template, but the private key and the public key are much
-/// longer.
-pub const PKCS8_DOCUMENT_MAX_LEN: usize = 40 + SCALAR_MAX_BYTES + keys::PUBLIC_KEY_MAX_LEN;
+/// `42` is the length of the P-521 template. It is actually a few bytes
+/// longer than the P-256 and P-384 templates, and the private key and the
+/// public key are longer too.
+pub const PKCS8_DOCUMENT_MAX_LEN: usize = 42 + SCALAR_MAX_BYTES + keys::PUBLIC_KEY_MAX_LEN;
 
 pub mod curve25519;
+pub mod curve448;
+#[cfg(feature = "use_heap")]
+pub mod hash_to_curve;
 mod keys;
 pub mod suite_b;
@@ -14,7 +14,14 @@
 
 //! Elliptic curve operations and schemes using Curve25519.
 
+#[cfg(feature = "sig-ed25519")]
 pub mod ed25519;
+#[cfg(feature = "use_heap")]
+pub mod hash_to_curve;
+#[cfg(feature = "use_heap")]
+pub mod spake2;
+#[cfg(feature = "use_heap")]
+pub mod vrf;
 pub mod x25519;
 
 mod ops;
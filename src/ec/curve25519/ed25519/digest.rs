@@ -1,9 +1,55 @@
-use crate::digest;
+use crate::{digest, error};
 
 use super::super::ops::{Scalar, UnreducedScalar, SCALAR_LEN};
 
-pub fn eddsa_digest(signature_r: &[u8], public_key: &[u8], msg: &[u8]) -> digest::Digest {
+/// The dom2(phflag, context) prefix of RFC 8032 Section 5.1, which is hashed
+/// into every digest of Ed25519ctx and Ed25519ph, but not of Ed25519.
+#[derive(Clone, Copy)]
+pub struct Dom2<'a> {
+    phflag: u8,
+    context: &'a [u8],
+}
+
+impl<'a> Dom2<'a> {
+    /// Ed25519ctx, which requires a non-empty context (RFC 8032 Section 8.3).
+    pub fn ctx(context: &'a [u8]) -> Result<Self, error::Unspecified> {
+        if context.is_empty() {
+            return Err(error::Unspecified);
+        }
+        Self::new(0, context)
+    }
+
+    /// Ed25519ph, for which the context may be empty.
+    pub fn ph(context: &'a [u8]) -> Result<Self, error::Unspecified> {
+        Self::new(1, context)
+    }
+
+    fn new(phflag: u8, context: &'a [u8]) -> Result<Self, error::Unspecified> {
+        if context.len() > MAX_CONTEXT_LEN {
+            return Err(error::Unspecified);
+        }
+        Ok(Self { phflag, context })
+    }
+
+    pub fn update(&self, ctx: &mut digest::Context) {
+        ctx.update(b"SigEd25519 no Ed25519 collisions");
+        ctx.update(&[self.phflag, self.context.len() as u8]);
+        ctx.update(self.context);
+    }
+}
+
+const MAX_CONTEXT_LEN: usize = 255;
+
+pub fn eddsa_digest(
+    dom2: Option<Dom2>,
+    signature_r: &[u8],
+    public_key: &[u8],
+    msg: &[u8],
+) -> digest::Digest {
     let mut ctx = digest::Context::new(&digest::SHA512);
+    if let Some(dom2) = dom2 {
+        dom2.update(&mut ctx);
+    }
     ctx.update(signature_r);
     ctx.update(public_key);
     ctx.update(msg);
@@ -18,10 +18,11 @@ use super::{super::ops::*, ED25519_PUBLIC_KEY_LEN};
 use crate::{
     digest, error,
     io::der,
-    pkcs8,
+    pkcs8, policy,
     polyfill::convert::*,
     rand,
-    signature::{self, KeyPair as SigningKeyPair},
+    signature,
+    zeroize::Zeroize,
 };
 use untrusted;
 
@@ -29,6 +30,10 @@ use super::digest::*;
 
 /// An Ed25519 key pair, for signing.
 pub struct Ed25519KeyPair {
+    // RFC 8032 Section 5.1.5 calls this *k*. It is kept only so that the key
+    // pair can be exported.
+    seed: Seed,
+
     // RFC 8032 Section 5.1.6 calls this *s*.
     private_scalar: Scalar,
 
@@ -53,14 +58,59 @@ impl Ed25519KeyPair {
     pub fn generate_pkcs8(
         rng: &dyn rand::SecureRandom,
     ) -> Result<pkcs8::Document, error::Unspecified> {
+        policy::check(FIPS_APPROVED)?;
         let mut seed = [0u8; SEED_LEN];
         rng.fill(&mut seed)?;
-        let key_pair = Self::from_seed_(&seed);
-        Ok(pkcs8::wrap_key(
-            &PKCS8_TEMPLATE,
-            &seed[..],
-            key_pair.public_key().as_ref(),
-        ))
+        Ok(Self::pkcs8_from_seed(&seed))
+    }
+
+    /// Like `generate_pkcs8()`, but doesn't block the executor while the
+    /// system random number generator is not yet ready. See
+    /// `rand::fill_async()`.
+    #[cfg(feature = "async")]
+    pub async fn generate_pkcs8_async<R: rand::SecureRandom + ?Sized>(
+        rng: &R,
+    ) -> Result<pkcs8::Document, error::Unspecified> {
+        policy::check(FIPS_APPROVED)?;
+        let mut seed = [0u8; SEED_LEN];
+        rand::fill_async(rng, &mut seed).await?;
+        Ok(Self::pkcs8_from_seed(&seed))
+    }
+
+    fn pkcs8_from_seed(seed: &Seed) -> pkcs8::Document {
+        Self::from_seed_(seed).to_pkcs8()
+    }
+
+    /// Serializes the key pair as an unencrypted PKCS#8 v2
+    /// `OneAsymmetricKey` with the public key, in the same format as
+    /// `generate_pkcs8()`.
+    ///
+    /// The result can be parsed with `Ed25519KeyPair::from_pkcs8()`.
+    pub fn to_pkcs8(&self) -> pkcs8::Document {
+        pkcs8::wrap_key(&PKCS8_TEMPLATE, &self.seed[..], self.public_key.as_ref())
+    }
+
+    /// Serializes the key pair as an unencrypted PKCS#8 v1 `PrivateKeyInfo`,
+    /// as described in [RFC 8410 Section 7], for systems that don't accept
+    /// PKCS#8 v2.
+    ///
+    /// The public key isn't included, so prefer `to_pkcs8()` when possible.
+    /// The result can be parsed with
+    /// `Ed25519KeyPair::from_pkcs8_maybe_unchecked()`.
+    ///
+    /// [RFC 8410 Section 7]: https://tools.ietf.org/html/rfc8410#section-7
+    pub fn to_pkcs8v1(&self) -> pkcs8::Document {
+        pkcs8::wrap_key(&PKCS8_V1_TEMPLATE, &self.seed[..], &[])
+    }
+
+    /// Returns the 32-byte private key seed.
+    ///
+    /// This is the inverse of `Ed25519KeyPair::from_seed_unchecked()`. The
+    /// seed alone carries no public key, so whoever imports it can't detect
+    /// misuse or corruption of the key; prefer `to_pkcs8()`. The seed is the
+    /// private key, so it must be protected like one.
+    pub fn export_seed_unchecked(&self) -> &[u8] {
+        &self.seed[..]
     }
 
     /// Constructs an Ed25519 key pair by parsing an unencrypted PKCS#8 v2
@@ -148,6 +198,7 @@ impl Ed25519KeyPair {
     /// the private key. It is not possible to detect misuse or corruption of
     /// the private key since the public key isn't given as input.
     pub fn from_seed_unchecked(seed: &[u8]) -> Result<Self, error::KeyRejected> {
+        policy::check(FIPS_APPROVED)?;
         let seed = seed
             .try_into_()
             .map_err(|_| error::KeyRejected::invalid_encoding())?;
@@ -165,12 +216,10 @@ impl Ed25519KeyPair {
         let mut prefix = [0u8; PREFIX_LEN];
         prefix.copy_from_slice(prefix_encoded);
 
-        let mut a = ExtPoint::new_at_infinity();
-        unsafe {
-            GFp_x25519_ge_scalarmult_base(&mut a, &scalar);
-        }
+        let a = ExtPoint::from_scalarmult_base(&scalar);
 
         Self {
+            seed: *seed,
             private_scalar: scalar,
             private_prefix: prefix,
             public_key: PublicKey(a.into_encoded_point()),
@@ -179,24 +228,74 @@ impl Ed25519KeyPair {
 
     /// Returns the signature of the message `msg`.
     pub fn sign(&self, msg: &[u8]) -> signature::Signature {
+        self.sign_(None, msg)
+    }
+
+    /// Returns the Ed25519ctx signature of the message `msg` in the context
+    /// `context`, as specified in [RFC 8032 Section 5.1].
+    ///
+    /// Fails unless `context` is 1 to 255 bytes long. Use
+    /// `signature::ED25519.verify_ctx` to verify the signature.
+    ///
+    /// [RFC 8032 Section 5.1]: https://tools.ietf.org/html/rfc8032#section-5.1
+    pub fn sign_ctx(
+        &self,
+        context: &[u8],
+        msg: &[u8],
+    ) -> Result<signature::Signature, error::Unspecified> {
+        Ok(self.sign_(Some(Dom2::ctx(context)?), msg))
+    }
+
+    /// Returns the Ed25519ph signature of the message whose SHA-512 digest is
+    /// `msg_digest`, in the context `context`, as specified in
+    /// [RFC 8032 Section 5.1].
+    ///
+    /// Since only the digest of the message is needed, the message can be
+    /// hashed incrementally with `digest::Context`, without holding all of it
+    /// in memory.
+    ///
+    /// Fails if `msg_digest` isn't a SHA-512 digest or if `context` is longer
+    /// than 255 bytes. Use `signature::ED25519.verify_prehashed` to verify the
+    /// signature.
+    ///
+    /// [RFC 8032 Section 5.1]: https://tools.ietf.org/html/rfc8032#section-5.1
+    pub fn sign_prehashed(
+        &self,
+        context: &[u8],
+        msg_digest: &digest::Digest,
+    ) -> Result<signature::Signature, error::Unspecified> {
+        if msg_digest.algorithm() != &digest::SHA512 {
+            return Err(error::Unspecified);
+        }
+        Ok(self.sign_(Some(Dom2::ph(context)?), msg_digest.as_ref()))
+    }
+
+    fn sign_(&self, dom2: Option<Dom2>, msg: &[u8]) -> signature::Signature {
+        #[cfg(feature = "audit")]
+        crate::audit::record_public_key(
+            &super::verification::ED25519,
+            crate::audit::Operation::Sign,
+            self.public_key.as_ref(),
+            msg.len(),
+        );
         signature::Signature::new(|signature_bytes| {
             let (signature_bytes, _unused) = signature_bytes.into_();
             // Borrow `signature_bytes`.
             let (signature_r, signature_s) = signature_bytes.into_();
             let nonce = {
                 let mut ctx = digest::Context::new(&digest::SHA512);
+                if let Some(dom2) = dom2 {
+                    dom2.update(&mut ctx);
+                }
                 ctx.update(&self.private_prefix);
                 ctx.update(msg);
                 ctx.finish()
             };
             let nonce = digest_scalar(nonce);
 
-            let mut r = ExtPoint::new_at_infinity();
-            unsafe {
-                GFp_x25519_ge_scalarmult_base(&mut r, &nonce);
-            }
+            let r = ExtPoint::from_scalarmult_base(&nonce);
             *signature_r = r.into_encoded_point();
-            let hram_digest = eddsa_digest(signature_r, &self.public_key.as_ref(), msg);
+            let hram_digest = eddsa_digest(dom2, signature_r, &self.public_key.as_ref(), msg);
             let hram = digest_scalar(hram_digest);
             unsafe {
                 GFp_x25519_sc_muladd(signature_s, &hram, &self.private_scalar, &nonce);
@@ -207,6 +306,14 @@ impl Ed25519KeyPair {
     }
 }
 
+impl Drop for Ed25519KeyPair {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+        self.private_scalar.zeroize();
+        self.private_prefix.zeroize();
+    }
+}
+
 impl signature::KeyPair for Ed25519KeyPair {
     type PublicKey = PublicKey;
 
@@ -215,6 +322,29 @@ impl signature::KeyPair for Ed25519KeyPair {
     }
 }
 
+#[cfg(feature = "use_heap")]
+impl signature::Signer for Ed25519KeyPair {
+    fn public_key_bytes(&self) -> &[u8] {
+        self.public_key.as_ref()
+    }
+
+    fn supports(&self, scheme: signature::SignatureScheme) -> bool {
+        scheme == signature::SignatureScheme::ED25519
+    }
+
+    fn sign(
+        &self,
+        scheme: signature::SignatureScheme,
+        _rng: &dyn rand::SecureRandom,
+        message: &[u8],
+    ) -> Result<std::vec::Vec<u8>, error::Unspecified> {
+        if !signature::Signer::supports(self, scheme) {
+            return Err(error::Unspecified);
+        }
+        Ok(Ed25519KeyPair::sign(self, message).as_ref().to_vec())
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct PublicKey([u8; ED25519_PUBLIC_KEY_LEN]);
 
@@ -240,7 +370,6 @@ fn unwrap_pkcs8(
 }
 
 extern "C" {
-    fn GFp_x25519_ge_scalarmult_base(h: &mut ExtPoint, a: &Seed);
     fn GFp_x25519_sc_mask(a: &mut Scalar);
     fn GFp_x25519_sc_muladd(s: &mut Scalar, a: &Scalar, b: &Scalar, c: &Scalar);
 }
@@ -253,6 +382,9 @@ const SIGNATURE_LEN: usize = ELEM_LEN + SCALAR_LEN;
 type Seed = [u8; SEED_LEN];
 const SEED_LEN: usize = 32;
 
+// Ed25519 isn't allowed by `policy::Policy::FipsApproved`.
+const FIPS_APPROVED: bool = false;
+
 static PKCS8_TEMPLATE: pkcs8::Template = pkcs8::Template {
     bytes: include_bytes!("ed25519_pkcs8_v2_template.der"),
     alg_id_range: core::ops::Range { start: 7, end: 12 },
@@ -260,4 +392,11 @@ static PKCS8_TEMPLATE: pkcs8::Template = pkcs8::Template {
     private_key_index: 0x10,
 };
 
+static PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
+    bytes: include_bytes!("ed25519_pkcs8_v1_template.der"),
+    alg_id_range: core::ops::Range { start: 7, end: 12 },
+    curve_id_index: 0,
+    private_key_index: 0x10,
+};
+
 impl_array_split!(u8, SIGNATURE_LEN, signature::MAX_LEN - SIGNATURE_LEN);
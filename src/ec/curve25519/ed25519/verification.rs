@@ -15,17 +15,28 @@
 //! EdDSA Signatures.
 
 use super::super::ops::*;
-use crate::{error, polyfill::convert::*, sealed, signature};
+use crate::{digest, error, polyfill::convert::*, sealed, signature, spki};
 use untrusted;
 
 use super::digest::*;
 
+use crate::{bssl, c};
+#[cfg(feature = "use_heap")]
+use std::{vec, vec::Vec};
+
 /// Parameters for EdDSA signing and verification.
-pub struct EdDSAParameters;
+pub struct EdDSAParameters {
+    policy: Policy,
+}
 
 impl core::fmt::Debug for EdDSAParameters {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
-        write!(f, "ring::signature::ED25519")
+        let name = match self.policy {
+            Policy::Legacy => "ED25519",
+            Policy::Zip215 => "ED25519_ZIP215",
+            Policy::Strict => "ED25519_STRICT",
+        };
+        write!(f, "ring::signature::{}", name)
     }
 }
 
@@ -33,8 +44,225 @@ impl core::fmt::Debug for EdDSAParameters {
 ///
 /// Ed25519 uses SHA-512 as the digest algorithm.
 ///
+/// Public keys are accepted if they decode to a point on the curve, even if
+/// their encoding isn't canonical. `R` must be the canonical encoding of a
+/// point, the top three bits of `S` must be zero, and the cofactorless
+/// verification equation is checked. Use `ED25519_ZIP215` or
+/// `ED25519_STRICT` when the exact set of valid signatures matters, e.g. for
+/// consensus.
+///
 /// [Ed25519]: https://ed25519.cr.yp.to/
-pub static ED25519: EdDSAParameters = EdDSAParameters {};
+pub static ED25519: EdDSAParameters = EdDSAParameters {
+    policy: Policy::Legacy,
+};
+
+/// Verification of Ed25519 signatures with the [ZIP 215] rules.
+///
+/// Public keys and `R` are accepted if they decode to a point on the curve,
+/// even if their encoding isn't canonical, `S` must be less than the group
+/// order, and the cofactored verification equation
+/// `[8][S]B = [8]R + [8][k]A` is checked, with `k` computed from the
+/// encodings of `R` and `A` as given. These rules are the same for single and
+/// batch verification, so `verify_batch()` accepts exactly the batches in
+/// which every signature is valid.
+///
+/// [ZIP 215]: https://zips.z.cash/zip-0215
+pub static ED25519_ZIP215: EdDSAParameters = EdDSAParameters {
+    policy: Policy::Zip215,
+};
+
+/// Verification of Ed25519 signatures with strict rules.
+///
+/// Public keys and `R` must be canonical encodings of points that are not of
+/// small order, `S` must be less than the group order, as
+/// [RFC 8032 Section 5.1.7] requires, and the cofactorless verification
+/// equation is checked. This is the most restrictive of the three.
+///
+/// [RFC 8032 Section 5.1.7]: https://tools.ietf.org/html/rfc8032#section-5.1.7
+pub static ED25519_STRICT: EdDSAParameters = EdDSAParameters {
+    policy: Policy::Strict,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Policy {
+    Legacy,
+    Zip215,
+    Strict,
+}
+
+impl EdDSAParameters {
+    /// Verifies the Ed25519ctx signature `signature` of `msg` in the context
+    /// `context`, using the public key `public_key`, as specified in
+    /// [RFC 8032 Section 5.1].
+    ///
+    /// Fails unless `context` is 1 to 255 bytes long.
+    ///
+    /// [RFC 8032 Section 5.1]: https://tools.ietf.org/html/rfc8032#section-5.1
+    pub fn verify_ctx(
+        &self,
+        public_key: &[u8],
+        context: &[u8],
+        msg: &[u8],
+        signature: &[u8],
+    ) -> Result<(), error::Unspecified> {
+        #[cfg(feature = "audit")]
+        crate::audit::record_public_key(
+            self,
+            crate::audit::Operation::Verify,
+            public_key,
+            msg.len(),
+        );
+        verify_(
+            self.policy,
+            Some(Dom2::ctx(context)?),
+            public_key,
+            msg,
+            signature,
+        )
+    }
+
+    /// Verifies the Ed25519ph signature `signature` of the message whose
+    /// SHA-512 digest is `msg_digest`, in the context `context`, using the
+    /// public key `public_key`, as specified in [RFC 8032 Section 5.1].
+    ///
+    /// Fails if `msg_digest` isn't a SHA-512 digest or if `context` is longer
+    /// than 255 bytes.
+    ///
+    /// [RFC 8032 Section 5.1]: https://tools.ietf.org/html/rfc8032#section-5.1
+    pub fn verify_prehashed(
+        &self,
+        public_key: &[u8],
+        context: &[u8],
+        msg_digest: &digest::Digest,
+        signature: &[u8],
+    ) -> Result<(), error::Unspecified> {
+        #[cfg(feature = "audit")]
+        crate::audit::record_public_key(
+            self,
+            crate::audit::Operation::Verify,
+            public_key,
+            msg_digest.as_ref().len(),
+        );
+        if msg_digest.algorithm() != &digest::SHA512 {
+            return Err(error::Unspecified);
+        }
+        verify_(
+            self.policy,
+            Some(Dom2::ph(context)?),
+            public_key,
+            msg_digest.as_ref(),
+            signature,
+        )
+    }
+
+    /// Verifies a batch of Ed25519 signatures, given as
+    /// `(public_key, msg, signature)` tuples, at once.
+    ///
+    /// This succeeds only if every signature in the batch is valid. It
+    /// doesn't say which signatures are invalid; use
+    /// `signature::UnparsedPublicKey::verify` to find out. An empty batch
+    /// is valid.
+    ///
+    /// All the verification equations are checked with a single
+    /// multi-scalar multiplication, so this is much faster than verifying
+    /// the signatures one at a time for batches of more than a few
+    /// signatures. The equations are combined using coefficients derived
+    /// from a hash of the whole batch, so the result is deterministic.
+    ///
+    /// Except with `ED25519_ZIP215`, whose verification equation is always
+    /// multiplied by the cofactor, the batch equation is multiplied by the
+    /// cofactor unlike individual verification, so a signature that
+    /// individual verification rejects only because of a small-order
+    /// component, which honest signers never produce, is accepted.
+    #[cfg(feature = "use_heap")]
+    pub fn verify_batch(&self, batch: &[(&[u8], &[u8], &[u8])]) -> Result<(), error::Unspecified> {
+        let mut points = Vec::with_capacity(2 * batch.len());
+        let mut hs_and_ss = Vec::with_capacity(batch.len());
+
+        let mut transcript = digest::Context::new(&digest::SHA512);
+        transcript.update(BATCH_DOMAIN);
+
+        for &(public_key, msg, signature) in batch {
+            #[cfg(feature = "audit")]
+            crate::audit::record_public_key(
+                self,
+                crate::audit::Operation::Verify,
+                public_key,
+                msg.len(),
+            );
+
+            let public_key: &[u8; ELEM_LEN] = public_key.try_into_()?;
+            self.policy.check_public_key(public_key)?;
+            let signature: &[u8; ELEM_LEN + SCALAR_LEN] = signature.try_into_()?;
+            let (signature_r, signature_s): (&[u8; ELEM_LEN], &[u8; SCALAR_LEN]) =
+                signature.into_();
+            self.policy.check_signature(signature_r, signature_s)?;
+
+            // Except with `ED25519_ZIP215`, `verify` compares `signature_r` to
+            // an encoded point, so it only accepts canonical encodings.
+            if self.policy != Policy::Zip215 && !is_canonical_encoding(signature_r) {
+                return Err(error::Unspecified);
+            }
+
+            let mut a = ExtPoint::from_encoded_point_vartime(public_key)?;
+            a.invert_vartime();
+            let mut r = ExtPoint::from_encoded_point_vartime(signature_r)?;
+            r.invert_vartime();
+
+            let h_digest = eddsa_digest(None, signature_r, public_key, msg);
+            let h = digest_scalar(h_digest);
+
+            transcript.update(signature_r);
+            transcript.update(public_key);
+            transcript.update(signature_s);
+            transcript.update(&h);
+
+            points.push(a);
+            points.push(r);
+            hs_and_ss.push((h, *signature_s));
+        }
+
+        let seed = transcript.finish();
+
+        // Check that 8 * ((sum z_i * s_i) * B - sum (z_i * h_i) * A_i -
+        // sum z_i * R_i) is the identity, for 128-bit coefficients z_i.
+        let zero = [0u8; SCALAR_LEN];
+        let mut b_scalar = zero;
+        let mut scalars = Vec::with_capacity(points.len());
+        for (i, (h, s)) in hs_and_ss.iter().enumerate() {
+            let mut ctx = digest::Context::new(&digest::SHA512);
+            ctx.update(seed.as_ref());
+            ctx.update(&(i as u64).to_le_bytes());
+            let mut z = zero;
+            z[..BATCH_COEFFICIENT_LEN]
+                .copy_from_slice(&ctx.finish().as_ref()[..BATCH_COEFFICIENT_LEN]);
+
+            let mut zh = zero;
+            let sum = b_scalar;
+            unsafe {
+                GFp_x25519_sc_muladd(&mut zh, &z, h, &zero);
+                GFp_x25519_sc_muladd(&mut b_scalar, &z, s, &sum);
+            }
+            scalars.push(zh);
+            scalars.push(z);
+        }
+
+        let mut tables = (0..(8 * points.len()))
+            .map(|_| CachedPoint::zero())
+            .collect::<Vec<_>>();
+        let mut slides = vec![0i8; 256 * points.len()];
+        Result::from(unsafe {
+            GFp_x25519_ge_multiscalarmult_cofactor_is_identity_vartime(
+                &b_scalar,
+                scalars.as_ptr(),
+                points.as_ptr(),
+                points.len(),
+                tables.as_mut_ptr(),
+                slides.as_mut_ptr(),
+            )
+        })
+    }
+}
 
 impl signature::VerificationAlgorithm for EdDSAParameters {
     fn verify(
@@ -43,26 +271,174 @@ impl signature::VerificationAlgorithm for EdDSAParameters {
         msg: untrusted::Input,
         signature: untrusted::Input,
     ) -> Result<(), error::Unspecified> {
-        let public_key = public_key.as_slice_less_safe();
-        let public_key: &[u8; ELEM_LEN] = public_key.try_into_()?;;
-        let signature: &[u8; ELEM_LEN + SCALAR_LEN] = signature.as_slice_less_safe().try_into_()?;
-        let (signature_r, signature_s): (&[u8; ELEM_LEN], &[u8; SCALAR_LEN]) = signature.into_();
+        verify_(
+            self.policy,
+            None,
+            public_key.as_slice_less_safe(),
+            msg.as_slice_less_safe(),
+            signature.as_slice_less_safe(),
+        )
+    }
 
-        // Ensure `s` is not too large.
-        if (signature_s[SCALAR_LEN - 1] & 0b11100000) != 0 {
-            return Err(error::Unspecified);
+    fn parse_public_key(
+        &self,
+        public_key: untrusted::Input,
+    ) -> Result<signature::ParsedKey, error::Unspecified> {
+        let public_key = ParsedPublicKey::new(self.policy, public_key.as_slice_less_safe())?;
+        Ok(signature::ParsedKey::Ed25519(public_key))
+    }
+
+    fn verify_parsed(
+        &self,
+        public_key: &signature::ParsedKey,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), error::Unspecified> {
+        match public_key {
+            signature::ParsedKey::Ed25519(public_key) => verify_parsed(
+                self.policy,
+                None,
+                public_key,
+                msg.as_slice_less_safe(),
+                signature.as_slice_less_safe(),
+            ),
+            _ => Err(error::Unspecified),
         }
+    }
+
+    fn fips_approved(&self) -> bool {
+        false
+    }
+}
+
+/// A decoded Ed25519 public key.
+pub struct ParsedPublicKey {
+    encoded: [u8; ELEM_LEN],
+
+    // The negation of the public key point, as needed by `verify_parsed`.
+    minus_a: ExtPoint,
+}
+
+impl ParsedPublicKey {
+    fn new(policy: Policy, public_key: &[u8]) -> Result<Self, error::Unspecified> {
+        let encoded: &[u8; ELEM_LEN] = public_key.try_into_()?;
+        policy.check_public_key(encoded)?;
+        let mut minus_a = ExtPoint::from_encoded_point_vartime(encoded)?;
+        minus_a.invert_vartime();
+        Ok(Self {
+            encoded: *encoded,
+            minus_a,
+        })
+    }
+}
+
+fn verify_(
+    policy: Policy,
+    dom2: Option<Dom2>,
+    public_key: &[u8],
+    msg: &[u8],
+    signature: &[u8],
+) -> Result<(), error::Unspecified> {
+    let public_key = ParsedPublicKey::new(policy, public_key)?;
+    verify_parsed(policy, dom2, &public_key, msg, signature)
+}
+
+fn verify_parsed(
+    policy: Policy,
+    dom2: Option<Dom2>,
+    public_key: &ParsedPublicKey,
+    msg: &[u8],
+    signature: &[u8],
+) -> Result<(), error::Unspecified> {
+    let signature: &[u8; ELEM_LEN + SCALAR_LEN] = signature.try_into_()?;
+    let (signature_r, signature_s): (&[u8; ELEM_LEN], &[u8; SCALAR_LEN]) = signature.into_();
+    policy.check_signature(signature_r, signature_s)?;
+
+    let a = &public_key.minus_a;
+
+    let h_digest = eddsa_digest(dom2, signature_r, &public_key.encoded, msg);
+    let h = digest_scalar(h_digest);
+
+    if policy == Policy::Zip215 {
+        return verify_cofactored(a, signature_r, &h, signature_s);
+    }
+
+    let mut r = Point::new_at_infinity();
+    unsafe { GFp_x25519_ge_double_scalarmult_vartime(&mut r, &h, a, &signature_s) };
+    let r_check = r.into_encoded_point();
+    if *signature_r != r_check {
+        return Err(error::Unspecified);
+    }
+    Ok(())
+}
+
+// Checks that 8 * (s * B - h * A - R) is the identity, given -A.
+fn verify_cofactored(
+    minus_a: &ExtPoint,
+    signature_r: &EncodedPoint,
+    h: &Scalar,
+    signature_s: &Scalar,
+) -> Result<(), error::Unspecified> {
+    let mut minus_r = ExtPoint::from_encoded_point_vartime(signature_r)?;
+    minus_r.invert_vartime();
 
-        let mut a = ExtPoint::from_encoded_point_vartime(public_key)?;
-        a.invert_vartime();
+    let mut one = [0u8; SCALAR_LEN];
+    one[0] = 1;
+    let scalars = [*h, one];
+    let points = [minus_a.clone(), minus_r];
+    let mut tables = [
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+        CachedPoint::zero(),
+    ];
+    let mut slides = [0i8; 256 * 2];
+    Result::from(unsafe {
+        GFp_x25519_ge_multiscalarmult_cofactor_is_identity_vartime(
+            signature_s,
+            scalars.as_ptr(),
+            points.as_ptr(),
+            points.len(),
+            tables.as_mut_ptr(),
+            slides.as_mut_ptr(),
+        )
+    })
+}
 
-        let h_digest = eddsa_digest(signature_r, public_key, msg.as_slice_less_safe());
-        let h = digest_scalar(h_digest);
+impl Policy {
+    fn check_public_key(self, encoded: &EncodedPoint) -> Result<(), error::Unspecified> {
+        if self == Policy::Strict && (!is_canonical_encoding(encoded) || is_small_order(encoded)) {
+            return Err(error::Unspecified);
+        }
+        Ok(())
+    }
 
-        let mut r = Point::new_at_infinity();
-        unsafe { GFp_x25519_ge_double_scalarmult_vartime(&mut r, &h, &a, &signature_s) };
-        let r_check = r.into_encoded_point();
-        if *signature_r != r_check {
+    fn check_signature(
+        self,
+        signature_r: &EncodedPoint,
+        signature_s: &Scalar,
+    ) -> Result<(), error::Unspecified> {
+        let s_is_valid = match self {
+            // Ensure `s` is not too large.
+            Policy::Legacy => (signature_s[SCALAR_LEN - 1] & 0b11100000) == 0,
+            Policy::Zip215 | Policy::Strict => is_reduced_scalar(signature_s),
+        };
+        if !s_is_valid {
+            return Err(error::Unspecified);
+        }
+        if self == Policy::Strict && is_small_order(signature_r) {
             return Err(error::Unspecified);
         }
         Ok(())
@@ -71,6 +447,12 @@ impl signature::VerificationAlgorithm for EdDSAParameters {
 
 impl sealed::Sealed for EdDSAParameters {}
 
+impl spki::PublicKeyAlgorithm for EdDSAParameters {
+    fn public_key_algorithm_id(&self) -> &'static [u8] {
+        include_bytes!("../../../data/alg-ed25519.der")
+    }
+}
+
 extern "C" {
     fn GFp_x25519_ge_double_scalarmult_vartime(
         r: &mut Point,
@@ -78,4 +460,94 @@ extern "C" {
         a: &ExtPoint,
         b_coeff: &Scalar,
     );
+
+    #[cfg(feature = "use_heap")]
+    fn GFp_x25519_sc_muladd(s: &mut Scalar, a: &Scalar, b: &Scalar, c: &Scalar);
+
+    fn GFp_x25519_ge_multiscalarmult_cofactor_is_identity_vartime(
+        b: &Scalar,
+        scalars: *const Scalar,
+        points: *const ExtPoint,
+        num_points: c::size_t,
+        tables: *mut CachedPoint,
+        slides: *mut i8,
+    ) -> bssl::Result;
+}
+
+#[cfg(feature = "use_heap")]
+const BATCH_DOMAIN: &[u8] = b"ring Ed25519 batch verification\0";
+
+#[cfg(feature = "use_heap")]
+const BATCH_COEFFICIENT_LEN: usize = 16;
+
+// Whether `encoded` is the canonical encoding of the point it decodes to, if
+// any: its y coordinate must be reduced, and the sign of x must be positive
+// if x is zero.
+fn is_canonical_encoding(encoded: &EncodedPoint) -> bool {
+    const LAST: usize = ELEM_LEN - 1;
+
+    let mut y = *encoded;
+    let sign = y[LAST] >> 7;
+    y[LAST] &= 0x7f;
+
+    // p = 2**255 - 19.
+    let y_is_reduced = !(y[0] >= 0xed && y[1..LAST].iter().all(|&b| b == 0xff) && y[LAST] == 0x7f);
+
+    // x is zero when y is 1 or p - 1.
+    let y_is_one = y[0] == 1 && y[1..].iter().all(|&b| b == 0);
+    let y_is_minus_one = y[0] == 0xec && y[1..LAST].iter().all(|&b| b == 0xff) && y[LAST] == 0x7f;
+
+    y_is_reduced && !(sign == 1 && (y_is_one || y_is_minus_one))
+}
+
+// Whether `encoded` is the canonical encoding of one of the eight points of
+// small order.
+fn is_small_order(encoded: &EncodedPoint) -> bool {
+    const SMALL_ORDER_POINTS: [EncodedPoint; 8] = [
+        // The identity.
+        [
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ],
+        // Order 2.
+        [
+            0xec, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0x7f,
+        ],
+        // Order 4.
+        [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ],
+        [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x80,
+        ],
+        // Order 8.
+        [
+            0x26, 0xe8, 0x95, 0x8f, 0xc2, 0xb2, 0x27, 0xb0, 0x45, 0xc3, 0xf4, 0x89, 0xf2, 0xef,
+            0x98, 0xf0, 0xd5, 0xdf, 0xac, 0x05, 0xd3, 0xc6, 0x33, 0x39, 0xb1, 0x38, 0x02, 0x88,
+            0x6d, 0x53, 0xfc, 0x05,
+        ],
+        [
+            0x26, 0xe8, 0x95, 0x8f, 0xc2, 0xb2, 0x27, 0xb0, 0x45, 0xc3, 0xf4, 0x89, 0xf2, 0xef,
+            0x98, 0xf0, 0xd5, 0xdf, 0xac, 0x05, 0xd3, 0xc6, 0x33, 0x39, 0xb1, 0x38, 0x02, 0x88,
+            0x6d, 0x53, 0xfc, 0x85,
+        ],
+        [
+            0xc7, 0x17, 0x6a, 0x70, 0x3d, 0x4d, 0xd8, 0x4f, 0xba, 0x3c, 0x0b, 0x76, 0x0d, 0x10,
+            0x67, 0x0f, 0x2a, 0x20, 0x53, 0xfa, 0x2c, 0x39, 0xcc, 0xc6, 0x4e, 0xc7, 0xfd, 0x77,
+            0x92, 0xac, 0x03, 0x7a,
+        ],
+        [
+            0xc7, 0x17, 0x6a, 0x70, 0x3d, 0x4d, 0xd8, 0x4f, 0xba, 0x3c, 0x0b, 0x76, 0x0d, 0x10,
+            0x67, 0x0f, 0x2a, 0x20, 0x53, 0xfa, 0x2c, 0x39, 0xcc, 0xc6, 0x4e, 0xc7, 0xfd, 0x77,
+            0x92, 0xac, 0x03, 0xfa,
+        ],
+    ];
+    SMALL_ORDER_POINTS.iter().any(|point| point == encoded)
 }
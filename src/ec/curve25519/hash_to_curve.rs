@@ -0,0 +1,135 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! The Elligator 2 map to edwards25519 from RFC 9380.
+
+use super::ops::*;
+
+/// The length of the input to `elem_reduced`, *L* in RFC 9380.
+pub const HASHED_ELEM_LEN: usize = 48;
+
+/// Reduces the big-endian number `bytes`, which must be `HASHED_ELEM_LEN`
+/// bytes long, modulo q.
+pub fn elem_reduced(bytes: &[u8]) -> Elem<T> {
+    assert_eq!(bytes.len(), HASHED_ELEM_LEN);
+    // `bytes` is hi * 2**256 + lo. 2**256 ≡ 38 (mod q), and
+    // `Elem::from_bytes` ignores bit 255 of `lo`, which is worth
+    // 2**255 ≡ 19 (mod q).
+    let (hi, lo) = bytes.split_at(HASHED_ELEM_LEN - ELEM_LEN);
+    let mut hi_le = [0u8; ELEM_LEN];
+    for (out, b) in hi_le.iter_mut().zip(hi.iter().rev()) {
+        *out = *b;
+    }
+    let mut lo_le = [0u8; ELEM_LEN];
+    for (out, b) in lo_le.iter_mut().zip(lo.iter().rev()) {
+        *out = *b;
+    }
+    let mut lo_bit_255 = [0u8; ELEM_LEN];
+    lo_bit_255[0] = 19 * (lo_le[ELEM_LEN - 1] >> 7);
+
+    Elem::from_bytes(&hi_le)
+        .mul(&small(38))
+        .add(&Elem::from_bytes(&lo_le))
+        .add(&Elem::from_bytes(&lo_bit_255))
+}
+
+/// `map_to_curve_elligator2_edwards25519` from RFC 9380 Appendix G.2.2,
+/// with `map_to_curve_elligator2_curve25519` from Appendix G.2.1 inlined.
+///
+/// This is constant time with respect to `u`.
+pub fn map_to_curve(u: &Elem<T>) -> ExtPoint {
+    // 2**((q + 3) / 8).
+    const C2: [u8; ELEM_LEN] = [
+        0xb1, 0xa0, 0x0e, 0x4a, 0x27, 0x1b, 0xee, 0xc4, 0x78, 0xe4, 0x2f, 0xad, 0x06, 0x18, 0x43,
+        0x2f, 0xa7, 0xd7, 0xfb, 0x3d, 0x99, 0x00, 0x4d, 0x2b, 0x0b, 0xdf, 0xc1, 0x4f, 0x80, 0x24,
+        0x83, 0x2b,
+    ];
+    // sqrt(-1).
+    const C3: [u8; ELEM_LEN] = [
+        0xb0, 0xa0, 0x0e, 0x4a, 0x27, 0x1b, 0xee, 0xc4, 0x78, 0xe4, 0x2f, 0xad, 0x06, 0x18, 0x43,
+        0x2f, 0xa7, 0xd7, 0xfb, 0x3d, 0x99, 0x00, 0x4d, 0x2b, 0x0b, 0xdf, 0xc1, 0x4f, 0x80, 0x24,
+        0x83, 0x2b,
+    ];
+    // sqrt(-486664), with sgn0 equal to 0.
+    const C1_EDWARDS: [u8; ELEM_LEN] = [
+        0x06, 0x7e, 0x45, 0xff, 0xaa, 0x04, 0x6e, 0xcc, 0x82, 0x1a, 0x7d, 0x4b, 0xd1, 0xd3, 0xa1,
+        0xc5, 0x7e, 0x4f, 0xfc, 0x03, 0xdc, 0x08, 0x7b, 0xd2, 0xbb, 0x06, 0xa0, 0x60, 0xf4, 0xed,
+        0x26, 0x0f,
+    ];
+
+    let c2 = Elem::from_bytes(&C2);
+    let c3 = Elem::from_bytes(&C3);
+    let j = small_u32(486_662);
+    let one = Elem::one();
+
+    // The curve25519 map, with x = xn / xd and y = y / 1.
+    let tv1 = u.squared();
+    let tv1 = tv1.add(&tv1);
+    let xd = tv1.add(&one);
+    let x1n = j.negated();
+    let tv2 = xd.squared();
+    let gxd = tv2.mul(&xd);
+    let gx1 = j.mul(&tv1).mul(&x1n).add(&tv2).mul(&x1n);
+    let tv3 = gxd.squared();
+    let tv2 = tv3.squared();
+    let tv3 = tv3.mul(&gxd).mul(&gx1);
+    let tv2 = tv2.mul(&tv3);
+    let y11 = tv2.pow22523().mul(&tv3);
+    let y12 = y11.mul(&c3);
+    let e1 = y11.squared().mul(&gxd).equals(&gx1);
+    let y1 = Elem::select(&y12, &y11, e1);
+    let x2n = x1n.mul(&tv1);
+    let y21 = y11.mul(u).mul(&c2);
+    let y22 = y21.mul(&c3);
+    let gx2 = gx1.mul(&tv1);
+    let e2 = y21.squared().mul(&gxd).equals(&gx2);
+    let y2 = Elem::select(&y22, &y21, e2);
+    let e3 = y1.squared().mul(&gxd).equals(&gx1);
+    let xn = Elem::select(&x2n, &x1n, e3);
+    let y = Elem::select(&y2, &y1, e3);
+    let e4 = y.is_negative();
+    let y = Elem::select(&y, &y.negated(), e3 ^ e4);
+
+    // The rational map to edwards25519, with x = xn / xd and y = yn / yd.
+    let xn_edwards = xn.mul(&Elem::from_bytes(&C1_EDWARDS));
+    let xd_edwards = xd.mul(&y);
+    let yn = xn.sub(&xd);
+    let yd = xn.add(&xd);
+    let e = xd_edwards.mul(&yd).is_zero();
+    let xn_edwards = Elem::select(&xn_edwards, &small(0), e);
+    let xd_edwards = Elem::select(&xd_edwards, &one, e);
+    let yn = Elem::select(&yn, &one, e);
+    let yd = Elem::select(&yd, &one, e);
+
+    let x = xn_edwards.mul(&xd_edwards.inverse());
+    let y = yn.mul(&yd.inverse());
+    ExtPoint::from_affine(x, y)
+}
+
+/// Multiplies `p` by the cofactor 8.
+pub fn clear_cofactor(p: &ExtPoint) -> ExtPoint {
+    let p2 = p.add_point(p);
+    let p4 = p2.add_point(&p2);
+    p4.add_point(&p4)
+}
+
+fn small(value: u8) -> Elem<T> {
+    small_u32(value.into())
+}
+
+fn small_u32(value: u32) -> Elem<T> {
+    let mut bytes = [0u8; ELEM_LEN];
+    bytes[..4].copy_from_slice(&value.to_le_bytes());
+    Elem::from_bytes(&bytes)
+}
@@ -15,27 +15,48 @@
 //! Elliptic curve operations on the birationally equivalent curves Curve25519
 //! and Edwards25519.
 
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 use crate::{
     bssl, error,
     limb::{Limb, LIMB_BITS},
 };
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 use core::marker::PhantomData;
 
+#[cfg(feature = "use_heap")]
+use crate::c;
+
 // Elem<T>` is `fe` in curve25519/internal.h.
 // Elem<L> is `fe_loose` in curve25519/internal.h.
 // Keep this in sync with curve25519/internal.h.
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 #[repr(C)]
 pub struct Elem<E: Encoding> {
     limbs: [Limb; ELEM_LIMBS], // This is called `v` in the C code.
     encoding: PhantomData<E>,
 }
 
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 pub trait Encoding {}
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 pub struct T;
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 impl Encoding for T {}
 
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 const ELEM_LIMBS: usize = 5 * 64 / LIMB_BITS;
 
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
+impl<E: Encoding> Clone for Elem<E> {
+    fn clone(&self) -> Self {
+        Self {
+            limbs: self.limbs,
+            encoding: PhantomData,
+        }
+    }
+}
+
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 impl<E: Encoding> Elem<E> {
     fn zero() -> Self {
         Self {
@@ -45,6 +66,7 @@ impl<E: Encoding> Elem<E> {
     }
 }
 
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 impl Elem<T> {
     fn negate(&mut self) {
         unsafe {
@@ -53,6 +75,93 @@ impl Elem<T> {
     }
 }
 
+// Field arithmetic for hash-to-curve. The masks taken and returned by these
+// functions are all ones for true and zero for false.
+#[cfg(feature = "use_heap")]
+impl Elem<T> {
+    // Decodes a little-endian element, ignoring the top bit.
+    pub fn from_bytes(bytes: &[u8; ELEM_LEN]) -> Self {
+        let mut r = Self::zero();
+        unsafe { GFp_x25519_fe_frombytes(&mut r, bytes) };
+        r
+    }
+
+    pub fn one() -> Self {
+        let mut one = [0u8; ELEM_LEN];
+        one[0] = 1;
+        Self::from_bytes(&one)
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        let mut r = Self::zero();
+        unsafe { GFp_x25519_fe_add(&mut r, self, other) };
+        r
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        let mut r = Self::zero();
+        unsafe { GFp_x25519_fe_sub(&mut r, self, other) };
+        r
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        let mut r = Self::zero();
+        unsafe { GFp_x25519_fe_mul_ttt(&mut r, self, other) };
+        r
+    }
+
+    pub fn squared(&self) -> Self {
+        let mut r = Self::zero();
+        unsafe { GFp_x25519_fe_sq(&mut r, self) };
+        r
+    }
+
+    pub fn negated(&self) -> Self {
+        let mut r = self.clone();
+        r.negate();
+        r
+    }
+
+    // Returns `self**-1`, or zero if `self` is zero.
+    pub fn inverse(&self) -> Self {
+        let mut r = Self::zero();
+        unsafe { GFp_x25519_fe_invert(&mut r, self) };
+        r
+    }
+
+    // Returns `self**((q - 5) / 8)`.
+    pub fn pow22523(&self) -> Self {
+        let mut r = Self::zero();
+        unsafe { GFp_x25519_fe_pow22523(&mut r, self) };
+        r
+    }
+
+    pub fn is_zero(&self) -> Limb {
+        let is_nonzero = unsafe { GFp_x25519_fe_isnonzero(self) };
+        (is_nonzero as Limb).wrapping_sub(1)
+    }
+
+    pub fn equals(&self, other: &Self) -> Limb {
+        self.sub(other).is_zero()
+    }
+
+    // Returns whether `self`, fully reduced, is odd; this is `sgn0` in
+    // RFC 9380.
+    pub fn is_negative(&self) -> Limb {
+        let is_negative = unsafe { GFp_x25519_fe_isnegative(self) };
+        Limb::from(is_negative).wrapping_neg()
+    }
+
+    // Returns `b` if `choose_b` is true, or `a` otherwise.
+    pub fn select(a: &Self, b: &Self, choose_b: Limb) -> Self {
+        let mut r = Self::zero();
+        for (r, (a, b)) in r.limbs.iter_mut().zip(a.limbs.iter().zip(b.limbs.iter())) {
+            *r = (a & !choose_b) | (b & choose_b);
+        }
+        r
+    }
+}
+
 // An encoding of a curve point. If on Curve25519, it should be encoded as
 // described in Section 5 of [RFC 7748]. If on Edwards25519, it should be
 // encoded as described in section 5.1.2 of [RFC 8032].
@@ -65,10 +174,32 @@ pub const ELEM_LEN: usize = 32;
 pub type Scalar = [u8; SCALAR_LEN];
 pub const SCALAR_LEN: usize = 32;
 
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 pub type UnreducedScalar = [u8; UNREDUCED_SCALAR_LEN];
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 const UNREDUCED_SCALAR_LEN: usize = SCALAR_LEN * 2;
 
+// Whether `s` is less than the group order
+// l = 2**252 + 27742317777372353535851937790883648493.
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
+pub(crate) fn is_reduced_scalar(s: &Scalar) -> bool {
+    const L: Scalar = [
+        0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde,
+        0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x10,
+    ];
+    // Compare as little-endian integers, most significant byte first.
+    for (s_byte, l_byte) in s.iter().rev().zip(L.iter().rev()) {
+        if s_byte != l_byte {
+            return s_byte < l_byte;
+        }
+    }
+    false
+}
+
 // Keep this in sync with `ge_p3` in curve25519/internal.h.
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
+#[derive(Clone)]
 #[repr(C)]
 pub struct ExtPoint {
     x: Elem<T>,
@@ -77,6 +208,7 @@ pub struct ExtPoint {
     t: Elem<T>,
 }
 
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 impl ExtPoint {
     pub fn new_at_infinity() -> Self {
         Self {
@@ -98,13 +230,50 @@ impl ExtPoint {
         encode_point(self.x, self.y, self.z)
     }
 
+    // Returns the point (x, y), which must be on the curve.
+    #[cfg(feature = "use_heap")]
+    pub fn from_affine(x: Elem<T>, y: Elem<T>) -> Self {
+        let t = x.mul(&y);
+        Self {
+            x,
+            y,
+            z: Elem::one(),
+            t,
+        }
+    }
+
     pub fn invert_vartime(&mut self) {
         self.x.negate();
         self.t.negate();
     }
+
+    // `scalar` must be less than 2**255.
+    pub fn from_scalarmult_base(scalar: &Scalar) -> Self {
+        debug_assert_eq!(scalar[SCALAR_LEN - 1] & 0x80, 0);
+        let mut point = Self::new_at_infinity();
+        unsafe { GFp_x25519_ge_scalarmult_base(&mut point, scalar) };
+        point
+    }
+
+    // Computes `scalar * self` in constant time. Unlike the other
+    // functions here, `scalar` may be any 256-bit value.
+    #[cfg(feature = "use_heap")]
+    pub fn scalar_mult(&self, scalar: &Scalar) -> Self {
+        let mut point = Self::new_at_infinity();
+        unsafe { GFp_x25519_ge_scalarmult(&mut point, scalar, self) };
+        point
+    }
+
+    #[cfg(feature = "use_heap")]
+    pub fn add_point(&self, other: &Self) -> Self {
+        let mut point = Self::new_at_infinity();
+        unsafe { GFp_x25519_ge_add(&mut point, self, other) };
+        point
+    }
 }
 
 // Keep this in sync with `ge_p2` in curve25519/internal.h.
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 #[repr(C)]
 pub struct Point {
     x: Elem<T>,
@@ -112,6 +281,7 @@ pub struct Point {
     z: Elem<T>,
 }
 
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 impl Point {
     pub fn new_at_infinity() -> Self {
         Self {
@@ -126,6 +296,30 @@ impl Point {
     }
 }
 
+// Keep this in sync with `ge_cached` in curve25519/internal.h. This is only
+// used as scratch space by the C code.
+#[cfg(feature = "sig-ed25519")]
+#[repr(C)]
+pub struct CachedPoint {
+    y_plus_x: Elem<T>,
+    y_minus_x: Elem<T>,
+    z: Elem<T>,
+    t2d: Elem<T>,
+}
+
+#[cfg(feature = "sig-ed25519")]
+impl CachedPoint {
+    pub fn zero() -> Self {
+        Self {
+            y_plus_x: Elem::zero(),
+            y_minus_x: Elem::zero(),
+            z: Elem::zero(),
+            t2d: Elem::zero(),
+        }
+    }
+}
+
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 fn encode_point(x: Elem<T>, y: Elem<T>, z: Elem<T>) -> EncodedPoint {
     let mut bytes = [0; ELEM_LEN];
 
@@ -150,6 +344,7 @@ fn encode_point(x: Elem<T>, y: Elem<T>, z: Elem<T>) -> EncodedPoint {
     bytes
 }
 
+#[cfg(any(feature = "sig-ed25519", feature = "use_heap"))]
 extern "C" {
     fn GFp_x25519_fe_invert(out: &mut Elem<T>, z: &Elem<T>);
     fn GFp_x25519_fe_isnegative(elem: &Elem<T>) -> u8;
@@ -157,4 +352,17 @@ extern "C" {
     fn GFp_x25519_fe_neg(f: &mut Elem<T>);
     fn GFp_x25519_fe_tobytes(bytes: &mut EncodedPoint, elem: &Elem<T>);
     fn GFp_x25519_ge_frombytes_vartime(h: &mut ExtPoint, s: &EncodedPoint) -> bssl::Result;
+    fn GFp_x25519_ge_scalarmult_base(h: &mut ExtPoint, a: &Scalar);
+}
+
+#[cfg(feature = "use_heap")]
+extern "C" {
+    fn GFp_x25519_fe_add(h: &mut Elem<T>, f: &Elem<T>, g: &Elem<T>);
+    fn GFp_x25519_fe_frombytes(h: &mut Elem<T>, s: &[u8; ELEM_LEN]);
+    fn GFp_x25519_fe_isnonzero(f: &Elem<T>) -> c::int;
+    fn GFp_x25519_fe_pow22523(out: &mut Elem<T>, z: &Elem<T>);
+    fn GFp_x25519_fe_sq(h: &mut Elem<T>, f: &Elem<T>);
+    fn GFp_x25519_fe_sub(h: &mut Elem<T>, f: &Elem<T>, g: &Elem<T>);
+    fn GFp_x25519_ge_add(r: &mut ExtPoint, p: &ExtPoint, q: &ExtPoint);
+    fn GFp_x25519_ge_scalarmult(h: &mut ExtPoint, a: &Scalar, point: &ExtPoint);
 }
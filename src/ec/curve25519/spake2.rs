@@ -0,0 +1,314 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! SPAKE2 over edwards25519.
+
+use super::ops::*;
+use crate::{constant_time, digest, error, hkdf, hmac, rand, zeroize::Zeroize};
+use std::vec::Vec;
+
+/// The length of the password hash that the parties start with.
+pub const PASSWORD_HASH_LEN: usize = 64;
+
+/// The length of a share.
+pub const SHARE_LEN: usize = ELEM_LEN;
+
+/// The length of a key confirmation message.
+pub const CONFIRMATION_LEN: usize = digest::SHA256_OUTPUT_LEN;
+
+/// The length of the shared key.
+pub const SHARED_KEY_LEN: usize = digest::SHA256_OUTPUT_LEN / 2;
+
+/// A party's role in the protocol.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Role {
+    /// The party called A in the specification, whose share is blinded with
+    /// the point M.
+    A,
+
+    /// The party called B in the specification, whose share is blinded with
+    /// the point N.
+    B,
+}
+
+/// One party's state from the start of the protocol until it receives the
+/// other party's share.
+pub struct Spake2 {
+    role: Role,
+    x: Scalar,
+    w: Scalar,
+    my_identity: Vec<u8>,
+    peer_identity: Vec<u8>,
+    share: EncodedPoint,
+}
+
+impl Spake2 {
+    /// Starts the protocol.
+    ///
+    /// `password_hash` is the output of a memory-hard function of the
+    /// password, e.g. `argon2`, and must be `PASSWORD_HASH_LEN` bytes long.
+    /// Both parties must use the same one. `my_identity` and `peer_identity`
+    /// identify the parties, and may be empty if the protocol that uses
+    /// SPAKE2 doesn't identify them.
+    pub fn new(
+        role: Role,
+        password_hash: &[u8],
+        my_identity: &[u8],
+        peer_identity: &[u8],
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<Self, error::Unspecified> {
+        if password_hash.len() != PASSWORD_HASH_LEN {
+            return Err(error::Unspecified);
+        }
+        let mut unreduced: UnreducedScalar = [0u8; PASSWORD_HASH_LEN];
+        unreduced.copy_from_slice(password_hash);
+        let w = reduce(&mut unreduced);
+
+        rng.fill(&mut unreduced)?;
+        let x = reduce(&mut unreduced);
+
+        // T = w*M + x*P or S = w*N + y*P.
+        let blind = blinding_point(role).scalar_mult(&w);
+        let share = ExtPoint::from_scalarmult_base(&x)
+            .add_point(&blind)
+            .into_encoded_point();
+
+        Ok(Self {
+            role,
+            x,
+            w,
+            my_identity: my_identity.to_vec(),
+            peer_identity: peer_identity.to_vec(),
+            share,
+        })
+    }
+
+    /// The role of this party.
+    #[inline]
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// The share to send to the other party, `pA` or `pB`.
+    #[inline]
+    pub fn share(&self) -> &[u8] {
+        &self.share
+    }
+
+    /// Computes the shared secret from the other party's share and derives
+    /// the keys from it.
+    ///
+    /// `aad` is bound to the key confirmation messages; both parties must use
+    /// the same one. Fails if `peer_share` isn't a valid encoding of a point,
+    /// or if it is one of the few points that would make the shared secret
+    /// independent of this party's secret.
+    pub fn finish(
+        self,
+        peer_share: &[u8],
+        aad: &[u8],
+    ) -> Result<KeyConfirmation, error::Unspecified> {
+        if peer_share.len() != SHARE_LEN {
+            return Err(error::Unspecified);
+        }
+        let mut peer_share_bytes = [0u8; SHARE_LEN];
+        peer_share_bytes.copy_from_slice(peer_share);
+        let peer_point = ExtPoint::from_encoded_point_vartime(&peer_share_bytes)?;
+
+        let peer_role = match self.role {
+            Role::A => Role::B,
+            Role::B => Role::A,
+        };
+        let mut minus_peer_blind = blinding_point(peer_role).scalar_mult(&self.w);
+        minus_peer_blind.invert_vartime();
+
+        // K = h*x*(S - w*N) or K = h*y*(T - w*M), where h = 8 is the
+        // cofactor. `x` is less than 2**253, so `8*x` fits in a `Scalar`
+        // without reduction, which would defeat the purpose of `h`.
+        let mut cofactor_x = [0u8; SCALAR_LEN];
+        let mut carry = 0;
+        for (out, x) in cofactor_x.iter_mut().zip(self.x.iter()) {
+            *out = (x << 3) | carry;
+            carry = x >> 5;
+        }
+        let k = peer_point
+            .add_point(&minus_peer_blind)
+            .scalar_mult(&cofactor_x)
+            .into_encoded_point();
+        cofactor_x[..].zeroize();
+
+        let mut identity = [0u8; ELEM_LEN];
+        identity[0] = 1;
+        if k == identity {
+            return Err(error::Unspecified);
+        }
+
+        // `w` is encoded as a big-endian number in the transcript.
+        let mut w = self.w;
+        w.reverse();
+
+        let (a_identity, b_identity, p_a, p_b) = match self.role {
+            Role::A => (
+                &self.my_identity,
+                &self.peer_identity,
+                &self.share,
+                &peer_share_bytes,
+            ),
+            Role::B => (
+                &self.peer_identity,
+                &self.my_identity,
+                &peer_share_bytes,
+                &self.share,
+            ),
+        };
+        let mut transcript = Vec::new();
+        for part in &[
+            &a_identity[..],
+            &b_identity[..],
+            &p_a[..],
+            &p_b[..],
+            &k[..],
+            &w[..],
+        ] {
+            transcript.extend_from_slice(&(part.len() as u64).to_le_bytes());
+            transcript.extend_from_slice(part);
+        }
+        w[..].zeroize();
+
+        // Ke || Ka = Hash(TT).
+        let ke_ka = digest::digest(&digest::SHA256, &transcript);
+        let (ke, ka) = ke_ka.as_ref().split_at(SHARED_KEY_LEN);
+
+        // KcA || KcB = KDF(nil, Ka, "ConfirmationKeys" || AAD).
+        let mut kc_a_kc_b = [0u8; 2 * CONFIRMATION_KEY_LEN];
+        hkdf::Salt::new(&digest::SHA256, &[])
+            .extract(ka)
+            .fill_with_info_parts(&[&b"ConfirmationKeys"[..], aad], &mut kc_a_kc_b)?;
+        let (kc_a, kc_b) = kc_a_kc_b.split_at(CONFIRMATION_KEY_LEN);
+        let (my_kc, peer_kc) = match self.role {
+            Role::A => (kc_a, kc_b),
+            Role::B => (kc_b, kc_a),
+        };
+
+        let mut confirmation = [0u8; CONFIRMATION_LEN];
+        confirmation.copy_from_slice(
+            hmac::sign(&hmac::Key::new(&digest::SHA256, my_kc), &transcript).as_ref(),
+        );
+        let mut expected_peer_confirmation = [0u8; CONFIRMATION_LEN];
+        expected_peer_confirmation.copy_from_slice(
+            hmac::sign(&hmac::Key::new(&digest::SHA256, peer_kc), &transcript).as_ref(),
+        );
+        kc_a_kc_b[..].zeroize();
+        transcript[..].zeroize();
+
+        let mut shared_key = [0u8; SHARED_KEY_LEN];
+        shared_key.copy_from_slice(ke);
+
+        Ok(KeyConfirmation {
+            confirmation,
+            expected_peer_confirmation,
+            shared_key,
+        })
+    }
+}
+
+impl Drop for Spake2 {
+    fn drop(&mut self) {
+        self.x[..].zeroize();
+        self.w[..].zeroize();
+    }
+}
+
+impl core::fmt::Debug for Spake2 {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Spake2").field("role", &self.role).finish()
+    }
+}
+
+/// One party's state after the shares have been exchanged, until the key
+/// confirmation messages have been exchanged.
+pub struct KeyConfirmation {
+    confirmation: [u8; CONFIRMATION_LEN],
+    expected_peer_confirmation: [u8; CONFIRMATION_LEN],
+    shared_key: [u8; SHARED_KEY_LEN],
+}
+
+impl KeyConfirmation {
+    /// The key confirmation message to send to the other party, `cA` or
+    /// `cB`.
+    #[inline]
+    pub fn confirmation(&self) -> &[u8] {
+        &self.confirmation
+    }
+
+    /// Verifies the other party's key confirmation message and then calls
+    /// `kdf` with the `SHARED_KEY_LEN`-byte shared key, `Ke`.
+    ///
+    /// If the confirmation message is wrong, `kdf` isn't called and
+    /// `error_value` is returned; the other party didn't use the same
+    /// password, identities, or `aad`, or the messages were tampered with.
+    pub fn verify<F, R, E>(self, peer_confirmation: &[u8], error_value: E, kdf: F) -> Result<R, E>
+    where
+        F: FnOnce(&[u8]) -> Result<R, E>,
+    {
+        constant_time::verify_slices_are_equal(peer_confirmation, &self.expected_peer_confirmation)
+            .map_err(|error::Unspecified| error_value)?;
+        kdf(&self.shared_key)
+    }
+}
+
+impl Drop for KeyConfirmation {
+    fn drop(&mut self) {
+        self.shared_key[..].zeroize();
+    }
+}
+
+impl core::fmt::Debug for KeyConfirmation {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("KeyConfirmation").finish()
+    }
+}
+
+const CONFIRMATION_KEY_LEN: usize = digest::SHA256_OUTPUT_LEN / 2;
+
+fn blinding_point(role: Role) -> ExtPoint {
+    // The points M and N for edwards25519 from RFC 9382.
+    const M: EncodedPoint = [
+        0xd0, 0x48, 0x03, 0x2c, 0x6e, 0xa0, 0xb6, 0xd6, 0x97, 0xdd, 0xc2, 0xe8, 0x6b, 0xda, 0x85,
+        0xa3, 0x3a, 0xda, 0xc9, 0x20, 0xf1, 0xbf, 0x18, 0xe1, 0xb0, 0xc6, 0xd1, 0x66, 0xa5, 0xce,
+        0xcd, 0xaf,
+    ];
+    const N: EncodedPoint = [
+        0xd3, 0xbf, 0xb5, 0x18, 0xf4, 0x4f, 0x34, 0x30, 0xf2, 0x9d, 0x0c, 0x92, 0xaf, 0x50, 0x38,
+        0x65, 0xa1, 0xed, 0x32, 0x81, 0xdc, 0x69, 0xb3, 0x5d, 0xd8, 0x68, 0xba, 0x85, 0xf8, 0x86,
+        0xc4, 0xab,
+    ];
+    let encoded = match role {
+        Role::A => &M,
+        Role::B => &N,
+    };
+    ExtPoint::from_encoded_point_vartime(encoded).unwrap()
+}
+
+// Reduces `unreduced` modulo the group order, and zeroes it.
+fn reduce(unreduced: &mut UnreducedScalar) -> Scalar {
+    unsafe { GFp_x25519_sc_reduce(unreduced) };
+    let mut scalar = [0u8; SCALAR_LEN];
+    scalar.copy_from_slice(&unreduced[..SCALAR_LEN]);
+    unreduced[..].zeroize();
+    scalar
+}
+
+extern "C" {
+    fn GFp_x25519_sc_reduce(s: &mut UnreducedScalar);
+}
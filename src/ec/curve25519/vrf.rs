@@ -0,0 +1,285 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! ECVRF-EDWARDS25519-SHA512-TAI from RFC 9381.
+
+use super::{hash_to_curve::clear_cofactor, ops::*};
+use crate::{constant_time, digest, error, polyfill::convert::*, zeroize::Zeroize};
+
+/// The length of a seed, the secret key `SK`.
+pub const SEED_LEN: usize = 32;
+
+/// The length of a public key, `PK_string`.
+pub const PUBLIC_KEY_LEN: usize = ELEM_LEN;
+
+/// The length of a proof, `pi_string`.
+pub const PROOF_LEN: usize = ELEM_LEN + CHALLENGE_LEN + SCALAR_LEN;
+
+/// The length of a VRF output, `beta_string`.
+pub const OUTPUT_LEN: usize = digest::SHA512_OUTPUT_LEN;
+
+// `cLen` in RFC 9381.
+const CHALLENGE_LEN: usize = 16;
+
+const SUITE_STRING: u8 = 0x03;
+
+/// A key pair for computing VRF proofs.
+pub struct KeyPair {
+    seed: [u8; SEED_LEN],
+    scalar: Scalar,
+    nonce_prefix: [u8; digest::SHA512_OUTPUT_LEN - SCALAR_LEN],
+    public_key: EncodedPoint,
+}
+
+impl KeyPair {
+    /// Constructs a key pair from a seed, which must be `SEED_LEN` uniformly
+    /// random bytes.
+    ///
+    /// The key pair is derived from the seed the same way an Ed25519 key
+    /// pair is, so the public key is the Ed25519 public key for the same
+    /// seed. Don't use the same seed for both.
+    pub fn from_seed(seed: &[u8]) -> Result<Self, error::KeyRejected> {
+        let seed: &[u8; SEED_LEN] = seed
+            .try_into_()
+            .map_err(|_| error::KeyRejected::invalid_encoding())?;
+        let h = digest::digest(&digest::SHA512, seed);
+        let (scalar_encoded, nonce_prefix_encoded) = h.as_ref().split_at(SCALAR_LEN);
+
+        let mut scalar = [0u8; SCALAR_LEN];
+        scalar.copy_from_slice(scalar_encoded);
+        unsafe { GFp_x25519_sc_mask(&mut scalar) };
+
+        let mut nonce_prefix = [0u8; digest::SHA512_OUTPUT_LEN - SCALAR_LEN];
+        nonce_prefix.copy_from_slice(nonce_prefix_encoded);
+
+        Ok(Self {
+            seed: *seed,
+            scalar,
+            nonce_prefix,
+            public_key: ExtPoint::from_scalarmult_base(&scalar).into_encoded_point(),
+        })
+    }
+
+    /// The public key, `PK_string`.
+    #[inline]
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// Computes the proof for `alpha`, `ECVRF_prove(SK, alpha)`.
+    ///
+    /// The VRF output is `proof_to_hash()` of the proof; the proof lets
+    /// anybody with the public key verify that the output is correct.
+    pub fn prove(&self, alpha: &[u8]) -> Proof {
+        let h = encode_to_curve(&self.public_key, alpha);
+        let h_string = h.clone().into_encoded_point();
+        let gamma = h.scalar_mult(&self.scalar).into_encoded_point();
+
+        // The nonce is generated as in RFC 8032 Section 5.1.6.
+        let mut k = {
+            let nonce = digest::digest_all(&digest::SHA512, &[&self.nonce_prefix, &h_string]);
+            let mut unreduced: UnreducedScalar = [0u8; digest::SHA512_OUTPUT_LEN];
+            unreduced.copy_from_slice(nonce.as_ref());
+            unsafe { GFp_x25519_sc_reduce(&mut unreduced) };
+            let mut k = [0u8; SCALAR_LEN];
+            k.copy_from_slice(&unreduced[..SCALAR_LEN]);
+            unreduced[..].zeroize();
+            k
+        };
+        let u = ExtPoint::from_scalarmult_base(&k).into_encoded_point();
+        let v = h.scalar_mult(&k).into_encoded_point();
+
+        let c = challenge(&self.public_key, &h_string, &gamma, &u, &v);
+
+        // s = (k + c*x) mod q.
+        let mut s = [0u8; SCALAR_LEN];
+        unsafe { GFp_x25519_sc_muladd(&mut s, &c, &self.scalar, &k) };
+        k[..].zeroize();
+
+        let mut proof = [0u8; PROOF_LEN];
+        let (gamma_out, rest) = proof.split_at_mut(ELEM_LEN);
+        let (c_out, s_out) = rest.split_at_mut(CHALLENGE_LEN);
+        gamma_out.copy_from_slice(&gamma);
+        c_out.copy_from_slice(&c[..CHALLENGE_LEN]);
+        s_out.copy_from_slice(&s);
+        Proof(proof)
+    }
+}
+
+impl Drop for KeyPair {
+    fn drop(&mut self) {
+        self.seed[..].zeroize();
+        self.scalar[..].zeroize();
+        self.nonce_prefix[..].zeroize();
+    }
+}
+
+impl core::fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        crate::debug::write_hex_tuple(f, "KeyPair", &self.public_key)
+    }
+}
+
+/// A VRF proof, `pi_string`.
+#[derive(Clone, Copy)]
+pub struct Proof([u8; PROOF_LEN]);
+
+impl AsRef<[u8]> for Proof {
+    fn as_ref(&self) -> &[u8] {
+        &self.0[..]
+    }
+}
+
+derive_debug_self_as_ref_hex_bytes!(Proof);
+
+/// A VRF output, `beta_string`.
+#[derive(Clone, Copy)]
+pub struct Output([u8; OUTPUT_LEN]);
+
+impl AsRef<[u8]> for Output {
+    fn as_ref(&self) -> &[u8] {
+        &self.0[..]
+    }
+}
+
+derive_debug_self_as_ref_hex_bytes!(Output);
+
+/// Verifies that `proof` is a valid proof for `alpha` under `public_key`,
+/// `ECVRF_verify(PK, alpha, pi, validate_key = TRUE)`, and returns the VRF
+/// output.
+///
+/// Public keys of small order, for which proofs could be forged, are
+/// rejected.
+pub fn verify(public_key: &[u8], alpha: &[u8], proof: &[u8]) -> Result<Output, error::Unspecified> {
+    let public_key: &EncodedPoint = public_key.try_into_()?;
+    let y = ExtPoint::from_encoded_point_vartime(public_key)?;
+    if clear_cofactor(&y).into_encoded_point() == IDENTITY {
+        return Err(error::Unspecified);
+    }
+    let (gamma, c, s) = decode_proof(proof)?;
+
+    let h = encode_to_curve(public_key, alpha);
+    let h_string = h.clone().into_encoded_point();
+
+    // U = s*B - c*Y.
+    let mut minus_y = y;
+    minus_y.invert_vartime();
+    let mut u = Point::new_at_infinity();
+    unsafe { GFp_x25519_ge_double_scalarmult_vartime(&mut u, &c, &minus_y, &s) };
+    let u = u.into_encoded_point();
+
+    // V = s*H - c*Gamma.
+    let mut minus_gamma = gamma.clone();
+    minus_gamma.invert_vartime();
+    let v = h
+        .scalar_mult(&s)
+        .add_point(&minus_gamma.scalar_mult(&c))
+        .into_encoded_point();
+
+    let gamma_string = gamma.clone().into_encoded_point();
+    let expected_c = challenge(public_key, &h_string, &gamma_string, &u, &v);
+    constant_time::verify_slices_are_equal(&c, &expected_c)?;
+
+    Ok(gamma_to_hash(&gamma))
+}
+
+/// Computes the VRF output for `proof`, `ECVRF_proof_to_hash(pi)`.
+///
+/// This doesn't verify the proof. Use the output of `verify()` instead
+/// unless the proof is known to be valid, e.g. because it was just computed
+/// with `KeyPair::prove()`.
+pub fn proof_to_hash(proof: &[u8]) -> Result<Output, error::Unspecified> {
+    let (gamma, _, _) = decode_proof(proof)?;
+    Ok(gamma_to_hash(&gamma))
+}
+
+// `ECVRF_decode_proof`.
+fn decode_proof(proof: &[u8]) -> Result<(ExtPoint, Scalar, Scalar), error::Unspecified> {
+    if proof.len() != PROOF_LEN {
+        return Err(error::Unspecified);
+    }
+    let (gamma, rest) = proof.split_at(ELEM_LEN);
+    let (c_encoded, s) = rest.split_at(CHALLENGE_LEN);
+    let gamma = ExtPoint::from_encoded_point_vartime(gamma.try_into_()?)?;
+    let mut c = [0u8; SCALAR_LEN];
+    c[..CHALLENGE_LEN].copy_from_slice(c_encoded);
+    let s: &Scalar = s.try_into_()?;
+    if !is_reduced_scalar(s) {
+        return Err(error::Unspecified);
+    }
+    Ok((gamma, c, *s))
+}
+
+// `ECVRF_encode_to_curve_try_and_increment`, with `PK_string` as the salt.
+// This isn't constant time, but `alpha` is public.
+fn encode_to_curve(public_key: &EncodedPoint, alpha: &[u8]) -> ExtPoint {
+    for ctr in 0..=255u8 {
+        let hash = digest::digest_all(
+            &digest::SHA512,
+            &[&[SUITE_STRING, 0x01], public_key, alpha, &[ctr, 0x00]],
+        );
+        let candidate: &EncodedPoint = hash.as_ref()[..ELEM_LEN].try_into_().unwrap();
+        if let Ok(h) = ExtPoint::from_encoded_point_vartime(candidate) {
+            return clear_cofactor(&h);
+        }
+    }
+    // Each candidate is a valid encoding with probability about 1/2.
+    unreachable!()
+}
+
+// `ECVRF_challenge_generation`, returning `c` as a scalar.
+fn challenge(
+    y: &EncodedPoint,
+    h: &EncodedPoint,
+    gamma: &EncodedPoint,
+    u: &EncodedPoint,
+    v: &EncodedPoint,
+) -> Scalar {
+    let hash = digest::digest_all(
+        &digest::SHA512,
+        &[&[SUITE_STRING, 0x02], y, h, gamma, u, v, &[0x00]],
+    );
+    let mut c = [0u8; SCALAR_LEN];
+    c[..CHALLENGE_LEN].copy_from_slice(&hash.as_ref()[..CHALLENGE_LEN]);
+    c
+}
+
+// The hash in `ECVRF_proof_to_hash`.
+fn gamma_to_hash(gamma: &ExtPoint) -> Output {
+    let gamma_string = clear_cofactor(gamma).into_encoded_point();
+    let hash = digest::digest_all(
+        &digest::SHA512,
+        &[&[SUITE_STRING, 0x03], &gamma_string, &[0x00]],
+    );
+    let mut output = [0u8; OUTPUT_LEN];
+    output.copy_from_slice(hash.as_ref());
+    Output(output)
+}
+
+const IDENTITY: EncodedPoint = [
+    0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+extern "C" {
+    fn GFp_x25519_ge_double_scalarmult_vartime(
+        r: &mut Point,
+        a_coeff: &Scalar,
+        a: &ExtPoint,
+        b_coeff: &Scalar,
+    );
+    fn GFp_x25519_sc_mask(a: &mut Scalar);
+    fn GFp_x25519_sc_muladd(s: &mut Scalar, a: &Scalar, b: &Scalar, c: &Scalar);
+    fn GFp_x25519_sc_reduce(s: &mut UnreducedScalar);
+}
@@ -15,7 +15,7 @@
 //! X25519 Key agreement.
 
 use super::ops;
-use crate::{agreement, constant_time, cpu, ec, error, polyfill::convert::*, rand};
+use crate::{agreement, constant_time, cpu, ec, error, pkcs8, polyfill::convert::*, rand};
 use untrusted;
 
 static CURVE25519: ec::Curve = ec::Curve {
@@ -38,6 +38,8 @@ static CURVE25519: ec::Curve = ec::Curve {
 pub static X25519: agreement::Algorithm = agreement::Algorithm {
     curve: &CURVE25519,
     ecdh: x25519_ecdh,
+    pkcs8_template: &PKCS8_TEMPLATE,
+    private_key_from_pkcs8: x25519_private_key_from_pkcs8,
 };
 
 fn x25519_check_private_key_bytes(bytes: &[u8]) -> Result<(), error::Unspecified> {
@@ -88,6 +90,13 @@ fn x25519_public_from_private(
     Ok(())
 }
 
+fn x25519_private_key_from_pkcs8(
+    input: untrusted::Input,
+    cpu_features: cpu::Features,
+) -> Result<ec::Seed, error::KeyRejected> {
+    ec::Seed::from_rfc8410_pkcs8(&CURVE25519, &PKCS8_TEMPLATE, input, cpu_features)
+}
+
 fn x25519_ecdh(
     out: &mut [u8],
     my_private_key: &ec::Seed,
@@ -98,32 +107,6 @@ fn x25519_ecdh(
     let peer_public_key: &[u8; PUBLIC_KEY_LEN] =
         peer_public_key.as_slice_less_safe().try_into_()?;
 
-    #[cfg_attr(not(target_arch = "arm"), allow(unused_variables))]
-    fn scalar_mult(
-        out: &mut ops::EncodedPoint,
-        scalar: &ops::Scalar,
-        point: &ops::EncodedPoint,
-        cpu_features: cpu::Features,
-    ) {
-        #[cfg(target_arch = "arm")]
-        {
-            if cpu::arm::NEON.available(cpu_features) {
-                return x25519_neon(out, scalar, point);
-            }
-        }
-
-        extern "C" {
-            fn GFp_x25519_scalar_mult_generic(
-                out: &mut ops::EncodedPoint,
-                scalar: &ops::Scalar,
-                point: &ops::EncodedPoint,
-            );
-        }
-        unsafe {
-            GFp_x25519_scalar_mult_generic(out, scalar, point);
-        }
-    }
-
     scalar_mult(
         out.try_into_()?,
         my_private_key,
@@ -140,6 +123,34 @@ fn x25519_ecdh(
     Ok(())
 }
 
+// Computes X25519(scalar, point) as specified in RFC 7748, without checking
+// the result.
+#[cfg_attr(not(target_arch = "arm"), allow(unused_variables))]
+pub(crate) fn scalar_mult(
+    out: &mut ops::EncodedPoint,
+    scalar: &ops::Scalar,
+    point: &ops::EncodedPoint,
+    cpu_features: cpu::Features,
+) {
+    #[cfg(target_arch = "arm")]
+    {
+        if cpu::arm::NEON.available(cpu_features) {
+            return x25519_neon(out, scalar, point);
+        }
+    }
+
+    extern "C" {
+        fn GFp_x25519_scalar_mult_generic(
+            out: &mut ops::EncodedPoint,
+            scalar: &ops::Scalar,
+            point: &ops::EncodedPoint,
+        );
+    }
+    unsafe {
+        GFp_x25519_scalar_mult_generic(out, scalar, point);
+    }
+}
+
 #[cfg(target_arch = "arm")]
 fn x25519_neon(out: &mut ops::EncodedPoint, scalar: &ops::Scalar, point: &ops::EncodedPoint) {
     extern "C" {
@@ -165,3 +176,10 @@ const PUBLIC_KEY_LEN: usize = ELEM_AND_SCALAR_LEN;
 // An X25519 shared secret as an encoded Curve25519 point.
 type SharedSecret = [u8; SHARED_SECRET_LEN];
 const SHARED_SECRET_LEN: usize = ELEM_AND_SCALAR_LEN;
+
+static PKCS8_TEMPLATE: pkcs8::Template = pkcs8::Template {
+    bytes: include_bytes!("x25519_pkcs8_v2_template.der"),
+    alg_id_range: core::ops::Range { start: 7, end: 12 },
+    curve_id_index: 0,
+    private_key_index: 0x10,
+};
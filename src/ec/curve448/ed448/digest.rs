@@ -0,0 +1,56 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::super::ops::Scalar;
+use crate::{digest::Shake256, error};
+
+/// dom4(F, C) of RFC 8032 Section 5.2, with F = 0, i.e. for Ed448 and not
+/// Ed448ph. Unlike dom2 of Ed25519, it is used even if the context is empty.
+#[derive(Clone, Copy)]
+pub struct Dom4<'a> {
+    context: &'a [u8],
+}
+
+impl<'a> Dom4<'a> {
+    pub fn new(context: &'a [u8]) -> Result<Self, error::Unspecified> {
+        if context.len() > MAX_CONTEXT_LEN {
+            return Err(error::Unspecified);
+        }
+        Ok(Self { context })
+    }
+
+    fn update(&self, ctx: &mut Shake256) {
+        ctx.update(b"SigEd448");
+        ctx.update(&[0, self.context.len() as u8]);
+        ctx.update(self.context);
+    }
+}
+
+const MAX_CONTEXT_LEN: usize = 255;
+
+/// Returns SHAKE256(dom4 || parts..., 114) interpreted as a little-endian
+/// integer, reduced modulo L.
+pub fn digest_scalar(dom4: Dom4, parts: &[&[u8]]) -> Scalar {
+    let mut ctx = Shake256::new();
+    dom4.update(&mut ctx);
+    for part in parts {
+        ctx.update(part);
+    }
+    let mut digest = [0u8; DIGEST_LEN];
+    ctx.squeeze(&mut digest);
+    Scalar::from_bytes_reduced(&digest)
+}
+
+/// The length of the SHAKE256 output used throughout Ed448.
+pub const DIGEST_LEN: usize = 114;
@@ -0,0 +1,290 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Ed448 Signatures.
+
+use super::{
+    super::ops::{ExtPoint, Scalar, SCALAR_LEN},
+    digest::*,
+    ED448_PUBLIC_KEY_LEN,
+};
+use crate::{
+    digest::Shake256,
+    error,
+    io::der,
+    pkcs8, policy,
+    polyfill::convert::*,
+    rand,
+    signature::{self, KeyPair as SigningKeyPair},
+};
+use untrusted;
+
+/// An Ed448 key pair, for signing.
+pub struct Ed448KeyPair {
+    // RFC 8032 Section 5.2.5 calls this *s*.
+    private_scalar: Scalar,
+
+    // RFC 8032 Section 5.2.6 calls this *prefix*.
+    private_prefix: Prefix,
+
+    // RFC 8032 Section 5.2.5 calls this *A*.
+    public_key: PublicKey,
+}
+
+derive_debug_via_field!(Ed448KeyPair, stringify!(Ed448KeyPair), public_key);
+
+impl Ed448KeyPair {
+    /// Generates a new key pair and returns the key pair serialized as a
+    /// PKCS#8 document.
+    ///
+    /// The PKCS#8 document will be a v2 `OneAsymmetricKey` with the public key,
+    /// as described in [RFC 5958 Section 2] and [RFC 8410].
+    ///
+    /// [RFC 5958 Section 2]: https://tools.ietf.org/html/rfc5958#section-2
+    /// [RFC 8410]: https://tools.ietf.org/html/rfc8410
+    pub fn generate_pkcs8(
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<pkcs8::Document, error::Unspecified> {
+        policy::check(FIPS_APPROVED)?;
+        let mut seed = [0u8; SEED_LEN];
+        rng.fill(&mut seed)?;
+        let key_pair = Self::from_seed_(&seed);
+        Ok(pkcs8::wrap_key(
+            &PKCS8_TEMPLATE,
+            &seed[..],
+            key_pair.public_key().as_ref(),
+        ))
+    }
+
+    /// Constructs an Ed448 key pair by parsing an unencrypted PKCS#8 v2 Ed448
+    /// private key.
+    ///
+    /// The input must be in PKCS#8 v2 format, and in particular it must contain
+    /// the public key in addition to the private key. `from_pkcs8()` will
+    /// verify that the public key and the private key are consistent with each
+    /// other.
+    ///
+    /// If you need to parse PKCS#8 v1 files (without the public key) then use
+    /// `Ed448KeyPair::from_pkcs8_maybe_unchecked()` instead.
+    pub fn from_pkcs8(pkcs8: &[u8]) -> Result<Self, error::KeyRejected> {
+        let (seed, public_key) =
+            unwrap_pkcs8(pkcs8::Version::V2Only, untrusted::Input::from(pkcs8))?;
+        Self::from_seed_and_public_key(
+            seed.as_slice_less_safe(),
+            public_key.unwrap().as_slice_less_safe(),
+        )
+    }
+
+    /// Constructs an Ed448 key pair by parsing an unencrypted PKCS#8 v1 or v2
+    /// Ed448 private key.
+    ///
+    /// This is like `Ed25519KeyPair::from_pkcs8_maybe_unchecked()`: when a v1
+    /// file is parsed the public key will be computed from the private key,
+    /// and there will be no consistency check between the public key and the
+    /// private key.
+    pub fn from_pkcs8_maybe_unchecked(pkcs8: &[u8]) -> Result<Self, error::KeyRejected> {
+        let (seed, public_key) =
+            unwrap_pkcs8(pkcs8::Version::V1OrV2, untrusted::Input::from(pkcs8))?;
+        if let Some(public_key) = public_key {
+            Self::from_seed_and_public_key(
+                seed.as_slice_less_safe(),
+                public_key.as_slice_less_safe(),
+            )
+        } else {
+            Self::from_seed_unchecked(seed.as_slice_less_safe())
+        }
+    }
+
+    /// Constructs an Ed448 key pair from the 57-byte private key seed `seed`
+    /// and its public key `public_key`.
+    ///
+    /// It is recommended to use `Ed448KeyPair::from_pkcs8()` instead.
+    ///
+    /// The private and public keys will be verified to be consistent with each
+    /// other.
+    pub fn from_seed_and_public_key(
+        seed: &[u8],
+        public_key: &[u8],
+    ) -> Result<Self, error::KeyRejected> {
+        let pair = Self::from_seed_unchecked(seed)?;
+
+        // This implicitly verifies that `public_key` is the right length.
+        if public_key != pair.public_key.as_ref() {
+            let err = if public_key.len() != pair.public_key.as_ref().len() {
+                error::KeyRejected::invalid_encoding()
+            } else {
+                error::KeyRejected::inconsistent_components()
+            };
+            return Err(err);
+        }
+
+        Ok(pair)
+    }
+
+    /// Constructs an Ed448 key pair from the 57-byte private key seed `seed`.
+    ///
+    /// It is recommended to use `Ed448KeyPair::from_pkcs8()` instead. When
+    /// that is not practical, it is recommended to use
+    /// `Ed448KeyPair::from_seed_and_public_key()` instead.
+    pub fn from_seed_unchecked(seed: &[u8]) -> Result<Self, error::KeyRejected> {
+        policy::check(FIPS_APPROVED)?;
+        let seed = seed
+            .try_into_()
+            .map_err(|_| error::KeyRejected::invalid_encoding())?;
+        Ok(Self::from_seed_(seed))
+    }
+
+    fn from_seed_(seed: &Seed) -> Self {
+        let mut h = [0u8; DIGEST_LEN];
+        let mut ctx = Shake256::new();
+        ctx.update(seed);
+        ctx.squeeze(&mut h);
+        let (scalar_encoded, prefix_encoded) = h.split_at(SCALAR_LEN);
+
+        // RFC 8032 Section 5.2.5, step 2.
+        let mut scalar = [0u8; SCALAR_LEN];
+        scalar.copy_from_slice(scalar_encoded);
+        scalar[0] &= 252;
+        scalar[SCALAR_LEN - 1] = 0;
+        scalar[SCALAR_LEN - 2] |= 0x80;
+        let scalar = Scalar::from_bytes_reduced(&scalar);
+
+        let mut prefix = [0u8; PREFIX_LEN];
+        prefix.copy_from_slice(prefix_encoded);
+
+        let a = ExtPoint::base().mul(&scalar);
+
+        Self {
+            private_scalar: scalar,
+            private_prefix: prefix,
+            public_key: PublicKey(a.into_encoded_point()),
+        }
+    }
+
+    /// Returns the signature of the message `msg`.
+    pub fn sign(&self, msg: &[u8]) -> signature::Signature {
+        self.sign_(Dom4::new(&[]).unwrap(), msg)
+    }
+
+    /// Returns the signature of the message `msg` in the context `context`,
+    /// as specified in [RFC 8032 Section 5.2].
+    ///
+    /// Fails if `context` is longer than 255 bytes. Use
+    /// `signature::ED448.verify_ctx` to verify the signature.
+    ///
+    /// [RFC 8032 Section 5.2]: https://tools.ietf.org/html/rfc8032#section-5.2
+    pub fn sign_ctx(
+        &self,
+        context: &[u8],
+        msg: &[u8],
+    ) -> Result<signature::Signature, error::Unspecified> {
+        Ok(self.sign_(Dom4::new(context)?, msg))
+    }
+
+    fn sign_(&self, dom4: Dom4, msg: &[u8]) -> signature::Signature {
+        #[cfg(feature = "audit")]
+        crate::audit::record_public_key(
+            &super::verification::ED448,
+            crate::audit::Operation::Sign,
+            self.public_key.as_ref(),
+            msg.len(),
+        );
+        signature::Signature::new(|signature_bytes| {
+            let (signature_r, signature_s) =
+                signature_bytes[..SIGNATURE_LEN].split_at_mut(ED448_PUBLIC_KEY_LEN);
+            let nonce = digest_scalar(dom4, &[&self.private_prefix, msg]);
+
+            let r = ExtPoint::base().mul(&nonce).into_encoded_point();
+            signature_r.copy_from_slice(&r);
+            let hram = digest_scalar(dom4, &[&r, self.public_key.as_ref(), msg]);
+            let s = Scalar::mul_add(&hram, &self.private_scalar, &nonce);
+            signature_s.copy_from_slice(&s.to_bytes());
+
+            SIGNATURE_LEN
+        })
+    }
+}
+
+impl signature::KeyPair for Ed448KeyPair {
+    type PublicKey = PublicKey;
+
+    fn public_key(&self) -> &Self::PublicKey {
+        &self.public_key
+    }
+}
+
+#[cfg(feature = "use_heap")]
+impl signature::Signer for Ed448KeyPair {
+    fn public_key_bytes(&self) -> &[u8] {
+        self.public_key.as_ref()
+    }
+
+    fn supports(&self, scheme: signature::SignatureScheme) -> bool {
+        scheme == signature::SignatureScheme::ED448
+    }
+
+    fn sign(
+        &self,
+        scheme: signature::SignatureScheme,
+        _rng: &dyn rand::SecureRandom,
+        message: &[u8],
+    ) -> Result<std::vec::Vec<u8>, error::Unspecified> {
+        if !signature::Signer::supports(self, scheme) {
+            return Err(error::Unspecified);
+        }
+        Ok(Ed448KeyPair::sign(self, message).as_ref().to_vec())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct PublicKey([u8; ED448_PUBLIC_KEY_LEN]);
+
+impl AsRef<[u8]> for PublicKey {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+derive_debug_self_as_ref_hex_bytes!(PublicKey);
+
+fn unwrap_pkcs8(
+    version: pkcs8::Version,
+    input: untrusted::Input,
+) -> Result<(untrusted::Input, Option<untrusted::Input>), error::KeyRejected> {
+    let (private_key, public_key) = pkcs8::unwrap_key(&PKCS8_TEMPLATE, version, input)?;
+    let private_key = private_key
+        .read_all(error::Unspecified, |input| {
+            der::expect_tag_and_get_value(input, der::Tag::OctetString)
+        })
+        .map_err(|error::Unspecified| error::KeyRejected::invalid_encoding())?;
+    Ok((private_key, public_key))
+}
+
+type Prefix = [u8; PREFIX_LEN];
+const PREFIX_LEN: usize = DIGEST_LEN - SCALAR_LEN;
+
+const SIGNATURE_LEN: usize = ED448_PUBLIC_KEY_LEN + SCALAR_LEN;
+
+type Seed = [u8; SEED_LEN];
+const SEED_LEN: usize = 57;
+
+// Ed448 isn't allowed by `policy::Policy::FipsApproved`.
+const FIPS_APPROVED: bool = false;
+
+static PKCS8_TEMPLATE: pkcs8::Template = pkcs8::Template {
+    bytes: include_bytes!("ed448_pkcs8_v2_template.der"),
+    alg_id_range: core::ops::Range { start: 8, end: 13 },
+    curve_id_index: 0,
+    private_key_index: 0x11,
+};
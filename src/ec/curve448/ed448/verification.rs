@@ -0,0 +1,171 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Ed448 Signatures.
+
+use super::{
+    super::ops::{EncodedPoint, ExtPoint, Scalar, SCALAR_LEN},
+    digest::*,
+    ED448_PUBLIC_KEY_LEN,
+};
+use crate::{error, polyfill::convert::*, sealed, signature, spki};
+use untrusted;
+
+/// Parameters for Ed448 signing and verification.
+pub struct Ed448Parameters;
+
+impl core::fmt::Debug for Ed448Parameters {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        write!(f, "ring::signature::ED448")
+    }
+}
+
+/// Verification of Ed448 signatures, as specified in [RFC 8032 Section 5.2].
+///
+/// Ed448 uses SHAKE256 as the digest algorithm. The signature is checked with
+/// the equation [S]B = R + [k]A, without the cofactor, like `ED25519`.
+///
+/// [RFC 8032 Section 5.2]: https://tools.ietf.org/html/rfc8032#section-5.2
+pub static ED448: Ed448Parameters = Ed448Parameters {};
+
+impl Ed448Parameters {
+    /// Verifies the Ed448 signature `signature` of `msg` in the context
+    /// `context`, using the public key `public_key`, as specified in
+    /// [RFC 8032 Section 5.2].
+    ///
+    /// Fails if `context` is longer than 255 bytes.
+    ///
+    /// [RFC 8032 Section 5.2]: https://tools.ietf.org/html/rfc8032#section-5.2
+    pub fn verify_ctx(
+        &self,
+        public_key: &[u8],
+        context: &[u8],
+        msg: &[u8],
+        signature: &[u8],
+    ) -> Result<(), error::Unspecified> {
+        #[cfg(feature = "audit")]
+        crate::audit::record_public_key(
+            self,
+            crate::audit::Operation::Verify,
+            public_key,
+            msg.len(),
+        );
+        verify_(Dom4::new(context)?, public_key, msg, signature)
+    }
+}
+
+impl signature::VerificationAlgorithm for Ed448Parameters {
+    fn verify(
+        &self,
+        public_key: untrusted::Input,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), error::Unspecified> {
+        verify_(
+            Dom4::new(&[])?,
+            public_key.as_slice_less_safe(),
+            msg.as_slice_less_safe(),
+            signature.as_slice_less_safe(),
+        )
+    }
+
+    fn parse_public_key(
+        &self,
+        public_key: untrusted::Input,
+    ) -> Result<signature::ParsedKey, error::Unspecified> {
+        let public_key = ParsedPublicKey::new(public_key.as_slice_less_safe())?;
+        Ok(signature::ParsedKey::Ed448(public_key))
+    }
+
+    fn verify_parsed(
+        &self,
+        public_key: &signature::ParsedKey,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), error::Unspecified> {
+        match public_key {
+            signature::ParsedKey::Ed448(public_key) => verify_parsed(
+                Dom4::new(&[])?,
+                public_key,
+                msg.as_slice_less_safe(),
+                signature.as_slice_less_safe(),
+            ),
+            _ => Err(error::Unspecified),
+        }
+    }
+
+    fn fips_approved(&self) -> bool {
+        false
+    }
+}
+
+/// A decoded Ed448 public key.
+pub struct ParsedPublicKey {
+    encoded: EncodedPoint,
+
+    // The negation of the public key point, as needed by `verify_parsed`.
+    minus_a: ExtPoint,
+}
+
+impl ParsedPublicKey {
+    fn new(public_key: &[u8]) -> Result<Self, error::Unspecified> {
+        let encoded: &EncodedPoint = public_key.try_into_()?;
+        let a = ExtPoint::from_encoded_point_vartime(encoded)?;
+        Ok(Self {
+            encoded: *encoded,
+            minus_a: a.negate(),
+        })
+    }
+}
+
+fn verify_(
+    dom4: Dom4,
+    public_key: &[u8],
+    msg: &[u8],
+    signature: &[u8],
+) -> Result<(), error::Unspecified> {
+    let public_key = ParsedPublicKey::new(public_key)?;
+    verify_parsed(dom4, &public_key, msg, signature)
+}
+
+fn verify_parsed(
+    dom4: Dom4,
+    public_key: &ParsedPublicKey,
+    msg: &[u8],
+    signature: &[u8],
+) -> Result<(), error::Unspecified> {
+    let signature: &[u8; ED448_PUBLIC_KEY_LEN + SCALAR_LEN] = signature.try_into_()?;
+    let (signature_r, signature_s) = signature.split_at(ED448_PUBLIC_KEY_LEN);
+    let signature_s: &[u8; SCALAR_LEN] = signature_s.try_into_()?;
+
+    // Ensure `s` is not too large.
+    let s = Scalar::from_bytes_checked_vartime(signature_s)?;
+
+    let k = digest_scalar(dom4, &[signature_r, &public_key.encoded, msg]);
+
+    let r = ExtPoint::base().mul(&s).add(&public_key.minus_a.mul(&k));
+    let r_check = r.into_encoded_point();
+    if signature_r != &r_check[..] {
+        return Err(error::Unspecified);
+    }
+    Ok(())
+}
+
+impl sealed::Sealed for Ed448Parameters {}
+
+impl spki::PublicKeyAlgorithm for Ed448Parameters {
+    fn public_key_algorithm_id(&self) -> &'static [u8] {
+        include_bytes!("../../../data/alg-ed448.der")
+    }
+}
@@ -0,0 +1,624 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Elliptic curve operations on the birationally equivalent curves Curve448
+//! and Edwards448.
+//!
+//! Field elements are represented with eight 56-bit limbs, so that the
+//! reduction modulo p = 2**448 - 2**224 - 1 is a matter of adding the high
+//! limbs into limbs 0 and 4. Everything here is constant-time with respect to
+//! the values of field elements and scalars, except where noted.
+
+use crate::error;
+
+pub const ELEM_LEN: usize = 56;
+pub const SCALAR_LEN: usize = 57;
+
+// An encoding of an Edwards448 point, as described in RFC 8032 Section 5.2.2.
+pub const ENCODED_POINT_LEN: usize = 57;
+pub type EncodedPoint = [u8; ENCODED_POINT_LEN];
+
+const LIMBS: usize = 8;
+const LIMB_BITS: u32 = 56;
+const LIMB_MASK: u64 = (1 << LIMB_BITS) - 1;
+
+// p in the limb representation.
+const P: [u64; LIMBS] = [
+    LIMB_MASK,
+    LIMB_MASK,
+    LIMB_MASK,
+    LIMB_MASK,
+    LIMB_MASK - 1,
+    LIMB_MASK,
+    LIMB_MASK,
+    LIMB_MASK,
+];
+
+// Returns all ones if `bit` is 1 and zero if it is 0.
+#[inline]
+fn mask(bit: u64) -> u64 {
+    0u64.wrapping_sub(bit)
+}
+
+/// An element of GF(p). The limbs are always less than 2**57, but the value
+/// isn't necessarily fully reduced.
+#[derive(Clone, Copy)]
+pub struct Elem([u64; LIMBS]);
+
+impl Elem {
+    pub const ZERO: Self = Elem([0; LIMBS]);
+    pub const ONE: Self = Elem([1, 0, 0, 0, 0, 0, 0, 0]);
+
+    /// Decodes a little-endian encoded value. Values that are not fully
+    /// reduced are accepted.
+    pub fn from_bytes(bytes: &[u8; ELEM_LEN]) -> Self {
+        let mut limbs = [0u64; LIMBS];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(7)) {
+            *limb = chunk
+                .iter()
+                .rev()
+                .fold(0, |acc, &b| (acc << 8) | u64::from(b));
+        }
+        Elem(limbs)
+    }
+
+    /// Returns the little-endian encoding of the fully-reduced value.
+    pub fn to_bytes(self) -> [u8; ELEM_LEN] {
+        let limbs = self.strong_reduce();
+        let mut bytes = [0u8; ELEM_LEN];
+        for (chunk, limb) in bytes.chunks_mut(7).zip(limbs.iter()) {
+            for (i, b) in chunk.iter_mut().enumerate() {
+                *b = (limb >> (8 * i)) as u8;
+            }
+        }
+        bytes
+    }
+
+    pub fn add(&self, b: &Self) -> Self {
+        let mut r = self.0;
+        for (r, b) in r.iter_mut().zip(b.0.iter()) {
+            *r += b;
+        }
+        Elem(weak_reduce(r))
+    }
+
+    pub fn sub(&self, b: &Self) -> Self {
+        // Add 2p to avoid underflow.
+        let mut r = [0u64; LIMBS];
+        for i in 0..LIMBS {
+            r[i] = self.0[i] + (2 * P[i]) - b.0[i];
+        }
+        Elem(weak_reduce(r))
+    }
+
+    pub fn neg(&self) -> Self {
+        Self::ZERO.sub(self)
+    }
+
+    pub fn mul(&self, b: &Self) -> Self {
+        let (a, b) = (&self.0, &b.0);
+        let mut c = [0u128; 2 * LIMBS];
+        for i in 0..LIMBS {
+            for j in 0..LIMBS {
+                c[i + j] += u128::from(a[i]) * u128::from(b[j]);
+            }
+        }
+        // 2**448 == 2**224 + 1 (mod p). Going from the top down, folded values
+        // that land in the high half are folded again.
+        for k in (LIMBS..(2 * LIMBS)).rev() {
+            c[k - LIMBS] += c[k];
+            c[k - (LIMBS / 2)] += c[k];
+        }
+        let mut r = [0u128; LIMBS];
+        r.copy_from_slice(&c[..LIMBS]);
+        carry_wide(r)
+    }
+
+    pub fn square(&self) -> Self {
+        self.mul(self)
+    }
+
+    pub fn mul_small(&self, k: u32) -> Self {
+        let mut r = [0u128; LIMBS];
+        for (r, &a) in r.iter_mut().zip(self.0.iter()) {
+            *r = u128::from(a) * u128::from(k);
+        }
+        carry_wide(r)
+    }
+
+    pub fn invert(&self) -> Self {
+        // p - 2.
+        const P_MINUS_2: [u64; 7] = [
+            0xffff_ffff_ffff_fffd,
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+            0xffff_fffe_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+        ];
+        self.pow(&P_MINUS_2)
+    }
+
+    // Returns `self**((p - 3) / 4)`.
+    fn pow_p_minus_3_div_4(&self) -> Self {
+        const P_MINUS_3_DIV_4: [u64; 7] = [
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_bfff_ffff,
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+            0x3fff_ffff_ffff_ffff,
+        ];
+        self.pow(&P_MINUS_3_DIV_4)
+    }
+
+    // The exponent is public, so only the base is treated as secret.
+    fn pow(&self, exponent: &[u64; 7]) -> Self {
+        let mut r = Self::ONE;
+        for i in (0..(64 * exponent.len())).rev() {
+            r = r.square();
+            if (exponent[i / 64] >> (i % 64)) & 1 == 1 {
+                r = r.mul(self);
+            }
+        }
+        r
+    }
+
+    // Returns 1 if the value is zero (mod p) and 0 otherwise.
+    pub fn is_zero(&self) -> u64 {
+        let acc = self.strong_reduce().iter().fold(0, |acc, &limb| acc | limb);
+        1 ^ ((acc | acc.wrapping_neg()) >> 63)
+    }
+
+    // Returns the least significant bit of the fully-reduced value; RFC 8032
+    // calls elements for which this is 1 "negative".
+    pub fn is_negative(&self) -> u64 {
+        self.strong_reduce()[0] & 1
+    }
+
+    /// Sets `self` to `b` if `bit` is 1; leaves it unchanged if `bit` is 0.
+    pub fn conditional_assign(&mut self, b: &Self, bit: u64) {
+        let m = mask(bit);
+        for i in 0..LIMBS {
+            self.0[i] ^= m & (self.0[i] ^ b.0[i]);
+        }
+    }
+
+    /// Swaps `a` and `b` if `bit` is 1; leaves them unchanged if `bit` is 0.
+    pub fn conditional_swap(a: &mut Self, b: &mut Self, bit: u64) {
+        let m = mask(bit);
+        for i in 0..LIMBS {
+            let t = m & (a.0[i] ^ b.0[i]);
+            a.0[i] ^= t;
+            b.0[i] ^= t;
+        }
+    }
+
+    // Returns the limbs of the value fully reduced modulo p.
+    fn strong_reduce(&self) -> [u64; LIMBS] {
+        let mut r = weak_reduce(self.0);
+
+        // Now the value is less than 2p. Subtract p...
+        let mut borrow = 0i128;
+        for i in 0..LIMBS {
+            borrow += i128::from(r[i]) - i128::from(P[i]);
+            r[i] = (borrow as u64) & LIMB_MASK;
+            borrow >>= LIMB_BITS;
+        }
+        debug_assert!(borrow == 0 || borrow == -1);
+
+        // ...and add it back if that underflowed.
+        let add_back = borrow as u64;
+        let mut carry = 0u64;
+        for i in 0..LIMBS {
+            carry += r[i] + (add_back & P[i]);
+            r[i] = carry & LIMB_MASK;
+            carry >>= LIMB_BITS;
+        }
+        r
+    }
+}
+
+// Reduces limbs of less than 2**63 to limbs of less than 2**56 + 2**8.
+fn weak_reduce(mut a: [u64; LIMBS]) -> [u64; LIMBS] {
+    let top = a[LIMBS - 1] >> LIMB_BITS;
+    a[LIMBS / 2] += top;
+    for i in (1..LIMBS).rev() {
+        a[i] = (a[i] & LIMB_MASK) + (a[i - 1] >> LIMB_BITS);
+    }
+    a[0] = (a[0] & LIMB_MASK) + top;
+    a
+}
+
+fn carry_wide(mut r: [u128; LIMBS]) -> Elem {
+    for i in 0..(LIMBS - 1) {
+        r[i + 1] += r[i] >> LIMB_BITS;
+        r[i] &= u128::from(LIMB_MASK);
+    }
+    let top = r[LIMBS - 1] >> LIMB_BITS;
+    r[LIMBS - 1] &= u128::from(LIMB_MASK);
+    r[0] += top;
+    r[LIMBS / 2] += top;
+    r[1] += r[0] >> LIMB_BITS;
+    r[0] &= u128::from(LIMB_MASK);
+    r[(LIMBS / 2) + 1] += r[LIMBS / 2] >> LIMB_BITS;
+    r[LIMBS / 2] &= u128::from(LIMB_MASK);
+
+    let mut limbs = [0u64; LIMBS];
+    for i in 0..LIMBS {
+        limbs[i] = r[i] as u64;
+    }
+    Elem(limbs)
+}
+
+// d = -39081.
+const EDWARDS_D_NEG: u32 = 39081;
+
+/// A point on Edwards448 in extended coordinates (X : Y : Z : T), where
+/// x = X/Z, y = Y/Z, and x*y = T/Z.
+#[derive(Clone, Copy)]
+pub struct ExtPoint {
+    x: Elem,
+    y: Elem,
+    z: Elem,
+    t: Elem,
+}
+
+impl ExtPoint {
+    pub fn identity() -> Self {
+        Self {
+            x: Elem::ZERO,
+            y: Elem::ONE,
+            z: Elem::ONE,
+            t: Elem::ZERO,
+        }
+    }
+
+    /// The base point B of RFC 8032 Section 5.2.
+    pub fn base() -> Self {
+        let x = Elem([
+            0x0026_a82b_c70c_c05e,
+            0x0080_e18b_0093_8e26,
+            0x00f7_2ab6_6511_433b,
+            0x00a3_d3a4_6412_ae1a,
+            0x000f_1767_ea6d_e324,
+            0x0036_da9e_1465_7047,
+            0x00ed_221d_15a6_22bf,
+            0x004f_1970_c66b_ed0d,
+        ]);
+        let y = Elem([
+            0x0008_795b_f230_fa14,
+            0x0013_2c4e_d7c8_ad98,
+            0x001c_e67c_39c4_fdbd,
+            0x0005_a0c2_d73a_d3ff,
+            0x00a3_9840_8778_9c1e,
+            0x00c7_624b_ea73_736c,
+            0x0024_8876_2037_56c9,
+            0x0069_3f46_716e_b6bc,
+        ]);
+        Self {
+            x,
+            y,
+            z: Elem::ONE,
+            t: x.mul(&y),
+        }
+    }
+
+    /// Decodes a point as described in RFC 8032 Section 5.2.3. This is not
+    /// constant-time, so it must only be used for public values.
+    pub fn from_encoded_point_vartime(encoded: &EncodedPoint) -> Result<Self, error::Unspecified> {
+        let (y_bytes, last) = encoded.split_at(ELEM_LEN);
+        if last[0] & 0x7f != 0 {
+            return Err(error::Unspecified);
+        }
+        let x_0 = u64::from(last[0] >> 7);
+
+        let mut y_encoded = [0u8; ELEM_LEN];
+        y_encoded.copy_from_slice(y_bytes);
+        let y = Elem::from_bytes(&y_encoded);
+        if y.to_bytes() != y_encoded {
+            return Err(error::Unspecified); // y >= p.
+        }
+
+        // x**2 = u/v where u = y**2 - 1 and v = d*y**2 - 1.
+        let yy = y.square();
+        let u = yy.sub(&Elem::ONE);
+        let v = yy.mul_small(EDWARDS_D_NEG).neg().sub(&Elem::ONE);
+
+        // x = u**3 * v * (u**5 * v**3)**((p - 3) / 4).
+        let u3v = u.square().mul(&u).mul(&v);
+        let u5v3 = u3v.mul(&u.square()).mul(&v.square());
+        let mut x = u3v.mul(&u5v3.pow_p_minus_3_div_4());
+        if v.mul(&x.square()).sub(&u).is_zero() != 1 {
+            return Err(error::Unspecified);
+        }
+
+        if x.is_zero() == 1 && x_0 == 1 {
+            return Err(error::Unspecified);
+        }
+        if x.is_negative() != x_0 {
+            x = x.neg();
+        }
+
+        Ok(Self {
+            x,
+            y,
+            z: Elem::ONE,
+            t: x.mul(&y),
+        })
+    }
+
+    pub fn into_encoded_point(self) -> EncodedPoint {
+        let z_inv = self.z.invert();
+        let x = self.x.mul(&z_inv);
+        let y = self.y.mul(&z_inv);
+
+        let mut encoded = [0u8; ENCODED_POINT_LEN];
+        encoded[..ELEM_LEN].copy_from_slice(&y.to_bytes());
+        encoded[ELEM_LEN] = (x.is_negative() as u8) << 7;
+        encoded
+    }
+
+    pub fn negate(&self) -> Self {
+        Self {
+            x: self.x.neg(),
+            y: self.y,
+            z: self.z,
+            t: self.t.neg(),
+        }
+    }
+
+    /// The "add-2008-hwcd" formulas, which are complete on Edwards448.
+    pub fn add(&self, b: &Self) -> Self {
+        let a = self.x.mul(&b.x);
+        let bb = self.y.mul(&b.y);
+        let c = self.t.mul(&b.t).mul_small(EDWARDS_D_NEG).neg();
+        let d = self.z.mul(&b.z);
+        let e = self.x.add(&self.y).mul(&b.x.add(&b.y)).sub(&a).sub(&bb);
+        let f = d.sub(&c);
+        let g = d.add(&c);
+        let h = bb.sub(&a);
+        Self {
+            x: e.mul(&f),
+            y: g.mul(&h),
+            z: f.mul(&g),
+            t: e.mul(&h),
+        }
+    }
+
+    /// The "dbl-2008-hwcd" formulas.
+    pub fn double(&self) -> Self {
+        let a = self.x.square();
+        let b = self.y.square();
+        let c = self.z.square().mul_small(2);
+        let e = self.x.add(&self.y).square().sub(&a).sub(&b);
+        let g = a.add(&b);
+        let f = g.sub(&c);
+        let h = a.sub(&b);
+        Self {
+            x: e.mul(&f),
+            y: g.mul(&h),
+            z: f.mul(&g),
+            t: e.mul(&h),
+        }
+    }
+
+    /// Returns `scalar * self`, using fixed 4-bit windows.
+    pub fn mul(&self, scalar: &Scalar) -> Self {
+        let mut table = [Self::identity(); 16];
+        for i in 1..16 {
+            table[i] = table[i - 1].add(self);
+        }
+
+        let mut r = Self::identity();
+        for i in (0..(SCALAR_WORDS * 16)).rev() {
+            r = r.double().double().double().double();
+            let window = (scalar.0[i / 16] >> (4 * (i % 16))) & 0xf;
+            let mut selected = Self::identity();
+            for (j, entry) in table.iter().enumerate() {
+                let bit =
+                    1 ^ (((window ^ (j as u64)) | (window ^ (j as u64)).wrapping_neg()) >> 63);
+                selected.conditional_assign(entry, bit);
+            }
+            r = r.add(&selected);
+        }
+        r
+    }
+
+    fn conditional_assign(&mut self, b: &Self, bit: u64) {
+        self.x.conditional_assign(&b.x, bit);
+        self.y.conditional_assign(&b.y, bit);
+        self.z.conditional_assign(&b.z, bit);
+        self.t.conditional_assign(&b.t, bit);
+    }
+}
+
+const SCALAR_WORDS: usize = 7;
+
+// L, the order of the base point.
+const L: [u64; SCALAR_WORDS] = [
+    0x2378_c292_ab58_44f3,
+    0x216c_c272_8dc5_8f55,
+    0xc44e_db49_aed6_3690,
+    0xffff_ffff_7cca_23e9,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+    0x3fff_ffff_ffff_ffff,
+];
+
+/// An integer modulo L, fully reduced.
+#[derive(Clone, Copy)]
+pub struct Scalar([u64; SCALAR_WORDS]);
+
+impl Scalar {
+    /// Reduces the little-endian encoded value `bytes` modulo L.
+    pub fn from_bytes_reduced(bytes: &[u8]) -> Self {
+        // Shift the value in one bit at a time, subtracting L whenever the
+        // result isn't less than L. The result of each shift is less than
+        // 2L < 2**447, so it fits.
+        let mut r = [0u64; SCALAR_WORDS];
+        for &byte in bytes.iter().rev() {
+            for bit in (0..8).rev() {
+                let mut carry = u64::from(byte >> bit) & 1;
+                for word in r.iter_mut() {
+                    let shifted = (*word << 1) | carry;
+                    carry = *word >> 63;
+                    *word = shifted;
+                }
+
+                let mut difference = [0u64; SCALAR_WORDS];
+                let mut borrow = 0u64;
+                for i in 0..SCALAR_WORDS {
+                    let (d, b1) = r[i].overflowing_sub(L[i]);
+                    let (d, b2) = d.overflowing_sub(borrow);
+                    difference[i] = d;
+                    borrow = u64::from(b1 | b2);
+                }
+                let keep = mask(borrow);
+                for i in 0..SCALAR_WORDS {
+                    r[i] = (r[i] & keep) | (difference[i] & !keep);
+                }
+            }
+        }
+        Scalar(r)
+    }
+
+    /// Decodes the little-endian encoded value `bytes`, which must be less
+    /// than L. This is not constant-time.
+    pub fn from_bytes_checked_vartime(
+        bytes: &[u8; SCALAR_LEN],
+    ) -> Result<Self, error::Unspecified> {
+        if bytes[SCALAR_LEN - 1] != 0 {
+            return Err(error::Unspecified);
+        }
+        let mut r = [0u64; SCALAR_WORDS];
+        for (word, chunk) in r.iter_mut().zip(bytes.chunks(8)) {
+            *word = chunk
+                .iter()
+                .rev()
+                .fold(0, |acc, &b| (acc << 8) | u64::from(b));
+        }
+        for i in (0..SCALAR_WORDS).rev() {
+            if r[i] < L[i] {
+                return Ok(Scalar(r));
+            }
+            if r[i] > L[i] {
+                break;
+            }
+        }
+        Err(error::Unspecified)
+    }
+
+    /// Returns `(a * b + c) mod L`.
+    pub fn mul_add(a: &Self, b: &Self, c: &Self) -> Self {
+        let mut product = [0u64; 2 * SCALAR_WORDS];
+        for i in 0..SCALAR_WORDS {
+            let mut carry = 0u128;
+            for j in 0..SCALAR_WORDS {
+                let t =
+                    u128::from(a.0[i]) * u128::from(b.0[j]) + u128::from(product[i + j]) + carry;
+                product[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            product[i + SCALAR_WORDS] = carry as u64;
+        }
+        let mut carry = 0u128;
+        for (i, word) in product.iter_mut().enumerate() {
+            let t = u128::from(*word) + u128::from(c.0.get(i).map_or(0, |&w| w)) + carry;
+            *word = t as u64;
+            carry = t >> 64;
+        }
+
+        let mut bytes = [0u8; 2 * SCALAR_WORDS * 8];
+        for (chunk, word) in bytes.chunks_mut(8).zip(product.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        Self::from_bytes_reduced(&bytes)
+    }
+
+    pub fn to_bytes(self) -> [u8; SCALAR_LEN] {
+        let mut bytes = [0u8; SCALAR_LEN];
+        for (chunk, word) in bytes.chunks_mut(8).zip(self.0.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_point_round_trip() {
+        let encoded = ExtPoint::base().into_encoded_point();
+        let decoded = ExtPoint::from_encoded_point_vartime(&encoded).unwrap();
+        assert_eq!(&decoded.into_encoded_point()[..], &encoded[..]);
+    }
+
+    #[test]
+    fn test_base_point_order() {
+        let mut l_minus_1 = L;
+        l_minus_1[0] -= 1;
+        let p = ExtPoint::base().mul(&Scalar(l_minus_1));
+        assert_eq!(
+            &p.into_encoded_point()[..],
+            &ExtPoint::base().negate().into_encoded_point()[..]
+        );
+    }
+
+    #[test]
+    fn test_elem_reduction() {
+        // p is encoded as 0, and (p - 1) + 1 == 0.
+        let mut p = [0xffu8; ELEM_LEN];
+        p[28] = 0xfe;
+        assert_eq!(Elem::from_bytes(&p).to_bytes(), [0u8; ELEM_LEN]);
+        assert_eq!(Elem::from_bytes(&p).is_zero(), 1);
+        p[0] = 0xfe;
+        let mut one = [0u8; ELEM_LEN];
+        one[0] = 1;
+        assert_eq!(
+            Elem::from_bytes(&p).add(&Elem::ONE).to_bytes(),
+            [0u8; ELEM_LEN]
+        );
+        assert_eq!(Elem::ZERO.sub(&Elem::ONE).neg().to_bytes(), one);
+    }
+
+    #[test]
+    fn test_scalar_reduction() {
+        let l = Scalar(L).to_bytes();
+        assert!(Scalar::from_bytes_checked_vartime(&l).is_err());
+        assert_eq!(Scalar::from_bytes_reduced(&l).to_bytes(), [0u8; SCALAR_LEN]);
+
+        let mut l_plus_1 = l;
+        l_plus_1[0] += 1;
+        let mut one = [0u8; SCALAR_LEN];
+        one[0] = 1;
+        assert_eq!(Scalar::from_bytes_reduced(&l_plus_1).to_bytes(), one);
+
+        let mut l_minus_1 = l;
+        l_minus_1[0] -= 1;
+        let l_minus_1 = Scalar::from_bytes_checked_vartime(&l_minus_1).unwrap();
+        // (L - 1)**2 + 1 == 2 (mod L).
+        let one = Scalar::from_bytes_reduced(&one);
+        let mut two = [0u8; SCALAR_LEN];
+        two[0] = 2;
+        assert_eq!(
+            Scalar::mul_add(&l_minus_1, &l_minus_1, &one).to_bytes(),
+            two
+        );
+    }
+}
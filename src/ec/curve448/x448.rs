@@ -0,0 +1,159 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! X448 Key agreement.
+
+use super::ops::{self, Elem};
+use crate::{agreement, constant_time, cpu, ec, error, pkcs8, polyfill::convert::*, rand};
+use untrusted;
+
+static CURVE448: ec::Curve = ec::Curve {
+    public_key_len: PUBLIC_KEY_LEN,
+    elem_scalar_seed_len: ELEM_AND_SCALAR_LEN,
+    id: ec::CurveID::Curve448,
+    check_private_key_bytes: x448_check_private_key_bytes,
+    generate_private_key: x448_generate_private_key,
+    public_from_private: x448_public_from_private,
+};
+
+/// X448 (ECDH using Curve448) as described in [RFC 7748].
+///
+/// Everything is as described in RFC 7748. Key agreement will fail if the
+/// result of the X448 operation is zero; see the notes on the "all-zero value"
+/// in [RFC 7748 section 6.2].
+///
+/// [RFC 7748]: https://tools.ietf.org/html/rfc7748
+/// [RFC 7748 section 6.2]: https://tools.ietf.org/html/rfc7748#section-6.2
+pub static X448: agreement::Algorithm = agreement::Algorithm {
+    curve: &CURVE448,
+    ecdh: x448_ecdh,
+    pkcs8_template: &PKCS8_TEMPLATE,
+    private_key_from_pkcs8: x448_private_key_from_pkcs8,
+};
+
+fn x448_check_private_key_bytes(bytes: &[u8]) -> Result<(), error::Unspecified> {
+    debug_assert_eq!(bytes.len(), PRIVATE_KEY_LEN);
+    Ok(())
+}
+
+fn x448_generate_private_key(
+    rng: &dyn rand::SecureRandom,
+    out: &mut [u8],
+) -> Result<(), error::Unspecified> {
+    rng.fill(out)
+}
+
+fn x448_public_from_private(
+    public_out: &mut [u8],
+    private_key: &ec::Seed,
+) -> Result<(), error::Unspecified> {
+    let public_out = public_out.try_into_()?;
+    let private_key = private_key.bytes_less_safe().try_into_()?;
+
+    let mut montgomery_base_point: PublicKey = [0; PUBLIC_KEY_LEN];
+    montgomery_base_point[0] = 5;
+    scalar_mult(public_out, private_key, &montgomery_base_point);
+
+    Ok(())
+}
+
+fn x448_private_key_from_pkcs8(
+    input: untrusted::Input,
+    cpu_features: cpu::Features,
+) -> Result<ec::Seed, error::KeyRejected> {
+    ec::Seed::from_rfc8410_pkcs8(&CURVE448, &PKCS8_TEMPLATE, input, cpu_features)
+}
+
+fn x448_ecdh(
+    out: &mut [u8],
+    my_private_key: &ec::Seed,
+    peer_public_key: untrusted::Input,
+) -> Result<(), error::Unspecified> {
+    let my_private_key = my_private_key.bytes_less_safe().try_into_()?;
+    let peer_public_key: &PublicKey = peer_public_key.as_slice_less_safe().try_into_()?;
+
+    scalar_mult(out.try_into_()?, my_private_key, peer_public_key);
+
+    let zeros: SharedSecret = [0; SHARED_SECRET_LEN];
+    if constant_time::verify_slices_are_equal(out, &zeros).is_ok() {
+        // All-zero output results when the input is a point of small order.
+        return Err(error::Unspecified);
+    }
+
+    Ok(())
+}
+
+// The Montgomery ladder of RFC 7748 Section 5.
+fn scalar_mult(out: &mut SharedSecret, scalar: &PrivateKey, point: &PublicKey) {
+    const A24: u32 = 39081;
+
+    let mut k = *scalar;
+    k[0] &= 252;
+    k[ELEM_AND_SCALAR_LEN - 1] |= 128;
+
+    // Non-canonical encodings are accepted as RFC 7748 requires.
+    let x_1 = Elem::from_bytes(point);
+    let mut x_2 = Elem::ONE;
+    let mut z_2 = Elem::ZERO;
+    let mut x_3 = x_1;
+    let mut z_3 = Elem::ONE;
+    let mut swap = 0;
+
+    for t in (0..(8 * ELEM_AND_SCALAR_LEN)).rev() {
+        let k_t = u64::from(k[t / 8] >> (t % 8)) & 1;
+        swap ^= k_t;
+        Elem::conditional_swap(&mut x_2, &mut x_3, swap);
+        Elem::conditional_swap(&mut z_2, &mut z_3, swap);
+        swap = k_t;
+
+        let a = x_2.add(&z_2);
+        let aa = a.square();
+        let b = x_2.sub(&z_2);
+        let bb = b.square();
+        let e = aa.sub(&bb);
+        let c = x_3.add(&z_3);
+        let d = x_3.sub(&z_3);
+        let da = d.mul(&a);
+        let cb = c.mul(&b);
+        x_3 = da.add(&cb).square();
+        z_3 = x_1.mul(&da.sub(&cb).square());
+        x_2 = aa.mul(&bb);
+        z_2 = e.mul(&aa.add(&e.mul_small(A24)));
+    }
+    Elem::conditional_swap(&mut x_2, &mut x_3, swap);
+    Elem::conditional_swap(&mut z_2, &mut z_3, swap);
+
+    *out = x_2.mul(&z_2.invert()).to_bytes();
+}
+
+const ELEM_AND_SCALAR_LEN: usize = ops::ELEM_LEN;
+
+// An X448 private key as an unmasked scalar.
+type PrivateKey = [u8; PRIVATE_KEY_LEN];
+const PRIVATE_KEY_LEN: usize = ELEM_AND_SCALAR_LEN;
+
+// An X448 public key as an encoded Curve448 point.
+type PublicKey = [u8; PUBLIC_KEY_LEN];
+const PUBLIC_KEY_LEN: usize = ELEM_AND_SCALAR_LEN;
+
+// An X448 shared secret as an encoded Curve448 point.
+type SharedSecret = [u8; SHARED_SECRET_LEN];
+const SHARED_SECRET_LEN: usize = ELEM_AND_SCALAR_LEN;
+
+static PKCS8_TEMPLATE: pkcs8::Template = pkcs8::Template {
+    bytes: include_bytes!("x448_pkcs8_v2_template.der"),
+    alg_id_range: core::ops::Range { start: 8, end: 13 },
+    curve_id_index: 0,
+    private_key_index: 0x11,
+};
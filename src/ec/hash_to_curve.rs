@@ -0,0 +1,198 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Hashing to elliptic curves, from RFC 9380.
+
+use super::{
+    curve25519,
+    suite_b::{
+        self,
+        ops::{p256, Point},
+    },
+};
+use crate::{digest, error};
+
+/// A hash-to-curve suite: a curve, a hash function for
+/// `expand_message_xmd`, and a mapping from field elements to points.
+pub struct Suite {
+    id: &'static str,
+    hash_to_curve: fn(msg: &[u8], dst: &[u8]) -> Result<EncodedPoint, error::Unspecified>,
+}
+
+impl Suite {
+    /// The RFC 9380 suite ID, e.g. `"P256_XMD:SHA-256_SSWU_RO_"`.
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+}
+
+impl core::fmt::Debug for Suite {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.write_str(self.id)
+    }
+}
+
+/// P256_XMD:SHA-256_SSWU_RO_ from RFC 9380 Section 8.2.
+///
+/// The output is an uncompressed SEC 1 point.
+pub static P256_XMD_SHA256_SSWU_RO: Suite = Suite {
+    id: "P256_XMD:SHA-256_SSWU_RO_",
+    hash_to_curve: p256_hash_to_curve,
+};
+
+/// edwards25519_XMD:SHA-512_ELL2_RO_ from RFC 9380 Section 8.5.
+///
+/// The output is an RFC 8032 encoded point.
+pub static EDWARDS25519_XMD_SHA512_ELL2_RO: Suite = Suite {
+    id: "edwards25519_XMD:SHA-512_ELL2_RO_",
+    hash_to_curve: edwards25519_hash_to_curve,
+};
+
+const ENCODED_POINT_MAX_LEN: usize = 1 + (2 * 32);
+
+/// A point produced by `hash_to_curve`, in the encoding documented for its
+/// `Suite`.
+#[derive(Clone, Copy)]
+pub struct EncodedPoint {
+    bytes: [u8; ENCODED_POINT_MAX_LEN],
+    len: usize,
+}
+
+impl AsRef<[u8]> for EncodedPoint {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+derive_debug_self_as_ref_hex_bytes!(EncodedPoint);
+
+/// Hashes `msg` to a point on the suite's curve.
+///
+/// `dst` is the domain separation tag, which must not be empty. RFC 9380
+/// Section 3.1 describes how to choose it.
+pub fn hash_to_curve(
+    suite: &'static Suite,
+    msg: &[u8],
+    dst: &[u8],
+) -> Result<EncodedPoint, error::Unspecified> {
+    (suite.hash_to_curve)(msg, dst)
+}
+
+/// Fills `out` with `expand_message_xmd(msg, dst, out.len())` from RFC 9380
+/// Section 5.3.1, using the hash function `algorithm`.
+///
+/// This fails if `dst` is empty or if `out` is longer than 255 digests.
+/// Domain separation tags longer than 255 bytes are hashed as described in
+/// RFC 9380 Section 5.3.3.
+pub fn expand_message_xmd(
+    algorithm: &'static digest::Algorithm,
+    msg: &[u8],
+    dst: &[u8],
+    out: &mut [u8],
+) -> Result<(), error::Unspecified> {
+    let b_in_bytes = algorithm.output_len;
+    // This also limits `out.len()` to much less than the 65535 bytes that
+    // the length encoding allows.
+    let ell = (out.len() + b_in_bytes - 1) / b_in_bytes;
+    if dst.is_empty() || ell > 255 {
+        return Err(error::Unspecified);
+    }
+
+    let oversize_dst;
+    let dst = if dst.len() > 255 {
+        oversize_dst = digest::digest_all(algorithm, &[b"H2C-OVERSIZE-DST-", dst]);
+        oversize_dst.as_ref()
+    } else {
+        dst
+    };
+    let dst_len = [dst.len() as u8];
+    let len_in_bytes = (out.len() as u16).to_be_bytes();
+
+    let b_0 = {
+        let mut ctx = digest::Context::new(algorithm);
+        ctx.update(&[0u8; digest::MAX_BLOCK_LEN][..algorithm.block_len]);
+        ctx.update(msg);
+        ctx.update(&len_in_bytes);
+        ctx.update(&[0]);
+        ctx.update(dst);
+        ctx.update(&dst_len);
+        ctx.finish()
+    };
+
+    let mut b_i = digest::digest_all(algorithm, &[b_0.as_ref(), &[1], dst, &dst_len]);
+    for (i, chunk) in out.chunks_mut(b_in_bytes).enumerate() {
+        if i > 0 {
+            let mut xored = [0u8; digest::MAX_OUTPUT_LEN];
+            let xored = &mut xored[..b_in_bytes];
+            for (x, (a, b)) in xored
+                .iter_mut()
+                .zip(b_0.as_ref().iter().zip(b_i.as_ref().iter()))
+            {
+                *x = a ^ b;
+            }
+            b_i = digest::digest_all(algorithm, &[xored, &[(i + 1) as u8], dst, &dst_len]);
+        }
+        chunk.copy_from_slice(&b_i.as_ref()[..chunk.len()]);
+    }
+
+    Ok(())
+}
+
+fn p256_hash_to_curve(msg: &[u8], dst: &[u8]) -> Result<EncodedPoint, error::Unspecified> {
+    use suite_b::hash_to_curve::{elem_reduced, map_to_curve, HASHED_ELEM_LEN};
+
+    let mut uniform_bytes = [0u8; 2 * HASHED_ELEM_LEN];
+    expand_message_xmd(&digest::SHA256, msg, dst, &mut uniform_bytes)?;
+    let (u0, u1) = uniform_bytes.split_at(HASHED_ELEM_LEN);
+    let q0 = map_to_curve(&elem_reduced(u0));
+    let q1 = map_to_curve(&elem_reduced(u1));
+    let p: Point = p256::COMMON_OPS.point_sum(&q0, &q1);
+
+    // P-256 has cofactor 1, so the result only needs to be checked for the
+    // point at infinity, which has negligible probability.
+    p256::COMMON_OPS.elem_verify_is_not_zero(&p256::COMMON_OPS.point_z(&p))?;
+
+    let mut r = EncodedPoint {
+        bytes: [0u8; ENCODED_POINT_MAX_LEN],
+        len: 1 + (2 * 32),
+    };
+    r.bytes[0] = 4; // Uncompressed.
+    let (x, y) = r.bytes[1..].split_at_mut(32);
+    suite_b::private_key::big_endian_affine_from_jacobian(
+        &p256::PRIVATE_KEY_OPS,
+        Some(x),
+        Some(y),
+        &p,
+    )?;
+    Ok(r)
+}
+
+fn edwards25519_hash_to_curve(msg: &[u8], dst: &[u8]) -> Result<EncodedPoint, error::Unspecified> {
+    use curve25519::hash_to_curve::{clear_cofactor, elem_reduced, map_to_curve, HASHED_ELEM_LEN};
+
+    let mut uniform_bytes = [0u8; 2 * HASHED_ELEM_LEN];
+    expand_message_xmd(&digest::SHA512, msg, dst, &mut uniform_bytes)?;
+    let (u0, u1) = uniform_bytes.split_at(HASHED_ELEM_LEN);
+    let q0 = map_to_curve(&elem_reduced(u0));
+    let q1 = map_to_curve(&elem_reduced(u1));
+    let p = clear_cofactor(&q0.add_point(&q1));
+
+    let encoded = p.into_encoded_point();
+    let mut r = EncodedPoint {
+        bytes: [0u8; ENCODED_POINT_MAX_LEN],
+        len: encoded.len(),
+    };
+    r.bytes[..encoded.len()].copy_from_slice(&encoded);
+    Ok(r)
+}
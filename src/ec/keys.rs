@@ -1,5 +1,5 @@
 use super::{Curve, ELEM_MAX_BYTES, SEED_MAX_BYTES};
-use crate::{cpu, error, rand};
+use crate::{cpu, error, io::der, pkcs8, rand, zeroize::Zeroize};
 
 pub struct KeyPair {
     seed: Seed,
@@ -26,6 +26,12 @@ pub struct Seed {
     pub(crate) cpu_features: cpu::Features,
 }
 
+impl Drop for Seed {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+    }
+}
+
 impl Seed {
     pub(crate) fn generate(
         curve: &'static Curve,
@@ -60,17 +66,45 @@ impl Seed {
         Ok(r)
     }
 
+    /// Parses an unencrypted PKCS#8 v1 or v2 private key in the
+    /// [RFC 8410] format, where the private key is an OCTET STRING holding
+    /// the raw key bytes. If the public key is present then it must match the
+    /// private key.
+    ///
+    /// [RFC 8410]: https://tools.ietf.org/html/rfc8410
+    pub(crate) fn from_rfc8410_pkcs8(
+        curve: &'static Curve,
+        template: &pkcs8::Template,
+        input: untrusted::Input,
+        cpu_features: cpu::Features,
+    ) -> Result<Seed, error::KeyRejected> {
+        let (private_key, public_key) = pkcs8::unwrap_key(template, pkcs8::Version::V1OrV2, input)?;
+        let private_key = private_key
+            .read_all(error::Unspecified, |input| {
+                der::expect_tag_and_get_value(input, der::Tag::OctetString)
+            })
+            .map_err(|error::Unspecified| error::KeyRejected::invalid_encoding())?;
+        let seed = Self::from_bytes(curve, private_key, cpu_features)
+            .map_err(|error::Unspecified| error::KeyRejected::invalid_component())?;
+        if let Some(public_key) = public_key {
+            let computed_public_key = seed
+                .compute_public_key()
+                .map_err(|error::Unspecified| error::KeyRejected::unexpected_error())?;
+            if public_key.as_slice_less_safe() != computed_public_key.as_ref() {
+                return Err(error::KeyRejected::inconsistent_components());
+            }
+        }
+        Ok(seed)
+    }
+
     pub fn bytes_less_safe(&self) -> &[u8] {
         &self.bytes[..self.curve.elem_scalar_seed_len]
     }
 
     pub fn compute_public_key(&self) -> Result<PublicKey, error::Unspecified> {
-        let mut public_key = PublicKey {
-            bytes: [0u8; PUBLIC_KEY_MAX_LEN],
-            len: self.curve.public_key_len,
-        };
-        (self.curve.public_from_private)(&mut public_key.bytes[..public_key.len], self)?;
-        Ok(public_key)
+        PublicKey::from_fn(self.curve.public_key_len, |out| {
+            (self.curve.public_from_private)(out, self)
+        })
     }
 }
 
@@ -86,5 +120,39 @@ impl AsRef<[u8]> for PublicKey {
     }
 }
 
+impl PublicKey {
+    /// Constructs a `len`-byte public key whose encoding is written by
+    /// `fill`.
+    pub fn from_fn(
+        len: usize,
+        fill: impl FnOnce(&mut [u8]) -> Result<(), error::Unspecified>,
+    ) -> Result<Self, error::Unspecified> {
+        let mut public_key = Self {
+            bytes: [0u8; PUBLIC_KEY_MAX_LEN],
+            len,
+        };
+        fill(&mut public_key.bytes[..len])?;
+        Ok(public_key)
+    }
+
+    /// Returns the SEC 1 compressed form of an uncompressed public key, or
+    /// fails if the public key isn't in uncompressed form.
+    pub fn compressed(&self) -> Result<Self, error::Unspecified> {
+        let uncompressed = self.as_ref();
+        if uncompressed.first() != Some(&4) || uncompressed.len() % 2 != 1 {
+            return Err(error::Unspecified);
+        }
+        let elem_len = uncompressed.len() / 2;
+        let (x, y) = uncompressed[1..].split_at(elem_len);
+        let mut r = Self {
+            bytes: [0u8; PUBLIC_KEY_MAX_LEN],
+            len: 1 + elem_len,
+        };
+        r.bytes[0] = 2 | (y[elem_len - 1] & 1);
+        r.bytes[1..r.len].copy_from_slice(x);
+        Ok(r)
+    }
+}
+
 /// The maximum length, in bytes, of an encoded public key.
 pub const PUBLIC_KEY_MAX_LEN: usize = 1 + (2 * ELEM_MAX_BYTES);
@@ -161,8 +161,17 @@ pub(crate) fn key_pair_from_pkcs8(
     cpu_features: cpu::Features,
 ) -> Result<ec::KeyPair, error::KeyRejected> {
     let (ec_private_key, _) = pkcs8::unwrap_key(template, pkcs8::Version::V1Only, input)?;
+    key_pair_from_ec_private_key(curve, template, ec_private_key, cpu_features)
+}
+
+pub(crate) fn key_pair_from_ec_private_key(
+    curve: &'static ec::Curve,
+    template: &pkcs8::Template,
+    input: untrusted::Input,
+    cpu_features: cpu::Features,
+) -> Result<ec::KeyPair, error::KeyRejected> {
     let (private_key, public_key) =
-        ec_private_key.read_all(error::KeyRejected::invalid_encoding(), |input| {
+        input.read_all(error::KeyRejected::invalid_encoding(), |input| {
             // https://tools.ietf.org/html/rfc5915#section-3
             der::nested(
                 input,
@@ -231,9 +240,12 @@ pub(crate) fn key_pair_from_bytes(
 pub mod curve;
 pub mod ecdh;
 pub mod ecdsa;
+#[cfg(feature = "use_heap")]
+pub mod hash_to_curve;
 
 #[macro_use]
-mod ops;
+pub(crate) mod ops;
 
-mod private_key;
-mod public_key;
+pub(crate) mod private_key;
+pub(crate) mod public_key;
+pub mod tweak;
@@ -42,7 +42,7 @@ macro_rules! suite_b_curve {
         };
 
         fn $check_private_key_bytes(bytes: &[u8]) -> Result<(), error::Unspecified> {
-            debug_assert_eq!(bytes.len(), $bits / 8);
+            debug_assert_eq!(bytes.len(), ($bits + 7) / 8);
             ec::suite_b::private_key::check_scalar_big_endian_bytes($private_key_ops, bytes)
         }
 
@@ -62,6 +62,16 @@ macro_rules! suite_b_curve {
     };
 }
 
+suite_b_curve!(
+    K256,
+    256,
+    &ec::suite_b::ops::k256::PRIVATE_KEY_OPS,
+    ec::CurveID::K256,
+    k256_check_private_key_bytes,
+    k256_generate_private_key,
+    k256_public_from_private
+);
+
 suite_b_curve!(
     P256,
     256,
@@ -81,3 +91,13 @@ suite_b_curve!(
     p384_generate_private_key,
     p384_public_from_private
 );
+
+suite_b_curve!(
+    P521,
+    521,
+    &ec::suite_b::ops::p521::PRIVATE_KEY_OPS,
+    ec::CurveID::P521,
+    p521_check_private_key_bytes,
+    p521_generate_private_key,
+    p521_public_from_private
+);
@@ -12,24 +12,28 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-//! ECDH key agreement using the P-256 and P-384 curves.
+//! ECDH key agreement using the P-256, P-384, and secp256k1 curves.
 
-use super::{ops::*, private_key::*, public_key::*};
-use crate::{agreement, ec, error};
+use super::{ecdsa, ops::*, private_key::*, public_key::*};
+use crate::{agreement, cpu, ec, error};
 use untrusted;
 
 /// A key agreement algorithm.
 macro_rules! ecdh {
     ( $NAME:ident, $curve:expr, $name_str:expr, $private_key_ops:expr,
-      $public_key_ops:expr, $ecdh:ident ) => {
-        #[doc = "ECDH using the NSA Suite B"]
+      $public_key_ops:expr, $pkcs8_template:expr, $ecdh:ident,
+      $private_key_from_pkcs8:ident ) => {
+        #[doc = "ECDH using the"]
         #[doc=$name_str]
         #[doc = "curve."]
         ///
-        /// Public keys are encoding in uncompressed form using the
-        /// Octet-String-to-Elliptic-Curve-Point algorithm in
-        /// [SEC 1: Elliptic Curve Cryptography, Version 2.0]. Public keys are
-        /// validated during key agreement according to
+        /// Public keys are encoded in uncompressed form using the
+        /// Elliptic-Curve-Point-to-Octet-String algorithm in
+        /// [SEC 1: Elliptic Curve Cryptography, Version 2.0], or in
+        /// compressed form by
+        /// `EphemeralPrivateKey::compute_compressed_public_key()`. Peer public
+        /// keys may be in either form. Public keys are validated during key
+        /// agreement according to
         /// [NIST Special Publication 800-56A, revision 2] and Appendix B.3 of
         /// the NSA's [Suite B Implementer's Guide to NIST SP 800-56A].
         ///
@@ -42,6 +46,8 @@ macro_rules! ecdh {
         pub static $NAME: agreement::Algorithm = agreement::Algorithm {
             curve: $curve,
             ecdh: $ecdh,
+            pkcs8_template: $pkcs8_template,
+            private_key_from_pkcs8: $private_key_from_pkcs8,
         };
 
         fn $ecdh(
@@ -57,25 +63,50 @@ macro_rules! ecdh {
                 peer_public_key,
             )
         }
+
+        fn $private_key_from_pkcs8(
+            input: untrusted::Input,
+            cpu_features: cpu::Features,
+        ) -> Result<ec::Seed, error::KeyRejected> {
+            let key_pair =
+                ec::suite_b::key_pair_from_pkcs8($curve, $pkcs8_template, input, cpu_features)?;
+            let (seed, _) = key_pair.split();
+            Ok(seed)
+        }
     };
 }
 
 ecdh!(
     ECDH_P256,
     &ec::suite_b::curve::P256,
-    "P-256 (secp256r1)",
+    "NSA Suite B P-256 (secp256r1)",
     &p256::PRIVATE_KEY_OPS,
     &p256::PUBLIC_KEY_OPS,
-    p256_ecdh
+    &ecdsa::signing::EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE,
+    p256_ecdh,
+    p256_private_key_from_pkcs8
 );
 
 ecdh!(
     ECDH_P384,
     &ec::suite_b::curve::P384,
-    "P-384 (secp384r1)",
+    "NSA Suite B P-384 (secp384r1)",
     &p384::PRIVATE_KEY_OPS,
     &p384::PUBLIC_KEY_OPS,
-    p384_ecdh
+    &ecdsa::signing::EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE,
+    p384_ecdh,
+    p384_private_key_from_pkcs8
+);
+
+ecdh!(
+    ECDH_K256,
+    &ec::suite_b::curve::K256,
+    "secp256k1 (K-256)",
+    &k256::PRIVATE_KEY_OPS,
+    &k256::PUBLIC_KEY_OPS,
+    &ecdsa::signing::EC_PUBLIC_KEY_K256_PKCS8_V1_TEMPLATE,
+    k256_ecdh,
+    k256_private_key_from_pkcs8
 );
 
 fn ecdh(
@@ -96,10 +127,9 @@ fn ecdh(
     // NIST SP 800-56Ar2 5.6.2.2.2.
     // NSA Guide Step 2.
     //
-    // `parse_uncompressed_point` verifies that the point is not at infinity
-    // and that it is on the curve, using the Partial Public-Key Validation
-    // Routine.
-    let peer_public_key = parse_uncompressed_point(public_key_ops, peer_public_key)?;
+    // `parse_point` verifies that the point is not at infinity and that it is
+    // on the curve, using the Partial Public-Key Validation Routine.
+    let peer_public_key = parse_point(public_key_ops, peer_public_key)?;
 
     // NIST SP 800-56Ar2 Step 1.
     // NSA Guide Step 3 (except point at infinity check).
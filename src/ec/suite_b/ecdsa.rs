@@ -1,3 +1,4 @@
 mod digest_scalar;
+mod rfc6979;
 pub mod signing;
 pub mod verification;
@@ -12,13 +12,9 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-//! ECDSA Signatures using the P-256 and P-384 curves.
+//! ECDSA Signatures using the P-256, P-384, and P-521 curves.
 
-use crate::{
-    digest,
-    ec::suite_b::ops::*,
-    limb::{self, LIMB_BYTES},
-};
+use crate::{digest, ec::suite_b::ops::*, limb};
 use untrusted;
 
 /// Calculate the digest of `msg` using the digest algorithm `digest_alg`. Then
@@ -46,7 +42,8 @@ use untrusted;
 /// less than 2**256. If the value is larger than `n` then shifting it one bit
 /// right will give a value less than 2**255, which is less than `n`. The
 /// analogous argument applies for P-384. However, it does *not* apply in
-/// general; for example, it doesn't apply to P-521.
+/// general; for example, it doesn't apply to P-521. For P-521 no reduction
+/// is needed at all, because SHA-512 digests are shorter than `n`.
 pub fn digest_scalar(ops: &ScalarOps, msg: digest::Digest) -> Scalar {
     digest_scalar_(ops, msg.as_ref())
 }
@@ -60,9 +57,8 @@ pub(crate) fn digest_bytes_scalar(ops: &ScalarOps, digest: &[u8]) -> Scalar {
 // values like all-zero values and values larger than `n`.
 fn digest_scalar_(ops: &ScalarOps, digest: &[u8]) -> Scalar {
     let cops = ops.common;
-    let num_limbs = cops.num_limbs;
-    let digest = if digest.len() > num_limbs * LIMB_BYTES {
-        &digest[..(num_limbs * LIMB_BYTES)]
+    let digest = if digest.len() > cops.len() {
+        &digest[..cops.len()]
     } else {
         digest
     };
@@ -78,12 +74,7 @@ fn digest_scalar_(ops: &ScalarOps, digest: &[u8]) -> Scalar {
 #[cfg(test)]
 mod tests {
     use super::digest_bytes_scalar;
-    use crate::{
-        digest,
-        ec::suite_b::ops::*,
-        limb::{self, LIMB_BYTES},
-        test,
-    };
+    use crate::{digest, ec::suite_b::ops::*, limb, test};
     use untrusted;
 
     #[test]
@@ -103,6 +94,7 @@ mod tests {
                     ("P-256", "SHA384") => (&p256::PUBLIC_SCALAR_OPS, &digest::SHA384),
                     ("P-384", "SHA256") => (&p384::PUBLIC_SCALAR_OPS, &digest::SHA256),
                     ("P-384", "SHA384") => (&p384::PUBLIC_SCALAR_OPS, &digest::SHA384),
+                    ("P-521", "SHA512") => (&p521::PUBLIC_SCALAR_OPS, &digest::SHA512),
                     _ => {
                         panic!("Unsupported curve+digest: {}+{}", curve_name, digest_name);
                     }
@@ -110,10 +102,7 @@ mod tests {
 
                 let num_limbs = ops.public_key_ops.common.num_limbs;
                 assert_eq!(input.len(), digest_alg.output_len);
-                assert_eq!(
-                    output.len(),
-                    ops.public_key_ops.common.num_limbs * LIMB_BYTES
-                );
+                assert_eq!(output.len(), ops.public_key_ops.common.len());
 
                 let expected = scalar_parse_big_endian_variable(
                     ops.public_key_ops.common,
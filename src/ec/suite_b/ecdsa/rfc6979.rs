@@ -0,0 +1,147 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Deterministic generation of ECDSA nonces, as specified in [RFC 6979].
+//!
+//! [RFC 6979]: https://tools.ietf.org/html/rfc6979
+
+use crate::{
+    arithmetic::montgomery::R,
+    digest,
+    ec::{
+        self,
+        suite_b::{ops::*, private_key},
+    },
+    error, hmac, limb, polyfill,
+};
+
+/// The HMAC_DRBG of [RFC 6979 Section 3.2], instantiated for one private key
+/// and one message digest. Each call to `next()` returns the next candidate
+/// nonce `k`.
+///
+/// [RFC 6979 Section 3.2]: https://tools.ietf.org/html/rfc6979#section-3.2
+pub struct NonceGenerator {
+    k: hmac::Key,
+    v: [u8; digest::MAX_OUTPUT_LEN],
+
+    // Whether a candidate has been returned already, in which case K and V
+    // must be updated as in step h.3 before generating the next one.
+    started: bool,
+}
+
+impl NonceGenerator {
+    /// Steps a through g. `d` is the private key and `e` is the message digest
+    /// converted with `digest_scalar()`, i.e. bits2int(H(m)) mod n.
+    pub fn new(
+        ops: &PrivateScalarOps,
+        digest_alg: &'static digest::Algorithm,
+        d: &Scalar<R>,
+        e: &Scalar,
+    ) -> Self {
+        let scalar_ops = ops.scalar_ops;
+        let cops = scalar_ops.common;
+
+        let d = {
+            let mut one: Scalar = Scalar::zero();
+            one.limbs[0] = 1;
+            scalar_ops.scalar_product(&one, d)
+        };
+
+        // int2octets(x) and bits2octets(h1).
+        let mut x = [0u8; ec::SCALAR_MAX_BYTES];
+        let x = &mut x[..cops.len()];
+        limb::big_endian_from_limbs(&d.limbs[..cops.num_limbs], x);
+        let mut h = [0u8; ec::SCALAR_MAX_BYTES];
+        let h = &mut h[..cops.len()];
+        limb::big_endian_from_limbs(&e.limbs[..cops.num_limbs], h);
+
+        // Steps b and c.
+        let len = digest_alg.output_len;
+        let mut v = [0u8; digest::MAX_OUTPUT_LEN];
+        polyfill::slice::fill(&mut v[..len], 0x01);
+        let k = [0u8; digest::MAX_OUTPUT_LEN];
+        let mut generator = Self {
+            k: hmac::Key::new(digest_alg, &k[..len]),
+            v,
+            started: false,
+        };
+
+        // Steps d through g.
+        generator.update(0x00, &[x, h]);
+        generator.update(0x01, &[x, h]);
+
+        generator
+    }
+
+    /// Step h. Returns the next candidate nonce in the range [1, n).
+    pub fn next(&mut self, ops: &PrivateKeyOps) -> Result<Scalar, error::Unspecified> {
+        let cops = ops.common;
+        let len = self.k.digest_algorithm().output_len;
+
+        // XXX: The value 100 matches `private_key::generate_private_scalar_bytes`.
+        // Each candidate is out of range with negligible probability.
+        for _ in 0..100 {
+            // Step h.3, for every candidate but the first.
+            if self.started {
+                self.update(0x00, &[]);
+            }
+            self.started = true;
+
+            // Step h.2. Only the leftmost qlen bits of T are used, so it is
+            // enough to generate the first `cops.len()` bytes of it.
+            let mut t = [0u8; ec::SCALAR_MAX_BYTES];
+            let t = &mut t[..cops.len()];
+            for chunk in t.chunks_mut(len) {
+                self.next_v();
+                chunk.copy_from_slice(&self.v[..chunk.len()]);
+            }
+
+            // bits2int(T). This is only a shift for P-521, since 521 isn't a
+            // multiple of 8.
+            let shift = (8 * t.len()) - cops.order_bits;
+            if shift != 0 {
+                for i in (1..t.len()).rev() {
+                    t[i] = (t[i] >> shift) | (t[i - 1] << (8 - shift));
+                }
+                t[0] >>= shift;
+            }
+
+            if let Ok(k) = private_key::scalar_from_big_endian_bytes(ops, t) {
+                return Ok(k);
+            }
+        }
+
+        Err(error::Unspecified)
+    }
+
+    // K = HMAC_K(V || tag || provided_data); V = HMAC_K(V).
+    fn update(&mut self, tag: u8, provided_data: &[&[u8]]) {
+        let digest_alg = self.k.digest_algorithm();
+        let mut ctx = hmac::Context::with_key(&self.k);
+        ctx.update(&self.v[..digest_alg.output_len]);
+        ctx.update(&[tag]);
+        for data in provided_data {
+            ctx.update(data);
+        }
+        self.k = hmac::Key::new(digest_alg, ctx.sign().as_ref());
+        self.next_v();
+    }
+
+    // V = HMAC_K(V).
+    fn next_v(&mut self) {
+        let len = self.k.digest_algorithm().output_len;
+        let v = hmac::sign(&self.k, &self.v[..len]);
+        self.v[..len].copy_from_slice(v.as_ref());
+    }
+}
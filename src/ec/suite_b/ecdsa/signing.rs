@@ -12,9 +12,9 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-//! ECDSA Signatures using the P-256 and P-384 curves.
+//! ECDSA Signatures using the P-256, P-384, P-521, and secp256k1 curves.
 
-use super::digest_scalar::digest_scalar;
+use super::{digest_scalar::digest_scalar, rfc6979, verification};
 use crate::{
     arithmetic::montgomery::*,
     cpu, digest,
@@ -36,15 +36,36 @@ pub struct EcdsaSigningAlgorithm {
     digest_alg: &'static digest::Algorithm,
     pkcs8_template: &'static pkcs8::Template,
     format_rs: fn(ops: &'static ScalarOps, r: &Scalar, s: &Scalar, out: &mut [u8]) -> usize,
+    nonce: Nonce,
     id: AlgorithmID,
 }
 
+/// How the nonce `k` is chosen for each signature.
+#[derive(Clone, Copy)]
+enum Nonce {
+    /// `k` is generated using the `rng` passed to `EcdsaKeyPair::sign()`.
+    Random,
+
+    /// `k` is derived from the private key and the message digest as
+    /// specified in RFC 6979.
+    Deterministic,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 enum AlgorithmID {
     ECDSA_P256_SHA256_FIXED_SIGNING,
     ECDSA_P384_SHA384_FIXED_SIGNING,
+    ECDSA_P521_SHA512_FIXED_SIGNING,
     ECDSA_P256_SHA256_ASN1_SIGNING,
     ECDSA_P384_SHA384_ASN1_SIGNING,
+    ECDSA_P521_SHA512_ASN1_SIGNING,
+    ECDSA_P256_SHA256_FIXED_DETERMINISTIC_SIGNING,
+    ECDSA_P384_SHA384_FIXED_DETERMINISTIC_SIGNING,
+    ECDSA_P521_SHA512_FIXED_DETERMINISTIC_SIGNING,
+    ECDSA_P256_SHA256_ASN1_DETERMINISTIC_SIGNING,
+    ECDSA_P384_SHA384_ASN1_DETERMINISTIC_SIGNING,
+    ECDSA_P521_SHA512_ASN1_DETERMINISTIC_SIGNING,
+    ECDSA_K256_SHA256_FIXED_SIGNING,
 }
 
 derive_debug_via_id!(EcdsaSigningAlgorithm);
@@ -59,9 +80,77 @@ impl Eq for EcdsaSigningAlgorithm {}
 
 impl sealed::Sealed for EcdsaSigningAlgorithm {}
 
+impl EcdsaSigningAlgorithm {
+    #[cfg(feature = "jwk")]
+    pub(crate) fn curve(&self) -> &'static ec::Curve {
+        self.curve
+    }
+
+    /// The verification algorithm for the fixed-length form of this
+    /// algorithm's signatures.
+    fn fixed_verification_alg(&self) -> &'static verification::EcdsaVerificationAlgorithm {
+        match self.id {
+            AlgorithmID::ECDSA_P256_SHA256_FIXED_SIGNING
+            | AlgorithmID::ECDSA_P256_SHA256_ASN1_SIGNING
+            | AlgorithmID::ECDSA_P256_SHA256_FIXED_DETERMINISTIC_SIGNING
+            | AlgorithmID::ECDSA_P256_SHA256_ASN1_DETERMINISTIC_SIGNING => {
+                &verification::ECDSA_P256_SHA256_FIXED
+            }
+            AlgorithmID::ECDSA_P384_SHA384_FIXED_SIGNING
+            | AlgorithmID::ECDSA_P384_SHA384_ASN1_SIGNING
+            | AlgorithmID::ECDSA_P384_SHA384_FIXED_DETERMINISTIC_SIGNING
+            | AlgorithmID::ECDSA_P384_SHA384_ASN1_DETERMINISTIC_SIGNING => {
+                &verification::ECDSA_P384_SHA384_FIXED
+            }
+            AlgorithmID::ECDSA_P521_SHA512_FIXED_SIGNING
+            | AlgorithmID::ECDSA_P521_SHA512_ASN1_SIGNING
+            | AlgorithmID::ECDSA_P521_SHA512_FIXED_DETERMINISTIC_SIGNING
+            | AlgorithmID::ECDSA_P521_SHA512_ASN1_DETERMINISTIC_SIGNING => {
+                &verification::ECDSA_P521_SHA512_FIXED
+            }
+            AlgorithmID::ECDSA_K256_SHA256_FIXED_SIGNING => &verification::ECDSA_K256_SHA256_FIXED,
+        }
+    }
+
+    #[cfg(feature = "use_heap")]
+    fn signature_scheme(&self) -> signature::SignatureScheme {
+        use signature::SignatureScheme;
+        match self.id {
+            AlgorithmID::ECDSA_P256_SHA256_FIXED_SIGNING
+            | AlgorithmID::ECDSA_P256_SHA256_FIXED_DETERMINISTIC_SIGNING => {
+                SignatureScheme::ECDSA_P256_SHA256_FIXED
+            }
+            AlgorithmID::ECDSA_P384_SHA384_FIXED_SIGNING
+            | AlgorithmID::ECDSA_P384_SHA384_FIXED_DETERMINISTIC_SIGNING => {
+                SignatureScheme::ECDSA_P384_SHA384_FIXED
+            }
+            AlgorithmID::ECDSA_P521_SHA512_FIXED_SIGNING
+            | AlgorithmID::ECDSA_P521_SHA512_FIXED_DETERMINISTIC_SIGNING => {
+                SignatureScheme::ECDSA_P521_SHA512_FIXED
+            }
+            AlgorithmID::ECDSA_K256_SHA256_FIXED_SIGNING => {
+                SignatureScheme::ECDSA_K256_SHA256_FIXED
+            }
+            AlgorithmID::ECDSA_P256_SHA256_ASN1_SIGNING
+            | AlgorithmID::ECDSA_P256_SHA256_ASN1_DETERMINISTIC_SIGNING => {
+                SignatureScheme::ECDSA_P256_SHA256_ASN1
+            }
+            AlgorithmID::ECDSA_P384_SHA384_ASN1_SIGNING
+            | AlgorithmID::ECDSA_P384_SHA384_ASN1_DETERMINISTIC_SIGNING => {
+                SignatureScheme::ECDSA_P384_SHA384_ASN1
+            }
+            AlgorithmID::ECDSA_P521_SHA512_ASN1_SIGNING
+            | AlgorithmID::ECDSA_P521_SHA512_ASN1_DETERMINISTIC_SIGNING => {
+                SignatureScheme::ECDSA_P521_SHA512_ASN1
+            }
+        }
+    }
+}
+
 /// An ECDSA key pair, used for signing.
 pub struct EcdsaKeyPair {
     d: Scalar<R>,
+    seed: ec::Seed,
     alg: &'static EcdsaSigningAlgorithm,
     public_key: PublicKey,
 }
@@ -93,6 +182,22 @@ impl EcdsaKeyPair {
         ))
     }
 
+    /// Like `generate_pkcs8()`, but doesn't block the executor while the
+    /// system random number generator is not yet ready. See
+    /// `rand::fill_async()`.
+    #[cfg(feature = "async")]
+    pub async fn generate_pkcs8_async<R: rand::SecureRandom>(
+        alg: &'static EcdsaSigningAlgorithm,
+        rng: &R,
+    ) -> Result<pkcs8::Document, error::Unspecified> {
+        // Generating the private key may take several attempts, so wait until
+        // `rng` is ready instead of filling the key directly. After that,
+        // `rng.fill()` won't block.
+        let mut probe = [0u8; 1];
+        rand::fill_async(rng, &mut probe).await?;
+        Self::generate_pkcs8(alg, rng)
+    }
+
     /// Constructs an ECDSA key pair by parsing an unencrypted PKCS#8 v1
     /// id-ecPublicKey `ECPrivateKey` key.
     ///
@@ -116,6 +221,27 @@ impl EcdsaKeyPair {
         Ok(Self::new(alg, key_pair))
     }
 
+    /// Constructs an ECDSA key pair by parsing an [RFC 5915] `ECPrivateKey`,
+    /// the format of OpenSSL's `EC PRIVATE KEY` PEM blocks.
+    ///
+    /// Like the `ECPrivateKey` in a key for `from_pkcs8()`, it must contain
+    /// the public key, and its `parameters`, if present, must name the curve
+    /// of `alg`.
+    ///
+    /// [RFC 5915]: https://tools.ietf.org/html/rfc5915
+    pub fn from_ec_private_key(
+        alg: &'static EcdsaSigningAlgorithm,
+        ec_private_key: &[u8],
+    ) -> Result<Self, error::KeyRejected> {
+        let key_pair = ec::suite_b::key_pair_from_ec_private_key(
+            alg.curve,
+            alg.pkcs8_template,
+            untrusted::Input::from(ec_private_key),
+            cpu::features(),
+        )?;
+        Ok(Self::new(alg, key_pair))
+    }
+
     /// Constructs an ECDSA key pair directly from the big-endian-encoded
     /// private key and public key bytes.
     ///
@@ -146,30 +272,102 @@ impl EcdsaKeyPair {
 
         Self {
             d,
+            seed,
             alg,
             public_key: PublicKey(public_key),
         }
     }
 
+    /// Serializes the key pair as an unencrypted PKCS#8 v1 document, in the
+    /// same format as `generate_pkcs8()`.
+    ///
+    /// The result can be parsed with `EcdsaKeyPair::from_pkcs8()`.
+    pub fn to_pkcs8(&self) -> pkcs8::Document {
+        pkcs8::wrap_key(
+            &self.alg.pkcs8_template,
+            self.seed.bytes_less_safe(),
+            self.public_key.as_ref(),
+        )
+    }
+
+    /// Returns the big-endian-encoded private key.
+    ///
+    /// This is the inverse of `EcdsaKeyPair::from_private_key_and_public_key()`
+    /// without the public key. Prefer `to_pkcs8()`, which records the curve
+    /// and the public key. The result is the private key, so it must be
+    /// protected like one.
+    pub fn export_private_key_unchecked(&self) -> &[u8] {
+        self.seed.bytes_less_safe()
+    }
+
+    /// The algorithm the key pair is used with.
+    #[inline]
+    pub fn algorithm(&self) -> &'static EcdsaSigningAlgorithm {
+        self.alg
+    }
+
     /// Returns the signature of the `message` using a random nonce
     /// generated by `rng`.
+    ///
+    /// For the `ECDSA_*_DETERMINISTIC_SIGNING` algorithms, the nonce is
+    /// instead derived from the private key and `message` as specified in
+    /// [RFC 6979], so signing the same message twice gives the same
+    /// signature, and `rng` is not used.
+    ///
+    /// [RFC 6979]: https://tools.ietf.org/html/rfc6979
     pub fn sign(
         &self,
         rng: &dyn rand::SecureRandom,
         message: &[u8],
     ) -> Result<signature::Signature, error::Unspecified> {
+        #[cfg(feature = "audit")]
+        crate::audit::record_public_key(
+            self.alg,
+            crate::audit::Operation::Sign,
+            self.public_key.as_ref(),
+            message.len(),
+        );
+
         // Step 4 (out of order).
+        let h = digest::digest(self.alg.digest_alg, message);
+        let (signature, _) = self.sign_(rng, h)?;
+        Ok(signature)
+    }
+
+    /// Returns the signature of the `message`, like `sign()`, along with a
+    /// recovery ID that allows the public key to be recovered from the
+    /// signature and `message` using
+    /// `EcdsaVerificationAlgorithm::recover_public_key()`.
+    ///
+    /// The recovery ID is in the range `0..=3`, using the same convention as
+    /// secp256k1 recoverable signatures: its low bit is the parity of the `y`
+    /// coordinate of the point `R = k*G`, and its high bit is set if the `x`
+    /// coordinate of `R` was reduced modulo `n` to get `r`.
+    pub fn sign_recoverable(
+        &self,
+        rng: &dyn rand::SecureRandom,
+        message: &[u8],
+    ) -> Result<(signature::Signature, u8), error::Unspecified> {
+        #[cfg(feature = "audit")]
+        crate::audit::record_public_key(
+            self.alg,
+            crate::audit::Operation::Sign,
+            self.public_key.as_ref(),
+            message.len(),
+        );
+
         let h = digest::digest(self.alg.digest_alg, message);
         self.sign_(rng, h)
     }
 
     /// Returns the signature of message digest `h` using a "random" nonce
-    /// generated by `rng`.
+    /// generated by `rng`, or a deterministic nonce, depending on the
+    /// algorithm, along with its recovery ID.
     fn sign_(
         &self,
         rng: &dyn rand::SecureRandom,
         h: digest::Digest,
-    ) -> Result<signature::Signature, error::Unspecified> {
+    ) -> Result<(signature::Signature, u8), error::Unspecified> {
         // NSA Suite B Implementer's Guide to ECDSA Section 3.4.1: ECDSA
         // Signature Generation.
 
@@ -199,30 +397,51 @@ impl EcdsaKeyPair {
         let cops = scalar_ops.common;
         let private_key_ops = self.alg.private_key_ops;
 
+        // Step 4 is done by the caller.
+
+        // Step 5 (out of order).
+        let e = digest_scalar(scalar_ops, h);
+
+        let mut deterministic_nonces = match self.alg.nonce {
+            Nonce::Random => None,
+            Nonce::Deterministic => Some(rfc6979::NonceGenerator::new(
+                ops,
+                self.alg.digest_alg,
+                &self.d,
+                &e,
+            )),
+        };
+
         for _ in 0..100 {
             // XXX: iteration conut?
             // Step 1.
-            let k = private_key::random_scalar(self.alg.private_key_ops, rng)?;
+            let k = match &mut deterministic_nonces {
+                None => private_key::random_scalar(private_key_ops, rng)?,
+                Some(nonces) => nonces.next(private_key_ops)?,
+            };
             let k_inv = scalar_ops.scalar_inv_to_mont(&k);
 
             // Step 2.
             let r = private_key_ops.point_mul_base(&k);
 
             // Step 3.
-            let r = {
-                let (x, _) = private_key::affine_from_jacobian(private_key_ops, &r)?;
+            let (r, recovery_id) = {
+                let (x, y) = private_key::affine_from_jacobian(private_key_ops, &r)?;
                 let x = cops.elem_unencoded(&x);
-                elem_reduced_to_scalar(cops, &x)
+                let y = cops.elem_unencoded(&y);
+                let r = elem_reduced_to_scalar(cops, &x);
+
+                // The recovery ID is public, as it is sent along with the
+                // signature, so it doesn't need to be computed in constant
+                // time.
+                let x_was_reduced = x.limbs[..cops.num_limbs] != r.limbs[..cops.num_limbs];
+                let recovery_id = ((x_was_reduced as u8) << 1) | ((y.limbs[0] & 1) as u8);
+                (r, recovery_id)
             };
             if cops.is_zero(&r) {
                 continue;
             }
 
-            // Step 4 is done by the caller.
-
-            // Step 5.
-            let e = digest_scalar(scalar_ops, h);
-
             // Step 6.
             let s = {
                 let dr = scalar_ops.scalar_product(&self.d, &r);
@@ -234,9 +453,10 @@ impl EcdsaKeyPair {
             }
 
             // Step 7 with encoding.
-            return Ok(signature::Signature::new(|sig_bytes| {
+            let signature = signature::Signature::new(|sig_bytes| {
                 (self.alg.format_rs)(scalar_ops, &r, &s, sig_bytes)
-            }));
+            });
+            return Ok((signature, recovery_id));
         }
 
         Err(error::Unspecified)
@@ -251,8 +471,173 @@ impl signature::KeyPair for EcdsaKeyPair {
     }
 }
 
+#[cfg(feature = "use_heap")]
+impl signature::Signer for EcdsaKeyPair {
+    fn public_key_bytes(&self) -> &[u8] {
+        self.public_key.as_ref()
+    }
+
+    fn supports(&self, scheme: signature::SignatureScheme) -> bool {
+        scheme == self.alg.signature_scheme()
+    }
+
+    fn sign(
+        &self,
+        scheme: signature::SignatureScheme,
+        rng: &dyn rand::SecureRandom,
+        message: &[u8],
+    ) -> Result<std::vec::Vec<u8>, error::Unspecified> {
+        if !signature::Signer::supports(self, scheme) {
+            return Err(error::Unspecified);
+        }
+        Ok(EcdsaKeyPair::sign(self, rng, message)?.as_ref().to_vec())
+    }
+}
+
+/// A private ECDSA key that is held elsewhere, e.g. in an HSM, a TPM, or a
+/// cloud KMS, and that signs message digests.
+///
+/// See `ExternalEcdsaKeyPair`.
+pub trait EcdsaDigestSigner: core::fmt::Debug {
+    /// Signs the message digest `digest`, writing the signature to
+    /// `signature` in the fixed-length format of PKCS#11's `CKM_ECDSA`: *r*
+    /// followed by *s*, each big-endian and padded to the length of the
+    /// curve's order.
+    fn sign_digest(&self, digest: &[u8], signature: &mut [u8]) -> Result<(), error::Unspecified>;
+}
+
+/// An ECDSA key pair whose private key is held by an `EcdsaDigestSigner`.
+///
+/// *ring* hashes the message, checks the signature that the
+/// `EcdsaDigestSigner` returns against the public key, and encodes it as
+/// `alg` specifies. How the nonce is chosen is up to the
+/// `EcdsaDigestSigner`, so the `ECDSA_*_DETERMINISTIC_SIGNING` algorithms
+/// are the same as the others here.
+///
+/// Ed25519 signs the message itself, not a digest, so Ed25519 keys that are
+/// held elsewhere implement `Signer` directly instead.
+pub struct ExternalEcdsaKeyPair<S> {
+    signer: S,
+    alg: &'static EcdsaSigningAlgorithm,
+    public_key: PublicKey,
+}
+
+impl<S: core::fmt::Debug> core::fmt::Debug for ExternalEcdsaKeyPair<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("ExternalEcdsaKeyPair")
+            .field("signer", &self.signer)
+            .field("public_key", &self.public_key)
+            .finish()
+    }
+}
+
+impl<S: EcdsaDigestSigner> ExternalEcdsaKeyPair<S> {
+    /// Constructs a key pair for `alg` from `signer` and its public key
+    /// `public_key`, an uncompressed point.
+    ///
+    /// `signer` isn't used here, so it is up to the caller to ensure that
+    /// `public_key` is its public key; otherwise every signature will be
+    /// rejected by `sign()`.
+    pub fn new(
+        alg: &'static EcdsaSigningAlgorithm,
+        public_key: &[u8],
+        signer: S,
+    ) -> Result<Self, error::KeyRejected> {
+        let _ = signature::VerificationAlgorithm::parse_public_key(
+            alg.fixed_verification_alg(),
+            untrusted::Input::from(public_key),
+        )
+        .map_err(|error::Unspecified| error::KeyRejected::invalid_encoding())?;
+        let public_key = ec::PublicKey::from_fn(public_key.len(), |out| {
+            out.copy_from_slice(public_key);
+            Ok(())
+        })
+        .map_err(|error::Unspecified| error::KeyRejected::unexpected_error())?;
+        Ok(Self {
+            signer,
+            alg,
+            public_key: PublicKey(public_key),
+        })
+    }
+
+    /// The algorithm the key pair is used with.
+    #[inline]
+    pub fn algorithm(&self) -> &'static EcdsaSigningAlgorithm {
+        self.alg
+    }
+
+    /// Returns the signature of the `message`.
+    ///
+    /// Fails if the signer fails or if its signature isn't a valid signature
+    /// of `message` for the public key, so a faulty signer can't produce
+    /// invalid signatures.
+    pub fn sign(&self, message: &[u8]) -> Result<signature::Signature, error::Unspecified> {
+        #[cfg(feature = "audit")]
+        crate::audit::record_public_key(
+            self.alg,
+            crate::audit::Operation::Sign,
+            self.public_key.as_ref(),
+            message.len(),
+        );
+
+        let scalar_ops = self.alg.private_scalar_ops.scalar_ops;
+        let scalar_len = scalar_ops.scalar_bytes_len();
+
+        let h = digest::digest(self.alg.digest_alg, message);
+        let mut fixed = [0u8; 2 * ec::SCALAR_MAX_BYTES];
+        let fixed = &mut fixed[..(2 * scalar_len)];
+        self.signer.sign_digest(h.as_ref(), fixed)?;
+
+        signature::VerificationAlgorithm::verify(
+            self.alg.fixed_verification_alg(),
+            untrusted::Input::from(self.public_key.as_ref()),
+            untrusted::Input::from(message),
+            untrusted::Input::from(fixed),
+        )?;
+
+        let cops = scalar_ops.common;
+        let (r, s) = fixed.split_at(scalar_len);
+        let r = scalar_parse_big_endian_fixed_consttime(cops, untrusted::Input::from(r))?;
+        let s = scalar_parse_big_endian_fixed_consttime(cops, untrusted::Input::from(s))?;
+        Ok(signature::Signature::new(|sig_bytes| {
+            (self.alg.format_rs)(scalar_ops, &r, &s, sig_bytes)
+        }))
+    }
+}
+
+impl<S: EcdsaDigestSigner + Send + Sync> signature::KeyPair for ExternalEcdsaKeyPair<S> {
+    type PublicKey = PublicKey;
+
+    fn public_key(&self) -> &Self::PublicKey {
+        &self.public_key
+    }
+}
+
+#[cfg(feature = "use_heap")]
+impl<S: EcdsaDigestSigner> signature::Signer for ExternalEcdsaKeyPair<S> {
+    fn public_key_bytes(&self) -> &[u8] {
+        self.public_key.as_ref()
+    }
+
+    fn supports(&self, scheme: signature::SignatureScheme) -> bool {
+        scheme == self.alg.signature_scheme()
+    }
+
+    fn sign(
+        &self,
+        scheme: signature::SignatureScheme,
+        _rng: &dyn rand::SecureRandom,
+        message: &[u8],
+    ) -> Result<std::vec::Vec<u8>, error::Unspecified> {
+        if !signature::Signer::supports(self, scheme) {
+            return Err(error::Unspecified);
+        }
+        Ok(ExternalEcdsaKeyPair::sign(self, message)?.as_ref().to_vec())
+    }
+}
+
 #[derive(Clone, Copy)]
-pub struct PublicKey(ec::PublicKey);
+pub struct PublicKey(pub(super) ec::PublicKey);
 
 derive_debug_self_as_ref_hex_bytes!(PublicKey);
 
@@ -308,16 +693,24 @@ fn format_rs_asn1(ops: &'static ScalarOps, r: &Scalar, s: &Scalar, out: &mut [u8
         2 + value.len()
     }
 
+    // The value is written after a two-byte length, which is only needed
+    // for P-521; otherwise it is moved down a byte afterwards.
     out[0] = der::Tag::Sequence as u8;
-    let r_tlv_len = format_integer_tlv(ops, r, &mut out[2..]);
-    let s_tlv_len = format_integer_tlv(ops, s, &mut out[2..][r_tlv_len..]);
+    let r_tlv_len = format_integer_tlv(ops, r, &mut out[3..]);
+    let s_tlv_len = format_integer_tlv(ops, s, &mut out[3..][r_tlv_len..]);
 
-    // Lengths less than 128 are encoded in one byte.
     let value_len = r_tlv_len + s_tlv_len;
-    assert!(value_len < 128);
-    out[1] = value_len as u8;
-
-    2 + value_len
+    assert!(value_len < 256);
+    if value_len < 128 {
+        // Lengths less than 128 are encoded in one byte.
+        out.copy_within(3..(3 + value_len), 2);
+        out[1] = value_len as u8;
+        2 + value_len
+    } else {
+        out[1] = 0x81;
+        out[2] = value_len as u8;
+        3 + value_len
+    }
 }
 
 /// Signing of fixed-length (PKCS#11 style) ECDSA signatures using the
@@ -332,6 +725,7 @@ pub static ECDSA_P256_SHA256_FIXED_SIGNING: EcdsaSigningAlgorithm = EcdsaSigning
     digest_alg: &digest::SHA256,
     pkcs8_template: &EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE,
     format_rs: format_rs_fixed,
+    nonce: Nonce::Random,
     id: AlgorithmID::ECDSA_P256_SHA256_FIXED_SIGNING,
 };
 
@@ -347,9 +741,26 @@ pub static ECDSA_P384_SHA384_FIXED_SIGNING: EcdsaSigningAlgorithm = EcdsaSigning
     digest_alg: &digest::SHA384,
     pkcs8_template: &EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE,
     format_rs: format_rs_fixed,
+    nonce: Nonce::Random,
     id: AlgorithmID::ECDSA_P384_SHA384_FIXED_SIGNING,
 };
 
+/// Signing of fixed-length (PKCS#11 style) ECDSA signatures using the
+/// secp256k1 curve and SHA-256.
+///
+/// See "`ECDSA_*_FIXED` Details" in `ring::signature`'s module-level
+/// documentation for more details.
+pub static ECDSA_K256_SHA256_FIXED_SIGNING: EcdsaSigningAlgorithm = EcdsaSigningAlgorithm {
+    curve: &ec::suite_b::curve::K256,
+    private_scalar_ops: &k256::PRIVATE_SCALAR_OPS,
+    private_key_ops: &k256::PRIVATE_KEY_OPS,
+    digest_alg: &digest::SHA256,
+    pkcs8_template: &EC_PUBLIC_KEY_K256_PKCS8_V1_TEMPLATE,
+    format_rs: format_rs_fixed,
+    nonce: Nonce::Random,
+    id: AlgorithmID::ECDSA_K256_SHA256_FIXED_SIGNING,
+};
+
 /// Signing of ASN.1 DER-encoded ECDSA signatures using the P-256 curve and
 /// SHA-256.
 ///
@@ -362,6 +773,7 @@ pub static ECDSA_P256_SHA256_ASN1_SIGNING: EcdsaSigningAlgorithm = EcdsaSigningA
     digest_alg: &digest::SHA256,
     pkcs8_template: &EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE,
     format_rs: format_rs_asn1,
+    nonce: Nonce::Random,
     id: AlgorithmID::ECDSA_P256_SHA256_ASN1_SIGNING,
 };
 
@@ -377,23 +789,184 @@ pub static ECDSA_P384_SHA384_ASN1_SIGNING: EcdsaSigningAlgorithm = EcdsaSigningA
     digest_alg: &digest::SHA384,
     pkcs8_template: &EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE,
     format_rs: format_rs_asn1,
+    nonce: Nonce::Random,
     id: AlgorithmID::ECDSA_P384_SHA384_ASN1_SIGNING,
 };
 
-static EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
+/// Signing of fixed-length (PKCS#11 style) ECDSA signatures using the
+/// P-521 curve and SHA-512.
+///
+/// See "`ECDSA_*_FIXED` Details" in `ring::signature`'s module-level
+/// documentation for more details.
+pub static ECDSA_P521_SHA512_FIXED_SIGNING: EcdsaSigningAlgorithm = EcdsaSigningAlgorithm {
+    curve: &ec::suite_b::curve::P521,
+    private_scalar_ops: &p521::PRIVATE_SCALAR_OPS,
+    private_key_ops: &p521::PRIVATE_KEY_OPS,
+    digest_alg: &digest::SHA512,
+    pkcs8_template: &EC_PUBLIC_KEY_P521_PKCS8_V1_TEMPLATE,
+    format_rs: format_rs_fixed,
+    nonce: Nonce::Random,
+    id: AlgorithmID::ECDSA_P521_SHA512_FIXED_SIGNING,
+};
+
+/// Signing of ASN.1 DER-encoded ECDSA signatures using the P-521 curve and
+/// SHA-512.
+///
+/// See "`ECDSA_*_ASN1` Details" in `ring::signature`'s module-level
+/// documentation for more details.
+pub static ECDSA_P521_SHA512_ASN1_SIGNING: EcdsaSigningAlgorithm = EcdsaSigningAlgorithm {
+    curve: &ec::suite_b::curve::P521,
+    private_scalar_ops: &p521::PRIVATE_SCALAR_OPS,
+    private_key_ops: &p521::PRIVATE_KEY_OPS,
+    digest_alg: &digest::SHA512,
+    pkcs8_template: &EC_PUBLIC_KEY_P521_PKCS8_V1_TEMPLATE,
+    format_rs: format_rs_asn1,
+    nonce: Nonce::Random,
+    id: AlgorithmID::ECDSA_P521_SHA512_ASN1_SIGNING,
+};
+
+/// Signing of fixed-length (PKCS#11 style) ECDSA signatures using the
+/// P-256 curve and SHA-256, with nonces generated as specified in [RFC 6979].
+///
+/// See "`ECDSA_*_FIXED` Details" in `ring::signature`'s module-level
+/// documentation for more details.
+///
+/// [RFC 6979]: https://tools.ietf.org/html/rfc6979
+pub static ECDSA_P256_SHA256_FIXED_DETERMINISTIC_SIGNING: EcdsaSigningAlgorithm =
+    EcdsaSigningAlgorithm {
+        curve: &ec::suite_b::curve::P256,
+        private_scalar_ops: &p256::PRIVATE_SCALAR_OPS,
+        private_key_ops: &p256::PRIVATE_KEY_OPS,
+        digest_alg: &digest::SHA256,
+        pkcs8_template: &EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE,
+        format_rs: format_rs_fixed,
+        nonce: Nonce::Deterministic,
+        id: AlgorithmID::ECDSA_P256_SHA256_FIXED_DETERMINISTIC_SIGNING,
+    };
+
+/// Signing of fixed-length (PKCS#11 style) ECDSA signatures using the
+/// P-384 curve and SHA-384, with nonces generated as specified in [RFC 6979].
+///
+/// See "`ECDSA_*_FIXED` Details" in `ring::signature`'s module-level
+/// documentation for more details.
+///
+/// [RFC 6979]: https://tools.ietf.org/html/rfc6979
+pub static ECDSA_P384_SHA384_FIXED_DETERMINISTIC_SIGNING: EcdsaSigningAlgorithm =
+    EcdsaSigningAlgorithm {
+        curve: &ec::suite_b::curve::P384,
+        private_scalar_ops: &p384::PRIVATE_SCALAR_OPS,
+        private_key_ops: &p384::PRIVATE_KEY_OPS,
+        digest_alg: &digest::SHA384,
+        pkcs8_template: &EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE,
+        format_rs: format_rs_fixed,
+        nonce: Nonce::Deterministic,
+        id: AlgorithmID::ECDSA_P384_SHA384_FIXED_DETERMINISTIC_SIGNING,
+    };
+
+/// Signing of fixed-length (PKCS#11 style) ECDSA signatures using the
+/// P-521 curve and SHA-512, with nonces generated as specified in [RFC 6979].
+///
+/// See "`ECDSA_*_FIXED` Details" in `ring::signature`'s module-level
+/// documentation for more details.
+///
+/// [RFC 6979]: https://tools.ietf.org/html/rfc6979
+pub static ECDSA_P521_SHA512_FIXED_DETERMINISTIC_SIGNING: EcdsaSigningAlgorithm =
+    EcdsaSigningAlgorithm {
+        curve: &ec::suite_b::curve::P521,
+        private_scalar_ops: &p521::PRIVATE_SCALAR_OPS,
+        private_key_ops: &p521::PRIVATE_KEY_OPS,
+        digest_alg: &digest::SHA512,
+        pkcs8_template: &EC_PUBLIC_KEY_P521_PKCS8_V1_TEMPLATE,
+        format_rs: format_rs_fixed,
+        nonce: Nonce::Deterministic,
+        id: AlgorithmID::ECDSA_P521_SHA512_FIXED_DETERMINISTIC_SIGNING,
+    };
+
+/// Signing of ASN.1 DER-encoded ECDSA signatures using the P-256 curve and
+/// SHA-256, with nonces generated as specified in [RFC 6979].
+///
+/// See "`ECDSA_*_ASN1` Details" in `ring::signature`'s module-level
+/// documentation for more details.
+///
+/// [RFC 6979]: https://tools.ietf.org/html/rfc6979
+pub static ECDSA_P256_SHA256_ASN1_DETERMINISTIC_SIGNING: EcdsaSigningAlgorithm =
+    EcdsaSigningAlgorithm {
+        curve: &ec::suite_b::curve::P256,
+        private_scalar_ops: &p256::PRIVATE_SCALAR_OPS,
+        private_key_ops: &p256::PRIVATE_KEY_OPS,
+        digest_alg: &digest::SHA256,
+        pkcs8_template: &EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE,
+        format_rs: format_rs_asn1,
+        nonce: Nonce::Deterministic,
+        id: AlgorithmID::ECDSA_P256_SHA256_ASN1_DETERMINISTIC_SIGNING,
+    };
+
+/// Signing of ASN.1 DER-encoded ECDSA signatures using the P-384 curve and
+/// SHA-384, with nonces generated as specified in [RFC 6979].
+///
+/// See "`ECDSA_*_ASN1` Details" in `ring::signature`'s module-level
+/// documentation for more details.
+///
+/// [RFC 6979]: https://tools.ietf.org/html/rfc6979
+pub static ECDSA_P384_SHA384_ASN1_DETERMINISTIC_SIGNING: EcdsaSigningAlgorithm =
+    EcdsaSigningAlgorithm {
+        curve: &ec::suite_b::curve::P384,
+        private_scalar_ops: &p384::PRIVATE_SCALAR_OPS,
+        private_key_ops: &p384::PRIVATE_KEY_OPS,
+        digest_alg: &digest::SHA384,
+        pkcs8_template: &EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE,
+        format_rs: format_rs_asn1,
+        nonce: Nonce::Deterministic,
+        id: AlgorithmID::ECDSA_P384_SHA384_ASN1_DETERMINISTIC_SIGNING,
+    };
+
+/// Signing of ASN.1 DER-encoded ECDSA signatures using the P-521 curve and
+/// SHA-512, with nonces generated as specified in [RFC 6979].
+///
+/// See "`ECDSA_*_ASN1` Details" in `ring::signature`'s module-level
+/// documentation for more details.
+///
+/// [RFC 6979]: https://tools.ietf.org/html/rfc6979
+pub static ECDSA_P521_SHA512_ASN1_DETERMINISTIC_SIGNING: EcdsaSigningAlgorithm =
+    EcdsaSigningAlgorithm {
+        curve: &ec::suite_b::curve::P521,
+        private_scalar_ops: &p521::PRIVATE_SCALAR_OPS,
+        private_key_ops: &p521::PRIVATE_KEY_OPS,
+        digest_alg: &digest::SHA512,
+        pkcs8_template: &EC_PUBLIC_KEY_P521_PKCS8_V1_TEMPLATE,
+        format_rs: format_rs_asn1,
+        nonce: Nonce::Deterministic,
+        id: AlgorithmID::ECDSA_P521_SHA512_ASN1_DETERMINISTIC_SIGNING,
+    };
+
+pub(crate) static EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
     bytes: include_bytes!("ecPublicKey_p256_pkcs8_v1_template.der"),
     alg_id_range: core::ops::Range { start: 8, end: 27 },
     curve_id_index: 9,
     private_key_index: 0x24,
 };
 
-static EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
+pub(crate) static EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
     bytes: include_bytes!("ecPublicKey_p384_pkcs8_v1_template.der"),
     alg_id_range: core::ops::Range { start: 8, end: 24 },
     curve_id_index: 9,
     private_key_index: 0x23,
 };
 
+static EC_PUBLIC_KEY_P521_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
+    bytes: include_bytes!("ecPublicKey_p521_pkcs8_v1_template.der"),
+    alg_id_range: core::ops::Range { start: 8, end: 24 },
+    curve_id_index: 9,
+    private_key_index: 0x23,
+};
+
+pub(crate) static EC_PUBLIC_KEY_K256_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
+    bytes: include_bytes!("ecPublicKey_k256_pkcs8_v1_template.der"),
+    alg_id_range: core::ops::Range { start: 8, end: 24 },
+    curve_id_index: 9,
+    private_key_index: 0x21,
+};
+
 #[cfg(test)]
 mod tests {
     use crate::{signature, test};
@@ -417,6 +990,8 @@ mod tests {
                 let alg = match (curve_name.as_str(), digest_name.as_str()) {
                     ("P-256", "SHA256") => &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
                     ("P-384", "SHA384") => &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+                    ("P-521", "SHA512") => &signature::ECDSA_P521_SHA512_FIXED_SIGNING,
+                    ("K-256", "SHA256") => &signature::ECDSA_K256_SHA256_FIXED_SIGNING,
                     _ => {
                         panic!("Unsupported curve+digest: {}+{}", curve_name, digest_name);
                     }
@@ -454,6 +1029,7 @@ mod tests {
                 let alg = match (curve_name.as_str(), digest_name.as_str()) {
                     ("P-256", "SHA256") => &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
                     ("P-384", "SHA384") => &signature::ECDSA_P384_SHA384_ASN1_SIGNING,
+                    ("P-521", "SHA512") => &signature::ECDSA_P521_SHA512_ASN1_SIGNING,
                     _ => {
                         panic!("Unsupported curve+digest: {}+{}", curve_name, digest_name);
                     }
@@ -471,4 +1047,64 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn signature_ecdsa_sign_deterministic_test() {
+        test::run(
+            test_file!("ecdsa_sign_deterministic_tests.txt"),
+            |section, test_case| {
+                assert_eq!(section, "");
+
+                let curve_name = test_case.consume_string("Curve");
+                let digest_name = test_case.consume_string("Digest");
+                let msg = test_case.consume_bytes("Msg");
+                let d = test_case.consume_bytes("d");
+                let q = test_case.consume_bytes("Q");
+
+                let expected_result = test_case.consume_bytes("Sig");
+
+                let (alg, asn1_alg, verification_alg) =
+                    match (curve_name.as_str(), digest_name.as_str()) {
+                        ("P-256", "SHA256") => (
+                            &signature::ECDSA_P256_SHA256_FIXED_DETERMINISTIC_SIGNING,
+                            &signature::ECDSA_P256_SHA256_ASN1_DETERMINISTIC_SIGNING,
+                            &signature::ECDSA_P256_SHA256_ASN1,
+                        ),
+                        ("P-384", "SHA384") => (
+                            &signature::ECDSA_P384_SHA384_FIXED_DETERMINISTIC_SIGNING,
+                            &signature::ECDSA_P384_SHA384_ASN1_DETERMINISTIC_SIGNING,
+                            &signature::ECDSA_P384_SHA384_ASN1,
+                        ),
+                        ("P-521", "SHA512") => (
+                            &signature::ECDSA_P521_SHA512_FIXED_DETERMINISTIC_SIGNING,
+                            &signature::ECDSA_P521_SHA512_ASN1_DETERMINISTIC_SIGNING,
+                            &signature::ECDSA_P521_SHA512_ASN1,
+                        ),
+                        _ => {
+                            panic!("Unsupported curve+digest: {}+{}", curve_name, digest_name);
+                        }
+                    };
+
+                // `FixedSliceRandom` panics if it is used, since the nonce
+                // must not depend on `rng`.
+                let rng = test::rand::FixedSliceRandom { bytes: &[] };
+
+                let private_key =
+                    signature::EcdsaKeyPair::from_private_key_and_public_key(alg, &d, &q).unwrap();
+                let actual_result = private_key.sign(&rng, &msg).unwrap();
+                assert_eq!(actual_result.as_ref(), &expected_result[..]);
+
+                let private_key =
+                    signature::EcdsaKeyPair::from_private_key_and_public_key(asn1_alg, &d, &q)
+                        .unwrap();
+                let sig = private_key.sign(&rng, &msg).unwrap();
+                assert_eq!(sig.as_ref(), private_key.sign(&rng, &msg).unwrap().as_ref());
+                assert!(signature::UnparsedPublicKey::new(verification_alg, &q)
+                    .verify(&msg, sig.as_ref())
+                    .is_ok());
+
+                Ok(())
+            },
+        );
+    }
 }
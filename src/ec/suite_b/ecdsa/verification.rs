@@ -12,16 +12,19 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-//! ECDSA Signatures using the P-256 and P-384 curves.
+//! ECDSA Signatures using the P-256, P-384, P-521, and secp256k1 curves.
 
-use super::digest_scalar::digest_scalar;
+use super::{digest_scalar::digest_scalar, signing::PublicKey};
 use crate::{
     arithmetic::montgomery::*,
     digest,
-    ec::suite_b::{ops::*, public_key::*, verify_jacobian_point_is_on_the_curve},
+    ec::{
+        self,
+        suite_b::{ops::*, private_key, public_key::*, verify_jacobian_point_is_on_the_curve},
+    },
     error,
     io::der,
-    limb, sealed, signature,
+    limb, sealed, signature, spki,
 };
 use untrusted;
 
@@ -46,10 +49,16 @@ enum AlgorithmID {
     ECDSA_P384_SHA256_ASN1,
     ECDSA_P384_SHA384_ASN1,
     ECDSA_P384_SHA384_FIXED,
+    ECDSA_P521_SHA512_ASN1,
+    ECDSA_P521_SHA512_FIXED,
+    ECDSA_K256_SHA256_FIXED,
 }
 
 derive_debug_via_id!(EcdsaVerificationAlgorithm);
 
+/// A validated ECDSA public key.
+pub struct ParsedPublicKey((Elem<R>, Elem<R>));
+
 impl signature::VerificationAlgorithm for EcdsaVerificationAlgorithm {
     fn verify(
         &self,
@@ -57,21 +66,141 @@ impl signature::VerificationAlgorithm for EcdsaVerificationAlgorithm {
         msg: untrusted::Input,
         signature: untrusted::Input,
     ) -> Result<(), error::Unspecified> {
-        let e = {
-            // NSA Guide Step 2: "Use the selected hash function to compute H =
-            // Hash(M)."
-            let h = digest::digest(self.digest_alg, msg.as_slice_less_safe());
-
-            // NSA Guide Step 3: "Convert the bit string H to an integer e as
-            // described in Appendix B.2."
-            digest_scalar(self.ops.scalar_ops, h)
+        let e = self.digest_msg(msg);
+        self.verify_digest(public_key, e, signature)
+    }
+
+    fn parse_public_key(
+        &self,
+        public_key: untrusted::Input,
+    ) -> Result<signature::ParsedKey, error::Unspecified> {
+        let point = parse_uncompressed_point(self.ops.public_key_ops, public_key)?;
+        Ok(signature::ParsedKey::Ecdsa(ParsedPublicKey(point)))
+    }
+
+    fn verify_parsed(
+        &self,
+        public_key: &signature::ParsedKey,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), error::Unspecified> {
+        let public_key = match public_key {
+            signature::ParsedKey::Ecdsa(ParsedPublicKey(point)) => point,
+            _ => {
+                return Err(error::Unspecified);
+            }
         };
+        let e = self.digest_msg(msg);
+        self.verify_digest_with_point(public_key, e, signature)
+    }
 
-        self.verify_digest(public_key, e, signature)
+    fn fips_approved(&self) -> bool {
+        match self.id {
+            AlgorithmID::ECDSA_K256_SHA256_FIXED => false,
+            _ => true,
+        }
     }
 }
 
 impl EcdsaVerificationAlgorithm {
+    /// Recovers the public key that produced `signature` of `msg`, given the
+    /// recovery ID returned by `EcdsaKeyPair::sign_recoverable()`.
+    ///
+    /// The public key is returned in uncompressed form. A successful recovery
+    /// only shows that `signature` is a valid signature of `msg` by the
+    /// returned key; it is up to the caller to decide whether that key is
+    /// trusted.
+    ///
+    /// This implements the Public Key Recovery Operation in [SEC 1] Section
+    /// 4.1.6, with the candidate point selected by `recovery_id`.
+    ///
+    /// [SEC 1]: http://www.secg.org/sec1-v2.pdf
+    pub fn recover_public_key(
+        &self,
+        msg: &[u8],
+        signature: &[u8],
+        recovery_id: u8,
+    ) -> Result<PublicKey, error::Unspecified> {
+        let public_key_ops = self.ops.public_key_ops;
+        let private_key_ops = self.ops.private_key_ops;
+        let scalar_ops = self.ops.scalar_ops;
+        let cops = public_key_ops.common;
+
+        if recovery_id > 3 {
+            return Err(error::Unspecified);
+        }
+
+        let (r, s) = untrusted::Input::from(signature).read_all(error::Unspecified, |input| {
+            (self.split_rs)(scalar_ops, input)
+        })?;
+        let r = scalar_parse_big_endian_variable(cops, limb::AllowZero::No, r)?;
+        let s = scalar_parse_big_endian_variable(cops, limb::AllowZero::No, s)?;
+
+        // SEC 1 Step 1.1: "Let x = r + jn." `j` is the high bit of the
+        // recovery ID, and `x` must be less than `q`.
+        let x = self.ops.scalar_as_elem(&r);
+        let x = if recovery_id & 2 == 0 {
+            x
+        } else {
+            if !self.ops.elem_less_than(&x, &self.ops.q_minus_n) {
+                return Err(error::Unspecified);
+            }
+            self.ops.elem_sum(&x, &cops.n)
+        };
+
+        // SEC 1 Steps 1.2 and 1.3: Convert `x` to the point `R` whose `y`
+        // has the parity given by the low bit of the recovery ID, failing if
+        // there is no such point.
+        let big_r = {
+            let elem_len = cops.len();
+            let mut compressed = [0u8; 1 + ec::ELEM_MAX_BYTES];
+            let compressed = &mut compressed[..(1 + elem_len)];
+            compressed[0] = 2 | (recovery_id & 1);
+            limb::big_endian_from_limbs(&x.limbs[..cops.num_limbs], &mut compressed[1..]);
+            parse_compressed_point(public_key_ops, untrusted::Input::from(compressed))?
+        };
+
+        // SEC 1 Step 1.5: "Compute e from M[.]"
+        let e = digest_scalar(scalar_ops, digest::digest(self.digest_alg, msg));
+
+        // SEC 1 Step 1.6.1: "Compute a candidate public key as:
+        // Q = r**-1 (sR - eG)."
+        let r_inv = scalar_ops.scalar_inv_to_mont(&r);
+        let u1 = scalar_ops.scalar_product(&e, &r_inv);
+        let u2 = scalar_ops.scalar_product(&s, &r_inv);
+        let q = cops.point_sum(
+            &private_key_ops.point_mul(&u2, &big_r),
+            &cops.point_negated(&private_key_ops.point_mul_base(&u1)),
+        );
+
+        // A signature can be constructed so that `sR == eG`, in which case
+        // there is no public key to recover. `big_endian_affine_from_jacobian`
+        // asserts that the point isn't at infinity, so check first.
+        cops.elem_verify_is_not_zero(&cops.point_z(&q))?;
+
+        let public_key = ec::PublicKey::from_fn(1 + (2 * cops.len()), |out| {
+            out[0] = 4; // Uncompressed encoding.
+            let (x_out, y_out) = out[1..].split_at_mut(cops.len());
+            private_key::big_endian_affine_from_jacobian(
+                private_key_ops,
+                Some(x_out),
+                Some(y_out),
+                &q,
+            )
+        })?;
+        Ok(PublicKey(public_key))
+    }
+
+    fn digest_msg(&self, msg: untrusted::Input) -> Scalar {
+        // NSA Guide Step 2: "Use the selected hash function to compute H =
+        // Hash(M)."
+        let h = digest::digest(self.digest_alg, msg.as_slice_less_safe());
+
+        // NSA Guide Step 3: "Convert the bit string H to an integer e as
+        // described in Appendix B.2."
+        digest_scalar(self.ops.scalar_ops, h)
+    }
+
     /// This is intentionally not public.
     fn verify_digest(
         &self,
@@ -81,9 +210,6 @@ impl EcdsaVerificationAlgorithm {
     ) -> Result<(), error::Unspecified> {
         // NSA Suite B Implementer's Guide to ECDSA Section 3.4.2.
 
-        let public_key_ops = self.ops.public_key_ops;
-        let scalar_ops = self.ops.scalar_ops;
-
         // NSA Guide Prerequisites:
         //
         //    Prior to accepting a verified digital signature as valid the
@@ -101,7 +227,19 @@ impl EcdsaVerificationAlgorithm {
         // can do. Prerequisite #2 is handled implicitly as the domain
         // parameters are hard-coded into the source. Prerequisite #3 is
         // handled by `parse_uncompressed_point`.
-        let peer_pub_key = parse_uncompressed_point(public_key_ops, public_key)?;
+        let peer_pub_key = parse_uncompressed_point(self.ops.public_key_ops, public_key)?;
+
+        self.verify_digest_with_point(&peer_pub_key, e, signature)
+    }
+
+    fn verify_digest_with_point(
+        &self,
+        peer_pub_key: &(Elem<R>, Elem<R>),
+        e: Scalar,
+        signature: untrusted::Input,
+    ) -> Result<(), error::Unspecified> {
+        let public_key_ops = self.ops.public_key_ops;
+        let scalar_ops = self.ops.scalar_ops;
 
         let (r, s) = signature.read_all(error::Unspecified, |input| {
             (self.split_rs)(scalar_ops, input)
@@ -124,7 +262,7 @@ impl EcdsaVerificationAlgorithm {
         // NSA Guide Step 6: "Compute the elliptic curve point
         // R = (xR, yR) = u1*G + u2*Q, using EC scalar multiplication and EC
         // addition. If R is equal to the point at infinity, output INVALID."
-        let product = twin_mul(self.ops.private_key_ops, &u1, &u2, &peer_pub_key);
+        let product = twin_mul(self.ops.private_key_ops, &u1, &u2, peer_pub_key);
 
         // Verify that the point we computed is on the curve; see
         // `verify_affine_point_is_on_the_curve_scaled` for details on why. It
@@ -170,6 +308,29 @@ impl EcdsaVerificationAlgorithm {
 
 impl sealed::Sealed for EcdsaVerificationAlgorithm {}
 
+impl spki::PublicKeyAlgorithm for EcdsaVerificationAlgorithm {
+    fn public_key_algorithm_id(&self) -> &'static [u8] {
+        match self.id {
+            AlgorithmID::ECDSA_P256_SHA256_ASN1
+            | AlgorithmID::ECDSA_P256_SHA256_FIXED
+            | AlgorithmID::ECDSA_P256_SHA384_ASN1 => {
+                include_bytes!("../../../data/alg-ec-public-key-p256.der")
+            }
+            AlgorithmID::ECDSA_P384_SHA256_ASN1
+            | AlgorithmID::ECDSA_P384_SHA384_ASN1
+            | AlgorithmID::ECDSA_P384_SHA384_FIXED => {
+                include_bytes!("../../../data/alg-ec-public-key-p384.der")
+            }
+            AlgorithmID::ECDSA_P521_SHA512_ASN1 | AlgorithmID::ECDSA_P521_SHA512_FIXED => {
+                include_bytes!("../../../data/alg-ec-public-key-p521.der")
+            }
+            AlgorithmID::ECDSA_K256_SHA256_FIXED => {
+                include_bytes!("../../../data/alg-ec-public-key-k256.der")
+            }
+        }
+    }
+}
+
 fn split_rs_fixed<'a>(
     ops: &'static ScalarOps,
     input: &mut untrusted::Reader<'a>,
@@ -227,6 +388,30 @@ pub static ECDSA_P384_SHA384_FIXED: EcdsaVerificationAlgorithm = EcdsaVerificati
     id: AlgorithmID::ECDSA_P384_SHA384_FIXED,
 };
 
+/// Verification of fixed-length (PKCS#11 style) ECDSA signatures using the
+/// P-521 curve and SHA-512.
+///
+/// See "`ECDSA_*_FIXED` Details" in `ring::signature`'s module-level
+/// documentation for more details.
+pub static ECDSA_P521_SHA512_FIXED: EcdsaVerificationAlgorithm = EcdsaVerificationAlgorithm {
+    ops: &p521::PUBLIC_SCALAR_OPS,
+    digest_alg: &digest::SHA512,
+    split_rs: split_rs_fixed,
+    id: AlgorithmID::ECDSA_P521_SHA512_FIXED,
+};
+
+/// Verification of fixed-length (PKCS#11 style) ECDSA signatures using the
+/// secp256k1 curve and SHA-256.
+///
+/// See "`ECDSA_*_FIXED` Details" in `ring::signature`'s module-level
+/// documentation for more details.
+pub static ECDSA_K256_SHA256_FIXED: EcdsaVerificationAlgorithm = EcdsaVerificationAlgorithm {
+    ops: &k256::PUBLIC_SCALAR_OPS,
+    digest_alg: &digest::SHA256,
+    split_rs: split_rs_fixed,
+    id: AlgorithmID::ECDSA_K256_SHA256_FIXED,
+};
+
 /// Verification of ASN.1 DER-encoded ECDSA signatures using the P-256 curve
 /// and SHA-256.
 ///
@@ -285,6 +470,18 @@ pub static ECDSA_P384_SHA384_ASN1: EcdsaVerificationAlgorithm = EcdsaVerificatio
     id: AlgorithmID::ECDSA_P384_SHA384_ASN1,
 };
 
+/// Verification of ASN.1 DER-encoded ECDSA signatures using the P-521 curve
+/// and SHA-512.
+///
+/// See "`ECDSA_*_ASN1` Details" in `ring::signature`'s module-level
+/// documentation for more details.
+pub static ECDSA_P521_SHA512_ASN1: EcdsaVerificationAlgorithm = EcdsaVerificationAlgorithm {
+    ops: &p521::PUBLIC_SCALAR_OPS,
+    digest_alg: &digest::SHA512,
+    split_rs: split_rs_asn1,
+    id: AlgorithmID::ECDSA_P521_SHA512_ASN1,
+};
+
 #[cfg(test)]
 mod tests {
     use super::*;
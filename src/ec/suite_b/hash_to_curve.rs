@@ -0,0 +1,105 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! The simplified SWU map to P-256 from RFC 9380.
+
+use super::ops::*;
+use crate::{
+    arithmetic::montgomery::R,
+    error,
+    limb::{limbs_are_zero_constant_time, Limb},
+};
+use untrusted;
+
+/// The length of the input to `elem_reduced`, *L* in RFC 9380.
+pub const HASHED_ELEM_LEN: usize = 48;
+
+/// Reduces the big-endian number `bytes`, which must be `HASHED_ELEM_LEN`
+/// bytes long, modulo q.
+pub fn elem_reduced(bytes: &[u8]) -> Elem<R> {
+    assert_eq!(bytes.len(), HASHED_ELEM_LEN);
+    elem_reduced_from_big_endian_wide(&p256::COMMON_OPS, bytes).unwrap()
+}
+
+/// `map_to_curve_simple_swu` from RFC 9380 Section 6.6.2, with Z = -10,
+/// using the exceptional-case handling of Section F.2.
+///
+/// This is constant time with respect to `u`.
+pub fn map_to_curve(u: &Elem<R>) -> Point {
+    let ops = &p256::COMMON_OPS;
+    let (a, b) = (&ops.a, &ops.b);
+    let z = ops.elem_negated(&small(10));
+    let one = small(1);
+
+    // inv0(x) = 1 / x, or 0 if x is 0.
+    let inv0 = |x: &Elem<R>| ops.elem_product(x, &p256::PRIVATE_KEY_OPS.elem_inverse_squared(x));
+    // g(x) = x**3 + A*x + B.
+    let g = |x: &Elem<R>| {
+        let mut r = ops.elem_squared(x);
+        ops.elem_add(&mut r, a);
+        ops.elem_mul(&mut r, x);
+        ops.elem_add(&mut r, b);
+        r
+    };
+
+    let zu2 = ops.elem_product(&z, &ops.elem_squared(u));
+    let mut tv1 = ops.elem_squared(&zu2);
+    ops.elem_add(&mut tv1, &zu2);
+    let tv1 = inv0(&tv1);
+
+    // x1 = (-B / A) * (1 + tv1), or B / (Z * A) if tv1 is zero.
+    let mut x1 = tv1;
+    ops.elem_add(&mut x1, &one);
+    let x1 = ops.elem_product(&ops.elem_product(&ops.elem_negated(b), &inv0(a)), &x1);
+    let exceptional_x1 = ops.elem_product(b, &inv0(&ops.elem_product(&z, a)));
+    let tv1_is_zero = limbs_are_zero_constant_time(&tv1.limbs[..ops.num_limbs]) as Limb;
+    let x1 = select(&x1, &exceptional_x1, tv1_is_zero);
+    let gx1 = g(&x1);
+    let x2 = ops.elem_product(&zu2, &x1);
+    let gx2 = g(&x2);
+
+    let y1 = p256::p256_elem_sqrt_candidate(&gx1);
+    let gx1_is_square = ops.elems_are_equal(&ops.elem_squared(&y1), &gx1) as Limb;
+    let y2 = p256::p256_elem_sqrt_candidate(&gx2);
+    let x = select(&x2, &x1, gx1_is_square);
+    let y = select(&y2, &y1, gx1_is_square);
+    let signs_differ = (sgn0(u) ^ sgn0(&y)).wrapping_neg();
+    let y = select(&y, &ops.elem_negated(&y), signs_differ);
+
+    ops.point_from_affine(&(x, y))
+}
+
+// Returns the least significant bit of `a`.
+fn sgn0(a: &Elem<R>) -> Limb {
+    p256::COMMON_OPS.elem_unencoded(a).limbs[0] & 1
+}
+
+// Returns `b` if `choose_b` is all ones and `a` if it is zero.
+fn select(a: &Elem<R>, b: &Elem<R>, choose_b: Limb) -> Elem<R> {
+    let mut r = *a;
+    for (r, b) in r.limbs.iter_mut().zip(b.limbs.iter()) {
+        *r ^= (*r ^ *b) & choose_b;
+    }
+    r
+}
+
+fn small(value: u8) -> Elem<R> {
+    let mut bytes = [0u8; 32];
+    bytes[31] = value;
+    untrusted::Input::from(&bytes)
+        .read_all(error::Unspecified, |input| {
+            p256::PUBLIC_KEY_OPS.elem_parse(input)
+        })
+        .unwrap()
+}
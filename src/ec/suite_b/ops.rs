@@ -34,6 +34,7 @@ pub type Scalar<E = Unencoded> = elem::Elem<N, E>;
 #[derive(Clone, Copy)]
 pub enum N {}
 
+#[derive(Clone, Copy)]
 pub struct Point {
     // The coordinates are stored in a contiguous array, where the first
     // `ops.num_limbs` elements are the X coordinate, the next
@@ -58,7 +59,7 @@ macro_rules! limbs {
       $limb_3:expr, $limb_2:expr, $limb_1:expr, $limb_0:expr ) => {
         [
             $limb_0, $limb_1, $limb_2, $limb_3, $limb_4, $limb_5, $limb_6, $limb_7, $limb_8,
-            $limb_9, $limb_a, $limb_b,
+            $limb_9, $limb_a, $limb_b, 0, 0, 0, 0, 0,
         ]
     };
 }
@@ -75,6 +76,9 @@ macro_rules! limbs {
             (($limb_7 | 0u64) << 32) | $limb_6,
             (($limb_9 | 0u64) << 32) | $limb_8,
             (($limb_b | 0u64) << 32) | $limb_a,
+            0,
+            0,
+            0,
         ]
     };
 }
@@ -88,10 +92,11 @@ static ONE: Elem<Unencoded> = Elem {
 /// Operations and values needed by all curve operations.
 pub struct CommonOps {
     pub num_limbs: usize,
+    pub order_bits: usize,
     q: Modulus,
     pub n: Elem<Unencoded>,
 
-    pub a: Elem<R>, // Must be -3 mod q
+    pub a: Elem<R>, // Must be -3 mod q, except for secp256k1 where it is 0
     pub b: Elem<R>,
 
     // In all cases, `r`, `a`, and `b` may all alias each other.
@@ -103,6 +108,12 @@ pub struct CommonOps {
 }
 
 impl CommonOps {
+    /// The length of an encoded field element or scalar, in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        (self.order_bits + 7) / 8
+    }
+
     #[inline]
     pub fn elem_add<E: Encoding>(&self, a: &mut Elem<E>, b: &Elem<E>) {
         binary_op_assign(self.elem_add_impl, a, b)
@@ -158,6 +169,96 @@ impl CommonOps {
         }
     }
 
+    /// Returns `q - a`, or zero if `a` is zero.
+    pub fn elem_negated<E: Encoding>(&self, a: &Elem<E>) -> Elem<E> {
+        let zero = Elem::<E>::zero();
+        let mut r = Elem::zero();
+        unsafe {
+            LIMBS_sub_mod(
+                r.limbs.as_mut_ptr(),
+                zero.limbs.as_ptr(),
+                a.limbs.as_ptr(),
+                self.q.p.as_ptr(),
+                self.num_limbs,
+            )
+        }
+        r
+    }
+
+    /// Returns a square root of `a`, or fails if `a` isn't a square.
+    ///
+    /// Every supported curve has q ≡ 3 (mod 4), so the candidate square root
+    /// is `a**((q + 1) / 4)`. This is only used to decompress public keys, so
+    /// whether `a` is a square isn't secret.
+    pub fn elem_sqrt(&self, a: &Elem<R>) -> Result<Elem<R>, error::Unspecified> {
+        let num_limbs = self.num_limbs;
+
+        // e = (q + 1) / 4. `q + 1` doesn't overflow since no `q` has all
+        // of its limbs set to all ones.
+        let mut e = [0; MAX_LIMBS];
+        let e = &mut e[..num_limbs];
+        e.copy_from_slice(&self.q.p[..num_limbs]);
+        for limb in e.iter_mut() {
+            *limb = limb.wrapping_add(1);
+            if *limb != 0 {
+                break;
+            }
+        }
+        for i in 0..num_limbs {
+            let next = if i + 1 < num_limbs { e[i + 1] } else { 0 };
+            e[i] = (e[i] >> 2) | (next << (LIMB_BITS - 2));
+        }
+
+        // Left-to-right binary exponentiation, starting at the most
+        // significant set bit of `e`.
+        let mut r: Option<Elem<R>> = None;
+        for limb in e.iter().rev() {
+            for bit in (0..LIMB_BITS).rev() {
+                if let Some(r) = &mut r {
+                    self.elem_square(r);
+                }
+                if (limb >> bit) & 1 == 1 {
+                    match &mut r {
+                        Some(r) => self.elem_mul(r, a),
+                        None => r = Some(*a),
+                    }
+                }
+            }
+        }
+        let r = r.unwrap();
+
+        if self.elems_are_equal(&self.elem_squared(&r), a) != LimbMask::True {
+            return Err(error::Unspecified);
+        }
+        Ok(r)
+    }
+
+    /// Returns the Jacobian point (x, y, 1).
+    #[cfg(any(feature = "p256_arithmetic", feature = "use_heap"))]
+    pub fn point_from_affine(&self, (x, y): &(Elem<R>, Elem<R>)) -> Point {
+        let rr: Elem<RR> = Elem {
+            limbs: self.q.rr,
+            m: PhantomData,
+            encoding: PhantomData,
+        };
+        let one: Elem<R> = self.elem_product(&ONE, &rr);
+        let n = self.num_limbs;
+        let mut r = Point::new_at_infinity();
+        r.xyz[..n].copy_from_slice(&x.limbs[..n]);
+        r.xyz[n..(2 * n)].copy_from_slice(&y.limbs[..n]);
+        r.xyz[(2 * n)..(3 * n)].copy_from_slice(&one.limbs[..n]);
+        r
+    }
+
+    /// Returns the point (x, -y, z).
+    pub fn point_negated(&self, p: &Point) -> Point {
+        let n = self.num_limbs;
+        let y = self.elem_negated(&self.point_y(p));
+        let mut r = *p;
+        r.xyz[n..(2 * n)].copy_from_slice(&y.limbs[..n]);
+        r
+    }
+
     pub fn point_sum(&self, a: &Point, b: &Point) -> Point {
         let mut r = Point::new_at_infinity();
         unsafe {
@@ -243,7 +344,7 @@ impl PublicKeyOps {
     // implements NIST SP 800-56A Step 2: "Verify that xQ and yQ are integers
     // in the interval [0, p-1] in the case that q is an odd prime p[.]"
     pub fn elem_parse(&self, input: &mut untrusted::Reader) -> Result<Elem<R>, error::Unspecified> {
-        let encoded_value = input.read_bytes(self.common.num_limbs * LIMB_BYTES)?;
+        let encoded_value = input.read_bytes(self.common.len())?;
         let parsed = elem_parse_big_endian_fixed_consttime(self.common, encoded_value)?;
         let mut r = Elem::zero();
         // Montgomery encode (elem_to_mont).
@@ -271,7 +372,7 @@ pub struct ScalarOps {
 impl ScalarOps {
     // The (maximum) length of a scalar, not including any padding.
     pub fn scalar_bytes_len(&self) -> usize {
-        self.common.num_limbs * LIMB_BYTES
+        self.common.len()
     }
 
     /// Returns the modular inverse of `a` (mod `n`). Panics of `a` is zero,
@@ -369,6 +470,21 @@ pub fn scalar_sum(ops: &CommonOps, a: &Scalar, b: &Scalar) -> Scalar {
     r
 }
 
+#[cfg(feature = "p256_arithmetic")]
+pub fn scalar_difference(ops: &CommonOps, a: &Scalar, b: &Scalar) -> Scalar {
+    let mut r = Scalar::zero();
+    unsafe {
+        LIMBS_sub_mod(
+            r.limbs.as_mut_ptr(),
+            a.limbs.as_ptr(),
+            b.limbs.as_ptr(),
+            ops.n.limbs.as_ptr(),
+            ops.num_limbs,
+        )
+    }
+    r
+}
+
 // Returns (`a` squared `squarings` times) * `b`.
 fn elem_sqr_mul(ops: &CommonOps, a: &Elem<R>, squarings: usize, b: &Elem<R>) -> Elem<R> {
     debug_assert!(squarings >= 1);
@@ -388,6 +504,50 @@ fn elem_sqr_mul_acc(ops: &CommonOps, acc: &mut Elem<R>, squarings: usize, b: &El
     ops.elem_mul(acc, b)
 }
 
+/// Reduces the big-endian number `bytes` modulo q, returning it in Montgomery
+/// form. `bytes` must be longer than `ops.len()` bytes but no more than twice
+/// as long, and `ops.len()` bytes must be exactly the size of R, as it is for
+/// P-256 and P-384.
+#[cfg(feature = "use_heap")]
+pub fn elem_reduced_from_big_endian_wide(
+    ops: &CommonOps,
+    bytes: &[u8],
+) -> Result<Elem<R>, error::Unspecified> {
+    let len = ops.len();
+    debug_assert_eq!(len * 8, ops.num_limbs * LIMB_BITS);
+    if bytes.len() <= len || bytes.len() > 2 * len {
+        return Err(error::Unspecified);
+    }
+    let parse = |bytes: &[u8]| -> Result<Elem<Unencoded>, error::Unspecified> {
+        let mut r = Elem::zero();
+        parse_big_endian_in_range_partially_reduced_and_pad_consttime(
+            untrusted::Input::from(bytes),
+            AllowZero::Yes,
+            &ops.q.p[..ops.num_limbs],
+            &mut r.limbs[..ops.num_limbs],
+        )?;
+        Ok(r)
+    };
+    let rr: Elem<RR> = Elem {
+        limbs: ops.q.rr,
+        m: PhantomData,
+        encoding: PhantomData,
+    };
+    // R**2 (mod q) is the Montgomery encoding of R.
+    let r: Elem<R> = Elem {
+        limbs: ops.q.rr,
+        m: PhantomData,
+        encoding: PhantomData,
+    };
+
+    // `bytes` is hi * R + lo.
+    let (hi, lo) = bytes.split_at(bytes.len() - len);
+    let hi: Elem<R> = ops.elem_product(&parse(hi)?, &rr);
+    let mut result = ops.elem_product(&hi, &r);
+    ops.elem_add(&mut result, &ops.elem_product(&parse(lo)?, &rr));
+    Ok(result)
+}
+
 #[inline]
 pub fn elem_parse_big_endian_fixed_consttime(
     ops: &CommonOps,
@@ -441,7 +601,7 @@ fn parse_big_endian_fixed_consttime<M>(
     allow_zero: AllowZero,
     max_exclusive: &[Limb],
 ) -> Result<elem::Elem<M, Unencoded>, error::Unspecified> {
-    if bytes.len() != ops.num_limbs * LIMB_BYTES {
+    if bytes.len() != ops.len() {
         return Err(error::Unspecified);
     }
     let mut r = elem::Elem::zero();
@@ -462,6 +622,13 @@ extern "C" {
         m: *const Limb,
         num_limbs: c::size_t,
     );
+    fn LIMBS_sub_mod(
+        r: *mut Limb,
+        a: *const Limb,
+        b: *const Limb,
+        m: *const Limb,
+        num_limbs: c::size_t,
+    );
 }
 
 #[cfg(test)]
@@ -484,6 +651,11 @@ mod tests {
         assert!(cops.is_zero(&x));
     }
 
+    #[test]
+    fn k256_q_minus_n_plus_n_equals_0_test() {
+        q_minus_n_plus_n_equals_0_test(&k256::PUBLIC_SCALAR_OPS);
+    }
+
     #[test]
     fn p256_q_minus_n_plus_n_equals_0_test() {
         q_minus_n_plus_n_equals_0_test(&p256::PUBLIC_SCALAR_OPS);
@@ -494,6 +666,19 @@ mod tests {
         q_minus_n_plus_n_equals_0_test(&p384::PUBLIC_SCALAR_OPS);
     }
 
+    #[test]
+    fn p521_q_minus_n_plus_n_equals_0_test() {
+        q_minus_n_plus_n_equals_0_test(&p521::PUBLIC_SCALAR_OPS);
+    }
+
+    #[test]
+    fn k256_elem_add_test() {
+        elem_add_test(
+            &k256::PUBLIC_SCALAR_OPS,
+            test_file!("ops/k256_elem_sum_tests.txt"),
+        );
+    }
+
     #[test]
     fn p256_elem_add_test() {
         elem_add_test(
@@ -510,6 +695,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn p521_elem_add_test() {
+        elem_add_test(
+            &p521::PUBLIC_SCALAR_OPS,
+            test_file!("ops/p521_elem_sum_tests.txt"),
+        );
+    }
+
     fn elem_add_test(ops: &PublicScalarOps, test_file: test::File) {
         test::run(test_file, |section, test_case| {
             assert_eq!(section, "");
@@ -534,6 +727,18 @@ mod tests {
     // XXX: There's no `GFp_nistz256_sub` in *ring*; it's logic is inlined into
     // the point arithmetic functions. Thus, we can't test it.
 
+    #[test]
+    fn k256_elem_sub_test() {
+        extern "C" {
+            fn GFp_k256_elem_sub(r: *mut Limb, a: *const Limb, b: *const Limb);
+        }
+        elem_sub_test(
+            &k256::COMMON_OPS,
+            GFp_k256_elem_sub,
+            test_file!("ops/k256_elem_sum_tests.txt"),
+        );
+    }
+
     #[test]
     fn p384_elem_sub_test() {
         extern "C" {
@@ -546,6 +751,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn p521_elem_sub_test() {
+        extern "C" {
+            fn GFp_p521_elem_sub(r: *mut Limb, a: *const Limb, b: *const Limb);
+        }
+        elem_sub_test(
+            &p521::COMMON_OPS,
+            GFp_p521_elem_sub,
+            test_file!("ops/p521_elem_sum_tests.txt"),
+        );
+    }
+
     fn elem_sub_test(
         ops: &CommonOps,
         elem_sub: unsafe extern "C" fn(r: *mut Limb, a: *const Limb, b: *const Limb),
@@ -585,6 +802,18 @@ mod tests {
     // XXX: There's no `GFp_nistz256_div_by_2` in *ring*; it's logic is inlined
     // into the point arithmetic functions. Thus, we can't test it.
 
+    #[test]
+    fn k256_elem_div_by_2_test() {
+        extern "C" {
+            fn GFp_k256_elem_div_by_2(r: *mut Limb, a: *const Limb);
+        }
+        elem_div_by_2_test(
+            &k256::COMMON_OPS,
+            GFp_k256_elem_div_by_2,
+            test_file!("ops/k256_elem_div_by_2_tests.txt"),
+        );
+    }
+
     #[test]
     fn p384_elem_div_by_2_test() {
         extern "C" {
@@ -597,6 +826,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn p521_elem_div_by_2_test() {
+        extern "C" {
+            fn GFp_p521_elem_div_by_2(r: *mut Limb, a: *const Limb);
+        }
+        elem_div_by_2_test(
+            &p521::COMMON_OPS,
+            GFp_p521_elem_div_by_2,
+            test_file!("ops/p521_elem_div_by_2_tests.txt"),
+        );
+    }
+
     fn elem_div_by_2_test(
         ops: &CommonOps,
         elem_div_by_2: unsafe extern "C" fn(r: *mut Limb, a: *const Limb),
@@ -619,6 +860,18 @@ mod tests {
     }
 
     // TODO: Add test vectors that test the range of values above `q`.
+    #[test]
+    fn k256_elem_neg_test() {
+        extern "C" {
+            fn GFp_k256_elem_neg(r: *mut Limb, a: *const Limb);
+        }
+        elem_neg_test(
+            &k256::COMMON_OPS,
+            GFp_k256_elem_neg,
+            test_file!("ops/k256_elem_neg_tests.txt"),
+        );
+    }
+
     #[test]
     fn p256_elem_neg_test() {
         extern "C" {
@@ -643,6 +896,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn p521_elem_neg_test() {
+        extern "C" {
+            fn GFp_p521_elem_neg(r: *mut Limb, a: *const Limb);
+        }
+        elem_neg_test(
+            &p521::COMMON_OPS,
+            GFp_p521_elem_neg,
+            test_file!("ops/p521_elem_neg_tests.txt"),
+        );
+    }
+
     fn elem_neg_test(
         ops: &CommonOps,
         elem_neg: unsafe extern "C" fn(r: *mut Limb, a: *const Limb),
@@ -676,6 +941,11 @@ mod tests {
         })
     }
 
+    #[test]
+    fn k256_elem_mul_test() {
+        elem_mul_test(&k256::COMMON_OPS, test_file!("ops/k256_elem_mul_tests.txt"));
+    }
+
     #[test]
     fn p256_elem_mul_test() {
         elem_mul_test(&p256::COMMON_OPS, test_file!("ops/p256_elem_mul_tests.txt"));
@@ -686,6 +956,11 @@ mod tests {
         elem_mul_test(&p384::COMMON_OPS, test_file!("ops/p384_elem_mul_tests.txt"));
     }
 
+    #[test]
+    fn p521_elem_mul_test() {
+        elem_mul_test(&p521::COMMON_OPS, test_file!("ops/p521_elem_mul_tests.txt"));
+    }
+
     fn elem_mul_test(ops: &CommonOps, test_file: test::File) {
         test::run(test_file, |section, test_case| {
             assert_eq!(section, "");
@@ -700,6 +975,14 @@ mod tests {
         })
     }
 
+    #[test]
+    fn k256_scalar_mul_test() {
+        scalar_mul_test(
+            &k256::SCALAR_OPS,
+            test_file!("ops/k256_scalar_mul_tests.txt"),
+        );
+    }
+
     #[test]
     fn p256_scalar_mul_test() {
         scalar_mul_test(
@@ -716,6 +999,28 @@ mod tests {
         );
     }
 
+    // The Montgomery factor R for P-521 scalars depends on the limb size, so
+    // the P-521 test vectors contain plain (non-Montgomery) products.
+    #[test]
+    fn p521_scalar_mul_test() {
+        let ops = &p521::PRIVATE_SCALAR_OPS;
+        test::run(
+            test_file!("ops/p521_scalar_mul_tests.txt"),
+            |section, test_case| {
+                assert_eq!(section, "");
+                let cops = ops.scalar_ops.common;
+                let a = consume_scalar(cops, test_case, "a");
+                let b = consume_scalar(cops, test_case, "b");
+                let expected_result = consume_scalar(cops, test_case, "r");
+                let a = ops.scalar_ops.scalar_product(&a, &ops.oneRR_mod_n);
+                let actual_result = ops.scalar_ops.scalar_product(&a, &b);
+                assert_limbs_are_equal(cops, &actual_result.limbs, &expected_result.limbs);
+
+                Ok(())
+            },
+        )
+    }
+
     fn scalar_mul_test(ops: &ScalarOps, test_file: test::File) {
         test::run(test_file, |section, test_case| {
             assert_eq!(section, "");
@@ -777,6 +1082,12 @@ mod tests {
         })
     }
 
+    #[test]
+    #[should_panic(expected = "!self.common.is_zero(a)")]
+    fn k256_scalar_inv_to_mont_zero_panic_test() {
+        let _ = k256::SCALAR_OPS.scalar_inv_to_mont(&ZERO_SCALAR);
+    }
+
     #[test]
     #[should_panic(expected = "!self.common.is_zero(a)")]
     fn p256_scalar_inv_to_mont_zero_panic_test() {
@@ -789,6 +1100,20 @@ mod tests {
         let _ = p384::SCALAR_OPS.scalar_inv_to_mont(&ZERO_SCALAR);
     }
 
+    #[test]
+    #[should_panic(expected = "!self.common.is_zero(a)")]
+    fn p521_scalar_inv_to_mont_zero_panic_test() {
+        let _ = p521::SCALAR_OPS.scalar_inv_to_mont(&ZERO_SCALAR);
+    }
+
+    #[test]
+    fn k256_point_sum_test() {
+        point_sum_test(
+            &k256::PRIVATE_KEY_OPS,
+            test_file!("ops/k256_point_sum_tests.txt"),
+        );
+    }
+
     #[test]
     fn p256_point_sum_test() {
         point_sum_test(
@@ -805,6 +1130,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn p521_point_sum_test() {
+        point_sum_test(
+            &p521::PRIVATE_KEY_OPS,
+            test_file!("ops/p521_point_sum_tests.txt"),
+        );
+    }
+
     fn point_sum_test(ops: &PrivateKeyOps, test_file: test::File) {
         test::run(test_file, |section, test_case| {
             assert_eq!(section, "");
@@ -865,6 +1198,21 @@ mod tests {
         });
     }
 
+    #[test]
+    fn k256_point_double_test() {
+        extern "C" {
+            fn GFp_k256_point_double(
+                r: *mut Limb,   // [k256::COMMON_OPS.num_limbs*3]
+                a: *const Limb, // [k256::COMMON_OPS.num_limbs*3]
+            );
+        }
+        point_double_test(
+            &k256::PRIVATE_KEY_OPS,
+            GFp_k256_point_double,
+            test_file!("ops/k256_point_double_tests.txt"),
+        );
+    }
+
     #[test]
     fn p256_point_double_test() {
         extern "C" {
@@ -895,6 +1243,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn p521_point_double_test() {
+        extern "C" {
+            fn GFp_nistz521_point_double(
+                r: *mut Limb,   // [p521::COMMON_OPS.num_limbs*3]
+                a: *const Limb, // [p521::COMMON_OPS.num_limbs*3]
+            );
+        }
+        point_double_test(
+            &p521::PRIVATE_KEY_OPS,
+            GFp_nistz521_point_double,
+            test_file!("ops/p521_point_double_tests.txt"),
+        );
+    }
+
     fn point_double_test(
         ops: &PrivateKeyOps,
         point_double: unsafe extern "C" fn(
@@ -920,6 +1283,14 @@ mod tests {
         });
     }
 
+    #[test]
+    fn k256_point_mul_test() {
+        point_mul_tests(
+            &k256::PRIVATE_KEY_OPS,
+            test_file!("ops/k256_point_mul_tests.txt"),
+        );
+    }
+
     #[test]
     fn p256_point_mul_test() {
         point_mul_tests(
@@ -936,6 +1307,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn p521_point_mul_test() {
+        point_mul_tests(
+            &p521::PRIVATE_KEY_OPS,
+            test_file!("ops/p521_point_mul_tests.txt"),
+        );
+    }
+
     fn point_mul_tests(ops: &PrivateKeyOps, test_file: test::File) {
         test::run(test_file, |section, test_case| {
             assert_eq!(section, "");
@@ -984,9 +1363,9 @@ mod tests {
 
             let product = priv_ops.point_mul(&p_scalar, &p);
 
-            let mut actual_result = vec![4u8; 1 + (2 * (cops.num_limbs * LIMB_BYTES))];
+            let mut actual_result = vec![4u8; 1 + (2 * cops.len())];
             {
-                let (x, y) = actual_result[1..].split_at_mut(cops.num_limbs * LIMB_BYTES);
+                let (x, y) = actual_result[1..].split_at_mut(cops.len());
                 super::super::private_key::big_endian_affine_from_jacobian(
                     priv_ops,
                     Some(x),
@@ -1002,6 +1381,14 @@ mod tests {
         })
     }
 
+    #[test]
+    fn k256_point_mul_base_test() {
+        point_mul_base_tests(
+            &k256::PRIVATE_KEY_OPS,
+            test_file!("ops/k256_point_mul_base_tests.txt"),
+        );
+    }
+
     #[test]
     fn p256_point_mul_base_test() {
         point_mul_base_tests(
@@ -1018,6 +1405,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn p521_point_mul_base_test() {
+        point_mul_base_tests(
+            &p521::PRIVATE_KEY_OPS,
+            test_file!("ops/p521_point_mul_base_tests.txt"),
+        );
+    }
+
     fn point_mul_base_tests(ops: &PrivateKeyOps, test_file: test::File) {
         test::run(test_file, |section, test_case| {
             assert_eq!(section, "");
@@ -1194,7 +1589,7 @@ mod tests {
         name: &str,
     ) -> Vec<u8> {
         let unpadded_bytes = test_case.consume_bytes(name);
-        let mut bytes = vec![0; (ops.num_limbs * LIMB_BYTES) - unpadded_bytes.len()];
+        let mut bytes = vec![0; ops.len() - unpadded_bytes.len()];
         bytes.extend(&unpadded_bytes);
         bytes
     }
@@ -1279,5 +1674,7 @@ macro_rules! bench_curve {
 }
 
 mod elem;
+pub mod k256;
 pub mod p256;
 pub mod p384;
+pub mod p521;
@@ -0,0 +1,381 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! secp256k1 (a.k.a. K-256), from SEC 2.
+//!
+//! Unlike the NIST curves, secp256k1 has `a == 0`, so the point doubling
+//! implementation is specific to this curve.
+
+use super::{
+    elem::{binary_op, binary_op_assign},
+    elem_sqr_mul, elem_sqr_mul_acc, Modulus, *,
+};
+use core::marker::PhantomData;
+
+macro_rules! k256_limbs {
+    [$limb_7:expr, $limb_6:expr, $limb_5:expr, $limb_4:expr,
+     $limb_3:expr, $limb_2:expr, $limb_1:expr, $limb_0:expr] => {
+        limbs![0, 0, 0, 0,
+               $limb_7, $limb_6, $limb_5, $limb_4,
+               $limb_3, $limb_2, $limb_1, $limb_0]
+    };
+}
+
+pub static COMMON_OPS: CommonOps = CommonOps {
+    num_limbs: 256 / LIMB_BITS,
+    order_bits: 256,
+
+    q: Modulus {
+        p: k256_limbs![
+            0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xfffffffe,
+            0xfffffc2f
+        ],
+        rr: k256_limbs![0, 0, 0, 0, 0, 1, 0x000007a2, 0x000e90a1],
+    },
+
+    n: Elem {
+        limbs: k256_limbs![
+            0xffffffff, 0xffffffff, 0xffffffff, 0xfffffffe, 0xbaaedce6, 0xaf48a03b, 0xbfd25e8c,
+            0xd0364141
+        ],
+        m: PhantomData,
+        encoding: PhantomData, // Unencoded
+    },
+
+    a: Elem {
+        limbs: k256_limbs![0, 0, 0, 0, 0, 0, 0, 0],
+        m: PhantomData,
+        encoding: PhantomData, // R
+    },
+    b: Elem {
+        limbs: k256_limbs![0, 0, 0, 0, 0, 0, 0x00000007, 0x00001ab7],
+        m: PhantomData,
+        encoding: PhantomData, // R
+    },
+
+    elem_add_impl: GFp_k256_elem_add,
+    elem_mul_mont: GFp_k256_elem_mul_mont,
+    elem_sqr_mont: GFp_k256_elem_sqr_mont,
+
+    point_add_jacobian_impl: GFp_k256_point_add,
+};
+
+pub static PRIVATE_KEY_OPS: PrivateKeyOps = PrivateKeyOps {
+    common: &COMMON_OPS,
+    elem_inv_squared: k256_elem_inv_squared,
+    point_mul_base_impl: k256_point_mul_base_impl,
+    point_mul_impl: GFp_k256_point_mul,
+};
+
+fn k256_elem_inv_squared(a: &Elem<R>) -> Elem<R> {
+    // Calculate a**-2 (mod q) == a**(q - 3) (mod q)
+    //
+    // The exponent (q - 3) is:
+    //
+    //    0xffffffffffffffffffffffffffffffffffffffffffffffffffffffff\
+    //      fffffffefffffc2c
+
+    #[inline]
+    fn sqr_mul(a: &Elem<R>, squarings: usize, b: &Elem<R>) -> Elem<R> {
+        elem_sqr_mul(&COMMON_OPS, a, squarings, b)
+    }
+
+    #[inline]
+    fn sqr_mul_acc(a: &mut Elem<R>, squarings: usize, b: &Elem<R>) {
+        elem_sqr_mul_acc(&COMMON_OPS, a, squarings, b)
+    }
+
+    // `x<N>` is `a` raised to the power of a number consisting of N one bits.
+    let b_1 = &a;
+    let b_11 = sqr_mul(b_1, 1, b_1);
+    let x3 = sqr_mul(&b_11, 1, b_1);
+    let x6 = sqr_mul(&x3, 3, &x3);
+    let x9 = sqr_mul(&x6, 3, &x3);
+    let x11 = sqr_mul(&x9, 2, &b_11);
+    let x22 = sqr_mul(&x11, 11, &x11);
+    let x44 = sqr_mul(&x22, 22, &x22);
+    let x88 = sqr_mul(&x44, 44, &x44);
+    let x176 = sqr_mul(&x88, 88, &x88);
+    let x220 = sqr_mul(&x176, 44, &x44);
+
+    // The first 223 bits of the exponent are all ones.
+    let mut acc = sqr_mul(&x220, 3, &x3);
+
+    // The remaining 33 bits are 0_1111111111111111111111_0000_1_0_11_00.
+    sqr_mul_acc(&mut acc, 1 + 22, &x22);
+    sqr_mul_acc(&mut acc, 4 + 1, b_1);
+    sqr_mul_acc(&mut acc, 1 + 2, &b_11);
+    COMMON_OPS.elem_square(&mut acc);
+    COMMON_OPS.elem_square(&mut acc);
+
+    acc
+}
+
+fn k256_point_mul_base_impl(a: &Scalar) -> Point {
+    // XXX: Not efficient. TODO: Precompute multiples of the generator.
+    static K256_GENERATOR: (Elem<R>, Elem<R>) = (
+        Elem {
+            limbs: k256_limbs![
+                0x9981e643, 0xe9089f48, 0x979f48c0, 0x33fd129c, 0x231e2953, 0x29bc66db, 0xd7362e5a,
+                0x487e2097
+            ],
+            m: PhantomData,
+            encoding: PhantomData,
+        },
+        Elem {
+            limbs: k256_limbs![
+                0xcf3f851f, 0xd4a582d6, 0x70b6b59a, 0xac19c136, 0x8dfc5d5d, 0x1f1dc64d, 0xb15ea6d2,
+                0xd3dbabe2
+            ],
+            m: PhantomData,
+            encoding: PhantomData,
+        },
+    );
+
+    PRIVATE_KEY_OPS.point_mul(a, &K256_GENERATOR)
+}
+
+pub static PUBLIC_KEY_OPS: PublicKeyOps = PublicKeyOps {
+    common: &COMMON_OPS,
+};
+
+pub static SCALAR_OPS: ScalarOps = ScalarOps {
+    common: &COMMON_OPS,
+    scalar_inv_to_mont_impl: k256_scalar_inv_to_mont,
+    scalar_mul_mont: GFp_k256_scalar_mul_mont,
+};
+
+pub static PUBLIC_SCALAR_OPS: PublicScalarOps = PublicScalarOps {
+    scalar_ops: &SCALAR_OPS,
+    public_key_ops: &PUBLIC_KEY_OPS,
+    private_key_ops: &PRIVATE_KEY_OPS,
+
+    q_minus_n: Elem {
+        limbs: k256_limbs![0, 0, 0, 1, 0x45512319, 0x50b75fc4, 0x402da172, 0x2fc9baee],
+
+        m: PhantomData,
+        encoding: PhantomData, // Unencoded
+    },
+};
+
+pub static PRIVATE_SCALAR_OPS: PrivateScalarOps = PrivateScalarOps {
+    scalar_ops: &SCALAR_OPS,
+
+    oneRR_mod_n: Scalar {
+        limbs: N_RR_LIMBS,
+        m: PhantomData,
+        encoding: PhantomData, // R
+    },
+};
+
+fn k256_scalar_inv_to_mont(a: &Scalar<Unencoded>) -> Scalar<R> {
+    // Calculate the modular inverse of scalar |a| using Fermat's Little
+    // Theorem:
+    //
+    //   a**-1 (mod n) == a**(n - 2) (mod n)
+    //
+    // The exponent (n - 2) is:
+    //
+    //     0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd036413f
+
+    fn mul(a: &Scalar<R>, b: &Scalar<R>) -> Scalar<R> {
+        binary_op(GFp_k256_scalar_mul_mont, a, b)
+    }
+
+    fn sqr(a: &Scalar<R>) -> Scalar<R> {
+        binary_op(GFp_k256_scalar_mul_mont, a, a)
+    }
+
+    fn sqr_mut(a: &mut Scalar<R>) {
+        unary_op_from_binary_op_assign(GFp_k256_scalar_mul_mont, a);
+    }
+
+    // Returns (`a` squared `squarings` times) * `b`.
+    fn sqr_mul(a: &Scalar<R>, squarings: usize, b: &Scalar<R>) -> Scalar<R> {
+        debug_assert!(squarings >= 1);
+        let mut tmp = sqr(a);
+        for _ in 1..squarings {
+            sqr_mut(&mut tmp);
+        }
+        mul(&tmp, b)
+    }
+
+    // Sets `acc` = (`acc` squared `squarings` times) * `b`.
+    fn sqr_mul_acc(acc: &mut Scalar<R>, squarings: usize, b: &Scalar<R>) {
+        debug_assert!(squarings >= 1);
+        for _ in 0..squarings {
+            sqr_mut(acc);
+        }
+        binary_op_assign(GFp_k256_scalar_mul_mont, acc, b)
+    }
+
+    fn to_mont(a: &Scalar<Unencoded>) -> Scalar<R> {
+        static N_RR: Scalar<Unencoded> = Scalar {
+            limbs: N_RR_LIMBS,
+            m: PhantomData,
+            encoding: PhantomData,
+        };
+        binary_op(GFp_k256_scalar_mul_mont, a, &N_RR)
+    }
+
+    // Indexes into `d`.
+    const B_1: usize = 0;
+    const B_11: usize = 1;
+    const B_101: usize = 2;
+    const B_111: usize = 3;
+    const B_1001: usize = 4;
+    const B_1011: usize = 5;
+    const B_1101: usize = 6;
+    const B_1111: usize = 7;
+    const DIGIT_COUNT: usize = 8;
+
+    let mut d = [Scalar::zero(); DIGIT_COUNT];
+    d[B_1] = to_mont(a);
+    let b_10 = sqr(&d[B_1]);
+    for i in B_11..DIGIT_COUNT {
+        d[i] = mul(&d[i - 1], &b_10);
+    }
+
+    let ff = sqr_mul(&d[B_1111], 0 + 4, &d[B_1111]);
+    let ffff = sqr_mul(&ff, 0 + 8, &ff);
+    let ffffffff = sqr_mul(&ffff, 0 + 16, &ffff);
+
+    let ffffffffffffffff = sqr_mul(&ffffffff, 0 + 32, &ffffffff);
+
+    // ffffffffffffffffffffffff
+    let mut acc = sqr_mul(&ffffffffffffffff, 0 + 32, &ffffffff);
+
+    // fffffffffffffffffffffffffffffff_111
+    sqr_mul_acc(&mut acc, 0 + 16, &ffff);
+    sqr_mul_acc(&mut acc, 0 + 8, &ff);
+    sqr_mul_acc(&mut acc, 0 + 4, &d[B_1111]);
+    sqr_mul_acc(&mut acc, 0 + 3, &d[B_111]);
+
+    // The rest of the exponent, in binary, is:
+    //
+    //    0101110101010111011011100111001101010111101001000101000000011101
+    //    1101111111101001001011110100011001101000000110110010000010011111
+    //    1
+
+    static REMAINING_WINDOWS: [(u8, u8); 26] = [
+        (1 + 4, B_1011 as u8),
+        (3, B_101 as u8),
+        (1 + 3, B_101 as u8),
+        (1 + 3, B_111 as u8),
+        (1 + 4, B_1101 as u8),
+        (2, B_11 as u8),
+        (2 + 3, B_111 as u8),
+        (2 + 4, B_1101 as u8),
+        (1 + 4, B_1011 as u8),
+        (4, B_1101 as u8),
+        (2 + 1, B_1 as u8),
+        (3 + 3, B_101 as u8),
+        (7 + 3, B_111 as u8),
+        (1 + 3, B_111 as u8),
+        (1 + 4, B_1111 as u8),
+        (4, B_1111 as u8),
+        (1 + 4, B_1001 as u8),
+        (2 + 4, B_1011 as u8),
+        (4, B_1101 as u8),
+        (3 + 2, B_11 as u8),
+        (2 + 4, B_1101 as u8),
+        (6 + 4, B_1101 as u8),
+        (4, B_1001 as u8),
+        (5 + 4, B_1001 as u8),
+        (4, B_1111 as u8),
+        (1, B_1 as u8),
+    ];
+
+    for &(squarings, digit) in &REMAINING_WINDOWS[..] {
+        sqr_mul_acc(&mut acc, usize::from(squarings), &d[usize::from(digit)]);
+    }
+
+    acc
+}
+
+unsafe extern "C" fn GFp_k256_elem_sqr_mont(
+    r: *mut Limb,   // [COMMON_OPS.num_limbs]
+    a: *const Limb, // [COMMON_OPS.num_limbs]
+) {
+    // XXX: Inefficient. TODO: Make a dedicated squaring routine.
+    GFp_k256_elem_mul_mont(r, a, a);
+}
+
+const N_RR_LIMBS: [Limb; MAX_LIMBS] = k256_limbs![
+    0x9d671cd5, 0x81c69bc5, 0xe697f5e4, 0x5bcd07c6, 0x741496c2, 0x0e7cf878, 0x896cf214, 0x67d7d140
+];
+
+extern "C" {
+    fn GFp_k256_elem_add(
+        r: *mut Limb,   // [COMMON_OPS.num_limbs]
+        a: *const Limb, // [COMMON_OPS.num_limbs]
+        b: *const Limb, // [COMMON_OPS.num_limbs]
+    );
+    fn GFp_k256_elem_mul_mont(
+        r: *mut Limb,   // [COMMON_OPS.num_limbs]
+        a: *const Limb, // [COMMON_OPS.num_limbs]
+        b: *const Limb, // [COMMON_OPS.num_limbs]
+    );
+
+    fn GFp_k256_point_add(
+        r: *mut Limb,   // [3][COMMON_OPS.num_limbs]
+        a: *const Limb, // [3][COMMON_OPS.num_limbs]
+        b: *const Limb, // [3][COMMON_OPS.num_limbs]
+    );
+    fn GFp_k256_point_mul(
+        r: *mut Limb,          // [3][COMMON_OPS.num_limbs]
+        p_scalar: *const Limb, // [COMMON_OPS.num_limbs]
+        p_x: *const Limb,      // [COMMON_OPS.num_limbs]
+        p_y: *const Limb,      // [COMMON_OPS.num_limbs]
+    );
+
+    fn GFp_k256_scalar_mul_mont(
+        r: *mut Limb,   // [COMMON_OPS.num_limbs]
+        a: *const Limb, // [COMMON_OPS.num_limbs]
+        b: *const Limb, // [COMMON_OPS.num_limbs]
+    );
+}
+
+#[cfg(feature = "internal_benches")]
+mod internal_benches {
+    use super::{super::internal_benches::*, *};
+
+    bench_curve!(&[
+        Scalar {
+            limbs: LIMBS_1,
+            encoding: PhantomData,
+            m: PhantomData
+        },
+        Scalar {
+            limbs: LIMBS_ALTERNATING_10,
+            encoding: PhantomData,
+            m: PhantomData
+        },
+        Scalar {
+            // n - 1
+            limbs: k256_limbs![
+                0xffffffff,
+                0xffffffff,
+                0xffffffff,
+                0xfffffffe,
+                0xbaaedce6,
+                0xaf48a03b,
+                0xbfd25e8c,
+                0xd0364141 - 1
+            ],
+            encoding: PhantomData,
+            m: PhantomData,
+        },
+    ]);
+}
@@ -29,6 +29,7 @@ macro_rules! p256_limbs {
 
 pub static COMMON_OPS: CommonOps = CommonOps {
     num_limbs: 256 / LIMB_BITS,
+    order_bits: 256,
 
     q: Modulus {
         p: p256_limbs![
@@ -126,6 +127,41 @@ fn p256_elem_inv_squared(a: &Elem<R>) -> Elem<R> {
     acc
 }
 
+/// Returns a**((q + 1) / 4) (mod q). Since q ≡ 3 (mod 4), this is a square
+/// root of `a` if `a` is a square; the caller must check that it is.
+#[cfg(any(feature = "p256_arithmetic", feature = "use_heap"))]
+pub fn p256_elem_sqrt_candidate(a: &Elem<R>) -> Elem<R> {
+    // The exponent (q + 1) / 4 is:
+    //
+    //    0x3fffffffc0000000400000000000000000000000400000000000000000000000
+    //
+    // which is (2**32 - 1) * 2**222 + 2**190 + 2**94.
+
+    #[inline]
+    fn sqr_mul(a: &Elem<R>, squarings: usize, b: &Elem<R>) -> Elem<R> {
+        elem_sqr_mul(&COMMON_OPS, a, squarings, b)
+    }
+
+    let b_1 = &a;
+    let b_11 = sqr_mul(b_1, 1, b_1);
+    let f = sqr_mul(&b_11, 2, &b_11);
+    let ff = sqr_mul(&f, 4, &f);
+    let ffff = sqr_mul(&ff, 8, &ff);
+    let ffffffff = sqr_mul(&ffff, 16, &ffff);
+
+    // (2**32 - 1) * 2**32 + 1
+    let mut acc = sqr_mul(&ffffffff, 32, b_1);
+
+    // ((2**32 - 1) * 2**32 + 1) * 2**96 + 1
+    elem_sqr_mul_acc(&COMMON_OPS, &mut acc, 96, b_1);
+
+    for _ in 0..94 {
+        COMMON_OPS.elem_square(&mut acc);
+    }
+
+    acc
+}
+
 fn p256_point_mul_base_impl(g_scalar: &Scalar) -> Point {
     let mut r = Point::new_at_infinity();
     unsafe {
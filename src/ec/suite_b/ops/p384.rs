@@ -30,6 +30,7 @@ macro_rules! p384_limbs {
 
 pub static COMMON_OPS: CommonOps = CommonOps {
     num_limbs: 384 / LIMB_BITS,
+    order_bits: 384,
 
     q: Modulus {
         p: p384_limbs![
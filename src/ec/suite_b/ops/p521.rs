@@ -0,0 +1,469 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! P-521 field elements are not Montgomery-encoded; i.e. R = 1 for the field,
+//! since reduction modulo the Mersenne prime q = 2**521 - 1 is cheap. Scalars
+//! use the usual Montgomery encoding.
+
+use super::{
+    elem::{binary_op, binary_op_assign},
+    elem_sqr_mul, elem_sqr_mul_acc, Modulus, *,
+};
+use core::marker::PhantomData;
+
+// 521 bits don't fit in the 12 words that `limbs!` takes, so P-521 has its
+// own version of it that takes 17 words, most significant word first.
+#[cfg(all(target_pointer_width = "32", target_endian = "little"))]
+macro_rules! p521_limbs {
+    [$limb_10:expr, $limb_f:expr, $limb_e:expr, $limb_d:expr, $limb_c:expr,
+     $limb_b:expr, $limb_a:expr, $limb_9:expr, $limb_8:expr,
+     $limb_7:expr, $limb_6:expr, $limb_5:expr, $limb_4:expr,
+     $limb_3:expr, $limb_2:expr, $limb_1:expr, $limb_0:expr] => {
+        [
+            $limb_0, $limb_1, $limb_2, $limb_3, $limb_4, $limb_5, $limb_6, $limb_7, $limb_8,
+            $limb_9, $limb_a, $limb_b, $limb_c, $limb_d, $limb_e, $limb_f, $limb_10,
+        ]
+    };
+}
+
+#[cfg(all(target_pointer_width = "64", target_endian = "little"))]
+macro_rules! p521_limbs {
+    [$limb_10:expr, $limb_f:expr, $limb_e:expr, $limb_d:expr, $limb_c:expr,
+     $limb_b:expr, $limb_a:expr, $limb_9:expr, $limb_8:expr,
+     $limb_7:expr, $limb_6:expr, $limb_5:expr, $limb_4:expr,
+     $limb_3:expr, $limb_2:expr, $limb_1:expr, $limb_0:expr] => {
+        [
+            (($limb_1 | 0u64) << 32) | $limb_0,
+            (($limb_3 | 0u64) << 32) | $limb_2,
+            (($limb_5 | 0u64) << 32) | $limb_4,
+            (($limb_7 | 0u64) << 32) | $limb_6,
+            (($limb_9 | 0u64) << 32) | $limb_8,
+            (($limb_b | 0u64) << 32) | $limb_a,
+            (($limb_d | 0u64) << 32) | $limb_c,
+            (($limb_f | 0u64) << 32) | $limb_e,
+            $limb_10,
+        ]
+    };
+}
+
+pub static COMMON_OPS: CommonOps = CommonOps {
+    num_limbs: (521 + (LIMB_BITS - 1)) / LIMB_BITS,
+    order_bits: 521,
+
+    q: Modulus {
+        p: p521_limbs![
+            0x000001ff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff,
+            0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff,
+            0xffffffff, 0xffffffff, 0xffffffff
+        ],
+        rr: p521_limbs![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+    },
+
+    n: Elem {
+        limbs: p521_limbs![
+            0x000001ff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff,
+            0xffffffff, 0xfffffffa, 0x51868783, 0xbf2f966b, 0x7fcc0148, 0xf709a5d0, 0x3bb5c9b8,
+            0x899c47ae, 0xbb6fb71e, 0x91386409
+        ],
+        m: PhantomData,
+        encoding: PhantomData, // Unencoded
+    },
+
+    a: Elem {
+        limbs: p521_limbs![
+            0x000001ff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff,
+            0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff,
+            0xffffffff, 0xffffffff, 0xfffffffc
+        ],
+        m: PhantomData,
+        encoding: PhantomData, // R == 1
+    },
+    b: Elem {
+        limbs: p521_limbs![
+            0x00000051, 0x953eb961, 0x8e1c9a1f, 0x929a21a0, 0xb68540ee, 0xa2da725b, 0x99b315f3,
+            0xb8b48991, 0x8ef109e1, 0x56193951, 0xec7e937b, 0x1652c0bd, 0x3bb1bf07, 0x3573df88,
+            0x3d2c34f1, 0xef451fd4, 0x6b503f00
+        ],
+        m: PhantomData,
+        encoding: PhantomData, // R == 1
+    },
+
+    elem_add_impl: GFp_p521_elem_add,
+    elem_mul_mont: GFp_p521_elem_mul_mont,
+    elem_sqr_mont: GFp_p521_elem_sqr_mont,
+
+    point_add_jacobian_impl: GFp_nistz521_point_add,
+};
+
+pub static PRIVATE_KEY_OPS: PrivateKeyOps = PrivateKeyOps {
+    common: &COMMON_OPS,
+    elem_inv_squared: p521_elem_inv_squared,
+    point_mul_base_impl: p521_point_mul_base_impl,
+    point_mul_impl: GFp_nistz521_point_mul,
+};
+
+fn p521_elem_inv_squared(a: &Elem<R>) -> Elem<R> {
+    // Calculate a**-2 (mod q) == a**(q - 3) (mod q)
+    //
+    // The exponent (q - 3) is 2**521 - 4, i.e. 519 one bits followed by two
+    // zero bits.
+
+    #[inline]
+    fn sqr_mul(a: &Elem<R>, squarings: usize, b: &Elem<R>) -> Elem<R> {
+        elem_sqr_mul(&COMMON_OPS, a, squarings, b)
+    }
+
+    #[inline]
+    fn sqr_mul_acc(a: &mut Elem<R>, squarings: usize, b: &Elem<R>) {
+        elem_sqr_mul_acc(&COMMON_OPS, a, squarings, b)
+    }
+
+    let b_1 = &a;
+    let b_11 = sqr_mul(b_1, 1, b_1);
+    let b_111 = sqr_mul(&b_11, 1, b_1);
+    let f = sqr_mul(&b_11, 2, &b_11);
+    let ff = sqr_mul(&f, 4, &f);
+    let ffff = sqr_mul(&ff, 8, &ff);
+    let ffffffff = sqr_mul(&ffff, 16, &ffff);
+    let ffffffffffffffff = sqr_mul(&ffffffff, 32, &ffffffff);
+
+    // 128 one bits.
+    let ones_128 = sqr_mul(&ffffffffffffffff, 64, &ffffffffffffffff);
+
+    // 256 one bits.
+    let ones_256 = sqr_mul(&ones_128, 128, &ones_128);
+
+    // 512 one bits.
+    let mut acc = sqr_mul(&ones_256, 256, &ones_256);
+
+    // 519 one bits.
+    sqr_mul_acc(&mut acc, 4, &f);
+    sqr_mul_acc(&mut acc, 3, &b_111);
+
+    // 519 one bits followed by two zero bits.
+    COMMON_OPS.elem_square(&mut acc);
+    COMMON_OPS.elem_square(&mut acc);
+
+    acc
+}
+
+fn p521_point_mul_base_impl(a: &Scalar) -> Point {
+    // XXX: Not efficient. TODO: Precompute multiples of the generator.
+    static P521_GENERATOR: (Elem<R>, Elem<R>) = (
+        Elem {
+            limbs: p521_limbs![
+                0x000000c6, 0x858e06b7, 0x0404e9cd, 0x9e3ecb66, 0x2395b442, 0x9c648139, 0x053fb521,
+                0xf828af60, 0x6b4d3dba, 0xa14b5e77, 0xefe75928, 0xfe1dc127, 0xa2ffa8de, 0x3348b3c1,
+                0x856a429b, 0xf97e7e31, 0xc2e5bd66
+            ],
+            m: PhantomData,
+            encoding: PhantomData,
+        },
+        Elem {
+            limbs: p521_limbs![
+                0x00000118, 0x39296a78, 0x9a3bc004, 0x5c8a5fb4, 0x2c7d1bd9, 0x98f54449, 0x579b4468,
+                0x17afbd17, 0x273e662c, 0x97ee7299, 0x5ef42640, 0xc550b901, 0x3fad0761, 0x353c7086,
+                0xa272c240, 0x88be9476, 0x9fd16650
+            ],
+            m: PhantomData,
+            encoding: PhantomData,
+        },
+    );
+
+    PRIVATE_KEY_OPS.point_mul(a, &P521_GENERATOR)
+}
+
+pub static PUBLIC_KEY_OPS: PublicKeyOps = PublicKeyOps {
+    common: &COMMON_OPS,
+};
+
+pub static SCALAR_OPS: ScalarOps = ScalarOps {
+    common: &COMMON_OPS,
+    scalar_inv_to_mont_impl: p521_scalar_inv_to_mont,
+    scalar_mul_mont: GFp_p521_scalar_mul_mont,
+};
+
+pub static PUBLIC_SCALAR_OPS: PublicScalarOps = PublicScalarOps {
+    scalar_ops: &SCALAR_OPS,
+    public_key_ops: &PUBLIC_KEY_OPS,
+    private_key_ops: &PRIVATE_KEY_OPS,
+
+    q_minus_n: Elem {
+        limbs: p521_limbs![
+            0, 0, 0, 0, 0, 0, 0, 0, 0x00000005, 0xae79787c, 0x40d06994, 0x8033feb7, 0x08f65a2f,
+            0xc44a3647, 0x7663b851, 0x449048e1, 0x6ec79bf6
+        ],
+
+        m: PhantomData,
+        encoding: PhantomData, // Unencoded
+    },
+};
+
+pub static PRIVATE_SCALAR_OPS: PrivateScalarOps = PrivateScalarOps {
+    scalar_ops: &SCALAR_OPS,
+
+    oneRR_mod_n: Scalar {
+        limbs: N_RR_LIMBS,
+        m: PhantomData,
+        encoding: PhantomData, // R
+    },
+};
+
+fn p521_scalar_inv_to_mont(a: &Scalar<Unencoded>) -> Scalar<R> {
+    // Calculate the modular inverse of scalar |a| using Fermat's Little
+    // Theorem:
+    //
+    //   a**-1 (mod n) == a**(n - 2) (mod n)
+    //
+    // The exponent (n - 2) is:
+    //
+    //     0x1ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff\
+    //       fa51868783bf2f966b7fcc0148f709a5d03bb5c9b8899c47aebb6fb71e91386407.
+
+    fn mul(a: &Scalar<R>, b: &Scalar<R>) -> Scalar<R> {
+        binary_op(GFp_p521_scalar_mul_mont, a, b)
+    }
+
+    fn sqr(a: &Scalar<R>) -> Scalar<R> {
+        binary_op(GFp_p521_scalar_mul_mont, a, a)
+    }
+
+    fn sqr_mut(a: &mut Scalar<R>) {
+        unary_op_from_binary_op_assign(GFp_p521_scalar_mul_mont, a);
+    }
+
+    // Returns (`a` squared `squarings` times) * `b`.
+    fn sqr_mul(a: &Scalar<R>, squarings: usize, b: &Scalar<R>) -> Scalar<R> {
+        debug_assert!(squarings >= 1);
+        let mut tmp = sqr(a);
+        for _ in 1..squarings {
+            sqr_mut(&mut tmp);
+        }
+        mul(&tmp, b)
+    }
+
+    // Sets `acc` = (`acc` squared `squarings` times) * `b`.
+    fn sqr_mul_acc(acc: &mut Scalar<R>, squarings: usize, b: &Scalar<R>) {
+        debug_assert!(squarings >= 1);
+        for _ in 0..squarings {
+            sqr_mut(acc);
+        }
+        binary_op_assign(GFp_p521_scalar_mul_mont, acc, b)
+    }
+
+    fn to_mont(a: &Scalar<Unencoded>) -> Scalar<R> {
+        static N_RR: Scalar<Unencoded> = Scalar {
+            limbs: N_RR_LIMBS,
+            m: PhantomData,
+            encoding: PhantomData,
+        };
+        binary_op(GFp_p521_scalar_mul_mont, a, &N_RR)
+    }
+
+    // Indexes into `d`.
+    const B_1: usize = 0;
+    const B_11: usize = 1;
+    const B_101: usize = 2;
+    const B_111: usize = 3;
+    const B_1001: usize = 4;
+    const B_1011: usize = 5;
+    const B_1101: usize = 6;
+    const B_1111: usize = 7;
+    const DIGIT_COUNT: usize = 8;
+
+    let mut d = [Scalar::zero(); DIGIT_COUNT];
+    d[B_1] = to_mont(a);
+    let b_10 = sqr(&d[B_1]);
+    for i in B_11..DIGIT_COUNT {
+        d[i] = mul(&d[i - 1], &b_10);
+    }
+
+    let ff = sqr_mul(&d[B_1111], 0 + 4, &d[B_1111]);
+    let ffff = sqr_mul(&ff, 0 + 8, &ff);
+    let ffffffff = sqr_mul(&ffff, 0 + 16, &ffff);
+
+    let ffffffffffffffff = sqr_mul(&ffffffff, 0 + 32, &ffffffff);
+
+    // 128 one bits.
+    let ones_128 = sqr_mul(&ffffffffffffffff, 0 + 64, &ffffffffffffffff);
+
+    // 256 one bits.
+    let mut acc = sqr_mul(&ones_128, 0 + 128, &ones_128);
+
+    // 262 one bits, i.e. 0x1ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff
+    // followed by 0xfffffff_11.
+    sqr_mul_acc(&mut acc, 0 + 4, &d[B_1111]);
+    sqr_mul_acc(&mut acc, 0 + 2, &d[B_11]);
+
+    // The rest of the exponent, in binary, is:
+    //
+    //    0100101000110000110100001111000001110111111001011111001011001101
+    //    0110111111111001100000000010100100011110111000010011010010111010
+    //    0000011101110110101110010011011100010001001100111000100011110101
+    //    1101011101101101111101101110001111010010001001110000110010000000
+    //    111
+
+    static REMAINING_WINDOWS: [(u8, u8); 52] = [
+        (1 + 4, B_1001 as u8),
+        (1 + 1, B_1 as u8),
+        (3 + 2, B_11 as u8),
+        (4 + 4, B_1101 as u8),
+        (4 + 4, B_1111 as u8),
+        (5 + 3, B_111 as u8),
+        (1 + 4, B_1111 as u8),
+        (2, B_11 as u8),
+        (2 + 4, B_1011 as u8),
+        (3, B_111 as u8),
+        (2 + 4, B_1011 as u8),
+        (2 + 4, B_1101 as u8),
+        (1 + 4, B_1101 as u8),
+        (4, B_1111 as u8),
+        (4, B_1111 as u8),
+        (2 + 2, B_11 as u8),
+        (9 + 3, B_101 as u8),
+        (2 + 1, B_1 as u8),
+        (3 + 4, B_1111 as u8),
+        (1 + 3, B_111 as u8),
+        (4 + 4, B_1001 as u8),
+        (3, B_101 as u8),
+        (2 + 4, B_1011 as u8),
+        (3, B_101 as u8),
+        (6 + 3, B_111 as u8),
+        (1 + 3, B_111 as u8),
+        (1 + 4, B_1101 as u8),
+        (1 + 3, B_111 as u8),
+        (2 + 4, B_1001 as u8),
+        (4, B_1011 as u8),
+        (1, B_1 as u8),
+        (3 + 1, B_1 as u8),
+        (3 + 4, B_1001 as u8),
+        (4, B_1001 as u8),
+        (2, B_11 as u8),
+        (3 + 1, B_1 as u8),
+        (3 + 4, B_1111 as u8),
+        (1 + 4, B_1011 as u8),
+        (3, B_101 as u8),
+        (1 + 3, B_111 as u8),
+        (1 + 4, B_1101 as u8),
+        (4, B_1011 as u8),
+        (3, B_111 as u8),
+        (1 + 4, B_1101 as u8),
+        (2, B_11 as u8),
+        (3 + 4, B_1111 as u8),
+        (1 + 4, B_1001 as u8),
+        (3 + 4, B_1001 as u8),
+        (2, B_11 as u8),
+        (4 + 2, B_11 as u8),
+        (2 + 1, B_1 as u8),
+        (7 + 3, B_111 as u8),
+    ];
+
+    for &(squarings, digit) in &REMAINING_WINDOWS[..] {
+        sqr_mul_acc(&mut acc, usize::from(squarings), &d[usize::from(digit)]);
+    }
+
+    acc
+}
+
+unsafe extern "C" fn GFp_p521_elem_sqr_mont(
+    r: *mut Limb,   // [COMMON_OPS.num_limbs]
+    a: *const Limb, // [COMMON_OPS.num_limbs]
+) {
+    // XXX: Inefficient. TODO: Make a dedicated squaring routine.
+    GFp_p521_elem_mul_mont(r, a, a);
+}
+
+// R**2 (mod n), where R = 2**(COMMON_OPS.num_limbs * LIMB_BITS) differs between
+// 32-bit and 64-bit targets.
+#[cfg(target_pointer_width = "32")]
+const N_RR_LIMBS: [Limb; MAX_LIMBS] = p521_limbs![
+    0x0000019a, 0x5b5a3afe, 0x8c44383d, 0x2d8e03d1, 0x492d0d45, 0x5bcc6d61, 0xa8e567bc, 0xcff3d142,
+    0xb7756e3a, 0x4fb35b72, 0xd3402705, 0x5d4dd6d3, 0x0791d9dc, 0x18354a56, 0x4374a642, 0x1163115a,
+    0x61c64ca7
+];
+
+#[cfg(target_pointer_width = "64")]
+const N_RR_LIMBS: [Limb; MAX_LIMBS] = p521_limbs![
+    0x0000003d, 0x2d8e03d1, 0x492d0d45, 0x5bcc6d61, 0xa8e567bc, 0xcff3d142, 0xb7756e3e, 0xdd6e23d8,
+    0x2e49c7db, 0xd3721ef5, 0x57f75e06, 0x12a78d38, 0x794573ff, 0xf707badc, 0xe5547ea3, 0x137cd04d,
+    0xcf15dd04
+];
+
+extern "C" {
+    fn GFp_p521_elem_add(
+        r: *mut Limb,   // [COMMON_OPS.num_limbs]
+        a: *const Limb, // [COMMON_OPS.num_limbs]
+        b: *const Limb, // [COMMON_OPS.num_limbs]
+    );
+    fn GFp_p521_elem_mul_mont(
+        r: *mut Limb,   // [COMMON_OPS.num_limbs]
+        a: *const Limb, // [COMMON_OPS.num_limbs]
+        b: *const Limb, // [COMMON_OPS.num_limbs]
+    );
+
+    fn GFp_nistz521_point_add(
+        r: *mut Limb,   // [3][COMMON_OPS.num_limbs]
+        a: *const Limb, // [3][COMMON_OPS.num_limbs]
+        b: *const Limb, // [3][COMMON_OPS.num_limbs]
+    );
+    fn GFp_nistz521_point_mul(
+        r: *mut Limb,          // [3][COMMON_OPS.num_limbs]
+        p_scalar: *const Limb, // [COMMON_OPS.num_limbs]
+        p_x: *const Limb,      // [COMMON_OPS.num_limbs]
+        p_y: *const Limb,      // [COMMON_OPS.num_limbs]
+    );
+
+    fn GFp_p521_scalar_mul_mont(
+        r: *mut Limb,   // [COMMON_OPS.num_limbs]
+        a: *const Limb, // [COMMON_OPS.num_limbs]
+        b: *const Limb, // [COMMON_OPS.num_limbs]
+    );
+}
+
+#[cfg(feature = "internal_benches")]
+mod internal_benches {
+    use super::{super::internal_benches::*, *};
+
+    bench_curve!(&[
+        Scalar {
+            limbs: LIMBS_1,
+            encoding: PhantomData,
+            m: PhantomData
+        },
+        Scalar {
+            // n - 1
+            limbs: p521_limbs![
+                0x000001ff,
+                0xffffffff,
+                0xffffffff,
+                0xffffffff,
+                0xffffffff,
+                0xffffffff,
+                0xffffffff,
+                0xffffffff,
+                0xfffffffa,
+                0x51868783,
+                0xbf2f966b,
+                0x7fcc0148,
+                0xf709a5d0,
+                0x3bb5c9b8,
+                0x899c47ae,
+                0xbb6fb71e,
+                0x91386409 - 1
+            ],
+            encoding: PhantomData,
+            m: PhantomData,
+        },
+    ]);
+}
@@ -16,12 +16,7 @@
 //! ECDSA signing).
 
 use super::{ops::*, verify_affine_point_is_on_the_curve};
-use crate::{
-    arithmetic::montgomery::R,
-    ec, error,
-    limb::{self, LIMB_BYTES},
-    rand,
-};
+use crate::{arithmetic::montgomery::R, ec, error, limb, rand};
 use untrusted;
 
 /// Generates a random scalar in the range [1, n).
@@ -29,9 +24,8 @@ pub fn random_scalar(
     ops: &PrivateKeyOps,
     rng: &dyn rand::SecureRandom,
 ) -> Result<Scalar, error::Unspecified> {
-    let num_limbs = ops.common.num_limbs;
     let mut bytes = [0; ec::SCALAR_MAX_BYTES];
-    let bytes = &mut bytes[..(num_limbs * LIMB_BYTES)];
+    let bytes = &mut bytes[..ops.common.len()];
     generate_private_scalar_bytes(ops, rng, bytes)?;
     scalar_from_big_endian_bytes(ops, bytes)
 }
@@ -72,6 +66,11 @@ pub fn generate_private_scalar_bytes(
         // requested security strength is delegated to `rng`.
         rng.fill(candidate)?;
 
+        // For P-521, `n` is 521 bits long but the candidate is 528 bits long.
+        // Clear the excess high-order bits so that the candidate is in range
+        // with about the same probability as for the other curves.
+        candidate[0] &= 0xff >> ((8 * candidate.len()) - ops.common.order_bits);
+
         // NSA Guide Steps 5, 6, and 7.
         if check_scalar_big_endian_bytes(ops, candidate).is_err() {
             continue;
@@ -100,7 +99,7 @@ pub fn check_scalar_big_endian_bytes(
     ops: &PrivateKeyOps,
     bytes: &[u8],
 ) -> Result<(), error::Unspecified> {
-    debug_assert_eq!(bytes.len(), ops.common.num_limbs * LIMB_BYTES);
+    debug_assert_eq!(bytes.len(), ops.common.len());
     scalar_from_big_endian_bytes(ops, bytes).map(|_| ())
 }
 
@@ -136,7 +135,7 @@ pub fn public_from_private(
     public_out: &mut [u8],
     my_private_key: &ec::Seed,
 ) -> Result<(), error::Unspecified> {
-    let elem_and_scalar_bytes = ops.common.num_limbs * LIMB_BYTES;
+    let elem_and_scalar_bytes = ops.common.len();
     debug_assert_eq!(public_out.len(), 1 + (2 * elem_and_scalar_bytes));
     let my_private_key = private_key_as_scalar(ops, my_private_key);
     let my_public_key = ops.point_mul_base(&my_private_key);
@@ -65,10 +65,68 @@ pub fn parse_uncompressed_point(
     Ok((x, y))
 }
 
+/// Parses a public key encoded in either uncompressed or compressed form
+/// using the Octet-String-to-Elliptic-Curve-Point algorithm in [SEC 1]
+/// Section 2.3.4. The key is validated the same way as in
+/// `parse_uncompressed_point`.
+///
+/// [SEC 1]: http://www.secg.org/sec1-v2.pdf
+pub fn parse_point(
+    ops: &PublicKeyOps,
+    input: untrusted::Input,
+) -> Result<(Elem<R>, Elem<R>), error::Unspecified> {
+    match input.as_slice_less_safe().first() {
+        Some(4) => parse_uncompressed_point(ops, input),
+        Some(2) | Some(3) => parse_compressed_point(ops, input),
+        _ => Err(error::Unspecified),
+    }
+}
+
+/// Parses a public key encoded in compressed form, recovering `y` from `x`
+/// and the parity of `y` given by the encoding byte.
+pub fn parse_compressed_point(
+    ops: &PublicKeyOps,
+    input: untrusted::Input,
+) -> Result<(Elem<R>, Elem<R>), error::Unspecified> {
+    let (y_is_odd, x) = input.read_all(error::Unspecified, |input| {
+        // The encoding must be 2 or 3, which are the encodings for
+        // "compressed" with an even or odd `y`, respectively.
+        let y_is_odd = match input.read_byte()? {
+            2 => false,
+            3 => true,
+            _ => {
+                return Err(error::Unspecified);
+            }
+        };
+
+        // NIST SP 800-56A Step 2 for `x`; `y` is computed in [0, p-1].
+        let x = ops.elem_parse(input)?;
+        Ok((y_is_odd, x))
+    })?;
+
+    let common = ops.common;
+
+    // NIST SP 800-56A Step 3: `elem_sqrt` fails unless there is a `y` such
+    // that y**2 = x**3 + ax + b.
+    let mut rhs = common.elem_squared(&x);
+    common.elem_add(&mut rhs, &common.a);
+    common.elem_mul(&mut rhs, &x);
+    common.elem_add(&mut rhs, &common.b);
+    let mut y = common.elem_sqrt(&rhs)?;
+
+    // `y` is never zero since there are no points of order two on
+    // prime-order curves, so exactly one of `y` and `p - y` is odd.
+    if (common.elem_unencoded(&y).limbs[0] & 1 == 1) != y_is_odd {
+        y = common.elem_negated(&y);
+    }
+
+    Ok((x, y))
+}
+
 #[cfg(test)]
 mod tests {
     use super::{super::ops, *};
-    use crate::test;
+    use crate::{ec::ELEM_MAX_BYTES, limb::*, test};
     use untrusted;
 
     #[test]
@@ -97,11 +155,88 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_point_test() {
+        test::run(
+            test_file!("suite_b_public_key_tests.txt"),
+            |section, test_case| {
+                assert_eq!(section, "");
+
+                let curve_name = test_case.consume_string("Curve");
+                let public_key = test_case.consume_bytes("Q");
+                let is_valid = test_case.consume_string("Result") == "P";
+                if !is_valid {
+                    return Ok(());
+                }
+
+                let ops = public_key_ops_from_curve_name(&curve_name);
+                let common = ops.common;
+                let (x, y) = parse_point(ops, untrusted::Input::from(&public_key)).unwrap();
+
+                // The compressed form decodes to the same point, and
+                // flipping the parity bit decodes to its negation.
+                let elem_len = common.len();
+                let mut compressed = [0u8; 1 + ELEM_MAX_BYTES];
+                let compressed = &mut compressed[..(1 + elem_len)];
+                compressed[0] = 2 | (public_key[2 * elem_len] & 1);
+                compressed[1..].copy_from_slice(&public_key[1..(1 + elem_len)]);
+                let (cx, cy) = parse_point(ops, untrusted::Input::from(compressed)).unwrap();
+                assert_eq!(common.elems_are_equal(&x, &cx), LimbMask::True);
+                assert_eq!(common.elems_are_equal(&y, &cy), LimbMask::True);
+
+                compressed[0] ^= 1;
+                let (nx, ny) = parse_point(ops, untrusted::Input::from(compressed)).unwrap();
+                assert_eq!(common.elems_are_equal(&x, &nx), LimbMask::True);
+                assert_eq!(
+                    common.elems_are_equal(&common.elem_negated(&y), &ny),
+                    LimbMask::True
+                );
+
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn parse_compressed_point_invalid_test() {
+        // For each curve, the smallest `x` for which x**3 + ax + b isn't a
+        // square.
+        for &(curve_name, x_without_y) in &[("K-256", 0), ("P-256", 1), ("P-384", 1), ("P-521", 3)]
+        {
+            let ops = public_key_ops_from_curve_name(curve_name);
+            let elem_len = ops.common.len();
+            let mut encoded = [0u8; 1 + ELEM_MAX_BYTES];
+            let encoded = &mut encoded[..(1 + elem_len)];
+
+            encoded[0] = 2;
+            encoded[elem_len] = x_without_y;
+            assert!(parse_point(ops, untrusted::Input::from(encoded)).is_err());
+
+            // x == 1, with an invalid encoding byte and with a missing byte.
+            encoded[elem_len] = 1;
+            encoded[0] = 5;
+            assert!(parse_point(ops, untrusted::Input::from(encoded)).is_err());
+            encoded[0] = 3;
+            let truncated = &encoded[..elem_len];
+            assert!(parse_point(ops, untrusted::Input::from(truncated)).is_err());
+
+            // x > p.
+            for b in encoded[1..].iter_mut() {
+                *b = 0xff;
+            }
+            assert!(parse_point(ops, untrusted::Input::from(encoded)).is_err());
+        }
+    }
+
     fn public_key_ops_from_curve_name(curve_name: &str) -> &'static PublicKeyOps {
-        if curve_name == "P-256" {
+        if curve_name == "K-256" {
+            &ops::k256::PUBLIC_KEY_OPS
+        } else if curve_name == "P-256" {
             &ops::p256::PUBLIC_KEY_OPS
         } else if curve_name == "P-384" {
             &ops::p384::PUBLIC_KEY_OPS
+        } else if curve_name == "P-521" {
+            &ops::p521::PUBLIC_KEY_OPS
         } else {
             panic!("Unsupported curve: {}", curve_name);
         }
@@ -0,0 +1,113 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Adding a scalar "tweak" to a private key, or the corresponding point to a
+//! public key, as done in BIP-32-style hierarchical key derivation.
+
+use super::{ops::*, private_key::*, public_key::*};
+use crate::{
+    ec, error,
+    limb::{self, AllowZero},
+};
+use untrusted;
+
+/// Sets `out` to the big-endian encoding of `private_key + tweak (mod n)`.
+///
+/// Fails if `tweak` isn't in the range [0, n) or if the result is zero.
+/// `private_key` must be a valid private key for `curve`. This is constant
+/// time with respect to `private_key` and, when it is in range, `tweak`.
+pub(crate) fn private_key_add(
+    curve: &'static ec::Curve,
+    private_key: &[u8],
+    tweak: &[u8],
+    out: &mut [u8],
+) -> Result<(), error::Unspecified> {
+    let ops = private_key_ops(curve);
+    let num_limbs = ops.common.num_limbs;
+    debug_assert_eq!(out.len(), ops.common.len());
+
+    let private_key = scalar_from_big_endian_bytes(ops, private_key)?;
+    let tweak =
+        scalar_parse_big_endian_fixed(ops.common, AllowZero::Yes, untrusted::Input::from(tweak))?;
+    let sum = scalar_sum(ops.common, &private_key, &tweak);
+    if ops.common.is_zero(&sum) {
+        return Err(error::Unspecified);
+    }
+    limb::big_endian_from_limbs(&sum.limbs[..num_limbs], out);
+    Ok(())
+}
+
+/// Sets `out` to the uncompressed encoding of `public_key + tweak*G`.
+///
+/// Fails if `public_key` isn't a valid uncompressed public key for `curve`,
+/// if `tweak` isn't in the range [0, n), or if the result is the point at
+/// infinity. This is not constant time.
+pub(crate) fn public_key_add(
+    curve: &'static ec::Curve,
+    public_key: &[u8],
+    tweak: &[u8],
+    out: &mut [u8],
+) -> Result<(), error::Unspecified> {
+    let ops = private_key_ops(curve);
+    let public_key_ops = match curve.id {
+        ec::CurveID::K256 => &k256::PUBLIC_KEY_OPS,
+        ec::CurveID::P256 => &p256::PUBLIC_KEY_OPS,
+        ec::CurveID::P384 => &p384::PUBLIC_KEY_OPS,
+        ec::CurveID::P521 => &p521::PUBLIC_KEY_OPS,
+        ec::CurveID::Curve25519 | ec::CurveID::Curve448 => unreachable!(),
+    };
+    debug_assert_eq!(out.len(), curve.public_key_len);
+
+    let point = parse_uncompressed_point(public_key_ops, untrusted::Input::from(public_key))?;
+    let tweak =
+        scalar_parse_big_endian_fixed(ops.common, AllowZero::Yes, untrusted::Input::from(tweak))?;
+
+    // Convert the affine point to Jacobian coordinates by multiplying it by
+    // one.
+    let one =
+        scalar_parse_big_endian_variable(ops.common, AllowZero::No, untrusted::Input::from(&[1]))?;
+    let point = ops.point_mul(&one, &point);
+    let sum = if ops.common.is_zero(&tweak) {
+        point
+    } else {
+        ops.common.point_sum(&ops.point_mul_base(&tweak), &point)
+    };
+    ops.common
+        .elem_verify_is_not_zero(&ops.common.point_z(&sum))?;
+
+    out[0] = 4; // Uncompressed encoding.
+    let (x_out, y_out) = out[1..].split_at_mut(ops.common.len());
+    big_endian_affine_from_jacobian(ops, Some(x_out), Some(y_out), &sum)
+}
+
+fn private_key_ops(curve: &'static ec::Curve) -> &'static PrivateKeyOps {
+    match curve.id {
+        ec::CurveID::K256 => &k256::PRIVATE_KEY_OPS,
+        ec::CurveID::P256 => &p256::PRIVATE_KEY_OPS,
+        ec::CurveID::P384 => &p384::PRIVATE_KEY_OPS,
+        ec::CurveID::P521 => &p521::PRIVATE_KEY_OPS,
+        ec::CurveID::Curve25519 | ec::CurveID::Curve448 => unreachable!(),
+    }
+}
+
+fn scalar_parse_big_endian_fixed(
+    ops: &CommonOps,
+    allow_zero: AllowZero,
+    bytes: untrusted::Input,
+) -> Result<Scalar, error::Unspecified> {
+    if bytes.len() != ops.len() {
+        return Err(error::Unspecified);
+    }
+    scalar_parse_big_endian_variable(ops, allow_zero, bytes)
+}
@@ -0,0 +1,227 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Envelope encryption of a message for several recipients.
+//!
+//! `seal` generates a random data-encryption key (DEK), encrypts the
+//! message once with AES-256-GCM-SIV under the DEK, and wraps the DEK for
+//! each recipient's X25519 public key with HPKE (see the `hpke` module).
+//! `open` unwraps the DEK with one recipient's `hpke::KeyPair` and decrypts
+//! the message.
+//!
+//! A version 1 envelope has the following format, with integers in
+//! big-endian byte order:
+//!
+//! | Offset | Length | Field
+//! |--------|--------|------------------------------------------------------
+//! | 0      | 4      | `RENV`
+//! | 4      | 1      | Version: 1
+//! | 5      | 2      | Number of recipients, at least 1
+//! | 7      |        | The recipients
+//! |        |        | Encrypted message followed by the tag
+//!
+//! Each recipient is:
+//!
+//! | Offset | Length | Field
+//! |--------|--------|------------------------------------------------------
+//! | 0      | 1      | Key wrapping algorithm: 1 = HPKE
+//! | 1      | 8      | Key ID: the first 8 bytes of the SHA-256 digest of the recipient's public key
+//! | 9      | 2      | Length of the wrapped key
+//! | 11     |        | Wrapped key
+//!
+//! For HPKE, the wrapped key is the 32-byte encapsulated key followed by the
+//! DEK sealed with DHKEM(X25519, HKDF-SHA256), HKDF-SHA256, and AES-256-GCM
+//! in base mode, with `info` "ring envelope v1" and the first 5 bytes of the
+//! envelope as the AAD.
+//!
+//! The message is sealed with an all-zero nonce, which is safe because each
+//! DEK is used only once. Its AAD is everything that precedes it in the
+//! envelope followed by the caller's AAD, so the list of recipients can't
+//! be changed without detection by anybody who holds the DEK.
+
+use crate::{aead, digest, error, hpke, rand, zeroize::Zeroize};
+use std::vec::Vec;
+use untrusted;
+
+/// Encrypts `plaintext` for the holders of the private keys for the X25519
+/// public keys `recipients`.
+///
+/// `aad` is authenticated but not encrypted, and must be passed to `open()`
+/// too. Fails if there are no recipients, more than 65535 recipients, or if
+/// any of the public keys is invalid.
+pub fn seal(
+    recipients: &[&[u8]],
+    aad: &[u8],
+    plaintext: &[u8],
+    rng: &dyn rand::SecureRandom,
+) -> Result<Vec<u8>, error::Unspecified> {
+    if recipients.is_empty() || recipients.len() > usize::from(u16::max_value()) {
+        return Err(error::Unspecified);
+    }
+
+    let mut dek = [0u8; DEK_LEN];
+    rng.fill(&mut dek)?;
+
+    let mut envelope = Vec::with_capacity(
+        HEADER_LEN
+            + recipients.len() * (RECIPIENT_HEADER_LEN + HPKE_WRAPPED_KEY_LEN)
+            + plaintext.len()
+            + TAG_LEN,
+    );
+    envelope.extend_from_slice(MAGIC);
+    envelope.push(VERSION);
+    envelope.extend_from_slice(&(recipients.len() as u16).to_be_bytes());
+
+    for recipient in recipients {
+        let (enc, mut sender) = hpke::Sender::setup_base(HPKE_SUITE, recipient, HPKE_INFO, rng)?;
+        let mut wrapped_dek = [0u8; DEK_LEN + TAG_LEN];
+        wrapped_dek[..DEK_LEN].copy_from_slice(&dek);
+        let _ = sender.seal_in_place(
+            aead::Aad::from(&envelope[..WRAPPING_AAD_LEN]),
+            &mut wrapped_dek,
+            TAG_LEN,
+        )?;
+
+        envelope.push(WRAPPING_HPKE);
+        envelope.extend_from_slice(&key_id(recipient));
+        envelope.extend_from_slice(&(HPKE_WRAPPED_KEY_LEN as u16).to_be_bytes());
+        envelope.extend_from_slice(enc.as_ref());
+        envelope.extend_from_slice(&wrapped_dek);
+    }
+
+    let aad = [&envelope[..], aad].concat();
+    let message_start = envelope.len();
+    envelope.extend_from_slice(plaintext);
+    envelope.extend_from_slice(&[0; TAG_LEN]);
+
+    let key = aead::SealingKey::new(&aead::AES_256_GCM_SIV, &dek);
+    dek[..].zeroize();
+    let _ = aead::seal_in_place(
+        &key?,
+        aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]),
+        aead::Aad::from(aad),
+        &mut envelope[message_start..],
+        TAG_LEN,
+    )?;
+    Ok(envelope)
+}
+
+/// Authenticates and decrypts `envelope` with the private key of one of its
+/// recipients.
+///
+/// `aad` must be the same as was passed to `seal()`.
+pub fn open(
+    key_pair: &hpke::KeyPair,
+    aad: &[u8],
+    envelope: &[u8],
+) -> Result<Vec<u8>, error::Unspecified> {
+    let key_id = key_id(key_pair.public_key());
+    let (header_len, mut dek) =
+        untrusted::Input::from(envelope).read_all(error::Unspecified, |input| {
+            if input.read_bytes(MAGIC.len())? != MAGIC[..] || input.read_byte()? != VERSION {
+                return Err(error::Unspecified);
+            }
+            let recipients = read_u16(input)?;
+            if recipients == 0 {
+                return Err(error::Unspecified);
+            }
+            let mut dek = None;
+            for _ in 0..recipients {
+                let wrapping = input.read_byte()?;
+                let recipient_key_id = input.read_bytes(KEY_ID_LEN)?;
+                let wrapped_key_len = read_u16(input)?;
+                let wrapped_key = input.read_bytes(usize::from(wrapped_key_len))?;
+                // A key ID can collide, so every matching recipient is tried
+                // until one of them unwraps successfully.
+                if dek.is_none() && wrapping == WRAPPING_HPKE && recipient_key_id == key_id[..] {
+                    dek = unwrap_hpke(
+                        key_pair,
+                        &envelope[..WRAPPING_AAD_LEN],
+                        wrapped_key.as_slice_less_safe(),
+                    )
+                    .ok();
+                }
+            }
+            let header_len = envelope.len() - input.read_bytes_to_end().len();
+            Ok((header_len, dek.ok_or(error::Unspecified)?))
+        })?;
+
+    let (header, ciphertext_and_tag) = envelope.split_at(header_len);
+    let key = aead::OpeningKey::new(&aead::AES_256_GCM_SIV, &dek);
+    dek[..].zeroize();
+    let key = key?;
+    let mut in_out = Vec::from(ciphertext_and_tag);
+    let plaintext_len = aead::open_in_place(
+        &key,
+        aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]),
+        aead::Aad::from([header, aad].concat()),
+        0,
+        &mut in_out,
+    )?
+    .len();
+    in_out.truncate(plaintext_len);
+    Ok(in_out)
+}
+
+fn unwrap_hpke(
+    key_pair: &hpke::KeyPair,
+    aad: &[u8],
+    wrapped_key: &[u8],
+) -> Result<[u8; DEK_LEN], error::Unspecified> {
+    if wrapped_key.len() != HPKE_WRAPPED_KEY_LEN {
+        return Err(error::Unspecified);
+    }
+    let (enc, wrapped_dek) = wrapped_key.split_at(HPKE_ENC_LEN);
+    let mut recipient = hpke::Recipient::setup_base(HPKE_SUITE, enc, key_pair, HPKE_INFO)?;
+    let mut in_out = [0u8; DEK_LEN + TAG_LEN];
+    in_out.copy_from_slice(wrapped_dek);
+    let result = recipient
+        .open_in_place(aead::Aad::from(aad), &mut in_out)
+        .map(|dek| {
+            let mut dek_bytes = [0u8; DEK_LEN];
+            dek_bytes.copy_from_slice(dek);
+            dek_bytes
+        });
+    in_out[..].zeroize();
+    result
+}
+
+fn key_id(public_key: &[u8]) -> [u8; KEY_ID_LEN] {
+    let digest = digest::digest(&digest::SHA256, public_key);
+    let mut key_id = [0u8; KEY_ID_LEN];
+    key_id.copy_from_slice(&digest.as_ref()[..KEY_ID_LEN]);
+    key_id
+}
+
+fn read_u16(input: &mut untrusted::Reader) -> Result<u16, error::Unspecified> {
+    let hi = input.read_byte()?;
+    let lo = input.read_byte()?;
+    Ok(u16::from_be_bytes([hi, lo]))
+}
+
+const MAGIC: &[u8; 4] = b"RENV";
+const VERSION: u8 = 1;
+
+const WRAPPING_HPKE: u8 = 1;
+static HPKE_SUITE: &hpke::Suite = &hpke::DHKEM_X25519_HKDF_SHA256_AES_256_GCM;
+const HPKE_INFO: &[u8] = b"ring envelope v1";
+const HPKE_ENC_LEN: usize = 32;
+const HPKE_WRAPPED_KEY_LEN: usize = HPKE_ENC_LEN + DEK_LEN + TAG_LEN;
+
+const HEADER_LEN: usize = 7;
+const WRAPPING_AAD_LEN: usize = 5;
+const RECIPIENT_HEADER_LEN: usize = 1 + KEY_ID_LEN + 2;
+const KEY_ID_LEN: usize = 8;
+const DEK_LEN: usize = 32;
+const TAG_LEN: usize = 16;
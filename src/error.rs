@@ -142,6 +142,11 @@ impl From<TryFromSliceError> for Unspecified {
 ///  * Wrong algorithm: The key is not valid for the algorithm in which it was
 ///    being used.
 ///
+///  * Wrong passphrase: The key is encrypted and the passphrase is wrong.
+///
+///  * Algorithm not allowed: The algorithm isn't allowed by the current
+///    `policy`.
+///
 ///  * Unexpected errors: Report this as a bug.
 #[derive(Copy, Clone, Debug)]
 pub struct KeyRejected(&'static str);
@@ -169,12 +174,17 @@ impl KeyRejected {
         KeyRejected("PublicKeyIsMissing")
     }
 
-    #[cfg(feature = "use_heap")]
+    #[cfg(feature = "jwk")]
+    pub(crate) fn private_key_is_missing() -> Self {
+        KeyRejected("PrivateKeyIsMissing")
+    }
+
+    #[cfg(feature = "rsa")]
     pub(crate) fn too_small() -> Self {
         KeyRejected("TooSmall")
     }
 
-    #[cfg(feature = "use_heap")]
+    #[cfg(feature = "rsa")]
     pub(crate) fn too_large() -> Self {
         KeyRejected("TooLarge")
     }
@@ -187,7 +197,16 @@ impl KeyRejected {
         KeyRejected("WrongAlgorithm")
     }
 
-    #[cfg(feature = "use_heap")]
+    #[cfg(feature = "rsa")]
+    pub(crate) fn wrong_passphrase() -> Self {
+        KeyRejected("WrongPassphrase")
+    }
+
+    pub(crate) fn algorithm_not_allowed() -> Self {
+        KeyRejected("AlgorithmNotAllowed")
+    }
+
+    #[cfg(feature = "rsa")]
     pub(crate) fn private_modulus_len_not_multiple_of_512_bits() -> Self {
         KeyRejected("PrivateModulusLenNotMultipleOf512Bits")
     }
@@ -219,3 +238,72 @@ impl From<KeyRejected> for Unspecified {
         Unspecified
     }
 }
+
+/// An error processing the input to an operation, such as the ciphertext,
+/// nonce, or additional authenticated data of an AEAD operation.
+///
+/// Like `KeyRejected`, the `Display` implementation and
+/// `<InputRejected as Error>::description()` return a string that helps to
+/// understand why the input was rejected. The set of strings isn't fixed;
+/// new ones may be added and existing ones may be made more specific.
+/// Errors from parsing keys are reported with `KeyRejected` instead.
+///
+/// When an AEAD tag doesn't match, the reason is always
+/// `"AuthenticationFailed"`, whichever part of the input was modified, so
+/// that the error doesn't reveal anything about the key or the plaintext.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InputRejected(&'static str);
+
+impl InputRejected {
+    /// The value returned from <Self as std::error::Error>::description()
+    pub fn description_(&self) -> &'static str {
+        self.0
+    }
+
+    pub(crate) fn authentication_failed() -> Self {
+        InputRejected("AuthenticationFailed")
+    }
+
+    pub(crate) fn too_short() -> Self {
+        InputRejected("TooShort")
+    }
+
+    pub(crate) fn too_long() -> Self {
+        InputRejected("TooLong")
+    }
+
+    pub(crate) fn aad_too_long() -> Self {
+        InputRejected("AadTooLong")
+    }
+
+    pub(crate) fn wrong_nonce_type() -> Self {
+        InputRejected("WrongNonceType")
+    }
+
+    pub(crate) fn invalid_suffix_capacity() -> Self {
+        InputRejected("InvalidSuffixCapacity")
+    }
+}
+
+#[cfg(feature = "use_heap")]
+impl std::error::Error for InputRejected {
+    fn cause(&self) -> Option<&dyn std::error::Error> {
+        None
+    }
+
+    fn description(&self) -> &str {
+        self.description_()
+    }
+}
+
+impl core::fmt::Display for InputRejected {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.description_())
+    }
+}
+
+impl From<InputRejected> for Unspecified {
+    fn from(_: InputRejected) -> Self {
+        Unspecified
+    }
+}
@@ -0,0 +1,702 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Format-preserving encryption with FF1 and FF3-1, as specified in
+//! [NIST SP 800-38G Rev. 1].
+//!
+//! Format-preserving encryption encrypts a string of numerals in some radix
+//! into another string of numerals of the same length and radix, e.g. a
+//! 16-digit card number into another 16-digit number. It is deterministic
+//! and unauthenticated, and when the domain is small an attacker may learn
+//! something from a modest number of ciphertexts, so use a tweak that
+//! varies with the context of each value wherever possible. Prefer FF1;
+//! FF3-1 is provided for interoperability with existing systems.
+//!
+//! `Key` operates on numerals directly. `Alphabet` maps the characters of a
+//! string to and from numerals for the `*_str` functions.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::fpe;
+//!
+//! let alphabet = fpe::Alphabet::new("0123456789")?;
+//! let key = fpe::Key::new(&fpe::FF1_AES_256, &[0x42; 32], alphabet.radix())?;
+//!
+//! let pan = "4111111111111111";
+//! let tweak = b"merchant 1234";
+//!
+//! let token = key.encrypt_str(&alphabet, tweak, pan)?;
+//! assert_eq!(token.len(), pan.len());
+//! assert!(token.chars().all(|c| c.is_ascii_digit()));
+//!
+//! assert_eq!(key.decrypt_str(&alphabet, tweak, &token)?, pan);
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [NIST SP 800-38G Rev. 1]:
+//!     https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-38Gr1-draft.pdf
+
+use crate::{
+    aead::{
+        aes,
+        block::{Block, BLOCK_LEN},
+    },
+    cpu, error, polyfill,
+    zeroize::Zeroize,
+};
+use core::cmp::Ordering;
+use std::{string::String, vec, vec::Vec};
+
+/// A key for format-preserving encryption of strings of numerals in a
+/// fixed radix.
+pub struct Key {
+    aes_key: aes::Key,
+    radix: u32,
+    min_len: usize,
+    max_len: u64,
+    algorithm: &'static Algorithm,
+}
+
+impl core::fmt::Debug for Key {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Key")
+            .field("algorithm", self.algorithm)
+            .field("radix", &self.radix)
+            .finish()
+    }
+}
+
+impl Key {
+    /// Constructs a key for `algorithm` with the given key value, for
+    /// numerals in radix `radix`.
+    ///
+    /// `key_value` must be exactly `algorithm.key_len()` bytes long, and
+    /// `radix` must be in the range [2, 2**16].
+    pub fn new(
+        algorithm: &'static Algorithm,
+        key_value: &[u8],
+        radix: u32,
+    ) -> Result<Self, error::Unspecified> {
+        if !(2..=MAX_RADIX).contains(&radix) {
+            return Err(error::Unspecified);
+        }
+        let cpu_features = cpu::features();
+
+        let aes_key = match algorithm.mode {
+            Mode::FF1 => aes::Key::new(key_value, algorithm.variant, cpu_features)?,
+
+            // FF3-1 uses the key with its bytes reversed (REVB(K)).
+            Mode::FF3_1 => {
+                if key_value.len() != algorithm.key_len {
+                    return Err(error::Unspecified);
+                }
+                let mut reversed = [0u8; MAX_KEY_LEN];
+                let reversed = &mut reversed[..key_value.len()];
+                for (r, k) in reversed.iter_mut().zip(key_value.iter().rev()) {
+                    *r = *k;
+                }
+                let aes_key = aes::Key::new(reversed, algorithm.variant, cpu_features);
+                reversed.zeroize();
+                aes_key?
+            }
+        };
+
+        // SP 800-38G Rev. 1 Section 5.2: radix**minlen >= 1,000,000 and
+        // minlen >= 2.
+        let mut min_len = 2;
+        let mut domain = u64::from(radix) * u64::from(radix);
+        while domain < 1_000_000 {
+            domain *= u64::from(radix);
+            min_len += 1;
+        }
+
+        let max_len = match algorithm.mode {
+            // The length is encoded in four bytes.
+            Mode::FF1 => u64::from(u32::max_value()),
+
+            // 2 * floor(log_radix(2**96)), so each half fits in 96 bits.
+            Mode::FF3_1 => {
+                let mut half = 0;
+                let mut domain = 1u128;
+                while domain * u128::from(radix) <= 1 << 96 {
+                    domain *= u128::from(radix);
+                    half += 1;
+                }
+                2 * half
+            }
+        };
+
+        Ok(Self {
+            aes_key,
+            radix,
+            min_len,
+            max_len,
+            algorithm,
+        })
+    }
+
+    /// The key's algorithm.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+
+    /// The radix of the numerals the key encrypts.
+    #[inline(always)]
+    pub fn radix(&self) -> u32 {
+        self.radix
+    }
+
+    /// Encrypts `numerals` in place, using the given tweak.
+    ///
+    /// Each numeral must be less than `self.radix()`. `numerals.len()` must
+    /// be at least the smallest `n >= 2` with `radix**n >= 1,000,000`; for
+    /// FF3-1 it must also be at most `2 * floor(log_radix(2**96))`, and the
+    /// tweak must be exactly `FF3_1_TWEAK_LEN` bytes long.
+    pub fn encrypt(&self, tweak: &[u8], numerals: &mut [u16]) -> Result<(), error::Unspecified> {
+        self.crypt(tweak, numerals, Direction::Encrypt)
+    }
+
+    /// Decrypts `numerals` in place, using the given tweak.
+    ///
+    /// The requirements on the inputs are the same as for `encrypt`.
+    pub fn decrypt(&self, tweak: &[u8], numerals: &mut [u16]) -> Result<(), error::Unspecified> {
+        self.crypt(tweak, numerals, Direction::Decrypt)
+    }
+
+    /// Encrypts the string `input`, whose characters are in `alphabet`, using
+    /// the given tweak.
+    ///
+    /// `alphabet.radix()` must equal `self.radix()`.
+    pub fn encrypt_str(
+        &self,
+        alphabet: &Alphabet,
+        tweak: &[u8],
+        input: &str,
+    ) -> Result<String, error::Unspecified> {
+        self.crypt_str(alphabet, tweak, input, Direction::Encrypt)
+    }
+
+    /// Decrypts the string `input`, whose characters are in `alphabet`, using
+    /// the given tweak.
+    ///
+    /// `alphabet.radix()` must equal `self.radix()`.
+    pub fn decrypt_str(
+        &self,
+        alphabet: &Alphabet,
+        tweak: &[u8],
+        input: &str,
+    ) -> Result<String, error::Unspecified> {
+        self.crypt_str(alphabet, tweak, input, Direction::Decrypt)
+    }
+
+    fn crypt_str(
+        &self,
+        alphabet: &Alphabet,
+        tweak: &[u8],
+        input: &str,
+        direction: Direction,
+    ) -> Result<String, error::Unspecified> {
+        if alphabet.radix() != self.radix {
+            return Err(error::Unspecified);
+        }
+        let mut numerals = alphabet.to_numerals(input)?;
+        self.crypt(tweak, &mut numerals, direction)?;
+        Ok(alphabet.to_string(&numerals))
+    }
+
+    fn crypt(
+        &self,
+        tweak: &[u8],
+        numerals: &mut [u16],
+        direction: Direction,
+    ) -> Result<(), error::Unspecified> {
+        let n = numerals.len();
+        if n < self.min_len || polyfill::u64_from_usize(n) > self.max_len {
+            return Err(error::Unspecified);
+        }
+        if numerals.iter().any(|&x| u32::from(x) >= self.radix) {
+            return Err(error::Unspecified);
+        }
+        match self.algorithm.mode {
+            Mode::FF1 => self.ff1(tweak, numerals, direction),
+            Mode::FF3_1 => self.ff3_1(tweak, numerals, direction),
+        }
+    }
+
+    // SP 800-38G Rev. 1 Algorithms 7 and 8.
+    fn ff1(
+        &self,
+        tweak: &[u8],
+        x: &mut [u16],
+        direction: Direction,
+    ) -> Result<(), error::Unspecified> {
+        if polyfill::u64_from_usize(tweak.len()) > u64::from(u32::max_value()) {
+            return Err(error::Unspecified);
+        }
+        let radix = self.radix;
+        let n = x.len();
+        let u = n / 2;
+        let v = n - u;
+
+        // b = ceil(ceil(v * log2(radix)) / 8), the length of the byte string
+        // encoding of a half of length `v`.
+        let mut max_half = Nat::pow(radix, v);
+        max_half.sub_assign(&Nat::one());
+        let b = (max_half.bit_len() + 7) / 8;
+        let d = 4 * ((b + 3) / 4) + 4;
+
+        let mut p = [0u8; BLOCK_LEN];
+        p[..3].copy_from_slice(&[1, 2, 1]);
+        p[3..6].copy_from_slice(&radix.to_be_bytes()[1..]);
+        p[6] = 10;
+        p[7] = u as u8;
+        p[8..12].copy_from_slice(&(n as u32).to_be_bytes());
+        p[12..].copy_from_slice(&(tweak.len() as u32).to_be_bytes());
+        let p_mac = self.aes_key.encrypt_block(Block::from(&p));
+
+        // Q = T || [0]**((-t-b-1) mod 16) || [i]1 || [NUM_radix(B)]b.
+        let pad = (BLOCK_LEN - (tweak.len() + b + 1) % BLOCK_LEN) % BLOCK_LEN;
+        let i_index = tweak.len() + pad;
+        let mut q = vec![0u8; i_index + 1 + b];
+        q[..tweak.len()].copy_from_slice(tweak);
+
+        let round = |i: u8, half: &[u16], s: &mut Vec<u8>| {
+            q[i_index] = i;
+            Nat::from_numerals(half, radix).write_be_bytes(&mut q[(i_index + 1)..]);
+
+            // R = PRF(P || Q), the CBC-MAC of P || Q.
+            let mut r = p_mac;
+            for chunk in q.chunks(BLOCK_LEN) {
+                let mut block = Block::zero();
+                block.partial_copy_from(chunk);
+                r.bitxor_assign(block);
+                r = self.aes_key.encrypt_block(r);
+            }
+
+            // S = the first d bytes of R || CIPH(R ^ [1]16) || ...
+            s.clear();
+            s.extend_from_slice(r.as_ref());
+            let mut j = 1u64;
+            while s.len() < d {
+                let mut j_block = [0u8; BLOCK_LEN];
+                j_block[8..].copy_from_slice(&j.to_be_bytes());
+                let mut block = r;
+                block.bitxor_assign(Block::from(&j_block));
+                s.extend_from_slice(self.aes_key.encrypt_block(block).as_ref());
+                j += 1;
+            }
+            s.truncate(d);
+        };
+
+        self.feistel(x, u, 10, direction, round);
+        Ok(())
+    }
+
+    // SP 800-38G Rev. 1 Algorithms 9 and 10.
+    fn ff3_1(
+        &self,
+        tweak: &[u8],
+        x: &mut [u16],
+        direction: Direction,
+    ) -> Result<(), error::Unspecified> {
+        if tweak.len() != FF3_1_TWEAK_LEN {
+            return Err(error::Unspecified);
+        }
+        let radix = self.radix;
+        let n = x.len();
+        let u = (n + 1) / 2;
+
+        // T_L = T[0..27] || 0**4 and T_R = T[32..55] || T[28..31] || 0**4.
+        let t_l = [tweak[0], tweak[1], tweak[2], tweak[3] & 0xf0];
+        let t_r = [tweak[4], tweak[5], tweak[6], tweak[3] << 4];
+
+        let round = |i: u8, half: &[u16], s: &mut Vec<u8>| {
+            let w = if i % 2 == 0 { &t_r } else { &t_l };
+            let mut p = [0u8; BLOCK_LEN];
+            p[..4].copy_from_slice(w);
+            p[3] ^= i;
+            Nat::from_numerals(half, radix).write_be_bytes(&mut p[4..]);
+
+            // S = REVB(CIPH_REVB(K)(REVB(P))).
+            let r = self.aes_key.encrypt_block(Block::from(&p).reverse());
+            s.clear();
+            s.extend_from_slice(r.reverse().as_ref());
+        };
+
+        // FF3-1 interprets each half, and each round's output, with the
+        // least significant numeral first (NUM_radix(REV(X)) and
+        // REV(STR_radix(c))). Reversing the halves up front lets it share the
+        // most-significant-first Feistel network with FF1.
+        x[..u].reverse();
+        x[u..].reverse();
+        self.feistel(x, u, 8, direction, round);
+        x[..u].reverse();
+        x[u..].reverse();
+        Ok(())
+    }
+
+    // The Feistel network common to FF1 and FF3-1. `x` is split into halves
+    // A = x[..u] and B = x[u..]. `round(i, half, s)` sets `s` to the round
+    // function's output for round `i` applied to `half`.
+    fn feistel(
+        &self,
+        x: &mut [u16],
+        u: usize,
+        rounds: u8,
+        direction: Direction,
+        mut round: impl FnMut(u8, &[u16], &mut Vec<u8>),
+    ) {
+        let radix = self.radix;
+        let moduli = [Nat::pow(radix, u), Nat::pow(radix, x.len() - u)];
+
+        let mut a = x[..u].to_vec();
+        let mut b = x[u..].to_vec();
+        let mut s = Vec::new();
+
+        for j in 0..rounds {
+            // The output of round `i` has `u` numerals when `i` is even and
+            // `v` numerals when `i` is odd.
+            match direction {
+                Direction::Encrypt => {
+                    let i = j;
+                    let modulus = &moduli[usize::from(i % 2)];
+                    round(i, &b, &mut s);
+                    let y = Nat::from_be_bytes_mod(&s, modulus);
+                    let mut c = Nat::from_numerals(&a, radix);
+                    c.add_assign(&y);
+                    c.reduce_once(modulus);
+                    c.write_numerals(radix, &mut a);
+                }
+                Direction::Decrypt => {
+                    let i = rounds - 1 - j;
+                    let modulus = &moduli[usize::from(i % 2)];
+                    round(i, &a, &mut s);
+                    let y = Nat::from_be_bytes_mod(&s, modulus);
+                    let mut c = Nat::from_numerals(&b, radix);
+                    c.add_assign(modulus);
+                    c.sub_assign(&y);
+                    c.reduce_once(modulus);
+                    c.write_numerals(radix, &mut b);
+                }
+            }
+            core::mem::swap(&mut a, &mut b);
+        }
+
+        x[..u].copy_from_slice(&a);
+        x[u..].copy_from_slice(&b);
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Encrypt,
+    Decrypt,
+}
+
+/// An alphabet of distinct characters, which are the numerals 0, 1, ...,
+/// `radix - 1` in order.
+#[derive(Clone, Debug)]
+pub struct Alphabet {
+    symbols: Vec<char>,
+}
+
+impl Alphabet {
+    /// Constructs an alphabet of the characters of `symbols`, in order.
+    ///
+    /// `symbols` must contain between 2 and 2**16 characters, with no
+    /// character repeated.
+    pub fn new(symbols: &str) -> Result<Self, error::Unspecified> {
+        let symbols: Vec<char> = symbols.chars().collect();
+        if symbols.len() < 2 || symbols.len() > MAX_RADIX as usize {
+            return Err(error::Unspecified);
+        }
+        let mut sorted = symbols.clone();
+        sorted.sort_unstable();
+        if sorted.windows(2).any(|w| w[0] == w[1]) {
+            return Err(error::Unspecified);
+        }
+        Ok(Self { symbols })
+    }
+
+    /// The number of characters in the alphabet.
+    #[inline]
+    pub fn radix(&self) -> u32 {
+        self.symbols.len() as u32
+    }
+
+    /// Converts `input` to numerals. Fails if any character of `input` isn't
+    /// in the alphabet.
+    pub fn to_numerals(&self, input: &str) -> Result<Vec<u16>, error::Unspecified> {
+        input
+            .chars()
+            .map(|c| {
+                self.symbols
+                    .iter()
+                    .position(|&symbol| symbol == c)
+                    .map(|numeral| numeral as u16)
+                    .ok_or(error::Unspecified)
+            })
+            .collect()
+    }
+
+    /// Converts `numerals` to a string.
+    ///
+    /// # Panics
+    ///
+    /// `to_string` panics if any numeral is not less than `self.radix()`.
+    pub fn to_string(&self, numerals: &[u16]) -> String {
+        numerals
+            .iter()
+            .map(|&numeral| self.symbols[usize::from(numeral)])
+            .collect()
+    }
+}
+
+/// A format-preserving encryption algorithm.
+pub struct Algorithm {
+    mode: Mode,
+    variant: aes::Variant,
+    key_len: usize,
+    id: AlgorithmID,
+}
+
+impl Algorithm {
+    /// The length of the key.
+    #[inline(always)]
+    pub fn key_len(&self) -> usize {
+        self.key_len
+    }
+}
+
+derive_debug_via_id!(Algorithm);
+
+#[derive(Clone, Copy)]
+enum Mode {
+    FF1,
+    FF3_1,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum AlgorithmID {
+    FF1_AES_128,
+    FF1_AES_256,
+    FF3_1_AES_128,
+    FF3_1_AES_256,
+}
+
+impl PartialEq for Algorithm {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Algorithm {}
+
+/// FF1 with AES-128.
+pub static FF1_AES_128: Algorithm = Algorithm {
+    mode: Mode::FF1,
+    variant: aes::Variant::AES_128,
+    key_len: 16,
+    id: AlgorithmID::FF1_AES_128,
+};
+
+/// FF1 with AES-256.
+pub static FF1_AES_256: Algorithm = Algorithm {
+    mode: Mode::FF1,
+    variant: aes::Variant::AES_256,
+    key_len: 32,
+    id: AlgorithmID::FF1_AES_256,
+};
+
+/// FF3-1 with AES-128.
+pub static FF3_1_AES_128: Algorithm = Algorithm {
+    mode: Mode::FF3_1,
+    variant: aes::Variant::AES_128,
+    key_len: 16,
+    id: AlgorithmID::FF3_1_AES_128,
+};
+
+/// FF3-1 with AES-256.
+pub static FF3_1_AES_256: Algorithm = Algorithm {
+    mode: Mode::FF3_1,
+    variant: aes::Variant::AES_256,
+    key_len: 32,
+    id: AlgorithmID::FF3_1_AES_256,
+};
+
+/// The length of an FF3-1 tweak (56 bits).
+pub const FF3_1_TWEAK_LEN: usize = 7;
+
+const MAX_KEY_LEN: usize = 32;
+const MAX_RADIX: u32 = 1 << 16;
+
+/// A natural number, as little-endian 32-bit limbs.
+///
+/// FPE operates on numbers that are public in the sense that their sizes are
+/// public and that the algorithms aren't designed to be constant-time, so
+/// this is a simple variable-time implementation of just the operations
+/// needed.
+struct Nat(Vec<u32>);
+
+impl Nat {
+    fn one() -> Self {
+        Nat(vec![1])
+    }
+
+    // radix**exponent.
+    fn pow(radix: u32, exponent: usize) -> Self {
+        let mut r = Self::one();
+        for _ in 0..exponent {
+            r.mul_add_small(radix, 0);
+        }
+        r
+    }
+
+    // NUM_radix(X), with the most significant numeral first.
+    fn from_numerals(numerals: &[u16], radix: u32) -> Self {
+        let mut r = Nat(Vec::new());
+        for &numeral in numerals {
+            r.mul_add_small(radix, u32::from(numeral));
+        }
+        r
+    }
+
+    // NUM(X) mod `modulus`, for the big-endian byte string `bytes`.
+    fn from_be_bytes_mod(bytes: &[u8], modulus: &Self) -> Self {
+        let mut r = Nat(vec![0; modulus.0.len()]);
+        for byte in bytes {
+            for i in (0..8).rev() {
+                r.shl1_add(u32::from(byte >> i) & 1);
+                r.reduce_once(modulus);
+            }
+        }
+        r
+    }
+
+    // STR^m_radix(self), where m = `out.len()`. `self` must be less than
+    // radix**m.
+    fn write_numerals(mut self, radix: u32, out: &mut [u16]) {
+        for numeral in out.iter_mut().rev() {
+            *numeral = self.div_rem_small(radix) as u16;
+        }
+        debug_assert!(self.is_zero());
+    }
+
+    // [self]s, where s = `out.len()`. `self` must be less than 2**(8*s).
+    fn write_be_bytes(&self, out: &mut [u8]) {
+        for (i, byte) in out.iter_mut().rev().enumerate() {
+            *byte = (self.limb(i / 4) >> (8 * (i % 4))) as u8;
+        }
+        debug_assert!(self.bit_len() <= 8 * out.len());
+    }
+
+    fn limb(&self, i: usize) -> u32 {
+        self.0.get(i).cloned().unwrap_or(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.iter().all(|&limb| limb == 0)
+    }
+
+    fn bit_len(&self) -> usize {
+        match self.0.iter().rposition(|&limb| limb != 0) {
+            Some(i) => 32 * i + (32 - self.0[i].leading_zeros() as usize),
+            None => 0,
+        }
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        let len = core::cmp::max(self.0.len(), other.0.len());
+        (0..len)
+            .rev()
+            .map(|i| self.limb(i).cmp(&other.limb(i)))
+            .find(|&ordering| ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+
+    // self = self * m + a.
+    fn mul_add_small(&mut self, m: u32, a: u32) {
+        let mut carry = u64::from(a);
+        for limb in self.0.iter_mut() {
+            let t = u64::from(*limb) * u64::from(m) + carry;
+            *limb = t as u32;
+            carry = t >> 32;
+        }
+        if carry != 0 {
+            self.0.push(carry as u32);
+        }
+    }
+
+    // self = 2 * self + bit.
+    fn shl1_add(&mut self, bit: u32) {
+        let mut carry = bit;
+        for limb in self.0.iter_mut() {
+            let next = *limb >> 31;
+            *limb = (*limb << 1) | carry;
+            carry = next;
+        }
+        if carry != 0 {
+            self.0.push(carry);
+        }
+    }
+
+    // self = floor(self / d), returning self mod d.
+    fn div_rem_small(&mut self, d: u32) -> u32 {
+        let mut rem = 0u64;
+        for limb in self.0.iter_mut().rev() {
+            let t = (rem << 32) | u64::from(*limb);
+            *limb = (t / u64::from(d)) as u32;
+            rem = t % u64::from(d);
+        }
+        rem as u32
+    }
+
+    fn add_assign(&mut self, other: &Self) {
+        if self.0.len() < other.0.len() {
+            self.0.resize(other.0.len(), 0);
+        }
+        let mut carry = 0u64;
+        for (i, limb) in self.0.iter_mut().enumerate() {
+            let t = u64::from(*limb) + u64::from(other.limb(i)) + carry;
+            *limb = t as u32;
+            carry = t >> 32;
+        }
+        if carry != 0 {
+            self.0.push(carry as u32);
+        }
+    }
+
+    // self = self - other. `self` must not be less than `other`.
+    fn sub_assign(&mut self, other: &Self) {
+        debug_assert!(self.compare(other) != Ordering::Less);
+        let mut borrow = 0u64;
+        for (i, limb) in self.0.iter_mut().enumerate() {
+            let t = u64::from(*limb)
+                .wrapping_sub(u64::from(other.limb(i)))
+                .wrapping_sub(borrow);
+            *limb = t as u32;
+            borrow = t >> 63;
+        }
+    }
+
+    // self = self mod modulus, when self < 2 * modulus.
+    fn reduce_once(&mut self, modulus: &Self) {
+        if self.compare(modulus) != Ordering::Less {
+            self.sub_assign(modulus);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! GHASH, the universal hash function of AES-GCM, as specified in
+//! [NIST SP 800-38D Section 6.4].
+//!
+//! **This is a hazmat API.** GHASH isn't a MAC; its output must be
+//! encrypted, as AES-GCM does, before it can be used as an authenticator. It
+//! is exposed for implementing constructions such as AES-GCM variants on top
+//! of *ring*'s carry-less multiplication code. Use `ring::aead` unless a
+//! protocol specifies GHASH itself.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::ghash;
+//!
+//! let key = [0x66; ghash::KEY_LEN];
+//! let mut ctx = ghash::Context::new(&key);
+//! ctx.update_padded(b"additional data");
+//! ctx.update_blocks(&[0x03; 2 * ghash::BLOCK_LEN])?;
+//! let output = ctx.finalize();
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [NIST SP 800-38D Section 6.4]:
+//!     https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38d.pdf
+
+use crate::{
+    aead::{block::Block, gcm},
+    cpu, error,
+};
+
+/// The length of a GHASH key, the hash subkey *H*.
+pub const KEY_LEN: usize = 16;
+
+/// The length of the blocks that GHASH processes, and of its output.
+pub const BLOCK_LEN: usize = 16;
+
+/// A GHASH computation.
+pub struct Context(gcm::Context);
+
+impl Context {
+    /// Starts a computation with the key `key`.
+    pub fn new(key: &[u8; KEY_LEN]) -> Self {
+        let cpu_features = cpu::features();
+        let key = gcm::Key::new(Block::from(key), cpu_features);
+        Self(gcm::Context::new(&key, cpu_features))
+    }
+
+    /// Updates the computation with `input`, which must be a whole number of
+    /// blocks.
+    ///
+    /// Fails, without updating the computation, if `input.len()` isn't a
+    /// multiple of `BLOCK_LEN`.
+    pub fn update_blocks(&mut self, input: &[u8]) -> Result<(), error::Unspecified> {
+        if input.len() % BLOCK_LEN != 0 {
+            return Err(error::Unspecified);
+        }
+        if !input.is_empty() {
+            self.0.update_blocks(input);
+        }
+        Ok(())
+    }
+
+    /// Updates the computation with `input` followed by the zeros that pad it
+    /// to a whole number of blocks, as AES-GCM does for the additional data
+    /// and the ciphertext.
+    pub fn update_padded(&mut self, input: &[u8]) {
+        let whole_len = input.len() - (input.len() % BLOCK_LEN);
+        let (whole, remainder) = input.split_at(whole_len);
+        if !whole.is_empty() {
+            self.0.update_blocks(whole);
+        }
+        if !remainder.is_empty() {
+            let mut block = Block::zero();
+            block.partial_copy_from(remainder);
+            self.0.update_block(block);
+        }
+    }
+
+    /// Returns the GHASH of the blocks that the computation was updated with.
+    pub fn finalize(self) -> [u8; BLOCK_LEN] {
+        *self.0.value().as_ref()
+    }
+}
+
+impl core::fmt::Debug for Context {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Context").finish()
+    }
+}
@@ -0,0 +1,45 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Hashing to elliptic curves.
+//!
+//! This implements the random-oracle hash-to-curve suites
+//! P256_XMD:SHA-256_SSWU_RO_ and edwards25519_XMD:SHA-512_ELL2_RO_ from
+//! [RFC 9380], and the `expand_message_xmd` function that they use to hash
+//! their input, which is also useful on its own.
+//!
+//! The output point is encoded in the usual way for the curve, as documented
+//! for each `Suite`. Hashing is constant time with respect to the message.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::hash_to_curve;
+//!
+//! let point = hash_to_curve::hash_to_curve(
+//!     &hash_to_curve::P256_XMD_SHA256_SSWU_RO,
+//!     b"message",
+//!     b"MyApp-V01-CS01-with-P256_XMD:SHA-256_SSWU_RO_",
+//! )?;
+//! assert_eq!(point.as_ref().len(), 65);
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [RFC 9380]: https://tools.ietf.org/html/rfc9380
+
+pub use crate::ec::hash_to_curve::{
+    expand_message_xmd, hash_to_curve, EncodedPoint, Suite, EDWARDS25519_XMD_SHA512_ELL2_RO,
+    P256_XMD_SHA256_SSWU_RO,
+};
@@ -0,0 +1,419 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Hierarchical deterministic key derivation.
+//!
+//! This implements [BIP-32] for secp256k1 and [SLIP-10], its generalization
+//! to other curves, for Ed25519 and P-256. A master `ExtendedPrivateKey` is
+//! derived from a seed, and child keys are derived from it along a path of
+//! indexes. Indexes of `HARDENED` or greater are hardened.
+//!
+//! Ed25519 supports only hardened derivation. P-256 and secp256k1 also
+//! support normal derivation, which can be done from an `ExtendedPublicKey`
+//! without the private key.
+//!
+//! Only the keys and chain codes are derived; the BIP-32 serialization
+//! (`xprv`/`xpub`) isn't supported.
+//!
+//! ```
+//! use ring::{hd, signature};
+//!
+//! # #[cfg(feature = "sig-ed25519")]
+//! # fn main() -> Result<(), ring::error::Unspecified> {
+//! let seed = [0x5a; 32];
+//! let master = hd::ExtendedPrivateKey::from_seed(&hd::ED25519, &seed)?;
+//! let child = master.derive(&[44 | hd::HARDENED, 1 | hd::HARDENED])?;
+//! let key_pair = child.ed25519_key_pair().map_err(|_| ring::error::Unspecified)?;
+//!
+//! const MESSAGE: &[u8] = b"hello, world";
+//! let sig = key_pair.sign(MESSAGE);
+//! let public_key = child.public_key()?;
+//! signature::UnparsedPublicKey::new(&signature::ED25519, public_key.public_key_bytes())
+//!     .verify(MESSAGE, sig.as_ref())?;
+//! # Ok(())
+//! # }
+//! #
+//! # #[cfg(not(feature = "sig-ed25519"))]
+//! # fn main() {}
+//! ```
+//!
+//! [SLIP-10]: https://github.com/satoshilabs/slips/blob/master/slip-0010.md
+//! [BIP-32]: https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki
+
+use crate::{cpu, digest, ec, error, hmac, signature};
+use untrusted;
+
+/// The smallest hardened index.
+pub const HARDENED: u32 = 0x8000_0000;
+
+/// A curve that keys can be derived for.
+pub struct Algorithm {
+    id: AlgorithmID,
+    seed_key: &'static [u8],
+    public_key_len: usize,
+
+    // Whether an IL that isn't a valid key is retried as SLIP-10 specifies,
+    // instead of failing as BIP-32 specifies.
+    retry_invalid: bool,
+}
+
+derive_debug_via_id!(Algorithm);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum AlgorithmID {
+    #[cfg(feature = "sig-ed25519")]
+    ED25519,
+    P256,
+    SECP256K1,
+}
+
+impl PartialEq for Algorithm {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Algorithm {}
+
+/// Ed25519 keys, for use with `signature::Ed25519KeyPair`.
+#[cfg(feature = "sig-ed25519")]
+pub static ED25519: Algorithm = Algorithm {
+    id: AlgorithmID::ED25519,
+    seed_key: b"ed25519 seed",
+    public_key_len: ED25519_PUBLIC_KEY_LEN,
+    retry_invalid: true,
+};
+
+/// P-256 keys, for use with `signature::EcdsaKeyPair`. SLIP-10 calls this
+/// curve nist256p1.
+pub static P256: Algorithm = Algorithm {
+    id: AlgorithmID::P256,
+    seed_key: b"Nist256p1 seed",
+    public_key_len: ECDSA_PUBLIC_KEY_LEN,
+    retry_invalid: true,
+};
+
+/// secp256k1 keys, as specified by BIP-32, for use with
+/// `signature::EcdsaKeyPair`.
+pub static SECP256K1: Algorithm = Algorithm {
+    id: AlgorithmID::SECP256K1,
+    seed_key: b"Bitcoin seed",
+    public_key_len: ECDSA_PUBLIC_KEY_LEN,
+    retry_invalid: false,
+};
+
+impl Algorithm {
+    // The curve of an ECDSA algorithm, or `None` for Ed25519.
+    fn ecdsa_curve(&self) -> Option<&'static ec::Curve> {
+        match self.id {
+            #[cfg(feature = "sig-ed25519")]
+            AlgorithmID::ED25519 => None,
+            AlgorithmID::P256 => Some(&ec::suite_b::curve::P256),
+            AlgorithmID::SECP256K1 => Some(&ec::suite_b::curve::K256),
+        }
+    }
+}
+
+/// A private key and chain code, from which child keys can be derived.
+pub struct ExtendedPrivateKey {
+    alg: &'static Algorithm,
+    private_key: [u8; KEY_LEN],
+    chain_code: [u8; CHAIN_CODE_LEN],
+}
+
+derive_debug_via_field!(ExtendedPrivateKey, alg);
+
+impl ExtendedPrivateKey {
+    /// Derives the master key from `seed`, which must be between 16 and 64
+    /// bytes long.
+    ///
+    /// For `SECP256K1`, fails in the (negligibly likely) case that BIP-32
+    /// considers `seed` invalid.
+    pub fn from_seed(alg: &'static Algorithm, seed: &[u8]) -> Result<Self, error::Unspecified> {
+        if seed.len() < MIN_SEED_LEN || seed.len() > MAX_SEED_LEN {
+            return Err(error::Unspecified);
+        }
+        let key = hmac::Key::new(&digest::SHA512, alg.seed_key);
+        let mut i = hmac::sign(&key, seed);
+        loop {
+            let (il, ir) = i.as_ref().split_at(KEY_LEN);
+            let valid = match alg.ecdsa_curve() {
+                None => true,
+                Some(curve) => {
+                    ec::Seed::from_bytes(curve, untrusted::Input::from(il), cpu::features()).is_ok()
+                }
+            };
+            if valid {
+                return Ok(Self::new(alg, il, ir));
+            }
+            if !alg.retry_invalid {
+                return Err(error::Unspecified);
+            }
+            // SLIP-10: "If curve is not ed25519 and IL is 0 or ≥ n (invalid
+            // key): Set S := I and continue at step 2."
+            i = hmac::sign(&key, i.as_ref());
+        }
+    }
+
+    /// Derives the descendant of this key along `path`.
+    pub fn derive(&self, path: &[u32]) -> Result<Self, error::Unspecified> {
+        let mut key = Self::new(self.alg, &self.private_key, &self.chain_code);
+        for &index in path {
+            key = key.derive_child(index)?;
+        }
+        Ok(key)
+    }
+
+    /// Derives the child of this key at `index`.
+    ///
+    /// Fails if `index` isn't hardened and the algorithm only supports
+    /// hardened derivation. For `SECP256K1`, also fails in the (negligibly
+    /// likely) case that BIP-32 considers the child at `index` invalid.
+    pub fn derive_child(&self, index: u32) -> Result<Self, error::Unspecified> {
+        let parent_data = if index >= HARDENED {
+            let mut data = [0u8; 1 + KEY_LEN];
+            data[1..].copy_from_slice(&self.private_key);
+            data
+        } else {
+            self.public_key()?.compressed()?
+        };
+        let child = |il: &[u8], ir: &[u8]| match self.alg.ecdsa_curve() {
+            None => Ok(Self::new(self.alg, il, ir)),
+            Some(curve) => {
+                let mut private_key = [0u8; KEY_LEN];
+                ec::suite_b::tweak::private_key_add(
+                    curve,
+                    &self.private_key,
+                    il,
+                    &mut private_key,
+                )?;
+                Ok(Self::new(self.alg, &private_key, ir))
+            }
+        };
+        derive(self.alg, &self.chain_code, &parent_data, index, child)
+    }
+
+    /// Returns the public key and chain code.
+    pub fn public_key(&self) -> Result<ExtendedPublicKey, error::Unspecified> {
+        let mut public_key = [0u8; PUBLIC_KEY_MAX_LEN];
+        match self.alg.id {
+            #[cfg(feature = "sig-ed25519")]
+            AlgorithmID::ED25519 => {
+                let key_pair = self
+                    .ed25519_key_pair()
+                    .map_err(|_: error::KeyRejected| error::Unspecified)?;
+                public_key[..ED25519_PUBLIC_KEY_LEN]
+                    .copy_from_slice(signature::KeyPair::public_key(&key_pair).as_ref());
+            }
+            AlgorithmID::P256 | AlgorithmID::SECP256K1 => {
+                let seed = ec::Seed::from_bytes(
+                    self.alg.ecdsa_curve().unwrap(),
+                    untrusted::Input::from(&self.private_key),
+                    cpu::features(),
+                )?;
+                public_key[..ECDSA_PUBLIC_KEY_LEN]
+                    .copy_from_slice(seed.compute_public_key()?.as_ref());
+            }
+        }
+        Ok(ExtendedPublicKey {
+            alg: self.alg,
+            public_key,
+            chain_code: self.chain_code,
+        })
+    }
+
+    /// The algorithm the key is used with.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.alg
+    }
+
+    /// The chain code.
+    #[inline]
+    pub fn chain_code(&self) -> &[u8] {
+        &self.chain_code
+    }
+
+    /// Returns the private key as an Ed25519 key pair.
+    ///
+    /// Fails if the key isn't an `ED25519` key.
+    #[cfg(feature = "sig-ed25519")]
+    pub fn ed25519_key_pair(&self) -> Result<signature::Ed25519KeyPair, error::KeyRejected> {
+        if self.alg.id != AlgorithmID::ED25519 {
+            return Err(error::KeyRejected::wrong_algorithm());
+        }
+        signature::Ed25519KeyPair::from_seed_unchecked(&self.private_key)
+    }
+
+    /// Returns the private key as an ECDSA key pair for `alg`.
+    ///
+    /// Fails if the key isn't a `P256` or `SECP256K1` key or if `alg` doesn't
+    /// use the key's curve.
+    pub fn ecdsa_key_pair(
+        &self,
+        alg: &'static signature::EcdsaSigningAlgorithm,
+    ) -> Result<signature::EcdsaKeyPair, error::KeyRejected> {
+        if self.alg.ecdsa_curve().is_none() {
+            return Err(error::KeyRejected::wrong_algorithm());
+        }
+        let public_key = self
+            .public_key()
+            .map_err(|error::Unspecified| error::KeyRejected::unexpected_error())?;
+        signature::EcdsaKeyPair::from_private_key_and_public_key(
+            alg,
+            &self.private_key,
+            public_key.public_key_bytes(),
+        )
+    }
+
+    fn new(alg: &'static Algorithm, private_key: &[u8], chain_code: &[u8]) -> Self {
+        let mut r = Self {
+            alg,
+            private_key: [0; KEY_LEN],
+            chain_code: [0; CHAIN_CODE_LEN],
+        };
+        r.private_key.copy_from_slice(private_key);
+        r.chain_code.copy_from_slice(chain_code);
+        r
+    }
+}
+
+/// A public key and chain code.
+///
+/// For algorithms that support normal derivation, the public keys of normal
+/// children can be derived from it.
+#[derive(Clone)]
+pub struct ExtendedPublicKey {
+    alg: &'static Algorithm,
+    public_key: [u8; PUBLIC_KEY_MAX_LEN],
+    chain_code: [u8; CHAIN_CODE_LEN],
+}
+
+derive_debug_via_field!(ExtendedPublicKey, alg);
+
+impl ExtendedPublicKey {
+    /// Derives the descendant of this key along `path`.
+    ///
+    /// Fails if any index in `path` is hardened.
+    pub fn derive(&self, path: &[u32]) -> Result<Self, error::Unspecified> {
+        let mut key = self.clone();
+        for &index in path {
+            key = key.derive_child(index)?;
+        }
+        Ok(key)
+    }
+
+    /// Derives the child of this key at `index`.
+    ///
+    /// Fails if `index` is hardened or if the algorithm only supports hardened
+    /// derivation.
+    pub fn derive_child(&self, index: u32) -> Result<Self, error::Unspecified> {
+        if index >= HARDENED {
+            return Err(error::Unspecified);
+        }
+        let compressed = self.compressed()?;
+        let curve = self.alg.ecdsa_curve().ok_or(error::Unspecified)?;
+        derive(self.alg, &self.chain_code, &compressed, index, |il, ir| {
+            let mut public_key = [0u8; PUBLIC_KEY_MAX_LEN];
+            ec::suite_b::tweak::public_key_add(
+                curve,
+                self.public_key_bytes(),
+                il,
+                &mut public_key[..ECDSA_PUBLIC_KEY_LEN],
+            )?;
+            let mut chain_code = [0u8; CHAIN_CODE_LEN];
+            chain_code.copy_from_slice(ir);
+            Ok(Self {
+                alg: self.alg,
+                public_key,
+                chain_code,
+            })
+        })
+    }
+
+    /// The algorithm the key is used with.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.alg
+    }
+
+    /// The chain code.
+    #[inline]
+    pub fn chain_code(&self) -> &[u8] {
+        &self.chain_code
+    }
+
+    /// The public key, in the encoding used by `signature::UnparsedPublicKey`:
+    /// the 32-byte encoding for Ed25519, and the uncompressed encoding for
+    /// P-256 and secp256k1.
+    #[inline]
+    pub fn public_key_bytes(&self) -> &[u8] {
+        &self.public_key[..self.alg.public_key_len]
+    }
+
+    // The SEC 1 compressed encoding of an ECDSA public key, which is what is
+    // hashed for normal derivation. Ed25519 doesn't support normal derivation.
+    fn compressed(&self) -> Result<[u8; 1 + KEY_LEN], error::Unspecified> {
+        if self.alg.ecdsa_curve().is_none() {
+            return Err(error::Unspecified);
+        }
+        let mut compressed = [0u8; 1 + KEY_LEN];
+        compressed[0] = 2 | (self.public_key[ECDSA_PUBLIC_KEY_LEN - 1] & 1);
+        compressed[1..].copy_from_slice(&self.public_key[1..(1 + KEY_LEN)]);
+        Ok(compressed)
+    }
+}
+
+// Computes I = HMAC-SHA512(Key = c_par, Data = `parent_data` || ser32(i)) and
+// calls `f` with (IL, IR). If `f` fails because IL doesn't produce a valid
+// key, retries with I = HMAC-SHA512(Key = c_par, Data = 0x01 || IR ||
+// ser32(i)) as SLIP-10 specifies, or fails as BIP-32 specifies, depending on
+// `alg`.
+fn derive<T>(
+    alg: &Algorithm,
+    chain_code: &[u8],
+    parent_data: &[u8],
+    index: u32,
+    f: impl Fn(&[u8], &[u8]) -> Result<T, error::Unspecified>,
+) -> Result<T, error::Unspecified> {
+    let key = hmac::Key::new(&digest::SHA512, chain_code);
+    let mut ctx = hmac::Context::with_key(&key);
+    ctx.update(parent_data);
+    ctx.update(&index.to_be_bytes());
+    let mut i = ctx.sign();
+    loop {
+        let (il, ir) = i.as_ref().split_at(KEY_LEN);
+        match f(il, ir) {
+            Ok(r) => return Ok(r),
+            Err(e) if !alg.retry_invalid => return Err(e),
+            Err(error::Unspecified) => (),
+        }
+        let mut ctx = hmac::Context::with_key(&key);
+        ctx.update(&[1]);
+        ctx.update(ir);
+        ctx.update(&index.to_be_bytes());
+        i = ctx.sign();
+    }
+}
+
+const KEY_LEN: usize = 32;
+const CHAIN_CODE_LEN: usize = 32;
+
+#[cfg(feature = "sig-ed25519")]
+const ED25519_PUBLIC_KEY_LEN: usize = 32;
+const ECDSA_PUBLIC_KEY_LEN: usize = 1 + (2 * KEY_LEN);
+const PUBLIC_KEY_MAX_LEN: usize = ECDSA_PUBLIC_KEY_LEN;
+
+const MIN_SEED_LEN: usize = 16;
+const MAX_SEED_LEN: usize = 64;
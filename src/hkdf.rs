@@ -31,7 +31,10 @@
 //!
 //! [RFC 5869]: https://tools.ietf.org/html/rfc5869
 
-use crate::{digest, error, hmac};
+use crate::{digest, error, hmac, sealed};
+
+#[cfg(feature = "use_heap")]
+use std::{vec, vec::Vec};
 
 /// A salt for HKDF operations.
 #[derive(Debug)]
@@ -68,6 +71,23 @@ impl Salt {
 pub struct Prk(hmac::Key);
 
 impl Prk {
+    /// Construct a new `Prk` directly with the given value.
+    ///
+    /// Usually one can avoid using this. It is useful when the application
+    /// uses a secret derived elsewhere as a PRK, e.g. a TLS 1.3 traffic
+    /// secret from which `expand_label()` derives the traffic keys. `value`
+    /// must be a uniformly random key of `digest_algorithm.output_len`
+    /// bytes, such as the output of HKDF-Extract or HKDF-Expand; use
+    /// `Salt::extract()` for anything else.
+    pub fn new_less_safe(digest_algorithm: &'static digest::Algorithm, value: &[u8]) -> Self {
+        Prk(hmac::Key::new(digest_algorithm, value))
+    }
+
+    #[inline]
+    pub(crate) fn digest_algorithm(&self) -> &'static digest::Algorithm {
+        self.0.digest_algorithm()
+    }
+
     /// The [HKDF-Expand] operation.
     ///
     /// [HKDF-Expand]: https://tools.ietf.org/html/rfc5869#section-2.3
@@ -75,36 +95,105 @@ impl Prk {
     pub fn expand<'a>(&'a self, info: &'a [u8]) -> Okm<'a> {
         Okm { prk: self, info }
     }
-}
 
-/// An HKDF OKM (Output Keying Material)
-///
-/// Intentionally not `Clone` or `Copy` as an OKM is generally only safe to
-/// use once.
-#[derive(Debug)]
-pub struct Okm<'a> {
-    prk: &'a Prk,
-    info: &'a [u8],
-}
+    /// Fills `out` with the output of [HKDF-Expand] for `info`, in one step.
+    ///
+    /// This is equivalent to `self.expand(info).fill(out)`.
+    ///
+    /// [HKDF-Expand]: https://tools.ietf.org/html/rfc5869#section-2.3
+    #[inline]
+    pub fn fill(&self, info: &[u8], out: &mut [u8]) -> Result<(), error::Unspecified> {
+        self.expand(info).fill(out)
+    }
 
-impl Okm<'_> {
-    /// Fills `out` with the output of the HKDF-Expand operation for the given
-    /// inputs.
+    /// Fills each output buffer with the output of [HKDF-Expand] for the
+    /// `info` it is paired with.
     ///
-    /// Fails if (and only if) the requested output length is larger than 255
-    /// times the size of the digest algorithm's output. (This is the limit
-    /// imposed by the HKDF specification due to the way HKDF's counter is
-    /// constructed.)
-    pub fn fill(self, out: &mut [u8]) -> Result<(), error::Unspecified> {
-        let digest_alg = self.prk.0.digest_algorithm();
+    /// This is convenient for deriving many subkeys with different labels
+    /// from the same PRK. Fails, without writing to any of the buffers, if
+    /// any of them is longer than `Okm::fill()` allows.
+    ///
+    /// [HKDF-Expand]: https://tools.ietf.org/html/rfc5869#section-2.3
+    pub fn expand_multi(
+        &self,
+        outputs: &mut [(&[u8], &mut [u8])],
+    ) -> Result<(), error::Unspecified> {
+        let max_len = 255 * self.0.digest_algorithm().output_len;
+        if outputs.iter().any(|(_, out)| out.len() > max_len) {
+            return Err(error::Unspecified);
+        }
+        for (info, out) in outputs.iter_mut() {
+            self.fill(info, out)?;
+        }
+        Ok(())
+    }
+
+    /// The HKDF-Expand-Label function of [TLS 1.3], for key schedules like
+    /// that of TLS 1.3.
+    ///
+    /// Fills `out` with the output of HKDF-Expand for the `HkdfLabel` made
+    /// of `out.len()`, `"tls13 "` followed by `label`, and `context`. Fails if
+    /// `label` is empty or longer than 249 bytes, if `context` is longer than
+    /// 255 bytes, or if `out` is longer than `Okm::fill()` allows.
+    ///
+    /// [TLS 1.3]: https://tools.ietf.org/html/rfc8446#section-7.1
+    pub fn expand_label(
+        &self,
+        label: &[u8],
+        context: &[u8],
+        out: &mut [u8],
+    ) -> Result<(), error::Unspecified> {
+        const LABEL_PREFIX: &[u8] = b"tls13 ";
+        const MAX_INFO_LEN: usize = 2 + 1 + 255 + 1 + 255;
+
+        let full_label_len = LABEL_PREFIX.len() + label.len();
+        if label.is_empty() || full_label_len > 255 || context.len() > 255 {
+            return Err(error::Unspecified);
+        }
+        let out_len = out.len();
+        if out_len > usize::from(u16::max_value()) {
+            return Err(error::Unspecified);
+        }
+
+        let mut info = [0u8; MAX_INFO_LEN];
+        let info_len = {
+            let parts: [&[u8]; 6] = [
+                &(out_len as u16).to_be_bytes(),
+                &[full_label_len as u8],
+                LABEL_PREFIX,
+                label,
+                &[context.len() as u8],
+                context,
+            ];
+            let mut len = 0;
+            for part in parts.iter() {
+                info[len..][..part.len()].copy_from_slice(part);
+                len += part.len();
+            }
+            len
+        };
+        self.fill(&info[..info_len], out)
+    }
+
+    /// Like `fill()`, but with the `info` given as the parts that are
+    /// concatenated to form it, for callers that build `info` from labels
+    /// and variable-length inputs without allocating.
+    pub(crate) fn fill_with_info_parts(
+        &self,
+        info: &[&[u8]],
+        out: &mut [u8],
+    ) -> Result<(), error::Unspecified> {
+        let digest_alg = self.0.digest_algorithm();
         assert!(digest_alg.block_len >= digest_alg.output_len);
 
-        let mut ctx = hmac::Context::with_key(&self.prk.0);
+        let mut ctx = hmac::Context::with_key(&self.0);
 
         let mut n = 1u8;
         let mut out = out;
         loop {
-            ctx.update(self.info);
+            for part in info {
+                ctx.update(part);
+            }
             ctx.update(&[n]);
 
             let t = ctx.sign();
@@ -125,13 +214,99 @@ impl Okm<'_> {
                 return Ok(());
             }
 
-            ctx = hmac::Context::with_key(&self.prk.0);
+            ctx = hmac::Context::with_key(&self.0);
             ctx.update(t);
             n = n.checked_add(1).ok_or(error::Unspecified)?;
         }
     }
 }
 
+/// An HKDF OKM (Output Keying Material)
+///
+/// Intentionally not `Clone` or `Copy` as an OKM is generally only safe to
+/// use once.
+#[derive(Debug)]
+pub struct Okm<'a> {
+    prk: &'a Prk,
+    info: &'a [u8],
+}
+
+impl Okm<'_> {
+    /// Fills `out` with the output of the HKDF-Expand operation for the given
+    /// inputs.
+    ///
+    /// Fails if (and only if) the requested output length is larger than 255
+    /// times the size of the digest algorithm's output. (This is the limit
+    /// imposed by the HKDF specification due to the way HKDF's counter is
+    /// constructed.)
+    pub fn fill(self, out: &mut [u8]) -> Result<(), error::Unspecified> {
+        self.prk.fill_with_info_parts(&[self.info], out)
+    }
+
+    /// Returns the output of the HKDF-Expand operation as a byte array like
+    /// `[u8; 32]`, filled in the same way as `fill()` would fill it. See
+    /// `OutputArray` for the supported lengths.
+    ///
+    /// This avoids declaring and filling a buffer when the output length is
+    /// known at compile time:
+    ///
+    /// ```
+    /// use ring::{digest, hkdf};
+    ///
+    /// let prk = hkdf::Salt::new(&digest::SHA256, b"salt").extract(b"secret");
+    /// let iv: [u8; 12] = prk.expand(b"iv").fill_exact()?;
+    ///
+    /// # Ok::<(), ring::error::Unspecified>(())
+    /// ```
+    pub fn fill_exact<A: OutputArray>(self) -> Result<A, error::Unspecified> {
+        let mut out = A::zero();
+        self.fill(out.as_mut())?;
+        Ok(out)
+    }
+
+    /// Returns the first `len` bytes of the output of the HKDF-Expand
+    /// operation, for output lengths only known at runtime.
+    ///
+    /// Fails under the same conditions as `fill()`.
+    #[cfg(feature = "use_heap")]
+    pub fn into_vec(self, len: usize) -> Result<Vec<u8>, error::Unspecified> {
+        let mut out = vec![0u8; len];
+        self.fill(&mut out)?;
+        Ok(out)
+    }
+}
+
+/// A fixed-size byte array that `Okm::fill_exact()` can return.
+///
+/// This is implemented for `[u8; 1]` through `[u8; 64]`. It is never
+/// implemented for empty or variable-length types, so `fill_exact()` can't
+/// silently return zero bytes of output.
+pub trait OutputArray: AsMut<[u8]> + sealed::Sealed {
+    #[doc(hidden)]
+    fn zero() -> Self;
+}
+
+macro_rules! impl_output_array {
+    ( $( $len:expr ),+ ) => {
+        $(
+            impl sealed::Sealed for [u8; $len] {}
+
+            impl OutputArray for [u8; $len] {
+                #[inline]
+                fn zero() -> Self {
+                    [0; $len]
+                }
+            }
+        )+
+    };
+}
+
+impl_output_array!(
+    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26,
+    27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50,
+    51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64
+);
+
 /// Deprecated shortcut for
 /// `salt.extract(secret).expand(info).fill(out).unwrap()`.
 #[deprecated(note = "Will be removed in the next release.")]
@@ -22,7 +22,13 @@
 //! Frequently all the data to be signed in a message is available in a single
 //! contiguous piece. In that case, the module-level `sign` function can be
 //! used. Otherwise, if the input is in multiple parts, `Context` should be
-//! used.
+//! used for signing and `VerificationContext` should be used for verification.
+//!
+//! The hash function is given by the digest algorithm of the `Key`, e.g.
+//! `digest::SHA512_256` for HMAC-SHA-512/256. That isn't the same as
+//! HMAC-SHA-512 truncated to 256 bits, as used by NaCl's `crypto_auth` and
+//! [RFC 4868]; for that, use `digest::SHA512` with `Tag::truncated()` and
+//! `verify_truncated()`.
 //!
 //! # Examples:
 //!
@@ -96,16 +102,17 @@
 //! // The receiver (somehow!) knows the key value, and uses it to verify the
 //! // integrity of the message.
 //! let v_key = hmac::Key::new(&digest::SHA384, key_value.as_ref());
-//! let mut msg = Vec::<u8>::new();
+//! let mut v_ctx = hmac::VerificationContext::with_key(&v_key);
 //! for part in &parts {
-//!     msg.extend(part.as_bytes());
+//!     v_ctx.update(part.as_bytes());
 //! }
-//! hmac::verify(&v_key, &msg.as_ref(), tag.as_ref())?;
+//! v_ctx.verify(tag.as_ref())?;
 //!
 //! # Ok::<(), ring::error::Unspecified>(())
 //! ```
 //!
 //! [RFC 2104]: https://tools.ietf.org/html/rfc2104
+//! [RFC 4868]: https://tools.ietf.org/html/rfc4868
 //! [code for `ring::pbkdf2`]:
 //!     https://github.com/briansmith/ring/blob/master/src/pbkdf2.rs
 //! [code for `ring::hkdf`]:
@@ -113,6 +120,9 @@
 
 use crate::{constant_time, digest, error, hkdf, rand};
 
+#[cfg(feature = "audit")]
+use crate::audit;
+
 /// A deprecated alias for `Tag`.
 #[deprecated(note = "`Signature` was renamed to `Tag`. This alias will be removed soon.")]
 pub type Signature = Tag;
@@ -130,6 +140,30 @@ impl AsRef<[u8]> for Tag {
     }
 }
 
+impl Tag {
+    /// The first `len` bytes of the tag.
+    ///
+    /// Fails if `len` is longer than the tag or shorter than half of it or
+    /// than 10 bytes (80 bits), the limits recommended in
+    /// [RFC 2104 Section 5].
+    ///
+    /// [RFC 2104 Section 5]: https://tools.ietf.org/html/rfc2104#section-5
+    pub fn truncated(&self, len: usize) -> Result<&[u8], error::Unspecified> {
+        let tag = self.as_ref();
+        if len < core::cmp::max(tag.len() / 2, MIN_TRUNCATED_LEN) {
+            return Err(error::Unspecified);
+        }
+        tag.get(..len).ok_or(error::Unspecified)
+    }
+}
+
+// https://tools.ietf.org/html/rfc2104#section-5: "We recommend that the
+// output length t be not less than half the length of the hash output (to
+// match the birthday attack bound) and not less than 80 bits (a suitable
+// lower bound on the number of bits that need to be predicted by an
+// attacker)."
+const MIN_TRUNCATED_LEN: usize = 80 / 8;
+
 /// A key to use for HMAC signing.
 #[derive(Clone)]
 pub struct Key {
@@ -200,11 +234,20 @@ impl Key {
     /// the truncation described above reduces their strength to only
     /// `digest_alg.output_len * 8` bits. Support for such keys is likely to be
     /// removed in a future version of *ring*.
+    ///
+    /// Panics if `digest_alg` is `digest::ASCON_HASH`, whose blocks are
+    /// shorter than its output, so that HMAC isn't defined for it.
     pub fn new(digest_alg: &'static digest::Algorithm, key_value: &[u8]) -> Self {
+        assert!(digest_alg.output_len <= digest_alg.block_len);
+
         let mut key = Self {
             ctx_prototype: Context {
                 inner: digest::Context::new(digest_alg),
                 outer: digest::Context::new(digest_alg),
+                #[cfg(feature = "audit")]
+                fingerprint: audit::Fingerprint::new(key_value),
+                #[cfg(feature = "audit")]
+                len: 0,
             },
         };
 
@@ -254,6 +297,10 @@ impl Key {
 pub struct Context {
     inner: digest::Context,
     outer: digest::Context,
+    #[cfg(feature = "audit")]
+    fingerprint: audit::Fingerprint,
+    #[cfg(feature = "audit")]
+    len: usize,
 }
 
 /// `hmac::SigningContext` was renamed to `hmac::Context`.
@@ -279,6 +326,10 @@ impl Context {
     /// zero or more times until `finish` is called.
     pub fn update(&mut self, data: &[u8]) {
         self.inner.update(data);
+        #[cfg(feature = "audit")]
+        {
+            self.len = self.len.wrapping_add(data.len());
+        }
     }
 
     /// Finalizes the HMAC calculation and returns the HMAC value. `sign`
@@ -288,10 +339,77 @@ impl Context {
     /// It is generally not safe to implement HMAC verification by comparing
     /// the return value of `sign` to a tag. Use `verify` for verification
     /// instead.
-    pub fn sign(mut self) -> Tag {
+    pub fn sign(self) -> Tag {
+        #[cfg(feature = "audit")]
+        self.record(audit::Operation::HmacSign);
+        self.sign_()
+    }
+
+    fn sign_(mut self) -> Tag {
         self.outer.update(self.inner.finish().as_ref());
         Tag(self.outer.finish())
     }
+
+    #[cfg(feature = "audit")]
+    fn record(&self, operation: audit::Operation) {
+        audit::record(
+            self.inner.algorithm(),
+            operation,
+            &self.fingerprint,
+            self.len,
+        );
+    }
+}
+
+/// A context for multi-step (Init-Update-Finish) HMAC verification.
+///
+/// Only the digest state is kept between `update` calls, so arbitrarily long
+/// inputs can be verified without buffering them. Use `verify` for
+/// single-step HMAC verification.
+#[derive(Clone)]
+pub struct VerificationContext(Context);
+
+impl core::fmt::Debug for VerificationContext {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("VerificationContext")
+            .field("algorithm", self.0.inner.algorithm())
+            .finish()
+    }
+}
+
+impl VerificationContext {
+    /// Constructs a new HMAC verification context using the given key.
+    pub fn with_key(key: &Key) -> Self {
+        VerificationContext(Context::with_key(key))
+    }
+
+    /// Updates the HMAC with all the data in `data`. `update` may be called
+    /// zero or more times until `verify` is called.
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    /// Finalizes the HMAC calculation and verifies whether the resultant value
+    /// equals `tag`. `verify` consumes the context so it cannot be (mis-)used
+    /// after `verify` has been called.
+    ///
+    /// The verification will be done in constant time to prevent timing
+    /// attacks.
+    pub fn verify(self, tag: &[u8]) -> Result<(), error::Unspecified> {
+        #[cfg(feature = "audit")]
+        self.0.record(audit::Operation::HmacVerify);
+        constant_time::verify_slices_are_equal(self.0.sign_().as_ref(), tag)
+    }
+
+    /// Like `verify`, but `tag` is the HMAC value truncated to `tag.len()`
+    /// bytes, as by `Tag::truncated()`.
+    ///
+    /// Fails if `tag.len()` isn't a length that `Tag::truncated()` accepts.
+    pub fn verify_truncated(self, tag: &[u8]) -> Result<(), error::Unspecified> {
+        #[cfg(feature = "audit")]
+        self.0.record(audit::Operation::HmacVerify);
+        constant_time::verify_slices_are_equal(self.0.sign_().truncated(tag.len())?, tag)
+    }
 }
 
 /// Calculates the HMAC of `data` using the key `key` in one step.
@@ -306,15 +424,43 @@ pub fn sign(key: &Key, data: &[u8]) -> Tag {
     ctx.sign()
 }
 
+/// Calculates the HMAC of the concatenation of `parts` using the key `key` in
+/// one step, without needing to concatenate them first.
+///
+/// It is generally not safe to implement HMAC verification by comparing the
+/// return value of `sign_all` to a tag. Use `verify` for verification instead.
+pub fn sign_all(key: &Key, parts: &[&[u8]]) -> Tag {
+    let mut ctx = Context::with_key(key);
+    for part in parts {
+        ctx.update(part);
+    }
+    ctx.sign()
+}
+
 /// Calculates the HMAC of `data` using the signing key `key`, and verifies
 /// whether the resultant value equals `tag`, in one step.
 ///
 /// This is logically equivalent to, but more efficient than, constructing a
 /// `Key` with the same value as `key` and then using `verify`.
 ///
+/// Use `VerificationContext` to verify HMACs where the input is in multiple
+/// parts.
+///
 /// The verification will be done in constant time to prevent timing attacks.
 pub fn verify(key: &Key, data: &[u8], tag: &[u8]) -> Result<(), error::Unspecified> {
-    constant_time::verify_slices_are_equal(sign(key, data).as_ref(), tag)
+    let mut ctx = VerificationContext::with_key(key);
+    ctx.update(data);
+    ctx.verify(tag)
+}
+
+/// Like `verify`, but `tag` is the HMAC value truncated to `tag.len()` bytes,
+/// as by `Tag::truncated()`.
+///
+/// Fails if `tag.len()` isn't a length that `Tag::truncated()` accepts.
+pub fn verify_truncated(key: &Key, data: &[u8], tag: &[u8]) -> Result<(), error::Unspecified> {
+    let mut ctx = VerificationContext::with_key(key);
+    ctx.update(data);
+    ctx.verify_truncated(tag)
 }
 
 #[cfg(test)]
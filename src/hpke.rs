@@ -0,0 +1,457 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Hybrid Public Key Encryption (HPKE), as specified in [RFC 9180].
+//!
+//! Only the base mode (`mode_base`), without a PSK or sender
+//! authentication, is supported, with the DHKEM(X25519, HKDF-SHA256) KEM and
+//! the HKDF-SHA256 KDF.
+//!
+//! The sender uses the recipient's public key to set up a `Sender` context and
+//! an encapsulated key, which it sends to the recipient along with the
+//! ciphertexts. The recipient uses the encapsulated key and its `KeyPair` to
+//! set up the matching `Recipient` context. Each context numbers the messages
+//! it seals or opens, so the recipient must open the messages in the order
+//! that the sender sealed them.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "aead-chacha")]
+//! # fn main() -> Result<(), ring::error::Unspecified> {
+//! use ring::{aead::Aad, hpke, rand};
+//!
+//! let suite = &hpke::DHKEM_X25519_HKDF_SHA256_CHACHA20_POLY1305;
+//! let rng = rand::SystemRandom::new();
+//! let info = b"example";
+//!
+//! let recipient_key_pair = hpke::KeyPair::generate(&rng)?;
+//!
+//! let (enc, mut sender) =
+//!     hpke::Sender::setup_base(suite, recipient_key_pair.public_key(), info, &rng)?;
+//! let mut in_out = b"hello, world".to_vec();
+//! in_out.extend_from_slice(&[0; 16]);
+//! let out_len = sender.seal_in_place(Aad::empty(), &mut in_out, 16)?;
+//! in_out.truncate(out_len);
+//!
+//! let mut recipient =
+//!     hpke::Recipient::setup_base(suite, enc.as_ref(), &recipient_key_pair, info)?;
+//! let plaintext = recipient.open_in_place(Aad::empty(), &mut in_out)?;
+//! assert_eq!(plaintext, b"hello, world");
+//!
+//! # Ok(())
+//! # }
+//! #
+//! # #[cfg(not(feature = "aead-chacha"))]
+//! # fn main() {}
+//! ```
+//!
+//! [RFC 9180]: https://tools.ietf.org/html/rfc9180
+
+use crate::{aead, agreement, cpu, digest, ec, error, hkdf, hmac, rand};
+use untrusted;
+
+/// An HPKE cipher suite: a KEM, a KDF, and an AEAD.
+pub struct Suite {
+    aead: &'static aead::Algorithm,
+    suite_id: [u8; SUITE_ID_LEN],
+    id: SuiteID,
+}
+
+impl Suite {
+    /// The suite's AEAD algorithm.
+    #[inline(always)]
+    pub fn aead_algorithm(&self) -> &'static aead::Algorithm {
+        self.aead
+    }
+}
+
+derive_debug_via_id!(Suite);
+
+#[derive(Debug, Eq, PartialEq)]
+enum SuiteID {
+    DHKEM_X25519_HKDF_SHA256_AES_128_GCM,
+    DHKEM_X25519_HKDF_SHA256_AES_256_GCM,
+    #[cfg(feature = "aead-chacha")]
+    DHKEM_X25519_HKDF_SHA256_CHACHA20_POLY1305,
+}
+
+impl PartialEq for Suite {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Suite {}
+
+/// DHKEM(X25519, HKDF-SHA256), HKDF-SHA256, and AES-128-GCM.
+pub static DHKEM_X25519_HKDF_SHA256_AES_128_GCM: Suite = Suite {
+    aead: &aead::AES_128_GCM,
+    suite_id: *b"HPKE\x00\x20\x00\x01\x00\x01",
+    id: SuiteID::DHKEM_X25519_HKDF_SHA256_AES_128_GCM,
+};
+
+/// DHKEM(X25519, HKDF-SHA256), HKDF-SHA256, and AES-256-GCM.
+pub static DHKEM_X25519_HKDF_SHA256_AES_256_GCM: Suite = Suite {
+    aead: &aead::AES_256_GCM,
+    suite_id: *b"HPKE\x00\x20\x00\x01\x00\x02",
+    id: SuiteID::DHKEM_X25519_HKDF_SHA256_AES_256_GCM,
+};
+
+/// DHKEM(X25519, HKDF-SHA256), HKDF-SHA256, and ChaCha20-Poly1305.
+#[cfg(feature = "aead-chacha")]
+pub static DHKEM_X25519_HKDF_SHA256_CHACHA20_POLY1305: Suite = Suite {
+    aead: &aead::CHACHA20_POLY1305,
+    suite_id: *b"HPKE\x00\x20\x00\x01\x00\x03",
+    id: SuiteID::DHKEM_X25519_HKDF_SHA256_CHACHA20_POLY1305,
+};
+
+/// A recipient's X25519 key pair.
+pub struct KeyPair {
+    seed: ec::Seed,
+    public_key: ec::PublicKey,
+}
+
+impl core::fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        crate::debug::write_hex_tuple(f, "KeyPair", &self.public_key)
+    }
+}
+
+impl KeyPair {
+    /// Generates a new key pair.
+    pub fn generate(rng: &dyn rand::SecureRandom) -> Result<Self, error::Unspecified> {
+        let seed = ec::Seed::generate(agreement::X25519.curve, rng, cpu::features())?;
+        Self::from_seed(seed)
+    }
+
+    /// Derives a key pair from the input keying material `ikm`, using the
+    /// KEM's `DeriveKeyPair()` function.
+    ///
+    /// `ikm` must be at least 32 bytes long and should be uniformly random.
+    pub fn derive(ikm: &[u8]) -> Result<Self, error::Unspecified> {
+        if ikm.len() < X25519_LEN {
+            return Err(error::Unspecified);
+        }
+        let dkp_prk = labeled_extract(KEM_SUITE_ID, &[], b"dkp_prk", ikm);
+        let mut sk = [0u8; X25519_LEN];
+        labeled_expand(&dkp_prk, KEM_SUITE_ID, b"sk", &[], &mut sk)?;
+        let seed = ec::Seed::from_bytes(
+            agreement::X25519.curve,
+            untrusted::Input::from(&sk),
+            cpu::features(),
+        )?;
+        Self::from_seed(seed)
+    }
+
+    fn from_seed(seed: ec::Seed) -> Result<Self, error::Unspecified> {
+        let public_key = seed.compute_public_key()?;
+        Ok(Self { seed, public_key })
+    }
+
+    /// The public key, which the sender passes to `Sender::setup_base()`.
+    #[inline]
+    pub fn public_key(&self) -> &[u8] {
+        self.public_key.as_ref()
+    }
+}
+
+/// The sender's context, for sealing messages to a recipient.
+pub struct Sender {
+    key: aead::SealingKey,
+    context: Context,
+}
+
+derive_debug_via_field!(Sender, key);
+
+impl Sender {
+    /// Sets up a base-mode context for sending to the holder of the private
+    /// key for `recipient_public_key`.
+    ///
+    /// Returns the encapsulated key, which must be sent to the recipient, and
+    /// the context. `info` is application-supplied information that the
+    /// recipient must pass to `Recipient::setup_base()` too.
+    pub fn setup_base(
+        suite: &'static Suite,
+        recipient_public_key: &[u8],
+        info: &[u8],
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<(agreement::PublicKey, Self), error::Unspecified> {
+        let ephemeral = agreement::EphemeralPrivateKey::generate(&agreement::X25519, rng)?;
+        let enc = ephemeral.compute_public_key()?;
+        let shared_secret = agreement::agree_ephemeral(
+            ephemeral,
+            &agreement::UnparsedPublicKey::new(&agreement::X25519, recipient_public_key),
+            error::Unspecified,
+            |dh| extract_and_expand(dh, enc.as_ref(), recipient_public_key),
+        )?;
+        let mut key = [0u8; MAX_AEAD_KEY_LEN];
+        let context = key_schedule(suite, &shared_secret, info, &mut key)?;
+        let key = aead::SealingKey::new(suite.aead, &key[..suite.aead.key_len()])?;
+        Ok((enc, Self { key, context }))
+    }
+
+    /// Encrypts and signs (“seals”) the next message in place.
+    ///
+    /// This is like `aead::seal_in_place()`, with the nonce computed from the
+    /// message's sequence number. The sequence number is incremented only if
+    /// sealing succeeds.
+    pub fn seal_in_place<A: AsRef<[u8]>>(
+        &mut self,
+        aad: aead::Aad<A>,
+        in_out: &mut [u8],
+        out_suffix_capacity: usize,
+    ) -> Result<usize, error::Unspecified> {
+        let nonce = self.context.nonce()?;
+        let out_len = aead::seal_in_place(&self.key, nonce, aad, in_out, out_suffix_capacity)?;
+        self.context.seq += 1;
+        Ok(out_len)
+    }
+
+    /// Fills `out` with a secret exported from the context, for
+    /// `exporter_context`; see [RFC 9180 Section 5.3].
+    ///
+    /// Fails if `out` is longer than 255 * 32 bytes.
+    ///
+    /// [RFC 9180 Section 5.3]: https://tools.ietf.org/html/rfc9180#section-5.3
+    #[inline]
+    pub fn export(
+        &self,
+        exporter_context: &[u8],
+        out: &mut [u8],
+    ) -> Result<(), error::Unspecified> {
+        self.context.export(exporter_context, out)
+    }
+}
+
+/// The recipient's context, for opening messages from a sender.
+pub struct Recipient {
+    key: aead::OpeningKey,
+    context: Context,
+}
+
+derive_debug_via_field!(Recipient, key);
+
+impl Recipient {
+    /// Sets up a base-mode context for receiving from the sender that sent
+    /// the encapsulated key `enc`.
+    ///
+    /// `info` must be the same as the sender passed to `Sender::setup_base()`.
+    pub fn setup_base(
+        suite: &'static Suite,
+        enc: &[u8],
+        key_pair: &KeyPair,
+        info: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        let mut dh = [0u8; X25519_LEN];
+        (agreement::X25519.ecdh)(&mut dh, &key_pair.seed, untrusted::Input::from(enc))?;
+        let shared_secret = extract_and_expand(&dh, enc, key_pair.public_key())?;
+        let mut key = [0u8; MAX_AEAD_KEY_LEN];
+        let context = key_schedule(suite, &shared_secret, info, &mut key)?;
+        let key = aead::OpeningKey::new(suite.aead, &key[..suite.aead.key_len()])?;
+        Ok(Self { key, context })
+    }
+
+    /// Authenticates and decrypts (“opens”) the next message in place.
+    ///
+    /// `in_out` is the ciphertext followed by the tag. When
+    /// `open_in_place()` returns `Ok(plaintext)`, the plaintext is
+    /// `&mut in_out[..plaintext.len()]`. The sequence number is incremented
+    /// only if opening succeeds, so a forged message doesn't desynchronize
+    /// the context from the sender's.
+    pub fn open_in_place<'a, A: AsRef<[u8]>>(
+        &mut self,
+        aad: aead::Aad<A>,
+        in_out: &'a mut [u8],
+    ) -> Result<&'a mut [u8], error::Unspecified> {
+        let nonce = self.context.nonce()?;
+        let plaintext = aead::open_in_place(&self.key, nonce, aad, 0, in_out)?;
+        self.context.seq += 1;
+        Ok(plaintext)
+    }
+
+    /// Fills `out` with a secret exported from the context, for
+    /// `exporter_context`; see [RFC 9180 Section 5.3].
+    ///
+    /// Fails if `out` is longer than 255 * 32 bytes.
+    ///
+    /// [RFC 9180 Section 5.3]: https://tools.ietf.org/html/rfc9180#section-5.3
+    #[inline]
+    pub fn export(
+        &self,
+        exporter_context: &[u8],
+        out: &mut [u8],
+    ) -> Result<(), error::Unspecified> {
+        self.context.export(exporter_context, out)
+    }
+}
+
+struct Context {
+    suite: &'static Suite,
+    base_nonce: [u8; aead::NONCE_LEN],
+    seq: u64,
+    exporter_secret: hkdf::Prk,
+}
+
+impl Context {
+    fn nonce(&self) -> Result<aead::Nonce, error::Unspecified> {
+        // Stop before `seq` would wrap, which is far earlier than the limit
+        // of 2^96 - 1 messages that the specification allows.
+        if self.seq == u64::max_value() {
+            return Err(error::Unspecified);
+        }
+        let mut nonce = self.base_nonce;
+        for (n, s) in nonce[(aead::NONCE_LEN - 8)..]
+            .iter_mut()
+            .zip(self.seq.to_be_bytes().iter())
+        {
+            *n ^= s;
+        }
+        Ok(aead::Nonce::assume_unique_for_key(nonce))
+    }
+
+    fn export(&self, exporter_context: &[u8], out: &mut [u8]) -> Result<(), error::Unspecified> {
+        labeled_expand(
+            &self.exporter_secret,
+            &self.suite.suite_id,
+            b"sec",
+            &[exporter_context],
+            out,
+        )
+    }
+}
+
+// https://tools.ietf.org/html/rfc9180#section-4.1
+fn extract_and_expand(
+    dh: &[u8],
+    enc: &[u8],
+    recipient_public_key: &[u8],
+) -> Result<[u8; SHARED_SECRET_LEN], error::Unspecified> {
+    let eae_prk = labeled_extract(KEM_SUITE_ID, &[], b"eae_prk", dh);
+    let mut shared_secret = [0u8; SHARED_SECRET_LEN];
+    labeled_expand(
+        &eae_prk,
+        KEM_SUITE_ID,
+        b"shared_secret",
+        &[enc, recipient_public_key],
+        &mut shared_secret,
+    )?;
+    Ok(shared_secret)
+}
+
+// https://tools.ietf.org/html/rfc9180#section-5.1, for `mode_base`. The AEAD
+// key is written to `key`, which must be at least `suite.aead.key_len()`
+// bytes long.
+fn key_schedule(
+    suite: &'static Suite,
+    shared_secret: &[u8],
+    info: &[u8],
+    key: &mut [u8],
+) -> Result<Context, error::Unspecified> {
+    const MODE_BASE: u8 = 0x00;
+
+    let suite_id = &suite.suite_id[..];
+    let psk_id_hash = labeled_extract_tag(suite_id, &[], b"psk_id_hash", &[]);
+    let info_hash = labeled_extract_tag(suite_id, &[], b"info_hash", info);
+    let key_schedule_context: [&[u8]; 3] = [&[MODE_BASE], psk_id_hash.as_ref(), info_hash.as_ref()];
+
+    let secret = labeled_extract(suite_id, shared_secret, b"secret", &[]);
+
+    labeled_expand(
+        &secret,
+        suite_id,
+        b"key",
+        &key_schedule_context,
+        &mut key[..suite.aead.key_len()],
+    )?;
+    let mut base_nonce = [0u8; aead::NONCE_LEN];
+    labeled_expand(
+        &secret,
+        suite_id,
+        b"base_nonce",
+        &key_schedule_context,
+        &mut base_nonce,
+    )?;
+    let mut exporter_secret = [0u8; SHARED_SECRET_LEN];
+    labeled_expand(
+        &secret,
+        suite_id,
+        b"exp",
+        &key_schedule_context,
+        &mut exporter_secret,
+    )?;
+
+    Ok(Context {
+        suite,
+        base_nonce,
+        seq: 0,
+        exporter_secret: hkdf::Prk::new_less_safe(&digest::SHA256, &exporter_secret),
+    })
+}
+
+// `LabeledExtract()` from https://tools.ietf.org/html/rfc9180#section-4.
+fn labeled_extract(suite_id: &[u8], salt: &[u8], label: &[u8], ikm: &[u8]) -> hkdf::Prk {
+    let prk = labeled_extract_tag(suite_id, salt, label, ikm);
+    hkdf::Prk::new_less_safe(&digest::SHA256, prk.as_ref())
+}
+
+fn labeled_extract_tag(suite_id: &[u8], salt: &[u8], label: &[u8], ikm: &[u8]) -> hmac::Tag {
+    // As in `hkdf::Salt::extract()`, an empty salt is equivalent to one of
+    // `Nh` zeros.
+    let salt = hmac::Key::new(&digest::SHA256, salt);
+    let mut ctx = hmac::Context::with_key(&salt);
+    ctx.update(VERSION_LABEL);
+    ctx.update(suite_id);
+    ctx.update(label);
+    ctx.update(ikm);
+    ctx.sign()
+}
+
+// `LabeledExpand()` from https://tools.ietf.org/html/rfc9180#section-4, with
+// `info` given as up to three parts that are concatenated to form it.
+fn labeled_expand(
+    prk: &hkdf::Prk,
+    suite_id: &[u8],
+    label: &[u8],
+    info: &[&[u8]],
+    out: &mut [u8],
+) -> Result<(), error::Unspecified> {
+    const MAX_INFO_PARTS: usize = 3;
+
+    if info.len() > MAX_INFO_PARTS || out.len() > usize::from(u16::max_value()) {
+        return Err(error::Unspecified);
+    }
+    let out_len = (out.len() as u16).to_be_bytes();
+    let mut parts: [&[u8]; 4 + MAX_INFO_PARTS] =
+        [&out_len, VERSION_LABEL, suite_id, label, &[], &[], &[]];
+    parts[4..][..info.len()].copy_from_slice(info);
+    prk.fill_with_info_parts(&parts, out)
+}
+
+const VERSION_LABEL: &[u8] = b"HPKE-v1";
+
+// "KEM" || I2OSP(kem_id, 2), for DHKEM(X25519, HKDF-SHA256).
+const KEM_SUITE_ID: &[u8] = b"KEM\x00\x20";
+
+// "HPKE" || I2OSP(kem_id, 2) || I2OSP(kdf_id, 2) || I2OSP(aead_id, 2).
+const SUITE_ID_LEN: usize = 10;
+
+const X25519_LEN: usize = 32;
+
+// `Nsecret` for DHKEM(X25519, HKDF-SHA256), and `Nh` for HKDF-SHA256.
+const SHARED_SECRET_LEN: usize = 32;
+
+// `Nk` for AES-256-GCM and ChaCha20-Poly1305, the longest of the suites' AEAD
+// keys.
+const MAX_AEAD_KEY_LEN: usize = 32;
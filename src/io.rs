@@ -18,7 +18,7 @@
 pub mod der;
 
 #[cfg(feature = "use_heap")]
-mod writer;
+pub(crate) mod writer;
 
 #[cfg(feature = "use_heap")]
 pub(crate) mod der_writer;
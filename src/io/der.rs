@@ -32,10 +32,16 @@ pub enum Tag {
     OctetString = 0x04,
     Null = 0x05,
     OID = 0x06,
+    UTF8String = 0x0c,
+    PrintableString = 0x13,
     Sequence = CONSTRUCTED | 0x10, // 0x30
+    Set = CONSTRUCTED | 0x11,      // 0x31
     UTCTime = 0x17,
     GeneralizedTime = 0x18,
 
+    ContextSpecific0 = CONTEXT_SPECIFIC | 0,
+    ContextSpecific2 = CONTEXT_SPECIFIC | 2,
+
     ContextSpecificConstructed0 = CONTEXT_SPECIFIC | CONSTRUCTED | 0,
     ContextSpecificConstructed1 = CONTEXT_SPECIFIC | CONSTRUCTED | 1,
     ContextSpecificConstructed3 = CONTEXT_SPECIFIC | CONSTRUCTED | 3,
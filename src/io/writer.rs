@@ -74,6 +74,7 @@ impl Accumulator for Writer {
     }
 }
 
+#[cfg(feature = "rsa")]
 pub fn write_copy(accumulator: &mut dyn Accumulator, to_copy: untrusted::Input) {
     accumulator.write_bytes(to_copy.as_slice_less_safe())
 }
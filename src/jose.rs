@@ -0,0 +1,313 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! JSON Web Signature (JWS) compact serialization.
+//!
+//! See [RFC 7515] and [RFC 7518]. A JSON Web Token (JWT) is a JWS whose
+//! payload is a JSON object of claims; validating the claims (`exp`, `aud`,
+//! etc.) is left to the caller.
+//!
+//! The verifier always decides which algorithm is acceptable; the `alg` in
+//! the protected header must match it exactly. This prevents the well-known
+//! attacks where a token claims to use `none` or claims to use HMAC with
+//! the verifier's public key as the secret. Tokens with a `crit` header
+//! parameter are rejected since no extensions are supported.
+//!
+//! [RFC 7515]: https://tools.ietf.org/html/rfc7515
+//! [RFC 7518]: https://tools.ietf.org/html/rfc7518
+
+use crate::{base64, digest, error, hmac, rand, signature};
+use std::{string::String, vec::Vec};
+
+/// A JWS algorithm.
+pub struct Algorithm {
+    name: &'static str,
+    verification: Verification,
+    id: AlgorithmID,
+}
+
+enum Verification {
+    PublicKey(&'static dyn signature::VerificationAlgorithm),
+    Hmac(&'static digest::Algorithm),
+}
+
+derive_debug_via_id!(Algorithm);
+
+#[derive(Debug, Eq, PartialEq)]
+enum AlgorithmID {
+    ES256,
+    ES384,
+    EdDSA,
+    PS256,
+    RS256,
+    HS256,
+}
+
+impl PartialEq for Algorithm {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Algorithm {}
+
+impl Algorithm {
+    /// The value of the `alg` header parameter.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// ECDSA using P-256 and SHA-256.
+pub static ES256: Algorithm = Algorithm {
+    name: "ES256",
+    verification: Verification::PublicKey(&signature::ECDSA_P256_SHA256_FIXED),
+    id: AlgorithmID::ES256,
+};
+
+/// ECDSA using P-384 and SHA-384.
+pub static ES384: Algorithm = Algorithm {
+    name: "ES384",
+    verification: Verification::PublicKey(&signature::ECDSA_P384_SHA384_FIXED),
+    id: AlgorithmID::ES384,
+};
+
+/// EdDSA using Ed25519, as specified in [RFC 8037].
+///
+/// [RFC 8037]: https://tools.ietf.org/html/rfc8037
+pub static EDDSA: Algorithm = Algorithm {
+    name: "EdDSA",
+    verification: Verification::PublicKey(&signature::ED25519),
+    id: AlgorithmID::EdDSA,
+};
+
+/// RSASSA-PSS using SHA-256 and MGF1 with SHA-256.
+pub static PS256: Algorithm = Algorithm {
+    name: "PS256",
+    verification: Verification::PublicKey(&signature::RSA_PSS_2048_8192_SHA256),
+    id: AlgorithmID::PS256,
+};
+
+/// RSASSA-PKCS1-v1_5 using SHA-256.
+pub static RS256: Algorithm = Algorithm {
+    name: "RS256",
+    verification: Verification::PublicKey(&signature::RSA_PKCS1_2048_8192_SHA256),
+    id: AlgorithmID::RS256,
+};
+
+/// HMAC using SHA-256.
+pub static HS256: Algorithm = Algorithm {
+    name: "HS256",
+    verification: Verification::Hmac(&digest::SHA256),
+    id: AlgorithmID::HS256,
+};
+
+/// A key for signing.
+#[derive(Clone, Copy, Debug)]
+pub enum SigningKey<'a> {
+    /// An ECDSA key pair. It must have been constructed with the `_FIXED`
+    /// signing algorithm for the curve.
+    Ecdsa(&'a signature::EcdsaKeyPair),
+
+    /// An Ed25519 key pair.
+    Ed25519(&'a signature::Ed25519KeyPair),
+
+    /// An RSA key pair.
+    Rsa(&'a signature::RsaKeyPair),
+
+    /// Any other private key, e.g. one held in an HSM.
+    Signer(&'a dyn signature::Signer),
+
+    /// An HMAC key.
+    Hmac(&'a hmac::Key),
+}
+
+/// A key for verification.
+#[derive(Clone, Copy, Debug)]
+pub enum VerificationKey<'a> {
+    /// A public key, in the format used by the corresponding
+    /// `signature::VerificationAlgorithm`.
+    PublicKey(&'a [u8]),
+
+    /// An HMAC key.
+    Hmac(&'a hmac::Key),
+}
+
+/// Signs `payload`, returning the JWS in the compact serialization.
+///
+/// The protected header contains only the `alg` parameter.
+pub fn sign(
+    algorithm: &'static Algorithm,
+    key: SigningKey,
+    payload: &[u8],
+    rng: &dyn rand::SecureRandom,
+) -> Result<String, error::Unspecified> {
+    let mut header = String::from("{\"alg\":\"");
+    header.push_str(algorithm.name);
+    header.push_str("\"}");
+
+    let mut jws = base64::url_encode(header.as_bytes());
+    jws.push('.');
+    jws.push_str(&base64::url_encode(payload));
+
+    let signature = match key {
+        SigningKey::Ecdsa(key_pair) => sign_with(algorithm, key_pair, rng, jws.as_bytes())?,
+        SigningKey::Ed25519(key_pair) => sign_with(algorithm, key_pair, rng, jws.as_bytes())?,
+        SigningKey::Rsa(key_pair) => sign_with(algorithm, key_pair, rng, jws.as_bytes())?,
+        SigningKey::Signer(signer) => sign_with(algorithm, signer, rng, jws.as_bytes())?,
+        SigningKey::Hmac(key)
+            if algorithm.id == AlgorithmID::HS256 && key.digest_algorithm() == &digest::SHA256 =>
+        {
+            hmac::sign(key, jws.as_bytes()).as_ref().to_vec()
+        }
+        SigningKey::Hmac(_) => {
+            return Err(error::Unspecified);
+        }
+    };
+
+    jws.push('.');
+    jws.push_str(&base64::url_encode(&signature));
+    Ok(jws)
+}
+
+fn sign_with(
+    algorithm: &'static Algorithm,
+    signer: &dyn signature::Signer,
+    rng: &dyn rand::SecureRandom,
+    msg: &[u8],
+) -> Result<Vec<u8>, error::Unspecified> {
+    let scheme = match algorithm.id {
+        AlgorithmID::ES256 => signature::SignatureScheme::ECDSA_P256_SHA256_FIXED,
+        AlgorithmID::ES384 => signature::SignatureScheme::ECDSA_P384_SHA384_FIXED,
+        AlgorithmID::EdDSA => signature::SignatureScheme::ED25519,
+        AlgorithmID::PS256 => signature::SignatureScheme::RSA_PSS_SHA256,
+        AlgorithmID::RS256 => signature::SignatureScheme::RSA_PKCS1_SHA256,
+        AlgorithmID::HS256 => {
+            return Err(error::Unspecified);
+        }
+    };
+    if !signer.supports(scheme) {
+        return Err(error::Unspecified);
+    }
+    signer.sign(scheme, rng, msg)
+}
+
+/// Verifies the compact-serialized JWS `jws` and returns its decoded
+/// payload.
+///
+/// Verification fails unless the `alg` in the protected header is
+/// `algorithm`.
+pub fn verify(
+    algorithm: &'static Algorithm,
+    key: VerificationKey,
+    jws: &str,
+) -> Result<Vec<u8>, error::Unspecified> {
+    let mut parts = jws.split('.');
+    let (header, payload, signature) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(header), Some(payload), Some(signature)) => (header, payload, signature),
+        _ => {
+            return Err(error::Unspecified);
+        }
+    };
+    if parts.next().is_some() {
+        return Err(error::Unspecified);
+    }
+    let signing_input = &jws.as_bytes()[..(header.len() + 1 + payload.len())];
+
+    let header = base64::url_decode(header)?;
+    let alg = json::parse_header(&header)?;
+    if alg != algorithm.name.as_bytes() {
+        return Err(error::Unspecified);
+    }
+
+    let signature = base64::url_decode(signature)?;
+    match (&algorithm.verification, key) {
+        (Verification::PublicKey(verification_alg), VerificationKey::PublicKey(public_key)) => {
+            signature::UnparsedPublicKey::new(*verification_alg, public_key)
+                .verify(signing_input, &signature)?;
+        }
+        (Verification::Hmac(digest_alg), VerificationKey::Hmac(key))
+            if key.digest_algorithm() == *digest_alg =>
+        {
+            hmac::verify(key, signing_input, &signature)?;
+        }
+        _ => {
+            return Err(error::Unspecified);
+        }
+    }
+
+    base64::url_decode(payload)
+}
+
+/// Finds the `alg` of a JOSE header.
+mod json {
+    use crate::{error, json};
+    use std::vec::Vec;
+
+    /// Returns the value of the `alg` member of the header, which must be a
+    /// JSON object with no duplicate member names and no `crit` member.
+    pub fn parse_header(input: &[u8]) -> Result<Vec<u8>, error::Unspecified> {
+        let mut alg = None;
+        json::parse_object(input, |name, parser| {
+            if name == b"alg" {
+                alg = Some(parser.string()?);
+            } else if name == b"crit" {
+                return Err(error::Unspecified);
+            } else {
+                parser.skip_value(1)?;
+            }
+            Ok(())
+        })?;
+        alg.ok_or(error::Unspecified)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header() {
+        const GOOD: &[(&str, &str)] = &[
+            (r#"{"alg":"ES256"}"#, "ES256"),
+            (r#" { "typ" : "JWT" , "alg" : "HS256" } "#, "HS256"),
+            (
+                r#"{"alg":"EdDSA","x":[1,-2.5e3,{"y":null}],"z":true}"#,
+                "EdDSA",
+            ),
+        ];
+        for (header, alg) in GOOD {
+            assert_eq!(
+                &json::parse_header(header.as_bytes()).unwrap()[..],
+                alg.as_bytes()
+            );
+        }
+
+        const BAD: &[&str] = &[
+            r#"{}"#,
+            r#"{"typ":"JWT"}"#,
+            r#"{"alg":"ES256","alg":"none"}"#,
+            r#"{"alg":"ES256","crit":["exp"]}"#,
+            r#"{"alg":"ES256"}x"#,
+            r#"{"alg":"ES256",}"#,
+            r#"{"alg":ES256}"#,
+            r#"["alg","ES256"]"#,
+            r#"{"alg":"ES256","x":01}"#,
+        ];
+        for header in BAD {
+            assert!(json::parse_header(header.as_bytes()).is_err(), "{}", header);
+        }
+    }
+}
@@ -0,0 +1,315 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Just enough of a JSON (RFC 8259) parser for JOSE headers, JWKs, and
+//! Wycheproof test vectors.
+
+use crate::error;
+use std::vec::Vec;
+
+// Nesting beyond this is rejected to bound recursion.
+const MAX_DEPTH: usize = 16;
+
+/// Parses `input`, which must be a JSON object with no duplicate member
+/// names, calling `member` with each member's name and a parser positioned at
+/// its value. `member` must consume the value, e.g. with `Parser::string()` or
+/// `Parser::skip_value()`.
+pub(crate) fn parse_object<F>(input: &[u8], mut member: F) -> Result<(), error::Unspecified>
+where
+    F: FnMut(&[u8], &mut Parser) -> Result<(), error::Unspecified>,
+{
+    let mut parser = Parser::new(input);
+    parser.skip_whitespace();
+    parser.object(0, &mut member)?;
+    parser.skip_whitespace();
+    if !parser.is_at_end() {
+        return Err(error::Unspecified);
+    }
+    Ok(())
+}
+
+pub(crate) struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a [u8]) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.pos == self.input.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).cloned()
+    }
+
+    fn next(&mut self) -> Result<u8, error::Unspecified> {
+        let b = self.peek().ok_or(error::Unspecified)?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn consume(&mut self, b: u8) -> bool {
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, b: u8) -> Result<(), error::Unspecified> {
+        if !self.consume(b) {
+            return Err(error::Unspecified);
+        }
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    // Returns the string's value, UTF-8 encoded.
+    pub fn string(&mut self) -> Result<Vec<u8>, error::Unspecified> {
+        self.expect(b'"')?;
+        let mut out = Vec::new();
+        loop {
+            match self.next()? {
+                b'"' => break,
+                b'\\' => {
+                    let c = match self.next()? {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => {
+                            return Err(error::Unspecified);
+                        }
+                    };
+                    let mut buf = [0; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                b if b < 0x20 => {
+                    return Err(error::Unspecified);
+                }
+                b => out.push(b),
+            }
+        }
+        let _ = core::str::from_utf8(&out).map_err(|_| error::Unspecified)?;
+        Ok(out)
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, error::Unspecified> {
+        let hi = self.hex4()?;
+        let code_point = if hi >= 0xd800 && hi < 0xdc00 {
+            self.expect(b'\\')?;
+            self.expect(b'u')?;
+            let lo = self.hex4()?;
+            if lo < 0xdc00 || lo >= 0xe000 {
+                return Err(error::Unspecified);
+            }
+            0x10000 + ((hi - 0xd800) << 10) + (lo - 0xdc00)
+        } else {
+            hi
+        };
+        core::char::from_u32(code_point).ok_or(error::Unspecified)
+    }
+
+    fn hex4(&mut self) -> Result<u32, error::Unspecified> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let digit = char::from(self.next()?)
+                .to_digit(16)
+                .ok_or(error::Unspecified)?;
+            value = (value << 4) | digit;
+        }
+        Ok(value)
+    }
+
+    // Parses an object with no duplicate member names, calling `member` like
+    // `parse_object()` does. `depth` is the nesting depth of the object.
+    pub fn object<F>(&mut self, depth: usize, mut member: F) -> Result<(), error::Unspecified>
+    where
+        F: FnMut(&[u8], &mut Parser) -> Result<(), error::Unspecified>,
+    {
+        if depth > MAX_DEPTH {
+            return Err(error::Unspecified);
+        }
+        let mut names: Vec<Vec<u8>> = Vec::new();
+        self.expect(b'{')?;
+        self.skip_whitespace();
+        if !self.consume(b'}') {
+            loop {
+                self.skip_whitespace();
+                let name = self.string()?;
+                if names.contains(&name) {
+                    return Err(error::Unspecified);
+                }
+                self.skip_whitespace();
+                self.expect(b':')?;
+                self.skip_whitespace();
+                member(&name, self)?;
+                names.push(name);
+                self.skip_whitespace();
+                if self.consume(b'}') {
+                    break;
+                }
+                self.expect(b',')?;
+            }
+        }
+        Ok(())
+    }
+
+    // Parses an array, calling `element` with a parser positioned at each
+    // element, which `element` must consume. `depth` is the nesting depth of
+    // the array.
+    pub fn array<F>(&mut self, depth: usize, mut element: F) -> Result<(), error::Unspecified>
+    where
+        F: FnMut(&mut Parser) -> Result<(), error::Unspecified>,
+    {
+        if depth > MAX_DEPTH {
+            return Err(error::Unspecified);
+        }
+        self.expect(b'[')?;
+        self.skip_whitespace();
+        if !self.consume(b']') {
+            loop {
+                self.skip_whitespace();
+                element(self)?;
+                self.skip_whitespace();
+                if self.consume(b']') {
+                    break;
+                }
+                self.expect(b',')?;
+            }
+        }
+        Ok(())
+    }
+
+    // Whether the next value is a string.
+    pub fn is_string(&self) -> bool {
+        self.peek() == Some(b'"')
+    }
+
+    // Returns the value of a number that is a non-negative integer.
+    pub fn usize(&mut self) -> Result<usize, error::Unspecified> {
+        let start = self.pos;
+        self.digits()?;
+        let digits = &self.input[start..self.pos];
+        if digits.len() > 1 && digits[0] == b'0' {
+            return Err(error::Unspecified);
+        }
+        digits.iter().try_fold(0usize, |value, digit| {
+            value
+                .checked_mul(10)
+                .and_then(|value| value.checked_add(usize::from(digit - b'0')))
+                .ok_or(error::Unspecified)
+        })
+    }
+
+    pub fn skip_value(&mut self, depth: usize) -> Result<(), error::Unspecified> {
+        if depth > MAX_DEPTH {
+            return Err(error::Unspecified);
+        }
+        match self.peek().ok_or(error::Unspecified)? {
+            b'"' => {
+                let _ = self.string()?;
+            }
+            b'{' => {
+                self.pos += 1;
+                self.skip_whitespace();
+                if !self.consume(b'}') {
+                    loop {
+                        self.skip_whitespace();
+                        let _ = self.string()?;
+                        self.skip_whitespace();
+                        self.expect(b':')?;
+                        self.skip_whitespace();
+                        self.skip_value(depth + 1)?;
+                        self.skip_whitespace();
+                        if self.consume(b'}') {
+                            break;
+                        }
+                        self.expect(b',')?;
+                    }
+                }
+            }
+            b'[' => {
+                self.pos += 1;
+                self.skip_whitespace();
+                if !self.consume(b']') {
+                    loop {
+                        self.skip_whitespace();
+                        self.skip_value(depth + 1)?;
+                        self.skip_whitespace();
+                        if self.consume(b']') {
+                            break;
+                        }
+                        self.expect(b',')?;
+                    }
+                }
+            }
+            b't' => self.literal(b"true")?,
+            b'f' => self.literal(b"false")?,
+            b'n' => self.literal(b"null")?,
+            _ => self.number()?,
+        }
+        Ok(())
+    }
+
+    fn literal(&mut self, literal: &[u8]) -> Result<(), error::Unspecified> {
+        for b in literal {
+            self.expect(*b)?;
+        }
+        Ok(())
+    }
+
+    fn number(&mut self) -> Result<(), error::Unspecified> {
+        let _ = self.consume(b'-');
+        if !self.consume(b'0') {
+            self.digits()?;
+        }
+        if self.consume(b'.') {
+            self.digits()?;
+        }
+        if self.consume(b'e') || self.consume(b'E') {
+            if !self.consume(b'+') {
+                let _ = self.consume(b'-');
+            }
+            self.digits()?;
+        }
+        Ok(())
+    }
+
+    fn digits(&mut self) -> Result<(), error::Unspecified> {
+        let start = self.pos;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(error::Unspecified);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,670 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! JSON Web Keys (JWK).
+//!
+//! A `Jwk` converts between *ring*'s key types and the JSON representation
+//! of [RFC 7517]: `EcdsaKeyPair` as `EC` keys and `RsaKeyPair` as `RSA` keys
+//! ([RFC 7518 Section 6]), `Ed25519KeyPair` as `OKP` keys ([RFC 8037]), and
+//! symmetric AEAD and HMAC keys as `oct` keys. `Jwk::thumbprint()` computes
+//! the [RFC 7638] thumbprint, which is often used as a key ID.
+//!
+//! Parsing is strict about the key material: every base64url value must be
+//! unpadded, integers must be minimal, and EC coordinates and Ed25519 keys
+//! must be exactly the right length. Members that aren't needed, such as
+//! `kid`, `use`, and `alg`, are ignored. Multi-prime RSA keys (with an `oth`
+//! member) aren't supported.
+//!
+//! ```
+//! use ring::{jwk, rand, signature::{self, KeyPair}};
+//!
+//! # fn main() -> Result<(), ring::error::Unspecified> {
+//! let rng = rand::SystemRandom::new();
+//! let pkcs8 = signature::Ed25519KeyPair::generate_pkcs8(&rng)?;
+//! let key_pair = signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+//!     .map_err(|_| ring::error::Unspecified)?;
+//!
+//! // Publish the public key.
+//! let public_jwk = jwk::Jwk::from_ed25519_key_pair(&key_pair).to_public()?;
+//! let json = public_jwk.to_json();
+//!
+//! // Later, verify a signature with it.
+//! let public_jwk = jwk::Jwk::parse(&json).map_err(|_| ring::error::Unspecified)?;
+//! const MESSAGE: &[u8] = b"hello, world";
+//! let sig = key_pair.sign(MESSAGE);
+//! signature::UnparsedPublicKey::new(&signature::ED25519, public_jwk.public_key()?)
+//!     .verify(MESSAGE, sig.as_ref())?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [RFC 7517]: https://tools.ietf.org/html/rfc7517
+//! [RFC 7518 Section 6]: https://tools.ietf.org/html/rfc7518#section-6
+//! [RFC 7638]: https://tools.ietf.org/html/rfc7638
+//! [RFC 8037]: https://tools.ietf.org/html/rfc8037
+
+use crate::{
+    aead, base64, digest, ec,
+    error::{self, KeyRejected},
+    hmac,
+    io::{der, der_writer, writer::Accumulator, Positive},
+    json,
+    signature::{self, KeyPair},
+    zeroize::Zeroize,
+};
+use std::{string::String, vec, vec::Vec};
+use untrusted;
+
+/// A JSON Web Key.
+///
+/// A `Jwk` may hold a private or secret key; its `Debug` implementation
+/// shows only the key type.
+pub struct Jwk {
+    params: Params,
+}
+
+enum Params {
+    Ec {
+        curve: &'static EcCurve,
+        x: Vec<u8>,
+        y: Vec<u8>,
+        d: Option<Secret>,
+    },
+    Okp {
+        x: Vec<u8>,
+        d: Option<Secret>,
+    },
+    Rsa {
+        n: Vec<u8>,
+        e: Vec<u8>,
+        private: Option<RsaPrivate>,
+    },
+    Oct {
+        k: Secret,
+    },
+}
+
+struct RsaPrivate {
+    d: Secret,
+    p: Secret,
+    q: Secret,
+    dp: Secret,
+    dq: Secret,
+    qi: Secret,
+}
+
+// Key material that is wiped when it is dropped.
+struct Secret(Vec<u8>);
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0[..].zeroize();
+    }
+}
+
+struct EcCurve {
+    id: ec::CurveID,
+    name: &'static str,
+    elem_len: usize,
+}
+
+static EC_CURVES: [EcCurve; 4] = [
+    EcCurve {
+        id: ec::CurveID::P256,
+        name: "P-256",
+        elem_len: 32,
+    },
+    EcCurve {
+        id: ec::CurveID::P384,
+        name: "P-384",
+        elem_len: 48,
+    },
+    EcCurve {
+        id: ec::CurveID::P521,
+        name: "P-521",
+        elem_len: 66,
+    },
+    EcCurve {
+        id: ec::CurveID::K256,
+        name: "secp256k1",
+        elem_len: 32,
+    },
+];
+
+const ED25519: &str = "Ed25519";
+const ED25519_KEY_LEN: usize = 32;
+
+impl Jwk {
+    /// Parses a JWK from its JSON representation.
+    pub fn parse(json: &str) -> Result<Self, KeyRejected> {
+        let members = Members::parse(json.as_bytes())?;
+        let kty = members
+            .kty
+            .as_ref()
+            .ok_or_else(KeyRejected::invalid_encoding)?;
+        let params = match &kty.0[..] {
+            b"EC" => {
+                let crv = required(&members.crv)?;
+                let curve = EC_CURVES
+                    .iter()
+                    .find(|curve| curve.name.as_bytes() == &crv.0[..])
+                    .ok_or_else(KeyRejected::wrong_algorithm)?;
+                Params::Ec {
+                    curve,
+                    x: fixed_len(&members.x, curve.elem_len)?.0.clone(),
+                    y: fixed_len(&members.y, curve.elem_len)?.0.clone(),
+                    d: optional_fixed_len(&members.d, curve.elem_len)?,
+                }
+            }
+            b"OKP" => {
+                if &required(&members.crv)?.0[..] != ED25519.as_bytes() {
+                    return Err(KeyRejected::wrong_algorithm());
+                }
+                Params::Okp {
+                    x: fixed_len(&members.x, ED25519_KEY_LEN)?.0.clone(),
+                    d: optional_fixed_len(&members.d, ED25519_KEY_LEN)?,
+                }
+            }
+            b"RSA" => {
+                if members.oth {
+                    return Err(KeyRejected::version_not_supported());
+                }
+                let private = match &members.d {
+                    None => None,
+                    Some(_) => Some(RsaPrivate {
+                        d: uint(&members.d)?,
+                        p: uint(&members.p)?,
+                        q: uint(&members.q)?,
+                        dp: uint(&members.dp)?,
+                        dq: uint(&members.dq)?,
+                        qi: uint(&members.qi)?,
+                    }),
+                };
+                Params::Rsa {
+                    n: uint(&members.n)?.0.clone(),
+                    e: uint(&members.e)?.0.clone(),
+                    private,
+                }
+            }
+            b"oct" => {
+                let k = decode(required(&members.k)?)?;
+                if k.0.is_empty() {
+                    return Err(KeyRejected::invalid_encoding());
+                }
+                Params::Oct { k }
+            }
+            _ => {
+                return Err(KeyRejected::wrong_algorithm());
+            }
+        };
+        Ok(Self { params })
+    }
+
+    /// Constructs an `EC` JWK with the private and public key of `key_pair`.
+    pub fn from_ecdsa_key_pair(key_pair: &signature::EcdsaKeyPair) -> Self {
+        let id = key_pair.algorithm().curve().id;
+        // All the curves of `EcdsaKeyPair` are in `EC_CURVES`.
+        let curve = EC_CURVES.iter().find(|curve| curve.id == id).unwrap();
+        // The public key is the uncompressed point `04 || x || y`.
+        let (x, y) = key_pair.public_key().as_ref()[1..].split_at(curve.elem_len);
+        Self {
+            params: Params::Ec {
+                curve,
+                x: x.to_vec(),
+                y: y.to_vec(),
+                d: Some(Secret(key_pair.export_private_key_unchecked().to_vec())),
+            },
+        }
+    }
+
+    /// Constructs an `OKP` JWK with the private and public key of
+    /// `key_pair`.
+    pub fn from_ed25519_key_pair(key_pair: &signature::Ed25519KeyPair) -> Self {
+        Self {
+            params: Params::Okp {
+                x: key_pair.public_key().as_ref().to_vec(),
+                d: Some(Secret(key_pair.export_seed_unchecked().to_vec())),
+            },
+        }
+    }
+
+    /// Constructs an `RSA` JWK with the private and public key of
+    /// `key_pair`.
+    pub fn from_rsa_key_pair(key_pair: &signature::RsaKeyPair) -> Self {
+        // The `RSAPrivateKey` was already parsed successfully when
+        // `key_pair` was constructed.
+        let rsa_private_key = untrusted::Input::from(key_pair.private_key_der());
+        let (n, e, private) = rsa_private_key
+            .read_all(error::Unspecified, |input| {
+                der::nested(input, der::Tag::Sequence, error::Unspecified, |input| {
+                    let _version = der::small_nonnegative_integer(input)?;
+                    let mut next = || -> Result<Vec<u8>, error::Unspecified> {
+                        Ok(der::positive_integer(input)?
+                            .big_endian_without_leading_zero()
+                            .to_vec())
+                    };
+                    let n = next()?;
+                    let e = next()?;
+                    let private = RsaPrivate {
+                        d: Secret(next()?),
+                        p: Secret(next()?),
+                        q: Secret(next()?),
+                        dp: Secret(next()?),
+                        dq: Secret(next()?),
+                        qi: Secret(next()?),
+                    };
+                    Ok((n, e, private))
+                })
+            })
+            .unwrap();
+        Self {
+            params: Params::Rsa {
+                n,
+                e,
+                private: Some(private),
+            },
+        }
+    }
+
+    /// Constructs an `oct` JWK for the symmetric key `key`, e.g. the key of an
+    /// `aead::SealingKey` or an `hmac::Key`.
+    ///
+    /// Fails if `key` is empty.
+    pub fn from_symmetric_key(key: &[u8]) -> Result<Self, error::Unspecified> {
+        if key.is_empty() {
+            return Err(error::Unspecified);
+        }
+        Ok(Self {
+            params: Params::Oct {
+                k: Secret(key.to_vec()),
+            },
+        })
+    }
+
+    /// The value of the `kty` member: `EC`, `OKP`, `RSA`, or `oct`.
+    pub fn key_type(&self) -> &'static str {
+        match self.params {
+            Params::Ec { .. } => "EC",
+            Params::Okp { .. } => "OKP",
+            Params::Rsa { .. } => "RSA",
+            Params::Oct { .. } => "oct",
+        }
+    }
+
+    /// Whether the JWK contains a private or secret key.
+    pub fn is_private(&self) -> bool {
+        match &self.params {
+            Params::Ec { d, .. } | Params::Okp { d, .. } => d.is_some(),
+            Params::Rsa { private, .. } => private.is_some(),
+            Params::Oct { .. } => true,
+        }
+    }
+
+    /// Returns a JWK with only the public key.
+    ///
+    /// Fails for `oct` keys, which have no public part.
+    pub fn to_public(&self) -> Result<Self, error::Unspecified> {
+        let params = match &self.params {
+            Params::Ec { curve, x, y, .. } => Params::Ec {
+                curve: *curve,
+                x: x.clone(),
+                y: y.clone(),
+                d: None,
+            },
+            Params::Okp { x, .. } => Params::Okp {
+                x: x.clone(),
+                d: None,
+            },
+            Params::Rsa { n, e, .. } => Params::Rsa {
+                n: n.clone(),
+                e: e.clone(),
+                private: None,
+            },
+            Params::Oct { .. } => {
+                return Err(error::Unspecified);
+            }
+        };
+        Ok(Self { params })
+    }
+
+    /// Serializes the JWK as JSON, including the private or secret key if
+    /// there is one.
+    pub fn to_json(&self) -> String {
+        let mut members = Vec::new();
+        match &self.params {
+            Params::Ec { curve, x, y, d } => {
+                members.push(("kty", String::from("EC")));
+                members.push(("crv", String::from(curve.name)));
+                members.push(("x", base64::url_encode(x)));
+                members.push(("y", base64::url_encode(y)));
+                if let Some(d) = d {
+                    members.push(("d", base64::url_encode(&d.0)));
+                }
+            }
+            Params::Okp { x, d } => {
+                members.push(("kty", String::from("OKP")));
+                members.push(("crv", String::from(ED25519)));
+                members.push(("x", base64::url_encode(x)));
+                if let Some(d) = d {
+                    members.push(("d", base64::url_encode(&d.0)));
+                }
+            }
+            Params::Rsa { n, e, private } => {
+                members.push(("kty", String::from("RSA")));
+                members.push(("n", base64::url_encode(n)));
+                members.push(("e", base64::url_encode(e)));
+                if let Some(private) = private {
+                    members.push(("d", base64::url_encode(&private.d.0)));
+                    members.push(("p", base64::url_encode(&private.p.0)));
+                    members.push(("q", base64::url_encode(&private.q.0)));
+                    members.push(("dp", base64::url_encode(&private.dp.0)));
+                    members.push(("dq", base64::url_encode(&private.dq.0)));
+                    members.push(("qi", base64::url_encode(&private.qi.0)));
+                }
+            }
+            Params::Oct { k } => {
+                members.push(("kty", String::from("oct")));
+                members.push(("k", base64::url_encode(&k.0)));
+            }
+        }
+        write_object(&members)
+    }
+
+    /// Computes the [RFC 7638] thumbprint of the JWK using `digest_alg`,
+    /// usually SHA-256.
+    ///
+    /// Only the required public members are hashed, so a private key and
+    /// its public key have the same thumbprint. When the thumbprint is used
+    /// as a `kid`, it is base64url-encoded.
+    ///
+    /// [RFC 7638]: https://tools.ietf.org/html/rfc7638
+    pub fn thumbprint(&self, digest_alg: &'static digest::Algorithm) -> digest::Digest {
+        // The required members, in lexicographic order.
+        let members = match &self.params {
+            Params::Ec { curve, x, y, .. } => vec![
+                ("crv", String::from(curve.name)),
+                ("kty", String::from("EC")),
+                ("x", base64::url_encode(x)),
+                ("y", base64::url_encode(y)),
+            ],
+            Params::Okp { x, .. } => vec![
+                ("crv", String::from(ED25519)),
+                ("kty", String::from("OKP")),
+                ("x", base64::url_encode(x)),
+            ],
+            Params::Rsa { n, e, .. } => vec![
+                ("e", base64::url_encode(e)),
+                ("kty", String::from("RSA")),
+                ("n", base64::url_encode(n)),
+            ],
+            Params::Oct { k } => vec![
+                ("k", base64::url_encode(&k.0)),
+                ("kty", String::from("oct")),
+            ],
+        };
+        digest::digest(digest_alg, write_object(&members).as_bytes())
+    }
+
+    /// Returns the public key in the format used by the corresponding
+    /// `signature::VerificationAlgorithm`: the uncompressed point for `EC`
+    /// keys, the 32-byte public key for `OKP` keys, and the DER-encoded
+    /// `RSAPublicKey` for `RSA` keys.
+    ///
+    /// Fails for `oct` keys.
+    pub fn public_key(&self) -> Result<Vec<u8>, error::Unspecified> {
+        match &self.params {
+            Params::Ec { x, y, .. } => {
+                let mut public_key = Vec::with_capacity(1 + x.len() + y.len());
+                public_key.push(4);
+                public_key.extend_from_slice(x);
+                public_key.extend_from_slice(y);
+                Ok(public_key)
+            }
+            Params::Okp { x, .. } => Ok(x.clone()),
+            Params::Rsa { n, e, .. } => Ok(der_writer::write_all(der::Tag::Sequence, &|output| {
+                write_integer(output, n);
+                write_integer(output, e);
+            })
+            .into()),
+            Params::Oct { .. } => Err(error::Unspecified),
+        }
+    }
+
+    /// Constructs an ECDSA key pair for `alg` from an `EC` JWK.
+    ///
+    /// The curve of the JWK must be the curve of `alg`. The public key is
+    /// verified to be consistent with the private key.
+    pub fn ecdsa_key_pair(
+        &self,
+        alg: &'static signature::EcdsaSigningAlgorithm,
+    ) -> Result<signature::EcdsaKeyPair, KeyRejected> {
+        match &self.params {
+            Params::Ec { curve, d, .. } if curve.id == alg.curve().id => {
+                let d = d.as_ref().ok_or_else(KeyRejected::private_key_is_missing)?;
+                let public_key = self
+                    .public_key()
+                    .map_err(|error::Unspecified| KeyRejected::unexpected_error())?;
+                signature::EcdsaKeyPair::from_private_key_and_public_key(alg, &d.0, &public_key)
+            }
+            _ => Err(KeyRejected::wrong_algorithm()),
+        }
+    }
+
+    /// Constructs an Ed25519 key pair from an `OKP` JWK.
+    ///
+    /// The public key is verified to be consistent with the private key.
+    pub fn ed25519_key_pair(&self) -> Result<signature::Ed25519KeyPair, KeyRejected> {
+        match &self.params {
+            Params::Okp { x, d } => {
+                let d = d.as_ref().ok_or_else(KeyRejected::private_key_is_missing)?;
+                signature::Ed25519KeyPair::from_seed_and_public_key(&d.0, x)
+            }
+            _ => Err(KeyRejected::wrong_algorithm()),
+        }
+    }
+
+    /// Constructs an RSA key pair from an `RSA` JWK.
+    ///
+    /// The JWK must have all the private key members, including the CRT
+    /// parameters. The key is validated as in `RsaKeyPair::from_der()`.
+    pub fn rsa_key_pair(&self) -> Result<signature::RsaKeyPair, KeyRejected> {
+        match &self.params {
+            Params::Rsa { n, e, private } => {
+                let private = private
+                    .as_ref()
+                    .ok_or_else(KeyRejected::private_key_is_missing)?;
+                let rsa_private_key = Secret(
+                    der_writer::write_all(der::Tag::Sequence, &|output| {
+                        // version
+                        der_writer::write_tlv(output, der::Tag::Integer, |output| {
+                            output.write_byte(0)
+                        });
+                        for value in &[
+                            &n[..],
+                            &e[..],
+                            &private.d.0[..],
+                            &private.p.0[..],
+                            &private.q.0[..],
+                            &private.dp.0[..],
+                            &private.dq.0[..],
+                            &private.qi.0[..],
+                        ] {
+                            write_integer(output, value);
+                        }
+                    })
+                    .into(),
+                );
+                signature::RsaKeyPair::from_der(&rsa_private_key.0)
+            }
+            _ => Err(KeyRejected::wrong_algorithm()),
+        }
+    }
+
+    /// Constructs an AEAD sealing key for `algorithm` from an `oct` JWK.
+    pub fn aead_sealing_key(
+        &self,
+        algorithm: &'static aead::Algorithm,
+    ) -> Result<aead::SealingKey, KeyRejected> {
+        aead::SealingKey::new(algorithm, self.symmetric_key()?)
+            .map_err(|error::Unspecified| KeyRejected::invalid_component())
+    }
+
+    /// Constructs an AEAD opening key for `algorithm` from an `oct` JWK.
+    pub fn aead_opening_key(
+        &self,
+        algorithm: &'static aead::Algorithm,
+    ) -> Result<aead::OpeningKey, KeyRejected> {
+        aead::OpeningKey::new(algorithm, self.symmetric_key()?)
+            .map_err(|error::Unspecified| KeyRejected::invalid_component())
+    }
+
+    /// Constructs an HMAC key using `digest_alg` from an `oct` JWK.
+    pub fn hmac_key(
+        &self,
+        digest_alg: &'static digest::Algorithm,
+    ) -> Result<hmac::Key, KeyRejected> {
+        Ok(hmac::Key::new(digest_alg, self.symmetric_key()?))
+    }
+
+    fn symmetric_key(&self) -> Result<&[u8], KeyRejected> {
+        match &self.params {
+            Params::Oct { k } => Ok(&k.0),
+            _ => Err(KeyRejected::wrong_algorithm()),
+        }
+    }
+}
+
+impl core::fmt::Debug for Jwk {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Jwk")
+            .field("kty", &self.key_type())
+            .finish()
+    }
+}
+
+/// The members of a JWK that are used, still base64url-encoded except for
+/// `kty` and `crv`.
+#[derive(Default)]
+struct Members {
+    kty: Option<Secret>,
+    crv: Option<Secret>,
+    x: Option<Secret>,
+    y: Option<Secret>,
+    d: Option<Secret>,
+    n: Option<Secret>,
+    e: Option<Secret>,
+    p: Option<Secret>,
+    q: Option<Secret>,
+    dp: Option<Secret>,
+    dq: Option<Secret>,
+    qi: Option<Secret>,
+    k: Option<Secret>,
+    oth: bool,
+}
+
+impl Members {
+    fn parse(input: &[u8]) -> Result<Self, KeyRejected> {
+        let mut members = Self::default();
+        json::parse_object(input, |name, parser| {
+            let member = match name {
+                b"kty" => &mut members.kty,
+                b"crv" => &mut members.crv,
+                b"x" => &mut members.x,
+                b"y" => &mut members.y,
+                b"d" => &mut members.d,
+                b"n" => &mut members.n,
+                b"e" => &mut members.e,
+                b"p" => &mut members.p,
+                b"q" => &mut members.q,
+                b"dp" => &mut members.dp,
+                b"dq" => &mut members.dq,
+                b"qi" => &mut members.qi,
+                b"k" => &mut members.k,
+                _ => {
+                    if name == b"oth" {
+                        members.oth = true;
+                    }
+                    return parser.skip_value(1);
+                }
+            };
+            *member = Some(Secret(parser.string()?));
+            Ok(())
+        })
+        .map_err(|error::Unspecified| KeyRejected::invalid_encoding())?;
+        Ok(members)
+    }
+}
+
+fn required(member: &Option<Secret>) -> Result<&Secret, KeyRejected> {
+    member.as_ref().ok_or_else(KeyRejected::invalid_encoding)
+}
+
+// Decodes a base64url member.
+fn decode(member: &Secret) -> Result<Secret, KeyRejected> {
+    let encoded = core::str::from_utf8(&member.0).map_err(|_| KeyRejected::invalid_encoding())?;
+    base64::url_decode(encoded)
+        .map(Secret)
+        .map_err(|error::Unspecified| KeyRejected::invalid_encoding())
+}
+
+// Decodes a member that must be exactly `len` bytes long.
+fn fixed_len(member: &Option<Secret>, len: usize) -> Result<Secret, KeyRejected> {
+    let value = decode(required(member)?)?;
+    if value.0.len() != len {
+        return Err(KeyRejected::invalid_encoding());
+    }
+    Ok(value)
+}
+
+fn optional_fixed_len(member: &Option<Secret>, len: usize) -> Result<Option<Secret>, KeyRejected> {
+    match member {
+        Some(_) => fixed_len(member, len).map(Some),
+        None => Ok(None),
+    }
+}
+
+// Decodes a Base64urlUInt member, which must be a positive integer encoded
+// with the minimum number of bytes.
+fn uint(member: &Option<Secret>) -> Result<Secret, KeyRejected> {
+    let value = decode(required(member)?)?;
+    if value.0.first().cloned().unwrap_or(0) == 0 {
+        return Err(KeyRejected::invalid_encoding());
+    }
+    Ok(value)
+}
+
+fn write_integer(output: &mut dyn Accumulator, value: &[u8]) {
+    der_writer::write_positive_integer(
+        output,
+        &Positive::new_non_empty_without_leading_zeros(untrusted::Input::from(value)),
+    );
+}
+
+// The names are fixed and the values are base64url or curve names, so
+// nothing needs to be escaped.
+fn write_object(members: &[(&str, String)]) -> String {
+    let mut json = String::from("{");
+    for (i, (name, value)) in members.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push('"');
+        json.push_str(name);
+        json.push_str("\":\"");
+        json.push_str(value);
+        json.push('"');
+    }
+    json.push('}');
+    json
+}
@@ -0,0 +1,168 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! The KDF chains of the [Double Ratchet algorithm].
+//!
+//! `RootKey::ratchet()` is `KDF_RK`: HKDF-SHA256 with the root key as the
+//! salt, the DH output as the input keying material, and an
+//! application-specific `info`, producing a new root key and a chain key.
+//! `ChainKey::advance()` is `KDF_CK`: HMAC-SHA256 keyed with the chain key,
+//! over the byte 0x01 for the message key and over the byte 0x02 for the
+//! next chain key, as the specification recommends.
+//!
+//! A `MessageKey` is turned into an AEAD key and a nonce with HKDF-SHA256.
+//! Since each message key is used for only one message, the nonce can be
+//! derived along with the key.
+//!
+//! The keys are zeroed when they are dropped. The ratchet methods borrow
+//! the current key, rather than consuming it, so that a caller can discard
+//! the new keys if a message fails to decrypt.
+//!
+//! [Double Ratchet algorithm]: https://signal.org/docs/specifications/doubleratchet/
+
+use crate::{aead, digest, error, hkdf, hmac, zeroize::Zeroize};
+
+/// The length of root keys, chain keys, and message keys.
+pub const KEY_LEN: usize = digest::SHA256_OUTPUT_LEN;
+
+/// A root key (`RK`).
+pub struct RootKey([u8; KEY_LEN]);
+
+/// A sending or receiving chain key (`CK`).
+pub struct ChainKey([u8; KEY_LEN]);
+
+/// A message key (`MK`).
+pub struct MessageKey([u8; KEY_LEN]);
+
+macro_rules! impl_key {
+    ($key:ident) => {
+        impl $key {
+            /// Constructs a key from its `KEY_LEN`-byte value, e.g. one that
+            /// was previously exported or that the key agreement produced.
+            pub fn new(value: &[u8]) -> Result<Self, error::Unspecified> {
+                if value.len() != KEY_LEN {
+                    return Err(error::Unspecified);
+                }
+                let mut key = Self([0u8; KEY_LEN]);
+                key.0.copy_from_slice(value);
+                Ok(key)
+            }
+
+            /// Exports the key's value, e.g. for storing the ratchet state.
+            ///
+            /// The value must be kept secret.
+            #[inline]
+            pub fn export_unchecked(&self) -> &[u8] {
+                &self.0[..]
+            }
+        }
+
+        impl Drop for $key {
+            fn drop(&mut self) {
+                self.0[..].zeroize();
+            }
+        }
+
+        impl core::fmt::Debug for $key {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+                f.debug_struct(stringify!($key)).finish()
+            }
+        }
+    };
+}
+
+impl_key!(RootKey);
+impl_key!(ChainKey);
+impl_key!(MessageKey);
+
+impl RootKey {
+    /// Performs a DH ratchet step (`KDF_RK(rk, dh_out)`), returning the new
+    /// root key and the new chain key.
+    ///
+    /// `info` identifies the application, e.g. `b"MyProtocol"`; it must be
+    /// the same for both parties.
+    pub fn ratchet(&self, dh_output: &[u8], info: &[u8]) -> (RootKey, ChainKey) {
+        let prk = hkdf::Salt::new(&digest::SHA256, &self.0).extract(dh_output);
+        let mut output = [0u8; 2 * KEY_LEN];
+        // `output` is much shorter than the HKDF-Expand limit.
+        prk.fill(info, &mut output).unwrap();
+        let (root_key, chain_key) = output.split_at(KEY_LEN);
+        let keys = (
+            RootKey::new(root_key).unwrap(),
+            ChainKey::new(chain_key).unwrap(),
+        );
+        output[..].zeroize();
+        keys
+    }
+}
+
+impl ChainKey {
+    /// Performs a symmetric-key ratchet step (`KDF_CK(ck)`), returning the
+    /// next chain key and the message key.
+    pub fn advance(&self) -> (ChainKey, MessageKey) {
+        const MESSAGE_KEY_CONSTANT: u8 = 0x01;
+        const CHAIN_KEY_CONSTANT: u8 = 0x02;
+
+        let key = hmac::Key::new(&digest::SHA256, &self.0);
+        let chain_key = hmac::sign(&key, &[CHAIN_KEY_CONSTANT]);
+        let message_key = hmac::sign(&key, &[MESSAGE_KEY_CONSTANT]);
+        (
+            ChainKey::new(chain_key.as_ref()).unwrap(),
+            MessageKey::new(message_key.as_ref()).unwrap(),
+        )
+    }
+}
+
+impl MessageKey {
+    /// Derives the key and nonce for sealing the message.
+    ///
+    /// `info` identifies the application; it must be the same for both
+    /// parties. The key is consumed since it must only be used once.
+    pub fn into_sealing_key(
+        self,
+        algorithm: &'static aead::Algorithm,
+        info: &[u8],
+    ) -> Result<(aead::SealingKey, aead::Nonce), error::Unspecified> {
+        self.derive(algorithm, info, aead::SealingKey::new)
+    }
+
+    /// Derives the key and nonce for opening the message.
+    ///
+    /// `info` must be the same as the sender used.
+    pub fn into_opening_key(
+        self,
+        algorithm: &'static aead::Algorithm,
+        info: &[u8],
+    ) -> Result<(aead::OpeningKey, aead::Nonce), error::Unspecified> {
+        self.derive(algorithm, info, aead::OpeningKey::new)
+    }
+
+    fn derive<K>(
+        &self,
+        algorithm: &'static aead::Algorithm,
+        info: &[u8],
+        new_key: fn(&'static aead::Algorithm, &[u8]) -> Result<K, error::Unspecified>,
+    ) -> Result<(K, aead::Nonce), error::Unspecified> {
+        let prk = hkdf::Salt::new(&digest::SHA256, &[]).extract(&self.0);
+        let mut output = [0u8; aead::MAX_KEY_LEN + aead::NONCE_LEN];
+        let key_len = algorithm.key_len();
+        let output = &mut output[..(key_len + aead::NONCE_LEN)];
+        prk.fill(info, output)?;
+        let (key_bytes, nonce) = output.split_at_mut(key_len);
+        let result = aead::Nonce::try_assume_unique_for_key(nonce)
+            .and_then(|nonce| Ok((new_key(algorithm, key_bytes)?, nonce)));
+        key_bytes.zeroize();
+        result
+    }
+}
@@ -0,0 +1,321 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Encrypted key blobs for storing keys at rest.
+//!
+//! `seal_pkcs8` and `seal_aead_key` encrypt a PKCS#8 private key document
+//! (e.g. from `Ed25519KeyPair::generate_pkcs8()`) or an AEAD key into a
+//! self-describing blob, and `open` decrypts it again. The blob is encrypted
+//! with AES-256-GCM under a key derived from a password with
+//! PBKDF2-HMAC-SHA256 or from a high-entropy secret with HKDF-SHA256.
+//!
+//! A version 1 blob has the following format, with integers in big-endian
+//! byte order. The header is authenticated as the AAD.
+//!
+//! | Offset | Length | Field
+//! |--------|--------|------------------------------------------------------
+//! | 0      | 4      | `RKEY`
+//! | 4      | 1      | Version: 1
+//! | 5      | 1      | KDF: 1 = PBKDF2-HMAC-SHA256, 2 = HKDF-SHA256
+//! | 6      | 4      | PBKDF2 iterations, or 0 for HKDF
+//! | 10     | 1      | Contents: 1 = PKCS#8, 2 = AEAD key
+//! | 11     | 1      | AEAD algorithm of an AEAD key, or 0
+//! | 12     | 16     | KDF salt
+//! | 28     | 12     | AES-256-GCM nonce
+//! | 40     |        | Encrypted contents followed by the tag
+//!
+//! The AEAD algorithms are 1 = AES-128-GCM, 2 = AES-256-GCM,
+//! 3 = ChaCha20-Poly1305, 4 = AES-128-GCM-SIV, and 5 = AES-256-GCM-SIV.
+
+use crate::{aead, digest, error, hkdf, pbkdf2, rand};
+use core::num::NonZeroU32;
+use std::vec::Vec;
+
+/// The key that a blob is encrypted with.
+pub enum WrappingKey<'a> {
+    /// A password, stretched with PBKDF2-HMAC-SHA256.
+    ///
+    /// When sealing, `iterations` is the iteration count. When opening, it is
+    /// the maximum iteration count that is accepted, so that a forged blob
+    /// can't make `open` run for an unbounded amount of time.
+    Password {
+        /// The password.
+        password: &'a [u8],
+
+        /// The iteration count.
+        iterations: NonZeroU32,
+    },
+
+    /// A uniformly random secret, e.g. a key from a KMS, expanded with
+    /// HKDF-SHA256.
+    Secret(&'a [u8]),
+}
+
+impl core::fmt::Debug for WrappingKey<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self {
+            WrappingKey::Password { iterations, .. } => f
+                .debug_struct("Password")
+                .field("iterations", iterations)
+                .finish(),
+            WrappingKey::Secret(_) => f.debug_struct("Secret").finish(),
+        }
+    }
+}
+
+/// The decrypted contents of a blob.
+pub enum Contents {
+    /// A PKCS#8 private key document.
+    Pkcs8(Vec<u8>),
+
+    /// An AEAD key.
+    AeadKey {
+        /// The key's algorithm.
+        algorithm: &'static aead::Algorithm,
+
+        /// The key's value.
+        key_bytes: Vec<u8>,
+    },
+}
+
+impl core::fmt::Debug for Contents {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self {
+            Contents::Pkcs8(_) => f.debug_struct("Pkcs8").finish(),
+            Contents::AeadKey { algorithm, .. } => f
+                .debug_struct("AeadKey")
+                .field("algorithm", algorithm)
+                .finish(),
+        }
+    }
+}
+
+/// Encrypts the PKCS#8 document `pkcs8` into a blob, using a salt and nonce
+/// generated by `rng`.
+pub fn seal_pkcs8(
+    wrapping_key: &WrappingKey,
+    pkcs8: &[u8],
+    rng: &dyn rand::SecureRandom,
+) -> Result<Vec<u8>, error::Unspecified> {
+    seal(wrapping_key, CONTENTS_PKCS8, 0, pkcs8, rng)
+}
+
+/// Encrypts the `algorithm` key `key_bytes` into a blob, using a salt and
+/// nonce generated by `rng`.
+pub fn seal_aead_key(
+    wrapping_key: &WrappingKey,
+    algorithm: &'static aead::Algorithm,
+    key_bytes: &[u8],
+    rng: &dyn rand::SecureRandom,
+) -> Result<Vec<u8>, error::Unspecified> {
+    if key_bytes.len() != algorithm.key_len() {
+        return Err(error::Unspecified);
+    }
+    let id = AEAD_ALGORITHMS
+        .iter()
+        .find(|(_, alg)| *alg == algorithm)
+        .map(|(id, _)| *id)
+        .ok_or(error::Unspecified)?;
+    seal(wrapping_key, CONTENTS_AEAD_KEY, id, key_bytes, rng)
+}
+
+/// Authenticates and decrypts `blob`.
+pub fn open(wrapping_key: &WrappingKey, blob: &[u8]) -> Result<Contents, error::Unspecified> {
+    if blob.len() < HEADER_LEN + TAG_LEN || blob[..MAGIC.len()] != MAGIC[..] {
+        return Err(error::Unspecified);
+    }
+    let (header, ciphertext_and_tag) = blob.split_at(HEADER_LEN);
+    if header[4] != VERSION {
+        return Err(error::Unspecified);
+    }
+    let kdf = Kdf::parse(wrapping_key, header[5], &header[6..10])?;
+    let (contents_type, algorithm_id) = (header[10], header[11]);
+    let salt = &header[12..(12 + SALT_LEN)];
+    let mut nonce = [0u8; aead::NONCE_LEN];
+    nonce.copy_from_slice(&header[(12 + SALT_LEN)..]);
+
+    let key = kdf.derive(salt);
+    let key = aead::OpeningKey::new(&aead::AES_256_GCM, &key)?;
+    let mut in_out = Vec::from(ciphertext_and_tag);
+    let plaintext_len = aead::open_in_place(
+        &key,
+        aead::Nonce::assume_unique_for_key(nonce),
+        aead::Aad::from(header),
+        0,
+        &mut in_out,
+    )?
+    .len();
+    in_out.truncate(plaintext_len);
+
+    match (contents_type, algorithm_id) {
+        (CONTENTS_PKCS8, 0) => Ok(Contents::Pkcs8(in_out)),
+        (CONTENTS_AEAD_KEY, id) => {
+            let algorithm = AEAD_ALGORITHMS
+                .iter()
+                .find(|(alg_id, _)| *alg_id == id)
+                .map(|(_, alg)| *alg)
+                .ok_or(error::Unspecified)?;
+            if in_out.len() != algorithm.key_len() {
+                return Err(error::Unspecified);
+            }
+            Ok(Contents::AeadKey {
+                algorithm,
+                key_bytes: in_out,
+            })
+        }
+        _ => Err(error::Unspecified),
+    }
+}
+
+fn seal(
+    wrapping_key: &WrappingKey,
+    contents_type: u8,
+    algorithm_id: u8,
+    contents: &[u8],
+    rng: &dyn rand::SecureRandom,
+) -> Result<Vec<u8>, error::Unspecified> {
+    let mut salt_and_nonce = [0u8; SALT_LEN + aead::NONCE_LEN];
+    rng.fill(&mut salt_and_nonce)?;
+    let (salt, nonce) = salt_and_nonce.split_at(SALT_LEN);
+
+    let kdf = Kdf::from(wrapping_key);
+    let (kdf_id, iterations) = match kdf {
+        Kdf::Pbkdf2 { iterations, .. } => (KDF_PBKDF2, iterations.get()),
+        Kdf::Hkdf { .. } => (KDF_HKDF, 0),
+    };
+
+    let mut blob = Vec::with_capacity(HEADER_LEN + contents.len() + TAG_LEN);
+    blob.extend_from_slice(MAGIC);
+    blob.push(VERSION);
+    blob.push(kdf_id);
+    blob.extend_from_slice(&iterations.to_be_bytes());
+    blob.push(contents_type);
+    blob.push(algorithm_id);
+    blob.extend_from_slice(&salt_and_nonce);
+    debug_assert_eq!(blob.len(), HEADER_LEN);
+    blob.extend_from_slice(contents);
+    blob.extend_from_slice(&[0; TAG_LEN]);
+
+    let key = kdf.derive(salt);
+    let key = aead::SealingKey::new(&aead::AES_256_GCM, &key)?;
+    let (header, in_out) = blob.split_at_mut(HEADER_LEN);
+    let _ = aead::seal_in_place(
+        &key,
+        aead::Nonce::try_assume_unique_for_key(nonce)?,
+        aead::Aad::from(&header[..]),
+        in_out,
+        TAG_LEN,
+    )?;
+    Ok(blob)
+}
+
+enum Kdf<'a> {
+    Pbkdf2 {
+        password: &'a [u8],
+        iterations: NonZeroU32,
+    },
+    Hkdf {
+        secret: &'a [u8],
+    },
+}
+
+impl<'a> From<&WrappingKey<'a>> for Kdf<'a> {
+    fn from(wrapping_key: &WrappingKey<'a>) -> Self {
+        match *wrapping_key {
+            WrappingKey::Password {
+                password,
+                iterations,
+            } => Kdf::Pbkdf2 {
+                password,
+                iterations,
+            },
+            WrappingKey::Secret(secret) => Kdf::Hkdf { secret },
+        }
+    }
+}
+
+impl<'a> Kdf<'a> {
+    // Checks that the KDF in a blob's header matches `wrapping_key`.
+    fn parse(
+        wrapping_key: &WrappingKey<'a>,
+        kdf_id: u8,
+        iterations: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        let mut iterations_bytes = [0u8; 4];
+        iterations_bytes.copy_from_slice(iterations);
+        let iterations = u32::from_be_bytes(iterations_bytes);
+        match (Kdf::from(wrapping_key), kdf_id) {
+            (
+                Kdf::Pbkdf2 {
+                    password,
+                    iterations: max_iterations,
+                },
+                KDF_PBKDF2,
+            ) => {
+                let iterations = NonZeroU32::new(iterations).ok_or(error::Unspecified)?;
+                if iterations > max_iterations {
+                    return Err(error::Unspecified);
+                }
+                Ok(Kdf::Pbkdf2 {
+                    password,
+                    iterations,
+                })
+            }
+            (kdf @ Kdf::Hkdf { .. }, KDF_HKDF) if iterations == 0 => Ok(kdf),
+            _ => Err(error::Unspecified),
+        }
+    }
+
+    fn derive(&self, salt: &[u8]) -> [u8; WRAPPING_KEY_LEN] {
+        let mut key = [0u8; WRAPPING_KEY_LEN];
+        match self {
+            Kdf::Pbkdf2 {
+                password,
+                iterations,
+            } => pbkdf2::derive(&digest::SHA256, *iterations, salt, password, &mut key),
+            Kdf::Hkdf { secret } => hkdf::Salt::new(&digest::SHA256, salt)
+                .extract(secret)
+                .expand(HKDF_INFO)
+                .fill(&mut key)
+                .unwrap(),
+        }
+        key
+    }
+}
+
+const MAGIC: &[u8; 4] = b"RKEY";
+const VERSION: u8 = 1;
+
+const KDF_PBKDF2: u8 = 1;
+const KDF_HKDF: u8 = 2;
+const HKDF_INFO: &[u8] = b"ring keystore v1";
+
+const CONTENTS_PKCS8: u8 = 1;
+const CONTENTS_AEAD_KEY: u8 = 2;
+
+static AEAD_ALGORITHMS: &[(u8, &aead::Algorithm)] = &[
+    (1, &aead::AES_128_GCM),
+    (2, &aead::AES_256_GCM),
+    #[cfg(feature = "aead-chacha")]
+    (3, &aead::CHACHA20_POLY1305),
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    (4, &aead::AES_128_GCM_SIV),
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    (5, &aead::AES_256_GCM_SIV),
+];
+
+const SALT_LEN: usize = 16;
+const HEADER_LEN: usize = 12 + SALT_LEN + aead::NONCE_LEN;
+const WRAPPING_KEY_LEN: usize = 32;
+const TAG_LEN: usize = 16;
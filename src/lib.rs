@@ -22,6 +22,18 @@
 //! <table>
 //! <tr><th>Feature
 //!     <th>Description
+//! <tr><td><code>aead-aes-gcm-siv (default)</code>
+//!     <td>Enable <code>aead::AES_128_GCM_SIV</code>,
+//!         <code>aead::AES_256_GCM_SIV</code>, the multi-part
+//!         <code>aead::SealingContext</code> and
+//!         <code>aead::OpeningContext</code>, AEAD key serialization, and the
+//!         <code>envelope</code> module.
+//! <tr><td><code>aead-chacha (default)</code>
+//!     <td>Enable <code>aead::CHACHA20_POLY1305</code>,
+//!         <code>aead::XCHACHA20_POLY1305</code>, the
+//!         <code>poly1305</code> and <code>stream::chacha20</code> modules,
+//!         and the ChaCha20-Poly1305 suites of <code>hpke</code>,
+//!         <code>noise</code>, and <code>paseto</code>.
 //! <tr><td><code>dev_urandom_fallback (default)</code>
 //!     <td>This is only applicable to Linux. On Linux, by default,
 //!         <code>ring::rand::SystemRandom</code> will fall back to reading
@@ -30,8 +42,109 @@
 //!         <code>dev_urandom_fallback</code> feature is disabled, such
 //!         fallbacks will not occur. See the documentation for
 //!         <code>rand::SystemRandom</code> for more details.
+//! <tr><td><code>rsa (default)</code>
+//!     <td>Enable RSA signing and verification, and the <code>cms</code>,
+//!         <code>ct</code>, <code>dnssec</code>, <code>ssh</code>,
+//!         <code>webauthn</code>, and <code>x509</code> modules, which depend
+//!         on it. Implies <code>use_heap</code>.
+//! <tr><td><code>sig-ed25519 (default)</code>
+//!     <td>Enable <code>signature::Ed25519KeyPair</code> and
+//!         <code>signature::ED25519</code>, Ed25519 keys in the
+//!         <code>cose</code>, <code>dnssec</code>, <code>hd</code>,
+//!         <code>ssh</code>, <code>sshsig</code>, <code>webauthn</code>, and
+//!         <code>x509</code> modules, and the <code>minisign</code> and
+//!         <code>paseto</code> modules.
 //! <tr><td><code>use_heap (default)</code>
-//!     <td>Enable features that require use of the heap, RSA in particular.
+//!     <td>Enable features that require use of the heap.
+//!         Without it, *ring* doesn't use `std`; modules such as
+//!         <code>aead</code>, <code>digest</code>, <code>hmac</code>, and
+//!         <code>hkdf</code> work in <code>#![no_std]</code> environments.
+//! <tr><td><code>aead_parallel</code>
+//!     <td>Enable <code>aead::seal_in_place_parallel()</code> and
+//!         <code>aead::open_in_place_parallel()</code>, which seal and open
+//!         long AES-GCM-SIV messages on several threads. Implies
+//!         <code>aead-aes-gcm-siv</code> and <code>use_heap</code>.
+//! <tr><td><code>async</code>
+//!     <td>Enable <code>signature::AsyncSigner</code>, for signing with keys
+//!         that are accessed over the network, and
+//!         <code>rand::fill_async()</code> and the <code>generate_pkcs8_async()</code>
+//!         key generation functions, which don't block while the system random
+//!         number generator is not yet ready. Implies <code>use_heap</code>.
+//! <tr><td><code>audit</code>
+//!     <td>Enable the <code>audit</code> module, which allows registering a
+//!         hook that is called for every AEAD, HMAC, and signature
+//!         operation.
+//! <tr><td><code>blake3_parallel</code>
+//!     <td>Enable <code>blake3::Context::update_parallel()</code>, which
+//!         hashes long inputs on several threads. Implies
+//!         <code>use_heap</code>.
+//! <tr><td><code>block_cipher</code>
+//!     <td>Enable the <code>cipher</code> module, which exposes single-block
+//!         AES encryption and decryption for building other constructions.
+//!         This is a hazardous low-level API; use <code>aead</code> for
+//!         encryption.
+//! <tr><td><code>intrinsics</code>
+//!     <td>On x86-64, use implementations of the AES-NI and CLMUL code for
+//!         AES-GCM and AES-GCM-SIV that are written in Rust with
+//!         <code>core::arch</code> intrinsics, instead of the assembly
+//!         language implementations generated by perlasm, and leave those
+//!         assembly language files out of the build. AES-GCM then doesn't
+//!         use the AVX GHASH and integrated AES-GCM code, which only exist
+//!         in assembly language form. AES-GCM-SIV uses intrinsics versions
+//!         of its AVX and AES-NI kernels; on CPUs without AVX and AES-NI it
+//!         uses the portable implementation, on top of the intrinsics-based
+//!         AES and CLMUL primitives where the CPU has them. The assembly
+//!         language code for other algorithms, e.g. SHA-2,
+//!         ChaCha20-Poly1305, P-256, and RSA, is still used, so building
+//!         from a Git checkout still runs Perl.
+//! <tr><td><code>jose</code>
+//!     <td>Enable the <code>jose</code> module, which implements JSON Web
+//!         Signatures (JWS/JWT). Implies <code>rsa</code> and
+//!         <code>sig-ed25519</code>.
+//! <tr><td><code>jwk</code>
+//!     <td>Enable the <code>jwk</code> module, which converts keys to and
+//!         from JSON Web Keys. Implies <code>rsa</code> and
+//!         <code>sig-ed25519</code>.
+//! <tr><td><code>memory_locking</code>
+//!     <td>Enable the <code>secret</code> module, and keep the expanded AEAD
+//!         keys and RSA private keys in locked memory that is left out of
+//!         core dumps and zeroized when it is freed. Implies
+//!         <code>use_heap</code>.
+//! <tr><td><code>modular_arithmetic</code>
+//!     <td>Enable the <code>modular</code> module, which exposes low-level
+//!         constant-time modular arithmetic with large moduli. Implies
+//!         <code>rsa</code>.
+//! <tr><td><code>oprf</code>
+//!     <td>Enable the <code>oprf</code> module, which implements oblivious
+//!         pseudorandom functions. Implies <code>p256_arithmetic</code> and
+//!         <code>use_heap</code>.
+//! <tr><td><code>pem</code>
+//!     <td>Enable the <code>pem</code> module, which parses and encodes PEM
+//!         blocks of keys. Implies <code>use_heap</code>.
+//! <tr><td><code>p256_arithmetic</code>
+//!     <td>Enable the <code>p256</code> module, which exposes low-level
+//!         P-256 scalar and point arithmetic.
+//! <tr><td><code>rsa_parallel</code>
+//!     <td>Enable <code>signature::RsaKeyPair::sign_parallel()</code>, which
+//!         does the two private key exponentiations of RSA signing on two
+//!         threads. Implies <code>rsa</code>.
+//! <tr><td><code>small_stack</code>
+//!     <td>Use less stack space in the AES-GCM-SIV implementations, at the
+//!         cost of speed, for targets with small (e.g. 4KB) thread stacks.
+//! <tr><td><code>srp</code>
+//!     <td>Enable the <code>srp</code> module, which implements the SRP-6a
+//!         password-authenticated key exchange for interoperating with
+//!         existing deployments. Implies <code>modular_arithmetic</code>.
+//! <tr><td><code>testing-internals</code>
+//!     <td>Enable the <code>aead::internals</code> module, which exposes the
+//!         individual stages of AES-GCM-SIV for property-based testing and
+//!         differential fuzzing. It is not a stable API. Implies
+//!         <code>aead-aes-gcm-siv</code>.
+//! <tr><td><code>testvec</code>
+//!     <td>Enable the <code>testvec</code> module, which runs Wycheproof and
+//!         <i>ring</i> test vectors against AEAD, signature, and key
+//!         agreement algorithms and reports the results. Implies
+//!         <code>use_heap</code>.
 //! </table>
 
 #![doc(html_root_url = "https://briansmith.org/rustdoc/")]
@@ -81,31 +194,169 @@ pub mod test;
 mod arithmetic;
 
 pub mod aead;
+pub mod aes_cmac;
 pub mod agreement;
 
+#[cfg(feature = "use_heap")]
+pub mod argon2;
+
+#[cfg(feature = "audit")]
+pub mod audit;
+
+#[cfg(feature = "use_heap")]
+mod base64;
+
 mod bits;
 
+pub mod blake2;
+pub mod blake3;
+
 pub(crate) mod c;
+
+#[cfg(feature = "use_heap")]
+mod cbor;
+
+#[cfg(feature = "block_cipher")]
+pub mod cipher;
+
+#[cfg(feature = "rsa")]
+pub mod cms;
 pub mod constant_time;
 
+#[cfg(feature = "use_heap")]
+pub mod cose;
+
+#[cfg(feature = "rsa")]
+pub mod ct;
+
 pub mod io;
 
+#[cfg(feature = "jose")]
+pub mod jose;
+
+#[cfg(any(feature = "jose", feature = "jwk", feature = "testvec"))]
+mod json;
+
+#[cfg(feature = "jwk")]
+pub mod jwk;
+
+pub mod kdf;
+
+#[cfg(feature = "use_heap")]
+pub mod keystore;
+
 mod cpu;
 pub mod digest;
+
+#[cfg(feature = "rsa")]
+pub mod dnssec;
+
+pub mod drbg;
+
 mod ec;
 mod endian;
+
+#[cfg(all(feature = "use_heap", feature = "aead-aes-gcm-siv"))]
+pub mod envelope;
+
 pub mod error;
+
+#[cfg(feature = "use_heap")]
+pub mod fpe;
+
+pub mod ghash;
+
+#[cfg(feature = "use_heap")]
+pub mod hash_to_curve;
+
+pub mod hd;
 pub mod hkdf;
 pub mod hmac;
+pub mod hpke;
 mod limb;
+pub mod merkle;
+
+#[cfg(all(feature = "use_heap", feature = "sig-ed25519"))]
+pub mod minisign;
+
+#[cfg(feature = "modular_arithmetic")]
+pub mod modular;
+
+pub mod noise;
+
+#[cfg(feature = "oprf")]
+pub mod oprf;
+
+pub mod otp;
+
+#[cfg(feature = "p256_arithmetic")]
+pub mod p256;
+
+#[cfg(feature = "pem")]
+pub mod pem;
+
+#[cfg(feature = "use_heap")]
+pub mod pake;
+
+#[cfg(all(feature = "use_heap", feature = "aead-chacha", feature = "sig-ed25519"))]
+pub mod paseto;
+
 pub mod pbkdf2;
+
+#[cfg(feature = "use_heap")]
+pub mod pkcs10;
+
 pub mod pkcs8;
+pub mod policy;
+
+#[cfg(feature = "aead-chacha")]
+pub mod poly1305;
+pub mod polyval;
 pub mod rand;
 
-#[cfg(feature = "use_heap")]
+#[cfg(feature = "rsa")]
 mod rsa;
 
+#[cfg(feature = "use_heap")]
+pub mod scrypt;
+
+#[cfg(feature = "memory_locking")]
+pub mod secret;
+
+pub mod self_test;
+
+#[cfg(feature = "use_heap")]
+pub mod shamir;
+
 pub mod signature;
+mod spki;
+
+#[cfg(feature = "srp")]
+pub mod srp;
+
+#[cfg(feature = "rsa")]
+pub mod ssh;
+
+#[cfg(feature = "use_heap")]
+pub mod sshsig;
+
+pub mod stream;
+
+#[cfg(feature = "testvec")]
+pub mod testvec;
+
+pub mod tls13;
+
+#[cfg(feature = "use_heap")]
+pub mod vrf;
+
+#[cfg(feature = "rsa")]
+pub mod webauthn;
+
+#[cfg(feature = "rsa")]
+pub mod x509;
+
+mod zeroize;
 
 mod sealed {
     /// Traits that are designed to only be implemented internally in *ring*.
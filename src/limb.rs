@@ -21,10 +21,10 @@
 use crate::{c, error};
 use untrusted;
 
-#[cfg(any(test, feature = "use_heap"))]
+#[cfg(any(test, feature = "rsa"))]
 use crate::bits;
 
-#[cfg(feature = "use_heap")]
+#[cfg(feature = "rsa")]
 use core::num::Wrapping;
 
 // XXX: Not correct for x32 ABIs.
@@ -77,7 +77,7 @@ pub fn limbs_less_than_limbs_vartime(a: &[Limb], b: &[Limb]) -> bool {
 }
 
 #[inline]
-#[cfg(feature = "use_heap")]
+#[cfg(feature = "rsa")]
 pub fn limbs_less_than_limb_constant_time(a: &[Limb], b: Limb) -> LimbMask {
     unsafe { LIMBS_less_than_limb(a.as_ptr(), b, a.len()) }
 }
@@ -87,13 +87,13 @@ pub fn limbs_are_zero_constant_time(limbs: &[Limb]) -> LimbMask {
     unsafe { LIMBS_are_zero(limbs.as_ptr(), limbs.len()) }
 }
 
-#[cfg(any(test, feature = "use_heap"))]
+#[cfg(any(test, feature = "rsa"))]
 #[inline]
 pub fn limbs_are_even_constant_time(limbs: &[Limb]) -> LimbMask {
     unsafe { LIMBS_are_even(limbs.as_ptr(), limbs.len()) }
 }
 
-#[cfg(any(test, feature = "use_heap"))]
+#[cfg(any(test, feature = "rsa"))]
 #[inline]
 pub fn limbs_equal_limb_constant_time(a: &[Limb], b: Limb) -> LimbMask {
     unsafe { LIMBS_equal_limb(a.as_ptr(), b, a.len()) }
@@ -106,7 +106,7 @@ pub fn limbs_equal_limb_constant_time(a: &[Limb], b: Limb) -> LimbMask {
 // with respect to `a.len()` or the value of the result or the value of the
 // most significant bit (It's 1, unless the input is zero, in which case it's
 // zero.)
-#[cfg(any(test, feature = "use_heap"))]
+#[cfg(any(test, feature = "rsa"))]
 pub fn limbs_minimal_bits(a: &[Limb]) -> bits::BitLength {
     for num_limbs in (1..=a.len()).rev() {
         let high_limb = a[num_limbs - 1];
@@ -239,20 +239,17 @@ pub fn parse_big_endian_and_pad_consttime(
     })
 }
 
+/// Writes the big-endian encoding of `limbs` to `out`. `out` may be shorter
+/// than `limbs`, e.g. for P-521, in which case the value must fit in `out`.
 pub fn big_endian_from_limbs(limbs: &[Limb], out: &mut [u8]) {
-    let num_limbs = limbs.len();
-    let out_len = out.len();
-    assert_eq!(out_len, num_limbs * LIMB_BYTES);
-    for i in 0..num_limbs {
-        let mut limb = limbs[i];
-        for j in 0..LIMB_BYTES {
-            out[((num_limbs - i - 1) * LIMB_BYTES) + (LIMB_BYTES - j - 1)] = (limb & 0xff) as u8;
-            limb >>= 8;
-        }
+    assert!(out.len() <= limbs.len() * LIMB_BYTES);
+    for (i, out) in out.iter_mut().rev().enumerate() {
+        let limb = limbs[i / LIMB_BYTES];
+        *out = (limb >> (8 * (i % LIMB_BYTES))) as u8;
     }
 }
 
-#[cfg(feature = "use_heap")]
+#[cfg(feature = "rsa")]
 pub type Window = Limb;
 
 /// Processes `limbs` as a sequence of 5-bit windows, folding the windows from
@@ -267,7 +264,7 @@ pub type Window = Limb;
 /// channels as long as `init` and `fold` are side-channel free.
 ///
 /// Panics if `limbs` is empty.
-#[cfg(feature = "use_heap")]
+#[cfg(feature = "rsa")]
 pub fn fold_5_bit_windows<R, I: FnOnce(Window) -> R, F: Fn(R, Window) -> R>(
     limbs: &[Limb],
     init: I,
@@ -333,16 +330,16 @@ pub fn fold_5_bit_windows<R, I: FnOnce(Window) -> R, F: Fn(R, Window) -> R>(
 }
 
 extern "C" {
-    #[cfg(any(test, feature = "use_heap"))]
+    #[cfg(any(test, feature = "rsa"))]
     fn LIMB_shr(a: Limb, shift: c::size_t) -> Limb;
 
-    #[cfg(any(test, feature = "use_heap"))]
+    #[cfg(any(test, feature = "rsa"))]
     fn LIMBS_are_even(a: *const Limb, num_limbs: c::size_t) -> LimbMask;
     fn LIMBS_are_zero(a: *const Limb, num_limbs: c::size_t) -> LimbMask;
-    #[cfg(any(test, feature = "use_heap"))]
+    #[cfg(any(test, feature = "rsa"))]
     fn LIMBS_equal_limb(a: *const Limb, b: Limb, num_limbs: c::size_t) -> LimbMask;
     fn LIMBS_less_than(a: *const Limb, b: *const Limb, num_limbs: c::size_t) -> LimbMask;
-    #[cfg(feature = "use_heap")]
+    #[cfg(feature = "rsa")]
     fn LIMBS_less_than_limb(a: *const Limb, b: Limb, num_limbs: c::size_t) -> LimbMask;
     fn LIMBS_reduce_once(r: *mut Limb, m: *const Limb, num_limbs: c::size_t);
 }
@@ -453,7 +450,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "use_heap")]
+    #[cfg(feature = "rsa")]
     fn test_limbs_less_than_limb_constant_time() {
         static LESSER: &[(&[Limb], Limb)] = &[
             (&[0], 1),
@@ -558,6 +555,21 @@ mod tests {
         assert_eq!(&out[..], &expected[..]);
     }
 
+    #[test]
+    fn test_big_endian_from_limbs_shorter_output() {
+        #[cfg(target_pointer_width = "32")]
+        let limbs = [0xbccddeef, 0x89900aab, 0x00000034];
+
+        #[cfg(target_pointer_width = "64")]
+        let limbs = [0x89900aab_bccddeef, 0x00000000_00000034];
+
+        let expected = [0x34, 0x89, 0x90, 0x0a, 0xab, 0xbc, 0xcd, 0xde, 0xef];
+
+        let mut out = [0xabu8; 9];
+        big_endian_from_limbs(&limbs[..], &mut out);
+        assert_eq!(&out[..], &expected[..]);
+    }
+
     #[should_panic]
     #[test]
     fn test_big_endian_from_limbs_fewer_limbs() {
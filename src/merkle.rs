@@ -0,0 +1,197 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Binary Merkle trees, as used by Certificate Transparency.
+//!
+//! The trees are those of [RFC 6962 Section 2.1]: a leaf is hashed as
+//! `H(0x00 || leaf)` and an interior node as `H(0x01 || left || right)`, so
+//! that a leaf can't be passed off as an interior node. A tree of `n > 1`
+//! leaves is split after the first `k` leaves, where `k` is the largest power
+//! of two less than `n`. The hash of the empty tree is `H("")`.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::{digest, merkle};
+//!
+//! let leaves: &[&[u8]] = &[b"a", b"b", b"c"];
+//! let root = merkle::root(&digest::SHA256, leaves);
+//!
+//! let proof = merkle::inclusion_proof(&digest::SHA256, leaves, 2)?;
+//! merkle::verify_inclusion(&digest::SHA256, b"c", 2, leaves.len(), &proof, root.as_ref())?;
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [RFC 6962 Section 2.1]: https://tools.ietf.org/html/rfc6962#section-2.1
+
+use crate::{digest, error};
+
+#[cfg(feature = "use_heap")]
+use std::vec::Vec;
+
+/// The hash of the leaf `leaf`, `H(0x00 || leaf)`.
+pub fn leaf_hash(algorithm: &'static digest::Algorithm, leaf: &[u8]) -> digest::Digest {
+    Hasher::new(algorithm).leaf(leaf)
+}
+
+/// The hash of the interior node with the children `left` and `right`,
+/// `H(0x01 || left || right)`.
+pub fn node_hash(
+    algorithm: &'static digest::Algorithm,
+    left: &[u8],
+    right: &[u8],
+) -> digest::Digest {
+    Hasher::new(algorithm).node(left, right)
+}
+
+/// The root hash of the tree with the leaves `leaves`, in order.
+pub fn root<L: AsRef<[u8]>>(algorithm: &'static digest::Algorithm, leaves: &[L]) -> digest::Digest {
+    if leaves.is_empty() {
+        return digest::digest(algorithm, &[]);
+    }
+    Hasher::new(algorithm).subtree(leaves)
+}
+
+/// The inclusion proof, or audit path, for the leaf at `index` in the tree
+/// with the leaves `leaves`: the hashes of the siblings of the nodes on the
+/// path from the leaf to the root, starting at the leaf.
+///
+/// Fails if `index` is out of range.
+#[cfg(feature = "use_heap")]
+pub fn inclusion_proof<L: AsRef<[u8]>>(
+    algorithm: &'static digest::Algorithm,
+    leaves: &[L],
+    index: usize,
+) -> Result<Vec<digest::Digest>, error::Unspecified> {
+    if index >= leaves.len() {
+        return Err(error::Unspecified);
+    }
+    let hasher = Hasher::new(algorithm);
+    let mut proof = Vec::new();
+    let mut leaves = leaves;
+    let mut index = index;
+    // The siblings are found from the root down, so they are collected in
+    // reverse order.
+    while leaves.len() > 1 {
+        let (left, right) = leaves.split_at(split_point(leaves.len()));
+        if index < left.len() {
+            proof.push(hasher.subtree(right));
+            leaves = left;
+        } else {
+            proof.push(hasher.subtree(left));
+            index -= left.len();
+            leaves = right;
+        }
+    }
+    proof.reverse();
+    Ok(proof)
+}
+
+/// Verifies that `proof` proves that `leaf` is the leaf at `index` in the
+/// tree of `tree_size` leaves with the root hash `root`.
+///
+/// This is the verification algorithm of [RFC 9162 Section 2.1.3.2], which
+/// also applies to the proofs of RFC 6962 logs.
+///
+/// [RFC 9162 Section 2.1.3.2]: https://tools.ietf.org/html/rfc9162#section-2.1.3.2
+pub fn verify_inclusion<P: AsRef<[u8]>>(
+    algorithm: &'static digest::Algorithm,
+    leaf: &[u8],
+    index: usize,
+    tree_size: usize,
+    proof: &[P],
+    root: &[u8],
+) -> Result<(), error::Unspecified> {
+    if index >= tree_size {
+        return Err(error::Unspecified);
+    }
+    let hasher = Hasher::new(algorithm);
+    let mut f_n = index;
+    let mut s_n = tree_size - 1;
+    let mut r = hasher.leaf(leaf);
+    for p in proof {
+        let p = p.as_ref();
+        if s_n == 0 || p.len() != algorithm.output_len {
+            return Err(error::Unspecified);
+        }
+        if f_n & 1 == 1 || f_n == s_n {
+            r = hasher.node(p, r.as_ref());
+            while f_n & 1 == 0 && f_n != 0 {
+                f_n >>= 1;
+                s_n >>= 1;
+            }
+        } else {
+            r = hasher.node(r.as_ref(), p);
+        }
+        f_n >>= 1;
+        s_n >>= 1;
+    }
+    if s_n != 0 || r.as_ref() != root {
+        return Err(error::Unspecified);
+    }
+    Ok(())
+}
+
+// Computes many leaf and node hashes with the same algorithm. The contexts
+// have already been fed the prefixes and are cloned for each hash.
+struct Hasher {
+    leaf: digest::Context,
+    node: digest::Context,
+}
+
+impl Hasher {
+    fn new(algorithm: &'static digest::Algorithm) -> Self {
+        let mut leaf = digest::Context::new(algorithm);
+        leaf.update(&[LEAF_PREFIX]);
+        let mut node = digest::Context::new(algorithm);
+        node.update(&[NODE_PREFIX]);
+        Self { leaf, node }
+    }
+
+    fn leaf(&self, leaf: &[u8]) -> digest::Digest {
+        let mut ctx = self.leaf.clone();
+        ctx.update(leaf);
+        ctx.finish()
+    }
+
+    fn node(&self, left: &[u8], right: &[u8]) -> digest::Digest {
+        let mut ctx = self.node.clone();
+        ctx.update(left);
+        ctx.update(right);
+        ctx.finish()
+    }
+
+    // The root hash of a non-empty subtree.
+    fn subtree<L: AsRef<[u8]>>(&self, leaves: &[L]) -> digest::Digest {
+        if leaves.len() == 1 {
+            return self.leaf(leaves[0].as_ref());
+        }
+        let (left, right) = leaves.split_at(split_point(leaves.len()));
+        self.node(self.subtree(left).as_ref(), self.subtree(right).as_ref())
+    }
+}
+
+// The largest power of two less than `n`, for `n > 1`.
+fn split_point(n: usize) -> usize {
+    debug_assert!(n > 1);
+    let mut k = 1;
+    while k * 2 < n {
+        k *= 2;
+    }
+    k
+}
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
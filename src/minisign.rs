@@ -0,0 +1,312 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Minisign and OpenBSD signify signatures.
+//!
+//! Both tools use Ed25519 and share the same public key format: an untrusted
+//! comment line followed by the base64 encoding of the algorithm, an 8-byte
+//! key ID, and the public key.
+//!
+//! A [signify] signature file has the same shape, and its signature is over
+//! the message itself. A [minisign] signature file is followed by a trusted
+//! comment line and a global signature over the signature and the trusted
+//! comment, so the trusted comment can't be modified. `sign` creates
+//! signatures over the BLAKE2b-512 digest of the message, like `minisign -S`
+//! does by default; `verify` also accepts signatures over the message itself.
+//!
+//! Secret key files are encrypted with a password-based scheme and aren't
+//! supported; signing uses an Ed25519 `signature::Signer`, such as an
+//! `Ed25519KeyPair`, and a key ID directly.
+//!
+//! [minisign]: https://jedisct1.github.io/minisign/
+//! [signify]: https://man.openbsd.org/signify
+
+use crate::{
+    base64,
+    digest::blake2b,
+    error,
+    polyfill::convert::*,
+    rand,
+    signature::{self, KeyPair},
+};
+use std::{string::String, vec::Vec};
+
+/// The length of a key ID.
+pub const KEY_ID_LEN: usize = 8;
+
+/// A public key.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PublicKey {
+    key_id: [u8; KEY_ID_LEN],
+    public_key: [u8; PUBLIC_KEY_LEN],
+}
+
+impl PublicKey {
+    /// Constructs the public key of `key_pair`, identified by `key_id`.
+    pub fn new(key_pair: &signature::Ed25519KeyPair, key_id: &[u8; KEY_ID_LEN]) -> Self {
+        let mut public_key = [0u8; PUBLIC_KEY_LEN];
+        public_key.copy_from_slice(key_pair.public_key().as_ref());
+        Self {
+            key_id: *key_id,
+            public_key,
+        }
+    }
+
+    /// Parses a public key file.
+    pub fn from_file_contents(contents: &str) -> Result<Self, error::Unspecified> {
+        let mut lines = Lines::new(contents)?;
+        let _ = lines.untrusted_comment()?;
+        let key = lines.base64()?;
+        lines.end()?;
+
+        let (algorithm, key_id, public_key) = parse_blob(&key)?;
+        if algorithm != ALGORITHM_ED25519 {
+            return Err(error::Unspecified);
+        }
+        let public_key: &[u8; PUBLIC_KEY_LEN] = public_key.try_into_()?;
+        Ok(Self {
+            key_id,
+            public_key: *public_key,
+        })
+    }
+
+    /// Serializes the public key as a public key file with the given
+    /// untrusted comment.
+    pub fn to_file_contents(&self, untrusted_comment: &str) -> Result<String, error::Unspecified> {
+        let mut out = String::new();
+        push_line(&mut out, UNTRUSTED_COMMENT_PREFIX, untrusted_comment)?;
+        let key = serialize_blob(ALGORITHM_ED25519, &self.key_id, &self.public_key);
+        push_line(&mut out, "", &base64::encode(&key))?;
+        Ok(out)
+    }
+
+    /// The key ID.
+    #[inline]
+    pub fn key_id(&self) -> &[u8; KEY_ID_LEN] {
+        &self.key_id
+    }
+
+    /// The Ed25519 public key.
+    #[inline]
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    fn verify_signature(&self, msg: &[u8], signature: &[u8]) -> Result<(), error::Unspecified> {
+        signature::UnparsedPublicKey::new(&signature::ED25519, &self.public_key)
+            .verify(msg, signature)
+    }
+}
+
+/// Signs `message` with `key_pair`, which must support
+/// `SignatureScheme::ED25519`, returning a minisign signature file.
+///
+/// The signature is over the BLAKE2b-512 digest of `message`. The comments
+/// must not contain line breaks.
+pub fn sign(
+    key_pair: &dyn signature::Signer,
+    key_id: &[u8; KEY_ID_LEN],
+    message: &[u8],
+    untrusted_comment: &str,
+    trusted_comment: &str,
+) -> Result<String, error::Unspecified> {
+    let digest = blake2b::digest(&[], blake2b::MAX_OUTPUT_LEN, message);
+    let signature = sign_ed25519(key_pair, digest.as_ref())?;
+
+    let mut global_signed = Vec::with_capacity(SIGNATURE_LEN + trusted_comment.len());
+    global_signed.extend_from_slice(signature.as_ref());
+    global_signed.extend_from_slice(trusted_comment.as_bytes());
+    let global_signature = sign_ed25519(key_pair, &global_signed)?;
+
+    let mut out = String::new();
+    push_line(&mut out, UNTRUSTED_COMMENT_PREFIX, untrusted_comment)?;
+    let blob = serialize_blob(ALGORITHM_ED25519_PREHASHED, key_id, signature.as_ref());
+    push_line(&mut out, "", &base64::encode(&blob))?;
+    push_line(&mut out, TRUSTED_COMMENT_PREFIX, trusted_comment)?;
+    push_line(&mut out, "", &base64::encode(global_signature.as_ref()))?;
+    Ok(out)
+}
+
+/// Verifies the minisign signature file `signature_file` over `message`,
+/// returning the trusted comment.
+///
+/// Both signatures over the BLAKE2b-512 digest of the message and legacy
+/// signatures over the message itself are accepted.
+pub fn verify(
+    public_key: &PublicKey,
+    message: &[u8],
+    signature_file: &str,
+) -> Result<String, error::Unspecified> {
+    let mut lines = Lines::new(signature_file)?;
+    let _ = lines.untrusted_comment()?;
+    let blob = lines.base64()?;
+    let trusted_comment = lines.prefixed(TRUSTED_COMMENT_PREFIX)?;
+    let global_signature = lines.base64()?;
+    lines.end()?;
+
+    let (algorithm, key_id, signature) = parse_blob(&blob)?;
+    if key_id != public_key.key_id {
+        return Err(error::Unspecified);
+    }
+    if algorithm == ALGORITHM_ED25519_PREHASHED {
+        let digest = blake2b::digest(&[], blake2b::MAX_OUTPUT_LEN, message);
+        public_key.verify_signature(digest.as_ref(), signature)?;
+    } else if algorithm == ALGORITHM_ED25519 {
+        public_key.verify_signature(message, signature)?;
+    } else {
+        return Err(error::Unspecified);
+    }
+
+    let mut global_signed = Vec::with_capacity(SIGNATURE_LEN + trusted_comment.len());
+    global_signed.extend_from_slice(signature);
+    global_signed.extend_from_slice(trusted_comment.as_bytes());
+    public_key.verify_signature(&global_signed, &global_signature)?;
+
+    Ok(String::from(trusted_comment))
+}
+
+/// Signs `message` with `key_pair`, which must support
+/// `SignatureScheme::ED25519`, returning a signify signature file.
+///
+/// `untrusted_comment` must not contain line breaks.
+pub fn sign_signify(
+    key_pair: &dyn signature::Signer,
+    key_id: &[u8; KEY_ID_LEN],
+    message: &[u8],
+    untrusted_comment: &str,
+) -> Result<String, error::Unspecified> {
+    let signature = sign_ed25519(key_pair, message)?;
+    let mut out = String::new();
+    push_line(&mut out, UNTRUSTED_COMMENT_PREFIX, untrusted_comment)?;
+    let blob = serialize_blob(ALGORITHM_ED25519, key_id, signature.as_ref());
+    push_line(&mut out, "", &base64::encode(&blob))?;
+    Ok(out)
+}
+
+/// Verifies the signify signature file `signature_file` over `message`.
+///
+/// Signature files that embed the message (`signify -e`) aren't supported.
+pub fn verify_signify(
+    public_key: &PublicKey,
+    message: &[u8],
+    signature_file: &str,
+) -> Result<(), error::Unspecified> {
+    let mut lines = Lines::new(signature_file)?;
+    let _ = lines.untrusted_comment()?;
+    let blob = lines.base64()?;
+    lines.end()?;
+
+    let (algorithm, key_id, signature) = parse_blob(&blob)?;
+    if algorithm != ALGORITHM_ED25519 || key_id != public_key.key_id {
+        return Err(error::Unspecified);
+    }
+    public_key.verify_signature(message, signature)
+}
+
+fn sign_ed25519(key_pair: &dyn signature::Signer, msg: &[u8]) -> Result<Vec<u8>, error::Unspecified> {
+    // Ed25519 signing is deterministic, but external signers may use `rng`.
+    let rng = rand::SystemRandom::new();
+    let signature = key_pair.sign(signature::SignatureScheme::ED25519, &rng, msg)?;
+    if signature.len() != SIGNATURE_LEN {
+        return Err(error::Unspecified);
+    }
+    Ok(signature)
+}
+
+const ALGORITHM_ED25519: &[u8] = b"Ed";
+const ALGORITHM_ED25519_PREHASHED: &[u8] = b"ED";
+
+const PUBLIC_KEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+
+const UNTRUSTED_COMMENT_PREFIX: &str = "untrusted comment: ";
+const TRUSTED_COMMENT_PREFIX: &str = "trusted comment: ";
+
+// Splits `blob`, which is a public key or a signature, into the algorithm,
+// the key ID, and the value.
+fn parse_blob(blob: &[u8]) -> Result<(&[u8], [u8; KEY_ID_LEN], &[u8]), error::Unspecified> {
+    const ALGORITHM_LEN: usize = 2;
+    if blob.len() < ALGORITHM_LEN + KEY_ID_LEN {
+        return Err(error::Unspecified);
+    }
+    let (algorithm, rest) = blob.split_at(ALGORITHM_LEN);
+    let (key_id, value) = rest.split_at(KEY_ID_LEN);
+    let mut key_id_bytes = [0u8; KEY_ID_LEN];
+    key_id_bytes.copy_from_slice(key_id);
+    Ok((algorithm, key_id_bytes, value))
+}
+
+fn serialize_blob(algorithm: &[u8], key_id: &[u8; KEY_ID_LEN], value: &[u8]) -> Vec<u8> {
+    let mut blob = Vec::with_capacity(algorithm.len() + key_id.len() + value.len());
+    blob.extend_from_slice(algorithm);
+    blob.extend_from_slice(key_id);
+    blob.extend_from_slice(value);
+    blob
+}
+
+fn push_line(out: &mut String, prefix: &str, value: &str) -> Result<(), error::Unspecified> {
+    if value.contains(|c| c == '\n' || c == '\r') {
+        return Err(error::Unspecified);
+    }
+    out.push_str(prefix);
+    out.push_str(value);
+    out.push('\n');
+    Ok(())
+}
+
+// The lines of a key or signature file. Every line, including the last, must
+// be terminated by a newline.
+struct Lines<'a> {
+    lines: core::str::Split<'a, char>,
+}
+
+impl<'a> Lines<'a> {
+    fn new(contents: &'a str) -> Result<Self, error::Unspecified> {
+        if !contents.ends_with('\n') {
+            return Err(error::Unspecified);
+        }
+        Ok(Self {
+            lines: contents[..(contents.len() - 1)].split('\n'),
+        })
+    }
+
+    // Files written on Windows may have CRLF line endings.
+    fn next(&mut self) -> Result<&'a str, error::Unspecified> {
+        let line = self.lines.next().ok_or(error::Unspecified)?;
+        Ok(line.trim_end_matches('\r'))
+    }
+
+    fn prefixed(&mut self, prefix: &str) -> Result<&'a str, error::Unspecified> {
+        let line = self.next()?;
+        if !line.starts_with(prefix) {
+            return Err(error::Unspecified);
+        }
+        Ok(&line[prefix.len()..])
+    }
+
+    fn untrusted_comment(&mut self) -> Result<&'a str, error::Unspecified> {
+        self.prefixed(UNTRUSTED_COMMENT_PREFIX)
+    }
+
+    fn base64(&mut self) -> Result<Vec<u8>, error::Unspecified> {
+        base64::decode(self.next()?)
+    }
+
+    fn end(mut self) -> Result<(), error::Unspecified> {
+        if self.lines.next().is_some() {
+            return Err(error::Unspecified);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Low-level modular arithmetic with large moduli.
+//!
+//! This is for implementing protocols that *ring* doesn't provide, such as
+//! SRP and other finite-field Diffie-Hellman-based protocols, using the same
+//! multi-precision Montgomery arithmetic that *ring*'s RSA implementation
+//! uses. Prefer the higher-level modules whenever they suffice; it is easy to
+//! build an insecure protocol out of these operations.
+//!
+//! A `Modulus` is an odd modulus *m* together with its precomputed Montgomery
+//! multiplication context, and an `Elem` is an element of ℤ/mℤ. Every `Elem`
+//! borrows the `Modulus` it was created from.
+//!
+//! The modulus is treated as public. Arithmetic on elements, including
+//! `Elem::pow` and `Elem::invert`, is constant time with respect to the
+//! values of the elements and exponents, but not their lengths.
+
+use crate::{
+    arithmetic::montgomery::{Unencoded, R},
+    error,
+    rsa::bigint,
+};
+use std::{vec, vec::Vec};
+use untrusted;
+
+// All moduli share this type; an `Elem`'s modulus is tracked by reference
+// instead.
+enum M {}
+
+/// An odd modulus and its Montgomery multiplication context.
+pub struct Modulus {
+    m: bigint::Modulus<M>,
+    len: usize,
+}
+
+impl Modulus {
+    /// Decodes a big-endian modulus.
+    ///
+    /// The modulus must be odd and must not have leading zero bytes. Its
+    /// length must be a multiple of 512 bits in the range [512, 8192] bits;
+    /// this is the same restriction that *ring* places on RSA private key
+    /// moduli, and the groups in RFC 3526 and RFC 5054 all satisfy it.
+    pub fn from_be_bytes(bytes: &[u8]) -> Result<Self, error::Unspecified> {
+        let (m, bits) =
+            bigint::Modulus::from_be_bytes_with_bit_length(untrusted::Input::from(bytes))?;
+        let bits = bits.as_usize_bits();
+        if bits < 512 || bits % 512 != 0 {
+            return Err(error::Unspecified);
+        }
+        Ok(Self { m, len: bits / 8 })
+    }
+
+    /// The length of the modulus, and of an encoded element, in bytes.
+    pub fn byte_len(&self) -> usize {
+        self.len
+    }
+
+    /// Zero.
+    pub fn zero(&self) -> Elem {
+        self.small(0)
+    }
+
+    /// One.
+    pub fn one(&self) -> Elem {
+        self.small(1)
+    }
+
+    /// Decodes a big-endian element, which must be less than the modulus.
+    ///
+    /// `bytes` may be shorter than `byte_len()` bytes, but not longer.
+    pub fn elem_from_be_bytes(&self, bytes: &[u8]) -> Result<Elem, error::Unspecified> {
+        let a = bigint::Elem::from_be_bytes_padded(untrusted::Input::from(bytes), &self.m)?;
+        Ok(self.encode(a))
+    }
+
+    fn small(&self, value: u8) -> Elem {
+        // The modulus is at least 512 bits long.
+        self.elem_from_be_bytes(&[value]).unwrap()
+    }
+
+    fn encode(&self, a: bigint::Elem<M, Unencoded>) -> Elem {
+        Elem {
+            value: bigint::elem_mul(self.m.oneRR().as_ref(), a, &self.m),
+            m: self,
+        }
+    }
+}
+
+impl core::fmt::Debug for Modulus {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Modulus")
+            .field("byte_len", &self.len)
+            .finish()
+    }
+}
+
+/// An element of ℤ/mℤ for a `Modulus` *m*.
+///
+/// The methods that take another element panic if it belongs to a different
+/// `Modulus`.
+pub struct Elem<'a> {
+    value: bigint::Elem<M, R>,
+    m: &'a Modulus,
+}
+
+impl<'a> Elem<'a> {
+    /// Encodes the element as `byte_len()` big-endian bytes.
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let mut r = vec![0u8; self.m.len];
+        self.value
+            .clone()
+            .into_unencoded(&self.m.m)
+            .fill_be_bytes(&mut r);
+        r
+    }
+
+    /// Returns whether the element is zero.
+    pub fn is_zero(&self) -> bool {
+        // Zero is the only element whose Montgomery encoding is zero.
+        self.value.is_zero()
+    }
+
+    /// Returns `self + other (mod m)`.
+    pub fn add(&self, other: &Self) -> Self {
+        self.check_same_modulus(other);
+        self.with_value(bigint::elem_add(
+            self.value.clone(),
+            other.value.clone(),
+            &self.m.m,
+        ))
+    }
+
+    /// Returns `self - other (mod m)`.
+    pub fn sub(&self, other: &Self) -> Self {
+        self.check_same_modulus(other);
+        self.with_value(bigint::elem_sub(
+            self.value.clone(),
+            &other.value,
+            &self.m.m,
+        ))
+    }
+
+    /// Returns `-self (mod m)`.
+    pub fn neg(&self) -> Self {
+        self.m.zero().sub(self)
+    }
+
+    /// Returns `self * other (mod m)`.
+    pub fn mul(&self, other: &Self) -> Self {
+        self.check_same_modulus(other);
+        self.with_value(bigint::elem_mul(
+            &self.value,
+            other.value.clone(),
+            &self.m.m,
+        ))
+    }
+
+    /// Returns `self**exponent (mod m)`.
+    ///
+    /// `exponent` is a nonempty big-endian integer that is no longer than
+    /// `byte_len()` bytes; it doesn't need to be less than *m*. The
+    /// exponent is treated as secret.
+    pub fn pow(&self, exponent: &[u8]) -> Result<Self, error::Unspecified> {
+        let exponent = bigint::PrivateExponent::from_be_bytes_padded_unreduced(
+            untrusted::Input::from(exponent),
+            &self.m.m,
+        )?;
+        let r = bigint::elem_exp_consttime(self.value.clone(), &exponent, &self.m.m)?;
+        Ok(self.m.encode(r))
+    }
+
+    /// Returns `self**-1 (mod m)`.
+    ///
+    /// The modulus must be prime; the inverse is calculated as
+    /// `self**(m - 2) (mod m)` using Fermat's Little Theorem. This fails if
+    /// the result isn't the inverse of `self`, e.g. when `self` is zero.
+    pub fn invert(&self) -> Result<Self, error::Unspecified> {
+        let one = self.m.one();
+        let m_minus_2 = self.m.zero().sub(&one).sub(&one);
+        let r = self.pow(&m_minus_2.to_be_bytes())?;
+        let r_unencoded = r.value.clone().into_unencoded(&self.m.m);
+        bigint::verify_inverses_consttime(&self.value, r_unencoded, &self.m.m)?;
+        Ok(r)
+    }
+
+    fn with_value(&self, value: bigint::Elem<M, R>) -> Self {
+        Elem { value, m: self.m }
+    }
+
+    fn check_same_modulus(&self, other: &Self) {
+        assert!(
+            core::ptr::eq(self.m, other.m),
+            "elements belong to different moduli"
+        );
+    }
+}
+
+impl core::fmt::Debug for Elem<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.write_str("Elem")
+    }
+}
@@ -0,0 +1,569 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Primitives for the [Noise Protocol Framework].
+//!
+//! This provides the DH, cipher, and hash functions of Section 4 of the
+//! specification, for the suites `25519_AESGCM_SHA256`,
+//! `25519_ChaChaPoly_SHA256`, and `25519_ChaChaPoly_BLAKE2s`, and the
+//! `CipherState` and `SymmetricState` objects of Section 5 that are built on
+//! them. Processing handshake patterns, i.e. the `HandshakeState` object,
+//! is left to the caller.
+//!
+//! Unlike `hpke`, Noise uses static DH keys and an explicit nonce counter,
+//! so `KeyPair` can be constructed from a stored private key and
+//! `CipherState` allows the nonce to be set.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "aead-chacha")]
+//! # fn main() -> Result<(), ring::error::Unspecified> {
+//! use ring::{noise, rand};
+//!
+//! let suite = &noise::X25519_CHACHAPOLY_BLAKE2S;
+//! let rng = rand::SystemRandom::new();
+//! let protocol_name = b"Noise_NN_25519_ChaChaPoly_BLAKE2s";
+//!
+//! let initiator_e = noise::KeyPair::generate(&rng)?;
+//! let responder_e = noise::KeyPair::generate(&rng)?;
+//!
+//! // -> e
+//! let mut initiator = noise::SymmetricState::initialize(suite, protocol_name);
+//! initiator.mix_hash(b"");
+//! initiator.mix_hash(initiator_e.public_key());
+//!
+//! // <- e, ee
+//! let mut responder = noise::SymmetricState::initialize(suite, protocol_name);
+//! responder.mix_hash(b"");
+//! responder.mix_hash(initiator_e.public_key());
+//! responder.mix_hash(responder_e.public_key());
+//! responder.mix_key(responder_e.dh(initiator_e.public_key())?.as_ref())?;
+//!
+//! initiator.mix_hash(responder_e.public_key());
+//! initiator.mix_key(initiator_e.dh(responder_e.public_key())?.as_ref())?;
+//!
+//! let (mut initiator_send, _) = initiator.split()?;
+//! let (mut responder_receive, _) = responder.split()?;
+//!
+//! let mut in_out = b"hello, world".to_vec();
+//! in_out.extend_from_slice(&[0; noise::TAG_LEN]);
+//! let out_len = initiator_send.encrypt_with_ad(b"", &mut in_out, noise::TAG_LEN)?;
+//! let plaintext = responder_receive.decrypt_with_ad(b"", &mut in_out[..out_len])?;
+//! assert_eq!(plaintext, b"hello, world");
+//!
+//! # Ok(())
+//! # }
+//! #
+//! # #[cfg(not(feature = "aead-chacha"))]
+//! # fn main() {}
+//! ```
+//!
+//! [Noise Protocol Framework]: https://noiseprotocol.org/noise.html
+
+use crate::{aead, agreement, cpu, digest, ec, error, hkdf, rand, zeroize::Zeroize};
+use untrusted;
+
+/// A Noise suite: a DH function, a cipher function, and a hash function.
+pub struct Suite {
+    name: &'static str,
+    aead: &'static aead::Algorithm,
+    nonce_endianness: aead::Endianness,
+    hash: &'static digest::Algorithm,
+    id: SuiteID,
+}
+
+impl Suite {
+    /// The name of the suite as used in protocol names, e.g.
+    /// `"25519_AESGCM_SHA256"`.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The suite's hash function.
+    #[inline]
+    pub fn hash_algorithm(&self) -> &'static digest::Algorithm {
+        self.hash
+    }
+}
+
+derive_debug_via_id!(Suite);
+
+#[derive(Debug, Eq, PartialEq)]
+enum SuiteID {
+    X25519_AESGCM_SHA256,
+    #[cfg(feature = "aead-chacha")]
+    X25519_CHACHAPOLY_SHA256,
+    #[cfg(feature = "aead-chacha")]
+    X25519_CHACHAPOLY_BLAKE2S,
+}
+
+impl PartialEq for Suite {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Suite {}
+
+/// `25519_AESGCM_SHA256`: X25519, AES-256-GCM, and SHA-256.
+pub static X25519_AESGCM_SHA256: Suite = Suite {
+    name: "25519_AESGCM_SHA256",
+    aead: &aead::AES_256_GCM,
+    nonce_endianness: aead::Endianness::Big,
+    hash: &digest::SHA256,
+    id: SuiteID::X25519_AESGCM_SHA256,
+};
+
+/// `25519_ChaChaPoly_SHA256`: X25519, ChaCha20-Poly1305, and SHA-256.
+#[cfg(feature = "aead-chacha")]
+pub static X25519_CHACHAPOLY_SHA256: Suite = Suite {
+    name: "25519_ChaChaPoly_SHA256",
+    aead: &aead::CHACHA20_POLY1305,
+    nonce_endianness: aead::Endianness::Little,
+    hash: &digest::SHA256,
+    id: SuiteID::X25519_CHACHAPOLY_SHA256,
+};
+
+/// `25519_ChaChaPoly_BLAKE2s`: X25519, ChaCha20-Poly1305, and BLAKE2s.
+#[cfg(feature = "aead-chacha")]
+pub static X25519_CHACHAPOLY_BLAKE2S: Suite = Suite {
+    name: "25519_ChaChaPoly_BLAKE2s",
+    aead: &aead::CHACHA20_POLY1305,
+    nonce_endianness: aead::Endianness::Little,
+    hash: &digest::BLAKE2S_256,
+    id: SuiteID::X25519_CHACHAPOLY_BLAKE2S,
+};
+
+/// The length of public keys and DH outputs (`DHLEN`).
+pub const DH_LEN: usize = 32;
+
+/// The length of cipher keys.
+pub const KEY_LEN: usize = 32;
+
+/// The length of the authentication tag that encryption appends.
+pub const TAG_LEN: usize = aead::MAX_TAG_LEN;
+
+/// The length of the output of the hash function (`HASHLEN`) of every
+/// suite.
+pub const HASH_LEN: usize = 32;
+
+/// An X25519 key pair, for static or ephemeral keys.
+pub struct KeyPair {
+    seed: ec::Seed,
+    public_key: ec::PublicKey,
+}
+
+impl core::fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        crate::debug::write_hex_tuple(f, "KeyPair", &self.public_key)
+    }
+}
+
+impl KeyPair {
+    /// Generates a new key pair (`GENERATE_KEYPAIR()`).
+    pub fn generate(rng: &dyn rand::SecureRandom) -> Result<Self, error::Unspecified> {
+        let seed = ec::Seed::generate(agreement::X25519.curve, rng, cpu::features())?;
+        Self::from_seed(seed)
+    }
+
+    /// Constructs a key pair from a stored `DH_LEN`-byte private key, e.g.
+    /// a static key.
+    pub fn from_private_key(private_key: &[u8]) -> Result<Self, error::Unspecified> {
+        let seed = ec::Seed::from_bytes(
+            agreement::X25519.curve,
+            untrusted::Input::from(private_key),
+            cpu::features(),
+        )?;
+        Self::from_seed(seed)
+    }
+
+    fn from_seed(seed: ec::Seed) -> Result<Self, error::Unspecified> {
+        let public_key = seed.compute_public_key()?;
+        Ok(Self { seed, public_key })
+    }
+
+    /// The public key.
+    #[inline]
+    pub fn public_key(&self) -> &[u8] {
+        self.public_key.as_ref()
+    }
+
+    /// Exports the private key, for storing a static key.
+    ///
+    /// The private key must be kept secret, and should be stored
+    /// encrypted.
+    #[inline]
+    pub fn export_private_key_unchecked(&self) -> &[u8] {
+        self.seed.bytes_less_safe()
+    }
+
+    /// Performs a DH between the private key and `peer_public_key`
+    /// (`DH(key_pair, public_key)`).
+    ///
+    /// Fails if `peer_public_key` is invalid, including when it is a point
+    /// of small order, as the specification allows.
+    pub fn dh(&self, peer_public_key: &[u8]) -> Result<SharedSecret, error::Unspecified> {
+        let mut shared_secret = SharedSecret([0u8; DH_LEN]);
+        (agreement::X25519.ecdh)(
+            &mut shared_secret.0,
+            &self.seed,
+            untrusted::Input::from(peer_public_key),
+        )?;
+        Ok(shared_secret)
+    }
+}
+
+/// The output of `KeyPair::dh()`. It is zeroed when dropped.
+pub struct SharedSecret([u8; DH_LEN]);
+
+impl AsRef<[u8]> for SharedSecret {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.0[..]
+    }
+}
+
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        self.0[..].zeroize();
+    }
+}
+
+impl core::fmt::Debug for SharedSecret {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("SharedSecret").finish()
+    }
+}
+
+/// A `CipherState`: a cipher key, if any, and a nonce counter.
+pub struct CipherState {
+    suite: &'static Suite,
+    keys: Option<(aead::SealingKey, aead::OpeningKey)>,
+    n: u64,
+}
+
+impl core::fmt::Debug for CipherState {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("CipherState")
+            .field("suite", self.suite)
+            .field("has_key", &self.has_key())
+            .field("n", &self.n)
+            .finish()
+    }
+}
+
+impl CipherState {
+    /// Constructs a `CipherState` without a key, so that encryption and
+    /// decryption pass data through unchanged.
+    pub fn new(suite: &'static Suite) -> Self {
+        Self {
+            suite,
+            keys: None,
+            n: 0,
+        }
+    }
+
+    /// Sets the key to `key`, which must be `KEY_LEN` bytes long, and the
+    /// nonce to zero (`InitializeKey(key)`).
+    pub fn initialize_key(&mut self, key: &[u8]) -> Result<(), error::Unspecified> {
+        self.set_key(key)?;
+        self.n = 0;
+        Ok(())
+    }
+
+    // Replaces `k`, leaving `n` as it is.
+    fn set_key(&mut self, key: &[u8]) -> Result<(), error::Unspecified> {
+        if key.len() != KEY_LEN {
+            return Err(error::Unspecified);
+        }
+        self.keys = Some((
+            aead::SealingKey::new(self.suite.aead, key)?,
+            aead::OpeningKey::new(self.suite.aead, key)?,
+        ));
+        Ok(())
+    }
+
+    /// Whether a key has been set (`HasKey()`).
+    #[inline]
+    pub fn has_key(&self) -> bool {
+        self.keys.is_some()
+    }
+
+    /// The nonce that the next encryption or decryption will use.
+    #[inline]
+    pub fn nonce(&self) -> u64 {
+        self.n
+    }
+
+    /// Sets the nonce (`SetNonce(nonce)`), e.g. for protocols that deliver
+    /// transport messages out of order.
+    #[inline]
+    pub fn set_nonce(&mut self, nonce: u64) {
+        self.n = nonce;
+    }
+
+    /// Encrypts the plaintext in place with the associated data `ad`, and
+    /// increments the nonce (`EncryptWithAd(ad, plaintext)`).
+    ///
+    /// This is like `aead::seal_in_place()`: the plaintext is the first
+    /// `in_out.len() - out_suffix_capacity` bytes of `in_out`, and the
+    /// length of the output is returned. `out_suffix_capacity` must be
+    /// `TAG_LEN`. If there is no key, the plaintext is returned
+    /// unchanged and the nonce isn't incremented.
+    ///
+    /// Fails if the nonce is 2^64 - 1, which is reserved.
+    pub fn encrypt_with_ad(
+        &mut self,
+        ad: &[u8],
+        in_out: &mut [u8],
+        out_suffix_capacity: usize,
+    ) -> Result<usize, error::Unspecified> {
+        let (sealing_key, _) = match &self.keys {
+            Some(keys) => keys,
+            None => {
+                return in_out
+                    .len()
+                    .checked_sub(out_suffix_capacity)
+                    .ok_or(error::Unspecified);
+            }
+        };
+        let nonce = self.checked_nonce()?;
+        let out_len = aead::seal_in_place(
+            sealing_key,
+            nonce,
+            aead::Aad::from(ad),
+            in_out,
+            out_suffix_capacity,
+        )?;
+        self.n += 1;
+        Ok(out_len)
+    }
+
+    /// Authenticates and decrypts the ciphertext `in_out` in place with the
+    /// associated data `ad`, and increments the nonce
+    /// (`DecryptWithAd(ad, ciphertext)`).
+    ///
+    /// If there is no key, the ciphertext is returned unchanged and the
+    /// nonce isn't incremented. The nonce isn't incremented if decryption
+    /// fails either.
+    ///
+    /// Fails if the nonce is 2^64 - 1, which is reserved.
+    pub fn decrypt_with_ad<'a>(
+        &mut self,
+        ad: &[u8],
+        in_out: &'a mut [u8],
+    ) -> Result<&'a mut [u8], error::Unspecified> {
+        let (_, opening_key) = match &self.keys {
+            Some(keys) => keys,
+            None => {
+                return Ok(in_out);
+            }
+        };
+        let nonce = self.checked_nonce()?;
+        let plaintext = aead::open_in_place(opening_key, nonce, aead::Aad::from(ad), 0, in_out)?;
+        self.n += 1;
+        Ok(plaintext)
+    }
+
+    /// Replaces the key with `REKEY(k)`, the first `KEY_LEN` bytes of the
+    /// encryption of `KEY_LEN` zeros with the nonce 2^64 - 1 (`Rekey()`).
+    ///
+    /// The nonce counter is unchanged. Fails if there is no key.
+    pub fn rekey(&mut self) -> Result<(), error::Unspecified> {
+        let mut new_key = [0u8; KEY_LEN];
+        {
+            let (sealing_key, _) = self.keys.as_ref().ok_or(error::Unspecified)?;
+            let _ = aead::seal_in_place_detached(
+                sealing_key,
+                self.nonce_for(u64::max_value()),
+                aead::Aad::empty(),
+                &mut new_key,
+            )?;
+        }
+        let result = self.set_key(&new_key);
+        new_key[..].zeroize();
+        result
+    }
+
+    fn checked_nonce(&self) -> Result<aead::Nonce, error::Unspecified> {
+        if self.n == u64::max_value() {
+            return Err(error::Unspecified);
+        }
+        Ok(self.nonce_for(self.n))
+    }
+
+    // The 32 bits of zeros followed by the 64-bit counter in the byte order
+    // that the cipher function specifies.
+    fn nonce_for(&self, n: u64) -> aead::Nonce {
+        let mut nonce = [0u8; aead::NONCE_LEN];
+        let counter = match self.suite.nonce_endianness {
+            aead::Endianness::Big => n.to_be_bytes(),
+            aead::Endianness::Little => n.to_le_bytes(),
+        };
+        nonce[aead::COUNTER_NONCE_PREFIX_LEN..].copy_from_slice(&counter);
+        aead::Nonce::assume_unique_for_key(nonce)
+    }
+}
+
+/// A `SymmetricState`: a chaining key, a handshake hash, and a
+/// `CipherState`.
+pub struct SymmetricState {
+    suite: &'static Suite,
+    ck: [u8; HASH_LEN],
+    h: [u8; HASH_LEN],
+    cipher_state: CipherState,
+}
+
+derive_debug_via_field!(SymmetricState, cipher_state);
+
+impl Drop for SymmetricState {
+    fn drop(&mut self) {
+        self.ck[..].zeroize();
+    }
+}
+
+impl SymmetricState {
+    /// Initializes the state for the full protocol name, e.g.
+    /// `b"Noise_XX_25519_AESGCM_SHA256"` (`InitializeSymmetric(protocol_name)`).
+    pub fn initialize(suite: &'static Suite, protocol_name: &[u8]) -> Self {
+        let mut h = [0u8; HASH_LEN];
+        if protocol_name.len() <= HASH_LEN {
+            h[..protocol_name.len()].copy_from_slice(protocol_name);
+        } else {
+            h.copy_from_slice(digest::digest(suite.hash, protocol_name).as_ref());
+        }
+        Self {
+            suite,
+            ck: h,
+            h,
+            cipher_state: CipherState::new(suite),
+        }
+    }
+
+    /// Mixes `input_key_material`, e.g. a DH output, into the chaining key
+    /// and sets the cipher key (`MixKey(input_key_material)`).
+    pub fn mix_key(&mut self, input_key_material: &[u8]) -> Result<(), error::Unspecified> {
+        let mut output = [0u8; 2 * HASH_LEN];
+        self.hkdf(input_key_material, &mut output)?;
+        self.ck.copy_from_slice(&output[..HASH_LEN]);
+        let result = self
+            .cipher_state
+            .initialize_key(&output[HASH_LEN..(HASH_LEN + KEY_LEN)]);
+        output[..].zeroize();
+        result
+    }
+
+    /// Mixes `data` into the handshake hash (`MixHash(data)`).
+    pub fn mix_hash(&mut self, data: &[u8]) {
+        let mut ctx = digest::Context::new(self.suite.hash);
+        ctx.update(&self.h);
+        ctx.update(data);
+        self.h.copy_from_slice(ctx.finish().as_ref());
+    }
+
+    /// Mixes `input_key_material`, e.g. a pre-shared key, into both the
+    /// chaining key and the handshake hash, and sets the cipher key
+    /// (`MixKeyAndHash(input_key_material)`).
+    pub fn mix_key_and_hash(
+        &mut self,
+        input_key_material: &[u8],
+    ) -> Result<(), error::Unspecified> {
+        let mut output = [0u8; 3 * HASH_LEN];
+        self.hkdf(input_key_material, &mut output)?;
+        self.ck.copy_from_slice(&output[..HASH_LEN]);
+        self.mix_hash(&output[HASH_LEN..(2 * HASH_LEN)]);
+        let result = self
+            .cipher_state
+            .initialize_key(&output[(2 * HASH_LEN)..(2 * HASH_LEN + KEY_LEN)]);
+        output[..].zeroize();
+        result
+    }
+
+    /// The handshake hash (`GetHandshakeHash()`), e.g. for channel binding.
+    #[inline]
+    pub fn handshake_hash(&self) -> &[u8] {
+        &self.h
+    }
+
+    /// Whether a cipher key has been set.
+    #[inline]
+    pub fn has_key(&self) -> bool {
+        self.cipher_state.has_key()
+    }
+
+    /// Encrypts the plaintext in place with the handshake hash as the
+    /// associated data, and mixes the ciphertext into the handshake hash
+    /// (`EncryptAndHash(plaintext)`).
+    ///
+    /// `in_out` and `out_suffix_capacity` are as for
+    /// `CipherState::encrypt_with_ad()`.
+    pub fn encrypt_and_hash(
+        &mut self,
+        in_out: &mut [u8],
+        out_suffix_capacity: usize,
+    ) -> Result<usize, error::Unspecified> {
+        let h = self.h;
+        let out_len = self
+            .cipher_state
+            .encrypt_with_ad(&h, in_out, out_suffix_capacity)?;
+        self.mix_hash(&in_out[..out_len]);
+        Ok(out_len)
+    }
+
+    /// Decrypts the ciphertext `in_out` in place with the handshake hash as
+    /// the associated data, and mixes the ciphertext into the handshake
+    /// hash (`DecryptAndHash(ciphertext)`).
+    ///
+    /// The handshake hash is unchanged if decryption fails.
+    pub fn decrypt_and_hash<'a>(
+        &mut self,
+        in_out: &'a mut [u8],
+    ) -> Result<&'a mut [u8], error::Unspecified> {
+        let h = self.h;
+        let mut ctx = digest::Context::new(self.suite.hash);
+        ctx.update(&h);
+        ctx.update(in_out);
+        let new_h = ctx.finish();
+        let plaintext = self.cipher_state.decrypt_with_ad(&h, in_out)?;
+        self.h.copy_from_slice(new_h.as_ref());
+        Ok(plaintext)
+    }
+
+    /// Derives the two `CipherState`s for transport messages (`Split()`).
+    ///
+    /// The initiator encrypts with the first and the responder encrypts
+    /// with the second.
+    pub fn split(self) -> Result<(CipherState, CipherState), error::Unspecified> {
+        let mut output = [0u8; 2 * HASH_LEN];
+        self.hkdf(&[], &mut output)?;
+        let mut c1 = CipherState::new(self.suite);
+        let mut c2 = CipherState::new(self.suite);
+        let result = c1
+            .initialize_key(&output[..KEY_LEN])
+            .and_then(|()| c2.initialize_key(&output[HASH_LEN..(HASH_LEN + KEY_LEN)]));
+        output[..].zeroize();
+        result.map(|()| (c1, c2))
+    }
+
+    // `HKDF(ck, input_key_material, num_outputs)`, with the number of
+    // outputs given by the length of `out`. This is HKDF with an empty
+    // `info`.
+    fn hkdf(&self, input_key_material: &[u8], out: &mut [u8]) -> Result<(), error::Unspecified> {
+        hkdf::Salt::new(self.suite.hash, &self.ck)
+            .extract(input_key_material)
+            .fill(&[], out)
+    }
+}
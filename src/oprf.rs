@@ -0,0 +1,486 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Oblivious pseudorandom functions.
+//!
+//! This implements the OPRF and VOPRF modes of [RFC 9497] with the
+//! P256-SHA256 ciphersuite. The server holds a `ServerKey`; the client
+//! learns the PRF output for an input of its choice without learning the
+//! key, and the server learns neither the input nor the output:
+//!
+//! 1. The client starts a `Client` with its input, which blinds the input,
+//!    and sends its `blinded_element()` to the server.
+//! 2. The server passes the blinded element to `blind_evaluate()` and sends
+//!    the resulting `Evaluation` to the client.
+//! 3. The client passes the evaluated element to `finalize()`, or, in the
+//!    VOPRF mode, the evaluated element, the proof, and the server's public
+//!    key to `finalize_verifiable()`, which gives the PRF output.
+//!
+//! In the VOPRF mode, the server proves that it used the key corresponding
+//! to its public key, so the client can be sure that the output isn't
+//! specific to it. The server can compute the output for an input it knows
+//! directly with `evaluate()`.
+//!
+//! The partially-oblivious mode and batched evaluation aren't implemented.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::{oprf, rand};
+//!
+//! let rng = rand::SystemRandom::new();
+//! let server_key = oprf::ServerKey::derive(oprf::Mode::Voprf, &[0x42; oprf::SEED_LEN], b"")?;
+//!
+//! let client = oprf::Client::blind(oprf::Mode::Voprf, b"input", &rng)?;
+//! let evaluation = server_key.blind_evaluate(client.blinded_element(), &rng)?;
+//! let output = client.finalize_verifiable(
+//!     evaluation.evaluated_element(),
+//!     evaluation.proof().unwrap(),
+//!     server_key.public_key(),
+//! )?;
+//!
+//! assert_eq!(output.as_ref(), server_key.evaluate(b"input")?.as_ref());
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [RFC 9497]: https://tools.ietf.org/html/rfc9497
+
+use crate::{
+    constant_time, digest, error, hash_to_curve,
+    p256::{self, Point, Scalar},
+    rand,
+};
+use std::vec::Vec;
+
+/// The length of a serialized group element, `Ne`.
+pub const ELEMENT_LEN: usize = p256::COMPRESSED_POINT_LEN;
+
+/// The length of a serialized private key, `Ns`.
+pub const PRIVATE_KEY_LEN: usize = p256::SCALAR_LEN;
+
+/// The length of a proof.
+pub const PROOF_LEN: usize = 2 * p256::SCALAR_LEN;
+
+/// The length of the PRF output, `Nh`.
+pub const OUTPUT_LEN: usize = digest::SHA256_OUTPUT_LEN;
+
+/// The length of the seed for `ServerKey::derive()`, `Nseed`.
+pub const SEED_LEN: usize = 32;
+
+// Inputs, and everything else that is hashed with a two-byte length prefix,
+// must be shorter than 2**16 bytes.
+const MAX_LEN: usize = 0xffff;
+
+/// A protocol mode.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    /// The base mode, `modeOPRF`.
+    Oprf,
+
+    /// The verifiable mode, `modeVOPRF`, in which the server proves which
+    /// key it used.
+    Voprf,
+}
+
+impl Mode {
+    // `contextString`.
+    fn context_string(self) -> [u8; CONTEXT_STRING_LEN] {
+        let mut r = *b"OPRFV1-\x00-P256-SHA256";
+        r[7] = match self {
+            Mode::Oprf => 0x00,
+            Mode::Voprf => 0x01,
+        };
+        r
+    }
+}
+
+const CONTEXT_STRING_LEN: usize = 20;
+
+/// A server's private key.
+pub struct ServerKey {
+    mode: Mode,
+    key: Scalar,
+    public_key: Point,
+    public_key_encoded: [u8; ELEMENT_LEN],
+}
+
+impl ServerKey {
+    /// Derives a key from a `SEED_LEN`-byte random seed and `info`,
+    /// `DeriveKeyPair(seed, info)`.
+    pub fn derive(mode: Mode, seed: &[u8], info: &[u8]) -> Result<Self, error::Unspecified> {
+        if seed.len() != SEED_LEN || info.len() > MAX_LEN {
+            return Err(error::Unspecified);
+        }
+        let mut derive_input = Vec::with_capacity(SEED_LEN + 2 + info.len() + 1);
+        derive_input.extend_from_slice(seed);
+        append_with_len(&mut derive_input, info);
+        derive_input.push(0);
+        for counter in 0..=255u8 {
+            *derive_input.last_mut().unwrap() = counter;
+            let key = hash_to_scalar(&derive_input, b"DeriveKeyPair", mode);
+            if !key.is_zero() {
+                return Self::new(mode, key);
+            }
+        }
+        Err(error::Unspecified)
+    }
+
+    /// Decodes a `PRIVATE_KEY_LEN`-byte big-endian private key, which must be
+    /// nonzero and less than the group order.
+    pub fn from_bytes(mode: Mode, key: &[u8]) -> Result<Self, error::Unspecified> {
+        let key = Scalar::from_bytes(key)?;
+        if key.is_zero() {
+            return Err(error::Unspecified);
+        }
+        Self::new(mode, key)
+    }
+
+    fn new(mode: Mode, key: Scalar) -> Result<Self, error::Unspecified> {
+        let public_key = Point::mul_base(&key);
+        Ok(Self {
+            mode,
+            key,
+            public_key,
+            public_key_encoded: public_key.to_compressed()?,
+        })
+    }
+
+    /// The mode this key is used in.
+    #[inline]
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// The public key, which VOPRF clients need.
+    #[inline]
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key_encoded
+    }
+
+    /// Evaluates the PRF on a client's blinded input,
+    /// `BlindEvaluate(skS, blindedElement)`.
+    ///
+    /// In the VOPRF mode, `rng` is used to generate the proof.
+    pub fn blind_evaluate(
+        &self,
+        blinded_element: &[u8],
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<Evaluation, error::Unspecified> {
+        let blinded = deserialize_element(blinded_element)?;
+        let evaluated = blinded.mul(&self.key);
+        let proof = match self.mode {
+            Mode::Oprf => None,
+            Mode::Voprf => Some(generate_proof(
+                self.mode,
+                &self.key,
+                &self.public_key,
+                &blinded,
+                &evaluated,
+                rng,
+            )?),
+        };
+        Ok(Evaluation {
+            evaluated_element: evaluated.to_compressed()?,
+            proof,
+        })
+    }
+
+    /// Computes the PRF output for `input` directly, `Evaluate(skS, input)`.
+    pub fn evaluate(&self, input: &[u8]) -> Result<Output, error::Unspecified> {
+        let evaluated = hash_to_group(input, self.mode)?.mul(&self.key);
+        finalize_hash(input, &evaluated)
+    }
+}
+
+impl core::fmt::Debug for ServerKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("ServerKey")
+            .field("mode", &self.mode)
+            .finish()
+    }
+}
+
+/// The server's response to a blinded input.
+pub struct Evaluation {
+    evaluated_element: [u8; ELEMENT_LEN],
+    proof: Option<[u8; PROOF_LEN]>,
+}
+
+impl Evaluation {
+    /// The evaluated element to send to the client.
+    #[inline]
+    pub fn evaluated_element(&self) -> &[u8] {
+        &self.evaluated_element
+    }
+
+    /// The proof to send to the client in the VOPRF mode, or `None` in the
+    /// OPRF mode.
+    #[inline]
+    pub fn proof(&self) -> Option<&[u8]> {
+        self.proof.as_ref().map(|proof| &proof[..])
+    }
+}
+
+impl core::fmt::Debug for Evaluation {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.write_str("Evaluation")
+    }
+}
+
+/// A client's state from blinding its input until it receives the server's
+/// evaluation.
+pub struct Client {
+    mode: Mode,
+    input: Vec<u8>,
+    blind: Scalar,
+    blinded: Point,
+    blinded_element: [u8; ELEMENT_LEN],
+}
+
+impl Client {
+    /// Blinds `input`, `Blind(input)`.
+    ///
+    /// Fails if `input` is 2**16 bytes or longer.
+    pub fn blind(
+        mode: Mode,
+        input: &[u8],
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<Self, error::Unspecified> {
+        if input.len() > MAX_LEN {
+            return Err(error::Unspecified);
+        }
+        let blind = Scalar::random(rng)?;
+        let blinded = hash_to_group(input, mode)?.mul(&blind);
+        Ok(Self {
+            mode,
+            input: input.to_vec(),
+            blind,
+            blinded,
+            blinded_element: blinded.to_compressed()?,
+        })
+    }
+
+    /// The blinded element to send to the server.
+    #[inline]
+    pub fn blinded_element(&self) -> &[u8] {
+        &self.blinded_element
+    }
+
+    /// Computes the PRF output from the server's evaluated element in the
+    /// OPRF mode, `Finalize(input, blind, evaluatedElement)`.
+    ///
+    /// Fails in the VOPRF mode.
+    pub fn finalize(self, evaluated_element: &[u8]) -> Result<Output, error::Unspecified> {
+        if self.mode != Mode::Oprf {
+            return Err(error::Unspecified);
+        }
+        let evaluated = deserialize_element(evaluated_element)?;
+        self.unblind_and_hash(&evaluated)
+    }
+
+    /// Verifies the server's proof and computes the PRF output from its
+    /// evaluated element in the VOPRF mode,
+    /// `Finalize(input, blind, evaluatedElement, blindedElement, pkS, proof)`.
+    ///
+    /// Fails in the OPRF mode, or if the proof isn't valid for
+    /// `server_public_key`.
+    pub fn finalize_verifiable(
+        self,
+        evaluated_element: &[u8],
+        proof: &[u8],
+        server_public_key: &[u8],
+    ) -> Result<Output, error::Unspecified> {
+        if self.mode != Mode::Voprf {
+            return Err(error::Unspecified);
+        }
+        let evaluated = deserialize_element(evaluated_element)?;
+        let public_key = deserialize_element(server_public_key)?;
+        verify_proof(self.mode, &public_key, &self.blinded, &evaluated, proof)?;
+        self.unblind_and_hash(&evaluated)
+    }
+
+    fn unblind_and_hash(&self, evaluated: &Point) -> Result<Output, error::Unspecified> {
+        let unblinded = evaluated.mul(&self.blind.invert()?);
+        finalize_hash(&self.input, &unblinded)
+    }
+}
+
+impl core::fmt::Debug for Client {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Client").field("mode", &self.mode).finish()
+    }
+}
+
+/// A PRF output.
+pub struct Output([u8; OUTPUT_LEN]);
+
+impl AsRef<[u8]> for Output {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+// The output is often used as a secret key, so don't print it.
+impl core::fmt::Debug for Output {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.write_str("Output")
+    }
+}
+
+// `DeserializeElement`: the compressed SEC 1 encoding of a point on the
+// curve. The uncompressed encoding isn't accepted, and neither is the
+// identity, which has no encoding.
+fn deserialize_element(bytes: &[u8]) -> Result<Point, error::Unspecified> {
+    if bytes.len() != ELEMENT_LEN {
+        return Err(error::Unspecified);
+    }
+    let point = Point::from_bytes(bytes)?;
+    if point.is_identity() {
+        return Err(error::Unspecified);
+    }
+    Ok(point)
+}
+
+// The hash in `Finalize` and `Evaluate`.
+fn finalize_hash(input: &[u8], unblinded: &Point) -> Result<Output, error::Unspecified> {
+    if input.len() > MAX_LEN {
+        return Err(error::Unspecified);
+    }
+    let mut transcript = Vec::new();
+    append_with_len(&mut transcript, input);
+    append_with_len(&mut transcript, &unblinded.to_compressed()?);
+    transcript.extend_from_slice(b"Finalize");
+    let mut output = [0u8; OUTPUT_LEN];
+    output.copy_from_slice(digest::digest(&digest::SHA256, &transcript).as_ref());
+    Ok(Output(output))
+}
+
+// `GenerateProof(k, G, pkS, [blinded], [evaluated])`. The generator is
+// implied.
+fn generate_proof(
+    mode: Mode,
+    key: &Scalar,
+    public_key: &Point,
+    blinded: &Point,
+    evaluated: &Point,
+    rng: &dyn rand::SecureRandom,
+) -> Result<[u8; PROOF_LEN], error::Unspecified> {
+    let (m, z) = compute_composites(mode, public_key, blinded, evaluated)?;
+    let r = Scalar::random(rng)?;
+    let t2 = Point::mul_base(&r);
+    let t3 = m.mul(&r);
+    let c = challenge(mode, public_key, &m, &z, &t2, &t3)?;
+    let s = r.sub(&c.mul(key));
+
+    let mut proof = [0u8; PROOF_LEN];
+    let (c_out, s_out) = proof.split_at_mut(p256::SCALAR_LEN);
+    c_out.copy_from_slice(&c.to_bytes());
+    s_out.copy_from_slice(&s.to_bytes());
+    Ok(proof)
+}
+
+// `VerifyProof(G, pkS, [blinded], [evaluated], proof)`.
+fn verify_proof(
+    mode: Mode,
+    public_key: &Point,
+    blinded: &Point,
+    evaluated: &Point,
+    proof: &[u8],
+) -> Result<(), error::Unspecified> {
+    if proof.len() != PROOF_LEN {
+        return Err(error::Unspecified);
+    }
+    let (c, s) = proof.split_at(p256::SCALAR_LEN);
+    let c = Scalar::from_bytes(c)?;
+    let s = Scalar::from_bytes(s)?;
+
+    let (m, z) = compute_composites(mode, public_key, blinded, evaluated)?;
+    let t2 = Point::mul_base(&s).add(&public_key.mul(&c));
+    let t3 = m.mul(&s).add(&z.mul(&c));
+    let expected_c = challenge(mode, public_key, &m, &z, &t2, &t3)?;
+    constant_time::verify_slices_are_equal(&c.to_bytes(), &expected_c.to_bytes())
+}
+
+// `ComputeComposites(pkS, [blinded], [evaluated])`, returning `(M, Z)`. For
+// a batch of one element, the prover's `ComputeCompositesFast` computes the
+// same values.
+fn compute_composites(
+    mode: Mode,
+    public_key: &Point,
+    blinded: &Point,
+    evaluated: &Point,
+) -> Result<(Point, Point), error::Unspecified> {
+    let mut seed_dst = b"Seed-".to_vec();
+    seed_dst.extend_from_slice(&mode.context_string());
+    let mut seed_transcript = Vec::new();
+    append_with_len(&mut seed_transcript, &public_key.to_compressed()?);
+    append_with_len(&mut seed_transcript, &seed_dst);
+    let seed = digest::digest(&digest::SHA256, &seed_transcript);
+
+    let mut composite_transcript = Vec::new();
+    append_with_len(&mut composite_transcript, seed.as_ref());
+    composite_transcript.extend_from_slice(&0u16.to_be_bytes());
+    append_with_len(&mut composite_transcript, &blinded.to_compressed()?);
+    append_with_len(&mut composite_transcript, &evaluated.to_compressed()?);
+    composite_transcript.extend_from_slice(b"Composite");
+    let d0 = hash_to_scalar(&composite_transcript, b"HashToScalar-", mode);
+
+    Ok((blinded.mul(&d0), evaluated.mul(&d0)))
+}
+
+// The challenge `c` in `GenerateProof` and `VerifyProof`.
+fn challenge(
+    mode: Mode,
+    public_key: &Point,
+    m: &Point,
+    z: &Point,
+    t2: &Point,
+    t3: &Point,
+) -> Result<Scalar, error::Unspecified> {
+    let mut transcript = Vec::new();
+    for element in &[public_key, m, z, t2, t3] {
+        append_with_len(&mut transcript, &element.to_compressed()?);
+    }
+    transcript.extend_from_slice(b"Challenge");
+    Ok(hash_to_scalar(&transcript, b"HashToScalar-", mode))
+}
+
+// `HashToGroup`, which fails in the negligibly likely case that the result is
+// the identity.
+fn hash_to_group(input: &[u8], mode: Mode) -> Result<Point, error::Unspecified> {
+    let mut dst = b"HashToGroup-".to_vec();
+    dst.extend_from_slice(&mode.context_string());
+    let encoded =
+        hash_to_curve::hash_to_curve(&hash_to_curve::P256_XMD_SHA256_SSWU_RO, input, &dst)?;
+    Point::from_bytes(encoded.as_ref())
+}
+
+// `HashToScalar` with the DST `dst_prefix || contextString`.
+fn hash_to_scalar(msg: &[u8], dst_prefix: &[u8], mode: Mode) -> Scalar {
+    let mut dst = dst_prefix.to_vec();
+    dst.extend_from_slice(&mode.context_string());
+    let mut uniform_bytes = [0u8; 48];
+    hash_to_curve::expand_message_xmd(&digest::SHA256, msg, &dst, &mut uniform_bytes).unwrap();
+    Scalar::from_bytes_wide_reduced(&uniform_bytes)
+}
+
+// Appends `I2OSP(len(value), 2) || value`. `value` must be shorter than
+// 2**16 bytes.
+fn append_with_len(out: &mut Vec<u8>, value: &[u8]) {
+    debug_assert!(value.len() <= MAX_LEN);
+    out.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    out.extend_from_slice(value);
+}
@@ -0,0 +1,233 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! HMAC-based one-time passwords: [HOTP] and [TOTP].
+//!
+//! A `Key` holds the shared secret, the HMAC digest algorithm, and the number
+//! of digits. HOTP codes are computed from a counter; TOTP codes from the
+//! number of `period`-second time steps since the Unix epoch.
+//!
+//! Verification accepts a code from a window of counters or time steps, to
+//! allow for clocks and counters that are out of sync, and returns the
+//! counter or time step that matched. The caller must store it and reject
+//! codes for it and for any earlier counter or time step afterwards, so that
+//! each code can be used only once.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::{digest, otp};
+//!
+//! // The secret is usually shared with the user's authenticator app in a
+//! // QR code.
+//! let key = otp::Key::new(&digest::SHA1, 6, b"12345678901234567890")?;
+//! let totp = otp::Totp::new(key, 30)?;
+//!
+//! let now = 1_111_111_109; // Seconds since the Unix epoch.
+//! let code = totp.generate(now);
+//! assert_eq!(code.as_str(), "081804");
+//!
+//! // Accept codes from one time step before or after the current one.
+//! let time_step = totp.verify(now + 30, 1, "081804")?;
+//! assert_eq!(time_step, now / 30);
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [HOTP]: https://tools.ietf.org/html/rfc4226
+//! [TOTP]: https://tools.ietf.org/html/rfc6238
+
+use crate::{constant_time, digest, error, hmac};
+
+/// The smallest number of digits a code may have.
+pub const MIN_DIGITS: usize = 6;
+
+/// The largest number of digits a code may have.
+pub const MAX_DIGITS: usize = 8;
+
+/// A key for computing and verifying one-time passwords.
+pub struct Key {
+    key: hmac::Key,
+    digits: usize,
+}
+
+impl Key {
+    /// Constructs a key for codes of `digits` digits, computed with HMAC
+    /// using `digest_alg` and the shared secret `secret`.
+    ///
+    /// `digest_alg` is usually `digest::SHA1`, as in RFC 4226, or
+    /// `digest::SHA256` or `digest::SHA512`, which RFC 6238 also allows.
+    /// Fails unless `digits` is at least `MIN_DIGITS` and at most
+    /// `MAX_DIGITS`.
+    pub fn new(
+        digest_alg: &'static digest::Algorithm,
+        digits: usize,
+        secret: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        if digits < MIN_DIGITS || digits > MAX_DIGITS {
+            return Err(error::Unspecified);
+        }
+        Ok(Self {
+            key: hmac::Key::new(digest_alg, secret),
+            digits,
+        })
+    }
+
+    /// The number of digits of the codes.
+    #[inline]
+    pub fn digits(&self) -> usize {
+        self.digits
+    }
+
+    /// Computes the HOTP code for `counter`.
+    pub fn hotp(&self, counter: u64) -> Code {
+        let tag = hmac::sign(&self.key, &counter.to_be_bytes());
+        let tag = tag.as_ref();
+
+        // Dynamic truncation (RFC 4226 Section 5.3).
+        let offset = usize::from(tag[tag.len() - 1] & 0xf);
+        let mut binary = [0u8; 4];
+        binary.copy_from_slice(&tag[offset..(offset + 4)]);
+        let mut value = u32::from_be_bytes(binary) & 0x7fff_ffff;
+
+        let mut code = Code {
+            digits: [0u8; MAX_DIGITS],
+            len: self.digits,
+        };
+        for digit in code.digits[..self.digits].iter_mut().rev() {
+            *digit = b'0' + (value % 10) as u8;
+            value /= 10;
+        }
+        code
+    }
+
+    /// Verifies that `code` is the HOTP code for one of the counters from
+    /// `counter` to `counter + look_ahead`, and returns that counter.
+    ///
+    /// After a successful verification, the next expected counter is the
+    /// returned counter plus one.
+    pub fn verify_hotp(
+        &self,
+        counter: u64,
+        look_ahead: u64,
+        code: &str,
+    ) -> Result<u64, error::Unspecified> {
+        let last = counter.checked_add(look_ahead).ok_or(error::Unspecified)?;
+        self.verify_range(counter, last, code)
+    }
+
+    fn verify_range(&self, first: u64, last: u64, code: &str) -> Result<u64, error::Unspecified> {
+        (first..=last)
+            .find(|&counter| {
+                constant_time::verify_slices_are_equal(
+                    self.hotp(counter).as_str().as_bytes(),
+                    code.as_bytes(),
+                )
+                .is_ok()
+            })
+            .ok_or(error::Unspecified)
+    }
+}
+
+derive_debug_via_field!(Key, digits);
+
+/// A key for computing and verifying time-based one-time passwords.
+#[derive(Debug)]
+pub struct Totp {
+    key: Key,
+    period: u64,
+}
+
+impl Totp {
+    /// Constructs a TOTP key with time steps of `period` seconds, usually
+    /// 30, counted from the Unix epoch.
+    ///
+    /// Fails if `period` is zero.
+    pub fn new(key: Key, period: u64) -> Result<Self, error::Unspecified> {
+        if period == 0 {
+            return Err(error::Unspecified);
+        }
+        Ok(Self { key, period })
+    }
+
+    /// The underlying key.
+    #[inline]
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    /// The length of a time step in seconds.
+    #[inline]
+    pub fn period(&self) -> u64 {
+        self.period
+    }
+
+    /// The time step that contains `time`, in seconds since the Unix epoch.
+    #[inline]
+    pub fn time_step(&self, time: u64) -> u64 {
+        time / self.period
+    }
+
+    /// Computes the TOTP code for `time`, in seconds since the Unix epoch.
+    pub fn generate(&self, time: u64) -> Code {
+        self.key.hotp(self.time_step(time))
+    }
+
+    /// Verifies that `code` is the TOTP code for the time step that contains
+    /// `time`, or for one of the `drift` time steps before or after it, and
+    /// returns that time step.
+    pub fn verify(&self, time: u64, drift: u64, code: &str) -> Result<u64, error::Unspecified> {
+        let time_step = self.time_step(time);
+        self.key.verify_range(
+            time_step.saturating_sub(drift),
+            time_step.saturating_add(drift),
+            code,
+        )
+    }
+}
+
+/// A one-time password: a string of decimal digits.
+#[derive(Clone, Copy)]
+pub struct Code {
+    digits: [u8; MAX_DIGITS],
+    len: usize,
+}
+
+impl Code {
+    /// The code as a string.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // `digits` only contains ASCII digits.
+        core::str::from_utf8(&self.digits[..self.len]).unwrap()
+    }
+}
+
+impl AsRef<str> for Code {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl core::fmt::Display for Code {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl core::fmt::Debug for Code {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Code").finish()
+    }
+}
@@ -0,0 +1,308 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Low-level P-256 group arithmetic.
+//!
+//! This is for implementing protocols that *ring* doesn't provide, such as
+//! ECVRF, blind signatures, and threshold ECDSA, using the same field and
+//! group arithmetic that *ring*'s ECDH and ECDSA use. Prefer the
+//! `agreement` and `signature` modules whenever they suffice; it is easy to
+//! build an insecure protocol out of these operations.
+//!
+//! `Scalar` is an integer modulo the group order *n*, and `Point` is a point
+//! on the curve, possibly the identity (the point at infinity).
+//!
+//! Scalar arithmetic and `Point::mul` and `Point::mul_base` are constant time.
+//! `Point::add` is constant time except when an input is the identity or the
+//! inputs are equal, negations of each other, or produce the identity.
+//! Encoding and decoding points is constant time with respect to a valid
+//! point, but not the point at infinity.
+
+use crate::{
+    ec::suite_b::{
+        ops::{self, p256, *},
+        private_key,
+        public_key::parse_uncompressed_point,
+    },
+    error,
+    limb::{self, AllowZero, Limb, LimbMask},
+    rand,
+};
+use untrusted;
+
+/// The length of an encoded scalar.
+pub const SCALAR_LEN: usize = 32;
+
+/// The length of an uncompressed point encoding.
+pub const UNCOMPRESSED_POINT_LEN: usize = 1 + (2 * ELEM_LEN);
+
+/// The length of a compressed point encoding.
+pub const COMPRESSED_POINT_LEN: usize = 1 + ELEM_LEN;
+
+const ELEM_LEN: usize = 32;
+
+/// An integer modulo the order *n* of the P-256 group.
+#[derive(Clone, Copy)]
+pub struct Scalar(ops::Scalar);
+
+impl Scalar {
+    /// Zero.
+    pub fn zero() -> Self {
+        Scalar(ops::Scalar::zero())
+    }
+
+    /// One.
+    pub fn one() -> Self {
+        Self::from_small(1)
+    }
+
+    /// Generates a uniformly random nonzero scalar.
+    pub fn random(rng: &dyn rand::SecureRandom) -> Result<Self, error::Unspecified> {
+        private_key::random_scalar(&p256::PRIVATE_KEY_OPS, rng).map(Scalar)
+    }
+
+    /// Decodes a 32-byte big-endian scalar, which must be less than *n*.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, error::Unspecified> {
+        if bytes.len() != SCALAR_LEN {
+            return Err(error::Unspecified);
+        }
+        scalar_parse_big_endian_variable(
+            &p256::COMMON_OPS,
+            AllowZero::Yes,
+            untrusted::Input::from(bytes),
+        )
+        .map(Scalar)
+    }
+
+    /// Decodes a 32-byte big-endian integer and reduces it modulo *n*.
+    ///
+    /// The result is biased; to map a hash output to a uniform scalar, reduce
+    /// a wider value instead.
+    pub fn from_bytes_reduced(bytes: &[u8; SCALAR_LEN]) -> Self {
+        // 2**256 < 2n, so any 256-bit value is partially reduced.
+        let r = scalar_parse_big_endian_partially_reduced_variable_consttime(
+            &p256::COMMON_OPS,
+            AllowZero::Yes,
+            untrusted::Input::from(bytes),
+        )
+        .unwrap();
+        Scalar(r)
+    }
+
+    /// Encodes the scalar as 32 big-endian bytes.
+    pub fn to_bytes(&self) -> [u8; SCALAR_LEN] {
+        let mut r = [0u8; SCALAR_LEN];
+        limb::big_endian_from_limbs(&self.0.limbs[..p256::COMMON_OPS.num_limbs], &mut r);
+        r
+    }
+
+    /// Returns whether the scalar is zero.
+    pub fn is_zero(&self) -> bool {
+        p256::COMMON_OPS.is_zero(&self.0)
+    }
+
+    /// Returns `self + other (mod n)`.
+    pub fn add(&self, other: &Self) -> Self {
+        Scalar(scalar_sum(&p256::COMMON_OPS, &self.0, &other.0))
+    }
+
+    /// Returns `self - other (mod n)`.
+    pub fn sub(&self, other: &Self) -> Self {
+        Scalar(scalar_difference(&p256::COMMON_OPS, &self.0, &other.0))
+    }
+
+    /// Returns `-self (mod n)`.
+    pub fn neg(&self) -> Self {
+        Self::zero().sub(self)
+    }
+
+    /// Returns `self * other (mod n)`.
+    pub fn mul(&self, other: &Self) -> Self {
+        let ops = &p256::PRIVATE_SCALAR_OPS;
+        let a = ops.scalar_ops.scalar_product(&self.0, &ops.oneRR_mod_n);
+        Scalar(ops.scalar_ops.scalar_product(&a, &other.0))
+    }
+
+    /// Returns `self**-1 (mod n)`. Fails if `self` is zero.
+    pub fn invert(&self) -> Result<Self, error::Unspecified> {
+        if self.is_zero() {
+            return Err(error::Unspecified);
+        }
+        let ops = &p256::SCALAR_OPS;
+        let inv = ops.scalar_inv_to_mont(&self.0);
+        Ok(Scalar(ops.scalar_product(&inv, &Self::one().0)))
+    }
+
+    // Reduces the big-endian integer `bytes`, which must be more than 32 and
+    // at most 64 bytes long, modulo *n*.
+    #[cfg(feature = "oprf")]
+    pub(crate) fn from_bytes_wide_reduced(bytes: &[u8]) -> Self {
+        // 2**256 (mod n).
+        const R_MOD_N: [u8; SCALAR_LEN] = [
+            0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x43, 0x19, 0x05, 0x52, 0x58, 0xe8, 0x61, 0x7b, 0x0c, 0x46, 0x35, 0x3d,
+            0x03, 0x9c, 0xda, 0xaf,
+        ];
+        assert!(bytes.len() > SCALAR_LEN && bytes.len() <= 2 * SCALAR_LEN);
+
+        // `bytes` is hi * 2**256 + lo.
+        let (hi, lo) = bytes.split_at(bytes.len() - SCALAR_LEN);
+        let mut hi_padded = [0u8; SCALAR_LEN];
+        hi_padded[(SCALAR_LEN - hi.len())..].copy_from_slice(hi);
+        let mut lo_bytes = [0u8; SCALAR_LEN];
+        lo_bytes.copy_from_slice(lo);
+        Self::from_bytes_reduced(&hi_padded)
+            .mul(&Self::from_bytes_reduced(&R_MOD_N))
+            .add(&Self::from_bytes_reduced(&lo_bytes))
+    }
+
+    fn from_small(value: u8) -> Self {
+        Self::from_bytes_reduced(&{
+            let mut bytes = [0u8; SCALAR_LEN];
+            bytes[SCALAR_LEN - 1] = value;
+            bytes
+        })
+    }
+}
+
+// Scalars are often secret, so don't print their values.
+impl core::fmt::Debug for Scalar {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.write_str("Scalar")
+    }
+}
+
+/// A point on the P-256 curve, or the identity.
+#[derive(Clone, Copy)]
+pub struct Point(ops::Point);
+
+impl Point {
+    /// The identity (the point at infinity).
+    pub fn identity() -> Self {
+        Point(ops::Point::new_at_infinity())
+    }
+
+    /// The standard generator *G*.
+    pub fn generator() -> Self {
+        Self::mul_base(&Scalar::one())
+    }
+
+    /// Decodes a point in the uncompressed or compressed encoding of
+    /// [SEC 1: Elliptic Curve Cryptography, Version 2.0] Section 2.3.3. The
+    /// point must be on the curve. The identity has no encoding.
+    ///
+    /// [SEC 1: Elliptic Curve Cryptography, Version 2.0]:
+    ///     http://www.secg.org/sec1-v2.pdf
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, error::Unspecified> {
+        let common = &p256::COMMON_OPS;
+        let affine = match (bytes.first(), bytes.len()) {
+            (Some(4), UNCOMPRESSED_POINT_LEN) => {
+                parse_uncompressed_point(&p256::PUBLIC_KEY_OPS, untrusted::Input::from(bytes))?
+            }
+            (Some(&prefix @ 2), COMPRESSED_POINT_LEN)
+            | (Some(&prefix @ 3), COMPRESSED_POINT_LEN) => {
+                let x = untrusted::Input::from(&bytes[1..])
+                    .read_all(error::Unspecified, |input| {
+                        p256::PUBLIC_KEY_OPS.elem_parse(input)
+                    })?;
+
+                // y**2 = x**3 + a*x + b.
+                let mut rhs = common.elem_squared(&x);
+                common.elem_add(&mut rhs, &common.a);
+                common.elem_mul(&mut rhs, &x);
+                common.elem_add(&mut rhs, &common.b);
+
+                let mut y = p256::p256_elem_sqrt_candidate(&rhs);
+                if common.elems_are_equal(&common.elem_squared(&y), &rhs) != LimbMask::True {
+                    return Err(error::Unspecified);
+                }
+                let y_is_odd = common.elem_unencoded(&y).limbs[0] & 1;
+                if y_is_odd != Limb::from(prefix & 1) {
+                    y = common.elem_negated(&y);
+                }
+                (x, y)
+            }
+            _ => {
+                return Err(error::Unspecified);
+            }
+        };
+        Ok(Point(common.point_from_affine(&affine)))
+    }
+
+    /// Encodes the point in uncompressed form. Fails for the identity.
+    pub fn to_uncompressed(&self) -> Result<[u8; UNCOMPRESSED_POINT_LEN], error::Unspecified> {
+        let mut r = [0u8; UNCOMPRESSED_POINT_LEN];
+        r[0] = 4;
+        let (x, y) = r[1..].split_at_mut(ELEM_LEN);
+        self.write_affine(x, Some(y))?;
+        Ok(r)
+    }
+
+    /// Encodes the point in compressed form. Fails for the identity.
+    pub fn to_compressed(&self) -> Result<[u8; COMPRESSED_POINT_LEN], error::Unspecified> {
+        let mut r = [0u8; COMPRESSED_POINT_LEN];
+        let mut y = [0u8; ELEM_LEN];
+        self.write_affine(&mut r[1..], Some(&mut y))?;
+        r[0] = 2 | (y[ELEM_LEN - 1] & 1);
+        Ok(r)
+    }
+
+    /// Returns whether the point is the identity.
+    pub fn is_identity(&self) -> bool {
+        let common = &p256::COMMON_OPS;
+        common.is_zero(&common.point_z(&self.0))
+    }
+
+    /// Returns `self + other`.
+    pub fn add(&self, other: &Self) -> Self {
+        Point(p256::COMMON_OPS.point_sum(&self.0, &other.0))
+    }
+
+    /// Returns `-self`.
+    pub fn neg(&self) -> Self {
+        Point(p256::COMMON_OPS.point_negated(&self.0))
+    }
+
+    /// Returns `scalar * self`.
+    pub fn mul(&self, scalar: &Scalar) -> Self {
+        if self.is_identity() {
+            return *self;
+        }
+        let ops = &p256::PRIVATE_KEY_OPS;
+        // `self` isn't the identity, so this can't fail.
+        let affine = private_key::affine_from_jacobian(ops, &self.0).unwrap();
+        Point(ops.point_mul(&scalar.0, &affine))
+    }
+
+    /// Returns `scalar * G`.
+    pub fn mul_base(scalar: &Scalar) -> Self {
+        Point(p256::PRIVATE_KEY_OPS.point_mul_base(&scalar.0))
+    }
+
+    fn write_affine(&self, x: &mut [u8], y: Option<&mut [u8]>) -> Result<(), error::Unspecified> {
+        if self.is_identity() {
+            return Err(error::Unspecified);
+        }
+        private_key::big_endian_affine_from_jacobian(&p256::PRIVATE_KEY_OPS, Some(x), y, &self.0)
+    }
+}
+
+impl core::fmt::Debug for Point {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self.to_uncompressed() {
+            Ok(encoded) => crate::debug::write_hex_tuple(f, "Point", &&encoded[..]),
+            Err(error::Unspecified) => f.write_str("Point(identity)"),
+        }
+    }
+}
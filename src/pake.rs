@@ -0,0 +1,65 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Password-authenticated key exchange.
+//!
+//! This implements [SPAKE2] with the ciphersuite
+//! SPAKE2-edwards25519-SHA256-HKDF-HMAC, including key confirmation. Two
+//! parties that share a low-entropy password, e.g. a code displayed on one
+//! device and typed into another, agree on a shared key; an attacker who
+//! doesn't know the password can test only one guess per protocol run.
+//!
+//! Each party starts with a `Spake2`, sends its `share()` to the other party,
+//! and passes the other party's share to `finish()`. Then each party sends
+//! its `confirmation()` to the other party, and passes the other party's
+//! confirmation message to `verify()`, which provides the shared key only if
+//! the confirmation message is valid.
+//!
+//! The password is not used directly: the parties start with the
+//! `PASSWORD_HASH_LEN`-byte output of a memory-hard function of the
+//! password, e.g. from `argon2`, which is reduced to the scalar `w`. In the
+//! transcript `TT`, `w` is encoded as a 32-byte big-endian number.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::{error, pake, rand};
+//!
+//! let rng = rand::SystemRandom::new();
+//!
+//! // Both parties derive this from the password.
+//! let password_hash = [0x42; pake::PASSWORD_HASH_LEN];
+//!
+//! let a = pake::Spake2::new(pake::Role::A, &password_hash, b"phone", b"laptop", &rng)?;
+//! let b = pake::Spake2::new(pake::Role::B, &password_hash, b"laptop", b"phone", &rng)?;
+//! let a_share = a.share().to_vec();
+//! let b_share = b.share().to_vec();
+//!
+//! let a = a.finish(&b_share, b"")?;
+//! let b = b.finish(&a_share, b"")?;
+//! let a_confirmation = a.confirmation().to_vec();
+//! let b_confirmation = b.confirmation().to_vec();
+//!
+//! let a_key = a.verify(&b_confirmation, error::Unspecified, |key| Ok(key.to_vec()))?;
+//! let b_key = b.verify(&a_confirmation, error::Unspecified, |key| Ok(key.to_vec()))?;
+//! assert_eq!(a_key, b_key);
+//!
+//! # Ok::<(), error::Unspecified>(())
+//! ```
+//!
+//! [SPAKE2]: https://tools.ietf.org/html/rfc9382
+
+pub use crate::ec::curve25519::spake2::{
+    KeyConfirmation, Role, Spake2, CONFIRMATION_LEN, PASSWORD_HASH_LEN, SHARED_KEY_LEN, SHARE_LEN,
+};
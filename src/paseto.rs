@@ -0,0 +1,295 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! PASETO version 4 tokens.
+//!
+//! See the [PASETO specification]. `v4.local` tokens are encrypted with
+//! XChaCha20 and authenticated with keyed BLAKE2b; `v4.public` tokens are
+//! signed with Ed25519. No other versions are supported.
+//!
+//! The footer is authenticated but not encrypted. The implicit assertion is
+//! authenticated but isn't part of the token, so the verifier must supply
+//! the same value that was used when the token was created. Validating the
+//! claims in the message is left to the caller.
+//!
+//! [PASETO specification]:
+//!     https://github.com/paseto-standard/paseto-spec/blob/master/docs/01-Protocol-Versions/Version4.md
+
+use crate::{
+    aead::{chacha, xchacha20_poly1305},
+    base64, constant_time,
+    digest::blake2b,
+    error,
+    polyfill::convert::*,
+    rand, signature,
+};
+use std::{string::String, vec::Vec};
+
+/// The length of a `v4.local` key.
+pub const LOCAL_KEY_LEN: usize = 32;
+
+/// A key for `v4.local` tokens.
+pub struct LocalKey {
+    key: [u8; LOCAL_KEY_LEN],
+}
+
+impl core::fmt::Debug for LocalKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("LocalKey").finish()
+    }
+}
+
+impl LocalKey {
+    /// Constructs a key from its raw bytes, which must be `LOCAL_KEY_LEN`
+    /// bytes long.
+    pub fn new(key_bytes: &[u8]) -> Result<Self, error::Unspecified> {
+        let key: &[u8; LOCAL_KEY_LEN] = key_bytes.try_into_()?;
+        Ok(Self { key: *key })
+    }
+
+    /// Encrypts `message` into a `v4.local` token, using a nonce generated
+    /// by `rng`.
+    pub fn encrypt(
+        &self,
+        message: &[u8],
+        footer: &[u8],
+        implicit_assertion: &[u8],
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<String, error::Unspecified> {
+        let mut nonce = [0u8; LOCAL_NONCE_LEN];
+        rng.fill(&mut nonce)?;
+
+        let mut payload = Vec::with_capacity(LOCAL_NONCE_LEN + message.len() + LOCAL_TAG_LEN);
+        payload.extend_from_slice(&nonce);
+        payload.extend_from_slice(message);
+
+        let (encryption_key, auth_key) = self.derive_keys(&nonce);
+        xchacha20_in_place(&encryption_key, &mut payload[LOCAL_NONCE_LEN..]);
+        let tag = local_tag(
+            &auth_key,
+            &nonce,
+            &payload[LOCAL_NONCE_LEN..],
+            footer,
+            implicit_assertion,
+        );
+        payload.extend_from_slice(tag.as_ref());
+
+        Ok(encode_token(LOCAL_HEADER, &payload, footer))
+    }
+
+    /// Authenticates and decrypts the `v4.local` token `token`, returning the
+    /// message.
+    ///
+    /// Fails unless the token's footer is exactly `footer`.
+    pub fn decrypt(
+        &self,
+        token: &str,
+        footer: &[u8],
+        implicit_assertion: &[u8],
+    ) -> Result<Vec<u8>, error::Unspecified> {
+        let mut payload = decode_token(LOCAL_HEADER, token, footer)?;
+        if payload.len() < LOCAL_NONCE_LEN + LOCAL_TAG_LEN {
+            return Err(error::Unspecified);
+        }
+        let tag_start = payload.len() - LOCAL_TAG_LEN;
+
+        let mut nonce = [0u8; LOCAL_NONCE_LEN];
+        nonce.copy_from_slice(&payload[..LOCAL_NONCE_LEN]);
+        let (encryption_key, auth_key) = self.derive_keys(&nonce);
+        let tag = local_tag(
+            &auth_key,
+            &nonce,
+            &payload[LOCAL_NONCE_LEN..tag_start],
+            footer,
+            implicit_assertion,
+        );
+        constant_time::verify_slices_are_equal(tag.as_ref(), &payload[tag_start..])?;
+
+        payload.truncate(tag_start);
+        let mut message = payload.split_off(LOCAL_NONCE_LEN);
+        xchacha20_in_place(&encryption_key, &mut message);
+        Ok(message)
+    }
+
+    // Returns the XChaCha20 key and nonce, concatenated, and the
+    // authentication key.
+    fn derive_keys(&self, nonce: &[u8; LOCAL_NONCE_LEN]) -> (blake2b::Digest, blake2b::Digest) {
+        let mut ctx = blake2b::Context::new(&self.key, chacha::KEY_LEN + xchacha20_poly1305::NONCE_LEN);
+        ctx.update(b"paseto-encryption-key");
+        ctx.update(nonce);
+        let encryption_key = ctx.finish();
+
+        let mut ctx = blake2b::Context::new(&self.key, LOCAL_AUTH_KEY_LEN);
+        ctx.update(b"paseto-auth-key-for-aead");
+        ctx.update(nonce);
+        let auth_key = ctx.finish();
+
+        (encryption_key, auth_key)
+    }
+}
+
+fn local_tag(
+    auth_key: &blake2b::Digest,
+    nonce: &[u8],
+    ciphertext: &[u8],
+    footer: &[u8],
+    implicit_assertion: &[u8],
+) -> blake2b::Digest {
+    let pre_auth = pae(&[
+        LOCAL_HEADER.as_bytes(),
+        nonce,
+        ciphertext,
+        footer,
+        implicit_assertion,
+    ]);
+    blake2b::digest(auth_key.as_ref(), LOCAL_TAG_LEN, &pre_auth)
+}
+
+// `key_and_nonce` is the 32-byte key followed by the 24-byte extended nonce.
+fn xchacha20_in_place(key_and_nonce: &blake2b::Digest, in_out: &mut [u8]) {
+    let (key, nonce) = key_and_nonce.as_ref().split_at(chacha::KEY_LEN);
+    let mut key_bytes = [0u8; chacha::KEY_LEN];
+    key_bytes.copy_from_slice(key);
+    let mut nonce_bytes = [0u8; xchacha20_poly1305::NONCE_LEN];
+    nonce_bytes.copy_from_slice(nonce);
+
+    let (subkey, nonce) = xchacha20_poly1305::derive_subkey(&key_bytes, &nonce_bytes);
+    subkey.encrypt_in_place(chacha::Counter::zero(nonce), in_out);
+}
+
+/// Signs `message` with `key_pair`, which must support
+/// `SignatureScheme::ED25519`, returning a `v4.public` token.
+///
+/// The message is not encrypted. `rng` is passed to `key_pair`; Ed25519
+/// signing itself doesn't use it.
+pub fn sign(
+    key_pair: &dyn signature::Signer,
+    message: &[u8],
+    footer: &[u8],
+    implicit_assertion: &[u8],
+    rng: &dyn rand::SecureRandom,
+) -> Result<String, error::Unspecified> {
+    let pre_auth = pae(&[
+        PUBLIC_HEADER.as_bytes(),
+        message,
+        footer,
+        implicit_assertion,
+    ]);
+    let signature = key_pair.sign(signature::SignatureScheme::ED25519, rng, &pre_auth)?;
+    if signature.len() != PUBLIC_SIGNATURE_LEN {
+        return Err(error::Unspecified);
+    }
+
+    let mut payload = Vec::with_capacity(message.len() + PUBLIC_SIGNATURE_LEN);
+    payload.extend_from_slice(message);
+    payload.extend_from_slice(&signature);
+    Ok(encode_token(PUBLIC_HEADER, &payload, footer))
+}
+
+/// Verifies the `v4.public` token `token` with the Ed25519 public key
+/// `public_key`, returning the message.
+///
+/// Fails unless the token's footer is exactly `footer`.
+pub fn verify(
+    public_key: &[u8],
+    token: &str,
+    footer: &[u8],
+    implicit_assertion: &[u8],
+) -> Result<Vec<u8>, error::Unspecified> {
+    let mut payload = decode_token(PUBLIC_HEADER, token, footer)?;
+    if payload.len() < PUBLIC_SIGNATURE_LEN {
+        return Err(error::Unspecified);
+    }
+    let message_len = payload.len() - PUBLIC_SIGNATURE_LEN;
+    {
+        let (message, signature) = payload.split_at(message_len);
+        let pre_auth = pae(&[
+            PUBLIC_HEADER.as_bytes(),
+            message,
+            footer,
+            implicit_assertion,
+        ]);
+        signature::UnparsedPublicKey::new(&signature::ED25519, public_key)
+            .verify(&pre_auth, signature)?;
+    }
+    payload.truncate(message_len);
+    Ok(payload)
+}
+
+/// The pre-authentication encoding (PAE) of `pieces`.
+///
+/// This is the count of pieces followed by each piece prefixed with its
+/// length, where all integers are 64-bit little-endian with the most
+/// significant bit cleared.
+pub fn pae(pieces: &[&[u8]]) -> Vec<u8> {
+    fn le64(n: usize) -> [u8; 8] {
+        ((n as u64) & 0x7fff_ffff_ffff_ffff).to_le_bytes()
+    }
+
+    let len = pieces.iter().map(|piece| 8 + piece.len()).sum::<usize>();
+    let mut out = Vec::with_capacity(8 + len);
+    out.extend_from_slice(&le64(pieces.len()));
+    for piece in pieces {
+        out.extend_from_slice(&le64(piece.len()));
+        out.extend_from_slice(piece);
+    }
+    out
+}
+
+fn encode_token(header: &str, payload: &[u8], footer: &[u8]) -> String {
+    let mut token = String::from(header);
+    token.push_str(&base64::url_encode(payload));
+    if !footer.is_empty() {
+        token.push('.');
+        token.push_str(&base64::url_encode(footer));
+    }
+    token
+}
+
+// Returns the decoded payload of `token` after checking its header and
+// footer.
+fn decode_token(header: &str, token: &str, footer: &[u8]) -> Result<Vec<u8>, error::Unspecified> {
+    if !token.starts_with(header) {
+        return Err(error::Unspecified);
+    }
+    let mut parts = token[header.len()..].split('.');
+    let payload = parts.next().ok_or(error::Unspecified)?;
+    let received_footer = parts.next();
+    if parts.next().is_some() {
+        return Err(error::Unspecified);
+    }
+
+    // An empty footer is encoded by omitting it, so a token that has a
+    // footer part must have a non-empty footer.
+    match (received_footer, footer.is_empty()) {
+        (None, true) => (),
+        (Some(received_footer), false) => {
+            let received_footer = base64::url_decode(received_footer)?;
+            constant_time::verify_slices_are_equal(&received_footer, footer)?;
+        }
+        _ => {
+            return Err(error::Unspecified);
+        }
+    }
+
+    base64::url_decode(payload)
+}
+
+const LOCAL_HEADER: &str = "v4.local.";
+const PUBLIC_HEADER: &str = "v4.public.";
+
+const LOCAL_NONCE_LEN: usize = 32;
+const LOCAL_AUTH_KEY_LEN: usize = 32;
+const LOCAL_TAG_LEN: usize = 32;
+const PUBLIC_SIGNATURE_LEN: usize = 64;
@@ -0,0 +1,250 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! PEM, the textual encoding of keys and other DER-encoded structures
+//! specified in [RFC 7468].
+//!
+//! A PEM block is a label and base64-encoded contents between
+//! `-----BEGIN <label>-----` and `-----END <label>-----` lines. The contents
+//! of a `PRIVATE KEY` block can be passed to `Ed25519KeyPair::from_pkcs8()`,
+//! `EcdsaKeyPair::from_pkcs8()`, or `RsaKeyPair::from_pkcs8()`, and those of
+//! an `EC PRIVATE KEY` block to `EcdsaKeyPair::from_ec_private_key()`. For a
+//! `PUBLIC KEY` block, `Pem::subject_public_key()` returns the public key in
+//! the form that `signature::UnparsedPublicKey` expects.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::{pem, rand, signature};
+//!
+//! let rng = rand::SystemRandom::new();
+//! let pkcs8 = signature::Ed25519KeyPair::generate_pkcs8(&rng)?;
+//! let encoded = pem::encode(pem::PRIVATE_KEY, pkcs8.as_ref());
+//!
+//! let decoded = pem::parse(&encoded)?;
+//! assert_eq!(decoded.label(), pem::PRIVATE_KEY);
+//! let key_pair = signature::Ed25519KeyPair::from_pkcs8(decoded.contents())
+//!     .map_err(|_| ring::error::Unspecified)?;
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [RFC 7468]: https://tools.ietf.org/html/rfc7468
+
+use crate::{base64, error, spki};
+use std::{string::String, vec::Vec};
+use untrusted;
+
+/// The label of a PKCS#8 `PrivateKeyInfo` or `OneAsymmetricKey`.
+pub const PRIVATE_KEY: &str = "PRIVATE KEY";
+
+/// The label of a `SubjectPublicKeyInfo`.
+pub const PUBLIC_KEY: &str = "PUBLIC KEY";
+
+/// The label of an [RFC 5915] `ECPrivateKey`.
+///
+/// [RFC 5915]: https://tools.ietf.org/html/rfc5915
+pub const EC_PRIVATE_KEY: &str = "EC PRIVATE KEY";
+
+/// The label of a [RFC 8017] `RSAPrivateKey`.
+///
+/// [RFC 8017]: https://tools.ietf.org/html/rfc8017#appendix-A.1.2
+pub const RSA_PRIVATE_KEY: &str = "RSA PRIVATE KEY";
+
+/// A PEM block.
+///
+/// The contents may be a private key, so they aren't included in the `Debug`
+/// output.
+#[derive(Clone)]
+pub struct Pem {
+    label: String,
+    contents: Vec<u8>,
+}
+
+derive_debug_via_field!(Pem, label);
+
+impl Pem {
+    /// Constructs a PEM block with the given label and contents.
+    ///
+    /// Fails if `label` isn't a valid label; see [RFC 7468 Section 3].
+    ///
+    /// [RFC 7468 Section 3]: https://tools.ietf.org/html/rfc7468#section-3
+    pub fn new(label: &str, contents: &[u8]) -> Result<Self, error::Unspecified> {
+        if !is_valid_label(label) {
+            return Err(error::Unspecified);
+        }
+        Ok(Self {
+            label: String::from(label),
+            contents: contents.to_vec(),
+        })
+    }
+
+    /// The label, e.g. `PRIVATE_KEY`.
+    #[inline]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The decoded contents.
+    #[inline]
+    pub fn contents(&self) -> &[u8] {
+        &self.contents
+    }
+
+    /// The `subjectPublicKey` of the `SubjectPublicKeyInfo` in a `PUBLIC KEY`
+    /// block.
+    ///
+    /// This is the public key in the form that `signature::UnparsedPublicKey`
+    /// expects: the encoded point for ECDSA, the 32-byte key for Ed25519, and
+    /// the DER-encoded `RSAPublicKey` for RSA. The algorithm identifier isn't
+    /// checked; the verification algorithm determines how the key is used.
+    /// Use `signature::UnparsedPublicKey::from_spki_der()` with `contents()`
+    /// to check it.
+    ///
+    /// Fails if the label isn't `PUBLIC_KEY` or the contents aren't a valid
+    /// `SubjectPublicKeyInfo`.
+    pub fn subject_public_key(&self) -> Result<&[u8], error::Unspecified> {
+        if self.label != PUBLIC_KEY {
+            return Err(error::Unspecified);
+        }
+        let (_algorithm, public_key) = spki::parse(untrusted::Input::from(&self.contents))?;
+        Ok(public_key.as_slice_less_safe())
+    }
+
+    /// Encodes the block, with the base64-encoded contents wrapped at 64
+    /// characters.
+    pub fn encode(&self) -> String {
+        encode(&self.label, &self.contents)
+    }
+}
+
+/// Encodes `contents` as a PEM block labeled `label`, with the
+/// base64-encoded contents wrapped at 64 characters.
+///
+/// Every line, including the last, ends with `"\n"`.
+pub fn encode(label: &str, contents: &[u8]) -> String {
+    let encoded = base64::encode(contents);
+    let mut out = String::new();
+    out.push_str(BEGIN);
+    out.push_str(label);
+    out.push_str(DASHES);
+    out.push('\n');
+    for line in encoded.as_bytes().chunks(LINE_LEN) {
+        // `encoded` is ASCII.
+        out.push_str(core::str::from_utf8(line).unwrap());
+        out.push('\n');
+    }
+    out.push_str(END);
+    out.push_str(label);
+    out.push_str(DASHES);
+    out.push('\n');
+    out
+}
+
+/// Parses the first PEM block in `input`.
+///
+/// Any text before the `-----BEGIN` line or after the `-----END` line is
+/// ignored, as [RFC 7468 Section 5.2] allows. Lines may end with either
+/// `"\n"` or `"\r\n"` and have trailing whitespace. The base64-encoded
+/// contents must be padded and use the standard alphabet.
+///
+/// [RFC 7468 Section 5.2]: https://tools.ietf.org/html/rfc7468#section-5.2
+pub fn parse(input: &str) -> Result<Pem, error::Unspecified> {
+    let mut lines = input.lines();
+    parse_next(&mut lines)?.ok_or(error::Unspecified)
+}
+
+/// Parses every PEM block in `input`, e.g. a certificate chain.
+///
+/// Fails if any block is invalid. Text between blocks is ignored.
+pub fn parse_all(input: &str) -> Result<Vec<Pem>, error::Unspecified> {
+    let mut lines = input.lines();
+    let mut blocks = Vec::new();
+    while let Some(block) = parse_next(&mut lines)? {
+        blocks.push(block);
+    }
+    Ok(blocks)
+}
+
+fn parse_next<'a>(
+    lines: &mut impl Iterator<Item = &'a str>,
+) -> Result<Option<Pem>, error::Unspecified> {
+    let label = loop {
+        let line = match lines.next() {
+            Some(line) => line.trim_end(),
+            None => {
+                return Ok(None);
+            }
+        };
+        if let Some(label) = strip_delimiters(line, BEGIN) {
+            break label;
+        }
+    };
+    if !is_valid_label(label) {
+        return Err(error::Unspecified);
+    }
+
+    let mut encoded = String::new();
+    loop {
+        let line = lines.next().ok_or(error::Unspecified)?.trim_end();
+        if let Some(end_label) = strip_delimiters(line, END) {
+            if end_label != label {
+                return Err(error::Unspecified);
+            }
+            break;
+        }
+        encoded.push_str(line);
+    }
+
+    Ok(Some(Pem {
+        label: String::from(label),
+        contents: base64::decode(&encoded)?,
+    }))
+}
+
+fn strip_delimiters<'a>(line: &'a str, prefix: &str) -> Option<&'a str> {
+    if line.starts_with(prefix)
+        && line.ends_with(DASHES)
+        && line.len() >= prefix.len() + DASHES.len()
+    {
+        Some(&line[prefix.len()..(line.len() - DASHES.len())])
+    } else {
+        None
+    }
+}
+
+// https://tools.ietf.org/html/rfc7468#section-3:
+//
+//     labelchar = %x21-2C / %x2E-7E ; any printable character,
+//                                   ; except hyphen-minus
+//     label = [ labelchar *( ["-" / SP] labelchar ) ]
+fn is_valid_label(label: &str) -> bool {
+    let is_labelchar = |b: u8| (0x21..=0x7e).contains(&b) && b != b'-';
+    let mut previous_was_separator = true;
+    for &b in label.as_bytes() {
+        if is_labelchar(b) {
+            previous_was_separator = false;
+        } else if (b == b'-' || b == b' ') && !previous_was_separator {
+            previous_was_separator = true;
+        } else {
+            return false;
+        }
+    }
+    label.is_empty() || !previous_was_separator
+}
+
+const BEGIN: &str = "-----BEGIN ";
+const END: &str = "-----END ";
+const DASHES: &str = "-----";
+const LINE_LEN: usize = 64;
@@ -0,0 +1,492 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! PKCS#10 certification requests and self-signed X.509 certificates.
+//!
+//! `certification_request()` builds and signs a `CertificationRequest`
+//! ([RFC 2986]) and `self_signed_certificate()` builds and signs a simple
+//! X.509 v3 certificate ([RFC 5280]), for provisioning keys generated with
+//! this crate. Both are DER-encoded; use `pem::encode()` with the label
+//! `CERTIFICATE REQUEST` or `CERTIFICATE` for the PEM encoding.
+//!
+//! The subject's distinguished name is encoded with each attribute in its own
+//! relative distinguished name, in the order given, as most tools do. The
+//! only extension that is supported is the subject alternative name extension
+//! with DNS names; in a request, it is in an `extensionRequest` attribute.
+//!
+//! Any `signature::Signer` can be used, e.g. an `EcdsaKeyPair` constructed
+//! with the `_ASN1` signing algorithm that matches `SignatureAlgorithm`, an
+//! `Ed25519KeyPair`, an `RsaKeyPair`, or a key held in an HSM.
+//!
+//! [RFC 2986]: https://tools.ietf.org/html/rfc2986
+//! [RFC 5280]: https://tools.ietf.org/html/rfc5280
+
+use crate::{
+    error,
+    io::{
+        der::Tag,
+        der_writer::{self, write_tlv},
+        writer::Accumulator,
+    },
+    rand,
+    signature::{self, SignatureScheme},
+};
+use std::vec::Vec;
+
+/// A signature algorithm for requests and certificates.
+pub struct SignatureAlgorithm {
+    scheme: SignatureScheme,
+    public_key_alg_id: &'static [u8],
+    signature_alg_id: &'static [u8],
+}
+
+derive_debug_via_field!(SignatureAlgorithm, scheme);
+
+/// ECDSA using P-256 and SHA-256 (`ecdsa-with-SHA256`).
+pub static ECDSA_P256_SHA256: SignatureAlgorithm = SignatureAlgorithm {
+    scheme: SignatureScheme::ECDSA_P256_SHA256_ASN1,
+    public_key_alg_id: include_bytes!("data/alg-ec-public-key-p256.der"),
+    signature_alg_id: include_bytes!("data/alg-ecdsa-sha256.der"),
+};
+
+/// ECDSA using P-384 and SHA-384 (`ecdsa-with-SHA384`).
+pub static ECDSA_P384_SHA384: SignatureAlgorithm = SignatureAlgorithm {
+    scheme: SignatureScheme::ECDSA_P384_SHA384_ASN1,
+    public_key_alg_id: include_bytes!("data/alg-ec-public-key-p384.der"),
+    signature_alg_id: include_bytes!("data/alg-ecdsa-sha384.der"),
+};
+
+/// Ed25519 (`id-Ed25519`).
+pub static ED25519: SignatureAlgorithm = SignatureAlgorithm {
+    scheme: SignatureScheme::ED25519,
+    public_key_alg_id: include_bytes!("data/alg-ed25519.der"),
+    signature_alg_id: include_bytes!("data/alg-ed25519.der"),
+};
+
+/// Ed448 (`id-Ed448`).
+pub static ED448: SignatureAlgorithm = SignatureAlgorithm {
+    scheme: SignatureScheme::ED448,
+    public_key_alg_id: include_bytes!("data/alg-ed448.der"),
+    signature_alg_id: include_bytes!("data/alg-ed448.der"),
+};
+
+/// RSA PKCS#1 1.5 padding using SHA-256 (`sha256WithRSAEncryption`).
+pub static RSA_PKCS1_SHA256: SignatureAlgorithm = SignatureAlgorithm {
+    scheme: SignatureScheme::RSA_PKCS1_SHA256,
+    public_key_alg_id: include_bytes!("data/alg-rsa-encryption.der"),
+    signature_alg_id: include_bytes!("data/alg-rsa-pkcs1-sha256.der"),
+};
+
+/// RSA PKCS#1 1.5 padding using SHA-384 (`sha384WithRSAEncryption`).
+pub static RSA_PKCS1_SHA384: SignatureAlgorithm = SignatureAlgorithm {
+    scheme: SignatureScheme::RSA_PKCS1_SHA384,
+    public_key_alg_id: include_bytes!("data/alg-rsa-encryption.der"),
+    signature_alg_id: include_bytes!("data/alg-rsa-pkcs1-sha384.der"),
+};
+
+/// RSA PKCS#1 1.5 padding using SHA-512 (`sha512WithRSAEncryption`).
+pub static RSA_PKCS1_SHA512: SignatureAlgorithm = SignatureAlgorithm {
+    scheme: SignatureScheme::RSA_PKCS1_SHA512,
+    public_key_alg_id: include_bytes!("data/alg-rsa-encryption.der"),
+    signature_alg_id: include_bytes!("data/alg-rsa-pkcs1-sha512.der"),
+};
+
+/// RSA PSS padding using SHA-256 for the digest and MGF1, and a 32-byte
+/// salt (`id-RSASSA-PSS`).
+pub static RSA_PSS_SHA256: SignatureAlgorithm = SignatureAlgorithm {
+    scheme: SignatureScheme::RSA_PSS_SHA256,
+    public_key_alg_id: include_bytes!("data/alg-rsa-encryption.der"),
+    signature_alg_id: include_bytes!("data/alg-rsa-pss-sha256.der"),
+};
+
+/// RSA PSS padding using SHA-384 for the digest and MGF1, and a 48-byte
+/// salt (`id-RSASSA-PSS`).
+pub static RSA_PSS_SHA384: SignatureAlgorithm = SignatureAlgorithm {
+    scheme: SignatureScheme::RSA_PSS_SHA384,
+    public_key_alg_id: include_bytes!("data/alg-rsa-encryption.der"),
+    signature_alg_id: include_bytes!("data/alg-rsa-pss-sha384.der"),
+};
+
+/// RSA PSS padding using SHA-512 for the digest and MGF1, and a 64-byte
+/// salt (`id-RSASSA-PSS`).
+pub static RSA_PSS_SHA512: SignatureAlgorithm = SignatureAlgorithm {
+    scheme: SignatureScheme::RSA_PSS_SHA512,
+    public_key_alg_id: include_bytes!("data/alg-rsa-encryption.der"),
+    signature_alg_id: include_bytes!("data/alg-rsa-pss-sha512.der"),
+};
+
+/// An attribute of a distinguished name.
+#[derive(Clone, Copy, Debug)]
+pub enum NameAttribute<'a> {
+    /// `commonName` (CN).
+    CommonName(&'a str),
+
+    /// `countryName` (C); a two-letter ISO 3166 country code.
+    Country(&'a str),
+
+    /// `localityName` (L).
+    Locality(&'a str),
+
+    /// `organizationName` (O).
+    Organization(&'a str),
+
+    /// `organizationalUnitName` (OU).
+    OrganizationalUnit(&'a str),
+
+    /// `stateOrProvinceName` (ST).
+    StateOrProvince(&'a str),
+}
+
+impl<'a> NameAttribute<'a> {
+    // The OID, the value, and the upper bound of the length of the value
+    // from RFC 5280 Appendix A.1.
+    fn oid_and_value(&self) -> (&'static [u8], &'a str, usize) {
+        match *self {
+            NameAttribute::CommonName(value) => (OID_COMMON_NAME, value, 64),
+            NameAttribute::Country(value) => (OID_COUNTRY, value, 2),
+            NameAttribute::Locality(value) => (OID_LOCALITY, value, 128),
+            NameAttribute::Organization(value) => (OID_ORGANIZATION, value, 64),
+            NameAttribute::OrganizationalUnit(value) => (OID_ORGANIZATIONAL_UNIT, value, 64),
+            NameAttribute::StateOrProvince(value) => (OID_STATE_OR_PROVINCE, value, 128),
+        }
+    }
+}
+
+/// The contents of a request or certificate.
+#[derive(Clone, Copy, Debug)]
+pub struct Params<'a> {
+    /// The attributes of the subject's distinguished name. For a
+    /// self-signed certificate, this is also the issuer's name.
+    pub subject: &'a [NameAttribute<'a>],
+
+    /// The DNS names for the subject alternative name extension. The
+    /// extension is omitted if this is empty.
+    pub dns_names: &'a [&'a str],
+}
+
+impl<'a> Params<'a> {
+    fn check(&self) -> Result<(), error::Unspecified> {
+        for attribute in self.subject {
+            let (_, value, max_len) = attribute.oid_and_value();
+            if value.is_empty() || value.len() > max_len {
+                return Err(error::Unspecified);
+            }
+            if let NameAttribute::Country(value) = attribute {
+                if value.len() != 2 || !value.bytes().all(|b| b.is_ascii_uppercase()) {
+                    return Err(error::Unspecified);
+                }
+            }
+        }
+        if self.dns_names.len() > MAX_DNS_NAMES {
+            return Err(error::Unspecified);
+        }
+        for dns_name in self.dns_names {
+            if dns_name.is_empty()
+                || dns_name.len() > MAX_DNS_NAME_LEN
+                || !dns_name
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'.' || b == b'*')
+            {
+                return Err(error::Unspecified);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The validity period of a certificate, in seconds since the Unix epoch.
+#[derive(Clone, Copy, Debug)]
+pub struct Validity {
+    /// The first second the certificate is valid.
+    pub not_before: u64,
+
+    /// The last second the certificate is valid.
+    pub not_after: u64,
+}
+
+/// The maximum number of DNS names in `Params::dns_names`.
+///
+/// This keeps requests and certificates short enough for `io::der`.
+pub const MAX_DNS_NAMES: usize = 100;
+
+/// Builds a `CertificationRequest` for `key`'s public key and signs it with
+/// `key` using `algorithm`, returning its DER encoding.
+///
+/// Fails if `key` doesn't support `algorithm`, or if `params` contains an
+/// attribute that is empty or longer than its upper bound in RFC 5280, a
+/// country that isn't two uppercase letters, more than `MAX_DNS_NAMES` DNS
+/// names, or a DNS name that is longer than 253 bytes or has characters
+/// other than letters, digits, `-`, `.`, and `*`.
+pub fn certification_request(
+    algorithm: &'static SignatureAlgorithm,
+    key: &dyn signature::Signer,
+    params: &Params,
+    rng: &dyn rand::SecureRandom,
+) -> Result<Vec<u8>, error::Unspecified> {
+    if !key.supports(algorithm.scheme) {
+        return Err(error::Unspecified);
+    }
+    params.check()?;
+
+    let request_info = der_writer::write_all(Tag::Sequence, &|output| {
+        write_small_integer(output, 0); // version
+        write_name(output, params.subject);
+        write_spki(output, algorithm, key.public_key_bytes());
+        write_tlv(output, Tag::ContextSpecificConstructed0, |output| {
+            if !params.dns_names.is_empty() {
+                write_tlv(output, Tag::Sequence, |output| {
+                    write_oid(output, OID_EXTENSION_REQUEST);
+                    write_tlv(output, Tag::Set, |output| {
+                        write_extensions(output, params.dns_names)
+                    });
+                });
+            }
+        });
+    });
+    sign(algorithm, key, &request_info, rng)
+}
+
+/// Builds an X.509 v3 certificate for `key`'s public key, issued by the
+/// subject itself, and signs it with `key` using `algorithm`, returning its
+/// DER encoding.
+///
+/// The serial number is generated randomly using `rng`.
+///
+/// Fails in the same cases as `certification_request()`, or if
+/// `validity.not_before` is later than `validity.not_after`.
+pub fn self_signed_certificate(
+    algorithm: &'static SignatureAlgorithm,
+    key: &dyn signature::Signer,
+    params: &Params,
+    validity: Validity,
+    rng: &dyn rand::SecureRandom,
+) -> Result<Vec<u8>, error::Unspecified> {
+    if !key.supports(algorithm.scheme) {
+        return Err(error::Unspecified);
+    }
+    params.check()?;
+    if validity.not_before > validity.not_after {
+        return Err(error::Unspecified);
+    }
+    let not_before = Time::new(validity.not_before)?;
+    let not_after = Time::new(validity.not_after)?;
+
+    // A positive, minimally-encoded serial number with 126 random bits.
+    let mut serial_number = [0; SERIAL_NUMBER_LEN];
+    rng.fill(&mut serial_number)?;
+    serial_number[0] = (serial_number[0] & 0x3f) | 0x40;
+
+    let tbs_certificate = der_writer::write_all(Tag::Sequence, &|output| {
+        write_tlv(output, Tag::ContextSpecificConstructed0, |output| {
+            write_small_integer(output, 2) // v3
+        });
+        write_tlv(output, Tag::Integer, |output| {
+            output.write_bytes(&serial_number)
+        });
+        write_algorithm_id(output, algorithm.signature_alg_id);
+        write_name(output, params.subject); // issuer
+        write_tlv(output, Tag::Sequence, |output| {
+            not_before.write(output);
+            not_after.write(output);
+        });
+        write_name(output, params.subject);
+        write_spki(output, algorithm, key.public_key_bytes());
+        if !params.dns_names.is_empty() {
+            write_tlv(output, Tag::ContextSpecificConstructed3, |output| {
+                write_extensions(output, params.dns_names)
+            });
+        }
+    });
+    sign(algorithm, key, &tbs_certificate, rng)
+}
+
+// Signs `tbs` and wraps it, the signature algorithm, and the signature in a
+// `SEQUENCE`, as both `CertificationRequest` and `Certificate` are.
+fn sign(
+    algorithm: &'static SignatureAlgorithm,
+    key: &dyn signature::Signer,
+    tbs: &[u8],
+    rng: &dyn rand::SecureRandom,
+) -> Result<Vec<u8>, error::Unspecified> {
+    let signature = key.sign(algorithm.scheme, rng, tbs)?;
+    let signed = der_writer::write_all(Tag::Sequence, &|output| {
+        output.write_bytes(tbs);
+        write_algorithm_id(output, algorithm.signature_alg_id);
+        write_bit_string(output, &signature);
+    });
+    Ok(signed.into_vec())
+}
+
+fn write_name(output: &mut dyn Accumulator, attributes: &[NameAttribute]) {
+    write_tlv(output, Tag::Sequence, |output| {
+        for attribute in attributes {
+            let (oid, value, _) = attribute.oid_and_value();
+            let tag = match attribute {
+                NameAttribute::Country(_) => Tag::PrintableString,
+                _ => Tag::UTF8String,
+            };
+            write_tlv(output, Tag::Set, |output| {
+                write_tlv(output, Tag::Sequence, |output| {
+                    write_oid(output, oid);
+                    write_tlv(output, tag, |output| output.write_bytes(value.as_bytes()));
+                })
+            });
+        }
+    })
+}
+
+fn write_spki(output: &mut dyn Accumulator, algorithm: &SignatureAlgorithm, public_key: &[u8]) {
+    write_tlv(output, Tag::Sequence, |output| {
+        write_algorithm_id(output, algorithm.public_key_alg_id);
+        write_bit_string(output, public_key);
+    })
+}
+
+fn write_extensions(output: &mut dyn Accumulator, dns_names: &[&str]) {
+    write_tlv(output, Tag::Sequence, |output| {
+        write_tlv(output, Tag::Sequence, |output| {
+            write_oid(output, OID_SUBJECT_ALT_NAME);
+            write_tlv(output, Tag::OctetString, |output| {
+                write_tlv(output, Tag::Sequence, |output| {
+                    for dns_name in dns_names {
+                        write_tlv(output, Tag::ContextSpecific2, |output| {
+                            output.write_bytes(dns_name.as_bytes())
+                        });
+                    }
+                })
+            });
+        })
+    })
+}
+
+fn write_algorithm_id(output: &mut dyn Accumulator, algorithm_id: &[u8]) {
+    write_tlv(output, Tag::Sequence, |output| {
+        output.write_bytes(algorithm_id)
+    })
+}
+
+fn write_bit_string(output: &mut dyn Accumulator, value: &[u8]) {
+    write_tlv(output, Tag::BitString, |output| {
+        output.write_byte(0); // No unused bits.
+        output.write_bytes(value);
+    })
+}
+
+fn write_oid(output: &mut dyn Accumulator, oid: &[u8]) {
+    write_tlv(output, Tag::OID, |output| output.write_bytes(oid))
+}
+
+fn write_small_integer(output: &mut dyn Accumulator, value: u8) {
+    debug_assert!(value < 0x80);
+    write_tlv(output, Tag::Integer, |output| output.write_byte(value))
+}
+
+// A `Time`: a `UTCTime` through 2049 and a `GeneralizedTime` after that, as
+// RFC 5280 Section 4.1.2.5 requires.
+struct Time {
+    tag: Tag,
+    value: [u8; 15],
+    len: usize,
+}
+
+impl Time {
+    fn new(seconds: u64) -> Result<Self, error::Unspecified> {
+        let (year, month, day) = civil_from_days(seconds / 86400);
+        if year > 9999 {
+            return Err(error::Unspecified);
+        }
+        let seconds_of_day = seconds % 86400;
+        let fields = [
+            month,
+            day,
+            seconds_of_day / 3600,
+            (seconds_of_day / 60) % 60,
+            seconds_of_day % 60,
+        ];
+
+        let mut value = [0; 15];
+        let (tag, mut len) = if year < 2050 {
+            write_two_digits(&mut value[..2], year % 100);
+            (Tag::UTCTime, 2)
+        } else {
+            write_two_digits(&mut value[..2], year / 100);
+            write_two_digits(&mut value[2..4], year % 100);
+            (Tag::GeneralizedTime, 4)
+        };
+        for field in &fields {
+            write_two_digits(&mut value[len..][..2], *field);
+            len += 2;
+        }
+        value[len] = b'Z';
+        len += 1;
+
+        Ok(Self { tag, value, len })
+    }
+
+    fn write(&self, output: &mut dyn Accumulator) {
+        write_tlv(output, self.tag, |output| {
+            output.write_bytes(&self.value[..self.len])
+        })
+    }
+}
+
+fn write_two_digits(out: &mut [u8], value: u64) {
+    out[0] = b'0' + (value / 10) as u8;
+    out[1] = b'0' + (value % 10) as u8;
+}
+
+// Converts days since 1970-01-01 to a (year, month, day) in the proleptic
+// Gregorian calendar, using Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+const SERIAL_NUMBER_LEN: usize = 16;
+
+// The longest DNS name, without the trailing dot.
+const MAX_DNS_NAME_LEN: usize = 253;
+
+// id-at-commonName, 2.5.4.3.
+const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+
+// id-at-countryName, 2.5.4.6.
+const OID_COUNTRY: &[u8] = &[0x55, 0x04, 0x06];
+
+// id-at-localityName, 2.5.4.7.
+const OID_LOCALITY: &[u8] = &[0x55, 0x04, 0x07];
+
+// id-at-stateOrProvinceName, 2.5.4.8.
+const OID_STATE_OR_PROVINCE: &[u8] = &[0x55, 0x04, 0x08];
+
+// id-at-organizationName, 2.5.4.10.
+const OID_ORGANIZATION: &[u8] = &[0x55, 0x04, 0x0a];
+
+// id-at-organizationalUnitName, 2.5.4.11.
+const OID_ORGANIZATIONAL_UNIT: &[u8] = &[0x55, 0x04, 0x0b];
+
+// id-ce-subjectAltName, 2.5.29.17.
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11];
+
+// pkcs-9-at-extensionRequest, 1.2.840.113549.1.9.14.
+const OID_EXTENSION_REQUEST: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x0e];
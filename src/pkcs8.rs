@@ -14,11 +14,24 @@
 
 //! PKCS#8 is specified in [RFC 5958].
 //!
+//! With the `use_heap` feature, `encrypt()` and `decrypt()` convert PKCS#8
+//! documents to and from password-encrypted `EncryptedPrivateKeyInfo`
+//! documents.
+//!
 //! [RFC 5958]: https://tools.ietf.org/html/rfc5958.
 
 use crate::{ec, error, io::der};
 use untrusted;
 
+#[cfg(feature = "use_heap")]
+mod encrypted;
+
+#[cfg(feature = "use_heap")]
+pub use self::encrypted::{
+    decrypt, encrypt, EncryptionAlgorithm, MAX_PKCS8_LEN, PBES2_HMAC_SHA256_AES_256_CBC,
+    PBES2_HMAC_SHA256_AES_256_GCM,
+};
+
 pub(crate) enum Version {
     V1Only,
     V1OrV2,
@@ -48,7 +61,7 @@ pub(crate) struct Template {
 
 impl Template {
     #[inline]
-    fn alg_id_value(&self) -> untrusted::Input {
+    pub fn alg_id_value(&self) -> untrusted::Input {
         untrusted::Input::from(self.alg_id_value_())
     }
 
@@ -0,0 +1,417 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Password-encrypted PKCS#8 documents, `EncryptedPrivateKeyInfo`, using
+//! PBES2 ([RFC 8018 Section 6.2]) with PBKDF2-HMAC-SHA256.
+//!
+//! [RFC 8018 Section 6.2]: https://tools.ietf.org/html/rfc8018#section-6.2
+
+use crate::{
+    aead::{
+        self, aes,
+        block::{Block, BLOCK_LEN},
+        inverse_cipher,
+    },
+    cpu, digest, error,
+    io::{
+        der::{self, Tag},
+        der_writer::{self, write_tlv},
+        writer::Accumulator,
+    },
+    pbkdf2, rand,
+};
+use core::num::NonZeroU32;
+use std::vec::Vec;
+use untrusted;
+
+/// A PBES2 encryption scheme for `encrypt()`.
+pub struct EncryptionAlgorithm {
+    id: AlgorithmID,
+}
+
+derive_debug_via_id!(EncryptionAlgorithm);
+
+/// The maximum length of a PKCS#8 document that `encrypt()` accepts. This is
+/// enough for an 8192-bit RSA key.
+pub const MAX_PKCS8_LEN: usize = 0x8000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum AlgorithmID {
+    AES_256_CBC,
+    AES_256_GCM,
+}
+
+/// PBES2 with PBKDF2-HMAC-SHA256 and AES-256-CBC. This is what
+/// `openssl pkcs8 -topk8` produces by default, so it is the most widely
+/// supported.
+pub static PBES2_HMAC_SHA256_AES_256_CBC: EncryptionAlgorithm = EncryptionAlgorithm {
+    id: AlgorithmID::AES_256_CBC,
+};
+
+/// PBES2 with PBKDF2-HMAC-SHA256 and AES-256-GCM with a 16-byte tag, as
+/// specified in [RFC 5084]. Unlike AES-256-CBC, it detects a wrong password
+/// or a corrupted document reliably.
+///
+/// [RFC 5084]: https://tools.ietf.org/html/rfc5084
+pub static PBES2_HMAC_SHA256_AES_256_GCM: EncryptionAlgorithm = EncryptionAlgorithm {
+    id: AlgorithmID::AES_256_GCM,
+};
+
+/// Encrypts the PKCS#8 document `pkcs8` with `password` into a DER-encoded
+/// `EncryptedPrivateKeyInfo`, using a salt and IV or nonce generated by
+/// `rng`.
+///
+/// Fails if `pkcs8` is longer than `MAX_PKCS8_LEN` bytes.
+pub fn encrypt(
+    algorithm: &'static EncryptionAlgorithm,
+    pkcs8: &[u8],
+    password: &[u8],
+    iterations: NonZeroU32,
+    rng: &dyn rand::SecureRandom,
+) -> Result<Vec<u8>, error::Unspecified> {
+    if pkcs8.len() > MAX_PKCS8_LEN {
+        return Err(error::Unspecified);
+    }
+    let mut salt = [0u8; SALT_LEN];
+    rng.fill(&mut salt)?;
+    let key = derive_key(password, &salt, iterations);
+
+    let (scheme, ciphertext) = match algorithm.id {
+        AlgorithmID::AES_256_CBC => {
+            let mut iv = [0u8; BLOCK_LEN];
+            rng.fill(&mut iv)?;
+            let ciphertext = cbc_encrypt(&key, &iv, pkcs8)?;
+            (Scheme::Cbc { iv }, ciphertext)
+        }
+        AlgorithmID::AES_256_GCM => {
+            let mut nonce = [0u8; aead::NONCE_LEN];
+            rng.fill(&mut nonce)?;
+            let key = aead::SealingKey::new(&aead::AES_256_GCM, &key)?;
+            let mut in_out = Vec::with_capacity(pkcs8.len() + GCM_TAG_LEN);
+            in_out.extend_from_slice(pkcs8);
+            in_out.extend_from_slice(&[0; GCM_TAG_LEN]);
+            let _ = aead::seal_in_place(
+                &key,
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::empty(),
+                &mut in_out,
+                GCM_TAG_LEN,
+            )?;
+            (Scheme::Gcm { nonce }, in_out)
+        }
+    };
+
+    let encrypted = der_writer::write_all(Tag::Sequence, &|output| {
+        write_tlv(output, Tag::Sequence, |output| {
+            write_oid(output, OID_PBES2);
+            write_tlv(output, Tag::Sequence, |output| {
+                write_tlv(output, Tag::Sequence, |output| {
+                    write_oid(output, OID_PBKDF2);
+                    write_tlv(output, Tag::Sequence, |output| {
+                        write_octet_string(output, &salt);
+                        write_u32(output, iterations.get());
+                        write_tlv(output, Tag::Sequence, |output| {
+                            write_oid(output, OID_HMAC_WITH_SHA256);
+                            write_tlv(output, Tag::Null, |_| ());
+                        });
+                    });
+                });
+                write_tlv(output, Tag::Sequence, |output| match &scheme {
+                    Scheme::Cbc { iv } => {
+                        write_oid(output, OID_AES_256_CBC);
+                        write_octet_string(output, iv);
+                    }
+                    Scheme::Gcm { nonce } => {
+                        write_oid(output, OID_AES_256_GCM);
+                        write_tlv(output, Tag::Sequence, |output| {
+                            write_octet_string(output, nonce);
+                            write_u32(output, GCM_TAG_LEN as u32);
+                        });
+                    }
+                });
+            });
+        });
+        write_octet_string(output, &ciphertext);
+    });
+    Ok(encrypted.into_vec())
+}
+
+/// Decrypts the DER-encoded `EncryptedPrivateKeyInfo` `encrypted` with
+/// `password`, returning the PKCS#8 document, e.g. for
+/// `signature::EcdsaKeyPair::from_pkcs8()`.
+///
+/// Only PBES2 with PBKDF2-HMAC-SHA256, and AES-256-CBC or AES-256-GCM with a
+/// 16-byte tag, is supported. To bound the time a forged document can make
+/// this take, documents with more than `max_iterations` PBKDF2 iterations
+/// are rejected.
+///
+/// With AES-256-CBC, a wrong password is detected only when the decrypted
+/// padding or outer `SEQUENCE` is malformed. That is very likely but not
+/// certain, so the key constructor may be the one that rejects the result.
+pub fn decrypt(
+    encrypted: &[u8],
+    password: &[u8],
+    max_iterations: NonZeroU32,
+) -> Result<Vec<u8>, error::Unspecified> {
+    let (params, ciphertext) =
+        untrusted::Input::from(encrypted).read_all(error::Unspecified, |input| {
+            der::nested(input, Tag::Sequence, error::Unspecified, |input| {
+                let params = der::nested(input, Tag::Sequence, error::Unspecified, |alg_id| {
+                    expect_oid(alg_id, OID_PBES2)?;
+                    der::nested(
+                        alg_id,
+                        Tag::Sequence,
+                        error::Unspecified,
+                        parse_pbes2_params,
+                    )
+                })?;
+                let ciphertext = der::expect_tag_and_get_value(input, Tag::OctetString)?;
+                Ok((params, ciphertext.as_slice_less_safe()))
+            })
+        })?;
+    if params.iterations > max_iterations {
+        return Err(error::Unspecified);
+    }
+    let key = derive_key(password, params.salt, params.iterations);
+
+    let pkcs8 = match params.scheme {
+        Scheme::Cbc { iv } => cbc_decrypt(&key, &iv, ciphertext)?,
+        Scheme::Gcm { nonce } => {
+            let key = aead::OpeningKey::new(&aead::AES_256_GCM, &key)?;
+            let mut in_out = Vec::from(ciphertext);
+            let plaintext_len = aead::open_in_place(
+                &key,
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::empty(),
+                0,
+                &mut in_out,
+            )?
+            .len();
+            in_out.truncate(plaintext_len);
+            in_out
+        }
+    };
+
+    // Reject most wrong passwords for AES-256-CBC here instead of returning
+    // garbage.
+    untrusted::Input::from(&pkcs8).read_all(error::Unspecified, |input| {
+        der::expect_tag_and_get_value(input, Tag::Sequence).map(|_| ())
+    })?;
+    Ok(pkcs8)
+}
+
+struct Params<'a> {
+    salt: &'a [u8],
+    iterations: NonZeroU32,
+    scheme: Scheme,
+}
+
+enum Scheme {
+    Cbc { iv: [u8; BLOCK_LEN] },
+    Gcm { nonce: [u8; aead::NONCE_LEN] },
+}
+
+fn parse_pbes2_params<'a>(
+    input: &mut untrusted::Reader<'a>,
+) -> Result<Params<'a>, error::Unspecified> {
+    let (salt, iterations) = der::nested(input, Tag::Sequence, error::Unspecified, |kdf| {
+        expect_oid(kdf, OID_PBKDF2)?;
+        der::nested(kdf, Tag::Sequence, error::Unspecified, |params| {
+            let salt = der::expect_tag_and_get_value(params, Tag::OctetString)?;
+            let iterations = read_u32(params)?;
+            let iterations = NonZeroU32::new(iterations).ok_or(error::Unspecified)?;
+            if params.peek(Tag::Integer.into()) && read_u32(params)? != KEY_LEN as u32 {
+                return Err(error::Unspecified);
+            }
+            // The default PRF is HMAC-SHA1, which isn't supported.
+            der::nested(params, Tag::Sequence, error::Unspecified, |prf| {
+                expect_oid(prf, OID_HMAC_WITH_SHA256)?;
+                skip_null_parameters(prf)
+            })?;
+            Ok((salt.as_slice_less_safe(), iterations))
+        })
+    })?;
+
+    let scheme = der::nested(input, Tag::Sequence, error::Unspecified, |scheme| {
+        let oid = der::expect_tag_and_get_value(scheme, Tag::OID)?;
+        if oid.as_slice_less_safe() == OID_AES_256_CBC {
+            let mut iv = [0u8; BLOCK_LEN];
+            copy_octet_string(scheme, &mut iv)?;
+            Ok(Scheme::Cbc { iv })
+        } else if oid.as_slice_less_safe() == OID_AES_256_GCM {
+            der::nested(scheme, Tag::Sequence, error::Unspecified, |params| {
+                let mut nonce = [0u8; aead::NONCE_LEN];
+                copy_octet_string(params, &mut nonce)?;
+                // The default tag length is 12 bytes, which isn't supported.
+                if read_u32(params)? != GCM_TAG_LEN as u32 {
+                    return Err(error::Unspecified);
+                }
+                Ok(Scheme::Gcm { nonce })
+            })
+        } else {
+            Err(error::Unspecified)
+        }
+    })?;
+
+    Ok(Params {
+        salt,
+        iterations,
+        scheme,
+    })
+}
+
+fn derive_key(password: &[u8], salt: &[u8], iterations: NonZeroU32) -> [u8; KEY_LEN] {
+    let mut key = [0u8; KEY_LEN];
+    pbkdf2::derive(&digest::SHA256, iterations, salt, password, &mut key);
+    key
+}
+
+// Encrypts `plaintext` with PKCS#7 padding, as RFC 8018 Section 6.2.1
+// requires.
+fn cbc_encrypt(
+    key: &[u8; KEY_LEN],
+    iv: &[u8; BLOCK_LEN],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, error::Unspecified> {
+    let key = aes::Key::new(key, aes::Variant::AES_256, cpu::features())?;
+    let padding_len = BLOCK_LEN - (plaintext.len() % BLOCK_LEN);
+    let mut ciphertext = Vec::with_capacity(plaintext.len() + padding_len);
+    ciphertext.extend_from_slice(plaintext);
+    ciphertext.resize(plaintext.len() + padding_len, padding_len as u8);
+
+    let mut chain = *iv;
+    for chunk in ciphertext.chunks_mut(BLOCK_LEN) {
+        let mut block = [0u8; BLOCK_LEN];
+        for ((b, p), c) in block.iter_mut().zip(chunk.iter()).zip(chain.iter()) {
+            *b = p ^ c;
+        }
+        chain = *key.encrypt_block(Block::from(&block)).as_ref();
+        chunk.copy_from_slice(&chain);
+    }
+    Ok(ciphertext)
+}
+
+fn cbc_decrypt(
+    key: &[u8; KEY_LEN],
+    iv: &[u8; BLOCK_LEN],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, error::Unspecified> {
+    if ciphertext.is_empty() || ciphertext.len() % BLOCK_LEN != 0 {
+        return Err(error::Unspecified);
+    }
+    let key = inverse_cipher::Key::new(key, aes::Variant::AES_256, cpu::features());
+    let mut plaintext = Vec::from(ciphertext);
+
+    let mut chain = *iv;
+    for chunk in plaintext.chunks_mut(BLOCK_LEN) {
+        let mut block = [0u8; BLOCK_LEN];
+        block.copy_from_slice(chunk);
+        let decrypted = key.decrypt_block(Block::from(&block));
+        for ((p, d), c) in chunk.iter_mut().zip(decrypted.as_ref()).zip(chain.iter()) {
+            *p = d ^ c;
+        }
+        chain = block;
+    }
+
+    let padding_len = usize::from(plaintext[plaintext.len() - 1]);
+    if padding_len == 0
+        || padding_len > BLOCK_LEN
+        || plaintext[(plaintext.len() - padding_len)..]
+            .iter()
+            .any(|b| usize::from(*b) != padding_len)
+    {
+        return Err(error::Unspecified);
+    }
+    let plaintext_len = plaintext.len() - padding_len;
+    plaintext.truncate(plaintext_len);
+    Ok(plaintext)
+}
+
+fn expect_oid(input: &mut untrusted::Reader, oid: &[u8]) -> Result<(), error::Unspecified> {
+    let actual = der::expect_tag_and_get_value(input, Tag::OID)?;
+    if actual.as_slice_less_safe() != oid {
+        return Err(error::Unspecified);
+    }
+    Ok(())
+}
+
+fn skip_null_parameters(input: &mut untrusted::Reader) -> Result<(), error::Unspecified> {
+    if !input.at_end() {
+        let null = der::expect_tag_and_get_value(input, Tag::Null)?;
+        if !null.is_empty() {
+            return Err(error::Unspecified);
+        }
+    }
+    Ok(())
+}
+
+fn copy_octet_string(
+    input: &mut untrusted::Reader,
+    out: &mut [u8],
+) -> Result<(), error::Unspecified> {
+    let value = der::expect_tag_and_get_value(input, Tag::OctetString)?;
+    if value.len() != out.len() {
+        return Err(error::Unspecified);
+    }
+    out.copy_from_slice(value.as_slice_less_safe());
+    Ok(())
+}
+
+fn read_u32(input: &mut untrusted::Reader) -> Result<u32, error::Unspecified> {
+    let value = der::positive_integer(input)?;
+    let value = value.big_endian_without_leading_zero();
+    if value.len() > 4 {
+        return Err(error::Unspecified);
+    }
+    Ok(value.iter().fold(0, |acc, b| (acc << 8) | u32::from(*b)))
+}
+
+fn write_u32(output: &mut dyn Accumulator, value: u32) {
+    let bytes = value.to_be_bytes();
+    let leading_zeros = bytes.iter().take_while(|b| **b == 0).count().min(3);
+    let value = &bytes[leading_zeros..];
+    write_tlv(output, Tag::Integer, |output| {
+        if value[0] & 0x80 != 0 {
+            output.write_byte(0); // Disambiguate negative number.
+        }
+        output.write_bytes(value);
+    })
+}
+
+fn write_octet_string(output: &mut dyn Accumulator, value: &[u8]) {
+    write_tlv(output, Tag::OctetString, |output| output.write_bytes(value))
+}
+
+fn write_oid(output: &mut dyn Accumulator, oid: &[u8]) {
+    write_tlv(output, Tag::OID, |output| output.write_bytes(oid))
+}
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const GCM_TAG_LEN: usize = 16;
+
+// id-PBES2, 1.2.840.113549.1.5.13.
+const OID_PBES2: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x05, 0x0d];
+
+// id-PBKDF2, 1.2.840.113549.1.5.12.
+const OID_PBKDF2: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x05, 0x0c];
+
+// id-hmacWithSHA256, 1.2.840.113549.2.9.
+const OID_HMAC_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x02, 0x09];
+
+// aes256-CBC-PAD, 2.16.840.1.101.3.4.1.42.
+const OID_AES_256_CBC: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x2a];
+
+// id-aes256-GCM, 2.16.840.1.101.3.4.1.46.
+const OID_AES_256_GCM: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x2e];
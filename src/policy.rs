@@ -0,0 +1,109 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! A process-wide policy restricting which algorithms may be used.
+//!
+//! By default every algorithm may be used. An application that must only use
+//! FIPS-approved algorithms calls `set(Policy::FipsApproved)` once, early in
+//! `main()`, before any keys are constructed. From then on, constructing a
+//! key of an algorithm that isn't approved fails; constructors that return
+//! `error::KeyRejected` report `"AlgorithmNotAllowed"`. Keys that were
+//! constructed before aren't affected. The restriction can't be lifted
+//! again, so no code elsewhere in the process can bypass it.
+//!
+//! The policy is enforced when:
+//!
+//! * an AEAD key is constructed, e.g. by `aead::OpeningKey::new()` or
+//!   `aead::OpeningKey::derive()`;
+//! * a signature is verified with `signature::UnparsedPublicKey::verify()`,
+//!   or a public key is parsed with `signature::ParsedPublicKey::new()`;
+//! * an Ed25519 or Ed448 key pair is generated or constructed;
+//! * a key agreement private key is generated or constructed.
+//!
+//! The approved algorithms are AES-GCM; ECDSA with P-256, P-384, and P-521;
+//! RSA with keys of at least 2048 bits; and ECDH with P-256 and P-384. In
+//! particular ChaCha20-Poly1305, XChaCha20-Poly1305, AES-GCM-SIV, AES-SIV,
+//! AEGIS, Ascon, Ed25519, Ed448, X25519, X448, and secp256k1 aren't. Digests,
+//! HMAC, and key derivation functions aren't restricted.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "aead-chacha")]
+//! # fn main() -> Result<(), ring::error::Unspecified> {
+//! use ring::{aead, policy};
+//!
+//! policy::set(policy::Policy::FipsApproved)?;
+//!
+//! assert!(aead::SealingKey::new(&aead::AES_256_GCM, &[0; 32]).is_ok());
+//! assert!(aead::SealingKey::new(&aead::CHACHA20_POLY1305, &[0; 32]).is_err());
+//!
+//! // The policy can't be relaxed.
+//! assert!(policy::set(policy::Policy::All).is_err());
+//!
+//! # Ok(())
+//! # }
+//! #
+//! # #[cfg(not(feature = "aead-chacha"))]
+//! # fn main() {}
+//! ```
+
+use crate::error;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Which algorithms may be used.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Policy {
+    /// All algorithms may be used. This is the default.
+    All,
+
+    /// Only FIPS-approved algorithms may be used.
+    FipsApproved,
+}
+
+/// Sets the policy for the rest of the lifetime of the process.
+///
+/// Fails if the policy is `Policy::FipsApproved` and `policy` is
+/// `Policy::All`.
+pub fn set(policy: Policy) -> Result<(), error::Unspecified> {
+    match policy {
+        Policy::FipsApproved => FIPS_APPROVED_ONLY.store(true, Ordering::SeqCst),
+        Policy::All => {
+            if FIPS_APPROVED_ONLY.load(Ordering::SeqCst) {
+                return Err(error::Unspecified);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The current policy.
+pub fn get() -> Policy {
+    if FIPS_APPROVED_ONLY.load(Ordering::SeqCst) {
+        Policy::FipsApproved
+    } else {
+        Policy::All
+    }
+}
+
+// Fails if the current policy doesn't allow an algorithm that is or isn't
+// FIPS-approved.
+pub(crate) fn check(fips_approved: bool) -> Result<(), error::KeyRejected> {
+    if !fips_approved && get() == Policy::FipsApproved {
+        return Err(error::KeyRejected::algorithm_not_allowed());
+    }
+    Ok(())
+}
+
+static FIPS_APPROVED_ONLY: AtomicBool = AtomicBool::new(false);
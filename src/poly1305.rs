@@ -0,0 +1,87 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Poly1305, the one-time authenticator specified in [RFC 8439 Section 2.5].
+//!
+//! **This is a hazmat API.** A Poly1305 key must never authenticate more than
+//! one message; an attacker who sees the tags of two messages under the same
+//! key can forge tags for other messages. So `sign()` and `verify()` consume
+//! the `Key`. Protocols derive a fresh key for each message, e.g. from the
+//! ChaCha20 keystream as in the original ChaCha20-Poly1305 construction.
+//! Use `ring::aead` or `ring::hmac` unless a protocol specifies Poly1305
+//! itself.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::poly1305;
+//!
+//! let msg = "hello, world";
+//!
+//! // In a real protocol the key would be derived for this one message.
+//! let key_bytes = [0x42; poly1305::KEY_LEN];
+//!
+//! let tag = poly1305::sign(poly1305::Key::new(key_bytes), msg.as_bytes());
+//! poly1305::verify(poly1305::Key::new(key_bytes), msg.as_bytes(), tag.as_ref())?;
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [RFC 8439 Section 2.5]: https://tools.ietf.org/html/rfc8439#section-2.5
+
+use crate::{
+    aead::{block::Block, poly1305, Tag},
+    constant_time, error,
+    polyfill::convert::*,
+};
+
+/// The length of a Poly1305 key.
+pub const KEY_LEN: usize = poly1305::KEY_BLOCKS * 16;
+
+/// The length of a Poly1305 tag.
+pub const TAG_LEN: usize = 16;
+
+/// A one-time Poly1305 key.
+///
+/// `Key` intentionally doesn't implement `Clone` to ensure that each one is
+/// used at most once.
+pub struct Key(poly1305::Key);
+
+impl Key {
+    /// Constructs a key with the given value, the concatenation of *r* and
+    /// *s*.
+    pub fn new(key_bytes: [u8; KEY_LEN]) -> Self {
+        let blocks: [Block; poly1305::KEY_BLOCKS] = (&key_bytes).into_();
+        Self(poly1305::Key::from(blocks))
+    }
+}
+
+impl core::fmt::Debug for Key {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Key").finish()
+    }
+}
+
+/// Calculates the Poly1305 tag of `input` using the one-time key `key`.
+pub fn sign(key: Key, input: &[u8]) -> Tag {
+    poly1305::sign(key.0, input)
+}
+
+/// Calculates the Poly1305 tag of `input` using the one-time key `key`, and
+/// verifies whether it equals `tag`.
+///
+/// The verification will be done in constant time to prevent timing attacks.
+pub fn verify(key: Key, input: &[u8], tag: &[u8]) -> Result<(), error::Unspecified> {
+    constant_time::verify_slices_are_equal(sign(key, input).as_ref(), tag)
+}
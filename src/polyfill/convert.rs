@@ -16,10 +16,12 @@
 /// conversions between types defined outside this crate.
 ///
 /// Do not use this this in situations where `From` could be used.
+#[cfg(any(feature = "aead-chacha", feature = "sig-ed25519"))]
 pub trait From_<F>: Sized {
     fn from_(value: F) -> Self;
 }
 
+#[cfg(any(feature = "aead-chacha", feature = "sig-ed25519"))]
 pub trait Into_<T>
 where
     T: Sized,
@@ -27,6 +29,7 @@ where
     fn into_(self) -> T;
 }
 
+#[cfg(any(feature = "aead-chacha", feature = "sig-ed25519"))]
 impl<T, F> Into_<T> for F
 where
     T: From_<F>,
@@ -93,8 +96,12 @@ macro_rules! impl_array_try_from {
 
 impl_array_try_from!(u8, 12);
 impl_array_try_from!(u8, 16);
+impl_array_try_from!(u8, 24);
 impl_array_try_from!(u8, 32);
+impl_array_try_from!(u8, 56);
+impl_array_try_from!(u8, 57);
 impl_array_try_from!(u8, 64);
+impl_array_try_from!(u8, 114);
 
 #[inline]
 unsafe fn transmute_slice<A, T>(slice: &[T], expected_len: usize) -> Result<&A, TryFromSliceError> {
@@ -114,6 +121,7 @@ unsafe fn transmute_slice_mut<A, T>(
     Ok(core::mem::transmute(slice.as_ptr()))
 }
 
+#[cfg(any(feature = "aead-chacha", feature = "sig-ed25519"))]
 macro_rules! impl_array_split {
     ($ty:ty, $first:expr, $second:expr) => {
         impl From_<&[$ty; $first + $second]> for (&[$ty; $first], &[$ty; $second]) {
@@ -136,4 +144,5 @@ macro_rules! impl_array_split {
     };
 }
 
+#[cfg(any(feature = "aead-chacha", feature = "sig-ed25519"))]
 impl_array_split!(u8, 32, 32);
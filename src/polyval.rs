@@ -0,0 +1,98 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! POLYVAL, the universal hash function of AES-GCM-SIV, as specified in
+//! [RFC 8452 Section 3].
+//!
+//! **This is a hazmat API.** POLYVAL isn't a MAC; its output must be
+//! encrypted, as AES-GCM-SIV does, before it can be used as an authenticator.
+//! It is exposed for implementing constructions such as AES-GCM-SIV variants
+//! on top of *ring*'s carry-less multiplication code. Use `ring::aead` unless
+//! a protocol specifies POLYVAL itself.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::polyval;
+//!
+//! let key = [0x25; polyval::KEY_LEN];
+//! let mut ctx = polyval::Context::new(&key);
+//! ctx.update_blocks(&[0x4f; 2 * polyval::BLOCK_LEN])?;
+//! ctx.update_padded(b"a partial block");
+//! let output = ctx.finalize();
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [RFC 8452 Section 3]: https://tools.ietf.org/html/rfc8452#section-3
+
+use crate::{
+    aead::{block::Block, gcm::PolyValContext},
+    cpu, error,
+};
+
+/// The length of a POLYVAL key, *H*.
+pub const KEY_LEN: usize = 16;
+
+/// The length of the blocks that POLYVAL processes, and of its output.
+pub const BLOCK_LEN: usize = 16;
+
+/// A POLYVAL computation.
+pub struct Context(PolyValContext);
+
+impl Context {
+    /// Starts a computation with the key `key`.
+    pub fn new(key: &[u8; KEY_LEN]) -> Self {
+        Self(PolyValContext::new(&Block::from(key), cpu::features()))
+    }
+
+    /// Updates the computation with `input`, which must be a whole number of
+    /// blocks.
+    ///
+    /// Fails, without updating the computation, if `input.len()` isn't a
+    /// multiple of `BLOCK_LEN`.
+    pub fn update_blocks(&mut self, input: &[u8]) -> Result<(), error::Unspecified> {
+        if input.len() % BLOCK_LEN != 0 {
+            return Err(error::Unspecified);
+        }
+        self.0.update_blocks(input);
+        Ok(())
+    }
+
+    /// Updates the computation with `input` followed by the zeros that pad it
+    /// to a whole number of blocks, as AES-GCM-SIV does for the additional
+    /// data and the plaintext.
+    pub fn update_padded(&mut self, input: &[u8]) {
+        let whole_len = input.len() - (input.len() % BLOCK_LEN);
+        let (whole, remainder) = input.split_at(whole_len);
+        self.0.update_blocks(whole);
+        if !remainder.is_empty() {
+            let mut block = [0u8; BLOCK_LEN];
+            block[..remainder.len()].copy_from_slice(remainder);
+            self.0.update_blocks(&block);
+        }
+    }
+
+    /// Returns the POLYVAL of the blocks that the computation was updated
+    /// with.
+    pub fn finalize(mut self) -> [u8; BLOCK_LEN] {
+        *self.0.pre_finish().as_ref()
+    }
+}
+
+impl core::fmt::Debug for Context {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Context").finish()
+    }
+}
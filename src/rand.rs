@@ -24,6 +24,16 @@
 //! can be replayed. Following this pattern also may help with sandboxing
 //! (seccomp filters on Linux in particular). See `SystemRandom`'s
 //! documentation for more details.
+//!
+//! `SecureRandom` can't be implemented outside of *ring*. To use another
+//! source of randomness, such as an HSM or a TPM, implement `RandomSource` for
+//! it and wrap it in an `ExternalRandom`. Deterministic implementations for
+//! testing are in `rand::test`.
+//!
+//! Early in boot, before the operating system has gathered enough entropy,
+//! `SystemRandom::fill()` may block. Code that must not block, such as code
+//! running on an async executor, can use `SecureRandom::try_fill()` or, with
+//! the `async` feature, `rand::fill_async()` instead.
 
 use crate::error;
 
@@ -31,8 +41,53 @@ use crate::error;
 pub trait SecureRandom: sealed::Sealed {
     /// Fills `dest` with random bytes.
     fn fill(&self, dest: &mut [u8]) -> Result<(), error::Unspecified>;
+
+    /// Fills `dest` with random bytes without blocking.
+    ///
+    /// Fails with `TryFillError::WouldBlock` if the random number generator
+    /// isn't ready yet, in which case the contents of `dest` are unspecified.
+    /// Only `SystemRandom` ever fails that way; for other implementations
+    /// this is the same as `fill()`.
+    fn try_fill(&self, dest: &mut [u8]) -> Result<(), TryFillError> {
+        self.fill(dest).map_err(TryFillError::from)
+    }
+}
+
+/// An error from `SecureRandom::try_fill()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TryFillError {
+    /// The random number generator isn't ready yet; filling the output would
+    /// have blocked. Try again later.
+    WouldBlock,
+
+    /// Filling the output failed for some other reason.
+    Unspecified,
+}
+
+impl From<error::Unspecified> for TryFillError {
+    fn from(_: error::Unspecified) -> Self {
+        TryFillError::Unspecified
+    }
+}
+
+impl From<TryFillError> for error::Unspecified {
+    fn from(_: TryFillError) -> Self {
+        error::Unspecified
+    }
+}
+
+impl core::fmt::Display for TryFillError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(match self {
+            TryFillError::WouldBlock => "ring::rand::TryFillError::WouldBlock",
+            TryFillError::Unspecified => "ring::rand::TryFillError::Unspecified",
+        })
+    }
 }
 
+#[cfg(feature = "use_heap")]
+impl std::error::Error for TryFillError {}
+
 /// A secure random number generator where the random values come directly
 /// from the operating system.
 ///
@@ -75,6 +130,11 @@ pub trait SecureRandom: sealed::Sealed {
 /// and `read` up until the first call to `fill()` succeeds. After that, allow
 /// `getrandom` and `read`.
 ///
+/// `try_fill()` never blocks. On Linux and Android it uses `getrandom` with
+/// `GRND_NONBLOCK`, and fails with `TryFillError::WouldBlock` until the
+/// kernel's entropy pool has been initialized. On other platforms `fill()`
+/// doesn't block, so `try_fill()` is the same as `fill()`.
+///
 /// [`getrandom`]: http://man7.org/linux/man-pages/man2/getrandom.2.html
 pub struct SystemRandom;
 
@@ -91,10 +151,274 @@ impl SecureRandom for SystemRandom {
     fn fill(&self, dest: &mut [u8]) -> Result<(), error::Unspecified> {
         fill_impl(dest)
     }
+
+    #[inline(always)]
+    fn try_fill(&self, dest: &mut [u8]) -> Result<(), TryFillError> {
+        try_fill_impl(dest)
+    }
 }
 
 impl sealed::Sealed for SystemRandom {}
 
+/// Fills `dest` with random bytes from `rng` without blocking the executor.
+///
+/// The returned future polls `rng.try_fill()`. While the system random
+/// number generator isn't ready, a helper thread waits for it and then wakes
+/// the task, so the calling thread is never blocked.
+///
+/// ```
+/// # fn block_on<F: core::future::Future>(f: F) -> F::Output {
+/// #     use std::{sync::Arc, task::{Context, Poll, Wake, Waker}};
+/// #     struct Noop;
+/// #     impl Wake for Noop { fn wake(self: Arc<Self>) {} }
+/// #     let waker = Waker::from(Arc::new(Noop));
+/// #     let mut cx = Context::from_waker(&waker);
+/// #     let mut f = Box::pin(f);
+/// #     loop {
+/// #         if let Poll::Ready(r) = f.as_mut().poll(&mut cx) { return r; }
+/// #     }
+/// # }
+/// use ring::rand;
+///
+/// let rng = rand::SystemRandom::new();
+/// let mut nonce = [0u8; 12];
+/// block_on(rand::fill_async(&rng, &mut nonce))?;
+/// # Ok::<(), ring::error::Unspecified>(())
+/// ```
+#[cfg(feature = "async")]
+pub fn fill_async<'a, R: SecureRandom + ?Sized>(rng: &'a R, dest: &'a mut [u8]) -> Fill<'a, R> {
+    Fill {
+        rng,
+        dest,
+        waiter: None,
+    }
+}
+
+/// The future returned by `fill_async()`.
+#[cfg(feature = "async")]
+pub struct Fill<'a, R: SecureRandom + ?Sized> {
+    rng: &'a R,
+    dest: &'a mut [u8],
+    waiter: Option<std::sync::Arc<std::sync::Mutex<Waiter>>>,
+}
+
+#[cfg(feature = "async")]
+struct Waiter {
+    ready: bool,
+    waker: Option<core::task::Waker>,
+}
+
+#[cfg(feature = "async")]
+impl<R: SecureRandom + ?Sized> core::future::Future for Fill<'_, R> {
+    type Output = Result<(), error::Unspecified>;
+
+    fn poll(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context,
+    ) -> core::task::Poll<Self::Output> {
+        use core::task::Poll;
+        use std::sync::{Arc, Mutex};
+
+        let this = self.get_mut();
+        match this.rng.try_fill(this.dest) {
+            Ok(()) => return Poll::Ready(Ok(())),
+            Err(TryFillError::Unspecified) => return Poll::Ready(Err(error::Unspecified)),
+            Err(TryFillError::WouldBlock) => (),
+        }
+
+        if this.waiter.is_none() {
+            // Only the system random number generator can block, so wait for
+            // it to become ready on another thread.
+            let waiter = Arc::new(Mutex::new(Waiter {
+                ready: false,
+                waker: None,
+            }));
+            let shared = waiter.clone();
+            let spawned = std::thread::Builder::new().spawn(move || {
+                let mut dummy = [0u8; 1];
+                let _ = fill_impl(&mut dummy);
+                let mut waiter = shared.lock().unwrap();
+                waiter.ready = true;
+                if let Some(waker) = waiter.waker.take() {
+                    waker.wake();
+                }
+            });
+            if spawned.is_err() {
+                return Poll::Ready(Err(error::Unspecified));
+            }
+            this.waiter = Some(waiter);
+        }
+
+        let mut waiter = this.waiter.as_ref().unwrap().lock().unwrap();
+        if waiter.ready {
+            // The generator became ready after `try_fill()` failed.
+            cx.waker().wake_by_ref();
+        } else {
+            waiter.waker = Some(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: SecureRandom + ?Sized> core::fmt::Debug for Fill<'_, R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Fill").finish()
+    }
+}
+
+/// A source of random bytes that is implemented outside of *ring*.
+///
+/// Implementations are responsible for their own security; *ring* uses
+/// whatever bytes they provide as-is.
+pub trait RandomSource {
+    /// Fills `dest` with random bytes, or fails if that isn't possible.
+    fn fill(&self, dest: &mut [u8]) -> Result<(), error::Unspecified>;
+}
+
+/// A `SecureRandom` that gets its random bytes from a `RandomSource`.
+///
+/// ```
+/// use ring::{error, rand::{self, SecureRandom}};
+///
+/// struct Counter;
+///
+/// impl rand::RandomSource for Counter {
+///     fn fill(&self, dest: &mut [u8]) -> Result<(), error::Unspecified> {
+///         for (i, b) in dest.iter_mut().enumerate() {
+///             *b = i as u8;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let rng = rand::ExternalRandom::new(&Counter);
+/// let mut bytes = [0u8; 4];
+/// rng.fill(&mut bytes)?;
+/// assert_eq!(bytes, [0, 1, 2, 3]);
+/// # Ok::<(), error::Unspecified>(())
+/// ```
+pub struct ExternalRandom<'a> {
+    source: &'a dyn RandomSource,
+}
+
+impl<'a> ExternalRandom<'a> {
+    /// Constructs a new `ExternalRandom` that uses `source`.
+    pub fn new(source: &'a dyn RandomSource) -> Self {
+        Self { source }
+    }
+}
+
+impl SecureRandom for ExternalRandom<'_> {
+    fn fill(&self, dest: &mut [u8]) -> Result<(), error::Unspecified> {
+        self.source.fill(dest)
+    }
+}
+
+impl sealed::Sealed for ExternalRandom<'_> {}
+
+impl core::fmt::Debug for ExternalRandom<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("ExternalRandom").finish()
+    }
+}
+
+/// Deterministic implementations of `SecureRandom`.
+///
+/// These implementations are particularly useful for testing implementations
+/// of randomized algorithms & protocols using known-answer-tests where the
+/// test vectors contain the random seed to use. They are also especially
+/// useful for some types of fuzzing. They must never be used to generate
+/// real keys.
+pub mod test {
+    use crate::{error, polyfill, rand, sealed};
+
+    /// An implementation of `SecureRandom` that always fills the output slice
+    /// with the given byte.
+    #[derive(Debug)]
+    pub struct FixedByteRandom {
+        /// The byte to fill the output with.
+        pub byte: u8,
+    }
+
+    impl rand::SecureRandom for FixedByteRandom {
+        fn fill(&self, dest: &mut [u8]) -> Result<(), error::Unspecified> {
+            polyfill::slice::fill(dest, self.byte);
+            Ok(())
+        }
+    }
+
+    /// An implementation of `SecureRandom` that always fills the output slice
+    /// with the slice in `bytes`. The length of the slice given to `slice`
+    /// must match exactly.
+    #[derive(Debug)]
+    pub struct FixedSliceRandom<'a> {
+        /// The output of every call to `fill()`.
+        pub bytes: &'a [u8],
+    }
+
+    impl rand::SecureRandom for FixedSliceRandom<'_> {
+        fn fill(&self, dest: &mut [u8]) -> Result<(), error::Unspecified> {
+            dest.copy_from_slice(self.bytes);
+            Ok(())
+        }
+    }
+
+    /// An implementation of `SecureRandom` where each slice in `bytes` is a
+    /// test vector for one call to `fill()`. *Not thread-safe.*
+    ///
+    /// The first slice in `bytes` is the output for the first call to
+    /// `fill()`, the second slice is the output for the second call to
+    /// `fill()`, etc. The output slice passed to `fill()` must have exactly
+    /// the length of the corresponding entry in `bytes`. `fill()` must be
+    /// called exactly once for each entry in `bytes`; otherwise dropping the
+    /// `FixedSliceSequenceRandom` panics.
+    #[derive(Debug)]
+    pub struct FixedSliceSequenceRandom<'a> {
+        /// The outputs of successive calls to `fill()`.
+        pub bytes: &'a [&'a [u8]],
+
+        /// The index into `bytes` of the next output. Must be initialized to
+        /// zero.
+        pub current: core::cell::UnsafeCell<usize>,
+    }
+
+    impl<'a> FixedSliceSequenceRandom<'a> {
+        /// Constructs a new `FixedSliceSequenceRandom` that will return each
+        /// slice of `bytes` in turn.
+        pub fn new(bytes: &'a [&'a [u8]]) -> Self {
+            Self {
+                bytes,
+                current: core::cell::UnsafeCell::new(0),
+            }
+        }
+    }
+
+    impl rand::SecureRandom for FixedSliceSequenceRandom<'_> {
+        fn fill(&self, dest: &mut [u8]) -> Result<(), error::Unspecified> {
+            let current = unsafe { *self.current.get() };
+            let bytes = self.bytes[current];
+            dest.copy_from_slice(bytes);
+            // Remember that we returned this slice and prepare to return
+            // the next one, if any.
+            unsafe { *self.current.get() += 1 };
+            Ok(())
+        }
+    }
+
+    impl Drop for FixedSliceSequenceRandom<'_> {
+        fn drop(&mut self) {
+            // Ensure that `fill()` was called exactly the right number of
+            // times.
+            assert_eq!(unsafe { *self.current.get() }, self.bytes.len());
+        }
+    }
+
+    impl sealed::Sealed for FixedByteRandom {}
+    impl sealed::Sealed for FixedSliceRandom<'_> {}
+    impl sealed::Sealed for FixedSliceSequenceRandom<'_> {}
+}
+
 #[cfg(any(
     target_os = "android",
     all(target_os = "linux", not(feature = "dev_urandom_fallback")),
@@ -111,14 +435,38 @@ use self::darwin::fill as fill_impl;
 #[cfg(any(target_os = "fuchsia"))]
 use self::fuchsia::fill as fill_impl;
 
+#[cfg(any(
+    target_os = "android",
+    all(target_os = "linux", not(feature = "dev_urandom_fallback"))
+))]
+use self::sysrand::try_fill as try_fill_impl;
+
+#[cfg(all(target_os = "linux", feature = "dev_urandom_fallback"))]
+use self::sysrand_or_urandom::try_fill as try_fill_impl;
+
+// Everywhere else `fill_impl` never blocks.
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+#[inline(always)]
+fn try_fill_impl(dest: &mut [u8]) -> Result<(), TryFillError> {
+    fill_impl(dest).map_err(TryFillError::from)
+}
+
 use crate::sealed;
 
 #[cfg(any(target_os = "android", target_os = "linux"))]
 mod sysrand_chunk {
+    use super::TryFillError;
     use crate::{c, error};
 
+    // See `GRND_NONBLOCK` in #include <linux/random.h>.
+    pub const GRND_NONBLOCK: c::uint = 0x0001;
+
     #[inline]
     pub fn chunk(dest: &mut [u8]) -> Result<usize, error::Unspecified> {
+        chunk_with_flags(dest, 0).map_err(error::Unspecified::from)
+    }
+
+    pub fn chunk_with_flags(dest: &mut [u8], flags: c::uint) -> Result<usize, TryFillError> {
         // See `SYS_getrandom` in #include <sys/syscall.h>.
 
         #[cfg(target_arch = "aarch64")]
@@ -134,7 +482,7 @@ mod sysrand_chunk {
         const SYS_GETRANDOM: c::long = 318;
 
         let chunk_len: c::size_t = dest.len();
-        let r = unsafe { libc::syscall(SYS_GETRANDOM, dest.as_mut_ptr(), chunk_len, 0) };
+        let r = unsafe { libc::syscall(SYS_GETRANDOM, dest.as_mut_ptr(), chunk_len, flags) };
         if r < 0 {
             let errno;
 
@@ -154,7 +502,12 @@ mod sysrand_chunk {
                 // will cause the caller to try again.
                 return Ok(0);
             }
-            return Err(error::Unspecified);
+            if errno == libc::EAGAIN {
+                // `GRND_NONBLOCK` was given and the entropy pool hasn't been
+                // initialized yet.
+                return Err(TryFillError::WouldBlock);
+            }
+            return Err(TryFillError::Unspecified);
         }
         Ok(r as usize)
     }
@@ -197,6 +550,18 @@ mod sysrand {
         }
         Ok(())
     }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn try_fill(dest: &mut [u8]) -> Result<(), super::TryFillError> {
+        use super::sysrand_chunk::{chunk_with_flags, GRND_NONBLOCK};
+
+        let mut read_len = 0;
+        while read_len < dest.len() {
+            let chunk_len = chunk_with_flags(&mut dest[read_len..], GRND_NONBLOCK)?;
+            read_len += chunk_len;
+        }
+        Ok(())
+    }
 }
 
 // Keep the `cfg` conditions in sync with the conditions in lib.rs.
@@ -230,32 +595,43 @@ mod urandom {
 // Keep the `cfg` conditions in sync with the conditions in lib.rs.
 #[cfg(all(target_os = "linux", feature = "dev_urandom_fallback"))]
 mod sysrand_or_urandom {
+    use super::TryFillError;
     use crate::error;
+    use lazy_static::lazy_static;
 
     enum Mechanism {
         Sysrand,
         DevURandom,
     }
 
-    pub fn fill(dest: &mut [u8]) -> Result<(), error::Unspecified> {
-        use lazy_static::lazy_static;
-
-        lazy_static! {
-            static ref MECHANISM: Mechanism = {
-                let mut dummy = [0u8; 1];
-                if super::sysrand_chunk::chunk(&mut dummy[..]).is_err() {
-                    Mechanism::DevURandom
-                } else {
-                    Mechanism::Sysrand
-                }
-            };
-        }
+    lazy_static! {
+        static ref MECHANISM: Mechanism = {
+            // Probe without blocking so that `try_fill` never blocks. An
+            // uninitialized entropy pool still means `getrandom` exists.
+            let mut dummy = [0u8; 1];
+            match super::sysrand_chunk::chunk_with_flags(
+                &mut dummy[..],
+                super::sysrand_chunk::GRND_NONBLOCK,
+            ) {
+                Err(TryFillError::Unspecified) => Mechanism::DevURandom,
+                Ok(_) | Err(TryFillError::WouldBlock) => Mechanism::Sysrand,
+            }
+        };
+    }
 
+    pub fn fill(dest: &mut [u8]) -> Result<(), error::Unspecified> {
         match *MECHANISM {
             Mechanism::Sysrand => super::sysrand::fill(dest),
             Mechanism::DevURandom => super::urandom::fill(dest),
         }
     }
+
+    pub fn try_fill(dest: &mut [u8]) -> Result<(), TryFillError> {
+        match *MECHANISM {
+            Mechanism::Sysrand => super::sysrand::try_fill(dest),
+            Mechanism::DevURandom => super::urandom::fill(dest).map_err(TryFillError::from),
+        }
+    }
 }
 
 #[cfg(any(target_os = "macos", target_os = "ios"))]
@@ -29,8 +29,8 @@ mod padding;
 
 // `RSA_PKCS1_SHA1` is intentionally not exposed.
 pub use self::padding::{
-    RsaEncoding, RSA_PKCS1_SHA256, RSA_PKCS1_SHA384, RSA_PKCS1_SHA512, RSA_PSS_SHA256,
-    RSA_PSS_SHA384, RSA_PSS_SHA512,
+    RsaEncoding, PSS as RsaPssPadding, RSA_PKCS1_SHA256, RSA_PKCS1_SHA384, RSA_PKCS1_SHA512,
+    RSA_PSS_SHA256, RSA_PSS_SHA384, RSA_PSS_SHA512,
 };
 
 // Maximum RSA modulus size supported for signature verification (in bytes).
@@ -46,6 +46,20 @@ pub struct RsaParameters {
     min_bits: bits::BitLength,
 }
 
+impl RsaParameters {
+    /// Verification of signatures using RSA keys of 2048-8192 bits and the
+    /// PSS padding `padding`, for PSS parameters that don't match any of the
+    /// `RSA_PSS_2048_8192_*` algorithms.
+    ///
+    /// See `RsaPssPadding` for an example.
+    pub const fn pss(padding: &'static RsaPssPadding) -> Self {
+        Self {
+            padding_alg: padding,
+            min_bits: bits::BitLength::from_usize_bits(2048),
+        }
+    }
+}
+
 fn parse_public_key(
     input: untrusted::Input,
 ) -> Result<(io::Positive, io::Positive), error::Unspecified> {
@@ -67,4 +81,6 @@ pub mod verification;
 
 pub mod signing;
 
-mod bigint;
+pub(crate) mod bigint;
+
+pub(crate) mod keygen;
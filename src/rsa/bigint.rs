@@ -49,7 +49,7 @@ use core::{
     marker::PhantomData,
     ops::{Deref, DerefMut},
 };
-use std::{borrow::ToOwned as _, boxed::Box, vec, vec::Vec};
+use std::{vec, vec::Vec};
 use untrusted;
 
 pub unsafe trait Prime {}
@@ -63,12 +63,20 @@ struct Width<M> {
 
 /// All `BoxedLimbs<M>` are stored in the same number of limbs.
 struct BoxedLimbs<M> {
-    limbs: Box<[Limb]>,
+    limbs: LimbStorage,
 
     /// The modulus *m* that determines the size of `limbx`.
     m: PhantomData<M>,
 }
 
+// With `memory_locking`, limbs are kept in locked memory, since most of them
+// are private keys or are computed from them.
+#[cfg(not(feature = "memory_locking"))]
+type LimbStorage = std::boxed::Box<[Limb]>;
+
+#[cfg(feature = "memory_locking")]
+type LimbStorage = crate::secret::Box<[Limb]>;
+
 impl<M> Deref for BoxedLimbs<M> {
     type Target = [Limb];
     #[inline]
@@ -117,7 +125,7 @@ impl<M> BoxedLimbs<M> {
     fn minimal_width_from_unpadded(limbs: &[Limb]) -> Self {
         debug_assert_ne!(limbs.last(), Some(&0));
         Self {
-            limbs: limbs.to_owned().into_boxed_slice(),
+            limbs: LimbStorage::from(limbs),
             m: PhantomData,
         }
     }
@@ -141,7 +149,7 @@ impl<M> BoxedLimbs<M> {
 
     fn zero(width: Width<M>) -> Self {
         Self {
-            limbs: vec![0; width.num_limbs].to_owned().into_boxed_slice(),
+            limbs: LimbStorage::from(&vec![0; width.num_limbs][..]),
             m: PhantomData,
         }
     }
@@ -244,7 +252,7 @@ impl<M> Modulus<M> {
         n: Nonnegative,
     ) -> Result<(Self, bits::BitLength), error::KeyRejected> {
         let limbs = BoxedLimbs {
-            limbs: n.limbs.into_boxed_slice(),
+            limbs: LimbStorage::from(&n.limbs[..]),
             m: PhantomData,
         };
         Self::from_boxed_limbs(limbs)
@@ -818,6 +826,18 @@ impl<M> PrivateExponent<M> {
 
         Ok(Self { limbs: dP })
     }
+
+    /// Decodes any exponent that is no wider than `m`, unlike
+    /// `from_be_bytes_padded`, which requires an RSA CRT exponent.
+    #[cfg(feature = "modular_arithmetic")]
+    pub fn from_be_bytes_padded_unreduced(
+        input: untrusted::Input,
+        m: &Modulus<M>,
+    ) -> Result<Self, error::Unspecified> {
+        let mut limbs = BoxedLimbs::zero(m.width());
+        limb::parse_big_endian_and_pad_consttime(input, &mut limbs)?;
+        Ok(Self { limbs })
+    }
 }
 
 impl<M: Prime> PrivateExponent<M> {
@@ -0,0 +1,479 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! RSA key pair generation, following [FIPS 186-4] Appendix B.3.3.
+//!
+//! The modular exponentiations of the primality tests and the computation of
+//! `qInv` use `bigint`. The remaining arithmetic isn't modular (or is modulo
+//! the even numbers `p - 1` and `q - 1`), so it is done with the simple
+//! functions on little-endian 32-bit words at the end of this file. None of
+//! it is performance-critical.
+//!
+//! [FIPS 186-4]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-4.pdf
+
+use super::bigint::{self, Prime};
+use crate::{
+    error,
+    io::{der, der_writer, writer::Accumulator, Positive},
+    rand,
+};
+use std::{boxed::Box, vec, vec::Vec};
+use untrusted;
+
+/// The public exponent of generated keys.
+const E: u32 = 65537;
+
+// Primality tests are done modulo candidates that might not be prime.
+enum Candidate {}
+
+enum P {}
+unsafe impl Prime for P {}
+
+/// Generates an RSA key pair with a `bits`-bit public modulus, returning the
+/// PKCS#8 encoding of its `RSAPrivateKey`.
+pub(super) fn generate_pkcs8(
+    bits: usize,
+    rng: &dyn rand::SecureRandom,
+) -> Result<Box<[u8]>, error::Unspecified> {
+    match bits {
+        2048 | 3072 | 4096 => (),
+        _ => {
+            return Err(error::Unspecified);
+        }
+    }
+
+    let small_primes = small_primes();
+    let p = generate_prime(bits / 2, None, &small_primes, rng)?;
+    let q = generate_prime(bits / 2, Some(&p), &small_primes, rng)?;
+
+    // `RsaKeyPair` requires `p > q`.
+    let (p, q) = match words_sub(&p, &q) {
+        Some(_) => (p, q),
+        None => (q, p),
+    };
+
+    let n = words_mul(&p, &q);
+    let p_minus_1 = words_sub(&p, &[1]).unwrap();
+    let q_minus_1 = words_sub(&q, &[1]).unwrap();
+
+    // `d` is computed modulo φ(n) instead of λ(n); both are acceptable per
+    // RFC 8017, and `RsaKeyPair` only uses the CRT components anyway.
+    let d = e_inverse(&words_mul(&p_minus_1, &q_minus_1));
+    let dP = e_inverse(&p_minus_1);
+    let dQ = e_inverse(&q_minus_1);
+    let qInv = q_inverse_mod_p(&p, &q)?;
+
+    let rsa_private_key = der_writer::write_all(der::Tag::Sequence, &|output| {
+        write_small_integer(output, 0); // version
+        for value in &[&n[..], &[E], &d, &p, &q, &dP, &dQ, &qInv] {
+            write_integer(output, value);
+        }
+    });
+
+    const RSA_ENCRYPTION: &[u8] = include_bytes!("../data/alg-rsa-encryption.der");
+    Ok(der_writer::write_all(der::Tag::Sequence, &|output| {
+        write_small_integer(output, 0); // version
+        der_writer::write_tlv(output, der::Tag::Sequence, |output| {
+            output.write_bytes(RSA_ENCRYPTION)
+        });
+        der_writer::write_tlv(output, der::Tag::OctetString, |output| {
+            output.write_bytes(&rsa_private_key)
+        });
+    }))
+}
+
+/// Returns the `RSAPrivateKey` encoding of the key with the given big-endian
+/// components, computing the CRT exponents `dP` and `dQ`, which some formats
+/// (e.g. OpenSSH's) leave out.
+///
+/// Only `e == 65537` is supported, as `dP` and `dQ` are computed as the
+/// inverses of `E`. The result isn't otherwise validated; that is left to
+/// `RsaKeyPair::from_der()`.
+pub(crate) fn rsa_private_key_from_components(
+    n: &[u8],
+    e: &[u8],
+    d: &[u8],
+    p: &[u8],
+    q: &[u8],
+    qInv: &[u8],
+) -> Result<Box<[u8]>, error::Unspecified> {
+    let e = words_from_be_bytes(e);
+    if words_bit_length(&e) > 32 || e.first() != Some(&E) {
+        return Err(error::Unspecified);
+    }
+    let n = words_from_be_bytes(n);
+    let d = words_from_be_bytes(d);
+    let p = words_from_be_bytes(p);
+    let q = words_from_be_bytes(q);
+    let qInv = words_from_be_bytes(qInv);
+    if [&n, &d, &qInv]
+        .iter()
+        .any(|value| words_bit_length(value) == 0)
+    {
+        return Err(error::Unspecified);
+    }
+
+    // `E` must not divide `p - 1` or `q - 1`, or it wouldn't be invertible.
+    let crt_exponent = |prime: &[u32]| {
+        let prime_minus_1 = words_sub(prime, &[1]).ok_or(error::Unspecified)?;
+        if words_rem_small(&prime_minus_1, E) == 0 {
+            return Err(error::Unspecified);
+        }
+        Ok(e_inverse(&prime_minus_1))
+    };
+    let dP = crt_exponent(&p)?;
+    let dQ = crt_exponent(&q)?;
+
+    Ok(der_writer::write_all(der::Tag::Sequence, &|output| {
+        write_small_integer(output, 0); // version
+        for value in &[&n[..], &e, &d, &p, &q, &dP, &dQ, &qInv] {
+            write_integer(output, value);
+        }
+    }))
+}
+
+/// FIPS 186-4 Appendix B.3.3, steps 4 and 5: generates a random `bits`-bit
+/// prime `p` where `GCD(p - 1, E) == 1`. If `other` is the first prime, the
+/// result is also sufficiently far from it.
+fn generate_prime(
+    bits: usize,
+    other: Option<&[u32]>,
+    small_primes: &[u32],
+    rng: &dyn rand::SecureRandom,
+) -> Result<Vec<u32>, error::Unspecified> {
+    let mut bytes = vec![0u8; bits / 8];
+
+    // Steps 4.7 and 5.8.
+    for _ in 0..(5 * bits) {
+        // Steps 4.2 and 5.2. Setting the top two bits ensures the candidate is
+        // at least √2 * 2**(bits - 1) (steps 4.4 and 5.4) and that the product
+        // of the two primes has exactly `2 * bits` bits.
+        rng.fill(&mut bytes)?;
+        bytes[0] |= 0xc0;
+        *bytes.last_mut().unwrap() |= 1;
+        let candidate = words_from_be_bytes(&bytes);
+
+        // Step 5.4: |p - q| > 2**(bits - 100).
+        if let Some(other) = other {
+            let difference = words_sub(&candidate, other)
+                .or_else(|| words_sub(other, &candidate))
+                .unwrap();
+            if words_bit_length(&difference) <= bits - 100 {
+                continue;
+            }
+        }
+
+        // Trial division, to avoid most of the expensive primality tests.
+        if small_primes
+            .iter()
+            .any(|&prime| words_rem_small(&candidate, prime) == 0)
+        {
+            continue;
+        }
+
+        // Steps 4.5 and 5.5. `E` is prime so GCD(candidate - 1, E) == 1 unless
+        // `E` divides `candidate - 1`.
+        if words_rem_small(&candidate, E) == 1 {
+            continue;
+        }
+
+        // Steps 4.5.1 and 5.5.1. The number of rounds is from FIPS 186-4 Table
+        // C.3; 2048-bit primes use the number for 1536-bit primes.
+        let rounds = if bits < 1536 { 5 } else { 4 };
+        if is_probably_prime(&bytes, rounds, rng)? {
+            return Ok(candidate);
+        }
+    }
+
+    Err(error::Unspecified)
+}
+
+/// The Miller-Rabin probabilistic primality test of FIPS 186-4 Appendix
+/// C.3.1, for an odd `w` (big-endian encoded) that has no small factors.
+fn is_probably_prime(
+    w: &[u8],
+    rounds: usize,
+    rng: &dyn rand::SecureRandom,
+) -> Result<bool, error::Unspecified> {
+    let (m, _) =
+        bigint::Modulus::<Candidate>::from_be_bytes_with_bit_length(untrusted::Input::from(w))?;
+
+    // Steps 1 and 2: w - 1 == 2**a * m.
+    let w_minus_1 = {
+        let mut w_minus_1 = w.to_vec();
+        *w_minus_1.last_mut().unwrap() &= !1;
+        w_minus_1
+    };
+    let a = words_trailing_zeros(&words_from_be_bytes(&w_minus_1));
+    let exponent = {
+        let mut exponent = words_from_be_bytes(&w_minus_1);
+        words_shr(&mut exponent, a);
+        words_to_be_bytes(&exponent, w.len())
+    };
+    let exponent =
+        bigint::PrivateExponent::from_be_bytes_padded(untrusted::Input::from(&exponent), &m)?;
+
+    let mut one = vec![0u8; w.len()];
+    *one.last_mut().unwrap() = 1;
+    let is = |z: &bigint::Elem<Candidate>, value: &[u8]| {
+        let mut bytes = vec![0u8; w.len()];
+        z.fill_be_bytes(&mut bytes);
+        bytes == value
+    };
+
+    let mut b = vec![0u8; w.len()];
+    for _ in 0..rounds {
+        // Steps 4.1 and 4.2: 1 < b < w - 1. Clearing the top bit makes
+        // `b < w - 1`.
+        loop {
+            rng.fill(&mut b)?;
+            b[0] &= 0x7f;
+            if b.iter().rev().skip(1).any(|&byte| byte != 0) || b[b.len() - 1] > 1 {
+                break;
+            }
+        }
+        let b = bigint::Elem::from_be_bytes_padded(untrusted::Input::from(&b), &m)?;
+        let b = bigint::elem_mul(m.oneRR().as_ref(), b, &m);
+
+        // Steps 4.3 through 4.7.
+        let mut z = bigint::elem_exp_consttime(b, &exponent, &m)?;
+        if is(&z, &one) || is(&z, &w_minus_1) {
+            continue;
+        }
+        let mut passed = false;
+        for _ in 1..a {
+            let z_encoded = bigint::elem_mul(m.oneRR().as_ref(), z.clone(), &m);
+            z = bigint::elem_mul(&z_encoded, z, &m);
+            if is(&z, &w_minus_1) {
+                passed = true;
+                break;
+            }
+            if is(&z, &one) {
+                break;
+            }
+        }
+        if !passed {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Returns `E**-1 (mod m)`, given that `E` doesn't divide `m`.
+///
+/// If `k * m == -1 (mod E)` then `(1 + k*m) / E` is the inverse.
+fn e_inverse(m: &[u32]) -> Vec<u32> {
+    let m_mod_e = words_rem_small(m, E);
+    debug_assert_ne!(m_mod_e, 0);
+
+    // `E` is prime, so the inverse of `m_mod_e` is `m_mod_e**(E - 2)`.
+    let mut m_inv_mod_e = 1u64;
+    for _ in 0..(E - 2) {
+        m_inv_mod_e = (m_inv_mod_e * u64::from(m_mod_e)) % u64::from(E);
+    }
+    let k = E - (m_inv_mod_e as u32);
+
+    let (d, remainder) = words_div_small(&words_mul_small_add(m, k, 1), E);
+    debug_assert_eq!(remainder, 0);
+    d
+}
+
+/// Returns `q**-1 (mod p)` for primes `q < p`.
+fn q_inverse_mod_p(p: &[u32], q: &[u32]) -> Result<Vec<u32>, error::Unspecified> {
+    let len = 4 * p.len();
+    let p = words_to_be_bytes(p, len);
+    let (p, _) = bigint::Modulus::<P>::from_be_bytes_with_bit_length(untrusted::Input::from(&p))?;
+    let q =
+        bigint::Elem::from_be_bytes_padded(untrusted::Input::from(&words_to_be_bytes(q, len)), &p)?;
+    let q = bigint::elem_mul(p.oneRR().as_ref(), q, &p);
+    let q_inv = bigint::elem_inverse_consttime(q, &p)?;
+
+    let mut q_inv_bytes = vec![0u8; len];
+    q_inv.fill_be_bytes(&mut q_inv_bytes);
+    Ok(words_from_be_bytes(&q_inv_bytes))
+}
+
+/// The odd primes less than 2**11, for trial division.
+fn small_primes() -> Vec<u32> {
+    const LIMIT: usize = 1 << 11;
+    let mut composite = [false; LIMIT];
+    let mut primes = Vec::new();
+    for i in 3..LIMIT {
+        if composite[i] {
+            continue;
+        }
+        primes.push(i as u32);
+        for multiple in (i * i..LIMIT).step_by(i) {
+            composite[multiple] = true;
+        }
+    }
+    primes
+}
+
+fn write_small_integer(output: &mut dyn Accumulator, value: u8) {
+    der_writer::write_tlv(output, der::Tag::Integer, |output| output.write_byte(value));
+}
+
+fn write_integer(output: &mut dyn Accumulator, value: &[u32]) {
+    let bytes = words_to_be_bytes(value, 4 * value.len());
+    let first_nonzero = bytes.iter().position(|&b| b != 0).unwrap();
+    der_writer::write_positive_integer(
+        output,
+        &Positive::new_non_empty_without_leading_zeros(untrusted::Input::from(
+            &bytes[first_nonzero..],
+        )),
+    );
+}
+
+fn words_from_be_bytes(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .rchunks(4)
+        .map(|chunk| chunk.iter().fold(0, |word, &b| (word << 8) | u32::from(b)))
+        .collect()
+}
+
+fn words_to_be_bytes(words: &[u32], len: usize) -> Vec<u8> {
+    debug_assert!(words_bit_length(words) <= 8 * len);
+    let mut bytes = vec![0u8; len];
+    for (i, byte) in bytes.iter_mut().rev().enumerate() {
+        *byte = words
+            .get(i / 4)
+            .map_or(0, |word| (word >> (8 * (i % 4))) as u8);
+    }
+    bytes
+}
+
+fn words_bit_length(a: &[u32]) -> usize {
+    match a.iter().rposition(|&word| word != 0) {
+        Some(i) => (32 * (i + 1)) - (a[i].leading_zeros() as usize),
+        None => 0,
+    }
+}
+
+fn words_trailing_zeros(a: &[u32]) -> usize {
+    let i = a.iter().position(|&word| word != 0).unwrap();
+    (32 * i) + (a[i].trailing_zeros() as usize)
+}
+
+// a >>= shift
+fn words_shr(a: &mut [u32], shift: usize) {
+    let (word_shift, bit_shift) = (shift / 32, shift % 32);
+    for i in 0..a.len() {
+        let lo = a.get(i + word_shift).map_or(0, |&word| word);
+        let hi = a.get(i + word_shift + 1).map_or(0, |&word| word);
+        a[i] = if bit_shift == 0 {
+            lo
+        } else {
+            (lo >> bit_shift) | (hi << (32 - bit_shift))
+        };
+    }
+}
+
+// Returns `a - b`, or `None` if `a < b`.
+fn words_sub(a: &[u32], b: &[u32]) -> Option<Vec<u32>> {
+    let mut r = Vec::with_capacity(a.len());
+    let mut borrow = 0u64;
+    for (i, &a) in a.iter().enumerate() {
+        let b = u64::from(b.get(i).map_or(0, |&word| word));
+        let t = (u64::from(a) | (1 << 32)) - b - borrow;
+        r.push(t as u32);
+        borrow = 1 - (t >> 32);
+    }
+    if borrow != 0 || b.len() > a.len() && words_bit_length(&b[a.len()..]) != 0 {
+        return None;
+    }
+    Some(r)
+}
+
+fn words_mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut r = vec![0u32; a.len() + b.len()];
+    for (i, &a) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &b) in b.iter().enumerate() {
+            let t = (u64::from(a) * u64::from(b)) + u64::from(r[i + j]) + carry;
+            r[i + j] = t as u32;
+            carry = t >> 32;
+        }
+        r[i + b.len()] = carry as u32;
+    }
+    r
+}
+
+// Returns `a * k + c`.
+fn words_mul_small_add(a: &[u32], k: u32, c: u32) -> Vec<u32> {
+    let mut r = Vec::with_capacity(a.len() + 1);
+    let mut carry = u64::from(c);
+    for &a in a {
+        let t = (u64::from(a) * u64::from(k)) + carry;
+        r.push(t as u32);
+        carry = t >> 32;
+    }
+    r.push(carry as u32);
+    r
+}
+
+// Returns `(a / d, a % d)`.
+fn words_div_small(a: &[u32], d: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for (q, &a) in quotient.iter_mut().zip(a.iter()).rev() {
+        let t = (remainder << 32) | u64::from(a);
+        *q = (t / u64::from(d)) as u32;
+        remainder = t % u64::from(d);
+    }
+    (quotient, remainder as u32)
+}
+
+fn words_rem_small(a: &[u32], d: u32) -> u32 {
+    a.iter().rev().fold(0, |remainder, &a| {
+        (((u64::from(remainder) << 32) | u64::from(a)) % u64::from(d)) as u32
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words_be_bytes() {
+        const BYTES: &[u8] = &[0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0xff];
+        let a = words_from_be_bytes(BYTES);
+        assert_eq!(a, [0xabcd_efff, 0x2345_6789, 0x01]);
+        assert_eq!(words_bit_length(&a), 65);
+        assert_eq!(&words_to_be_bytes(&a, BYTES.len())[..], BYTES);
+        assert_eq!(&words_to_be_bytes(&a, 12)[3..], BYTES);
+    }
+
+    #[test]
+    fn test_words_sub() {
+        assert_eq!(words_sub(&[0, 1], &[1]), Some(vec![0xffff_ffff, 0]));
+        assert_eq!(words_sub(&[1], &[0, 1]), None);
+        assert_eq!(words_sub(&[1, 1], &[2, 1]), None);
+    }
+
+    #[test]
+    fn test_e_inverse() {
+        let to_u64 = |a: &[u32]| {
+            assert_eq!(words_bit_length(a), words_bit_length(&a[..2]));
+            (u64::from(a[1]) << 32) | u64::from(a[0])
+        };
+        for m in &[[1000, 0], [0xffff_fffe, 0xffff_fffe], [0, 0x8000_0000]] {
+            let d = to_u64(&e_inverse(m));
+            let m = to_u64(m);
+            assert!(d < m);
+            assert_eq!((u128::from(d) * u128::from(E)) % u128::from(m), 1);
+        }
+    }
+}
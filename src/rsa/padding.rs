@@ -214,10 +214,63 @@ pkcs1_digestinfo_prefix!(
 /// See "`RSA_PSS_*` Details\" in `ring::signature`'s module-level
 /// documentation for more details.
 ///
+/// `RSA_PSS_SHA256`, `RSA_PSS_SHA384`, and `RSA_PSS_SHA512` use the message
+/// digest algorithm for MGF1 and a salt as long as the digest. Other
+/// parameters can be built with `new()`, `salt_len()`, and
+/// `mgf1_digest_alg()`. These are `const fn`s so that the result can be
+/// stored in a `static`, as `RsaKeyPair::sign()` and `RsaParameters::pss()`
+/// require:
+///
+/// ```
+/// use ring::{digest, signature};
+///
+/// static RSA_PSS_SHA256_SALT_20: signature::RsaPssPadding =
+///     signature::RsaPssPadding::new(&digest::SHA256).salt_len(20);
+///
+/// static RSA_PSS_2048_8192_SHA256_SALT_20: signature::RsaParameters =
+///     signature::RsaParameters::pss(&RSA_PSS_SHA256_SALT_20);
+/// ```
+///
 /// [RFC 3447 Section 8.1]: https://tools.ietf.org/html/rfc3447#section-8.1
 #[derive(Debug)]
 pub struct PSS {
     digest_alg: &'static digest::Algorithm,
+    mgf1_digest_alg: &'static digest::Algorithm,
+    salt_len: Option<usize>,
+}
+
+impl PSS {
+    /// PSS padding using `digest_alg` to digest the message and for MGF1,
+    /// with a salt as long as the digest.
+    ///
+    /// Any digest algorithm can be used, including the SHA-3 algorithms.
+    pub const fn new(digest_alg: &'static digest::Algorithm) -> Self {
+        Self {
+            digest_alg,
+            mgf1_digest_alg: digest_alg,
+            salt_len: None,
+        }
+    }
+
+    /// Returns `self` with a salt that is `salt_len` bytes long.
+    ///
+    /// `salt_len` may be anywhere from zero to the length of the message
+    /// digest, inclusive; signing and verification fail for longer salts.
+    pub const fn salt_len(self, salt_len: usize) -> Self {
+        Self {
+            salt_len: Some(salt_len),
+            ..self
+        }
+    }
+
+    /// Returns `self` using `mgf1_digest_alg` for MGF1 instead of the message
+    /// digest algorithm.
+    pub const fn mgf1_digest_alg(self, mgf1_digest_alg: &'static digest::Algorithm) -> Self {
+        Self {
+            mgf1_digest_alg,
+            ..self
+        }
+    }
 }
 
 impl crate::sealed::Sealed for PSS {}
@@ -242,7 +295,7 @@ impl RsaEncoding for PSS {
         mod_bits: bits::BitLength,
         rng: &dyn rand::SecureRandom,
     ) -> Result<(), error::Unspecified> {
-        let metrics = PSSMetrics::new(self.digest_alg, mod_bits)?;
+        let metrics = PSSMetrics::new(self, mod_bits)?;
 
         // The `m_out` this function fills is the big-endian-encoded value of `m`
         // from the specification, padded to `k` bytes, where `k` is the length
@@ -275,7 +328,7 @@ impl RsaEncoding for PSS {
 
         // Step 9. First output the mask into the out buffer.
         let (mut masked_db, digest_terminator) = em.split_at_mut(metrics.db_len);
-        mgf1(self.digest_alg, h_hash.as_ref(), &mut masked_db)?;
+        mgf1(self.mgf1_digest_alg, h_hash.as_ref(), &mut masked_db)?;
 
         {
             // Steps 7.
@@ -313,7 +366,7 @@ impl Verification for PSS {
         m: &mut untrusted::Reader,
         mod_bits: bits::BitLength,
     ) -> Result<(), error::Unspecified> {
-        let metrics = PSSMetrics::new(self.digest_alg, mod_bits)?;
+        let metrics = PSSMetrics::new(self, mod_bits)?;
 
         // RSASSA-PSS-VERIFY Step 2(c). The `m` this function is given is the
         // big-endian-encoded value of `m` from the specification, padded to
@@ -351,7 +404,7 @@ impl Verification for PSS {
         let mut db = [0u8; PUBLIC_KEY_PUBLIC_MODULUS_MAX_LEN];
         let db = &mut db[..metrics.db_len];
 
-        mgf1(self.digest_alg, h_hash.as_slice_less_safe(), db)?;
+        mgf1(self.mgf1_digest_alg, h_hash.as_slice_less_safe(), db)?;
 
         masked_db.read_all(error::Unspecified, |masked_bytes| {
             // Step 6. Check the top bits of first byte are zero.
@@ -408,20 +461,21 @@ struct PSSMetrics {
 }
 
 impl PSSMetrics {
-    fn new(
-        digest_alg: &'static digest::Algorithm,
-        mod_bits: bits::BitLength,
-    ) -> Result<PSSMetrics, error::Unspecified> {
+    fn new(pss: &PSS, mod_bits: bits::BitLength) -> Result<PSSMetrics, error::Unspecified> {
         let em_bits = mod_bits.try_sub_1()?;
         let em_len = em_bits.as_usize_bytes_rounded_up();
         let leading_zero_bits = (8 * em_len) - em_bits.as_usize_bits();
         debug_assert!(leading_zero_bits < 8);
         let top_byte_mask = 0xffu8 >> leading_zero_bits;
 
-        let h_len = digest_alg.output_len;
+        let h_len = pss.digest_alg.output_len;
 
-        // We require the salt length to be equal to the digest length.
-        let s_len = h_len;
+        // The salt length defaults to the digest length, and we don't allow
+        // longer salts.
+        let s_len = pss.salt_len.unwrap_or(h_len);
+        if s_len > h_len {
+            return Err(error::Unspecified);
+        }
 
         // Step 3 of both `EMSA-PSS-ENCODE` is `EMSA-PSS-VERIFY` requires that
         // we reject inputs where "emLen < hLen + sLen + 2". The definition of
@@ -430,8 +484,8 @@ impl PSSMetrics {
         // two conditions are equivalent. 9 bits are required as the 0x01
         // before the salt requires 1 bit and the 0xbc after the digest
         // requires 8 bits.
-        let db_len = em_len.checked_sub(1 + s_len).ok_or(error::Unspecified)?;
-        let ps_len = db_len.checked_sub(h_len + 1).ok_or(error::Unspecified)?;
+        let db_len = em_len.checked_sub(1 + h_len).ok_or(error::Unspecified)?;
+        let ps_len = db_len.checked_sub(s_len + 1).ok_or(error::Unspecified)?;
 
         debug_assert!(em_bits.as_usize_bits() >= (8 * h_len) + (8 * s_len) + 9);
 
@@ -489,9 +543,7 @@ fn pss_digest(
 macro_rules! rsa_pss_padding {
     ( $PADDING_ALGORITHM:ident, $digest_alg:expr, $doc_str:expr ) => {
         #[doc=$doc_str]
-        pub static $PADDING_ALGORITHM: PSS = PSS {
-            digest_alg: $digest_alg,
-        };
+        pub static $PADDING_ALGORITHM: PSS = PSS::new($digest_alg);
     };
 }
 
@@ -15,7 +15,7 @@
 //! Verification of RSA signatures.
 
 use super::{bigint, parse_public_key, RsaParameters, N, PUBLIC_KEY_PUBLIC_MODULUS_MAX_LEN};
-use crate::{bits, cpu, digest, error, sealed, signature};
+use crate::{bits, cpu, digest, error, sealed, signature, spki};
 
 use untrusted;
 
@@ -98,10 +98,47 @@ impl signature::VerificationAlgorithm for RsaParameters {
             signature,
         )
     }
+
+    fn parse_public_key(
+        &self,
+        public_key: untrusted::Input,
+    ) -> Result<signature::ParsedKey, error::Unspecified> {
+        let (n, e) = parse_public_key(public_key)?;
+        let key = public_key_for_verification(
+            self,
+            (
+                n.big_endian_without_leading_zero_as_input(),
+                e.big_endian_without_leading_zero_as_input(),
+            ),
+        )?;
+        Ok(signature::ParsedKey::Rsa(key))
+    }
+
+    fn verify_parsed(
+        &self,
+        public_key: &signature::ParsedKey,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), error::Unspecified> {
+        match public_key {
+            signature::ParsedKey::Rsa(key) => verify_rsa_with_key(self, key, msg, signature),
+            _ => Err(error::Unspecified),
+        }
+    }
+
+    fn fips_approved(&self) -> bool {
+        self.min_bits.as_usize_bits() >= 2048
+    }
 }
 
 impl sealed::Sealed for RsaParameters {}
 
+impl spki::PublicKeyAlgorithm for RsaParameters {
+    fn public_key_algorithm_id(&self) -> &'static [u8] {
+        include_bytes!("../data/alg-rsa-encryption.der")
+    }
+}
+
 macro_rules! rsa_params {
     ( $VERIFY_ALGORITHM:ident, $min_bits:expr, $PADDING_ALGORITHM:expr,
       $doc_str:expr ) => {
@@ -255,13 +292,36 @@ pub(crate) fn verify_rsa_(
     msg: untrusted::Input,
     signature: untrusted::Input,
 ) -> Result<(), error::Unspecified> {
+    let key = public_key_for_verification(params, (n, e))?;
+    verify_rsa_with_key(params, &key, msg, signature)
+}
+
+fn public_key_for_verification(
+    params: &RsaParameters,
+    (n, e): (untrusted::Input, untrusted::Input),
+) -> Result<Key, error::Unspecified> {
     let max_bits = bits::BitLength::from_usize_bytes(PUBLIC_KEY_PUBLIC_MODULUS_MAX_LEN)?;
 
     // XXX: FIPS 186-4 seems to indicate that the minimum
     // exponent value is 2**16 + 1, but it isn't clear if this is just for
     // signing or also for verification. We support exponents of 3 and larger
     // for compatibility with other commonly-used crypto libraries.
-    let Key { n, e, n_bits } = Key::from_modulus_and_exponent(n, e, params.min_bits, max_bits, 3)?;
+    Ok(Key::from_modulus_and_exponent(
+        n,
+        e,
+        params.min_bits,
+        max_bits,
+        3,
+    )?)
+}
+
+fn verify_rsa_with_key(
+    params: &RsaParameters,
+    Key { n, e, n_bits }: &Key,
+    msg: untrusted::Input,
+    signature: untrusted::Input,
+) -> Result<(), error::Unspecified> {
+    let n_bits = *n_bits;
 
     // The signature must be the same length as the modulus, in bytes.
     if signature.len() != n_bits.as_usize_bytes_rounded_up() {
@@ -271,14 +331,14 @@ pub(crate) fn verify_rsa_(
     // RFC 8017 Section 5.2.2: RSAVP1.
 
     // Step 1.
-    let s = bigint::Elem::from_be_bytes_padded(signature, &n)?;
+    let s = bigint::Elem::from_be_bytes_padded(signature, n)?;
     if s.is_zero() {
         return Err(error::Unspecified);
     }
 
     // Step 2.
-    let m = bigint::elem_exp_vartime(s, e, &n);
-    let m = m.into_unencoded(&n);
+    let m = bigint::elem_exp_vartime(s, *e, n);
+    let m = m.into_unencoded(n);
 
     // Step 3.
     let mut decoded = [0u8; PUBLIC_KEY_PUBLIC_MODULUS_MAX_LEN];
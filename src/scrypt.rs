@@ -0,0 +1,268 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! The scrypt password-based key derivation function, as specified in
+//! [RFC 7914].
+//!
+//! scrypt is provided mainly to verify existing credentials. For new
+//! credentials, prefer `ring::argon2`.
+//!
+//! # Example
+//!
+//! ```
+//! use ring::scrypt;
+//! use std::num::NonZeroU32;
+//!
+//! // Normally these parameters would be loaded along with the credential.
+//! let params = scrypt::Params::new(
+//!     10, // N = 2**10
+//!     NonZeroU32::new(8).unwrap(),
+//!     NonZeroU32::new(1).unwrap(),
+//! )?;
+//!
+//! let salt = b"alice@example.com, db 1";
+//!
+//! let mut to_store = [0u8; 32];
+//! scrypt::derive(&params, salt, b"@74d7]404j|W}6u", &mut to_store);
+//!
+//! assert!(scrypt::verify(&params, salt, b"wrong password", &to_store).is_err());
+//! assert!(scrypt::verify(&params, salt, b"@74d7]404j|W}6u", &to_store).is_ok());
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [RFC 7914]: https://tools.ietf.org/html/rfc7914
+
+use crate::{constant_time, digest, error, pbkdf2};
+use core::num::NonZeroU32;
+use std::vec;
+
+/// The cost parameters of an scrypt computation.
+#[derive(Clone, Copy, Debug)]
+pub struct Params {
+    log_n: u8,
+    r: NonZeroU32,
+    p: NonZeroU32,
+}
+
+impl Params {
+    /// Constructs scrypt cost parameters.
+    ///
+    /// | Parameter   | RFC 7914 Section 6 Term
+    /// |-------------|--------------------------------------
+    /// | log_n       | log2(N) (CPU/Memory cost parameter)
+    /// | r           | r (block size parameter)
+    /// | p           | p (parallelization parameter)
+    ///
+    /// Fails unless `1 <= log_n < 16 * r` and `r * p < 2**30`, per RFC 7914,
+    /// or if the memory required, `128 * r * 2**log_n` bytes, can't be
+    /// addressed on this platform.
+    pub fn new(log_n: u8, r: NonZeroU32, p: NonZeroU32) -> Result<Self, error::Unspecified> {
+        let (r_, p_) = (u64::from(r.get()), u64::from(p.get()));
+        if log_n == 0 || u64::from(log_n) >= 16 * r_ || r_ * p_ >= (1 << 30) {
+            return Err(error::Unspecified);
+        }
+        // `log_n < 64` since `r < 2**30`, but the shift may still overflow
+        // `usize`.
+        let n = 1usize
+            .checked_shl(u32::from(log_n))
+            .ok_or(error::Unspecified)?;
+        let block_len = (r.get() as usize)
+            .checked_mul(BLOCK_LEN)
+            .ok_or(error::Unspecified)?;
+        let _ = n.checked_mul(block_len).ok_or(error::Unspecified)?;
+        let _ = (p.get() as usize)
+            .checked_mul(block_len)
+            .ok_or(error::Unspecified)?;
+        Ok(Self { log_n, r, p })
+    }
+
+    /// log2 of the CPU/memory cost parameter N.
+    #[inline]
+    pub fn log_n(&self) -> u8 {
+        self.log_n
+    }
+
+    /// The block size parameter.
+    #[inline]
+    pub fn r(&self) -> NonZeroU32 {
+        self.r
+    }
+
+    /// The parallelization parameter.
+    #[inline]
+    pub fn p(&self) -> NonZeroU32 {
+        self.p
+    }
+}
+
+/// Fills `out` with the key derived using scrypt with the given inputs.
+///
+/// Do not use `derive` as part of verifying a secret; use `verify` instead, to
+/// minimize the effectiveness of timing attacks.
+///
+/// | Parameter   | RFC 7914 Section 6 Term
+/// |-------------|--------------------------------------
+/// | params      | N, r, p
+/// | salt        | S (salt)
+/// | secret      | P (passphrase)
+/// | out         | DK (derived key)
+/// | out.len()   | dkLen (intended output length)
+///
+/// # Panics
+///
+/// `derive` panics if `out.len()` is larger than (2**32 - 1) * 32, per the
+/// scrypt specification.
+pub fn derive(params: &Params, salt: &[u8], secret: &[u8], out: &mut [u8]) {
+    let r = params.r.get() as usize;
+    let block_words = 2 * r * SALSA_WORDS;
+    let one = NonZeroU32::new(1).unwrap();
+
+    let mut b = vec![0u8; params.p.get() as usize * r * BLOCK_LEN];
+    pbkdf2::derive(&digest::SHA256, one, salt, secret, &mut b);
+
+    let mut v = vec![0u32; block_words << params.log_n];
+    let mut x = vec![0u32; 2 * block_words];
+    for chunk in b.chunks_mut(r * BLOCK_LEN) {
+        ro_mix(params, chunk, &mut v, &mut x);
+    }
+
+    pbkdf2::derive(&digest::SHA256, one, &b, secret, out);
+}
+
+/// Verifies that a previously-derived (e.g., using `derive`) scrypt value
+/// matches the scrypt value derived from the other inputs.
+///
+/// The comparison is done in constant time to prevent timing attacks. The
+/// comparison will fail if `previously_derived` is empty (has a length of
+/// zero).
+///
+/// | Parameter                  | RFC 7914 Section 6 Term
+/// |----------------------------|--------------------------------------
+/// | params                     | N, r, p
+/// | salt                       | S (salt)
+/// | secret                     | P (passphrase)
+/// | previously_derived         | DK (derived key)
+/// | previously_derived.len()   | dkLen (intended output length)
+///
+/// # Panics
+///
+/// `verify` panics if `previously_derived.len()` is larger than
+/// (2**32 - 1) * 32, per the scrypt specification.
+pub fn verify(
+    params: &Params,
+    salt: &[u8],
+    secret: &[u8],
+    previously_derived: &[u8],
+) -> Result<(), error::Unspecified> {
+    if previously_derived.is_empty() {
+        return Err(error::Unspecified);
+    }
+    let mut derived = vec![0u8; previously_derived.len()];
+    derive(params, salt, secret, &mut derived);
+    constant_time::verify_slices_are_equal(&derived, previously_derived)
+}
+
+// scryptBlockMix's input is `2 * r` Salsa20 blocks, i.e. `r * BLOCK_LEN` bytes.
+const BLOCK_LEN: usize = 128;
+
+const SALSA_WORDS: usize = 16;
+
+/// scryptROMix (RFC 7914 Section 5), which replaces `b` with its output.
+/// `v` must have room for N blocks and `x` for two blocks.
+fn ro_mix(params: &Params, b: &mut [u8], v: &mut [u32], x: &mut [u32]) {
+    let block_words = b.len() / 4;
+    let n = 1usize << params.log_n;
+    let (x, y) = x.split_at_mut(block_words);
+
+    for (x, bytes) in x.iter_mut().zip(b.chunks_exact(4)) {
+        let mut le = [0u8; 4];
+        le.copy_from_slice(bytes);
+        *x = u32::from_le_bytes(le);
+    }
+
+    for v in v.chunks_exact_mut(block_words) {
+        v.copy_from_slice(x);
+        block_mix(x, y);
+        x.copy_from_slice(y);
+    }
+
+    for _ in 0..n {
+        let j = integerify(x) & (n - 1);
+        for (x, v) in x
+            .iter_mut()
+            .zip(v[(j * block_words)..][..block_words].iter())
+        {
+            *x ^= v;
+        }
+        block_mix(x, y);
+        x.copy_from_slice(y);
+    }
+
+    for (bytes, x) in b.chunks_exact_mut(4).zip(x.iter()) {
+        bytes.copy_from_slice(&x.to_le_bytes());
+    }
+}
+
+// Returns the low bits of Integerify(X), enough to compute `Integerify(X) mod
+// N` for any N that `Params` accepts.
+fn integerify(x: &[u32]) -> usize {
+    let last = &x[(x.len() - SALSA_WORDS)..];
+    ((u64::from(last[1]) << 32) | u64::from(last[0])) as usize
+}
+
+/// scryptBlockMix (RFC 7914 Section 4) of `input`, into `out`.
+fn block_mix(input: &[u32], out: &mut [u32]) {
+    let r = input.len() / (2 * SALSA_WORDS);
+    let mut x = [0u32; SALSA_WORDS];
+    x.copy_from_slice(&input[(input.len() - SALSA_WORDS)..]);
+    for (i, block) in input.chunks_exact(SALSA_WORDS).enumerate() {
+        for (x, b) in x.iter_mut().zip(block.iter()) {
+            *x ^= b;
+        }
+        salsa20_8(&mut x);
+        // The even-numbered blocks go in the first half of the output and the
+        // odd-numbered blocks in the second half.
+        let position = (i / 2) + (i % 2) * r;
+        out[(position * SALSA_WORDS)..][..SALSA_WORDS].copy_from_slice(&x);
+    }
+}
+
+/// The Salsa20/8 core (RFC 7914 Section 3).
+fn salsa20_8(b: &mut [u32; SALSA_WORDS]) {
+    let mut x = *b;
+    for _ in 0..4 {
+        // Columns.
+        quarter_round(&mut x, 0, 4, 8, 12);
+        quarter_round(&mut x, 5, 9, 13, 1);
+        quarter_round(&mut x, 10, 14, 2, 6);
+        quarter_round(&mut x, 15, 3, 7, 11);
+        // Rows.
+        quarter_round(&mut x, 0, 1, 2, 3);
+        quarter_round(&mut x, 5, 6, 7, 4);
+        quarter_round(&mut x, 10, 11, 8, 9);
+        quarter_round(&mut x, 15, 12, 13, 14);
+    }
+    for (b, x) in b.iter_mut().zip(x.iter()) {
+        *b = b.wrapping_add(*x);
+    }
+}
+
+#[inline(always)]
+fn quarter_round(x: &mut [u32; SALSA_WORDS], a: usize, b: usize, c: usize, d: usize) {
+    x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+    x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+    x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+    x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+}
@@ -0,0 +1,252 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Locked memory for long-lived secrets.
+//!
+//! A `Box<T>` is like `std::boxed::Box<T>`, except that its value is kept on
+//! pages of its own that are locked into physical memory, so they are never
+//! written to swap, and that are left out of core dumps. When the `Box` is
+//! dropped, its value is dropped and then its pages are zeroized before they
+//! are unlocked and freed.
+//!
+//! With the `memory_locking` feature, *ring* keeps the expanded keys of
+//! `aead::OpeningKey` and `aead::SealingKey`, the private keys of
+//! `signature::RsaKeyPair`, and the RSA big integers computed from them in
+//! `Box`es. Other key types are small values that the application can put in
+//! a `Box` itself, keeping in mind that moving a value into a `Box` may leave
+//! a copy of it behind.
+//!
+//! Pages are locked with `mlock()` on Unix-like systems and `VirtualLock()`
+//! on Windows. They are left out of core dumps with `madvise()` on Linux and
+//! Android only. Locking is best-effort: operating systems limit how much
+//! memory a process may lock (on Linux, `RLIMIT_MEMLOCK`), and a `Box` whose
+//! pages couldn't be locked still works, with `is_locked()` returning `false`.
+//! Since memory is locked a page at a time, every `Box` takes at least a whole
+//! page.
+
+use crate::zeroize::Zeroize;
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+};
+use std::alloc::{self, Layout};
+
+/// A value on locked pages of its own, which are zeroized when it is dropped.
+pub struct Box<T: ?Sized> {
+    value: NonNull<T>,
+    layout: Layout,
+    locked: bool,
+    _owns: PhantomData<T>,
+}
+
+// Like `std::boxed::Box<T>`, a `Box<T>` owns its value exclusively.
+unsafe impl<T: ?Sized + Send> Send for Box<T> {}
+unsafe impl<T: ?Sized + Sync> Sync for Box<T> {}
+
+impl<T> Box<T> {
+    /// Moves `value` onto newly allocated pages, locking them first.
+    ///
+    /// Like `std::boxed::Box::new()`, this aborts if the pages can't be
+    /// allocated.
+    pub fn new(value: T) -> Self {
+        let (pages, layout, locked) = allocate(Layout::new::<T>());
+        let value_ptr = pages.as_ptr() as *mut T;
+        unsafe { ptr::write(value_ptr, value) };
+        Self {
+            value: unsafe { NonNull::new_unchecked(value_ptr) },
+            layout,
+            locked,
+            _owns: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Box<T> {
+    /// Whether the pages were locked into physical memory.
+    #[inline]
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl<T: Copy> From<&[T]> for Box<[T]> {
+    /// Copies `values` onto newly allocated pages, locking them first.
+    fn from(values: &[T]) -> Self {
+        let size = core::mem::size_of::<T>().checked_mul(values.len()).unwrap();
+        let layout = Layout::from_size_align(size, core::mem::align_of::<T>()).unwrap();
+        let (pages, layout, locked) = allocate(layout);
+        let values_ptr = pages.as_ptr() as *mut T;
+        unsafe { ptr::copy_nonoverlapping(values.as_ptr(), values_ptr, values.len()) };
+        let values = unsafe { core::slice::from_raw_parts_mut(values_ptr, values.len()) };
+        Self {
+            value: NonNull::from(values),
+            layout,
+            locked,
+            _owns: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy> Clone for Box<[T]> {
+    fn clone(&self) -> Self {
+        Self::from(&self[..])
+    }
+}
+
+impl<T: ?Sized> Deref for Box<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for Box<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<T: ?Sized> Drop for Box<T> {
+    fn drop(&mut self) {
+        let pages = self.value.as_ptr() as *mut u8;
+        unsafe {
+            ptr::drop_in_place(self.value.as_ptr());
+            core::slice::from_raw_parts_mut(pages, self.layout.size()).zeroize();
+            unlock(pages, self.layout.size());
+            alloc::dealloc(pages, self.layout);
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Box<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("Box").field("locked", &self.locked).finish()
+    }
+}
+
+// Allocates zeroed, page-aligned memory for a value with layout `layout`,
+// rounded up to whole pages so that no other value shares them, and locks it.
+fn allocate(layout: Layout) -> (NonNull<u8>, Layout, bool) {
+    let page_size = page_size();
+    let size = core::cmp::max(layout.size(), 1);
+    let layout = Layout::from_size_align(
+        (size + page_size - 1) / page_size * page_size,
+        core::cmp::max(layout.align(), page_size),
+    )
+    .unwrap();
+    let pages = match NonNull::new(unsafe { alloc::alloc_zeroed(layout) }) {
+        Some(pages) => pages,
+        None => alloc::handle_alloc_error(layout),
+    };
+    let locked = unsafe { lock(pages.as_ptr(), layout.size()) };
+    (pages, layout, locked)
+}
+
+#[cfg(unix)]
+fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+#[cfg(unix)]
+unsafe fn lock(pages: *mut u8, len: usize) -> bool {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        let _ = libc::madvise(pages as *mut libc::c_void, len, libc::MADV_DONTDUMP);
+    }
+    libc::mlock(pages as *const libc::c_void, len) == 0
+}
+
+#[cfg(unix)]
+unsafe fn unlock(pages: *mut u8, len: usize) {
+    // This succeeds whether or not the pages were locked.
+    let _ = libc::munlock(pages as *const libc::c_void, len);
+    // The allocator may reuse the pages for values that aren't secret.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        let _ = libc::madvise(pages as *mut libc::c_void, len, libc::MADV_DODUMP);
+    }
+}
+
+#[cfg(windows)]
+fn page_size() -> usize {
+    use winapi::um::sysinfoapi::{GetSystemInfo, SYSTEM_INFO};
+
+    let mut info: SYSTEM_INFO = unsafe { core::mem::zeroed() };
+    unsafe { GetSystemInfo(&mut info) };
+    info.dwPageSize as usize
+}
+
+#[cfg(windows)]
+unsafe fn lock(pages: *mut u8, len: usize) -> bool {
+    winapi::um::memoryapi::VirtualLock(pages as winapi::shared::minwindef::LPVOID, len) != 0
+}
+
+#[cfg(windows)]
+unsafe fn unlock(pages: *mut u8, len: usize) {
+    let _ = winapi::um::memoryapi::VirtualUnlock(pages as winapi::shared::minwindef::LPVOID, len);
+}
+
+#[cfg(not(any(unix, windows)))]
+fn page_size() -> usize {
+    4096
+}
+
+#[cfg(not(any(unix, windows)))]
+unsafe fn lock(_pages: *mut u8, _len: usize) -> bool {
+    false
+}
+
+#[cfg(not(any(unix, windows)))]
+unsafe fn unlock(_pages: *mut u8, _len: usize) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn secret_box_test() {
+        let value = Rc::new([0x5au8; 100]);
+        let mut b = Box::new(value.clone());
+        assert_eq!(Rc::strong_count(&value), 2);
+        assert_eq!(b[..], value[..]);
+        let address: *const Rc<[u8; 100]> = &*b;
+        assert_eq!(address as usize % page_size(), 0);
+
+        *b = Rc::new([0; 100]);
+        assert_eq!(Rc::strong_count(&value), 1);
+        let replaced = Rc::clone(&b);
+        drop(b);
+        assert_eq!(Rc::strong_count(&replaced), 1);
+    }
+
+    #[test]
+    fn secret_box_slice_test() {
+        let mut b = Box::<[u32]>::from(&[1, 2, 3][..]);
+        assert_eq!(&b[..], &[1, 2, 3]);
+        b[1] = 5;
+
+        let c = b.clone();
+        assert_eq!(&c[..], &[1, 5, 3]);
+        assert_ne!(c.as_ptr(), b.as_ptr());
+
+        let empty = Box::<[u64]>::from(&[][..]);
+        assert!(empty.is_empty());
+    }
+}
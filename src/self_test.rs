@@ -0,0 +1,296 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Power-on self-tests.
+//!
+//! `run()` runs a known-answer test for each of AES-GCM-SIV (with the
+//! `aead-aes-gcm-siv` feature), SHA-2, and HMAC, and a pairwise-consistency
+//! test for ECDSA, in the style of the
+//! self-tests that FIPS 140 requires a module to run when it starts. It
+//! reports the result of each test separately, so that an application can
+//! log which algorithm failed before refusing to start.
+//!
+//! The tests use the same implementations, including the same CPU-specific
+//! code paths, as the rest of *ring*.
+//!
+//! The tests construct keys like any other code, so under
+//! `policy::Policy::FipsApproved` the tests of algorithms that the policy
+//! doesn't allow, such as AES-GCM-SIV, fail.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::{rand, self_test};
+//!
+//! let rng = rand::SystemRandom::new();
+//! let report = self_test::run(&rng);
+//! for &(test, result) in report.results() {
+//!     assert!(result.is_ok(), "{:?} failed", test);
+//! }
+//! assert!(report.passed());
+//! ```
+
+use crate::{digest, error, hmac, rand, signature};
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+use crate::aead;
+
+/// A self-test.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Test {
+    /// Seals and opens a known-answer vector with `aead::AES_128_GCM_SIV`,
+    /// and checks that a modified ciphertext is rejected.
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    Aes128GcmSiv,
+
+    /// Like `Aes128GcmSiv`, with `aead::AES_256_GCM_SIV`.
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    Aes256GcmSiv,
+
+    /// Hashes a known-answer vector with `digest::SHA256`.
+    Sha256,
+
+    /// Hashes a known-answer vector with `digest::SHA384`.
+    Sha384,
+
+    /// Hashes a known-answer vector with `digest::SHA512`.
+    Sha512,
+
+    /// Signs and verifies a known-answer vector with HMAC-SHA256.
+    HmacSha256,
+
+    /// Generates an ECDSA P-256 key pair, signs a message with it, and
+    /// verifies the signature with its public key; a signature of a
+    /// different message must not verify.
+    EcdsaP256PairwiseConsistency,
+}
+
+impl Test {
+    /// Runs the test. `rng` is used to generate the ECDSA key pair and
+    /// signature.
+    pub fn run(self, rng: &dyn rand::SecureRandom) -> Result<(), error::Unspecified> {
+        match self {
+            #[cfg(feature = "aead-aes-gcm-siv")]
+            Test::Aes128GcmSiv => aead_kat(&aead::AES_128_GCM_SIV, &AES_128_GCM_SIV_KAT),
+            #[cfg(feature = "aead-aes-gcm-siv")]
+            Test::Aes256GcmSiv => aead_kat(&aead::AES_256_GCM_SIV, &AES_256_GCM_SIV_KAT),
+            Test::Sha256 => digest_kat(&digest::SHA256, SHA256_ABC),
+            Test::Sha384 => digest_kat(&digest::SHA384, SHA384_ABC),
+            Test::Sha512 => digest_kat(&digest::SHA512, SHA512_ABC),
+            Test::HmacSha256 => hmac_sha256_kat(),
+            Test::EcdsaP256PairwiseConsistency => ecdsa_p256_pairwise_consistency(rng),
+        }
+    }
+}
+
+/// Runs all the self-tests.
+pub fn run(rng: &dyn rand::SecureRandom) -> Report {
+    let mut results = [(Test::Sha256, Ok(())); NUM_TESTS];
+    for (result, &test) in results.iter_mut().zip(TESTS.iter()) {
+        *result = (test, test.run(rng));
+    }
+    Report { results }
+}
+
+/// The results of `run()`.
+#[derive(Clone, Debug)]
+pub struct Report {
+    results: [(Test, Result<(), error::Unspecified>); NUM_TESTS],
+}
+
+impl Report {
+    /// Whether all the tests passed.
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|(_, result)| result.is_ok())
+    }
+
+    /// The result of each test, in the order they were run.
+    #[inline]
+    pub fn results(&self) -> &[(Test, Result<(), error::Unspecified>)] {
+        &self.results
+    }
+
+    /// The result of `test`.
+    pub fn result(&self, test: Test) -> Result<(), error::Unspecified> {
+        self.results
+            .iter()
+            .find(|&&(t, _)| t == test)
+            .map(|&(_, result)| result)
+            .unwrap()
+    }
+}
+
+const NUM_TESTS: usize = TESTS.len();
+
+const TESTS: &[Test] = &[
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    Test::Aes128GcmSiv,
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    Test::Aes256GcmSiv,
+    Test::Sha256,
+    Test::Sha384,
+    Test::Sha512,
+    Test::HmacSha256,
+    Test::EcdsaP256PairwiseConsistency,
+];
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+struct AeadKat {
+    key: &'static [u8],
+    nonce: [u8; aead::NONCE_LEN],
+    aad: &'static [u8],
+    plaintext: &'static [u8],
+    ciphertext_and_tag: &'static [u8],
+}
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+const MAX_AEAD_KAT_LEN: usize = 32;
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+fn aead_kat(alg: &'static aead::Algorithm, kat: &AeadKat) -> Result<(), error::Unspecified> {
+    let mut buf = [0u8; MAX_AEAD_KAT_LEN];
+    let in_out = &mut buf[..kat.ciphertext_and_tag.len()];
+    in_out[..kat.plaintext.len()].copy_from_slice(kat.plaintext);
+
+    let s_key = aead::SealingKey::new(alg, kat.key)?;
+    let nonce = aead::Nonce::assume_unique_for_key(kat.nonce);
+    let out_len = aead::seal_in_place(
+        &s_key,
+        nonce,
+        aead::Aad::from(kat.aad),
+        in_out,
+        aead::MAX_TAG_LEN,
+    )?;
+    if &in_out[..out_len] != kat.ciphertext_and_tag {
+        return Err(error::Unspecified);
+    }
+
+    let o_key = aead::OpeningKey::new(alg, kat.key)?;
+    let nonce = aead::Nonce::assume_unique_for_key(kat.nonce);
+    let plaintext = aead::open_in_place(&o_key, nonce, aead::Aad::from(kat.aad), 0, in_out)?;
+    if plaintext != kat.plaintext {
+        return Err(error::Unspecified);
+    }
+
+    let in_out = &mut buf[..kat.ciphertext_and_tag.len()];
+    in_out.copy_from_slice(kat.ciphertext_and_tag);
+    in_out[0] ^= 1;
+    let nonce = aead::Nonce::assume_unique_for_key(kat.nonce);
+    if aead::open_in_place(&o_key, nonce, aead::Aad::from(kat.aad), 0, in_out).is_ok() {
+        return Err(error::Unspecified);
+    }
+
+    Ok(())
+}
+
+fn digest_kat(alg: &'static digest::Algorithm, expected: &[u8]) -> Result<(), error::Unspecified> {
+    if digest::digest(alg, b"abc").as_ref() != expected {
+        return Err(error::Unspecified);
+    }
+    Ok(())
+}
+
+// RFC 4231 Section 4.3 (Test Case 2).
+fn hmac_sha256_kat() -> Result<(), error::Unspecified> {
+    const KEY: &[u8] = b"Jefe";
+    const DATA: &[u8] = b"what do ya want for nothing?";
+    const TAG: [u8; digest::SHA256_OUTPUT_LEN] = [
+        0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95, 0x75,
+        0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9, 0x64, 0xec,
+        0x38, 0x43,
+    ];
+
+    let key = hmac::Key::new(&digest::SHA256, KEY);
+    if hmac::sign(&key, DATA).as_ref() != &TAG[..] {
+        return Err(error::Unspecified);
+    }
+    hmac::verify(&key, DATA, &TAG)
+}
+
+fn ecdsa_p256_pairwise_consistency(rng: &dyn rand::SecureRandom) -> Result<(), error::Unspecified> {
+    use crate::signature::KeyPair;
+
+    const MESSAGE: &[u8] = b"ring self-test";
+
+    let alg = &signature::ECDSA_P256_SHA256_FIXED_SIGNING;
+    let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(alg, rng)?;
+    let key_pair = signature::EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref())?;
+    let sig = key_pair.sign(rng, MESSAGE)?;
+
+    let public_key = signature::UnparsedPublicKey::new(
+        &signature::ECDSA_P256_SHA256_FIXED,
+        key_pair.public_key().as_ref(),
+    );
+    public_key.verify(MESSAGE, sig.as_ref())?;
+    if public_key.verify(b"ring self-test!", sig.as_ref()).is_ok() {
+        return Err(error::Unspecified);
+    }
+    Ok(())
+}
+
+// From the draft-irtf-cfrg-gcmsiv-04 examples, which are the same as those
+// of RFC 8452.
+#[cfg(feature = "aead-aes-gcm-siv")]
+const AES_128_GCM_SIV_KAT: AeadKat = AeadKat {
+    key: &[
+        0xee, 0x8e, 0x1e, 0xd9, 0xff, 0x25, 0x40, 0xae, 0x8f, 0x2b, 0xa9, 0xf5, 0x0b, 0xc2, 0xf2,
+        0x7c,
+    ],
+    nonce: [
+        0x75, 0x2a, 0xba, 0xd3, 0xe0, 0xaf, 0xb5, 0xf4, 0x34, 0xdc, 0x43, 0x10,
+    ],
+    aad: b"example",
+    plaintext: b"Hello world",
+    ciphertext_and_tag: &[
+        0x5d, 0x34, 0x9e, 0xad, 0x17, 0x5e, 0xf6, 0xb1, 0xde, 0xf6, 0xfd, 0x4f, 0xbc, 0xde, 0xb7,
+        0xe4, 0x79, 0x3f, 0x4a, 0x1d, 0x7e, 0x4f, 0xaa, 0x70, 0x10, 0x0a, 0xf1,
+    ],
+};
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+const AES_256_GCM_SIV_KAT: AeadKat = AeadKat {
+    key: &[
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00,
+    ],
+    nonce: [
+        0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ],
+    aad: &[0x01],
+    plaintext: &[0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    ciphertext_and_tag: &[
+        0x1d, 0xe2, 0x29, 0x67, 0x23, 0x7a, 0x81, 0x32, 0x91, 0x21, 0x3f, 0x26, 0x7e, 0x3b, 0x45,
+        0x2f, 0x02, 0xd0, 0x1a, 0xe3, 0x3e, 0x4e, 0xc8, 0x54,
+    ],
+};
+
+// The hashes of "abc" from FIPS 180-2 Appendices B.1, C.1, and D.1.
+const SHA256_ABC: &[u8] = &[
+    0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22, 0x23,
+    0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00, 0x15, 0xad,
+];
+
+const SHA384_ABC: &[u8] = &[
+    0xcb, 0x00, 0x75, 0x3f, 0x45, 0xa3, 0x5e, 0x8b, 0xb5, 0xa0, 0x3d, 0x69, 0x9a, 0xc6, 0x50, 0x07,
+    0x27, 0x2c, 0x32, 0xab, 0x0e, 0xde, 0xd1, 0x63, 0x1a, 0x8b, 0x60, 0x5a, 0x43, 0xff, 0x5b, 0xed,
+    0x80, 0x86, 0x07, 0x2b, 0xa1, 0xe7, 0xcc, 0x23, 0x58, 0xba, 0xec, 0xa1, 0x34, 0xc8, 0x25, 0xa7,
+];
+
+const SHA512_ABC: &[u8] = &[
+    0xdd, 0xaf, 0x35, 0xa1, 0x93, 0x61, 0x7a, 0xba, 0xcc, 0x41, 0x73, 0x49, 0xae, 0x20, 0x41, 0x31,
+    0x12, 0xe6, 0xfa, 0x4e, 0x89, 0xa9, 0x7e, 0xa2, 0x0a, 0x9e, 0xee, 0xe6, 0x4b, 0x55, 0xd3, 0x9a,
+    0x21, 0x92, 0x99, 0x2a, 0x27, 0x4f, 0xc1, 0xa8, 0x36, 0xba, 0x3c, 0x23, 0xa3, 0xfe, 0xeb, 0xbd,
+    0x45, 0x4d, 0x44, 0x23, 0x64, 0x3c, 0xe8, 0x0e, 0x2a, 0x9a, 0xc9, 0x4f, 0xa5, 0x4c, 0xa4, 0x9f,
+];
@@ -0,0 +1,244 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Shamir secret sharing over GF(2^8).
+//!
+//! `split` splits a secret, such as an AEAD key or the seed of a key pair,
+//! into `share_count` shares so that any `threshold` of them can be given to
+//! `combine` to reconstruct it, and fewer reveal nothing about it. Each byte
+//! of the secret is shared independently using a random polynomial over
+//! GF(2^8) with the AES reduction polynomial x^8 + x^4 + x^3 + x + 1.
+//!
+//! Every share carries a tag, HMAC-SHA256 keyed with the secret over the rest
+//! of the share, truncated to 16 bytes. `combine` checks the tags of all the
+//! shares it is given against the reconstructed secret, so it fails instead
+//! of returning a wrong secret when a share is corrupted, forged, or from a
+//! different split. Because the tag is keyed with the secret, a single share
+//! allows an offline guessing attack on the secret; only high-entropy secrets
+//! may be shared, and secrets shorter than 16 bytes are rejected.
+//!
+//! The arithmetic on secret values, including reconstruction, is constant
+//! time. A share is encoded as the threshold, the share's index (from 1 to
+//! `share_count`), the share's value, and the tag.
+
+use crate::{constant_time, digest, error, hmac, rand};
+use std::{vec, vec::Vec};
+
+/// The minimum length of a secret.
+pub const MIN_SECRET_LEN: usize = 16;
+
+/// The length of a share's integrity tag.
+pub const TAG_LEN: usize = 16;
+
+/// One share of a secret.
+#[derive(Clone)]
+pub struct Share {
+    bytes: Vec<u8>,
+}
+
+impl Share {
+    /// Parses an encoded share, as returned by `as_ref()`.
+    ///
+    /// The tag isn't checked until the share is given to `combine`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, error::Unspecified> {
+        if bytes.len() < HEADER_LEN + MIN_SECRET_LEN + TAG_LEN || bytes[0] == 0 || bytes[1] == 0 {
+            return Err(error::Unspecified);
+        }
+        Ok(Self {
+            bytes: Vec::from(bytes),
+        })
+    }
+
+    /// The number of shares needed to reconstruct the secret.
+    #[inline]
+    pub fn threshold(&self) -> u8 {
+        self.bytes[0]
+    }
+
+    /// The index of the share, which is different for every share of a
+    /// secret.
+    #[inline]
+    pub fn index(&self) -> u8 {
+        self.bytes[1]
+    }
+
+    fn value(&self) -> &[u8] {
+        &self.bytes[HEADER_LEN..(self.bytes.len() - TAG_LEN)]
+    }
+
+    fn tag(&self) -> &[u8] {
+        &self.bytes[(self.bytes.len() - TAG_LEN)..]
+    }
+}
+
+impl AsRef<[u8]> for Share {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl core::fmt::Debug for Share {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Share")
+            .field("threshold", &self.threshold())
+            .field("index", &self.index())
+            .finish()
+    }
+}
+
+/// Splits `secret` into `share_count` shares, any `threshold` of which can
+/// reconstruct it, using polynomial coefficients generated by `rng`.
+///
+/// `threshold` must be at least 2 and at most `share_count`.
+pub fn split(
+    secret: &[u8],
+    threshold: u8,
+    share_count: u8,
+    rng: &dyn rand::SecureRandom,
+) -> Result<Vec<Share>, error::Unspecified> {
+    if secret.len() < MIN_SECRET_LEN || threshold < 2 || threshold > share_count {
+        return Err(error::Unspecified);
+    }
+
+    // The coefficients of degree 1 through `threshold - 1` of the polynomial
+    // for each byte of the secret.
+    let mut coefficients = vec![0u8; usize::from(threshold - 1) * secret.len()];
+    rng.fill(&mut coefficients)?;
+
+    let tag_key = hmac::Key::new(&digest::SHA256, secret);
+    let shares = (1..=share_count)
+        .map(|x| {
+            let mut bytes = Vec::with_capacity(HEADER_LEN + secret.len() + TAG_LEN);
+            bytes.push(threshold);
+            bytes.push(x);
+            bytes.extend(secret.iter().enumerate().map(|(i, &s)| {
+                // Horner's method, from the highest-degree coefficient down
+                // to the secret.
+                coefficients[i..]
+                    .iter()
+                    .step_by(secret.len())
+                    .rev()
+                    .chain(core::iter::once(&s))
+                    .fold(0, |acc, &c| gf_mul(acc, x) ^ c)
+            }));
+            let tag = tag(&tag_key, &bytes);
+            bytes.extend_from_slice(&tag);
+            Share { bytes }
+        })
+        .collect();
+    Ok(shares)
+}
+
+/// Reconstructs the secret from `shares`.
+///
+/// Fails if there are fewer shares than the threshold, if the shares are
+/// inconsistent or have duplicate indexes, or if any share's tag doesn't
+/// match the reconstructed secret.
+pub fn combine(shares: &[Share]) -> Result<Vec<u8>, error::Unspecified> {
+    let first = shares.first().ok_or(error::Unspecified)?;
+    let threshold = first.threshold();
+    let len = first.value().len();
+    if shares.len() < usize::from(threshold) {
+        return Err(error::Unspecified);
+    }
+    for (i, share) in shares.iter().enumerate() {
+        if share.threshold() != threshold || share.value().len() != len {
+            return Err(error::Unspecified);
+        }
+        if shares[..i]
+            .iter()
+            .any(|other| other.index() == share.index())
+        {
+            return Err(error::Unspecified);
+        }
+    }
+
+    // Lagrange interpolation at x = 0 using the first `threshold` shares.
+    let points = &shares[..usize::from(threshold)];
+    let mut secret = vec![0u8; len];
+    for share in points {
+        let x_i = share.index();
+        let basis = points
+            .iter()
+            .filter(|other| other.index() != x_i)
+            .fold(1, |acc, other| {
+                let x_j = other.index();
+                gf_mul(acc, gf_mul(x_j, gf_inv(x_j ^ x_i)))
+            });
+        for (s, &y) in secret.iter_mut().zip(share.value()) {
+            *s ^= gf_mul(basis, y);
+        }
+    }
+
+    let tag_key = hmac::Key::new(&digest::SHA256, &secret);
+    let mut all_valid = Ok(());
+    for share in shares {
+        let expected = tag(&tag_key, &share.bytes[..(share.bytes.len() - TAG_LEN)]);
+        let valid = constant_time::verify_slices_are_equal(&expected, share.tag());
+        all_valid = all_valid.and(valid);
+    }
+    all_valid?;
+    Ok(secret)
+}
+
+fn tag(key: &hmac::Key, share_without_tag: &[u8]) -> [u8; TAG_LEN] {
+    let mut ctx = hmac::Context::with_key(key);
+    ctx.update(TAG_LABEL);
+    ctx.update(share_without_tag);
+    let mut tag = [0u8; TAG_LEN];
+    tag.copy_from_slice(&ctx.sign().as_ref()[..TAG_LEN]);
+    tag
+}
+
+// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1, in constant time.
+fn gf_mul(a: u8, b: u8) -> u8 {
+    let mut a = a;
+    let mut r = 0;
+    for i in 0..8 {
+        r ^= a & 0u8.wrapping_sub((b >> i) & 1);
+        a = (a << 1) ^ (0x1b & 0u8.wrapping_sub(a >> 7));
+    }
+    r
+}
+
+// a^254 = a^-1 for nonzero a.
+fn gf_inv(a: u8) -> u8 {
+    let a2 = gf_mul(a, a);
+    let a3 = gf_mul(a2, a);
+    let a6 = gf_mul(a3, a3);
+    let a12 = gf_mul(a6, a6);
+    let a15 = gf_mul(a12, a3);
+    let a30 = gf_mul(a15, a15);
+    let a60 = gf_mul(a30, a30);
+    let a120 = gf_mul(a60, a60);
+    let a126 = gf_mul(a120, a6);
+    let a127 = gf_mul(a126, a);
+    gf_mul(a127, a127)
+}
+
+const HEADER_LEN: usize = 2;
+const TAG_LABEL: &[u8] = b"ring shamir share tag\0";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gf_inv() {
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1);
+        }
+    }
+}
@@ -56,8 +56,9 @@
 //!
 //! The signature is *r*||*s*, where || denotes concatenation, and where both
 //! *r* and *s* are both big-endian-encoded values that are left-padded to the
-//! maximum length. A P-256 signature will be 64 bytes long (two 32-byte
-//! components) and a P-384 signature will be 96 bytes long (two 48-byte
+//! maximum length. A P-256 or secp256k1 signature will be 64 bytes long (two
+//! 32-byte components), a P-384 signature will be 96 bytes long (two 48-byte
+//! components), and a P-521 signature will be 132 bytes long (two 66-byte
 //! components). This is the form of ECDSA signature used PKCS#11 and DNSSEC.
 //!
 //! The public key is encoding in uncompressed form using the
@@ -105,6 +106,10 @@
 //! Additionally, the entire salt is randomly generated separately for each
 //! signature using the secure random number generator passed to `sign()`.
 //!
+//! For interoperability with signers that use other parameters, such as a
+//! 20-byte salt, `RsaPssPadding` can be used to choose the salt length and
+//! the MGF1 digest algorithm for both signing and verification.
+//!
 //!
 //! [SEC 1: Elliptic Curve Cryptography, Version 2.0]:
 //!     http://www.secg.org/sec1-v2.pdf
@@ -132,6 +137,7 @@
 //!     signature::{self, KeyPair},
 //! };
 //!
+//! # #[cfg(feature = "sig-ed25519")]
 //! # fn sign_and_verify_ed25519() -> Result<(), ring::error::Unspecified> {
 //! // Generate a key pair in PKCS#8 (v2) format.
 //! let rng = rand::SystemRandom::new();
@@ -160,6 +166,11 @@
 //!
 //! # Ok(())
 //! # }
+//! #
+//! # #[cfg(not(feature = "sig-ed25519"))]
+//! # fn sign_and_verify_ed25519() -> Result<(), ring::error::Unspecified> {
+//! #     Ok(())
+//! # }
 //!
 //! # fn main() { sign_and_verify_ed25519().unwrap() }
 //! ```
@@ -195,7 +206,7 @@
 //! ```
 //! use ring::{rand, signature};
 //!
-//! # #[cfg(feature = "use_heap")]
+//! # #[cfg(feature = "rsa")]
 //! fn sign_and_verify_rsa(private_key_path: &std::path::Path,
 //!                        public_key_path: &std::path::Path)
 //!                        -> Result<(), MyError> {
@@ -240,7 +251,7 @@
 //!     Ok(contents)
 //! }
 //! #
-//! # #[cfg(not(feature = "use_heap"))]
+//! # #[cfg(not(feature = "rsa"))]
 //! # fn sign_and_verify_rsa(_private_key_path: &std::path::Path,
 //! #                        _public_key_path: &std::path::Path)
 //! #                        -> Result<(), ()> {
@@ -256,30 +267,49 @@
 //! # }
 //! ```
 
-use crate::{cpu, ec, error, sealed};
+use crate::{cpu, ec, error, policy, sealed, spki};
 use untrusted;
 
+#[cfg(feature = "use_heap")]
+use crate::rand;
+
+#[cfg(feature = "use_heap")]
+use std::vec::Vec;
+
 pub use crate::ec::{
-    curve25519::ed25519::{
-        signing::Ed25519KeyPair,
-        verification::{EdDSAParameters, ED25519},
-        ED25519_PUBLIC_KEY_LEN,
+    curve448::ed448::{
+        signing::Ed448KeyPair,
+        verification::{Ed448Parameters, ED448},
+        ED448_PUBLIC_KEY_LEN,
     },
     suite_b::ecdsa::{
         signing::{
-            EcdsaKeyPair, EcdsaSigningAlgorithm, ECDSA_P256_SHA256_ASN1_SIGNING,
-            ECDSA_P256_SHA256_FIXED_SIGNING, ECDSA_P384_SHA384_ASN1_SIGNING,
-            ECDSA_P384_SHA384_FIXED_SIGNING,
+            EcdsaDigestSigner, EcdsaKeyPair, EcdsaSigningAlgorithm, ExternalEcdsaKeyPair,
+            ECDSA_K256_SHA256_FIXED_SIGNING, ECDSA_P256_SHA256_ASN1_DETERMINISTIC_SIGNING,
+            ECDSA_P256_SHA256_ASN1_SIGNING, ECDSA_P256_SHA256_FIXED_DETERMINISTIC_SIGNING,
+            ECDSA_P256_SHA256_FIXED_SIGNING, ECDSA_P384_SHA384_ASN1_DETERMINISTIC_SIGNING,
+            ECDSA_P384_SHA384_ASN1_SIGNING, ECDSA_P384_SHA384_FIXED_DETERMINISTIC_SIGNING,
+            ECDSA_P384_SHA384_FIXED_SIGNING, ECDSA_P521_SHA512_ASN1_DETERMINISTIC_SIGNING,
+            ECDSA_P521_SHA512_ASN1_SIGNING, ECDSA_P521_SHA512_FIXED_DETERMINISTIC_SIGNING,
+            ECDSA_P521_SHA512_FIXED_SIGNING,
         },
         verification::{
-            EcdsaVerificationAlgorithm, ECDSA_P256_SHA256_ASN1, ECDSA_P256_SHA256_FIXED,
-            ECDSA_P256_SHA384_ASN1, ECDSA_P384_SHA256_ASN1, ECDSA_P384_SHA384_ASN1,
-            ECDSA_P384_SHA384_FIXED,
+            EcdsaVerificationAlgorithm, ECDSA_K256_SHA256_FIXED, ECDSA_P256_SHA256_ASN1,
+            ECDSA_P256_SHA256_FIXED, ECDSA_P256_SHA384_ASN1, ECDSA_P384_SHA256_ASN1,
+            ECDSA_P384_SHA384_ASN1, ECDSA_P384_SHA384_FIXED, ECDSA_P521_SHA512_ASN1,
+            ECDSA_P521_SHA512_FIXED,
         },
     },
 };
 
-#[cfg(feature = "use_heap")]
+#[cfg(feature = "sig-ed25519")]
+pub use crate::ec::curve25519::ed25519::{
+    signing::Ed25519KeyPair,
+    verification::{EdDSAParameters, ED25519, ED25519_STRICT, ED25519_ZIP215},
+    ED25519_PUBLIC_KEY_LEN,
+};
+
+#[cfg(feature = "rsa")]
 pub use crate::rsa::{
     signing::RsaKeyPair,
     signing::RsaSubjectPublicKey,
@@ -292,6 +322,7 @@ pub use crate::rsa::{
 
     RsaEncoding,
     RsaParameters,
+    RsaPssPadding,
 
     // `RSA_PKCS1_SHA1` is intentionally not exposed. At a minimum, we'd need
     // to create test vectors for signing with it, which we don't currently
@@ -343,15 +374,224 @@ pub trait KeyPair: core::fmt::Debug + Send + Sized + Sync {
     fn public_key(&self) -> &Self::PublicKey;
 }
 
-/// The longest signature is an ASN.1 P-384 signature where *r* and *s* are of
-/// maximum length with the leading high bit set on each. Then each component
-/// will have a tag, a one-byte length, and a one-byte “I'm not negative”
-/// prefix, and the outer sequence will have a two-byte length.
+/// A signature scheme, identifying both the kind of key and how the message
+/// is signed with it.
+///
+/// Each scheme corresponds to the verification algorithm with the same name,
+/// e.g. `SignatureScheme::RSA_PSS_SHA256` signatures can be verified with
+/// `RSA_PSS_2048_8192_SHA256`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SignatureScheme {
+    /// Ed25519.
+    ED25519,
+
+    /// Ed448.
+    ED448,
+
+    /// ECDSA using P-256 and SHA-256, with ASN.1 DER-encoded signatures.
+    ECDSA_P256_SHA256_ASN1,
+
+    /// ECDSA using P-256 and SHA-256, with fixed-length signatures.
+    ECDSA_P256_SHA256_FIXED,
+
+    /// ECDSA using P-384 and SHA-384, with ASN.1 DER-encoded signatures.
+    ECDSA_P384_SHA384_ASN1,
+
+    /// ECDSA using P-384 and SHA-384, with fixed-length signatures.
+    ECDSA_P384_SHA384_FIXED,
+
+    /// ECDSA using P-521 and SHA-512, with ASN.1 DER-encoded signatures.
+    ECDSA_P521_SHA512_ASN1,
+
+    /// ECDSA using P-521 and SHA-512, with fixed-length signatures.
+    ECDSA_P521_SHA512_FIXED,
+
+    /// ECDSA using secp256k1 and SHA-256, with fixed-length signatures.
+    ECDSA_K256_SHA256_FIXED,
+
+    /// RSA PKCS#1 1.5 padding using SHA-256.
+    RSA_PKCS1_SHA256,
+
+    /// RSA PKCS#1 1.5 padding using SHA-384.
+    RSA_PKCS1_SHA384,
+
+    /// RSA PKCS#1 1.5 padding using SHA-512.
+    RSA_PKCS1_SHA512,
+
+    /// RSA PSS padding using SHA-256.
+    RSA_PSS_SHA256,
+
+    /// RSA PSS padding using SHA-384.
+    RSA_PSS_SHA384,
+
+    /// RSA PSS padding using SHA-512.
+    RSA_PSS_SHA512,
+}
+
+impl SignatureScheme {
+    /// All the signature schemes.
+    pub fn all() -> &'static [Self] {
+        &ALL_SIGNATURE_SCHEMES
+    }
+
+    /// The scheme with the TLS `SignatureScheme` code point `id`, as listed
+    /// in [RFC 8446 Section 4.2.3], e.g. `RSA_PSS_SHA256` for 0x0804
+    /// (`rsa_pss_rsae_sha256`).
+    ///
+    /// TLS's ECDSA schemes use ASN.1 DER-encoded signatures, so they map to
+    /// the `_ASN1` variants. Returns `None` for code points that aren't
+    /// supported, including the `rsa_pss_pss_*` ones.
+    ///
+    /// [RFC 8446 Section 4.2.3]: https://tools.ietf.org/html/rfc8446#section-4.2.3
+    pub fn from_tls_id(id: u16) -> Option<Self> {
+        match id {
+            0x0401 => Some(SignatureScheme::RSA_PKCS1_SHA256),
+            0x0501 => Some(SignatureScheme::RSA_PKCS1_SHA384),
+            0x0601 => Some(SignatureScheme::RSA_PKCS1_SHA512),
+            0x0403 => Some(SignatureScheme::ECDSA_P256_SHA256_ASN1),
+            0x0503 => Some(SignatureScheme::ECDSA_P384_SHA384_ASN1),
+            0x0603 => Some(SignatureScheme::ECDSA_P521_SHA512_ASN1),
+            0x0804 => Some(SignatureScheme::RSA_PSS_SHA256),
+            0x0805 => Some(SignatureScheme::RSA_PSS_SHA384),
+            0x0806 => Some(SignatureScheme::RSA_PSS_SHA512),
+            0x0807 => Some(SignatureScheme::ED25519),
+            0x0808 => Some(SignatureScheme::ED448),
+            _ => None,
+        }
+    }
+
+    /// The scheme named `name`, e.g. `RSA_PSS_SHA256` for `"RSA_PSS_SHA256"`.
+    /// The names are those of the variants, which are also what the `Debug`
+    /// implementation prints.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .find(|scheme| scheme.name() == name)
+            .cloned()
+    }
+
+    // Must match the `Debug` output.
+    fn name(self) -> &'static str {
+        match self {
+            SignatureScheme::ED25519 => "ED25519",
+            SignatureScheme::ED448 => "ED448",
+            SignatureScheme::ECDSA_P256_SHA256_ASN1 => "ECDSA_P256_SHA256_ASN1",
+            SignatureScheme::ECDSA_P256_SHA256_FIXED => "ECDSA_P256_SHA256_FIXED",
+            SignatureScheme::ECDSA_P384_SHA384_ASN1 => "ECDSA_P384_SHA384_ASN1",
+            SignatureScheme::ECDSA_P384_SHA384_FIXED => "ECDSA_P384_SHA384_FIXED",
+            SignatureScheme::ECDSA_P521_SHA512_ASN1 => "ECDSA_P521_SHA512_ASN1",
+            SignatureScheme::ECDSA_P521_SHA512_FIXED => "ECDSA_P521_SHA512_FIXED",
+            SignatureScheme::ECDSA_K256_SHA256_FIXED => "ECDSA_K256_SHA256_FIXED",
+            SignatureScheme::RSA_PKCS1_SHA256 => "RSA_PKCS1_SHA256",
+            SignatureScheme::RSA_PKCS1_SHA384 => "RSA_PKCS1_SHA384",
+            SignatureScheme::RSA_PKCS1_SHA512 => "RSA_PKCS1_SHA512",
+            SignatureScheme::RSA_PSS_SHA256 => "RSA_PSS_SHA256",
+            SignatureScheme::RSA_PSS_SHA384 => "RSA_PSS_SHA384",
+            SignatureScheme::RSA_PSS_SHA512 => "RSA_PSS_SHA512",
+        }
+    }
+}
+
+static ALL_SIGNATURE_SCHEMES: [SignatureScheme; 15] = [
+    SignatureScheme::ED25519,
+    SignatureScheme::ED448,
+    SignatureScheme::ECDSA_P256_SHA256_ASN1,
+    SignatureScheme::ECDSA_P256_SHA256_FIXED,
+    SignatureScheme::ECDSA_P384_SHA384_ASN1,
+    SignatureScheme::ECDSA_P384_SHA384_FIXED,
+    SignatureScheme::ECDSA_P521_SHA512_ASN1,
+    SignatureScheme::ECDSA_P521_SHA512_FIXED,
+    SignatureScheme::ECDSA_K256_SHA256_FIXED,
+    SignatureScheme::RSA_PKCS1_SHA256,
+    SignatureScheme::RSA_PKCS1_SHA384,
+    SignatureScheme::RSA_PKCS1_SHA512,
+    SignatureScheme::RSA_PSS_SHA256,
+    SignatureScheme::RSA_PSS_SHA384,
+    SignatureScheme::RSA_PSS_SHA512,
+];
+
+/// A private key for signing.
+///
+/// `Ed25519KeyPair`, `EcdsaKeyPair`, `ExternalEcdsaKeyPair`, and `RsaKeyPair`
+/// implement `Signer`. Implement it for keys that are held elsewhere, e.g. in
+/// an HSM, a TPM, or a cloud KMS, to use them with the higher-level signing
+/// functions like `jose::sign()`. For ECDSA keys that sign digests, it is
+/// easier to implement `EcdsaDigestSigner` and use `ExternalEcdsaKeyPair`.
+#[cfg(feature = "use_heap")]
+pub trait Signer: core::fmt::Debug {
+    /// The public key, in the format used by the verification algorithms for
+    /// the schemes the key supports.
+    fn public_key_bytes(&self) -> &[u8];
+
+    /// Whether the key can sign with `scheme`.
+    fn supports(&self, scheme: SignatureScheme) -> bool;
+
+    /// Signs `message` using `scheme`, which must be supported. `rng` is used
+    /// for the schemes that need randomness.
+    fn sign(
+        &self,
+        scheme: SignatureScheme,
+        rng: &dyn rand::SecureRandom,
+        message: &[u8],
+    ) -> Result<Vec<u8>, error::Unspecified>;
+}
+
+/// The result of `AsyncSigner::sign_async()`.
+#[cfg(feature = "async")]
+pub type SignFuture<'a> = core::pin::Pin<
+    std::boxed::Box<
+        dyn core::future::Future<Output = Result<Vec<u8>, error::Unspecified>> + Send + 'a,
+    >,
+>;
+
+/// A private key for signing that may need to wait for I/O, e.g. one held by
+/// a cloud KMS.
+///
+/// Every `Signer` that is `Send + Sync` is also an `AsyncSigner` whose
+/// futures are ready immediately, so code that signs with an `AsyncSigner`
+/// works with the crate's own key pairs too. Since randomness for a remote
+/// signature is generated remotely, `sign_async` doesn't take an `rng`; the
+/// `AsyncSigner` implementation for `Signer`s uses `rand::SystemRandom`.
+#[cfg(feature = "async")]
+pub trait AsyncSigner: core::fmt::Debug + Send + Sync {
+    /// The public key, in the format used by the verification algorithms for
+    /// the schemes the key supports.
+    fn public_key_bytes(&self) -> &[u8];
+
+    /// Whether the key can sign with `scheme`.
+    fn supports(&self, scheme: SignatureScheme) -> bool;
+
+    /// Signs `message` using `scheme`, which must be supported.
+    fn sign_async<'a>(&'a self, scheme: SignatureScheme, message: &'a [u8]) -> SignFuture<'a>;
+}
+
+#[cfg(feature = "async")]
+impl<T: Signer + Send + Sync> AsyncSigner for T {
+    fn public_key_bytes(&self) -> &[u8] {
+        Signer::public_key_bytes(self)
+    }
+
+    fn supports(&self, scheme: SignatureScheme) -> bool {
+        Signer::supports(self, scheme)
+    }
+
+    fn sign_async<'a>(&'a self, scheme: SignatureScheme, message: &'a [u8]) -> SignFuture<'a> {
+        let result = Signer::sign(self, scheme, &rand::SystemRandom::new(), message);
+        std::boxed::Box::pin(async move { result })
+    }
+}
+
+/// The longest signature is an ASN.1 P-521 signature where *r* and *s* are of
+/// maximum length. Then each component will have a tag and a one-byte length
+/// (P-521 components never need the one-byte “I'm not negative” prefix, but
+/// it is counted anyway), and the outer sequence will have a two-byte length.
 pub(crate) const MAX_LEN: usize = 1/*tag:SEQUENCE*/ + 2/*len*/ +
     (2 * (1/*tag:INTEGER*/ + 1/*len*/ + 1/*zero*/ + ec::SCALAR_MAX_BYTES));
 
 /// A signature verification algorithm.
-pub trait VerificationAlgorithm: core::fmt::Debug + Sync + sealed::Sealed {
+pub trait VerificationAlgorithm:
+    core::fmt::Debug + Sync + sealed::Sealed + spki::PublicKeyAlgorithm
+{
     /// Verify the signature `signature` of message `msg` with the public key
     /// `public_key`.
     fn verify(
@@ -360,6 +600,40 @@ pub trait VerificationAlgorithm: core::fmt::Debug + Sync + sealed::Sealed {
         msg: untrusted::Input,
         signature: untrusted::Input,
     ) -> Result<(), error::Unspecified>;
+
+    /// Parses and validates `public_key` for `verify_parsed()`.
+    #[doc(hidden)]
+    fn parse_public_key(
+        &self,
+        public_key: untrusted::Input,
+    ) -> Result<ParsedKey, error::Unspecified>;
+
+    /// Like `verify()`, but with a public key from `parse_public_key()`.
+    #[doc(hidden)]
+    fn verify_parsed(
+        &self,
+        public_key: &ParsedKey,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), error::Unspecified>;
+
+    /// Whether the algorithm is allowed by `policy::Policy::FipsApproved`.
+    #[doc(hidden)]
+    fn fips_approved(&self) -> bool;
+}
+
+/// A public key for verifying signatures.
+///
+/// `UnparsedPublicKey` and `ParsedPublicKey` implement `Verifier`, so code
+/// that verifies signatures can accept either of them, e.g. as a
+/// `&dyn Verifier` or a `Box<dyn Verifier>`, without knowing the algorithm.
+pub trait Verifier {
+    /// The verification algorithm.
+    fn algorithm(&self) -> &'static dyn VerificationAlgorithm;
+
+    /// Verifies `signature` is a valid signature of `message` using the
+    /// public key.
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), error::Unspecified>;
 }
 
 /// An unparsed, possibly malformed, public key for signature verification.
@@ -391,12 +665,26 @@ impl<B: AsRef<[u8]>> UnparsedPublicKey<B> {
         Self { algorithm, bytes }
     }
 
+    /// The verification algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static dyn VerificationAlgorithm {
+        self.algorithm
+    }
+
     /// Parses the public key and verifies `signature` is a valid signature of
     /// `message` using it.
     ///
     /// See the [crate::signature] module-level documentation for examples.
     pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), error::Unspecified> {
+        policy::check(self.algorithm.fips_approved())?;
         let _ = cpu::features();
+        #[cfg(feature = "audit")]
+        crate::audit::record_public_key(
+            self.algorithm,
+            crate::audit::Operation::Verify,
+            self.bytes.as_ref(),
+            message.len(),
+        );
         self.algorithm.verify(
             untrusted::Input::from(self.bytes.as_ref()),
             untrusted::Input::from(message),
@@ -405,6 +693,155 @@ impl<B: AsRef<[u8]>> UnparsedPublicKey<B> {
     }
 }
 
+impl<B: AsRef<[u8]>> UnparsedPublicKey<B> {
+    /// Parses and validates the public key, for verifying many signatures
+    /// with it. See `ParsedPublicKey`.
+    pub fn parse(&self) -> Result<ParsedPublicKey, error::Unspecified> {
+        ParsedPublicKey::new(self.algorithm, self.bytes.as_ref())
+    }
+}
+
+impl<B: AsRef<[u8]>> Verifier for UnparsedPublicKey<B> {
+    #[inline]
+    fn algorithm(&self) -> &'static dyn VerificationAlgorithm {
+        UnparsedPublicKey::algorithm(self)
+    }
+
+    #[inline]
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), error::Unspecified> {
+        UnparsedPublicKey::verify(self, message, signature)
+    }
+}
+
+impl<'a> UnparsedPublicKey<&'a [u8]> {
+    /// Construct a new `UnparsedPublicKey` from a DER-encoded
+    /// `SubjectPublicKeyInfo`, e.g. the contents of a `PUBLIC KEY` PEM block
+    /// or the public key of an X.509 certificate.
+    ///
+    /// The algorithm identifier in the `SubjectPublicKeyInfo` only identifies
+    /// the type of the key, not the signature algorithm; an `rsaEncryption`
+    /// key can be used with PKCS#1 or PSS padding and any digest algorithm.
+    /// So `algorithms` lists the verification algorithms the caller accepts,
+    /// and the first one whose keys have the type of the
+    /// `SubjectPublicKeyInfo` is used. For example, with
+    /// `&[&ECDSA_P256_SHA256_ASN1, &ECDSA_P384_SHA384_ASN1, &ED25519]`, a
+    /// P-256 key is used with `ECDSA_P256_SHA256_ASN1`.
+    ///
+    /// Fails if `spki` isn't a valid `SubjectPublicKeyInfo` or none of
+    /// `algorithms` matches its algorithm identifier. As with `new()`, the
+    /// public key itself isn't validated until `verify()` is called.
+    pub fn from_spki_der(
+        algorithms: &[&'static dyn VerificationAlgorithm],
+        spki: &'a [u8],
+    ) -> Result<Self, error::Unspecified> {
+        let (algorithm_id, public_key) = spki::parse(untrusted::Input::from(spki))?;
+        let algorithm = algorithms
+            .iter()
+            .find(|algorithm| {
+                algorithm.public_key_algorithm_id() == algorithm_id.as_slice_less_safe()
+            })
+            .ok_or(error::Unspecified)?;
+        Ok(Self::new(*algorithm, public_key.as_slice_less_safe()))
+    }
+}
+
+/// A public key that has been parsed and validated, for verifying many
+/// signatures made with the same key.
+///
+/// `UnparsedPublicKey::verify()` parses and validates the public key every
+/// time it is called. For an RSA key, that includes setting up the
+/// Montgomery arithmetic for its modulus; for an ECDSA key, it includes
+/// checking that the point is on the curve; for an Ed25519 or Ed448 key, it
+/// includes decompressing the point. A `ParsedPublicKey` does that work once,
+/// when it is constructed. It is `Send` and `Sync`, so it can be cached and
+/// shared between threads, e.g. in an `Arc`.
+pub struct ParsedPublicKey {
+    algorithm: &'static dyn VerificationAlgorithm,
+    key: ParsedKey,
+    #[cfg(feature = "audit")]
+    fingerprint: crate::audit::Fingerprint,
+}
+
+impl ParsedPublicKey {
+    /// Parses and validates `public_key`, which is encoded as for
+    /// `UnparsedPublicKey`.
+    pub fn new(
+        algorithm: &'static dyn VerificationAlgorithm,
+        public_key: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        policy::check(algorithm.fips_approved())?;
+        let _ = cpu::features();
+        let key = algorithm.parse_public_key(untrusted::Input::from(public_key))?;
+        Ok(Self {
+            algorithm,
+            key,
+            #[cfg(feature = "audit")]
+            fingerprint: crate::audit::Fingerprint::new(public_key),
+        })
+    }
+
+    /// The verification algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static dyn VerificationAlgorithm {
+        self.algorithm
+    }
+
+    /// Verifies `signature` is a valid signature of `message` using the
+    /// public key.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), error::Unspecified> {
+        #[cfg(feature = "audit")]
+        crate::audit::record(
+            self.algorithm,
+            crate::audit::Operation::Verify,
+            &self.fingerprint,
+            message.len(),
+        );
+        self.algorithm.verify_parsed(
+            &self.key,
+            untrusted::Input::from(message),
+            untrusted::Input::from(signature),
+        )
+    }
+}
+
+impl Verifier for ParsedPublicKey {
+    #[inline]
+    fn algorithm(&self) -> &'static dyn VerificationAlgorithm {
+        ParsedPublicKey::algorithm(self)
+    }
+
+    #[inline]
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), error::Unspecified> {
+        ParsedPublicKey::verify(self, message, signature)
+    }
+}
+
+impl core::fmt::Debug for ParsedPublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("ParsedPublicKey")
+            .field("algorithm", &self.algorithm)
+            .finish()
+    }
+}
+
+pub(crate) use self::parsed_key::ParsedKey;
+
+// `ParsedKey` is public, so that it can be used in `VerificationAlgorithm`,
+// but it is in a private module so that it can't be named outside *ring*.
+mod parsed_key {
+    use crate::ec;
+
+    /// The algorithm-specific form of a `ParsedPublicKey`'s key.
+    pub enum ParsedKey {
+        Ecdsa(ec::suite_b::ecdsa::verification::ParsedPublicKey),
+        #[cfg(feature = "sig-ed25519")]
+        Ed25519(ec::curve25519::ed25519::verification::ParsedPublicKey),
+        Ed448(ec::curve448::ed448::verification::ParsedPublicKey),
+        #[cfg(feature = "rsa")]
+        Rsa(crate::rsa::verification::Key),
+    }
+}
+
 /// Deprecated. Use [UnparsedPublicKey::verify()].
 ///
 /// [UnparsedPublicKey::verify()]: UnparsedPublicKey::verify
@@ -0,0 +1,49 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! `SubjectPublicKeyInfo`, the encoding of public keys in X.509 certificates
+//! and `PUBLIC KEY` PEM blocks, specified in [RFC 5280 Section 4.1.2.7].
+//!
+//! [RFC 5280 Section 4.1.2.7]: https://tools.ietf.org/html/rfc5280#section-4.1.2.7
+
+use crate::{
+    error,
+    io::der::{self, Tag},
+};
+use untrusted;
+
+/// The `algorithm` of the `SubjectPublicKeyInfo` of keys of an algorithm.
+///
+/// This is public only so that it can be a supertrait of
+/// `signature::VerificationAlgorithm`; it isn't reachable from outside the
+/// crate.
+pub trait PublicKeyAlgorithm {
+    /// The contents of the DER-encoded `AlgorithmIdentifier`, without the
+    /// outer `SEQUENCE` tag and length.
+    fn public_key_algorithm_id(&self) -> &'static [u8];
+}
+
+/// Parses a `SubjectPublicKeyInfo`, returning the contents of its
+/// `algorithm` and its `subjectPublicKey`.
+pub(crate) fn parse(
+    spki: untrusted::Input,
+) -> Result<(untrusted::Input, untrusted::Input), error::Unspecified> {
+    spki.read_all(error::Unspecified, |input| {
+        der::nested(input, Tag::Sequence, error::Unspecified, |spki| {
+            let algorithm = der::expect_tag_and_get_value(spki, Tag::Sequence)?;
+            let public_key = der::bit_string_with_no_unused_bits(spki)?;
+            Ok((algorithm, public_key))
+        })
+    })
+}
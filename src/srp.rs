@@ -0,0 +1,592 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! SRP-6a, the Secure Remote Password protocol.
+//!
+//! This implements SRP-6a as specified in [RFC 5054], with the groups from
+//! its appendix A and SHA-256 as the hash function *H*. New protocols should
+//! use `pake` instead; SRP is provided for interoperating with existing
+//! deployments.
+//!
+//! At registration, the client chooses a random salt and sends the server the
+//! salt and the verifier from `generate_verifier()`, which the server stores
+//! along with the identity. To log in:
+//!
+//! 1. The client sends its identity *I* to the server.
+//! 2. The server looks up the salt and the verifier, starts a `Server`, and
+//!    sends the salt and its `public_key()`, *B*, to the client.
+//! 3. The client starts a `Client` and passes the password, the salt, and
+//!    *B* to `finish()`. Then it sends its `public_key()`, *A*, and its
+//!    `proof()`, *M1*, to the server.
+//! 4. The server passes *A* to `finish()`, and then passes *M1* to
+//!    `verify()`, which provides the shared key and the server's proof, *M2*,
+//!    only if *M1* is valid. The server sends *M2* to the client.
+//! 5. The client passes *M2* to `verify()`, which provides the shared key
+//!    only if *M2* is valid.
+//!
+//! Public keys and verifiers are encoded as big-endian numbers padded to the
+//! length of *N*; `PAD()` below denotes this encoding. The values that
+//! aren't specified by RFC 5054 are computed as in [RFC 2945]:
+//!
+//! * *k* = H(*N* | PAD(*g*)) and *u* = H(PAD(*A*) | PAD(*B*)).
+//! * *x* = H(*s* | H(*I* | ":" | *P*)).
+//! * *K* = H(PAD(*S*)).
+//! * *M1* = H((H(*N*) XOR H(*g*)) | H(*I*) | *s* | PAD(*A*) | PAD(*B*) | *K*).
+//! * *M2* = H(PAD(*A*) | *M1* | *K*).
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::{error, rand, srp};
+//!
+//! let rng = rand::SystemRandom::new();
+//! let group = &srp::RFC5054_2048;
+//!
+//! // Registration.
+//! let salt = [0x42; 16];
+//! let verifier = srp::generate_verifier(group, b"alice", b"password", &salt);
+//!
+//! // Login.
+//! let server = srp::Server::new(group, &verifier, &rng)?;
+//! let client = srp::Client::new(group, &rng)?;
+//! let client_public_key = client.public_key().to_vec();
+//!
+//! let client = client.finish(b"alice", b"password", &salt, server.public_key())?;
+//! let server = server.finish(b"alice", &salt, &client_public_key)?;
+//!
+//! let (server_proof, server_key) =
+//!     server.verify(client.proof(), error::Unspecified, |server_proof, key| {
+//!         Ok((server_proof.to_vec(), key.to_vec()))
+//!     })?;
+//! let client_key = client.verify(&server_proof, error::Unspecified, |key| Ok(key.to_vec()))?;
+//! assert_eq!(client_key, server_key);
+//!
+//! # Ok::<(), error::Unspecified>(())
+//! ```
+//!
+//! [RFC 5054]: https://tools.ietf.org/html/rfc5054
+//! [RFC 2945]: https://tools.ietf.org/html/rfc2945
+
+use crate::{
+    constant_time, digest, error,
+    modular::{Elem, Modulus},
+    rand,
+    zeroize::Zeroize,
+};
+use std::{vec, vec::Vec};
+
+/// The length of a proof, *M1* or *M2*.
+pub const PROOF_LEN: usize = digest::SHA256_OUTPUT_LEN;
+
+/// The length of the shared key.
+pub const SHARED_KEY_LEN: usize = digest::SHA256_OUTPUT_LEN;
+
+/// An SRP group: a safe prime *N* and a generator *g*.
+pub struct Group {
+    n: &'static [u8],
+    g: u8,
+}
+
+impl Group {
+    /// The length of *N*, and so of public keys and verifiers, in bytes.
+    #[inline]
+    pub fn modulus_len(&self) -> usize {
+        self.n.len()
+    }
+
+    fn modulus(&self) -> Modulus {
+        Modulus::from_be_bytes(self.n).unwrap()
+    }
+
+    fn generator<'a>(&self, n: &'a Modulus) -> Elem<'a> {
+        n.elem_from_be_bytes(&[self.g]).unwrap()
+    }
+
+    // k = H(N | PAD(g)).
+    fn k<'a>(&self, n: &'a Modulus) -> Elem<'a> {
+        let mut padded_g = vec![0u8; self.n.len()];
+        padded_g[self.n.len() - 1] = self.g;
+        let k = digest::digest_all(&digest::SHA256, &[self.n, &padded_g]);
+        n.elem_from_be_bytes(k.as_ref()).unwrap()
+    }
+}
+
+impl core::fmt::Debug for Group {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Group")
+            .field("bits", &(self.n.len() * 8))
+            .field("g", &self.g)
+            .finish()
+    }
+}
+
+/// The 1024-bit group from RFC 5054.
+pub static RFC5054_1024: Group = Group {
+    n: &RFC5054_1024_N,
+    g: 2,
+};
+
+/// The 1536-bit group from RFC 5054.
+pub static RFC5054_1536: Group = Group {
+    n: &RFC5054_1536_N,
+    g: 2,
+};
+
+/// The 2048-bit group from RFC 5054.
+pub static RFC5054_2048: Group = Group {
+    n: &RFC5054_2048_N,
+    g: 2,
+};
+
+/// The 3072-bit group from RFC 5054.
+pub static RFC5054_3072: Group = Group {
+    n: &RFC5054_3072_N,
+    g: 5,
+};
+
+/// The 4096-bit group from RFC 5054.
+pub static RFC5054_4096: Group = Group {
+    n: &RFC5054_4096_N,
+    g: 5,
+};
+
+/// Computes the verifier *v* = *g*<sup>*x*</sup> for the given identity,
+/// password, and salt.
+///
+/// The salt should be at least 16 random bytes, chosen when the password is
+/// set.
+pub fn generate_verifier(
+    group: &'static Group,
+    identity: &[u8],
+    password: &[u8],
+    salt: &[u8],
+) -> Vec<u8> {
+    let n = group.modulus();
+    let x = x(identity, password, salt);
+    group.generator(&n).pow(x.as_ref()).unwrap().to_be_bytes()
+}
+
+/// The client's state from the start of the protocol until it receives the
+/// server's public key.
+pub struct Client {
+    group: &'static Group,
+    a: [u8; EXPONENT_LEN],
+    public_key: Vec<u8>,
+}
+
+impl Client {
+    /// Starts the protocol by generating the client's ephemeral key pair.
+    pub fn new(
+        group: &'static Group,
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<Self, error::Unspecified> {
+        let mut a = [0u8; EXPONENT_LEN];
+        rng.fill(&mut a)?;
+        let n = group.modulus();
+        let public_key = group.generator(&n).pow(&a)?.to_be_bytes();
+        Ok(Self {
+            group,
+            a,
+            public_key,
+        })
+    }
+
+    /// The public key to send to the server, *A*.
+    #[inline]
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// Computes the shared secret from the password, the salt, and the
+    /// server's public key *B*, and derives the keys and proofs from it.
+    ///
+    /// Fails if `server_public_key` isn't a nonzero number less than *N*.
+    pub fn finish(
+        self,
+        identity: &[u8],
+        password: &[u8],
+        salt: &[u8],
+        server_public_key: &[u8],
+    ) -> Result<ClientSession, error::Unspecified> {
+        let n = self.group.modulus();
+        let b_pub = n.elem_from_be_bytes(server_public_key)?;
+        if b_pub.is_zero() {
+            return Err(error::Unspecified);
+        }
+        let padded_b_pub = b_pub.to_be_bytes();
+        let u = u(&self.public_key, &padded_b_pub)?;
+        let x = x(identity, password, salt);
+
+        // S = (B - k*g**x)**(a + u*x) = (B - k*g**x)**a * ((B - k*g**x)**u)**x.
+        let g_x = self.group.generator(&n).pow(x.as_ref())?;
+        let base = b_pub.sub(&self.group.k(&n).mul(&g_x));
+        let s = base
+            .pow(&self.a)?
+            .mul(&base.pow(u.as_ref())?.pow(x.as_ref())?);
+
+        let keys = SessionKeys::new(
+            self.group,
+            identity,
+            salt,
+            &self.public_key,
+            &padded_b_pub,
+            &s,
+        );
+        Ok(ClientSession(keys))
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.a[..].zeroize();
+    }
+}
+
+impl core::fmt::Debug for Client {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Client").field("group", self.group).finish()
+    }
+}
+
+/// The client's state after it has computed the shared secret, until it
+/// receives the server's proof.
+pub struct ClientSession(SessionKeys);
+
+impl ClientSession {
+    /// The proof to send to the server, *M1*.
+    #[inline]
+    pub fn proof(&self) -> &[u8] {
+        &self.0.m1
+    }
+
+    /// Verifies the server's proof, *M2*, and then calls `kdf` with the
+    /// `SHARED_KEY_LEN`-byte shared key, *K*.
+    ///
+    /// If the proof is wrong, `kdf` isn't called and `error_value` is
+    /// returned.
+    pub fn verify<F, R, E>(self, server_proof: &[u8], error_value: E, kdf: F) -> Result<R, E>
+    where
+        F: FnOnce(&[u8]) -> Result<R, E>,
+    {
+        constant_time::verify_slices_are_equal(server_proof, &self.0.m2)
+            .map_err(|error::Unspecified| error_value)?;
+        kdf(&self.0.key)
+    }
+}
+
+impl core::fmt::Debug for ClientSession {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("ClientSession").finish()
+    }
+}
+
+/// The server's state from the start of the protocol until it receives the
+/// client's public key.
+pub struct Server {
+    group: &'static Group,
+    verifier: Vec<u8>,
+    b: [u8; EXPONENT_LEN],
+    public_key: Vec<u8>,
+}
+
+impl Server {
+    /// Starts the protocol by generating the server's ephemeral key pair.
+    ///
+    /// `verifier` is the client's verifier from `generate_verifier()`. Fails
+    /// if it isn't a nonzero number less than *N*.
+    pub fn new(
+        group: &'static Group,
+        verifier: &[u8],
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<Self, error::Unspecified> {
+        let n = group.modulus();
+        let v = n.elem_from_be_bytes(verifier)?;
+        if v.is_zero() {
+            return Err(error::Unspecified);
+        }
+        let mut b = [0u8; EXPONENT_LEN];
+        rng.fill(&mut b)?;
+
+        // B = k*v + g**b.
+        let public_key = group
+            .k(&n)
+            .mul(&v)
+            .add(&group.generator(&n).pow(&b)?)
+            .to_be_bytes();
+        Ok(Self {
+            group,
+            verifier: v.to_be_bytes(),
+            b,
+            public_key,
+        })
+    }
+
+    /// The public key to send to the client, *B*.
+    #[inline]
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// Computes the shared secret from the client's public key *A*, and
+    /// derives the keys and proofs from it.
+    ///
+    /// `identity` and `salt` must be the ones the verifier was generated
+    /// with. Fails if `client_public_key` isn't a nonzero number less than
+    /// *N*.
+    pub fn finish(
+        self,
+        identity: &[u8],
+        salt: &[u8],
+        client_public_key: &[u8],
+    ) -> Result<ServerSession, error::Unspecified> {
+        let n = self.group.modulus();
+        let a_pub = n.elem_from_be_bytes(client_public_key)?;
+        if a_pub.is_zero() {
+            return Err(error::Unspecified);
+        }
+        let padded_a_pub = a_pub.to_be_bytes();
+        let u = u(&padded_a_pub, &self.public_key)?;
+
+        // S = (A * v**u)**b.
+        let v = n.elem_from_be_bytes(&self.verifier)?;
+        let s = a_pub.mul(&v.pow(u.as_ref())?).pow(&self.b)?;
+
+        let keys = SessionKeys::new(
+            self.group,
+            identity,
+            salt,
+            &padded_a_pub,
+            &self.public_key,
+            &s,
+        );
+        Ok(ServerSession(keys))
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.b[..].zeroize();
+    }
+}
+
+impl core::fmt::Debug for Server {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Server").field("group", self.group).finish()
+    }
+}
+
+/// The server's state after it has computed the shared secret, until it
+/// receives the client's proof.
+pub struct ServerSession(SessionKeys);
+
+impl ServerSession {
+    /// Verifies the client's proof, *M1*, and then calls `f` with the
+    /// server's proof to send to the client, *M2*, and the
+    /// `SHARED_KEY_LEN`-byte shared key, *K*.
+    ///
+    /// If the proof is wrong, `f` isn't called and `error_value` is
+    /// returned; the client used the wrong password, or the messages were
+    /// tampered with. *M2* must not be sent in this case, since it would let
+    /// the client test guesses of the password offline.
+    pub fn verify<F, R, E>(self, client_proof: &[u8], error_value: E, f: F) -> Result<R, E>
+    where
+        F: FnOnce(&[u8], &[u8]) -> Result<R, E>,
+    {
+        constant_time::verify_slices_are_equal(client_proof, &self.0.m1)
+            .map_err(|error::Unspecified| error_value)?;
+        f(&self.0.m2, &self.0.key)
+    }
+}
+
+impl core::fmt::Debug for ServerSession {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("ServerSession").finish()
+    }
+}
+
+// The length of the ephemeral private keys `a` and `b`, as recommended by
+// RFC 5054 Section 2.5.4.
+const EXPONENT_LEN: usize = 32;
+
+struct SessionKeys {
+    m1: [u8; PROOF_LEN],
+    m2: [u8; PROOF_LEN],
+    key: [u8; SHARED_KEY_LEN],
+}
+
+impl SessionKeys {
+    fn new(
+        group: &Group,
+        identity: &[u8],
+        salt: &[u8],
+        a_pub: &[u8],
+        b_pub: &[u8],
+        s: &Elem,
+    ) -> Self {
+        let mut s = s.to_be_bytes();
+        let h_s = digest::digest(&digest::SHA256, &s);
+        s[..].zeroize();
+        let mut key = [0u8; SHARED_KEY_LEN];
+        key.copy_from_slice(h_s.as_ref());
+
+        let mut n_xor_g = [0u8; digest::SHA256_OUTPUT_LEN];
+        n_xor_g.copy_from_slice(digest::digest(&digest::SHA256, group.n).as_ref());
+        let h_g = digest::digest(&digest::SHA256, &[group.g]);
+        for (a, b) in n_xor_g.iter_mut().zip(h_g.as_ref()) {
+            *a ^= b;
+        }
+        let h_i = digest::digest(&digest::SHA256, identity);
+
+        let mut m1 = [0u8; PROOF_LEN];
+        m1.copy_from_slice(
+            digest::digest_all(
+                &digest::SHA256,
+                &[&n_xor_g, h_i.as_ref(), salt, a_pub, b_pub, &key],
+            )
+            .as_ref(),
+        );
+        let mut m2 = [0u8; PROOF_LEN];
+        m2.copy_from_slice(digest::digest_all(&digest::SHA256, &[a_pub, &m1, &key]).as_ref());
+
+        Self { m1, m2, key }
+    }
+}
+
+impl Drop for SessionKeys {
+    fn drop(&mut self) {
+        self.key[..].zeroize();
+    }
+}
+
+// x = H(s | H(I | ":" | P)).
+fn x(identity: &[u8], password: &[u8], salt: &[u8]) -> digest::Digest {
+    let inner = digest::digest_all(&digest::SHA256, &[identity, b":", password]);
+    digest::digest_all(&digest::SHA256, &[salt, inner.as_ref()])
+}
+
+// u = H(PAD(A) | PAD(B)), which must not be zero.
+fn u(a_pub: &[u8], b_pub: &[u8]) -> Result<digest::Digest, error::Unspecified> {
+    let u = digest::digest_all(&digest::SHA256, &[a_pub, b_pub]);
+    if u.as_ref().iter().all(|&b| b == 0) {
+        return Err(error::Unspecified);
+    }
+    Ok(u)
+}
+
+const RFC5054_1024_N: [u8; 128] = [
+    0xee, 0xaf, 0x0a, 0xb9, 0xad, 0xb3, 0x8d, 0xd6, 0x9c, 0x33, 0xf8, 0x0a, 0xfa, 0x8f, 0xc5, 0xe8,
+    0x60, 0x72, 0x61, 0x87, 0x75, 0xff, 0x3c, 0x0b, 0x9e, 0xa2, 0x31, 0x4c, 0x9c, 0x25, 0x65, 0x76,
+    0xd6, 0x74, 0xdf, 0x74, 0x96, 0xea, 0x81, 0xd3, 0x38, 0x3b, 0x48, 0x13, 0xd6, 0x92, 0xc6, 0xe0,
+    0xe0, 0xd5, 0xd8, 0xe2, 0x50, 0xb9, 0x8b, 0xe4, 0x8e, 0x49, 0x5c, 0x1d, 0x60, 0x89, 0xda, 0xd1,
+    0x5d, 0xc7, 0xd7, 0xb4, 0x61, 0x54, 0xd6, 0xb6, 0xce, 0x8e, 0xf4, 0xad, 0x69, 0xb1, 0x5d, 0x49,
+    0x82, 0x55, 0x9b, 0x29, 0x7b, 0xcf, 0x18, 0x85, 0xc5, 0x29, 0xf5, 0x66, 0x66, 0x0e, 0x57, 0xec,
+    0x68, 0xed, 0xbc, 0x3c, 0x05, 0x72, 0x6c, 0xc0, 0x2f, 0xd4, 0xcb, 0xf4, 0x97, 0x6e, 0xaa, 0x9a,
+    0xfd, 0x51, 0x38, 0xfe, 0x83, 0x76, 0x43, 0x5b, 0x9f, 0xc6, 0x1d, 0x2f, 0xc0, 0xeb, 0x06, 0xe3,
+];
+
+const RFC5054_1536_N: [u8; 192] = [
+    0x9d, 0xef, 0x3c, 0xaf, 0xb9, 0x39, 0x27, 0x7a, 0xb1, 0xf1, 0x2a, 0x86, 0x17, 0xa4, 0x7b, 0xbb,
+    0xdb, 0xa5, 0x1d, 0xf4, 0x99, 0xac, 0x4c, 0x80, 0xbe, 0xee, 0xa9, 0x61, 0x4b, 0x19, 0xcc, 0x4d,
+    0x5f, 0x4f, 0x5f, 0x55, 0x6e, 0x27, 0xcb, 0xde, 0x51, 0xc6, 0xa9, 0x4b, 0xe4, 0x60, 0x7a, 0x29,
+    0x15, 0x58, 0x90, 0x3b, 0xa0, 0xd0, 0xf8, 0x43, 0x80, 0xb6, 0x55, 0xbb, 0x9a, 0x22, 0xe8, 0xdc,
+    0xdf, 0x02, 0x8a, 0x7c, 0xec, 0x67, 0xf0, 0xd0, 0x81, 0x34, 0xb1, 0xc8, 0xb9, 0x79, 0x89, 0x14,
+    0x9b, 0x60, 0x9e, 0x0b, 0xe3, 0xba, 0xb6, 0x3d, 0x47, 0x54, 0x83, 0x81, 0xdb, 0xc5, 0xb1, 0xfc,
+    0x76, 0x4e, 0x3f, 0x4b, 0x53, 0xdd, 0x9d, 0xa1, 0x15, 0x8b, 0xfd, 0x3e, 0x2b, 0x9c, 0x8c, 0xf5,
+    0x6e, 0xdf, 0x01, 0x95, 0x39, 0x34, 0x96, 0x27, 0xdb, 0x2f, 0xd5, 0x3d, 0x24, 0xb7, 0xc4, 0x86,
+    0x65, 0x77, 0x2e, 0x43, 0x7d, 0x6c, 0x7f, 0x8c, 0xe4, 0x42, 0x73, 0x4a, 0xf7, 0xcc, 0xb7, 0xae,
+    0x83, 0x7c, 0x26, 0x4a, 0xe3, 0xa9, 0xbe, 0xb8, 0x7f, 0x8a, 0x2f, 0xe9, 0xb8, 0xb5, 0x29, 0x2e,
+    0x5a, 0x02, 0x1f, 0xff, 0x5e, 0x91, 0x47, 0x9e, 0x8c, 0xe7, 0xa2, 0x8c, 0x24, 0x42, 0xc6, 0xf3,
+    0x15, 0x18, 0x0f, 0x93, 0x49, 0x9a, 0x23, 0x4d, 0xcf, 0x76, 0xe3, 0xfe, 0xd1, 0x35, 0xf9, 0xbb,
+];
+
+const RFC5054_2048_N: [u8; 256] = [
+    0xac, 0x6b, 0xdb, 0x41, 0x32, 0x4a, 0x9a, 0x9b, 0xf1, 0x66, 0xde, 0x5e, 0x13, 0x89, 0x58, 0x2f,
+    0xaf, 0x72, 0xb6, 0x65, 0x19, 0x87, 0xee, 0x07, 0xfc, 0x31, 0x92, 0x94, 0x3d, 0xb5, 0x60, 0x50,
+    0xa3, 0x73, 0x29, 0xcb, 0xb4, 0xa0, 0x99, 0xed, 0x81, 0x93, 0xe0, 0x75, 0x77, 0x67, 0xa1, 0x3d,
+    0xd5, 0x23, 0x12, 0xab, 0x4b, 0x03, 0x31, 0x0d, 0xcd, 0x7f, 0x48, 0xa9, 0xda, 0x04, 0xfd, 0x50,
+    0xe8, 0x08, 0x39, 0x69, 0xed, 0xb7, 0x67, 0xb0, 0xcf, 0x60, 0x95, 0x17, 0x9a, 0x16, 0x3a, 0xb3,
+    0x66, 0x1a, 0x05, 0xfb, 0xd5, 0xfa, 0xaa, 0xe8, 0x29, 0x18, 0xa9, 0x96, 0x2f, 0x0b, 0x93, 0xb8,
+    0x55, 0xf9, 0x79, 0x93, 0xec, 0x97, 0x5e, 0xea, 0xa8, 0x0d, 0x74, 0x0a, 0xdb, 0xf4, 0xff, 0x74,
+    0x73, 0x59, 0xd0, 0x41, 0xd5, 0xc3, 0x3e, 0xa7, 0x1d, 0x28, 0x1e, 0x44, 0x6b, 0x14, 0x77, 0x3b,
+    0xca, 0x97, 0xb4, 0x3a, 0x23, 0xfb, 0x80, 0x16, 0x76, 0xbd, 0x20, 0x7a, 0x43, 0x6c, 0x64, 0x81,
+    0xf1, 0xd2, 0xb9, 0x07, 0x87, 0x17, 0x46, 0x1a, 0x5b, 0x9d, 0x32, 0xe6, 0x88, 0xf8, 0x77, 0x48,
+    0x54, 0x45, 0x23, 0xb5, 0x24, 0xb0, 0xd5, 0x7d, 0x5e, 0xa7, 0x7a, 0x27, 0x75, 0xd2, 0xec, 0xfa,
+    0x03, 0x2c, 0xfb, 0xdb, 0xf5, 0x2f, 0xb3, 0x78, 0x61, 0x60, 0x27, 0x90, 0x04, 0xe5, 0x7a, 0xe6,
+    0xaf, 0x87, 0x4e, 0x73, 0x03, 0xce, 0x53, 0x29, 0x9c, 0xcc, 0x04, 0x1c, 0x7b, 0xc3, 0x08, 0xd8,
+    0x2a, 0x56, 0x98, 0xf3, 0xa8, 0xd0, 0xc3, 0x82, 0x71, 0xae, 0x35, 0xf8, 0xe9, 0xdb, 0xfb, 0xb6,
+    0x94, 0xb5, 0xc8, 0x03, 0xd8, 0x9f, 0x7a, 0xe4, 0x35, 0xde, 0x23, 0x6d, 0x52, 0x5f, 0x54, 0x75,
+    0x9b, 0x65, 0xe3, 0x72, 0xfc, 0xd6, 0x8e, 0xf2, 0x0f, 0xa7, 0x11, 0x1f, 0x9e, 0x4a, 0xff, 0x73,
+];
+
+const RFC5054_3072_N: [u8; 384] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xc9, 0x0f, 0xda, 0xa2, 0x21, 0x68, 0xc2, 0x34,
+    0xc4, 0xc6, 0x62, 0x8b, 0x80, 0xdc, 0x1c, 0xd1, 0x29, 0x02, 0x4e, 0x08, 0x8a, 0x67, 0xcc, 0x74,
+    0x02, 0x0b, 0xbe, 0xa6, 0x3b, 0x13, 0x9b, 0x22, 0x51, 0x4a, 0x08, 0x79, 0x8e, 0x34, 0x04, 0xdd,
+    0xef, 0x95, 0x19, 0xb3, 0xcd, 0x3a, 0x43, 0x1b, 0x30, 0x2b, 0x0a, 0x6d, 0xf2, 0x5f, 0x14, 0x37,
+    0x4f, 0xe1, 0x35, 0x6d, 0x6d, 0x51, 0xc2, 0x45, 0xe4, 0x85, 0xb5, 0x76, 0x62, 0x5e, 0x7e, 0xc6,
+    0xf4, 0x4c, 0x42, 0xe9, 0xa6, 0x37, 0xed, 0x6b, 0x0b, 0xff, 0x5c, 0xb6, 0xf4, 0x06, 0xb7, 0xed,
+    0xee, 0x38, 0x6b, 0xfb, 0x5a, 0x89, 0x9f, 0xa5, 0xae, 0x9f, 0x24, 0x11, 0x7c, 0x4b, 0x1f, 0xe6,
+    0x49, 0x28, 0x66, 0x51, 0xec, 0xe4, 0x5b, 0x3d, 0xc2, 0x00, 0x7c, 0xb8, 0xa1, 0x63, 0xbf, 0x05,
+    0x98, 0xda, 0x48, 0x36, 0x1c, 0x55, 0xd3, 0x9a, 0x69, 0x16, 0x3f, 0xa8, 0xfd, 0x24, 0xcf, 0x5f,
+    0x83, 0x65, 0x5d, 0x23, 0xdc, 0xa3, 0xad, 0x96, 0x1c, 0x62, 0xf3, 0x56, 0x20, 0x85, 0x52, 0xbb,
+    0x9e, 0xd5, 0x29, 0x07, 0x70, 0x96, 0x96, 0x6d, 0x67, 0x0c, 0x35, 0x4e, 0x4a, 0xbc, 0x98, 0x04,
+    0xf1, 0x74, 0x6c, 0x08, 0xca, 0x18, 0x21, 0x7c, 0x32, 0x90, 0x5e, 0x46, 0x2e, 0x36, 0xce, 0x3b,
+    0xe3, 0x9e, 0x77, 0x2c, 0x18, 0x0e, 0x86, 0x03, 0x9b, 0x27, 0x83, 0xa2, 0xec, 0x07, 0xa2, 0x8f,
+    0xb5, 0xc5, 0x5d, 0xf0, 0x6f, 0x4c, 0x52, 0xc9, 0xde, 0x2b, 0xcb, 0xf6, 0x95, 0x58, 0x17, 0x18,
+    0x39, 0x95, 0x49, 0x7c, 0xea, 0x95, 0x6a, 0xe5, 0x15, 0xd2, 0x26, 0x18, 0x98, 0xfa, 0x05, 0x10,
+    0x15, 0x72, 0x8e, 0x5a, 0x8a, 0xaa, 0xc4, 0x2d, 0xad, 0x33, 0x17, 0x0d, 0x04, 0x50, 0x7a, 0x33,
+    0xa8, 0x55, 0x21, 0xab, 0xdf, 0x1c, 0xba, 0x64, 0xec, 0xfb, 0x85, 0x04, 0x58, 0xdb, 0xef, 0x0a,
+    0x8a, 0xea, 0x71, 0x57, 0x5d, 0x06, 0x0c, 0x7d, 0xb3, 0x97, 0x0f, 0x85, 0xa6, 0xe1, 0xe4, 0xc7,
+    0xab, 0xf5, 0xae, 0x8c, 0xdb, 0x09, 0x33, 0xd7, 0x1e, 0x8c, 0x94, 0xe0, 0x4a, 0x25, 0x61, 0x9d,
+    0xce, 0xe3, 0xd2, 0x26, 0x1a, 0xd2, 0xee, 0x6b, 0xf1, 0x2f, 0xfa, 0x06, 0xd9, 0x8a, 0x08, 0x64,
+    0xd8, 0x76, 0x02, 0x73, 0x3e, 0xc8, 0x6a, 0x64, 0x52, 0x1f, 0x2b, 0x18, 0x17, 0x7b, 0x20, 0x0c,
+    0xbb, 0xe1, 0x17, 0x57, 0x7a, 0x61, 0x5d, 0x6c, 0x77, 0x09, 0x88, 0xc0, 0xba, 0xd9, 0x46, 0xe2,
+    0x08, 0xe2, 0x4f, 0xa0, 0x74, 0xe5, 0xab, 0x31, 0x43, 0xdb, 0x5b, 0xfc, 0xe0, 0xfd, 0x10, 0x8e,
+    0x4b, 0x82, 0xd1, 0x20, 0xa9, 0x3a, 0xd2, 0xca, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
+
+const RFC5054_4096_N: [u8; 512] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xc9, 0x0f, 0xda, 0xa2, 0x21, 0x68, 0xc2, 0x34,
+    0xc4, 0xc6, 0x62, 0x8b, 0x80, 0xdc, 0x1c, 0xd1, 0x29, 0x02, 0x4e, 0x08, 0x8a, 0x67, 0xcc, 0x74,
+    0x02, 0x0b, 0xbe, 0xa6, 0x3b, 0x13, 0x9b, 0x22, 0x51, 0x4a, 0x08, 0x79, 0x8e, 0x34, 0x04, 0xdd,
+    0xef, 0x95, 0x19, 0xb3, 0xcd, 0x3a, 0x43, 0x1b, 0x30, 0x2b, 0x0a, 0x6d, 0xf2, 0x5f, 0x14, 0x37,
+    0x4f, 0xe1, 0x35, 0x6d, 0x6d, 0x51, 0xc2, 0x45, 0xe4, 0x85, 0xb5, 0x76, 0x62, 0x5e, 0x7e, 0xc6,
+    0xf4, 0x4c, 0x42, 0xe9, 0xa6, 0x37, 0xed, 0x6b, 0x0b, 0xff, 0x5c, 0xb6, 0xf4, 0x06, 0xb7, 0xed,
+    0xee, 0x38, 0x6b, 0xfb, 0x5a, 0x89, 0x9f, 0xa5, 0xae, 0x9f, 0x24, 0x11, 0x7c, 0x4b, 0x1f, 0xe6,
+    0x49, 0x28, 0x66, 0x51, 0xec, 0xe4, 0x5b, 0x3d, 0xc2, 0x00, 0x7c, 0xb8, 0xa1, 0x63, 0xbf, 0x05,
+    0x98, 0xda, 0x48, 0x36, 0x1c, 0x55, 0xd3, 0x9a, 0x69, 0x16, 0x3f, 0xa8, 0xfd, 0x24, 0xcf, 0x5f,
+    0x83, 0x65, 0x5d, 0x23, 0xdc, 0xa3, 0xad, 0x96, 0x1c, 0x62, 0xf3, 0x56, 0x20, 0x85, 0x52, 0xbb,
+    0x9e, 0xd5, 0x29, 0x07, 0x70, 0x96, 0x96, 0x6d, 0x67, 0x0c, 0x35, 0x4e, 0x4a, 0xbc, 0x98, 0x04,
+    0xf1, 0x74, 0x6c, 0x08, 0xca, 0x18, 0x21, 0x7c, 0x32, 0x90, 0x5e, 0x46, 0x2e, 0x36, 0xce, 0x3b,
+    0xe3, 0x9e, 0x77, 0x2c, 0x18, 0x0e, 0x86, 0x03, 0x9b, 0x27, 0x83, 0xa2, 0xec, 0x07, 0xa2, 0x8f,
+    0xb5, 0xc5, 0x5d, 0xf0, 0x6f, 0x4c, 0x52, 0xc9, 0xde, 0x2b, 0xcb, 0xf6, 0x95, 0x58, 0x17, 0x18,
+    0x39, 0x95, 0x49, 0x7c, 0xea, 0x95, 0x6a, 0xe5, 0x15, 0xd2, 0x26, 0x18, 0x98, 0xfa, 0x05, 0x10,
+    0x15, 0x72, 0x8e, 0x5a, 0x8a, 0xaa, 0xc4, 0x2d, 0xad, 0x33, 0x17, 0x0d, 0x04, 0x50, 0x7a, 0x33,
+    0xa8, 0x55, 0x21, 0xab, 0xdf, 0x1c, 0xba, 0x64, 0xec, 0xfb, 0x85, 0x04, 0x58, 0xdb, 0xef, 0x0a,
+    0x8a, 0xea, 0x71, 0x57, 0x5d, 0x06, 0x0c, 0x7d, 0xb3, 0x97, 0x0f, 0x85, 0xa6, 0xe1, 0xe4, 0xc7,
+    0xab, 0xf5, 0xae, 0x8c, 0xdb, 0x09, 0x33, 0xd7, 0x1e, 0x8c, 0x94, 0xe0, 0x4a, 0x25, 0x61, 0x9d,
+    0xce, 0xe3, 0xd2, 0x26, 0x1a, 0xd2, 0xee, 0x6b, 0xf1, 0x2f, 0xfa, 0x06, 0xd9, 0x8a, 0x08, 0x64,
+    0xd8, 0x76, 0x02, 0x73, 0x3e, 0xc8, 0x6a, 0x64, 0x52, 0x1f, 0x2b, 0x18, 0x17, 0x7b, 0x20, 0x0c,
+    0xbb, 0xe1, 0x17, 0x57, 0x7a, 0x61, 0x5d, 0x6c, 0x77, 0x09, 0x88, 0xc0, 0xba, 0xd9, 0x46, 0xe2,
+    0x08, 0xe2, 0x4f, 0xa0, 0x74, 0xe5, 0xab, 0x31, 0x43, 0xdb, 0x5b, 0xfc, 0xe0, 0xfd, 0x10, 0x8e,
+    0x4b, 0x82, 0xd1, 0x20, 0xa9, 0x21, 0x08, 0x01, 0x1a, 0x72, 0x3c, 0x12, 0xa7, 0x87, 0xe6, 0xd7,
+    0x88, 0x71, 0x9a, 0x10, 0xbd, 0xba, 0x5b, 0x26, 0x99, 0xc3, 0x27, 0x18, 0x6a, 0xf4, 0xe2, 0x3c,
+    0x1a, 0x94, 0x68, 0x34, 0xb6, 0x15, 0x0b, 0xda, 0x25, 0x83, 0xe9, 0xca, 0x2a, 0xd4, 0x4c, 0xe8,
+    0xdb, 0xbb, 0xc2, 0xdb, 0x04, 0xde, 0x8e, 0xf9, 0x2e, 0x8e, 0xfc, 0x14, 0x1f, 0xbe, 0xca, 0xa6,
+    0x28, 0x7c, 0x59, 0x47, 0x4e, 0x6b, 0xc0, 0x5d, 0x99, 0xb2, 0x96, 0x4f, 0xa0, 0x90, 0xc3, 0xa2,
+    0x23, 0x3b, 0xa1, 0x86, 0x51, 0x5b, 0xe7, 0xed, 0x1f, 0x61, 0x29, 0x70, 0xce, 0xe2, 0xd7, 0xaf,
+    0xb8, 0x1b, 0xdd, 0x76, 0x21, 0x70, 0x48, 0x1c, 0xd0, 0x06, 0x91, 0x27, 0xd5, 0xb0, 0x5a, 0xa9,
+    0x93, 0xb4, 0xea, 0x98, 0x8d, 0x8f, 0xdd, 0xc1, 0x86, 0xff, 0xb7, 0xdc, 0x90, 0xa6, 0xc0, 0x8f,
+    0x4d, 0xf4, 0x35, 0xc9, 0x34, 0x06, 0x31, 0x99, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
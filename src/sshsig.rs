@@ -0,0 +1,446 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! SSH signatures (`SSHSIG`).
+//!
+//! This is the detached signature format produced by `ssh-keygen -Y sign`
+//! and used for signing Git commits and tags. See [PROTOCOL.sshsig].
+//!
+//! The signature is over a preimage that includes a namespace, such as
+//! `"git"` or `"file"`, so a signature made for one purpose can't be used
+//! for another. Ed25519, ECDSA P-256, and ECDSA P-384 keys are supported.
+//!
+//! Public keys are in the SSH wire format, which is the base64-decoded
+//! second field of an `authorized_keys` or `allowed_signers` entry;
+//! `public_key_from_openssh` decodes such an entry.
+//!
+//! [PROTOCOL.sshsig]:
+//!     https://github.com/openssh/openssh-portable/blob/master/PROTOCOL.sshsig
+
+use crate::{base64, digest, error, rand, signature};
+use std::{string::String, vec::Vec};
+use untrusted;
+
+/// A key for signing.
+#[derive(Clone, Copy, Debug)]
+pub enum SigningKey<'a> {
+    /// An Ed25519 key pair.
+    #[cfg(feature = "sig-ed25519")]
+    Ed25519(&'a signature::Ed25519KeyPair),
+
+    /// An ECDSA key pair. It must have been constructed with
+    /// `signature::ECDSA_P256_SHA256_FIXED_SIGNING` or
+    /// `signature::ECDSA_P384_SHA384_FIXED_SIGNING`.
+    Ecdsa(&'a signature::EcdsaKeyPair),
+
+    /// Any other private key, e.g. one held in an HSM. It must support
+    /// `SignatureScheme::ED25519`, `SignatureScheme::ECDSA_P256_SHA256_FIXED`,
+    /// or `SignatureScheme::ECDSA_P384_SHA384_FIXED`.
+    Signer(&'a dyn signature::Signer),
+}
+
+impl<'a> SigningKey<'a> {
+    /// The public key, in the SSH wire format.
+    pub fn public_key(&self) -> Result<Vec<u8>, error::Unspecified> {
+        let signer = self.signer();
+        let mut out = Vec::new();
+        match key_kind(signer)? {
+            KeyKind::Ed25519 => {
+                write_string(&mut out, KEY_TYPE_ED25519);
+            }
+            KeyKind::Ecdsa(curve) => {
+                write_string(&mut out, curve.key_type);
+                write_string(&mut out, curve.name);
+            }
+        }
+        write_string(&mut out, signer.public_key_bytes());
+        Ok(out)
+    }
+
+    fn signer(&self) -> &'a dyn signature::Signer {
+        match *self {
+            #[cfg(feature = "sig-ed25519")]
+            SigningKey::Ed25519(key_pair) => key_pair,
+            SigningKey::Ecdsa(key_pair) => key_pair,
+            SigningKey::Signer(signer) => signer,
+        }
+    }
+}
+
+enum KeyKind {
+    Ed25519,
+    Ecdsa(&'static EcdsaCurve),
+}
+
+fn key_kind(signer: &dyn signature::Signer) -> Result<KeyKind, error::Unspecified> {
+    if signer.supports(signature::SignatureScheme::ED25519) {
+        return Ok(KeyKind::Ed25519);
+    }
+    ECDSA_CURVES
+        .iter()
+        .find(|curve| signer.supports(curve.scheme))
+        .map(KeyKind::Ecdsa)
+        .ok_or(error::Unspecified)
+}
+
+/// Signs `message` for use in `namespace`, returning the armored signature.
+///
+/// The message is hashed with SHA-512, as `ssh-keygen` does.
+pub fn sign(
+    key: SigningKey,
+    namespace: &str,
+    message: &[u8],
+    rng: &dyn rand::SecureRandom,
+) -> Result<String, error::Unspecified> {
+    if namespace.is_empty() {
+        return Err(error::Unspecified);
+    }
+    let public_key = key.public_key()?;
+    let signed_data = signed_data(namespace, &SHA512, message);
+
+    let signer = key.signer();
+    let mut signature = Vec::new();
+    match key_kind(signer)? {
+        KeyKind::Ed25519 => {
+            let sig = signer.sign(signature::SignatureScheme::ED25519, rng, &signed_data)?;
+            write_string(&mut signature, KEY_TYPE_ED25519);
+            write_string(&mut signature, &sig);
+        }
+        KeyKind::Ecdsa(curve) => {
+            let fixed = signer.sign(curve.scheme, rng, &signed_data)?;
+            if fixed.len() != 2 * curve.scalar_len {
+                return Err(error::Unspecified);
+            }
+            let (r, s) = fixed.split_at(curve.scalar_len);
+            let mut r_and_s = Vec::new();
+            write_mpint(&mut r_and_s, r);
+            write_mpint(&mut r_and_s, s);
+            write_string(&mut signature, curve.key_type);
+            write_string(&mut signature, &r_and_s);
+        }
+    }
+
+    let mut blob = Vec::new();
+    blob.extend_from_slice(MAGIC);
+    blob.extend_from_slice(&SIG_VERSION.to_be_bytes());
+    write_string(&mut blob, &public_key);
+    write_string(&mut blob, namespace.as_bytes());
+    write_string(&mut blob, b"");
+    write_string(&mut blob, SHA512.name);
+    write_string(&mut blob, &signature);
+
+    let encoded = base64::encode(&blob);
+    let mut armored = String::from(ARMOR_BEGIN);
+    armored.push('\n');
+    for line in encoded.as_bytes().chunks(ARMOR_LINE_LEN) {
+        // `encoded` is ASCII so every chunk is valid UTF-8.
+        armored.push_str(core::str::from_utf8(line).map_err(|_| error::Unspecified)?);
+        armored.push('\n');
+    }
+    armored.push_str(ARMOR_END);
+    armored.push('\n');
+    Ok(armored)
+}
+
+/// A parsed SSH signature.
+#[derive(Clone, Debug)]
+pub struct Signature {
+    public_key: Vec<u8>,
+    namespace: String,
+    hash_algorithm: &'static HashAlgorithm,
+    signature: Vec<u8>,
+}
+
+impl Signature {
+    /// Parses an armored signature, as written by `ssh-keygen -Y sign`.
+    pub fn from_armored(armored: &str) -> Result<Self, error::Unspecified> {
+        let mut lines = armored.lines().map(|line| line.trim_end_matches('\r'));
+        if lines.next() != Some(ARMOR_BEGIN) {
+            return Err(error::Unspecified);
+        }
+        let mut encoded = String::new();
+        loop {
+            let line = lines.next().ok_or(error::Unspecified)?;
+            if line == ARMOR_END {
+                break;
+            }
+            encoded.push_str(line);
+        }
+        if lines.any(|line| !line.is_empty()) {
+            return Err(error::Unspecified);
+        }
+        Self::from_bytes(&base64::decode(&encoded)?)
+    }
+
+    /// Parses an unarmored signature.
+    pub fn from_bytes(input: &[u8]) -> Result<Self, error::Unspecified> {
+        untrusted::Input::from(input).read_all(error::Unspecified, |input| {
+            let magic = input.read_bytes(MAGIC.len())?;
+            if magic.as_slice_less_safe() != MAGIC {
+                return Err(error::Unspecified);
+            }
+            if read_u32(input)? != SIG_VERSION {
+                return Err(error::Unspecified);
+            }
+            let public_key = read_string(input)?;
+            let namespace = read_string(input)?;
+            let _reserved = read_string(input)?;
+            let hash_algorithm = read_string(input)?;
+            let hash_algorithm = HASH_ALGORITHMS
+                .iter()
+                .find(|alg| hash_algorithm.as_slice_less_safe() == alg.name)
+                .ok_or(error::Unspecified)?;
+            let signature = read_string(input)?;
+
+            let namespace = core::str::from_utf8(namespace.as_slice_less_safe())
+                .map_err(|_| error::Unspecified)?;
+            Ok(Self {
+                public_key: public_key.as_slice_less_safe().to_vec(),
+                namespace: String::from(namespace),
+                hash_algorithm,
+                signature: signature.as_slice_less_safe().to_vec(),
+            })
+        })
+    }
+
+    /// The public key of the signer, in the SSH wire format.
+    ///
+    /// This is useful for finding the signer's entry in an
+    /// `allowed_signers` file. It must not be trusted on its own.
+    #[inline]
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// The namespace the signature was made for.
+    #[inline]
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Verifies that this is a signature over `message` for use in
+    /// `namespace` by the holder of `public_key`, which is in the SSH wire
+    /// format.
+    pub fn verify(
+        &self,
+        public_key: &[u8],
+        namespace: &str,
+        message: &[u8],
+    ) -> Result<(), error::Unspecified> {
+        if public_key != &self.public_key[..] || namespace != self.namespace {
+            return Err(error::Unspecified);
+        }
+        if namespace.is_empty() {
+            return Err(error::Unspecified);
+        }
+        let signed_data = signed_data(namespace, self.hash_algorithm, message);
+
+        // `None` for Ed25519.
+        let (curve, key) =
+            untrusted::Input::from(public_key).read_all(error::Unspecified, |input| {
+                let key_type = read_string(input)?.as_slice_less_safe();
+                let curve = if key_type == KEY_TYPE_ED25519 {
+                    None
+                } else {
+                    let curve = ECDSA_CURVES
+                        .iter()
+                        .find(|curve| key_type == curve.key_type)
+                        .ok_or(error::Unspecified)?;
+                    if read_string(input)?.as_slice_less_safe() != curve.name {
+                        return Err(error::Unspecified);
+                    }
+                    Some(curve)
+                };
+                let key = read_string(input)?.as_slice_less_safe();
+                Ok((curve, key))
+            })?;
+        let key_type = curve.map_or(KEY_TYPE_ED25519, |curve| curve.key_type);
+
+        untrusted::Input::from(&self.signature).read_all(error::Unspecified, |input| {
+            if read_string(input)?.as_slice_less_safe() != key_type {
+                return Err(error::Unspecified);
+            }
+            let signature = read_string(input)?;
+
+            let curve = match curve {
+                #[cfg(feature = "sig-ed25519")]
+                None => {
+                    return signature::UnparsedPublicKey::new(&signature::ED25519, key)
+                        .verify(&signed_data, signature.as_slice_less_safe());
+                }
+                #[cfg(not(feature = "sig-ed25519"))]
+                None => {
+                    return Err(error::Unspecified);
+                }
+                Some(curve) => curve,
+            };
+
+            // Convert the pair of `mpint`s to the fixed-length encoding.
+            let mut fixed = [0u8; MAX_ECDSA_FIXED_LEN];
+            let fixed = &mut fixed[..(2 * curve.scalar_len)];
+            {
+                let (r, s) = fixed.split_at_mut(curve.scalar_len);
+                signature.read_all(error::Unspecified, |input| {
+                    read_mpint_fixed(input, r)?;
+                    read_mpint_fixed(input, s)
+                })?;
+            }
+            signature::UnparsedPublicKey::new(curve.verification_alg, key)
+                .verify(&signed_data, fixed)
+        })
+    }
+}
+
+/// Decodes a public key in the OpenSSH `authorized_keys` format, e.g.
+/// `ssh-ed25519 AAAAC3Nza... user@example.com`, into the SSH wire format.
+///
+/// Options before the key type aren't supported.
+pub fn public_key_from_openssh(line: &str) -> Result<Vec<u8>, error::Unspecified> {
+    let mut fields = line.split_whitespace();
+    let key_type = fields.next().ok_or(error::Unspecified)?;
+    let key = base64::decode(fields.next().ok_or(error::Unspecified)?)?;
+
+    // The key type is repeated inside the key.
+    let embedded_key_type = untrusted::Input::from(&key).read_all(error::Unspecified, |input| {
+        let key_type = read_string(input)?;
+        input.skip_to_end();
+        Ok(key_type)
+    })?;
+    if embedded_key_type.as_slice_less_safe() != key_type.as_bytes() {
+        return Err(error::Unspecified);
+    }
+    Ok(key)
+}
+
+// The data that is actually signed.
+fn signed_data(namespace: &str, hash_algorithm: &HashAlgorithm, message: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    write_string(&mut out, namespace.as_bytes());
+    write_string(&mut out, b"");
+    write_string(&mut out, hash_algorithm.name);
+    write_string(
+        &mut out,
+        digest::digest(hash_algorithm.digest_alg, message).as_ref(),
+    );
+    out
+}
+
+#[derive(Debug)]
+struct HashAlgorithm {
+    name: &'static [u8],
+    digest_alg: &'static digest::Algorithm,
+}
+
+static SHA256: HashAlgorithm = HashAlgorithm {
+    name: b"sha256",
+    digest_alg: &digest::SHA256,
+};
+
+static SHA512: HashAlgorithm = HashAlgorithm {
+    name: b"sha512",
+    digest_alg: &digest::SHA512,
+};
+
+static HASH_ALGORITHMS: &[&HashAlgorithm] = &[&SHA256, &SHA512];
+
+pub(crate) struct EcdsaCurve {
+    pub(crate) key_type: &'static [u8],
+    pub(crate) name: &'static [u8],
+    pub(crate) scalar_len: usize,
+    pub(crate) scheme: signature::SignatureScheme,
+    pub(crate) verification_alg: &'static signature::EcdsaVerificationAlgorithm,
+}
+
+pub(crate) static ECDSA_CURVES: &[EcdsaCurve] = &[
+    EcdsaCurve {
+        key_type: b"ecdsa-sha2-nistp256",
+        name: b"nistp256",
+        scalar_len: 32,
+        scheme: signature::SignatureScheme::ECDSA_P256_SHA256_FIXED,
+        verification_alg: &signature::ECDSA_P256_SHA256_FIXED,
+    },
+    EcdsaCurve {
+        key_type: b"ecdsa-sha2-nistp384",
+        name: b"nistp384",
+        scalar_len: 48,
+        scheme: signature::SignatureScheme::ECDSA_P384_SHA384_FIXED,
+        verification_alg: &signature::ECDSA_P384_SHA384_FIXED,
+    },
+];
+
+pub(crate) const MAX_ECDSA_FIXED_LEN: usize = 2 * 48;
+
+const MAGIC: &[u8] = b"SSHSIG";
+const SIG_VERSION: u32 = 1;
+pub(crate) const KEY_TYPE_ED25519: &[u8] = b"ssh-ed25519";
+
+const ARMOR_BEGIN: &str = "-----BEGIN SSH SIGNATURE-----";
+const ARMOR_END: &str = "-----END SSH SIGNATURE-----";
+const ARMOR_LINE_LEN: usize = 70;
+
+pub(crate) fn read_u32(input: &mut untrusted::Reader) -> Result<u32, error::Unspecified> {
+    let mut value = 0u32;
+    for _ in 0..4 {
+        value = (value << 8) | u32::from(input.read_byte()?);
+    }
+    Ok(value)
+}
+
+pub(crate) fn read_string<'a>(
+    input: &mut untrusted::Reader<'a>,
+) -> Result<untrusted::Input<'a>, error::Unspecified> {
+    let len = read_u32(input)?;
+    Ok(input.read_bytes(len as usize)?)
+}
+
+// Reads a non-negative `mpint` into `out`, left-padding it with zeros.
+pub(crate) fn read_mpint_fixed(
+    input: &mut untrusted::Reader,
+    out: &mut [u8],
+) -> Result<(), error::Unspecified> {
+    let value = read_string(input)?.as_slice_less_safe();
+    // Negative values and unnecessary leading zeros are rejected. Zero,
+    // which is encoded as the empty string, is never a valid ECDSA scalar.
+    let value = match value.first() {
+        Some(0) if value.len() > 1 && value[1] & 0x80 != 0 => &value[1..],
+        Some(first) if *first != 0 && *first & 0x80 == 0 => value,
+        _ => {
+            return Err(error::Unspecified);
+        }
+    };
+    if value.len() > out.len() {
+        return Err(error::Unspecified);
+    }
+    let padding_len = out.len() - value.len();
+    out[padding_len..].copy_from_slice(value);
+    Ok(())
+}
+
+pub(crate) fn write_string(out: &mut Vec<u8>, value: &[u8]) {
+    out.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    out.extend_from_slice(value);
+}
+
+// Writes the non-negative big-endian integer `value` as an `mpint`.
+pub(crate) fn write_mpint(out: &mut Vec<u8>, value: &[u8]) {
+    let first_nonzero = value.iter().position(|b| *b != 0).unwrap_or(value.len());
+    let value = &value[first_nonzero..];
+    let mut encoded = Vec::with_capacity(value.len() + 1);
+    if value.first().map_or(false, |b| b & 0x80 != 0) {
+        encoded.push(0);
+    }
+    encoded.extend_from_slice(value);
+    write_string(out, &encoded);
+}
@@ -0,0 +1,67 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Low-level stream ciphers: ChaCha20 and AES-CTR, without authentication.
+//!
+//! **This is a hazmat API.** Encryption with a stream cipher alone doesn't
+//! protect the integrity of the data: an attacker can flip any bit of the
+//! plaintext by flipping the same bit of the ciphertext. Use `ring::aead`
+//! unless a protocol, such as WireGuard's cookie encryption or SRTP,
+//! specifies the raw cipher and authenticates the data some other way.
+//!
+//! `xor_keystream()` XORs the keystream for a (key, nonce) pair, starting at
+//! the block numbered `counter`, into its input, so the same operation both
+//! encrypts and decrypts. Reusing a (key, nonce) pair with overlapping
+//! counter ranges reveals the XOR of the plaintexts.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "aead-chacha")]
+//! # fn main() -> Result<(), ring::error::Unspecified> {
+//! use ring::{aead::Nonce, stream::chacha20};
+//!
+//! let key = chacha20::Key::new(&[0x42; chacha20::KEY_LEN]);
+//!
+//! let mut in_out = *b"hello, world";
+//! key.xor_keystream(Nonce::assume_unique_for_key([0; 12]), 1, &mut in_out)?;
+//! assert_ne!(&in_out, b"hello, world");
+//!
+//! key.xor_keystream(Nonce::assume_unique_for_key([0; 12]), 1, &mut in_out)?;
+//! assert_eq!(&in_out, b"hello, world");
+//!
+//! # Ok(())
+//! # }
+//! #
+//! # #[cfg(not(feature = "aead-chacha"))]
+//! # fn main() {}
+//! ```
+
+use crate::{error, polyfill};
+
+pub mod aes_ctr;
+
+#[cfg(feature = "aead-chacha")]
+pub mod chacha20;
+
+// Fails if processing `len` bytes, in blocks of `block_len` bytes, starting
+// with the block numbered `counter` would wrap the 32-bit block counter.
+fn check_counter(counter: u32, block_len: usize, len: usize) -> Result<(), error::Unspecified> {
+    let block_len = polyfill::u64_from_usize(block_len);
+    let blocks = (polyfill::u64_from_usize(len) + block_len - 1) / block_len;
+    if blocks > (1u64 << 32) - u64::from(counter) {
+        return Err(error::Unspecified);
+    }
+    Ok(())
+}
@@ -0,0 +1,121 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! AES in counter (CTR) mode, as specified in [NIST SP 800-38A].
+//!
+//! The initial counter block is the 12-byte nonce followed by the 32-bit
+//! block counter, in big-endian order, and only the block counter is
+//! incremented. For a protocol that specifies a 16-byte initial counter
+//! block, pass its first 12 bytes as the nonce and its last 4 bytes, decoded
+//! as a big-endian integer, as the counter.
+//!
+//! [NIST SP 800-38A]:
+//!     https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38a.pdf
+
+use crate::{
+    aead::{aes, block::BLOCK_LEN, Nonce},
+    cpu, error,
+};
+
+/// An AES-CTR key.
+pub struct Key {
+    aes_key: aes::Key,
+    algorithm: &'static Algorithm,
+}
+
+derive_debug_via_field!(Key, algorithm);
+
+impl Key {
+    /// Constructs a key for `algorithm` with the given value.
+    ///
+    /// `key_bytes` must be exactly `algorithm.key_len()` bytes long.
+    pub fn new(
+        algorithm: &'static Algorithm,
+        key_bytes: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        Ok(Self {
+            aes_key: aes::Key::new(key_bytes, algorithm.variant, cpu::features())?,
+            algorithm,
+        })
+    }
+
+    /// The key's algorithm.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+
+    /// XORs the AES-CTR keystream for `nonce`, starting at the 16-byte block
+    /// numbered `counter`, into `in_out`.
+    ///
+    /// Fails, without modifying `in_out`, if the 32-bit block counter would
+    /// wrap, i.e. if `in_out` is longer than `(2^32 - counter) * 16` bytes.
+    pub fn xor_keystream(
+        &self,
+        nonce: Nonce,
+        counter: u32,
+        in_out: &mut [u8],
+    ) -> Result<(), error::Unspecified> {
+        super::check_counter(counter, BLOCK_LEN, in_out.len())?;
+        let mut ctr = aes::Counter::zero(nonce);
+        ctr.increment_by_less_safe(counter);
+        self.aes_key.ctr32_encrypt_in_place(ctr, in_out);
+        Ok(())
+    }
+}
+
+/// An AES-CTR algorithm.
+pub struct Algorithm {
+    variant: aes::Variant,
+    key_len: usize,
+    id: AlgorithmID,
+}
+
+impl Algorithm {
+    /// The length of the key.
+    #[inline(always)]
+    pub fn key_len(&self) -> usize {
+        self.key_len
+    }
+}
+
+derive_debug_via_id!(Algorithm);
+
+#[derive(Debug, Eq, PartialEq)]
+enum AlgorithmID {
+    AES_128_CTR,
+    AES_256_CTR,
+}
+
+impl PartialEq for Algorithm {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Algorithm {}
+
+/// AES-CTR with a 128-bit key.
+pub static AES_128: Algorithm = Algorithm {
+    variant: aes::Variant::AES_128,
+    key_len: 16,
+    id: AlgorithmID::AES_128_CTR,
+};
+
+/// AES-CTR with a 256-bit key.
+pub static AES_256: Algorithm = Algorithm {
+    variant: aes::Variant::AES_256,
+    key_len: 32,
+    id: AlgorithmID::AES_256_CTR,
+};
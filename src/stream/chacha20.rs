@@ -0,0 +1,61 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! ChaCha20, as specified in [RFC 8439 Section 2.4].
+//!
+//! [RFC 8439 Section 2.4]: https://tools.ietf.org/html/rfc8439#section-2.4
+
+use crate::{
+    aead::{chacha, Nonce},
+    error,
+};
+
+/// The length of a ChaCha20 key.
+pub const KEY_LEN: usize = chacha::KEY_LEN;
+
+const BLOCK_LEN: usize = 64;
+
+/// A ChaCha20 key.
+pub struct Key(chacha::Key);
+
+impl Key {
+    /// Constructs a key with the given value.
+    pub fn new(key_bytes: &[u8; KEY_LEN]) -> Self {
+        Self(chacha::Key::from(key_bytes))
+    }
+
+    /// XORs the ChaCha20 keystream for `nonce`, starting at the 64-byte block
+    /// numbered `counter`, into `in_out`.
+    ///
+    /// Fails, without modifying `in_out`, if the 32-bit block counter would
+    /// wrap, i.e. if `in_out` is longer than `(2^32 - counter) * 64` bytes.
+    pub fn xor_keystream(
+        &self,
+        nonce: Nonce,
+        counter: u32,
+        in_out: &mut [u8],
+    ) -> Result<(), error::Unspecified> {
+        super::check_counter(counter, BLOCK_LEN, in_out.len())?;
+        let mut ctr = chacha::Counter::zero(nonce);
+        ctr.increment_by_less_safe(counter);
+        self.0.encrypt_in_place(ctr, in_out);
+        Ok(())
+    }
+}
+
+impl core::fmt::Debug for Key {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Key").finish()
+    }
+}
@@ -162,8 +162,9 @@ impl TestCase {
         }
     }
 
-    /// Maps the strings "SHA1", "SHA256", "SHA384", and "SHA512" to digest
-    /// algorithms, maps "SHA224" to `None`, and panics on other (erroneous)
+    /// Maps the strings "SHA1", "SHA256", "SHA384", "SHA512", "SHA512_256",
+    /// "SHA3_256", "SHA3_384", "SHA3_512", "BLAKE2B_512", "BLAKE2S_256", and
+    /// "ASCON_HASH" to digest algorithms, maps "SHA224" to `None`, and panics on other (erroneous)
     /// inputs. "SHA224" is mapped to None because *ring* intentionally does
     /// not support SHA224, but we need to consume test vectors from NIST that
     /// have SHA224 vectors in them.
@@ -176,6 +177,12 @@ impl TestCase {
             "SHA384" => Some(&digest::SHA384),
             "SHA512" => Some(&digest::SHA512),
             "SHA512_256" => Some(&digest::SHA512_256),
+            "SHA3_256" => Some(&digest::SHA3_256),
+            "SHA3_384" => Some(&digest::SHA3_384),
+            "SHA3_512" => Some(&digest::SHA3_512),
+            "BLAKE2B_512" => Some(&digest::BLAKE2B_512),
+            "BLAKE2S_256" => Some(&digest::BLAKE2S_256),
+            "ASCON_HASH" => Some(&digest::ASCON_HASH),
             _ => panic!("Unsupported digest algorithm: {}", name),
         }
     }
@@ -185,54 +192,10 @@ impl TestCase {
     /// empty (zero-length) value is represented as "".
     pub fn consume_bytes(&mut self, key: &str) -> Vec<u8> {
         let s = self.consume_string(key);
-        if s.starts_with('\"') {
-            // The value is a quoted UTF-8 string.
-
-            let mut bytes = Vec::with_capacity(s.as_bytes().len() - 2);
-            let mut s = s.as_bytes().iter().skip(1);
-            loop {
-                let b = match s.next() {
-                    Some(b'\\') => {
-                        match s.next() {
-                            // We don't allow all octal escape sequences, only "\0" for null.
-                            Some(b'0') => 0u8,
-                            Some(b't') => b'\t',
-                            Some(b'n') => b'\n',
-                            // "\xHH"
-                            Some(b'x') => {
-                                let hi = s.next().expect("Invalid hex escape sequence in string.");
-                                let lo = s.next().expect("Invalid hex escape sequence in string.");
-                                if let (Ok(hi), Ok(lo)) = (from_hex_digit(*hi), from_hex_digit(*lo))
-                                {
-                                    (hi << 4) | lo
-                                } else {
-                                    panic!("Invalid hex escape sequence in string.");
-                                }
-                            }
-                            _ => {
-                                panic!("Invalid hex escape sequence in string.");
-                            }
-                        }
-                    }
-                    Some(b'"') => {
-                        if s.next().is_some() {
-                            panic!("characters after the closing quote of a quoted string.");
-                        }
-                        break;
-                    }
-                    Some(b) => *b,
-                    None => panic!("Missing terminating '\"' in string literal."),
-                };
-                bytes.push(b);
-            }
-            bytes
-        } else {
-            // The value is hex encoded.
-            match from_hex(&s) {
-                Ok(s) => s,
-                Err(err_str) => {
-                    panic!("{} in {}", err_str, s);
-                }
+        match bytes_from_value(&s) {
+            Ok(bytes) => bytes,
+            Err(err_str) => {
+                panic!("{} in {}", err_str, s);
             }
         }
     }
@@ -309,7 +272,12 @@ where
     let mut failed = false;
 
     #[allow(box_pointers)]
-    while let Some(mut test_case) = parse_test_case(&mut current_section, lines) {
+    loop {
+        let mut test_case = match parse_test_case(&mut current_section, lines) {
+            Ok(Some(test_case)) => test_case,
+            Ok(None) => break,
+            Err(msg) => panic!("{}", msg),
+        };
         let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
             f(&current_section, &mut test_case)
         }));
@@ -364,6 +332,60 @@ pub fn from_hex(hex_str: &str) -> Result<Vec<u8>, String> {
     Ok(result)
 }
 
+/// Decodes the value of an attribute that is encoded as a sequence of an even
+/// number of hex digits, or as a double-quoted UTF-8 string, as
+/// `TestCase::consume_bytes()` does.
+pub(crate) fn bytes_from_value(s: &str) -> Result<Vec<u8>, String> {
+    if s.starts_with('\"') {
+        // The value is a quoted UTF-8 string.
+
+        let mut bytes = Vec::with_capacity(s.len().saturating_sub(2));
+        let mut s = s.as_bytes().iter().skip(1);
+        loop {
+            let b = match s.next() {
+                Some(b'\\') => {
+                    match s.next() {
+                        // We don't allow all octal escape sequences, only "\0" for null.
+                        Some(b'0') => 0u8,
+                        Some(b't') => b'\t',
+                        Some(b'n') => b'\n',
+                        // "\xHH"
+                        Some(b'x') => {
+                            let hi = s.next().ok_or("Invalid hex escape sequence in string.")?;
+                            let lo = s.next().ok_or("Invalid hex escape sequence in string.")?;
+                            if let (Ok(hi), Ok(lo)) = (from_hex_digit(*hi), from_hex_digit(*lo)) {
+                                (hi << 4) | lo
+                            } else {
+                                return Err(String::from("Invalid hex escape sequence in string."));
+                            }
+                        }
+                        _ => {
+                            return Err(String::from("Invalid hex escape sequence in string."));
+                        }
+                    }
+                }
+                Some(b'"') => {
+                    if s.next().is_some() {
+                        return Err(String::from(
+                            "characters after the closing quote of a quoted string.",
+                        ));
+                    }
+                    break;
+                }
+                Some(b) => *b,
+                None => {
+                    return Err(String::from("Missing terminating '\"' in string literal."));
+                }
+            };
+            bytes.push(b);
+        }
+        Ok(bytes)
+    } else {
+        // The value is hex encoded.
+        from_hex(s)
+    }
+}
+
 fn from_hex_digit(d: u8) -> Result<u8, String> {
     if d >= b'0' && d <= b'9' {
         Ok(d - b'0')
@@ -376,10 +398,13 @@ fn from_hex_digit(d: u8) -> Result<u8, String> {
     }
 }
 
-fn parse_test_case(
+/// Parses the next test case out of `lines`, or returns `Ok(None)` at the
+/// end of the file. `current_section` is updated when a section header is
+/// read.
+pub(crate) fn parse_test_case(
     current_section: &mut String,
     lines: &mut dyn Iterator<Item = &str>,
-) -> Option<TestCase> {
+) -> Result<Option<TestCase>, &'static str> {
     let mut attributes = Vec::new();
 
     let mut is_first_line = true;
@@ -396,18 +421,18 @@ fn parse_test_case(
             // If we get to EOF when we're not in the middle of a test case,
             // then we're done.
             None if is_first_line => {
-                return None;
+                return Ok(None);
             }
 
             // End of the file on a non-empty test cases ends the test case.
             None => {
-                return Some(TestCase { attributes });
+                return Ok(Some(TestCase { attributes }));
             }
 
             // A blank line ends a test case if the test case isn't empty.
             Some(ref line) if line.is_empty() => {
                 if !is_first_line {
-                    return Some(TestCase { attributes });
+                    return Ok(Some(TestCase { attributes }));
                 }
                 // Ignore leading blank lines.
             }
@@ -416,8 +441,9 @@ fn parse_test_case(
             Some(ref line) if line.starts_with('#') => (),
 
             Some(ref line) if line.starts_with('[') => {
-                assert!(is_first_line);
-                assert!(line.ends_with(']'));
+                if !is_first_line || !line.ends_with(']') {
+                    return Err("Syntax error: Invalid section header.");
+                }
                 current_section.truncate(0);
                 current_section.push_str(line);
                 let _ = current_section.pop();
@@ -429,7 +455,7 @@ fn parse_test_case(
 
                 let parts: Vec<&str> = line.splitn(2, " = ").collect();
                 if parts.len() != 2 {
-                    panic!("Syntax error: Expected Key = Value.");
+                    return Err("Syntax error: Expected Key = Value.");
                 };
 
                 let key = parts[0].trim();
@@ -437,7 +463,9 @@ fn parse_test_case(
 
                 // Don't allow the value to be ommitted. An empty value can be
                 // represented as an empty quoted string.
-                assert_ne!(value.len(), 0);
+                if value.is_empty() {
+                    return Err("Syntax error: Empty value.");
+                }
 
                 // Checking is_none() ensures we don't accept duplicate keys.
                 attributes.push((String::from(key), String::from(value), false));
@@ -448,84 +476,8 @@ fn parse_test_case(
 
 /// Deterministic implementations of `ring::rand::SecureRandom`.
 ///
-/// These implementations are particularly useful for testing implementations
-/// of randomized algorithms & protocols using known-answer-tests where the
-/// test vectors contain the random seed to use. They are also especially
-/// useful for some types of fuzzing.
-#[allow(missing_docs)]
-pub mod rand {
-    use crate::{error, polyfill, rand, sealed};
-
-    /// An implementation of `SecureRandom` that always fills the output slice
-    /// with the given byte.
-    #[derive(Debug)]
-    pub struct FixedByteRandom {
-        pub byte: u8,
-    }
-
-    impl rand::SecureRandom for FixedByteRandom {
-        fn fill(&self, dest: &mut [u8]) -> Result<(), error::Unspecified> {
-            polyfill::slice::fill(dest, self.byte);
-            Ok(())
-        }
-    }
-
-    /// An implementation of `SecureRandom` that always fills the output slice
-    /// with the slice in `bytes`. The length of the slice given to `slice`
-    /// must match exactly.
-    #[derive(Debug)]
-    pub struct FixedSliceRandom<'a> {
-        pub bytes: &'a [u8],
-    }
-
-    impl rand::SecureRandom for FixedSliceRandom<'_> {
-        fn fill(&self, dest: &mut [u8]) -> Result<(), error::Unspecified> {
-            dest.copy_from_slice(self.bytes);
-            Ok(())
-        }
-    }
-
-    /// An implementation of `SecureRandom` where each slice in `bytes` is a
-    /// test vector for one call to `fill()`. *Not thread-safe.*
-    ///
-    /// The first slice in `bytes` is the output for the first call to
-    /// `fill()`, the second slice is the output for the second call to
-    /// `fill()`, etc. The output slice passed to `fill()` must have exactly
-    /// the length of the corresponding entry in `bytes`. `current` must be
-    /// initialized to zero. `fill()` must be called exactly once for each
-    /// entry in `bytes`.
-    #[derive(Debug)]
-    pub struct FixedSliceSequenceRandom<'a> {
-        /// The value.
-        pub bytes: &'a [&'a [u8]],
-        pub current: core::cell::UnsafeCell<usize>,
-    }
-
-    impl rand::SecureRandom for FixedSliceSequenceRandom<'_> {
-        fn fill(&self, dest: &mut [u8]) -> Result<(), error::Unspecified> {
-            let current = unsafe { *self.current.get() };
-            let bytes = self.bytes[current];
-            dest.copy_from_slice(bytes);
-            // Remember that we returned this slice and prepare to return
-            // the next one, if any.
-            unsafe { *self.current.get() += 1 };
-            Ok(())
-        }
-    }
-
-    impl Drop for FixedSliceSequenceRandom<'_> {
-        fn drop(&mut self) {
-            // Ensure that `fill()` was called exactly the right number of
-            // times.
-            assert_eq!(unsafe { *self.current.get() }, self.bytes.len());
-        }
-    }
-
-    impl sealed::Sealed for FixedByteRandom {}
-    impl sealed::Sealed for FixedSliceRandom<'_> {}
-    impl sealed::Sealed for FixedSliceSequenceRandom<'_> {}
-
-}
+/// These are the same as the ones in `ring::rand::test`.
+pub use crate::rand::test as rand;
 
 #[cfg(test)]
 mod tests {
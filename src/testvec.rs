@@ -0,0 +1,625 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Running test vectors against the algorithms of a build of *ring*.
+//!
+//! `run_aead()`, `run_signature()`, and `run_agreement()` run a file of test
+//! vectors against an algorithm and report the result of each vector,
+//! instead of panicking at the first failure like *ring*'s own tests do.
+//! This lets an application that embeds *ring* check its own build, with its
+//! own features, target, and `policy::Policy`, in its own test suite.
+//!
+//! Two formats are supported:
+//!
+//! * [Wycheproof] JSON files, e.g. `aes_gcm_test.json`,
+//!   `ecdsa_secp256r1_sha256_test.json`, `eddsa_test.json`,
+//!   `rsa_signature_2048_sha256_test.json`, `ecdh_secp256r1_test.json`, and
+//!   `x25519_test.json`. A signature test group's public key is read from
+//!   its `keyDer` or `publicKeyDer` `SubjectPublicKeyInfo`. An ECDH test's
+//!   public key is a `SubjectPublicKeyInfo` if its group's `encoding` is
+//!   `"asn"`, and is in the algorithm's own format otherwise.
+//!
+//! * The `.txt` format of *ring*'s own test vectors. AEAD vectors have the
+//!   attributes `KEY`, `NONCE`, `IN`, `AD`, `CT`, and `TAG`, and are invalid
+//!   if they have a `FAILS` attribute. Signature vectors have `PUB`, `Q`, or
+//!   `Key` for the public key; `MESSAGE` or `Msg`; and `SIG` or `Sig`; and are
+//!   invalid if their `Result` starts with `F`. Key agreement vectors have
+//!   `D`, `PeerQ`, and `Output`, and are invalid if they have an `Error`
+//!   attribute. Other attributes, such as `Curve` and `Digest`, are ignored,
+//!   so every vector of the file must be for the algorithm being tested.
+//!
+//! A malformed file is rejected as a whole with an error.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::{aead, testvec};
+//!
+//! let vectors = b"
+//! KEY = 00000000000000000000000000000000
+//! NONCE = 000000000000000000000000
+//! IN = \"\"
+//! AD = \"\"
+//! CT = \"\"
+//! TAG = 58e2fccefa7e3061367f1d57a4e7455a
+//! ";
+//! let report = testvec::run_aead(&aead::AES_128_GCM, testvec::Format::Txt, vectors)?;
+//! for failure in report.failures() {
+//!     println!("test {} ({}) failed", failure.id, failure.comment);
+//! }
+//! assert!(report.passed());
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [Wycheproof]: https://github.com/google/wycheproof
+
+use crate::{
+    aead::{self, aegis, ascon},
+    agreement, error, json, rand, signature, spki, test,
+};
+use std::{string::String, vec, vec::Vec};
+
+/// The format of a file of test vectors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// A Wycheproof JSON file.
+    Wycheproof,
+
+    /// The `.txt` format of *ring*'s own test vectors.
+    Txt,
+}
+
+/// The outcome that a test vector expects.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Expected {
+    /// The inputs must be accepted, with the expected outputs.
+    Valid,
+
+    /// The inputs must be rejected.
+    Invalid,
+
+    /// The inputs may be accepted or rejected, e.g. because they use a
+    /// weak but legal parameter.
+    Acceptable,
+}
+
+/// The result of running one test vector.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestResult {
+    /// The `tcId` of a Wycheproof vector, or the position of a `.txt` vector
+    /// in its file, starting at 1.
+    pub id: usize,
+
+    /// The `comment` of a Wycheproof vector; empty for a `.txt` vector.
+    pub comment: String,
+
+    /// The `flags` of a Wycheproof vector; empty for a `.txt` vector.
+    pub flags: Vec<String>,
+
+    /// The outcome that the vector expects.
+    pub expected: Expected,
+
+    /// Whether the algorithm behaved as expected. A vector that expects
+    /// `Expected::Acceptable` always passes.
+    pub passed: bool,
+}
+
+/// The results of running a file of test vectors.
+#[derive(Clone, Debug)]
+pub struct Report {
+    results: Vec<TestResult>,
+}
+
+impl Report {
+    /// Whether every vector passed.
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|result| result.passed)
+    }
+
+    /// The results of the vectors, in the order of the file.
+    pub fn results(&self) -> &[TestResult] {
+        &self.results
+    }
+
+    /// The results of the vectors that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &TestResult> {
+        self.results.iter().filter(|result| !result.passed)
+    }
+}
+
+/// Runs the AEAD test vectors in `input` against `algorithm`.
+///
+/// A valid vector passes if sealing its plaintext gives its ciphertext and
+/// tag and opening them gives back its plaintext. An invalid vector passes
+/// if opening its ciphertext and tag fails.
+pub fn run_aead(
+    algorithm: &'static aead::Algorithm,
+    format: Format,
+    input: &[u8],
+) -> Result<Report, error::Unspecified> {
+    run(&AEAD, format, input, |vector| {
+        let key = vector.value("key")?;
+        let iv = vector.value("iv")?;
+        let aad = vector.value("aad")?;
+        let msg = vector.value("msg")?;
+        let ct = vector.value("ct")?;
+        let tag = vector.value("tag")?;
+
+        let mut ciphertext_and_tag = Vec::with_capacity(ct.len() + tag.len());
+        ciphertext_and_tag.extend_from_slice(ct);
+        ciphertext_and_tag.extend_from_slice(tag);
+
+        let sealed = aead::SealingKey::new(algorithm, key)
+            .and_then(|key| {
+                let mut in_out = msg.to_vec();
+                in_out.resize(msg.len() + algorithm.tag_len(), 0);
+                let len = seal(&key, iv, aad, &mut in_out)?;
+                in_out.truncate(len);
+                Ok(in_out)
+            })
+            .map(|sealed| sealed == ciphertext_and_tag)
+            .unwrap_or(false);
+        let opened = aead::OpeningKey::new(algorithm, key)
+            .and_then(|key| {
+                let mut in_out = ciphertext_and_tag.clone();
+                open(&key, iv, aad, &mut in_out).map(|plaintext| plaintext.to_vec())
+            })
+            .ok();
+
+        Ok(Accepted {
+            valid: sealed && opened.as_ref().map(|plaintext| &plaintext[..]) == Some(msg),
+            invalid: opened.is_some(),
+        })
+    })
+}
+
+/// Runs the signature verification test vectors in `input` against
+/// `algorithm`.
+///
+/// A valid vector passes if its signature verifies, and an invalid vector
+/// passes if it doesn't. A Wycheproof public key whose
+/// `SubjectPublicKeyInfo` isn't of `algorithm`'s key type doesn't verify
+/// anything.
+pub fn run_signature(
+    algorithm: &'static dyn signature::VerificationAlgorithm,
+    format: Format,
+    input: &[u8],
+) -> Result<Report, error::Unspecified> {
+    run(&SIGNATURE, format, input, |vector| {
+        let public_key = vector.value("public")?;
+        let msg = vector.value("msg")?;
+        let sig = vector.value("sig")?;
+
+        let public_key = if vector.public_key_is_spki {
+            signature::UnparsedPublicKey::from_spki_der(&[algorithm], public_key)
+        } else {
+            Ok(signature::UnparsedPublicKey::new(algorithm, public_key))
+        };
+        let verified = public_key
+            .and_then(|public_key| public_key.verify(msg, sig))
+            .is_ok();
+
+        Ok(Accepted {
+            valid: verified,
+            invalid: verified,
+        })
+    })
+}
+
+/// Runs the key agreement test vectors in `input` against `algorithm`.
+///
+/// A valid vector passes if the agreement of its private key and its peer's
+/// public key gives its shared secret, and an invalid vector passes if the
+/// agreement fails.
+pub fn run_agreement(
+    algorithm: &'static agreement::Algorithm,
+    format: Format,
+    input: &[u8],
+) -> Result<Report, error::Unspecified> {
+    run(&AGREEMENT, format, input, |vector| {
+        let private_key = vector.value("private")?;
+        let public_key = vector.value("public")?;
+
+        let shared = agree(
+            algorithm,
+            private_key,
+            public_key,
+            vector.public_key_is_spki,
+        );
+        let valid = match (&shared, vector.expected) {
+            (Some(shared), Expected::Valid) => shared.as_slice() == vector.value("shared")?,
+            _ => false,
+        };
+
+        Ok(Accepted {
+            valid,
+            invalid: shared.is_some(),
+        })
+    })
+}
+
+// Whether the algorithm accepted a vector, as judged for a valid vector and
+// for an invalid one. These differ because a valid vector is only accepted
+// if the outputs are right, while an invalid one is accepted whenever the
+// inputs aren't rejected.
+struct Accepted {
+    valid: bool,
+    invalid: bool,
+}
+
+fn run<F>(
+    kind: &Kind,
+    format: Format,
+    input: &[u8],
+    mut accepted: F,
+) -> Result<Report, error::Unspecified>
+where
+    F: FnMut(&Vector) -> Result<Accepted, error::Unspecified>,
+{
+    let vectors = match format {
+        Format::Wycheproof => parse_wycheproof(kind, input)?,
+        Format::Txt => parse_txt(kind, input)?,
+    };
+    let mut results = Vec::with_capacity(vectors.len());
+    for vector in vectors {
+        let accepted = accepted(&vector)?;
+        let passed = match vector.expected {
+            Expected::Valid => accepted.valid,
+            Expected::Invalid => !accepted.invalid,
+            Expected::Acceptable => true,
+        };
+        results.push(TestResult {
+            id: vector.id,
+            comment: vector.comment,
+            flags: vector.flags,
+            expected: vector.expected,
+            passed,
+        });
+    }
+    Ok(Report { results })
+}
+
+fn seal(
+    key: &aead::SealingKey,
+    iv: &[u8],
+    aad: &[u8],
+    in_out: &mut [u8],
+) -> Result<usize, error::Unspecified> {
+    let tag_len = key.algorithm().tag_len();
+    #[cfg(feature = "aead-chacha")]
+    {
+        use crate::aead::xchacha20_poly1305;
+        if key.algorithm() == &aead::XCHACHA20_POLY1305 {
+            let nonce = xchacha20_poly1305::Nonce::try_assume_unique_for_key(iv)?;
+            return xchacha20_poly1305::seal_in_place(
+                key,
+                nonce,
+                aead::Aad::from(aad),
+                in_out,
+                tag_len,
+            );
+        }
+    }
+    if is_aegis(key.algorithm()) {
+        let nonce = aegis::Nonce::try_assume_unique_for_key(iv)?;
+        aegis::seal_in_place(key, nonce, aead::Aad::from(aad), in_out, tag_len)
+    } else if key.algorithm() == &aead::ASCON_128 {
+        let nonce = ascon::Nonce::try_assume_unique_for_key(iv)?;
+        ascon::seal_in_place(key, nonce, aead::Aad::from(aad), in_out, tag_len)
+    } else {
+        let nonce = aead::Nonce::try_assume_unique_for_key(iv)?;
+        aead::seal_in_place(key, nonce, aead::Aad::from(aad), in_out, tag_len)
+    }
+}
+
+fn open<'a>(
+    key: &aead::OpeningKey,
+    iv: &[u8],
+    aad: &[u8],
+    in_out: &'a mut [u8],
+) -> Result<&'a mut [u8], error::Unspecified> {
+    #[cfg(feature = "aead-chacha")]
+    {
+        use crate::aead::xchacha20_poly1305;
+        if key.algorithm() == &aead::XCHACHA20_POLY1305 {
+            let nonce = xchacha20_poly1305::Nonce::try_assume_unique_for_key(iv)?;
+            return xchacha20_poly1305::open_in_place(key, nonce, aead::Aad::from(aad), 0, in_out);
+        }
+    }
+    if is_aegis(key.algorithm()) {
+        let nonce = aegis::Nonce::try_assume_unique_for_key(iv)?;
+        aegis::open_in_place(key, nonce, aead::Aad::from(aad), 0, in_out)
+    } else if key.algorithm() == &aead::ASCON_128 {
+        let nonce = ascon::Nonce::try_assume_unique_for_key(iv)?;
+        ascon::open_in_place(key, nonce, aead::Aad::from(aad), 0, in_out)
+    } else {
+        let nonce = aead::Nonce::try_assume_unique_for_key(iv)?;
+        aead::open_in_place(key, nonce, aead::Aad::from(aad), 0, in_out)
+    }
+}
+
+fn is_aegis(algorithm: &aead::Algorithm) -> bool {
+    algorithm == &aead::AEGIS_128L || algorithm == &aead::AEGIS_256
+}
+
+// Returns the shared secret, or `None` if the agreement fails.
+fn agree(
+    algorithm: &'static agreement::Algorithm,
+    private_key: &[u8],
+    public_key: &[u8],
+    public_key_is_spki: bool,
+) -> Option<Vec<u8>> {
+    // Wycheproof encodes ECDH private keys as big-endian integers, which may
+    // have a leading zero byte or be shorter than the algorithm's private
+    // keys. This leaves X25519 and X448 private keys, which always have the
+    // full length, unchanged.
+    let private_key_len = algorithm.curve.elem_scalar_seed_len;
+    let leading_zeros = private_key.iter().take_while(|&&b| b == 0).count();
+    let significant = &private_key[leading_zeros..];
+    if significant.len() > private_key_len {
+        return None;
+    }
+    let mut private_key = vec![0u8; private_key_len];
+    private_key[(private_key_len - significant.len())..].copy_from_slice(significant);
+
+    let public_key = if public_key_is_spki {
+        let (algorithm_id, public_key) = spki::parse(untrusted::Input::from(public_key)).ok()?;
+        if algorithm_id.as_slice_less_safe()
+            != algorithm.pkcs8_template.alg_id_value().as_slice_less_safe()
+        {
+            return None;
+        }
+        public_key.as_slice_less_safe()
+    } else {
+        public_key
+    };
+
+    let rng = rand::test::FixedSliceRandom {
+        bytes: &private_key,
+    };
+    let private_key = agreement::EphemeralPrivateKey::generate(algorithm, &rng).ok()?;
+    agreement::agree_ephemeral(
+        private_key,
+        &agreement::UnparsedPublicKey::new(algorithm, public_key),
+        (),
+        |shared| Ok(shared.to_vec()),
+    )
+    .ok()
+}
+
+// A test vector of any kind, with its inputs and outputs named by their
+// Wycheproof names.
+struct Vector {
+    id: usize,
+    comment: String,
+    flags: Vec<String>,
+    expected: Expected,
+    values: Vec<(String, Vec<u8>)>,
+    public_key_is_spki: bool,
+}
+
+impl Vector {
+    fn value(&self, name: &str) -> Result<&[u8], error::Unspecified> {
+        self.values
+            .iter()
+            .find(|(value_name, _)| value_name == name)
+            .map(|(_, value)| value.as_slice())
+            .ok_or(error::Unspecified)
+    }
+}
+
+// How the vectors of one kind of algorithm are encoded.
+struct Kind {
+    // The names of a Wycheproof test's inputs and outputs.
+    wycheproof_values: &'static [&'static str],
+
+    // Each `.txt` attribute name, mapped to the Wycheproof name.
+    txt_values: &'static [(&'static str, &'static str)],
+
+    // The `.txt` attribute whose presence makes a vector invalid, and
+    // whether the attribute is a `Result` of "P" or "F" instead.
+    txt_invalid: &'static str,
+    txt_invalid_is_result: bool,
+}
+
+const AEAD: Kind = Kind {
+    wycheproof_values: &["key", "iv", "aad", "msg", "ct", "tag"],
+    txt_values: &[
+        ("KEY", "key"),
+        ("NONCE", "iv"),
+        ("AD", "aad"),
+        ("IN", "msg"),
+        ("CT", "ct"),
+        ("TAG", "tag"),
+    ],
+    txt_invalid: "FAILS",
+    txt_invalid_is_result: false,
+};
+
+const SIGNATURE: Kind = Kind {
+    wycheproof_values: &["msg", "sig"],
+    txt_values: &[
+        ("PUB", "public"),
+        ("Q", "public"),
+        ("Key", "public"),
+        ("MESSAGE", "msg"),
+        ("Msg", "msg"),
+        ("SIG", "sig"),
+        ("Sig", "sig"),
+    ],
+    txt_invalid: "Result",
+    txt_invalid_is_result: true,
+};
+
+const AGREEMENT: Kind = Kind {
+    wycheproof_values: &["public", "private", "shared"],
+    txt_values: &[("D", "private"), ("PeerQ", "public"), ("Output", "shared")],
+    txt_invalid: "Error",
+    txt_invalid_is_result: false,
+};
+
+fn parse_txt(kind: &Kind, input: &[u8]) -> Result<Vec<Vector>, error::Unspecified> {
+    let input = core::str::from_utf8(input).map_err(|_| error::Unspecified)?;
+    let lines = &mut input.lines();
+    let mut current_section = String::new();
+    let mut vectors = Vec::new();
+    while let Some(mut test_case) =
+        test::parse_test_case(&mut current_section, lines).map_err(|_| error::Unspecified)?
+    {
+        let mut values = Vec::new();
+        for &(txt_name, name) in kind.txt_values {
+            if let Some(value) = test_case.consume_optional_string(txt_name) {
+                let value = test::bytes_from_value(&value).map_err(|_| error::Unspecified)?;
+                values.push((String::from(name), value));
+            }
+        }
+        let expected = match test_case.consume_optional_string(kind.txt_invalid) {
+            None => Expected::Valid,
+            Some(_) if !kind.txt_invalid_is_result => Expected::Invalid,
+            Some(ref result) if result.starts_with('P') || result == "OK" => Expected::Valid,
+            Some(ref result) if result.starts_with('F') => Expected::Invalid,
+            Some(_) => {
+                return Err(error::Unspecified);
+            }
+        };
+        vectors.push(Vector {
+            id: vectors.len() + 1,
+            comment: String::new(),
+            flags: Vec::new(),
+            expected,
+            values,
+            public_key_is_spki: false,
+        });
+    }
+    Ok(vectors)
+}
+
+fn parse_wycheproof(kind: &Kind, input: &[u8]) -> Result<Vec<Vector>, error::Unspecified> {
+    let mut vectors = Vec::new();
+    json::parse_object(input, |name, parser| match name {
+        b"testGroups" => parser.array(1, |parser| {
+            parse_wycheproof_group(kind, parser, &mut vectors)
+        }),
+        _ => parser.skip_value(1),
+    })?;
+    Ok(vectors)
+}
+
+fn parse_wycheproof_group(
+    kind: &Kind,
+    parser: &mut json::Parser,
+    vectors: &mut Vec<Vector>,
+) -> Result<(), error::Unspecified> {
+    // The members of a group may be in any order, so the group's public key
+    // and encoding are only applied to its tests at the end.
+    let mut tests = Vec::new();
+    let mut public_key = None;
+    let mut encoding = None;
+    parser.object(2, |name, parser| match name {
+        b"tests" => parser.array(3, |parser| {
+            tests.push(parse_wycheproof_test(kind, parser)?);
+            Ok(())
+        }),
+        b"keyDer" | b"publicKeyDer" => {
+            public_key = Some(hex(&parser.string()?)?);
+            Ok(())
+        }
+        b"encoding" => {
+            encoding = Some(parser.string()?);
+            Ok(())
+        }
+        _ => parser.skip_value(3),
+    })?;
+
+    for mut test in tests {
+        if let Some(public_key) = &public_key {
+            test.values
+                .push((String::from("public"), public_key.clone()));
+            test.public_key_is_spki = true;
+        }
+        if encoding.as_ref().map(|encoding| &encoding[..]) == Some(&b"asn"[..]) {
+            test.public_key_is_spki = true;
+        }
+        vectors.push(test);
+    }
+    Ok(())
+}
+
+fn parse_wycheproof_test(
+    kind: &Kind,
+    parser: &mut json::Parser,
+) -> Result<Vector, error::Unspecified> {
+    let mut id = None;
+    let mut comment = String::new();
+    let mut flags = Vec::new();
+    let mut expected = None;
+    let mut values = Vec::new();
+    parser.object(4, |name, parser| {
+        match name {
+            b"tcId" => {
+                id = Some(parser.usize()?);
+            }
+            b"comment" => {
+                comment = utf8(parser.string()?)?;
+            }
+            b"flags" => {
+                parser.array(5, |parser| {
+                    flags.push(utf8(parser.string()?)?);
+                    Ok(())
+                })?;
+            }
+            b"result" => {
+                expected = Some(match &parser.string()?[..] {
+                    b"valid" => Expected::Valid,
+                    b"invalid" => Expected::Invalid,
+                    b"acceptable" => Expected::Acceptable,
+                    _ => {
+                        return Err(error::Unspecified);
+                    }
+                });
+            }
+            _ => match kind
+                .wycheproof_values
+                .iter()
+                .find(|&&value_name| value_name.as_bytes() == name)
+            {
+                Some(&value_name) if parser.is_string() => {
+                    values.push((String::from(value_name), hex(&parser.string()?)?));
+                }
+                _ => {
+                    parser.skip_value(5)?;
+                }
+            },
+        }
+        Ok(())
+    })?;
+    Ok(Vector {
+        id: id.ok_or(error::Unspecified)?,
+        comment,
+        flags,
+        expected: expected.ok_or(error::Unspecified)?,
+        values,
+        public_key_is_spki: false,
+    })
+}
+
+fn hex(value: &[u8]) -> Result<Vec<u8>, error::Unspecified> {
+    let value = core::str::from_utf8(value).map_err(|_| error::Unspecified)?;
+    test::from_hex(value).map_err(|_| error::Unspecified)
+}
+
+fn utf8(value: Vec<u8>) -> Result<String, error::Unspecified> {
+    String::from_utf8(value).map_err(|_| error::Unspecified)
+}
@@ -0,0 +1,248 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! The TLS 1.3 key schedule, as specified in [RFC 8446 Section 7].
+//!
+//! `KeySchedule` steps through the Early Secret, the Handshake Secret, and the
+//! Master Secret, and `derive_secret()` derives the traffic secrets from them.
+//! The labels are those of RFC 8446, e.g. `b"c hs traffic"`; the `"tls13 "`
+//! prefix is added here, as are the lengths of the `HkdfLabel` structure.
+//!
+//! The secrets are `hkdf::Prk`s, which don't expose their values.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::{aead, digest, tls13};
+//!
+//! # let ecdhe_shared_secret = [1; 32];
+//! let mut transcript = digest::Context::new(&digest::SHA256);
+//! // ... `transcript.update()` with the ClientHello and ServerHello ...
+//!
+//! let mut key_schedule = tls13::KeySchedule::new(&digest::SHA256);
+//! key_schedule.input_secret(&ecdhe_shared_secret)?;
+//! let transcript_hash = transcript.clone().finish();
+//! let client_handshake_traffic_secret =
+//!     key_schedule.derive_secret(b"c hs traffic", transcript_hash.as_ref())?;
+//! let (key, iv) =
+//!     tls13::derive_sealing_key(&client_handshake_traffic_secret, &aead::AES_128_GCM)?;
+//! let nonce = iv.nonce_for(0);
+//!
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [RFC 8446 Section 7]: https://tools.ietf.org/html/rfc8446#section-7
+
+use crate::{aead, digest, error, hkdf, hmac};
+
+/// The TLS 1.3 key schedule.
+///
+/// A new key schedule holds the Early Secret. The first call to
+/// `input_secret()` (with the (EC)DHE shared secret) or `input_empty()`
+/// advances it to the Handshake Secret, and the second call advances it to
+/// the Master Secret.
+#[derive(Debug)]
+pub struct KeySchedule {
+    current: hkdf::Prk,
+}
+
+impl KeySchedule {
+    /// Starts a key schedule without a PSK.
+    pub fn new(digest_algorithm: &'static digest::Algorithm) -> Self {
+        let zeros = [0u8; digest::MAX_OUTPUT_LEN];
+        Self::new_with_psk(digest_algorithm, &zeros[..digest_algorithm.output_len])
+    }
+
+    /// Starts a key schedule with the pre-shared key `psk`, e.g. a
+    /// resumption PSK derived from a previous connection's
+    /// `resumption_master_secret`.
+    pub fn new_with_psk(digest_algorithm: &'static digest::Algorithm, psk: &[u8]) -> Self {
+        let zeros = [0u8; digest::MAX_OUTPUT_LEN];
+        let salt = hkdf::Salt::new(digest_algorithm, &zeros[..digest_algorithm.output_len]);
+        Self {
+            current: salt.extract(psk),
+        }
+    }
+
+    /// Advances the key schedule to the next secret, with `secret`, e.g. the
+    /// (EC)DHE shared secret, as the input keying material.
+    pub fn input_secret(&mut self, secret: &[u8]) -> Result<(), error::Unspecified> {
+        let digest_algorithm = self.current.digest_algorithm();
+        let empty_hash = digest::digest(digest_algorithm, &[]);
+        let mut derived = [0u8; digest::MAX_OUTPUT_LEN];
+        let derived = &mut derived[..digest_algorithm.output_len];
+        self.current
+            .expand_label(b"derived", empty_hash.as_ref(), derived)?;
+        self.current = hkdf::Salt::new(digest_algorithm, derived).extract(secret);
+        Ok(())
+    }
+
+    /// Advances the key schedule to the next secret without any input keying
+    /// material, as is done to get the Master Secret, and to get the Handshake
+    /// Secret in PSK-only key exchange.
+    pub fn input_empty(&mut self) -> Result<(), error::Unspecified> {
+        let zeros = [0u8; digest::MAX_OUTPUT_LEN];
+        let len = self.current.digest_algorithm().output_len;
+        self.input_secret(&zeros[..len])
+    }
+
+    /// Derives the secret for `label` from the current secret; see
+    /// `derive_secret()`.
+    #[inline]
+    pub fn derive_secret(
+        &self,
+        label: &[u8],
+        transcript_hash: &[u8],
+    ) -> Result<hkdf::Prk, error::Unspecified> {
+        derive_secret(&self.current, label, transcript_hash)
+    }
+}
+
+/// The `Derive-Secret()` function.
+///
+/// `transcript_hash` is the hash of the handshake messages, e.g. the value of
+/// a `digest::Digest`. Fails if it isn't the length of the output of
+/// `secret`'s digest algorithm.
+pub fn derive_secret(
+    secret: &hkdf::Prk,
+    label: &[u8],
+    transcript_hash: &[u8],
+) -> Result<hkdf::Prk, error::Unspecified> {
+    if transcript_hash.len() != secret.digest_algorithm().output_len {
+        return Err(error::Unspecified);
+    }
+    expand_secret(secret, label, transcript_hash)
+}
+
+/// The `HKDF-Expand-Label()` function.
+///
+/// This is `hkdf::Prk::expand_label()`.
+#[inline]
+pub fn hkdf_expand_label(
+    secret: &hkdf::Prk,
+    label: &[u8],
+    context: &[u8],
+    out: &mut [u8],
+) -> Result<(), error::Unspecified> {
+    secret.expand_label(label, context, out)
+}
+
+/// Derives the key for the Finished message's HMAC from `base_key`, the
+/// sender's handshake traffic secret.
+///
+/// The Finished message's `verify_data` is the HMAC of the transcript hash
+/// with this key.
+pub fn finished_key(base_key: &hkdf::Prk) -> Result<hmac::Key, error::Unspecified> {
+    let digest_algorithm = base_key.digest_algorithm();
+    let mut key = [0u8; digest::MAX_OUTPUT_LEN];
+    let key = &mut key[..digest_algorithm.output_len];
+    base_key.expand_label(b"finished", &[], key)?;
+    Ok(hmac::Key::new(digest_algorithm, key))
+}
+
+/// Derives the next application traffic secret from `secret` for a
+/// KeyUpdate; see [RFC 8446 Section 7.2].
+///
+/// [RFC 8446 Section 7.2]: https://tools.ietf.org/html/rfc8446#section-7.2
+#[inline]
+pub fn next_traffic_secret(secret: &hkdf::Prk) -> Result<hkdf::Prk, error::Unspecified> {
+    expand_secret(secret, b"traffic upd", &[])
+}
+
+/// Derives the key and IV for sealing records from the traffic secret
+/// `secret`; see [RFC 8446 Section 7.3].
+///
+/// [RFC 8446 Section 7.3]: https://tools.ietf.org/html/rfc8446#section-7.3
+pub fn derive_sealing_key(
+    secret: &hkdf::Prk,
+    algorithm: &'static aead::Algorithm,
+) -> Result<(aead::SealingKey, Iv), error::Unspecified> {
+    let mut key = [0u8; MAX_KEY_LEN];
+    let iv = derive_key_and_iv(secret, algorithm, &mut key)?;
+    let key = aead::SealingKey::new(algorithm, &key[..algorithm.key_len()])?;
+    Ok((key, iv))
+}
+
+/// Derives the key and IV for opening records from the traffic secret
+/// `secret`; see [RFC 8446 Section 7.3].
+///
+/// [RFC 8446 Section 7.3]: https://tools.ietf.org/html/rfc8446#section-7.3
+pub fn derive_opening_key(
+    secret: &hkdf::Prk,
+    algorithm: &'static aead::Algorithm,
+) -> Result<(aead::OpeningKey, Iv), error::Unspecified> {
+    let mut key = [0u8; MAX_KEY_LEN];
+    let iv = derive_key_and_iv(secret, algorithm, &mut key)?;
+    let key = aead::OpeningKey::new(algorithm, &key[..algorithm.key_len()])?;
+    Ok((key, iv))
+}
+
+/// The per-record nonce of [RFC 8446 Section 5.3].
+///
+/// [RFC 8446 Section 5.3]: https://tools.ietf.org/html/rfc8446#section-5.3
+pub struct Iv([u8; aead::NONCE_LEN]);
+
+impl Iv {
+    /// The nonce for the record with the sequence number `seq`, the IV XORed
+    /// with `seq` in big-endian order.
+    pub fn nonce_for(&self, seq: u64) -> aead::Nonce {
+        let mut nonce = self.0;
+        for (n, s) in nonce[(aead::NONCE_LEN - 8)..]
+            .iter_mut()
+            .zip(seq.to_be_bytes().iter())
+        {
+            *n ^= s;
+        }
+        aead::Nonce::assume_unique_for_key(nonce)
+    }
+}
+
+impl core::fmt::Debug for Iv {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Iv").finish()
+    }
+}
+
+fn derive_key_and_iv(
+    secret: &hkdf::Prk,
+    algorithm: &'static aead::Algorithm,
+    key: &mut [u8; MAX_KEY_LEN],
+) -> Result<Iv, error::Unspecified> {
+    if algorithm.nonce_len() != aead::NONCE_LEN {
+        return Err(error::Unspecified);
+    }
+    let key = key
+        .get_mut(..algorithm.key_len())
+        .ok_or(error::Unspecified)?;
+    secret.expand_label(b"key", &[], key)?;
+    let mut iv = [0u8; aead::NONCE_LEN];
+    secret.expand_label(b"iv", &[], &mut iv)?;
+    Ok(Iv(iv))
+}
+
+// Expands a secret of the same length as `secret`.
+fn expand_secret(
+    secret: &hkdf::Prk,
+    label: &[u8],
+    context: &[u8],
+) -> Result<hkdf::Prk, error::Unspecified> {
+    let digest_algorithm = secret.digest_algorithm();
+    let mut out = [0u8; digest::MAX_OUTPUT_LEN];
+    let out = &mut out[..digest_algorithm.output_len];
+    secret.expand_label(label, context, out)?;
+    Ok(hkdf::Prk::new_less_safe(digest_algorithm, out))
+}
+
+// The longest key of the AEADs used in TLS 1.3.
+const MAX_KEY_LEN: usize = 32;
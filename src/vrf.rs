@@ -0,0 +1,45 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Verifiable random functions.
+//!
+//! This implements ECVRF-EDWARDS25519-SHA512-TAI from [RFC 9381]. The holder
+//! of a `KeyPair` computes a proof for an input `alpha` with `prove()`. The
+//! VRF output for `alpha` is a pseudorandom function of the input that only
+//! the key holder can compute, but anybody with the public key can check
+//! that it is correct by passing the proof to `verify()`, which returns the
+//! output. The output is unique: there is only one valid output for each
+//! public key and input.
+//!
+//! # Examples
+//!
+//! ```
+//! use ring::vrf;
+//!
+//! let key_pair = vrf::KeyPair::from_seed(&[0x42; vrf::SEED_LEN])?;
+//! let proof = key_pair.prove(b"round 1");
+//!
+//! let output = vrf::verify(key_pair.public_key(), b"round 1", proof.as_ref())?;
+//! assert_eq!(output.as_ref(), vrf::proof_to_hash(proof.as_ref())?.as_ref());
+//!
+//! assert!(vrf::verify(key_pair.public_key(), b"round 2", proof.as_ref()).is_err());
+//!
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [RFC 9381]: https://tools.ietf.org/html/rfc9381
+
+pub use crate::ec::curve25519::vrf::{
+    proof_to_hash, verify, KeyPair, Output, Proof, OUTPUT_LEN, PROOF_LEN, PUBLIC_KEY_LEN, SEED_LEN,
+};
@@ -0,0 +1,241 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! WebAuthn assertion signature verification.
+//!
+//! A credential's public key is a COSE key ([RFC 8152] Section 13), as found
+//! in the attested credential data of a registration. An assertion signature
+//! is over the authenticator data followed by the SHA-256 digest of the
+//! client data JSON; see [Web Authentication] Section 7.2, steps 19 and 20.
+//!
+//! The supported algorithms are ES256 (ECDSA P-256 with SHA-256, with
+//! ASN.1 DER-encoded signatures), EdDSA (Ed25519), and PS256 (RSASSA-PSS
+//! with SHA-256). As with the rest of *ring*, RSA keys smaller than 2048 bits
+//! are rejected.
+//!
+//! Only the signature is checked. The caller is responsible for the rest of
+//! the relying party's procedure, such as checking the challenge and origin
+//! in the client data, the RP ID hash and flags in the authenticator data,
+//! and the signature counter.
+//!
+//! [RFC 8152]: https://tools.ietf.org/html/rfc8152
+//! [Web Authentication]: https://www.w3.org/TR/webauthn/
+
+use crate::{
+    cbor::Value,
+    cose::{read_key_params, CRV_P256, KTY_EC2, KTY_RSA},
+    digest, error, signature,
+};
+
+#[cfg(feature = "sig-ed25519")]
+use crate::cose::{CRV_ED25519, KTY_OKP};
+use std::vec::Vec;
+use untrusted;
+
+/// A COSE signature algorithm.
+pub struct Algorithm {
+    cose_identifier: i32,
+    id: AlgorithmID,
+}
+
+derive_debug_via_id!(Algorithm);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum AlgorithmID {
+    ES256,
+    #[cfg(feature = "sig-ed25519")]
+    EDDSA,
+    PS256,
+}
+
+impl PartialEq for Algorithm {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Algorithm {}
+
+impl Algorithm {
+    /// The COSE algorithm identifier, as registered with IANA.
+    #[inline]
+    pub fn cose_identifier(&self) -> i32 {
+        self.cose_identifier
+    }
+}
+
+/// ECDSA using the P-256 curve and SHA-256.
+pub static ES256: Algorithm = Algorithm {
+    cose_identifier: -7,
+    id: AlgorithmID::ES256,
+};
+
+/// EdDSA. Only Ed25519 keys are supported.
+#[cfg(feature = "sig-ed25519")]
+pub static EDDSA: Algorithm = Algorithm {
+    cose_identifier: -8,
+    id: AlgorithmID::EDDSA,
+};
+
+/// RSASSA-PSS using SHA-256.
+pub static PS256: Algorithm = Algorithm {
+    cose_identifier: -37,
+    id: AlgorithmID::PS256,
+};
+
+static ALL_ALGORITHMS: &[&Algorithm] = &[
+    &ES256,
+    #[cfg(feature = "sig-ed25519")]
+    &EDDSA,
+    &PS256,
+];
+
+/// A credential public key.
+#[derive(Clone, Debug)]
+pub struct PublicKey {
+    algorithm: &'static Algorithm,
+    key: Key,
+}
+
+#[derive(Clone, Debug)]
+enum Key {
+    // The uncompressed point.
+    Ecdsa(Vec<u8>),
+    #[cfg(feature = "sig-ed25519")]
+    Ed25519(Vec<u8>),
+    Rsa(signature::RsaPublicKeyComponents<Vec<u8>>),
+}
+
+impl PublicKey {
+    /// Parses a CBOR-encoded COSE key.
+    ///
+    /// The key must have an algorithm (`alg`) parameter, and its key type
+    /// and curve must match the algorithm. Parameters that aren't needed
+    /// for the algorithm, such as `kid`, are ignored.
+    pub fn from_cose(cose_key: &[u8]) -> Result<Self, error::Unspecified> {
+        let params =
+            untrusted::Input::from(cose_key).read_all(error::Unspecified, read_key_params)?;
+
+        let algorithm = match params.alg {
+            Some(Value::Int(alg)) => ALL_ALGORITHMS
+                .iter()
+                .find(|algorithm| i64::from(algorithm.cose_identifier) == alg)
+                .ok_or(error::Unspecified)?,
+            _ => {
+                return Err(error::Unspecified);
+            }
+        };
+
+        let key = match (algorithm.id, params.kty, params.minus_1, params.minus_2) {
+            (
+                AlgorithmID::ES256,
+                Some(Value::Int(KTY_EC2)),
+                Some(Value::Int(CRV_P256)),
+                Some(Value::Bytes(x)),
+            ) => {
+                let y = match params.minus_3 {
+                    Some(Value::Bytes(y)) => y,
+                    _ => {
+                        return Err(error::Unspecified);
+                    }
+                };
+                if x.len() != P256_ELEM_LEN || y.len() != P256_ELEM_LEN {
+                    return Err(error::Unspecified);
+                }
+                let mut point = Vec::with_capacity(1 + x.len() + y.len());
+                point.push(0x04);
+                point.extend_from_slice(x);
+                point.extend_from_slice(y);
+                Key::Ecdsa(point)
+            }
+            #[cfg(feature = "sig-ed25519")]
+            (
+                AlgorithmID::EDDSA,
+                Some(Value::Int(KTY_OKP)),
+                Some(Value::Int(CRV_ED25519)),
+                Some(Value::Bytes(x)),
+            ) => {
+                if x.len() != ED25519_PUBLIC_KEY_LEN {
+                    return Err(error::Unspecified);
+                }
+                Key::Ed25519(x.to_vec())
+            }
+            (
+                AlgorithmID::PS256,
+                Some(Value::Int(KTY_RSA)),
+                Some(Value::Bytes(n)),
+                Some(Value::Bytes(e)),
+            ) => Key::Rsa(signature::RsaPublicKeyComponents {
+                n: n.to_vec(),
+                e: e.to_vec(),
+            }),
+            _ => {
+                return Err(error::Unspecified);
+            }
+        };
+
+        Ok(Self { algorithm, key })
+    }
+
+    /// The algorithm of the key.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+
+    /// Verifies the assertion signature `signature` over `authenticator_data`
+    /// and `client_data_json`.
+    ///
+    /// `client_data_json` is the client data as it was received, not a
+    /// re-serialization of it.
+    pub fn verify(
+        &self,
+        authenticator_data: &[u8],
+        client_data_json: &[u8],
+        signature: &[u8],
+    ) -> Result<(), error::Unspecified> {
+        let signed_data = signed_data(authenticator_data, client_data_json);
+        match &self.key {
+            Key::Ecdsa(point) => {
+                signature::UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_ASN1, point)
+                    .verify(&signed_data, signature)
+            }
+            #[cfg(feature = "sig-ed25519")]
+            Key::Ed25519(public_key) => {
+                signature::UnparsedPublicKey::new(&signature::ED25519, public_key)
+                    .verify(&signed_data, signature)
+            }
+            Key::Rsa(components) => components.verify(
+                &signature::RSA_PSS_2048_8192_SHA256,
+                &signed_data,
+                signature,
+            ),
+        }
+    }
+}
+
+/// The data that is signed by an assertion signature: `authenticator_data`
+/// followed by the SHA-256 digest of `client_data_json`.
+pub fn signed_data(authenticator_data: &[u8], client_data_json: &[u8]) -> Vec<u8> {
+    let client_data_hash = digest::digest(&digest::SHA256, client_data_json);
+    let mut signed_data =
+        Vec::with_capacity(authenticator_data.len() + client_data_hash.as_ref().len());
+    signed_data.extend_from_slice(authenticator_data);
+    signed_data.extend_from_slice(client_data_hash.as_ref());
+    signed_data
+}
+
+const P256_ELEM_LEN: usize = 32;
+#[cfg(feature = "sig-ed25519")]
+const ED25519_PUBLIC_KEY_LEN: usize = 32;
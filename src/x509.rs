@@ -0,0 +1,136 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Verification of the signatures of X.509 certificates and CRLs.
+//!
+//! A certificate ([RFC 5280 Section 4.1]) is signed by its issuer over the
+//! DER encoding of its `tbsCertificate`, using the algorithm identified by
+//! its `signatureAlgorithm`; CRLs and OCSP responses are signed the same way.
+//! `verify_signature()` maps the signature algorithm identifier and the type
+//! of the issuer's public key to the matching verification algorithm and
+//! verifies the signature with it.
+//!
+//! Parsing certificates, building and validating certification paths, and
+//! checking names, validity periods, and extensions are out of scope.
+//!
+//! The following signature algorithms are supported, with the encodings of
+//! their identifiers that [RFC 4055], [RFC 5758], and [RFC 8410] require:
+//!
+//! | `signatureAlgorithm`      | Issuer key      | Verification algorithm             |
+//! |---------------------------|-----------------|------------------------------------|
+//! | `ecdsa-with-SHA256`       | P-256           | `ECDSA_P256_SHA256_ASN1`           |
+//! | `ecdsa-with-SHA256`       | P-384           | `ECDSA_P384_SHA256_ASN1`           |
+//! | `ecdsa-with-SHA384`       | P-256           | `ECDSA_P256_SHA384_ASN1`           |
+//! | `ecdsa-with-SHA384`       | P-384           | `ECDSA_P384_SHA384_ASN1`           |
+//! | `sha256WithRSAEncryption` | `rsaEncryption` | `RSA_PKCS1_2048_8192_SHA256`       |
+//! | `sha384WithRSAEncryption` | `rsaEncryption` | `RSA_PKCS1_2048_8192_SHA384`       |
+//! | `sha512WithRSAEncryption` | `rsaEncryption` | `RSA_PKCS1_2048_8192_SHA512`       |
+//! | `id-RSASSA-PSS`           | `rsaEncryption` | `RSA_PSS_2048_8192_SHA256/384/512` |
+//! | `id-Ed25519`              | Ed25519         | `ED25519`                          |
+//! | `id-Ed448`                | Ed448           | `ED448`                            |
+//!
+//! `id-RSASSA-PSS` is only supported with SHA-256, SHA-384, or SHA-512 used
+//! for both the message digest and MGF1, and a salt as long as the digest.
+//!
+//! [RFC 5280 Section 4.1]: https://tools.ietf.org/html/rfc5280#section-4.1
+//! [RFC 4055]: https://tools.ietf.org/html/rfc4055
+//! [RFC 5758]: https://tools.ietf.org/html/rfc5758
+//! [RFC 8410]: https://tools.ietf.org/html/rfc8410
+
+use crate::{
+    error,
+    io::der::{self, Tag},
+    signature::{self, VerificationAlgorithm},
+};
+use untrusted;
+
+/// Verifies that `signature` is a valid signature of `tbs` made by the
+/// holder of the private key of `issuer_spki` using `signature_algorithm`.
+///
+/// `tbs` is the DER encoding of the signed data, e.g. the `tbsCertificate`
+/// of a certificate, including its outer `SEQUENCE` tag and length.
+/// `signature_algorithm` is the DER-encoded `AlgorithmIdentifier`, including
+/// its outer `SEQUENCE` tag and length. `signature` is the value of the
+/// `signatureValue` `BIT STRING`, without the leading octet that counts the
+/// unused bits. `issuer_spki` is the DER-encoded `SubjectPublicKeyInfo` of
+/// the issuer.
+///
+/// Fails if the signature algorithm isn't supported, if it can't be used
+/// with the issuer's key, or if the signature isn't valid.
+pub fn verify_signature(
+    signature_algorithm: &[u8],
+    issuer_spki: &[u8],
+    tbs: &[u8],
+    signature: &[u8],
+) -> Result<(), error::Unspecified> {
+    let signature_algorithm = untrusted::Input::from(signature_algorithm)
+        .read_all(error::Unspecified, |input| {
+            der::expect_tag_and_get_value(input, Tag::Sequence)
+        })?;
+    let candidates = SIGNATURE_ALGORITHMS
+        .iter()
+        .find(|(id, _)| signature_algorithm.as_slice_less_safe() == *id)
+        .map(|(_, candidates)| *candidates)
+        .ok_or(error::Unspecified)?;
+    signature::UnparsedPublicKey::from_spki_der(candidates, issuer_spki)?.verify(tbs, signature)
+}
+
+// The verification algorithms that may be used for each signature algorithm,
+// one per type of public key.
+static SIGNATURE_ALGORITHMS: &[(&[u8], &[&dyn VerificationAlgorithm])] = &[
+    (
+        include_bytes!("data/alg-ecdsa-sha256.der"),
+        &[
+            &signature::ECDSA_P256_SHA256_ASN1,
+            &signature::ECDSA_P384_SHA256_ASN1,
+        ],
+    ),
+    (
+        include_bytes!("data/alg-ecdsa-sha384.der"),
+        &[
+            &signature::ECDSA_P256_SHA384_ASN1,
+            &signature::ECDSA_P384_SHA384_ASN1,
+        ],
+    ),
+    (
+        include_bytes!("data/alg-rsa-pkcs1-sha256.der"),
+        &[&signature::RSA_PKCS1_2048_8192_SHA256],
+    ),
+    (
+        include_bytes!("data/alg-rsa-pkcs1-sha384.der"),
+        &[&signature::RSA_PKCS1_2048_8192_SHA384],
+    ),
+    (
+        include_bytes!("data/alg-rsa-pkcs1-sha512.der"),
+        &[&signature::RSA_PKCS1_2048_8192_SHA512],
+    ),
+    (
+        include_bytes!("data/alg-rsa-pss-sha256.der"),
+        &[&signature::RSA_PSS_2048_8192_SHA256],
+    ),
+    (
+        include_bytes!("data/alg-rsa-pss-sha384.der"),
+        &[&signature::RSA_PSS_2048_8192_SHA384],
+    ),
+    (
+        include_bytes!("data/alg-rsa-pss-sha512.der"),
+        &[&signature::RSA_PSS_2048_8192_SHA512],
+    ),
+    #[cfg(feature = "sig-ed25519")]
+    (
+        include_bytes!("data/alg-ed25519.der"),
+        &[&signature::ED25519],
+    ),
+    (include_bytes!("data/alg-ed448.der"), &[&signature::ED448]),
+];
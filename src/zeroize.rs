@@ -0,0 +1,84 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Wiping of secrets.
+//!
+//! Writing zeros over a value that is about to be dropped is a dead store
+//! that the compiler may remove, so `Zeroize` uses volatile writes, followed
+//! by a compiler fence so that they aren't reordered after the memory is
+//! reused.
+
+use core::{
+    num::Wrapping,
+    ptr,
+    sync::atomic::{compiler_fence, Ordering},
+};
+
+/// A value that can be wiped.
+pub(crate) trait Zeroize {
+    /// Overwrites the value with zeros.
+    fn zeroize(&mut self);
+}
+
+/// A type that can be wiped by overwriting its bytes with zeros.
+///
+/// # Safety
+///
+/// All zeros must be a valid value of the type.
+pub(crate) unsafe trait Zeroable: Copy {}
+
+unsafe impl Zeroable for u8 {}
+unsafe impl Zeroable for u32 {}
+unsafe impl Zeroable for u64 {}
+unsafe impl Zeroable for Wrapping<u32> {}
+unsafe impl Zeroable for Wrapping<u64> {}
+
+impl<T: Zeroable> Zeroize for [T] {
+    fn zeroize(&mut self) {
+        for value in self.iter_mut() {
+            // Safe because `value` is a valid, aligned `T`, and all zeros is a
+            // valid `T`.
+            unsafe { ptr::write_volatile(value, core::mem::zeroed()) };
+        }
+        compiler_fence(Ordering::SeqCst);
+    }
+}
+
+impl<T: Zeroable> Zeroize for T {
+    #[inline]
+    fn zeroize(&mut self) {
+        core::slice::from_mut(self).zeroize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zeroize_test() {
+        let mut bytes = [0xffu8; 33];
+        bytes[1..].zeroize();
+        assert_eq!(bytes[0], 0xff);
+        assert!(bytes[1..].iter().all(|b| *b == 0));
+
+        let mut words = [Wrapping(u64::max_value()); 3];
+        words.zeroize();
+        assert!(words.iter().all(|w| w.0 == 0));
+
+        let mut word = u32::max_value();
+        word.zeroize();
+        assert_eq!(word, 0);
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "testing-internals")]
+
+use ring::{aead::internals, test};
+
+// The vectors are from RFC 8452 Appendix C.
+
+#[test]
+fn aead_internals_derive_keys() {
+    let nonce = nonce();
+
+    let keys = internals::derive_keys(&key_128(), &nonce).unwrap();
+    assert_eq!(
+        &keys.message_authentication_key()[..],
+        &test::from_hex("d9b360279694941ac5dbc6987ada7377").unwrap()[..]
+    );
+    assert_eq!(
+        keys.message_encryption_key(),
+        &test::from_hex("4004a0dcd862f2a57360219d2d44ef6c").unwrap()[..]
+    );
+
+    let keys = internals::derive_keys(&key_256(), &nonce).unwrap();
+    assert_eq!(
+        &keys.message_authentication_key()[..],
+        &test::from_hex("b5d3c529dfafac43136d2d11be284d7f").unwrap()[..]
+    );
+    assert_eq!(
+        keys.message_encryption_key(),
+        &test::from_hex("b914f4742be9e1d7a2f84addbf96dec3456e3c6c05ecc157cdbf0700fedad222")
+            .unwrap()[..]
+    );
+
+    assert!(internals::derive_keys(&key_128()[1..], &nonce).is_err());
+    assert!(internals::derive_keys(&[0; 24], &nonce).is_err());
+}
+
+#[test]
+fn aead_internals_polyval_tag() {
+    let nonce = nonce();
+
+    let tag = internals::polyval_tag(&key_128(), &nonce, &[], &[]).unwrap();
+    assert_eq!(
+        &tag[..],
+        &test::from_hex("03000000000000000000000000000000").unwrap()[..]
+    );
+
+    let plaintext = test::from_hex("0100000000000000").unwrap();
+    let tag = internals::polyval_tag(&key_128(), &nonce, &[], &plaintext).unwrap();
+    assert_eq!(
+        &tag[..],
+        &test::from_hex("e893b7740962c5e49d2a90a7dc5cec74").unwrap()[..]
+    );
+
+    // A partial block of each of the additional data and the plaintext.
+    let plaintext = test::from_hex("0200000000000000000000000000000003000000").unwrap();
+    let tag = internals::polyval_tag(&key_256(), &nonce, &[0x01], &plaintext).unwrap();
+    assert_eq!(
+        &tag[..],
+        &test::from_hex("c760b77d9a3fa248665d4532bd58d707").unwrap()[..]
+    );
+}
+
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn aead_internals_crypt_last_block() {
+    let message_encryption_key = test::from_hex("4004a0dcd862f2a57360219d2d44ef6c").unwrap();
+    let mut tag = [0u8; 16];
+    tag.copy_from_slice(&test::from_hex("dc20e2d83f25705bb49e439eca56de25").unwrap());
+    let message = (0..20).collect::<Vec<u8>>();
+    let expected_last_block = test::from_hex("276b65f2").unwrap();
+
+    for &in_prefix_len in &[0, 3, 16] {
+        let mut in_out = vec![0xffu8; in_prefix_len];
+        in_out.extend_from_slice(&message);
+        let result =
+            internals::crypt_last_block(&message_encryption_key, &tag, in_prefix_len, &mut in_out);
+        if result.is_err() {
+            // The CPU doesn't support the AVX/AES-NI implementation.
+            return;
+        }
+        assert_eq!(&in_out[16..20], &expected_last_block[..]);
+        // The input after the output is left as it was.
+        assert_eq!(&in_out[20..], &message[(20 - in_prefix_len)..]);
+    }
+
+    let mut in_out = message.clone();
+    assert!(
+        internals::crypt_last_block(&message_encryption_key[1..], &tag, 0, &mut in_out).is_err()
+    );
+    assert!(internals::crypt_last_block(&message_encryption_key, &tag, 21, &mut in_out).is_err());
+}
+
+fn key_128() -> Vec<u8> {
+    test::from_hex("01000000000000000000000000000000").unwrap()
+}
+
+fn key_256() -> Vec<u8> {
+    test::from_hex("0100000000000000000000000000000000000000000000000000000000000000").unwrap()
+}
+
+fn nonce() -> [u8; 12] {
+    [3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+}
@@ -0,0 +1,207 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::aead::{self, stream};
+
+#[cfg(feature = "use_heap")]
+use std::io::{Read, Write};
+
+const NONCE_PREFIX: [u8; stream::NONCE_PREFIX_LEN] = [1, 2, 3, 4, 5, 6, 7];
+
+fn keys(algorithm: &'static aead::Algorithm) -> (aead::SealingKey, aead::OpeningKey) {
+    let key_bytes = vec![0x42; algorithm.key_len()];
+    (
+        aead::SealingKey::new(algorithm, &key_bytes).unwrap(),
+        aead::OpeningKey::new(algorithm, &key_bytes).unwrap(),
+    )
+}
+
+#[test]
+fn aead_stream_chunks() {
+    for &algorithm in aead::Algorithm::all() {
+        if algorithm.nonce_len() != aead::NONCE_LEN {
+            continue;
+        }
+        let (s_key, o_key) = keys(algorithm);
+
+        let mut first = b"first chunk".to_vec();
+        first.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+        let mut last = b"last".to_vec();
+        last.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+
+        let mut encryptor = stream::Encryptor::new(&s_key, NONCE_PREFIX).unwrap();
+        let len = encryptor
+            .seal_next_in_place(aead::Aad::from(b"header"), &mut first, aead::MAX_TAG_LEN)
+            .unwrap();
+        assert_eq!(len, first.len());
+        let len = encryptor
+            .seal_last_in_place(aead::Aad::empty(), &mut last, aead::MAX_TAG_LEN)
+            .unwrap();
+        assert_eq!(len, last.len());
+
+        // Each chunk only opens in its own position.
+        let mut decryptor = stream::Decryptor::new(&o_key, NONCE_PREFIX).unwrap();
+        assert!(decryptor
+            .open_next_in_place(aead::Aad::empty(), &mut last.clone())
+            .is_err());
+        assert!(stream::Decryptor::new(&o_key, NONCE_PREFIX)
+            .unwrap()
+            .open_last_in_place(aead::Aad::from(b"header"), &mut first.clone())
+            .is_err());
+
+        // The additional data is authenticated.
+        assert!(decryptor
+            .open_next_in_place(aead::Aad::empty(), &mut first.clone())
+            .is_err());
+
+        let mut decryptor = stream::Decryptor::new(&o_key, NONCE_PREFIX).unwrap();
+        let plaintext = decryptor
+            .open_next_in_place(aead::Aad::from(b"header"), &mut first)
+            .unwrap();
+        assert_eq!(plaintext, b"first chunk");
+        let plaintext = decryptor
+            .open_last_in_place(aead::Aad::empty(), &mut last)
+            .unwrap();
+        assert_eq!(plaintext, b"last");
+    }
+}
+
+#[cfg(feature = "aead-chacha")]
+#[test]
+fn aead_stream_xchacha20_poly1305() {
+    let (s_key, o_key) = keys(&aead::XCHACHA20_POLY1305);
+    assert!(stream::Encryptor::new(&s_key, NONCE_PREFIX).is_err());
+    assert!(stream::Decryptor::new(&o_key, NONCE_PREFIX).is_err());
+}
+
+#[cfg(feature = "use_heap")]
+#[test]
+fn aead_stream_writer_reader() {
+    const CHUNK_LEN: usize = 64;
+
+    let algorithms: &[&aead::Algorithm] = &[
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        &aead::AES_256_GCM_SIV,
+        &aead::AES_128_GCM,
+    ];
+    for &algorithm in algorithms {
+        let (s_key, o_key) = keys(algorithm);
+
+        for &len in &[0, 1, CHUNK_LEN - 1, CHUNK_LEN, CHUNK_LEN + 1, 5 * CHUNK_LEN] {
+            let plaintext: Vec<u8> = (0..len).map(|i| i as u8).collect();
+
+            let mut writer =
+                stream::Writer::new(&s_key, NONCE_PREFIX, CHUNK_LEN, Vec::new()).unwrap();
+            for piece in plaintext.chunks(7) {
+                writer.write_all(piece).unwrap();
+            }
+            let sealed = writer.finish().unwrap();
+
+            let chunks = std::cmp::max(1, (len + CHUNK_LEN - 1) / CHUNK_LEN);
+            assert_eq!(sealed.len(), len + chunks * aead::MAX_TAG_LEN);
+
+            let mut reader =
+                stream::Reader::new(&o_key, NONCE_PREFIX, CHUNK_LEN, &sealed[..]).unwrap();
+            let mut opened = Vec::new();
+            let _ = reader.read_to_end(&mut opened).unwrap();
+            assert_eq!(opened, plaintext);
+
+            // Truncating the message anywhere, including at a chunk boundary,
+            // is detected.
+            for truncated_len in 0..sealed.len() {
+                let mut reader =
+                    stream::Reader::new(&o_key, NONCE_PREFIX, CHUNK_LEN, &sealed[..truncated_len])
+                        .unwrap();
+                assert!(reader.read_to_end(&mut Vec::new()).is_err());
+            }
+
+            // So is opening with the wrong nonce prefix.
+            let mut reader = stream::Reader::new(
+                &o_key,
+                [0; stream::NONCE_PREFIX_LEN],
+                CHUNK_LEN,
+                &sealed[..],
+            )
+            .unwrap();
+            assert!(reader.read_to_end(&mut Vec::new()).is_err());
+        }
+    }
+}
+
+#[cfg(all(feature = "use_heap", feature = "aead-aes-gcm-siv"))]
+#[test]
+fn aead_stream_reader_tampered() {
+    let (s_key, o_key) = keys(&aead::AES_256_GCM_SIV);
+
+    let mut writer = stream::Writer::new(&s_key, NONCE_PREFIX, 16, Vec::new()).unwrap();
+    writer.write_all(&[0; 40]).unwrap();
+    let mut sealed = writer.finish().unwrap();
+    sealed[40] ^= 1;
+
+    // The first chunk is returned, and then the error sticks.
+    let mut reader = stream::Reader::new(&o_key, NONCE_PREFIX, 16, &sealed[..]).unwrap();
+    let mut buf = [0xff; 16];
+    assert_eq!(reader.read(&mut buf).unwrap(), 16);
+    assert_eq!(buf, [0; 16]);
+    let e = reader.read(&mut buf).unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+    assert!(reader.read(&mut buf).is_err());
+}
+
+#[cfg(all(feature = "use_heap", feature = "aead-aes-gcm-siv"))]
+#[test]
+fn aead_stream_chunk_len_zero() {
+    let (s_key, o_key) = keys(&aead::AES_256_GCM_SIV);
+    assert!(stream::Writer::new(&s_key, NONCE_PREFIX, 0, Vec::new()).is_err());
+    assert!(stream::Reader::new(&o_key, NONCE_PREFIX, 0, &b""[..]).is_err());
+}
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+#[test]
+fn aead_stream_debug() {
+    let (s_key, o_key) = keys(&aead::AES_128_GCM_SIV);
+    assert_eq!(
+        "Encryptor { algorithm: AES_128_GCM_SIV }",
+        format!(
+            "{:?}",
+            stream::Encryptor::new(&s_key, NONCE_PREFIX).unwrap()
+        )
+    );
+    assert_eq!(
+        "Decryptor { algorithm: AES_128_GCM_SIV }",
+        format!(
+            "{:?}",
+            stream::Decryptor::new(&o_key, NONCE_PREFIX).unwrap()
+        )
+    );
+}
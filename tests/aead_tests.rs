@@ -32,6 +32,7 @@
 )]
 
 use ring::{aead, error, test, test_file};
+use std::mem::MaybeUninit;
 
 #[test]
 fn aead_aes_gcm_128() {
@@ -43,12 +44,31 @@ fn aead_aes_gcm_256() {
     test_aead(&aead::AES_256_GCM, test_file!("aead_aes_256_gcm_tests.txt"));
 }
 
+#[cfg(feature = "aead-aes-gcm-siv")]
 #[test]
 fn aead_aes_gcm_siv_128() { test_aead(&aead::AES_128_GCM_SIV, test_file!("aes_128_gcm_siv_tests.txt"), ); }
 
+#[cfg(feature = "aead-aes-gcm-siv")]
 #[test]
 fn aead_aes_gcm_siv_256() { test_aead(&aead::AES_256_GCM_SIV, test_file!("aes_256_gcm_siv_tests.txt"), ); }
 
+#[test]
+fn aead_aes_128_siv_cmac() {
+    test_aead(
+        &aead::AES_128_SIV_CMAC,
+        test_file!("aead_aes_128_siv_cmac_tests.txt"),
+    );
+}
+
+#[test]
+fn aead_aes_256_siv_cmac() {
+    test_aead(
+        &aead::AES_256_SIV_CMAC,
+        test_file!("aead_aes_256_siv_cmac_tests.txt"),
+    );
+}
+
+#[cfg(feature = "aead-chacha")]
 #[test]
 fn aead_chacha20_poly1305() {
     test_aead(
@@ -100,6 +120,30 @@ fn test_aead(aead_alg: &'static aead::Algorithm, test_file: test::File) {
         if s_result.is_ok() {
             assert_eq!(Ok(ct.len()), s_result);
             assert_eq!(&ct[..], &s_in_out[..ct.len()]);
+            test_aead_into(aead_alg, &key_bytes, &nonce, &ad, &plaintext, &ct)?;
+            test_aead_to(aead_alg, &key_bytes, &nonce, &ad, &plaintext, &ct)?;
+            test_aead_detached(aead_alg, &key_bytes, &nonce, &ad, &plaintext, &ct)?;
+            test_aead_batch(aead_alg, &key_bytes, &nonce, &ad, &plaintext, &ct)?;
+            #[cfg(feature = "aead-aes-gcm-siv")]
+            {
+                if aead_alg == &aead::AES_128_GCM_SIV || aead_alg == &aead::AES_256_GCM_SIV {
+                    test_aead_stream(aead_alg, &key_bytes, &nonce, &ad, &plaintext, &ct)?;
+                }
+            }
+
+            // Sealing must not modify the key, so sealing the same input
+            // again gives the same output.
+            let mut s_in_out = plaintext.clone();
+            s_in_out.extend_from_slice(&vec![0; tag_len]);
+            let nonce = aead::Nonce::try_assume_unique_for_key(&nonce).unwrap();
+            let _ = aead::seal_in_place(
+                &s_key,
+                nonce,
+                aead::Aad::from(&ad),
+                &mut s_in_out[..],
+                tag_len,
+            )?;
+            assert_eq!(&ct[..], &s_in_out[..]);
         }
 
         let o_key = aead::OpeningKey::new(aead_alg, &key_bytes[..])?;
@@ -175,6 +219,15 @@ fn test_aead(aead_alg: &'static aead::Algorithm, test_file: test::File) {
                 o_in_out.push(123);
             }
             o_in_out.extend_from_slice(&ct[..]);
+            let mut w_in_out = o_in_out.clone();
+            let w_nonce = aead::Nonce::try_assume_unique_for_key(&nonce).unwrap();
+            let w_result = aead::open_within(
+                &o_key,
+                w_nonce,
+                aead::Aad::from(&ad),
+                &mut w_in_out[..],
+                *in_prefix_len..,
+            );
             let nonce = aead::Nonce::try_assume_unique_for_key(&nonce).unwrap();
             let o_result = aead::open_in_place(
                 &o_key,
@@ -183,6 +236,13 @@ fn test_aead(aead_alg: &'static aead::Algorithm, test_file: test::File) {
                 *in_prefix_len,
                 &mut o_in_out[..],
             );
+
+            // `open_within()` is the same with the prefix given as a range.
+            assert_eq!(
+                o_result.as_ref().ok().map(|plaintext| &plaintext[..]),
+                w_result.ok().map(|plaintext| &plaintext[..])
+            );
+
             match error {
                 None => {
                     assert!(s_result.is_ok());
@@ -202,6 +262,256 @@ fn test_aead(aead_alg: &'static aead::Algorithm, test_file: test::File) {
     });
 }
 
+// Sealing into an uninitialized buffer gives the same output as sealing in
+// place, and opening it into an uninitialized buffer recovers the plaintext.
+fn test_aead_into(
+    aead_alg: &'static aead::Algorithm,
+    key_bytes: &[u8],
+    nonce: &[u8],
+    ad: &[u8],
+    plaintext: &[u8],
+    ct: &[u8],
+) -> Result<(), error::Unspecified> {
+    let s_key = aead::SealingKey::new(aead_alg, key_bytes)?;
+    let mut out = vec![MaybeUninit::<u8>::uninit(); ct.len() + 1];
+    let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+    let sealed = aead::seal_into(&s_key, n, aead::Aad::from(ad), plaintext, &mut out[..])?;
+    assert_eq!(ct, &sealed[..]);
+
+    let o_key = aead::OpeningKey::new(aead_alg, key_bytes)?;
+    let mut out = vec![MaybeUninit::<u8>::uninit(); ct.len()];
+    let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+    let opened = aead::open_into(&o_key, n, aead::Aad::from(ad), ct, &mut out[..])?;
+    assert_eq!(plaintext, &opened[..]);
+
+    // Sealing the plaintext split into segments gives the same output.
+    let (a, rest) = plaintext.split_at(plaintext.len() / 3);
+    let (b, c) = rest.split_at(rest.len() / 2);
+    for segments in &[&[a, b, c][..], &[&[][..], plaintext, &[][..]][..]] {
+        let mut out = vec![MaybeUninit::<u8>::uninit(); ct.len() + 1];
+        let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+        let sealed =
+            aead::seal_into_vectored(&s_key, n, aead::Aad::from(ad), segments, &mut out[..])?;
+        assert_eq!(ct, &sealed[..]);
+    }
+
+    // The output buffers must have room for the tag.
+    let mut out = vec![MaybeUninit::<u8>::uninit(); ct.len() - 1];
+    let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+    assert!(aead::seal_into(&s_key, n, aead::Aad::from(ad), plaintext, &mut out[..]).is_err());
+    let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+    assert!(
+        aead::seal_into_vectored(&s_key, n, aead::Aad::from(ad), &[a, b, c], &mut out[..])
+            .is_err()
+    );
+    let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+    assert!(aead::open_into(&o_key, n, aead::Aad::from(ad), ct, &mut out[..]).is_err());
+
+    Ok(())
+}
+
+// Sealing and opening into a separate output buffer gives the same output as
+// sealing and opening in place, without modifying the input.
+fn test_aead_to(
+    aead_alg: &'static aead::Algorithm,
+    key_bytes: &[u8],
+    nonce: &[u8],
+    ad: &[u8],
+    plaintext: &[u8],
+    ct: &[u8],
+) -> Result<(), error::Unspecified> {
+    let s_key = aead::SealingKey::new(aead_alg, key_bytes)?;
+    let mut out = vec![123u8; ct.len() + 1];
+    let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+    let sealed = aead::seal_to(&s_key, n, aead::Aad::from(ad), plaintext, &mut out)?;
+    assert_eq!(ct, &sealed[..]);
+    assert_eq!(out[ct.len()], 123);
+
+    let o_key = aead::OpeningKey::new(aead_alg, key_bytes)?;
+    let mut out = vec![123u8; plaintext.len()];
+    let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+    let opened = aead::open_to(&o_key, n, aead::Aad::from(ad), ct, &mut out)?;
+    assert_eq!(plaintext, &opened[..]);
+
+    // A wrong tag is rejected and the output is zeroed.
+    let mut wrong_ct = ct.to_vec();
+    *wrong_ct.last_mut().unwrap() ^= 1;
+    let mut out = vec![123u8; plaintext.len()];
+    let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+    assert!(aead::open_to(&o_key, n, aead::Aad::from(ad), &wrong_ct, &mut out).is_err());
+    assert!(out.iter().all(|&b| b == 0));
+
+    // The output buffers must be long enough.
+    let mut out = vec![0u8; ct.len() - 1];
+    let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+    assert!(aead::seal_to(&s_key, n, aead::Aad::from(ad), plaintext, &mut out).is_err());
+    if !plaintext.is_empty() {
+        let mut out = vec![0u8; plaintext.len() - 1];
+        let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+        assert!(aead::open_to(&o_key, n, aead::Aad::from(ad), ct, &mut out).is_err());
+    }
+
+    Ok(())
+}
+
+// Sealing and opening with a detached tag gives the same ciphertext and tag
+// as sealing and opening with the tag appended.
+fn test_aead_detached(
+    aead_alg: &'static aead::Algorithm,
+    key_bytes: &[u8],
+    nonce: &[u8],
+    ad: &[u8],
+    plaintext: &[u8],
+    ct: &[u8],
+) -> Result<(), error::Unspecified> {
+    let (expected_ct, expected_tag) = ct.split_at(plaintext.len());
+
+    let s_key = aead::SealingKey::new(aead_alg, key_bytes)?;
+    let mut in_out = plaintext.to_vec();
+    let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+    let tag = aead::seal_in_place_detached(&s_key, n, aead::Aad::from(ad), &mut in_out)?;
+    assert_eq!(&in_out[..], expected_ct);
+    assert_eq!(tag.as_ref(), expected_tag);
+
+    let o_key = aead::OpeningKey::new(aead_alg, key_bytes)?;
+    let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+    let opened =
+        aead::open_in_place_detached(&o_key, n, aead::Aad::from(ad), tag.as_ref(), &mut in_out)?;
+    assert_eq!(plaintext, &opened[..]);
+
+    // A wrong tag is rejected and the output is zeroed.
+    let mut in_out = expected_ct.to_vec();
+    let mut wrong_tag = expected_tag.to_vec();
+    wrong_tag[0] ^= 1;
+    let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+    assert!(
+        aead::open_in_place_detached(&o_key, n, aead::Aad::from(ad), &wrong_tag, &mut in_out)
+            .is_err()
+    );
+    assert!(in_out.iter().all(|&b| b == 0));
+
+    // So is a tag of the wrong length.
+    let mut in_out = expected_ct.to_vec();
+    let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+    assert!(aead::open_in_place_detached(
+        &o_key,
+        n,
+        aead::Aad::from(ad),
+        &expected_tag[1..],
+        &mut in_out
+    )
+    .is_err());
+
+    Ok(())
+}
+
+// Opening a batch of copies of a record gives the same output as opening it
+// alone, and a batch with a wrong tag in one copy is rejected as a whole.
+fn test_aead_batch(
+    aead_alg: &'static aead::Algorithm,
+    key_bytes: &[u8],
+    nonce: &[u8],
+    ad: &[u8],
+    plaintext: &[u8],
+    ct: &[u8],
+) -> Result<(), error::Unspecified> {
+    const COPIES: usize = 6;
+
+    let o_key = aead::OpeningKey::new(aead_alg, key_bytes)?;
+    for &wrong in &[None, Some(0), Some(COPIES - 1)] {
+        let mut in_outs = vec![ct.to_vec(); COPIES];
+        if let Some(wrong) = wrong {
+            let last = ct.len() - 1;
+            in_outs[wrong][last] ^= 1;
+        }
+        let mut records = Vec::new();
+        for in_out in in_outs.iter_mut() {
+            let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+            records.push((n, aead::Aad::from(ad), &mut in_out[..]));
+        }
+        let result = aead::open_batch(&o_key, &mut records);
+        for in_out in &in_outs {
+            if wrong.is_none() {
+                assert_eq!(&in_out[..plaintext.len()], plaintext);
+            } else {
+                assert!(in_out[..plaintext.len()].iter().all(|&b| b == 0));
+            }
+        }
+        assert_eq!(result.is_ok(), wrong.is_none());
+    }
+
+    Ok(())
+}
+
+// Sealing and opening in chunks of various sizes gives the same output as
+// sealing and opening in one shot.
+#[cfg(feature = "aead-aes-gcm-siv")]
+fn test_aead_stream(
+    aead_alg: &'static aead::Algorithm,
+    key_bytes: &[u8],
+    nonce: &[u8],
+    ad: &[u8],
+    plaintext: &[u8],
+    ct: &[u8],
+) -> Result<(), error::Unspecified> {
+    let s_key = aead::SealingKey::new(aead_alg, key_bytes)?;
+    let o_key = aead::OpeningKey::new(aead_alg, key_bytes)?;
+    let (expected_ct, expected_tag) = ct.split_at(plaintext.len());
+
+    for &chunk_len in &[1, 7, 16, 17, 100, usize::MAX] {
+        let chunk_len = core::cmp::min(chunk_len, plaintext.len()).max(1);
+
+        let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+        let mut sealing = aead::SealingContext::new(&s_key, n, aead::Aad::from(ad))?;
+        for chunk in plaintext.chunks(chunk_len) {
+            sealing.update(chunk)?;
+        }
+        let mut encryptor = sealing.finish();
+        assert_eq!(encryptor.tag(), expected_tag);
+        let mut in_out = plaintext.to_vec();
+        for chunk in in_out.chunks_mut(chunk_len) {
+            encryptor.encrypt(chunk)?;
+        }
+        assert_eq!(&in_out[..], expected_ct);
+        assert_eq!(&encryptor.finish()?[..], expected_tag);
+
+        let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+        let mut opening =
+            aead::OpeningContext::new(&o_key, n, aead::Aad::from(ad), expected_tag)?;
+        for chunk in in_out.chunks_mut(chunk_len) {
+            opening.update(chunk)?;
+        }
+        opening.finish()?;
+        assert_eq!(&in_out[..], plaintext);
+    }
+
+    // A modified ciphertext is rejected.
+    let mut in_out = expected_ct.to_vec();
+    in_out.push(0);
+    let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+    let mut opening = aead::OpeningContext::new(&o_key, n, aead::Aad::from(ad), expected_tag)?;
+    opening.update(&mut in_out)?;
+    assert!(opening.finish().is_err());
+
+    // The second pass must cover exactly the plaintext of the first.
+    let new_encryptor = || -> Result<aead::SealingEncryptor, error::Unspecified> {
+        let n = aead::Nonce::try_assume_unique_for_key(nonce)?;
+        let mut sealing = aead::SealingContext::new(&s_key, n, aead::Aad::from(ad))?;
+        sealing.update(plaintext)?;
+        Ok(sealing.finish())
+    };
+    let mut too_long = plaintext.to_vec();
+    too_long.push(0);
+    assert!(new_encryptor()?.encrypt(&mut too_long).is_err());
+    if let Some((_, too_short)) = plaintext.split_last() {
+        let mut encryptor = new_encryptor()?;
+        encryptor.encrypt(&mut too_short.to_vec())?;
+        assert!(encryptor.finish().is_err());
+    }
+
+    Ok(())
+}
+
 fn test_aead_key_sizes(aead_alg: &'static aead::Algorithm) {
     let key_len = aead_alg.key_len();
     let key_data = vec![0u8; key_len * 2];
@@ -264,6 +574,49 @@ fn test_aead_nonce_sizes() -> Result<(), error::Unspecified> {
     Ok(())
 }
 
+#[test]
+fn test_aead_nonce_constructors() {
+    let prefix = [0xa0, 0xa1, 0xa2, 0xa3];
+    assert_eq!(
+        aead::Nonce::from_u64_counter(prefix, 0x0102).as_ref(),
+        &[0xa0, 0xa1, 0xa2, 0xa3, 0, 0, 0, 0, 0, 0, 0x01, 0x02]
+    );
+    assert_eq!(
+        aead::Nonce::from_u64_counter(prefix, u64::max_value()).as_ref(),
+        &[0xa0, 0xa1, 0xa2, 0xa3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+    );
+
+    assert_eq!(
+        aead::Nonce::from_u96(0x752abad3e0afb5f434dc4310)
+            .unwrap()
+            .as_ref(),
+        &[0x75, 0x2a, 0xba, 0xd3, 0xe0, 0xaf, 0xb5, 0xf4, 0x34, 0xdc, 0x43, 0x10]
+    );
+    assert_eq!(aead::Nonce::from_u96(0).unwrap().as_ref(), &[0; 12]);
+    assert_eq!(
+        aead::Nonce::from_u96((1 << 96) - 1).unwrap().as_ref(),
+        &[0xff; 12]
+    );
+    assert!(aead::Nonce::from_u96(1 << 96).is_err());
+    assert!(aead::Nonce::from_u96(u128::max_value()).is_err());
+}
+
+#[test]
+fn test_aead_tag_conversions() {
+    use core::convert::TryFrom;
+
+    let bytes = [0x5a; aead::MAX_TAG_LEN + 1];
+    let tag = aead::Tag::try_from(&bytes[..aead::MAX_TAG_LEN]).unwrap();
+    assert_eq!(tag.as_ref(), &bytes[..aead::MAX_TAG_LEN]);
+    assert!(aead::Tag::try_from(&bytes[..]).is_err());
+    assert!(aead::Tag::try_from(&bytes[..(aead::MAX_TAG_LEN - 1)]).is_err());
+    assert!(aead::Tag::try_from(&[][..]).is_err());
+
+    let tag = aead::Tag::from([0xa5; aead::MAX_TAG_LEN]);
+    assert_eq!(tag.as_ref(), &[0xa5; aead::MAX_TAG_LEN][..]);
+}
+
+#[cfg(feature = "aead-chacha")]
 #[test]
 fn aead_chacha20_poly1305_openssh() {
     // TODO: test_aead_key_sizes(...);
@@ -311,6 +664,600 @@ fn aead_chacha20_poly1305_openssh() {
     );
 }
 
+#[cfg(feature = "aead-chacha")]
+#[test]
+fn aead_xchacha20_poly1305() {
+    let aead_alg = &aead::XCHACHA20_POLY1305;
+    test_aead_key_sizes(aead_alg);
+    assert_eq!(aead_alg.nonce_len(), aead::xchacha20_poly1305::NONCE_LEN);
+
+    test::run(
+        test_file!("aead_xchacha20_poly1305_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+            let key_bytes = test_case.consume_bytes("KEY");
+            let nonce = test_case.consume_bytes("NONCE");
+            let plaintext = test_case.consume_bytes("IN");
+            let ad = test_case.consume_bytes("AD");
+            let mut ct = test_case.consume_bytes("CT");
+            let tag = test_case.consume_bytes("TAG");
+            ct.extend(tag);
+
+            let tag_len = aead_alg.tag_len();
+            let mut s_in_out = plaintext.clone();
+            s_in_out.extend_from_slice(&vec![0; tag_len]);
+            let s_key = aead::SealingKey::new(aead_alg, &key_bytes)?;
+            let s_nonce = aead::xchacha20_poly1305::Nonce::try_assume_unique_for_key(&nonce)?;
+            let s_result = aead::xchacha20_poly1305::seal_in_place(
+                &s_key,
+                s_nonce,
+                aead::Aad::from(&ad),
+                &mut s_in_out,
+                tag_len,
+            );
+            assert_eq!(s_result, Ok(ct.len()));
+            assert_eq!(&s_in_out, &ct);
+
+            let o_key = aead::OpeningKey::new(aead_alg, &key_bytes)?;
+            let open = |in_out: &mut [u8]| {
+                let nonce = aead::xchacha20_poly1305::Nonce::try_assume_unique_for_key(&nonce)?;
+                aead::xchacha20_poly1305::open_in_place(
+                    &o_key,
+                    nonce,
+                    aead::Aad::from(&ad),
+                    0,
+                    in_out,
+                )
+                .map(|plaintext| plaintext.to_vec())
+            };
+            assert_eq!(open(&mut ct.clone()), Ok(plaintext.clone()));
+
+            let mut tampered = ct.clone();
+            tampered[0] ^= 1;
+            assert!(open(&mut tampered).is_err());
+
+            let mut in_out = vec![123u8; 5];
+            in_out.extend_from_slice(&ct);
+            let nonce = aead::xchacha20_poly1305::Nonce::try_assume_unique_for_key(&nonce)?;
+            let opened = aead::xchacha20_poly1305::open_within(
+                &o_key,
+                nonce,
+                aead::Aad::from(&ad),
+                &mut in_out,
+                5..,
+            )?;
+            assert_eq!(opened, &plaintext[..]);
+
+            Ok(())
+        },
+    );
+}
+
+#[cfg(feature = "aead-chacha")]
+#[test]
+fn test_aead_xchacha20_poly1305_misuse() {
+    let key_bytes = [0x42; 32];
+
+    let nonce_len = aead::xchacha20_poly1305::NONCE_LEN;
+    let nonce = vec![0u8; nonce_len + 1];
+    assert!(aead::xchacha20_poly1305::Nonce::try_assume_unique_for_key(&nonce[..nonce_len]).is_ok());
+    assert!(
+        aead::xchacha20_poly1305::Nonce::try_assume_unique_for_key(&nonce[..aead::NONCE_LEN])
+            .is_err()
+    );
+    assert!(aead::xchacha20_poly1305::Nonce::try_assume_unique_for_key(&nonce).is_err());
+
+    // XChaCha20-Poly1305 keys can't be used with 96-bit nonces.
+    let s_key = aead::SealingKey::new(&aead::XCHACHA20_POLY1305, &key_bytes).unwrap();
+    let o_key = aead::OpeningKey::new(&aead::XCHACHA20_POLY1305, &key_bytes).unwrap();
+    let mut in_out = [0u8; 10 + aead::MAX_TAG_LEN];
+    let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+    assert!(aead::seal_in_place(&s_key, nonce, aead::Aad::empty(), &mut in_out, 16).is_err());
+    let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+    assert!(aead::seal_in_place_detached(&s_key, nonce, aead::Aad::empty(), &mut in_out).is_err());
+    let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+    assert!(aead::open_in_place(&o_key, nonce, aead::Aad::empty(), 0, &mut in_out).is_err());
+    let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+    let (in_out, tag) = in_out.split_at_mut(10);
+    assert!(
+        aead::open_in_place_detached(&o_key, nonce, aead::Aad::empty(), tag, in_out).is_err()
+    );
+
+    // Other keys can't be used with 192-bit nonces.
+    let s_key = aead::SealingKey::new(&aead::CHACHA20_POLY1305, &key_bytes).unwrap();
+    let o_key = aead::OpeningKey::new(&aead::CHACHA20_POLY1305, &key_bytes).unwrap();
+    let mut in_out = [0u8; 10 + aead::MAX_TAG_LEN];
+    let nonce = aead::xchacha20_poly1305::Nonce::assume_unique_for_key([1; 24]);
+    assert!(aead::xchacha20_poly1305::seal_in_place(
+        &s_key,
+        nonce,
+        aead::Aad::empty(),
+        &mut in_out,
+        16
+    )
+    .is_err());
+    let nonce = aead::xchacha20_poly1305::Nonce::assume_unique_for_key([1; 24]);
+    assert!(aead::xchacha20_poly1305::open_in_place(
+        &o_key,
+        nonce,
+        aead::Aad::empty(),
+        0,
+        &mut in_out
+    )
+    .is_err());
+}
+
+#[test]
+fn aead_aegis_128l() {
+    let aead_alg = &aead::AEGIS_128L;
+    test_aead_key_sizes(aead_alg);
+    assert_eq!(aead_alg.nonce_len(), aead::aegis::AEGIS_128L_NONCE_LEN);
+    test::run(
+        test_file!("aead_aegis_128l_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+            test_aegis(aead_alg, test_case)
+        },
+    );
+}
+
+#[test]
+fn aead_aegis_256() {
+    let aead_alg = &aead::AEGIS_256;
+    test_aead_key_sizes(aead_alg);
+    assert_eq!(aead_alg.nonce_len(), aead::aegis::AEGIS_256_NONCE_LEN);
+    test::run(
+        test_file!("aead_aegis_256_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+            test_aegis(aead_alg, test_case)
+        },
+    );
+}
+
+fn test_aegis(
+    aead_alg: &'static aead::Algorithm,
+    test_case: &mut test::TestCase,
+) -> Result<(), error::Unspecified> {
+    let key_bytes = test_case.consume_bytes("KEY");
+    let nonce = test_case.consume_bytes("NONCE");
+    let plaintext = test_case.consume_bytes("IN");
+    let ad = test_case.consume_bytes("AD");
+    let mut ct = test_case.consume_bytes("CT");
+    let tag = test_case.consume_bytes("TAG");
+    ct.extend(tag);
+
+    let tag_len = aead_alg.tag_len();
+    let mut s_in_out = plaintext.clone();
+    s_in_out.extend_from_slice(&vec![0; tag_len]);
+    let s_key = aead::SealingKey::new(aead_alg, &key_bytes)?;
+    let s_nonce = aead::aegis::Nonce::try_assume_unique_for_key(&nonce)?;
+    let s_result = aead::aegis::seal_in_place(
+        &s_key,
+        s_nonce,
+        aead::Aad::from(&ad),
+        &mut s_in_out,
+        tag_len,
+    );
+    assert_eq!(s_result, Ok(ct.len()));
+    assert_eq!(&s_in_out, &ct);
+
+    let o_key = aead::OpeningKey::new(aead_alg, &key_bytes)?;
+    let open = |in_out: &mut [u8]| {
+        let nonce = aead::aegis::Nonce::try_assume_unique_for_key(&nonce)?;
+        aead::aegis::open_in_place(&o_key, nonce, aead::Aad::from(&ad), 0, in_out)
+            .map(|plaintext| plaintext.to_vec())
+    };
+    assert_eq!(open(&mut ct.clone()), Ok(plaintext.clone()));
+
+    let mut tampered = ct.clone();
+    let last = tampered.len() - 1;
+    tampered[last] ^= 1;
+    assert!(open(&mut tampered).is_err());
+
+    // Decrypt with a prefix long enough that the plaintext of one block
+    // overlaps the ciphertext of the next.
+    for &prefix_len in &[1, 5, 16, 33] {
+        let mut in_out = vec![123u8; prefix_len];
+        in_out.extend_from_slice(&ct);
+        let nonce = aead::aegis::Nonce::try_assume_unique_for_key(&nonce)?;
+        let opened = aead::aegis::open_within(
+            &o_key,
+            nonce,
+            aead::Aad::from(&ad),
+            &mut in_out,
+            prefix_len..,
+        )?;
+        assert_eq!(opened, &plaintext[..]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_aead_aegis_misuse() {
+    let key_bytes = [0x42; 32];
+
+    let nonce = [0u8; aead::aegis::AEGIS_256_NONCE_LEN + 1];
+    for &(len, ok) in &[
+        (aead::aegis::AEGIS_128L_NONCE_LEN, true),
+        (aead::aegis::AEGIS_256_NONCE_LEN, true),
+        (aead::NONCE_LEN, false),
+        (aead::aegis::AEGIS_256_NONCE_LEN + 1, false),
+    ] {
+        let result = aead::aegis::Nonce::try_assume_unique_for_key(&nonce[..len]);
+        assert_eq!(result.is_ok(), ok);
+    }
+
+    for &(aead_alg, wrong_nonce_len) in &[
+        (&aead::AEGIS_128L, aead::aegis::AEGIS_256_NONCE_LEN),
+        (&aead::AEGIS_256, aead::aegis::AEGIS_128L_NONCE_LEN),
+    ] {
+        let key_bytes = &key_bytes[..aead_alg.key_len()];
+        let s_key = aead::SealingKey::new(aead_alg, key_bytes).unwrap();
+        let o_key = aead::OpeningKey::new(aead_alg, key_bytes).unwrap();
+
+        // AEGIS keys can't be used with 96-bit nonces.
+        let mut in_out = [0u8; 10 + aead::MAX_TAG_LEN];
+        let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+        assert!(aead::seal_in_place(&s_key, nonce, aead::Aad::empty(), &mut in_out, 16).is_err());
+        let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+        assert!(aead::open_in_place(&o_key, nonce, aead::Aad::empty(), 0, &mut in_out).is_err());
+
+        // Nor with the nonces of the other AEGIS algorithm.
+        let nonce =
+            aead::aegis::Nonce::try_assume_unique_for_key(&[1; 32][..wrong_nonce_len]).unwrap();
+        assert!(
+            aead::aegis::seal_in_place(&s_key, nonce, aead::Aad::empty(), &mut in_out, 16).is_err()
+        );
+        let nonce =
+            aead::aegis::Nonce::try_assume_unique_for_key(&[1; 32][..wrong_nonce_len]).unwrap();
+        assert!(
+            aead::aegis::open_in_place(&o_key, nonce, aead::Aad::empty(), 0, &mut in_out).is_err()
+        );
+    }
+
+    // Other keys can't be used with AEGIS nonces.
+    let s_key = aead::SealingKey::new(&aead::AES_128_GCM, &key_bytes[..16]).unwrap();
+    let mut in_out = [0u8; 10 + aead::MAX_TAG_LEN];
+    let nonce = aead::aegis::Nonce::try_assume_unique_for_key(&[1; 16]).unwrap();
+    assert!(
+        aead::aegis::seal_in_place(&s_key, nonce, aead::Aad::empty(), &mut in_out, 16).is_err()
+    );
+}
+
+#[test]
+fn aead_ascon_128() {
+    let aead_alg = &aead::ASCON_128;
+    test_aead_key_sizes(aead_alg);
+    assert_eq!(aead_alg.nonce_len(), aead::ascon::NONCE_LEN);
+    test::run(
+        test_file!("aead_ascon_128_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+            test_ascon(aead_alg, test_case)
+        },
+    );
+}
+
+fn test_ascon(
+    aead_alg: &'static aead::Algorithm,
+    test_case: &mut test::TestCase,
+) -> Result<(), error::Unspecified> {
+    let key_bytes = test_case.consume_bytes("KEY");
+    let nonce = test_case.consume_bytes("NONCE");
+    let plaintext = test_case.consume_bytes("IN");
+    let ad = test_case.consume_bytes("AD");
+    let mut ct = test_case.consume_bytes("CT");
+    let tag = test_case.consume_bytes("TAG");
+    ct.extend(tag);
+
+    let tag_len = aead_alg.tag_len();
+    let mut s_in_out = plaintext.clone();
+    s_in_out.extend_from_slice(&vec![0; tag_len]);
+    let s_key = aead::SealingKey::new(aead_alg, &key_bytes)?;
+    let s_nonce = aead::ascon::Nonce::try_assume_unique_for_key(&nonce)?;
+    let s_result = aead::ascon::seal_in_place(
+        &s_key,
+        s_nonce,
+        aead::Aad::from(&ad),
+        &mut s_in_out,
+        tag_len,
+    );
+    assert_eq!(s_result, Ok(ct.len()));
+    assert_eq!(&s_in_out, &ct);
+
+    let o_key = aead::OpeningKey::new(aead_alg, &key_bytes)?;
+    let open = |in_out: &mut [u8]| {
+        let nonce = aead::ascon::Nonce::try_assume_unique_for_key(&nonce)?;
+        aead::ascon::open_in_place(&o_key, nonce, aead::Aad::from(&ad), 0, in_out)
+            .map(|plaintext| plaintext.to_vec())
+    };
+    assert_eq!(open(&mut ct.clone()), Ok(plaintext.clone()));
+
+    let mut tampered = ct.clone();
+    tampered[0] ^= 1;
+    assert!(open(&mut tampered).is_err());
+
+    // Decrypt with prefixes shorter and longer than a block.
+    for &prefix_len in &[1, 5, 8, 13] {
+        let mut in_out = vec![123u8; prefix_len];
+        in_out.extend_from_slice(&ct);
+        let nonce = aead::ascon::Nonce::try_assume_unique_for_key(&nonce)?;
+        let opened = aead::ascon::open_within(
+            &o_key,
+            nonce,
+            aead::Aad::from(&ad),
+            &mut in_out,
+            prefix_len..,
+        )?;
+        assert_eq!(opened, &plaintext[..]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_aead_ascon_misuse() {
+    let key_bytes = [0x42; 16];
+
+    let nonce = [0u8; aead::ascon::NONCE_LEN + 1];
+    for &(len, ok) in &[
+        (aead::ascon::NONCE_LEN, true),
+        (aead::NONCE_LEN, false),
+        (aead::ascon::NONCE_LEN + 1, false),
+    ] {
+        let result = aead::ascon::Nonce::try_assume_unique_for_key(&nonce[..len]);
+        assert_eq!(result.is_ok(), ok);
+    }
+
+    // Ascon keys can't be used with 96-bit nonces.
+    let s_key = aead::SealingKey::new(&aead::ASCON_128, &key_bytes).unwrap();
+    let o_key = aead::OpeningKey::new(&aead::ASCON_128, &key_bytes).unwrap();
+    let mut in_out = [0u8; 10 + aead::MAX_TAG_LEN];
+    let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+    assert!(aead::seal_in_place(&s_key, nonce, aead::Aad::empty(), &mut in_out, 16).is_err());
+    let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+    assert!(aead::open_in_place(&o_key, nonce, aead::Aad::empty(), 0, &mut in_out).is_err());
+
+    // Other keys can't be used with Ascon nonces.
+    let s_key = aead::SealingKey::new(&aead::AES_128_GCM, &key_bytes).unwrap();
+    let nonce = aead::ascon::Nonce::assume_unique_for_key([1; aead::ascon::NONCE_LEN]);
+    assert!(
+        aead::ascon::seal_in_place(&s_key, nonce, aead::Aad::empty(), &mut in_out, 16).is_err()
+    );
+}
+
+#[test]
+fn aead_committing_test() {
+    use aead::committing;
+
+    let key_bytes: Vec<u8> = (0..32).collect();
+    let aad = b"header";
+    let plaintext = b"committed message";
+    // Generated with an independent implementation built on the Python
+    // cryptography package.
+    let expected = test::from_hex(
+        "63d9b403a676daf2fd05dac5e20b1249549068402b2da02aefe1dbb33f8b5389eec91d9784a9ebe005bb7b88\
+         0df00abbeb7892a825ebe2f87b06f3fab3ed3de541",
+    )
+    .unwrap();
+    let nonce = || aead::Nonce::assume_unique_for_key([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+    let suffix_len = aead::MAX_TAG_LEN + committing::COMMITMENT_LEN;
+
+    let s_key = committing::SealingKey::new(&aead::AES_256_GCM, &key_bytes).unwrap();
+    assert_eq!(s_key.algorithm(), &aead::AES_256_GCM);
+    let mut in_out = plaintext.to_vec();
+    in_out.extend_from_slice(&vec![0; suffix_len]);
+    let out_len =
+        committing::seal_in_place(&s_key, nonce(), aead::Aad::from(aad), &mut in_out, suffix_len)
+            .unwrap();
+    assert_eq!(&in_out[..out_len], &expected[..]);
+
+    let o_key = committing::OpeningKey::new(&aead::AES_256_GCM, &key_bytes).unwrap();
+    let mut in_out = expected.clone();
+    let opened =
+        committing::open_in_place(&o_key, nonce(), aead::Aad::from(aad), 0, &mut in_out).unwrap();
+    assert_eq!(opened, &plaintext[..]);
+
+    // The commitment is checked.
+    let mut in_out = expected.clone();
+    *in_out.last_mut().unwrap() ^= 1;
+    assert!(
+        committing::open_in_place(&o_key, nonce(), aead::Aad::from(aad), 0, &mut in_out).is_err()
+    );
+
+    // A message can't be opened with a different key, nor without its
+    // commitment.
+    let other_key = committing::OpeningKey::new(&aead::AES_256_GCM, &[0x42; 32]).unwrap();
+    let mut in_out = expected.clone();
+    assert!(
+        committing::open_in_place(&other_key, nonce(), aead::Aad::from(aad), 0, &mut in_out)
+            .is_err()
+    );
+    let mut in_out = expected[..(expected.len() - committing::COMMITMENT_LEN)].to_vec();
+    assert!(
+        committing::open_in_place(&o_key, nonce(), aead::Aad::from(aad), 0, &mut in_out).is_err()
+    );
+
+    // The key length is checked, and the suffix must have room for the
+    // commitment.
+    assert!(committing::SealingKey::new(&aead::AES_256_GCM, &key_bytes[..16]).is_err());
+    let mut in_out = vec![0; aead::MAX_TAG_LEN];
+    assert!(committing::seal_in_place(
+        &s_key,
+        nonce(),
+        aead::Aad::empty(),
+        &mut in_out,
+        aead::MAX_TAG_LEN
+    )
+    .is_err());
+}
+
+#[test]
+fn aead_aes_siv_cmac_components() {
+    test::run(
+        test_file!("aead_aes_siv_cmac_components_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+            let key_bytes = test_case.consume_bytes("KEY");
+            let mut components = Vec::new();
+            while let Some(ad) =
+                test_case.consume_optional_string(&format!("AD{}", components.len()))
+            {
+                let ad = if ad == "\"\"" {
+                    Vec::new()
+                } else {
+                    test::from_hex(&ad).unwrap()
+                };
+                components.push(ad);
+            }
+            let plaintext = test_case.consume_bytes("IN");
+            let mut ct = test_case.consume_bytes("CT");
+            let tag = test_case.consume_bytes("TAG");
+            ct.extend(tag);
+
+            let aead_alg = match key_bytes.len() {
+                32 => &aead::AES_128_SIV_CMAC,
+                64 => &aead::AES_256_SIV_CMAC,
+                _ => unreachable!(),
+            };
+            let components: Vec<&[u8]> = components.iter().map(|ad| &ad[..]).collect();
+
+            let s_key = aead::SealingKey::new(aead_alg, &key_bytes)?;
+            let mut s_in_out = plaintext.clone();
+            s_in_out.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+            let s_result =
+                aead::aes_siv::seal_in_place(&s_key, &components, &mut s_in_out, aead::MAX_TAG_LEN);
+            assert_eq!(Ok(ct.len()), s_result);
+            assert_eq!(ct, s_in_out);
+
+            let o_key = aead::OpeningKey::new(aead_alg, &key_bytes)?;
+            for &in_prefix_len in &[0, 1, 16, 17] {
+                let mut o_in_out = vec![123u8; in_prefix_len];
+                o_in_out.extend_from_slice(&ct);
+                let o_result =
+                    aead::aes_siv::open_in_place(&o_key, &components, in_prefix_len, &mut o_in_out);
+                assert_eq!(o_result.as_ref().map(|pt| &pt[..]), Ok(&plaintext[..]));
+            }
+
+            // Changing the ciphertext, the tag, or the components must make
+            // opening fail.
+            for i in 0..ct.len() {
+                let mut o_in_out = ct.clone();
+                o_in_out[i] ^= 1;
+                assert!(
+                    aead::aes_siv::open_in_place(&o_key, &components, 0, &mut o_in_out).is_err()
+                );
+            }
+            let mut more_components = components.clone();
+            more_components.push(&[]);
+            let mut o_in_out = ct.clone();
+            assert!(
+                aead::aes_siv::open_in_place(&o_key, &more_components, 0, &mut o_in_out).is_err()
+            );
+
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn test_aead_aes_siv_cmac_misuse() {
+    let key_bytes = [0x42; 64];
+
+    // Too many components.
+    let s_key = aead::SealingKey::new(&aead::AES_256_SIV_CMAC, &key_bytes).unwrap();
+    let o_key = aead::OpeningKey::new(&aead::AES_256_SIV_CMAC, &key_bytes).unwrap();
+    let components = [&[][..]; aead::aes_siv::MAX_ASSOCIATED_DATA_COMPONENTS + 1];
+    let mut in_out = [0u8; 10 + aead::MAX_TAG_LEN];
+    let max = aead::aes_siv::MAX_ASSOCIATED_DATA_COMPONENTS;
+    assert!(aead::aes_siv::seal_in_place(&s_key, &components[..max], &mut in_out, 16).is_ok());
+    assert!(aead::aes_siv::open_in_place(&o_key, &components[..max], 0, &mut in_out).is_ok());
+    assert!(aead::aes_siv::seal_in_place(&s_key, &components, &mut in_out, 16).is_err());
+    assert!(aead::aes_siv::open_in_place(&o_key, &components, 0, &mut in_out).is_err());
+
+    // Other keys can't be used with the AES-SIV functions.
+    #[cfg(feature = "aead-aes-gcm-siv")]
+    {
+        let s_key = aead::SealingKey::new(&aead::AES_256_GCM_SIV, &key_bytes[..32]).unwrap();
+        let o_key = aead::OpeningKey::new(&aead::AES_256_GCM_SIV, &key_bytes[..32]).unwrap();
+        assert!(aead::aes_siv::seal_in_place(&s_key, &[], &mut in_out, 16).is_err());
+        assert!(aead::aes_siv::open_in_place(&o_key, &[], 0, &mut in_out).is_err());
+    }
+}
+
+#[test]
+fn test_aead_open_batch() {
+    // Records of various lengths, some consecutive ones sharing a nonce.
+    const RECORDS: &[(u8, usize)] = &[
+        (1, 0),
+        (2, 1),
+        (2, 16),
+        (2, 17),
+        (3, 40),
+        (4, 5),
+        (4, 64),
+        (5, 100),
+        (1, 33),
+    ];
+
+    let aead_algs: &[&aead::Algorithm] = &[
+        &aead::AES_128_GCM,
+        &aead::AES_256_GCM,
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        &aead::AES_128_GCM_SIV,
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        &aead::AES_256_GCM_SIV,
+        &aead::AES_128_SIV_CMAC,
+        #[cfg(feature = "aead-chacha")]
+        &aead::CHACHA20_POLY1305,
+    ];
+    for aead_alg in aead_algs {
+        let key_bytes = vec![0x42; aead_alg.key_len()];
+        let s_key = aead::SealingKey::new(aead_alg, &key_bytes).unwrap();
+        let o_key = aead::OpeningKey::new(aead_alg, &key_bytes).unwrap();
+
+        let plaintexts = RECORDS
+            .iter()
+            .map(|&(nonce, len)| vec![nonce; len])
+            .collect::<Vec<_>>();
+        let mut in_outs = Vec::new();
+        for (&(nonce, _), plaintext) in RECORDS.iter().zip(plaintexts.iter()) {
+            let mut in_out = plaintext.clone();
+            in_out.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+            let ad = [nonce; 3];
+            let nonce = aead::Nonce::assume_unique_for_key([nonce; aead::NONCE_LEN]);
+            let _ = aead::seal_in_place(
+                &s_key,
+                nonce,
+                aead::Aad::from(&ad),
+                &mut in_out,
+                aead::MAX_TAG_LEN,
+            )
+            .unwrap();
+            in_outs.push(in_out);
+        }
+
+        let mut records = Vec::new();
+        for (&(nonce, _), in_out) in RECORDS.iter().zip(in_outs.iter_mut()) {
+            let ad = aead::Aad::from([nonce; 3]);
+            let nonce = aead::Nonce::assume_unique_for_key([nonce; aead::NONCE_LEN]);
+            records.push((nonce, ad, &mut in_out[..]));
+        }
+        assert!(aead::open_batch(&o_key, &mut records).is_ok());
+        for (in_out, plaintext) in in_outs.iter().zip(plaintexts.iter()) {
+            assert_eq!(&in_out[..plaintext.len()], &plaintext[..]);
+        }
+
+        // Records that are too short are rejected.
+        let mut short = [0u8; aead::MAX_TAG_LEN - 1];
+        let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+        let mut records = [(nonce, aead::Aad::empty(), &mut short[..])];
+        assert!(aead::open_batch(&o_key, &mut records).is_err());
+    }
+}
+
 #[test]
 fn test_aead_key_debug() {
     let key_bytes = [0; 32];
@@ -321,9 +1268,735 @@ fn test_aead_key_debug() {
         format!("{:?}", key)
     );
 
-    let key = aead::SealingKey::new(&aead::CHACHA20_POLY1305, &key_bytes).unwrap();
+    #[cfg(feature = "aead-chacha")]
+    {
+        let key = aead::SealingKey::new(&aead::CHACHA20_POLY1305, &key_bytes).unwrap();
+        assert_eq!(
+            "SealingKey { key: Key { algorithm: CHACHA20_POLY1305 } }",
+            format!("{:?}", key)
+        );
+    }
+}
+
+#[cfg(feature = "use_heap")]
+#[test]
+fn test_aead_key_clone() {
+    let key_bytes = [0x42; 32];
+    let opening_key = aead::OpeningKey::new(&aead::AES_256_GCM, &key_bytes).unwrap();
+    let sealing_key = aead::SealingKey::new(&aead::AES_256_GCM, &key_bytes).unwrap();
+
+    // A clone can be used on another thread while the original is still in
+    // use.
+    let cloned_sealing_key = sealing_key.clone();
+    let sealed = std::thread::spawn(move || {
+        let mut in_out = b"hello".to_vec();
+        in_out.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+        let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+        let len = aead::seal_in_place(
+            &cloned_sealing_key,
+            nonce,
+            aead::Aad::empty(),
+            &mut in_out,
+            aead::MAX_TAG_LEN,
+        )
+        .unwrap();
+        in_out.truncate(len);
+        in_out
+    })
+    .join()
+    .unwrap();
+
+    let mut in_out = b"hello".to_vec();
+    in_out.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+    let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+    let _ = aead::seal_in_place(
+        &sealing_key,
+        nonce,
+        aead::Aad::empty(),
+        &mut in_out,
+        aead::MAX_TAG_LEN,
+    )
+    .unwrap();
+    assert_eq!(sealed, in_out);
+
+    let cloned_opening_key = opening_key.clone();
+    let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+    let opened =
+        aead::open_in_place(&cloned_opening_key, nonce, aead::Aad::empty(), 0, &mut in_out)
+            .unwrap();
+    assert_eq!(opened, b"hello");
+}
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+#[test]
+fn test_aead_stream_long() {
+    // Long enough for the wide kernels, with chunks that split blocks.
+    let plaintext: Vec<u8> = (0..5000).map(|i| i as u8).collect();
+    let ad = [0x5a; 40];
+    for aead_alg in &[&aead::AES_128_GCM_SIV, &aead::AES_256_GCM_SIV] {
+        let key_bytes = vec![0x42; aead_alg.key_len()];
+        let nonce = [7u8; aead::NONCE_LEN];
+        let s_key = aead::SealingKey::new(aead_alg, &key_bytes).unwrap();
+        let mut ct = plaintext.clone();
+        ct.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+        let len = aead::seal_in_place(
+            &s_key,
+            aead::Nonce::assume_unique_for_key(nonce),
+            aead::Aad::from(&ad[..]),
+            &mut ct,
+            aead::MAX_TAG_LEN,
+        )
+        .unwrap();
+        assert_eq!(len, ct.len());
+        test_aead_stream(aead_alg, &key_bytes, &nonce, &ad, &plaintext, &ct).unwrap();
+    }
+}
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+#[test]
+fn test_aead_stream_other_algorithms() {
+    let key_bytes = [0x42; 32];
+    let aead_algs: &[&aead::Algorithm] = &[
+        &aead::AES_256_GCM,
+        &aead::AES_128_SIV_CMAC,
+        #[cfg(feature = "aead-chacha")]
+        &aead::CHACHA20_POLY1305,
+        #[cfg(feature = "aead-chacha")]
+        &aead::XCHACHA20_POLY1305,
+    ];
+    for aead_alg in aead_algs {
+        let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+        let s_key = aead::SealingKey::new(aead_alg, &key_bytes).unwrap();
+        assert!(aead::SealingContext::new(&s_key, nonce, aead::Aad::empty()).is_err());
+
+        let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+        let o_key = aead::OpeningKey::new(aead_alg, &key_bytes).unwrap();
+        let tag = [0; aead::MAX_TAG_LEN];
+        assert!(aead::OpeningContext::new(&o_key, nonce, aead::Aad::empty(), &tag).is_err());
+    }
+}
+
+#[cfg(feature = "aead_parallel")]
+#[test]
+fn test_aead_parallel() {
+    // Long enough to be split across threads, ending with a partial block.
+    let plaintext: Vec<u8> = (0..((4 << 20) + 5)).map(|i| (i * 7) as u8).collect();
+    let ad = [0x5a; 40];
+    for aead_alg in &[
+        &aead::AES_128_GCM_SIV,
+        &aead::AES_256_GCM_SIV,
+        &aead::AES_256_GCM,
+        &aead::CHACHA20_POLY1305,
+    ] {
+        let key_bytes = vec![0x42; aead_alg.key_len()];
+        let nonce = [7u8; aead::NONCE_LEN];
+        let s_key = aead::SealingKey::new(aead_alg, &key_bytes).unwrap();
+        let o_key = aead::OpeningKey::new(aead_alg, &key_bytes).unwrap();
+
+        let mut expected = plaintext.clone();
+        expected.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+        let _ = aead::seal_in_place(
+            &s_key,
+            aead::Nonce::assume_unique_for_key(nonce),
+            aead::Aad::from(&ad[..]),
+            &mut expected,
+            aead::MAX_TAG_LEN,
+        )
+        .unwrap();
+
+        let mut ct = plaintext.clone();
+        ct.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+        let len = aead::seal_in_place_parallel(
+            &s_key,
+            aead::Nonce::assume_unique_for_key(nonce),
+            aead::Aad::from(&ad[..]),
+            &mut ct,
+            aead::MAX_TAG_LEN,
+        )
+        .unwrap();
+        assert_eq!(len, ct.len());
+        assert!(ct == expected);
+
+        for &in_prefix_len in &[0, 3] {
+            let mut in_out = vec![0xff; in_prefix_len];
+            in_out.extend_from_slice(&ct);
+            let opened = aead::open_in_place_parallel(
+                &o_key,
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::from(&ad[..]),
+                in_prefix_len,
+                &mut in_out,
+            )
+            .unwrap();
+            assert!(opened == &plaintext[..]);
+        }
+
+        let mut tampered = ct.clone();
+        tampered[1 << 20] ^= 1;
+        assert!(aead::open_in_place_parallel(
+            &o_key,
+            aead::Nonce::assume_unique_for_key(nonce),
+            aead::Aad::from(&ad[..]),
+            0,
+            &mut tampered,
+        )
+        .is_err());
+    }
+}
+
+#[test]
+fn test_aead_counter_nonce_sequence() {
+    use aead::NonceSequence;
+
+    let prefix = [0xa0, 0xa1, 0xa2, 0xa3];
+    let mut nonces = aead::CounterNonceSequence::new(prefix, 0x0102, aead::Endianness::Big);
+    assert_eq!(
+        nonces.advance().unwrap().as_ref(),
+        &[0xa0, 0xa1, 0xa2, 0xa3, 0, 0, 0, 0, 0, 0, 0x01, 0x02]
+    );
+    assert_eq!(
+        nonces.advance().unwrap().as_ref(),
+        &[0xa0, 0xa1, 0xa2, 0xa3, 0, 0, 0, 0, 0, 0, 0x01, 0x03]
+    );
+
+    let mut nonces = aead::CounterNonceSequence::new(prefix, 0x0102, aead::Endianness::Little);
     assert_eq!(
-        "SealingKey { key: Key { algorithm: CHACHA20_POLY1305 } }",
-        format!("{:?}", key)
+        nonces.advance().unwrap().as_ref(),
+        &[0xa0, 0xa1, 0xa2, 0xa3, 0x02, 0x01, 0, 0, 0, 0, 0, 0]
+    );
+
+    // The sequence is exhausted instead of wrapping around.
+    let mut nonces =
+        aead::CounterNonceSequence::new(prefix, u64::max_value() - 1, aead::Endianness::Big);
+    assert!(nonces.advance().is_ok());
+    assert_eq!(
+        nonces.advance().unwrap().as_ref(),
+        &[0xa0, 0xa1, 0xa2, 0xa3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+    );
+    assert!(nonces.advance().is_err());
+    assert!(nonces.advance().is_err());
+}
+
+#[test]
+fn test_aead_random_nonce_sequence() {
+    use aead::NonceSequence;
+
+    let mut nonces = aead::RandomNonceSequence::new();
+    let a = *nonces.advance().unwrap().as_ref();
+    let b = *nonces.advance().unwrap().as_ref();
+    assert_ne!(a, b);
+}
+
+#[cfg(feature = "aead-chacha")]
+#[test]
+fn test_aead_limited_nonce_sequence() {
+    use aead::NonceSequence;
+
+    let key_bytes = [0x42; 32];
+    let s_key = aead::SealingKey::new(&aead::CHACHA20_POLY1305, &key_bytes).unwrap();
+    let mut nonces = aead::LimitedNonceSequence::new(
+        aead::CounterNonceSequence::new([0; 4], 0, aead::Endianness::Big),
+        2,
+    );
+    for remaining in (0..2).rev() {
+        let mut in_out = [0u8; aead::MAX_TAG_LEN];
+        let _ = aead::seal_in_place(
+            &s_key,
+            nonces.advance().unwrap(),
+            aead::Aad::empty(),
+            &mut in_out,
+            aead::MAX_TAG_LEN,
+        )
+        .unwrap();
+        assert_eq!(nonces.remaining(), remaining);
+    }
+    assert!(nonces.advance().is_err());
+
+    let mut nonces = aead::LimitedNonceSequence::new(aead::RandomNonceSequence::new(), 0);
+    assert!(nonces.advance().is_err());
+}
+
+#[test]
+fn test_aead_bounded_use_key() {
+    let key_bytes = [0x42; 32];
+    let o_key = aead::OpeningKey::new(&aead::AES_256_GCM, &key_bytes).unwrap();
+    let mut key = aead::BoundedUseKey::new(&aead::AES_256_GCM, &key_bytes, 2).unwrap();
+    assert_eq!(key.algorithm(), &aead::AES_256_GCM);
+
+    let nonce = |n: u8| aead::Nonce::assume_unique_for_key([n; aead::NONCE_LEN]);
+
+    let mut in_out = *b"message\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0";
+    let out_len = key
+        .seal_in_place(nonce(1), aead::Aad::empty(), &mut in_out, aead::MAX_TAG_LEN)
+        .unwrap();
+    assert_eq!(key.remaining(), 1);
+    let plaintext = aead::open_in_place(
+        &o_key,
+        nonce(1),
+        aead::Aad::empty(),
+        0,
+        &mut in_out[..out_len],
+    )
+    .unwrap();
+    assert_eq!(plaintext, b"message");
+
+    let mut in_out = *b"message";
+    let tag = key
+        .seal_in_place_detached(nonce(2), aead::Aad::empty(), &mut in_out)
+        .unwrap();
+    assert_eq!(key.remaining(), 0);
+    let plaintext = aead::open_in_place_detached(
+        &o_key,
+        nonce(2),
+        aead::Aad::empty(),
+        tag.as_ref(),
+        &mut in_out,
+    )
+    .unwrap();
+    assert_eq!(plaintext, b"message");
+
+    let mut in_out = [0u8; aead::MAX_TAG_LEN];
+    assert!(key
+        .seal_in_place(nonce(3), aead::Aad::empty(), &mut in_out, aead::MAX_TAG_LEN)
+        .is_err());
+    assert!(key
+        .seal_in_place_detached(nonce(3), aead::Aad::empty(), &mut [])
+        .is_err());
+    assert_eq!(key.remaining(), 0);
+}
+
+#[test]
+fn test_aead_bounded_use_key_recommended_limit() {
+    let key_bytes = [0x42; 64];
+    let supported: &[(&aead::Algorithm, u64)] = &[
+        (&aead::AES_128_GCM, 1 << 32),
+        (&aead::AES_256_GCM, 1 << 32),
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        (&aead::AES_128_GCM_SIV, 1 << 48),
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        (&aead::AES_256_GCM_SIV, 1 << 48),
+        (&aead::AES_128_SIV_CMAC, 1 << 48),
+        (&aead::AES_256_SIV_CMAC, 1 << 48),
+        #[cfg(feature = "aead-chacha")]
+        (&aead::CHACHA20_POLY1305, 1 << 32),
+    ];
+    for &(aead_alg, limit) in supported {
+        let key_bytes = &key_bytes[..aead_alg.key_len()];
+        assert_eq!(
+            aead::BoundedUseKey::recommended_limit(aead_alg).unwrap(),
+            limit
+        );
+        let mut key = aead::BoundedUseKey::with_recommended_limit(aead_alg, key_bytes).unwrap();
+        assert_eq!(key.remaining(), limit);
+
+        let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+        let mut in_out = [0u8; aead::MAX_TAG_LEN];
+        assert!(key
+            .seal_in_place(nonce, aead::Aad::empty(), &mut in_out, aead::MAX_TAG_LEN)
+            .is_ok());
+        assert_eq!(key.remaining(), limit - 1);
+    }
+
+    let unsupported: &[&aead::Algorithm] = &[
+        &aead::AEGIS_128L,
+        &aead::AEGIS_256,
+        &aead::ASCON_128,
+        #[cfg(feature = "aead-chacha")]
+        &aead::XCHACHA20_POLY1305,
+    ];
+    for aead_alg in unsupported {
+        let key_bytes = &key_bytes[..aead_alg.key_len()];
+        assert!(aead::BoundedUseKey::recommended_limit(aead_alg).is_err());
+        assert!(aead::BoundedUseKey::new(aead_alg, key_bytes, 1).is_err());
+        assert!(aead::BoundedUseKey::with_recommended_limit(aead_alg, key_bytes).is_err());
+    }
+}
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+#[test]
+fn test_aead_key_serialize() {
+    let key_bytes = [0x42; 32];
+    for aead_alg in &[&aead::AES_128_GCM_SIV, &aead::AES_256_GCM_SIV] {
+        let key_bytes = &key_bytes[..aead_alg.key_len()];
+        let s_key = aead::SealingKey::new(aead_alg, key_bytes).unwrap();
+        let serialized = s_key.serialize().unwrap();
+        assert_eq!(serialized.as_ref().len(), aead::SERIALIZED_KEY_LEN);
+
+        let seal = |s_key: &aead::SealingKey| {
+            let mut in_out = b"hello".to_vec();
+            in_out.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+            let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+            let len = aead::seal_in_place(
+                s_key,
+                nonce,
+                aead::Aad::empty(),
+                &mut in_out,
+                aead::MAX_TAG_LEN,
+            )
+            .unwrap();
+            in_out.truncate(len);
+            in_out
+        };
+        let mut sealed = seal(&s_key);
+        let restored = aead::SealingKey::deserialize(aead_alg, serialized.as_ref()).unwrap();
+        assert_eq!(seal(&restored), sealed);
+
+        // A serialized sealing key can be restored as an opening key.
+        let o_key = aead::OpeningKey::deserialize(aead_alg, serialized.as_ref()).unwrap();
+        assert_eq!(o_key.serialize().unwrap().as_ref(), serialized.as_ref());
+        let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+        let opened =
+            aead::open_in_place(&o_key, nonce, aead::Aad::empty(), 0, &mut sealed).unwrap();
+        assert_eq!(opened, b"hello");
+    }
+
+    // Keys of the other algorithm, or of different versions or lengths, are
+    // rejected.
+    let serialized = aead::SealingKey::new(&aead::AES_128_GCM_SIV, &key_bytes[..16])
+        .unwrap()
+        .serialize()
+        .unwrap();
+    assert!(aead::SealingKey::deserialize(&aead::AES_256_GCM_SIV, serialized.as_ref()).is_err());
+    let mut bad = serialized.as_ref().to_vec();
+    bad[0] ^= 1;
+    assert!(aead::SealingKey::deserialize(&aead::AES_128_GCM_SIV, &bad).is_err());
+    assert!(aead::SealingKey::deserialize(
+        &aead::AES_128_GCM_SIV,
+        &serialized.as_ref()[..(aead::SERIALIZED_KEY_LEN - 1)]
+    )
+    .is_err());
+
+    let aead_algs: &[&aead::Algorithm] = &[
+        &aead::AES_256_GCM,
+        #[cfg(feature = "aead-chacha")]
+        &aead::CHACHA20_POLY1305,
+        #[cfg(feature = "aead-chacha")]
+        &aead::XCHACHA20_POLY1305,
+    ];
+    for aead_alg in aead_algs {
+        let s_key = aead::SealingKey::new(aead_alg, &key_bytes).unwrap();
+        assert!(s_key.serialize().is_err());
+        assert!(aead::SealingKey::deserialize(aead_alg, serialized.as_ref()).is_err());
+    }
+}
+
+#[cfg(all(feature = "use_heap", feature = "aead-aes-gcm-siv"))]
+#[test]
+fn test_aead_key_ring() {
+    let seal = |key_ring: &aead::KeyRing| {
+        let mut in_out = vec![0; aead::KEY_ID_LEN];
+        in_out.extend_from_slice(b"hello");
+        in_out.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+        let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+        let len = key_ring
+            .seal_in_place(
+                nonce,
+                aead::Aad::from(b"aad"),
+                &mut in_out,
+                aead::MAX_TAG_LEN,
+            )
+            .unwrap();
+        in_out.truncate(len);
+        in_out
+    };
+    let open = |key_ring: &aead::KeyRing, sealed: &[u8]| {
+        let mut in_out = sealed.to_vec();
+        let nonce = aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+        key_ring
+            .open_in_place(nonce, aead::Aad::from(b"aad"), &mut in_out)
+            .map(|plaintext| plaintext.to_vec())
+    };
+
+    let mut key_ring = aead::KeyRing::new(&aead::AES_256_GCM_SIV);
+    assert_eq!(key_ring.sealing_key_id(), None);
+    assert!(key_ring
+        .seal_in_place(
+            aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]),
+            aead::Aad::empty(),
+            &mut [0; aead::KEY_ID_LEN + aead::MAX_TAG_LEN],
+            aead::MAX_TAG_LEN,
+        )
+        .is_err());
+
+    key_ring.add(1, &[1; 32]).unwrap();
+    let sealed_1 = seal(&key_ring);
+    assert_eq!(&sealed_1[..aead::KEY_ID_LEN], &[0, 0, 0, 1]);
+
+    // The output after the key ID is the same as that of the key alone.
+    let mut expected = b"hello".to_vec();
+    expected.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+    let len = aead::seal_in_place(
+        &aead::SealingKey::new(&aead::AES_256_GCM_SIV, &[1; 32]).unwrap(),
+        aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]),
+        aead::Aad::from(b"aad"),
+        &mut expected,
+        aead::MAX_TAG_LEN,
+    )
+    .unwrap();
+    assert_eq!(&sealed_1[aead::KEY_ID_LEN..], &expected[..len]);
+
+    // The newest key is used for sealing, and older keys can still open.
+    key_ring.add(2, &[2; 32]).unwrap();
+    assert_eq!(key_ring.sealing_key_id(), Some(2));
+    let sealed_2 = seal(&key_ring);
+    assert_eq!(&sealed_2[..aead::KEY_ID_LEN], &[0, 0, 0, 2]);
+    assert_eq!(open(&key_ring, &sealed_1).unwrap(), b"hello");
+    assert_eq!(open(&key_ring, &sealed_2).unwrap(), b"hello");
+
+    // Key IDs are unique.
+    assert!(key_ring.add(1, &[3; 32]).is_err());
+    assert!(key_ring.add(3, &[3; 16]).is_err());
+    assert_eq!(key_ring.sealing_key_id(), Some(2));
+
+    // Changing the key ID selects a different key, which fails to open.
+    let mut tampered = sealed_2.clone();
+    tampered[aead::KEY_ID_LEN - 1] = 1;
+    assert!(open(&key_ring, &tampered).is_err());
+    tampered[aead::KEY_ID_LEN - 1] = 3;
+    assert!(open(&key_ring, &tampered).is_err());
+    assert!(open(&key_ring, &sealed_2[..(aead::KEY_ID_LEN - 1)]).is_err());
+
+    // Removed keys can't open anymore.
+    key_ring.remove(1).unwrap();
+    assert!(!key_ring.contains(1));
+    assert!(key_ring.remove(1).is_err());
+    assert!(open(&key_ring, &sealed_1).is_err());
+    assert_eq!(open(&key_ring, &sealed_2).unwrap(), b"hello");
+
+    assert_eq!(
+        "KeyRing { algorithm: AES_256_GCM_SIV, sealing_key_id: Some(2) }",
+        format!("{:?}", key_ring)
+    );
+}
+
+#[cfg(all(feature = "use_heap", feature = "aead-aes-gcm-siv"))]
+#[test]
+fn test_aead_key_ring_derived() {
+    use ring::{digest, hkdf};
+
+    let salt = hkdf::Salt::new(&digest::SHA256, b"salt");
+    let prk = salt.extract(b"fleet secret");
+
+    let mut sealer = aead::KeyRing::new(&aead::AES_128_GCM_SIV);
+    sealer.add_derived(7, &prk).unwrap();
+    assert!(sealer.add_derived(7, &prk).is_err());
+
+    // Another member of the fleet derives the same key for the same key ID.
+    let mut opener = aead::KeyRing::new(&aead::AES_128_GCM_SIV);
+    opener.add_derived(6, &prk).unwrap();
+    opener.add_derived(7, &prk).unwrap();
+
+    let mut key_bytes = [0; 16];
+    prk.fill(&7u32.to_be_bytes(), &mut key_bytes).unwrap();
+    let mut explicit = aead::KeyRing::new(&aead::AES_128_GCM_SIV);
+    explicit.add(7, &key_bytes).unwrap();
+
+    let mut in_out = vec![0; aead::KEY_ID_LEN];
+    in_out.extend_from_slice(b"hello");
+    in_out.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+    let nonce = aead::Nonce::assume_unique_for_key([2; aead::NONCE_LEN]);
+    let len = sealer
+        .seal_in_place(nonce, aead::Aad::empty(), &mut in_out, aead::MAX_TAG_LEN)
+        .unwrap();
+    in_out.truncate(len);
+
+    for key_ring in &[&opener, &explicit] {
+        let mut in_out = in_out.clone();
+        let nonce = aead::Nonce::assume_unique_for_key([2; aead::NONCE_LEN]);
+        let plaintext = key_ring
+            .open_in_place(nonce, aead::Aad::empty(), &mut in_out)
+            .unwrap();
+        assert_eq!(plaintext, b"hello");
+    }
+}
+
+#[test]
+fn aead_algorithm_registry() {
+    let mut expected_len = 7;
+    if cfg!(feature = "aead-aes-gcm-siv") {
+        expected_len += 2;
+    }
+    if cfg!(feature = "aead-chacha") {
+        expected_len += 2;
+    }
+    assert_eq!(aead::Algorithm::all().len(), expected_len);
+    for &alg in aead::Algorithm::all() {
+        let name = format!("{:?}", alg);
+        assert_eq!(aead::Algorithm::from_name(&name), Some(alg));
+    }
+    assert_eq!(aead::Algorithm::from_name("aes_128_gcm"), None);
+    assert_eq!(aead::Algorithm::from_name(""), None);
+
+    for &(id, expected) in &[
+        (0x1301, &aead::AES_128_GCM),
+        (0x1302, &aead::AES_256_GCM),
+        #[cfg(feature = "aead-chacha")]
+        (0x1303, &aead::CHACHA20_POLY1305),
+    ] {
+        assert_eq!(aead::Algorithm::from_tls_id(id), Some(expected));
+    }
+    assert_eq!(aead::Algorithm::from_tls_id(0x1304), None); // TLS_AES_128_CCM_SHA256
+    assert_eq!(aead::Algorithm::from_tls_id(0), None);
+}
+
+#[test]
+fn test_aead_input_len_limits() {
+    let tests: &[(&aead::Algorithm, u64, u64)] = &[
+        (&aead::AES_128_GCM, 68_719_476_704, (1 << 61) - 1),
+        (&aead::AES_256_GCM, 68_719_476_704, (1 << 61) - 1),
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        (&aead::AES_128_GCM_SIV, 68_719_476_704, 1 << 36),
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        (&aead::AES_256_GCM_SIV, 68_719_476_704, 1 << 36),
+        (&aead::AES_128_SIV_CMAC, 34_359_738_368, u64::max_value()),
+        (&aead::AES_256_SIV_CMAC, 34_359_738_368, u64::max_value()),
+        (&aead::AEGIS_128L, (1 << 61) - 1, (1 << 61) - 1),
+        (&aead::AEGIS_256, (1 << 61) - 1, (1 << 61) - 1),
+        (&aead::ASCON_128, u64::max_value(), u64::max_value()),
+        #[cfg(feature = "aead-chacha")]
+        (&aead::CHACHA20_POLY1305, 274_877_906_880, u64::max_value()),
+        #[cfg(feature = "aead-chacha")]
+        (&aead::XCHACHA20_POLY1305, 274_877_906_880, u64::max_value()),
+    ];
+    for &(alg, max_input_len, max_aad_len) in tests.iter() {
+        assert_eq!(alg.max_input_len(), max_input_len);
+        assert_eq!(alg.max_aad_len(), max_aad_len);
+        assert_eq!(alg.check_input_lens(0, 0), Ok(()));
+
+        #[cfg(target_pointer_width = "64")]
+        {
+            let max_input_len = max_input_len as usize;
+            assert_eq!(alg.check_input_lens(0, max_input_len), Ok(()));
+            if max_input_len == usize::max_value() {
+                // Neither length is limited.
+                assert_eq!(alg.check_input_lens(usize::max_value(), 0), Ok(()));
+                continue;
+            }
+            let err = alg.check_input_lens(0, max_input_len + 1).unwrap_err();
+            assert_eq!(err.what(), aead::TooLongInput::InOut);
+            assert_eq!(err.max(), alg.max_input_len());
+
+            if max_aad_len < u64::max_value() {
+                let max_aad_len = max_aad_len as usize;
+                assert_eq!(alg.check_input_lens(max_aad_len, 0), Ok(()));
+                let err = alg.check_input_lens(max_aad_len + 1, 0).unwrap_err();
+                assert_eq!(err.what(), aead::TooLongInput::Aad);
+                assert_eq!(err.max(), alg.max_aad_len());
+
+                // The AAD is checked first.
+                let err = alg
+                    .check_input_lens(max_aad_len + 1, max_input_len + 1)
+                    .unwrap_err();
+                assert_eq!(err.what(), aead::TooLongInput::Aad);
+            } else {
+                assert_eq!(alg.check_input_lens(usize::max_value(), 0), Ok(()));
+            }
+        }
+    }
+
+    #[cfg(all(target_pointer_width = "64", feature = "aead-aes-gcm-siv"))]
+    {
+        let err = aead::AES_128_GCM_SIV
+            .check_input_lens(0, usize::max_value())
+            .unwrap_err();
+        assert_eq!(
+            format!("{}", err),
+            "ring::aead::TooLong { what: InOut, max: 68719476704 }"
+        );
+        let _: error::Unspecified = err.into();
+    }
+}
+
+#[cfg(feature = "aead-chacha")]
+#[test]
+fn test_aead_detailed_errors() {
+    let key_bytes = [0x42; 32];
+    let nonce = || aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]);
+    let s_key = aead::SealingKey::new(&aead::CHACHA20_POLY1305, &key_bytes).unwrap();
+    let o_key = aead::OpeningKey::new(&aead::CHACHA20_POLY1305, &key_bytes).unwrap();
+
+    let mut in_out = [0u8; 5 + aead::MAX_TAG_LEN];
+    assert_eq!(
+        aead::seal_in_place_detailed(&s_key, nonce(), aead::Aad::empty(), &mut in_out, 15)
+            .unwrap_err()
+            .description_(),
+        "InvalidSuffixCapacity"
     );
+    let out_len =
+        aead::seal_in_place_detailed(&s_key, nonce(), aead::Aad::empty(), &mut in_out, 16).unwrap();
+    assert_eq!(out_len, in_out.len());
+
+    // Every kind of tampering is reported the same way.
+    for tampered in &[0, 4, 5, in_out.len() - 1] {
+        let mut in_out = in_out;
+        in_out[*tampered] ^= 1;
+        let err = aead::open_in_place_detailed(&o_key, nonce(), aead::Aad::empty(), 0, &mut in_out)
+            .unwrap_err();
+        assert_eq!(err.description_(), "AuthenticationFailed");
+        assert_eq!(format!("{}", err), "AuthenticationFailed");
+    }
+    let err = aead::open_in_place_detailed(
+        &o_key,
+        nonce(),
+        aead::Aad::from(b"aad"),
+        0,
+        &mut in_out.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err.description_(), "AuthenticationFailed");
+
+    let err = aead::open_in_place_detailed(
+        &o_key,
+        nonce(),
+        aead::Aad::empty(),
+        0,
+        &mut in_out[..aead::MAX_TAG_LEN - 1],
+    )
+    .unwrap_err();
+    assert_eq!(err.description_(), "TooShort");
+    let _: error::Unspecified = err.into();
+
+    let plaintext =
+        aead::open_in_place_detailed(&o_key, nonce(), aead::Aad::empty(), 0, &mut in_out).unwrap();
+    assert_eq!(plaintext, &[0u8; 5][..]);
+
+    // XChaCha20-Poly1305 keys need the extended nonces of `xchacha20_poly1305`.
+    let x_key = aead::OpeningKey::new(&aead::XCHACHA20_POLY1305, &key_bytes).unwrap();
+    let err = aead::open_in_place_detailed(&x_key, nonce(), aead::Aad::empty(), 0, &mut in_out)
+        .unwrap_err();
+    assert_eq!(err.description_(), "WrongNonceType");
+}
+
+#[cfg(feature = "aead-aes-gcm-siv")]
+#[test]
+fn test_aead_per_message_keys() {
+    use aead::per_message::MasterKey;
+
+    let master_key_bytes: Vec<u8> = (0..32).collect();
+    let master_key = MasterKey::new(&aead::AES_256_GCM_SIV, &master_key_bytes).unwrap();
+    assert_eq!(master_key.algorithm(), &aead::AES_256_GCM_SIV);
+
+    let (key, nonce) = master_key.sealing_key(b"message 1").unwrap();
+    let mut in_out = b"hello".to_vec();
+    in_out.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+    let len = aead::seal_in_place(&key, nonce, aead::Aad::empty(), &mut in_out, 16).unwrap();
+    assert_eq!(
+        &in_out[..len],
+        &test::from_hex("b29e918fd4d22a3fe9799211ef25986f34a3906809").unwrap()[..]
+    );
+
+    let (key, nonce) = master_key.opening_key(b"message 2").unwrap();
+    let mut wrong_id = in_out.clone();
+    assert!(aead::open_in_place(&key, nonce, aead::Aad::empty(), 0, &mut wrong_id).is_err());
+
+    let (key, nonce) = master_key.opening_key(b"message 1").unwrap();
+    let plaintext = aead::open_in_place(&key, nonce, aead::Aad::empty(), 0, &mut in_out).unwrap();
+    assert_eq!(plaintext, &b"hello"[..]);
+
+    assert!(MasterKey::new(&aead::AES_256_GCM_SIV, &master_key_bytes[..16]).is_err());
+    #[cfg(feature = "aead-chacha")]
+    {
+        assert!(MasterKey::new(&aead::XCHACHA20_POLY1305, &master_key_bytes).is_err());
+        assert!(MasterKey::new(&aead::CHACHA20_POLY1305, &master_key_bytes).is_ok());
+    }
 }
@@ -0,0 +1,122 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{aes_cmac, test, test_file};
+
+#[test]
+fn aes_cmac_tests() {
+    test::run(test_file!("aes_cmac_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let algorithm = match test_case.consume_string("Algorithm").as_str() {
+            "AES_128" => &aes_cmac::AES_128,
+            "AES_256" => &aes_cmac::AES_256,
+            _ => unreachable!(),
+        };
+        let key_value = test_case.consume_bytes("Key");
+        let mut input = test_case.consume_bytes("Input");
+        let output = test_case.consume_bytes("Output");
+
+        let key = aes_cmac::Key::new(algorithm, &key_value)?;
+
+        aes_cmac_test_case_inner(&key, &input, &output, true);
+
+        // Tamper with the input and check that verification fails.
+        if input.is_empty() {
+            input.push(0);
+        } else {
+            input[0] ^= 1;
+        }
+
+        aes_cmac_test_case_inner(&key, &input, &output, false);
+
+        Ok(())
+    });
+}
+
+fn aes_cmac_test_case_inner(key: &aes_cmac::Key, input: &[u8], output: &[u8], is_ok: bool) {
+    // One-shot API.
+    {
+        let tag = aes_cmac::sign(key, input);
+        assert_eq!(is_ok, tag.as_ref() == output);
+        assert_eq!(is_ok, aes_cmac::verify(key, input, output).is_ok());
+    }
+
+    // Multi-part API, byte by byte.
+    {
+        let mut ctx = aes_cmac::Context::with_key(key);
+        for b in input {
+            ctx.update(&[*b]);
+        }
+        let tag = ctx.sign();
+        assert_eq!(is_ok, tag.as_ref() == output);
+    }
+
+    // Multi-part API, a block at a time, with an empty part at the end.
+    {
+        let mut ctx = aes_cmac::Context::with_key(key);
+        for chunk in input.chunks(aes_cmac::TAG_LEN) {
+            ctx.update(chunk);
+        }
+        ctx.update(&[]);
+        let tag = ctx.sign();
+        assert_eq!(is_ok, tag.as_ref() == output);
+    }
+}
+
+#[test]
+fn aes_cmac_key_new() {
+    for &(algorithm, key_len) in &[(&aes_cmac::AES_128, 16), (&aes_cmac::AES_256, 32)] {
+        assert_eq!(algorithm.key_len(), key_len);
+        assert!(aes_cmac::Key::new(algorithm, &vec![0; key_len]).is_ok());
+        assert!(aes_cmac::Key::new(algorithm, &vec![0; key_len - 1]).is_err());
+        assert!(aes_cmac::Key::new(algorithm, &vec![0; key_len + 1]).is_err());
+    }
+    assert!(aes_cmac::Key::new(&aes_cmac::AES_128, &[0; 24]).is_err());
+}
+
+#[test]
+fn aes_cmac_verify_truncated_tag() {
+    let key = aes_cmac::Key::new(&aes_cmac::AES_128, &[0; 16]).unwrap();
+    let tag = aes_cmac::sign(&key, b"hello, world");
+    assert!(aes_cmac::verify(&key, b"hello, world", tag.as_ref()).is_ok());
+    assert!(aes_cmac::verify(&key, b"hello, world", &tag.as_ref()[..8]).is_err());
+}
+
+#[test]
+fn aes_cmac_debug() {
+    let key = aes_cmac::Key::new(&aes_cmac::AES_256, &[0; 32]).unwrap();
+    assert_eq!("Key { algorithm: AES_256_CMAC }", format!("{:?}", &key));
+
+    let ctx = aes_cmac::Context::with_key(&key);
+    assert_eq!("Context { algorithm: AES_256_CMAC }", format!("{:?}", &ctx));
+}
@@ -0,0 +1,122 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{aead::aes_kw, error, test, test_file};
+
+#[test]
+fn aes_kw_tests() {
+    test::run(test_file!("aes_kw_tests.txt"), |section, test_case| {
+        let kek = test_case.consume_bytes("KEK");
+        let wrapped = test_case.consume_bytes("WRAPPED");
+        let error = test_case.consume_optional_string("Error");
+
+        let algorithm = match kek.len() {
+            16 => &aes_kw::AES_128,
+            32 => &aes_kw::AES_256,
+            _ => unreachable!(),
+        };
+        let key = aes_kw::Key::new(algorithm, &kek)?;
+
+        let (wrap, unwrap): (WrapFn, WrapFn) = match section {
+            "KW" => (aes_kw::Key::wrap, aes_kw::Key::unwrap),
+            "KWP" => (
+                aes_kw::Key::wrap_with_padding,
+                aes_kw::Key::unwrap_with_padding,
+            ),
+            _ => unreachable!(),
+        };
+
+        let mut out = vec![0xffu8; wrapped.len() + 8];
+
+        if error.is_some() {
+            assert!(unwrap(&key, &wrapped, &mut out).is_err());
+            assert!(out[..(wrapped.len() - 8)].iter().all(|&b| b == 0));
+            return Ok(());
+        }
+
+        let plaintext = test_case.consume_bytes("KEY");
+
+        let len = wrap(&key, &plaintext, &mut out)?;
+        assert_eq!(&out[..len], &wrapped[..]);
+
+        // The output buffer may not be too short, but may be longer.
+        assert!(wrap(&key, &plaintext, &mut out[..(wrapped.len() - 1)]).is_err());
+        assert!(unwrap(&key, &wrapped, &mut out[..(wrapped.len() - 9)]).is_err());
+
+        let len = unwrap(&key, &wrapped, &mut out)?;
+        assert_eq!(&out[..len], &plaintext[..]);
+
+        // Any modification is detected.
+        for i in 0..wrapped.len() {
+            let mut tampered = wrapped.clone();
+            tampered[i] ^= 1;
+            assert!(unwrap(&key, &tampered, &mut out).is_err());
+        }
+        assert!(unwrap(&key, &wrapped[..(wrapped.len() - 8)], &mut out).is_err());
+
+        Ok(())
+    });
+}
+
+type WrapFn = fn(&aes_kw::Key, &[u8], &mut [u8]) -> Result<usize, error::Unspecified>;
+
+#[test]
+fn aes_kw_input_lengths() {
+    let key = aes_kw::Key::new(&aes_kw::AES_128, &[0u8; 16]).unwrap();
+    let mut out = [0u8; 64];
+
+    // AES-KW requires at least two semiblocks.
+    assert!(key.wrap(&[], &mut out).is_err());
+    assert!(key.wrap(&[0u8; 8], &mut out).is_err());
+    assert!(key.wrap(&[0u8; 17], &mut out).is_err());
+    assert_eq!(key.wrap(&[0u8; 16], &mut out), Ok(24));
+    assert!(key.unwrap(&out[..16], &mut [0u8; 64]).is_err());
+
+    // AES-KWP requires at least one byte.
+    assert!(key.wrap_with_padding(&[], &mut out).is_err());
+    assert_eq!(key.wrap_with_padding(&[0u8; 1], &mut out), Ok(16));
+    assert_eq!(key.wrap_with_padding(&[0u8; 17], &mut out), Ok(32));
+    assert!(key.unwrap_with_padding(&out[..8], &mut [0u8; 64]).is_err());
+    assert!(key.unwrap_with_padding(&out[..20], &mut [0u8; 64]).is_err());
+
+    // Keys must be exactly the right length.
+    assert!(aes_kw::Key::new(&aes_kw::AES_128, &[0u8; 32]).is_err());
+    assert!(aes_kw::Key::new(&aes_kw::AES_256, &[0u8; 16]).is_err());
+}
+
+#[test]
+fn aes_kw_debug() {
+    let key = aes_kw::Key::new(&aes_kw::AES_256, &[0u8; 32]).unwrap();
+    assert_eq!("AES_256", format!("{:?}", key.algorithm()));
+    assert_eq!("Key { algorithm: AES_256 }", format!("{:?}", key));
+}
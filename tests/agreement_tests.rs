@@ -31,7 +31,7 @@
     warnings
 )]
 
-use ring::{agreement, error, rand, test, test_file};
+use ring::{agreement, agreement::KeyAgreement, error, rand, test, test_file};
 
 #[test]
 fn agreement_agree_ephemeral() {
@@ -46,9 +46,11 @@ fn agreement_agree_ephemeral() {
 
         match test_case.consume_optional_string("Error") {
             None => {
-                let my_private = test_case.consume_bytes("D");
+                let my_private_bytes = test_case.consume_bytes("D");
                 let my_private = {
-                    let rng = test::rand::FixedSliceRandom { bytes: &my_private };
+                    let rng = test::rand::FixedSliceRandom {
+                        bytes: &my_private_bytes,
+                    };
                     agreement::EphemeralPrivateKey::generate(alg, &rng)?
                 };
                 let my_public = test_case.consume_bytes("MyQ");
@@ -64,6 +66,84 @@ fn agreement_agree_ephemeral() {
                     })
                     .is_ok()
                 );
+
+                // The same, through `KeyAgreement`.
+                let my_private = {
+                    let rng = test::rand::FixedSliceRandom {
+                        bytes: &my_private_bytes,
+                    };
+                    agreement::EphemeralPrivateKey::generate(alg, &rng)?
+                };
+                let mut computed_public = [0u8; 97];
+                let len = my_private.compute_public_key_bytes(&mut computed_public)?;
+                assert_eq!(&computed_public[..len], &my_public[..]);
+                let peer_public =
+                    agreement::UnparsedPublicKey::new(alg, &peer_public.bytes()[..]);
+                assert_eq!(peer_public.algorithm(), alg);
+                assert!(KeyAgreement::agree(my_private, &peer_public, (), |key_material| {
+                    assert_eq!(key_material, &output[..]);
+                    Ok(())
+                })
+                .is_ok());
+
+                // The same, with compressed public keys, for the curves that
+                // have them.
+                let my_private = {
+                    let rng = test::rand::FixedSliceRandom {
+                        bytes: &my_private_bytes,
+                    };
+                    agreement::EphemeralPrivateKey::generate(alg, &rng)?
+                };
+                match my_private.compute_compressed_public_key() {
+                    Ok(computed_public) => {
+                        assert_eq!(computed_public.as_ref(), &compress(&my_public)[..]);
+                        let peer_public = agreement::UnparsedPublicKey::new(
+                            alg,
+                            compress(peer_public.bytes()),
+                        );
+                        assert!(agreement::agree_ephemeral(
+                            my_private,
+                            &peer_public,
+                            (),
+                            |key_material| {
+                                assert_eq!(key_material, &output[..]);
+                                Ok(())
+                            }
+                        )
+                        .is_ok());
+                    }
+                    Err(_) => {
+                        assert!(curve_name == "X25519" || curve_name == "X448");
+                    }
+                }
+
+                // The same, with a reusable private key, used twice.
+                let my_private = {
+                    let rng = test::rand::FixedSliceRandom {
+                        bytes: &my_private_bytes,
+                    };
+                    agreement::ReusablePrivateKey::generate(alg, &rng)?
+                };
+                assert_eq!(my_private.algorithm(), alg);
+                let computed_public = my_private.compute_public_key().unwrap();
+                assert_eq!(computed_public.as_ref(), &my_public[..]);
+                for _ in 0..2 {
+                    assert!(agreement::agree_reusable(
+                        &my_private,
+                        &peer_public,
+                        (),
+                        |key_material| {
+                            assert_eq!(key_material, &output[..]);
+                            Ok(())
+                        }
+                    )
+                    .is_ok());
+                }
+                assert!(KeyAgreement::agree(&my_private, &peer_public, (), |key_material| {
+                    assert_eq!(key_material, &output[..]);
+                    Ok(())
+                })
+                .is_ok());
             }
 
             Some(_) => {
@@ -90,6 +170,74 @@ fn agreement_agree_ephemeral() {
     });
 }
 
+#[test]
+fn agreement_reusable_from_pkcs8() {
+    test::run(
+        test_file!("agreement_from_pkcs8_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+
+            let curve_name = test_case.consume_string("Curve");
+            let alg = alg_from_curve_name(&curve_name);
+            let input = test_case.consume_bytes("Input");
+
+            match (
+                agreement::ReusablePrivateKey::from_pkcs8(alg, &input),
+                test_case.consume_optional_string("Error"),
+            ) {
+                (Ok(private_key), None) => {
+                    let public_key = test_case.consume_bytes("PublicKey");
+                    assert_eq!(private_key.algorithm(), alg);
+                    assert_eq!(
+                        private_key.compute_public_key().unwrap().as_ref(),
+                        &public_key[..]
+                    );
+                }
+                (Err(e), None) => panic!("Failed with error \"{}\", but expected to succeed", e),
+                (Ok(_), Some(e)) => panic!("Succeeded, but expected error \"{}\"", e),
+                (Err(actual), Some(expected)) => assert_eq!(actual.to_string(), expected),
+            };
+
+            Ok(())
+        },
+    );
+}
+
+// Verify that, at least, we generate PKCS#8 documents that we can read, and
+// that the keys in them agree with each other.
+#[test]
+fn agreement_reusable_generate_pkcs8() {
+    let rng = rand::SystemRandom::new();
+
+    for alg in &[
+        &agreement::ECDH_P256,
+        &agreement::ECDH_P384,
+        &agreement::ECDH_K256,
+        &agreement::X25519,
+        &agreement::X448,
+    ] {
+        let a = agreement::ReusablePrivateKey::generate_pkcs8(alg, &rng).unwrap();
+        let a = agreement::ReusablePrivateKey::from_pkcs8(alg, a.as_ref()).unwrap();
+        let b = agreement::ReusablePrivateKey::generate_pkcs8(alg, &rng).unwrap();
+        let b = agreement::ReusablePrivateKey::from_pkcs8(alg, b.as_ref()).unwrap();
+
+        let a_public = a.compute_public_key().unwrap();
+        let a_public = agreement::UnparsedPublicKey::new(alg, a_public);
+        let b_public = b.compute_public_key().unwrap();
+        let b_public = agreement::UnparsedPublicKey::new(alg, b_public);
+
+        let ab = agreement::agree_reusable(&a, &b_public, (), |key_material| {
+            Ok(Vec::from(key_material))
+        })
+        .unwrap();
+        let ba = agreement::agree_reusable(&b, &a_public, (), |key_material| {
+            Ok(Vec::from(key_material))
+        })
+        .unwrap();
+        assert_eq!(ab, ba);
+    }
+}
+
 #[test]
 fn test_agreement_ecdh_x25519_rfc_iterated() {
     let mut k = h("0900000000000000000000000000000000000000000000000000000000000000");
@@ -142,14 +290,119 @@ fn test_agreement_ecdh_x25519_rfc_iterated() {
     }
 }
 
+#[test]
+fn test_agreement_x25519_scalar_mult() {
+    use agreement::x25519::{self, LowOrderCheck, ScalarMultError};
+
+    fn scalar_mult(
+        scalar: &str,
+        point: &str,
+        check: LowOrderCheck,
+    ) -> Result<Vec<u8>, ScalarMultError> {
+        let mut scalar_bytes = [0u8; x25519::SCALAR_LEN];
+        scalar_bytes.copy_from_slice(&h(scalar));
+        let mut point_bytes = [0u8; x25519::POINT_LEN];
+        point_bytes.copy_from_slice(&h(point));
+        x25519::scalar_mult(&scalar_bytes, &point_bytes, check).map(|out| out.to_vec())
+    }
+
+    // RFC 7748 Section 5.2.
+    const SCALAR: &str = "a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4";
+    const POINT: &str = "e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c";
+    const OUTPUT: &str = "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552";
+
+    const ZERO: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+    // A point of order 8, with the most significant bit set.
+    const ORDER_8: &str = "5f9c95bca3508c24b1d0b1559c83ef5b04445cc4581c8e86d8224eddd09f11d7";
+
+    for &check in &[
+        LowOrderCheck::None,
+        LowOrderCheck::RejectAllZeroOutput,
+        LowOrderCheck::RejectLowOrderPoints,
+    ] {
+        assert_eq!(scalar_mult(SCALAR, POINT, check), Ok(h(OUTPUT)));
+    }
+
+    for &point in &[ZERO, ORDER_8] {
+        assert_eq!(scalar_mult(SCALAR, point, LowOrderCheck::None), Ok(h(ZERO)));
+        assert_eq!(
+            scalar_mult(SCALAR, point, LowOrderCheck::RejectAllZeroOutput),
+            Err(ScalarMultError::AllZeroOutput)
+        );
+        assert_eq!(
+            scalar_mult(SCALAR, point, LowOrderCheck::RejectLowOrderPoints),
+            Err(ScalarMultError::LowOrderPoint)
+        );
+        assert!(x25519_(&h(SCALAR), &h(point)).is_err());
+    }
+
+    assert_eq!(
+        format!("{}", ScalarMultError::LowOrderPoint),
+        "ring::agreement::x25519::ScalarMultError::LowOrderPoint"
+    );
+}
+
+#[test]
+fn test_agreement_ecdh_x448_rfc_iterated() {
+    let mut k = h(
+        "0500000000000000000000000000000000000000000000000000000000000000\
+         000000000000000000000000000000000000000000000000",
+    );
+    let mut u = k.clone();
+
+    fn expect_iterated_x448(
+        expected_result: &str,
+        range: std::ops::Range<usize>,
+        k: &mut Vec<u8>,
+        u: &mut Vec<u8>,
+    ) {
+        for _ in range {
+            let new_k = x448(k, u);
+            *u = k.clone();
+            *k = new_k;
+        }
+        assert_eq!(&h(expected_result), k);
+    }
+
+    expect_iterated_x448(
+        "3f482c8a9f19b01e6c46ee9711d9dc14fd4bf67af30765c2ae2b846a4d23a8cd\
+         0db897086239492caf350b51f833868b9bc2b3bca9cf4113",
+        0..1,
+        &mut k,
+        &mut u,
+    );
+
+    if cfg!(feature = "slow_tests") {
+        expect_iterated_x448(
+            "aa3b4749d55b9daf1e5b00288826c467274ce3ebbdd5c17b975e09d4af6c67cf\
+             10d087202db88286e2b79fceea3ec353ef54faa26e219f38",
+            1..1_000,
+            &mut k,
+            &mut u,
+        );
+    }
+}
+
+fn x448(private_key: &[u8], public_key: &[u8]) -> Vec<u8> {
+    agree(&agreement::X448, private_key, public_key).unwrap()
+}
+
 fn x25519(private_key: &[u8], public_key: &[u8]) -> Vec<u8> {
     x25519_(private_key, public_key).unwrap()
 }
 
 fn x25519_(private_key: &[u8], public_key: &[u8]) -> Result<Vec<u8>, error::Unspecified> {
+    agree(&agreement::X25519, private_key, public_key)
+}
+
+fn agree(
+    alg: &'static agreement::Algorithm,
+    private_key: &[u8],
+    public_key: &[u8],
+) -> Result<Vec<u8>, error::Unspecified> {
     let rng = test::rand::FixedSliceRandom { bytes: private_key };
-    let private_key = agreement::EphemeralPrivateKey::generate(&agreement::X25519, &rng)?;
-    let public_key = agreement::UnparsedPublicKey::new(&agreement::X25519, public_key);
+    let private_key = agreement::EphemeralPrivateKey::generate(alg, &rng)?;
+    let public_key = agreement::UnparsedPublicKey::new(alg, public_key);
     agreement::agree_ephemeral(
         private_key,
         &public_key,
@@ -158,6 +411,18 @@ fn x25519_(private_key: &[u8], public_key: &[u8]) -> Result<Vec<u8>, error::Unsp
     )
 }
 
+// Converts an uncompressed SEC 1 point to compressed form. Compressed points
+// are returned unchanged.
+fn compress(uncompressed: &[u8]) -> Vec<u8> {
+    if uncompressed[0] != 4 {
+        return Vec::from(uncompressed);
+    }
+    let elem_len = uncompressed.len() / 2;
+    let mut compressed = vec![2 | (uncompressed[uncompressed.len() - 1] & 1)];
+    compressed.extend_from_slice(&uncompressed[1..(1 + elem_len)]);
+    compressed
+}
+
 fn h(s: &str) -> Vec<u8> {
     match test::from_hex(s) {
         Ok(v) => v,
@@ -172,8 +437,12 @@ fn alg_from_curve_name(curve_name: &str) -> &'static agreement::Algorithm {
         &agreement::ECDH_P256
     } else if curve_name == "P-384" {
         &agreement::ECDH_P384
+    } else if curve_name == "K-256" {
+        &agreement::ECDH_K256
     } else if curve_name == "X25519" {
         &agreement::X25519
+    } else if curve_name == "X448" {
+        &agreement::X448
     } else {
         panic!("Unsupported curve: {}", curve_name);
     }
@@ -0,0 +1,112 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{argon2, error, test, test_file};
+use std::num::NonZeroU32;
+
+#[test]
+fn argon2_tests() {
+    test::run(test_file!("argon2_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let algorithm = match test_case.consume_string("Alg").as_str() {
+            "Argon2id" => &argon2::ARGON2ID,
+            "Argon2i" => &argon2::ARGON2I,
+            _ => panic!("Unsupported value of \"Alg\""),
+        };
+        let params = argon2::Params::new(
+            test_case.consume_usize("m") as u32,
+            NonZeroU32::new(test_case.consume_usize("t") as u32).unwrap(),
+            NonZeroU32::new(test_case.consume_usize("p") as u32).unwrap(),
+        )
+        .unwrap();
+        let secret = test_case.consume_bytes("P");
+        let salt = test_case.consume_bytes("S");
+        let tag = test_case.consume_bytes("Tag");
+
+        let mut out = vec![0u8; tag.len()];
+        argon2::derive(algorithm, &params, &salt, &secret, &mut out).unwrap();
+        assert_eq!(out, tag);
+
+        assert_eq!(
+            argon2::verify(algorithm, &params, &salt, &secret, &tag),
+            Ok(())
+        );
+
+        let mut wrong = tag.clone();
+        wrong[0] ^= 1;
+        assert_eq!(
+            argon2::verify(algorithm, &params, &salt, &secret, &wrong),
+            Err(error::Unspecified)
+        );
+
+        // A shorter tag isn't a prefix of a longer one.
+        assert_eq!(
+            argon2::verify(algorithm, &params, &salt, &secret, &tag[..tag.len() - 1]),
+            Err(error::Unspecified)
+        );
+
+        Ok(())
+    });
+}
+
+#[test]
+fn argon2_params() {
+    let one = NonZeroU32::new(1).unwrap();
+    let four = NonZeroU32::new(4).unwrap();
+    assert!(argon2::Params::new(8, one, one).is_ok());
+    assert!(argon2::Params::new(7, one, one).is_err());
+    assert!(argon2::Params::new(32, one, four).is_ok());
+    assert!(argon2::Params::new(31, one, four).is_err());
+    assert!(argon2::Params::new(u32::max_value(), one, NonZeroU32::new(1 << 24).unwrap()).is_err());
+}
+
+#[test]
+fn argon2_rejects_short_inputs() {
+    let one = NonZeroU32::new(1).unwrap();
+    let params = argon2::Params::new(8, one, one).unwrap();
+    let mut out = [0u8; argon2::MIN_OUTPUT_LEN];
+    let short_salt = [0u8; argon2::MIN_SALT_LEN - 1];
+    let salt = [0u8; argon2::MIN_SALT_LEN];
+
+    assert!(argon2::derive(&argon2::ARGON2ID, &params, &short_salt, b"pw", &mut out).is_err());
+    assert!(argon2::derive(&argon2::ARGON2ID, &params, &salt, b"pw", &mut out).is_ok());
+    assert!(argon2::derive(
+        &argon2::ARGON2ID,
+        &params,
+        &salt,
+        b"pw",
+        &mut out[..argon2::MIN_OUTPUT_LEN - 1]
+    )
+    .is_err());
+    assert!(argon2::verify(&argon2::ARGON2ID, &params, &salt, b"pw", &[]).is_err());
+}
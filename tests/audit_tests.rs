@@ -0,0 +1,165 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "audit")]
+
+use ring::{aead, audit, digest, hmac, rand, signature, signature::KeyPair};
+use std::{string::String, sync::Mutex, vec::Vec};
+
+#[derive(Debug, PartialEq)]
+struct Recorded {
+    algorithm: String,
+    operation: audit::Operation,
+    key_fingerprint: Vec<u8>,
+    input_len: usize,
+}
+
+static EVENTS: Mutex<Vec<Recorded>> = Mutex::new(Vec::new());
+
+fn hook(event: &audit::Event) {
+    EVENTS.lock().unwrap().push(Recorded {
+        algorithm: format!("{:?}", event.algorithm()),
+        operation: event.operation(),
+        key_fingerprint: event.key_fingerprint().as_ref().to_vec(),
+        input_len: event.input_len(),
+    });
+}
+
+fn take_events() -> Vec<Recorded> {
+    std::mem::replace(&mut *EVENTS.lock().unwrap(), Vec::new())
+}
+
+// The hook is global, so everything is tested in one test.
+#[test]
+fn audit_test() {
+    audit::set_hook(hook).unwrap();
+    assert!(audit::set_hook(hook).is_err());
+
+    // AEAD
+    let key_bytes = [1u8; 16];
+    let s_key = aead::SealingKey::new(&aead::AES_128_GCM, &key_bytes).unwrap();
+    let o_key = aead::OpeningKey::new(&aead::AES_128_GCM, &key_bytes).unwrap();
+    let mut in_out = vec![0u8; 10 + aead::MAX_TAG_LEN];
+    let nonce = aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]);
+    let len = aead::seal_in_place(
+        &s_key,
+        nonce,
+        aead::Aad::from(b"aad"),
+        &mut in_out,
+        aead::MAX_TAG_LEN,
+    )
+    .unwrap();
+    in_out[0] ^= 1;
+    let nonce = aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]);
+    assert!(aead::open_in_place(&o_key, nonce, aead::Aad::empty(), 0, &mut in_out[..len]).is_err());
+
+    let events = take_events();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].algorithm, "AES_128_GCM");
+    assert_eq!(events[0].operation, audit::Operation::Seal);
+    assert_eq!(events[0].input_len, 10);
+    assert_eq!(events[1].operation, audit::Operation::Open);
+    assert_eq!(events[1].input_len, 10);
+    assert_eq!(events[0].key_fingerprint, events[1].key_fingerprint);
+    assert_eq!(events[0].key_fingerprint.len(), audit::FINGERPRINT_LEN);
+
+    let other_key = aead::SealingKey::new(&aead::AES_128_GCM, &[2u8; 16]).unwrap();
+    let nonce = aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]);
+    let _ = aead::seal_in_place(&other_key, nonce, aead::Aad::empty(), &mut in_out, 16).unwrap();
+    let other_events = take_events();
+    assert_ne!(events[0].key_fingerprint, other_events[0].key_fingerprint);
+
+    // A deserialized key keeps the fingerprint of the original key.
+    let siv_key = aead::SealingKey::new(&aead::AES_128_GCM_SIV, &key_bytes).unwrap();
+    let restored_key = aead::SealingKey::deserialize(
+        &aead::AES_128_GCM_SIV,
+        siv_key.serialize().unwrap().as_ref(),
+    )
+    .unwrap();
+    for key in &[&siv_key, &restored_key] {
+        let nonce = aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]);
+        let _ = aead::seal_in_place(key, nonce, aead::Aad::empty(), &mut in_out, 16).unwrap();
+    }
+    let siv_events = take_events();
+    assert_eq!(siv_events[0].key_fingerprint, events[0].key_fingerprint);
+    assert_eq!(siv_events[1].key_fingerprint, events[0].key_fingerprint);
+
+    // HMAC
+    let key = hmac::Key::new(&digest::SHA256, &key_bytes);
+    let mut ctx = hmac::Context::with_key(&key);
+    ctx.update(b"hello, ");
+    ctx.update(b"world");
+    let tag = ctx.sign();
+    hmac::verify(&key, b"hello, world", tag.as_ref()).unwrap();
+
+    let events = take_events();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].algorithm, "SHA256");
+    assert_eq!(events[0].operation, audit::Operation::HmacSign);
+    assert_eq!(events[0].input_len, 12);
+    assert_eq!(events[1].operation, audit::Operation::HmacVerify);
+    assert_eq!(events[1].input_len, 12);
+    assert_eq!(events[0].key_fingerprint, events[1].key_fingerprint);
+
+    // Signatures
+    let rng = rand::SystemRandom::new();
+    let pkcs8 = signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair = signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let sig = key_pair.sign(b"message");
+    signature::UnparsedPublicKey::new(&signature::ED25519, key_pair.public_key().as_ref())
+        .verify(b"message", sig.as_ref())
+        .unwrap();
+
+    let events = take_events();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].algorithm, "ring::signature::ED25519");
+    assert_eq!(events[0].operation, audit::Operation::Sign);
+    assert_eq!(events[0].input_len, 7);
+    assert_eq!(events[1].operation, audit::Operation::Verify);
+    assert_eq!(events[0].key_fingerprint, events[1].key_fingerprint);
+
+    // Each signature of a batch is recorded.
+    let public_key = key_pair.public_key().as_ref();
+    signature::ED25519
+        .verify_batch(&[
+            (public_key, b"message", sig.as_ref()),
+            (public_key, b"message", sig.as_ref()),
+        ])
+        .unwrap();
+    let batch_events = take_events();
+    assert_eq!(batch_events.len(), 2);
+    for event in &batch_events {
+        assert_eq!(event.operation, audit::Operation::Verify);
+        assert_eq!(event.input_len, 7);
+        assert_eq!(event.key_fingerprint, events[0].key_fingerprint);
+    }
+}
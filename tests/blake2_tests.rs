@@ -0,0 +1,115 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{blake2, digest, test, test_file};
+
+#[test]
+fn blake2_tests() {
+    test::run(test_file!("blake2_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let digest_alg = test_case.consume_digest_alg("Algorithm").unwrap();
+        let key_value = test_case.consume_bytes("Key");
+        let mut input = test_case.consume_bytes("Input");
+        let output = test_case.consume_bytes("Output");
+
+        blake2_test_case_inner(digest_alg, &key_value, &input, &output, true);
+
+        // Tamper with the input and check that verification fails.
+        if input.is_empty() {
+            input.push(0);
+        } else {
+            input[0] ^= 1;
+        }
+
+        blake2_test_case_inner(digest_alg, &key_value, &input, &output, false);
+
+        Ok(())
+    });
+}
+
+fn blake2_test_case_inner(
+    digest_alg: &'static digest::Algorithm,
+    key_value: &[u8],
+    input: &[u8],
+    output: &[u8],
+    is_ok: bool,
+) {
+    let key = blake2::Key::new(digest_alg, key_value).unwrap();
+
+    // One-shot API.
+    {
+        let tag = blake2::sign(&key, input);
+        assert_eq!(is_ok, tag.as_ref() == output);
+        assert_eq!(is_ok, blake2::verify(&key, input, output).is_ok());
+    }
+
+    // Multi-part API, byte by byte.
+    {
+        let mut ctx = blake2::Context::with_key(&key);
+        for b in input {
+            ctx.update(&[*b]);
+        }
+        let tag = ctx.sign();
+        assert_eq!(is_ok, tag.as_ref() == output);
+    }
+
+    // Multi-part API, a block at a time.
+    {
+        let mut ctx = blake2::Context::with_key(&key);
+        for chunk in input.chunks(digest_alg.block_len) {
+            ctx.update(chunk);
+        }
+        let tag = ctx.sign();
+        assert_eq!(is_ok, tag.as_ref() == output);
+    }
+}
+
+#[test]
+fn blake2_key_new() {
+    for &(digest_alg, max_len) in &[(&digest::BLAKE2B_512, 64), (&digest::BLAKE2S_256, 32)] {
+        assert!(blake2::Key::new(digest_alg, &[]).is_err());
+        assert!(blake2::Key::new(digest_alg, &[0; 1]).is_ok());
+        assert!(blake2::Key::new(digest_alg, &vec![0; max_len]).is_ok());
+        assert!(blake2::Key::new(digest_alg, &vec![0; max_len + 1]).is_err());
+    }
+    assert!(blake2::Key::new(&digest::SHA256, &[0; 32]).is_err());
+}
+
+#[test]
+fn blake2_debug() {
+    let key = blake2::Key::new(&digest::BLAKE2S_256, &[0; 32]).unwrap();
+    assert_eq!("Key { algorithm: BLAKE2S_256 }", format!("{:?}", &key));
+
+    let ctx = blake2::Context::with_key(&key);
+    assert_eq!("Context { algorithm: BLAKE2S_256 }", format!("{:?}", &ctx));
+}
@@ -0,0 +1,153 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{blake3, test, test_file};
+
+// The key and the context string of the official BLAKE3 test vectors.
+const KEY: &[u8; blake3::KEY_LEN] = b"whats the Elvish word for friend";
+const CONTEXT: &str = "BLAKE3 2019-12-27 16:29:52 test vectors context";
+
+#[test]
+fn blake3_tests() {
+    let key = blake3::Key::new(KEY).unwrap();
+    test::run(test_file!("blake3_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let input = input(test_case.consume_usize("InputLen"));
+        let hash = test_case.consume_bytes("Hash");
+        let keyed_hash = test_case.consume_bytes("KeyedHash");
+        let derive_key = test_case.consume_bytes("DeriveKey");
+
+        // One-shot API.
+        assert_eq!(blake3::hash(&input).as_ref(), &hash[..blake3::OUTPUT_LEN]);
+        assert_eq!(
+            blake3::keyed_hash(&key, &input).as_ref(),
+            &keyed_hash[..blake3::OUTPUT_LEN]
+        );
+        assert!(blake3::verify(&key, &input, &keyed_hash[..blake3::OUTPUT_LEN]).is_ok());
+        let mut out = vec![0; derive_key.len()];
+        blake3::derive_key(CONTEXT, &input, &mut out);
+        assert_eq!(out, derive_key);
+
+        // Multi-part API, with the extendable output.
+        for (ctx, expected) in &[
+            (blake3::Context::new(), &hash),
+            (blake3::Context::with_key(&key), &keyed_hash),
+            (blake3::Context::for_key_derivation(CONTEXT), &derive_key),
+        ] {
+            // Byte by byte.
+            let mut byte_ctx = ctx.clone();
+            for b in &input {
+                byte_ctx.update(&[*b]);
+            }
+            assert_eq!(byte_ctx.finish().as_ref(), &expected[..blake3::OUTPUT_LEN]);
+
+            // In parts that don't line up with blocks or chunks, with the
+            // output read in pieces of different sizes.
+            let mut ctx = ctx.clone();
+            for part in input.chunks(1000) {
+                ctx.update(part);
+            }
+            let mut reader = ctx.finish_xof();
+            let mut out = vec![0; expected.len()];
+            let (a, rest) = out.split_at_mut(1);
+            let (b, c) = rest.split_at_mut(64);
+            reader.squeeze(a);
+            reader.squeeze(b);
+            reader.squeeze(c);
+            assert_eq!(&out, *expected);
+        }
+
+        // Tamper with the tag and check that verification fails.
+        let mut tag = keyed_hash[..blake3::OUTPUT_LEN].to_vec();
+        tag[0] ^= 1;
+        assert!(blake3::verify(&key, &input, &tag).is_err());
+        assert!(blake3::verify(&key, &input, &tag[1..]).is_err());
+
+        Ok(())
+    });
+}
+
+#[cfg(feature = "blake3_parallel")]
+#[test]
+fn blake3_update_parallel_test() {
+    let key = blake3::Key::new(KEY).unwrap();
+    let input = input((1 << 22) + 1025);
+
+    // Inputs that do and don't start at chunk boundaries and that are and
+    // aren't a power of two number of chunks long.
+    for &(start, end) in &[
+        (0, 1 << 18),
+        (0, (1 << 19) + 1),
+        (0, 1 << 20),
+        (100, (1 << 21) + 100),
+        (1024, input.len()),
+        (5000, input.len()),
+    ] {
+        for ctx in &[blake3::Context::new(), blake3::Context::with_key(&key)] {
+            let mut expected = ctx.clone();
+            expected.update(&input[..end]);
+            expected.update(b"tail");
+
+            let mut ctx = ctx.clone();
+            ctx.update(&input[..start]);
+            ctx.update_parallel(&input[start..end]);
+            ctx.update(b"tail");
+            assert_eq!(ctx.finish().as_ref(), expected.finish().as_ref());
+        }
+    }
+}
+
+#[test]
+fn blake3_key_new() {
+    assert!(blake3::Key::new(&[]).is_err());
+    assert!(blake3::Key::new(&[0; blake3::KEY_LEN - 1]).is_err());
+    assert!(blake3::Key::new(&[0; blake3::KEY_LEN]).is_ok());
+    assert!(blake3::Key::new(&[0; blake3::KEY_LEN + 1]).is_err());
+}
+
+#[test]
+fn blake3_debug() {
+    assert_eq!(
+        "BLAKE3:af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+        format!("{:?}", blake3::hash(b""))
+    );
+
+    let key = blake3::Key::new(&[0; blake3::KEY_LEN]).unwrap();
+    assert_eq!("Key", format!("{:?}", &key));
+    assert_eq!("Context", format!("{:?}", blake3::Context::with_key(&key)));
+}
+
+// The first `len` bytes of the repeating sequence 0, 1, ..., 250.
+fn input(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
@@ -0,0 +1,76 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "block_cipher")]
+
+use ring::{cipher::aes, test, test_file};
+
+#[test]
+fn cipher_aes_tests() {
+    test::run(test_file!("cipher_aes_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let key_bytes = test_case.consume_bytes("Key");
+        let plaintext = test_case.consume_bytes("Plaintext");
+        let ciphertext = test_case.consume_bytes("Ciphertext");
+
+        let algorithm = match key_bytes.len() {
+            16 => &aes::AES_128,
+            32 => &aes::AES_256,
+            _ => unreachable!(),
+        };
+        let key = aes::Key::new(algorithm, &key_bytes)?;
+
+        let mut block = [0u8; aes::BLOCK_LEN];
+        block.copy_from_slice(&plaintext);
+        let encrypted = key.encrypt_block(block);
+        assert_eq!(&encrypted[..], &ciphertext[..]);
+        assert_eq!(&key.decrypt_block(encrypted)[..], &plaintext[..]);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn cipher_aes_key_lengths() {
+    assert!(aes::Key::new(&aes::AES_128, &[0u8; 16]).is_ok());
+    assert!(aes::Key::new(&aes::AES_128, &[0u8; 32]).is_err());
+    assert!(aes::Key::new(&aes::AES_256, &[0u8; 32]).is_ok());
+    assert!(aes::Key::new(&aes::AES_256, &[0u8; 16]).is_err());
+    assert!(aes::Key::new(&aes::AES_256, &[]).is_err());
+}
+
+#[test]
+fn cipher_aes_debug() {
+    let key = aes::Key::new(&aes::AES_256, &[0u8; 32]).unwrap();
+    assert_eq!("AES_256", format!("{:?}", key.algorithm()));
+    assert_eq!("Key { algorithm: AES_256 }", format!("{:?}", key));
+}
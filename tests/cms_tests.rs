@@ -0,0 +1,84 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "rsa")]
+
+use ring::{cms, test, test_file};
+
+#[test]
+fn cms_signed_data_verify_test() {
+    test::run(test_file!("cms_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+
+        let key_type = test_case.consume_string("KeyType");
+        let key = test_case.consume_bytes("Key");
+        let der = test_case.consume_bytes("SignedData");
+        let detached_content = test_case
+            .consume_optional_string("DetachedContent")
+            .map(|hex| test::from_hex(&hex).unwrap());
+        let expected_result = test_case.consume_string("Result");
+
+        let public_key = match key_type.as_str() {
+            "P-256" => cms::PublicKey::EcdsaP256(&key),
+            "P-384" => cms::PublicKey::EcdsaP384(&key),
+            "RSA" => cms::PublicKey::Rsa(&key),
+            _ => unreachable!(),
+        };
+
+        let signed_data = cms::SignedData::from_der(&der).unwrap();
+        assert_eq!(signed_data.signers().len(), 1);
+        let signer = &signed_data.signers()[0];
+
+        let actual_result = match signed_data.verify(
+            signer,
+            public_key,
+            detached_content.as_ref().map(|c| &c[..]),
+        ) {
+            Ok(()) => "OK",
+            Err(_) => "FAIL",
+        };
+        assert_eq!(actual_result, expected_result);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn cms_signed_data_parse_test() {
+    // A `ContentInfo` that isn't a `SignedData` (id-data).
+    const DATA: &[u8] = &[
+        0x30, 0x0f, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01, 0xa0, 0x02,
+        0x04, 0x00,
+    ];
+    assert!(cms::SignedData::from_der(DATA).is_err());
+    assert!(cms::SignedData::from_der(&[]).is_err());
+}
@@ -0,0 +1,196 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "use_heap")]
+
+use ring::{cose, rand, signature, signature::KeyPair, test};
+
+// The P-256 key "11" from the COSE WG examples, with `alg` ES256.
+const ES256_COSE_KEY: &str = "a601020242313103262001215820bac5b11cad8f99f9c72b05cf4b9e26d244dc189f745228255a219a86d6a09eff22582020138bf82dc1b6d562be0fa54ab7804a3a64b6d72ccfed6b6fb6ed28bbfc117e";
+
+// A tagged message signed with `ES256_COSE_KEY`, in the form of RFC 8152
+// Appendix C.2.1: the protected header also has a content type, and the
+// unprotected header has a `kid`.
+const ES256_SIGN1: &str = "d28445a201260300a10442313154546869732069732074686520636f6e74656e742e5840a6605db745842928e2a749bb9934e9aa8cb149db8ec418c1324324ce4d6632d7705fed532cc4039a9de14f2f2e965555142e663096710961419c3449bcaa90c3";
+
+// An untagged message signed with `ES256_COSE_KEY`, with a detached
+// payload and `ES256_DETACHED_AAD` as the external AAD.
+const ES256_SIGN1_DETACHED: &str = "8443a10126a0f658403e81ffa7ddf0e59583acc6749b0c6642c8c986eefa272b5b93b233f09ce7cfe17b45d1a86ee11b295c713b2d640496d0a77543d8469def8c8304e588e325591b";
+const ES256_DETACHED_AAD: &str = "11aa22bb33cc44dd55006699";
+
+const PAYLOAD: &[u8] = b"This is the content.";
+
+#[test]
+fn cose_key_test() {
+    let public_key =
+        cose::PublicKey::from_cose_key(&test::from_hex(ES256_COSE_KEY).unwrap()).unwrap();
+    assert_eq!(public_key.algorithm(), &cose::ES256);
+    assert_eq!(public_key.key_id(), Some(&b"11"[..]));
+    assert_eq!(public_key.as_ref().len(), 65);
+
+    // Parses a key whose last parameter is an `x` of `x_len` zero bytes.
+    let parse = |prefix: &str, x_len: usize| {
+        let mut cose_key = test::from_hex(prefix).unwrap();
+        cose_key.extend_from_slice(&vec![0; x_len]);
+        cose::PublicKey::from_cose_key(&cose_key)
+    };
+
+    // Without `kid`.
+    #[cfg(feature = "sig-ed25519")]
+    assert!(parse("a4010103272006215820", 32).is_ok());
+    // Without `alg`.
+    assert!(parse("a301012006215820", 32).is_err());
+    // EdDSA with an EC2 key type.
+    assert!(parse("a4010203272006215820", 32).is_err());
+    // Ed25519 key of the wrong length.
+    assert!(parse("a4010103272006215819", 25).is_err());
+    // Duplicate `alg`.
+    assert!(parse("a50101032703272006215820", 32).is_err());
+}
+
+#[test]
+fn cose_sign1_verify_test() {
+    let public_key =
+        cose::PublicKey::from_cose_key(&test::from_hex(ES256_COSE_KEY).unwrap()).unwrap();
+
+    let message = test::from_hex(ES256_SIGN1).unwrap();
+    assert_eq!(
+        cose::verify_sign1(&public_key, &message, &[]).unwrap(),
+        PAYLOAD
+    );
+    assert!(cose::verify_sign1(&public_key, &message, b"aad").is_err());
+    assert!(cose::verify_sign1_detached(&public_key, &message, PAYLOAD, &[]).is_err());
+
+    // Tampering with the payload is detected.
+    let mut tampered = message.clone();
+    let payload_index = tampered.len() - 64 - 2 - 1;
+    tampered[payload_index] ^= 1;
+    assert!(cose::verify_sign1(&public_key, &tampered, &[]).is_err());
+
+    // Trailing data is rejected.
+    let mut extended = message.clone();
+    extended.push(0);
+    assert!(cose::verify_sign1(&public_key, &extended, &[]).is_err());
+
+    let detached = test::from_hex(ES256_SIGN1_DETACHED).unwrap();
+    let aad = test::from_hex(ES256_DETACHED_AAD).unwrap();
+    assert!(cose::verify_sign1_detached(&public_key, &detached, PAYLOAD, &aad).is_ok());
+    assert!(cose::verify_sign1_detached(&public_key, &detached, PAYLOAD, &[]).is_err());
+    assert!(cose::verify_sign1_detached(&public_key, &detached, b"other", &aad).is_err());
+    assert!(cose::verify_sign1(&public_key, &detached, &aad).is_err());
+}
+
+#[test]
+fn cose_sign1_crit_test() {
+    let public_key =
+        cose::PublicKey::from_cose_key(&test::from_hex(ES256_COSE_KEY).unwrap()).unwrap();
+
+    // The protected header {1: -7, 2: [1]} has a `crit` parameter. The
+    // message is rejected before the signature is checked.
+    let mut message = test::from_hex("8446a20126028101a0").unwrap();
+    message.extend_from_slice(&test::from_hex("54").unwrap());
+    message.extend_from_slice(PAYLOAD);
+    message.extend_from_slice(&test::from_hex("5840").unwrap());
+    message.extend_from_slice(&[0u8; 64]);
+    assert!(cose::verify_sign1(&public_key, &message, &[]).is_err());
+}
+
+#[test]
+fn cose_sign1_round_trip_test() {
+    let rng = rand::SystemRandom::new();
+
+    #[cfg(feature = "sig-ed25519")]
+    {
+        let pkcs8 = signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let key_pair = signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let mut cose_key = test::from_hex("a40101032720062158").unwrap();
+        cose_key.push(32);
+        cose_key.extend_from_slice(key_pair.public_key().as_ref());
+        let public_key = cose::PublicKey::from_cose_key(&cose_key).unwrap();
+        assert_eq!(public_key.key_id(), None);
+
+        let message = cose::sign1(
+            &cose::EDDSA,
+            cose::SigningKey::Ed25519(&key_pair),
+            PAYLOAD,
+            b"aad",
+            &rng,
+        )
+        .unwrap();
+        assert_eq!(
+            cose::verify_sign1(&public_key, &message, b"aad").unwrap(),
+            PAYLOAD
+        );
+        assert!(cose::verify_sign1(&public_key, &message, &[]).is_err());
+
+        // The key's algorithm must match the signing algorithm.
+        assert!(cose::sign1(
+            &cose::ES256,
+            cose::SigningKey::Ed25519(&key_pair),
+            PAYLOAD,
+            &[],
+            &rng,
+        )
+        .is_err());
+    }
+
+    let pkcs8 =
+        signature::EcdsaKeyPair::generate_pkcs8(&signature::ECDSA_P384_SHA384_FIXED_SIGNING, &rng)
+            .unwrap();
+    let key_pair = signature::EcdsaKeyPair::from_pkcs8(
+        &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+        pkcs8.as_ref(),
+    )
+    .unwrap();
+    let point = key_pair.public_key().as_ref();
+    let mut cose_key = test::from_hex("a5010203382220022158").unwrap();
+    cose_key.push(48);
+    cose_key.extend_from_slice(&point[1..49]);
+    cose_key.extend_from_slice(&test::from_hex("225830").unwrap());
+    cose_key.extend_from_slice(&point[49..]);
+    let public_key = cose::PublicKey::from_cose_key(&cose_key).unwrap();
+    assert_eq!(public_key.algorithm(), &cose::ES384);
+    assert_eq!(public_key.as_ref(), point);
+
+    let message = cose::sign1(
+        &cose::ES384,
+        cose::SigningKey::Ecdsa(&key_pair),
+        PAYLOAD,
+        &[],
+        &rng,
+    )
+    .unwrap();
+    assert_eq!(
+        cose::verify_sign1(&public_key, &message, &[]).unwrap(),
+        PAYLOAD
+    );
+}
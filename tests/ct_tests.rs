@@ -0,0 +1,163 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "rsa")]
+
+use ring::{
+    ct, rand,
+    signature::{self, KeyPair},
+};
+
+const LOG_ID: [u8; ct::LOG_ID_LEN] = [0x11; ct::LOG_ID_LEN];
+const TIMESTAMP: u64 = 1_561_000_000_000;
+const CERT: &[u8] = b"not really a certificate";
+
+fn signed_data(timestamp: u64, cert: &[u8]) -> Vec<u8> {
+    let mut out = vec![0, 0];
+    out.extend_from_slice(&timestamp.to_be_bytes());
+    out.extend_from_slice(&[0, 0]); // x509_entry
+    out.extend_from_slice(&(cert.len() as u32).to_be_bytes()[1..]);
+    out.extend_from_slice(cert);
+    out.extend_from_slice(&[0, 0]); // No extensions.
+    out
+}
+
+fn serialize_sct(timestamp: u64, sig: &[u8]) -> Vec<u8> {
+    let mut out = vec![0];
+    out.extend_from_slice(&LOG_ID);
+    out.extend_from_slice(&timestamp.to_be_bytes());
+    out.extend_from_slice(&[0, 0]); // No extensions.
+    out.extend_from_slice(&[4, 3]); // SHA-256, ECDSA.
+    out.extend_from_slice(&(sig.len() as u16).to_be_bytes());
+    out.extend_from_slice(sig);
+    out
+}
+
+#[test]
+fn ct_sct_verify_test() {
+    let rng = rand::SystemRandom::new();
+    let key_pair = signature::EcdsaKeyPair::from_pkcs8(
+        &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+        include_bytes!("ecdsa_test_private_key_p256.p8"),
+    )
+    .unwrap();
+    let sig = key_pair.sign(&rng, &signed_data(TIMESTAMP, CERT)).unwrap();
+    let sct_bytes = serialize_sct(TIMESTAMP, sig.as_ref());
+
+    let log = ct::Log {
+        id: &LOG_ID,
+        public_key: key_pair.public_key().as_ref(),
+        algorithm: &ct::ECDSA_P256_SHA256,
+    };
+    let other_id = [0x22; ct::LOG_ID_LEN];
+    let other_log = ct::Log {
+        id: &other_id,
+        ..log
+    };
+
+    let sct = ct::Sct::parse(&sct_bytes).unwrap();
+    assert_eq!(sct.log_id(), &LOG_ID[..]);
+    assert_eq!(sct.timestamp(), TIMESTAMP);
+    assert_eq!(sct.algorithm(), &ct::ECDSA_P256_SHA256);
+
+    let entry = ct::Entry::X509(CERT);
+    let verified = sct.verify(&entry, &[other_log, log], TIMESTAMP).unwrap();
+    assert_eq!(&verified.id[..], &LOG_ID[..]);
+
+    // Unknown log.
+    assert!(sct.verify(&entry, &[other_log], TIMESTAMP).is_err());
+
+    // Issued in the future.
+    assert!(sct.verify(&entry, &[log], TIMESTAMP - 1).is_err());
+
+    // Wrong entry.
+    assert!(sct
+        .verify(&ct::Entry::X509(b"another certificate"), &[log], TIMESTAMP)
+        .is_err());
+    let precert = ct::Entry::Precert {
+        issuer_key_hash: &[0; 32],
+        tbs_certificate: CERT,
+    };
+    assert!(sct.verify(&precert, &[log], TIMESTAMP).is_err());
+
+    // Log configured with a different algorithm.
+    let rsa_log = ct::Log {
+        algorithm: &ct::RSA_PKCS1_SHA256,
+        ..log
+    };
+    assert!(sct.verify(&entry, &[rsa_log], TIMESTAMP).is_err());
+
+    // Tampered timestamp.
+    let tampered = serialize_sct(TIMESTAMP - 1, sig.as_ref());
+    let tampered = ct::Sct::parse(&tampered).unwrap();
+    assert!(tampered.verify(&entry, &[log], TIMESTAMP).is_err());
+}
+
+#[test]
+fn ct_sct_parse_test() {
+    let sct = serialize_sct(TIMESTAMP, &[1, 2, 3]);
+    assert!(ct::Sct::parse(&sct).is_ok());
+
+    // Trailing garbage.
+    let mut long = sct.clone();
+    long.push(0);
+    assert!(ct::Sct::parse(&long).is_err());
+
+    // Truncated.
+    assert!(ct::Sct::parse(&sct[..sct.len() - 1]).is_err());
+
+    // Unsupported version.
+    let mut v2 = sct.clone();
+    v2[0] = 1;
+    assert!(ct::Sct::parse(&v2).is_err());
+
+    // Unsupported algorithm (SHA-384).
+    let mut sha384 = sct.clone();
+    sha384[1 + 32 + 8 + 2] = 5;
+    assert!(ct::Sct::parse(&sha384).is_err());
+
+    // A list containing a good SCT and one with an unknown version; the
+    // latter is skipped.
+    let mut list = Vec::new();
+    for sct in &[&sct, &v2] {
+        list.extend_from_slice(&(sct.len() as u16).to_be_bytes());
+        list.extend_from_slice(sct);
+    }
+    let mut encoded = (list.len() as u16).to_be_bytes().to_vec();
+    encoded.extend_from_slice(&list);
+    let scts = ct::parse_sct_list(&encoded).unwrap();
+    assert_eq!(scts.len(), 1);
+    assert_eq!(scts[0].timestamp(), TIMESTAMP);
+
+    // Empty lists are not allowed.
+    assert!(ct::parse_sct_list(&[0, 0]).is_err());
+}
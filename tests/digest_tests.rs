@@ -55,10 +55,74 @@ fn digest_misc() {
         let actual_from_one_shot = digest::digest(digest_alg, &data);
         assert_eq!(&expected, &actual_from_one_shot.as_ref());
 
+        let parts = vec![&input[..]; repeat];
+        let actual_from_parts = digest::digest_all(digest_alg, &parts);
+        assert_eq!(&expected, &actual_from_parts.as_ref());
+
         Ok(())
     });
 }
 
+/// SHAKE test vectors, with the output read all at once and in pieces.
+#[test]
+fn shake() {
+    test::run(test_file!("shake_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let algorithm = test_case.consume_string("Algorithm");
+        let input = test_case.consume_bytes("Input");
+        let expected = test_case.consume_bytes("Output");
+
+        let squeeze_all = |squeeze: &mut dyn FnMut(&mut [u8])| {
+            let mut actual = vec![0u8; expected.len()];
+            squeeze(&mut actual);
+            assert_eq!(expected, actual);
+        };
+        let squeeze_pieces = |squeeze: &mut dyn FnMut(&mut [u8])| {
+            let mut actual = vec![0u8; expected.len()];
+            let mut piece_len = 0;
+            let mut remaining = &mut actual[..];
+            while !remaining.is_empty() {
+                let len = core::cmp::min(piece_len, remaining.len());
+                let (piece, rest) = remaining.split_at_mut(len);
+                squeeze(piece);
+                remaining = rest;
+                piece_len += 1;
+            }
+            assert_eq!(expected, actual);
+        };
+
+        match algorithm.as_ref() {
+            "SHAKE128" => {
+                let mut ctx = digest::Shake128::new();
+                ctx.update(&input);
+                let mut ctx2 = ctx.clone();
+                squeeze_all(&mut |out| ctx.squeeze(out));
+                squeeze_pieces(&mut |out| ctx2.squeeze(out));
+            }
+            "SHAKE256" => {
+                let mut ctx = digest::Shake256::new();
+                for byte in input.iter() {
+                    ctx.update(core::slice::from_ref(byte));
+                }
+                let mut ctx2 = ctx.clone();
+                squeeze_all(&mut |out| ctx.squeeze(out));
+                squeeze_pieces(&mut |out| ctx2.squeeze(out));
+            }
+            _ => unreachable!("Unsupported algorithm: {}", algorithm),
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+#[should_panic]
+fn shake_update_after_squeeze() {
+    let mut ctx = digest::Shake128::new();
+    ctx.squeeze(&mut [0u8; 1]);
+    ctx.update(b"abc");
+}
+
 mod digest_shavs {
     use ring::{digest, test};
 
@@ -224,6 +288,11 @@ test_i_u_f!(digest_test_i_u_f_sha1, digest::SHA1);
 test_i_u_f!(digest_test_i_u_f_sha256, digest::SHA256);
 test_i_u_f!(digest_test_i_u_f_sha384, digest::SHA384);
 test_i_u_f!(digest_test_i_u_f_sha512, digest::SHA512);
+test_i_u_f!(digest_test_i_u_f_sha3_256, digest::SHA3_256);
+test_i_u_f!(digest_test_i_u_f_sha3_512, digest::SHA3_512);
+test_i_u_f!(digest_test_i_u_f_blake2b_512, digest::BLAKE2B_512);
+test_i_u_f!(digest_test_i_u_f_blake2s_256, digest::BLAKE2S_256);
+test_i_u_f!(digest_test_i_u_f_ascon_hash, digest::ASCON_HASH);
 
 /// See https://bugzilla.mozilla.org/show_bug.cgi?id=610162. This tests the
 /// calculation of 8GB of the byte 123.
@@ -319,6 +388,122 @@ test_large_digest!(
 // TODO: test_large_digest!(digest_test_large_digest_sha512_256,
 //                            digest::SHA512_256, 256 / 8, [ ... ]);
 
+#[test]
+fn digest_serialize_state() {
+    let input: Vec<u8> = (0..300).map(|i| i as u8).collect();
+    for &alg in &[
+        &digest::SHA256,
+        &digest::SHA384,
+        &digest::SHA512,
+        &digest::SHA512_256,
+    ] {
+        let expected = digest::digest(alg, &input);
+        for &split in &[
+            0,
+            1,
+            alg.block_len - 1,
+            alg.block_len,
+            alg.block_len + 1,
+            300,
+        ] {
+            let mut ctx = digest::Context::new(alg);
+            ctx.update(&input[..split]);
+            let serialized = ctx.serialize_state().unwrap();
+            assert_eq!(serialized.as_ref().len(), digest::SERIALIZED_STATE_LEN);
+
+            let mut resumed = digest::Context::resume_from_state(alg, serialized.as_ref()).unwrap();
+            resumed.update(&input[split..]);
+            assert_eq!(resumed.finish().as_ref(), expected.as_ref());
+
+            // The original context is unaffected.
+            ctx.update(&input[split..]);
+            assert_eq!(ctx.finish().as_ref(), expected.as_ref());
+        }
+    }
+}
+
+#[test]
+fn digest_resume_from_state_rejects_bad_states() {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(b"hello, world");
+    let serialized = ctx.serialize_state().unwrap();
+    let serialized = serialized.as_ref();
+    assert!(digest::Context::resume_from_state(&digest::SHA256, serialized).is_ok());
+
+    // Every flipped bit is caught.
+    for i in 0..serialized.len() {
+        let mut tampered = serialized.to_vec();
+        tampered[i] ^= 0x10;
+        assert!(digest::Context::resume_from_state(&digest::SHA256, &tampered).is_err());
+    }
+
+    // Truncated or extended.
+    assert!(digest::Context::resume_from_state(
+        &digest::SHA256,
+        &serialized[..serialized.len() - 1]
+    )
+    .is_err());
+    let mut extended = serialized.to_vec();
+    extended.push(0);
+    assert!(digest::Context::resume_from_state(&digest::SHA256, &extended).is_err());
+
+    // Wrong algorithm.
+    assert!(digest::Context::resume_from_state(&digest::SHA512_256, serialized).is_err());
+    assert!(digest::Context::resume_from_state(&digest::SHA1, serialized).is_err());
+}
+
+#[test]
+fn digest_serialize_state_unsupported_algorithms() {
+    for &alg in &[
+        &digest::SHA1,
+        &digest::SHA3_256,
+        &digest::BLAKE2B_512,
+        &digest::BLAKE2S_256,
+        &digest::ASCON_HASH,
+    ] {
+        assert!(digest::Context::new(alg).serialize_state().is_err());
+    }
+}
+
+#[test]
+fn digest_multi_sha256() {
+    let data: Vec<u8> = (0..5008).map(|i| (i * 7) as u8).collect();
+
+    // Messages of equal lengths, and of lengths that differ by less than and
+    // by more than a block.
+    let lengths: &[[usize; 8]] = &[
+        [0; 8],
+        [64; 8],
+        [4096; 8],
+        [1000, 1001, 1002, 1003, 1004, 1005, 1006, 1007],
+        [0, 55, 56, 63, 64, 65, 119, 5000],
+        [5000, 4999, 128, 127, 129, 640, 1, 2],
+    ];
+    for lengths in lengths {
+        let mut messages: [&[u8]; 8] = [&[]; 8];
+        for (i, (message, len)) in messages.iter_mut().zip(lengths.iter()).enumerate() {
+            *message = &data[i..(i + len)];
+        }
+        let messages = messages;
+
+        let x8 = digest::multi::sha256_x8(&messages);
+        let mut x4 = [[0u8; digest::SHA256_OUTPUT_LEN]; 8];
+        for (half, out) in messages.chunks(4).zip(x4.chunks_mut(4)) {
+            let half = [half[0], half[1], half[2], half[3]];
+            for (digest, out) in digest::multi::sha256_x4(&half).iter().zip(out.iter_mut()) {
+                out.copy_from_slice(digest.as_ref());
+            }
+        }
+
+        for ((message, x8), x4) in messages.iter().zip(x8.iter()).zip(x4.iter()) {
+            let expected = digest::digest(&digest::SHA256, message);
+            assert_eq!(x8.algorithm(), &digest::SHA256);
+            assert_eq!(x8.as_ref(), expected.as_ref());
+            assert_eq!(&x4[..], expected.as_ref());
+        }
+    }
+}
+
 #[test]
 fn test_fmt_algorithm() {
     assert_eq!("SHA1", &format!("{:?}", digest::SHA1));
@@ -326,6 +511,12 @@ fn test_fmt_algorithm() {
     assert_eq!("SHA384", &format!("{:?}", digest::SHA384));
     assert_eq!("SHA512", &format!("{:?}", digest::SHA512));
     assert_eq!("SHA512_256", &format!("{:?}", digest::SHA512_256));
+    assert_eq!("SHA3_256", &format!("{:?}", digest::SHA3_256));
+    assert_eq!("SHA3_384", &format!("{:?}", digest::SHA3_384));
+    assert_eq!("SHA3_512", &format!("{:?}", digest::SHA3_512));
+    assert_eq!("BLAKE2B_512", &format!("{:?}", digest::BLAKE2B_512));
+    assert_eq!("BLAKE2S_256", &format!("{:?}", digest::BLAKE2S_256));
+    assert_eq!("ASCON_HASH", &format!("{:?}", digest::ASCON_HASH));
 }
 
 #[test]
@@ -358,3 +549,22 @@ fn digest_test_fmt() {
         &format!("{:?}", digest::digest(&digest::SHA512_256, b"hello, world"))
     );
 }
+
+#[test]
+fn digest_algorithm_registry() {
+    assert_eq!(digest::Algorithm::all().len(), 11);
+    for &alg in digest::Algorithm::all() {
+        let name = format!("{:?}", alg);
+        assert_eq!(digest::Algorithm::from_name(&name), Some(alg));
+    }
+    assert_eq!(digest::Algorithm::from_name("SHA-256"), None);
+
+    for &(id, expected) in &[
+        (0x1301, &digest::SHA256),
+        (0x1302, &digest::SHA384),
+        (0x1303, &digest::SHA256),
+    ] {
+        assert_eq!(digest::Algorithm::from_tls_id(id), Some(expected));
+    }
+    assert_eq!(digest::Algorithm::from_tls_id(0x1304), None);
+}
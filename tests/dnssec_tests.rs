@@ -0,0 +1,76 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "rsa")]
+
+use ring::{dnssec, test, test_file};
+
+#[test]
+fn dnssec_verify_tests() {
+    test::run(test_file!("dnssec_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+
+        let dnskey = test_case.consume_bytes("Dnskey");
+        let rrsig = test_case.consume_bytes("Rrsig");
+        let rrset = test_case.consume_bytes("Rrset");
+        let now = test_case.consume_usize("Now") as u32;
+        let expected_result = test_case.consume_string("Result");
+
+        // Ed25519 (algorithm 15) keys can't be parsed without the feature.
+        if cfg!(not(feature = "sig-ed25519")) && dnskey[3] == 15 {
+            return Ok(());
+        }
+
+        let dnskey = dnssec::Dnskey::parse(&dnskey).unwrap();
+        let rrsig = dnssec::Rrsig::parse(&rrsig).unwrap();
+        let actual_result = match rrsig.verify(&dnskey, &rrset, now) {
+            Ok(()) => "OK",
+            Err(_) => "FAIL",
+        };
+        assert_eq!(actual_result, expected_result);
+
+        Ok(())
+    });
+}
+
+// The root zone KSK-2017.
+#[test]
+fn dnssec_key_tag_test() {
+    const ROOT_KSK_2017: &str = "0101030803010001acffb409bcc939f831f7a1e5ec88f7a59255ec53040be432027390a4ce896d6f9086f3c5e177fbfe118163aaec7af1462c47945944c4e2c026be5e98bbcded25978272e1e3e079c5094d573f0e83c92f02b32d3513b1550b826929c80dd0f92cac966d17769fd5867b647c3f38029abdc48152eb8f207159ecc5d232c7c1537c79f4b7ac28ff11682f21681bf6d6aba555032bf6f9f036beb2aaa5b3778d6eebfba6bf9ea191be4ab0caea759e2f773a1f9029c73ecb8d5735b9321db085f1b8e2d8038fe2941992548cee0d67dd4547e11dd63af9c9fc1c5466fb684cf009d7197c2cf79e792ab501e6a8a1ca519af2cb9b5f6367e94c0d47502451357be1b5";
+    let rdata = test::from_hex(ROOT_KSK_2017).unwrap();
+    assert_eq!(dnssec::key_tag(&rdata), 20326);
+
+    let dnskey = dnssec::Dnskey::parse(&rdata).unwrap();
+    assert_eq!(dnskey.flags(), 257);
+    assert_eq!(dnskey.algorithm(), &dnssec::RSASHA256);
+    assert_eq!(dnskey.key_tag(), 20326);
+}
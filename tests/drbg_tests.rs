@@ -0,0 +1,102 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use core::convert::TryInto;
+use ring::{
+    drbg,
+    rand::{self, SecureRandom},
+    test, test_file,
+};
+
+#[test]
+fn drbg_tests() {
+    test::run(test_file!("drbg_tests.txt"), |section, test_case| {
+        let entropy = test_case.consume_bytes("EntropyInput");
+        let personalization = test_case.consume_bytes("PersonalizationString");
+        let additional_input_1 = test_case.consume_bytes("AdditionalInput1");
+        let additional_input_2 = test_case.consume_bytes("AdditionalInput2");
+        let expected = test_case.consume_bytes("ReturnedBits");
+
+        let drbg =
+            drbg::CtrDrbg::instantiate(entropy.as_slice().try_into().unwrap(), &personalization)
+                .unwrap();
+        match section {
+            "NoReseed" => (),
+            "Reseed" => {
+                let entropy = test_case.consume_bytes("EntropyInputReseed");
+                let additional_input = test_case.consume_bytes("AdditionalInputReseed");
+                drbg.reseed(entropy.as_slice().try_into().unwrap(), &additional_input)
+                    .unwrap();
+            }
+            _ => unreachable!(),
+        }
+        let mut actual = vec![0u8; expected.len()];
+        drbg.generate(&mut actual, &additional_input_1).unwrap();
+        drbg.generate(&mut actual, &additional_input_2).unwrap();
+        assert_eq!(actual, expected);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn drbg_limits() {
+    let entropy = [0u8; drbg::ENTROPY_LEN];
+    let too_long = [0u8; drbg::MAX_ADDITIONAL_INPUT_LEN + 1];
+
+    assert!(drbg::CtrDrbg::instantiate(&entropy, &too_long).is_err());
+
+    let drbg = drbg::CtrDrbg::instantiate(&entropy, &[]).unwrap();
+    assert!(drbg.reseed(&entropy, &too_long).is_err());
+
+    let mut out = vec![0u8; drbg::MAX_REQUEST_LEN + 1];
+    assert!(drbg.generate(&mut out[..1], &too_long).is_err());
+    assert!(drbg.generate(&mut out, &[]).is_err());
+    assert!(drbg
+        .generate(&mut out[..drbg::MAX_REQUEST_LEN], &[])
+        .is_ok());
+
+    // `fill()` splits long requests.
+    assert!(drbg.fill(&mut out).is_ok());
+}
+
+#[test]
+fn drbg_system_random() {
+    let system_random = rand::SystemRandom::new();
+    let drbg = drbg::CtrDrbg::new(&system_random, b"drbg_tests").unwrap();
+    let mut a = [0u8; 32];
+    let mut b = [0u8; 32];
+    drbg.fill(&mut a).unwrap();
+    drbg.fill(&mut b).unwrap();
+    assert_ne!(a, b);
+}
@@ -32,7 +32,7 @@
 )]
 
 use ring::{
-    rand,
+    digest, error, rand,
     signature::{self, KeyPair},
     test, test_file,
 };
@@ -71,6 +71,16 @@ fn ecdsa_from_pkcs8_test() {
                         &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
                     ),
                 ),
+                "P-521" => (
+                    (
+                        &signature::ECDSA_P521_SHA512_FIXED_SIGNING,
+                        &signature::ECDSA_P521_SHA512_ASN1_SIGNING,
+                    ),
+                    (
+                        &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+                        &signature::ECDSA_P384_SHA384_ASN1_SIGNING,
+                    ),
+                ),
                 _ => unreachable!(),
             };
 
@@ -116,6 +126,8 @@ fn ecdsa_generate_pkcs8_test() {
         &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
         &signature::ECDSA_P384_SHA384_ASN1_SIGNING,
         &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+        &signature::ECDSA_P521_SHA512_ASN1_SIGNING,
+        &signature::ECDSA_P521_SHA512_FIXED_SIGNING,
     ] {
         let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
         println!();
@@ -130,6 +142,31 @@ fn ecdsa_generate_pkcs8_test() {
     }
 }
 
+#[cfg(feature = "use_heap")]
+#[test]
+fn ecdsa_export_test() {
+    let rng = rand::SystemRandom::new();
+
+    for alg in &[
+        &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+        &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+        &signature::ECDSA_P521_SHA512_ASN1_SIGNING,
+    ] {
+        let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+        let key_pair = signature::EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref()).unwrap();
+        assert_eq!(key_pair.to_pkcs8().as_ref(), pkcs8.as_ref());
+
+        let private_key = key_pair.export_private_key_unchecked();
+        let imported = signature::EcdsaKeyPair::from_private_key_and_public_key(
+            alg,
+            private_key,
+            key_pair.public_key().as_ref(),
+        )
+        .unwrap();
+        assert_eq!(imported.to_pkcs8().as_ref(), pkcs8.as_ref());
+    }
+}
+
 #[test]
 fn signature_ecdsa_verify_asn1_test() {
     test::run(
@@ -149,6 +186,7 @@ fn signature_ecdsa_verify_asn1_test() {
                 ("P-256", "SHA384") => &signature::ECDSA_P256_SHA384_ASN1,
                 ("P-384", "SHA256") => &signature::ECDSA_P384_SHA256_ASN1,
                 ("P-384", "SHA384") => &signature::ECDSA_P384_SHA384_ASN1,
+                ("P-521", "SHA512") => &signature::ECDSA_P521_SHA512_ASN1,
                 _ => {
                     panic!("Unsupported curve+digest: {}+{}", curve_name, digest_name);
                 }
@@ -158,6 +196,11 @@ fn signature_ecdsa_verify_asn1_test() {
                 signature::UnparsedPublicKey::new(alg, &public_key).verify(&msg, &sig);
             assert_eq!(actual_result.is_ok(), is_valid);
 
+            // An invalid public key is rejected by `ParsedPublicKey::new()`.
+            let actual_result = signature::ParsedPublicKey::new(alg, &public_key)
+                .and_then(|public_key| public_key.verify(&msg, &sig));
+            assert_eq!(actual_result.is_ok(), is_valid);
+
             #[allow(deprecated)]
             let actual_result = signature::verify(
                 alg,
@@ -190,6 +233,8 @@ fn signature_ecdsa_verify_fixed_test() {
             let alg = match (curve_name.as_str(), digest_name.as_str()) {
                 ("P-256", "SHA256") => &signature::ECDSA_P256_SHA256_FIXED,
                 ("P-384", "SHA384") => &signature::ECDSA_P384_SHA384_FIXED,
+                ("P-521", "SHA512") => &signature::ECDSA_P521_SHA512_FIXED,
+                ("K-256", "SHA256") => &signature::ECDSA_K256_SHA256_FIXED,
                 _ => {
                     panic!("Unsupported curve+digest: {}+{}", curve_name, digest_name);
                 }
@@ -201,6 +246,11 @@ fn signature_ecdsa_verify_fixed_test() {
                 signature::UnparsedPublicKey::new(alg, &public_key).verify(&msg, &sig);
             assert_eq!(actual_result.is_ok(), is_valid);
 
+            // An invalid public key is rejected by `ParsedPublicKey::new()`.
+            let actual_result = signature::ParsedPublicKey::new(alg, &public_key)
+                .and_then(|public_key| public_key.verify(&msg, &sig));
+            assert_eq!(actual_result.is_ok(), is_valid);
+
             #[allow(deprecated)]
             let actual_result = signature::verify(
                 alg,
@@ -215,6 +265,198 @@ fn signature_ecdsa_verify_fixed_test() {
     );
 }
 
+#[test]
+fn signature_ecdsa_external_key_pair_test() {
+    #[derive(Debug)]
+    struct TestSigner {
+        digest: digest::Digest,
+        signature: Vec<u8>,
+    }
+
+    impl signature::EcdsaDigestSigner for TestSigner {
+        fn sign_digest(
+            &self,
+            digest: &[u8],
+            signature: &mut [u8],
+        ) -> Result<(), error::Unspecified> {
+            assert_eq!(digest, self.digest.as_ref());
+            if signature.len() != self.signature.len() {
+                return Err(error::Unspecified);
+            }
+            signature.copy_from_slice(&self.signature);
+            Ok(())
+        }
+    }
+
+    test::run(
+        test_file!("ecdsa_verify_fixed_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+
+            let curve_name = test_case.consume_string("Curve");
+            let digest_name = test_case.consume_string("Digest");
+
+            let msg = test_case.consume_bytes("Msg");
+            let public_key = test_case.consume_bytes("Q");
+            let sig = test_case.consume_bytes("Sig");
+            let expected_result = test_case.consume_string("Result");
+
+            let (fixed_alg, asn1_algs) = match (curve_name.as_str(), digest_name.as_str()) {
+                ("P-256", "SHA256") => (
+                    &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+                    Some((
+                        &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+                        &signature::ECDSA_P256_SHA256_ASN1,
+                    )),
+                ),
+                ("P-384", "SHA384") => (
+                    &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+                    Some((
+                        &signature::ECDSA_P384_SHA384_ASN1_SIGNING,
+                        &signature::ECDSA_P384_SHA384_ASN1,
+                    )),
+                ),
+                ("P-521", "SHA512") => (
+                    &signature::ECDSA_P521_SHA512_FIXED_SIGNING,
+                    Some((
+                        &signature::ECDSA_P521_SHA512_ASN1_SIGNING,
+                        &signature::ECDSA_P521_SHA512_ASN1,
+                    )),
+                ),
+                ("K-256", "SHA256") => (&signature::ECDSA_K256_SHA256_FIXED_SIGNING, None),
+                _ => {
+                    panic!("Unsupported curve+digest: {}+{}", curve_name, digest_name);
+                }
+            };
+            let digest_alg = match digest_name.as_str() {
+                "SHA256" => &digest::SHA256,
+                "SHA384" => &digest::SHA384,
+                _ => &digest::SHA512,
+            };
+
+            let is_valid = expected_result == "P (0 )";
+
+            let signer = |alg: &'static signature::EcdsaSigningAlgorithm| {
+                signature::ExternalEcdsaKeyPair::new(
+                    alg,
+                    &public_key,
+                    TestSigner {
+                        digest: digest::digest(digest_alg, &msg),
+                        signature: sig.clone(),
+                    },
+                )
+            };
+
+            // An invalid signature is never passed on, whether the public key
+            // or the signature is what is wrong.
+            let actual_result = signer(fixed_alg)
+                .map_err(|_| error::Unspecified)
+                .and_then(|key_pair| key_pair.sign(&msg));
+            assert_eq!(actual_result.is_ok(), is_valid);
+            if let Ok(actual) = actual_result {
+                assert_eq!(actual.as_ref(), &sig[..]);
+            }
+
+            if let (true, Some((asn1_alg, verification_alg))) = (is_valid, asn1_algs) {
+                let key_pair = signer(asn1_alg).unwrap();
+                assert_eq!(key_pair.public_key().as_ref(), &public_key[..]);
+                let actual = key_pair.sign(&msg).unwrap();
+                signature::UnparsedPublicKey::new(verification_alg, &public_key)
+                    .verify(&msg, actual.as_ref())
+                    .unwrap();
+            }
+
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn signature_ecdsa_recover_public_key_test() {
+    test::run(
+        test_file!("ecdsa_recover_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+
+            let curve_name = test_case.consume_string("Curve");
+            let digest_name = test_case.consume_string("Digest");
+            let msg = test_case.consume_bytes("Msg");
+            let sig = test_case.consume_bytes("Sig");
+            let recovery_id = test_case.consume_usize("RecoveryId");
+
+            let alg = match (curve_name.as_str(), digest_name.as_str()) {
+                ("P-256", "SHA256") => &signature::ECDSA_P256_SHA256_FIXED,
+                ("P-384", "SHA384") => &signature::ECDSA_P384_SHA384_FIXED,
+                ("K-256", "SHA256") => &signature::ECDSA_K256_SHA256_FIXED,
+                _ => {
+                    panic!("Unsupported curve+digest: {}+{}", curve_name, digest_name);
+                }
+            };
+
+            let result = alg.recover_public_key(&msg, &sig, recovery_id as u8);
+            match test_case.consume_optional_string("Error") {
+                None => {
+                    let expected_public_key = test_case.consume_bytes("Q");
+                    let public_key = result.unwrap();
+                    assert_eq!(public_key.as_ref(), &expected_public_key[..]);
+                    assert!(signature::UnparsedPublicKey::new(alg, &public_key)
+                        .verify(&msg, &sig)
+                        .is_ok());
+                }
+                Some(_) => {
+                    assert!(result.is_err());
+                }
+            }
+
+            Ok(())
+        },
+    );
+}
+
+#[cfg(feature = "use_heap")]
+#[test]
+fn ecdsa_sign_recoverable_test() {
+    let rng = rand::SystemRandom::new();
+    const MESSAGE: &[u8] = b"hello, world";
+
+    for &(signing_alg, verification_alg) in &[
+        (
+            &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+            &signature::ECDSA_P256_SHA256_FIXED,
+        ),
+        (
+            &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+            &signature::ECDSA_P256_SHA256_ASN1,
+        ),
+        (
+            &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+            &signature::ECDSA_P384_SHA384_FIXED,
+        ),
+        (
+            &signature::ECDSA_K256_SHA256_FIXED_SIGNING,
+            &signature::ECDSA_K256_SHA256_FIXED,
+        ),
+    ] {
+        let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(signing_alg, &rng).unwrap();
+        let key_pair = signature::EcdsaKeyPair::from_pkcs8(signing_alg, pkcs8.as_ref()).unwrap();
+
+        for _ in 0..8 {
+            let (sig, recovery_id) = key_pair.sign_recoverable(&rng, MESSAGE).unwrap();
+            assert!(recovery_id <= 3);
+            let recovered = verification_alg
+                .recover_public_key(MESSAGE, sig.as_ref(), recovery_id)
+                .unwrap();
+            assert_eq!(recovered.as_ref(), key_pair.public_key().as_ref());
+
+            // Flipping the parity bit recovers some other key.
+            let other = verification_alg
+                .recover_public_key(MESSAGE, sig.as_ref(), recovery_id ^ 1)
+                .unwrap();
+            assert_ne!(other.as_ref(), key_pair.public_key().as_ref());
+        }
+    }
+}
+
 #[test]
 fn ecdsa_test_public_key_coverage() {
     const PRIVATE_KEY: &[u8] = include_bytes!("ecdsa_test_private_key_p256.p8");
@@ -30,8 +30,10 @@
     variant_size_differences,
     warnings
 )]
+#![cfg(feature = "sig-ed25519")]
 
 use ring::{
+    digest,
     signature::{self, Ed25519KeyPair, KeyPair},
     test, test_file,
 };
@@ -84,9 +86,15 @@ fn test_signature_ed25519() {
         );
         assert!(actual_result.is_ok());
 
+        let parsed_public_key =
+            signature::ParsedPublicKey::new(&signature::ED25519, &public_key).unwrap();
+        assert!(parsed_public_key.verify(&msg, &expected_sig).is_ok());
+
         let mut tampered_sig = expected_sig;
         tampered_sig[0] ^= 1;
 
+        assert!(parsed_public_key.verify(&msg, &tampered_sig).is_err());
+
         assert!(
             signature::UnparsedPublicKey::new(&signature::ED25519, &public_key)
                 .verify(&msg, &tampered_sig)
@@ -106,6 +114,280 @@ fn test_signature_ed25519() {
     });
 }
 
+/// Test vectors from RFC 8032 Section 7.2, and more.
+#[test]
+fn test_signature_ed25519_ctx_ph() {
+    test::run(
+        test_file!("ed25519_ctx_ph_tests.txt"),
+        |section, test_case| {
+            let seed = test_case.consume_bytes("SEED");
+            let public_key = test_case.consume_bytes("PUB");
+            let msg = test_case.consume_bytes("MESSAGE");
+            let context = test_case.consume_bytes("CONTEXT");
+            let expected_sig = test_case.consume_bytes("SIG");
+
+            let key_pair = Ed25519KeyPair::from_seed_and_public_key(&seed, &public_key).unwrap();
+            let mut tampered_sig = expected_sig.clone();
+            tampered_sig[0] ^= 1;
+
+            match section {
+                "Ed25519ctx" => {
+                    let actual_sig = key_pair.sign_ctx(&context, &msg).unwrap();
+                    assert_eq!(&expected_sig[..], actual_sig.as_ref());
+                    assert!(signature::ED25519
+                        .verify_ctx(&public_key, &context, &msg, &expected_sig)
+                        .is_ok());
+                    assert!(signature::ED25519
+                        .verify_ctx(&public_key, &context, &msg, &tampered_sig)
+                        .is_err());
+
+                    // The signature isn't valid for Ed25519ph.
+                    let msg_digest = digest::digest(&digest::SHA512, &msg);
+                    assert!(signature::ED25519
+                        .verify_prehashed(&public_key, &context, &msg_digest, &expected_sig)
+                        .is_err());
+                }
+                "Ed25519ph" => {
+                    // Hash the message incrementally, as with a large file.
+                    let mut ctx = digest::Context::new(&digest::SHA512);
+                    for chunk in msg.chunks(2) {
+                        ctx.update(chunk);
+                    }
+                    let msg_digest = ctx.finish();
+
+                    let actual_sig = key_pair.sign_prehashed(&context, &msg_digest).unwrap();
+                    assert_eq!(&expected_sig[..], actual_sig.as_ref());
+                    assert!(signature::ED25519
+                        .verify_prehashed(&public_key, &context, &msg_digest, &expected_sig)
+                        .is_ok());
+                    assert!(signature::ED25519
+                        .verify_prehashed(&public_key, &context, &msg_digest, &tampered_sig)
+                        .is_err());
+
+                    // The signature isn't valid for Ed25519ctx.
+                    if !context.is_empty() {
+                        assert!(signature::ED25519
+                            .verify_ctx(&public_key, &context, &msg, &expected_sig)
+                            .is_err());
+                    }
+                }
+                _ => unreachable!(),
+            }
+
+            // The signature isn't valid for Ed25519.
+            assert!(
+                signature::UnparsedPublicKey::new(&signature::ED25519, &public_key)
+                    .verify(&msg, &expected_sig)
+                    .is_err()
+            );
+
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn test_signature_ed25519_ctx_ph_params() {
+    let key_pair = Ed25519KeyPair::from_seed_unchecked(&[1; 32]).unwrap();
+    let msg = b"message";
+    let sha512 = digest::digest(&digest::SHA512, msg);
+    let sha256 = digest::digest(&digest::SHA256, msg);
+
+    // Ed25519ctx doesn't allow an empty context; Ed25519ph does.
+    assert!(key_pair.sign_ctx(&[], msg).is_err());
+    assert!(key_pair.sign_ctx(&[0; 255], msg).is_ok());
+    assert!(key_pair.sign_ctx(&[0; 256], msg).is_err());
+    assert!(key_pair.sign_prehashed(&[], &sha512).is_ok());
+    assert!(key_pair.sign_prehashed(&[0; 255], &sha512).is_ok());
+    assert!(key_pair.sign_prehashed(&[0; 256], &sha512).is_err());
+
+    // Ed25519ph is only defined with SHA-512.
+    assert!(key_pair.sign_prehashed(&[], &sha256).is_err());
+    let sig = key_pair.sign_prehashed(&[], &sha512).unwrap();
+    let public_key = key_pair.public_key().as_ref();
+    assert!(signature::ED25519
+        .verify_prehashed(public_key, &[], &sha256, sig.as_ref())
+        .is_err());
+    assert!(signature::ED25519
+        .verify_ctx(public_key, &[], msg, sig.as_ref())
+        .is_err());
+}
+
+#[test]
+fn test_signature_ed25519_verify_batch() {
+    let mut vectors = Vec::new();
+    test::run(test_file!("ed25519_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let _ = test_case.consume_bytes("SEED");
+        let public_key = test_case.consume_bytes("PUB");
+        let msg = test_case.consume_bytes("MESSAGE");
+        let sig = test_case.consume_bytes("SIG");
+        vectors.push((public_key, msg, sig));
+        Ok(())
+    });
+
+    let batch = vectors
+        .iter()
+        .map(|(public_key, msg, sig)| (&public_key[..], &msg[..], &sig[..]))
+        .collect::<Vec<_>>();
+
+    assert!(signature::ED25519.verify_batch(&[]).is_ok());
+    assert!(signature::ED25519.verify_batch(&batch).is_ok());
+    for len in 1..=16 {
+        assert!(signature::ED25519.verify_batch(&batch[..len]).is_ok());
+    }
+
+    // Any single invalid signature makes the batch invalid.
+    for &i in &[0, 7, 15] {
+        let mut tampered_sig = vectors[i].2.clone();
+        tampered_sig[0] ^= 1;
+        let mut tampered = batch[..16].to_vec();
+        tampered[i].2 = &tampered_sig;
+        assert!(signature::ED25519.verify_batch(&tampered).is_err());
+
+        let mut tampered_sig = vectors[i].2.clone();
+        tampered_sig[32] ^= 1;
+        tampered[i].2 = &tampered_sig;
+        assert!(signature::ED25519.verify_batch(&tampered).is_err());
+
+        let mut tampered = batch[..16].to_vec();
+        tampered[i].1 = b"tampered";
+        assert!(signature::ED25519.verify_batch(&tampered).is_err());
+
+        let mut tampered = batch[..16].to_vec();
+        tampered[i].0 = batch[i + 1].0;
+        assert!(signature::ED25519.verify_batch(&tampered).is_err());
+
+        let mut tampered = batch[..16].to_vec();
+        tampered[i].2 = &vectors[i].2[..63];
+        assert!(signature::ED25519.verify_batch(&tampered).is_err());
+    }
+
+    // Swapping the signatures of two messages is detected.
+    let mut swapped = batch[..4].to_vec();
+    swapped[0].2 = batch[1].2;
+    swapped[1].2 = batch[0].2;
+    assert!(signature::ED25519.verify_batch(&swapped).is_err());
+}
+
+#[test]
+fn test_signature_ed25519_verify_batch_noncanonical_r() {
+    let rng = test::rand::FixedByteRandom { byte: 0x42 };
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let public_key = key_pair.public_key().as_ref();
+    let msg = b"hello, world";
+    let sig = key_pair.sign(msg);
+    assert!(signature::ED25519
+        .verify_batch(&[(public_key, msg, sig.as_ref())])
+        .is_ok());
+
+    // R = (0, 1), the identity, with the sign bit of x set.
+    let mut sig = [0u8; 64];
+    sig[0] = 1;
+    sig[31] = 0x80;
+    assert!(
+        signature::UnparsedPublicKey::new(&signature::ED25519, public_key)
+            .verify(msg, &sig)
+            .is_err()
+    );
+    assert!(signature::ED25519
+        .verify_batch(&[(public_key, msg, &sig)])
+        .is_err());
+
+    // y = p + 1, a non-canonical encoding of the identity.
+    let mut sig = [0u8; 64];
+    sig[0] = 0xee;
+    for b in &mut sig[1..31] {
+        *b = 0xff;
+    }
+    sig[31] = 0x7f;
+    assert!(signature::ED25519
+        .verify_batch(&[(public_key, msg, &sig)])
+        .is_err());
+}
+
+#[test]
+fn test_signature_ed25519_policies() {
+    // RFC 8032 Section 7.1, TEST 1.
+    let public_key =
+        test::from_hex("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a").unwrap();
+    let sig = test::from_hex(
+        "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+    )
+    .unwrap();
+
+    // The same signature with `S + l` instead of `S`.
+    let unreduced_s_sig = test::from_hex(
+        "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901554c8c7872aa064e049dbb3013fbf29380d25bf5f0595bbe24655141438e7a101b",
+    )
+    .unwrap();
+
+    // The identity, canonically and with y = p + 1.
+    let identity =
+        test::from_hex("0100000000000000000000000000000000000000000000000000000000000000").unwrap();
+    let noncanonical_identity =
+        test::from_hex("eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f").unwrap();
+
+    // A point of order 8. For the empty message, `k` is 7 (mod 8), so `[k]A`
+    // isn't the identity.
+    let order_8 =
+        test::from_hex("26e8958fc2b227b045c3f489f2ef98f0d5dfac05d3c63339b13802886d53fc05").unwrap();
+
+    // `R` followed by `S = 0`.
+    let zero_s_sig = |r: &[u8]| [r, &[0u8; 32][..]].concat();
+
+    // (public key, signature, ED25519, ED25519_ZIP215, ED25519_STRICT)
+    let cases: &[(&[u8], &[u8], bool, bool, bool)] = &[
+        (&public_key, &sig, true, true, true),
+        (&public_key, &unreduced_s_sig, true, false, false),
+        (&identity, &zero_s_sig(&identity), true, true, false),
+        (
+            &noncanonical_identity,
+            &zero_s_sig(&identity),
+            true,
+            true,
+            false,
+        ),
+        (
+            &identity,
+            &zero_s_sig(&noncanonical_identity),
+            false,
+            true,
+            false,
+        ),
+        (&order_8, &zero_s_sig(&identity), false, true, false),
+    ];
+
+    for &(public_key, sig, legacy, zip215, strict) in cases {
+        for &(alg, expected) in &[
+            (&signature::ED25519, legacy),
+            (&signature::ED25519_ZIP215, zip215),
+            (&signature::ED25519_STRICT, strict),
+        ] {
+            let result = signature::UnparsedPublicKey::new(alg, public_key).verify(b"", sig);
+            assert_eq!(result.is_ok(), expected);
+
+            let result = signature::ParsedPublicKey::new(alg, public_key)
+                .and_then(|public_key| public_key.verify(b"", sig));
+            assert_eq!(result.is_ok(), expected);
+        }
+
+        // Only with `ED25519_ZIP215` does batch verification always agree.
+        let result = signature::ED25519_ZIP215.verify_batch(&[(public_key, b"", sig)]);
+        assert_eq!(result.is_ok(), zip215);
+    }
+
+    assert_eq!(
+        format!("{:?}", signature::ED25519_ZIP215),
+        "ring::signature::ED25519_ZIP215"
+    );
+    assert_eq!(
+        format!("{:?}", signature::ED25519_STRICT),
+        "ring::signature::ED25519_STRICT"
+    );
+}
+
 #[test]
 fn test_ed25519_from_seed_and_public_key_misuse() {
     const PRIVATE_KEY: &[u8] = include_bytes!("ed25519_test_private_key.bin");
@@ -170,6 +452,33 @@ fn test_ed25519_from_pkcs8() {
     );
 }
 
+#[test]
+fn test_ed25519_export() {
+    const PRIVATE_KEY: &[u8] = include_bytes!("ed25519_test_private_key.p8");
+
+    let key_pair = Ed25519KeyPair::from_pkcs8(PRIVATE_KEY).unwrap();
+    assert_eq!(key_pair.to_pkcs8().as_ref(), PRIVATE_KEY);
+
+    let seed = key_pair.export_seed_unchecked();
+    assert_eq!(seed, &PRIVATE_KEY[16..48]);
+    let imported =
+        Ed25519KeyPair::from_seed_and_public_key(seed, key_pair.public_key().as_ref()).unwrap();
+    assert_eq!(imported.to_pkcs8().as_ref(), PRIVATE_KEY);
+
+    // The v1 document is the v2 document without the public key.
+    let v1 = key_pair.to_pkcs8v1();
+    assert_eq!(&v1.as_ref()[..2], &[0x30, 0x2e]);
+    assert_eq!(&v1.as_ref()[2..4], &[0x02, 0x01]);
+    assert_eq!(v1.as_ref()[4], 0x00);
+    assert_eq!(&v1.as_ref()[5..], &PRIVATE_KEY[5..48]);
+    assert!(Ed25519KeyPair::from_pkcs8(v1.as_ref()).is_err());
+    let imported = Ed25519KeyPair::from_pkcs8_maybe_unchecked(v1.as_ref()).unwrap();
+    assert_eq!(
+        imported.public_key().as_ref(),
+        key_pair.public_key().as_ref()
+    );
+}
+
 #[test]
 fn ed25519_test_public_key_coverage() {
     const PRIVATE_KEY: &[u8] = include_bytes!("ed25519_test_private_key.p8");
@@ -0,0 +1,113 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{
+    signature::{self, Ed448KeyPair, KeyPair},
+    test, test_file,
+};
+
+/// Test vectors from RFC 8032 Section 7.4.
+#[test]
+fn test_signature_ed448() {
+    test::run(test_file!("ed448_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let seed = test_case.consume_bytes("SEED");
+        assert_eq!(57, seed.len());
+
+        let public_key = test_case.consume_bytes("PUB");
+        assert_eq!(signature::ED448_PUBLIC_KEY_LEN, public_key.len());
+
+        let msg = test_case.consume_bytes("MESSAGE");
+        let context = test_case.consume_bytes("CONTEXT");
+        let expected_sig = test_case.consume_bytes("SIG");
+
+        let mut tampered_sig = expected_sig.clone();
+        tampered_sig[0] ^= 1;
+
+        {
+            let key_pair = Ed448KeyPair::from_seed_and_public_key(&seed, &public_key).unwrap();
+            let actual_sig = key_pair.sign_ctx(&context, &msg).unwrap();
+            assert_eq!(&expected_sig[..], actual_sig.as_ref());
+        }
+
+        // Test PKCS#8 generation, parsing, and private-to-public calculations.
+        let rng = test::rand::FixedSliceRandom { bytes: &seed };
+        let pkcs8 = Ed448KeyPair::generate_pkcs8(&rng).unwrap();
+        let key_pair = Ed448KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        assert_eq!(public_key, key_pair.public_key().as_ref());
+
+        let actual_sig = key_pair.sign_ctx(&context, &msg).unwrap();
+        assert_eq!(&expected_sig[..], actual_sig.as_ref());
+
+        assert!(signature::ED448
+            .verify_ctx(&public_key, &context, &msg, &expected_sig)
+            .is_ok());
+        assert!(signature::ED448
+            .verify_ctx(&public_key, &context, &msg, &tampered_sig)
+            .is_err());
+
+        // `sign` and `VerificationAlgorithm::verify` use the empty context.
+        let public_key = signature::UnparsedPublicKey::new(&signature::ED448, &public_key);
+        if context.is_empty() {
+            assert_eq!(&expected_sig[..], key_pair.sign(&msg).as_ref());
+            assert!(public_key.verify(&msg, &expected_sig).is_ok());
+        } else {
+            assert!(public_key.verify(&msg, &expected_sig).is_err());
+        }
+        assert!(public_key.verify(&msg, &tampered_sig).is_err());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn test_ed448_context_too_long() {
+    let rng = test::rand::FixedByteRandom { byte: 0x42 };
+    let pkcs8 = Ed448KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair = Ed448KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+
+    let context = [0u8; 256];
+    assert!(key_pair.sign_ctx(&context, b"").is_err());
+    let sig = key_pair.sign_ctx(&context[..255], b"").unwrap();
+    assert!(signature::ED448
+        .verify_ctx(
+            key_pair.public_key().as_ref(),
+            &context[..255],
+            b"",
+            sig.as_ref()
+        )
+        .is_ok());
+    assert!(signature::ED448
+        .verify_ctx(key_pair.public_key().as_ref(), &context, b"", sig.as_ref())
+        .is_err());
+}
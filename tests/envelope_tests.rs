@@ -0,0 +1,126 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(all(feature = "use_heap", feature = "aead-aes-gcm-siv"))]
+
+use ring::{envelope, error, hpke, rand, test};
+
+const SK1: &str = "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20";
+const SK2: &str = "4142434445464748494a4b4c4d4e4f505152535455565758595a5b5c5d5e5f60";
+const AAD: &[u8] = b"object 1234";
+const PLAINTEXT: &[u8] = b"The quick brown fox jumps over the lazy dog.";
+
+// Sealed for the key pairs from `SK1` and `SK2` with the DEK 0x42..., and
+// the ephemeral private keys 0x11... and 0x22.... Generated with an
+// independent implementation built on the Python cryptography package.
+const ENVELOPE: &str = "52454e5601000201aaa8fff703b50b2200507b4e909bbe7ffe44c465a220037d608ee35897d31ef972f07f74892cb0f73f13cf88fd151586056dc2a83285ad7488630c818ef8262348e24e278d6aa2a31642d291ee2d784800c94972985482a6824b01bc841db8fea3efd800500faa684ed28867b97f4a6a2dee5df8ce974e76b7018e3f22a1c4cf2678570f202971049a8f6a2265755db9e2e7fd240b7fcdd9a6a25bbf4760a72a91d524b2bdde04c2ae8f5ff6869d16583cdf6fd6a21924a435f7d928e6fb4ae9ef17da4a99daba3f3e1cccdd3657f4cc5872283b6a9162655311b58cce68ce9c0af7043265fc85a3153889eb8d211697b9";
+
+fn key_pair(private_key: &str) -> hpke::KeyPair {
+    let private_key = test::from_hex(private_key).unwrap();
+    hpke::KeyPair::generate(&test::rand::FixedSliceRandom {
+        bytes: &private_key,
+    })
+    .unwrap()
+}
+
+#[test]
+fn envelope_known_answer_test() {
+    let key_pair1 = key_pair(SK1);
+    let key_pair2 = key_pair(SK2);
+
+    let rng = test::rand::FixedSliceSequenceRandom::new(&[&[0x42; 32], &[0x11; 32], &[0x22; 32]]);
+    let sealed = envelope::seal(
+        &[key_pair1.public_key(), key_pair2.public_key()],
+        AAD,
+        PLAINTEXT,
+        &rng,
+    )
+    .unwrap();
+    assert_eq!(sealed, test::from_hex(ENVELOPE).unwrap());
+
+    for key_pair in &[&key_pair1, &key_pair2] {
+        assert_eq!(envelope::open(key_pair, AAD, &sealed).unwrap(), PLAINTEXT);
+    }
+}
+
+#[test]
+fn envelope_round_trip_test() {
+    let rng = rand::SystemRandom::new();
+    let key_pairs = (0..3)
+        .map(|_| hpke::KeyPair::generate(&rng).unwrap())
+        .collect::<Vec<_>>();
+    let public_keys = key_pairs
+        .iter()
+        .map(|key_pair| key_pair.public_key())
+        .collect::<Vec<_>>();
+
+    for plaintext in &[&b""[..], PLAINTEXT] {
+        let sealed = envelope::seal(&public_keys, AAD, plaintext, &rng).unwrap();
+        for key_pair in &key_pairs {
+            assert_eq!(envelope::open(key_pair, AAD, &sealed).unwrap(), *plaintext);
+        }
+    }
+}
+
+#[test]
+fn envelope_rejects_test() {
+    let rng = rand::SystemRandom::new();
+    let recipient = hpke::KeyPair::generate(&rng).unwrap();
+    let other = hpke::KeyPair::generate(&rng).unwrap();
+
+    assert_eq!(
+        envelope::seal(&[], AAD, PLAINTEXT, &rng),
+        Err(error::Unspecified)
+    );
+    assert!(envelope::seal(&[&[0; 31]], AAD, PLAINTEXT, &rng).is_err());
+
+    let sealed = envelope::seal(&[recipient.public_key()], AAD, PLAINTEXT, &rng).unwrap();
+    assert!(envelope::open(&recipient, AAD, &sealed).is_ok());
+
+    // Not a recipient.
+    assert!(envelope::open(&other, AAD, &sealed).is_err());
+
+    // Wrong AAD.
+    assert!(envelope::open(&recipient, b"object 1235", &sealed).is_err());
+
+    // Any modification, including to the list of recipients, is detected.
+    for i in 0..sealed.len() {
+        let mut modified = sealed.clone();
+        modified[i] ^= 1;
+        assert!(envelope::open(&recipient, AAD, &modified).is_err());
+    }
+
+    // Truncation is detected.
+    for len in 0..sealed.len() {
+        assert!(envelope::open(&recipient, AAD, &sealed[..len]).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "use_heap")]
+
+use ring::{fpe, test, test_file};
+
+const SYMBOLS: &str = "0123456789abcdefghijklmnopqrstuvwxyz";
+
+#[test]
+fn fpe_tests() {
+    test::run(test_file!("fpe_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let algorithm = match test_case.consume_string("Algorithm").as_str() {
+            "FF1_AES_128" => &fpe::FF1_AES_128,
+            "FF1_AES_256" => &fpe::FF1_AES_256,
+            "FF3_1_AES_128" => &fpe::FF3_1_AES_128,
+            "FF3_1_AES_256" => &fpe::FF3_1_AES_256,
+            _ => unreachable!(),
+        };
+        let key_value = test_case.consume_bytes("Key");
+        let radix = test_case.consume_usize("Radix");
+        let tweak = test_case.consume_bytes("Tweak");
+        let plaintext = test_case.consume_string("Plaintext");
+        let ciphertext = test_case.consume_string("Ciphertext");
+
+        let alphabet = fpe::Alphabet::new(&SYMBOLS[..radix])?;
+        let key = fpe::Key::new(algorithm, &key_value, alphabet.radix())?;
+
+        assert_eq!(key.encrypt_str(&alphabet, &tweak, &plaintext)?, ciphertext);
+        assert_eq!(key.decrypt_str(&alphabet, &tweak, &ciphertext)?, plaintext);
+
+        let mut numerals = alphabet.to_numerals(&plaintext)?;
+        key.encrypt(&tweak, &mut numerals)?;
+        assert_eq!(alphabet.to_string(&numerals), ciphertext);
+        key.decrypt(&tweak, &mut numerals)?;
+        assert_eq!(alphabet.to_string(&numerals), plaintext);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn fpe_key_new() {
+    for &(algorithm, key_len) in &[
+        (&fpe::FF1_AES_128, 16),
+        (&fpe::FF1_AES_256, 32),
+        (&fpe::FF3_1_AES_128, 16),
+        (&fpe::FF3_1_AES_256, 32),
+    ] {
+        assert_eq!(algorithm.key_len(), key_len);
+        assert!(fpe::Key::new(algorithm, &vec![0; key_len], 10).is_ok());
+        assert!(fpe::Key::new(algorithm, &vec![0; key_len - 1], 10).is_err());
+        assert!(fpe::Key::new(algorithm, &vec![0; key_len + 1], 10).is_err());
+        assert!(fpe::Key::new(algorithm, &vec![0; key_len], 1).is_err());
+        assert!(fpe::Key::new(algorithm, &vec![0; key_len], 1 << 16).is_ok());
+        assert!(fpe::Key::new(algorithm, &vec![0; key_len], (1 << 16) + 1).is_err());
+    }
+}
+
+#[test]
+fn fpe_input_lengths() {
+    let ff1 = fpe::Key::new(&fpe::FF1_AES_128, &[0; 16], 10).unwrap();
+    let ff3_1 = fpe::Key::new(&fpe::FF3_1_AES_128, &[0; 16], 10).unwrap();
+    let tweak = [0u8; fpe::FF3_1_TWEAK_LEN];
+
+    // 10**6 >= 1,000,000.
+    for key in &[&ff1, &ff3_1] {
+        assert!(key.encrypt(&tweak, &mut [0; 5]).is_err());
+        assert!(key.encrypt(&tweak, &mut [0; 6]).is_ok());
+        assert!(key.decrypt(&tweak, &mut [0; 5]).is_err());
+        assert!(key.decrypt(&tweak, &mut [0; 6]).is_ok());
+    }
+
+    // 2 * floor(log_10(2**96)) = 56.
+    assert!(ff3_1.encrypt(&tweak, &mut [0; 56]).is_ok());
+    assert!(ff3_1.encrypt(&tweak, &mut [0; 57]).is_err());
+    assert!(ff1.encrypt(&tweak, &mut [0; 57]).is_ok());
+
+    // FF3-1 tweaks are exactly 56 bits; FF1 tweaks may be any length.
+    assert!(ff3_1.encrypt(&tweak[1..], &mut [0; 6]).is_err());
+    assert!(ff3_1
+        .encrypt(&[0; fpe::FF3_1_TWEAK_LEN + 1], &mut [0; 6])
+        .is_err());
+    assert!(ff1.encrypt(&[], &mut [0; 6]).is_ok());
+    assert!(ff1.encrypt(&[0; 100], &mut [0; 6]).is_ok());
+
+    // Numerals must be less than the radix.
+    assert!(ff1.encrypt(&tweak, &mut [0, 0, 0, 0, 0, 10]).is_err());
+    assert!(ff1.encrypt(&tweak, &mut [0, 0, 0, 0, 0, 9]).is_ok());
+}
+
+#[test]
+fn fpe_alphabet() {
+    assert!(fpe::Alphabet::new("").is_err());
+    assert!(fpe::Alphabet::new("0").is_err());
+    assert!(fpe::Alphabet::new("01").is_ok());
+    assert!(fpe::Alphabet::new("0120").is_err());
+    assert_eq!(fpe::Alphabet::new("αβγ").unwrap().radix(), 3);
+
+    let alphabet = fpe::Alphabet::new("0123456789").unwrap();
+    assert_eq!(alphabet.to_numerals("0719").unwrap(), vec![0, 7, 1, 9]);
+    assert!(alphabet.to_numerals("07a9").is_err());
+    assert_eq!(alphabet.to_string(&[0, 7, 1, 9]), "0719");
+
+    // The alphabet and the key must agree on the radix.
+    let key = fpe::Key::new(&fpe::FF1_AES_128, &[0; 16], 16).unwrap();
+    assert!(key.encrypt_str(&alphabet, b"", "0123456789").is_err());
+}
+
+#[test]
+fn fpe_debug() {
+    let key = fpe::Key::new(&fpe::FF3_1_AES_256, &[0; 32], 10).unwrap();
+    assert_eq!("FF3_1_AES_256", format!("{:?}", key.algorithm()));
+    assert_eq!(
+        "Key { algorithm: FF3_1_AES_256, radix: 10 }",
+        format!("{:?}", key)
+    );
+}
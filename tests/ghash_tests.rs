@@ -0,0 +1,88 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{ghash, test, test_file};
+
+#[test]
+fn ghash_tests() {
+    test::run(test_file!("ghash_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let key = test_case.consume_bytes("Key");
+        let input = test_case.consume_bytes("Input");
+        let output = test_case.consume_bytes("Output");
+
+        let mut key_bytes = [0u8; ghash::KEY_LEN];
+        key_bytes.copy_from_slice(&key);
+
+        let mut ctx = ghash::Context::new(&key_bytes);
+        ctx.update_blocks(&input)?;
+        assert_eq!(&ctx.finalize()[..], &output[..]);
+
+        // Updating with the input split at each block boundary gives the
+        // same result.
+        for split in (0..=input.len()).step_by(ghash::BLOCK_LEN) {
+            let (first, second) = input.split_at(split);
+            let mut ctx = ghash::Context::new(&key_bytes);
+            ctx.update_blocks(first)?;
+            ctx.update_padded(second);
+            assert_eq!(&ctx.finalize()[..], &output[..]);
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn ghash_padded_test() {
+    let key = [0x42; ghash::KEY_LEN];
+    let input = [0x5a; 3 * ghash::BLOCK_LEN];
+
+    for len in 0..input.len() {
+        let mut padded = [0u8; 3 * ghash::BLOCK_LEN];
+        padded[..len].copy_from_slice(&input[..len]);
+        let padded_len = (len + ghash::BLOCK_LEN - 1) / ghash::BLOCK_LEN * ghash::BLOCK_LEN;
+
+        let mut expected = ghash::Context::new(&key);
+        expected.update_blocks(&padded[..padded_len]).unwrap();
+
+        let mut ctx = ghash::Context::new(&key);
+        ctx.update_padded(&input[..len]);
+        assert_eq!(ctx.finalize(), expected.finalize());
+    }
+
+    // Partial blocks must go through `update_padded()`.
+    let mut ctx = ghash::Context::new(&key);
+    assert!(ctx.update_blocks(&input[..1]).is_err());
+    assert!(ctx.update_blocks(&input[..(ghash::BLOCK_LEN + 1)]).is_err());
+    assert_eq!(ctx.finalize(), ghash::Context::new(&key).finalize());
+}
@@ -0,0 +1,106 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "use_heap")]
+
+use ring::{digest, hash_to_curve, test, test_file};
+
+#[test]
+fn hash_to_curve_test() {
+    test::run(
+        test_file!("hash_to_curve_tests.txt"),
+        |section, test_case| {
+            match section {
+                "expand_message_xmd" => {
+                    let digest_alg = test_case.consume_digest_alg("Digest").unwrap();
+                    let dst = test_case.consume_bytes("DST");
+                    let msg = test_case.consume_bytes("Msg");
+                    let len = test_case.consume_usize("Len");
+                    let expected = test_case.consume_bytes("Output");
+
+                    let mut actual = vec![0u8; len];
+                    hash_to_curve::expand_message_xmd(digest_alg, &msg, &dst, &mut actual)?;
+                    assert_eq!(actual, expected);
+                }
+
+                "hash_to_curve" => {
+                    let suite = suite(&test_case.consume_string("Suite"));
+                    let dst = test_case.consume_bytes("DST");
+                    let msg = test_case.consume_bytes("Msg");
+                    let expected = test_case.consume_bytes("P");
+
+                    let actual = hash_to_curve::hash_to_curve(suite, &msg, &dst)?;
+                    assert_eq!(actual.as_ref(), &expected[..]);
+                }
+
+                _ => unreachable!(),
+            }
+
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn hash_to_curve_empty_dst_test() {
+    let mut out = [0u8; 32];
+    assert!(hash_to_curve::expand_message_xmd(&digest::SHA256, b"msg", b"", &mut out).is_err());
+    assert!(
+        hash_to_curve::hash_to_curve(&hash_to_curve::P256_XMD_SHA256_SSWU_RO, b"msg", b"").is_err()
+    );
+}
+
+#[test]
+fn hash_to_curve_output_too_long_test() {
+    let mut out = vec![0u8; (255 * digest::SHA256.output_len) + 1];
+    assert!(hash_to_curve::expand_message_xmd(&digest::SHA256, b"msg", b"DST", &mut out).is_err());
+    let mut out = vec![0u8; 255 * digest::SHA256.output_len];
+    assert!(hash_to_curve::expand_message_xmd(&digest::SHA256, b"msg", b"DST", &mut out).is_ok());
+}
+
+#[test]
+fn hash_to_curve_suite_id_test() {
+    for id in &[
+        "P256_XMD:SHA-256_SSWU_RO_",
+        "edwards25519_XMD:SHA-512_ELL2_RO_",
+    ] {
+        assert_eq!(suite(id).id(), *id);
+    }
+}
+
+fn suite(id: &str) -> &'static hash_to_curve::Suite {
+    match id {
+        "P256_XMD:SHA-256_SSWU_RO_" => &hash_to_curve::P256_XMD_SHA256_SSWU_RO,
+        "edwards25519_XMD:SHA-512_ELL2_RO_" => &hash_to_curve::EDWARDS25519_XMD_SHA512_ELL2_RO,
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{
+    error, hd, rand,
+    signature::{self, KeyPair},
+    test, test_file,
+};
+
+#[test]
+fn hd_test() {
+    test::run(test_file!("hd_tests.txt"), |section, test_case| {
+        let seed = test_case.consume_bytes("Seed");
+        let path = parse_path(&test_case.consume_string("Path"));
+        let chain_code = test_case.consume_bytes("ChainCode");
+        let private_key = test_case.consume_bytes("PrivateKey");
+        let public_key = test_case.consume_bytes("PublicKey");
+
+        let alg = match section {
+            #[cfg(feature = "sig-ed25519")]
+            "ED25519" => &hd::ED25519,
+            #[cfg(not(feature = "sig-ed25519"))]
+            "ED25519" => return Ok(()),
+            "NIST_P256" => &hd::P256,
+            "SECP256K1" => &hd::SECP256K1,
+            _ => unreachable!(),
+        };
+
+        let master = hd::ExtendedPrivateKey::from_seed(alg, &seed)?;
+        let key = master.derive(&path)?;
+        assert_eq!(key.chain_code(), &chain_code[..]);
+        let extended_public_key = key.public_key()?;
+        assert_eq!(extended_public_key.chain_code(), &chain_code[..]);
+        assert_eq!(extended_public_key.public_key_bytes(), &public_key[..]);
+
+        match section {
+            #[cfg(feature = "sig-ed25519")]
+            "ED25519" => {
+                const MESSAGE: &[u8] = b"hello, world";
+                let key_pair = key.ed25519_key_pair().unwrap();
+                let expected =
+                    signature::Ed25519KeyPair::from_seed_unchecked(&private_key).unwrap();
+                assert_eq!(key_pair.public_key().as_ref(), &public_key[..]);
+                assert_eq!(
+                    key_pair.sign(MESSAGE).as_ref(),
+                    expected.sign(MESSAGE).as_ref()
+                );
+                assert!(key
+                    .ecdsa_key_pair(&signature::ECDSA_P256_SHA256_ASN1_SIGNING)
+                    .is_err());
+            }
+            _ => {
+                let (signing_alg, wrong_signing_alg) = if section == "SECP256K1" {
+                    (
+                        &signature::ECDSA_K256_SHA256_FIXED_SIGNING,
+                        &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+                    )
+                } else {
+                    (
+                        &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+                        &signature::ECDSA_P384_SHA384_ASN1_SIGNING,
+                    )
+                };
+                let key_pair = key.ecdsa_key_pair(signing_alg).unwrap();
+                assert_eq!(key_pair.public_key().as_ref(), &public_key[..]);
+                // `private_key` is the private key for `public_key`.
+                assert!(signature::EcdsaKeyPair::from_private_key_and_public_key(
+                    signing_alg,
+                    &private_key,
+                    &public_key
+                )
+                .is_ok());
+                #[cfg(feature = "sig-ed25519")]
+                assert!(key.ed25519_key_pair().is_err());
+                assert!(key.ecdsa_key_pair(wrong_signing_alg).is_err());
+            }
+        }
+
+        // Derive the public key of the normal suffix of the path from the
+        // public key of its last hardened ancestor.
+        let hardened_len = path
+            .iter()
+            .rposition(|&index| index >= hd::HARDENED)
+            .map_or(0, |i| i + 1);
+        let (hardened_path, normal_path) = path.split_at(hardened_len);
+        let ancestor = master.derive(hardened_path)?.public_key()?;
+        match ancestor.derive(normal_path) {
+            Ok(derived) => {
+                assert_eq!(derived.chain_code(), &chain_code[..]);
+                assert_eq!(derived.public_key_bytes(), &public_key[..]);
+            }
+            Err(error::Unspecified) => {
+                // Ed25519 has no normal derivation.
+                assert_eq!(section, "ED25519");
+                assert!(ancestor.derive_child(0).is_err());
+            }
+        }
+        assert!(ancestor.derive_child(hd::HARDENED).is_err());
+
+        Ok(())
+    });
+}
+
+#[cfg(feature = "sig-ed25519")]
+#[test]
+fn hd_ed25519_normal_derivation_test() {
+    let master = hd::ExtendedPrivateKey::from_seed(&hd::ED25519, &[0; 32]).unwrap();
+    assert!(master.derive_child(hd::HARDENED).is_ok());
+    assert!(master.derive_child(hd::HARDENED - 1).is_err());
+    assert!(master.derive(&[hd::HARDENED, 0]).is_err());
+}
+
+#[test]
+fn hd_seed_len_test() {
+    let algs: &[&hd::Algorithm] = &[
+        #[cfg(feature = "sig-ed25519")]
+        &hd::ED25519,
+        &hd::P256,
+        &hd::SECP256K1,
+    ];
+    for &alg in algs {
+        assert!(hd::ExtendedPrivateKey::from_seed(alg, &[0; 15]).is_err());
+        assert!(hd::ExtendedPrivateKey::from_seed(alg, &[0; 16]).is_ok());
+        assert!(hd::ExtendedPrivateKey::from_seed(alg, &[0; 64]).is_ok());
+        assert!(hd::ExtendedPrivateKey::from_seed(alg, &[0; 65]).is_err());
+    }
+}
+
+#[test]
+fn hd_sign_test() {
+    const MESSAGE: &[u8] = b"hello, world";
+    let rng = rand::SystemRandom::new();
+    let master = hd::ExtendedPrivateKey::from_seed(&hd::P256, &[1; 32]).unwrap();
+    let key = master.derive(&[hd::HARDENED, 7]).unwrap();
+    let key_pair = key
+        .ecdsa_key_pair(&signature::ECDSA_P256_SHA256_FIXED_SIGNING)
+        .unwrap();
+    let sig = key_pair.sign(&rng, MESSAGE).unwrap();
+
+    // A watch-only holder of the parent's extended public key can verify
+    // signatures from the child.
+    let parent = master
+        .derive_child(hd::HARDENED)
+        .unwrap()
+        .public_key()
+        .unwrap();
+    let public_key = parent.derive_child(7).unwrap();
+    signature::UnparsedPublicKey::new(
+        &signature::ECDSA_P256_SHA256_FIXED,
+        public_key.public_key_bytes(),
+    )
+    .verify(MESSAGE, sig.as_ref())
+    .unwrap();
+}
+
+fn parse_path(path: &str) -> Vec<u32> {
+    let mut components = path.split('/');
+    assert_eq!(components.next(), Some("m"));
+    components
+        .map(|component| match component.strip_suffix('\'') {
+            Some(index) => index.parse::<u32>().unwrap() | hd::HARDENED,
+            None => component.parse().unwrap(),
+        })
+        .collect()
+}
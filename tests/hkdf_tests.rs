@@ -31,7 +31,7 @@
     warnings
 )]
 
-use ring::{error, hkdf, test, test_file};
+use ring::{digest, error, hkdf, test, test_file};
 
 #[test]
 fn hkdf_tests() {
@@ -52,6 +52,10 @@ fn hkdf_tests() {
         salt.extract(&secret).expand(&info).fill(&mut out).unwrap();
         assert_eq!(out, expected_out);
 
+        let mut out = vec![0u8; expected_out.len()];
+        salt.extract(&secret).fill(&info, &mut out).unwrap();
+        assert_eq!(out, expected_out);
+
         // Test deprecated interface.
         let mut out = vec![0u8; expected_out.len()];
         #[allow(deprecated)]
@@ -61,3 +65,113 @@ fn hkdf_tests() {
         Ok(())
     });
 }
+
+#[test]
+fn hkdf_expand_multi() {
+    let salt = hkdf::Salt::new(&digest::SHA256, b"salt");
+    let prk = salt.extract(b"secret");
+
+    let mut key = [0u8; 32];
+    let mut iv = [0u8; 12];
+    let mut long = vec![0u8; 1000];
+    prk.expand_multi(&mut [
+        (b"key", &mut key[..]),
+        (b"iv", &mut iv[..]),
+        (b"long", &mut long[..]),
+    ])
+    .unwrap();
+
+    let expected = |info: &[u8], len: usize| {
+        let mut out = vec![0u8; len];
+        prk.expand(info).fill(&mut out).unwrap();
+        out
+    };
+    assert_eq!(&key[..], &expected(b"key", key.len())[..]);
+    assert_eq!(&iv[..], &expected(b"iv", iv.len())[..]);
+    assert_eq!(long, expected(b"long", long.len()));
+
+    // Nothing is written if any output is too long.
+    let mut key = [0u8; 32];
+    let mut too_long = vec![0u8; 255 * digest::SHA256_OUTPUT_LEN + 1];
+    assert!(prk
+        .expand_multi(&mut [(b"key", &mut key[..]), (b"too long", &mut too_long[..])])
+        .is_err());
+    assert_eq!(key, [0u8; 32]);
+}
+
+// Test vectors from RFC 8448 Section 3.
+#[test]
+fn hkdf_expand_label() {
+    let early_secret = hkdf::Salt::new(&digest::SHA256, &[0; 32]).extract(&[0; 32]);
+    let empty_hash = digest::digest(&digest::SHA256, b"");
+    let mut derived = [0u8; 32];
+    early_secret
+        .expand_label(b"derived", empty_hash.as_ref(), &mut derived)
+        .unwrap();
+    assert_eq!(
+        &derived[..],
+        &test::from_hex("6f2615a108c702c5678f54fc9dbab69716c076189c48250cebeac3576c3611ba")
+            .unwrap()[..]
+    );
+
+    // The server handshake write traffic key and IV.
+    let server_handshake_traffic_secret = hkdf::Prk::new_less_safe(
+        &digest::SHA256,
+        &test::from_hex("b67b7d690cc16c4e75e54213cb2d37b4e9c912bcded9105d42befd59d391ad38")
+            .unwrap(),
+    );
+    let mut key = [0u8; 16];
+    let mut iv = [0u8; 12];
+    server_handshake_traffic_secret
+        .expand_label(b"key", &[], &mut key)
+        .unwrap();
+    server_handshake_traffic_secret
+        .expand_label(b"iv", &[], &mut iv)
+        .unwrap();
+    assert_eq!(
+        &key[..],
+        &test::from_hex("3fce516009c21727d0f2e4e86ee403bc").unwrap()[..]
+    );
+    assert_eq!(
+        &iv[..],
+        &test::from_hex("5d313eb2671276ee13000b30").unwrap()[..]
+    );
+
+    // Invalid labels and contexts.
+    let mut out = [0u8; 32];
+    assert!(early_secret.expand_label(b"", &[], &mut out).is_err());
+    assert!(early_secret
+        .expand_label(&[b'a'; 249], &[], &mut out)
+        .is_ok());
+    assert!(early_secret
+        .expand_label(&[b'a'; 250], &[], &mut out)
+        .is_err());
+    assert!(early_secret
+        .expand_label(b"key", &[0; 255], &mut out)
+        .is_ok());
+    assert!(early_secret
+        .expand_label(b"key", &[0; 256], &mut out)
+        .is_err());
+}
+
+#[test]
+fn hkdf_okm_fill_exact_and_into_vec() {
+    let prk = hkdf::Salt::new(&digest::SHA256, b"salt").extract(b"secret");
+
+    let mut expected = [0u8; 32];
+    prk.expand(b"key").fill(&mut expected).unwrap();
+    let key: [u8; 32] = prk.expand(b"key").fill_exact().unwrap();
+    assert_eq!(key, expected);
+    let iv: [u8; 12] = prk.expand(b"key").fill_exact().unwrap();
+    assert_eq!(&iv[..], &expected[..12]);
+
+    assert_eq!(prk.expand(b"key").into_vec(32).unwrap(), &expected[..]);
+    assert!(prk.expand(b"key").into_vec(0).unwrap().is_empty());
+
+    let max_len = 255 * digest::SHA256_OUTPUT_LEN;
+    assert_eq!(
+        prk.expand(b"long").into_vec(max_len).unwrap().len(),
+        max_len
+    );
+    assert!(prk.expand(b"long").into_vec(max_len + 1).is_err());
+}
@@ -94,6 +94,43 @@ fn hmac_test_case_inner(
             let signature = ctx.sign();
             assert_eq!(is_ok, signature.as_ref() == output);
         }
+
+        // One-shot API, in parts.
+        {
+            let (a, b) = input.split_at(input.len() / 2);
+            let signature = hmac::sign_all(&key, &[a, &[], b]);
+            assert_eq!(is_ok, signature.as_ref() == output);
+        }
+
+        // Multi-part verification API, one single part.
+        {
+            let mut v_ctx = hmac::VerificationContext::with_key(&key);
+            v_ctx.update(input);
+            assert_eq!(is_ok, v_ctx.verify(output).is_ok());
+        }
+
+        // Multi-part verification API, byte by byte.
+        {
+            let mut v_ctx = hmac::VerificationContext::with_key(&key);
+            for b in input {
+                v_ctx.update(&[*b]);
+            }
+            assert_eq!(is_ok, v_ctx.verify(output).is_ok());
+        }
+
+        // Truncated to half the length.
+        {
+            let truncated = &output[..(output.len() / 2)];
+            let signature = hmac::sign(&key, input);
+            assert_eq!(
+                is_ok,
+                signature.truncated(truncated.len()).unwrap() == truncated
+            );
+            assert_eq!(
+                is_ok,
+                hmac::verify_truncated(&key, input, truncated).is_ok()
+            );
+        }
     };
 
     let key = hmac::Key::new(digest_alg, key_value);
@@ -103,6 +140,45 @@ fn hmac_test_case_inner(
     Ok(())
 }
 
+#[test]
+fn hmac_truncated() {
+    const INPUT: &[u8] = b"hello, world";
+
+    for &(digest_alg, min_len) in &[
+        (&digest::SHA1, 10),
+        (&digest::SHA256, 16),
+        (&digest::SHA512, 32),
+        (&digest::SHA512_256, 16),
+    ] {
+        let key = hmac::Key::new(digest_alg, &[0x42; 32]);
+        let tag = hmac::sign(&key, INPUT);
+        let output_len = digest_alg.output_len;
+
+        assert!(tag.truncated(min_len - 1).is_err());
+        assert!(tag.truncated(output_len + 1).is_err());
+        for len in min_len..=output_len {
+            let truncated = tag.truncated(len).unwrap();
+            assert_eq!(truncated, &tag.as_ref()[..len]);
+            assert!(hmac::verify_truncated(&key, INPUT, truncated).is_ok());
+
+            let mut v_ctx = hmac::VerificationContext::with_key(&key);
+            v_ctx.update(INPUT);
+            assert!(v_ctx.verify_truncated(truncated).is_ok());
+
+            let mut tampered = truncated.to_vec();
+            tampered[len - 1] ^= 1;
+            assert!(hmac::verify_truncated(&key, INPUT, &tampered).is_err());
+        }
+        assert!(hmac::verify_truncated(&key, INPUT, &tag.as_ref()[..(min_len - 1)]).is_err());
+        assert!(hmac::verify_truncated(&key, INPUT, &[]).is_err());
+    }
+
+    // HMAC-SHA-512 truncated to 256 bits isn't HMAC-SHA-512/256.
+    let sha512 = hmac::sign(&hmac::Key::new(&digest::SHA512, b"key"), INPUT);
+    let sha512_256 = hmac::sign(&hmac::Key::new(&digest::SHA512_256, b"key"), INPUT);
+    assert_ne!(sha512.truncated(32).unwrap(), sha512_256.as_ref());
+}
+
 #[test]
 fn hmac_debug() {
     let key = hmac::Key::new(&digest::SHA256, &[0; 32]);
@@ -110,4 +186,17 @@ fn hmac_debug() {
 
     let ctx = hmac::Context::with_key(&key);
     assert_eq!("Context { algorithm: SHA256 }", format!("{:?}", &ctx));
+
+    let v_ctx = hmac::VerificationContext::with_key(&key);
+    assert_eq!(
+        "VerificationContext { algorithm: SHA256 }",
+        format!("{:?}", &v_ctx)
+    );
+}
+
+#[test]
+#[should_panic]
+fn hmac_ascon_hash() {
+    // Ascon-Hash's blocks are shorter than its output.
+    let _ = hmac::Key::new(&digest::ASCON_HASH, &[0; 8]);
 }
@@ -0,0 +1,203 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{
+    aead::{self, Aad},
+    error, hpke, test, test_file,
+};
+
+fn suite_by_name(name: &str) -> Option<&'static hpke::Suite> {
+    match name {
+        "AES_128_GCM" => Some(&hpke::DHKEM_X25519_HKDF_SHA256_AES_128_GCM),
+        "AES_256_GCM" => Some(&hpke::DHKEM_X25519_HKDF_SHA256_AES_256_GCM),
+        #[cfg(feature = "aead-chacha")]
+        "CHACHA20_POLY1305" => Some(&hpke::DHKEM_X25519_HKDF_SHA256_CHACHA20_POLY1305),
+        #[cfg(not(feature = "aead-chacha"))]
+        "CHACHA20_POLY1305" => None,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn hpke_tests() {
+    test::run(test_file!("hpke_tests.txt"), |section, test_case| {
+        let suite = match suite_by_name(&test_case.consume_string("Suite")) {
+            Some(suite) => suite,
+            None => {
+                // Unsupported suite; skip the rest of the test case.
+                for key in &[
+                    "IKMR",
+                    "PKR",
+                    "SKE",
+                    "Enc",
+                    "Info",
+                    "Seq",
+                    "AAD",
+                    "PT",
+                    "CT",
+                    "ExporterContext",
+                    "ExportedValue",
+                ] {
+                    let _ = test_case.consume_optional_string(key);
+                }
+                return Ok(());
+            }
+        };
+        let ikm_r = test_case.consume_bytes("IKMR");
+        let pk_r = test_case.consume_bytes("PKR");
+        let sk_e = test_case.consume_bytes("SKE");
+        let expected_enc = test_case.consume_bytes("Enc");
+        let info = test_case.consume_bytes("Info");
+
+        let key_pair = hpke::KeyPair::derive(&ikm_r)?;
+        assert_eq!(key_pair.public_key(), &pk_r[..]);
+
+        let rng = test::rand::FixedSliceRandom { bytes: &sk_e };
+        let (enc, mut sender) = hpke::Sender::setup_base(suite, &pk_r, &info, &rng)?;
+        assert_eq!(enc.as_ref(), &expected_enc[..]);
+        let mut recipient = hpke::Recipient::setup_base(suite, enc.as_ref(), &key_pair, &info)?;
+
+        match section {
+            "Seal" => {
+                let seq = test_case.consume_usize("Seq");
+                let aad = test_case.consume_bytes("AAD");
+                let pt = test_case.consume_bytes("PT");
+                let ct = test_case.consume_bytes("CT");
+
+                // The nonce depends only on the sequence number, so skip to
+                // `seq` with empty messages.
+                let tag_len = suite.aead_algorithm().tag_len();
+                for _ in 0..seq {
+                    let mut in_out = vec![0; tag_len];
+                    let _ = sender.seal_in_place(Aad::empty(), &mut in_out, tag_len)?;
+                    let _ = recipient.open_in_place(Aad::empty(), &mut in_out)?;
+                }
+
+                let mut in_out = pt.clone();
+                in_out.extend_from_slice(&vec![0; tag_len]);
+                let out_len = sender.seal_in_place(Aad::from(&aad), &mut in_out, tag_len)?;
+                assert_eq!(&in_out[..out_len], &ct[..]);
+
+                let mut in_out = ct.clone();
+                let plaintext = recipient.open_in_place(Aad::from(&aad), &mut in_out)?;
+                assert_eq!(plaintext, &pt[..]);
+            }
+            "Export" => {
+                let exporter_context = test_case.consume_bytes("ExporterContext");
+                let expected = test_case.consume_bytes("ExportedValue");
+
+                let mut out = vec![0; expected.len()];
+                sender.export(&exporter_context, &mut out)?;
+                assert_eq!(out, expected);
+                recipient.export(&exporter_context, &mut out)?;
+                assert_eq!(out, expected);
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn hpke_open_failure_test() {
+    let rng = test::rand::FixedByteRandom { byte: 1 };
+    let suite = &hpke::DHKEM_X25519_HKDF_SHA256_AES_128_GCM;
+    let key_pair = hpke::KeyPair::derive(&[2; 32]).unwrap();
+
+    let (enc, mut sender) =
+        hpke::Sender::setup_base(suite, key_pair.public_key(), b"", &rng).unwrap();
+    let mut recipient = hpke::Recipient::setup_base(suite, enc.as_ref(), &key_pair, b"").unwrap();
+
+    let seal = |sender: &mut hpke::Sender, msg: &[u8]| {
+        let mut in_out = msg.to_vec();
+        in_out.extend_from_slice(&[0; aead::MAX_TAG_LEN]);
+        let out_len = sender
+            .seal_in_place(Aad::empty(), &mut in_out, aead::MAX_TAG_LEN)
+            .unwrap();
+        in_out.truncate(out_len);
+        in_out
+    };
+
+    // A forged message is rejected without advancing the sequence number, so
+    // the genuine message can still be opened.
+    let first = seal(&mut sender, b"first");
+    let mut forged = first.clone();
+    forged[0] ^= 1;
+    assert!(recipient.open_in_place(Aad::empty(), &mut forged).is_err());
+    let mut first = first;
+    assert_eq!(
+        recipient.open_in_place(Aad::empty(), &mut first).unwrap(),
+        b"first"
+    );
+
+    // Messages must be opened in the order they were sealed.
+    let mut second = seal(&mut sender, b"second");
+    let mut third = seal(&mut sender, b"third");
+    assert!(recipient.open_in_place(Aad::empty(), &mut third).is_err());
+    assert_eq!(
+        recipient.open_in_place(Aad::empty(), &mut second).unwrap(),
+        b"second"
+    );
+
+    // A recipient with a different key pair can't open anything.
+    let other_key_pair = hpke::KeyPair::derive(&[3; 32]).unwrap();
+    let mut other = hpke::Recipient::setup_base(suite, enc.as_ref(), &other_key_pair, b"").unwrap();
+    let mut fourth = seal(&mut sender, b"fourth");
+    assert!(other.open_in_place(Aad::empty(), &mut fourth).is_err());
+}
+
+#[test]
+fn hpke_key_pair_test() -> Result<(), error::Unspecified> {
+    assert!(hpke::KeyPair::derive(&[0; 31]).is_err());
+
+    let rng = test::rand::FixedByteRandom { byte: 1 };
+    let key_pair = hpke::KeyPair::generate(&rng)?;
+    assert_eq!(key_pair.public_key().len(), 32);
+
+    #[cfg(feature = "aead-chacha")]
+    {
+        let suite = &hpke::DHKEM_X25519_HKDF_SHA256_CHACHA20_POLY1305;
+        assert_eq!(suite.aead_algorithm(), &aead::CHACHA20_POLY1305);
+        assert_eq!(
+            "DHKEM_X25519_HKDF_SHA256_CHACHA20_POLY1305",
+            format!("{:?}", suite)
+        );
+    }
+
+    // The all-zero public key is a point of small order.
+    let suite = &hpke::DHKEM_X25519_HKDF_SHA256_AES_128_GCM;
+    assert!(hpke::Sender::setup_base(suite, &[0; 32], b"", &rng).is_err());
+    assert!(hpke::Recipient::setup_base(suite, &[0; 32], &key_pair, b"").is_err());
+    Ok(())
+}
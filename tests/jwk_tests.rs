@@ -0,0 +1,224 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "jwk")]
+
+use ring::{aead, digest, hmac, jwk::Jwk, rand, signature, signature::KeyPair, test};
+
+// RFC 7638 Section 3.1.
+const RSA_PUBLIC_JWK: &str = r#"{
+  "kty": "RSA",
+  "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+  "e": "AQAB",
+  "alg": "RS256",
+  "kid": "2011-04-29"
+}"#;
+const RSA_THUMBPRINT: &str = "3736cbb1787cb8309c77ee8c3705c5e16ffb9e859715901f1e4c59b11182f57b";
+
+// RFC 8037 Appendix A.
+const ED25519_JWK: &str = r#"{"kty":"OKP","crv":"Ed25519",
+   "d":"nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A",
+   "x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#;
+const ED25519_THUMBPRINT: &str = "90facafea9b1556698540f70c0117a22ea37bd5cf3ed3c47093c1707282b4b89";
+
+#[test]
+fn jwk_thumbprint_test() {
+    let rsa = Jwk::parse(RSA_PUBLIC_JWK).unwrap();
+    assert_eq!(rsa.key_type(), "RSA");
+    assert!(!rsa.is_private());
+    assert!(rsa.rsa_key_pair().is_err());
+    assert_eq!(
+        rsa.thumbprint(&digest::SHA256).as_ref(),
+        &test::from_hex(RSA_THUMBPRINT).unwrap()[..]
+    );
+
+    let ed25519 = Jwk::parse(ED25519_JWK).unwrap();
+    assert_eq!(
+        ed25519.thumbprint(&digest::SHA256).as_ref(),
+        &test::from_hex(ED25519_THUMBPRINT).unwrap()[..]
+    );
+    // The public key has the same thumbprint.
+    assert_eq!(
+        ed25519
+            .to_public()
+            .unwrap()
+            .thumbprint(&digest::SHA256)
+            .as_ref(),
+        &test::from_hex(ED25519_THUMBPRINT).unwrap()[..]
+    );
+}
+
+#[test]
+fn jwk_ed25519_test() {
+    let jwk = Jwk::parse(ED25519_JWK).unwrap();
+    let key_pair = jwk.ed25519_key_pair().unwrap();
+    assert_eq!(jwk.public_key().unwrap(), key_pair.public_key().as_ref());
+    assert!(jwk
+        .ecdsa_key_pair(&signature::ECDSA_P256_SHA256_FIXED_SIGNING)
+        .is_err());
+
+    let exported = Jwk::from_ed25519_key_pair(&key_pair);
+    assert_eq!(
+        exported.to_json(),
+        r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo","d":"nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A"}"#
+    );
+    assert_eq!(
+        exported.to_public().unwrap().to_json(),
+        r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#
+    );
+    assert!(exported.to_public().unwrap().ed25519_key_pair().is_err());
+
+    // Mismatched public key.
+    let mismatched = r#"{"kty":"OKP","crv":"Ed25519",
+       "d":"nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A",
+       "x":"21qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#;
+    assert!(Jwk::parse(mismatched).unwrap().ed25519_key_pair().is_err());
+}
+
+#[test]
+fn jwk_ecdsa_test() {
+    let rng = rand::SystemRandom::new();
+
+    for &(alg, other_alg) in &[
+        (
+            &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+            &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+        ),
+        (
+            &signature::ECDSA_P384_SHA384_ASN1_SIGNING,
+            &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+        ),
+        (
+            &signature::ECDSA_P521_SHA512_FIXED_SIGNING,
+            &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+        ),
+    ] {
+        let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+        let key_pair = signature::EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref()).unwrap();
+
+        let jwk = Jwk::parse(&Jwk::from_ecdsa_key_pair(&key_pair).to_json()).unwrap();
+        assert_eq!(jwk.key_type(), "EC");
+        assert_eq!(jwk.public_key().unwrap(), key_pair.public_key().as_ref());
+        let imported = jwk.ecdsa_key_pair(alg).unwrap();
+        assert_eq!(imported.to_pkcs8().as_ref(), pkcs8.as_ref());
+        assert!(jwk.ecdsa_key_pair(other_alg).is_err());
+        assert!(jwk.to_public().unwrap().ecdsa_key_pair(alg).is_err());
+    }
+}
+
+#[test]
+fn jwk_rsa_test() {
+    let key_pair =
+        signature::RsaKeyPair::from_pkcs8(include_bytes!("rsa_test_private_key_2048.p8")).unwrap();
+
+    let jwk = Jwk::parse(&Jwk::from_rsa_key_pair(&key_pair).to_json()).unwrap();
+    assert!(jwk.is_private());
+    assert_eq!(jwk.public_key().unwrap(), key_pair.public_key().as_ref());
+    let imported = jwk.rsa_key_pair().unwrap();
+    assert_eq!(
+        imported.public_key().as_ref(),
+        key_pair.public_key().as_ref()
+    );
+
+    // The imported key pair signs like the original.
+    const MESSAGE: &[u8] = b"hello, world";
+    let rng = rand::SystemRandom::new();
+    let mut signature = vec![0; imported.public_modulus_len()];
+    imported
+        .sign(&signature::RSA_PKCS1_SHA256, &rng, MESSAGE, &mut signature)
+        .unwrap();
+    signature::UnparsedPublicKey::new(
+        &signature::RSA_PKCS1_2048_8192_SHA256,
+        jwk.to_public().unwrap().public_key().unwrap(),
+    )
+    .verify(MESSAGE, &signature)
+    .unwrap();
+}
+
+#[test]
+fn jwk_oct_test() {
+    let key = [0x42u8; 16];
+    let jwk = Jwk::from_symmetric_key(&key).unwrap();
+    assert_eq!(
+        jwk.to_json(),
+        r#"{"kty":"oct","k":"QkJCQkJCQkJCQkJCQkJCQg"}"#
+    );
+    assert!(jwk.to_public().is_err());
+    assert!(jwk.public_key().is_err());
+
+    let jwk = Jwk::parse(&jwk.to_json()).unwrap();
+    assert!(jwk.aead_sealing_key(&aead::AES_128_GCM).is_ok());
+    assert!(jwk.aead_opening_key(&aead::AES_128_GCM).is_ok());
+    assert!(jwk.aead_sealing_key(&aead::AES_256_GCM).is_err());
+    let tag = hmac::sign(&jwk.hmac_key(&digest::SHA256).unwrap(), b"message");
+    assert!(hmac::verify(
+        &hmac::Key::new(&digest::SHA256, &key),
+        b"message",
+        tag.as_ref()
+    )
+    .is_ok());
+    assert!(jwk.ed25519_key_pair().is_err());
+
+    assert!(Jwk::from_symmetric_key(&[]).is_err());
+}
+
+#[test]
+fn jwk_parse_errors_test() {
+    const BAD: &[&str] = &[
+        r#"{}"#,
+        r#"{"crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#,
+        r#"{"kty":"OKP","crv":"X25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#,
+        r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHUR"}"#,
+        r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo="}"#,
+        r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo","kty":"OKP"}"#,
+        r#"{"kty":"OKP","crv":"Ed25519","x":12}"#,
+        r#"{"kty":"EC","crv":"P-256","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#,
+        r#"{"kty":"EC","crv":"P-192","x":"AA","y":"AA"}"#,
+        r#"{"kty":"RSA","n":"AAEB","e":"AQAB"}"#,
+        r#"{"kty":"RSA","n":"AQEB","e":""}"#,
+        r#"{"kty":"RSA","n":"AQEB","e":"AQAB","d":"AQ"}"#,
+        r#"{"kty":"RSA","n":"AQEB","e":"AQAB","oth":[]}"#,
+        r#"{"kty":"oct","k":""}"#,
+        r#"{"kty":"oct"}"#,
+        r#"{"kty":"XYZ","k":"AQ"}"#,
+        r#"{"kty":"oct","k":"AQ"} x"#,
+    ];
+    for &json in BAD {
+        assert!(Jwk::parse(json).is_err(), "{}", json);
+    }
+
+    // Unknown and unused members are ignored.
+    assert!(
+        Jwk::parse(r#"{"kty":"oct","k":"AQ","use":"enc","key_ops":["encrypt"],"ext":true}"#)
+            .is_ok()
+    );
+}
@@ -0,0 +1,123 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{aead, kdf::ratchet, test, test_file};
+
+fn aead_alg_by_name(name: &str) -> Option<&'static aead::Algorithm> {
+    match name {
+        "AES_128_GCM" => Some(&aead::AES_128_GCM),
+        "AES_256_GCM" => Some(&aead::AES_256_GCM),
+        #[cfg(feature = "aead-chacha")]
+        "CHACHA20_POLY1305" => Some(&aead::CHACHA20_POLY1305),
+        #[cfg(not(feature = "aead-chacha"))]
+        "CHACHA20_POLY1305" => None,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn kdf_ratchet_tests() {
+    test::run(test_file!("kdf_ratchet_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let alg = aead_alg_by_name(&test_case.consume_string("Alg"));
+        let rk = test_case.consume_bytes("RK");
+        let dh = test_case.consume_bytes("DH");
+        let info = test_case.consume_bytes("Info");
+        let expected_new_rk = test_case.consume_bytes("NewRK");
+        let steps = test_case.consume_usize("Steps");
+        let expected_next_ck = test_case.consume_bytes("NextCK");
+        let expected_mk = test_case.consume_bytes("MK");
+        let message_info = test_case.consume_bytes("MessageInfo");
+        let ad = test_case.consume_bytes("AD");
+        let pt = test_case.consume_bytes("PT");
+        let ct = test_case.consume_bytes("CT");
+
+        let alg = match alg {
+            Some(alg) => alg,
+            None => {
+                return Ok(());
+            } // Unsupported AEAD algorithm
+        };
+
+        let rk = ratchet::RootKey::new(&rk).unwrap();
+        let (new_rk, mut ck) = rk.ratchet(&dh, &info);
+        assert_eq!(new_rk.export_unchecked(), &expected_new_rk[..]);
+
+        let mut mk = None;
+        for _ in 0..steps {
+            let (next_ck, next_mk) = ck.advance();
+            ck = next_ck;
+            mk = Some(next_mk);
+        }
+        let mk = mk.unwrap();
+        assert_eq!(ck.export_unchecked(), &expected_next_ck[..]);
+        assert_eq!(mk.export_unchecked(), &expected_mk[..]);
+
+        // The receiver derives the same message key from the exported value.
+        let receiver_mk = ratchet::MessageKey::new(mk.export_unchecked()).unwrap();
+
+        let (sealing_key, nonce) = mk.into_sealing_key(alg, &message_info).unwrap();
+        let mut in_out = pt.clone();
+        in_out.extend_from_slice(&vec![0; alg.tag_len()]);
+        let out_len = aead::seal_in_place(
+            &sealing_key,
+            nonce,
+            aead::Aad::from(&ad),
+            &mut in_out,
+            alg.tag_len(),
+        )
+        .unwrap();
+        assert_eq!(&in_out[..out_len], &ct[..]);
+
+        let (opening_key, nonce) = receiver_mk.into_opening_key(alg, &message_info).unwrap();
+        let plaintext =
+            aead::open_in_place(&opening_key, nonce, aead::Aad::from(&ad), 0, &mut in_out).unwrap();
+        assert_eq!(plaintext, &pt[..]);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn kdf_ratchet_key_length_test() {
+    assert!(ratchet::RootKey::new(&[0; ratchet::KEY_LEN]).is_ok());
+    assert!(ratchet::RootKey::new(&[0; ratchet::KEY_LEN - 1]).is_err());
+    assert!(ratchet::ChainKey::new(&[0; ratchet::KEY_LEN + 1]).is_err());
+    assert!(ratchet::MessageKey::new(&[]).is_err());
+}
+
+#[test]
+fn kdf_ratchet_debug_test() {
+    let key = ratchet::ChainKey::new(&[0x42; ratchet::KEY_LEN]).unwrap();
+    assert_eq!("ChainKey", format!("{:?}", key));
+}
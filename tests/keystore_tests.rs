@@ -0,0 +1,159 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "use_heap")]
+
+use core::num::NonZeroU32;
+#[cfg(feature = "sig-ed25519")]
+use ring::signature;
+use ring::{aead, keystore, rand, test};
+
+const PASSWORD: &[u8] = b"correct horse battery staple";
+const SALT_AND_NONCE: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b";
+
+// Generated with an independent implementation of the format.
+const PBKDF2_PKCS8_BLOB: &str = "524b45590101000003e80100000102030405060708090a0b0c0d0e0f101112131415161718191a1b7288c50315a39cc80610101cfacd78b0e609a5334498b182e74cb5ab60e0571cbb90f3981b1de89cfc3d6190";
+const HKDF_AEAD_KEY_BLOB: &str = "524b45590102000000000202000102030405060708090a0b0c0d0e0f101112131415161718191a1bc2748f53cbb661534111f72d6e44c20c17f141c74e310634b1ea79f3977bedf1b0f295b767dc33626d1a1d4fb1116610";
+
+fn password(iterations: u32) -> keystore::WrappingKey<'static> {
+    keystore::WrappingKey::Password {
+        password: PASSWORD,
+        iterations: NonZeroU32::new(iterations).unwrap(),
+    }
+}
+
+#[test]
+fn keystore_known_answer_test() {
+    let salt_and_nonce = test::from_hex(SALT_AND_NONCE).unwrap();
+    let rng = test::rand::FixedSliceRandom {
+        bytes: &salt_and_nonce,
+    };
+
+    let pkcs8 = b"not really a PKCS#8 document";
+    let blob = keystore::seal_pkcs8(&password(1000), pkcs8, &rng).unwrap();
+    assert_eq!(blob, test::from_hex(PBKDF2_PKCS8_BLOB).unwrap());
+    match keystore::open(&password(1000), &blob).unwrap() {
+        keystore::Contents::Pkcs8(opened) => assert_eq!(&opened[..], &pkcs8[..]),
+        _ => unreachable!(),
+    }
+
+    let secret = keystore::WrappingKey::Secret(&[0x42; 32]);
+    let key_bytes = (0..32).collect::<Vec<u8>>();
+    let blob = keystore::seal_aead_key(&secret, &aead::AES_256_GCM, &key_bytes, &rng).unwrap();
+    assert_eq!(blob, test::from_hex(HKDF_AEAD_KEY_BLOB).unwrap());
+    match keystore::open(&secret, &blob).unwrap() {
+        keystore::Contents::AeadKey {
+            algorithm,
+            key_bytes: opened,
+        } => {
+            assert_eq!(algorithm, &aead::AES_256_GCM);
+            assert_eq!(opened, key_bytes);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn keystore_round_trip_test() {
+    let rng = rand::SystemRandom::new();
+    #[cfg(feature = "sig-ed25519")]
+    {
+        let pkcs8 = signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let blob = keystore::seal_pkcs8(&password(10), pkcs8.as_ref(), &rng).unwrap();
+        match keystore::open(&password(10), &blob).unwrap() {
+            keystore::Contents::Pkcs8(opened) => {
+                let _ = signature::Ed25519KeyPair::from_pkcs8(&opened).unwrap();
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    let algorithms: &[&aead::Algorithm] = &[
+        &aead::AES_128_GCM,
+        &aead::AES_256_GCM,
+        #[cfg(feature = "aead-chacha")]
+        &aead::CHACHA20_POLY1305,
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        &aead::AES_128_GCM_SIV,
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        &aead::AES_256_GCM_SIV,
+    ];
+    for algorithm in algorithms {
+        let key_bytes = vec![7u8; algorithm.key_len()];
+        let blob = keystore::seal_aead_key(&password(10), algorithm, &key_bytes, &rng).unwrap();
+        match keystore::open(&password(10), &blob).unwrap() {
+            keystore::Contents::AeadKey {
+                algorithm: opened_algorithm,
+                key_bytes: opened,
+            } => {
+                assert_eq!(opened_algorithm, *algorithm);
+                assert_eq!(opened, key_bytes);
+            }
+            _ => unreachable!(),
+        }
+
+        // The key must be the right length for the algorithm.
+        assert!(
+            keystore::seal_aead_key(&password(10), algorithm, &key_bytes[1..], &rng).is_err()
+        );
+    }
+}
+
+#[test]
+fn keystore_rejection_test() {
+    let blob = test::from_hex(PBKDF2_PKCS8_BLOB).unwrap();
+
+    // Wrong password.
+    let wrong = keystore::WrappingKey::Password {
+        password: b"Tr0ub4dor&3",
+        iterations: NonZeroU32::new(1000).unwrap(),
+    };
+    assert!(keystore::open(&wrong, &blob).is_err());
+
+    // The iteration count in the blob is limited by the caller.
+    assert!(keystore::open(&password(999), &blob).is_err());
+
+    // The KDF must match the kind of wrapping key.
+    assert!(keystore::open(&keystore::WrappingKey::Secret(PASSWORD), &blob).is_err());
+
+    // Every byte, including every header byte, is authenticated.
+    for i in 0..blob.len() {
+        let mut tampered = blob.clone();
+        tampered[i] ^= 1;
+        assert!(keystore::open(&password(1000), &tampered).is_err());
+    }
+
+    // Truncated blobs.
+    for len in 0..blob.len() {
+        assert!(keystore::open(&password(1000), &blob[..len]).is_err());
+    }
+}
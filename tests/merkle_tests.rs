@@ -0,0 +1,202 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "use_heap")]
+
+use ring::{digest, merkle, test};
+
+// The test vectors used by the Certificate Transparency reference
+// implementation.
+const LEAVES: [&[u8]; 8] = [
+    b"",
+    b"\x00",
+    b"\x10",
+    b"\x20\x21",
+    b"\x30\x31",
+    b"\x40\x41\x42\x43",
+    b"\x50\x51\x52\x53\x54\x55\x56\x57",
+    b"\x60\x61\x62\x63\x64\x65\x66\x67\x68\x69\x6a\x6b\x6c\x6d\x6e\x6f",
+];
+
+const ROOTS: [&str; 9] = [
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+    "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+    "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+    "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+    "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+    "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+    "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+    "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+];
+
+#[test]
+fn merkle_root_test() {
+    for (size, &expected) in ROOTS.iter().enumerate() {
+        let root = merkle::root(&digest::SHA256, &LEAVES[..size]);
+        assert_eq!(root.as_ref(), &test::from_hex(expected).unwrap()[..]);
+    }
+
+    assert_eq!(
+        merkle::leaf_hash(&digest::SHA256, LEAVES[0]).as_ref(),
+        &test::from_hex(ROOTS[1]).unwrap()[..]
+    );
+    let left = merkle::leaf_hash(&digest::SHA256, LEAVES[0]);
+    let right = merkle::leaf_hash(&digest::SHA256, LEAVES[1]);
+    assert_eq!(
+        merkle::node_hash(&digest::SHA256, left.as_ref(), right.as_ref()).as_ref(),
+        &test::from_hex(ROOTS[2]).unwrap()[..]
+    );
+}
+
+#[test]
+fn merkle_inclusion_proof_test() {
+    struct Case {
+        index: usize,
+        tree_size: usize,
+        proof: &'static [&'static str],
+    }
+    const CASES: &[Case] = &[
+        Case {
+            index: 0,
+            tree_size: 1,
+            proof: &[],
+        },
+        Case {
+            index: 0,
+            tree_size: 8,
+            proof: &[
+                "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+                "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+                "6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4",
+            ],
+        },
+        Case {
+            index: 5,
+            tree_size: 8,
+            proof: &[
+                "bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+                "ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0",
+                "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+            ],
+        },
+        Case {
+            index: 2,
+            tree_size: 3,
+            proof: &["fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125"],
+        },
+    ];
+
+    for case in CASES {
+        let leaves = &LEAVES[..case.tree_size];
+        let proof = merkle::inclusion_proof(&digest::SHA256, leaves, case.index).unwrap();
+        let expected: Vec<Vec<u8>> = case
+            .proof
+            .iter()
+            .map(|hash| test::from_hex(hash).unwrap())
+            .collect();
+        assert_eq!(proof.len(), expected.len());
+        for (actual, expected) in proof.iter().zip(expected.iter()) {
+            assert_eq!(actual.as_ref(), &expected[..]);
+        }
+
+        let root = test::from_hex(ROOTS[case.tree_size]).unwrap();
+        assert!(merkle::verify_inclusion(
+            &digest::SHA256,
+            LEAVES[case.index],
+            case.index,
+            case.tree_size,
+            &expected,
+            &root
+        )
+        .is_ok());
+    }
+}
+
+#[test]
+fn merkle_verify_inclusion_test() {
+    for tree_size in 1..=LEAVES.len() {
+        let leaves = &LEAVES[..tree_size];
+        let root = merkle::root(&digest::SHA256, leaves);
+        let root = root.as_ref();
+        for (index, &leaf) in leaves.iter().enumerate() {
+            let proof = merkle::inclusion_proof(&digest::SHA256, leaves, index).unwrap();
+            let verify = |leaf: &[u8], index, tree_size, proof: &[digest::Digest], root| {
+                merkle::verify_inclusion(&digest::SHA256, leaf, index, tree_size, proof, root)
+            };
+            assert!(verify(leaf, index, tree_size, &proof, root).is_ok());
+
+            // Wrong leaf, index, or root, or an index beyond the tree size.
+            assert!(verify(b"wrong", index, tree_size, &proof, root).is_err());
+            assert!(verify(leaf, index + 1, tree_size, &proof, root).is_err());
+            assert!(verify(leaf, index, index, &proof, root).is_err());
+            assert!(verify(leaf, index, tree_size, &proof, &root[1..]).is_err());
+
+            // Truncated and extended proofs.
+            if !proof.is_empty() {
+                assert!(verify(leaf, index, tree_size, &proof[1..], root).is_err());
+                assert!(verify(leaf, index, tree_size, &proof[..proof.len() - 1], root).is_err());
+            }
+            let mut extended = proof.clone();
+            extended.push(merkle::leaf_hash(&digest::SHA256, b""));
+            assert!(verify(leaf, index, tree_size, &extended, root).is_err());
+
+            // A leaf hash isn't accepted as a leaf.
+            let leaf_hash = merkle::leaf_hash(&digest::SHA256, leaf);
+            assert!(verify(leaf_hash.as_ref(), index, tree_size, &proof, root).is_err());
+        }
+
+        assert!(merkle::inclusion_proof(&digest::SHA256, leaves, tree_size).is_err());
+    }
+
+    // Proof hashes of the wrong length.
+    let root = merkle::root(&digest::SHA256, &LEAVES[..2]);
+    let proof = merkle::leaf_hash(&digest::SHA256, LEAVES[1]);
+    assert!(merkle::verify_inclusion(
+        &digest::SHA256,
+        LEAVES[0],
+        0,
+        2,
+        &[proof.as_ref()],
+        root.as_ref()
+    )
+    .is_ok());
+    assert!(merkle::verify_inclusion(
+        &digest::SHA256,
+        LEAVES[0],
+        0,
+        2,
+        &[&proof.as_ref()[1..]],
+        root.as_ref()
+    )
+    .is_err());
+}
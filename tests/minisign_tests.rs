@@ -0,0 +1,171 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(all(feature = "use_heap", feature = "sig-ed25519"))]
+
+use ring::{minisign, signature, test};
+
+// The key pair from RFC 8032 Section 7.1, Test 1.
+const SEED: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+const PUBLIC_KEY: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+const KEY_ID: [u8; minisign::KEY_ID_LEN] = [0xe3, 0xb4, 0xc1, 0xd2, 0xa5, 0xf6, 0x07, 0x18];
+
+const PUBLIC_KEY_FILE: &str = "untrusted comment: minisign public key\n\
+                               RWTjtMHSpfYHGNdamAGCsQq31Uv+08lkBzoO4XLz2qYjJa8CGmj3B1Ea\n";
+
+const MESSAGE: &[u8] = b"release-1.0.tar.gz contents\n";
+const TRUSTED_COMMENT: &str = "timestamp:1561000000\tfile:release-1.0.tar.gz";
+
+const MINISIGN_SIGNATURE_FILE: &str = "\
+untrusted comment: signature from minisign secret key
+RUTjtMHSpfYHGK7AQI/aTf5tpnIwR0hfXfck5TjbaxTMbWso80H4cp8wI/w5NfHZO+Q0kxxsMyXBGMqW1J/tGhywJ1B2eYN3oQM=
+trusted comment: timestamp:1561000000\tfile:release-1.0.tar.gz
+ELrBffE2ApG9sTvSGexLs5Zw/x4Z4NB+lfEJfM+piGGvfxbMQBwQOwCcM3LAdkN/o90pHDuTPcsgNX8kI/uzDQ==
+";
+
+// A signature over the message itself rather than its BLAKE2b-512 digest.
+const MINISIGN_LEGACY_SIGNATURE_FILE: &str = "\
+untrusted comment: signature from minisign secret key
+RWTjtMHSpfYHGLr6fDoda7X+d/h+Y9nlL+D9QlyxGBrEJXXjLllo3FPv+4zbwBr327kwQPm3EfIOCVJW0jTC/JjSSuvwfp8BSgk=
+trusted comment: timestamp:1561000000\tfile:release-1.0.tar.gz
+etqI03R3gM99Dm2GYksVm7qxBlr4uAWYJhsFXdjUddDzYpWzcYbISbt9KAcA7EkJfku+Lsb/CyJPGHLZ1Fi2Cw==
+";
+
+const SIGNIFY_SIGNATURE_FILE: &str = "\
+untrusted comment: verify with release.pub
+RWTjtMHSpfYHGLr6fDoda7X+d/h+Y9nlL+D9QlyxGBrEJXXjLllo3FPv+4zbwBr327kwQPm3EfIOCVJW0jTC/JjSSuvwfp8BSgk=
+";
+
+fn key_pair() -> signature::Ed25519KeyPair {
+    signature::Ed25519KeyPair::from_seed_and_public_key(
+        &test::from_hex(SEED).unwrap(),
+        &test::from_hex(PUBLIC_KEY).unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn minisign_public_key_test() {
+    let public_key = minisign::PublicKey::from_file_contents(PUBLIC_KEY_FILE).unwrap();
+    assert_eq!(public_key.key_id(), &KEY_ID);
+    assert_eq!(
+        public_key.public_key(),
+        &test::from_hex(PUBLIC_KEY).unwrap()[..]
+    );
+    assert_eq!(public_key, minisign::PublicKey::new(&key_pair(), &KEY_ID));
+    assert_eq!(
+        public_key.to_file_contents("minisign public key").unwrap(),
+        PUBLIC_KEY_FILE
+    );
+
+    for bad in &[
+        // Missing final newline.
+        &PUBLIC_KEY_FILE[..(PUBLIC_KEY_FILE.len() - 1)],
+        // Missing comment.
+        "RWTjtMHSpfYHGNdamAGCsQq31Uv+08lkBzoO4XLz2qYjJa8CGmj3B1Ea\n",
+        // Wrong algorithm.
+        "untrusted comment: x\nRUTjtMHSpfYHGNdamAGCsQq31Uv+08lkBzoO4XLz2qYjJa8CGmj3B1Ea\n",
+        // Truncated key.
+        "untrusted comment: x\nRWTjtMHSpfYHGNdamAGCsQq31Uv+08lkBzoO4XLz2qYjJa8CGmj3Bw==\n",
+        // Trailing garbage.
+        "untrusted comment: x\nRWTjtMHSpfYHGNdamAGCsQq31Uv+08lkBzoO4XLz2qYjJa8CGmj3B1Ea\nx\n",
+    ] {
+        assert!(
+            minisign::PublicKey::from_file_contents(bad).is_err(),
+            "{}",
+            bad
+        );
+    }
+
+    assert!(public_key.to_file_contents("two\nlines").is_err());
+}
+
+#[test]
+fn minisign_verify_test() {
+    let public_key = minisign::PublicKey::from_file_contents(PUBLIC_KEY_FILE).unwrap();
+
+    for signature_file in &[MINISIGN_SIGNATURE_FILE, MINISIGN_LEGACY_SIGNATURE_FILE] {
+        assert_eq!(
+            minisign::verify(&public_key, MESSAGE, signature_file).unwrap(),
+            TRUSTED_COMMENT
+        );
+        assert!(minisign::verify(&public_key, b"tampered", signature_file).is_err());
+
+        // The trusted comment is covered by the global signature.
+        let tampered = signature_file.replace("1561000000", "1561000001");
+        assert!(minisign::verify(&public_key, MESSAGE, &tampered).is_err());
+
+        // CRLF line endings are accepted.
+        let crlf = signature_file.replace('\n', "\r\n");
+        assert!(minisign::verify(&public_key, MESSAGE, &crlf).is_ok());
+    }
+
+    // The key ID must match.
+    let other_key = minisign::PublicKey::new(&key_pair(), &[0; minisign::KEY_ID_LEN]);
+    assert!(minisign::verify(&other_key, MESSAGE, MINISIGN_SIGNATURE_FILE).is_err());
+
+    // A signify signature has no trusted comment.
+    assert!(minisign::verify(&public_key, MESSAGE, SIGNIFY_SIGNATURE_FILE).is_err());
+}
+
+#[test]
+fn minisign_sign_test() {
+    let key_pair = key_pair();
+    let signature_file = minisign::sign(
+        &key_pair,
+        &KEY_ID,
+        MESSAGE,
+        "signature from minisign secret key",
+        TRUSTED_COMMENT,
+    )
+    .unwrap();
+    assert_eq!(signature_file, MINISIGN_SIGNATURE_FILE);
+
+    assert!(minisign::sign(&key_pair, &KEY_ID, MESSAGE, "", "two\nlines").is_err());
+    assert!(minisign::sign(&key_pair, &KEY_ID, MESSAGE, "two\rlines", "").is_err());
+}
+
+#[test]
+fn minisign_signify_test() {
+    let key_pair = key_pair();
+    let public_key = minisign::PublicKey::from_file_contents(PUBLIC_KEY_FILE).unwrap();
+
+    let signature_file =
+        minisign::sign_signify(&key_pair, &KEY_ID, MESSAGE, "verify with release.pub").unwrap();
+    assert_eq!(signature_file, SIGNIFY_SIGNATURE_FILE);
+
+    assert!(minisign::verify_signify(&public_key, MESSAGE, SIGNIFY_SIGNATURE_FILE).is_ok());
+    assert!(minisign::verify_signify(&public_key, b"tampered", SIGNIFY_SIGNATURE_FILE).is_err());
+
+    // Signify signatures are always over the message itself.
+    assert!(minisign::verify_signify(&public_key, MESSAGE, MINISIGN_SIGNATURE_FILE).is_err());
+}
@@ -0,0 +1,109 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "modular_arithmetic")]
+
+use ring::{modular::Modulus, test, test_file};
+
+#[test]
+fn modular_test() {
+    test::run(test_file!("modular_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let m = Modulus::from_be_bytes(&test_case.consume_bytes("M")).unwrap();
+        let a = m.elem_from_be_bytes(&test_case.consume_bytes("A")).unwrap();
+        let b = m.elem_from_be_bytes(&test_case.consume_bytes("B")).unwrap();
+        let e = test_case.consume_bytes("E");
+        let sum = test_case.consume_bytes("Sum");
+        let difference = test_case.consume_bytes("Difference");
+        let product = test_case.consume_bytes("Product");
+        let negation = test_case.consume_bytes("Negation");
+        let power = test_case.consume_bytes("Power");
+        let inverse = test_case.consume_bytes("Inverse");
+
+        assert_eq!(a.add(&b).to_be_bytes(), sum);
+        assert_eq!(a.sub(&b).to_be_bytes(), difference);
+        assert_eq!(a.mul(&b).to_be_bytes(), product);
+        assert_eq!(b.mul(&a).to_be_bytes(), product);
+        assert_eq!(a.neg().to_be_bytes(), negation);
+        assert_eq!(a.pow(&e).unwrap().to_be_bytes(), power);
+        match a.invert() {
+            Ok(inv) => {
+                assert_eq!(inv.to_be_bytes(), inverse);
+                assert_eq!(inv.mul(&a).to_be_bytes(), m.one().to_be_bytes());
+            }
+            Err(_) => assert!(inverse.is_empty()),
+        }
+        Ok(())
+    });
+}
+
+#[test]
+fn modular_modulus_decode_test() {
+    // Too short, even, and not a multiple of 512 bits.
+    assert!(Modulus::from_be_bytes(&[0xff; 32]).is_err());
+    let mut even = [0xff; 64];
+    even[63] = 0xfe;
+    assert!(Modulus::from_be_bytes(&even).is_err());
+    assert!(Modulus::from_be_bytes(&[0xff; 65]).is_err());
+
+    // Leading zeros.
+    let mut leading_zero = [0xff; 65];
+    leading_zero[0] = 0;
+    assert!(Modulus::from_be_bytes(&leading_zero).is_err());
+
+    let m = Modulus::from_be_bytes(&[0xff; 64]).unwrap();
+    assert_eq!(m.byte_len(), 64);
+    assert!(m.zero().is_zero());
+    assert!(!m.one().is_zero());
+
+    // Elements must be less than the modulus and no longer than it.
+    assert!(m.elem_from_be_bytes(&[0xff; 64]).is_err());
+    assert!(m.elem_from_be_bytes(&[0; 65]).is_err());
+    assert!(m.elem_from_be_bytes(&[]).is_err());
+    let mut m_minus_1 = [0xff; 64];
+    m_minus_1[63] = 0xfe;
+    let m_minus_1 = m.elem_from_be_bytes(&m_minus_1).unwrap();
+    assert!(m_minus_1.add(&m.one()).is_zero());
+
+    // Exponents may not be longer than the modulus.
+    assert!(m.one().pow(&[0; 65]).is_err());
+    assert!(m.one().pow(&[]).is_err());
+    assert!(m.zero().invert().is_err());
+}
+
+#[test]
+#[should_panic]
+fn modular_different_moduli_test() {
+    let m1 = Modulus::from_be_bytes(&[0xff; 64]).unwrap();
+    let m2 = Modulus::from_be_bytes(&[0xff; 64]).unwrap();
+    let _ = m1.one().add(&m2.one());
+}
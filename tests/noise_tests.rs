@@ -0,0 +1,236 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{noise, test, test_file};
+
+fn suite_by_name(name: &str) -> Option<&'static noise::Suite> {
+    match name {
+        "25519_AESGCM_SHA256" => Some(&noise::X25519_AESGCM_SHA256),
+        #[cfg(feature = "aead-chacha")]
+        "25519_ChaChaPoly_SHA256" => Some(&noise::X25519_CHACHAPOLY_SHA256),
+        #[cfg(feature = "aead-chacha")]
+        "25519_ChaChaPoly_BLAKE2s" => Some(&noise::X25519_CHACHAPOLY_BLAKE2S),
+        #[cfg(not(feature = "aead-chacha"))]
+        "25519_ChaChaPoly_SHA256" | "25519_ChaChaPoly_BLAKE2s" => None,
+        _ => unreachable!(),
+    }
+}
+
+fn encrypt(
+    cipher_state: &mut noise::CipherState,
+    ad: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, ring::error::Unspecified> {
+    let mut in_out = plaintext.to_vec();
+    in_out.extend_from_slice(&[0; noise::TAG_LEN]);
+    let out_len = cipher_state.encrypt_with_ad(ad, &mut in_out, noise::TAG_LEN)?;
+    in_out.truncate(out_len);
+    Ok(in_out)
+}
+
+#[test]
+fn noise_tests() {
+    test::run(test_file!("noise_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let suite_name = test_case.consume_string("Suite");
+        let suite = suite_by_name(&suite_name);
+        let ski = test_case.consume_bytes("SKI");
+        let skr = test_case.consume_bytes("SKR");
+        let pki = test_case.consume_bytes("PKI");
+        let pkr = test_case.consume_bytes("PKR");
+        let prologue = test_case.consume_bytes("Prologue");
+        let payload = test_case.consume_bytes("Payload");
+        let psk = test_case.consume_bytes("PSK");
+        let ciphertext1 = test_case.consume_bytes("Ciphertext1");
+        let ciphertext2 = test_case.consume_bytes("Ciphertext2");
+        let handshake_hash = test_case.consume_bytes("HandshakeHash");
+        let ad = test_case.consume_bytes("AD");
+        let pt = test_case.consume_bytes("PT");
+        let nonce = test_case.consume_usize("Nonce") as u64;
+        let transport1 = test_case.consume_bytes("Transport1");
+        let transport2 = test_case.consume_bytes("Transport2");
+        let transport3 = test_case.consume_bytes("Transport3");
+
+        let suite = match suite {
+            Some(suite) => suite,
+            None => {
+                return Ok(());
+            } // Unsupported suite
+        };
+        assert_eq!(suite.name(), suite_name);
+
+        let initiator_e = noise::KeyPair::from_private_key(&ski)?;
+        assert_eq!(initiator_e.public_key(), &pki[..]);
+        assert_eq!(initiator_e.export_private_key_unchecked(), &ski[..]);
+        let responder_e = noise::KeyPair::from_private_key(&skr)?;
+        assert_eq!(responder_e.public_key(), &pkr[..]);
+
+        let protocol_name = format!("Noise_NN_{}", suite_name);
+        let mut initiator = noise::SymmetricState::initialize(suite, protocol_name.as_bytes());
+        let mut responder = noise::SymmetricState::initialize(suite, protocol_name.as_bytes());
+        for state in [&mut initiator, &mut responder].iter_mut() {
+            state.mix_hash(&prologue);
+            state.mix_hash(&pki);
+            state.mix_hash(&pkr);
+            assert!(!state.has_key());
+        }
+        initiator.mix_key(initiator_e.dh(&pkr)?.as_ref())?;
+        responder.mix_key(responder_e.dh(&pki)?.as_ref())?;
+        assert!(initiator.has_key());
+
+        let mut in_out = payload.clone();
+        in_out.extend_from_slice(&[0; noise::TAG_LEN]);
+        let out_len = initiator.encrypt_and_hash(&mut in_out, noise::TAG_LEN)?;
+        assert_eq!(&in_out[..out_len], &ciphertext1[..]);
+        let plaintext = responder.decrypt_and_hash(&mut in_out[..out_len])?;
+        assert_eq!(plaintext, &payload[..]);
+
+        initiator.mix_key_and_hash(&psk)?;
+        responder.mix_key_and_hash(&psk)?;
+
+        let mut in_out = vec![0; noise::TAG_LEN];
+        let out_len = initiator.encrypt_and_hash(&mut in_out, noise::TAG_LEN)?;
+        assert_eq!(&in_out[..out_len], &ciphertext2[..]);
+
+        // A forged message leaves the handshake hash unchanged.
+        let mut forged = in_out.clone();
+        forged[0] ^= 1;
+        assert!(responder.decrypt_and_hash(&mut forged).is_err());
+        assert!(responder.decrypt_and_hash(&mut in_out)?.is_empty());
+
+        assert_eq!(initiator.handshake_hash(), &handshake_hash[..]);
+        assert_eq!(responder.handshake_hash(), &handshake_hash[..]);
+
+        let (mut initiator_c1, mut initiator_c2) = initiator.split()?;
+        let (mut responder_c1, _) = responder.split()?;
+
+        assert_eq!(encrypt(&mut initiator_c1, &ad, &pt)?, transport1);
+        initiator_c1.rekey()?;
+        assert_eq!(initiator_c1.nonce(), 1);
+        assert_eq!(encrypt(&mut initiator_c1, &ad, &pt)?, transport2);
+
+        let mut in_out = transport1.clone();
+        assert_eq!(responder_c1.decrypt_with_ad(&ad, &mut in_out)?, &pt[..]);
+        responder_c1.rekey()?;
+        let mut in_out = transport2.clone();
+        assert_eq!(responder_c1.decrypt_with_ad(&ad, &mut in_out)?, &pt[..]);
+
+        initiator_c2.set_nonce(nonce);
+        assert_eq!(encrypt(&mut initiator_c2, &ad, &pt)?, transport3);
+        assert_eq!(initiator_c2.nonce(), nonce + 1);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn noise_cipher_state_test() {
+    let suite = &noise::X25519_AESGCM_SHA256;
+
+    // Without a key, data passes through unchanged.
+    let mut cipher_state = noise::CipherState::new(suite);
+    assert!(cipher_state.rekey().is_err());
+    let mut in_out = b"hello".to_vec();
+    assert_eq!(cipher_state.encrypt_with_ad(b"", &mut in_out, 0), Ok(5));
+    assert_eq!(
+        cipher_state.decrypt_with_ad(b"", &mut in_out).unwrap(),
+        b"hello"
+    );
+    assert_eq!(cipher_state.nonce(), 0);
+
+    assert!(cipher_state
+        .initialize_key(&[1; noise::KEY_LEN - 1])
+        .is_err());
+    cipher_state.initialize_key(&[1; noise::KEY_LEN]).unwrap();
+
+    // The nonce 2^64 - 1 is reserved.
+    cipher_state.set_nonce(u64::max_value());
+    assert!(encrypt(&mut cipher_state, b"", b"").is_err());
+    cipher_state.set_nonce(u64::max_value() - 1);
+    let ciphertext = encrypt(&mut cipher_state, b"", b"").unwrap();
+    assert!(encrypt(&mut cipher_state, b"", b"").is_err());
+
+    // A failed decryption doesn't advance the nonce.
+    let mut receiver = noise::CipherState::new(suite);
+    receiver.initialize_key(&[1; noise::KEY_LEN]).unwrap();
+    receiver.set_nonce(u64::max_value() - 1);
+    let mut forged = ciphertext.clone();
+    forged[0] ^= 1;
+    assert!(receiver.decrypt_with_ad(b"", &mut forged).is_err());
+    assert_eq!(receiver.nonce(), u64::max_value() - 1);
+    let mut in_out = ciphertext.clone();
+    assert_eq!(receiver.decrypt_with_ad(b"", &mut in_out).unwrap(), b"");
+}
+
+#[test]
+fn noise_rekey_test() {
+    let suite = &noise::X25519_AESGCM_SHA256;
+
+    // Rekeying doesn't reset the nonce, so the messages sealed after a rekey
+    // don't reuse the nonces of the messages sealed before it.
+    let mut sender = noise::CipherState::new(suite);
+    sender.initialize_key(&[2; noise::KEY_LEN]).unwrap();
+    let mut nonces = Vec::new();
+    let mut ciphertexts = Vec::new();
+    for i in 0..4 {
+        if i == 2 {
+            sender.rekey().unwrap();
+        }
+        nonces.push(sender.nonce());
+        ciphertexts.push(encrypt(&mut sender, b"", b"hello").unwrap());
+    }
+    assert_eq!(nonces, [0, 1, 2, 3]);
+
+    let mut receiver = noise::CipherState::new(suite);
+    receiver.initialize_key(&[2; noise::KEY_LEN]).unwrap();
+    for (i, ciphertext) in ciphertexts.iter().enumerate() {
+        if i == 2 {
+            receiver.rekey().unwrap();
+        }
+        let mut in_out = ciphertext.clone();
+        assert_eq!(
+            receiver.decrypt_with_ad(b"", &mut in_out).unwrap(),
+            b"hello"
+        );
+    }
+    assert_eq!(receiver.nonce(), 4);
+}
+
+#[test]
+fn noise_dh_test() {
+    let key_pair = noise::KeyPair::from_private_key(&[1; noise::DH_LEN]).unwrap();
+    assert!(noise::KeyPair::from_private_key(&[1; noise::DH_LEN - 1]).is_err());
+
+    // A point of small order is rejected.
+    assert!(key_pair.dh(&[0; noise::DH_LEN]).is_err());
+}
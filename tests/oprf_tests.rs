@@ -0,0 +1,216 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "oprf")]
+
+use ring::{oprf, rand, test, test_file};
+
+#[test]
+fn oprf_test() {
+    test::run(test_file!("oprf_tests.txt"), |section, test_case| {
+        let mode = match section {
+            "OPRF" => oprf::Mode::Oprf,
+            "VOPRF" => oprf::Mode::Voprf,
+            _ => unreachable!(),
+        };
+        let seed = test_case.consume_bytes("Seed");
+        let info = test_case.consume_bytes("KeyInfo");
+        let sk = test_case.consume_bytes("skSm");
+        let pk = if mode == oprf::Mode::Voprf {
+            Some(test_case.consume_bytes("pkSm"))
+        } else {
+            None
+        };
+        let input = test_case.consume_bytes("Input");
+        let blind = test_case.consume_bytes("Blind");
+        let blinded_element = test_case.consume_bytes("BlindedElement");
+        let evaluated_element = test_case.consume_bytes("EvaluationElement");
+        let (proof_random, proof) = if mode == oprf::Mode::Voprf {
+            (
+                test_case.consume_bytes("ProofRandomScalar"),
+                Some(test_case.consume_bytes("Proof")),
+            )
+        } else {
+            (vec![0x42; 32], None)
+        };
+        let output = test_case.consume_bytes("Output");
+
+        let server_key = oprf::ServerKey::derive(mode, &seed, &info)?;
+        assert_eq!(server_key.mode(), mode);
+        let from_bytes = oprf::ServerKey::from_bytes(mode, &sk)?;
+        assert_eq!(server_key.public_key(), from_bytes.public_key());
+        if let Some(pk) = &pk {
+            assert_eq!(server_key.public_key(), &pk[..]);
+        }
+
+        let client = oprf::Client::blind(
+            mode,
+            &input,
+            &test::rand::FixedSliceRandom { bytes: &blind },
+        )?;
+        assert_eq!(client.blinded_element(), &blinded_element[..]);
+
+        let evaluation = server_key.blind_evaluate(
+            &blinded_element,
+            &test::rand::FixedSliceRandom {
+                bytes: &proof_random,
+            },
+        )?;
+        assert_eq!(evaluation.evaluated_element(), &evaluated_element[..]);
+        assert_eq!(evaluation.proof(), proof.as_ref().map(|proof| &proof[..]));
+
+        let actual = match &proof {
+            None => client.finalize(&evaluated_element)?,
+            Some(proof) => {
+                client.finalize_verifiable(&evaluated_element, proof, server_key.public_key())?
+            }
+        };
+        assert_eq!(actual.as_ref(), &output[..]);
+        assert_eq!(server_key.evaluate(&input)?.as_ref(), &output[..]);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn oprf_bad_proof_test() {
+    let rng = rand::SystemRandom::new();
+    let mode = oprf::Mode::Voprf;
+    let server_key = oprf::ServerKey::derive(mode, &[0x42; oprf::SEED_LEN], b"").unwrap();
+    let other_key = oprf::ServerKey::derive(mode, &[0x43; oprf::SEED_LEN], b"").unwrap();
+
+    let blind = [0x01; oprf::PRIVATE_KEY_LEN];
+    let client = || {
+        oprf::Client::blind(
+            mode,
+            b"input",
+            &test::rand::FixedSliceRandom { bytes: &blind },
+        )
+        .unwrap()
+    };
+    let evaluation = server_key
+        .blind_evaluate(client().blinded_element(), &rng)
+        .unwrap();
+    let evaluated_element = evaluation.evaluated_element();
+    let proof = evaluation.proof().unwrap();
+
+    assert!(client()
+        .finalize_verifiable(evaluated_element, proof, server_key.public_key())
+        .is_ok());
+
+    // The proof doesn't verify with another public key.
+    assert!(client()
+        .finalize_verifiable(evaluated_element, proof, other_key.public_key())
+        .is_err());
+
+    // The proof is bound to the blinded element.
+    let other_client = oprf::Client::blind(mode, b"input", &rng).unwrap();
+    assert!(other_client
+        .finalize_verifiable(evaluated_element, proof, server_key.public_key())
+        .is_err());
+
+    // A modified proof doesn't verify.
+    for i in 0..proof.len() {
+        let mut bad_proof = proof.to_vec();
+        bad_proof[i] ^= 1;
+        assert!(client()
+            .finalize_verifiable(evaluated_element, &bad_proof, server_key.public_key())
+            .is_err());
+    }
+
+    // A proof is required in the VOPRF mode.
+    assert!(client().finalize(evaluated_element).is_err());
+}
+
+#[test]
+fn oprf_mode_mismatch_test() {
+    let rng = rand::SystemRandom::new();
+    let server_key =
+        oprf::ServerKey::derive(oprf::Mode::Oprf, &[0x42; oprf::SEED_LEN], b"").unwrap();
+
+    let client = oprf::Client::blind(oprf::Mode::Oprf, b"input", &rng).unwrap();
+    let evaluation = server_key
+        .blind_evaluate(client.blinded_element(), &rng)
+        .unwrap();
+    assert!(evaluation.proof().is_none());
+    assert!(client
+        .finalize_verifiable(evaluation.evaluated_element(), &[0; oprf::PROOF_LEN], &[])
+        .is_err());
+}
+
+#[test]
+fn oprf_bad_input_test() {
+    let rng = rand::SystemRandom::new();
+    let mode = oprf::Mode::Oprf;
+
+    // The seed must be exactly `SEED_LEN` bytes.
+    assert!(oprf::ServerKey::derive(mode, &[0x42; oprf::SEED_LEN - 1], b"").is_err());
+    assert!(oprf::ServerKey::derive(mode, &[0x42; oprf::SEED_LEN + 1], b"").is_err());
+
+    // Zero isn't a valid private key.
+    assert!(oprf::ServerKey::from_bytes(mode, &[0; oprf::PRIVATE_KEY_LEN]).is_err());
+
+    // Invalid and identity-encoding elements are rejected.
+    let server_key = oprf::ServerKey::derive(mode, &[0x42; oprf::SEED_LEN], b"").unwrap();
+    assert!(server_key
+        .blind_evaluate(&[0; oprf::ELEMENT_LEN], &rng)
+        .is_err());
+    assert!(server_key.blind_evaluate(&[0], &rng).is_err());
+    let client = oprf::Client::blind(mode, b"input", &rng).unwrap();
+    assert!(server_key
+        .blind_evaluate(client.blinded_element(), &rng)
+        .is_ok());
+    assert!(client.finalize(&[0x02; oprf::ELEMENT_LEN - 1]).is_err());
+
+    // Only the compressed encoding is accepted.
+    let mut uncompressed = [0; 1 + 2 * (oprf::ELEMENT_LEN - 1)];
+    uncompressed[0] = 4;
+    assert!(server_key.blind_evaluate(&uncompressed, &rng).is_err());
+
+    // x = 1 isn't the x coordinate of a point on P-256.
+    let mut off_curve = [0; oprf::ELEMENT_LEN];
+    off_curve[0] = 0x02;
+    off_curve[oprf::ELEMENT_LEN - 1] = 1;
+    assert!(server_key.blind_evaluate(&off_curve, &rng).is_err());
+
+    // The server's public key is decoded the same way.
+    let mode = oprf::Mode::Voprf;
+    let server_key = oprf::ServerKey::derive(mode, &[0x42; oprf::SEED_LEN], b"").unwrap();
+    let client = oprf::Client::blind(mode, b"input", &rng).unwrap();
+    let evaluation = server_key
+        .blind_evaluate(client.blinded_element(), &rng)
+        .unwrap();
+    let proof = evaluation.proof().unwrap();
+    assert!(client
+        .finalize_verifiable(evaluation.evaluated_element(), proof, &off_curve)
+        .is_err());
+}
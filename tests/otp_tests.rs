@@ -0,0 +1,137 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{digest, otp};
+
+/// Test vectors from RFC 4226 Appendix D.
+#[test]
+fn otp_hotp_test() {
+    const EXPECTED: [&str; 10] = [
+        "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583", "399871",
+        "520489",
+    ];
+    let key = otp::Key::new(&digest::SHA1, 6, b"12345678901234567890").unwrap();
+    for (counter, &expected) in EXPECTED.iter().enumerate() {
+        let counter = counter as u64;
+        assert_eq!(key.hotp(counter).as_str(), expected);
+        assert_eq!(key.verify_hotp(counter, 0, expected), Ok(counter));
+        assert_eq!(key.verify_hotp(0, 9, expected), Ok(counter));
+    }
+
+    // Outside the look-ahead window.
+    assert!(key.verify_hotp(0, 2, EXPECTED[3]).is_err());
+    assert!(key.verify_hotp(4, 5, EXPECTED[3]).is_err());
+    assert!(key.verify_hotp(u64::max_value(), 1, EXPECTED[0]).is_err());
+
+    // Wrong codes.
+    assert!(key.verify_hotp(0, 0, "755225").is_err());
+    assert!(key.verify_hotp(0, 0, "75522").is_err());
+    assert!(key.verify_hotp(0, 0, "0755224").is_err());
+    assert!(key.verify_hotp(0, 0, "").is_err());
+}
+
+/// Test vectors from RFC 6238 Appendix B.
+#[test]
+fn otp_totp_test() {
+    const TIMES: [u64; 6] = [
+        59,
+        1_111_111_109,
+        1_111_111_111,
+        1_234_567_890,
+        2_000_000_000,
+        20_000_000_000,
+    ];
+    let tests: [(&'static digest::Algorithm, &[u8], [&str; 6]); 3] = [
+        (
+            &digest::SHA1,
+            b"12345678901234567890",
+            [
+                "94287082", "07081804", "14050471", "89005924", "69279037", "65353130",
+            ],
+        ),
+        (
+            &digest::SHA256,
+            b"12345678901234567890123456789012",
+            [
+                "46119246", "68084774", "67062674", "91819424", "90698825", "77737706",
+            ],
+        ),
+        (
+            &digest::SHA512,
+            b"1234567890123456789012345678901234567890123456789012345678901234",
+            [
+                "90693936", "25091201", "99943326", "93441116", "38618901", "47863826",
+            ],
+        ),
+    ];
+
+    for (digest_alg, secret, expected) in tests.iter() {
+        let key = otp::Key::new(*digest_alg, 8, secret).unwrap();
+        let totp = otp::Totp::new(key, 30).unwrap();
+        for (&time, &expected) in TIMES.iter().zip(expected.iter()) {
+            let code = totp.generate(time);
+            assert_eq!(code.as_str(), expected);
+            assert_eq!(format!("{}", code), expected);
+
+            let time_step = time / 30;
+            assert_eq!(totp.verify(time, 0, expected), Ok(time_step));
+            assert_eq!(totp.verify(time + 30, 1, expected), Ok(time_step));
+            assert_eq!(totp.verify(time - 30, 1, expected), Ok(time_step));
+            assert!(totp.verify(time + 60, 1, expected).is_err());
+            assert!(totp.verify(time - 30, 0, expected).is_err());
+        }
+    }
+}
+
+#[test]
+fn otp_parameters_test() {
+    for &digits in &[0, otp::MIN_DIGITS - 1, otp::MAX_DIGITS + 1] {
+        assert!(otp::Key::new(&digest::SHA1, digits, b"secret").is_err());
+    }
+    for digits in otp::MIN_DIGITS..=otp::MAX_DIGITS {
+        let key = otp::Key::new(&digest::SHA1, digits, b"secret").unwrap();
+        assert_eq!(key.digits(), digits);
+        assert_eq!(key.hotp(0).as_str().len(), digits);
+        assert_eq!(
+            format!("{:?}", key),
+            format!("Key {{ digits: {} }}", digits)
+        );
+    }
+
+    let key = otp::Key::new(&digest::SHA1, 6, b"secret").unwrap();
+    assert!(otp::Totp::new(key, 0).is_err());
+
+    // The digits of a code aren't shown.
+    let key = otp::Key::new(&digest::SHA1, 6, b"secret").unwrap();
+    assert_eq!(format!("{:?}", key.hotp(0)), "Code");
+}
@@ -0,0 +1,218 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "p256_arithmetic")]
+
+use ring::{
+    p256::{self, Point, Scalar},
+    rand, test, test_file,
+};
+
+#[test]
+fn p256_test() {
+    test::run(test_file!("p256_tests.txt"), |section, test_case| {
+        match section {
+            "Scalar" => scalar_test(test_case),
+            "Point" => point_test(test_case),
+            _ => unreachable!(),
+        }
+        Ok(())
+    });
+}
+
+fn scalar_test(test_case: &mut test::TestCase) {
+    let a = scalar(&test_case.consume_bytes("A"));
+    let b = scalar(&test_case.consume_bytes("B"));
+    let sum = test_case.consume_bytes("Sum");
+    let difference = test_case.consume_bytes("Difference");
+    let product = test_case.consume_bytes("Product");
+    let negation = test_case.consume_bytes("Negation");
+    let inverse = test_case.consume_bytes("Inverse");
+
+    assert_eq!(&a.add(&b).to_bytes()[..], &sum[..]);
+    assert_eq!(&a.sub(&b).to_bytes()[..], &difference[..]);
+    assert_eq!(&a.mul(&b).to_bytes()[..], &product[..]);
+    assert_eq!(&b.mul(&a).to_bytes()[..], &product[..]);
+    assert_eq!(&a.neg().to_bytes()[..], &negation[..]);
+    match a.invert() {
+        Ok(inv) => {
+            assert_eq!(&inv.to_bytes()[..], &inverse[..]);
+            assert_eq!(inv.mul(&a).to_bytes(), Scalar::one().to_bytes());
+        }
+        Err(_) => {
+            assert!(a.is_zero());
+            assert!(inverse.is_empty());
+        }
+    }
+}
+
+fn point_test(test_case: &mut test::TestCase) {
+    let a = scalar(&test_case.consume_bytes("A"));
+    let b = scalar(&test_case.consume_bytes("B"));
+    let ag = test_case.consume_bytes("AG");
+    let ag_compressed = test_case.consume_bytes("AGCompressed");
+    let bg = test_case.consume_bytes("BG");
+    let sum = test_case.consume_bytes("Sum");
+
+    let p = Point::mul_base(&a);
+    assert_eq!(&p.to_uncompressed().unwrap()[..], &ag[..]);
+    assert_eq!(&p.to_compressed().unwrap()[..], &ag_compressed[..]);
+    assert_eq!(
+        &Point::generator().mul(&a).to_uncompressed().unwrap()[..],
+        &ag[..]
+    );
+
+    let decoded = Point::from_bytes(&ag).unwrap();
+    assert_eq!(&decoded.to_uncompressed().unwrap()[..], &ag[..]);
+    let decoded = Point::from_bytes(&ag_compressed).unwrap();
+    assert_eq!(&decoded.to_uncompressed().unwrap()[..], &ag[..]);
+
+    let q = Point::from_bytes(&bg).unwrap();
+    let s = p.add(&q);
+    if sum.is_empty() {
+        assert!(s.is_identity());
+        assert!(s.to_uncompressed().is_err());
+        assert!(s.to_compressed().is_err());
+    } else {
+        assert_eq!(&s.to_uncompressed().unwrap()[..], &sum[..]);
+        assert_eq!(
+            &Point::mul_base(&a.add(&b)).to_uncompressed().unwrap()[..],
+            &sum[..]
+        );
+    }
+
+    // (a*b)G = b(aG).
+    assert_eq!(
+        Point::mul_base(&a.mul(&b)).to_compressed().ok(),
+        p.mul(&b).to_compressed().ok()
+    );
+    assert!(p.add(&p.neg()).is_identity());
+}
+
+#[test]
+fn p256_identity_test() {
+    let identity = Point::identity();
+    assert!(identity.is_identity());
+    assert!(Point::mul_base(&Scalar::zero()).is_identity());
+    assert!(Point::generator().mul(&Scalar::zero()).is_identity());
+    assert!(identity.mul(&Scalar::one()).is_identity());
+    assert!(identity.neg().is_identity());
+    assert!(!Point::generator().is_identity());
+    assert_eq!(
+        identity.add(&Point::generator()).to_uncompressed().ok(),
+        Point::generator().to_uncompressed().ok()
+    );
+    assert!(identity.to_uncompressed().is_err());
+    assert!(identity.to_compressed().is_err());
+
+    // The generator doubled is 2G.
+    let g = Point::generator();
+    let two = Scalar::one().add(&Scalar::one());
+    assert_eq!(
+        g.add(&g).to_uncompressed().ok(),
+        Point::mul_base(&two).to_uncompressed().ok()
+    );
+}
+
+#[test]
+fn p256_decode_test() {
+    let g = Point::generator().to_uncompressed().unwrap();
+    let compressed = Point::generator().to_compressed().unwrap();
+
+    // Wrong prefix.
+    let mut bad = g;
+    bad[0] = 2;
+    assert!(Point::from_bytes(&bad).is_err());
+    let mut bad = compressed;
+    bad[0] = 4;
+    assert!(Point::from_bytes(&bad).is_err());
+
+    // Flipping the parity bit gives -G.
+    let mut negated = compressed;
+    negated[0] ^= 1;
+    assert_eq!(
+        Point::from_bytes(&negated).unwrap().to_compressed().ok(),
+        Point::generator().neg().to_compressed().ok()
+    );
+
+    // Not on the curve.
+    let mut bad = g;
+    bad[p256::UNCOMPRESSED_POINT_LEN - 1] ^= 1;
+    assert!(Point::from_bytes(&bad).is_err());
+
+    // x = 1 has no corresponding y on P-256.
+    let mut bad = [0u8; p256::COMPRESSED_POINT_LEN];
+    bad[0] = 2;
+    bad[p256::COMPRESSED_POINT_LEN - 1] = 1;
+    assert!(Point::from_bytes(&bad).is_err());
+
+    // Wrong length.
+    assert!(Point::from_bytes(&g[..p256::COMPRESSED_POINT_LEN]).is_err());
+    assert!(Point::from_bytes(&[]).is_err());
+}
+
+#[test]
+fn p256_scalar_decode_test() {
+    const N_MINUS_1: [u8; p256::SCALAR_LEN] = [
+        0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63,
+        0x25, 0x50,
+    ];
+    let n_minus_1 = Scalar::from_bytes(&N_MINUS_1).unwrap();
+    assert_eq!(n_minus_1.to_bytes(), N_MINUS_1);
+    assert!(n_minus_1.add(&Scalar::one()).is_zero());
+
+    // n is out of range for `from_bytes` but reduces to zero.
+    let mut n = N_MINUS_1;
+    n[p256::SCALAR_LEN - 1] += 1;
+    assert!(Scalar::from_bytes(&n).is_err());
+    assert!(Scalar::from_bytes_reduced(&n).is_zero());
+    assert_eq!(
+        Scalar::from_bytes_reduced(&[0xff; p256::SCALAR_LEN]).to_bytes(),
+        Scalar::from_bytes_reduced(&[0xff; p256::SCALAR_LEN])
+            .sub(&Scalar::zero())
+            .to_bytes()
+    );
+    assert!(Scalar::from_bytes(&N_MINUS_1[1..]).is_err());
+
+    let rng = rand::SystemRandom::new();
+    let r = Scalar::random(&rng).unwrap();
+    assert!(!r.is_zero());
+    assert_eq!(
+        Scalar::from_bytes(&r.to_bytes()).unwrap().to_bytes(),
+        r.to_bytes()
+    );
+}
+
+fn scalar(bytes: &[u8]) -> Scalar {
+    Scalar::from_bytes(bytes).unwrap()
+}
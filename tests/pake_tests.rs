@@ -0,0 +1,192 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "use_heap")]
+
+use ring::{digest, error, pake, rand, test};
+
+const A_IDENTITY: &[u8] = b"phone";
+const B_IDENTITY: &[u8] = b"laptop";
+const AAD: &[u8] = b"pairing v1";
+
+fn password_hash(password: &[u8]) -> digest::Digest {
+    digest::digest(&digest::SHA512, password)
+}
+
+fn start(role: pake::Role, password: &[u8], rng: &dyn rand::SecureRandom) -> pake::Spake2 {
+    let (my_identity, peer_identity) = match role {
+        pake::Role::A => (A_IDENTITY, B_IDENTITY),
+        pake::Role::B => (B_IDENTITY, A_IDENTITY),
+    };
+    pake::Spake2::new(
+        role,
+        password_hash(password).as_ref(),
+        my_identity,
+        peer_identity,
+        rng,
+    )
+    .unwrap()
+}
+
+fn shared_key(
+    confirmation: pake::KeyConfirmation,
+    peer_confirmation: &[u8],
+) -> Result<Vec<u8>, error::Unspecified> {
+    confirmation.verify(
+        peer_confirmation,
+        error::Unspecified,
+        |key| Ok(key.to_vec()),
+    )
+}
+
+// Generated with an independent implementation built on the Python
+// cryptography package.
+#[test]
+fn spake2_known_answer_test() {
+    let a = start(
+        pake::Role::A,
+        b"password",
+        &test::rand::FixedSliceRandom { bytes: &[0x11; 64] },
+    );
+    let b = start(
+        pake::Role::B,
+        b"password",
+        &test::rand::FixedSliceRandom { bytes: &[0x22; 64] },
+    );
+    assert_eq!(
+        a.share(),
+        &test::from_hex("69e841144026c044f538c12e73898e13a1be79dc77d4f5749dde0eda0d0ecea3")
+            .unwrap()[..]
+    );
+    assert_eq!(
+        b.share(),
+        &test::from_hex("7ef9d11dd475273be542965c8ef99e0477d9c6b60139ef6882b832d42f01c00b")
+            .unwrap()[..]
+    );
+
+    let a_share = a.share().to_vec();
+    let b_share = b.share().to_vec();
+    let a = a.finish(&b_share, AAD).unwrap();
+    let b = b.finish(&a_share, AAD).unwrap();
+    assert_eq!(
+        a.confirmation(),
+        &test::from_hex("ada5092a87725e20fa967d0c245c06416a212ed88e1254b45a50b0c6c6fd7291")
+            .unwrap()[..]
+    );
+    assert_eq!(
+        b.confirmation(),
+        &test::from_hex("1d97c0491cf9e691008115373b90c5fd2ce7547aca257b5b184e43f243b4bf03")
+            .unwrap()[..]
+    );
+
+    let a_confirmation = a.confirmation().to_vec();
+    let b_confirmation = b.confirmation().to_vec();
+    let expected_key = test::from_hex("feebf060daedf0ed56446e4db3960834").unwrap();
+    assert_eq!(shared_key(a, &b_confirmation).unwrap(), expected_key);
+    assert_eq!(shared_key(b, &a_confirmation).unwrap(), expected_key);
+}
+
+#[test]
+fn spake2_mismatch_test() {
+    let rng = rand::SystemRandom::new();
+
+    // (B's password, B's AAD)
+    for &(b_password, b_aad) in &[
+        (&b"password"[..], &b"pairing v2"[..]),
+        (&b"passw0rd"[..], AAD),
+    ] {
+        let a = start(pake::Role::A, b"password", &rng);
+        let b = start(pake::Role::B, b_password, &rng);
+        let a_share = a.share().to_vec();
+        let b_share = b.share().to_vec();
+        let a = a.finish(&b_share, AAD).unwrap();
+        let b = b.finish(&a_share, b_aad).unwrap();
+        let a_confirmation = a.confirmation().to_vec();
+        let b_confirmation = b.confirmation().to_vec();
+        assert!(shared_key(a, &b_confirmation).is_err());
+        assert!(shared_key(b, &a_confirmation).is_err());
+    }
+
+    // Both parties in the same role.
+    let a1 = start(pake::Role::A, b"password", &rng);
+    let a2 = start(pake::Role::A, b"password", &rng);
+    let a1_share = a1.share().to_vec();
+    let a2_share = a2.share().to_vec();
+    let a1 = a1.finish(&a2_share, AAD).unwrap();
+    let a2 = a2.finish(&a1_share, AAD).unwrap();
+    let a1_confirmation = a1.confirmation().to_vec();
+    let a2_confirmation = a2.confirmation().to_vec();
+    assert!(shared_key(a1, &a2_confirmation).is_err());
+    assert!(shared_key(a2, &a1_confirmation).is_err());
+}
+
+#[test]
+fn spake2_invalid_share_test() {
+    let rng = rand::SystemRandom::new();
+    let b = start(pake::Role::B, b"password", &rng);
+    let b_share = b.share().to_vec();
+
+    // Too short, too long, and not a point.
+    let too_long = [&b_share[..], &[0u8][..]].concat();
+    let mut not_a_point = [0u8; pake::SHARE_LEN];
+    not_a_point[0] = 2;
+    for share in &[&b_share[..31], &too_long[..], &not_a_point[..]] {
+        let a = start(pake::Role::A, b"password", &rng);
+        assert!(a.finish(share, AAD).is_err());
+    }
+
+    // w*N plus a point of order 8, for the password "password", makes the
+    // shared secret the identity.
+    let share =
+        test::from_hex("95828709a0749972b2cdfe914591455dae1c9684fcafcaa381d06195f890e179").unwrap();
+    let a = start(pake::Role::A, b"password", &rng);
+    assert!(a.finish(&share, AAD).is_err());
+}
+
+#[test]
+fn spake2_password_hash_len_test() {
+    let rng = rand::SystemRandom::new();
+    for &len in &[0, pake::PASSWORD_HASH_LEN - 1, pake::PASSWORD_HASH_LEN + 1] {
+        let password_hash = vec![0; len];
+        assert!(pake::Spake2::new(pake::Role::A, &password_hash, b"", b"", &rng).is_err());
+    }
+}
+
+#[test]
+fn spake2_debug_test() {
+    let a = start(
+        pake::Role::A,
+        b"password",
+        &test::rand::FixedSliceRandom { bytes: &[0x11; 64] },
+    );
+    assert_eq!(format!("{:?}", a), "Spake2 { role: A }");
+}
@@ -0,0 +1,160 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(all(feature = "use_heap", feature = "aead-chacha", feature = "sig-ed25519"))]
+
+use ring::{paseto, rand, signature, test, test_file};
+
+#[test]
+fn paseto_test() {
+    test::run(test_file!("paseto_tests.txt"), |section, test_case| {
+        let message = test_case.consume_bytes("Message");
+        let footer = test_case.consume_bytes("Footer");
+        let implicit_assertion = test_case.consume_bytes("ImplicitAssertion");
+        let token = test_case.consume_string("Token");
+
+        match section {
+            "v4.local" => {
+                let key = paseto::LocalKey::new(&test_case.consume_bytes("Key")).unwrap();
+                let nonce = test_case.consume_bytes("Nonce");
+                let rng = test::rand::FixedSliceRandom { bytes: &nonce };
+
+                let actual = key
+                    .encrypt(&message, &footer, &implicit_assertion, &rng)
+                    .unwrap();
+                assert_eq!(actual, token);
+                assert_eq!(
+                    key.decrypt(&token, &footer, &implicit_assertion).unwrap(),
+                    message
+                );
+                assert!(key.decrypt(&token, &footer, b"wrong").is_err());
+                assert!(key.decrypt(&token, b"wrong", &implicit_assertion).is_err());
+                assert!(key
+                    .decrypt(&tamper(&token), &footer, &implicit_assertion)
+                    .is_err());
+            }
+
+            "v4.public" => {
+                let seed = test_case.consume_bytes("Seed");
+                let public_key = test_case.consume_bytes("PublicKey");
+                let key_pair =
+                    signature::Ed25519KeyPair::from_seed_and_public_key(&seed, &public_key)
+                        .unwrap();
+
+                let rng = rand::SystemRandom::new();
+                let actual =
+                    paseto::sign(&key_pair, &message, &footer, &implicit_assertion, &rng).unwrap();
+                assert_eq!(actual, token);
+                assert_eq!(
+                    paseto::verify(&public_key, &token, &footer, &implicit_assertion).unwrap(),
+                    message
+                );
+                assert!(paseto::verify(&public_key, &token, &footer, b"wrong").is_err());
+                assert!(
+                    paseto::verify(&public_key, &token, b"wrong", &implicit_assertion).is_err()
+                );
+                assert!(
+                    paseto::verify(&public_key, &tamper(&token), &footer, &implicit_assertion)
+                        .is_err()
+                );
+            }
+
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    });
+}
+
+// Flips a bit in the first character of the payload.
+fn tamper(token: &str) -> String {
+    let payload_start = token.match_indices('.').nth(1).unwrap().0 + 1;
+    let mut tampered = String::from(&token[..payload_start]);
+    let c = token.as_bytes()[payload_start];
+    tampered.push(if c == b'A' { 'B' } else { 'A' });
+    tampered.push_str(&token[(payload_start + 1)..]);
+    tampered
+}
+
+#[test]
+fn paseto_local_round_trip_test() {
+    let rng = rand::SystemRandom::new();
+    let key = paseto::LocalKey::new(&[0x42; paseto::LOCAL_KEY_LEN]).unwrap();
+    let token = key.encrypt(b"message", b"footer", b"", &rng).unwrap();
+    assert!(token.starts_with("v4.local."));
+    assert_eq!(key.decrypt(&token, b"footer", b"").unwrap(), b"message");
+
+    // A key of the wrong length is rejected.
+    assert!(paseto::LocalKey::new(&[0x42; paseto::LOCAL_KEY_LEN - 1]).is_err());
+
+    // A token for a different key is rejected.
+    let other_key = paseto::LocalKey::new(&[0x43; paseto::LOCAL_KEY_LEN]).unwrap();
+    assert!(other_key.decrypt(&token, b"footer", b"").is_err());
+
+    // The footer must be present exactly when one is expected.
+    let no_footer = &token[..token.rfind('.').unwrap()];
+    assert!(key.decrypt(no_footer, b"footer", b"").is_err());
+    assert!(key.decrypt(no_footer, b"", b"").is_err());
+    assert!(key.decrypt(&token, b"", b"").is_err());
+}
+
+#[test]
+fn paseto_wrong_purpose_test() {
+    let rng = rand::SystemRandom::new();
+    let key = paseto::LocalKey::new(&[0x42; paseto::LOCAL_KEY_LEN]).unwrap();
+    let pkcs8 = signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair = signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let public_key = signature::KeyPair::public_key(&key_pair);
+
+    let local = key.encrypt(b"message", b"", b"", &rng).unwrap();
+    let public = paseto::sign(&key_pair, b"message", b"", b"", &rng).unwrap();
+    assert!(paseto::verify(public_key.as_ref(), &local, b"", b"").is_err());
+    assert!(key.decrypt(&public, b"", b"").is_err());
+
+    // Other versions aren't accepted.
+    let v3 = public.replacen("v4.", "v3.", 1);
+    assert!(paseto::verify(public_key.as_ref(), &v3, b"", b"").is_err());
+}
+
+#[test]
+fn paseto_pae_test() {
+    // From the PASETO specification.
+    assert_eq!(paseto::pae(&[]), b"\x00\x00\x00\x00\x00\x00\x00\x00");
+    assert_eq!(
+        paseto::pae(&[b""]),
+        &b"\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00"[..]
+    );
+    assert_eq!(
+        paseto::pae(&[b"test"]),
+        &b"\x01\x00\x00\x00\x00\x00\x00\x00\x04\x00\x00\x00\x00\x00\x00\x00test"[..]
+    );
+}
@@ -0,0 +1,218 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "pem")]
+
+use ring::{pem, rand, signature, signature::KeyPair};
+
+#[test]
+fn pem_private_keys() {
+    let rng = rand::SystemRandom::new();
+    const MESSAGE: &[u8] = b"hello, world";
+
+    let ed25519 = pem::parse(include_str!("pem_test_private_key_ed25519.pem")).unwrap();
+    assert_eq!(ed25519.label(), pem::PRIVATE_KEY);
+    assert_eq!(
+        ed25519.contents(),
+        &include_bytes!("ed25519_test_private_key.p8")[..]
+    );
+    let key_pair = signature::Ed25519KeyPair::from_pkcs8(ed25519.contents()).unwrap();
+    assert_eq!(
+        key_pair.public_key().as_ref(),
+        &include_bytes!("ed25519_test_public_key.der")[..]
+    );
+
+    type FromDer = fn(
+        &'static signature::EcdsaSigningAlgorithm,
+        &[u8],
+    ) -> Result<signature::EcdsaKeyPair, ring::error::KeyRejected>;
+    let cases: [(&str, FromDer); 2] = [
+        (
+            include_str!("pem_test_private_key_p256.pem"),
+            signature::EcdsaKeyPair::from_pkcs8,
+        ),
+        (
+            include_str!("pem_test_ec_private_key_p256.pem"),
+            signature::EcdsaKeyPair::from_ec_private_key,
+        ),
+    ];
+    for (file, from) in &cases {
+        let block = pem::parse(file).unwrap();
+        let key_pair = from(&signature::ECDSA_P256_SHA256_ASN1_SIGNING, block.contents()).unwrap();
+        assert_eq!(
+            key_pair.public_key().as_ref(),
+            &include_bytes!("ecdsa_test_public_key_p256.der")[..]
+        );
+        let signature = key_pair.sign(&rng, MESSAGE).unwrap();
+        let public_key = pem::parse(include_str!("pem_test_public_key_p256.pem")).unwrap();
+        signature::UnparsedPublicKey::new(
+            &signature::ECDSA_P256_SHA256_ASN1,
+            public_key.subject_public_key().unwrap(),
+        )
+        .verify(MESSAGE, signature.as_ref())
+        .unwrap();
+    }
+
+    let block = pem::parse(include_str!("pem_test_ec_private_key_p256.pem")).unwrap();
+    assert_eq!(block.label(), pem::EC_PRIVATE_KEY);
+    assert!(signature::EcdsaKeyPair::from_ec_private_key(
+        &signature::ECDSA_P384_SHA384_ASN1_SIGNING,
+        block.contents()
+    )
+    .is_err());
+}
+
+#[cfg(feature = "rsa")]
+#[test]
+fn pem_rsa_private_keys() {
+    let rsa = pem::parse(include_str!("pem_test_private_key_rsa_2048.pem")).unwrap();
+    assert_eq!(
+        rsa.contents(),
+        &include_bytes!("rsa_test_private_key_2048.p8")[..]
+    );
+    let key_pair = signature::RsaKeyPair::from_pkcs8(rsa.contents()).unwrap();
+    let rsa = pem::parse(include_str!("pem_test_rsa_private_key_2048.pem")).unwrap();
+    assert_eq!(rsa.label(), pem::RSA_PRIVATE_KEY);
+    let key_pair_from_der = signature::RsaKeyPair::from_der(rsa.contents()).unwrap();
+    assert_eq!(
+        key_pair.public_key().as_ref(),
+        key_pair_from_der.public_key().as_ref()
+    );
+}
+
+#[test]
+fn pem_public_keys() {
+    let ed25519 = pem::parse(include_str!("pem_test_public_key_ed25519.pem")).unwrap();
+    assert_eq!(ed25519.label(), pem::PUBLIC_KEY);
+    assert_eq!(
+        ed25519.subject_public_key().unwrap(),
+        &include_bytes!("ed25519_test_public_key.der")[..]
+    );
+
+    let rsa = pem::parse(include_str!("pem_test_public_key_rsa_2048.pem")).unwrap();
+    assert_eq!(
+        rsa.subject_public_key().unwrap(),
+        &include_bytes!("rsa_test_public_key_2048.der")[..]
+    );
+
+    // Only `PUBLIC KEY` blocks have a subject public key.
+    let private_key = pem::parse(include_str!("pem_test_private_key_p256.pem")).unwrap();
+    assert!(private_key.subject_public_key().is_err());
+    let not_spki = pem::Pem::new(pem::PUBLIC_KEY, &[0x30, 0x00]).unwrap();
+    assert!(not_spki.subject_public_key().is_err());
+}
+
+#[test]
+fn pem_encode() {
+    let file = include_str!("pem_test_private_key_rsa_2048.pem");
+    let block = pem::parse(file).unwrap();
+    assert_eq!(block.encode(), file);
+    assert_eq!(pem::encode(block.label(), block.contents()), file);
+
+    assert_eq!(
+        pem::encode("TEST", b""),
+        "-----BEGIN TEST-----\n-----END TEST-----\n"
+    );
+    assert_eq!(
+        pem::Pem::new("TEST", b"abc").unwrap().encode(),
+        "-----BEGIN TEST-----\nYWJj\n-----END TEST-----\n"
+    );
+    assert_eq!(
+        pem::parse(&pem::encode("TEST", b"")).unwrap().contents(),
+        b""
+    );
+}
+
+#[test]
+fn pem_parse_lenient() {
+    // Explanatory text, CRLF line endings, and trailing whitespace.
+    let input = "Subject: test\r\n\
+                 -----BEGIN TEST-----  \r\n\
+                 YWJj\r\n\
+                 ZGVm\t\r\n\
+                 -----END TEST-----\r\n\
+                 trailing text";
+    let block = pem::parse(input).unwrap();
+    assert_eq!(block.label(), "TEST");
+    assert_eq!(block.contents(), b"abcdef");
+}
+
+#[test]
+fn pem_parse_all() {
+    let input = [
+        pem::encode("A", b"a"),
+        String::from("between\n"),
+        pem::encode("B B", b"bb"),
+    ]
+    .concat();
+    let blocks = pem::parse_all(&input).unwrap();
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0].label(), "A");
+    assert_eq!(blocks[0].contents(), b"a");
+    assert_eq!(blocks[1].label(), "B B");
+    assert_eq!(blocks[1].contents(), b"bb");
+
+    assert!(pem::parse_all("no blocks").unwrap().is_empty());
+    assert!(pem::parse("no blocks").is_err());
+    assert!(pem::parse_all(&[&input, "-----BEGIN C-----\n"].concat()).is_err());
+}
+
+#[test]
+fn pem_parse_invalid() {
+    for input in &[
+        // Mismatched labels.
+        "-----BEGIN A-----\nYWJj\n-----END B-----\n",
+        // No END line.
+        "-----BEGIN A-----\nYWJj\n",
+        // Invalid base64.
+        "-----BEGIN A-----\nYWJ\n-----END A-----\n",
+        "-----BEGIN A-----\nYW!j\n-----END A-----\n",
+        // Invalid labels.
+        "-----BEGIN -A-----\nYWJj\n-----END -A-----\n",
+        "-----BEGIN A--B-----\nYWJj\n-----END A--B-----\n",
+        "-----BEGIN A -----\nYWJj\n-----END A -----\n",
+    ] {
+        assert!(pem::parse(input).is_err(), "{}", input);
+    }
+
+    assert!(pem::Pem::new("PRIVATE KEY", b"").is_ok());
+    assert!(pem::Pem::new("PRIVATE-KEY", b"").is_ok());
+    assert!(pem::Pem::new("PRIVATE  KEY", b"").is_err());
+    assert!(pem::Pem::new("PRIVATE\nKEY", b"").is_err());
+    assert!(pem::Pem::new("-PRIVATE KEY", b"").is_err());
+}
+
+#[test]
+fn pem_debug() {
+    let block = pem::Pem::new(pem::PRIVATE_KEY, b"secret").unwrap();
+    assert_eq!("Pem { label: \"PRIVATE KEY\" }", format!("{:?}", block));
+}
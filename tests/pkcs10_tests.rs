@@ -0,0 +1,313 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(all(feature = "rsa", feature = "sig-ed25519"))]
+
+use ring::{pkcs10, rand, signature, test, x509};
+
+const SUBJECT: &[pkcs10::NameAttribute] = &[
+    pkcs10::NameAttribute::Country("US"),
+    pkcs10::NameAttribute::Organization("Example"),
+    pkcs10::NameAttribute::CommonName("example.com"),
+];
+
+const DNS_NAMES: &[&str] = &["example.com", "www.example.com"];
+
+fn ed25519_key_pair() -> signature::Ed25519KeyPair {
+    signature::Ed25519KeyPair::from_seed_and_public_key(
+        include_bytes!("ed25519_test_private_key.bin"),
+        include_bytes!("ed25519_test_public_key.bin"),
+    )
+    .unwrap()
+}
+
+#[test]
+fn pkcs10_certification_request_test() {
+    let key_pair = ed25519_key_pair();
+    let rng = rand::SystemRandom::new();
+
+    // Verified with `openssl req -verify -text`.
+    let params = pkcs10::Params {
+        subject: SUBJECT,
+        dns_names: DNS_NAMES,
+    };
+    let expected = test::from_hex(
+        "3081ef3081a20201003035310b30090603550406130255533110300e060355040a0c074578616d706c65\
+         3114301206035504030c0b6578616d706c652e636f6d302a300506032b6570032100d75a980182b10ab7\
+         d54bfed3c964073a0ee172f3daa62325af021a68f707511aa03a303806092a864886f70d01090e312b30\
+         2930270603551d110420301e820b6578616d706c652e636f6d820f7777772e6578616d706c652e636f6d\
+         300506032b65700341003fa6cf9ecac8e7e3f86242459aacf483ae052abad97355cac8ccb450fd2885ff\
+         e106fdc6ea62bcc943d2da734064251d9b31dab7f4fa0045f03717749275db0e",
+    )
+    .unwrap();
+    let actual = pkcs10::certification_request(&pkcs10::ED25519, &key_pair, &params, &rng).unwrap();
+    assert_eq!(actual, expected);
+
+    // Without the extension request.
+    let params = pkcs10::Params {
+        subject: &[pkcs10::NameAttribute::CommonName("example")],
+        dns_names: &[],
+    };
+    let expected = test::from_hex(
+        "308191304502010030123110300e06035504030c076578616d706c65302a300506032b6570032100d75a\
+         980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511aa000300506032b6570034100\
+         45f42c2c5267c06352992a81f2923ec4c5ecfc683642fb29e04ccbfa8261231380e98ddec7932027af71\
+         12d2c9fe034d8c31b99eff42e978fa61c5bb3439fb02",
+    )
+    .unwrap();
+    let actual = pkcs10::certification_request(&pkcs10::ED25519, &key_pair, &params, &rng).unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn pkcs10_self_signed_certificate_test() {
+    let key_pair = ed25519_key_pair();
+
+    // Verified with `openssl verify` and `openssl x509 -text`. `not_after`
+    // is after 2049 so it is encoded as a `GeneralizedTime`.
+    let params = pkcs10::Params {
+        subject: SUBJECT,
+        dns_names: DNS_NAMES,
+    };
+    let validity = pkcs10::Validity {
+        not_before: 1_700_000_000,
+        not_after: 2_600_000_000,
+    };
+    let expected = test::from_hex(
+        "3082015530820107a00302010202106bababababababababababababababab300506032b65703035310b30\
+         090603550406130255533110300e060355040a0c074578616d706c653114301206035504030c0b657861\
+         6d706c652e636f6d3020170d3233313131343232313332305a180f32303532303532323134313332305a\
+         3035310b30090603550406130255533110300e060355040a0c074578616d706c65311430120603550403\
+         0c0b6578616d706c652e636f6d302a300506032b6570032100d75a980182b10ab7d54bfed3c964073a0e\
+         e172f3daa62325af021a68f707511aa32b302930270603551d110420301e820b6578616d706c652e636f\
+         6d820f7777772e6578616d706c652e636f6d300506032b6570034100f4fbe24a6b6bcb7e34ccb2168239\
+         0b7b9a8c769a4c92b20cda0560abfadeab6cf8f134c7fc1a621955c7462a7128b2ceccfe982bb2d35e10\
+         a3b0f7c9bab3f80f",
+    )
+    .unwrap();
+    let rng = test::rand::FixedSliceRandom { bytes: &[0xab; 16] };
+    let actual =
+        pkcs10::self_signed_certificate(&pkcs10::ED25519, &key_pair, &params, validity, &rng)
+            .unwrap();
+    assert_eq!(actual, expected);
+
+    // Without extensions.
+    let params = pkcs10::Params {
+        subject: &[pkcs10::NameAttribute::CommonName("example")],
+        dns_names: &[],
+    };
+    let validity = pkcs10::Validity {
+        not_before: 0,
+        not_after: 1_700_000_000,
+    };
+    let expected = test::from_hex(
+        "3081df308192a003020102021041010101010101010101010101010101300506032b657030123110300e\
+         06035504030c076578616d706c65301e170d3730303130313030303030305a170d323331313134323231\
+         3332305a30123110300e06035504030c076578616d706c65302a300506032b6570032100d75a980182b1\
+         0ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a300506032b65700341009c17fc16abff\
+         53971b00356a7092441cb47744d67b600b30974060b2fc6e3981662e3532b4620c61e42243a07f485982\
+         78c7eb39b9be80dad1aa23eac9a9c50d",
+    )
+    .unwrap();
+    let rng = test::rand::FixedSliceRandom { bytes: &[0x01; 16] };
+    let actual =
+        pkcs10::self_signed_certificate(&pkcs10::ED25519, &key_pair, &params, validity, &rng)
+            .unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn pkcs10_signature_test() {
+    let rng = rand::SystemRandom::new();
+    let params = pkcs10::Params {
+        subject: SUBJECT,
+        dns_names: DNS_NAMES,
+    };
+    let validity = pkcs10::Validity {
+        not_before: 1_700_000_000,
+        not_after: 1_800_000_000,
+    };
+
+    let ecdsa_p256 = signature::EcdsaKeyPair::from_pkcs8(
+        &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+        include_bytes!("ecdsa_test_private_key_p256.p8"),
+    )
+    .unwrap();
+    let rsa =
+        signature::RsaKeyPair::from_pkcs8(include_bytes!("rsa_test_private_key_2048.p8")).unwrap();
+    let ed25519 = ed25519_key_pair();
+
+    let keys: &[(&pkcs10::SignatureAlgorithm, &dyn signature::Signer)] = &[
+        (&pkcs10::ECDSA_P256_SHA256, &ecdsa_p256),
+        (&pkcs10::ED25519, &ed25519),
+        (&pkcs10::RSA_PKCS1_SHA256, &rsa),
+        (&pkcs10::RSA_PKCS1_SHA512, &rsa),
+        (&pkcs10::RSA_PSS_SHA256, &rsa),
+        (&pkcs10::RSA_PSS_SHA384, &rsa),
+    ];
+    for &(algorithm, key) in keys {
+        let request = pkcs10::certification_request(algorithm, key, &params, &rng).unwrap();
+        let (request_info, signature_algorithm, signature) = split_signed(&request);
+        let spki = der_elements(request_info)[2];
+        assert_eq!(spki_public_key(spki), key.public_key_bytes());
+        x509::verify_signature(signature_algorithm, spki, request_info, signature).unwrap();
+
+        let certificate =
+            pkcs10::self_signed_certificate(algorithm, key, &params, validity, &rng).unwrap();
+        let (tbs_certificate, signature_algorithm, signature) = split_signed(&certificate);
+        let spki = der_elements(tbs_certificate)[6];
+        assert_eq!(spki_public_key(spki), key.public_key_bytes());
+        x509::verify_signature(signature_algorithm, spki, tbs_certificate, signature).unwrap();
+    }
+}
+
+#[test]
+fn pkcs10_bad_params_test() {
+    let key_pair = ed25519_key_pair();
+    let rng = rand::SystemRandom::new();
+    let validity = pkcs10::Validity {
+        not_before: 1_700_000_000,
+        not_after: 1_800_000_000,
+    };
+    let long_name = "a".repeat(254);
+    let too_many_dns_names = vec!["example.com"; pkcs10::MAX_DNS_NAMES + 1];
+
+    let bad_params: &[(&[pkcs10::NameAttribute], &[&str])] = &[
+        (&[pkcs10::NameAttribute::CommonName("")], &[]),
+        (&[pkcs10::NameAttribute::Organization(&long_name)], &[]),
+        (&[pkcs10::NameAttribute::Country("us")], &[]),
+        (&[pkcs10::NameAttribute::Country("USA")], &[]),
+        (SUBJECT, &[""]),
+        (SUBJECT, &["example .com"]),
+        (SUBJECT, &[&long_name]),
+        (SUBJECT, &too_many_dns_names),
+    ];
+    for &(subject, dns_names) in bad_params {
+        let params = pkcs10::Params { subject, dns_names };
+        assert!(pkcs10::certification_request(&pkcs10::ED25519, &key_pair, &params, &rng).is_err());
+        assert!(pkcs10::self_signed_certificate(
+            &pkcs10::ED25519,
+            &key_pair,
+            &params,
+            validity,
+            &rng
+        )
+        .is_err());
+    }
+
+    let params = pkcs10::Params {
+        subject: SUBJECT,
+        dns_names: DNS_NAMES,
+    };
+
+    // The key must support the algorithm.
+    assert!(
+        pkcs10::certification_request(&pkcs10::ECDSA_P256_SHA256, &key_pair, &params, &rng)
+            .is_err()
+    );
+    let ecdsa_fixed = signature::EcdsaKeyPair::from_pkcs8(
+        &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+        include_bytes!("ecdsa_test_private_key_p256.p8"),
+    )
+    .unwrap();
+    assert!(
+        pkcs10::certification_request(&pkcs10::ECDSA_P256_SHA256, &ecdsa_fixed, &params, &rng)
+            .is_err()
+    );
+
+    // The validity period must not be empty or end after 9999.
+    for &(not_before, not_after) in &[(1_800_000_000, 1_700_000_000), (0, 253_402_300_800)] {
+        let validity = pkcs10::Validity {
+            not_before,
+            not_after,
+        };
+        assert!(pkcs10::self_signed_certificate(
+            &pkcs10::ED25519,
+            &key_pair,
+            &params,
+            validity,
+            &rng
+        )
+        .is_err());
+    }
+    let validity = pkcs10::Validity {
+        not_before: 0,
+        not_after: 253_402_300_799,
+    };
+    assert!(
+        pkcs10::self_signed_certificate(&pkcs10::ED25519, &key_pair, &params, validity, &rng)
+            .is_ok()
+    );
+}
+
+// Splits a `CertificationRequest` or `Certificate` into the encoding of the
+// signed data, the encoding of the signature algorithm, and the signature.
+fn split_signed(der: &[u8]) -> (&[u8], &[u8], &[u8]) {
+    let elements = der_elements(der);
+    assert_eq!(elements.len(), 3);
+    let signature = contents(elements[2]);
+    assert_eq!(signature[0], 0);
+    (elements[0], elements[1], &signature[1..])
+}
+
+fn spki_public_key(spki: &[u8]) -> &[u8] {
+    let public_key = contents(der_elements(spki)[1]);
+    assert_eq!(public_key[0], 0);
+    &public_key[1..]
+}
+
+// The encodings of the elements of the DER-encoded `SEQUENCE` `der`.
+fn der_elements(der: &[u8]) -> Vec<&[u8]> {
+    let mut input = contents(der);
+    let mut elements = Vec::new();
+    while !input.is_empty() {
+        let (header_len, len) = header(input);
+        elements.push(&input[..header_len + len]);
+        input = &input[header_len + len..];
+    }
+    elements
+}
+
+fn contents(der: &[u8]) -> &[u8] {
+    let (header_len, len) = header(der);
+    assert_eq!(der.len(), header_len + len);
+    &der[header_len..]
+}
+
+fn header(der: &[u8]) -> (usize, usize) {
+    match der[1] {
+        len @ 0..=0x7f => (2, usize::from(len)),
+        0x81 => (3, usize::from(der[2])),
+        0x82 => (4, (usize::from(der[2]) << 8) | usize::from(der[3])),
+        _ => unreachable!(),
+    }
+}
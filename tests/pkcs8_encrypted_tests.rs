@@ -0,0 +1,151 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "use_heap")]
+
+use core::num::NonZeroU32;
+#[cfg(feature = "sig-ed25519")]
+use ring::signature::{self, KeyPair};
+use ring::{pkcs8, rand, test, test_file};
+
+#[test]
+fn pkcs8_encrypted_test() {
+    test::run(
+        test_file!("pkcs8_encrypted_tests.txt"),
+        |section, test_case| {
+            match section {
+                "Encrypt" => encrypt_test_case(test_case),
+                "Decrypt" => decrypt_test_case(test_case),
+                _ => unreachable!(),
+            }
+            Ok(())
+        },
+    );
+}
+
+fn encrypt_test_case(test_case: &mut test::TestCase) {
+    let algorithm = match test_case.consume_string("Algorithm").as_str() {
+        "PBES2_HMAC_SHA256_AES_256_CBC" => &pkcs8::PBES2_HMAC_SHA256_AES_256_CBC,
+        "PBES2_HMAC_SHA256_AES_256_GCM" => &pkcs8::PBES2_HMAC_SHA256_AES_256_GCM,
+        _ => unreachable!(),
+    };
+    let password = test_case.consume_bytes("Password");
+    let iterations = iterations(test_case.consume_usize("Iterations"));
+    let salt = test_case.consume_bytes("Salt");
+    let iv = test_case.consume_bytes("IV");
+    let plaintext = test_case.consume_bytes("Plaintext");
+    let expected = test_case.consume_bytes("Encrypted");
+
+    let salt_and_iv = [&salt[..], &iv[..]];
+    let rng = test::rand::FixedSliceSequenceRandom::new(&salt_and_iv);
+    let encrypted = pkcs8::encrypt(algorithm, &plaintext, &password, iterations, &rng).unwrap();
+    assert_eq!(encrypted, expected);
+
+    let decrypted = pkcs8::decrypt(&encrypted, &password, iterations).unwrap();
+    assert_eq!(decrypted, plaintext);
+}
+
+fn decrypt_test_case(test_case: &mut test::TestCase) {
+    let encrypted = test_case.consume_bytes("Encrypted");
+    let password = test_case.consume_bytes("Password");
+    let max_iterations = iterations(test_case.consume_usize("MaxIterations"));
+    let expected_result = test_case.consume_string("Result");
+
+    let result = pkcs8::decrypt(&encrypted, &password, max_iterations);
+    match expected_result.as_str() {
+        "OK" => assert_eq!(result.unwrap(), test_case.consume_bytes("Plaintext")),
+        "FAIL" => assert!(result.is_err()),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(feature = "sig-ed25519")]
+#[test]
+fn pkcs8_encrypt_round_trip_test() {
+    let rng = rand::SystemRandom::new();
+    let iteration_count = iterations(1000);
+
+    for algorithm in &[
+        &pkcs8::PBES2_HMAC_SHA256_AES_256_CBC,
+        &pkcs8::PBES2_HMAC_SHA256_AES_256_GCM,
+    ] {
+        let document = signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let key_pair = signature::Ed25519KeyPair::from_pkcs8(document.as_ref()).unwrap();
+
+        let encrypted = pkcs8::encrypt(
+            algorithm,
+            document.as_ref(),
+            b"password",
+            iteration_count,
+            &rng,
+        )
+        .unwrap();
+        let decrypted = pkcs8::decrypt(&encrypted, b"password", iteration_count).unwrap();
+        let decrypted_key_pair = signature::Ed25519KeyPair::from_pkcs8(&decrypted).unwrap();
+        assert_eq!(
+            decrypted_key_pair.public_key().as_ref(),
+            key_pair.public_key().as_ref()
+        );
+
+        // The salt and IV or nonce are random.
+        let encrypted_again = pkcs8::encrypt(
+            algorithm,
+            document.as_ref(),
+            b"password",
+            iteration_count,
+            &rng,
+        )
+        .unwrap();
+        assert_ne!(encrypted, encrypted_again);
+
+        assert!(pkcs8::decrypt(&encrypted, b"passwore", iteration_count).is_err());
+        assert!(pkcs8::decrypt(&encrypted, b"password", iterations(999)).is_err());
+    }
+}
+
+#[test]
+fn pkcs8_encrypt_too_long_test() {
+    let rng = rand::SystemRandom::new();
+    let too_long = vec![0u8; pkcs8::MAX_PKCS8_LEN + 1];
+    let result = pkcs8::encrypt(
+        &pkcs8::PBES2_HMAC_SHA256_AES_256_GCM,
+        &too_long,
+        b"password",
+        iterations(1),
+        &rng,
+    );
+    assert!(result.is_err());
+}
+
+fn iterations(value: usize) -> NonZeroU32 {
+    NonZeroU32::new(value as u32).unwrap()
+}
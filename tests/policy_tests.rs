@@ -0,0 +1,149 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "use_heap")]
+
+use ring::{aead, agreement, digest, hkdf, policy, rand, signature};
+
+// The policy is process-wide, so this is the only test in this file.
+#[test]
+fn policy_fips_approved() {
+    let rng = rand::SystemRandom::new();
+
+    assert_eq!(policy::get(), policy::Policy::All);
+    assert!(policy::set(policy::Policy::All).is_ok());
+
+    #[cfg(feature = "sig-ed25519")]
+    let (ed25519_pkcs8, ed25519_sig, ed25519_public_key) = {
+        let ed25519_pkcs8 = signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let ed25519 = signature::Ed25519KeyPair::from_pkcs8(ed25519_pkcs8.as_ref()).unwrap();
+        let ed25519_sig = ed25519.sign(b"message");
+        let ed25519_public_key = signature::UnparsedPublicKey::new(
+            &signature::ED25519,
+            signature::KeyPair::public_key(&ed25519).as_ref().to_vec(),
+        );
+        assert!(ed25519_public_key
+            .verify(b"message", ed25519_sig.as_ref())
+            .is_ok());
+        (ed25519_pkcs8, ed25519_sig, ed25519_public_key)
+    };
+    #[cfg(feature = "aead-chacha")]
+    let chacha_key = aead::SealingKey::new(&aead::CHACHA20_POLY1305, &[0; 32]).unwrap();
+
+    policy::set(policy::Policy::FipsApproved).unwrap();
+    assert_eq!(policy::get(), policy::Policy::FipsApproved);
+    assert!(policy::set(policy::Policy::All).is_err());
+    assert!(policy::set(policy::Policy::FipsApproved).is_ok());
+    assert_eq!(policy::get(), policy::Policy::FipsApproved);
+
+    // AEAD keys.
+    let aead_algs: &[(&aead::Algorithm, bool)] = &[
+        (&aead::AES_128_GCM, true),
+        (&aead::AES_256_GCM, true),
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        (&aead::AES_128_GCM_SIV, false),
+        #[cfg(feature = "aead-aes-gcm-siv")]
+        (&aead::AES_256_GCM_SIV, false),
+        (&aead::AES_128_SIV_CMAC, false),
+        (&aead::AES_256_SIV_CMAC, false),
+        (&aead::AEGIS_128L, false),
+        (&aead::AEGIS_256, false),
+        (&aead::ASCON_128, false),
+        #[cfg(feature = "aead-chacha")]
+        (&aead::CHACHA20_POLY1305, false),
+        #[cfg(feature = "aead-chacha")]
+        (&aead::XCHACHA20_POLY1305, false),
+    ];
+    let prk = hkdf::Prk::new_less_safe(&digest::SHA256, &[0; digest::SHA256_OUTPUT_LEN]);
+    for (alg, approved) in aead_algs {
+        let key_bytes = vec![0; alg.key_len()];
+        assert_eq!(aead::SealingKey::new(alg, &key_bytes).is_ok(), *approved);
+        assert_eq!(aead::OpeningKey::new(alg, &key_bytes).is_ok(), *approved);
+        assert_eq!(
+            aead::SealingKey::derive(alg, prk.expand(b"key")).is_ok(),
+            *approved
+        );
+        assert_eq!(
+            aead::OpeningKey::derive(alg, prk.expand(b"key")).is_ok(),
+            *approved
+        );
+    }
+
+    // Keys constructed earlier still work.
+    #[cfg(feature = "aead-chacha")]
+    {
+        let mut in_out = [0u8; aead::MAX_TAG_LEN];
+        let nonce = aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]);
+        assert!(aead::seal_in_place(
+            &chacha_key,
+            nonce,
+            aead::Aad::empty(),
+            &mut in_out,
+            aead::MAX_TAG_LEN
+        )
+        .is_ok());
+    }
+
+    // Signatures.
+    #[cfg(feature = "sig-ed25519")]
+    {
+        let err = signature::Ed25519KeyPair::from_pkcs8(ed25519_pkcs8.as_ref()).unwrap_err();
+        assert_eq!(err.description_(), "AlgorithmNotAllowed");
+        assert!(signature::Ed25519KeyPair::generate_pkcs8(&rng).is_err());
+        assert!(ed25519_public_key
+            .verify(b"message", ed25519_sig.as_ref())
+            .is_err());
+        assert!(ed25519_public_key.parse().is_err());
+    }
+
+    let ecdsa_pkcs8 =
+        signature::EcdsaKeyPair::generate_pkcs8(&signature::ECDSA_P256_SHA256_FIXED_SIGNING, &rng)
+            .unwrap();
+    let ecdsa = signature::EcdsaKeyPair::from_pkcs8(
+        &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+        ecdsa_pkcs8.as_ref(),
+    )
+    .unwrap();
+    let ecdsa_sig = ecdsa.sign(&rng, b"message").unwrap();
+    let ecdsa_public_key = signature::UnparsedPublicKey::new(
+        &signature::ECDSA_P256_SHA256_FIXED,
+        signature::KeyPair::public_key(&ecdsa).as_ref(),
+    );
+    assert!(ecdsa_public_key
+        .verify(b"message", ecdsa_sig.as_ref())
+        .is_ok());
+
+    // Key agreement.
+    assert!(agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng).is_ok());
+    assert!(agreement::EphemeralPrivateKey::generate(&agreement::X25519, &rng).is_err());
+    assert!(agreement::ReusablePrivateKey::generate(&agreement::X25519, &rng).is_err());
+}
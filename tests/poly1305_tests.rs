@@ -0,0 +1,70 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "aead-chacha")]
+
+use ring::{poly1305, test, test_file};
+
+#[test]
+fn poly1305_tests() {
+    test::run(
+        test_file!("../src/aead/poly1305_test.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+            let key_bytes = test_case.consume_bytes("Key");
+            let input = test_case.consume_bytes("Input");
+            let expected_mac = test_case.consume_bytes("MAC");
+
+            let mut key = [0; poly1305::KEY_LEN];
+            key.copy_from_slice(&key_bytes);
+
+            let tag = poly1305::sign(poly1305::Key::new(key), &input);
+            assert_eq!(tag.as_ref(), &expected_mac[..]);
+            assert!(poly1305::verify(poly1305::Key::new(key), &input, &expected_mac).is_ok());
+            assert!(poly1305::verify(
+                poly1305::Key::new(key),
+                &input,
+                &expected_mac[..(poly1305::TAG_LEN - 1)]
+            )
+            .is_err());
+
+            // Tamper with the tag and check that verification fails. (Tampering
+            // with the input doesn't necessarily change the tag; e.g. when
+            // r is zero, the tag is s.)
+            let mut tampered = expected_mac.clone();
+            tampered[0] ^= 1;
+            assert!(poly1305::verify(poly1305::Key::new(key), &input, &tampered).is_err());
+
+            Ok(())
+        },
+    );
+}
@@ -43,6 +43,7 @@ fn quic_aes_256() {
     test_quic(&quic::AES_256, test_file!("quic_aes_256_tests.txt"));
 }
 
+#[cfg(feature = "aead-chacha")]
 #[test]
 fn quic_chacha20() {
     test_quic(&quic::CHACHA20, test_file!("quic_chacha20_tests.txt"));
@@ -86,3 +87,60 @@ fn test_sample_len(alg: &'static quic::Algorithm) {
     // Sample is empty.
     assert!(key.new_mask(&[]).is_err());
 }
+
+// RFC 9001 Appendix A.2 (a long header) and Appendix A.5 (a short header).
+#[test]
+fn quic_header_protection_test() {
+    let cases: &[(&'static quic::Algorithm, &str, &str, &str, &str)] = &[
+        (
+            &quic::AES_128,
+            "9f50449e04a0e810283a1e9933adedd2",
+            "d1b1c98dd7689fb8ec11d242b123dc9b",
+            "c300000002",
+            "c07b9aec34",
+        ),
+        #[cfg(feature = "aead-chacha")]
+        (
+            &quic::CHACHA20,
+            "25a282b9e82f06f21f488917a4fc8f1b73573685608597d0efcb076b0ab7a7a4",
+            "5e5cd55c41f69080575d7999c25a5bfb",
+            "4200bff4",
+            "4cfe4189",
+        ),
+    ];
+    for &(alg, key, sample, unprotected, protected) in cases {
+        let key = quic::HeaderProtectionKey::new(alg, &test::from_hex(key).unwrap()).unwrap();
+        let sample = test::from_hex(sample).unwrap();
+        let unprotected = test::from_hex(unprotected).unwrap();
+        let protected = test::from_hex(protected).unwrap();
+
+        let mut header = unprotected.clone();
+        {
+            let (first, packet_number) = header.split_first_mut().unwrap();
+            key.encrypt_in_place(&sample, first, packet_number).unwrap();
+        }
+        assert_eq!(header, protected);
+
+        {
+            let (first, packet_number) = header.split_first_mut().unwrap();
+            key.decrypt_in_place(&sample, first, packet_number).unwrap();
+        }
+        assert_eq!(header, unprotected);
+
+        // Bytes after the packet number are left alone.
+        let mut header = unprotected.clone();
+        header.extend_from_slice(&[0xff; 4]);
+        {
+            let (first, packet_number) = header.split_first_mut().unwrap();
+            key.encrypt_in_place(&sample, first, packet_number).unwrap();
+        }
+        assert_eq!(&header[..protected.len()], &protected[..]);
+        assert_eq!(&header[protected.len()..], &[0xff; 4]);
+
+        // The packet number is too short for its encoded length.
+        let mut header = unprotected.clone();
+        let _ = header.pop();
+        let (first, packet_number) = header.split_first_mut().unwrap();
+        assert!(key.encrypt_in_place(&sample, first, packet_number).is_err());
+    }
+}
@@ -22,3 +22,114 @@ fn rand_test_system_random_lengths() {
         }
     }
 }
+
+#[test]
+fn rand_test_external_random() {
+    use ring::{agreement, error};
+
+    struct Failing;
+
+    impl rand::RandomSource for Failing {
+        fn fill(&self, _dest: &mut [u8]) -> Result<(), error::Unspecified> {
+            Err(error::Unspecified)
+        }
+    }
+
+    struct Repeating(u8);
+
+    impl rand::RandomSource for Repeating {
+        fn fill(&self, dest: &mut [u8]) -> Result<(), error::Unspecified> {
+            for b in dest.iter_mut() {
+                *b = self.0;
+            }
+            Ok(())
+        }
+    }
+
+    let mut buf = [0u8; 3];
+    assert!(rand::ExternalRandom::new(&Failing).fill(&mut buf).is_err());
+
+    // Key generation is reproducible with a deterministic source.
+    let source = Repeating(1);
+    let rng = rand::ExternalRandom::new(&source);
+    let public_key = || {
+        let private_key =
+            agreement::EphemeralPrivateKey::generate(&agreement::X25519, &rng).unwrap();
+        private_key.compute_public_key().unwrap()
+    };
+    assert_eq!(public_key().as_ref(), public_key().as_ref());
+}
+
+#[test]
+fn rand_test_fixed_slice_sequence_random() {
+    let rng = rand::test::FixedSliceSequenceRandom::new(&[&[1, 2], &[3]]);
+    let mut first = [0u8; 2];
+    let mut second = [0u8; 1];
+    assert!(rng.fill(&mut first).is_ok());
+    assert!(rng.fill(&mut second).is_ok());
+    assert_eq!(first, [1, 2]);
+    assert_eq!(second, [3]);
+}
+
+#[test]
+#[should_panic]
+fn rand_test_fixed_slice_sequence_random_unused() {
+    let _ = rand::test::FixedSliceSequenceRandom::new(&[&[1, 2]]);
+}
+
+#[test]
+fn rand_test_try_fill() {
+    // The entropy pool is initialized long before the tests run.
+    let mut buf = [0u8; 96];
+    assert_eq!(rand::SystemRandom::new().try_fill(&mut buf), Ok(()));
+    assert!(buf.iter().any(|x| *x != 0));
+
+    let rng = rand::test::FixedByteRandom { byte: 7 };
+    assert_eq!(rng.try_fill(&mut buf), Ok(()));
+    assert_eq!(&buf[..], &[7u8; 96][..]);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn rand_test_fill_async() {
+    use core::{
+        future::Future,
+        task::{Context, Poll},
+    };
+    use ring::signature::{self, KeyPair};
+    use std::{
+        boxed::Box,
+        sync::Arc,
+        task::{Wake, Waker},
+    };
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    let rng = rand::SystemRandom::new();
+    let mut buf = [0u8; 96];
+    assert!(block_on(rand::fill_async(&rng, &mut buf)).is_ok());
+    assert!(buf.iter().any(|x| *x != 0));
+
+    let pkcs8 = block_on(signature::Ed25519KeyPair::generate_pkcs8_async(&rng)).unwrap();
+    let key_pair = signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    assert_eq!(key_pair.public_key().as_ref().len(), 32);
+
+    let alg = &signature::ECDSA_P256_SHA256_FIXED_SIGNING;
+    let pkcs8 = block_on(signature::EcdsaKeyPair::generate_pkcs8_async(alg, &rng)).unwrap();
+    assert!(signature::EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref()).is_ok());
+}
@@ -31,16 +31,16 @@
     warnings
 )]
 
-#[cfg(feature = "use_heap")]
+#[cfg(feature = "rsa")]
 use ring::{
-    error,
+    digest, error,
     io::der,
     rand,
     signature::{self, KeyPair},
     test, test_file,
 };
 
-#[cfg(feature = "use_heap")]
+#[cfg(feature = "rsa")]
 #[test]
 fn rsa_from_pkcs8_test() {
     test::run(
@@ -65,7 +65,7 @@ fn rsa_from_pkcs8_test() {
     );
 }
 
-#[cfg(feature = "use_heap")]
+#[cfg(feature = "rsa")]
 #[test]
 fn test_signature_rsa_pkcs1_sign() {
     let rng = rand::SystemRandom::new();
@@ -102,12 +102,22 @@ fn test_signature_rsa_pkcs1_sign() {
                 .sign(alg, &rng, &msg, actual.as_mut_slice())
                 .unwrap();
             assert_eq!(actual.as_slice() == &expected[..], result == "Pass");
+
+            #[cfg(feature = "rsa_parallel")]
+            {
+                let mut actual_parallel = vec![0u8; key_pair.public_modulus_len()];
+                key_pair
+                    .sign_parallel(alg, &rng, &msg, actual_parallel.as_mut_slice())
+                    .unwrap();
+                assert_eq!(actual_parallel, actual);
+            }
+
             Ok(())
         },
     );
 }
 
-#[cfg(feature = "use_heap")]
+#[cfg(feature = "rsa")]
 #[test]
 fn test_signature_rsa_pss_sign() {
     test::run(
@@ -144,7 +154,7 @@ fn test_signature_rsa_pss_sign() {
     );
 }
 
-#[cfg(feature = "use_heap")]
+#[cfg(feature = "rsa")]
 #[test]
 fn test_signature_rsa_pkcs1_verify() {
     test::run(
@@ -187,6 +197,11 @@ fn test_signature_rsa_pkcs1_verify() {
                 signature::UnparsedPublicKey::new(alg, &public_key).verify(&msg, &sig);
             assert_eq!(actual_result.is_ok(), is_valid);
 
+            // An invalid public key is rejected by `ParsedPublicKey::new()`.
+            let actual_result = signature::ParsedPublicKey::new(alg, &public_key)
+                .and_then(|public_key| public_key.verify(&msg, &sig));
+            assert_eq!(actual_result.is_ok(), is_valid);
+
             // Deprecated API.
             #[allow(deprecated)]
             let actual_result = signature::verify(
@@ -202,7 +217,7 @@ fn test_signature_rsa_pkcs1_verify() {
     );
 }
 
-#[cfg(feature = "use_heap")]
+#[cfg(feature = "rsa")]
 #[test]
 fn test_signature_rsa_pss_verify() {
     test::run(
@@ -258,9 +273,76 @@ fn test_signature_rsa_pss_verify() {
     );
 }
 
+// Tests signing and verification with non-default PSS parameters.
+#[cfg(feature = "rsa")]
+#[test]
+fn test_signature_rsa_pss_params() {
+    const PRIVATE_KEY: &[u8] = include_bytes!("rsa_test_private_key_2048.p8");
+    let key_pair = signature::RsaKeyPair::from_pkcs8(PRIVATE_KEY).unwrap();
+
+    fn digest_alg(name: &str) -> &'static digest::Algorithm {
+        match name {
+            "SHA1" => &digest::SHA1,
+            "SHA256" => &digest::SHA256,
+            "SHA384" => &digest::SHA384,
+            "SHA512" => &digest::SHA512,
+            "SHA3-256" => &digest::SHA3_256,
+            "SHA3-512" => &digest::SHA3_512,
+            _ => panic!("Unsupported digest: {}", name),
+        }
+    }
+
+    test::run(
+        test_file!("rsa_pss_params_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+
+            let digest_name = test_case.consume_string("Digest");
+            let mgf1_digest_name = test_case.consume_string("MGF1Digest");
+            let salt_len = test_case.consume_usize("SaltLen");
+            let msg = test_case.consume_bytes("Msg");
+            let salt = test_case.consume_bytes("Salt");
+            let sig = test_case.consume_bytes("Sig");
+            let is_valid = test_case.consume_string("Result") == "P";
+
+            let padding: &'static signature::RsaPssPadding = Box::leak(Box::new(
+                signature::RsaPssPadding::new(digest_alg(&digest_name))
+                    .mgf1_digest_alg(digest_alg(&mgf1_digest_name))
+                    .salt_len(salt_len),
+            ));
+            let params: &'static signature::RsaParameters =
+                Box::leak(Box::new(signature::RsaParameters::pss(padding)));
+
+            let actual_result =
+                signature::UnparsedPublicKey::new(params, key_pair.public_key()).verify(&msg, &sig);
+            assert_eq!(actual_result.is_ok(), is_valid);
+
+            if is_valid {
+                let rng = test::rand::FixedSliceRandom { bytes: &salt };
+                let mut actual = vec![0u8; key_pair.public_modulus_len()];
+                key_pair
+                    .sign(padding, &rng, &msg, actual.as_mut_slice())
+                    .unwrap();
+                assert_eq!(actual, sig);
+            }
+
+            Ok(())
+        },
+    );
+
+    // Salts longer than the digest are rejected.
+    static RSA_PSS_SHA256_SALT_33: signature::RsaPssPadding =
+        signature::RsaPssPadding::new(&digest::SHA256).salt_len(33);
+    let rng = rand::SystemRandom::new();
+    let mut sig = vec![0u8; key_pair.public_modulus_len()];
+    assert!(key_pair
+        .sign(&RSA_PSS_SHA256_SALT_33, &rng, b"hello", &mut sig)
+        .is_err());
+}
+
 // Test for `primitive::verify()`. Read public key parts from a file
 // and use them to verify a signature.
-#[cfg(feature = "use_heap")]
+#[cfg(feature = "rsa")]
 #[test]
 fn test_signature_rsa_primitive_verification() {
     test::run(
@@ -280,7 +362,7 @@ fn test_signature_rsa_primitive_verification() {
     )
 }
 
-#[cfg(feature = "use_heap")]
+#[cfg(feature = "rsa")]
 #[test]
 fn rsa_test_public_key_coverage() {
     const PRIVATE_KEY: &[u8] = include_bytes!("rsa_test_private_key_2048.p8");
@@ -311,3 +393,63 @@ fn rsa_test_public_key_coverage() {
         format!("{:?}", key_pair)
     );
 }
+
+#[cfg(feature = "rsa")]
+#[test]
+fn rsa_key_pair_clone_test() {
+    const PRIVATE_KEY: &[u8] = include_bytes!("rsa_test_private_key_2048.p8");
+    const MESSAGE: &[u8] = b"hello, world";
+
+    let key_pair = signature::RsaKeyPair::from_pkcs8(PRIVATE_KEY).unwrap();
+    let rng = rand::SystemRandom::new();
+
+    // PKCS#1 1.5 signatures are deterministic, so a clone must produce the
+    // same signature as the original, even on another thread.
+    let cloned_key_pair = key_pair.clone();
+    let cloned_signature = std::thread::spawn(move || {
+        let rng = rand::SystemRandom::new();
+        let mut signature = vec![0; cloned_key_pair.public_modulus_len()];
+        cloned_key_pair
+            .sign(&signature::RSA_PKCS1_SHA256, &rng, MESSAGE, &mut signature)
+            .unwrap();
+        signature
+    })
+    .join()
+    .unwrap();
+
+    let mut signature = vec![0; key_pair.public_modulus_len()];
+    key_pair
+        .sign(&signature::RSA_PKCS1_SHA256, &rng, MESSAGE, &mut signature)
+        .unwrap();
+    assert_eq!(signature, cloned_signature);
+}
+
+#[cfg(feature = "rsa")]
+#[test]
+fn rsa_generate_pkcs8_test() {
+    const MESSAGE: &[u8] = b"hello, world";
+
+    let rng = rand::SystemRandom::new();
+
+    for &bits in &[0, 1024, 2047, 2049, 8192] {
+        assert!(signature::RsaKeyPair::generate_pkcs8(bits, &rng).is_err());
+    }
+
+    let pkcs8 = signature::RsaKeyPair::generate_pkcs8(2048, &rng).unwrap();
+    let key_pair = signature::RsaKeyPair::from_pkcs8(&pkcs8).unwrap();
+    assert_eq!(key_pair.public_modulus_len(), 2048 / 8);
+
+    let mut signature = vec![0; key_pair.public_modulus_len()];
+    key_pair
+        .sign(&signature::RSA_PSS_SHA256, &rng, MESSAGE, &mut signature)
+        .unwrap();
+    let public_key = signature::UnparsedPublicKey::new(
+        &signature::RSA_PSS_2048_8192_SHA256,
+        key_pair.public_key().as_ref(),
+    );
+    assert!(public_key.verify(MESSAGE, &signature).is_ok());
+
+    // Each call generates a different key.
+    let other = signature::RsaKeyPair::generate_pkcs8(2048, &rng).unwrap();
+    assert_ne!(&pkcs8[..], &other[..]);
+}
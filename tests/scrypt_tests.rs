@@ -0,0 +1,82 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{error, scrypt, test, test_file};
+use std::num::NonZeroU32;
+
+#[test]
+fn scrypt_tests() {
+    test::run(test_file!("scrypt_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let secret = test_case.consume_bytes("P");
+        let salt = test_case.consume_bytes("S");
+        let log_n = test_case.consume_usize("logN") as u8;
+        let r = NonZeroU32::new(test_case.consume_usize("r") as u32).unwrap();
+        let p = NonZeroU32::new(test_case.consume_usize("p") as u32).unwrap();
+        let dk = test_case.consume_bytes("DK");
+        let params = scrypt::Params::new(log_n, r, p).unwrap();
+
+        let mut out = vec![0u8; dk.len()];
+        scrypt::derive(&params, &salt, &secret, &mut out);
+        assert_eq!(out, dk);
+
+        assert_eq!(scrypt::verify(&params, &salt, &secret, &dk), Ok(()));
+
+        let mut wrong = dk.clone();
+        wrong[0] ^= 1;
+        assert_eq!(
+            scrypt::verify(&params, &salt, &secret, &wrong),
+            Err(error::Unspecified)
+        );
+        assert_eq!(
+            scrypt::verify(&params, &salt, &secret, &[]),
+            Err(error::Unspecified)
+        );
+
+        Ok(())
+    });
+}
+
+#[test]
+fn scrypt_params() {
+    let one = NonZeroU32::new(1).unwrap();
+    let eight = NonZeroU32::new(8).unwrap();
+    assert!(scrypt::Params::new(0, eight, one).is_err());
+    assert!(scrypt::Params::new(1, one, one).is_ok());
+    assert!(scrypt::Params::new(15, one, one).is_ok());
+    // N must be less than 2**(128 * r / 8).
+    assert!(scrypt::Params::new(16, one, one).is_err());
+    // r * p must be less than 2**30.
+    assert!(scrypt::Params::new(1, eight, NonZeroU32::new(1 << 27).unwrap()).is_err());
+    assert!(scrypt::Params::new(1, eight, NonZeroU32::new((1 << 27) - 1).unwrap()).is_ok());
+}
@@ -0,0 +1,69 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{error, rand, self_test, test};
+
+#[test]
+fn self_test_run() {
+    let rng = rand::SystemRandom::new();
+    let report = self_test::run(&rng);
+    assert!(report.passed());
+    let expected_len = 5 + if cfg!(feature = "aead-aes-gcm-siv") {
+        2
+    } else {
+        0
+    };
+    assert_eq!(report.results().len(), expected_len);
+    for &(kind, result) in report.results() {
+        assert_eq!(result, Ok(()), "{:?}", kind);
+        assert_eq!(report.result(kind), Ok(()));
+    }
+}
+
+#[test]
+fn self_test_failure_is_reported_per_test() {
+    // An all-zero private key is invalid, so key generation fails.
+    let rng = test::rand::FixedByteRandom { byte: 0 };
+    let report = self_test::run(&rng);
+    assert!(!report.passed());
+    assert_eq!(
+        report.result(self_test::Test::EcdsaP256PairwiseConsistency),
+        Err(error::Unspecified)
+    );
+    for &(kind, result) in report.results() {
+        if kind != self_test::Test::EcdsaP256PairwiseConsistency {
+            assert_eq!(result, Ok(()), "{:?}", kind);
+        }
+    }
+    assert_eq!(self_test::Test::Sha256.run(&rng), Ok(()));
+}
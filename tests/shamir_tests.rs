@@ -0,0 +1,151 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "use_heap")]
+
+use ring::{rand, shamir, test, test_file};
+
+#[test]
+fn shamir_test() {
+    test::run(test_file!("shamir_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let secret = test_case.consume_bytes("Secret");
+        let threshold = test_case.consume_usize("Threshold");
+        let share_count = test_case.consume_usize("ShareCount");
+        let random = test_case.consume_bytes("Random");
+        let expected = (1..=share_count)
+            .map(|i| test_case.consume_bytes(&format!("Share{}", i)))
+            .collect::<Vec<_>>();
+
+        let rng = test::rand::FixedSliceRandom { bytes: &random };
+        let shares = shamir::split(&secret, threshold as u8, share_count as u8, &rng).unwrap();
+        assert_eq!(shares.len(), share_count);
+        for (i, (share, expected)) in shares.iter().zip(&expected).enumerate() {
+            assert_eq!(share.as_ref(), &expected[..]);
+            assert_eq!(usize::from(share.threshold()), threshold);
+            assert_eq!(usize::from(share.index()), i + 1);
+        }
+
+        // Every subset of `threshold` or more shares, in any order,
+        // reconstructs the secret; smaller subsets are rejected.
+        for subset in 1u32..(1 << share_count) {
+            let mut shares = expected
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| subset & (1 << i) != 0)
+                .map(|(_, bytes)| shamir::Share::from_bytes(bytes).unwrap())
+                .collect::<Vec<_>>();
+            shares.reverse();
+            let result = shamir::combine(&shares);
+            if shares.len() >= threshold {
+                assert_eq!(result.unwrap(), secret);
+            } else {
+                assert!(result.is_err());
+            }
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn shamir_round_trip_test() {
+    let rng = rand::SystemRandom::new();
+    let secret = [0x42u8; 32];
+    let shares = shamir::split(&secret, 3, 5, &rng).unwrap();
+    assert_eq!(shamir::combine(&shares[1..4]).unwrap(), &secret[..]);
+    assert_eq!(shamir::combine(&shares).unwrap(), &secret[..]);
+    assert!(shamir::combine(&shares[..2]).is_err());
+    assert!(shamir::combine(&[]).is_err());
+
+    // Re-splitting the same secret uses new coefficients.
+    let other_shares = shamir::split(&secret, 3, 5, &rng).unwrap();
+    assert_ne!(shares[0].as_ref(), other_shares[0].as_ref());
+    let mixed = [
+        shares[0].clone(),
+        shares[1].clone(),
+        other_shares[2].clone(),
+    ];
+    assert!(shamir::combine(&mixed).is_err());
+}
+
+#[test]
+fn shamir_tampered_share_test() {
+    let rng = rand::SystemRandom::new();
+    let secret = [7u8; 16];
+    let shares = shamir::split(&secret, 2, 3, &rng).unwrap();
+
+    for i in 2..shares[1].as_ref().len() {
+        let mut bytes = shares[1].as_ref().to_vec();
+        bytes[i] ^= 1;
+        let tampered = shamir::Share::from_bytes(&bytes).unwrap();
+
+        // A tampered share is detected whether it is used for the
+        // reconstruction or is only an extra share.
+        assert!(shamir::combine(&[shares[0].clone(), tampered.clone()]).is_err());
+        assert!(
+            shamir::combine(&[shares[0].clone(), shares[2].clone(), tampered.clone()]).is_err()
+        );
+    }
+
+    // Duplicate indexes.
+    assert!(shamir::combine(&[shares[0].clone(), shares[0].clone()]).is_err());
+
+    // Inconsistent thresholds.
+    let mut bytes = shares[1].as_ref().to_vec();
+    bytes[0] = 3;
+    let share = shamir::Share::from_bytes(&bytes).unwrap();
+    assert!(shamir::combine(&[shares[0].clone(), share, shares[2].clone()]).is_err());
+
+    // Inconsistent lengths.
+    let longer = shamir::split(&[7u8; 17], 2, 3, &rng).unwrap();
+    assert!(shamir::combine(&[shares[0].clone(), longer[1].clone()]).is_err());
+}
+
+#[test]
+fn shamir_bad_parameters_test() {
+    let rng = rand::SystemRandom::new();
+    let secret = [1u8; 16];
+    assert!(shamir::split(&secret, 2, 2, &rng).is_ok());
+    assert!(shamir::split(&secret, 255, 255, &rng).is_ok());
+    assert!(shamir::split(&secret, 1, 3, &rng).is_err());
+    assert!(shamir::split(&secret, 4, 3, &rng).is_err());
+    assert!(shamir::split(&secret[..15], 2, 3, &rng).is_err());
+
+    let share = shamir::split(&secret, 2, 3, &rng).unwrap().remove(0);
+    let bytes = share.as_ref();
+    assert!(shamir::Share::from_bytes(bytes).is_ok());
+    assert!(shamir::Share::from_bytes(&bytes[..(bytes.len() - 1)]).is_err());
+    let mut zero_index = bytes.to_vec();
+    zero_index[1] = 0;
+    assert!(shamir::Share::from_bytes(&zero_index).is_err());
+}
@@ -1,9 +1,386 @@
 use ring::{signature, test};
 
+#[cfg(feature = "rsa")]
+use ring::{
+    rand,
+    signature::{SignatureScheme, Signer, Verifier},
+};
+
 #[test]
 fn signature_impl_test() {
     test::compile_time_assert_clone::<signature::Signature>();
     test::compile_time_assert_copy::<signature::Signature>();
     test::compile_time_assert_send::<signature::Signature>();
     test::compile_time_assert_sync::<signature::Signature>();
+
+    test::compile_time_assert_send::<signature::ParsedPublicKey>();
+    test::compile_time_assert_sync::<signature::ParsedPublicKey>();
+}
+
+#[cfg(feature = "rsa")]
+#[test]
+fn signature_signer_test() {
+    const SCHEMES: &[(SignatureScheme, &dyn signature::VerificationAlgorithm)] = &[
+        #[cfg(feature = "sig-ed25519")]
+        (SignatureScheme::ED25519, &signature::ED25519),
+        (SignatureScheme::ED448, &signature::ED448),
+        (
+            SignatureScheme::ECDSA_P256_SHA256_ASN1,
+            &signature::ECDSA_P256_SHA256_ASN1,
+        ),
+        (
+            SignatureScheme::ECDSA_P256_SHA256_FIXED,
+            &signature::ECDSA_P256_SHA256_FIXED,
+        ),
+        (
+            SignatureScheme::ECDSA_P384_SHA384_ASN1,
+            &signature::ECDSA_P384_SHA384_ASN1,
+        ),
+        (
+            SignatureScheme::ECDSA_P384_SHA384_FIXED,
+            &signature::ECDSA_P384_SHA384_FIXED,
+        ),
+        (
+            SignatureScheme::ECDSA_P521_SHA512_ASN1,
+            &signature::ECDSA_P521_SHA512_ASN1,
+        ),
+        (
+            SignatureScheme::ECDSA_P521_SHA512_FIXED,
+            &signature::ECDSA_P521_SHA512_FIXED,
+        ),
+        (
+            SignatureScheme::ECDSA_K256_SHA256_FIXED,
+            &signature::ECDSA_K256_SHA256_FIXED,
+        ),
+        (
+            SignatureScheme::RSA_PKCS1_SHA256,
+            &signature::RSA_PKCS1_2048_8192_SHA256,
+        ),
+        (
+            SignatureScheme::RSA_PKCS1_SHA384,
+            &signature::RSA_PKCS1_2048_8192_SHA384,
+        ),
+        (
+            SignatureScheme::RSA_PKCS1_SHA512,
+            &signature::RSA_PKCS1_2048_8192_SHA512,
+        ),
+        (
+            SignatureScheme::RSA_PSS_SHA256,
+            &signature::RSA_PSS_2048_8192_SHA256,
+        ),
+        (
+            SignatureScheme::RSA_PSS_SHA384,
+            &signature::RSA_PSS_2048_8192_SHA384,
+        ),
+        (
+            SignatureScheme::RSA_PSS_SHA512,
+            &signature::RSA_PSS_2048_8192_SHA512,
+        ),
+    ];
+
+    fn check(signer: &dyn Signer, supported: &[SignatureScheme]) {
+        const MESSAGE: &[u8] = b"hello, world";
+        let rng = rand::SystemRandom::new();
+        for &(scheme, verification_alg) in SCHEMES {
+            let expected = supported.contains(&scheme);
+            assert_eq!(signer.supports(scheme), expected, "{:?}", scheme);
+            let result = signer.sign(scheme, &rng, MESSAGE);
+            if !expected {
+                assert!(result.is_err(), "{:?}", scheme);
+                continue;
+            }
+            let sig = result.unwrap();
+            let public_key =
+                signature::UnparsedPublicKey::new(verification_alg, signer.public_key_bytes());
+            let verifiers: [Box<dyn Verifier + '_>; 2] =
+                [Box::new(public_key), Box::new(public_key.parse().unwrap())];
+            for verifier in verifiers.iter() {
+                assert_eq!(
+                    format!("{:?}", verifier.algorithm()),
+                    format!("{:?}", verification_alg)
+                );
+                assert!(verifier.verify(MESSAGE, &sig).is_ok(), "{:?}", scheme);
+                assert!(verifier.verify(b"goodbye", &sig).is_err(), "{:?}", scheme);
+            }
+        }
+    }
+
+    #[cfg(feature = "sig-ed25519")]
+    {
+        let key_pair =
+            signature::Ed25519KeyPair::from_pkcs8(include_bytes!("ed25519_test_private_key.p8"))
+                .unwrap();
+        check(&key_pair, &[SignatureScheme::ED25519]);
+    }
+
+    let rng = test::rand::FixedByteRandom { byte: 0x42 };
+    let pkcs8 = signature::Ed448KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair = signature::Ed448KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    check(&key_pair, &[SignatureScheme::ED448]);
+
+    for &(signing_alg, scheme) in &[
+        (
+            &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+            SignatureScheme::ECDSA_P256_SHA256_ASN1,
+        ),
+        (
+            &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+            SignatureScheme::ECDSA_P256_SHA256_FIXED,
+        ),
+    ] {
+        let key_pair = signature::EcdsaKeyPair::from_pkcs8(
+            signing_alg,
+            include_bytes!("ecdsa_test_private_key_p256.p8"),
+        )
+        .unwrap();
+        check(&key_pair, &[scheme]);
+    }
+
+    let pkcs8 =
+        signature::EcdsaKeyPair::generate_pkcs8(&signature::ECDSA_K256_SHA256_FIXED_SIGNING, &rng)
+            .unwrap();
+    let key_pair = signature::EcdsaKeyPair::from_pkcs8(
+        &signature::ECDSA_K256_SHA256_FIXED_SIGNING,
+        pkcs8.as_ref(),
+    )
+    .unwrap();
+    check(&key_pair, &[SignatureScheme::ECDSA_K256_SHA256_FIXED]);
+
+    let key_pair =
+        signature::RsaKeyPair::from_pkcs8(include_bytes!("rsa_test_private_key_2048.p8")).unwrap();
+    check(
+        &key_pair,
+        &[
+            SignatureScheme::RSA_PKCS1_SHA256,
+            SignatureScheme::RSA_PKCS1_SHA384,
+            SignatureScheme::RSA_PKCS1_SHA512,
+            SignatureScheme::RSA_PSS_SHA256,
+            SignatureScheme::RSA_PSS_SHA384,
+            SignatureScheme::RSA_PSS_SHA512,
+        ],
+    );
+}
+
+#[cfg(feature = "rsa")]
+#[test]
+fn signature_from_spki_der_test() {
+    const ALGORITHMS: &[&dyn signature::VerificationAlgorithm] = &[
+        &signature::ECDSA_P384_SHA384_ASN1,
+        &signature::ECDSA_P256_SHA256_ASN1,
+        &signature::RSA_PKCS1_2048_8192_SHA256,
+        #[cfg(feature = "sig-ed25519")]
+        &signature::ED25519,
+    ];
+    const MESSAGE: &[u8] = b"hello, world";
+
+    fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        let len = contents.len();
+        if len < 0x80 {
+            out.push(len as u8);
+        } else if len < 0x100 {
+            out.extend_from_slice(&[0x81, len as u8]);
+        } else {
+            out.extend_from_slice(&[0x82, (len >> 8) as u8, len as u8]);
+        }
+        out.extend_from_slice(contents);
+        out
+    }
+
+    fn spki(algorithm_id: &[u8], public_key: &[u8]) -> Vec<u8> {
+        let mut contents = der(0x30, algorithm_id);
+        let mut bit_string = vec![0];
+        bit_string.extend_from_slice(public_key);
+        contents.extend_from_slice(&der(0x03, &bit_string));
+        der(0x30, &contents)
+    }
+
+    fn check(
+        signer: &dyn Signer,
+        scheme: SignatureScheme,
+        algorithm_id: &[u8],
+        expected: &dyn signature::VerificationAlgorithm,
+    ) {
+        let rng = rand::SystemRandom::new();
+        let sig = signer.sign(scheme, &rng, MESSAGE).unwrap();
+        let spki = spki(algorithm_id, signer.public_key_bytes());
+        let public_key = signature::UnparsedPublicKey::from_spki_der(ALGORITHMS, &spki).unwrap();
+        assert_eq!(
+            format!("{:?}", public_key.algorithm()),
+            format!("{:?}", expected)
+        );
+        assert!(public_key.verify(MESSAGE, &sig).is_ok());
+        assert!(public_key.verify(b"goodbye", &sig).is_err());
+
+        // None of the acceptable algorithms has the right key type.
+        assert!(signature::UnparsedPublicKey::from_spki_der(&[], &spki).is_err());
+
+        // Truncated.
+        assert!(
+            signature::UnparsedPublicKey::from_spki_der(ALGORITHMS, &spki[..(spki.len() - 1)])
+                .is_err()
+        );
+    }
+
+    // id-ecPublicKey, secp256r1
+    const P256_ID: &[u8] = &[
+        0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce,
+        0x3d, 0x03, 0x01, 0x07,
+    ];
+    let key_pair = signature::EcdsaKeyPair::from_pkcs8(
+        &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+        include_bytes!("ecdsa_test_private_key_p256.p8"),
+    )
+    .unwrap();
+    check(
+        &key_pair,
+        SignatureScheme::ECDSA_P256_SHA256_ASN1,
+        P256_ID,
+        &signature::ECDSA_P256_SHA256_ASN1,
+    );
+
+    // rsaEncryption, NULL
+    const RSA_ID: &[u8] = &[
+        0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01, 0x05, 0x00,
+    ];
+    let key_pair =
+        signature::RsaKeyPair::from_pkcs8(include_bytes!("rsa_test_private_key_2048.p8")).unwrap();
+    check(
+        &key_pair,
+        SignatureScheme::RSA_PKCS1_SHA256,
+        RSA_ID,
+        &signature::RSA_PKCS1_2048_8192_SHA256,
+    );
+
+    #[cfg(feature = "sig-ed25519")]
+    {
+        // id-Ed25519
+        const ED25519_ID: &[u8] = &[0x06, 0x03, 0x2b, 0x65, 0x70];
+        let key_pair =
+            signature::Ed25519KeyPair::from_pkcs8(include_bytes!("ed25519_test_private_key.p8"))
+                .unwrap();
+        check(
+            &key_pair,
+            SignatureScheme::ED25519,
+            ED25519_ID,
+            &signature::ED25519,
+        );
+
+        // An Ed25519 key isn't accepted when only ECDSA is.
+        let spki = spki(ED25519_ID, &[0; 32]);
+        assert!(signature::UnparsedPublicKey::from_spki_der(ALGORITHMS, &spki).is_ok());
+        assert!(signature::UnparsedPublicKey::from_spki_der(&ALGORITHMS[..2], &spki).is_err());
+    }
+}
+
+#[cfg(all(feature = "async", feature = "sig-ed25519"))]
+#[test]
+fn signature_async_signer_test() {
+    use core::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+    use ring::signature::{AsyncSigner, SignFuture};
+    use std::{
+        sync::Arc,
+        task::{Wake, Waker},
+    };
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn block_on<F: Future + ?Sized>(mut future: Pin<Box<F>>) -> F::Output {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    // A future that isn't ready the first time it is polled, like a request
+    // to a remote service.
+    struct Remote<T>(Option<T>, bool);
+
+    impl<T: Unpin> Future for Remote<T> {
+        type Output = T;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<T> {
+            if !self.1 {
+                self.1 = true;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            Poll::Ready(self.0.take().unwrap())
+        }
+    }
+
+    #[derive(Debug)]
+    struct RemoteKey(signature::Ed25519KeyPair);
+
+    impl AsyncSigner for RemoteKey {
+        fn public_key_bytes(&self) -> &[u8] {
+            Signer::public_key_bytes(&self.0)
+        }
+
+        fn supports(&self, scheme: SignatureScheme) -> bool {
+            scheme == SignatureScheme::ED25519
+        }
+
+        fn sign_async<'a>(&'a self, scheme: SignatureScheme, message: &'a [u8]) -> SignFuture<'a> {
+            let rng = rand::SystemRandom::new();
+            Box::pin(Remote(Some(Signer::sign(&self.0, scheme, &rng, message)), false))
+        }
+    }
+
+    fn sign(signer: &dyn AsyncSigner, message: &[u8]) -> Vec<u8> {
+        assert!(signer.supports(SignatureScheme::ED25519));
+        block_on(signer.sign_async(SignatureScheme::ED25519, message)).unwrap()
+    }
+
+    const MESSAGE: &[u8] = b"hello, world";
+    let pkcs8 = include_bytes!("ed25519_test_private_key.p8");
+    let key_pair = signature::Ed25519KeyPair::from_pkcs8(pkcs8).unwrap();
+    let remote_key = RemoteKey(signature::Ed25519KeyPair::from_pkcs8(pkcs8).unwrap());
+
+    // Ed25519 signatures are deterministic.
+    let sig = sign(&key_pair, MESSAGE);
+    assert_eq!(sig, sign(&remote_key, MESSAGE));
+    assert_eq!(sig, key_pair.sign(MESSAGE).as_ref());
+    assert!(block_on(key_pair.sign_async(SignatureScheme::RSA_PSS_SHA256, MESSAGE)).is_err());
+}
+
+#[test]
+fn signature_scheme_registry() {
+    use ring::signature::SignatureScheme;
+
+    assert_eq!(SignatureScheme::all().len(), 15);
+    for &scheme in SignatureScheme::all() {
+        let name = format!("{:?}", scheme);
+        assert_eq!(SignatureScheme::from_name(&name), Some(scheme));
+    }
+    assert_eq!(SignatureScheme::from_name("ed25519"), None);
+
+    for &(id, expected) in &[
+        (0x0401, SignatureScheme::RSA_PKCS1_SHA256),
+        (0x0501, SignatureScheme::RSA_PKCS1_SHA384),
+        (0x0601, SignatureScheme::RSA_PKCS1_SHA512),
+        (0x0403, SignatureScheme::ECDSA_P256_SHA256_ASN1),
+        (0x0503, SignatureScheme::ECDSA_P384_SHA384_ASN1),
+        (0x0603, SignatureScheme::ECDSA_P521_SHA512_ASN1),
+        (0x0804, SignatureScheme::RSA_PSS_SHA256),
+        (0x0805, SignatureScheme::RSA_PSS_SHA384),
+        (0x0806, SignatureScheme::RSA_PSS_SHA512),
+        (0x0807, SignatureScheme::ED25519),
+        (0x0808, SignatureScheme::ED448),
+    ] {
+        assert_eq!(SignatureScheme::from_tls_id(id), Some(expected));
+    }
+    assert_eq!(SignatureScheme::from_tls_id(0x0201), None); // rsa_pkcs1_sha1
+    assert_eq!(SignatureScheme::from_tls_id(0x0809), None); // rsa_pss_pss_sha256
 }
@@ -0,0 +1,142 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "srp")]
+
+use ring::{error, rand, srp, test, test_file};
+
+fn group(bits: usize) -> &'static srp::Group {
+    match bits {
+        1024 => &srp::RFC5054_1024,
+        1536 => &srp::RFC5054_1536,
+        2048 => &srp::RFC5054_2048,
+        3072 => &srp::RFC5054_3072,
+        4096 => &srp::RFC5054_4096,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn srp_test() {
+    test::run(test_file!("srp_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let group = group(test_case.consume_usize("Group"));
+        let identity = test_case.consume_bytes("Identity");
+        let password = test_case.consume_bytes("Password");
+        let salt = test_case.consume_bytes("Salt");
+        let verifier = test_case.consume_bytes("Verifier");
+        let a = test_case.consume_bytes("a");
+        let a_pub = test_case.consume_bytes("A");
+        let b = test_case.consume_bytes("b");
+        let b_pub = test_case.consume_bytes("B");
+        let key = test_case.consume_bytes("K");
+        let m1 = test_case.consume_bytes("M1");
+        let m2 = test_case.consume_bytes("M2");
+
+        assert_eq!(
+            srp::generate_verifier(group, &identity, &password, &salt),
+            verifier
+        );
+        assert_eq!(verifier.len(), group.modulus_len());
+
+        let client = srp::Client::new(group, &test::rand::FixedSliceRandom { bytes: &a })?;
+        assert_eq!(client.public_key(), &a_pub[..]);
+        let server = srp::Server::new(
+            group,
+            &verifier,
+            &test::rand::FixedSliceRandom { bytes: &b },
+        )?;
+        assert_eq!(server.public_key(), &b_pub[..]);
+
+        let client = client.finish(&identity, &password, &salt, &b_pub)?;
+        assert_eq!(client.proof(), &m1[..]);
+        let server = server.finish(&identity, &salt, &a_pub)?;
+
+        let server_key = server.verify(&m1, error::Unspecified, |server_proof, server_key| {
+            assert_eq!(server_proof, &m2[..]);
+            Ok(server_key.to_vec())
+        })?;
+        assert_eq!(server_key, key);
+        let client_key = client.verify(&m2, error::Unspecified, |key| Ok(key.to_vec()))?;
+        assert_eq!(client_key, key);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn srp_wrong_password_test() {
+    let rng = rand::SystemRandom::new();
+    let group = &srp::RFC5054_2048;
+    let salt = [0x42; 16];
+    let verifier = srp::generate_verifier(group, b"alice", b"password", &salt);
+
+    let server = srp::Server::new(group, &verifier, &rng).unwrap();
+    let client = srp::Client::new(group, &rng).unwrap();
+    let client_public_key = client.public_key().to_vec();
+    let client = client
+        .finish(b"alice", b"wrong password", &salt, server.public_key())
+        .unwrap();
+    let server = server.finish(b"alice", &salt, &client_public_key).unwrap();
+
+    // The server must not reveal its proof to a client that fails to prove
+    // knowledge of the password.
+    assert_eq!(
+        server.verify(client.proof(), "wrong", |_, _| -> Result<(), _> {
+            panic!("called with the wrong password")
+        }),
+        Err("wrong")
+    );
+}
+
+#[test]
+fn srp_invalid_public_key_test() {
+    let rng = rand::SystemRandom::new();
+    let group = &srp::RFC5054_1024;
+    let salt = [0x42; 16];
+    let verifier = srp::generate_verifier(group, b"alice", b"password", &salt);
+    let zero = vec![0u8; group.modulus_len()];
+    let too_long = vec![0u8; group.modulus_len() + 1];
+    // Not less than N.
+    let too_large = vec![0xffu8; group.modulus_len()];
+
+    assert!(srp::Server::new(group, &zero, &rng).is_err());
+    assert!(srp::Server::new(group, &too_large, &rng).is_err());
+    assert!(srp::Server::new(group, &too_long, &rng).is_err());
+
+    for bad in &[&zero[..], &too_large[..], &too_long[..], &[][..]] {
+        let client = srp::Client::new(group, &rng).unwrap();
+        assert!(client.finish(b"alice", b"password", &salt, bad).is_err());
+        let server = srp::Server::new(group, &verifier, &rng).unwrap();
+        assert!(server.finish(b"alice", &salt, bad).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(all(feature = "rsa", feature = "sig-ed25519"))]
+
+use ring::{rand, ssh, test};
+
+// The keys were generated with `ssh-keygen`. The encrypted keys use the
+// passphrase `PASSPHRASE`; `ssh_test_ed25519_aes256_ctr` uses the default
+// cipher, and the others were generated with `-Z`.
+const PASSPHRASE: &[u8] = b"correct horse";
+
+// (private key, public key, comment, fingerprint shown by `ssh-keygen -l`)
+const KEYS: &[(&str, &str, &str, &str)] = &[
+    (
+        include_str!("ssh_test_ed25519"),
+        include_str!("ssh_test_ed25519.pub"),
+        "ed25519@example.com",
+        "SHA256:jC6uw/+XLsoCMSCKisXT9Xrdgvq+MgAVY9149f2E4lc",
+    ),
+    (
+        include_str!("ssh_test_p256"),
+        include_str!("ssh_test_p256.pub"),
+        "p256@example.com",
+        "SHA256:4TGttmZAonD3WQcRDGuf6UUty8IP2R0PwSxtczk3bYI",
+    ),
+    (
+        include_str!("ssh_test_p384"),
+        include_str!("ssh_test_p384.pub"),
+        "p384@example.com",
+        "SHA256:/MdS0VtHk0qLD/rgaWHr5s93dbAKCppmB7VoRakJeQ0",
+    ),
+    (
+        include_str!("ssh_test_rsa_2048"),
+        include_str!("ssh_test_rsa_2048.pub"),
+        "rsa@example.com",
+        "SHA256:4nYIdrvpLMQP0oFtpUTZc5DFwQu4dTDBGmL7XDYykV8",
+    ),
+    (
+        include_str!("ssh_test_ed25519_aes256_ctr"),
+        include_str!("ssh_test_ed25519_aes256_ctr.pub"),
+        "enc-ctr",
+        "SHA256:nt7e4EDYnOeYRKKIyAMS9126zI4Bc6aXQ6+yPJwhXIo",
+    ),
+    (
+        include_str!("ssh_test_p256_aes128_ctr"),
+        include_str!("ssh_test_p256_aes128_ctr.pub"),
+        "enc-128",
+        "SHA256:t3xKybYXTlzj228EWJ52G9heUMR4C6DSHaxv92e+C4I",
+    ),
+    (
+        include_str!("ssh_test_rsa_2048_aes256_gcm"),
+        include_str!("ssh_test_rsa_2048_aes256_gcm.pub"),
+        "enc-gcm",
+        "SHA256:sx4ACxd09bExx9uCVpbGAWnJ4Uq1G/pF2XDUT7yzsBE",
+    ),
+];
+
+// Signatures of `MESSAGE` made with the Python `cryptography` package.
+const MESSAGE: &[u8] = b"Deploy key test message\n";
+const ED25519_SIGNATURE: &str = "0000000b7373682d656432353531390000004092708660ac4bc95b02db3794ba2c56d01bbebb9ce561eea35ec216e800f52a3e431511968cb15f605c05551ee31f39957419ff702b25e02a5fad6d1791a2b30a";
+const RSA_SHA2_256_SIGNATURE: &str = "0000000c7273612d736861322d32353600000100b96339963f515863360dde259f7f97c63d135ef4801fb33fc6981b54679f02c86d61a5594e079b0882a07827f8945e7a07ebab2804065fe54ba7ab119946daa7fb86755cd67a1e1af9c3298884d8facb96c38811f1a681fda3775c7a6c5d0340620af8de4c188c451d156b0fecddebfd8bbd36d2d947bb906217dd8df70f280a6529b699107b94cd5bab0a27789a0936f75856b7c17ef014c51ae2fdee159972f7fdd4677086aca2aeba9cd864febf490b13334ec3c983aaffc192744cd6a7d18362cbfe2b6bff4237d6c704eda1e054d179066a1d05b2a26cfae9d28b35ecfa2e830b8e45607aa129a46ce00549d101958da213de35fee8eac7a44604431136";
+const RSA_SHA2_512_SIGNATURE: &str = "0000000c7273612d736861322d35313200000100bc53240f7ff83b514e081e85db787ee96061bc030df505a9a1bd6f8ede1db0cb0383fa72985d2f6c74b455102242c6a4813a135a06c053db247daee9cf57df0cdce82d50b89fe7604bfd1312bd28b75d83b50ee05419ba33da77579e3117f1c2043275638cb75c3b07633e1fdfdbd8fc410b60faab292dec633008d10d260132a6bfa128c7e4ce89f8a646e0cf4582aaf18044f7d00309b0c2a3558840a14fa2c2205a3e49b57c2f7c1cd585faa591b5c3cbe0b6b5142d4a36eb73fd6ce4f9f0df52a459c39a093a059519b7a598a238f3fd7d58582e941c5cb357e852ca73d4d24dabf445080654c000e07c90e8c16b1459255476e8ea5774f3ec7119e279fc";
+const ECDSA_P384_SIGNATURE: &str = "0000001365636473612d736861322d6e69737470333834000000690000003100e90b7ab042bd32ee030be40a0bdb6ccc784f6a595b9385d9c84dca8dcd743ac633f56822f49338ee6bfa40bbe95744b1000000301308bf7411c8472f6677860cb4e45f549921d2b4e8a19be429ace38670621ffc75e276f06cade8f9f90668d5d2fe9c4a";
+
+#[test]
+fn ssh_key_pair_test() {
+    let rng = rand::SystemRandom::new();
+    for &(private_key, public_key, comment, fingerprint) in KEYS {
+        let key_pair = ssh::KeyPair::from_openssh(private_key, PASSPHRASE).unwrap();
+        assert_eq!(key_pair.comment(), comment);
+
+        let public_key_line = public_key.trim_end();
+        let public_key = ssh::PublicKey::from_openssh(public_key).unwrap();
+        assert_eq!(key_pair.public_key(), public_key);
+        assert_eq!(public_key.to_openssh() + " " + comment, public_key_line);
+        assert_eq!(public_key.fingerprint(), fingerprint);
+        assert_eq!(
+            ssh::PublicKey::from_bytes(public_key.as_ref()).unwrap(),
+            public_key
+        );
+
+        assert!(public_key_line.starts_with(public_key.key_type()));
+
+        let algorithm = key_pair.default_signature_algorithm();
+        let signature = key_pair.sign(algorithm, &rng, MESSAGE).unwrap();
+        assert!(public_key.verify(MESSAGE, &signature).is_ok());
+        assert!(public_key.verify(b"another message", &signature).is_err());
+    }
+}
+
+#[test]
+fn ssh_sign_test() {
+    let rng = rand::SystemRandom::new();
+
+    // Ed25519 and RSA PKCS#1 1.5 signatures are deterministic.
+    let ed25519 = ssh::KeyPair::from_openssh(KEYS[0].0, b"").unwrap();
+    let signature = ed25519.sign(&ssh::SSH_ED25519, &rng, MESSAGE).unwrap();
+    assert_eq!(signature, test::from_hex(ED25519_SIGNATURE).unwrap());
+
+    let rsa = ssh::KeyPair::from_openssh(KEYS[3].0, b"").unwrap();
+    assert_eq!(rsa.default_signature_algorithm().name(), "rsa-sha2-512");
+    for &(algorithm, expected) in &[
+        (&ssh::RSA_SHA2_256, RSA_SHA2_256_SIGNATURE),
+        (&ssh::RSA_SHA2_512, RSA_SHA2_512_SIGNATURE),
+    ] {
+        let signature = rsa.sign(algorithm, &rng, MESSAGE).unwrap();
+        assert_eq!(signature, test::from_hex(expected).unwrap());
+    }
+
+    // The algorithm must match the key.
+    let p256 = ssh::KeyPair::from_openssh(KEYS[1].0, b"").unwrap();
+    assert!(ed25519.sign(&ssh::RSA_SHA2_256, &rng, MESSAGE).is_err());
+    assert!(rsa.sign(&ssh::SSH_ED25519, &rng, MESSAGE).is_err());
+    assert!(p256.sign(&ssh::ECDSA_SHA2_NISTP384, &rng, MESSAGE).is_err());
+}
+
+#[test]
+fn ssh_verify_test() {
+    let ed25519 = ssh::PublicKey::from_openssh(KEYS[0].1).unwrap();
+    let p384 = ssh::PublicKey::from_openssh(KEYS[2].1).unwrap();
+    let rsa = ssh::PublicKey::from_openssh(KEYS[3].1).unwrap();
+    let ed25519_signature = test::from_hex(ED25519_SIGNATURE).unwrap();
+    let p384_signature = test::from_hex(ECDSA_P384_SIGNATURE).unwrap();
+
+    assert!(ed25519.verify(MESSAGE, &ed25519_signature).is_ok());
+    assert!(p384.verify(MESSAGE, &p384_signature).is_ok());
+    for signature in &[RSA_SHA2_256_SIGNATURE, RSA_SHA2_512_SIGNATURE] {
+        let signature = test::from_hex(signature).unwrap();
+        assert!(rsa.verify(MESSAGE, &signature).is_ok());
+
+        // The signature doesn't verify with the other hash function.
+        let mut wrong_algorithm = signature.clone();
+        let other: &[u8] = if signature[13..16] == b"256"[..] {
+            b"512"
+        } else {
+            b"256"
+        };
+        wrong_algorithm[13..16].copy_from_slice(other);
+        assert!(rsa.verify(MESSAGE, &wrong_algorithm).is_err());
+    }
+
+    // Signatures by the wrong kind of key, or with trailing data, are
+    // rejected.
+    assert!(p384.verify(MESSAGE, &ed25519_signature).is_err());
+    assert!(ed25519.verify(MESSAGE, &p384_signature).is_err());
+    let mut trailing_data = ed25519_signature.clone();
+    trailing_data.push(0);
+    assert!(ed25519.verify(MESSAGE, &trailing_data).is_err());
+}
+
+#[test]
+fn ssh_passphrase_test() {
+    // Unencrypted keys ignore the passphrase.
+    assert!(ssh::KeyPair::from_openssh(KEYS[0].0, b"").is_ok());
+
+    for &(private_key, ..) in &KEYS[4..] {
+        for passphrase in &[&b""[..], b"correct horse battery staple"] {
+            match ssh::KeyPair::from_openssh(private_key, passphrase) {
+                Err(e) => assert_eq!(e.description_(), "WrongPassphrase"),
+                Ok(_) => panic!("wrong passphrase accepted"),
+            }
+        }
+    }
+}
+
+#[test]
+fn ssh_parse_test() {
+    let private_key = KEYS[0].0;
+    let crlf = private_key.replace('\n', "\r\n");
+    assert!(ssh::KeyPair::from_openssh(&crlf, b"").is_ok());
+    let truncated = private_key.replace("-----END OPENSSH PRIVATE KEY-----\n", "");
+    assert!(ssh::KeyPair::from_openssh(&truncated, b"").is_err());
+    let trailing_garbage = String::from(private_key) + "garbage\n";
+    assert!(ssh::KeyPair::from_openssh(&trailing_garbage, b"").is_err());
+
+    // A private key is not a public key, and vice versa.
+    assert!(ssh::PublicKey::from_openssh(private_key).is_err());
+    assert!(ssh::KeyPair::from_openssh(KEYS[0].1, b"").is_err());
+
+    // The key type must match the key.
+    let mismatched = KEYS[0].1.replace("ssh-ed25519", "ssh-rsa");
+    assert!(ssh::PublicKey::from_openssh(&mismatched).is_err());
+}
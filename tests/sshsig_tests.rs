@@ -0,0 +1,206 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(all(feature = "use_heap", feature = "sig-ed25519"))]
+
+use ring::{rand, signature, sshsig, test};
+
+// Generated with `ssh-keygen -Y sign -n git`.
+const MESSAGE: &[u8] = b"Signed commit contents\n";
+const NAMESPACE: &str = "git";
+
+const ED25519_SEED: &str = "f92ae0b8a0a05a7b3135aaf29a7bd7bf5b3201ce4e5f5cf0abbb8387e09685b0";
+const ED25519_PUBLIC_KEY: &str =
+    "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIE327b8h6e5Tv+YjUd9aavx6myyCzAnDut71l4u3OZKr";
+const ED25519_SIGNATURE: &str = "\
+-----BEGIN SSH SIGNATURE-----
+U1NIU0lHAAAAAQAAADMAAAALc3NoLWVkMjU1MTkAAAAgTfbtvyHp7lO/5iNR31pq/HqbLI
+LMCcO63vWXi7c5kqsAAAADZ2l0AAAAAAAAAAZzaGE1MTIAAABTAAAAC3NzaC1lZDI1NTE5
+AAAAQMzrtGXgqzB2N+LCOedfldg6snu8Vo9M/zyjcGJwKt3vIVEf3nmQbWQSElWxNAVELH
+WtbBuJpaupNp20VvzKPQE=
+-----END SSH SIGNATURE-----
+";
+
+const ECDSA_P256_PUBLIC_KEY: &str = "ecdsa-sha2-nistp256 AAAAE2VjZHNhLXNoYTItbmlzdHAyNTYAAAAIbmlzdHAyNTYAAABBBFf8+f93a5X9UqYZzWUfpebKk1hOG2AjoZ5cseiP0MwNm/AjP6hOHUXOliH2L20qf3MKf8YbTLHvr2fy3g+7AOU=";
+const ECDSA_P256_SIGNATURE: &str = "\
+-----BEGIN SSH SIGNATURE-----
+U1NIU0lHAAAAAQAAAGgAAAATZWNkc2Etc2hhMi1uaXN0cDI1NgAAAAhuaXN0cDI1NgAAAE
+EEV/z5/3drlf1SphnNZR+l5sqTWE4bYCOhnlyx6I/QzA2b8CM/qE4dRc6WIfYvbSp/cwp/
+xhtMse+vZ/LeD7sA5QAAAANnaXQAAAAAAAAABnNoYTUxMgAAAGMAAAATZWNkc2Etc2hhMi
+1uaXN0cDI1NgAAAEgAAAAgTSaD6J94XJAnhzMI4cjhl6Nzg/C0t8lemL/mfZJ6xGYAAAAg
+Wgf4HSJru1mq3KzzJGp7XeihUuFZJOEx7k/w6PJMOkk=
+-----END SSH SIGNATURE-----
+";
+
+const ECDSA_P384_PUBLIC_KEY: &str = "ecdsa-sha2-nistp384 AAAAE2VjZHNhLXNoYTItbmlzdHAzODQAAAAIbmlzdHAzODQAAABhBNZB7sgXhhfWa8CEFnxnrELBnFS2FkjuCE3fYv1i8NxClKidoP1Oe/lnBu6ctnTvAY+2sUENSXU6hXMWfUyhBbo7BXUAk6IJl9M1yH/oDOHDnRB7l8f77Qf5GT2NiE1gFw==";
+const ECDSA_P384_SIGNATURE: &str = "\
+-----BEGIN SSH SIGNATURE-----
+U1NIU0lHAAAAAQAAAIgAAAATZWNkc2Etc2hhMi1uaXN0cDM4NAAAAAhuaXN0cDM4NAAAAG
+EE1kHuyBeGF9ZrwIQWfGesQsGcVLYWSO4ITd9i/WLw3EKUqJ2g/U57+WcG7py2dO8Bj7ax
+QQ1JdTqFcxZ9TKEFujsFdQCTogmX0zXIf+gM4cOdEHuXx/vtB/kZPY2ITWAXAAAAA2dpdA
+AAAAAAAAAGc2hhNTEyAAAAgwAAABNlY2RzYS1zaGEyLW5pc3RwMzg0AAAAaAAAADBE6gbp
+taJzOdKeBQhzM0j/WxwyI1xY23aAvQRdnYBLMa7beDA/qatGoGlO1CQY6xQAAAAwR/NW+X
+PuRi+YjdtCHhy8/9YaAnYulz95WWa60pftAGYiqwSqOSEfS3B21IPTOpgN
+-----END SSH SIGNATURE-----
+";
+
+#[test]
+fn sshsig_verify_test() {
+    for (public_key, signature) in &[
+        (ED25519_PUBLIC_KEY, ED25519_SIGNATURE),
+        (ECDSA_P256_PUBLIC_KEY, ECDSA_P256_SIGNATURE),
+        (ECDSA_P384_PUBLIC_KEY, ECDSA_P384_SIGNATURE),
+    ] {
+        let public_key = sshsig::public_key_from_openssh(public_key).unwrap();
+        let signature = sshsig::Signature::from_armored(signature).unwrap();
+        assert_eq!(signature.public_key(), &public_key[..]);
+        assert_eq!(signature.namespace(), NAMESPACE);
+        assert!(signature.verify(&public_key, NAMESPACE, MESSAGE).is_ok());
+
+        assert!(signature.verify(&public_key, "file", MESSAGE).is_err());
+        assert!(signature
+            .verify(&public_key, NAMESPACE, b"Tampered commit contents\n")
+            .is_err());
+    }
+
+    // The signature must be from the expected key.
+    let other_key = sshsig::public_key_from_openssh(ECDSA_P256_PUBLIC_KEY).unwrap();
+    let signature = sshsig::Signature::from_armored(ED25519_SIGNATURE).unwrap();
+    assert!(signature.verify(&other_key, NAMESPACE, MESSAGE).is_err());
+}
+
+#[test]
+fn sshsig_parse_test() {
+    // CRLF line endings are accepted.
+    let crlf = ED25519_SIGNATURE.replace('\n', "\r\n");
+    assert!(sshsig::Signature::from_armored(&crlf).is_ok());
+
+    let missing_end = ED25519_SIGNATURE.replace("-----END SSH SIGNATURE-----", "");
+    assert!(sshsig::Signature::from_armored(&missing_end).is_err());
+    let trailing_garbage = [ED25519_SIGNATURE, "x\n"].concat();
+    assert!(sshsig::Signature::from_armored(&trailing_garbage).is_err());
+    let wrong_label = ED25519_SIGNATURE.replace("SSH SIGNATURE", "SIGNATURE");
+    assert!(sshsig::Signature::from_armored(&wrong_label).is_err());
+
+    // The key type in the public key must match the declared key type.
+    let mismatched = ED25519_PUBLIC_KEY.replace("ssh-ed25519", "ecdsa-sha2-nistp256");
+    assert!(sshsig::public_key_from_openssh(&mismatched).is_err());
+    assert!(sshsig::public_key_from_openssh("ssh-ed25519").is_err());
+}
+
+#[test]
+fn sshsig_sign_ed25519_test() {
+    let key_pair =
+        signature::Ed25519KeyPair::from_seed_unchecked(&test::from_hex(ED25519_SEED).unwrap())
+            .unwrap();
+    let key = sshsig::SigningKey::Ed25519(&key_pair);
+    assert_eq!(
+        key.public_key().unwrap(),
+        sshsig::public_key_from_openssh(ED25519_PUBLIC_KEY).unwrap()
+    );
+
+    // Ed25519 signatures are deterministic, so the result must match what
+    // `ssh-keygen` produced.
+    let rng = rand::SystemRandom::new();
+    let armored = sshsig::sign(key, NAMESPACE, MESSAGE, &rng).unwrap();
+    assert_eq!(armored, ED25519_SIGNATURE);
+
+    assert!(sshsig::sign(key, "", MESSAGE, &rng).is_err());
+}
+
+#[test]
+fn sshsig_sign_ecdsa_test() {
+    let rng = rand::SystemRandom::new();
+    let key_pair = signature::EcdsaKeyPair::from_pkcs8(
+        &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+        include_bytes!("ecdsa_test_private_key_p256.p8"),
+    )
+    .unwrap();
+    let key = sshsig::SigningKey::Ecdsa(&key_pair);
+    let public_key = key.public_key().unwrap();
+
+    let armored = sshsig::sign(key, "file", MESSAGE, &rng).unwrap();
+    let signature = sshsig::Signature::from_armored(&armored).unwrap();
+    assert!(signature.verify(&public_key, "file", MESSAGE).is_ok());
+    assert!(signature.verify(&public_key, NAMESPACE, MESSAGE).is_err());
+
+    // Only the fixed-length signing algorithms are supported.
+    let key_pair = signature::EcdsaKeyPair::from_pkcs8(
+        &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+        include_bytes!("ecdsa_test_private_key_p256.p8"),
+    )
+    .unwrap();
+    let key = sshsig::SigningKey::Ecdsa(&key_pair);
+    assert!(key.public_key().is_err());
+    assert!(sshsig::sign(key, "file", MESSAGE, &rng).is_err());
+}
+
+// A key that is only accessible through `signature::Signer`, like a key in an
+// HSM.
+#[derive(Debug)]
+struct ExternalKey(signature::Ed25519KeyPair);
+
+impl signature::Signer for ExternalKey {
+    fn public_key_bytes(&self) -> &[u8] {
+        signature::Signer::public_key_bytes(&self.0)
+    }
+
+    fn supports(&self, scheme: signature::SignatureScheme) -> bool {
+        signature::Signer::supports(&self.0, scheme)
+    }
+
+    fn sign(
+        &self,
+        scheme: signature::SignatureScheme,
+        rng: &dyn rand::SecureRandom,
+        message: &[u8],
+    ) -> Result<Vec<u8>, ring::error::Unspecified> {
+        signature::Signer::sign(&self.0, scheme, rng, message)
+    }
+}
+
+#[test]
+fn sshsig_sign_signer_test() {
+    let key_pair =
+        signature::Ed25519KeyPair::from_seed_unchecked(&test::from_hex(ED25519_SEED).unwrap())
+            .unwrap();
+    let external_key = ExternalKey(key_pair);
+    let key = sshsig::SigningKey::Signer(&external_key);
+    assert_eq!(
+        key.public_key().unwrap(),
+        sshsig::public_key_from_openssh(ED25519_PUBLIC_KEY).unwrap()
+    );
+
+    let rng = rand::SystemRandom::new();
+    let armored = sshsig::sign(key, NAMESPACE, MESSAGE, &rng).unwrap();
+    assert_eq!(armored, ED25519_SIGNATURE);
+}
@@ -0,0 +1,122 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{aead::Nonce, stream::aes_ctr, test, test_file};
+
+#[cfg(feature = "aead-chacha")]
+use ring::stream::chacha20;
+
+#[test]
+fn stream_tests() {
+    test::run(test_file!("stream_tests.txt"), |section, test_case| {
+        let key_bytes = test_case.consume_bytes("Key");
+        let nonce = test_case.consume_bytes("Nonce");
+        let counter = test_case.consume_usize("Counter") as u32;
+        let input = test_case.consume_bytes("Input");
+        let output = test_case.consume_bytes("Output");
+
+        if cfg!(not(feature = "aead-chacha")) && section == "ChaCha20" {
+            return Ok(());
+        }
+
+        let xor_keystream = |in_out: &mut [u8]| -> Result<(), ring::error::Unspecified> {
+            let nonce = Nonce::try_assume_unique_for_key(&nonce)?;
+            match section {
+                #[cfg(feature = "aead-chacha")]
+                "ChaCha20" => {
+                    let mut chacha20_key_bytes = [0; chacha20::KEY_LEN];
+                    chacha20_key_bytes.copy_from_slice(&key_bytes);
+                    chacha20::Key::new(&chacha20_key_bytes).xor_keystream(nonce, counter, in_out)
+                }
+                "AES-128-CTR" => aes_ctr::Key::new(&aes_ctr::AES_128, &key_bytes)?
+                    .xor_keystream(nonce, counter, in_out),
+                "AES-256-CTR" => aes_ctr::Key::new(&aes_ctr::AES_256, &key_bytes)?
+                    .xor_keystream(nonce, counter, in_out),
+                _ => unreachable!(),
+            }
+        };
+
+        // Encrypting and decrypting are the same operation.
+        for &(input, output) in &[(&input, &output), (&output, &input)] {
+            let mut in_out = input.clone();
+            xor_keystream(&mut in_out)?;
+            assert_eq!(&in_out, output);
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn stream_counter_wrap_test() {
+    #[cfg(feature = "aead-chacha")]
+    let chacha20_key = chacha20::Key::new(&[0; chacha20::KEY_LEN]);
+    let aes_key = aes_ctr::Key::new(&aes_ctr::AES_128, &[0; 16]).unwrap();
+
+    for &(counter, max_len) in &[(u32::max_value(), 1), (u32::max_value() - 1, 2)] {
+        let nonce = || Nonce::assume_unique_for_key([0; 12]);
+
+        #[cfg(feature = "aead-chacha")]
+        {
+            let mut in_out = vec![0; max_len * 64];
+            assert!(chacha20_key
+                .xor_keystream(nonce(), counter, &mut in_out)
+                .is_ok());
+            let mut in_out = vec![0; max_len * 64 + 1];
+            assert!(chacha20_key
+                .xor_keystream(nonce(), counter, &mut in_out)
+                .is_err());
+            assert!(in_out.iter().all(|b| *b == 0));
+        }
+
+        let mut in_out = vec![0; max_len * 16];
+        assert!(aes_key.xor_keystream(nonce(), counter, &mut in_out).is_ok());
+        let mut in_out = vec![0; max_len * 16 + 1];
+        assert!(aes_key
+            .xor_keystream(nonce(), counter, &mut in_out)
+            .is_err());
+        assert!(in_out.iter().all(|b| *b == 0));
+    }
+}
+
+#[test]
+fn stream_key_test() {
+    assert!(aes_ctr::Key::new(&aes_ctr::AES_128, &[0; 32]).is_err());
+    assert!(aes_ctr::Key::new(&aes_ctr::AES_256, &[0; 16]).is_err());
+
+    let key = aes_ctr::Key::new(&aes_ctr::AES_256, &[0; 32]).unwrap();
+    assert_eq!(key.algorithm(), &aes_ctr::AES_256);
+    assert_eq!(aes_ctr::AES_128.key_len(), 16);
+    assert_eq!(aes_ctr::AES_256.key_len(), 32);
+    assert_eq!("Key { algorithm: AES_256_CTR }", format!("{:?}", key));
+}
@@ -0,0 +1,295 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "testvec")]
+
+use ring::{aead, agreement, signature, testvec};
+
+const AES_GCM_WYCHEPROOF: &str = r#"{
+  "algorithm" : "AES-GCM",
+  "numberOfTests" : 4,
+  "testGroups" : [
+    {
+      "ivSize" : 96,
+      "keySize" : 128,
+      "tagSize" : 128,
+      "type" : "AeadTest",
+      "tests" : [
+        {
+          "tcId" : 1,
+          "comment" : "",
+          "key" : "000102030405060708090a0b0c0d0e0f",
+          "iv" : "000102030405060708090a0b",
+          "aad" : "616164",
+          "msg" : "68656c6c6f",
+          "ct" : "fb09cba209",
+          "tag" : "977841a36b7e50a33428ab4f5c15b002",
+          "result" : "valid",
+          "flags" : []
+        },
+        {
+          "tcId" : 2,
+          "comment" : "modified tag",
+          "key" : "000102030405060708090a0b0c0d0e0f",
+          "iv" : "000102030405060708090a0b",
+          "aad" : "616164",
+          "msg" : "68656c6c6f",
+          "ct" : "fb09cba209",
+          "tag" : "977841a36b7e50a33428ab4f5c15b003",
+          "result" : "invalid",
+          "flags" : ["ModifiedTag"]
+        },
+        {
+          "tcId" : 3,
+          "comment" : "wrongly claimed to be valid",
+          "key" : "000102030405060708090a0b0c0d0e0f",
+          "iv" : "000102030405060708090a0b",
+          "aad" : "",
+          "msg" : "68656c6c6f",
+          "ct" : "fb09cba209",
+          "tag" : "977841a36b7e50a33428ab4f5c15b002",
+          "result" : "valid",
+          "flags" : []
+        }
+      ]
+    },
+    {
+      "ivSize" : 64,
+      "keySize" : 128,
+      "tagSize" : 128,
+      "type" : "AeadTest",
+      "tests" : [
+        {
+          "tcId" : 4,
+          "comment" : "short nonce",
+          "key" : "000102030405060708090a0b0c0d0e0f",
+          "iv" : "0001020304050607",
+          "aad" : "",
+          "msg" : "",
+          "ct" : "",
+          "tag" : "00000000000000000000000000000000",
+          "result" : "acceptable",
+          "flags" : ["SmallIv"]
+        }
+      ]
+    }
+  ]
+}"#;
+
+#[test]
+fn testvec_aead_wycheproof() {
+    let report = testvec::run_aead(
+        &aead::AES_128_GCM,
+        testvec::Format::Wycheproof,
+        AES_GCM_WYCHEPROOF.as_bytes(),
+    )
+    .unwrap();
+    assert!(!report.passed());
+
+    let results = report.results();
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0].expected, testvec::Expected::Valid);
+    assert!(results[0].passed);
+    assert_eq!(results[1].expected, testvec::Expected::Invalid);
+    assert_eq!(&results[1].flags[..], &["ModifiedTag"]);
+    assert!(results[1].passed);
+    assert_eq!(results[3].expected, testvec::Expected::Acceptable);
+    assert!(results[3].passed);
+
+    let failures: Vec<_> = report.failures().collect();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].id, 3);
+    assert_eq!(failures[0].comment, "wrongly claimed to be valid");
+
+    // The vectors only pass with the algorithm they are for.
+    let report = testvec::run_aead(
+        &aead::AES_128_GCM_SIV,
+        testvec::Format::Wycheproof,
+        AES_GCM_WYCHEPROOF.as_bytes(),
+    )
+    .unwrap();
+    assert!(!report.results()[0].passed);
+}
+
+#[test]
+fn testvec_aead_txt() {
+    const VECTORS: &str = "\
+# A comment.
+
+KEY = 000102030405060708090a0b0c0d0e0f
+NONCE = 000102030405060708090a0b
+IN = \"hello\"
+AD = \"aad\"
+CT = fb09cba209
+TAG = 977841a36b7e50a33428ab4f5c15b002
+
+KEY = 000102030405060708090a0b0c0d0e0f
+NONCE = 0001020304050607
+IN = \"\"
+AD = \"\"
+CT = \"\"
+TAG = 00000000000000000000000000000000
+FAILS = WRONG_NONCE_LENGTH
+";
+    let report =
+        testvec::run_aead(&aead::AES_128_GCM, testvec::Format::Txt, VECTORS.as_bytes()).unwrap();
+    assert!(report.passed());
+    let results = report.results();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].id, 1);
+    assert_eq!(results[1].id, 2);
+    assert_eq!(results[1].expected, testvec::Expected::Invalid);
+}
+
+#[test]
+fn testvec_signature_wycheproof() {
+    const VECTORS: &str = r#"{
+  "algorithm" : "ECDSA",
+  "testGroups" : [
+    {
+      "key" : {
+        "curve" : "secp256r1",
+        "keySize" : 256,
+        "type" : "EcPublicKey"
+      },
+      "keyDer" : "3059301306072a8648ce3d020106082a8648ce3d0301070342000426efcebd0ee9e34a669187e18b3a9122b2f733945b649cc9f9f921e9f9dad81290238bde9cc7bb330d150c67704dd25ae7055205744b6f31bf4070745872d0e6",
+      "sha" : "SHA-256",
+      "type" : "EcdsaVerify",
+      "tests" : [
+        {
+          "tcId" : 1,
+          "comment" : "",
+          "msg" : "6d7367",
+          "sig" : "3045022100b9bb1cf53f07430b6e867ae630260ec23e053d7d1911f09334f6a2eb69b3c607022022926695b45d9352896318d088066a17a87b50196dfbae06fc595a42c7c81123",
+          "result" : "valid",
+          "flags" : []
+        },
+        {
+          "tcId" : 2,
+          "comment" : "modified message",
+          "msg" : "6d7368",
+          "sig" : "3045022100b9bb1cf53f07430b6e867ae630260ec23e053d7d1911f09334f6a2eb69b3c607022022926695b45d9352896318d088066a17a87b50196dfbae06fc595a42c7c81123",
+          "result" : "invalid",
+          "flags" : []
+        }
+      ]
+    }
+  ]
+}"#;
+    let report = testvec::run_signature(
+        &signature::ECDSA_P256_SHA256_ASN1,
+        testvec::Format::Wycheproof,
+        VECTORS.as_bytes(),
+    )
+    .unwrap();
+    assert!(report.passed());
+    assert_eq!(report.results().len(), 2);
+
+    // The `SubjectPublicKeyInfo` isn't of an Ed25519 key.
+    let report = testvec::run_signature(
+        &signature::ED25519,
+        testvec::Format::Wycheproof,
+        VECTORS.as_bytes(),
+    )
+    .unwrap();
+    assert!(!report.results()[0].passed);
+    assert!(report.results()[1].passed);
+}
+
+#[test]
+fn testvec_agreement() {
+    const WYCHEPROOF: &str = r#"{
+  "algorithm" : "XDH",
+  "testGroups" : [
+    {
+      "curve" : "curve25519",
+      "type" : "XdhComp",
+      "tests" : [
+        {
+          "tcId" : 1,
+          "comment" : "normal case",
+          "public" : "358072d6365880d1aeea329adf9121383851ed21a28e3b75e965d0d2cd166254",
+          "private" : "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+          "shared" : "9663aa1da97e848a914a436d04163dfbb89178f107f1b5b77ed3854203382854",
+          "result" : "valid",
+          "flags" : []
+        },
+        {
+          "tcId" : 2,
+          "comment" : "public key of low order",
+          "public" : "0000000000000000000000000000000000000000000000000000000000000000",
+          "private" : "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+          "shared" : "0000000000000000000000000000000000000000000000000000000000000000",
+          "result" : "invalid",
+          "flags" : ["ZeroSharedSecret"]
+        }
+      ]
+    }
+  ]
+}"#;
+    let report = testvec::run_agreement(
+        &agreement::X25519,
+        testvec::Format::Wycheproof,
+        WYCHEPROOF.as_bytes(),
+    )
+    .unwrap();
+    assert!(report.passed());
+    assert_eq!(report.results().len(), 2);
+
+    const TXT: &str = "\
+Curve = X25519
+PeerQ = 358072d6365880d1aeea329adf9121383851ed21a28e3b75e965d0d2cd166254
+D = 000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f
+Output = 9663aa1da97e848a914a436d04163dfbb89178f107f1b5b77ed3854203382855
+";
+    let report =
+        testvec::run_agreement(&agreement::X25519, testvec::Format::Txt, TXT.as_bytes()).unwrap();
+    assert!(!report.passed());
+}
+
+#[test]
+fn testvec_malformed() {
+    for input in &[
+        &b""[..],
+        b"{",
+        b"[]",
+        br#"{"testGroups": [{"tests": [{"tcId": 1}]}]}"#,
+        br#"{"testGroups": [{"tests": [{"tcId": 1, "result": "valid"}]}]}"#,
+        br#"{"testGroups": [{"tests": [{"tcId": 1, "result": "valid", "key": "0"}]}]}"#,
+    ] {
+        assert!(testvec::run_aead(&aead::AES_128_GCM, testvec::Format::Wycheproof, input).is_err());
+    }
+
+    for input in &[&b"KEY 00"[..], b"KEY = 0", b"\xff = 00"] {
+        assert!(testvec::run_aead(&aead::AES_128_GCM, testvec::Format::Txt, input).is_err());
+    }
+}
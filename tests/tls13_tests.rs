@@ -0,0 +1,209 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+
+use ring::{aead, digest, hkdf, hmac, test, tls13};
+
+fn from_hex(s: &str) -> Vec<u8> {
+    test::from_hex(s).unwrap()
+}
+
+// Checks `secret` by comparing the value of a key derived from it with the
+// value derived from `expected`, since `hkdf::Prk` doesn't expose its value.
+fn assert_secret_eq(secret: &hkdf::Prk, expected: &str) {
+    let expected = hkdf::Prk::new_less_safe(&digest::SHA256, &from_hex(expected));
+    let mut actual_out = [0u8; 32];
+    let mut expected_out = [0u8; 32];
+    tls13::hkdf_expand_label(secret, b"check", &[], &mut actual_out).unwrap();
+    tls13::hkdf_expand_label(&expected, b"check", &[], &mut expected_out).unwrap();
+    assert_eq!(actual_out, expected_out);
+}
+
+// Test vectors from RFC 8448 Section 3, "Simple 1-RTT Handshake".
+#[test]
+fn tls13_key_schedule_test() {
+    const ECDHE_SHARED_SECRET: &str =
+        "8bd4054fb55b9d63fdfbacf9f04b9f0d35e6d63f537563efd46272900f89492d";
+    // The hash of the ClientHello and ServerHello.
+    const HELLO_HASH: &str = "860c06edc07858ee8e78f0e7428c58edd6b43f2ca3e6e95f02ed063cf0e1cad8";
+    // The hash of the ClientHello through the server's Finished.
+    const SERVER_FINISHED_HASH: &str =
+        "9608102a0f1ccc6db6250b7b7e417b1a000eaada3daae4777a7686c9ff83df13";
+
+    let mut key_schedule = tls13::KeySchedule::new(&digest::SHA256);
+    key_schedule
+        .input_secret(&from_hex(ECDHE_SHARED_SECRET))
+        .unwrap();
+
+    let hello_hash = from_hex(HELLO_HASH);
+    let client_handshake_traffic_secret = key_schedule
+        .derive_secret(b"c hs traffic", &hello_hash)
+        .unwrap();
+    assert_secret_eq(
+        &client_handshake_traffic_secret,
+        "b3eddb126e067f35a780b3abf45e2d8f3b1a950738f52e9600746a0e27a55a21",
+    );
+    let server_handshake_traffic_secret = key_schedule
+        .derive_secret(b"s hs traffic", &hello_hash)
+        .unwrap();
+    assert_secret_eq(
+        &server_handshake_traffic_secret,
+        "b67b7d690cc16c4e75e54213cb2d37b4e9c912bcded9105d42befd59d391ad38",
+    );
+
+    let finished_key = tls13::finished_key(&server_handshake_traffic_secret).unwrap();
+    let expected_finished_key = hmac::Key::new(
+        &digest::SHA256,
+        &from_hex("008d3b66f816ea559f96b537e885c31fc068bf492c652f01f288a1d8cdc19fc8"),
+    );
+    assert_eq!(
+        hmac::sign(&finished_key, &hello_hash).as_ref(),
+        hmac::sign(&expected_finished_key, &hello_hash).as_ref()
+    );
+
+    key_schedule.input_empty().unwrap();
+    let server_finished_hash = from_hex(SERVER_FINISHED_HASH);
+    let client_application_traffic_secret = key_schedule
+        .derive_secret(b"c ap traffic", &server_finished_hash)
+        .unwrap();
+    assert_secret_eq(
+        &client_application_traffic_secret,
+        "9e40646ce79a7f9dc05af8889bce6552875afa0b06df0087f792ebb7c17504a5",
+    );
+    let server_application_traffic_secret = key_schedule
+        .derive_secret(b"s ap traffic", &server_finished_hash)
+        .unwrap();
+    assert_secret_eq(
+        &server_application_traffic_secret,
+        "a11af9f05531f856ad47116b45a950328204b4f44bfb6b3a4b4f1f3fcb631643",
+    );
+
+    // Not from RFC 8448.
+    let next_secret = tls13::next_traffic_secret(&server_application_traffic_secret).unwrap();
+    assert_secret_eq(
+        &next_secret,
+        "51921b8aa3001976eb401d0a4319a8516416a6c56001a357e5d162031e84f916",
+    );
+
+    // The transcript hash must be the digest algorithm's output length.
+    assert!(key_schedule
+        .derive_secret(b"c ap traffic", &server_finished_hash[..31])
+        .is_err());
+}
+
+#[test]
+fn tls13_traffic_key_test() {
+    // The server's application traffic key and IV from RFC 8448 Section 3.
+    let secret = hkdf::Prk::new_less_safe(
+        &digest::SHA256,
+        &from_hex("a11af9f05531f856ad47116b45a950328204b4f44bfb6b3a4b4f1f3fcb631643"),
+    );
+    let expected_key = from_hex("9f02283b6c9c07efc26bb9f2ac92e356");
+    let iv = from_hex("cf782b88dd83549aadf1e984");
+
+    let (sealing_key, sealing_iv) = tls13::derive_sealing_key(&secret, &aead::AES_128_GCM).unwrap();
+    let (opening_key, opening_iv) = tls13::derive_opening_key(&secret, &aead::AES_128_GCM).unwrap();
+    let expected_key = aead::SealingKey::new(&aead::AES_128_GCM, &expected_key).unwrap();
+
+    for &seq in &[0, 1, 0x0102_0304_0506_0708, u64::max_value()] {
+        let mut nonce = iv.clone();
+        for (n, s) in nonce[4..].iter_mut().zip(seq.to_be_bytes().iter()) {
+            *n ^= s;
+        }
+        let nonce = || aead::Nonce::try_assume_unique_for_key(&nonce).unwrap();
+
+        let mut expected = b"record".to_vec();
+        expected.extend_from_slice(&[0; 16]);
+        let mut in_out = expected.clone();
+        let _ = aead::seal_in_place(
+            &expected_key,
+            nonce(),
+            aead::Aad::empty(),
+            &mut expected,
+            16,
+        )
+        .unwrap();
+        let _ = aead::seal_in_place(
+            &sealing_key,
+            sealing_iv.nonce_for(seq),
+            aead::Aad::empty(),
+            &mut in_out,
+            16,
+        )
+        .unwrap();
+        assert_eq!(in_out, expected);
+
+        let plaintext = aead::open_in_place(
+            &opening_key,
+            opening_iv.nonce_for(seq),
+            aead::Aad::empty(),
+            0,
+            &mut in_out,
+        )
+        .unwrap();
+        assert_eq!(plaintext, b"record");
+    }
+
+    // The key must be as long as the AEAD's.
+    let secret = hkdf::Prk::new_less_safe(&digest::SHA256, &[0; 32]);
+    assert!(tls13::derive_sealing_key(&secret, &aead::AES_256_GCM).is_ok());
+    #[cfg(feature = "aead-chacha")]
+    assert!(tls13::derive_opening_key(&secret, &aead::CHACHA20_POLY1305).is_ok());
+}
+
+#[test]
+fn tls13_psk_test() {
+    // With a PSK of zeros, the Early Secret is the same as without a PSK.
+    let hash = [0u8; 48];
+    let with_psk = tls13::KeySchedule::new_with_psk(&digest::SHA384, &[0; 48]);
+    let without_psk = tls13::KeySchedule::new(&digest::SHA384);
+    let mut a = [0u8; 48];
+    let mut b = [0u8; 48];
+    tls13::hkdf_expand_label(
+        &with_psk.derive_secret(b"ext binder", &hash).unwrap(),
+        b"finished",
+        &[],
+        &mut a,
+    )
+    .unwrap();
+    tls13::hkdf_expand_label(
+        &without_psk.derive_secret(b"ext binder", &hash).unwrap(),
+        b"finished",
+        &[],
+        &mut b,
+    )
+    .unwrap();
+    assert_eq!(&a[..], &b[..]);
+
+    // A SHA-256 transcript hash doesn't fit a SHA-384 key schedule.
+    assert!(with_psk.derive_secret(b"ext binder", &hash[..32]).is_err());
+}
@@ -0,0 +1,115 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "use_heap")]
+
+use ring::{test, test_file, vrf};
+
+#[test]
+fn vrf_test() {
+    test::run(test_file!("vrf_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let seed = test_case.consume_bytes("SK");
+        let public_key = test_case.consume_bytes("PK");
+        let alpha = test_case.consume_bytes("Alpha");
+        let pi = test_case.consume_bytes("Pi");
+        let beta = test_case.consume_bytes("Beta");
+
+        let key_pair = vrf::KeyPair::from_seed(&seed)?;
+        assert_eq!(key_pair.public_key(), &public_key[..]);
+        let proof = key_pair.prove(&alpha);
+        assert_eq!(proof.as_ref(), &pi[..]);
+        assert_eq!(vrf::proof_to_hash(&pi)?.as_ref(), &beta[..]);
+        assert_eq!(vrf::verify(&public_key, &alpha, &pi)?.as_ref(), &beta[..]);
+
+        // Any change to the proof or the input invalidates the proof.
+        for i in 0..pi.len() {
+            let mut bad_pi = pi.clone();
+            bad_pi[i] ^= 1;
+            assert!(vrf::verify(&public_key, &alpha, &bad_pi).is_err());
+        }
+        let mut bad_alpha = alpha.clone();
+        bad_alpha.push(0);
+        assert!(vrf::verify(&public_key, &bad_alpha, &pi).is_err());
+        assert!(vrf::verify(&public_key, &alpha, &pi[..(pi.len() - 1)]).is_err());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn vrf_unreduced_s_test() {
+    let key_pair = vrf::KeyPair::from_seed(&[0x42; vrf::SEED_LEN]).unwrap();
+    let mut pi = key_pair.prove(b"alpha").as_ref().to_vec();
+    assert!(vrf::verify(key_pair.public_key(), b"alpha", &pi).is_ok());
+
+    // Adding the group order to `s` doesn't change `s * B`, but the proof
+    // must be rejected anyway.
+    const L: [u8; 32] = [
+        0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde,
+        0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x10,
+    ];
+    let s = &mut pi[(vrf::PROOF_LEN - 32)..];
+    let mut carry = 0u16;
+    for (s, l) in s.iter_mut().zip(L.iter()) {
+        let sum = u16::from(*s) + u16::from(*l) + carry;
+        *s = sum as u8;
+        carry = sum >> 8;
+    }
+    assert_eq!(carry, 0);
+    assert!(vrf::verify(key_pair.public_key(), b"alpha", &pi).is_err());
+}
+
+#[test]
+fn vrf_small_order_public_key_test() {
+    let key_pair = vrf::KeyPair::from_seed(&[0x42; vrf::SEED_LEN]).unwrap();
+    let proof = key_pair.prove(b"alpha");
+
+    // The identity and a point of order 2.
+    let mut identity = [0u8; vrf::PUBLIC_KEY_LEN];
+    identity[0] = 1;
+    let mut order_2 = [0xff; vrf::PUBLIC_KEY_LEN];
+    order_2[0] = 0xec;
+    order_2[vrf::PUBLIC_KEY_LEN - 1] = 0x7f;
+    for public_key in &[identity, order_2] {
+        assert!(vrf::verify(public_key, b"alpha", proof.as_ref()).is_err());
+    }
+
+    assert!(vrf::verify(&key_pair.public_key()[1..], b"alpha", proof.as_ref()).is_err());
+}
+
+#[test]
+fn vrf_key_pair_from_seed_test() {
+    assert!(vrf::KeyPair::from_seed(&[0x42; vrf::SEED_LEN - 1]).is_err());
+    assert!(vrf::KeyPair::from_seed(&[0x42; vrf::SEED_LEN + 1]).is_err());
+}
@@ -0,0 +1,73 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "rsa")]
+
+use ring::webauthn;
+#[cfg(feature = "sig-ed25519")]
+use ring::{test, test_file};
+
+#[cfg(feature = "sig-ed25519")]
+#[test]
+fn webauthn_tests() {
+    test::run(test_file!("webauthn_tests.txt"), |section, test_case| {
+        let cose_key = test_case.consume_bytes("CoseKey");
+        let expected_result = test_case.consume_string("Result");
+
+        let actual_result = match section {
+            "verify" => {
+                let authenticator_data = test_case.consume_bytes("AuthenticatorData");
+                let client_data_json = test_case.consume_bytes("ClientDataJSON");
+                let signature = test_case.consume_bytes("Signature");
+                let public_key = webauthn::PublicKey::from_cose(&cose_key).unwrap();
+                public_key.verify(&authenticator_data, &client_data_json, &signature)
+            }
+            "parse" => webauthn::PublicKey::from_cose(&cose_key).map(|_| ()),
+            _ => unreachable!("Unknown section: {}", section),
+        };
+        let actual_result = match actual_result {
+            Ok(()) => "OK",
+            Err(_) => "FAIL",
+        };
+        assert_eq!(actual_result, expected_result);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn webauthn_algorithm_test() {
+    assert_eq!(webauthn::ES256.cose_identifier(), -7);
+    #[cfg(feature = "sig-ed25519")]
+    assert_eq!(webauthn::EDDSA.cose_identifier(), -8);
+    assert_eq!(webauthn::PS256.cose_identifier(), -37);
+}
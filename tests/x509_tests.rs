@@ -0,0 +1,64 @@
+// Copyright 2019 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings
+)]
+#![cfg(feature = "rsa")]
+
+use ring::{test, test_file, x509};
+
+#[test]
+fn x509_verify_signature_test() {
+    test::run(test_file!("x509_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+
+        let signature_algorithm = test_case.consume_bytes("SignatureAlgorithm");
+        let issuer_spki = test_case.consume_bytes("IssuerSPKI");
+        let tbs = test_case.consume_bytes("TBS");
+        let signature = test_case.consume_bytes("Signature");
+        let expected_result = test_case.consume_string("Result");
+
+        // id-Ed25519
+        if cfg!(not(feature = "sig-ed25519"))
+            && signature_algorithm == [0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70]
+        {
+            return Ok(());
+        }
+
+        let actual_result =
+            match x509::verify_signature(&signature_algorithm, &issuer_spki, &tbs, &signature) {
+                Ok(()) => "OK",
+                Err(_) => "FAIL",
+            };
+        assert_eq!(actual_result, expected_result);
+
+        Ok(())
+    });
+}
@@ -891,6 +891,23 @@ static void slide(signed char *r, const uint8_t *a) {
   }
 }
 
+// Ai = A, 3A, 5A, 7A, 9A, 11A, 13A, 15A
+static void ge_precompute_odd_multiples(ge_cached Ai[8], const ge_p3 *A) {
+  ge_p1p1 t;
+  ge_p3 u;
+  ge_p3 A2;
+  int i;
+
+  x25519_ge_p3_to_cached(&Ai[0], A);
+  ge_p3_dbl(&t, A);
+  x25519_ge_p1p1_to_p3(&A2, &t);
+  for (i = 1; i < 8; ++i) {
+    x25519_ge_add(&t, &A2, &Ai[i - 1]);
+    x25519_ge_p1p1_to_p3(&u, &t);
+    x25519_ge_p3_to_cached(&Ai[i], &u);
+  }
+}
+
 // r = a * A + b * B
 // where a = a[0]+256*a[1]+...+256^31 a[31].
 // and b = b[0]+256*b[1]+...+256^31 b[31].
@@ -902,36 +919,12 @@ static void ge_double_scalarmult_vartime(ge_p2 *r, const uint8_t *a,
   ge_cached Ai[8];  // A,3A,5A,7A,9A,11A,13A,15A
   ge_p1p1 t;
   ge_p3 u;
-  ge_p3 A2;
   int i;
 
   slide(aslide, a);
   slide(bslide, b);
 
-  x25519_ge_p3_to_cached(&Ai[0], A);
-  ge_p3_dbl(&t, A);
-  x25519_ge_p1p1_to_p3(&A2, &t);
-  x25519_ge_add(&t, &A2, &Ai[0]);
-  x25519_ge_p1p1_to_p3(&u, &t);
-  x25519_ge_p3_to_cached(&Ai[1], &u);
-  x25519_ge_add(&t, &A2, &Ai[1]);
-  x25519_ge_p1p1_to_p3(&u, &t);
-  x25519_ge_p3_to_cached(&Ai[2], &u);
-  x25519_ge_add(&t, &A2, &Ai[2]);
-  x25519_ge_p1p1_to_p3(&u, &t);
-  x25519_ge_p3_to_cached(&Ai[3], &u);
-  x25519_ge_add(&t, &A2, &Ai[3]);
-  x25519_ge_p1p1_to_p3(&u, &t);
-  x25519_ge_p3_to_cached(&Ai[4], &u);
-  x25519_ge_add(&t, &A2, &Ai[4]);
-  x25519_ge_p1p1_to_p3(&u, &t);
-  x25519_ge_p3_to_cached(&Ai[5], &u);
-  x25519_ge_add(&t, &A2, &Ai[5]);
-  x25519_ge_p1p1_to_p3(&u, &t);
-  x25519_ge_p3_to_cached(&Ai[6], &u);
-  x25519_ge_add(&t, &A2, &Ai[6]);
-  x25519_ge_p1p1_to_p3(&u, &t);
-  x25519_ge_p3_to_cached(&Ai[7], &u);
+  ge_precompute_odd_multiples(Ai, A);
 
   ge_p2_0(r);
 
@@ -964,6 +957,135 @@ static void ge_double_scalarmult_vartime(ge_p2 *r, const uint8_t *a,
   }
 }
 
+// Returns 1 if 8 * (b * B + sum(scalars[j] * points[j])) is the identity and
+// 0 otherwise, where B is the Ed25519 base point and each scalar is 32
+// little-endian bytes. |tables| must have room for 8 * |num_points| elements
+// and |slides| for 256 * |num_points| elements.
+static int ge_multiscalarmult_cofactor_is_identity_vartime(
+    const uint8_t *b, const uint8_t *scalars, const ge_p3 *points,
+    size_t num_points, ge_cached *tables, signed char *slides) {
+  signed char bslide[256];
+  ge_p1p1 t;
+  ge_p3 u;
+  ge_p2 r;
+  fe_loose check;
+  size_t j;
+  int i;
+
+  slide(bslide, b);
+  for (j = 0; j < num_points; ++j) {
+    slide(&slides[256 * j], &scalars[32 * j]);
+    ge_precompute_odd_multiples(&tables[8 * j], &points[j]);
+  }
+
+  ge_p2_0(&r);
+
+  for (i = 255; i >= 0; --i) {
+    int any = bslide[i] != 0;
+    for (j = 0; j < num_points && !any; ++j) {
+      any = slides[256 * j + i] != 0;
+    }
+    if (any) {
+      break;
+    }
+  }
+
+  for (; i >= 0; --i) {
+    ge_p2_dbl(&t, &r);
+
+    for (j = 0; j < num_points; ++j) {
+      signed char digit = slides[256 * j + i];
+      if (digit > 0) {
+        x25519_ge_p1p1_to_p3(&u, &t);
+        x25519_ge_add(&t, &u, &tables[8 * j + digit / 2]);
+      } else if (digit < 0) {
+        x25519_ge_p1p1_to_p3(&u, &t);
+        x25519_ge_sub(&t, &u, &tables[8 * j + (-digit) / 2]);
+      }
+    }
+
+    if (bslide[i] > 0) {
+      x25519_ge_p1p1_to_p3(&u, &t);
+      ge_madd(&t, &u, &Bi[bslide[i] / 2]);
+    } else if (bslide[i] < 0) {
+      x25519_ge_p1p1_to_p3(&u, &t);
+      ge_msub(&t, &u, &Bi[(-bslide[i]) / 2]);
+    }
+
+    x25519_ge_p1p1_to_p2(&r, &t);
+  }
+
+  // Multiply by the cofactor.
+  for (i = 0; i < 3; ++i) {
+    ge_p2_dbl(&t, &r);
+    x25519_ge_p1p1_to_p2(&r, &t);
+  }
+
+  // The identity is (0 : Z : Z).
+  fe_copy_lt(&check, &r.X);
+  if (fe_isnonzero(&check)) {
+    return 0;
+  }
+  fe_sub(&check, &r.Y, &r.Z);
+  return !fe_isnonzero(&check);
+}
+
+static void ge_cached_0(ge_cached *h) {
+  fe_loose_1(&h->YplusX);
+  fe_loose_1(&h->YminusX);
+  fe_loose_1(&h->Z);
+  fe_loose_0(&h->T2d);
+}
+
+static void cmov_cached(ge_cached *t, const ge_cached *u, uint8_t b) {
+  fe_cmov(&t->YplusX, &u->YplusX, b);
+  fe_cmov(&t->YminusX, &u->YminusX, b);
+  fe_cmov(&t->Z, &u->Z, b);
+  fe_cmov(&t->T2d, &u->T2d, b);
+}
+
+// h = a * A, in constant time.
+// where a = a[0]+256*a[1]+...+256^31 a[31].
+static void ge_scalarmult(ge_p3 *h, const uint8_t *a, const ge_p3 *A) {
+  ge_cached Ai[16];  // 0,A,2A,3A,...,15A
+  ge_cached selected;
+  ge_p1p1 t;
+  ge_p2 r;
+  ge_p3 u;
+  int i;
+  int j;
+
+  ge_cached_0(&Ai[0]);
+  x25519_ge_p3_to_cached(&Ai[1], A);
+  x25519_ge_add(&t, A, &Ai[1]);
+  for (i = 2; i < 16; ++i) {
+    x25519_ge_p1p1_to_p3(&u, &t);
+    x25519_ge_p3_to_cached(&Ai[i], &u);
+    x25519_ge_add(&t, &u, &Ai[1]);
+  }
+
+  ge_p3_0(h);
+  for (i = 63; i >= 0; --i) {
+    signed char e = (a[i / 2] >> (4 * (i & 1))) & 15;
+
+    ge_p3_dbl(&t, h);
+    x25519_ge_p1p1_to_p2(&r, &t);
+    ge_p2_dbl(&t, &r);
+    x25519_ge_p1p1_to_p2(&r, &t);
+    ge_p2_dbl(&t, &r);
+    x25519_ge_p1p1_to_p2(&r, &t);
+    ge_p2_dbl(&t, &r);
+    x25519_ge_p1p1_to_p3(&u, &t);
+
+    ge_cached_0(&selected);
+    for (j = 1; j < 16; ++j) {
+      cmov_cached(&selected, &Ai[j], equal(e, (signed char)j));
+    }
+    x25519_ge_add(&t, &u, &selected);
+    x25519_ge_p1p1_to_p3(h, &t);
+  }
+}
+
 // int64_lshift21 returns |a << 21| but is defined when shifting bits into the
 // sign bit. This works around a language flaw in C.
 static inline int64_t int64_lshift21(int64_t a) {
@@ -1924,11 +2046,60 @@ void GFp_x25519_fe_tobytes(uint8_t s[32], const fe *h) {
   fe_tobytes(s, h);
 }
 
+void GFp_x25519_fe_frombytes(fe *h, const uint8_t s[32]) {
+  fe_frombytes(h, s);
+}
+
+void GFp_x25519_fe_add(fe *h, const fe *f, const fe *g) {
+  fe_loose t;
+  fe_add(&t, f, g);
+  fe_carry(h, &t);
+}
+
+void GFp_x25519_fe_sub(fe *h, const fe *f, const fe *g) {
+  fe_loose t;
+  fe_sub(&t, f, g);
+  fe_carry(h, &t);
+}
+
+void GFp_x25519_fe_sq(fe *h, const fe *f) {
+  fe_sq_tt(h, f);
+}
+
+void GFp_x25519_fe_pow22523(fe *out, const fe *z) {
+  fe_pow22523(out, z);
+}
+
+int GFp_x25519_fe_isnonzero(const fe *f) {
+  fe_loose t;
+  fe_copy_lt(&t, f);
+  return fe_isnonzero(&t);
+}
+
 void GFp_x25519_ge_double_scalarmult_vartime(ge_p2 *r, const uint8_t *a,
                                              const ge_p3 *A, const uint8_t *b) {
   ge_double_scalarmult_vartime(r, a, A, b);
 }
 
+int GFp_x25519_ge_multiscalarmult_cofactor_is_identity_vartime(
+    const uint8_t *b, const uint8_t *scalars, const ge_p3 *points,
+    size_t num_points, ge_cached *tables, signed char *slides) {
+  return ge_multiscalarmult_cofactor_is_identity_vartime(
+      b, scalars, points, num_points, tables, slides);
+}
+
+void GFp_x25519_ge_scalarmult(ge_p3 *h, const uint8_t *a, const ge_p3 *A) {
+  ge_scalarmult(h, a, A);
+}
+
+void GFp_x25519_ge_add(ge_p3 *r, const ge_p3 *p, const ge_p3 *q) {
+  ge_cached q_cached;
+  ge_p1p1 t;
+  x25519_ge_p3_to_cached(&q_cached, q);
+  x25519_ge_add(&t, p, &q_cached);
+  x25519_ge_p1p1_to_p3(r, &t);
+}
+
 void GFp_x25519_sc_mask(uint8_t a[32]) {
   a[0] &= 248;
   a[31] &= 127;